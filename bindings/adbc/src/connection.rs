@@ -0,0 +1,234 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::{new_empty_array, ArrayRef, StringArray, UInt32Array, UnionArray};
+use datafusion::arrow::buffer::Buffer;
+use datafusion::arrow::datatypes::{DataType, Schema};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use futures::lock::Mutex;
+use futures::StreamExt;
+use sqlexec::engine::TrackedSession;
+use sqlexec::session::ExecutionResult;
+use sqlexec::{LogicalPlan, OperationInfo};
+
+use crate::database::{parse_bool, Database, InitializedDatabase};
+use crate::error::{AdbcError, Result};
+use crate::ffi::{
+    ADBC_CONNECTION_OPTION_AUTOCOMMIT, ADBC_INFO_DRIVER_NAME, ADBC_INFO_DRIVER_VERSION,
+    ADBC_INFO_VENDOR_NAME, ADBC_INFO_VENDOR_VERSION,
+};
+use crate::ingest::IngestReader;
+use crate::objects::{self, ObjectsFilter};
+use crate::runtime::block_on;
+use crate::schemas::{GET_INFO_SCHEMA, GET_TABLE_TYPES_SCHEMA, INFO_STRING_VALUE_TYPE_ID};
+
+/// Table types reported by `AdbcConnectionGetTableTypes` and
+/// `AdbcConnectionGetObjects`.
+pub(crate) const TABLE_TYPES: [&str; 2] = ["TABLE", "VIEW"];
+
+/// A session opened for a connection, shared with the connection's
+/// statements.
+#[derive(Clone)]
+pub(crate) struct Session {
+    sess: Arc<Mutex<TrackedSession>>,
+    pub(crate) ingest: IngestReader,
+    _database: Arc<InitializedDatabase>,
+}
+
+impl Session {
+    async fn open(database: Arc<InitializedDatabase>) -> Result<Self> {
+        let mut sess = database.new_session().await?;
+        let ingest = IngestReader::default();
+        sess.register_env_reader(Box::new(ingest.clone()));
+        Ok(Session {
+            sess: Arc::new(Mutex::new(sess)),
+            ingest,
+            _database: database,
+        })
+    }
+
+    pub(crate) async fn plan(&self, query: &str) -> Result<LogicalPlan> {
+        let mut sess = self.sess.lock().await;
+        Ok(sess.create_logical_plan(query).await?)
+    }
+
+    /// Execute a plan, erroring if the execution errored.
+    pub(crate) async fn execute(&self, plan: LogicalPlan, query: &str) -> Result<ExecutionResult> {
+        let mut sess = self.sess.lock().await;
+        let op = OperationInfo::new().with_query_text(query.to_string());
+        match sess.execute_logical_plan(plan, &op).await? {
+            (_, ExecutionResult::Error(e)) => Err(e.into()),
+            (_, result) => Ok(result),
+        }
+    }
+
+    /// Run a query, collecting all of its results.
+    pub(crate) async fn query(
+        &self,
+        query: &str,
+        params: Vec<ScalarValue>,
+    ) -> Result<Vec<RecordBatch>> {
+        let mut plan = self.plan(query).await?;
+        plan.bind_parameters(params)?;
+        match self.execute(plan, query).await? {
+            ExecutionResult::Query { stream } => {
+                let batches = stream.collect::<Vec<_>>().await;
+                Ok(batches.into_iter().collect::<Result<Vec<_>, _>>()?)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+/// An `AdbcConnection`.
+///
+/// GlareDB doesn't provide transactional semantics, so connections are
+/// always in autocommit mode.
+#[derive(Default)]
+pub struct Connection {
+    inner: Option<Session>,
+}
+
+impl Connection {
+    pub fn set_option(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            ADBC_CONNECTION_OPTION_AUTOCOMMIT => {
+                if parse_bool(key, value)? {
+                    Ok(())
+                } else {
+                    Err(AdbcError::NotImplemented(
+                        "Transactions are not supported, connections are always in autocommit mode"
+                            .to_string(),
+                    ))
+                }
+            }
+            key => Err(AdbcError::NotImplemented(format!(
+                "Unknown connection option '{key}'"
+            ))),
+        }
+    }
+
+    pub fn init(&mut self, database: &Database) -> Result<()> {
+        if self.inner.is_some() {
+            return Err(AdbcError::InvalidState(
+                "Connection already initialized".to_string(),
+            ));
+        }
+        let database = database.initialized()?;
+        self.inner = Some(block_on(Session::open(database))?);
+        Ok(())
+    }
+
+    pub(crate) fn session(&self) -> Result<&Session> {
+        self.inner
+            .as_ref()
+            .ok_or_else(|| AdbcError::InvalidState("Connection not initialized".to_string()))
+    }
+
+    pub fn commit(&self) -> Result<()> {
+        self.session()?;
+        Err(AdbcError::InvalidState(
+            "Cannot commit, connection is in autocommit mode".to_string(),
+        ))
+    }
+
+    pub fn rollback(&self) -> Result<()> {
+        self.session()?;
+        Err(AdbcError::InvalidState(
+            "Cannot rollback, connection is in autocommit mode".to_string(),
+        ))
+    }
+
+    /// Get info about the driver and database.
+    ///
+    /// All info values are strings. Unknown codes are skipped.
+    pub fn get_info(&self, codes: Option<&[u32]>) -> Result<RecordBatch> {
+        self.session()?;
+
+        let version = env!("CARGO_PKG_VERSION");
+        let info = [
+            (ADBC_INFO_VENDOR_NAME, "GlareDB"),
+            (ADBC_INFO_VENDOR_VERSION, version),
+            (ADBC_INFO_DRIVER_NAME, "ADBC GlareDB Driver"),
+            (ADBC_INFO_DRIVER_VERSION, version),
+        ];
+        let info: Vec<_> = match codes {
+            Some(codes) => codes
+                .iter()
+                .filter_map(|code| info.iter().find(|(c, _)| c == code))
+                .collect(),
+            None => info.iter().collect(),
+        };
+
+        let names = UInt32Array::from_iter_values(info.iter().map(|(code, _)| *code));
+        let strings = StringArray::from_iter_values(info.iter().map(|(_, value)| *value));
+
+        let fields = match GET_INFO_SCHEMA.field(1).data_type() {
+            DataType::Union(fields, _) => fields.clone(),
+            _ => unreachable!("info_value is a dense union"),
+        };
+
+        // Every value is a string, so all values point into the string
+        // child.
+        let type_ids =
+            Buffer::from_iter(std::iter::repeat(INFO_STRING_VALUE_TYPE_ID).take(info.len()));
+        let offsets = Buffer::from_iter(0..info.len() as i32);
+        let children = fields
+            .iter()
+            .map(|(type_id, field)| {
+                let child: ArrayRef = if type_id == INFO_STRING_VALUE_TYPE_ID {
+                    Arc::new(strings.clone())
+                } else {
+                    new_empty_array(field.data_type())
+                };
+                (field.as_ref().clone(), child)
+            })
+            .collect();
+        let type_id_list: Vec<_> = fields.iter().map(|(type_id, _)| type_id).collect();
+        let values = UnionArray::try_new(&type_id_list, type_ids, Some(offsets), children)?;
+
+        Ok(RecordBatch::try_new(
+            GET_INFO_SCHEMA.clone(),
+            vec![Arc::new(names), Arc::new(values)],
+        )?)
+    }
+
+    pub fn get_table_types(&self) -> Result<RecordBatch> {
+        self.session()?;
+        Ok(RecordBatch::try_new(
+            GET_TABLE_TYPES_SCHEMA.clone(),
+            vec![Arc::new(StringArray::from_iter_values(TABLE_TYPES))],
+        )?)
+    }
+
+    /// Get the arrow schema of a table or view.
+    pub fn get_table_schema(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: &str,
+    ) -> Result<Schema> {
+        let sess = self.session()?;
+        let reference = [catalog, db_schema, Some(table_name)]
+            .into_iter()
+            .flatten()
+            .map(quote_ident)
+            .collect::<Vec<_>>()
+            .join(".");
+
+        let plan = block_on(sess.plan(&format!("SELECT * FROM {reference}")))
+            .map_err(|e| AdbcError::NotFound(format!("Table {reference} not found: {e}")))?;
+        plan.output_schema()
+            .ok_or_else(|| AdbcError::new("Table scan produced no schema"))
+    }
+
+    pub fn get_objects(&self, filter: ObjectsFilter<'_>) -> Result<RecordBatch> {
+        let sess = self.session()?;
+        block_on(objects::get_objects(sess, filter))
+    }
+}
+
+/// Quote an identifier so it can be used as is in a query.
+pub(crate) fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use datafusion::variable::VarType;
+use datafusion_ext::vars::SessionVars;
+use ioutil::ensure_dir;
+use sqlexec::engine::{Engine, SessionStorageConfig, TrackedSession};
+use sqlexec::remote::client::{RemoteClient, RemoteClientType};
+use url::Url;
+
+use crate::error::{AdbcError, Result};
+use crate::ffi::{ADBC_OPTION_URI, ADBC_OPTION_VALUE_DISABLED, ADBC_OPTION_VALUE_ENABLED};
+use crate::runtime::block_on;
+
+/// Location of the object store to use for storage, see
+/// `Engine::from_storage_options`.
+pub const OPTION_LOCATION: &str = "glaredb.location";
+/// Prefix for options passed through to the object store at `location`.
+pub const OPTION_STORAGE_PREFIX: &str = "glaredb.storage.";
+/// Directory to spill to when queries exceed memory limits.
+pub const OPTION_SPILL_PATH: &str = "glaredb.spill_path";
+/// Address of GlareDB Cloud, used when connecting to a cloud deployment.
+pub const OPTION_CLOUD_ADDR: &str = "glaredb.cloud_addr";
+/// Disable TLS when connecting to a cloud deployment.
+pub const OPTION_DISABLE_TLS: &str = "glaredb.disable_tls";
+
+/// Options set on a database before it's initialized.
+#[derive(Debug, Default)]
+struct DatabaseOptions {
+    /// Data directory or cloud url.
+    uri: Option<String>,
+    location: Option<String>,
+    storage_options: HashMap<String, String>,
+    spill_path: Option<String>,
+    cloud_addr: Option<String>,
+    disable_tls: bool,
+}
+
+/// An `AdbcDatabase`.
+///
+/// Holds the engine that all connections to the database share.
+#[derive(Default)]
+pub struct Database {
+    opts: DatabaseOptions,
+    inner: Option<Arc<InitializedDatabase>>,
+}
+
+pub(crate) struct InitializedDatabase {
+    engine: Engine,
+    cloud_url: Option<Url>,
+    cloud_addr: String,
+    disable_tls: bool,
+}
+
+impl Database {
+    pub fn set_option(&mut self, key: &str, value: &str) -> Result<()> {
+        if self.inner.is_some() {
+            return Err(AdbcError::InvalidState(format!(
+                "Cannot set option '{key}' after the database is initialized"
+            )));
+        }
+
+        match key {
+            ADBC_OPTION_URI => self.opts.uri = Some(value.to_string()),
+            OPTION_LOCATION => self.opts.location = Some(value.to_string()),
+            OPTION_SPILL_PATH => self.opts.spill_path = Some(value.to_string()),
+            OPTION_CLOUD_ADDR => self.opts.cloud_addr = Some(value.to_string()),
+            OPTION_DISABLE_TLS => self.opts.disable_tls = parse_bool(key, value)?,
+            key => match key.strip_prefix(OPTION_STORAGE_PREFIX) {
+                Some(storage_key) => {
+                    self.opts
+                        .storage_options
+                        .insert(storage_key.to_string(), value.to_string());
+                }
+                None => {
+                    return Err(AdbcError::NotImplemented(format!(
+                        "Unknown database option '{key}'"
+                    )))
+                }
+            },
+        }
+        Ok(())
+    }
+
+    pub fn init(&mut self) -> Result<()> {
+        if self.inner.is_some() {
+            return Err(AdbcError::InvalidState(
+                "Database already initialized".to_string(),
+            ));
+        }
+        let inner = block_on(InitializedDatabase::open(std::mem::take(&mut self.opts)))?;
+        self.inner = Some(Arc::new(inner));
+        Ok(())
+    }
+
+    pub(crate) fn initialized(&self) -> Result<Arc<InitializedDatabase>> {
+        self.inner
+            .clone()
+            .ok_or_else(|| AdbcError::InvalidState("Database not initialized".to_string()))
+    }
+}
+
+impl InitializedDatabase {
+    async fn open(opts: DatabaseOptions) -> Result<Self> {
+        let (data_dir, cloud_url) = match opts.uri.as_deref() {
+            None | Some("") | Some(":memory:") => (None, None),
+            Some(uri) => match Url::parse(uri) {
+                Ok(url) if url.scheme() == "file" => {
+                    let path = url.to_file_path().map_err(|_| {
+                        AdbcError::InvalidArgument(format!("Invalid file uri: {uri}"))
+                    })?;
+                    (Some(path), None)
+                }
+                Ok(url) => (None, Some(url)),
+                // Assume failing to parse a url just means a local path was
+                // provided.
+                Err(_) => (Some(PathBuf::from(uri)), None),
+            },
+        };
+
+        let engine = match opts.location {
+            Some(location) => {
+                Engine::from_storage_options(&location, &opts.storage_options).await?
+            }
+            // If data dir is provided, then both table storage and metastore
+            // storage will reside at that path. Otherwise everything is in
+            // memory.
+            None => Engine::from_data_dir(data_dir.as_ref()).await?,
+        };
+
+        // If spill path not provided, default to some tmp dir.
+        let spill_path = match opts.spill_path {
+            Some(p) => {
+                let path = PathBuf::from(p);
+                ensure_dir(&path).map_err(AdbcError::new)?;
+                Some(path)
+            }
+            None => {
+                let path = std::env::temp_dir().join("glaredb-adbc");
+                // If we don't have permission to write to the temp dir, then
+                // just don't use a spill path.
+                ensure_dir(&path).ok().map(|_| path)
+            }
+        };
+
+        Ok(InitializedDatabase {
+            engine: engine.with_spill_path(spill_path),
+            cloud_url,
+            cloud_addr: opts
+                .cloud_addr
+                .unwrap_or_else(|| String::from("https://console.glaredb.com")),
+            disable_tls: opts.disable_tls,
+        })
+    }
+
+    /// Create a new session for a connection, attaching it to the cloud
+    /// deployment if the database was opened with a cloud url.
+    pub(crate) async fn new_session(&self) -> Result<TrackedSession> {
+        let mut sess = self
+            .engine
+            .new_local_session_context(
+                SessionVars::default()
+                    .with_is_embedded(true, VarType::System)
+                    .with_is_admin(true, VarType::System),
+                SessionStorageConfig::default(),
+            )
+            .await?;
+
+        if let Some(url) = self.cloud_url.clone() {
+            let client = RemoteClient::connect_with_proxy_destination(
+                url.try_into()?,
+                self.cloud_addr.clone(),
+                self.disable_tls,
+                RemoteClientType::Adbc,
+            )
+            .await?;
+            sess.attach_remote_session(client, None).await?;
+        }
+
+        Ok(sess)
+    }
+}
+
+pub(crate) fn parse_bool(key: &str, value: &str) -> Result<bool> {
+    match value {
+        ADBC_OPTION_VALUE_ENABLED => Ok(true),
+        ADBC_OPTION_VALUE_DISABLED => Ok(false),
+        _ => Err(AdbcError::InvalidArgument(format!(
+            "Invalid value '{value}' for option '{key}', expected 'true' or 'false'"
+        ))),
+    }
+}
@@ -0,0 +1,809 @@
+//! The exported ADBC C API.
+//!
+//! Every function follows the contract in `adbc.h`: pointers are checked for
+//! null, errors are written to the caller's `AdbcError`, and panics are
+//! caught before they reach the caller.
+#![allow(non_snake_case, clippy::missing_safety_doc, clippy::too_many_arguments)]
+
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::panic::AssertUnwindSafe;
+
+use arrow::array::StructArray;
+use arrow::ffi::{from_ffi, FFI_ArrowArray, FFI_ArrowSchema};
+use arrow::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::connection::Connection;
+use crate::database::Database;
+use crate::error::{AdbcError, Result};
+use crate::ffi::*;
+use crate::objects::ObjectsFilter;
+use crate::statement::Statement;
+use crate::stream::{export_batches, export_stream};
+
+/// Run `f`, writing any error or panic to `error`.
+unsafe fn check(error: *mut FFI_AdbcError, f: impl FnOnce() -> Result<()>) -> AdbcStatusCode {
+    match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => ADBC_STATUS_OK,
+        Ok(Err(e)) => e.write(error),
+        Err(panic) => {
+            let msg = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            AdbcError::new(format!("GlareDB ADBC driver panicked: {msg}")).write(error)
+        }
+    }
+}
+
+unsafe fn non_null<'a, T>(ptr: *mut T, what: &str) -> Result<&'a mut T> {
+    ptr.as_mut()
+        .ok_or_else(|| AdbcError::InvalidArgument(format!("{what} must not be null")))
+}
+
+/// Get the driver's private data on an ADBC struct.
+unsafe fn private<'a, T>(private_data: *mut c_void, what: &str) -> Result<&'a mut T> {
+    (private_data as *mut T)
+        .as_mut()
+        .ok_or_else(|| AdbcError::InvalidState(format!("{what} not created")))
+}
+
+unsafe fn opt_str<'a>(ptr: *const c_char, what: &str) -> Result<Option<&'a str>> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map(Some)
+        .map_err(|_| AdbcError::InvalidArgument(format!("{what} must be valid UTF-8")))
+}
+
+unsafe fn str_arg<'a>(ptr: *const c_char, what: &str) -> Result<&'a str> {
+    opt_str(ptr, what)?
+        .ok_or_else(|| AdbcError::InvalidArgument(format!("{what} must not be null")))
+}
+
+/// Move a value of the driver's private data into a new allocation.
+fn into_private<T>(value: T) -> *mut c_void {
+    Box::into_raw(Box::new(value)) as *mut c_void
+}
+
+/// Take back ownership of private data created with `into_private`.
+unsafe fn take_private<T>(private_data: &mut *mut c_void, what: &str) -> Result<Box<T>> {
+    if private_data.is_null() {
+        return Err(AdbcError::InvalidState(format!("{what} not created")));
+    }
+    let value = Box::from_raw(*private_data as *mut T);
+    *private_data = std::ptr::null_mut();
+    Ok(value)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcDatabaseNew(
+    database: *mut FFI_AdbcDatabase,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let database = non_null(database, "database")?;
+        if !database.private_data.is_null() {
+            return Err(AdbcError::InvalidState(
+                "Database already created".to_string(),
+            ));
+        }
+        database.private_data = into_private(Database::default());
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcDatabaseSetOption(
+    database: *mut FFI_AdbcDatabase,
+    key: *const c_char,
+    value: *const c_char,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let database = non_null(database, "database")?;
+        let database: &mut Database = private(database.private_data, "Database")?;
+        database.set_option(str_arg(key, "key")?, str_arg(value, "value")?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcDatabaseInit(
+    database: *mut FFI_AdbcDatabase,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let database = non_null(database, "database")?;
+        let database: &mut Database = private(database.private_data, "Database")?;
+        database.init()
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcDatabaseRelease(
+    database: *mut FFI_AdbcDatabase,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let database = non_null(database, "database")?;
+        take_private::<Database>(&mut database.private_data, "Database")?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionNew(
+    connection: *mut FFI_AdbcConnection,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let connection = non_null(connection, "connection")?;
+        if !connection.private_data.is_null() {
+            return Err(AdbcError::InvalidState(
+                "Connection already created".to_string(),
+            ));
+        }
+        connection.private_data = into_private(Connection::default());
+        Ok(())
+    })
+}
+
+unsafe fn connection<'a>(connection: *mut FFI_AdbcConnection) -> Result<&'a mut Connection> {
+    let connection = non_null(connection, "connection")?;
+    private(connection.private_data, "Connection")
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionSetOption(
+    conn: *mut FFI_AdbcConnection,
+    key: *const c_char,
+    value: *const c_char,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        connection(conn)?.set_option(str_arg(key, "key")?, str_arg(value, "value")?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionInit(
+    conn: *mut FFI_AdbcConnection,
+    database: *mut FFI_AdbcDatabase,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let database = non_null(database, "database")?;
+        let database: &mut Database = private(database.private_data, "Database")?;
+        connection(conn)?.init(database)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionRelease(
+    conn: *mut FFI_AdbcConnection,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let conn = non_null(conn, "connection")?;
+        take_private::<Connection>(&mut conn.private_data, "Connection")?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionCommit(
+    conn: *mut FFI_AdbcConnection,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || connection(conn)?.commit())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionRollback(
+    conn: *mut FFI_AdbcConnection,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || connection(conn)?.rollback())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionGetInfo(
+    conn: *mut FFI_AdbcConnection,
+    info_codes: *const u32,
+    info_codes_length: usize,
+    out: *mut FFI_ArrowArrayStream,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let out = non_null(out, "out")?;
+        let codes = (!info_codes.is_null())
+            .then(|| std::slice::from_raw_parts(info_codes, info_codes_length));
+        let batch = connection(conn)?.get_info(codes)?;
+        export_batches(batch.schema(), vec![batch], out);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionGetObjects(
+    conn: *mut FFI_AdbcConnection,
+    depth: c_int,
+    catalog: *const c_char,
+    db_schema: *const c_char,
+    table_name: *const c_char,
+    table_type: *const *const c_char,
+    column_name: *const c_char,
+    out: *mut FFI_ArrowArrayStream,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let out = non_null(out, "out")?;
+
+        // Table types are a null terminated list of strings.
+        let table_types = if table_type.is_null() {
+            None
+        } else {
+            let mut types = Vec::new();
+            let mut ptr = table_type;
+            while !(*ptr).is_null() {
+                types.push(str_arg(*ptr, "table_type")?);
+                ptr = ptr.add(1);
+            }
+            Some(types)
+        };
+
+        let filter = ObjectsFilter {
+            depth,
+            catalog: opt_str(catalog, "catalog")?,
+            db_schema: opt_str(db_schema, "db_schema")?,
+            table_name: opt_str(table_name, "table_name")?,
+            table_types,
+            column_name: opt_str(column_name, "column_name")?,
+        };
+        let batch = connection(conn)?.get_objects(filter)?;
+        export_batches(batch.schema(), vec![batch], out);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionGetTableSchema(
+    conn: *mut FFI_AdbcConnection,
+    catalog: *const c_char,
+    db_schema: *const c_char,
+    table_name: *const c_char,
+    schema: *mut FFI_ArrowSchema,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let out = non_null(schema, "schema")?;
+        let schema = connection(conn)?.get_table_schema(
+            opt_str(catalog, "catalog")?,
+            opt_str(db_schema, "db_schema")?,
+            str_arg(table_name, "table_name")?,
+        )?;
+        std::ptr::write(out, FFI_ArrowSchema::try_from(&schema)?);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionGetTableTypes(
+    conn: *mut FFI_AdbcConnection,
+    out: *mut FFI_ArrowArrayStream,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let out = non_null(out, "out")?;
+        let batch = connection(conn)?.get_table_types()?;
+        export_batches(batch.schema(), vec![batch], out);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionReadPartition(
+    _conn: *mut FFI_AdbcConnection,
+    _serialized_partition: *const u8,
+    _serialized_length: usize,
+    _out: *mut FFI_ArrowArrayStream,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        Err(AdbcError::NotImplemented(
+            "Partitioned results are not supported".to_string(),
+        ))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementNew(
+    conn: *mut FFI_AdbcConnection,
+    statement: *mut FFI_AdbcStatement,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let statement = non_null(statement, "statement")?;
+        if !statement.private_data.is_null() {
+            return Err(AdbcError::InvalidState(
+                "Statement already created".to_string(),
+            ));
+        }
+        let stmt = Statement::new(connection(conn)?)?;
+        statement.private_data = into_private(stmt);
+        Ok(())
+    })
+}
+
+unsafe fn statement<'a>(statement: *mut FFI_AdbcStatement) -> Result<&'a mut Statement> {
+    let statement = non_null(statement, "statement")?;
+    private(statement.private_data, "Statement")
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementRelease(
+    stmt: *mut FFI_AdbcStatement,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let stmt = non_null(stmt, "statement")?;
+        take_private::<Statement>(&mut stmt.private_data, "Statement")?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementSetSqlQuery(
+    stmt: *mut FFI_AdbcStatement,
+    query: *const c_char,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        statement(stmt)?.set_sql_query(str_arg(query, "query")?);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementSetSubstraitPlan(
+    _stmt: *mut FFI_AdbcStatement,
+    _plan: *const u8,
+    _length: usize,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        Err(AdbcError::NotImplemented(
+            "Substrait plans are not supported".to_string(),
+        ))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementSetOption(
+    stmt: *mut FFI_AdbcStatement,
+    key: *const c_char,
+    value: *const c_char,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        statement(stmt)?.set_option(str_arg(key, "key")?, str_arg(value, "value")?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementPrepare(
+    stmt: *mut FFI_AdbcStatement,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || statement(stmt)?.prepare())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementGetParameterSchema(
+    stmt: *mut FFI_AdbcStatement,
+    schema: *mut FFI_ArrowSchema,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let out = non_null(schema, "schema")?;
+        let schema = statement(stmt)?.parameter_schema()?;
+        std::ptr::write(out, FFI_ArrowSchema::try_from(&schema)?);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementBind(
+    stmt: *mut FFI_AdbcStatement,
+    values: *mut FFI_ArrowArray,
+    schema: *mut FFI_ArrowSchema,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let values = non_null(values, "values")?;
+        let schema = non_null(schema, "schema")?;
+        // Take ownership of the array and schema, leaving released structs
+        // in their place.
+        let values = std::ptr::replace(values, FFI_ArrowArray::empty());
+        let schema = std::ptr::replace(schema, FFI_ArrowSchema::empty());
+
+        let data = from_ffi(values, &schema)?;
+        let batch = RecordBatch::from(StructArray::from(data));
+        statement(stmt)?.bind(batch);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementBindStream(
+    stmt: *mut FFI_AdbcStatement,
+    stream: *mut FFI_ArrowArrayStream,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        let stream = non_null(stream, "stream")?;
+        let stream = std::ptr::replace(stream, FFI_ArrowArrayStream::empty());
+
+        let reader = ArrowArrayStreamReader::try_new(stream)?;
+        let schema = reader.schema();
+        let batches = reader.collect::<Result<Vec<_>, _>>()?;
+        statement(stmt)?.bind_batches(schema, batches)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementExecuteQuery(
+    stmt: *mut FFI_AdbcStatement,
+    out: *mut FFI_ArrowArrayStream,
+    rows_affected: *mut i64,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        // A null stream means the caller doesn't want a result set.
+        let result = statement(stmt)?.execute(!out.is_null())?;
+        if let Some(stream) = result.stream {
+            export_stream(stream, out);
+        }
+        if let Some(rows_affected) = rows_affected.as_mut() {
+            *rows_affected = result.rows_affected.map(|n| n as i64).unwrap_or(-1);
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementExecutePartitions(
+    _stmt: *mut FFI_AdbcStatement,
+    _schema: *mut FFI_ArrowSchema,
+    _partitions: *mut FFI_AdbcPartitions,
+    _rows_affected: *mut i64,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        Err(AdbcError::NotImplemented(
+            "Partitioned results are not supported".to_string(),
+        ))
+    })
+}
+
+unsafe extern "C" fn release_driver(
+    driver: *mut FFI_AdbcDriver,
+    _error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    if let Some(driver) = driver.as_mut() {
+        driver.release = None;
+    }
+    ADBC_STATUS_OK
+}
+
+/// Entrypoint for the ADBC driver manager, filling in the driver's function
+/// table.
+#[no_mangle]
+pub unsafe extern "C" fn AdbcDriverInit(
+    version: c_int,
+    raw_driver: *mut c_void,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    check(error, || {
+        if version != ADBC_VERSION_1_0_0 {
+            return Err(AdbcError::NotImplemented(format!(
+                "Unsupported ADBC version {version}, only {ADBC_VERSION_1_0_0} is supported"
+            )));
+        }
+        let driver = non_null(raw_driver as *mut FFI_AdbcDriver, "driver")?;
+        *driver = FFI_AdbcDriver {
+            private_data: std::ptr::null_mut(),
+            private_manager: driver.private_manager,
+            release: Some(release_driver),
+            database_init: Some(AdbcDatabaseInit),
+            database_new: Some(AdbcDatabaseNew),
+            database_set_option: Some(AdbcDatabaseSetOption),
+            database_release: Some(AdbcDatabaseRelease),
+            connection_commit: Some(AdbcConnectionCommit),
+            connection_get_info: Some(AdbcConnectionGetInfo),
+            connection_get_objects: Some(AdbcConnectionGetObjects),
+            connection_get_table_schema: Some(AdbcConnectionGetTableSchema),
+            connection_get_table_types: Some(AdbcConnectionGetTableTypes),
+            connection_init: Some(AdbcConnectionInit),
+            connection_new: Some(AdbcConnectionNew),
+            connection_set_option: Some(AdbcConnectionSetOption),
+            connection_read_partition: Some(AdbcConnectionReadPartition),
+            connection_release: Some(AdbcConnectionRelease),
+            connection_rollback: Some(AdbcConnectionRollback),
+            statement_bind: Some(AdbcStatementBind),
+            statement_bind_stream: Some(AdbcStatementBindStream),
+            statement_execute_query: Some(AdbcStatementExecuteQuery),
+            statement_execute_partitions: Some(AdbcStatementExecutePartitions),
+            statement_get_parameter_schema: Some(AdbcStatementGetParameterSchema),
+            statement_new: Some(AdbcStatementNew),
+            statement_prepare: Some(AdbcStatementPrepare),
+            statement_release: Some(AdbcStatementRelease),
+            statement_set_option: Some(AdbcStatementSetOption),
+            statement_set_sql_query: Some(AdbcStatementSetSqlQuery),
+            statement_set_substrait_plan: Some(AdbcStatementSetSubstraitPlan),
+        };
+        Ok(())
+    })
+}
+
+/// Driver specific entrypoint, the name the driver manager looks for when
+/// loading `libadbc_driver_glaredb` without an explicit entrypoint.
+#[no_mangle]
+pub unsafe extern "C" fn AdbcDriverGlaredbInit(
+    version: c_int,
+    raw_driver: *mut c_void,
+    error: *mut FFI_AdbcError,
+) -> AdbcStatusCode {
+    AdbcDriverInit(version, raw_driver, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::sync::Arc;
+
+    use arrow::array::{Array, AsArray, Int64Array};
+    use arrow::datatypes::{DataType, Field, Int64Type, Schema};
+
+    use super::*;
+
+    fn new_error() -> FFI_AdbcError {
+        FFI_AdbcError {
+            message: std::ptr::null_mut(),
+            vendor_code: 0,
+            sqlstate: [0; 5],
+            release: None,
+        }
+    }
+
+    /// Assert a call succeeded, printing the error message if not.
+    unsafe fn ok(status: AdbcStatusCode, error: &mut FFI_AdbcError) {
+        if status != ADBC_STATUS_OK {
+            let msg = CStr::from_ptr(error.message).to_string_lossy();
+            panic!("status {status}: {msg}");
+        }
+    }
+
+    struct Handles {
+        database: FFI_AdbcDatabase,
+        connection: FFI_AdbcConnection,
+    }
+
+    unsafe fn connect() -> Handles {
+        let mut error = new_error();
+        let mut database = FFI_AdbcDatabase {
+            private_data: std::ptr::null_mut(),
+            private_driver: std::ptr::null_mut(),
+        };
+        ok(AdbcDatabaseNew(&mut database, &mut error), &mut error);
+        ok(AdbcDatabaseInit(&mut database, &mut error), &mut error);
+
+        let mut connection = FFI_AdbcConnection {
+            private_data: std::ptr::null_mut(),
+            private_driver: std::ptr::null_mut(),
+        };
+        ok(AdbcConnectionNew(&mut connection, &mut error), &mut error);
+        ok(
+            AdbcConnectionInit(&mut connection, &mut database, &mut error),
+            &mut error,
+        );
+        Handles {
+            database,
+            connection,
+        }
+    }
+
+    unsafe fn release(mut handles: Handles) {
+        let mut error = new_error();
+        ok(
+            AdbcConnectionRelease(&mut handles.connection, &mut error),
+            &mut error,
+        );
+        ok(
+            AdbcDatabaseRelease(&mut handles.database, &mut error),
+            &mut error,
+        );
+    }
+
+    unsafe fn new_statement(handles: &mut Handles) -> FFI_AdbcStatement {
+        let mut error = new_error();
+        let mut stmt = FFI_AdbcStatement {
+            private_data: std::ptr::null_mut(),
+            private_driver: std::ptr::null_mut(),
+        };
+        ok(
+            AdbcStatementNew(&mut handles.connection, &mut stmt, &mut error),
+            &mut error,
+        );
+        stmt
+    }
+
+    /// Execute a statement, collecting the results.
+    unsafe fn execute(
+        stmt: &mut FFI_AdbcStatement,
+        query: Option<&str>,
+    ) -> (Vec<RecordBatch>, i64) {
+        let mut error = new_error();
+        if let Some(query) = query {
+            let query = CString::new(query).unwrap();
+            ok(
+                AdbcStatementSetSqlQuery(stmt, query.as_ptr(), &mut error),
+                &mut error,
+            );
+        }
+        let mut out = FFI_ArrowArrayStream::empty();
+        let mut rows_affected = 0;
+        ok(
+            AdbcStatementExecuteQuery(stmt, &mut out, &mut rows_affected, &mut error),
+            &mut error,
+        );
+        let reader = ArrowArrayStreamReader::try_new(out).unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        (batches, rows_affected)
+    }
+
+    unsafe fn bind(stmt: &mut FFI_AdbcStatement, batch: RecordBatch) {
+        let mut error = new_error();
+        let data = StructArray::from(batch).into_data();
+        let mut array = FFI_ArrowArray::new(&data);
+        let mut schema = FFI_ArrowSchema::try_from(data.data_type()).unwrap();
+        ok(
+            AdbcStatementBind(stmt, &mut array, &mut schema, &mut error),
+            &mut error,
+        );
+    }
+
+    #[test]
+    fn execute_query() {
+        unsafe {
+            let mut handles = connect();
+            let mut stmt = new_statement(&mut handles);
+
+            let (batches, _) = execute(&mut stmt, Some("select * from generate_series(1, 5)"));
+            let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+            assert_eq!(5, rows);
+
+            let mut error = new_error();
+            ok(AdbcStatementRelease(&mut stmt, &mut error), &mut error);
+            release(handles);
+        }
+    }
+
+    #[test]
+    fn bind_parameters() {
+        unsafe {
+            let mut handles = connect();
+            let mut stmt = new_statement(&mut handles);
+
+            let query = CString::new("select $1 + 1 as a").unwrap();
+            let mut error = new_error();
+            ok(
+                AdbcStatementSetSqlQuery(&mut stmt, query.as_ptr(), &mut error),
+                &mut error,
+            );
+            ok(AdbcStatementPrepare(&mut stmt, &mut error), &mut error);
+
+            let schema = Arc::new(Schema::new(vec![Field::new("0", DataType::Int64, true)]));
+            let params =
+                RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(vec![41]))]).unwrap();
+            bind(&mut stmt, params);
+
+            let (batches, _) = execute(&mut stmt, None);
+            assert_eq!(1, batches.len());
+            let col = batches[0].column(0).as_primitive::<Int64Type>();
+            assert_eq!(42, col.value(0));
+
+            ok(AdbcStatementRelease(&mut stmt, &mut error), &mut error);
+            release(handles);
+        }
+    }
+
+    #[test]
+    fn ingest() {
+        unsafe {
+            let mut handles = connect();
+            let mut stmt = new_statement(&mut handles);
+
+            let mut error = new_error();
+            let key = CString::new(ADBC_INGEST_OPTION_TARGET_TABLE).unwrap();
+            let value = CString::new("ingested").unwrap();
+            ok(
+                AdbcStatementSetOption(&mut stmt, key.as_ptr(), value.as_ptr(), &mut error),
+                &mut error,
+            );
+
+            let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+            let data =
+                RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(vec![1, 2, 3]))])
+                    .unwrap();
+            bind(&mut stmt, data);
+            let (_, rows_affected) = execute(&mut stmt, None);
+            assert_eq!(3, rows_affected);
+
+            let (batches, _) = execute(&mut stmt, Some("select sum(a) from ingested"));
+            let col = batches[0].column(0).as_primitive::<Int64Type>();
+            assert_eq!(6, col.value(0));
+
+            ok(AdbcStatementRelease(&mut stmt, &mut error), &mut error);
+            release(handles);
+        }
+    }
+
+    #[test]
+    fn get_objects() {
+        unsafe {
+            let mut handles = connect();
+            let mut stmt = new_statement(&mut handles);
+            execute(&mut stmt, Some("create table objects_test (a int, b text)"));
+
+            let mut error = new_error();
+            let table_name = CString::new("objects_test").unwrap();
+            let mut out = FFI_ArrowArrayStream::empty();
+            ok(
+                AdbcConnectionGetObjects(
+                    &mut handles.connection,
+                    ADBC_OBJECT_DEPTH_ALL,
+                    std::ptr::null(),
+                    std::ptr::null(),
+                    table_name.as_ptr(),
+                    std::ptr::null(),
+                    std::ptr::null(),
+                    &mut out,
+                    &mut error,
+                ),
+                &mut error,
+            );
+            let reader = ArrowArrayStreamReader::try_new(out).unwrap();
+            let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(1, batches.len());
+
+            // Find the columns of the one table in the result.
+            let schemas = batches[0].column(1).as_list::<i32>().values().as_struct();
+            let tables = schemas.column(1).as_list::<i32>().values().as_struct();
+            assert_eq!(1, tables.len());
+            let columns = tables.column(2).as_list::<i32>().values().as_struct();
+            let names = columns.column(0).as_string::<i32>();
+            assert_eq!(vec![Some("a"), Some("b")], names.iter().collect::<Vec<_>>());
+
+            ok(AdbcStatementRelease(&mut stmt, &mut error), &mut error);
+            release(handles);
+        }
+    }
+
+    #[test]
+    fn errors_are_written() {
+        unsafe {
+            let mut handles = connect();
+            let mut error = new_error();
+            let status = AdbcConnectionCommit(&mut handles.connection, &mut error);
+            assert_eq!(ADBC_STATUS_INVALID_STATE, status);
+            assert!(!error.message.is_null());
+            (error.release.unwrap())(&mut error);
+            assert!(error.message.is_null());
+            release(handles);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::ffi::CString;
+use std::fmt::Display;
+
+use datafusion::arrow::error::ArrowError;
+use metastore::errors::MetastoreError;
+use sqlexec::errors::ExecError;
+
+use crate::ffi::{
+    AdbcStatusCode, FFI_AdbcError, ADBC_STATUS_INTERNAL, ADBC_STATUS_INVALID_ARGUMENT,
+    ADBC_STATUS_INVALID_DATA, ADBC_STATUS_INVALID_STATE, ADBC_STATUS_NOT_FOUND,
+    ADBC_STATUS_NOT_IMPLEMENTED, ADBC_STATUS_UNKNOWN,
+};
+
+pub type Result<T, E = AdbcError> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AdbcError {
+    #[error(transparent)]
+    Arrow(#[from] ArrowError),
+    #[error(transparent)]
+    Metastore(#[from] MetastoreError),
+    #[error(transparent)]
+    Exec(#[from] ExecError),
+    #[error(transparent)]
+    DataFusion(#[from] datafusion::error::DataFusionError),
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("{0}")]
+    InvalidState(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    NotImplemented(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl AdbcError {
+    pub fn new(msg: impl Display) -> Self {
+        Self::Other(msg.to_string())
+    }
+
+    pub fn status(&self) -> AdbcStatusCode {
+        match self {
+            Self::Arrow(_) => ADBC_STATUS_INVALID_DATA,
+            Self::Metastore(_) | Self::Exec(_) | Self::DataFusion(_) => ADBC_STATUS_INTERNAL,
+            Self::InvalidArgument(_) => ADBC_STATUS_INVALID_ARGUMENT,
+            Self::InvalidState(_) => ADBC_STATUS_INVALID_STATE,
+            Self::NotFound(_) => ADBC_STATUS_NOT_FOUND,
+            Self::NotImplemented(_) => ADBC_STATUS_NOT_IMPLEMENTED,
+            Self::Other(_) => ADBC_STATUS_UNKNOWN,
+        }
+    }
+
+    /// Write this error to the caller provided error struct, returning the
+    /// status code for the error.
+    ///
+    /// # Safety
+    ///
+    /// `out` must be null or point to a valid `AdbcError`.
+    pub unsafe fn write(&self, out: *mut FFI_AdbcError) -> AdbcStatusCode {
+        if let Some(out) = out.as_mut() {
+            if let Some(release) = out.release {
+                release(out);
+            }
+            // Messages can't contain interior nul bytes.
+            let msg = self.to_string().replace('\0', "");
+            out.message = CString::new(msg).unwrap_or_default().into_raw();
+            out.vendor_code = 0;
+            out.sqlstate = [0; 5];
+            out.release = Some(release_error);
+        }
+        self.status()
+    }
+}
+
+unsafe extern "C" fn release_error(error: *mut FFI_AdbcError) {
+    if let Some(error) = error.as_mut() {
+        if !error.message.is_null() {
+            drop(CString::from_raw(error.message));
+        }
+        error.message = std::ptr::null_mut();
+        error.release = None;
+    }
+}
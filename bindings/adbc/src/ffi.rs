@@ -0,0 +1,174 @@
+//! Definitions of the structs and constants in the ADBC C API (`adbc.h`).
+//!
+//! These must match the layout of the header exactly, see
+//! <https://arrow.apache.org/adbc/current/format/specification.html>.
+#![allow(non_camel_case_types, clippy::type_complexity)]
+
+use arrow::ffi::{FFI_ArrowArray, FFI_ArrowSchema};
+use arrow::ffi_stream::FFI_ArrowArrayStream;
+use std::ffi::{c_char, c_int, c_void};
+
+pub type AdbcStatusCode = u8;
+
+pub const ADBC_STATUS_OK: AdbcStatusCode = 0;
+pub const ADBC_STATUS_UNKNOWN: AdbcStatusCode = 1;
+pub const ADBC_STATUS_NOT_IMPLEMENTED: AdbcStatusCode = 2;
+pub const ADBC_STATUS_NOT_FOUND: AdbcStatusCode = 3;
+pub const ADBC_STATUS_ALREADY_EXISTS: AdbcStatusCode = 4;
+pub const ADBC_STATUS_INVALID_ARGUMENT: AdbcStatusCode = 5;
+pub const ADBC_STATUS_INVALID_STATE: AdbcStatusCode = 6;
+pub const ADBC_STATUS_INVALID_DATA: AdbcStatusCode = 7;
+pub const ADBC_STATUS_INTEGRITY: AdbcStatusCode = 8;
+pub const ADBC_STATUS_INTERNAL: AdbcStatusCode = 9;
+pub const ADBC_STATUS_IO: AdbcStatusCode = 10;
+pub const ADBC_STATUS_CANCELLED: AdbcStatusCode = 11;
+pub const ADBC_STATUS_TIMEOUT: AdbcStatusCode = 12;
+pub const ADBC_STATUS_UNAUTHENTICATED: AdbcStatusCode = 13;
+pub const ADBC_STATUS_UNAUTHORIZED: AdbcStatusCode = 14;
+
+pub const ADBC_VERSION_1_0_0: c_int = 1_000_000;
+
+pub const ADBC_INFO_VENDOR_NAME: u32 = 0;
+pub const ADBC_INFO_VENDOR_VERSION: u32 = 1;
+pub const ADBC_INFO_DRIVER_NAME: u32 = 100;
+pub const ADBC_INFO_DRIVER_VERSION: u32 = 101;
+
+pub const ADBC_OBJECT_DEPTH_ALL: c_int = 0;
+pub const ADBC_OBJECT_DEPTH_CATALOGS: c_int = 1;
+pub const ADBC_OBJECT_DEPTH_DB_SCHEMAS: c_int = 2;
+pub const ADBC_OBJECT_DEPTH_TABLES: c_int = 3;
+
+pub const ADBC_OPTION_VALUE_ENABLED: &str = "true";
+pub const ADBC_OPTION_VALUE_DISABLED: &str = "false";
+pub const ADBC_OPTION_URI: &str = "uri";
+pub const ADBC_CONNECTION_OPTION_AUTOCOMMIT: &str = "adbc.connection.autocommit";
+pub const ADBC_INGEST_OPTION_TARGET_TABLE: &str = "adbc.ingest.target_table";
+pub const ADBC_INGEST_OPTION_MODE: &str = "adbc.ingest.mode";
+pub const ADBC_INGEST_OPTION_MODE_CREATE: &str = "adbc.ingest.mode.create";
+pub const ADBC_INGEST_OPTION_MODE_APPEND: &str = "adbc.ingest.mode.append";
+
+#[repr(C)]
+pub struct FFI_AdbcError {
+    pub message: *mut c_char,
+    pub vendor_code: i32,
+    pub sqlstate: [c_char; 5],
+    pub release: Option<unsafe extern "C" fn(error: *mut FFI_AdbcError)>,
+}
+
+#[repr(C)]
+pub struct FFI_AdbcDatabase {
+    pub private_data: *mut c_void,
+    pub private_driver: *mut FFI_AdbcDriver,
+}
+
+#[repr(C)]
+pub struct FFI_AdbcConnection {
+    pub private_data: *mut c_void,
+    pub private_driver: *mut FFI_AdbcDriver,
+}
+
+#[repr(C)]
+pub struct FFI_AdbcStatement {
+    pub private_data: *mut c_void,
+    pub private_driver: *mut FFI_AdbcDriver,
+}
+
+#[repr(C)]
+pub struct FFI_AdbcPartitions {
+    pub num_partitions: usize,
+    pub partitions: *mut *const u8,
+    pub partition_lengths: *const usize,
+    pub private_data: *mut c_void,
+    pub release: Option<unsafe extern "C" fn(partitions: *mut FFI_AdbcPartitions)>,
+}
+
+type Error = *mut FFI_AdbcError;
+type Database = *mut FFI_AdbcDatabase;
+type Connection = *mut FFI_AdbcConnection;
+type Statement = *mut FFI_AdbcStatement;
+type Stream = *mut FFI_ArrowArrayStream;
+type Schema = *mut FFI_ArrowSchema;
+
+/// Table of driver functions, filled in by `AdbcDriverInit`.
+#[repr(C)]
+pub struct FFI_AdbcDriver {
+    pub private_data: *mut c_void,
+    pub private_manager: *mut c_void,
+    pub release: Option<unsafe extern "C" fn(*mut FFI_AdbcDriver, Error) -> AdbcStatusCode>,
+
+    pub database_init: Option<unsafe extern "C" fn(Database, Error) -> AdbcStatusCode>,
+    pub database_new: Option<unsafe extern "C" fn(Database, Error) -> AdbcStatusCode>,
+    pub database_set_option: Option<
+        unsafe extern "C" fn(Database, *const c_char, *const c_char, Error) -> AdbcStatusCode,
+    >,
+    pub database_release: Option<unsafe extern "C" fn(Database, Error) -> AdbcStatusCode>,
+
+    pub connection_commit: Option<unsafe extern "C" fn(Connection, Error) -> AdbcStatusCode>,
+    pub connection_get_info: Option<
+        unsafe extern "C" fn(Connection, *const u32, usize, Stream, Error) -> AdbcStatusCode,
+    >,
+    pub connection_get_objects: Option<
+        unsafe extern "C" fn(
+            Connection,
+            c_int,
+            *const c_char,
+            *const c_char,
+            *const c_char,
+            *const *const c_char,
+            *const c_char,
+            Stream,
+            Error,
+        ) -> AdbcStatusCode,
+    >,
+    pub connection_get_table_schema: Option<
+        unsafe extern "C" fn(
+            Connection,
+            *const c_char,
+            *const c_char,
+            *const c_char,
+            Schema,
+            Error,
+        ) -> AdbcStatusCode,
+    >,
+    pub connection_get_table_types:
+        Option<unsafe extern "C" fn(Connection, Stream, Error) -> AdbcStatusCode>,
+    pub connection_init:
+        Option<unsafe extern "C" fn(Connection, Database, Error) -> AdbcStatusCode>,
+    pub connection_new: Option<unsafe extern "C" fn(Connection, Error) -> AdbcStatusCode>,
+    pub connection_set_option: Option<
+        unsafe extern "C" fn(Connection, *const c_char, *const c_char, Error) -> AdbcStatusCode,
+    >,
+    pub connection_read_partition:
+        Option<unsafe extern "C" fn(Connection, *const u8, usize, Stream, Error) -> AdbcStatusCode>,
+    pub connection_release: Option<unsafe extern "C" fn(Connection, Error) -> AdbcStatusCode>,
+    pub connection_rollback: Option<unsafe extern "C" fn(Connection, Error) -> AdbcStatusCode>,
+
+    pub statement_bind: Option<
+        unsafe extern "C" fn(Statement, *mut FFI_ArrowArray, Schema, Error) -> AdbcStatusCode,
+    >,
+    pub statement_bind_stream:
+        Option<unsafe extern "C" fn(Statement, Stream, Error) -> AdbcStatusCode>,
+    pub statement_execute_query:
+        Option<unsafe extern "C" fn(Statement, Stream, *mut i64, Error) -> AdbcStatusCode>,
+    pub statement_execute_partitions: Option<
+        unsafe extern "C" fn(
+            Statement,
+            Schema,
+            *mut FFI_AdbcPartitions,
+            *mut i64,
+            Error,
+        ) -> AdbcStatusCode,
+    >,
+    pub statement_get_parameter_schema:
+        Option<unsafe extern "C" fn(Statement, Schema, Error) -> AdbcStatusCode>,
+    pub statement_new: Option<unsafe extern "C" fn(Connection, Statement, Error) -> AdbcStatusCode>,
+    pub statement_prepare: Option<unsafe extern "C" fn(Statement, Error) -> AdbcStatusCode>,
+    pub statement_release: Option<unsafe extern "C" fn(Statement, Error) -> AdbcStatusCode>,
+    pub statement_set_option: Option<
+        unsafe extern "C" fn(Statement, *const c_char, *const c_char, Error) -> AdbcStatusCode,
+    >,
+    pub statement_set_sql_query:
+        Option<unsafe extern "C" fn(Statement, *const c_char, Error) -> AdbcStatusCode>,
+    pub statement_set_substrait_plan:
+        Option<unsafe extern "C" fn(Statement, *const u8, usize, Error) -> AdbcStatusCode>,
+}
@@ -0,0 +1,47 @@
+use std::sync::{Arc, Mutex};
+
+use datafusion::datasource::TableProvider;
+use sqlexec::environment::EnvironmentReader;
+
+/// Name the data bound for bulk ingestion is exposed to queries under.
+pub(crate) const INGEST_TABLE_NAME: &str = "__adbc_ingest";
+
+/// Exposes data being ingested by a statement to its connection's session.
+///
+/// Ingestion is an `INSERT INTO ... SELECT` (or `CREATE TABLE ... AS
+/// SELECT`) from the bound data, which is only visible for the duration of
+/// that query.
+#[derive(Clone, Default)]
+pub(crate) struct IngestReader {
+    table: Arc<Mutex<Option<Arc<dyn TableProvider>>>>,
+}
+
+impl IngestReader {
+    /// Expose `table` until the returned guard is dropped.
+    pub(crate) fn expose(&self, table: Arc<dyn TableProvider>) -> IngestGuard<'_> {
+        *self.table.lock().unwrap() = Some(table);
+        IngestGuard { reader: self }
+    }
+}
+
+impl EnvironmentReader for IngestReader {
+    fn resolve_table(
+        &self,
+        name: &str,
+    ) -> Result<Option<Arc<dyn TableProvider>>, Box<dyn std::error::Error + Send + Sync>> {
+        if name != INGEST_TABLE_NAME {
+            return Ok(None);
+        }
+        Ok(self.table.lock().unwrap().clone())
+    }
+}
+
+pub(crate) struct IngestGuard<'a> {
+    reader: &'a IngestReader,
+}
+
+impl Drop for IngestGuard<'_> {
+    fn drop(&mut self) {
+        *self.reader.table.lock().unwrap() = None;
+    }
+}
@@ -0,0 +1,31 @@
+//! An [ADBC](https://arrow.apache.org/adbc/) driver for GlareDB.
+//!
+//! Builds a shared library exporting the ADBC 1.0 C API, which lets ADBC
+//! driver managers for C, Go, Java, Python, etc. use GlareDB directly, with
+//! results streamed as Arrow record batches instead of going through pgwire
+//! text encoding.
+//!
+//! ```python
+//! import adbc_driver_manager.dbapi
+//!
+//! conn = adbc_driver_manager.dbapi.connect(
+//!     driver="libadbc_driver_glaredb.so",
+//!     db_kwargs={"uri": "/path/to/data"},
+//! )
+//! ```
+//!
+//! The database `uri` option accepts the same values as `glaredb.connect` in
+//! the other bindings: a local data directory, a GlareDB Cloud url, or
+//! nothing for an in-memory database.
+pub mod connection;
+pub mod database;
+pub mod driver;
+pub mod error;
+pub mod ffi;
+pub mod objects;
+pub mod statement;
+
+mod ingest;
+mod runtime;
+mod schemas;
+mod stream;
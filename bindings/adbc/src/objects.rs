@@ -0,0 +1,404 @@
+//! Build `AdbcConnectionGetObjects` results from the `glare_catalog` tables.
+use std::collections::HashMap;
+use std::ffi::c_int;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{
+    new_empty_array, new_null_array, Array, ArrayRef, AsArray, Int16Array, Int32Array, ListArray,
+    StringArray, StructArray,
+};
+use datafusion::arrow::buffer::{NullBuffer, OffsetBuffer};
+use datafusion::arrow::datatypes::{DataType, Field, Fields, UInt32Type};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::scalar::ScalarValue;
+
+use crate::connection::{Session, TABLE_TYPES};
+use crate::error::Result;
+use crate::ffi::{ADBC_OBJECT_DEPTH_ALL, ADBC_OBJECT_DEPTH_DB_SCHEMAS, ADBC_OBJECT_DEPTH_TABLES};
+use crate::schemas::{
+    COLUMN_FIELDS, CONSTRAINT_FIELDS, DB_SCHEMA_FIELDS, GET_OBJECTS_SCHEMA, TABLE_FIELDS,
+};
+
+/// Filters for the objects to return.
+///
+/// All name filters are `LIKE` patterns, with `None` matching everything.
+#[derive(Debug, Default)]
+pub struct ObjectsFilter<'a> {
+    pub depth: c_int,
+    pub catalog: Option<&'a str>,
+    pub db_schema: Option<&'a str>,
+    pub table_name: Option<&'a str>,
+    pub table_types: Option<Vec<&'a str>>,
+    pub column_name: Option<&'a str>,
+}
+
+impl ObjectsFilter<'_> {
+    fn include_db_schemas(&self) -> bool {
+        self.depth == ADBC_OBJECT_DEPTH_ALL || self.depth >= ADBC_OBJECT_DEPTH_DB_SCHEMAS
+    }
+
+    fn include_tables(&self) -> bool {
+        self.depth == ADBC_OBJECT_DEPTH_ALL || self.depth >= ADBC_OBJECT_DEPTH_TABLES
+    }
+
+    fn include_columns(&self) -> bool {
+        self.depth == ADBC_OBJECT_DEPTH_ALL || self.depth > ADBC_OBJECT_DEPTH_TABLES
+    }
+}
+
+struct Catalog {
+    oid: u32,
+    name: String,
+}
+
+struct DbSchema {
+    oid: u32,
+    name: String,
+}
+
+struct Table {
+    oid: u32,
+    name: String,
+    table_type: String,
+}
+
+struct Column {
+    name: String,
+    ordinal: i32,
+    data_type: String,
+    nullable: bool,
+}
+
+pub(crate) async fn get_objects(sess: &Session, filter: ObjectsFilter<'_>) -> Result<RecordBatch> {
+    let catalogs = query_catalogs(sess, &filter).await?;
+
+    // Children of each object, keyed by the parent's oid.
+    let schemas = if filter.include_db_schemas() {
+        query_db_schemas(sess, &filter).await?
+    } else {
+        HashMap::new()
+    };
+    let tables = if filter.include_tables() {
+        query_tables(sess, &filter).await?
+    } else {
+        HashMap::new()
+    };
+    let columns = if filter.include_columns() {
+        query_columns(sess, &filter).await?
+    } else {
+        HashMap::new()
+    };
+
+    let mut catalog_names = Vec::new();
+    let mut catalog_schemas = Vec::new();
+    let mut schema_names = Vec::new();
+    let mut schema_tables = Vec::new();
+    let mut table_names = Vec::new();
+    let mut table_types = Vec::new();
+    let mut table_columns = Vec::new();
+    let mut cols = Vec::new();
+
+    for catalog in &catalogs {
+        let catalog_schemas_start = schema_names.len();
+        for schema in schemas.get(&catalog.oid).into_iter().flatten() {
+            let schema_tables_start = table_names.len();
+            for table in tables.get(&schema.oid).into_iter().flatten() {
+                let table_columns_start = cols.len();
+                cols.extend(columns.get(&table.oid).into_iter().flatten());
+                table_names.push(table.name.as_str());
+                table_types.push(table.table_type.as_str());
+                table_columns.push(cols.len() - table_columns_start);
+            }
+            schema_names.push(schema.name.as_str());
+            schema_tables.push(table_names.len() - schema_tables_start);
+        }
+        catalog_names.push(catalog.name.as_str());
+        catalog_schemas.push(schema_names.len() - catalog_schemas_start);
+    }
+
+    let columns = struct_array(
+        &COLUMN_FIELDS,
+        cols.len(),
+        vec![
+            (
+                "column_name",
+                Arc::new(StringArray::from_iter_values(cols.iter().map(|c| &c.name))) as ArrayRef,
+            ),
+            (
+                "ordinal_position",
+                Arc::new(Int32Array::from_iter_values(cols.iter().map(|c| c.ordinal))) as ArrayRef,
+            ),
+            (
+                "xdbc_type_name",
+                Arc::new(StringArray::from_iter_values(
+                    cols.iter().map(|c| &c.data_type),
+                )) as ArrayRef,
+            ),
+            (
+                "xdbc_nullable",
+                Arc::new(Int16Array::from_iter_values(
+                    cols.iter().map(|c| i16::from(c.nullable)),
+                )) as ArrayRef,
+            ),
+            (
+                "xdbc_is_nullable",
+                Arc::new(StringArray::from_iter_values(cols.iter().map(|c| {
+                    if c.nullable {
+                        "YES"
+                    } else {
+                        "NO"
+                    }
+                }))) as ArrayRef,
+            ),
+        ],
+    )?;
+
+    let constraints = new_empty_array(&DataType::Struct(CONSTRAINT_FIELDS.clone()));
+    let tables = struct_array(
+        &TABLE_FIELDS,
+        table_names.len(),
+        vec![
+            (
+                "table_name",
+                Arc::new(StringArray::from(table_names)) as ArrayRef,
+            ),
+            (
+                "table_type",
+                Arc::new(StringArray::from(table_types)) as ArrayRef,
+            ),
+            (
+                "table_columns",
+                list_array(columns, &table_columns, filter.include_columns())?,
+            ),
+            (
+                "table_constraints",
+                list_array(
+                    constraints,
+                    &vec![0; table_columns.len()],
+                    filter.include_columns(),
+                )?,
+            ),
+        ],
+    )?;
+
+    let schemas = struct_array(
+        &DB_SCHEMA_FIELDS,
+        schema_names.len(),
+        vec![
+            (
+                "db_schema_name",
+                Arc::new(StringArray::from(schema_names)) as ArrayRef,
+            ),
+            (
+                "db_schema_tables",
+                list_array(tables, &schema_tables, filter.include_tables())?,
+            ),
+        ],
+    )?;
+
+    Ok(RecordBatch::try_new(
+        GET_OBJECTS_SCHEMA.clone(),
+        vec![
+            Arc::new(StringArray::from(catalog_names)),
+            list_array(schemas, &catalog_schemas, filter.include_db_schemas())?,
+        ],
+    )?)
+}
+
+/// Build a struct array with the given columns, filling in the remaining
+/// fields with nulls.
+fn struct_array(fields: &Fields, len: usize, columns: Vec<(&str, ArrayRef)>) -> Result<ArrayRef> {
+    let mut columns: HashMap<_, _> = columns.into_iter().collect();
+    let arrays = fields
+        .iter()
+        .map(|field| {
+            columns
+                .remove(field.name().as_str())
+                .unwrap_or_else(|| new_null_array(field.data_type(), len))
+        })
+        .collect();
+    Ok(Arc::new(StructArray::try_new(
+        fields.clone(),
+        arrays,
+        None,
+    )?))
+}
+
+/// Build a list array from the length of each list. If `valid` is false,
+/// every list is null.
+fn list_array(values: ArrayRef, lengths: &[usize], valid: bool) -> Result<ArrayRef> {
+    let field = Arc::new(Field::new("item", values.data_type().clone(), true));
+    let nulls = (!valid).then(|| NullBuffer::new_null(lengths.len()));
+    Ok(Arc::new(ListArray::try_new(
+        field,
+        OffsetBuffer::from_lengths(lengths.iter().copied()),
+        values,
+        nulls,
+    )?))
+}
+
+fn pattern(pattern: Option<&str>) -> Vec<ScalarValue> {
+    vec![ScalarValue::Utf8(Some(pattern.unwrap_or("%").to_string()))]
+}
+
+async fn query_catalogs(sess: &Session, filter: &ObjectsFilter<'_>) -> Result<Vec<Catalog>> {
+    let batches = sess
+        .query(
+            "SELECT oid, database_name FROM glare_catalog.databases \
+             WHERE database_name LIKE $1 ORDER BY database_name",
+            pattern(filter.catalog),
+        )
+        .await?;
+
+    let mut catalogs = Vec::new();
+    for batch in batches {
+        let oids = batch.column(0).as_primitive::<UInt32Type>();
+        let names = batch.column(1).as_string::<i32>();
+        for row in 0..batch.num_rows() {
+            catalogs.push(Catalog {
+                oid: oids.value(row),
+                name: names.value(row).to_string(),
+            });
+        }
+    }
+    Ok(catalogs)
+}
+
+async fn query_db_schemas(
+    sess: &Session,
+    filter: &ObjectsFilter<'_>,
+) -> Result<HashMap<u32, Vec<DbSchema>>> {
+    let batches = sess
+        .query(
+            "SELECT database_oid, oid, schema_name FROM glare_catalog.schemas \
+             WHERE schema_name LIKE $1 ORDER BY schema_name",
+            pattern(filter.db_schema),
+        )
+        .await?;
+
+    let mut schemas: HashMap<_, Vec<_>> = HashMap::new();
+    for batch in batches {
+        let database_oids = batch.column(0).as_primitive::<UInt32Type>();
+        let oids = batch.column(1).as_primitive::<UInt32Type>();
+        let names = batch.column(2).as_string::<i32>();
+        for row in 0..batch.num_rows() {
+            schemas
+                .entry(database_oids.value(row))
+                .or_default()
+                .push(DbSchema {
+                    oid: oids.value(row),
+                    name: names.value(row).to_string(),
+                });
+        }
+    }
+    Ok(schemas)
+}
+
+async fn query_tables(
+    sess: &Session,
+    filter: &ObjectsFilter<'_>,
+) -> Result<HashMap<u32, Vec<Table>>> {
+    let batches = sess
+        .query(
+            &format!(
+                "SELECT schema_oid, oid, table_name, '{}' AS table_type FROM glare_catalog.tables \
+                 WHERE table_name LIKE $1 \
+                 UNION ALL \
+                 SELECT schema_oid, oid, view_name, '{}' FROM glare_catalog.views \
+                 WHERE view_name LIKE $1 \
+                 ORDER BY table_name",
+                TABLE_TYPES[0], TABLE_TYPES[1],
+            ),
+            pattern(filter.table_name),
+        )
+        .await?;
+
+    let mut tables: HashMap<_, Vec<_>> = HashMap::new();
+    for batch in batches {
+        let schema_oids = batch.column(0).as_primitive::<UInt32Type>();
+        let oids = batch.column(1).as_primitive::<UInt32Type>();
+        let names = batch.column(2).as_string::<i32>();
+        let types = batch.column(3).as_string::<i32>();
+        for row in 0..batch.num_rows() {
+            let table_type = types.value(row);
+            if let Some(allowed) = &filter.table_types {
+                if !allowed.iter().any(|t| t.eq_ignore_ascii_case(table_type)) {
+                    continue;
+                }
+            }
+            tables
+                .entry(schema_oids.value(row))
+                .or_default()
+                .push(Table {
+                    oid: oids.value(row),
+                    name: names.value(row).to_string(),
+                    table_type: table_type.to_string(),
+                });
+        }
+    }
+    Ok(tables)
+}
+
+async fn query_columns(
+    sess: &Session,
+    filter: &ObjectsFilter<'_>,
+) -> Result<HashMap<u32, Vec<Column>>> {
+    let batches = sess
+        .query(
+            "SELECT table_oid, column_name, column_ordinal, data_type, is_nullable \
+             FROM glare_catalog.columns \
+             WHERE column_name LIKE $1 ORDER BY table_oid, column_ordinal",
+            pattern(filter.column_name),
+        )
+        .await?;
+
+    let mut columns: HashMap<_, Vec<_>> = HashMap::new();
+    for batch in batches {
+        let table_oids = batch.column(0).as_primitive::<UInt32Type>();
+        let names = batch.column(1).as_string::<i32>();
+        let ordinals = batch.column(2).as_primitive::<UInt32Type>();
+        let data_types = batch.column(3).as_string::<i32>();
+        let nullable = batch.column(4).as_boolean();
+        for row in 0..batch.num_rows() {
+            columns
+                .entry(table_oids.value(row))
+                .or_default()
+                .push(Column {
+                    name: names.value(row).to_string(),
+                    // Ordinals are 1-based in ADBC.
+                    ordinal: ordinals.value(row) as i32 + 1,
+                    data_type: data_types.value(row).to_string(),
+                    nullable: nullable.value(row),
+                });
+        }
+    }
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::ADBC_OBJECT_DEPTH_CATALOGS;
+
+    #[test]
+    fn null_lists_when_not_included() {
+        let values = new_empty_array(&DataType::Utf8);
+        let list = list_array(values, &[0, 0], false).unwrap();
+        assert_eq!(2, list.len());
+        assert_eq!(2, list.null_count());
+    }
+
+    #[test]
+    fn depth_includes_levels() {
+        let filter = |depth| ObjectsFilter {
+            depth,
+            ..Default::default()
+        };
+        assert!(!filter(ADBC_OBJECT_DEPTH_CATALOGS).include_db_schemas());
+        assert!(filter(ADBC_OBJECT_DEPTH_DB_SCHEMAS).include_db_schemas());
+        assert!(!filter(ADBC_OBJECT_DEPTH_DB_SCHEMAS).include_tables());
+        assert!(filter(ADBC_OBJECT_DEPTH_TABLES).include_tables());
+        assert!(!filter(ADBC_OBJECT_DEPTH_TABLES).include_columns());
+        assert!(filter(ADBC_OBJECT_DEPTH_ALL).include_columns());
+    }
+}
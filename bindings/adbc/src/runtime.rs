@@ -0,0 +1,21 @@
+use std::future::Future;
+
+use once_cell::sync::Lazy;
+use tokio::runtime::{Builder, Runtime};
+
+/// Runtime shared by all databases opened through the driver.
+///
+/// ADBC calls are blocking, so every call into the engine blocks on this
+/// runtime.
+static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+    Builder::new_multi_thread()
+        .thread_name("glaredb-adbc")
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime")
+});
+
+/// Block the calling thread on a future.
+pub(crate) fn block_on<F: Future>(f: F) -> F::Output {
+    RUNTIME.block_on(f)
+}
@@ -0,0 +1,140 @@
+//! Schemas of the metadata results defined by ADBC.
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::{DataType, Field, Fields, Schema, UnionFields, UnionMode};
+use once_cell::sync::Lazy;
+
+/// Schema of `AdbcConnectionGetInfo` results.
+pub static GET_INFO_SCHEMA: Lazy<Arc<Schema>> = Lazy::new(|| {
+    Arc::new(Schema::new(vec![
+        Field::new("info_name", DataType::UInt32, false),
+        Field::new("info_value", info_value_type(), true),
+    ]))
+});
+
+/// Type id of `string_value` in the `info_value` union.
+pub const INFO_STRING_VALUE_TYPE_ID: i8 = 0;
+
+fn info_value_type() -> DataType {
+    let map_entries = Field::new(
+        "entries",
+        DataType::Struct(Fields::from(vec![
+            Field::new("key", DataType::Int32, false),
+            Field::new("value", list_of(DataType::Int32), true),
+        ])),
+        false,
+    );
+    DataType::Union(
+        UnionFields::new(
+            0..6,
+            vec![
+                Field::new("string_value", DataType::Utf8, true),
+                Field::new("bool_value", DataType::Boolean, true),
+                Field::new("int64_value", DataType::Int64, true),
+                Field::new("int32_bitmask", DataType::Int32, true),
+                Field::new("string_list", list_of(DataType::Utf8), true),
+                Field::new(
+                    "int32_to_int32_list_map",
+                    DataType::Map(Arc::new(map_entries), false),
+                    true,
+                ),
+            ],
+        ),
+        UnionMode::Dense,
+    )
+}
+
+/// Schema of `AdbcConnectionGetTableTypes` results.
+pub static GET_TABLE_TYPES_SCHEMA: Lazy<Arc<Schema>> = Lazy::new(|| {
+    Arc::new(Schema::new(vec![Field::new(
+        "table_type",
+        DataType::Utf8,
+        false,
+    )]))
+});
+
+/// Schema of `AdbcConnectionGetObjects` results.
+pub static GET_OBJECTS_SCHEMA: Lazy<Arc<Schema>> = Lazy::new(|| {
+    Arc::new(Schema::new(vec![
+        Field::new("catalog_name", DataType::Utf8, true),
+        Field::new(
+            "catalog_db_schemas",
+            list_of(DataType::Struct(DB_SCHEMA_FIELDS.clone())),
+            true,
+        ),
+    ]))
+});
+
+pub static DB_SCHEMA_FIELDS: Lazy<Fields> = Lazy::new(|| {
+    Fields::from(vec![
+        Field::new("db_schema_name", DataType::Utf8, true),
+        Field::new(
+            "db_schema_tables",
+            list_of(DataType::Struct(TABLE_FIELDS.clone())),
+            true,
+        ),
+    ])
+});
+
+pub static TABLE_FIELDS: Lazy<Fields> = Lazy::new(|| {
+    Fields::from(vec![
+        Field::new("table_name", DataType::Utf8, false),
+        Field::new("table_type", DataType::Utf8, false),
+        Field::new(
+            "table_columns",
+            list_of(DataType::Struct(COLUMN_FIELDS.clone())),
+            true,
+        ),
+        Field::new(
+            "table_constraints",
+            list_of(DataType::Struct(CONSTRAINT_FIELDS.clone())),
+            true,
+        ),
+    ])
+});
+
+pub static COLUMN_FIELDS: Lazy<Fields> = Lazy::new(|| {
+    Fields::from(vec![
+        Field::new("column_name", DataType::Utf8, false),
+        Field::new("ordinal_position", DataType::Int32, true),
+        Field::new("remarks", DataType::Utf8, true),
+        Field::new("xdbc_data_type", DataType::Int16, true),
+        Field::new("xdbc_type_name", DataType::Utf8, true),
+        Field::new("xdbc_column_size", DataType::Int32, true),
+        Field::new("xdbc_decimal_digits", DataType::Int16, true),
+        Field::new("xdbc_num_prec_radix", DataType::Int16, true),
+        Field::new("xdbc_nullable", DataType::Int16, true),
+        Field::new("xdbc_column_def", DataType::Utf8, true),
+        Field::new("xdbc_sql_data_type", DataType::Int16, true),
+        Field::new("xdbc_datetime_sub", DataType::Int16, true),
+        Field::new("xdbc_char_octet_length", DataType::Int32, true),
+        Field::new("xdbc_is_nullable", DataType::Utf8, true),
+        Field::new("xdbc_scope_catalog", DataType::Utf8, true),
+        Field::new("xdbc_scope_schema", DataType::Utf8, true),
+        Field::new("xdbc_scope_table", DataType::Utf8, true),
+        Field::new("xdbc_is_autoincrement", DataType::Boolean, true),
+        Field::new("xdbc_is_generatedcolumn", DataType::Boolean, true),
+    ])
+});
+
+pub static CONSTRAINT_FIELDS: Lazy<Fields> = Lazy::new(|| {
+    Fields::from(vec![
+        Field::new("constraint_name", DataType::Utf8, true),
+        Field::new("constraint_type", DataType::Utf8, false),
+        Field::new("constraint_column_names", list_of(DataType::Utf8), false),
+        Field::new(
+            "constraint_column_usage",
+            list_of(DataType::Struct(Fields::from(vec![
+                Field::new("fk_catalog", DataType::Utf8, true),
+                Field::new("fk_db_schema", DataType::Utf8, true),
+                Field::new("fk_table", DataType::Utf8, false),
+                Field::new("fk_column_name", DataType::Utf8, false),
+            ]))),
+            true,
+        ),
+    ])
+});
+
+pub fn list_of(data_type: DataType) -> DataType {
+    DataType::List(Arc::new(Field::new("item", data_type, true)))
+}
@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use datafusion::arrow::compute::concat_batches;
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::datasource::MemTable;
+use datafusion::physical_plan::{EmptyRecordBatchStream, SendableRecordBatchStream};
+use datafusion::scalar::ScalarValue;
+use futures::StreamExt;
+use sqlexec::session::ExecutionResult;
+use sqlexec::LogicalPlan;
+
+use crate::connection::{quote_ident, Connection, Session};
+use crate::error::{AdbcError, Result};
+use crate::ffi::{
+    ADBC_INGEST_OPTION_MODE, ADBC_INGEST_OPTION_MODE_APPEND, ADBC_INGEST_OPTION_MODE_CREATE,
+    ADBC_INGEST_OPTION_TARGET_TABLE,
+};
+use crate::ingest::INGEST_TABLE_NAME;
+use crate::runtime::block_on;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IngestMode {
+    /// Create the target table, erroring if it already exists.
+    Create,
+    /// Append to an existing target table.
+    Append,
+}
+
+/// An `AdbcStatement`.
+pub struct Statement {
+    sess: Session,
+    query: Option<String>,
+    /// Plan for the query, if the statement was prepared.
+    prepared: Option<LogicalPlan>,
+    /// Bound parameters, or data to ingest. Each row is a set of parameters
+    /// for one execution of the query.
+    bound: Option<RecordBatch>,
+    ingest_target: Option<String>,
+    ingest_mode: IngestMode,
+}
+
+/// Result of executing a statement.
+pub struct StatementResult {
+    /// Result stream, only set if results were requested.
+    pub stream: Option<SendableRecordBatchStream>,
+    /// Number of rows affected, if known.
+    pub rows_affected: Option<usize>,
+}
+
+impl Statement {
+    pub fn new(conn: &Connection) -> Result<Self> {
+        Ok(Statement {
+            sess: conn.session()?.clone(),
+            query: None,
+            prepared: None,
+            bound: None,
+            ingest_target: None,
+            ingest_mode: IngestMode::Create,
+        })
+    }
+
+    pub fn set_sql_query(&mut self, query: &str) {
+        self.query = Some(query.to_string());
+        self.prepared = None;
+        self.ingest_target = None;
+    }
+
+    pub fn set_option(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            ADBC_INGEST_OPTION_TARGET_TABLE => {
+                self.ingest_target = Some(value.to_string());
+                self.query = None;
+                self.prepared = None;
+            }
+            ADBC_INGEST_OPTION_MODE => {
+                self.ingest_mode = match value {
+                    ADBC_INGEST_OPTION_MODE_CREATE => IngestMode::Create,
+                    ADBC_INGEST_OPTION_MODE_APPEND => IngestMode::Append,
+                    other => {
+                        return Err(AdbcError::NotImplemented(format!(
+                            "Unsupported ingest mode '{other}'"
+                        )))
+                    }
+                }
+            }
+            key => {
+                return Err(AdbcError::NotImplemented(format!(
+                    "Unknown statement option '{key}'"
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Plan the query, so that its parameter types can be inspected and
+    /// the plan reused across executions.
+    pub fn prepare(&mut self) -> Result<()> {
+        let query = self.query()?;
+        let plan = block_on(self.sess.plan(query))?;
+        self.prepared = Some(plan);
+        Ok(())
+    }
+
+    /// Get the schema of the query's parameters, with fields named by their
+    /// position. Parameters whose type couldn't be inferred are `Null`.
+    pub fn parameter_schema(&self) -> Result<Schema> {
+        let plan = self.prepared.as_ref().ok_or_else(|| {
+            AdbcError::InvalidState("Statement must be prepared first".to_string())
+        })?;
+
+        let types = plan.get_parameter_types()?;
+        let fields = (1..=types.len())
+            .map(|idx| {
+                let data_type = types
+                    .get(&format!("${idx}"))
+                    .cloned()
+                    .flatten()
+                    .unwrap_or(DataType::Null);
+                Field::new(idx.to_string(), data_type, true)
+            })
+            .collect::<Vec<_>>();
+        Ok(Schema::new(fields))
+    }
+
+    pub fn bind(&mut self, batch: RecordBatch) {
+        self.bound = Some(batch);
+    }
+
+    pub fn bind_batches(&mut self, schema: SchemaRef, batches: Vec<RecordBatch>) -> Result<()> {
+        self.bound = Some(concat_batches(&schema, &batches)?);
+        Ok(())
+    }
+
+    /// Execute the statement.
+    ///
+    /// Queries with bound parameters are executed once for each row of
+    /// parameters. Only a single row can be bound if `want_results` is
+    /// set.
+    pub fn execute(&mut self, want_results: bool) -> Result<StatementResult> {
+        if self.ingest_target.is_some() {
+            let rows = self.ingest()?;
+            return Ok(StatementResult {
+                stream: want_results.then(empty_stream),
+                rows_affected: Some(rows),
+            });
+        }
+
+        let query = self.query()?.to_string();
+        let param_rows = match self.bound.as_ref() {
+            Some(batch) => params_from_batch(batch)?,
+            None => vec![Vec::new()],
+        };
+        if want_results && param_rows.len() != 1 {
+            return Err(AdbcError::InvalidArgument(format!(
+                "Queries returning results must be bound exactly one row of parameters, got {}",
+                param_rows.len()
+            )));
+        }
+
+        block_on(async {
+            let plan = match &self.prepared {
+                Some(plan) => plan.clone(),
+                None => self.sess.plan(&query).await?,
+            };
+
+            let mut rows_affected = Some(0);
+            let mut stream = None;
+            for params in param_rows {
+                let mut plan = plan.clone();
+                if self.bound.is_some() {
+                    plan.bind_parameters(params)?;
+                }
+
+                let result = self.sess.execute(plan, &query).await?;
+                let rows = rows_affected_by(&result);
+                rows_affected = rows_affected.zip(rows).map(|(total, rows)| total + rows);
+
+                match result {
+                    ExecutionResult::Query { stream: s } if want_results => stream = Some(s),
+                    ExecutionResult::Query { stream: mut s } => {
+                        // Results aren't wanted, run the query to completion.
+                        while let Some(batch) = s.next().await {
+                            batch?;
+                        }
+                    }
+                    _ => (),
+                }
+            }
+
+            Ok::<_, AdbcError>(StatementResult {
+                stream: want_results.then(|| stream.unwrap_or_else(empty_stream)),
+                rows_affected,
+            })
+        })
+    }
+
+    /// Ingest the bound data into the target table.
+    fn ingest(&mut self) -> Result<usize> {
+        let target = quote_ident(self.ingest_target.as_deref().unwrap_or_default());
+        let batch = self
+            .bound
+            .take()
+            .ok_or_else(|| AdbcError::InvalidState("No data bound for ingestion".to_string()))?;
+        let rows = batch.num_rows();
+
+        let query = match self.ingest_mode {
+            IngestMode::Create => {
+                format!("CREATE TABLE {target} AS SELECT * FROM {INGEST_TABLE_NAME}")
+            }
+            IngestMode::Append => format!("INSERT INTO {target} SELECT * FROM {INGEST_TABLE_NAME}"),
+        };
+
+        let table = MemTable::try_new(batch.schema(), vec![vec![batch]])?;
+        let _guard = self.sess.ingest.expose(Arc::new(table));
+        block_on(async {
+            let plan = self.sess.plan(&query).await?;
+            self.sess.execute(plan, &query).await
+        })?;
+
+        Ok(rows)
+    }
+
+    fn query(&self) -> Result<&str> {
+        self.query
+            .as_deref()
+            .ok_or_else(|| AdbcError::InvalidState("No query set on statement".to_string()))
+    }
+}
+
+/// Split a batch into a set of parameters for each row.
+fn params_from_batch(batch: &RecordBatch) -> Result<Vec<Vec<ScalarValue>>> {
+    (0..batch.num_rows())
+        .map(|row| {
+            batch
+                .columns()
+                .iter()
+                .map(|col| ScalarValue::try_from_array(col, row).map_err(AdbcError::from))
+                .collect()
+        })
+        .collect()
+}
+
+fn rows_affected_by(result: &ExecutionResult) -> Option<usize> {
+    match result {
+        ExecutionResult::InsertSuccess { rows_inserted } => Some(*rows_inserted),
+        ExecutionResult::DeleteSuccess { deleted_rows } => Some(*deleted_rows),
+        ExecutionResult::UpdateSuccess { updated_rows } => Some(*updated_rows),
+        _ => None,
+    }
+}
+
+fn empty_stream() -> SendableRecordBatchStream {
+    // TODO: Figure out the schema we actually want to use.
+    Box::pin(EmptyRecordBatchStream::new(Arc::new(Schema::empty())))
+}
@@ -0,0 +1,65 @@
+use arrow::error::ArrowError;
+use arrow::ffi_stream::FFI_ArrowArrayStream;
+use arrow::record_batch::{RecordBatch, RecordBatchIterator, RecordBatchReader};
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use futures::StreamExt;
+
+use crate::runtime::block_on;
+
+/// Reads batches from a result stream, pulling each batch on the driver's
+/// runtime as the consumer asks for it.
+pub(crate) struct StreamingRecordBatchReader {
+    stream: SendableRecordBatchStream,
+}
+
+impl StreamingRecordBatchReader {
+    pub(crate) fn new(stream: SendableRecordBatchStream) -> Self {
+        StreamingRecordBatchReader { stream }
+    }
+}
+
+impl Iterator for StreamingRecordBatchReader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        block_on(self.stream.next()).map(|r| r.map_err(|e| ArrowError::ExternalError(Box::new(e))))
+    }
+}
+
+impl RecordBatchReader for StreamingRecordBatchReader {
+    fn schema(&self) -> SchemaRef {
+        self.stream.schema()
+    }
+}
+
+/// Export a result stream through the Arrow C stream interface.
+///
+/// # Safety
+///
+/// `out` must be valid for writes. Any existing stream it points to is not
+/// released.
+pub(crate) unsafe fn export_stream(
+    stream: SendableRecordBatchStream,
+    out: *mut FFI_ArrowArrayStream,
+) {
+    let reader = Box::new(StreamingRecordBatchReader::new(stream));
+    std::ptr::write(out, FFI_ArrowArrayStream::new(reader));
+}
+
+/// Export already computed batches through the Arrow C stream interface.
+///
+/// # Safety
+///
+/// See [`export_stream`].
+pub(crate) unsafe fn export_batches(
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+    out: *mut FFI_ArrowArrayStream,
+) {
+    let reader = Box::new(RecordBatchIterator::new(
+        batches.into_iter().map(Ok),
+        schema,
+    ));
+    std::ptr::write(out, FFI_ArrowArrayStream::new(reader));
+}
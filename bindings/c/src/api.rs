@@ -0,0 +1,285 @@
+//! The exported C API, declared in `include/glaredb.h`.
+//!
+//! Every function checks its pointers for null, writes errors to the
+//! caller's `out_error` (if provided), and catches panics before they reach
+//! the caller.
+#![allow(non_camel_case_types, clippy::missing_safety_doc)]
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use arrow::ffi_stream::FFI_ArrowArrayStream;
+
+use crate::database::{Connection, Database};
+use crate::error::{GlareDbError, Result};
+use crate::runtime::block_on;
+use crate::stream::export_stream;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum glaredb_state {
+    GLAREDB_SUCCESS = 0,
+    GLAREDB_ERROR = 1,
+}
+
+/// Opaque handle to a database, `glaredb_database` in the header.
+pub type DatabaseHandle = Arc<Database>;
+
+/// Opaque handle to a connection, `glaredb_connection` in the header.
+pub type ConnectionHandle = Connection;
+
+/// Run `f`, writing any error or panic to `out_error`.
+unsafe fn check(out_error: *mut *mut c_char, f: impl FnOnce() -> Result<()>) -> glaredb_state {
+    if let Some(out_error) = out_error.as_mut() {
+        *out_error = std::ptr::null_mut();
+    }
+    let err = match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return glaredb_state::GLAREDB_SUCCESS,
+        Ok(Err(e)) => e,
+        Err(panic) => {
+            let msg = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            GlareDbError::new(format!("GlareDB panicked: {msg}"))
+        }
+    };
+    err.write(out_error);
+    glaredb_state::GLAREDB_ERROR
+}
+
+unsafe fn non_null<'a, T>(ptr: *mut T, what: &str) -> Result<&'a mut T> {
+    ptr.as_mut()
+        .ok_or_else(|| GlareDbError::InvalidArgument(format!("{what} must not be null")))
+}
+
+unsafe fn opt_str<'a>(ptr: *const c_char, what: &str) -> Result<Option<&'a str>> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map(Some)
+        .map_err(|_| GlareDbError::InvalidArgument(format!("{what} must be valid UTF-8")))
+}
+
+unsafe fn str_arg<'a>(ptr: *const c_char, what: &str) -> Result<&'a str> {
+    opt_str(ptr, what)?
+        .ok_or_else(|| GlareDbError::InvalidArgument(format!("{what} must not be null")))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glaredb_open(
+    path: *const c_char,
+    out_database: *mut *mut DatabaseHandle,
+    out_error: *mut *mut c_char,
+) -> glaredb_state {
+    check(out_error, || {
+        let out = non_null(out_database, "out_database")?;
+        *out = std::ptr::null_mut();
+        let path = opt_str(path, "path")?;
+        let database = block_on(Database::open(path))?;
+        *out = Box::into_raw(Box::new(Arc::new(database)));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glaredb_close(database: *mut *mut DatabaseHandle) {
+    let _ = check(std::ptr::null_mut(), || {
+        let Some(database) = database.as_mut() else {
+            return Ok(());
+        };
+        if database.is_null() {
+            return Ok(());
+        }
+        let handle = Box::from_raw(*database);
+        *database = std::ptr::null_mut();
+        // Connections keep the database alive, only shut down the engine
+        // once the last of them is gone.
+        if let Some(database) = Arc::into_inner(*handle) {
+            block_on(database.close());
+        }
+        Ok(())
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glaredb_connect(
+    database: *mut DatabaseHandle,
+    out_connection: *mut *mut ConnectionHandle,
+    out_error: *mut *mut c_char,
+) -> glaredb_state {
+    check(out_error, || {
+        let out = non_null(out_connection, "out_connection")?;
+        *out = std::ptr::null_mut();
+        let database = non_null(database, "database")?.clone();
+        let conn = block_on(Connection::connect(database))?;
+        *out = Box::into_raw(Box::new(conn));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glaredb_disconnect(connection: *mut *mut ConnectionHandle) {
+    if let Some(connection) = connection.as_mut() {
+        if !connection.is_null() {
+            drop(Box::from_raw(*connection));
+            *connection = std::ptr::null_mut();
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glaredb_query(
+    connection: *mut ConnectionHandle,
+    query: *const c_char,
+    out_stream: *mut FFI_ArrowArrayStream,
+    out_error: *mut *mut c_char,
+) -> glaredb_state {
+    check(out_error, || {
+        let conn = non_null(connection, "connection")?;
+        let query = str_arg(query, "query")?;
+        if out_stream.is_null() {
+            return Err(GlareDbError::InvalidArgument(
+                "out_stream must not be null".to_string(),
+            ));
+        }
+        let result = block_on(conn.query(query))?;
+        export_stream(result.stream, out_stream);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glaredb_execute(
+    connection: *mut ConnectionHandle,
+    query: *const c_char,
+    out_rows_affected: *mut i64,
+    out_error: *mut *mut c_char,
+) -> glaredb_state {
+    check(out_error, || {
+        let conn = non_null(connection, "connection")?;
+        let query = str_arg(query, "query")?;
+        let rows = block_on(conn.execute(query))?;
+        if let Some(out) = out_rows_affected.as_mut() {
+            *out = rows.map(|rows| rows as i64).unwrap_or(-1);
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn glaredb_free_error(error: *mut c_char) {
+    if !error.is_null() {
+        drop(CString::from_raw(error));
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn glaredb_library_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use arrow::array::Int64Array;
+    use arrow::ffi_stream::ArrowArrayStreamReader;
+    use arrow::record_batch::RecordBatch;
+
+    use super::*;
+
+    unsafe fn open() -> (*mut DatabaseHandle, *mut ConnectionHandle) {
+        let mut db = ptr::null_mut();
+        let status = glaredb_open(ptr::null(), &mut db, ptr::null_mut());
+        assert_eq!(glaredb_state::GLAREDB_SUCCESS, status);
+
+        let mut conn = ptr::null_mut();
+        let status = glaredb_connect(db, &mut conn, ptr::null_mut());
+        assert_eq!(glaredb_state::GLAREDB_SUCCESS, status);
+
+        (db, conn)
+    }
+
+    unsafe fn query(conn: *mut ConnectionHandle, sql: &str) -> Vec<RecordBatch> {
+        let sql = CString::new(sql).unwrap();
+        let mut stream = FFI_ArrowArrayStream::empty();
+        let status = glaredb_query(conn, sql.as_ptr(), &mut stream, ptr::null_mut());
+        assert_eq!(glaredb_state::GLAREDB_SUCCESS, status);
+
+        let reader = ArrowArrayStreamReader::try_new(stream).unwrap();
+        reader.collect::<Result<Vec<_>, _>>().unwrap()
+    }
+
+    #[test]
+    fn query_and_execute() {
+        unsafe {
+            let (mut db, mut conn) = open();
+
+            let sql = CString::new("CREATE TABLE t (a BIGINT)").unwrap();
+            let status = glaredb_execute(conn, sql.as_ptr(), ptr::null_mut(), ptr::null_mut());
+            assert_eq!(glaredb_state::GLAREDB_SUCCESS, status);
+
+            let sql = CString::new("INSERT INTO t VALUES (1), (2), (3)").unwrap();
+            let mut rows: i64 = 0;
+            let status = glaredb_execute(conn, sql.as_ptr(), &mut rows, ptr::null_mut());
+            assert_eq!(glaredb_state::GLAREDB_SUCCESS, status);
+            assert_eq!(3, rows);
+
+            let batches = query(conn, "SELECT sum(a) FROM t");
+            let sum = batches[0]
+                .column(0)
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap()
+                .value(0);
+            assert_eq!(6, sum);
+
+            glaredb_disconnect(&mut conn);
+            assert!(conn.is_null());
+            glaredb_close(&mut db);
+            assert!(db.is_null());
+        }
+    }
+
+    #[test]
+    fn errors_are_written() {
+        unsafe {
+            let (mut db, mut conn) = open();
+
+            let sql = CString::new("SELECT * FROM missing_table").unwrap();
+            let mut stream = FFI_ArrowArrayStream::empty();
+            let mut error = ptr::null_mut();
+            let status = glaredb_query(conn, sql.as_ptr(), &mut stream, &mut error);
+            assert_eq!(glaredb_state::GLAREDB_ERROR, status);
+            assert!(!error.is_null());
+            let msg = CStr::from_ptr(error).to_str().unwrap();
+            assert!(msg.contains("missing_table"), "{msg}");
+            glaredb_free_error(error);
+
+            let status = glaredb_query(conn, ptr::null(), &mut stream, &mut error);
+            assert_eq!(glaredb_state::GLAREDB_ERROR, status);
+            glaredb_free_error(error);
+
+            glaredb_disconnect(&mut conn);
+            glaredb_close(&mut db);
+        }
+    }
+
+    #[test]
+    fn connections_outlive_database_handle() {
+        unsafe {
+            let (mut db, mut conn) = open();
+            glaredb_close(&mut db);
+
+            let batches = query(conn, "SELECT 1");
+            assert_eq!(1, batches[0].num_rows());
+
+            glaredb_disconnect(&mut conn);
+        }
+    }
+}
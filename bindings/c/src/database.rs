@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::Schema;
+use datafusion::physical_plan::{EmptyRecordBatchStream, SendableRecordBatchStream};
+use datafusion::variable::VarType;
+use datafusion_ext::vars::SessionVars;
+use futures::lock::Mutex;
+use futures::StreamExt;
+use ioutil::ensure_dir;
+use sqlexec::engine::{Engine, SessionStorageConfig, TrackedSession};
+use sqlexec::remote::client::{RemoteClient, RemoteClientType};
+use sqlexec::session::ExecutionResult;
+use sqlexec::OperationInfo;
+use url::Url;
+
+use crate::error::{GlareDbError, Result};
+
+/// A database opened with `glaredb_open`.
+///
+/// Holds the engine that all connections to the database share.
+pub struct Database {
+    engine: Engine,
+    cloud_url: Option<Url>,
+}
+
+impl Database {
+    /// Open a database.
+    ///
+    /// `path` is a local data directory, a GlareDB Cloud url, or `None` (or
+    /// `":memory:"`) for an in-memory database.
+    pub async fn open(path: Option<&str>) -> Result<Self> {
+        let (data_dir, cloud_url) = match path {
+            None | Some("") | Some(":memory:") => (None, None),
+            Some(path) => match Url::parse(path) {
+                Ok(url) if url.scheme() == "file" => {
+                    let path = url.to_file_path().map_err(|_| {
+                        GlareDbError::InvalidArgument(format!("Invalid file uri: {path}"))
+                    })?;
+                    (Some(path), None)
+                }
+                Ok(url) => (None, Some(url)),
+                // Assume failing to parse a url just means a local path was
+                // provided.
+                Err(_) => (Some(PathBuf::from(path)), None),
+            },
+        };
+
+        // If data dir is provided, then both table storage and metastore
+        // storage will reside at that path. Otherwise everything is in memory.
+        let engine = Engine::from_data_dir(data_dir.as_ref()).await?;
+
+        // If we don't have permission to write to the temp dir, then just
+        // don't use a spill path.
+        let spill_path = std::env::temp_dir().join("glaredb-c");
+        let spill_path = ensure_dir(&spill_path).ok().map(|_| spill_path);
+
+        Ok(Database {
+            engine: engine.with_spill_path(spill_path),
+            cloud_url,
+        })
+    }
+
+    /// Close the database, waiting for data to be flushed.
+    pub async fn close(&self) {
+        self.engine.shutdown().await;
+    }
+}
+
+/// A connection to a database, created with `glaredb_connect`.
+///
+/// Each connection has its own session. The session is locked for the
+/// duration of each call, so a connection can be shared between threads, but
+/// calls on it will run one at a time.
+pub struct Connection {
+    sess: Mutex<TrackedSession>,
+    /// Keep the database alive for as long as the connection is.
+    _database: Arc<Database>,
+}
+
+/// Result of executing a query.
+pub struct QueryResult {
+    pub stream: SendableRecordBatchStream,
+    /// Number of rows affected, if the query was a DML statement.
+    pub rows_affected: Option<usize>,
+}
+
+impl Connection {
+    pub async fn connect(database: Arc<Database>) -> Result<Self> {
+        let mut sess = database
+            .engine
+            .new_local_session_context(
+                SessionVars::default()
+                    .with_is_embedded(true, VarType::System)
+                    .with_is_admin(true, VarType::System),
+                SessionStorageConfig::default(),
+            )
+            .await?;
+
+        if let Some(url) = database.cloud_url.clone() {
+            let client = RemoteClient::connect_with_proxy_destination(
+                url.try_into()?,
+                String::from("https://console.glaredb.com"),
+                false,
+                RemoteClientType::C,
+            )
+            .await?;
+            sess.attach_remote_session(client, None).await?;
+        }
+
+        Ok(Connection {
+            sess: Mutex::new(sess),
+            _database: database,
+        })
+    }
+
+    /// Execute a query, returning a stream of results.
+    ///
+    /// Statements that don't return rows produce an empty stream.
+    pub async fn query(&self, query: &str) -> Result<QueryResult> {
+        let mut sess = self.sess.lock().await;
+        let plan = sess.create_logical_plan(query).await?;
+        let op = OperationInfo::new().with_query_text(query.to_string());
+
+        let (stream, rows_affected) = match sess.execute_logical_plan(plan, &op).await? {
+            (_, ExecutionResult::Query { stream }) => (stream, None),
+            (_, ExecutionResult::Error(e)) => return Err(e.into()),
+            (_, ExecutionResult::InsertSuccess { rows_inserted }) => {
+                (empty_stream(), Some(rows_inserted))
+            }
+            (_, ExecutionResult::DeleteSuccess { deleted_rows }) => {
+                (empty_stream(), Some(deleted_rows))
+            }
+            (_, ExecutionResult::UpdateSuccess { updated_rows }) => {
+                (empty_stream(), Some(updated_rows))
+            }
+            (_, _) => (empty_stream(), None),
+        };
+
+        Ok(QueryResult {
+            stream,
+            rows_affected,
+        })
+    }
+
+    /// Execute a query to completion, discarding any results.
+    pub async fn execute(&self, query: &str) -> Result<Option<usize>> {
+        let mut result = self.query(query).await?;
+        while let Some(batch) = result.stream.next().await {
+            batch?;
+        }
+        Ok(result.rows_affected)
+    }
+}
+
+fn empty_stream() -> SendableRecordBatchStream {
+    Box::pin(EmptyRecordBatchStream::new(Arc::new(Schema::empty())))
+}
@@ -0,0 +1,45 @@
+use std::ffi::{c_char, CString};
+use std::fmt::Display;
+
+use datafusion::arrow::error::ArrowError;
+use metastore::errors::MetastoreError;
+use sqlexec::errors::ExecError;
+
+pub type Result<T, E = GlareDbError> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum GlareDbError {
+    #[error(transparent)]
+    Arrow(#[from] ArrowError),
+    #[error(transparent)]
+    Metastore(#[from] MetastoreError),
+    #[error(transparent)]
+    Exec(#[from] ExecError),
+    #[error(transparent)]
+    DataFusion(#[from] datafusion::error::DataFusionError),
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl GlareDbError {
+    pub fn new(msg: impl Display) -> Self {
+        Self::Other(msg.to_string())
+    }
+
+    /// Write this error's message to the caller provided location.
+    ///
+    /// The message must be freed with `glaredb_free_error`.
+    ///
+    /// # Safety
+    ///
+    /// `out` must be null or valid for writes.
+    pub unsafe fn write(&self, out: *mut *mut c_char) {
+        if let Some(out) = out.as_mut() {
+            // Messages can't contain interior nul bytes.
+            let msg = self.to_string().replace('\0', "");
+            *out = CString::new(msg).unwrap_or_default().into_raw();
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! C bindings for embedding GlareDB.
+//!
+//! Builds a shared (and static) library exporting the small C API declared
+//! in `include/glaredb.h`, for languages without first-class bindings (R,
+//! Julia, C#, ...). Results are returned through the Arrow C stream
+//! interface.
+//!
+//! ```c
+//! glaredb_database db;
+//! glaredb_connection conn;
+//! struct ArrowArrayStream stream;
+//! char *err = NULL;
+//!
+//! glaredb_open("/path/to/data", &db, &err);
+//! glaredb_connect(db, &conn, &err);
+//! if (glaredb_query(conn, "SELECT 1", &stream, &err) != GLAREDB_SUCCESS) {
+//!     fprintf(stderr, "%s\n", err);
+//!     glaredb_free_error(err);
+//! }
+//! ```
+pub mod api;
+pub mod database;
+pub mod error;
+
+mod runtime;
+mod stream;
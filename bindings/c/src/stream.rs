@@ -0,0 +1,42 @@
+use arrow::error::ArrowError;
+use arrow::ffi_stream::FFI_ArrowArrayStream;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use futures::StreamExt;
+
+use crate::runtime::block_on;
+
+/// Reads batches from a result stream, pulling each batch on the shared
+/// runtime as the consumer asks for it.
+pub(crate) struct StreamingRecordBatchReader {
+    stream: SendableRecordBatchStream,
+}
+
+impl Iterator for StreamingRecordBatchReader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        block_on(self.stream.next()).map(|r| r.map_err(|e| ArrowError::ExternalError(Box::new(e))))
+    }
+}
+
+impl RecordBatchReader for StreamingRecordBatchReader {
+    fn schema(&self) -> SchemaRef {
+        self.stream.schema()
+    }
+}
+
+/// Export a result stream through the Arrow C stream interface.
+///
+/// # Safety
+///
+/// `out` must be valid for writes. Any existing stream it points to is not
+/// released.
+pub(crate) unsafe fn export_stream(
+    stream: SendableRecordBatchStream,
+    out: *mut FFI_ArrowArrayStream,
+) {
+    let reader = Box::new(StreamingRecordBatchReader { stream });
+    std::ptr::write(out, FFI_ArrowArrayStream::new(reader));
+}
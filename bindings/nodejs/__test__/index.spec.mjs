@@ -7,3 +7,33 @@ test('able to connect', async (t) => {
     await glaredb.connect()
   })
 })
+
+test('binds query parameters', async (t) => {
+  const con = await glaredb.connect()
+  const rows = await con.sql('select $1 as a, $2 as b', [1, 'hello']).then((plan) => plan.toArray())
+  t.deepEqual(rows, [{ a: 1n, b: 'hello' }])
+})
+
+test('converts values to native types', async (t) => {
+  const con = await glaredb.connect()
+  const rows = await con
+    .sql("select 1::int as i, 2::bigint as b, true as t, null as n, '2023-01-02T03:04:05Z'::timestamp as ts")
+    .then((plan) => plan.toArray())
+  t.is(rows.length, 1)
+  t.is(rows[0].i, 1)
+  t.is(rows[0].b, 2n)
+  t.is(rows[0].t, true)
+  t.is(rows[0].n, null)
+  t.deepEqual(rows[0].ts, new Date('2023-01-02T03:04:05Z'))
+})
+
+test('streams result rows', async (t) => {
+  const con = await glaredb.connect()
+  const plan = await con.sql('select * from generate_series(1, 10) as s(n)')
+  let count = 0
+  for await (const row of plan.rows()) {
+    count += 1
+    t.is(typeof row.n, 'bigint')
+  }
+  t.is(count, 10)
+})
@@ -21,6 +21,30 @@ Object.assign(glaredb.JsLogicalPlan.prototype, {
     } catch (e) {
       throw new Error("apache-arrow is not installed, please run `npm install apache-arrow`")
     }
+  },
+  // Iterate over result batches as "apache-arrow" Tables, pulling batches
+  // from the query as they're consumed.
+  async *[Symbol.asyncIterator]() {
+    let arrow
+    try {
+      arrow = require("apache-arrow")
+    } catch (e) {
+      throw new Error("apache-arrow is not installed, please run `npm install apache-arrow`")
+    }
+    const stream = await this.stream()
+    let buf
+    while ((buf = await stream.nextIpc()) !== null) {
+      yield arrow.tableFromIPC(buf)
+    }
+  },
+  // Iterate over result rows, with values converted to native types. Rows
+  // are pulled from the query a batch at a time.
+  async *rows() {
+    const stream = await this.stream()
+    let rows
+    while ((rows = await stream.nextRows()) !== null) {
+      yield* rows
+    }
   }
 });
 
@@ -26,8 +26,9 @@ export class Connection {
    *
    * All operations that write or modify data are executed
    * directly, but all query operations run lazily when you process
-   * their results with `show`, `toArrow`, or
-   * `toPolars`, or call the `execute` method.
+   * their results with `show`, `toArray`, `toArrow`, or
+   * `toPolars`, iterate over their batches with `for await`, or call
+   * the `execute` method.
    *
    * # Examples
    *
@@ -59,8 +60,17 @@ export class Connection {
    * con = glaredb.connect()
    * await con.sql('create table my_table (a int)').then(cursor => cursor.execute())
    * ```
+   *
+   * Bind values to `$1`, `$2`, ... placeholders in the query.
+   *
+   * ```javascript
+   * import glaredb from "@glaredb/glaredb"
+   *
+   * con = glaredb.connect()
+   * let rows = await con.sql('select * from my_table where a > $1', [10]).then(cursor => cursor.toArray())
+   * ```
    */
-  sql(query: string): Promise<JsLogicalPlan>
+  sql(query: string, params?: Array<any>): Promise<JsLogicalPlan>
   /**
    * Run a PRQL query against a GlareDB database. Does not change
    * the state or dialect of the connection object.
@@ -90,16 +100,52 @@ export class Connection {
    * con = glaredb.connect()
    * con.execute('create table my_table (a int)')
    * ```
+   *
+   * Inserting a row, binding values to `$1`, `$2`, ... placeholders.
+   *
+   * ```js
+   * import glaredb from "@glaredb/glaredb"
+   *
+   * con = glaredb.connect()
+   * con.execute('insert into my_table values ($1)', [1])
+   * ```
    */
-  execute(query: string): Promise<void>
+  execute(query: string, params?: Array<any>): Promise<void>
   /** Close the current session. */
   close(): Promise<void>
 }
+/** A stream of result batches, pulled from the query as they're requested. */
+export class JsRecordBatchStream {
+  /**
+   * Get the next batch encoded as an Arrow IPC stream, or `null` if
+   * there are no more batches.
+   */
+  nextIpc(): Promise<Buffer | null>
+  /**
+   * Get the rows of the next batch, or `null` if there are no more
+   * batches.
+   */
+  nextRows(): Promise<Array<Record<string, any>> | null>
+}
 export class JsLogicalPlan {
   toString(): string
   show(): Promise<void>
   execute(): Promise<void>
   toIpc(): Promise<Buffer>
+  /**
+   * Collect all rows, converting values to native javascript types.
+   *
+   * 64-bit integers are returned as `BigInt`, and dates and timestamps as
+   * `Date`.
+   */
+  toArray(): Promise<Array<Record<string, any>>>
+  /**
+   * Execute the query, returning a stream of result batches.
+   *
+   * Prefer iterating over the plan directly with `for await`, which
+   * yields "apache-arrow" Tables.
+   */
+  stream(): Promise<JsRecordBatchStream>
   /**
    * Convert to a Polars DataFrame.
    * "nodejs-polars" must be installed as a peer dependency.
@@ -112,4 +158,8 @@ export class JsLogicalPlan {
    * See https://www.npmjs.com/package/apache-arrow
    */
   toArrow(): arrow.Table<any>
+  /** Iterate over result batches as "apache-arrow" Tables. */
+  [Symbol.asyncIterator](): AsyncIterator<arrow.Table<any>>
+  /** Iterate over result rows, with values converted to native types. */
+  rows(): AsyncIterableIterator<Record<string, any>>
 }
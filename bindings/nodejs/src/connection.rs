@@ -1,6 +1,8 @@
 use crate::error::JsGlareDbError;
 use crate::logical_plan::JsLogicalPlan;
+use crate::params::{self, JsParam};
 use datafusion::logical_expr::LogicalPlan as DFLogicalPlan;
+use datafusion::variable::VarType;
 use datafusion_ext::vars::SessionVars;
 use futures::lock::Mutex;
 use ioutil::ensure_dir;
@@ -104,7 +106,12 @@ impl Connection {
             .map_err(JsGlareDbError::from)?;
 
             let mut sess = engine
-                .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+                .new_local_session_context(
+                    SessionVars::default()
+                        .with_is_embedded(true, VarType::System)
+                        .with_is_admin(true, VarType::System),
+                    SessionStorageConfig::default(),
+                )
                 .await
                 .map_err(JsGlareDbError::from)?;
             sess.attach_remote_session(exec_client.clone(), None)
@@ -114,7 +121,12 @@ impl Connection {
             sess
         } else {
             engine
-                .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+                .new_local_session_context(
+                    SessionVars::default()
+                        .with_is_embedded(true, VarType::System)
+                        .with_is_admin(true, VarType::System),
+                    SessionStorageConfig::default(),
+                )
                 .await
                 .map_err(JsGlareDbError::from)?
         };
@@ -136,7 +148,12 @@ impl Connection {
             .await
             .map_err(JsGlareDbError::from)?;
         let sess = engine
-            .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+            .new_local_session_context(
+                SessionVars::default()
+                    .with_is_embedded(true, VarType::System)
+                    .with_is_admin(true, VarType::System),
+                SessionStorageConfig::default(),
+            )
             .await
             .map_err(JsGlareDbError::from)?;
         let con = Connection {
@@ -151,8 +168,9 @@ impl Connection {
     ///
     /// All operations that write or modify data are executed
     /// directly, but all query operations run lazily when you process
-    /// their results with `show`, `toArrow`, or
-    /// `toPolars`, or call the `execute` method.
+    /// their results with `show`, `toArray`, `toArrow`, or
+    /// `toPolars`, iterate over their batches with `for await`, or call
+    /// the `execute` method.
     ///
     /// # Examples
     ///
@@ -184,15 +202,32 @@ impl Connection {
     /// con = glaredb.connect()
     /// await con.sql('create table my_table (a int)').then(cursor => cursor.execute())
     /// ```
-    #[napi(catch_unwind)]
-    pub async fn sql(&self, query: String) -> napi::Result<JsLogicalPlan> {
+    ///
+    /// Bind values to `$1`, `$2`, ... placeholders in the query.
+    ///
+    /// ```javascript
+    /// import glaredb from "@glaredb/glaredb"
+    ///
+    /// con = glaredb.connect()
+    /// let rows = await con.sql('select * from my_table where a > $1', [10]).then(cursor => cursor.toArray())
+    /// ```
+    #[napi(catch_unwind, ts_args_type = "query: string, params?: Array<any>")]
+    pub async fn sql(
+        &self,
+        query: String,
+        params: Option<Vec<JsParam>>,
+    ) -> napi::Result<JsLogicalPlan> {
         let cloned_sess = self.sess.clone();
         let mut sess = self.sess.lock().await;
 
-        let plan = sess
+        let mut plan = sess
             .create_logical_plan(&query)
             .await
             .map_err(JsGlareDbError::from)?;
+        if let Some(params) = params {
+            plan.bind_parameters(params::scalars(params))
+                .map_err(JsGlareDbError::from)?;
+        }
 
         let op = OperationInfo::new().with_query_text(query);
 
@@ -258,15 +293,28 @@ impl Connection {
     /// con = glaredb.connect()
     /// con.execute('create table my_table (a int)')
     /// ```
-    #[napi(catch_unwind)]
-    pub async fn execute(&self, query: String) -> napi::Result<()> {
+    ///
+    /// Inserting a row, binding values to `$1`, `$2`, ... placeholders.
+    ///
+    /// ```js
+    /// import glaredb from "@glaredb/glaredb"
+    ///
+    /// con = glaredb.connect()
+    /// con.execute('insert into my_table values ($1)', [1])
+    /// ```
+    #[napi(catch_unwind, ts_args_type = "query: string, params?: Array<any>")]
+    pub async fn execute(&self, query: String, params: Option<Vec<JsParam>>) -> napi::Result<()> {
         let sess = self.sess.clone();
         let mut sess = sess.lock().await;
 
-        let plan = sess
+        let mut plan = sess
             .create_logical_plan(&query)
             .await
             .map_err(JsGlareDbError::from)?;
+        if let Some(params) = params {
+            plan.bind_parameters(params::scalars(params))
+                .map_err(JsGlareDbError::from)?;
+        }
 
         let op = OperationInfo::new().with_query_text(query);
 
@@ -1,12 +1,18 @@
+use std::sync::Arc;
+
 use arrow_util::pretty;
 
-use datafusion::arrow::ipc::writer::FileWriter;
+use datafusion::arrow::datatypes::Schema;
+use datafusion::arrow::ipc::writer::{FileWriter, StreamWriter};
 use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::physical_plan::{EmptyRecordBatchStream, SendableRecordBatchStream};
+use futures::lock::Mutex;
 use futures::StreamExt;
 
 use sqlexec::session::ExecutionResult;
 
 use crate::error::JsGlareDbError;
+use crate::values::JsRows;
 
 pub(crate) struct JsExecutionResult(pub(crate) ExecutionResult);
 
@@ -46,10 +52,89 @@ impl JsExecutionResult {
         Ok(res)
     }
 
+    pub(crate) async fn to_rows(&mut self) -> napi::Result<JsRows> {
+        let batches = match &mut self.0 {
+            ExecutionResult::Query { stream, .. } => stream
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<Vec<RecordBatch>, _>>()
+                .map_err(JsGlareDbError::from)?,
+            _ => Vec::new(),
+        };
+        Ok(JsRows(batches))
+    }
+
     pub(crate) async fn show(&mut self) -> napi::Result<()> {
         print_batch(&mut self.0).await?;
         Ok(())
     }
+
+    /// Get the result stream, erroring if the execution errored.
+    pub(crate) fn into_stream(self) -> napi::Result<SendableRecordBatchStream> {
+        match self.0 {
+            ExecutionResult::Query { stream } => Ok(stream),
+            ExecutionResult::Error(e) => Err(JsGlareDbError::from(e).into()),
+            // TODO: Figure out the schema we actually want to use.
+            _ => Ok(Box::pin(EmptyRecordBatchStream::new(Arc::new(
+                Schema::empty(),
+            )))),
+        }
+    }
+}
+
+/// A stream of result batches, pulled from the query as they're requested.
+#[napi]
+pub struct JsRecordBatchStream {
+    stream: Mutex<SendableRecordBatchStream>,
+}
+
+impl JsRecordBatchStream {
+    pub(crate) fn new(stream: SendableRecordBatchStream) -> Self {
+        JsRecordBatchStream {
+            stream: Mutex::new(stream),
+        }
+    }
+
+    async fn next_batch(&self) -> napi::Result<Option<RecordBatch>> {
+        let mut stream = self.stream.lock().await;
+        match stream.next().await {
+            Some(batch) => Ok(Some(batch.map_err(JsGlareDbError::from)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[napi]
+impl JsRecordBatchStream {
+    /// Get the next batch encoded as an Arrow IPC stream, or `null` if
+    /// there are no more batches.
+    #[napi(catch_unwind)]
+    pub async fn next_ipc(&self) -> napi::Result<Option<napi::bindgen_prelude::Buffer>> {
+        let batch = match self.next_batch().await? {
+            Some(batch) => batch,
+            None => return Ok(None),
+        };
+
+        let mut buf = Vec::new();
+        let mut writer = StreamWriter::try_new(&mut buf, batch.schema().as_ref())
+            .map_err(JsGlareDbError::from)?;
+        writer.write(&batch).map_err(JsGlareDbError::from)?;
+        writer.finish().map_err(JsGlareDbError::from)?;
+        drop(writer);
+
+        Ok(Some(buf.into()))
+    }
+
+    /// Get the rows of the next batch, or `null` if there are no more
+    /// batches.
+    #[napi(
+        catch_unwind,
+        ts_return_type = "Promise<Array<Record<string, any>> | null>"
+    )]
+    pub async fn next_rows(&self) -> napi::Result<Option<JsRows>> {
+        Ok(self.next_batch().await?.map(|batch| JsRows(vec![batch])))
+    }
 }
 
 async fn print_batch(result: &mut ExecutionResult) -> napi::Result<()> {
@@ -4,5 +4,7 @@ pub mod connection;
 pub mod error;
 pub mod execution_result;
 pub mod logical_plan;
+pub mod params;
+pub mod values;
 #[macro_use]
 extern crate napi_derive;
@@ -1,7 +1,10 @@
 use sqlexec::{LogicalPlan, OperationInfo};
 
 use crate::{
-    connection::JsTrackedSession, error::JsGlareDbError, execution_result::JsExecutionResult,
+    connection::JsTrackedSession,
+    error::JsGlareDbError,
+    execution_result::{JsExecutionResult, JsRecordBatchStream},
+    values::JsRows,
 };
 
 #[napi]
@@ -53,6 +56,25 @@ impl JsLogicalPlan {
         Ok(inner.into())
     }
 
+    /// Collect all rows, converting values to native javascript types.
+    ///
+    /// 64-bit integers are returned as `BigInt`, and dates and timestamps as
+    /// `Date`.
+    #[napi(catch_unwind, ts_return_type = "Promise<Array<Record<string, any>>>")]
+    pub async fn to_array(&self) -> napi::Result<JsRows> {
+        self.execute_inner().await?.to_rows().await
+    }
+
+    /// Execute the query, returning a stream of result batches.
+    ///
+    /// Prefer iterating over the plan directly with `for await`, which
+    /// yields "apache-arrow" Tables.
+    #[napi(catch_unwind)]
+    pub async fn stream(&self) -> napi::Result<JsRecordBatchStream> {
+        let stream = self.execute_inner().await?.into_stream()?;
+        Ok(JsRecordBatchStream::new(stream))
+    }
+
     #[napi(ts_return_type = "pl.DataFrame")]
     /// Convert to a Polars DataFrame.
     /// "nodejs-polars" must be installed as a peer dependency.
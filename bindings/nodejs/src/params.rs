@@ -0,0 +1,70 @@
+//! Binding javascript values to query parameters.
+use datafusion::scalar::ScalarValue;
+use napi::bindgen_prelude::{BigInt, Buffer, FromNapiValue, TypeName};
+use napi::{sys, JsDate, JsUnknown, NapiValue, ValueType};
+
+/// A value bound to a `$1`, `$2`, ... placeholder.
+///
+/// Numbers without a fractional part are bound as integers, bigints must fit
+/// in an int64, and dates are bound as millisecond timestamps. Values are
+/// cast to the placeholder's type if it could be inferred from the query.
+pub struct JsParam(pub ScalarValue);
+
+/// Largest integer a javascript number can represent exactly.
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+impl TypeName for JsParam {
+    fn type_name() -> &'static str {
+        "Param"
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Unknown
+    }
+}
+
+impl FromNapiValue for JsParam {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> napi::Result<Self> {
+        let value = JsUnknown::from_raw(env, napi_val)?;
+        let scalar = match value.get_type()? {
+            ValueType::Null | ValueType::Undefined => ScalarValue::Null,
+            ValueType::Boolean => ScalarValue::Boolean(Some(bool::from_napi_value(env, napi_val)?)),
+            ValueType::Number => {
+                let n = f64::from_napi_value(env, napi_val)?;
+                if n.fract() == 0.0 && n.abs() <= MAX_SAFE_INTEGER {
+                    ScalarValue::Int64(Some(n as i64))
+                } else {
+                    ScalarValue::Float64(Some(n))
+                }
+            }
+            ValueType::BigInt => {
+                let (n, lossless) = BigInt::from_napi_value(env, napi_val)?.get_i64();
+                if !lossless {
+                    return Err(napi::Error::from_reason(
+                        "BigInt parameter does not fit in a 64-bit integer",
+                    ));
+                }
+                ScalarValue::Int64(Some(n))
+            }
+            ValueType::String => ScalarValue::Utf8(Some(String::from_napi_value(env, napi_val)?)),
+            ValueType::Object if value.is_date()? => {
+                let ms = JsDate::from_raw(env, napi_val)?.value_of()?;
+                ScalarValue::TimestampMillisecond(Some(ms as i64), None)
+            }
+            ValueType::Object if value.is_buffer()? => {
+                ScalarValue::Binary(Some(Buffer::from_napi_value(env, napi_val)?.to_vec()))
+            }
+            other => {
+                return Err(napi::Error::from_reason(format!(
+                    "Unsupported parameter type: {other}"
+                )))
+            }
+        };
+        Ok(JsParam(scalar))
+    }
+}
+
+/// Convert parameters to scalars.
+pub fn scalars(params: Vec<JsParam>) -> Vec<ScalarValue> {
+    params.into_iter().map(|p| p.0).collect()
+}
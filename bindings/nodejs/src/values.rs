@@ -0,0 +1,171 @@
+//! Converting arrow values to javascript values.
+use datafusion::arrow::array::{
+    as_boolean_array, as_generic_binary_array, as_largestring_array, as_list_array,
+    as_primitive_array, as_string_array, as_struct_array, Array, ArrayRef,
+};
+use datafusion::arrow::datatypes::{
+    DataType, Date32Type, Date64Type, Float16Type, Float32Type, Float64Type, Int16Type, Int32Type,
+    Int64Type, Int8Type, TimeUnit, TimestampMicrosecondType, TimestampMillisecondType,
+    TimestampNanosecondType, TimestampSecondType, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::util::display::{ArrayFormatter, FormatOptions};
+use napi::bindgen_prelude::{ToNapiValue, TypeName};
+use napi::{sys, Env, JsUnknown, NapiRaw, ValueType};
+
+const MS_PER_DAY: f64 = 86_400_000.0;
+
+/// Rows of a query result, converted to an array of objects keyed by column
+/// name.
+///
+/// Values are mapped to native javascript types:
+/// - 64-bit integers to `BigInt`
+/// - dates and timestamps to `Date`, truncated to millisecond precision
+/// - binary to `Buffer`
+/// - lists to arrays, and structs to objects
+///
+/// Any other type (decimals, intervals, etc.) is converted to its string
+/// representation.
+pub struct JsRows(pub Vec<RecordBatch>);
+
+impl TypeName for JsRows {
+    fn type_name() -> &'static str {
+        "Array<Record<string, any>>"
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Object
+    }
+}
+
+impl ToNapiValue for JsRows {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+        let env = Env::from_raw(env);
+        let num_rows = val.0.iter().map(|b| b.num_rows()).sum::<usize>();
+        let mut rows = env.create_array_with_length(num_rows)?;
+
+        let mut idx: u32 = 0;
+        for batch in &val.0 {
+            let schema = batch.schema();
+            for row in 0..batch.num_rows() {
+                let mut obj = env.create_object()?;
+                for (field, col) in schema.fields().iter().zip(batch.columns()) {
+                    obj.set_named_property(field.name(), array_value(&env, col, row)?)?;
+                }
+                rows.set_element(idx, obj)?;
+                idx += 1;
+            }
+        }
+
+        Ok(rows.raw())
+    }
+}
+
+/// Convert a single value of an array to a javascript value.
+fn array_value(env: &Env, array: &ArrayRef, row: usize) -> napi::Result<JsUnknown> {
+    if array.is_null(row) {
+        return Ok(env.get_null()?.into_unknown());
+    }
+
+    Ok(match array.data_type() {
+        DataType::Boolean => env
+            .get_boolean(as_boolean_array(array).value(row))?
+            .into_unknown(),
+        DataType::Int8 => env
+            .create_int32(as_primitive_array::<Int8Type>(array).value(row) as i32)?
+            .into_unknown(),
+        DataType::Int16 => env
+            .create_int32(as_primitive_array::<Int16Type>(array).value(row) as i32)?
+            .into_unknown(),
+        DataType::Int32 => env
+            .create_int32(as_primitive_array::<Int32Type>(array).value(row))?
+            .into_unknown(),
+        DataType::Int64 => env
+            .create_bigint_from_i64(as_primitive_array::<Int64Type>(array).value(row))?
+            .into_unknown()?,
+        DataType::UInt8 => env
+            .create_uint32(as_primitive_array::<UInt8Type>(array).value(row) as u32)?
+            .into_unknown(),
+        DataType::UInt16 => env
+            .create_uint32(as_primitive_array::<UInt16Type>(array).value(row) as u32)?
+            .into_unknown(),
+        DataType::UInt32 => env
+            .create_uint32(as_primitive_array::<UInt32Type>(array).value(row))?
+            .into_unknown(),
+        DataType::UInt64 => env
+            .create_bigint_from_u64(as_primitive_array::<UInt64Type>(array).value(row))?
+            .into_unknown()?,
+        DataType::Float16 => env
+            .create_double(as_primitive_array::<Float16Type>(array).value(row).to_f64())?
+            .into_unknown(),
+        DataType::Float32 => env
+            .create_double(as_primitive_array::<Float32Type>(array).value(row) as f64)?
+            .into_unknown(),
+        DataType::Float64 => env
+            .create_double(as_primitive_array::<Float64Type>(array).value(row))?
+            .into_unknown(),
+        DataType::Utf8 => env
+            .create_string(as_string_array(array).value(row))?
+            .into_unknown(),
+        DataType::LargeUtf8 => env
+            .create_string(as_largestring_array(array).value(row))?
+            .into_unknown(),
+        DataType::Binary => env
+            .create_buffer_with_data(as_generic_binary_array::<i32>(array).value(row).to_vec())?
+            .into_raw()
+            .into_unknown(),
+        DataType::LargeBinary => env
+            .create_buffer_with_data(as_generic_binary_array::<i64>(array).value(row).to_vec())?
+            .into_raw()
+            .into_unknown(),
+        DataType::Date32 => {
+            let days = as_primitive_array::<Date32Type>(array).value(row);
+            env.create_date(days as f64 * MS_PER_DAY)?.into_unknown()
+        }
+        DataType::Date64 => {
+            let ms = as_primitive_array::<Date64Type>(array).value(row);
+            env.create_date(ms as f64)?.into_unknown()
+        }
+        DataType::Timestamp(unit, _) => {
+            let ms = match unit {
+                TimeUnit::Second => {
+                    as_primitive_array::<TimestampSecondType>(array).value(row) as f64 * 1000.0
+                }
+                TimeUnit::Millisecond => {
+                    as_primitive_array::<TimestampMillisecondType>(array).value(row) as f64
+                }
+                TimeUnit::Microsecond => {
+                    (as_primitive_array::<TimestampMicrosecondType>(array).value(row) / 1_000)
+                        as f64
+                }
+                TimeUnit::Nanosecond => {
+                    (as_primitive_array::<TimestampNanosecondType>(array).value(row) / 1_000_000)
+                        as f64
+                }
+            };
+            env.create_date(ms)?.into_unknown()
+        }
+        DataType::List(_) => {
+            let values = as_list_array(array).value(row);
+            let mut list = env.create_array_with_length(values.len())?;
+            for i in 0..values.len() {
+                list.set_element(i as u32, array_value(env, &values, i)?)?;
+            }
+            list.into_unknown()
+        }
+        DataType::Struct(fields) => {
+            let array = as_struct_array(array);
+            let mut obj = env.create_object()?;
+            for (field, col) in fields.iter().zip(array.columns()) {
+                obj.set_named_property(field.name(), array_value(env, col, row)?)?;
+            }
+            obj.into_unknown()
+        }
+        _ => {
+            let formatter = ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default())
+                .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+            env.create_string(&formatter.value(row).to_string())?
+                .into_unknown()
+        }
+    })
+}
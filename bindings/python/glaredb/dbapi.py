@@ -0,0 +1,352 @@
+# pylint: disable-all
+"""PEP 249 (DB-API 2.0) interface for GlareDB.
+
+Queries run in-process against a connection created with `glaredb.connect`,
+letting tools that expect a DB-API driver (pandas `read_sql`, SQLAlchemy,
+etc.) talk to GlareDB directly.
+
+```python
+from glaredb import dbapi
+
+con = dbapi.connect()
+cur = con.cursor()
+cur.execute("select * from generate_series(1, ?) as s(n)", (10,))
+print(cur.fetchmany(5))
+```
+
+Results are streamed, batches are only pulled from the query as rows are
+fetched.
+"""
+
+import collections
+import datetime
+import decimal
+
+import pyarrow.types as patypes
+
+from .glaredb import connect as _connect
+
+apilevel = "2.0"
+# Threads may share the module, but not connections.
+threadsafety = 1
+paramstyle = "qmark"
+
+
+class Warning(Exception):
+    pass
+
+
+class Error(Exception):
+    pass
+
+
+class InterfaceError(Error):
+    pass
+
+
+class DatabaseError(Error):
+    pass
+
+
+class DataError(DatabaseError):
+    pass
+
+
+class OperationalError(DatabaseError):
+    pass
+
+
+class IntegrityError(DatabaseError):
+    pass
+
+
+class InternalError(DatabaseError):
+    pass
+
+
+class ProgrammingError(DatabaseError):
+    pass
+
+
+class NotSupportedError(DatabaseError):
+    pass
+
+
+class _DBAPITypeObject:
+    """Compares equal to the `type_code` of matching result columns."""
+
+    def __init__(self, *predicates):
+        self.predicates = predicates
+
+    def __eq__(self, other):
+        return any(pred(other) for pred in self.predicates)
+
+    def __hash__(self):
+        return hash(self.predicates)
+
+
+STRING = _DBAPITypeObject(patypes.is_string, patypes.is_large_string)
+BINARY = _DBAPITypeObject(patypes.is_binary, patypes.is_large_binary)
+NUMBER = _DBAPITypeObject(patypes.is_integer, patypes.is_floating, patypes.is_decimal)
+DATETIME = _DBAPITypeObject(patypes.is_temporal)
+ROWID = _DBAPITypeObject()
+
+Date = datetime.date
+Time = datetime.time
+Timestamp = datetime.datetime
+Binary = bytes
+
+
+def DateFromTicks(ticks):
+    return Date.fromtimestamp(ticks)
+
+
+def TimeFromTicks(ticks):
+    return Timestamp.fromtimestamp(ticks).time()
+
+
+def TimestampFromTicks(ticks):
+    return Timestamp.fromtimestamp(ticks)
+
+
+def connect(data_dir_or_cloud_url=None, **kwargs):
+    """Connect to a GlareDB database.
+
+    Accepts the same arguments as `glaredb.connect`.
+    """
+    try:
+        return Connection(_connect(data_dir_or_cloud_url, **kwargs))
+    except Exception as e:
+        raise OperationalError(str(e)) from e
+
+
+def _convert_placeholders(operation):
+    """Rewrite `?` placeholders to the `$1`, `$2`, ... form GlareDB expects.
+
+    Placeholders inside string literals, quoted identifiers, and comments
+    are left alone.
+    """
+    out = []
+    count = 0
+    i = 0
+    n = len(operation)
+    while i < n:
+        c = operation[i]
+        if c in ("'", '"'):
+            end = i + 1
+            while end < n:
+                if operation[end] == c:
+                    # Doubled quotes are escapes.
+                    if end + 1 < n and operation[end + 1] == c:
+                        end += 2
+                        continue
+                    break
+                end += 1
+            out.append(operation[i : end + 1])
+            i = end + 1
+        elif operation.startswith("--", i):
+            end = operation.find("\n", i)
+            end = n if end == -1 else end
+            out.append(operation[i:end])
+            i = end
+        elif operation.startswith("/*", i):
+            end = operation.find("*/", i + 2)
+            end = n if end == -1 else end + 2
+            out.append(operation[i:end])
+            i = end
+        elif c == "?":
+            count += 1
+            out.append(f"${count}")
+            i += 1
+        else:
+            out.append(c)
+            i += 1
+    return "".join(out), count
+
+
+def _adapt_param(value):
+    """Convert values the bindings don't accept directly into strings, which
+    are cast to the parameter's type."""
+    if isinstance(value, (datetime.datetime, datetime.date, datetime.time)):
+        return value.isoformat()
+    if isinstance(value, decimal.Decimal):
+        return str(value)
+    if isinstance(value, (bytearray, memoryview)):
+        return bytes(value)
+    return value
+
+
+class Connection:
+    """A DB-API connection wrapping a GlareDB connection.
+
+    GlareDB doesn't provide transactional semantics, so every statement is
+    effectively autocommitted and `commit` and `rollback` do nothing.
+    """
+
+    def __init__(self, con):
+        self._con = con
+        self._closed = False
+
+    def _check_open(self):
+        if self._closed:
+            raise InterfaceError("connection is closed")
+
+    def close(self):
+        if not self._closed:
+            self._con.close()
+            self._closed = True
+
+    def commit(self):
+        self._check_open()
+
+    def rollback(self):
+        self._check_open()
+
+    def cursor(self):
+        self._check_open()
+        return Cursor(self)
+
+    def register(self, name, df):
+        """Register a dataframe as a table, see `glaredb.Connection.register`."""
+        self._check_open()
+        self._con.register(name, df)
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, exc_type, exc_value, traceback):
+        self.close()
+
+
+class Cursor:
+    arraysize = 1
+
+    def __init__(self, connection):
+        self.connection = connection
+        self.description = None
+        self.rowcount = -1
+        self._reader = None
+        self._rows = collections.deque()
+        self._closed = False
+
+    def _check_open(self):
+        if self._closed:
+            raise InterfaceError("cursor is closed")
+        self.connection._check_open()
+
+    def close(self):
+        self._reset()
+        self._closed = True
+
+    def _reset(self):
+        if self._reader is not None:
+            self._reader.close()
+        self._reader = None
+        self._rows.clear()
+        self.description = None
+        self.rowcount = -1
+
+    def execute(self, operation, parameters=None):
+        self._check_open()
+        self._reset()
+
+        query, count = _convert_placeholders(operation)
+        params = None
+        if parameters is not None or count > 0:
+            params = [_adapt_param(p) for p in (parameters or ())]
+            if len(params) != count:
+                raise ProgrammingError(
+                    f"query has {count} placeholders, got {len(params)} parameters"
+                )
+
+        try:
+            result = self.connection._con.execute(query, params)
+            reader = result.to_arrow_reader()
+        except (TypeError, ValueError) as e:
+            raise ProgrammingError(str(e)) from e
+        except Exception as e:
+            raise DatabaseError(str(e)) from e
+
+        if len(reader.schema) > 0:
+            self._reader = reader
+            self.description = [
+                (field.name, field.type, None, None, None, None, field.nullable)
+                for field in reader.schema
+            ]
+        else:
+            reader.close()
+            rows = result.rows_affected
+            self.rowcount = -1 if rows is None else rows
+        return self
+
+    def executemany(self, operation, seq_of_parameters):
+        total = 0
+        known = True
+        for parameters in seq_of_parameters:
+            self.execute(operation, parameters)
+            if self.rowcount < 0:
+                known = False
+            else:
+                total += self.rowcount
+        self.rowcount = total if known else -1
+        return self
+
+    def _fill(self, size):
+        """Buffer at least `size` rows, or all remaining rows if `size` is
+        `None`."""
+        while self._reader is not None and (size is None or len(self._rows) < size):
+            try:
+                batch = self._reader.read_next_batch()
+            except StopIteration:
+                self._reader = None
+                break
+            except Exception as e:
+                raise DatabaseError(str(e)) from e
+            columns = [col.to_pylist() for col in batch.columns]
+            self._rows.extend(zip(*columns))
+
+    def _check_result(self):
+        self._check_open()
+        if self.description is None:
+            raise ProgrammingError("no results to fetch")
+
+    def fetchone(self):
+        self._check_result()
+        self._fill(1)
+        if not self._rows:
+            return None
+        return self._rows.popleft()
+
+    def fetchmany(self, size=None):
+        self._check_result()
+        size = self.arraysize if size is None else size
+        self._fill(size)
+        return [self._rows.popleft() for _ in range(min(size, len(self._rows)))]
+
+    def fetchall(self):
+        self._check_result()
+        self._fill(None)
+        rows = list(self._rows)
+        self._rows.clear()
+        return rows
+
+    def setinputsizes(self, sizes):
+        pass
+
+    def setoutputsize(self, size, column=None):
+        pass
+
+    def __iter__(self):
+        return self
+
+    def __next__(self):
+        row = self.fetchone()
+        if row is None:
+            raise StopIteration
+        return row
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, exc_type, exc_value, traceback):
+        self.close()
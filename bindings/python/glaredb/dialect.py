@@ -0,0 +1,270 @@
+# pylint: disable-all
+"""A minimal SQLAlchemy dialect for GlareDB, using the in-process DB-API
+driver in `glaredb.dbapi`.
+
+```python
+import sqlalchemy
+
+# In-memory database.
+engine = sqlalchemy.create_engine("glaredb://")
+# Database persisted to disk.
+engine = sqlalchemy.create_engine("glaredb:///path/to/db")
+# GlareDB Cloud deployment.
+engine = sqlalchemy.create_engine("glaredb://<user>:<password>@<org>.remote.glaredb.com:6443/<deployment>")
+```
+
+SQL is compiled with the PostgreSQL dialect. Reflection reads from the
+`glare_catalog` tables, and only covers tables, views, and their columns.
+"""
+
+import re
+
+import sqlalchemy
+from sqlalchemy import types as sqltypes
+from sqlalchemy.dialects.postgresql.base import PGDialect
+from sqlalchemy.engine import default, reflection
+
+_SQLALCHEMY_1 = sqlalchemy.__version__.startswith("1.")
+
+_DEFAULT_DATABASE_OID = (
+    "(select oid from glare_catalog.databases where database_name = 'default')"
+)
+
+_INTEGER_TYPES = {
+    "Int8": sqltypes.SmallInteger,
+    "Int16": sqltypes.SmallInteger,
+    "Int32": sqltypes.Integer,
+    "Int64": sqltypes.BigInteger,
+    "UInt8": sqltypes.SmallInteger,
+    "UInt16": sqltypes.Integer,
+    "UInt32": sqltypes.BigInteger,
+    "UInt64": sqltypes.BigInteger,
+}
+
+_DECIMAL_RE = re.compile(r"Decimal(?:128|256)\((\d+),\s*(-?\d+)\)")
+
+
+def _column_type(data_type):
+    """Map the arrow type name from `glare_catalog.columns` to a SQLAlchemy
+    type."""
+    if data_type in _INTEGER_TYPES:
+        return _INTEGER_TYPES[data_type]()
+    if data_type in ("Float16", "Float32"):
+        return sqltypes.REAL()
+    if data_type == "Float64":
+        return sqltypes.Float()
+    if data_type in ("Utf8", "LargeUtf8"):
+        return sqltypes.String()
+    if data_type in ("Binary", "LargeBinary"):
+        return sqltypes.LargeBinary()
+    if data_type == "Boolean":
+        return sqltypes.Boolean()
+    if data_type in ("Date32", "Date64"):
+        return sqltypes.Date()
+    if data_type.startswith("Timestamp"):
+        return sqltypes.DateTime(timezone=not data_type.endswith("None)"))
+    if data_type.startswith("Time"):
+        return sqltypes.Time()
+    if data_type.startswith("Interval"):
+        return sqltypes.Interval()
+    m = _DECIMAL_RE.fullmatch(data_type)
+    if m:
+        return sqltypes.Numeric(int(m.group(1)), int(m.group(2)))
+    return sqltypes.NullType()
+
+
+class GlareDBDialect(PGDialect):
+    name = "glaredb"
+    driver = "glaredb"
+    default_paramstyle = "qmark"
+    supports_statement_cache = True
+
+    # GlareDB has no sequences, returning, or server side cursors.
+    supports_sequences = False
+    implicit_returning = False
+    full_returning = False
+    insert_returning = False
+    update_returning = False
+    delete_returning = False
+    postfetch_lastrowid = False
+    supports_server_side_cursors = False
+    supports_native_enum = False
+    supports_comments = False
+    supports_sane_rowcount = True
+    supports_sane_multi_rowcount = False
+    _backslash_escapes = False
+
+    @classmethod
+    def import_dbapi(cls):
+        from glaredb import dbapi
+
+        return dbapi
+
+    if _SQLALCHEMY_1:
+        dbapi = import_dbapi
+
+    def create_connect_args(self, url):
+        opts = dict(url.query)
+        if url.host:
+            target = url.set(drivername="glaredb", query={}).render_as_string(
+                hide_password=False
+            )
+        else:
+            target = url.database or None
+        return ([target], opts)
+
+    def initialize(self, connection):
+        # Skip the PostgreSQL specific server checks.
+        default.DefaultDialect.initialize(self, connection)
+
+    def _get_server_version_info(self, connection):
+        return (0, 0)
+
+    def _get_default_schema_name(self, connection):
+        return "public"
+
+    def _check_unicode_returns(self, connection, additional_tests=None):
+        return True
+
+    def get_isolation_level_values(self, dbapi_connection):
+        return ["AUTOCOMMIT"]
+
+    def get_isolation_level(self, dbapi_connection):
+        return "AUTOCOMMIT"
+
+    def set_isolation_level(self, dbapi_connection, level):
+        pass
+
+    def get_default_isolation_level(self, dbapi_connection):
+        return "AUTOCOMMIT"
+
+    def do_begin(self, dbapi_connection):
+        pass
+
+    def do_rollback(self, dbapi_connection):
+        pass
+
+    def do_commit(self, dbapi_connection):
+        pass
+
+    def do_ping(self, dbapi_connection):
+        cursor = dbapi_connection.cursor()
+        try:
+            cursor.execute("select 1")
+            cursor.fetchall()
+        finally:
+            cursor.close()
+        return True
+
+    @reflection.cache
+    def get_schema_names(self, connection, **kw):
+        rows = connection.exec_driver_sql(
+            "select schema_name from glare_catalog.schemas "
+            f"where database_oid = {_DEFAULT_DATABASE_OID} order by schema_name"
+        )
+        return [row[0] for row in rows]
+
+    @reflection.cache
+    def get_table_names(self, connection, schema=None, **kw):
+        rows = connection.exec_driver_sql(
+            "select table_name from glare_catalog.tables "
+            f"where database_oid = {_DEFAULT_DATABASE_OID} and schema_name = ? "
+            "order by table_name",
+            (schema or self.default_schema_name,),
+        )
+        return [row[0] for row in rows]
+
+    @reflection.cache
+    def get_view_names(self, connection, schema=None, **kw):
+        rows = connection.exec_driver_sql(
+            "select view_name from glare_catalog.views "
+            f"where database_oid = {_DEFAULT_DATABASE_OID} and schema_name = ? "
+            "order by view_name",
+            (schema or self.default_schema_name,),
+        )
+        return [row[0] for row in rows]
+
+    @reflection.cache
+    def get_view_definition(self, connection, view_name, schema=None, **kw):
+        rows = connection.exec_driver_sql(
+            "select sql from glare_catalog.views "
+            f"where database_oid = {_DEFAULT_DATABASE_OID} and schema_name = ? "
+            "and view_name = ?",
+            (schema or self.default_schema_name, view_name),
+        ).fetchall()
+        return rows[0][0] if rows else None
+
+    def has_table(self, connection, table_name, schema=None, **kw):
+        schema = schema or self.default_schema_name
+        return table_name in self.get_table_names(
+            connection, schema
+        ) or table_name in self.get_view_names(connection, schema)
+
+    def has_sequence(self, connection, sequence_name, schema=None, **kw):
+        return False
+
+    @reflection.cache
+    def get_columns(self, connection, table_name, schema=None, **kw):
+        rows = connection.exec_driver_sql(
+            "select c.column_name, c.data_type, c.is_nullable "
+            "from glare_catalog.columns c "
+            "join glare_catalog.tables t on c.table_oid = t.oid "
+            f"where t.database_oid = {_DEFAULT_DATABASE_OID} "
+            "and t.schema_name = ? and t.table_name = ? "
+            "order by c.column_ordinal",
+            (schema or self.default_schema_name, table_name),
+        )
+        return [
+            {
+                "name": name,
+                "type": _column_type(data_type),
+                "nullable": nullable,
+                "default": None,
+                "autoincrement": False,
+            }
+            for name, data_type, nullable in rows
+        ]
+
+    def get_pk_constraint(self, connection, table_name, schema=None, **kw):
+        return {"constrained_columns": [], "name": None}
+
+    def get_foreign_keys(self, connection, table_name, schema=None, **kw):
+        return []
+
+    def get_indexes(self, connection, table_name, schema=None, **kw):
+        return []
+
+    def get_unique_constraints(self, connection, table_name, schema=None, **kw):
+        return []
+
+    def get_check_constraints(self, connection, table_name, schema=None, **kw):
+        return []
+
+    def get_table_comment(self, connection, table_name, schema=None, **kw):
+        return {"text": None}
+
+    def get_materialized_view_names(self, connection, schema=None, **kw):
+        return []
+
+    def get_temp_table_names(self, connection, schema=None, **kw):
+        return []
+
+    def get_temp_view_names(self, connection, schema=None, **kw):
+        return []
+
+    if not _SQLALCHEMY_1:
+        # The PostgreSQL dialect reflects multiple tables at once by querying
+        # pg_catalog, use the generic implementations which call the single
+        # table methods above instead.
+        get_multi_columns = default.DefaultDialect.get_multi_columns
+        get_multi_pk_constraint = default.DefaultDialect.get_multi_pk_constraint
+        get_multi_foreign_keys = default.DefaultDialect.get_multi_foreign_keys
+        get_multi_indexes = default.DefaultDialect.get_multi_indexes
+        get_multi_unique_constraints = (
+            default.DefaultDialect.get_multi_unique_constraints
+        )
+        get_multi_check_constraints = default.DefaultDialect.get_multi_check_constraints
+        get_multi_table_comment = default.DefaultDialect.get_multi_table_comment
+
+
+dialect = GlareDBDialect
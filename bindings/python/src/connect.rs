@@ -4,7 +4,7 @@
 //! queries.
 
 use crate::connection::Connection;
-use crate::environment::PyEnvironmentReader;
+use crate::environment::{PyEnvironmentReader, RegisteredTables};
 use crate::error::PyGlareDbError;
 use crate::runtime::wait_for_future;
 use futures::lock::Mutex;
@@ -12,6 +12,7 @@ use std::collections::HashMap;
 use std::{path::PathBuf, sync::Arc};
 use url::Url;
 
+use datafusion::variable::VarType;
 use datafusion_ext::vars::SessionVars;
 use pyo3::prelude::*;
 use sqlexec::{
@@ -132,7 +133,12 @@ pub fn connect(
             .map_err(PyGlareDbError::from)?;
 
             let mut sess = engine
-                .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+                .new_local_session_context(
+                    SessionVars::default()
+                        .with_is_embedded(true, VarType::System)
+                        .with_is_admin(true, VarType::System),
+                    SessionStorageConfig::default(),
+                )
                 .await
                 .map_err(PyGlareDbError::from)?;
             sess.attach_remote_session(exec_client.clone(), None)
@@ -142,16 +148,23 @@ pub fn connect(
             sess
         } else {
             engine
-                .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+                .new_local_session_context(
+                    SessionVars::default()
+                        .with_is_embedded(true, VarType::System)
+                        .with_is_admin(true, VarType::System),
+                    SessionStorageConfig::default(),
+                )
                 .await
                 .map_err(PyGlareDbError::from)?
         };
 
-        session.register_env_reader(Box::new(PyEnvironmentReader));
+        let tables = RegisteredTables::default();
+        session.register_env_reader(Box::new(PyEnvironmentReader::new(tables.clone())));
         let sess = Arc::new(Mutex::new(session));
 
         Ok(Connection {
             sess,
+            tables,
             _engine: Arc::new(engine),
         })
     })
@@ -1,15 +1,18 @@
 use crate::execution_result::PyExecutionResult;
 use datafusion::logical_expr::LogicalPlan as DFLogicalPlan;
+use datafusion::variable::VarType;
 use datafusion_ext::vars::SessionVars;
 use futures::lock::Mutex;
 use once_cell::sync::OnceCell;
-use pyo3::{prelude::*, types::PyType};
+use pyo3::{exceptions::PyTypeError, prelude::*, types::PyType};
 use sqlexec::engine::{Engine, SessionStorageConfig, TrackedSession};
 use sqlexec::{LogicalPlan, OperationInfo};
 use std::sync::Arc;
 
 pub(super) type PyTrackedSession = Arc<Mutex<TrackedSession>>;
 
+use crate::environment::{resolve_python_object, RegisteredTables};
+use crate::params::scalars_from_python;
 use crate::{error::PyGlareDbError, logical_plan::PyLogicalPlan, runtime::wait_for_future};
 
 /// A connected session to a GlareDB database.
@@ -17,6 +20,8 @@ use crate::{error::PyGlareDbError, logical_plan::PyLogicalPlan, runtime::wait_fo
 #[derive(Clone)]
 pub struct Connection {
     pub(super) sess: PyTrackedSession,
+    /// Tables registered with `register`.
+    pub(super) tables: RegisteredTables,
     pub(super) _engine: Arc<Engine>,
 }
 
@@ -33,12 +38,15 @@ impl Connection {
                 let engine = Engine::from_data_dir(None).await?;
                 let sess = engine
                     .new_local_session_context(
-                        SessionVars::default(),
+                        SessionVars::default()
+                            .with_is_embedded(true, VarType::System)
+                            .with_is_admin(true, VarType::System),
                         SessionStorageConfig::default(),
                     )
                     .await?;
                 Ok(Connection {
                     sess: Arc::new(Mutex::new(sess)),
+                    tables: RegisteredTables::default(),
                     _engine: Arc::new(engine),
                 }) as Result<_, PyGlareDbError>
             })
@@ -69,8 +77,11 @@ impl Connection {
     ///
     /// All operations that write or modify data are executed
     /// directly, but all query operations run lazily when you process
-    /// their results with `show`, `to_arrow`, `to_pandas`, or
-    /// `to_polars`, or call the `execute` method.
+    /// their results with `show`, `to_arrow`, `to_arrow_reader`,
+    /// `to_pandas`, or `to_polars`, or call the `execute` method.
+    ///
+    /// Results can also be streamed into any library supporting the Arrow
+    /// PyCapsule interface, e.g. `pyarrow.table(con.sql('select 1'))`.
     ///
     /// # Examples
     ///
@@ -104,15 +115,36 @@ impl Connection {
     /// con = glaredb.connect()
     /// con.sql('create table my_table (a int)').execute()
     /// ```
-    pub fn sql(&mut self, py: Python<'_>, query: &str) -> PyResult<PyLogicalPlan> {
+    ///
+    /// Bind values to `$1`, `$2`, ... placeholders in the query.
+    ///
+    /// ```python
+    /// import glaredb
+    ///
+    /// con = glaredb.connect()
+    /// con.sql('select * from my_table where a > $1', [10]).show()
+    /// ```
+    #[pyo3(signature = (query, params=None))]
+    pub fn sql(
+        &mut self,
+        py: Python<'_>,
+        query: &str,
+        params: Option<Vec<&PyAny>>,
+    ) -> PyResult<PyLogicalPlan> {
+        let params = params
+            .map(|params| scalars_from_python(&params))
+            .transpose()?;
         let cloned_sess = self.sess.clone();
         wait_for_future(py, async move {
             let mut sess = self.sess.lock().await;
 
-            let plan = sess
+            let mut plan = sess
                 .create_logical_plan(query)
                 .await
                 .map_err(PyGlareDbError::from)?;
+            if let Some(params) = params {
+                plan.bind_parameters(params).map_err(PyGlareDbError::from)?;
+            }
 
             let op = OperationInfo::new().with_query_text(query);
 
@@ -176,14 +208,35 @@ impl Connection {
     /// con = glaredb.connect()
     /// con.execute('create table my_table (a int)')
     /// ```
-    pub fn execute(&mut self, py: Python<'_>, query: &str) -> PyResult<PyExecutionResult> {
+    ///
+    /// Inserting a row, binding values to `$1`, `$2`, ... placeholders.
+    ///
+    /// ```python
+    /// import glaredb
+    ///
+    /// con = glaredb.connect()
+    /// con.execute('insert into my_table values ($1)', [1])
+    /// ```
+    #[pyo3(signature = (query, params=None))]
+    pub fn execute(
+        &mut self,
+        py: Python<'_>,
+        query: &str,
+        params: Option<Vec<&PyAny>>,
+    ) -> PyResult<PyExecutionResult> {
+        let params = params
+            .map(|params| scalars_from_python(&params))
+            .transpose()?;
         let sess = self.sess.clone();
         let (_, exec_result) = wait_for_future(py, async move {
             let mut sess = sess.lock().await;
-            let plan = sess
+            let mut plan = sess
                 .create_logical_plan(query)
                 .await
                 .map_err(PyGlareDbError::from)?;
+            if let Some(params) = params {
+                plan.bind_parameters(params).map_err(PyGlareDbError::from)?;
+            }
 
             let op = OperationInfo::new().with_query_text(query);
 
@@ -195,6 +248,44 @@ impl Connection {
         Ok(PyExecutionResult(exec_result))
     }
 
+    /// Register a dataframe as a table, making it queryable by name.
+    ///
+    /// Accepts pandas and polars dataframes, polars lazy frames, and pyarrow
+    /// tables, record batches and record batch readers. Arrow data is shared
+    /// with the session without copying. Registered tables take precedence
+    /// over variables with the same name, and replace any table previously
+    /// registered with the name.
+    ///
+    /// # Examples
+    ///
+    /// Join a dataframe with a table in an external database.
+    ///
+    /// ```python
+    /// import glaredb
+    /// import pandas as pd
+    ///
+    /// con = glaredb.connect()
+    /// con.register('users', pd.DataFrame({'id': [1, 2], 'name': ['a', 'b']}))
+    /// con.sql('select * from users u join my_pg.public.orders o on u.id = o.user_id').show()
+    /// ```
+    pub fn register(&mut self, py: Python<'_>, name: &str, df: &PyAny) -> PyResult<()> {
+        let table = resolve_python_object(py, df).ok_or_else(|| {
+            PyTypeError::new_err(format!(
+                "Cannot register object of type '{}' as a table",
+                df.get_type().name().unwrap_or("unknown")
+            ))
+        })?;
+        self.tables.write().unwrap().insert(name.to_string(), table);
+        Ok(())
+    }
+
+    /// Remove a table registered with `register`.
+    ///
+    /// Returns `True` if a table with the name was registered.
+    pub fn unregister(&mut self, _py: Python<'_>, name: &str) -> PyResult<bool> {
+        Ok(self.tables.write().unwrap().remove(name).is_some())
+    }
+
     /// Close the current session.
     pub fn close(&mut self, _py: Python<'_>) -> PyResult<()> {
         // TODO: Remove this method. No longer required.
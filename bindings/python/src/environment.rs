@@ -1,51 +1,81 @@
 use datafusion::datasource::MemTable;
 use datafusion::{
-    arrow::{pyarrow::PyArrowType, record_batch::RecordBatch},
+    arrow::{datatypes::Schema, pyarrow::PyArrowType, record_batch::RecordBatch},
     datasource::TableProvider,
 };
+use pyo3::exceptions::PyValueError;
 use pyo3::types::IntoPyDict;
 use pyo3::types::PyTuple;
 use pyo3::{prelude::*, types::PyType};
 use sqlexec::environment::EnvironmentReader;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
 use crate::logical_plan::PyLogicalPlan;
 
+/// Tables explicitly registered on a connection.
+pub type RegisteredTables = Arc<RwLock<HashMap<String, Arc<dyn TableProvider>>>>;
+
 /// Read polars dataframes from the python environment.
-#[derive(Debug, Clone, Copy)]
-pub struct PyEnvironmentReader;
+///
+/// Tables registered on the connection take precedence over variables in
+/// the python environment.
+#[derive(Clone, Default)]
+pub struct PyEnvironmentReader {
+    registered: RegisteredTables,
+}
+
+impl PyEnvironmentReader {
+    pub fn new(registered: RegisteredTables) -> Self {
+        PyEnvironmentReader { registered }
+    }
+}
 
 impl EnvironmentReader for PyEnvironmentReader {
     fn resolve_table(
         &self,
         name: &str,
     ) -> Result<Option<Arc<dyn TableProvider>>, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(table) = self.registered.read().unwrap().get(name) {
+            return Ok(Some(table.clone()));
+        }
+
         Python::with_gil(|py| {
             let var = match get_stack_locals(py, name) {
                 Ok(Some(var)) => var,
                 _ => return Ok(None),
             };
 
-            // since the resolve functions will err if the library is uninstalled,
-            // dont `try` the results, we want to move on next resolver if this one errs.
-            if let Ok(Some(table)) = resolve_polars(py, var) {
-                return Ok(Some(table));
-            }
-            if let Ok(Some(table)) = resolve_polars_lazy(py, var) {
-                return Ok(Some(table));
-            }
-            if let Ok(Some(table)) = resolve_pandas(py, var) {
-                return Ok(Some(table));
-            }
-            if let Ok(Some(tbl)) = resolve_logical_plan(py, var) {
-                return Ok(Some(tbl));
-            }
-
-            Ok(None)
+            Ok(resolve_python_object(py, var))
         })
     }
 }
 
+/// Try to convert a python object into a table provider.
+///
+/// Returns `None` if the object isn't something we know how to read.
+pub fn resolve_python_object(py: Python, var: &PyAny) -> Option<Arc<dyn TableProvider>> {
+    // since the resolve functions will err if the library is uninstalled,
+    // dont `try` the results, we want to move on next resolver if this one errs.
+    if let Ok(Some(table)) = resolve_polars(py, var) {
+        return Some(table);
+    }
+    if let Ok(Some(table)) = resolve_polars_lazy(py, var) {
+        return Some(table);
+    }
+    if let Ok(Some(table)) = resolve_pandas(py, var) {
+        return Some(table);
+    }
+    if let Ok(Some(table)) = resolve_pyarrow(py, var) {
+        return Some(table);
+    }
+    if let Ok(Some(tbl)) = resolve_logical_plan(py, var) {
+        return Some(tbl);
+    }
+
+    None
+}
+
 /// Search for a python variable in the current frame, or any parent frame.
 fn get_stack_locals<'py>(py: Python<'py>, name: &str) -> PyResult<Option<&'py PyAny>> {
     let mut current_frame = py.import("inspect")?.getattr("currentframe")?.call0()?;
@@ -167,6 +197,41 @@ fn resolve_pandas(py: Python, var: &PyAny) -> PyResult<Option<Arc<dyn TableProvi
     Ok(Some(Arc::new(table) as Arc<dyn TableProvider>))
 }
 
+/// Try to resolve a variable as a pyarrow table, record batch, or record
+/// batch reader.
+///
+/// Batches are passed through the Arrow C data interface without copying.
+/// Readers are read to completion.
+///
+/// Returns `Ok(None)` if the variable isn't one of the above.
+fn resolve_pyarrow(py: Python, var: &PyAny) -> PyResult<Option<Arc<dyn TableProvider>>> {
+    let pyarrow = py.import("pyarrow")?;
+    let table_type: &PyType = pyarrow.getattr("Table")?.downcast()?;
+    let batch_type: &PyType = pyarrow.getattr("RecordBatch")?.downcast()?;
+    let reader_type: &PyType = pyarrow.getattr("RecordBatchReader")?.downcast()?;
+
+    let table = if var.is_instance(table_type)? {
+        var
+    } else if var.is_instance(batch_type)? {
+        table_type.call_method1("from_batches", (vec![var],))?
+    } else if var.is_instance(reader_type)? {
+        var.call_method0("read_all")?
+    } else {
+        return Ok(None);
+    };
+
+    let schema = table.getattr("schema")?.extract::<PyArrowType<Schema>>()?.0;
+    let batches = table
+        .call_method0("to_batches")?
+        .extract::<PyArrowType<Vec<RecordBatch>>>()?
+        .0;
+
+    let table = MemTable::try_new(Arc::new(schema), vec![batches])
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+    Ok(Some(Arc::new(table) as Arc<dyn TableProvider>))
+}
+
 fn resolve_logical_plan(_py: Python, var: &PyAny) -> PyResult<Option<Arc<dyn TableProvider>>> {
     let lp: PyLogicalPlan = var.extract()?;
     Ok(Some(Arc::new(lp) as Arc<dyn TableProvider>))
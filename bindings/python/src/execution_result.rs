@@ -1,15 +1,20 @@
 use crate::util::pyprint;
-use anyhow::Result;
 use arrow_util::pretty;
-use datafusion::arrow::datatypes::Schema;
-use datafusion::arrow::pyarrow::ToPyArrow;
-use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::datatypes::{Schema, SchemaRef};
+use datafusion::arrow::error::ArrowError;
+use datafusion::arrow::ffi_stream::FFI_ArrowArrayStream;
+use datafusion::arrow::pyarrow::IntoPyArrow;
+use datafusion::arrow::record_batch::{RecordBatch, RecordBatchReader};
+use datafusion::physical_plan::{EmptyRecordBatchStream, SendableRecordBatchStream};
 use futures::StreamExt;
+use pyo3::types::PyCapsule;
 use pyo3::{exceptions::PyRuntimeError, prelude::*, types::PyTuple};
 use sqlexec::session::ExecutionResult;
+use std::ffi::CString;
 use std::sync::Arc;
+use tokio::runtime::Handle;
 
-use crate::runtime::wait_for_future;
+use crate::runtime::{get_tokio_runtime, wait_for_future};
 
 /// The result of an executed query.
 #[pyclass]
@@ -18,45 +23,71 @@ pub struct PyExecutionResult(pub ExecutionResult);
 #[pymethods]
 impl PyExecutionResult {
     /// Convert to Arrow Table
-    /// Collect the batches and pass to Arrow Table
+    ///
+    /// Batches are streamed into the table as they're produced, without
+    /// copying the underlying buffers.
     pub fn to_arrow(&mut self, py: Python) -> PyResult<PyObject> {
-        let (batches, schema) = to_arrow_batches_and_schema(&mut self.0, py)?;
-
-        Python::with_gil(|py| {
-            // Instantiate pyarrow Table object and use its from_batches method
-            let table_class = py.import("pyarrow")?.getattr("Table")?;
-            let args = PyTuple::new(py, &[batches, schema]);
-            let table: PyObject = table_class.call_method1("from_batches", args)?.into();
-            Ok(table)
-        })
+        let reader = self.to_arrow_reader(py)?;
+        reader.call_method0(py, "read_all")
     }
 
     pub fn to_polars(&mut self, py: Python) -> PyResult<PyObject> {
-        let (batches, schema) = to_arrow_batches_and_schema(&mut self.0, py)?;
-
-        Python::with_gil(|py| {
-            let table_class = py.import("pyarrow")?.getattr("Table")?;
-            let args = PyTuple::new(py, &[batches, schema]);
-            let table: PyObject = table_class.call_method1("from_batches", args)?.into();
+        let table = self.to_arrow(py)?;
 
-            let table_class = py.import("polars")?.getattr("DataFrame")?;
-            let args = PyTuple::new(py, &[table]);
-            let result = table_class.call1(args)?.into();
-            Ok(result)
-        })
+        let table_class = py.import("polars")?.getattr("DataFrame")?;
+        let args = PyTuple::new(py, &[table]);
+        let result = table_class.call1(args)?.into();
+        Ok(result)
     }
 
     pub fn to_pandas(&mut self, py: Python) -> PyResult<PyObject> {
-        let (batches, schema) = to_arrow_batches_and_schema(&mut self.0, py)?;
+        let table = self.to_arrow(py)?;
+        table.call_method0(py, "to_pandas")
+    }
 
-        Python::with_gil(|py| {
-            let table_class = py.import("pyarrow")?.getattr("Table")?;
-            let args = PyTuple::new(py, &[batches, schema]);
-            let table: PyObject = table_class.call_method1("from_batches", args)?.into();
+    /// Convert to a `pyarrow.RecordBatchReader`.
+    ///
+    /// Batches are only pulled from the query as the reader is consumed, so
+    /// results larger than memory can be processed one batch at a time.
+    pub fn to_arrow_reader(&mut self, py: Python) -> PyResult<PyObject> {
+        let reader: Box<dyn RecordBatchReader + Send> = Box::new(self.take_reader(py)?);
+        reader.into_pyarrow(py)
+    }
 
-            let result = table.call_method0(py, "to_pandas")?;
-            Ok(result)
-        })
+    /// Export the result as an Arrow stream using the Arrow PyCapsule
+    /// interface.
+    ///
+    /// This allows libraries such as pyarrow, polars and duckdb to consume
+    /// the result directly without any intermediate conversions, e.g.
+    /// `pyarrow.table(result)`. Batches are pulled from the query as the
+    /// consumer reads the stream, so the result is never fully held in
+    /// memory.
+    ///
+    /// A requested schema is currently ignored, which the protocol permits.
+    #[pyo3(signature = (requested_schema=None))]
+    pub fn __arrow_c_stream__(
+        &mut self,
+        py: Python,
+        requested_schema: Option<PyObject>,
+    ) -> PyResult<PyObject> {
+        let _ = requested_schema;
+        let stream = FFI_ArrowArrayStream::new(Box::new(self.take_reader(py)?));
+
+        let name = CString::new("arrow_array_stream").unwrap();
+        let capsule = PyCapsule::new(py, stream, Some(name))?;
+        Ok(capsule.to_object(py))
+    }
+
+    /// Number of rows inserted, updated, or deleted by the statement, or
+    /// `None` if the statement didn't modify any rows.
+    #[getter]
+    pub fn rows_affected(&self) -> Option<usize> {
+        match &self.0 {
+            ExecutionResult::InsertSuccess { rows_inserted } => Some(*rows_inserted),
+            ExecutionResult::DeleteSuccess { deleted_rows } => Some(*deleted_rows),
+            ExecutionResult::UpdateSuccess { updated_rows } => Some(*updated_rows),
+            _ => None,
+        }
     }
 
     pub fn execute(&mut self, py: Python) -> PyResult<()> {
@@ -77,43 +108,57 @@ impl PyExecutionResult {
     }
 }
 
-fn to_arrow_batches_and_schema(
-    result: &mut ExecutionResult,
-    py: Python<'_>,
-) -> PyResult<(PyObject, PyObject)> {
-    match result {
-        ExecutionResult::Query { stream, .. } => {
-            let batches: Result<Vec<RecordBatch>> = wait_for_future(py, async move {
-                Ok(stream
-                    .collect::<Vec<_>>()
-                    .await
-                    .into_iter()
-                    .collect::<Result<Vec<_>, _>>()?)
-            });
-
-            let batches = batches
-                .map_err(|e| PyRuntimeError::new_err(format!("unhandled exception: {:?}", &e)))?;
-            let schema = batches[0].schema().to_pyarrow(py)?;
-
-            // TODO: currently we are iterating twice due to the GIL lock
-            // we can't use `to_pyarrow` within an async block.
-            let batches = batches
-                .into_iter()
-                .map(|batch| batch.to_pyarrow(py))
-                .collect::<Result<Vec<_>, _>>()?
-                .to_object(py);
-
-            Ok((batches, schema))
-        }
-        _ => {
-            // TODO: Figure out the schema we actually want to use.
-            let schema = Arc::new(Schema::empty());
-            let batches = vec![RecordBatch::new_empty(schema.clone()).to_pyarrow(py)]
-                .into_iter()
-                .collect::<Result<Vec<_>, _>>()?
-                .to_object(py);
-            Ok((batches, schema.to_pyarrow(py)?))
-        }
+impl PyExecutionResult {
+    /// Take the result stream, leaving an empty stream in its place.
+    ///
+    /// Errors if the execution errored.
+    fn take_reader(&mut self, py: Python<'_>) -> PyResult<StreamingRecordBatchReader> {
+        let stream = match &mut self.0 {
+            ExecutionResult::Query { stream, .. } => {
+                let empty = Box::pin(EmptyRecordBatchStream::new(stream.schema()));
+                std::mem::replace(stream, empty)
+            }
+            ExecutionResult::Error(_) => {
+                match std::mem::replace(&mut self.0, ExecutionResult::EmptyQuery) {
+                    ExecutionResult::Error(e) => return Err(e.into()),
+                    _ => unreachable!(),
+                }
+            }
+            // Statements that don't return rows produce a stream with no
+            // columns.
+            _ => Box::pin(EmptyRecordBatchStream::new(Arc::new(Schema::empty()))),
+        };
+        Ok(StreamingRecordBatchReader {
+            handle: get_tokio_runtime(py).0.handle().clone(),
+            stream,
+        })
+    }
+}
+
+/// A synchronous reader over a result stream, pulling the next batch when
+/// the reader is advanced.
+struct StreamingRecordBatchReader {
+    handle: Handle,
+    stream: SendableRecordBatchStream,
+}
+
+impl Iterator for StreamingRecordBatchReader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Consumers may call this from any thread, with or without holding
+        // the GIL. Release it while waiting on the next batch since
+        // producing it may need the GIL, e.g. when scanning a dataframe.
+        let handle = &self.handle;
+        let stream = &mut self.stream;
+        let next = Python::with_gil(|py| py.allow_threads(|| handle.block_on(stream.next())));
+        next.map(|result| result.map_err(|e| ArrowError::ExternalError(Box::new(e))))
+    }
+}
+
+impl RecordBatchReader for StreamingRecordBatchReader {
+    fn schema(&self) -> SchemaRef {
+        self.stream.schema()
     }
 }
 
@@ -6,6 +6,7 @@ mod environment;
 mod error;
 mod execution_result;
 mod logical_plan;
+mod params;
 mod runtime;
 mod util;
 
@@ -45,12 +46,12 @@ fn glaredb(_py: Python, m: &PyModule) -> PyResult<()> {
 #[pyfunction]
 pub fn sql(py: Python, query: &str) -> PyResult<PyLogicalPlan> {
     let mut con = Connection::default_in_memory(py)?;
-    con.sql(py, query)
+    con.sql(py, query, None)
 }
 
 /// Execute a query against an in-memory GlareDB database.
 #[pyfunction]
 pub fn execute(py: Python, query: &str) -> PyResult<PyExecutionResult> {
     let mut con = Connection::default_in_memory(py)?;
-    con.execute(py, query)
+    con.execute(py, query, None)
 }
@@ -61,6 +61,20 @@ impl PyLogicalPlan {
         self.execute_inner(py)?.to_pandas(py)
     }
 
+    fn to_arrow_reader(&self, py: Python) -> PyResult<PyObject> {
+        self.execute_inner(py)?.to_arrow_reader(py)
+    }
+
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_stream__(
+        &self,
+        py: Python,
+        requested_schema: Option<PyObject>,
+    ) -> PyResult<PyObject> {
+        self.execute_inner(py)?
+            .__arrow_c_stream__(py, requested_schema)
+    }
+
     fn show(&self, py: Python) -> PyResult<()> {
         self.execute_inner(py)?.show(py)
     }
@@ -0,0 +1,39 @@
+//! Binding python values to query parameters.
+use datafusion::scalar::ScalarValue;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyFloat, PyLong, PyString};
+
+/// Convert python values to scalars for binding to `$1`, `$2`, ... placeholders.
+///
+/// Only builtin types are supported. Other values (dates, decimals, etc.)
+/// should be passed as strings, which are cast to the placeholder's type if
+/// it could be inferred.
+pub fn scalars_from_python(params: &[&PyAny]) -> PyResult<Vec<ScalarValue>> {
+    params
+        .iter()
+        .map(|param| scalar_from_python(param))
+        .collect()
+}
+
+fn scalar_from_python(param: &PyAny) -> PyResult<ScalarValue> {
+    // Check bool before int since bools are ints in python.
+    Ok(if param.is_none() {
+        ScalarValue::Null
+    } else if param.is_instance_of::<PyBool>() {
+        ScalarValue::Boolean(Some(param.extract()?))
+    } else if param.is_instance_of::<PyLong>() {
+        ScalarValue::Int64(Some(param.extract()?))
+    } else if param.is_instance_of::<PyFloat>() {
+        ScalarValue::Float64(Some(param.extract()?))
+    } else if param.is_instance_of::<PyString>() {
+        ScalarValue::Utf8(Some(param.extract()?))
+    } else if param.is_instance_of::<PyBytes>() {
+        ScalarValue::Binary(Some(param.extract()?))
+    } else {
+        return Err(PyTypeError::new_err(format!(
+            "Unsupported parameter type: '{}'",
+            param.get_type().name().unwrap_or("unknown")
+        )));
+    })
+}
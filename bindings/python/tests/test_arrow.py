@@ -0,0 +1,90 @@
+import glaredb
+import pyarrow as pa
+
+
+def test_arrow_c_stream():
+    con = glaredb.connect()
+
+    res = con.execute("select 1 as a, 'hello' as b union all select 2, 'world'")
+    capsule = res.__arrow_c_stream__()
+    reader = pa.RecordBatchReader._import_from_c_capsule(capsule)
+    out = reader.read_all().sort_by("a")
+
+    assert out.column_names == ["a", "b"]
+    assert out.column("a").to_pylist() == [1, 2]
+    assert out.column("b").to_pylist() == ["hello", "world"]
+    con.close()
+
+
+def test_arrow_c_stream_from_logical_plan():
+    con = glaredb.connect()
+
+    # pyarrow picks up the PyCapsule interface directly.
+    out = pa.table(con.sql("select * from generate_series(1, 5) as s(n)"))
+
+    assert out.column("n").to_pylist() == [1, 2, 3, 4, 5]
+    con.close()
+
+
+def test_arrow_c_stream_empty_result():
+    con = glaredb.connect()
+
+    res = con.execute("select 1 as a where false")
+    reader = pa.RecordBatchReader._import_from_c_capsule(res.__arrow_c_stream__())
+    out = reader.read_all()
+
+    assert out.column_names == ["a"]
+    assert out.num_rows == 0
+    con.close()
+
+
+def test_arrow_c_stream_streams_batches():
+    con = glaredb.connect()
+
+    res = con.execute("select * from generate_series(1, 100000) as s(n)")
+    reader = pa.RecordBatchReader._import_from_c_capsule(res.__arrow_c_stream__())
+
+    num_batches = 0
+    total = 0
+    for batch in reader:
+        num_batches += 1
+        total += batch.num_rows
+    assert num_batches > 1
+    assert total == 100000
+    con.close()
+
+
+def test_arrow_c_stream_no_rows_returned():
+    con = glaredb.connect()
+
+    res = con.execute("create temp table t (a int)")
+    reader = pa.RecordBatchReader._import_from_c_capsule(res.__arrow_c_stream__())
+    out = reader.read_all()
+
+    assert out.num_columns == 0
+    assert out.num_rows == 0
+    con.close()
+
+
+def test_to_arrow_reader_streams_batches():
+    con = glaredb.connect()
+
+    reader = con.sql("select * from generate_series(1, 10000) as s(n)").to_arrow_reader()
+    assert isinstance(reader, pa.RecordBatchReader)
+    assert reader.schema.names == ["n"]
+
+    total = 0
+    for batch in reader:
+        total += batch.num_rows
+    assert total == 10000
+    con.close()
+
+
+def test_to_arrow_empty_result():
+    con = glaredb.connect()
+
+    out = con.sql("select 1 as a where false").to_arrow()
+
+    assert out.column_names == ["a"]
+    assert out.num_rows == 0
+    con.close()
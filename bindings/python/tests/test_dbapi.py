@@ -0,0 +1,65 @@
+import datetime
+
+import pandas as pd
+import pytest
+
+from glaredb import dbapi
+
+
+def test_module_globals():
+    assert dbapi.apilevel == "2.0"
+    assert dbapi.paramstyle == "qmark"
+
+
+def test_qmark_parameters():
+    with dbapi.connect() as con:
+        cur = con.cursor()
+        cur.execute("create table t (a bigint, b text)")
+        cur.executemany("insert into t values (?, ?)", [(1, "x"), (2, "y"), (3, "z")])
+        assert cur.rowcount == 3
+
+        cur.execute("select a, b from t where a > ? and b <> '?' order by a", (1,))
+        assert [d[0] for d in cur.description] == ["a", "b"]
+        assert cur.description[0][1] == dbapi.NUMBER
+        assert cur.description[1][1] == dbapi.STRING
+        assert cur.fetchall() == [(2, "y"), (3, "z")]
+
+
+def test_parameter_count_mismatch():
+    with dbapi.connect() as con:
+        cur = con.cursor()
+        with pytest.raises(dbapi.ProgrammingError):
+            cur.execute("select ?, ?", (1,))
+
+
+def test_date_parameters():
+    with dbapi.connect() as con:
+        cur = con.cursor()
+        cur.execute("select cast(? as date) as d", (datetime.date(2024, 1, 2),))
+        assert cur.fetchone() == (datetime.date(2024, 1, 2),)
+
+
+def test_fetchmany_streams():
+    with dbapi.connect() as con:
+        cur = con.cursor()
+        cur.execute("select * from generate_series(1, 10000) as s(n)")
+
+        assert len(cur.fetchmany(10)) == 10
+        assert cur.fetchone() == (11,)
+        assert len(cur.fetchall()) == 10000 - 11
+        assert cur.fetchone() is None
+
+
+def test_errors_raised():
+    with dbapi.connect() as con:
+        cur = con.cursor()
+        with pytest.raises(dbapi.DatabaseError):
+            cur.execute("select * from missing_table")
+
+
+def test_pandas_read_sql():
+    with dbapi.connect() as con:
+        # pandas warns about connections that aren't sqlalchemy connectables.
+        with pytest.warns(UserWarning):
+            out = pd.read_sql("select 1 as a, 'hello' as b", con)
+        assert out.to_dict("list") == {"a": [1], "b": ["hello"]}
@@ -0,0 +1,48 @@
+import glaredb
+import pandas as pd
+import polars as pl
+import pyarrow as pa
+import pytest
+
+
+def test_register_pandas():
+    con = glaredb.connect()
+    con.register("fruits", pd.DataFrame({"a": [1, 2, 3], "b": ["x", "y", "z"]}))
+
+    out = con.sql("select a from fruits where b <> 'y' order by a").to_pandas()
+
+    assert out["a"].tolist() == [1, 3]
+    con.close()
+
+
+def test_register_polars_and_pyarrow_join():
+    con = glaredb.connect()
+    con.register("left_side", pl.DataFrame({"id": [1, 2, 3], "l": ["a", "b", "c"]}))
+    con.register("right_side", pa.table({"id": [2, 3, 4], "r": ["x", "y", "z"]}))
+
+    out = con.sql(
+        "select l, r from left_side join right_side using (id) order by l"
+    ).to_arrow()
+
+    assert out.column("l").to_pylist() == ["b", "c"]
+    assert out.column("r").to_pylist() == ["x", "y"]
+    con.close()
+
+
+def test_register_replace_and_unregister():
+    con = glaredb.connect()
+    con.register("t", pa.table({"a": [1]}))
+    con.register("t", pa.table({"a": [1, 2]}))
+
+    assert con.sql("select count(*) as c from t").to_arrow().column("c").to_pylist() == [2]
+    assert con.unregister("t")
+    assert not con.unregister("t")
+    con.close()
+
+
+def test_register_unsupported_type():
+    con = glaredb.connect()
+
+    with pytest.raises(TypeError):
+        con.register("t", 42)
+    con.close()
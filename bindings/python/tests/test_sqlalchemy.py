@@ -0,0 +1,29 @@
+import pandas as pd
+import pytest
+
+sqlalchemy = pytest.importorskip("sqlalchemy")
+
+
+def test_engine_query():
+    engine = sqlalchemy.create_engine("glaredb://")
+    with engine.connect() as con:
+        out = con.execute(
+            sqlalchemy.text("select * from generate_series(1, :n) as s(n)"), {"n": 3}
+        ).fetchall()
+        assert [row[0] for row in out] == [1, 2, 3]
+
+
+def test_pandas_roundtrip_and_reflection():
+    engine = sqlalchemy.create_engine("glaredb://")
+    with engine.connect() as con:
+        df = pd.DataFrame({"a": [1, 2, 3], "b": ["x", "y", "z"]})
+        df.to_sql("roundtrip", con, index=False)
+
+        out = pd.read_sql("select * from roundtrip order by a", con)
+        assert out.to_dict("list") == df.to_dict("list")
+
+        inspector = sqlalchemy.inspect(con)
+        assert "roundtrip" in inspector.get_table_names()
+        columns = inspector.get_columns("roundtrip")
+        assert [c["name"] for c in columns] == ["a", "b"]
+        assert isinstance(columns[0]["type"], sqlalchemy.BigInteger)
@@ -0,0 +1,284 @@
+//! Plain text formats for record batches, for pasting results into documents.
+//!
+//! Unlike pretty tables, these never truncate values or elide rows and
+//! columns, so only the null text and alignments of the display options
+//! apply.
+use datafusion::arrow::datatypes::Schema;
+use datafusion::arrow::error::ArrowError;
+use datafusion::arrow::record_batch::RecordBatch;
+use std::fmt::Write;
+use textwrap::core::display_width;
+
+use crate::pretty::{Alignment, ColumnValues, DisplayOptions};
+
+/// Format record batches as a markdown (pipe) table.
+pub fn markdown_format_batches(
+    schema: &Schema,
+    batches: &[RecordBatch],
+    opts: &DisplayOptions,
+) -> Result<String, ArrowError> {
+    let headers: Vec<_> = schema
+        .fields
+        .iter()
+        .map(|f| escape_markdown(f.name()))
+        .collect();
+    let rows = format_rows(batches, opts, escape_markdown)?;
+
+    let mut widths: Vec<_> = headers.iter().map(|h| display_width(h).max(3)).collect();
+    for row in &rows {
+        for (width, val) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(val));
+        }
+    }
+    let alignments: Vec<_> = schema
+        .fields
+        .iter()
+        .map(|f| opts.alignment(f.data_type()))
+        .collect();
+
+    let mut buf = String::new();
+    write_markdown_row(&mut buf, &headers, &widths, &alignments);
+    let seps: Vec<_> = widths
+        .iter()
+        .zip(&alignments)
+        .map(|(width, alignment)| match alignment {
+            Alignment::Left => "-".repeat(*width),
+            Alignment::Right => format!("{}:", "-".repeat(width - 1)),
+            Alignment::Center => format!(":{}:", "-".repeat(width - 2)),
+        })
+        .collect();
+    write_markdown_row(&mut buf, &seps, &widths, &alignments);
+    for row in &rows {
+        write_markdown_row(&mut buf, row, &widths, &alignments);
+    }
+
+    Ok(buf)
+}
+
+fn write_markdown_row(
+    buf: &mut String,
+    vals: &[String],
+    widths: &[usize],
+    alignments: &[Alignment],
+) {
+    buf.push('|');
+    for ((val, width), alignment) in vals.iter().zip(widths).zip(alignments) {
+        let pad = width - display_width(val);
+        let (left, right) = match alignment {
+            Alignment::Left => (0, pad),
+            Alignment::Right => (pad, 0),
+            Alignment::Center => (pad / 2, pad - pad / 2),
+        };
+        let _ = write!(buf, " {}{val}{} |", " ".repeat(left), " ".repeat(right));
+    }
+    buf.push('\n');
+}
+
+fn escape_markdown(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// Format record batches as a LaTeX `tabular` environment.
+pub fn latex_format_batches(
+    schema: &Schema,
+    batches: &[RecordBatch],
+    opts: &DisplayOptions,
+) -> Result<String, ArrowError> {
+    let spec: String = schema
+        .fields
+        .iter()
+        .map(|f| match opts.alignment(f.data_type()) {
+            Alignment::Left => 'l',
+            Alignment::Right => 'r',
+            Alignment::Center => 'c',
+        })
+        .collect();
+    let headers: Vec<_> = schema
+        .fields
+        .iter()
+        .map(|f| escape_latex(f.name()))
+        .collect();
+
+    let mut buf = String::new();
+    let _ = writeln!(buf, "\\begin{{tabular}}{{{spec}}}");
+    buf.push_str("\\hline\n");
+    let _ = writeln!(buf, "{} \\\\", headers.join(" & "));
+    buf.push_str("\\hline\n");
+    for row in format_rows(batches, opts, escape_latex)? {
+        let _ = writeln!(buf, "{} \\\\", row.join(" & "));
+    }
+    buf.push_str("\\hline\n");
+    buf.push_str("\\end{tabular}\n");
+
+    Ok(buf)
+}
+
+fn escape_latex(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Format record batches with each column of a row on its own line, like
+/// psql's expanded display.
+pub fn expanded_format_batches(
+    schema: &Schema,
+    batches: &[RecordBatch],
+    opts: &DisplayOptions,
+) -> Result<String, ArrowError> {
+    let rows = format_rows(batches, opts, str::to_string)?;
+    if rows.is_empty() {
+        return Ok("(0 rows)\n".to_string());
+    }
+
+    let names: Vec<_> = schema.fields.iter().map(|f| f.name().as_str()).collect();
+    let name_width = names.iter().map(|n| display_width(n)).max().unwrap_or(0);
+    let val_width = rows
+        .iter()
+        .flatten()
+        .flat_map(|v| v.lines())
+        .map(display_width)
+        .max()
+        .unwrap_or(0);
+
+    let mut buf = String::new();
+    for (idx, row) in rows.iter().enumerate() {
+        let header = format!("-[ RECORD {} ]", idx + 1);
+        let fill = (name_width + 3 + val_width).saturating_sub(display_width(&header));
+        let _ = writeln!(buf, "{header}{}", "-".repeat(fill));
+
+        for (name, val) in names.iter().zip(row) {
+            let pad = " ".repeat(name_width - display_width(name));
+            let mut lines = val.lines();
+            let _ = writeln!(buf, "{name}{pad} | {}", lines.next().unwrap_or_default());
+            // Continuation lines of multiline values line up with the first.
+            for line in lines {
+                let _ = writeln!(buf, "{} | {line}", " ".repeat(name_width));
+            }
+        }
+    }
+
+    Ok(buf)
+}
+
+/// Format every value in the batches, row by row.
+fn format_rows(
+    batches: &[RecordBatch],
+    opts: &DisplayOptions,
+    escape: impl Fn(&str) -> String,
+) -> Result<Vec<Vec<String>>, ArrowError> {
+    let mut rows = Vec::new();
+    for batch in batches {
+        let cols = batch
+            .columns()
+            .iter()
+            .map(|col| ColumnValues::try_new_from_array(col, None, opts))
+            .collect::<Result<Vec<_>, _>>()?;
+        for row in 0..batch.num_rows() {
+            rows.push(cols.iter().map(|col| escape(&col.vals[row])).collect());
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::{Int32Array, StringArray};
+    use datafusion::arrow::datatypes::{DataType, Field};
+
+    use super::*;
+
+    fn batch() -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, true),
+        ]);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 20])),
+                Arc::new(StringArray::from(vec![Some("a|b"), None])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn markdown() {
+        let batch = batch();
+        let out =
+            markdown_format_batches(&batch.schema(), &[batch], &DisplayOptions::default()).unwrap();
+        let expected = [
+            "|  id | name |",
+            "| --: | ---- |",
+            "|   1 | a\\|b |",
+            "|  20 | NULL |",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(expected, out);
+    }
+
+    #[test]
+    fn latex() {
+        let batch = batch();
+        let out =
+            latex_format_batches(&batch.schema(), &[batch], &DisplayOptions::default()).unwrap();
+        let expected = [
+            "\\begin{tabular}{rl}",
+            "\\hline",
+            "id & name \\\\",
+            "\\hline",
+            "1 & a|b \\\\",
+            "20 & NULL \\\\",
+            "\\hline",
+            "\\end{tabular}",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(expected, out);
+
+        assert_eq!("50\\% of \\$x\\_y", escape_latex("50% of $x_y"));
+    }
+
+    #[test]
+    fn expanded() {
+        let batch = batch();
+        let opts = DisplayOptions {
+            null: "∅".to_string(),
+            ..Default::default()
+        };
+        let out = expanded_format_batches(&batch.schema(), &[batch], &opts).unwrap();
+        let expected = [
+            "-[ RECORD 1 ]",
+            "id   | 1",
+            "name | a|b",
+            "-[ RECORD 2 ]",
+            "id   | 20",
+            "name | ∅",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(expected, out);
+
+        let empty = RecordBatch::new_empty(batch().schema());
+        assert_eq!(
+            "(0 rows)\n",
+            expanded_format_batches(&empty.schema(), &[empty], &opts).unwrap()
+        );
+    }
+}
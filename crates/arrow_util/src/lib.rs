@@ -1,2 +1,3 @@
 //! Extra utilities for arrow.
+pub mod formats;
 pub mod pretty;
@@ -4,7 +4,7 @@ use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
 use datafusion::arrow::error::ArrowError;
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::arrow::util::display::{ArrayFormatter, FormatOptions};
-use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::ops::Range;
 use std::sync::Arc;
@@ -16,11 +16,122 @@ const DEFAULT_MAX_ROWS: usize = 20;
 /// How many values to use for the avg width calculation.
 const NUM_VALS_FOR_AVG: usize = 10;
 
-static TABLE_FORMAT_OPTS: Lazy<FormatOptions> = Lazy::new(|| {
-    FormatOptions::default()
-        .with_display_error(false)
-        .with_null("NULL")
-});
+/// Options for how values are displayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// Text shown for null values.
+    pub null: String,
+    /// Maximum width of a value before it's truncated with an ellipsis.
+    ///
+    /// Values may be truncated further to fit the table in its max width.
+    pub max_cell_width: Option<usize>,
+    /// Alignment of values by the category of their type, overriding the
+    /// default alignment for the category.
+    pub alignments: BTreeMap<TypeCategory, Alignment>,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        DisplayOptions {
+            null: "NULL".to_string(),
+            max_cell_width: None,
+            alignments: BTreeMap::new(),
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// Get the alignment for values of a type.
+    pub fn alignment(&self, dtype: &DataType) -> Alignment {
+        let category = TypeCategory::of(dtype);
+        self.alignments
+            .get(&category)
+            .copied()
+            .unwrap_or_else(|| category.default_alignment())
+    }
+
+    fn format_options(&self) -> FormatOptions<'_> {
+        FormatOptions::default()
+            .with_display_error(false)
+            .with_null(&self.null)
+    }
+}
+
+/// Categories of types that can be aligned separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TypeCategory {
+    Numeric,
+    Text,
+    Temporal,
+    Boolean,
+    Other,
+}
+
+impl TypeCategory {
+    pub const ALL: [TypeCategory; 5] = [
+        TypeCategory::Numeric,
+        TypeCategory::Text,
+        TypeCategory::Temporal,
+        TypeCategory::Boolean,
+        TypeCategory::Other,
+    ];
+
+    pub fn of(dtype: &DataType) -> Self {
+        match dtype {
+            dtype if dtype.is_numeric() => TypeCategory::Numeric,
+            dtype if dtype.is_temporal() => TypeCategory::Temporal,
+            DataType::Utf8 | DataType::LargeUtf8 => TypeCategory::Text,
+            DataType::Boolean => TypeCategory::Boolean,
+            _ => TypeCategory::Other,
+        }
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            TypeCategory::Numeric => "numeric",
+            TypeCategory::Text => "text",
+            TypeCategory::Temporal => "temporal",
+            TypeCategory::Boolean => "boolean",
+            TypeCategory::Other => "other",
+        }
+    }
+
+    const fn default_alignment(&self) -> Alignment {
+        match self {
+            TypeCategory::Numeric => Alignment::Right,
+            _ => Alignment::Left,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Right,
+    Center,
+}
+
+impl Alignment {
+    pub const ALL: [Alignment; 3] = [Alignment::Left, Alignment::Right, Alignment::Center];
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Alignment::Left => "left",
+            Alignment::Right => "right",
+            Alignment::Center => "center",
+        }
+    }
+}
+
+impl From<Alignment> for CellAlignment {
+    fn from(value: Alignment) -> Self {
+        match value {
+            Alignment::Left => CellAlignment::Left,
+            Alignment::Right => CellAlignment::Right,
+            Alignment::Center => CellAlignment::Center,
+        }
+    }
+}
 
 /// Pretty format record batches.
 pub fn pretty_format_batches(
@@ -29,7 +140,24 @@ pub fn pretty_format_batches(
     max_width: Option<usize>,
     max_rows: Option<usize>,
 ) -> Result<impl fmt::Display, ArrowError> {
-    PrettyTable::try_new(schema, batches, max_width, max_rows)
+    pretty_format_batches_with_options(
+        schema,
+        batches,
+        max_width,
+        max_rows,
+        &DisplayOptions::default(),
+    )
+}
+
+/// Pretty format record batches, displaying values according to `opts`.
+pub fn pretty_format_batches_with_options(
+    schema: &Schema,
+    batches: &[RecordBatch],
+    max_width: Option<usize>,
+    max_rows: Option<usize>,
+    opts: &DisplayOptions,
+) -> Result<impl fmt::Display, ArrowError> {
+    PrettyTable::try_new(schema, batches, max_width, max_rows, opts)
 }
 
 /// Get the terminal's width in characters.
@@ -77,6 +205,7 @@ impl PrettyTable {
         batches: &[RecordBatch],
         max_width: Option<usize>,
         max_rows: Option<usize>,
+        opts: &DisplayOptions,
     ) -> Result<Self, ArrowError> {
         let mut table = Table::new();
         table.load_preset(DEFAULT_PRESET);
@@ -86,7 +215,7 @@ impl PrettyTable {
         let col_headers: Vec<_> = schema
             .fields
             .iter()
-            .map(|f| ColumnHeader::from_field(f))
+            .map(|f| ColumnHeader::from_field(f, opts))
             .collect();
 
         // Try to get some of the values from the first batch. This will be used
@@ -96,7 +225,7 @@ impl PrettyTable {
                 .slice(0, std::cmp::min(NUM_VALS_FOR_AVG, batch.num_rows()))
                 .columns()
                 .iter()
-                .map(|col| ColumnValues::try_new_from_array(col, None))
+                .map(|col| ColumnValues::try_new_from_array(col, opts.max_cell_width, opts))
                 .collect::<Result<_, _>>()?,
             None => vec![ColumnValues::default(); col_headers.len()],
         };
@@ -167,7 +296,7 @@ impl PrettyTable {
             if tbl_rows < row_split_idx {
                 let remaining_rows = row_split_idx - tbl_rows;
                 let rows_to_take = remaining_rows.min(num_rows);
-                process_batch(&mut table, &format, batch, 0..rows_to_take)?;
+                process_batch(&mut table, &format, batch, 0..rows_to_take, opts)?;
                 tbl_rows += rows_to_take;
             }
 
@@ -192,7 +321,7 @@ impl PrettyTable {
                     (0..num_rows, num_rows)
                 };
 
-                process_batch(&mut table, &format, batch, row_range)?;
+                process_batch(&mut table, &format, batch, row_range, opts)?;
                 tbl_rows += rows_to_take;
             }
 
@@ -300,17 +429,11 @@ pub fn fmt_dtype(dtype: &DataType) -> String {
 }
 
 impl ColumnHeader {
-    fn from_field(f: &Field) -> Self {
-        let alignment = if f.data_type().is_numeric() {
-            CellAlignment::Right
-        } else {
-            CellAlignment::Left
-        };
-
+    fn from_field(f: &Field, opts: &DisplayOptions) -> Self {
         ColumnHeader {
             name: f.name().clone(),
             data_type: fmt_dtype(f.data_type()),
-            alignment,
+            alignment: opts.alignment(f.data_type()).into(),
         }
     }
 
@@ -491,8 +614,8 @@ impl TableFormat {
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
-struct ColumnValues {
-    vals: Vec<String>,
+pub(crate) struct ColumnValues {
+    pub(crate) vals: Vec<String>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -503,8 +626,12 @@ struct ColumnWidthSizeStats {
 }
 
 impl ColumnValues {
-    fn try_new_from_array(col: &dyn Array, trunc: Option<usize>) -> Result<Self, ArrowError> {
-        let formatter = ArrayFormatter::try_new(col, &TABLE_FORMAT_OPTS)?;
+    pub(crate) fn try_new_from_array(
+        col: &dyn Array,
+        trunc: Option<usize>,
+        opts: &DisplayOptions,
+    ) -> Result<Self, ArrowError> {
+        let formatter = ArrayFormatter::try_new(col, &opts.format_options())?;
         let vals = match col.data_type() {
             DataType::Float64 => {
                 // formatting float arrays
@@ -515,7 +642,10 @@ impl ColumnValues {
                     .values();
 
                 (0..floats_array.len())
-                    .map(|idx| Ok(fmt_floats(floats_array.get(idx))))
+                    .map(|idx| match col.is_null(idx) {
+                        true => Ok(opts.null.clone()),
+                        false => Ok(fmt_floats(floats_array.get(idx))),
+                    })
                     .collect::<Result<Vec<_>, ArrowError>>()?
             }
             _ => {
@@ -581,6 +711,7 @@ fn process_batch(
     format: &TableFormat,
     batch: &RecordBatch,
     rows: Range<usize>,
+    opts: &DisplayOptions,
 ) -> Result<(), ArrowError> {
     if rows.is_empty() {
         return Ok(());
@@ -594,7 +725,11 @@ fn process_batch(
         if format.is_elided[idx] {
             continue;
         }
-        let vals = ColumnValues::try_new_from_array(col, format.widths[idx])?;
+        let trunc = match (format.widths[idx], opts.max_cell_width) {
+            (Some(width), Some(max)) => Some(width.min(max)),
+            (width, max) => width.or(max),
+        };
+        let vals = ColumnValues::try_new_from_array(col, trunc, opts)?;
         col_vals.push(vals);
     }
 
@@ -723,7 +858,8 @@ mod tests {
         }
 
         for tc in test_cases {
-            let tc_result = ColumnValues::try_new_from_array(tc.input, tc.truncate);
+            let tc_result =
+                ColumnValues::try_new_from_array(tc.input, tc.truncate, &DisplayOptions::default());
 
             let result: ColumnValues = match tc_result {
                 Ok(x) => x,
@@ -1214,4 +1350,42 @@ mod tests {
 
         assert_eq_print(expected.join("\n"), table.to_string())
     }
+
+    #[test]
+    fn display_options() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Utf8, true),
+            Field::new("b", DataType::Int32, true),
+        ]));
+
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![Some("abcdefgh"), None])),
+                Arc::new(Int32Array::from(vec![None, Some(5)])),
+            ],
+        )
+        .unwrap();
+
+        let opts = DisplayOptions {
+            null: "∅".to_string(),
+            max_cell_width: Some(4),
+            alignments: BTreeMap::from([(TypeCategory::Numeric, Alignment::Left)]),
+        };
+        let table =
+            pretty_format_batches_with_options(&schema, &[batch], None, None, &opts).unwrap();
+
+        let expected = [
+            "┌──────┬───────┐",
+            "│ a    │ b     │",
+            "│ ──   │ ──    │",
+            "│ Utf8 │ Int32 │",
+            "╞══════╪═══════╡",
+            "│ abc… │ ∅     │",
+            "│ ∅    │ 5     │",
+            "└──────┴───────┘",
+        ];
+
+        assert_eq_print(expected.join("\n"), table.to_string())
+    }
 }
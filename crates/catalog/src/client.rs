@@ -51,22 +51,29 @@
 
 use crate::errors::{CatalogError, Result};
 use protogen::gen::metastore::service::metastore_service_client::MetastoreServiceClient;
-use protogen::gen::metastore::service::{FetchCatalogRequest, MutateRequest};
-use protogen::metastore::types::{catalog::CatalogState, service::Mutation};
+use protogen::gen::metastore::service::{
+    FetchCatalogRequest, FetchRolePasswordsRequest, MutateRequest,
+};
+use protogen::metastore::types::catalog::{CatalogState, RolePassword};
+use protogen::metastore::types::service::Mutation;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use telemetry::metrics::METRICS;
 use tokio::sync::RwLock;
 use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinHandle;
 use tonic::transport::Channel;
-use tracing::{debug, debug_span, error, warn, Instrument};
+use tracing::{debug, debug_span, error, info_span, warn, Instrument};
 use uuid::Uuid;
 
 /// Number of outstanding requests per database.
 const PER_DATABASE_BUFFER: usize = 128;
 
+/// Name of the cache of database workers used when reporting metrics.
+const WORKER_CACHE_NAME: &str = "metastore_client_worker";
+
 /// Configuration values used when starting up a worker.
 #[derive(Debug, Clone, Copy)]
 pub struct MetastoreClientConfig {
@@ -125,6 +132,17 @@ impl MetastoreClientHandle {
             .and_then(std::convert::identity) // Flatten
     }
 
+    /// Fetch the password verifiers of all roles.
+    ///
+    /// These aren't part of the catalog state, and should only be used for
+    /// authenticating connections.
+    pub async fn get_role_passwords(&self) -> Result<Vec<RolePassword>> {
+        let (tx, rx) = oneshot::channel();
+        self.send(ClientRequest::FetchRolePasswords { response: tx }, rx)
+            .await
+            .and_then(std::convert::identity) // Flatten
+    }
+
     /// Try to run mutations against the Metastore catalog.
     ///
     /// The version provided should be the version of the catalog state that the
@@ -150,7 +168,10 @@ impl MetastoreClientHandle {
     async fn send<R>(&self, req: ClientRequest, rx: oneshot::Receiver<R>) -> Result<R> {
         let tag = req.tag();
         let result = match self.send.try_send(req) {
-            Ok(_) => match rx.await {
+            Ok(_) => match rx
+                .instrument(info_span!("metastore_request", request = tag))
+                .await
+            {
                 Ok(result) => Ok(result),
                 Err(_) => Err(CatalogError::new(format!("response channel closed: {tag}"))),
             },
@@ -194,6 +215,11 @@ enum ClientRequest {
 
     /// Refresh the cached catalog state from persistence for some database
     RefreshCachedState { response: oneshot::Sender<()> },
+
+    /// Fetch the password verifiers of all roles. These are never cached.
+    FetchRolePasswords {
+        response: oneshot::Sender<Result<Vec<RolePassword>>>,
+    },
 }
 
 impl ClientRequest {
@@ -203,6 +229,7 @@ impl ClientRequest {
             ClientRequest::GetCachedState { .. } => "get_cached_state",
             ClientRequest::ExecMutations { .. } => "exec_mutations",
             ClientRequest::RefreshCachedState { .. } => "refresh_cached_state",
+            ClientRequest::FetchRolePasswords { .. } => "fetch_role_passwords",
         }
     }
 }
@@ -258,6 +285,7 @@ impl MetastoreClientSupervisor {
             let workers = self.workers.read().await;
             match workers.get(&db_id) {
                 Some(worker) if !worker.is_finished() => {
+                    METRICS.cache_hits.with_label(WORKER_CACHE_NAME).inc();
                     return Ok(MetastoreClientHandle {
                         version_hint: worker.version_hint.clone(),
                         send: worker.send.clone(),
@@ -269,6 +297,7 @@ impl MetastoreClientSupervisor {
         }
 
         // Slow path, need to initialize a worker.
+        METRICS.cache_misses.with_label(WORKER_CACHE_NAME).inc();
         let (worker, send) = StatefulWorker::init(db_id, self.client.clone()).await?;
 
         let mut workers = self.workers.write().await;
@@ -295,6 +324,23 @@ impl MetastoreClientSupervisor {
         Ok(MetastoreClientHandle { version_hint, send })
     }
 
+    /// Stop all database workers, waiting until they finish.
+    ///
+    /// Clients for the stopped workers will error. New clients may still be
+    /// initialized afterwards, so this should only be called once all sessions
+    /// have ended.
+    pub async fn shutdown(&self) {
+        let workers: Vec<_> = {
+            let mut workers = self.workers.write().await;
+            workers.drain().collect()
+        };
+        for (db_id, worker) in workers {
+            worker.handle.abort();
+            let _ = worker.handle.await;
+            debug!(%db_id, "stopped database worker");
+        }
+    }
+
     /// Terminate a worker, waiting until the worker thread finishes.
     ///
     /// Currently only used to test that we can start up a new worker for the
@@ -368,11 +414,14 @@ impl StatefulWorker {
         db_id: Uuid,
         mut client: MetastoreServiceClient<Channel>,
     ) -> Result<(StatefulWorker, mpsc::Sender<ClientRequest>)> {
+        let start = Instant::now();
         let resp = client
             .fetch_catalog(tonic::Request::new(FetchCatalogRequest {
                 db_id: db_id.into_bytes().to_vec(),
             }))
-            .await?;
+            .await;
+        observe_rpc("fetch_catalog", start);
+        let resp = resp?;
         let resp = resp.into_inner();
 
         let catalog: CatalogState = match resp.catalog {
@@ -468,15 +517,19 @@ impl StatefulWorker {
                     .map(|m| m.try_into())
                     .collect::<Result<_, _>>();
                 let result = match result {
-                    Ok(mutations) => self
-                        .client
-                        .mutate_catalog(tonic::Request::new(MutateRequest {
-                            db_id: self.db_id.into_bytes().to_vec(),
-                            catalog_version: version,
-                            mutations,
-                        }))
-                        .await
-                        .map_err(CatalogError::from),
+                    Ok(mutations) => {
+                        let start = Instant::now();
+                        let resp = self
+                            .client
+                            .mutate_catalog(tonic::Request::new(MutateRequest {
+                                db_id: self.db_id.into_bytes().to_vec(),
+                                catalog_version: version,
+                                mutations,
+                            }))
+                            .await;
+                        observe_rpc("mutate_catalog", start);
+                        resp.map_err(CatalogError::from)
+                    }
                     Err(e) => Err(CatalogError::new(e.to_string())),
                 };
 
@@ -510,18 +563,40 @@ impl StatefulWorker {
                     error!("failed to respond to refresh cached catalog state request");
                 }
             }
+            ClientRequest::FetchRolePasswords { response } => {
+                let start = Instant::now();
+                let resp = self
+                    .client
+                    .fetch_role_passwords(tonic::Request::new(FetchRolePasswordsRequest {
+                        db_id: self.db_id.into_bytes().to_vec(),
+                    }))
+                    .await;
+                observe_rpc("fetch_role_passwords", start);
+                let result = resp.map_err(CatalogError::from).map(|resp| {
+                    resp.into_inner()
+                        .role_passwords
+                        .into_iter()
+                        .map(Into::into)
+                        .collect()
+                });
+                if response.send(result).is_err() {
+                    error!("failed to send role passwords");
+                }
+            }
         }
     }
 
     /// Fetch the latest catalog from Metastore, updating this local catalog cache.
     async fn fetch(&mut self) {
-        match self
+        let start = Instant::now();
+        let resp = self
             .client
             .fetch_catalog(tonic::Request::new(FetchCatalogRequest {
                 db_id: self.db_id.into_bytes().to_vec(),
             }))
-            .await
-        {
+            .await;
+        observe_rpc("fetch_catalog", start);
+        match resp {
             Ok(resp) => {
                 let resp = resp.into_inner();
                 let catalog: CatalogState = match resp.catalog {
@@ -551,6 +626,14 @@ impl StatefulWorker {
     }
 }
 
+/// Record the latency of a metastore RPC.
+fn observe_rpc(method: &str, start: Instant) {
+    METRICS
+        .metastore_rpc_duration
+        .with_label(method)
+        .observe_duration(start.elapsed());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -582,11 +665,13 @@ mod tests {
             .try_mutate(
                 state.version,
                 vec![Mutation::CreateView(CreateView {
+                    database: "default".to_string(),
                     schema: "public".to_string(),
                     name: "mario".to_string(),
                     sql: "select 1".to_string(),
                     or_replace: false,
                     columns: Vec::new(),
+                    dependencies: Vec::new(),
                 })],
             )
             .await
@@ -616,6 +701,7 @@ mod tests {
             .try_mutate(
                 s1.version,
                 vec![Mutation::CreateSchema(CreateSchema {
+                    database: "default".to_string(),
                     name: "wario".to_string(),
                     if_not_exists: false,
                 })],
@@ -627,6 +713,7 @@ mod tests {
         c2.try_mutate(
             s2.version,
             vec![Mutation::CreateSchema(CreateSchema {
+                database: "default".to_string(),
                 name: "yoshi".to_string(),
                 if_not_exists: false,
             })],
@@ -646,6 +733,7 @@ mod tests {
             .try_mutate(
                 s2.version,
                 vec![Mutation::CreateSchema(CreateSchema {
+                    database: "default".to_string(),
                     name: "yoshi".to_string(),
                     if_not_exists: false,
                 })],
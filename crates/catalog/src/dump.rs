@@ -0,0 +1,591 @@
+//! Dumping and restoring the user objects in a catalog.
+//!
+//! A dump is the list of mutations required to recreate every user object in
+//! a catalog. Restoring a dump is just a matter of replaying those mutations
+//! against another (likely empty) catalog.
+use crate::errors::{CatalogError, Result};
+use protogen::export::prost::Message;
+use protogen::gen::metastore::service as proto;
+use protogen::metastore::types::catalog::{
+    CatalogEntry, CatalogState, RolePassword, SourceAccessMode,
+};
+use protogen::metastore::types::options::{DatabaseOptions, TableOptions};
+use protogen::metastore::types::service::{
+    AlterDatabase, AlterDatabaseOperation, AlterRole, AlterRoleOperation, AlterSchema,
+    AlterSchemaOperation, AlterTable, AlterTableOperation, CommentOn, CreateCredentials,
+    CreateDatabase, CreateExternalDatabase, CreateExternalTable, CreateSchema, CreateTable,
+    CreateTunnel, CreateView, Mutation, UpdateTableStatistics,
+};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{aead, pbkdf2};
+use std::num::NonZeroU32;
+
+/// Options to use when dumping a catalog.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DumpOptions {
+    /// Include credentials and role passwords in the dump.
+    ///
+    /// Credentials are written to the dump as is, so this is opt-in and the
+    /// dump should be encrypted when encoding it (see [`encode_dump`]). Role
+    /// passwords are written as their SCRAM-SHA-256 verifiers.
+    pub include_credentials: bool,
+}
+
+/// Create the mutations required to recreate all user objects in the catalog.
+///
+/// Builtin and temporary objects are skipped. Native tables are dumped as
+/// their definition only since their data lives outside of the catalog.
+///
+/// Mutations are ordered such that objects are always created after the
+/// objects they depend on (e.g. tunnels before the databases using them).
+///
+/// Role passwords aren't part of the catalog state and are passed in
+/// separately, they're only dumped when including credentials.
+pub fn dump_catalog(
+    state: &CatalogState,
+    role_passwords: &[RolePassword],
+    opts: DumpOptions,
+) -> Vec<Mutation> {
+    // Sort by oid so that dumps are deterministic.
+    let mut entries: Vec<_> = state
+        .entries
+        .iter()
+        .filter(|(_, ent)| {
+            let meta = ent.get_meta();
+            !meta.builtin && !meta.is_temp
+        })
+        .collect();
+    entries.sort_by_key(|(oid, _)| **oid);
+
+    let name_of = |oid: u32| {
+        state
+            .entries
+            .get(&oid)
+            .map(|ent| ent.get_meta().name.clone())
+    };
+    // Get the database and schema names for an object in a schema.
+    let schema_path_of = |schema_oid: u32| {
+        let schema = state.entries.get(&schema_oid)?.get_meta();
+        Some((name_of(schema.parent)?, schema.name.clone()))
+    };
+
+    let mut tunnels = Vec::new();
+    let mut credentials = Vec::new();
+    let mut databases = Vec::new();
+    let mut schemas = Vec::new();
+    let mut tables = Vec::new();
+    let mut views = Vec::new();
+    // Comments are set once all objects exist.
+    let mut comments = Vec::new();
+
+    for (_, ent) in entries {
+        match ent {
+            CatalogEntry::Tunnel(tunnel) => {
+                tunnels.push(Mutation::CreateTunnel(CreateTunnel {
+                    name: tunnel.meta.name.clone(),
+                    options: tunnel.options.clone(),
+                    if_not_exists: false,
+                }));
+            }
+            CatalogEntry::Credentials(creds) if opts.include_credentials => {
+                credentials.push(Mutation::CreateCredentials(CreateCredentials {
+                    name: creds.meta.name.clone(),
+                    options: creds.options.clone(),
+                    comment: creds.comment.clone(),
+                    or_replace: false,
+                }));
+            }
+            CatalogEntry::Credentials(_) => (),
+            CatalogEntry::Database(db) if matches!(db.options, DatabaseOptions::Internal(_)) => {
+                databases.push(Mutation::CreateDatabase(CreateDatabase {
+                    name: db.meta.name.clone(),
+                    if_not_exists: false,
+                }));
+            }
+            CatalogEntry::Database(db) => {
+                databases.push(Mutation::CreateExternalDatabase(CreateExternalDatabase {
+                    name: db.meta.name.clone(),
+                    options: db.options.clone(),
+                    if_not_exists: false,
+                    tunnel: db.tunnel_id.and_then(name_of),
+                }));
+                // Databases are created as read only.
+                if db.access_mode != SourceAccessMode::ReadOnly {
+                    databases.push(Mutation::AlterDatabase(AlterDatabase {
+                        name: db.meta.name.clone(),
+                        operation: AlterDatabaseOperation::SetAccessMode {
+                            access_mode: db.access_mode,
+                        },
+                    }));
+                }
+            }
+            CatalogEntry::Schema(schema) => {
+                let database = match name_of(schema.meta.parent) {
+                    Some(database) => database,
+                    None => continue,
+                };
+                schemas.push(Mutation::CreateSchema(CreateSchema {
+                    database: database.clone(),
+                    name: schema.meta.name.clone(),
+                    // New databases are created with a default schema which
+                    // will also be in the dump.
+                    if_not_exists: true,
+                }));
+                if !schema.tags.is_empty() {
+                    schemas.push(Mutation::AlterSchema(AlterSchema {
+                        database: database.clone(),
+                        name: schema.meta.name.clone(),
+                        operation: AlterSchemaOperation::SetTags {
+                            tags: schema.tags.clone(),
+                        },
+                    }));
+                }
+                if let Some(comment) = &schema.meta.description {
+                    comments.push(Mutation::CommentOn(CommentOn {
+                        database,
+                        schema: schema.meta.name.clone(),
+                        name: None,
+                        column: None,
+                        comment: Some(comment.clone()),
+                    }));
+                }
+            }
+            CatalogEntry::Table(table) => {
+                let (database, schema) = match schema_path_of(table.meta.parent) {
+                    Some(path) => path,
+                    None => continue,
+                };
+                match &table.options {
+                    // Only the definition of native tables is dumped, the
+                    // table is empty once restored.
+                    TableOptions::Internal(options) => {
+                        tables.push(Mutation::CreateTable(CreateTable {
+                            database: database.clone(),
+                            schema: schema.clone(),
+                            name: table.meta.name.clone(),
+                            options: options.clone(),
+                            if_not_exists: false,
+                            or_replace: false,
+                        }));
+                    }
+                    options => {
+                        tables.push(Mutation::CreateExternalTable(CreateExternalTable {
+                            database: database.clone(),
+                            schema: schema.clone(),
+                            name: table.meta.name.clone(),
+                            options: options.clone(),
+                            or_replace: false,
+                            if_not_exists: false,
+                            tunnel: table.tunnel_id.and_then(name_of),
+                        }));
+                    }
+                }
+                if let Some(statistics) = state.table_statistics.get(&table.meta.id) {
+                    tables.push(Mutation::UpdateTableStatistics(UpdateTableStatistics {
+                        database: database.clone(),
+                        schema: schema.clone(),
+                        name: table.meta.name.clone(),
+                        statistics: statistics.clone(),
+                    }));
+                }
+                if let Some(cached) = state.table_schemas.get(&table.meta.id) {
+                    tables.push(Mutation::AlterTable(AlterTable {
+                        database: database.clone(),
+                        schema: schema.clone(),
+                        name: table.meta.name.clone(),
+                        operation: AlterTableOperation::RefreshSchema {
+                            schema: cached.clone(),
+                        },
+                    }));
+                }
+                if !table.tags.is_empty() {
+                    tables.push(Mutation::AlterTable(AlterTable {
+                        database: database.clone(),
+                        schema: schema.clone(),
+                        name: table.meta.name.clone(),
+                        operation: AlterTableOperation::SetTags {
+                            tags: table.tags.clone(),
+                        },
+                    }));
+                }
+                let table_comment = table.meta.description.as_ref().map(|c| (None, c));
+                let column_comments = table
+                    .column_comments
+                    .iter()
+                    .map(|(column, c)| (Some(column), c));
+                for (column, comment) in table_comment.into_iter().chain(column_comments) {
+                    comments.push(Mutation::CommentOn(CommentOn {
+                        database: database.clone(),
+                        schema: schema.clone(),
+                        name: Some(table.meta.name.clone()),
+                        column: column.cloned(),
+                        comment: Some(comment.clone()),
+                    }));
+                }
+                // External tables are created as read only.
+                if table.meta.external && table.access_mode != SourceAccessMode::ReadOnly {
+                    tables.push(Mutation::AlterTable(AlterTable {
+                        database,
+                        schema,
+                        name: table.meta.name.clone(),
+                        operation: AlterTableOperation::SetAccessMode {
+                            access_mode: table.access_mode,
+                        },
+                    }));
+                }
+            }
+            CatalogEntry::View(view) => {
+                let (database, schema) = match schema_path_of(view.meta.parent) {
+                    Some(path) => path,
+                    None => continue,
+                };
+                if let Some(comment) = &view.meta.description {
+                    comments.push(Mutation::CommentOn(CommentOn {
+                        database: database.clone(),
+                        schema: schema.clone(),
+                        name: Some(view.meta.name.clone()),
+                        column: None,
+                        comment: Some(comment.clone()),
+                    }));
+                }
+                views.push(Mutation::CreateView(CreateView {
+                    database,
+                    schema,
+                    name: view.meta.name.clone(),
+                    sql: view.sql.clone(),
+                    or_replace: false,
+                    columns: view.columns.clone(),
+                    // Oids are different in the restored catalog.
+                    dependencies: Vec::new(),
+                }));
+            }
+            // User defined functions aren't a thing yet.
+            CatalogEntry::Function(_) => (),
+        }
+    }
+
+    let mut session_var_defaults = Vec::new();
+    for default in &state.session_var_defaults {
+        let database = match default.database_id {
+            Some(oid) => match name_of(oid) {
+                Some(name) => Some(name),
+                None => continue,
+            },
+            None => None,
+        };
+        let mutation = match (&default.role, database) {
+            (Some(role), database) => Mutation::AlterRole(AlterRole {
+                name: role.clone(),
+                database,
+                operation: AlterRoleOperation::SetVariable {
+                    variable: default.name.clone(),
+                    value: default.value.clone(),
+                },
+            }),
+            (None, Some(database)) => Mutation::AlterDatabase(AlterDatabase {
+                name: database,
+                operation: AlterDatabaseOperation::SetVariable {
+                    variable: default.name.clone(),
+                    value: default.value.clone(),
+                },
+            }),
+            (None, None) => continue,
+        };
+        session_var_defaults.push(mutation);
+    }
+
+    let mut password_mutations = Vec::new();
+    if opts.include_credentials {
+        for password in role_passwords {
+            password_mutations.push(Mutation::AlterRole(AlterRole {
+                name: password.role.clone(),
+                database: None,
+                operation: AlterRoleOperation::SetPassword {
+                    scram_verifier: Some(password.scram_verifier.clone()),
+                },
+            }));
+        }
+    }
+
+    let read_only_roles: Vec<_> = state
+        .read_only_roles
+        .iter()
+        .map(|role| {
+            Mutation::AlterRole(AlterRole {
+                name: role.clone(),
+                database: None,
+                operation: AlterRoleOperation::SetReadOnly { read_only: true },
+            })
+        })
+        .collect();
+
+    [
+        tunnels,
+        credentials,
+        databases,
+        schemas,
+        tables,
+        views,
+        comments,
+        session_var_defaults,
+        password_mutations,
+        read_only_roles,
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Number of PBKDF2 iterations used to derive the key for encrypted dumps.
+const DUMP_KEY_ITERATIONS: u32 = 600_000;
+
+/// Length of the salt used to derive the key for encrypted dumps.
+const DUMP_SALT_LEN: usize = 16;
+
+/// Encode mutations produced by [`dump_catalog`] to bytes.
+///
+/// If an encryption key is provided, the dump is encrypted with a key derived
+/// from it. The same key is needed to decode the dump.
+pub fn encode_dump(mutations: Vec<Mutation>, encryption_key: Option<&str>) -> Result<Vec<u8>> {
+    let dump = proto::CatalogDump {
+        mutations: mutations
+            .into_iter()
+            .map(|m| m.try_into())
+            .collect::<Result<_, _>>()?,
+        encrypted: None,
+    };
+    let dump = match encryption_key {
+        Some(key) => proto::CatalogDump {
+            mutations: Vec::new(),
+            encrypted: Some(encrypt_dump(dump.encode_to_vec(), key)?),
+        },
+        None => dump,
+    };
+    Ok(dump.encode_to_vec())
+}
+
+/// Decode a dump produced by [`encode_dump`] back into a list of mutations.
+pub fn decode_dump(buf: &[u8], encryption_key: Option<&str>) -> Result<Vec<Mutation>> {
+    let mut dump = proto::CatalogDump::decode(buf)
+        .map_err(|e| CatalogError::new(format!("failed to decode catalog dump: {e}")))?;
+    if let Some(encrypted) = dump.encrypted.take() {
+        let key = encryption_key.ok_or_else(|| {
+            CatalogError::new("catalog dump is encrypted, an encryption key is required")
+        })?;
+        let plaintext = decrypt_dump(encrypted, key)?;
+        dump = proto::CatalogDump::decode(plaintext.as_slice())
+            .map_err(|e| CatalogError::new(format!("failed to decode catalog dump: {e}")))?;
+    }
+    Ok(dump
+        .mutations
+        .into_iter()
+        .map(Mutation::try_from)
+        .collect::<Result<_, _>>()?)
+}
+
+fn encrypt_dump(mut plaintext: Vec<u8>, key: &str) -> Result<proto::EncryptedCatalogDump> {
+    let rng = SystemRandom::new();
+    let mut salt = [0; DUMP_SALT_LEN];
+    let mut nonce = [0; aead::NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| CatalogError::new("failed to generate random bytes"))?;
+
+    let key = derive_dump_key(key, &salt, DUMP_KEY_ITERATIONS)?;
+    key.seal_in_place_append_tag(
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::empty(),
+        &mut plaintext,
+    )
+    .map_err(|_| CatalogError::new("failed to encrypt catalog dump"))?;
+
+    Ok(proto::EncryptedCatalogDump {
+        salt: salt.to_vec(),
+        iterations: DUMP_KEY_ITERATIONS,
+        nonce: nonce.to_vec(),
+        ciphertext: plaintext,
+    })
+}
+
+fn decrypt_dump(encrypted: proto::EncryptedCatalogDump, key: &str) -> Result<Vec<u8>> {
+    let nonce = aead::Nonce::try_assume_unique_for_key(&encrypted.nonce)
+        .map_err(|_| CatalogError::new("invalid nonce in encrypted catalog dump"))?;
+    let key = derive_dump_key(key, &encrypted.salt, encrypted.iterations)?;
+
+    let mut ciphertext = encrypted.ciphertext;
+    let plaintext = key
+        .open_in_place(nonce, aead::Aad::empty(), &mut ciphertext)
+        .map_err(|_| {
+            CatalogError::new("failed to decrypt catalog dump, the encryption key may be wrong")
+        })?;
+    Ok(plaintext.to_vec())
+}
+
+fn derive_dump_key(key: &str, salt: &[u8], iterations: u32) -> Result<aead::LessSafeKey> {
+    let iterations = NonZeroU32::new(iterations)
+        .ok_or_else(|| CatalogError::new("invalid iterations in encrypted catalog dump"))?;
+    let mut derived = [0; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        key.as_bytes(),
+        &mut derived,
+    );
+    let key = aead::UnboundKey::new(&aead::AES_256_GCM, &derived)
+        .map_err(|_| CatalogError::new("failed to create catalog dump encryption key"))?;
+    Ok(aead::LessSafeKey::new(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{MetastoreClientSupervisor, DEFAULT_METASTORE_CLIENT_CONFIG};
+    use datafusion::arrow::datatypes::DataType;
+    use metastore::local::start_inprocess;
+    use object_store::memory::InMemory;
+    use protogen::metastore::types::options::{
+        CredentialsOptions, CredentialsOptionsDebug, InternalColumnDefinition, TableOptionsInternal,
+    };
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn dump_and_restore() {
+        let client = start_inprocess(Arc::new(InMemory::new())).await.unwrap();
+        let supervisor = MetastoreClientSupervisor::new(client, DEFAULT_METASTORE_CLIENT_CONFIG);
+
+        let source = supervisor.init_client(Uuid::new_v4()).await.unwrap();
+        let state = source.get_cached_state().await.unwrap();
+        let state = source
+            .try_mutate(
+                state.version,
+                vec![
+                    Mutation::CreateDatabase(CreateDatabase {
+                        name: "forest".to_string(),
+                        if_not_exists: false,
+                    }),
+                    Mutation::CreateView(CreateView {
+                        database: "forest".to_string(),
+                        schema: "public".to_string(),
+                        name: "toad".to_string(),
+                        sql: "select 2".to_string(),
+                        or_replace: false,
+                        columns: Vec::new(),
+                        dependencies: Vec::new(),
+                    }),
+                    Mutation::CreateSchema(CreateSchema {
+                        database: "default".to_string(),
+                        name: "mushroom".to_string(),
+                        if_not_exists: false,
+                    }),
+                    Mutation::CreateTable(CreateTable {
+                        database: "default".to_string(),
+                        schema: "mushroom".to_string(),
+                        name: "spores".to_string(),
+                        options: TableOptionsInternal {
+                            columns: vec![InternalColumnDefinition {
+                                name: "count".to_string(),
+                                nullable: true,
+                                arrow_type: DataType::Int64,
+                            }],
+                        },
+                        if_not_exists: false,
+                        or_replace: false,
+                    }),
+                    Mutation::AlterSchema(AlterSchema {
+                        database: "default".to_string(),
+                        name: "mushroom".to_string(),
+                        operation: AlterSchemaOperation::SetTags {
+                            tags: BTreeMap::from([("team".to_string(), "fungi".to_string())]),
+                        },
+                    }),
+                    Mutation::CreateView(CreateView {
+                        database: "default".to_string(),
+                        schema: "mushroom".to_string(),
+                        name: "kingdom".to_string(),
+                        sql: "select 1".to_string(),
+                        or_replace: false,
+                        columns: Vec::new(),
+                        dependencies: Vec::new(),
+                    }),
+                    Mutation::CommentOn(CommentOn {
+                        database: "default".to_string(),
+                        schema: "mushroom".to_string(),
+                        name: None,
+                        column: None,
+                        comment: Some("fungi".to_string()),
+                    }),
+                    Mutation::CommentOn(CommentOn {
+                        database: "default".to_string(),
+                        schema: "mushroom".to_string(),
+                        name: Some("kingdom".to_string()),
+                        column: None,
+                        comment: Some("all of them".to_string()),
+                    }),
+                    Mutation::CreateCredentials(CreateCredentials {
+                        name: "secret".to_string(),
+                        options: CredentialsOptions::Debug(CredentialsOptionsDebug {
+                            table_type: "never_ending".to_string(),
+                        }),
+                        comment: String::new(),
+                        or_replace: false,
+                    }),
+                    Mutation::AlterDatabase(AlterDatabase {
+                        name: "forest".to_string(),
+                        operation: AlterDatabaseOperation::SetVariable {
+                            variable: "search_path".to_string(),
+                            value: "public".to_string(),
+                        },
+                    }),
+                    Mutation::AlterRole(AlterRole {
+                        name: "sean".to_string(),
+                        database: None,
+                        operation: AlterRoleOperation::SetPassword {
+                            scram_verifier: Some("verifier".to_string()),
+                        },
+                    }),
+                ],
+            )
+            .await
+            .unwrap();
+
+        // Credentials and passwords are opt-in.
+        let passwords = source.get_role_passwords().await.unwrap();
+        let mutations = dump_catalog(&state, &passwords, DumpOptions::default());
+        // Database, two schemas, schema tags, a native table, two views, two
+        // comments, and a session variable default.
+        assert_eq!(10, mutations.len());
+
+        let mutations = dump_catalog(
+            &state,
+            &passwords,
+            DumpOptions {
+                include_credentials: true,
+            },
+        );
+        let buf = encode_dump(mutations.clone(), Some("hunter2")).unwrap();
+        decode_dump(&buf, None).unwrap_err();
+        decode_dump(&buf, Some("hunter3")).unwrap_err();
+        let decoded = decode_dump(&buf, Some("hunter2")).unwrap();
+        assert_eq!(mutations, decoded);
+
+        // Replay into a fresh catalog.
+        let dest = supervisor.init_client(Uuid::new_v4()).await.unwrap();
+        let dest_state = dest.get_cached_state().await.unwrap();
+        let dest_state = dest.try_mutate(dest_state.version, decoded).await.unwrap();
+        let dest_passwords = dest.get_role_passwords().await.unwrap();
+
+        assert_eq!(
+            mutations,
+            dump_catalog(
+                &dest_state,
+                &dest_passwords,
+                DumpOptions {
+                    include_credentials: true,
+                },
+            )
+        );
+    }
+}
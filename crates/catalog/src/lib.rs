@@ -1,5 +1,6 @@
 //! Session catalog definitions and interface to metastore.
 pub mod client;
+pub mod dump;
 pub mod errors;
 pub mod mutator;
 pub mod session_catalog;
@@ -2,24 +2,55 @@ use crate::errors::{CatalogError, Result};
 use protogen::metastore::strategy::ResolveErrorStrategy;
 use protogen::metastore::types::catalog::CatalogState;
 use protogen::metastore::types::service::Mutation;
+use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::debug;
 
 use super::client::MetastoreClientHandle;
 
+/// Max number of times to try applying mutations when the catalog keeps
+/// getting modified by other writers.
+const MAX_MUTATE_ATTEMPTS: u32 = 5;
+
+/// Base delay between attempts, multiplied by the number of attempts made so
+/// far.
+const MUTATE_RETRY_BACKOFF: Duration = Duration::from_millis(20);
+
+/// Observes mutations applied through a mutator.
+pub trait MutationObserver: Sync + Send + fmt::Debug {
+    /// Called once the mutations have been applied, with the resulting
+    /// catalog state.
+    fn observe(&self, mutations: &[Mutation], state: &CatalogState);
+}
+
 /// Wrapper around a metastore client for mutating the catalog.
 #[derive(Clone)]
 pub struct CatalogMutator {
     pub client: Option<MetastoreClientHandle>,
+    observer: Option<Arc<dyn MutationObserver>>,
 }
 
 impl CatalogMutator {
     pub fn empty() -> Self {
-        CatalogMutator { client: None }
+        CatalogMutator {
+            client: None,
+            observer: None,
+        }
     }
 
     pub fn new(client: Option<MetastoreClientHandle>) -> Self {
-        CatalogMutator { client }
+        CatalogMutator {
+            client,
+            observer: None,
+        }
+    }
+
+    /// Notify `observer` of every mutation successfully applied through this
+    /// mutator.
+    pub fn with_observer(mut self, observer: Arc<dyn MutationObserver>) -> Self {
+        self.observer = Some(observer);
+        self
     }
 
     pub fn get_metastore_client(&self) -> Option<&MetastoreClientHandle> {
@@ -31,7 +62,7 @@ impl CatalogMutator {
     /// Errors if the metastore client isn't configured.
     ///
     /// This will retry mutations if we were working with an out of date
-    /// catalog.
+    /// catalog, or if another writer modified the catalog at the same time.
     pub async fn mutate(
         &self,
         catalog_version: u64,
@@ -45,29 +76,49 @@ impl CatalogMutator {
         // Note that when we have transactions, these shouldn't be sent until
         // commit.
         let mutations: Vec<_> = mutations.into_iter().collect();
-        let state = match client.try_mutate(catalog_version, mutations.clone()).await {
-            Ok(state) => state,
-            Err(CatalogError {
-                msg,
-                strategy: Some(ResolveErrorStrategy::FetchCatalogAndRetry),
-            }) => {
-                // Go ahead and refetch the catalog and retry the mutation.
-                //
-                // Note that this relies on metastore _always_ being stricter
-                // when validating mutations. What this means is that retrying
-                // here should be semantically equivalent to manually refreshing
-                // the catalog and rerunning and replanning the query.
-                debug!(error_message = msg, "retrying mutations");
-
-                client.refresh_cached_state().await?;
-                let state = client.get_cached_state().await?;
-                let version = state.version;
-
-                client.try_mutate(version, mutations).await?
+        let mut version = catalog_version;
+        let mut attempts = 1;
+        let state = loop {
+            match client.try_mutate(version, mutations.clone()).await {
+                Ok(state) => break state,
+                Err(CatalogError {
+                    msg,
+                    strategy: Some(ResolveErrorStrategy::FetchCatalogAndRetry),
+                }) => {
+                    if attempts >= MAX_MUTATE_ATTEMPTS {
+                        return Err(CatalogError::new(format!(
+                            "Catalog is being concurrently modified, gave up after {attempts} attempts: {msg}"
+                        )));
+                    }
+
+                    // Go ahead and refetch the catalog and retry the mutation.
+                    //
+                    // Note that this relies on metastore _always_ being
+                    // stricter when validating mutations. What this means is
+                    // that retrying here should be semantically equivalent to
+                    // manually refreshing the catalog and rerunning and
+                    // replanning the query.
+                    debug!(error_message = msg, %attempts, "retrying mutations");
+
+                    // The first retry is usually just catching up to a newer
+                    // catalog. Subsequent retries mean we're racing with other
+                    // writers, back off to give them a chance to finish.
+                    if attempts > 1 {
+                        tokio::time::sleep(MUTATE_RETRY_BACKOFF * attempts).await;
+                    }
+
+                    client.refresh_cached_state().await?;
+                    version = client.get_cached_state().await?.version;
+                    attempts += 1;
+                }
+                Err(e) => return Err(e),
             }
-            Err(e) => return Err(e),
         };
 
+        if let Some(observer) = &self.observer {
+            observer.observe(&mutations, &state);
+        }
+
         Ok(state)
     }
 }
@@ -76,6 +127,7 @@ impl From<MetastoreClientHandle> for CatalogMutator {
     fn from(value: MetastoreClientHandle) -> Self {
         CatalogMutator {
             client: Some(value),
+            observer: None,
         }
     }
 }
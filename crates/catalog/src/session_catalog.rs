@@ -2,13 +2,14 @@ use crate::errors::Result;
 use datafusion::datasource::{MemTable, TableProvider};
 use parking_lot::Mutex;
 use protogen::metastore::types::catalog::{
-    CatalogEntry, CatalogState, CredentialsEntry, DatabaseEntry, DeploymentMetadata, EntryMeta,
-    EntryType, FunctionEntry, FunctionType, SchemaEntry, SourceAccessMode, TableEntry, TunnelEntry,
+    CachedTableSchema, CatalogEntry, CatalogState, CredentialsEntry, DatabaseEntry,
+    DeploymentMetadata, EntryMeta, EntryType, FunctionEntry, FunctionType, SchemaEntry,
+    SessionVarDefault, SourceAccessMode, TableEntry, TableStatistics, TunnelEntry,
 };
 use protogen::metastore::types::options::{
-    InternalColumnDefinition, TableOptions, TableOptionsInternal,
+    DatabaseOptions, InternalColumnDefinition, TableOptions, TableOptionsInternal,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tracing::debug;
 
@@ -41,8 +42,8 @@ pub struct SessionCatalog {
     tunnel_names: HashMap<String, u32>,
     /// Map credentials names to their ids.
     credentials_names: HashMap<String, u32>,
-    /// Map schema names to their ids.
-    schema_names: HashMap<String, u32>,
+    /// Map database IDs to the schemas in the database, keyed by name.
+    schema_names: HashMap<u32, HashMap<String, u32>>,
     /// Map schema IDs to objects in the schema.
     schema_objects: HashMap<u32, SchemaObjects>,
     /// Config for resolving entries.
@@ -82,14 +83,51 @@ impl SessionCatalog {
         &self.state
     }
 
+    /// Get the session variable defaults for a session connecting to a
+    /// database as some role.
+    ///
+    /// Defaults are ordered from most to least specific. A default for both
+    /// the role and database comes first, then defaults for just the role,
+    /// then defaults for just the database.
+    pub fn session_var_defaults(&self, database: &str, role: &str) -> Vec<&SessionVarDefault> {
+        let database_id = match self.database_names.get(database) {
+            Some(id) => *id,
+            None => return Vec::new(),
+        };
+
+        let mut defaults: Vec<_> = self
+            .state
+            .session_var_defaults
+            .iter()
+            .filter(|default| default.applies_to(database_id, role))
+            .collect();
+        defaults.sort_by_key(|default| (default.role.is_none(), default.database_id.is_none()));
+        defaults
+    }
+
+    /// Returns whether the role has been granted read-only access.
+    pub fn is_read_only_role(&self, role: &str) -> bool {
+        self.state.read_only_roles.iter().any(|name| name == role)
+    }
+
+    /// Get the statistics collected for a table, if any.
+    pub fn table_statistics(&self, oid: u32) -> Option<&TableStatistics> {
+        self.state.table_statistics.get(&oid)
+    }
+
+    /// Get the cached inferred schema for an external table, if any.
+    pub fn table_schema(&self, oid: u32) -> Option<&CachedTableSchema> {
+        self.state.table_schemas.get(&oid)
+    }
+
     /// Get a reference to the temporary catalog.
     pub fn get_temp_catalog(&self) -> &TempCatalog {
         &self.temp
     }
 
-    pub fn resolve_table(&self, _database: &str, schema: &str, name: &str) -> Option<&TableEntry> {
-        let schema_id = self.schema_names.get(schema)?;
-        let obj = self.schema_objects.get(schema_id)?;
+    pub fn resolve_table(&self, database: &str, schema: &str, name: &str) -> Option<&TableEntry> {
+        let schema_id = self.get_schema_id(database, schema)?;
+        let obj = self.schema_objects.get(&schema_id)?;
         let obj_id = obj.objects.get(name)?;
 
         let ent = self.state.entries.get(obj_id)?;
@@ -173,16 +211,16 @@ impl SessionCatalog {
         }
     }
 
-    /// Resolve a schema by name.
-    pub fn resolve_schema(&self, name: &str) -> Option<&SchemaEntry> {
+    /// Resolve a schema in a database by name.
+    pub fn resolve_schema(&self, database: &str, name: &str) -> Option<&SchemaEntry> {
         // Similar invariants as `resolve_database`. If we find an entry in the
         // schema map, it must exist in the state and must be a schema.
 
-        let id = self.schema_names.get(name)?;
+        let id = self.get_schema_id(database, name)?;
         let ent = self
             .state
             .entries
-            .get(id)
+            .get(&id)
             .expect("schema name points to invalid id");
 
         match ent {
@@ -222,14 +260,9 @@ impl SessionCatalog {
     /// Resolve an entry by schema name and object name.
     ///
     /// Note that this will never return a schema entry.
-    pub fn resolve_entry(
-        &self,
-        _database: &str,
-        schema: &str,
-        name: &str,
-    ) -> Option<&CatalogEntry> {
-        let schema_id = self.schema_names.get(schema)?;
-        let obj = self.schema_objects.get(schema_id)?;
+    pub fn resolve_entry(&self, database: &str, schema: &str, name: &str) -> Option<&CatalogEntry> {
+        let schema_id = self.get_schema_id(database, schema)?;
+        let obj = self.schema_objects.get(&schema_id)?;
         let obj_id = obj.objects.get(name)?;
 
         let ent = self
@@ -249,6 +282,42 @@ impl SessionCatalog {
         Some(ent)
     }
 
+    /// Get the id of a schema in a native database.
+    ///
+    /// Builtin schemas (e.g. `information_schema`) only exist in the default
+    /// database, but can be resolved from any native database.
+    fn get_schema_id(&self, database: &str, schema: &str) -> Option<u32> {
+        let database_id = self.database_names.get(database)?;
+        if let Some(id) = self
+            .schema_names
+            .get(database_id)
+            .and_then(|schemas| schemas.get(schema))
+        {
+            return Some(*id);
+        }
+
+        match self.state.entries.get(database_id)? {
+            CatalogEntry::Database(DatabaseEntry {
+                options: DatabaseOptions::Internal(_),
+                ..
+            }) => (),
+            _ => return None,
+        }
+
+        // The builtin default schema lives in the default database.
+        let default_database_id = self
+            .state
+            .entries
+            .get(&self.resolve_conf.default_schema_oid)?
+            .get_meta()
+            .parent;
+        let id = self.schema_names.get(&default_database_id)?.get(schema)?;
+        match self.state.entries.get(id)? {
+            ent if ent.get_meta().builtin => Some(*id),
+            _ => None,
+        }
+    }
+
     /// Get an entry by its id.
     pub fn get_by_oid(&self, oid: u32) -> Option<&CatalogEntry> {
         self.state.entries.get(&oid)
@@ -355,7 +424,11 @@ impl SessionCatalog {
                     self.credentials_names.insert(name, *id);
                 }
                 CatalogEntry::Schema(_) => {
-                    self.schema_names.insert(name, *id);
+                    let database_id = ent.get_meta().parent;
+                    self.schema_names
+                        .entry(database_id)
+                        .or_default()
+                        .insert(name, *id);
                 }
                 CatalogEntry::Table(_) | CatalogEntry::View(_) | CatalogEntry::Function(_) => {
                     let schema_id = ent.get_meta().parent;
@@ -452,6 +525,8 @@ impl TempCatalog {
                 options: TableOptions::Internal(TableOptionsInternal { columns }),
                 tunnel_id: None,
                 access_mode: SourceAccessMode::ReadWrite,
+                column_comments: BTreeMap::new(),
+                tags: BTreeMap::new(),
             }
         })
     }
@@ -476,6 +551,11 @@ impl TempCatalog {
         inner.tables.contains_key(name)
     }
 
+    /// Returns true if there are no temp tables.
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().tables.is_empty()
+    }
+
     pub fn get_table_entries(&self) -> Vec<TableEntry> {
         let inner = self.inner.lock();
         let mut ents = Vec::with_capacity(inner.tables.len());
@@ -497,6 +577,8 @@ impl TempCatalog {
                 }),
                 tunnel_id: None,
                 access_mode: SourceAccessMode::ReadWrite,
+                column_comments: BTreeMap::new(),
+                tags: BTreeMap::new(),
             });
         }
 
@@ -0,0 +1,504 @@
+use datafusion::arrow::array::{new_null_array, Array, ArrayRef, UInt32Array};
+use datafusion::arrow::compute::{interleave, take};
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::row::{Row, RowConverter, Rows, SortField};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::{PhysicalExpr, PhysicalSortExpr, PhysicalSortRequirement};
+use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use datafusion::scalar::ScalarValue;
+use futures::{stream, StreamExt};
+use std::any::Any;
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
+
+use super::AsofJoinType;
+
+/// Expressions evaluated against one input of the join.
+#[derive(Debug, Clone)]
+struct InputExprs {
+    keys: Vec<Arc<dyn PhysicalExpr>>,
+    time: Arc<dyn PhysicalExpr>,
+}
+
+impl InputExprs {
+    /// The ordering the input must be sorted by, keys followed by the time.
+    fn sort_requirement(&self) -> Vec<PhysicalSortRequirement> {
+        self.keys
+            .iter()
+            .chain(std::iter::once(&self.time))
+            .map(|expr| PhysicalSortRequirement::new(expr.clone(), Some(Default::default())))
+            .collect()
+    }
+}
+
+/// Joins each left row with the right row with the same keys and the latest
+/// time not after the left row's time.
+///
+/// Both inputs are sorted by their keys and time, and merged by moving through
+/// the right input as the left rows are joined. Only the right batches holding
+/// the current and last matching rows are kept in memory.
+#[derive(Debug)]
+pub struct AsofJoinExec {
+    left: Arc<dyn ExecutionPlan>,
+    right: Arc<dyn ExecutionPlan>,
+    left_exprs: InputExprs,
+    right_exprs: InputExprs,
+    inclusive: bool,
+    right_bound: Option<Arc<dyn PhysicalExpr>>,
+    join_type: AsofJoinType,
+    schema: SchemaRef,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl AsofJoinExec {
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_new(
+        left: Arc<dyn ExecutionPlan>,
+        right: Arc<dyn ExecutionPlan>,
+        on: Vec<(Arc<dyn PhysicalExpr>, Arc<dyn PhysicalExpr>)>,
+        left_time: Arc<dyn PhysicalExpr>,
+        right_time: Arc<dyn PhysicalExpr>,
+        inclusive: bool,
+        right_bound: Option<Arc<dyn PhysicalExpr>>,
+        join_type: AsofJoinType,
+        schema: SchemaRef,
+    ) -> Result<Self> {
+        let left_schema = left.schema();
+        let right_schema = right.schema();
+        for (l, r) in on
+            .iter()
+            .chain(std::iter::once(&(left_time.clone(), right_time.clone())))
+        {
+            let left_type = l.data_type(&left_schema)?;
+            let right_type = r.data_type(&right_schema)?;
+            if left_type != right_type {
+                return Err(DataFusionError::Internal(format!(
+                    "ASOF JOIN expressions {l} and {r} have different types {left_type} and {right_type}"
+                )));
+            }
+        }
+
+        let (left_keys, right_keys) = on.into_iter().unzip();
+        Ok(AsofJoinExec {
+            left,
+            right,
+            left_exprs: InputExprs {
+                keys: left_keys,
+                time: left_time,
+            },
+            right_exprs: InputExprs {
+                keys: right_keys,
+                time: right_time,
+            },
+            inclusive,
+            right_bound,
+            join_type,
+            schema,
+            metrics: ExecutionPlanMetricsSet::new(),
+        })
+    }
+
+    pub fn join_type(&self) -> AsofJoinType {
+        self.join_type
+    }
+}
+
+impl ExecutionPlan for AsofJoinExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.left.output_partitioning()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        self.left.output_ordering()
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        if self.left_exprs.keys.is_empty() {
+            vec![Distribution::SinglePartition, Distribution::SinglePartition]
+        } else {
+            vec![
+                Distribution::HashPartitioned(self.left_exprs.keys.clone()),
+                Distribution::HashPartitioned(self.right_exprs.keys.clone()),
+            ]
+        }
+    }
+
+    fn required_input_ordering(&self) -> Vec<Option<Vec<PhysicalSortRequirement>>> {
+        vec![
+            Some(self.left_exprs.sort_requirement()),
+            Some(self.right_exprs.sort_requirement()),
+        ]
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![true, false]
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.left.clone(), self.right.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.len() != 2 {
+            return Err(DataFusionError::Execution(
+                "AsofJoinExec requires exactly two children".to_string(),
+            ));
+        }
+        Ok(Arc::new(AsofJoinExec {
+            left: children[0].clone(),
+            right: children[1].clone(),
+            left_exprs: self.left_exprs.clone(),
+            right_exprs: self.right_exprs.clone(),
+            inclusive: self.inclusive,
+            right_bound: self.right_bound.clone(),
+            join_type: self.join_type,
+            schema: self.schema.clone(),
+            metrics: ExecutionPlanMetricsSet::new(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let left_schema = self.left.schema();
+        let sort_fields = self
+            .left_exprs
+            .keys
+            .iter()
+            .chain(std::iter::once(&self.left_exprs.time))
+            .map(|expr| Ok(SortField::new(expr.data_type(&left_schema)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let num_keys = self.left_exprs.keys.len();
+        let key_converter = if num_keys == 0 {
+            None
+        } else {
+            Some(RowConverter::new(sort_fields[..num_keys].to_vec())?)
+        };
+
+        let join = AsofJoinStream {
+            left: self.left.execute(partition, context.clone())?,
+            right: self.right.execute(partition, context)?,
+            left_exprs: self.left_exprs.clone(),
+            right_exprs: self.right_exprs.clone(),
+            inclusive: self.inclusive,
+            right_bound: self.right_bound.clone(),
+            join_type: self.join_type,
+            schema: self.schema.clone(),
+            key_converter,
+            sort_converter: RowConverter::new(sort_fields)?,
+            right_batch: None,
+            right_idx: 0,
+            right_done: false,
+            candidate: None,
+            metrics: BaselineMetrics::new(&self.metrics, partition),
+        };
+
+        let stream = stream::try_unfold(join, |mut join| async move {
+            loop {
+                match join.left.next().await {
+                    Some(batch) => {
+                        let batch = join.join_batch(batch?).await?;
+                        if batch.num_rows() > 0 {
+                            return Ok(Some((batch, join)));
+                        }
+                    }
+                    None => {
+                        join.metrics.done();
+                        return Ok::<_, DataFusionError>(None);
+                    }
+                }
+            }
+        });
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+}
+
+impl DisplayAs for AsofJoinExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        let on: Vec<_> = self
+            .left_exprs
+            .keys
+            .iter()
+            .zip(&self.right_exprs.keys)
+            .map(|(l, r)| format!("({l}, {r})"))
+            .collect();
+        let op = if self.inclusive { ">=" } else { ">" };
+        write!(
+            f,
+            "AsofJoinExec: join_type={}, on=[{}], time={} {op} {}",
+            self.join_type,
+            on.join(", "),
+            self.left_exprs.time,
+            self.right_exprs.time,
+        )?;
+        if let Some(bound) = &self.right_bound {
+            write!(f, ", bound={bound}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A batch with its join columns converted to rows for comparing.
+struct JoinBatch {
+    batch: RecordBatch,
+    /// The keys of each row, if joining on keys.
+    keys: Option<Rows>,
+    /// The keys and time of each row, comparing in the order the input is
+    /// sorted in.
+    sort: Rows,
+    time: ArrayRef,
+    /// For right batches, the latest left time each row may match.
+    bound: Option<ArrayRef>,
+    /// Whether the keys and time of each row are all non-null. Rows with nulls
+    /// never match.
+    valid: Vec<bool>,
+}
+
+struct AsofJoinStream {
+    left: SendableRecordBatchStream,
+    right: SendableRecordBatchStream,
+    left_exprs: InputExprs,
+    right_exprs: InputExprs,
+    inclusive: bool,
+    right_bound: Option<Arc<dyn PhysicalExpr>>,
+    join_type: AsofJoinType,
+    schema: SchemaRef,
+    key_converter: Option<RowConverter>,
+    sort_converter: RowConverter,
+    /// Right batch currently being merged.
+    right_batch: Option<Arc<JoinBatch>>,
+    /// Index of the next row in the right batch to merge.
+    right_idx: usize,
+    right_done: bool,
+    /// Last valid right row sorting before the current left row.
+    candidate: Option<(Arc<JoinBatch>, usize)>,
+    metrics: BaselineMetrics,
+}
+
+impl AsofJoinStream {
+    fn convert(
+        &mut self,
+        batch: RecordBatch,
+        exprs: &InputExprs,
+        bound: Option<&Arc<dyn PhysicalExpr>>,
+    ) -> Result<JoinBatch> {
+        let num_rows = batch.num_rows();
+        let keys = exprs
+            .keys
+            .iter()
+            .map(|expr| Ok(expr.evaluate(&batch)?.into_array(num_rows)))
+            .collect::<Result<Vec<_>>>()?;
+        let time = exprs.time.evaluate(&batch)?.into_array(num_rows);
+        let bound = bound
+            .map(|expr| Ok::<_, DataFusionError>(expr.evaluate(&batch)?.into_array(num_rows)))
+            .transpose()?;
+
+        let valid = (0..num_rows)
+            .map(|idx| time.is_valid(idx) && keys.iter().all(|key| key.is_valid(idx)))
+            .collect();
+        let key_rows = match &mut self.key_converter {
+            Some(converter) => Some(converter.convert_columns(&keys)?),
+            None => None,
+        };
+        let mut sort_columns = keys;
+        sort_columns.push(time.clone());
+        let sort = self.sort_converter.convert_columns(&sort_columns)?;
+
+        Ok(JoinBatch {
+            batch,
+            keys: key_rows,
+            sort,
+            time,
+            bound,
+            valid,
+        })
+    }
+
+    async fn join_batch(&mut self, batch: RecordBatch) -> Result<RecordBatch> {
+        let left_exprs = self.left_exprs.clone();
+        let left = self.convert(batch, &left_exprs, None)?;
+
+        let mut matches = Vec::with_capacity(left.batch.num_rows());
+        for idx in 0..left.batch.num_rows() {
+            if !left.valid[idx] {
+                matches.push(None);
+                continue;
+            }
+            self.advance(left.sort.row(idx)).await?;
+            let matched = match &self.candidate {
+                Some((right, right_idx)) if is_match(&left, idx, right, *right_idx)? => {
+                    Some((right.clone(), *right_idx))
+                }
+                _ => None,
+            };
+            matches.push(matched);
+        }
+
+        let batch = self.build_output(&left.batch, matches)?;
+        self.metrics.record_output(batch.num_rows());
+        Ok(batch)
+    }
+
+    /// Move through the right rows sorting before the given left row,
+    /// remembering the last valid one as the candidate match.
+    async fn advance(&mut self, target: Row<'_>) -> Result<()> {
+        loop {
+            let right = match &self.right_batch {
+                Some(right) if self.right_idx < right.batch.num_rows() => right.clone(),
+                _ => {
+                    if self.right_done {
+                        return Ok(());
+                    }
+                    match self.right.next().await {
+                        Some(batch) => {
+                            let right_exprs = self.right_exprs.clone();
+                            let bound = self.right_bound.clone();
+                            let right = self.convert(batch?, &right_exprs, bound.as_ref())?;
+                            self.right_batch = Some(Arc::new(right));
+                            self.right_idx = 0;
+                        }
+                        None => {
+                            self.right_done = true;
+                            self.right_batch = None;
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            while self.right_idx < right.batch.num_rows() {
+                let row = right.sort.row(self.right_idx);
+                let before = if self.inclusive {
+                    row <= target
+                } else {
+                    row < target
+                };
+                if !before {
+                    return Ok(());
+                }
+                if right.valid[self.right_idx] {
+                    self.candidate = Some((right.clone(), self.right_idx));
+                }
+                self.right_idx += 1;
+            }
+        }
+    }
+
+    fn build_output(
+        &self,
+        left: &RecordBatch,
+        matches: Vec<Option<(Arc<JoinBatch>, usize)>>,
+    ) -> Result<RecordBatch> {
+        // Index used for the null row of unmatched left rows.
+        const NULL_ROW: usize = usize::MAX;
+
+        let mut right_batches: Vec<Arc<JoinBatch>> = Vec::new();
+        let mut left_indices = Vec::with_capacity(matches.len());
+        let mut right_indices = Vec::with_capacity(matches.len());
+        for (left_idx, matched) in matches.into_iter().enumerate() {
+            match matched {
+                Some((right, right_idx)) => {
+                    // Matches move forward through the right input, so rows
+                    // from the same batch are next to each other.
+                    if !right_batches
+                        .last()
+                        .is_some_and(|last| Arc::ptr_eq(last, &right))
+                    {
+                        right_batches.push(right);
+                    }
+                    left_indices.push(left_idx as u32);
+                    right_indices.push((right_batches.len() - 1, right_idx));
+                }
+                None if self.join_type == AsofJoinType::Left => {
+                    left_indices.push(left_idx as u32);
+                    right_indices.push((NULL_ROW, 0));
+                }
+                None => (),
+            }
+        }
+        for (batch_idx, _) in right_indices.iter_mut() {
+            if *batch_idx == NULL_ROW {
+                *batch_idx = right_batches.len();
+            }
+        }
+
+        let left_indices = UInt32Array::from(left_indices);
+        let mut columns = left
+            .columns()
+            .iter()
+            .map(|col| take(col, &left_indices, None))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let right_fields = &self.schema.fields()[left.num_columns()..];
+        for (col, field) in right_fields.iter().enumerate() {
+            let nulls = new_null_array(field.data_type(), 1);
+            let mut arrays: Vec<&dyn Array> = right_batches
+                .iter()
+                .map(|right| right.batch.column(col).as_ref())
+                .collect();
+            arrays.push(nulls.as_ref());
+            columns.push(interleave(&arrays, &right_indices)?);
+        }
+
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+/// Check if the candidate right row matches the left row.
+fn is_match(
+    left: &JoinBatch,
+    left_idx: usize,
+    right: &JoinBatch,
+    right_idx: usize,
+) -> Result<bool> {
+    if let (Some(left_keys), Some(right_keys)) = (&left.keys, &right.keys) {
+        if left_keys.row(left_idx) != right_keys.row(right_idx) {
+            return Ok(false);
+        }
+    }
+    if let Some(bound) = &right.bound {
+        let time = ScalarValue::try_from_array(&left.time, left_idx)?;
+        let bound = ScalarValue::try_from_array(bound, right_idx)?;
+        if bound.is_null()
+            || !matches!(
+                time.partial_cmp(&bound),
+                Some(Ordering::Less | Ordering::Equal)
+            )
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
@@ -0,0 +1,346 @@
+//! ASOF joins.
+//!
+//! An ASOF join matches each row on the left with the right row that has the
+//! same keys and the latest time not after the time of the left row,
+//! optionally only if the right row's time is within a tolerance of the left
+//! row's time.
+//!
+//! Joins are planned as an `AsofJoin` logical node, executed by merging both
+//! inputs sorted by their keys and time.
+mod exec;
+
+pub use exec::AsofJoinExec;
+
+use async_trait::async_trait;
+use datafusion::arrow::datatypes::{DataType, Schema};
+use datafusion::common::DFSchemaRef;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::type_coercion::binary::comparison_coercion;
+use datafusion::logical_expr::utils::split_conjunction;
+use datafusion::logical_expr::{
+    build_join_schema, cast, BinaryExpr, Expr, ExprSchemable, Extension, JoinType, LogicalPlan,
+    Operator, UserDefinedLogicalNode, UserDefinedLogicalNodeCore,
+};
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::physical_planner::{ExtensionPlanner, PhysicalPlanner};
+use std::fmt;
+use std::sync::Arc;
+
+/// Name of the function `ASOF JOIN`s are marked with in their join condition
+/// when parsing.
+pub const ASOF_JOIN_MARKER: &str = "__glaredb_asof_join";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AsofJoinType {
+    /// Only output left rows with a match.
+    Inner,
+    /// Output all left rows, with nulls for the right columns of rows without
+    /// a match.
+    Left,
+}
+
+impl AsofJoinType {
+    fn as_join_type(&self) -> JoinType {
+        match self {
+            AsofJoinType::Inner => JoinType::Inner,
+            AsofJoinType::Left => JoinType::Left,
+        }
+    }
+}
+
+impl fmt::Display for AsofJoinType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsofJoinType::Inner => write!(f, "Inner"),
+            AsofJoinType::Left => write!(f, "Left"),
+        }
+    }
+}
+
+/// Logical node for an ASOF join.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AsofJoin {
+    pub left: LogicalPlan,
+    pub right: LogicalPlan,
+    /// Left and right expressions that must be equal for rows to match.
+    pub on: Vec<(Expr, Expr)>,
+    pub left_time: Expr,
+    pub right_time: Expr,
+    /// Whether right rows with the same time as the left row match.
+    pub inclusive: bool,
+    /// Latest left time a right row may match, if matches are limited by a
+    /// tolerance.
+    pub right_bound: Option<Expr>,
+    pub join_type: AsofJoinType,
+    pub schema: DFSchemaRef,
+}
+
+impl AsofJoin {
+    pub const EXTENSION_NAME: &'static str = "AsofJoin";
+
+    /// Create an ASOF join from the join condition.
+    ///
+    /// The condition must be a conjunction of equalities between left and
+    /// right expressions, and exactly one inequality comparing the left and
+    /// right time, e.g. `l.key = r.key AND l.ts >= r.ts`.
+    pub fn try_new(
+        left: LogicalPlan,
+        right: LogicalPlan,
+        condition: Expr,
+        tolerance: Option<Expr>,
+        join_type: AsofJoinType,
+    ) -> Result<Self> {
+        let mut on = Vec::new();
+        let mut time = None;
+
+        for conjunct in split_conjunction(&condition) {
+            let invalid = || {
+                DataFusionError::Plan(format!(
+                    "Unsupported ASOF JOIN condition '{conjunct}', expected equalities between left and right expressions and a single inequality between the left and right times"
+                ))
+            };
+            let (l, op, r) = match conjunct {
+                Expr::BinaryExpr(BinaryExpr {
+                    left: l,
+                    op,
+                    right: r,
+                }) => (l, op, r),
+                _ => return Err(invalid()),
+            };
+            let (left_expr, op, right_expr) =
+                match (side(l, &left, &right)?, side(r, &left, &right)?) {
+                    (Some(Side::Left), Some(Side::Right)) => (l.as_ref(), *op, r.as_ref()),
+                    (Some(Side::Right), Some(Side::Left)) => match op.swap() {
+                        Some(op) => (r.as_ref(), op, l.as_ref()),
+                        None => return Err(invalid()),
+                    },
+                    _ => return Err(invalid()),
+                };
+
+            match op {
+                Operator::Eq => on.push((left_expr.clone(), right_expr.clone())),
+                Operator::GtEq | Operator::Gt if time.is_none() => {
+                    time = Some((left_expr.clone(), right_expr.clone(), op == Operator::GtEq))
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        let (left_time, right_time, inclusive) = time.ok_or_else(|| {
+            DataFusionError::Plan(
+                "ASOF JOIN requires an inequality between the left and right times, e.g. 'l.ts >= r.ts'"
+                    .to_string(),
+            )
+        })?;
+
+        let on = on
+            .into_iter()
+            .map(|(l, r)| coerce(l, r, &left, &right))
+            .collect::<Result<Vec<_>>>()?;
+        let (left_time, right_time) = coerce(left_time, right_time, &left, &right)?;
+        let time_type = left_time.get_type(left.schema())?;
+
+        let right_bound = match tolerance {
+            Some(tolerance) => {
+                if let Expr::Literal(value) = &tolerance {
+                    if value.is_null() {
+                        return Err(DataFusionError::Plan(
+                            "ASOF JOIN tolerance must not be null".to_string(),
+                        ));
+                    }
+                }
+                // Type coercion of the addition happens during analysis, so
+                // only cast the result back to the type of the time.
+                Some(cast(right_time.clone() + tolerance, time_type))
+            }
+            None => None,
+        };
+
+        let schema = Arc::new(build_join_schema(
+            left.schema(),
+            right.schema(),
+            &join_type.as_join_type(),
+        )?);
+
+        Ok(AsofJoin {
+            left,
+            right,
+            on,
+            left_time,
+            right_time,
+            inclusive,
+            right_bound,
+            join_type,
+            schema,
+        })
+    }
+
+    pub fn into_logical_plan(self) -> LogicalPlan {
+        LogicalPlan::Extension(Extension {
+            node: Arc::new(self),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// Get the side of the join an expression references, if it only references
+/// columns from one side.
+fn side(expr: &Expr, left: &LogicalPlan, right: &LogicalPlan) -> Result<Option<Side>> {
+    let columns = expr.to_columns()?;
+    if columns.is_empty() {
+        return Ok(None);
+    }
+    if columns
+        .iter()
+        .all(|col| left.schema().index_of_column(col).is_ok())
+    {
+        return Ok(Some(Side::Left));
+    }
+    if columns
+        .iter()
+        .all(|col| right.schema().index_of_column(col).is_ok())
+    {
+        return Ok(Some(Side::Right));
+    }
+    Ok(None)
+}
+
+/// Cast the left and right expressions to a common type.
+fn coerce(l: Expr, r: Expr, left: &LogicalPlan, right: &LogicalPlan) -> Result<(Expr, Expr)> {
+    let left_type = l.get_type(left.schema())?;
+    let right_type = r.get_type(right.schema())?;
+    let common: DataType = comparison_coercion(&left_type, &right_type).ok_or_else(|| {
+        DataFusionError::Plan(format!(
+            "Cannot compare {left_type} and {right_type} in ASOF JOIN condition '{l}' and '{r}'"
+        ))
+    })?;
+    Ok((
+        l.cast_to(&common, left.schema())?,
+        r.cast_to(&common, right.schema())?,
+    ))
+}
+
+impl UserDefinedLogicalNodeCore for AsofJoin {
+    fn name(&self) -> &str {
+        Self::EXTENSION_NAME
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.left, &self.right]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.schema
+    }
+
+    /// Left keys, right keys, left time, right time, then the right bound.
+    fn expressions(&self) -> Vec<Expr> {
+        let mut exprs: Vec<Expr> = self.on.iter().map(|(l, _)| l.clone()).collect();
+        exprs.extend(self.on.iter().map(|(_, r)| r.clone()));
+        exprs.push(self.left_time.clone());
+        exprs.push(self.right_time.clone());
+        exprs.extend(self.right_bound.clone());
+        exprs
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let on: Vec<_> = self.on.iter().map(|(l, r)| format!("{l} = {r}")).collect();
+        let op = if self.inclusive { ">=" } else { ">" };
+        write!(
+            f,
+            "AsofJoin: type={}, on=[{}], time={} {op} {}",
+            self.join_type,
+            on.join(", "),
+            self.left_time,
+            self.right_time,
+        )?;
+        if let Some(bound) = &self.right_bound {
+            write!(f, ", bound={bound}")?;
+        }
+        Ok(())
+    }
+
+    fn from_template(&self, exprs: &[Expr], inputs: &[LogicalPlan]) -> Self {
+        let num_keys = self.on.len();
+        let on = exprs[..num_keys]
+            .iter()
+            .cloned()
+            .zip(exprs[num_keys..num_keys * 2].iter().cloned())
+            .collect();
+        AsofJoin {
+            left: inputs[0].clone(),
+            right: inputs[1].clone(),
+            on,
+            left_time: exprs[num_keys * 2].clone(),
+            right_time: exprs[num_keys * 2 + 1].clone(),
+            inclusive: self.inclusive,
+            right_bound: exprs.get(num_keys * 2 + 2).cloned(),
+            join_type: self.join_type,
+            schema: self.schema.clone(),
+        }
+    }
+}
+
+/// Plans `AsofJoin` nodes.
+#[derive(Debug, Default)]
+pub struct AsofJoinPlanner;
+
+#[async_trait]
+impl ExtensionPlanner for AsofJoinPlanner {
+    async fn plan_extension(
+        &self,
+        planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        logical_inputs: &[&LogicalPlan],
+        physical_inputs: &[Arc<dyn ExecutionPlan>],
+        session_state: &SessionState,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        let node = match node.as_any().downcast_ref::<AsofJoin>() {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+        let (left, right) = (&physical_inputs[0], &physical_inputs[1]);
+        let left_expr = |expr: &Expr| {
+            planner.create_physical_expr(
+                expr,
+                logical_inputs[0].schema(),
+                &left.schema(),
+                session_state,
+            )
+        };
+        let right_expr = |expr: &Expr| {
+            planner.create_physical_expr(
+                expr,
+                logical_inputs[1].schema(),
+                &right.schema(),
+                session_state,
+            )
+        };
+
+        let on = node
+            .on
+            .iter()
+            .map(|(l, r)| Ok((left_expr(l)?, right_expr(r)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let right_bound = node.right_bound.as_ref().map(right_expr).transpose()?;
+        let schema: Schema = node.schema.as_ref().into();
+
+        Ok(Some(Arc::new(AsofJoinExec::try_new(
+            left.clone(),
+            right.clone(),
+            on,
+            left_expr(&node.left_time)?,
+            right_expr(&node.right_time)?,
+            node.inclusive,
+            right_bound,
+            node.join_type,
+            Arc::new(schema),
+        )?)))
+    }
+}
@@ -0,0 +1,432 @@
+use datafusion::arrow::array::{
+    Array, ArrayRef, AsArray, Float64Array, TimestampNanosecondArray, UInt32Array,
+};
+use datafusion::arrow::compute::{cast, concat_batches, interleave, take};
+use datafusion::arrow::datatypes::{
+    DataType, Float64Type, SchemaRef, TimeUnit, TimestampNanosecondType,
+};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::row::{RowConverter, SortField};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::expressions::Column;
+use datafusion::physical_expr::{PhysicalExpr, PhysicalSortExpr, PhysicalSortRequirement};
+use datafusion::physical_plan::common::collect;
+use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use futures::stream;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use super::FillStrategy;
+
+/// Fills in missing time buckets of each series of its input.
+///
+/// The input must be sorted by the series columns and the time. Since the
+/// input is the output of an aggregate, each partition is gap filled as a
+/// whole.
+#[derive(Debug)]
+pub struct GapFillExec {
+    input: Arc<dyn ExecutionPlan>,
+    time: usize,
+    series: Vec<usize>,
+    fills: Vec<(usize, FillStrategy)>,
+    stride: i64,
+    start: i64,
+    end: i64,
+    schema: SchemaRef,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl GapFillExec {
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_new(
+        input: Arc<dyn ExecutionPlan>,
+        time: usize,
+        series: Vec<usize>,
+        fills: Vec<(usize, FillStrategy)>,
+        stride: i64,
+        start: i64,
+        end: i64,
+        schema: SchemaRef,
+    ) -> Result<Self> {
+        let input_schema = input.schema();
+        match input_schema.field(time).data_type() {
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => (),
+            other => {
+                return Err(DataFusionError::Internal(format!(
+                    "Gap fill time column must be a nanosecond timestamp, got {other}"
+                )))
+            }
+        }
+        for (idx, strategy) in &fills {
+            let field = input_schema.field(*idx);
+            if *strategy == FillStrategy::Interpolate && !field.data_type().is_numeric() {
+                return Err(DataFusionError::Plan(format!(
+                    "Cannot interpolate non-numeric column {} of type {}",
+                    field.name(),
+                    field.data_type()
+                )));
+            }
+        }
+
+        Ok(GapFillExec {
+            input,
+            time,
+            series,
+            fills,
+            stride,
+            start,
+            end,
+            schema,
+            metrics: ExecutionPlanMetricsSet::new(),
+        })
+    }
+
+    fn series_exprs(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        let input_schema = self.input.schema();
+        self.series
+            .iter()
+            .map(|idx| {
+                Arc::new(Column::new(input_schema.field(*idx).name(), *idx))
+                    as Arc<dyn PhysicalExpr>
+            })
+            .collect()
+    }
+}
+
+impl ExecutionPlan for GapFillExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        self.input.output_ordering()
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        if self.series.is_empty() {
+            vec![Distribution::SinglePartition]
+        } else {
+            vec![Distribution::HashPartitioned(self.series_exprs())]
+        }
+    }
+
+    fn required_input_ordering(&self) -> Vec<Option<Vec<PhysicalSortRequirement>>> {
+        let time = Arc::new(Column::new(
+            self.input.schema().field(self.time).name(),
+            self.time,
+        ));
+        let requirement = self
+            .series_exprs()
+            .into_iter()
+            .chain(std::iter::once(time as Arc<dyn PhysicalExpr>))
+            .map(|expr| PhysicalSortRequirement::new(expr, Some(Default::default())))
+            .collect();
+        vec![Some(requirement)]
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![true]
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Execution(
+                "GapFillExec requires exactly one child".to_string(),
+            ));
+        }
+        Ok(Arc::new(GapFillExec {
+            input: children[0].clone(),
+            time: self.time,
+            series: self.series.clone(),
+            fills: self.fills.clone(),
+            stride: self.stride,
+            start: self.start,
+            end: self.end,
+            schema: self.schema.clone(),
+            metrics: ExecutionPlanMetricsSet::new(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let input = self.input.execute(partition, context)?;
+        let filler = Filler {
+            time: self.time,
+            series: self.series.clone(),
+            fills: self.fills.clone(),
+            stride: self.stride,
+            start: self.start,
+            end: self.end,
+            schema: self.schema.clone(),
+        };
+        let metrics = BaselineMetrics::new(&self.metrics, partition);
+
+        let stream = stream::once(async move {
+            let input_schema = input.schema();
+            let batches = collect(input).await?;
+            let batch = concat_batches(&input_schema, &batches)?;
+            let timer = metrics.elapsed_compute().timer();
+            let batch = filler.fill(&batch)?;
+            timer.done();
+            metrics.record_output(batch.num_rows());
+            metrics.done();
+            Ok::<_, DataFusionError>(batch)
+        });
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+}
+
+impl DisplayAs for GapFillExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        let fills: Vec<_> = self
+            .fills
+            .iter()
+            .map(|(idx, strategy)| format!("{idx}={strategy}"))
+            .collect();
+        write!(
+            f,
+            "GapFillExec: time={}, series={:?}, fills=[{}], stride={}, start={}, end={}",
+            self.time,
+            self.series,
+            fills.join(", "),
+            self.stride,
+            self.start,
+            self.end,
+        )
+    }
+}
+
+/// A row of the output.
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    /// A row of the input.
+    Row(usize),
+    /// A missing bucket, with the input rows of the same series before and
+    /// after it.
+    Gap {
+        time: i64,
+        prev: Option<usize>,
+        next: Option<usize>,
+    },
+}
+
+impl Slot {
+    /// Input row to take the series values from.
+    fn series_row(&self) -> Option<usize> {
+        match self {
+            Slot::Row(row) => Some(*row),
+            Slot::Gap { prev, next, .. } => prev.or(*next),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Filler {
+    time: usize,
+    series: Vec<usize>,
+    fills: Vec<(usize, FillStrategy)>,
+    stride: i64,
+    start: i64,
+    end: i64,
+    schema: SchemaRef,
+}
+
+impl Filler {
+    fn fill(&self, batch: &RecordBatch) -> Result<RecordBatch> {
+        let times = batch
+            .column(self.time)
+            .as_primitive::<TimestampNanosecondType>();
+        let slots = self.slots(batch, times)?;
+
+        let mut columns: Vec<ArrayRef> = batch.columns().to_vec();
+        columns[self.time] = Arc::new(
+            slots
+                .iter()
+                .map(|slot| match slot {
+                    Slot::Row(row) => times.is_valid(*row).then(|| times.value(*row)),
+                    Slot::Gap { time, .. } => Some(*time),
+                })
+                .collect::<TimestampNanosecondArray>()
+                .with_timezone_opt(times.timezone()),
+        );
+        for idx in &self.series {
+            let indices: UInt32Array = slots
+                .iter()
+                .map(|slot| slot.series_row().map(|row| row as u32))
+                .collect();
+            columns[*idx] = take(batch.column(*idx), &indices, None)?;
+        }
+        for (idx, strategy) in &self.fills {
+            columns[*idx] = fill_column(batch.column(*idx), times, &slots, *strategy)?;
+        }
+
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+
+    /// Interleave the input rows with the missing buckets of each series.
+    fn slots(&self, batch: &RecordBatch, times: &TimestampNanosecondArray) -> Result<Vec<Slot>> {
+        let num_rows = batch.num_rows();
+        let mut slots = Vec::with_capacity(num_rows);
+
+        // Without series columns an empty input is still one (empty) series.
+        if num_rows == 0 {
+            if self.series.is_empty() {
+                self.push_gaps_from(&mut slots, self.start, None);
+            }
+            return Ok(slots);
+        }
+
+        let series_rows = if self.series.is_empty() {
+            None
+        } else {
+            let fields = self
+                .series
+                .iter()
+                .map(|idx| SortField::new(batch.column(*idx).data_type().clone()))
+                .collect();
+            let columns: Vec<_> = self
+                .series
+                .iter()
+                .map(|idx| batch.column(*idx).clone())
+                .collect();
+            Some(RowConverter::new(fields)?.convert_columns(&columns)?)
+        };
+
+        let mut next_bucket = self.start;
+        let mut prev = None;
+        for row in 0..num_rows {
+            let new_series = match &series_rows {
+                Some(rows) => row > 0 && rows.row(row) != rows.row(row - 1),
+                None => false,
+            };
+            if new_series {
+                self.push_gaps_from(&mut slots, next_bucket, prev);
+                next_bucket = self.start;
+                prev = None;
+            }
+
+            // Rows with a null time sort first and don't take up a bucket.
+            if times.is_valid(row) {
+                let time = times.value(row);
+                let until = time.min(self.end);
+                while next_bucket < until {
+                    slots.push(Slot::Gap {
+                        time: next_bucket,
+                        prev,
+                        next: Some(row),
+                    });
+                    next_bucket = next_bucket.saturating_add(self.stride);
+                }
+                if next_bucket == time {
+                    next_bucket = next_bucket.saturating_add(self.stride);
+                }
+            }
+            slots.push(Slot::Row(row));
+            prev = Some(row);
+        }
+        self.push_gaps_from(&mut slots, next_bucket, prev);
+
+        Ok(slots)
+    }
+
+    /// Push gaps for the buckets from `from` to the end of the range.
+    fn push_gaps_from(&self, slots: &mut Vec<Slot>, from: i64, prev: Option<usize>) {
+        let mut bucket = from;
+        while bucket < self.end {
+            slots.push(Slot::Gap {
+                time: bucket,
+                prev,
+                next: None,
+            });
+            bucket = bucket.saturating_add(self.stride);
+        }
+    }
+}
+
+/// Build the output column for an aggregate from the input column.
+fn fill_column(
+    column: &ArrayRef,
+    times: &TimestampNanosecondArray,
+    slots: &[Slot],
+    strategy: FillStrategy,
+) -> Result<ArrayRef> {
+    match strategy {
+        FillStrategy::Null | FillStrategy::Locf => {
+            let indices: UInt32Array = slots
+                .iter()
+                .map(|slot| match slot {
+                    Slot::Row(row) => Some(*row as u32),
+                    Slot::Gap { prev, .. } if strategy == FillStrategy::Locf => {
+                        prev.map(|row| row as u32)
+                    }
+                    Slot::Gap { .. } => None,
+                })
+                .collect();
+            Ok(take(column, &indices, None)?)
+        }
+        FillStrategy::Interpolate => {
+            let values = cast(column, &DataType::Float64)?;
+            let values = values.as_primitive::<Float64Type>();
+            let point = |row: usize| {
+                (values.is_valid(row) && times.is_valid(row))
+                    .then(|| (times.value(row), values.value(row)))
+            };
+            // Interpolate the gaps, and interleave them with the input rows to
+            // keep the input values exact.
+            let mut indices = Vec::with_capacity(slots.len());
+            let mut gaps = Vec::new();
+            for slot in slots {
+                match slot {
+                    Slot::Row(row) => indices.push((0, *row)),
+                    Slot::Gap { time, prev, next } => {
+                        let interpolated = prev.and_then(point).zip(next.and_then(point)).map(
+                            |((t0, v0), (t1, v1))| {
+                                v0 + (v1 - v0) * ((time - t0) as f64 / (t1 - t0) as f64)
+                            },
+                        );
+                        indices.push((1, gaps.len()));
+                        gaps.push(interpolated);
+                    }
+                }
+            }
+            let gaps = cast(&Float64Array::from(gaps), column.data_type())?;
+            Ok(interleave(&[column.as_ref(), gaps.as_ref()], &indices)?)
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! Gap filling for time bucketed aggregates.
+//!
+//! An aggregate grouping by `time_bucket_gapfill(stride, ts, start, finish)`
+//! outputs a row for every bucket between `start` and `finish`, for every
+//! combination of the other grouping keys, even if no rows fell into the
+//! bucket. Aggregate values of the added rows are null, unless the projection
+//! wraps them in `locf` (carry the last observation forward) or `interpolate`
+//! (linearly interpolate between the surrounding observations).
+//!
+//! Gap filling is planned as a `GapFill` logical node directly above the
+//! aggregate.
+mod exec;
+
+pub use exec::GapFillExec;
+
+use async_trait::async_trait;
+use datafusion::arrow::datatypes::{
+    DataType, IntervalDayTimeType, IntervalMonthDayNanoType, Schema, TimeUnit,
+};
+use datafusion::common::tree_node::{TreeNode, VisitRecursion};
+use datafusion::common::{Column, DFSchema, DFSchemaRef};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::expr::{Alias, ScalarUDF};
+use datafusion::logical_expr::{
+    Expr, Extension, LogicalPlan, UserDefinedLogicalNode, UserDefinedLogicalNodeCore,
+};
+use datafusion::optimizer::simplify_expressions::{ExprSimplifier, SimplifyContext};
+use datafusion::physical_expr::execution_props::ExecutionProps;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::physical_planner::{ExtensionPlanner, PhysicalPlanner};
+use datafusion::scalar::ScalarValue;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Name of the function grouping by which enables gap filling.
+pub const TIME_BUCKET_GAPFILL: &str = "time_bucket_gapfill";
+
+/// Name of the function marking aggregates to fill with the last observation.
+pub const LOCF: &str = "locf";
+
+/// Name of the function marking aggregates to fill by linear interpolation.
+pub const INTERPOLATE: &str = "interpolate";
+
+/// Upper bound on the number of buckets a single gap filled series may have.
+const MAX_BUCKETS: i64 = 10_000_000;
+
+/// How values of rows added for missing buckets are filled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FillStrategy {
+    /// Leave values null.
+    Null,
+    /// Use the value of the previous row in the series.
+    Locf,
+    /// Linearly interpolate between the previous and next rows in the series.
+    Interpolate,
+}
+
+impl fmt::Display for FillStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FillStrategy::Null => write!(f, "null"),
+            FillStrategy::Locf => write!(f, "locf"),
+            FillStrategy::Interpolate => write!(f, "interpolate"),
+        }
+    }
+}
+
+/// Logical node filling in missing time buckets of an aggregate.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GapFill {
+    pub input: LogicalPlan,
+    /// Column holding the time bucket.
+    pub time: Column,
+    /// Other grouping columns, each combination of which is one series.
+    pub series: Vec<Column>,
+    /// Aggregate columns and how to fill them.
+    pub fills: Vec<(Column, FillStrategy)>,
+    /// Width of the buckets in nanoseconds.
+    pub stride: i64,
+    /// Start of the first bucket, in nanoseconds since the epoch.
+    pub start: i64,
+    /// Exclusive end of the range to fill, in nanoseconds since the epoch.
+    pub end: i64,
+    pub schema: DFSchemaRef,
+}
+
+impl GapFill {
+    pub const EXTENSION_NAME: &'static str = "GapFill";
+
+    /// Wrap `plan` in a gap fill node if it's an aggregate grouping by
+    /// `time_bucket_gapfill`.
+    ///
+    /// `select_exprs` are the projection expressions rewritten to reference
+    /// the aggregate output, and are searched for `locf` and `interpolate`.
+    pub fn plan_for_aggregate(plan: LogicalPlan, select_exprs: &[Expr]) -> Result<LogicalPlan> {
+        let agg = match &plan {
+            LogicalPlan::Aggregate(agg) => agg,
+            _ => return Ok(plan),
+        };
+
+        let mut bucket = None;
+        for (idx, expr) in agg.group_expr.iter().enumerate() {
+            let expr = match expr {
+                Expr::Alias(Alias { expr, .. }) => expr.as_ref(),
+                expr => expr,
+            };
+            match expr {
+                Expr::ScalarUDF(ScalarUDF { fun, args }) if fun.name == TIME_BUCKET_GAPFILL => {
+                    if bucket.is_some() {
+                        return Err(DataFusionError::Plan(format!(
+                            "Only one {TIME_BUCKET_GAPFILL} grouping is allowed"
+                        )));
+                    }
+                    bucket = Some((idx, args.clone()));
+                }
+                Expr::GroupingSet(_) if contains_gapfill(expr) => {
+                    return Err(DataFusionError::Plan(format!(
+                        "{TIME_BUCKET_GAPFILL} is not supported in grouping sets"
+                    )));
+                }
+                _ => (),
+            }
+        }
+        let (time_idx, args) = match bucket {
+            Some(bucket) => bucket,
+            None => return Ok(plan),
+        };
+
+        let [stride, _, start, end] = args.as_slice() else {
+            return Err(DataFusionError::Plan(format!(
+                "{TIME_BUCKET_GAPFILL} expects a stride, a timestamp, a start and a finish"
+            )));
+        };
+        let stride = stride_nanos(&const_value(stride, "stride")?)?;
+        let start = timestamp_nanos(const_value(start, "start")?)?;
+        let end = timestamp_nanos(const_value(end, "finish")?)?;
+        // Align the start to the buckets, which start at the epoch.
+        let start = start - start.rem_euclid(stride);
+        if end > start && (end - start) / stride > MAX_BUCKETS {
+            return Err(DataFusionError::Plan(format!(
+                "{TIME_BUCKET_GAPFILL} range would produce more than {MAX_BUCKETS} buckets"
+            )));
+        }
+
+        let fields = agg.schema.fields();
+        let num_groups = agg.group_expr.len();
+        let time = fields[time_idx].qualified_column();
+        let series = fields[..num_groups]
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != time_idx)
+            .map(|(_, field)| field.qualified_column())
+            .collect();
+
+        let mut strategies = HashMap::new();
+        for expr in select_exprs {
+            find_fill_strategies(expr, &mut strategies)?;
+        }
+        let fills = fields[num_groups..]
+            .iter()
+            .map(|field| {
+                let col = field.qualified_column();
+                let strategy = strategies.get(&col).copied().unwrap_or(FillStrategy::Null);
+                (col, strategy)
+            })
+            .collect();
+
+        // Added rows have nulls for aggregates that aren't filled, or can't be
+        // filled for lack of surrounding observations.
+        let schema = DFSchema::new_with_metadata(
+            fields
+                .iter()
+                .enumerate()
+                .map(|(idx, field)| {
+                    if idx < num_groups {
+                        field.clone()
+                    } else {
+                        field.clone().with_nullable(true)
+                    }
+                })
+                .collect(),
+            agg.schema.metadata().clone(),
+        )?;
+
+        Ok(LogicalPlan::Extension(Extension {
+            node: Arc::new(GapFill {
+                input: plan,
+                time,
+                series,
+                fills,
+                stride,
+                start,
+                end,
+                schema: Arc::new(schema),
+            }),
+        }))
+    }
+}
+
+fn contains_gapfill(expr: &Expr) -> bool {
+    let mut found = false;
+    let _ = expr.apply(&mut |expr| {
+        if let Expr::ScalarUDF(ScalarUDF { fun, .. }) = expr {
+            if fun.name == TIME_BUCKET_GAPFILL {
+                found = true;
+            }
+        }
+        Ok(VisitRecursion::Continue)
+    });
+    found
+}
+
+/// Record the fill strategy of all aggregate columns wrapped in `locf` or
+/// `interpolate`.
+fn find_fill_strategies(expr: &Expr, strategies: &mut HashMap<Column, FillStrategy>) -> Result<()> {
+    let mut result = Ok(());
+    expr.apply(&mut |expr| {
+        if let Expr::ScalarUDF(ScalarUDF { fun, args }) = expr {
+            let strategy = match fun.name.as_str() {
+                LOCF => FillStrategy::Locf,
+                INTERPOLATE => FillStrategy::Interpolate,
+                _ => return Ok(VisitRecursion::Continue),
+            };
+            for arg in args {
+                for col in arg.to_columns()? {
+                    if let Some(existing) = strategies.insert(col.clone(), strategy) {
+                        if existing != strategy {
+                            result = Err(DataFusionError::Plan(format!(
+                                "Column {col} cannot be filled with both {existing} and {strategy}"
+                            )));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(VisitRecursion::Continue)
+    })?;
+    result
+}
+
+/// Evaluate an argument of `time_bucket_gapfill` that must be constant.
+fn const_value(expr: &Expr, name: &str) -> Result<ScalarValue> {
+    let props = ExecutionProps::new();
+    let info = SimplifyContext::new(&props).with_schema(Arc::new(DFSchema::empty()));
+    let simplifier = ExprSimplifier::new(info);
+    match simplifier.simplify(expr.clone())? {
+        Expr::Literal(value) if !value.is_null() => Ok(value),
+        _ => Err(DataFusionError::Plan(format!(
+            "The {name} of {TIME_BUCKET_GAPFILL} must be a non-null constant, got '{expr}'"
+        ))),
+    }
+}
+
+fn stride_nanos(value: &ScalarValue) -> Result<i64> {
+    const NANOS_PER_DAY: i64 = 86_400_000_000_000;
+    let nanos = match value {
+        ScalarValue::IntervalDayTime(Some(v)) => {
+            let (days, millis) = IntervalDayTimeType::to_parts(*v);
+            Some(days as i64 * NANOS_PER_DAY + millis as i64 * 1_000_000)
+        }
+        ScalarValue::IntervalMonthDayNano(Some(v)) => {
+            let (months, days, nanos) = IntervalMonthDayNanoType::to_parts(*v);
+            if months != 0 {
+                return Err(DataFusionError::Plan(format!(
+                    "The stride of {TIME_BUCKET_GAPFILL} cannot contain months"
+                )));
+            }
+            Some(days as i64 * NANOS_PER_DAY + nanos)
+        }
+        ScalarValue::IntervalYearMonth(_) => {
+            return Err(DataFusionError::Plan(format!(
+                "The stride of {TIME_BUCKET_GAPFILL} cannot contain months"
+            )))
+        }
+        _ => None,
+    };
+    match nanos {
+        Some(nanos) if nanos > 0 => Ok(nanos),
+        Some(_) => Err(DataFusionError::Plan(format!(
+            "The stride of {TIME_BUCKET_GAPFILL} must be positive"
+        ))),
+        None => Err(DataFusionError::Plan(format!(
+            "The stride of {TIME_BUCKET_GAPFILL} must be an interval, got '{value}'"
+        ))),
+    }
+}
+
+fn timestamp_nanos(value: ScalarValue) -> Result<i64> {
+    match value.cast_to(&DataType::Timestamp(TimeUnit::Nanosecond, None))? {
+        ScalarValue::TimestampNanosecond(Some(v), _) => Ok(v),
+        other => Err(DataFusionError::Plan(format!(
+            "Expected a timestamp for {TIME_BUCKET_GAPFILL}, got '{other}'"
+        ))),
+    }
+}
+
+impl UserDefinedLogicalNodeCore for GapFill {
+    fn name(&self) -> &str {
+        Self::EXTENSION_NAME
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        Vec::new()
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let series: Vec<_> = self.series.iter().map(|col| col.to_string()).collect();
+        let fills: Vec<_> = self
+            .fills
+            .iter()
+            .map(|(col, strategy)| format!("{col}={strategy}"))
+            .collect();
+        write!(
+            f,
+            "GapFill: time={}, series=[{}], fills=[{}], stride={}, start={}, end={}",
+            self.time,
+            series.join(", "),
+            fills.join(", "),
+            self.stride,
+            self.start,
+            self.end,
+        )
+    }
+
+    fn from_template(&self, _exprs: &[Expr], inputs: &[LogicalPlan]) -> Self {
+        GapFill {
+            input: inputs[0].clone(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Plans `GapFill` nodes.
+#[derive(Debug, Default)]
+pub struct GapFillPlanner;
+
+#[async_trait]
+impl ExtensionPlanner for GapFillPlanner {
+    async fn plan_extension(
+        &self,
+        _planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        logical_inputs: &[&LogicalPlan],
+        physical_inputs: &[Arc<dyn ExecutionPlan>],
+        _session_state: &SessionState,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        let node = match node.as_any().downcast_ref::<GapFill>() {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+        // The physical input has the same columns as the logical one.
+        let input_schema = logical_inputs[0].schema();
+        let index = |col: &Column| input_schema.index_of_column(col);
+
+        let series = node.series.iter().map(index).collect::<Result<Vec<_>>>()?;
+        let fills = node
+            .fills
+            .iter()
+            .map(|(col, strategy)| Ok((index(col)?, *strategy)))
+            .collect::<Result<Vec<_>>>()?;
+        let schema: Schema = node.schema.as_ref().into();
+
+        Ok(Some(Arc::new(GapFillExec::try_new(
+            physical_inputs[0].clone(),
+            index(&node.time)?,
+            series,
+            fills,
+            node.stride,
+            node.start,
+            node.end,
+            Arc::new(schema),
+        )?)))
+    }
+}
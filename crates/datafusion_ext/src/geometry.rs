@@ -0,0 +1,628 @@
+//! Geometries stored as well-known binary (WKB).
+//!
+//! Only two dimensional geometries are supported. Z and M coordinates are
+//! accepted when reading WKB or WKT, but are dropped. PostGIS' extended WKB
+//! (EWKB) is accepted as well, with the SRID being ignored.
+
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+const WKB_POINT: u32 = 1;
+const WKB_LINESTRING: u32 = 2;
+const WKB_POLYGON: u32 = 3;
+const WKB_MULTIPOINT: u32 = 4;
+const WKB_MULTILINESTRING: u32 = 5;
+const WKB_MULTIPOLYGON: u32 = 6;
+const WKB_GEOMETRYCOLLECTION: u32 = 7;
+
+const EWKB_Z_FLAG: u32 = 0x8000_0000;
+const EWKB_M_FLAG: u32 = 0x4000_0000;
+const EWKB_SRID_FLAG: u32 = 0x2000_0000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum GeometryError {
+    #[error("invalid well-known binary: {0}")]
+    InvalidWkb(String),
+
+    #[error("invalid input syntax for type geometry: \"{0}\"")]
+    InvalidWkt(String),
+}
+
+pub type Result<T, E = GeometryError> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coord {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Coord {
+    pub fn new(x: f64, y: f64) -> Self {
+        Coord { x, y }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    /// A point. Empty points have NaN coordinates.
+    Point(Coord),
+    LineString(Vec<Coord>),
+    /// A polygon made up of an exterior ring followed by any number of
+    /// interior rings (holes).
+    Polygon(Vec<Vec<Coord>>),
+    MultiPoint(Vec<Coord>),
+    MultiLineString(Vec<Vec<Coord>>),
+    MultiPolygon(Vec<Vec<Vec<Coord>>>),
+    GeometryCollection(Vec<Geometry>),
+}
+
+impl Geometry {
+    /// Read a geometry from WKB or EWKB.
+    pub fn from_wkb(buf: &[u8]) -> Result<Self> {
+        let mut reader = WkbReader { buf, pos: 0 };
+        let geom = reader.read_geometry()?;
+        if reader.pos != buf.len() {
+            return Err(GeometryError::InvalidWkb(format!(
+                "{} trailing bytes",
+                buf.len() - reader.pos
+            )));
+        }
+        Ok(geom)
+    }
+
+    /// Write the geometry as little endian WKB.
+    pub fn to_wkb(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write_wkb(&mut buf);
+        buf
+    }
+
+    /// Parse a geometry from its well-known text (WKT) representation, e.g.
+    /// 'POINT(1 2)'. An optional SRID prefix (e.g. 'SRID=4326;') is ignored.
+    pub fn from_wkt(s: &str) -> Result<Self> {
+        let wkt = match s.trim_start().strip_prefix("SRID=") {
+            Some(rest) => rest
+                .split_once(';')
+                .map(|(_, wkt)| wkt)
+                .ok_or_else(|| GeometryError::InvalidWkt(s.to_string()))?,
+            None => s,
+        };
+        let mut parser = WktParser {
+            chars: wkt.chars().peekable(),
+        };
+        let geom = parser
+            .parse_geometry()
+            .ok_or_else(|| GeometryError::InvalidWkt(s.to_string()))?;
+        parser.skip_whitespace();
+        if parser.chars.peek().is_some() {
+            return Err(GeometryError::InvalidWkt(s.to_string()));
+        }
+        Ok(geom)
+    }
+
+    /// Get the name of the geometry type as used in WKT.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Geometry::Point(_) => "POINT",
+            Geometry::LineString(_) => "LINESTRING",
+            Geometry::Polygon(_) => "POLYGON",
+            Geometry::MultiPoint(_) => "MULTIPOINT",
+            Geometry::MultiLineString(_) => "MULTILINESTRING",
+            Geometry::MultiPolygon(_) => "MULTIPOLYGON",
+            Geometry::GeometryCollection(_) => "GEOMETRYCOLLECTION",
+        }
+    }
+
+    /// Returns true if the geometry doesn't contain any points.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Geometry::Point(c) => c.x.is_nan() && c.y.is_nan(),
+            Geometry::LineString(v) | Geometry::MultiPoint(v) => v.is_empty(),
+            Geometry::Polygon(v) | Geometry::MultiLineString(v) => v.is_empty(),
+            Geometry::MultiPolygon(v) => v.is_empty(),
+            Geometry::GeometryCollection(v) => v.iter().all(|g| g.is_empty()),
+        }
+    }
+
+    fn write_wkb(&self, buf: &mut Vec<u8>) {
+        fn write_coords(buf: &mut Vec<u8>, coords: &[Coord]) {
+            buf.extend_from_slice(&(coords.len() as u32).to_le_bytes());
+            for c in coords {
+                write_coord(buf, c);
+            }
+        }
+        fn write_coord(buf: &mut Vec<u8>, c: &Coord) {
+            buf.extend_from_slice(&c.x.to_le_bytes());
+            buf.extend_from_slice(&c.y.to_le_bytes());
+        }
+        fn write_rings(buf: &mut Vec<u8>, rings: &[Vec<Coord>]) {
+            buf.extend_from_slice(&(rings.len() as u32).to_le_bytes());
+            for ring in rings {
+                write_coords(buf, ring);
+            }
+        }
+        fn write_header(buf: &mut Vec<u8>, typ: u32) {
+            buf.push(1);
+            buf.extend_from_slice(&typ.to_le_bytes());
+        }
+
+        match self {
+            Geometry::Point(c) => {
+                write_header(buf, WKB_POINT);
+                write_coord(buf, c);
+            }
+            Geometry::LineString(coords) => {
+                write_header(buf, WKB_LINESTRING);
+                write_coords(buf, coords);
+            }
+            Geometry::Polygon(rings) => {
+                write_header(buf, WKB_POLYGON);
+                write_rings(buf, rings);
+            }
+            Geometry::MultiPoint(points) => {
+                write_header(buf, WKB_MULTIPOINT);
+                buf.extend_from_slice(&(points.len() as u32).to_le_bytes());
+                for c in points {
+                    Geometry::Point(*c).write_wkb(buf);
+                }
+            }
+            Geometry::MultiLineString(lines) => {
+                write_header(buf, WKB_MULTILINESTRING);
+                buf.extend_from_slice(&(lines.len() as u32).to_le_bytes());
+                for coords in lines {
+                    write_header(buf, WKB_LINESTRING);
+                    write_coords(buf, coords);
+                }
+            }
+            Geometry::MultiPolygon(polygons) => {
+                write_header(buf, WKB_MULTIPOLYGON);
+                buf.extend_from_slice(&(polygons.len() as u32).to_le_bytes());
+                for rings in polygons {
+                    write_header(buf, WKB_POLYGON);
+                    write_rings(buf, rings);
+                }
+            }
+            Geometry::GeometryCollection(geoms) => {
+                write_header(buf, WKB_GEOMETRYCOLLECTION);
+                buf.extend_from_slice(&(geoms.len() as u32).to_le_bytes());
+                for geom in geoms {
+                    geom.write_wkb(buf);
+                }
+            }
+        }
+    }
+}
+
+/// Formats the geometry as WKT.
+impl fmt::Display for Geometry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_coords(f: &mut fmt::Formatter<'_>, coords: &[Coord]) -> fmt::Result {
+            write!(f, "(")?;
+            for (i, c) in coords.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{} {}", c.x, c.y)?;
+            }
+            write!(f, ")")
+        }
+        fn write_rings(f: &mut fmt::Formatter<'_>, rings: &[Vec<Coord>]) -> fmt::Result {
+            write!(f, "(")?;
+            for (i, ring) in rings.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write_coords(f, ring)?;
+            }
+            write!(f, ")")
+        }
+
+        write!(f, "{}", self.type_name())?;
+        if self.is_empty() {
+            return write!(f, " EMPTY");
+        }
+        match self {
+            Geometry::Point(c) => write_coords(f, &[*c]),
+            Geometry::LineString(coords) => write_coords(f, coords),
+            Geometry::Polygon(rings) => write_rings(f, rings),
+            Geometry::MultiPoint(points) => {
+                write!(f, "(")?;
+                for (i, c) in points.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_coords(f, &[*c])?;
+                }
+                write!(f, ")")
+            }
+            Geometry::MultiLineString(lines) => write_rings(f, lines),
+            Geometry::MultiPolygon(polygons) => {
+                write!(f, "(")?;
+                for (i, rings) in polygons.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_rings(f, rings)?;
+                }
+                write!(f, ")")
+            }
+            Geometry::GeometryCollection(geoms) => {
+                write!(f, "(")?;
+                for (i, geom) in geoms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{geom}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+struct WkbReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WkbReader<'a> {
+    fn read_bytes<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + N)
+            .ok_or_else(|| GeometryError::InvalidWkb("unexpected end of input".to_string()))?;
+        self.pos += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn read_u32(&mut self, le: bool) -> Result<u32> {
+        let bytes = self.read_bytes()?;
+        Ok(if le {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_f64(&mut self, le: bool) -> Result<f64> {
+        let bytes = self.read_bytes()?;
+        Ok(if le {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_coord(&mut self, le: bool, dims: usize) -> Result<Coord> {
+        let coord = Coord::new(self.read_f64(le)?, self.read_f64(le)?);
+        // Skip Z and M.
+        for _ in 2..dims {
+            self.read_f64(le)?;
+        }
+        Ok(coord)
+    }
+
+    fn read_coords(&mut self, le: bool, dims: usize) -> Result<Vec<Coord>> {
+        let n = self.read_u32(le)?;
+        // Don't trust the count for preallocating.
+        let mut coords = Vec::new();
+        for _ in 0..n {
+            coords.push(self.read_coord(le, dims)?);
+        }
+        Ok(coords)
+    }
+
+    fn read_rings(&mut self, le: bool, dims: usize) -> Result<Vec<Vec<Coord>>> {
+        let n = self.read_u32(le)?;
+        let mut rings = Vec::new();
+        for _ in 0..n {
+            rings.push(self.read_coords(le, dims)?);
+        }
+        Ok(rings)
+    }
+
+    fn read_geometries<T>(
+        &mut self,
+        le: bool,
+        f: impl Fn(Geometry) -> Option<T>,
+        expected: &str,
+    ) -> Result<Vec<T>> {
+        let n = self.read_u32(le)?;
+        let mut geoms = Vec::new();
+        for _ in 0..n {
+            let geom = self.read_geometry()?;
+            let actual = geom.type_name();
+            geoms.push(f(geom).ok_or_else(|| {
+                GeometryError::InvalidWkb(format!("expected {expected}, got {actual}"))
+            })?);
+        }
+        Ok(geoms)
+    }
+
+    fn read_geometry(&mut self) -> Result<Geometry> {
+        let le = match self.read_bytes::<1>()?[0] {
+            0 => false,
+            1 => true,
+            other => {
+                return Err(GeometryError::InvalidWkb(format!(
+                    "invalid byte order: {other}"
+                )))
+            }
+        };
+
+        let mut typ = self.read_u32(le)?;
+        let mut dims = 2;
+        // EWKB stores the dimensions and SRID as flags.
+        if typ & EWKB_Z_FLAG != 0 {
+            dims += 1;
+        }
+        if typ & EWKB_M_FLAG != 0 {
+            dims += 1;
+        }
+        if typ & EWKB_SRID_FLAG != 0 {
+            self.read_u32(le)?;
+        }
+        typ &= !(EWKB_Z_FLAG | EWKB_M_FLAG | EWKB_SRID_FLAG);
+        // ISO WKB uses 1000 for Z, 2000 for M, and 3000 for ZM.
+        dims += match typ / 1000 {
+            0 => 0,
+            1 | 2 => 1,
+            3 => 2,
+            _ => {
+                return Err(GeometryError::InvalidWkb(format!(
+                    "invalid geometry type: {typ}"
+                )))
+            }
+        };
+
+        Ok(match typ % 1000 {
+            WKB_POINT => Geometry::Point(self.read_coord(le, dims)?),
+            WKB_LINESTRING => Geometry::LineString(self.read_coords(le, dims)?),
+            WKB_POLYGON => Geometry::Polygon(self.read_rings(le, dims)?),
+            WKB_MULTIPOINT => Geometry::MultiPoint(self.read_geometries(
+                le,
+                |g| match g {
+                    Geometry::Point(c) => Some(c),
+                    _ => None,
+                },
+                "POINT",
+            )?),
+            WKB_MULTILINESTRING => Geometry::MultiLineString(self.read_geometries(
+                le,
+                |g| match g {
+                    Geometry::LineString(v) => Some(v),
+                    _ => None,
+                },
+                "LINESTRING",
+            )?),
+            WKB_MULTIPOLYGON => Geometry::MultiPolygon(self.read_geometries(
+                le,
+                |g| match g {
+                    Geometry::Polygon(v) => Some(v),
+                    _ => None,
+                },
+                "POLYGON",
+            )?),
+            WKB_GEOMETRYCOLLECTION => {
+                Geometry::GeometryCollection(self.read_geometries(le, Some, "GEOMETRY")?)
+            }
+            _ => {
+                return Err(GeometryError::InvalidWkb(format!(
+                    "invalid geometry type: {typ}"
+                )))
+            }
+        })
+    }
+}
+
+/// Recursive descent parser for WKT. Returns `None` on any syntax error.
+struct WktParser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> WktParser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    /// Consume the next non-whitespace character if it's `c`.
+    fn consume(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        self.chars.next_if_eq(&c).is_some()
+    }
+
+    fn peek_is(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        self.chars.peek() == Some(&c)
+    }
+
+    fn word(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        let mut word = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+            word.push(c.to_ascii_uppercase());
+        }
+        (!word.is_empty()).then_some(word)
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_whitespace();
+        let mut num = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            num.push(c);
+        }
+        num.parse().ok()
+    }
+
+    /// Parse a coordinate with the given number of dimensions, dropping Z and
+    /// M.
+    fn coord(&mut self, dims: usize) -> Option<Coord> {
+        let coord = Coord::new(self.number()?, self.number()?);
+        for _ in 2..dims {
+            self.number()?;
+        }
+        Some(coord)
+    }
+
+    /// Parse a comma separated list of items in parentheses.
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Option<T>) -> Option<Vec<T>> {
+        if !self.consume('(') {
+            return None;
+        }
+        let mut items = vec![item(self)?];
+        while self.consume(',') {
+            items.push(item(self)?);
+        }
+        self.consume(')').then_some(items)
+    }
+
+    fn coords(&mut self, dims: usize) -> Option<Vec<Coord>> {
+        self.list(|p| p.coord(dims))
+    }
+
+    fn rings(&mut self, dims: usize) -> Option<Vec<Vec<Coord>>> {
+        self.list(|p| p.coords(dims))
+    }
+
+    fn parse_geometry(&mut self) -> Option<Geometry> {
+        let tag = self.word()?;
+        // Dimensions may either be attached to the tag, or follow it.
+        let (tag, suffix) = match ["ZM", "Z", "M"].iter().find(|s| tag.ends_with(**s)) {
+            Some(suffix) => (tag[..tag.len() - suffix.len()].to_string(), suffix.len()),
+            _ => (tag, 0),
+        };
+        let mut dims = 2 + suffix;
+        if suffix == 0 && !self.peek_is('(') {
+            match self.word()?.as_str() {
+                "EMPTY" => return empty_geometry(&tag),
+                "Z" | "M" => dims = 3,
+                "ZM" => dims = 4,
+                _ => return None,
+            }
+        }
+        if !self.peek_is('(') {
+            return match self.word()?.as_str() {
+                "EMPTY" => empty_geometry(&tag),
+                _ => None,
+            };
+        }
+
+        Some(match tag.as_str() {
+            "POINT" => {
+                let mut coords = self.coords(dims)?;
+                if coords.len() != 1 {
+                    return None;
+                }
+                Geometry::Point(coords.pop().unwrap())
+            }
+            "LINESTRING" => Geometry::LineString(self.coords(dims)?),
+            "POLYGON" => Geometry::Polygon(self.rings(dims)?),
+            "MULTIPOINT" => {
+                // Points may or may not be wrapped in parentheses.
+                Geometry::MultiPoint(self.list(|p| {
+                    if p.consume('(') {
+                        let coord = p.coord(dims)?;
+                        p.consume(')').then_some(coord)
+                    } else {
+                        p.coord(dims)
+                    }
+                })?)
+            }
+            "MULTILINESTRING" => Geometry::MultiLineString(self.rings(dims)?),
+            "MULTIPOLYGON" => Geometry::MultiPolygon(self.list(|p| p.rings(dims))?),
+            "GEOMETRYCOLLECTION" => Geometry::GeometryCollection(self.list(Self::parse_geometry)?),
+            _ => return None,
+        })
+    }
+}
+
+fn empty_geometry(tag: &str) -> Option<Geometry> {
+    Some(match tag {
+        "POINT" => Geometry::Point(Coord::new(f64::NAN, f64::NAN)),
+        "LINESTRING" => Geometry::LineString(Vec::new()),
+        "POLYGON" => Geometry::Polygon(Vec::new()),
+        "MULTIPOINT" => Geometry::MultiPoint(Vec::new()),
+        "MULTILINESTRING" => Geometry::MultiLineString(Vec::new()),
+        "MULTIPOLYGON" => Geometry::MultiPolygon(Vec::new()),
+        "GEOMETRYCOLLECTION" => Geometry::GeometryCollection(Vec::new()),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wkt_round_trip() {
+        let test_cases = [
+            "POINT(1 2)",
+            "POINT(-1.5 2.25)",
+            "POINT EMPTY",
+            "LINESTRING(0 0,1 1,2 0)",
+            "POLYGON((0 0,4 0,4 4,0 4,0 0),(1 1,2 1,2 2,1 1))",
+            "MULTIPOINT((1 2),(3 4))",
+            "MULTILINESTRING((0 0,1 1),(2 2,3 3))",
+            "MULTIPOLYGON(((0 0,1 0,1 1,0 0)),((2 2,3 2,3 3,2 2)))",
+            "GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(0 0,1 1))",
+            "GEOMETRYCOLLECTION EMPTY",
+        ];
+
+        for wkt in test_cases {
+            let geom = Geometry::from_wkt(wkt).unwrap();
+            assert_eq!(wkt, geom.to_string());
+
+            let wkb = geom.to_wkb();
+            let from_wkb = Geometry::from_wkb(&wkb).unwrap();
+            assert_eq!(wkt, from_wkb.to_string());
+        }
+    }
+
+    #[test]
+    fn parse_wkt_variants() {
+        let test_cases = [
+            ("point (1 2)", "POINT(1 2)"),
+            ("SRID=4326;POINT(1 2)", "POINT(1 2)"),
+            ("POINT Z (1 2 3)", "POINT(1 2)"),
+            ("POINTM(1 2 3)", "POINT(1 2)"),
+            ("MULTIPOINT(1 2, 3 4)", "MULTIPOINT((1 2),(3 4))"),
+            ("  LINESTRING ( 0 0 , 1e1 -1 )  ", "LINESTRING(0 0,10 -1)"),
+        ];
+        for (input, expected) in test_cases {
+            assert_eq!(expected, Geometry::from_wkt(input).unwrap().to_string());
+        }
+
+        for input in [
+            "",
+            "POINT",
+            "POINT()",
+            "POINT(1)",
+            "POINT(1 2",
+            "POINT(1 2) extra",
+            "CIRCLE(1 2)",
+            "LINESTRING(0 0,)",
+        ] {
+            Geometry::from_wkt(input).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn read_ewkb() {
+        // SRID=4326;POINT Z (1 2 3), big endian.
+        let mut buf = vec![0];
+        buf.extend_from_slice(&(WKB_POINT | EWKB_Z_FLAG | EWKB_SRID_FLAG).to_be_bytes());
+        buf.extend_from_slice(&4326_u32.to_be_bytes());
+        for v in [1.0_f64, 2.0, 3.0] {
+            buf.extend_from_slice(&v.to_be_bytes());
+        }
+
+        let geom = Geometry::from_wkb(&buf).unwrap();
+        assert_eq!(Geometry::Point(Coord::new(1.0, 2.0)), geom);
+
+        Geometry::from_wkb(&buf[..buf.len() - 1]).unwrap_err();
+    }
+}
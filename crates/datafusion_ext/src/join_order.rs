@@ -0,0 +1,487 @@
+//! Cost based ordering of inner joins.
+//!
+//! Joins are otherwise executed in the order they're written in the query. For
+//! a group of inner equi-joins where row counts are known for every input (e.g.
+//! from `ANALYZE TABLE`), the group is rebuilt greedily: starting from the
+//! smallest input, the connected input producing the smallest estimated result
+//! is joined next. The smaller side of each join is placed on the left since
+//! that's the side hash joins build their table from.
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use datafusion::common::{Column, DFSchema};
+use datafusion::datasource::source_as_provider;
+use datafusion::error::Result;
+use datafusion::logical_expr::{
+    build_join_schema, Expr, Join, JoinConstraint, JoinType, LogicalPlan, LogicalPlanBuilder,
+};
+use datafusion::optimizer::optimizer::ApplyOrder;
+use datafusion::optimizer::{OptimizerConfig, OptimizerRule};
+
+/// Fraction of rows assumed to pass a filter.
+const FILTER_SELECTIVITY: f64 = 0.2;
+
+/// Reorders groups of inner joins using table statistics.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JoinReorder {}
+
+impl JoinReorder {
+    pub fn new() -> Self {
+        JoinReorder {}
+    }
+}
+
+impl OptimizerRule for JoinReorder {
+    fn try_optimize(
+        &self,
+        plan: &LogicalPlan,
+        _config: &dyn OptimizerConfig,
+    ) -> Result<Option<LogicalPlan>> {
+        match plan {
+            LogicalPlan::Join(join) if is_reorderable(join) => (),
+            _ => return Ok(None),
+        }
+
+        let mut leaves = Vec::new();
+        let mut conditions = Vec::new();
+        flatten(plan, &mut leaves, &mut conditions);
+
+        let inputs = match leaves
+            .into_iter()
+            .map(|plan| {
+                Some(Input {
+                    estimate: estimate(&plan)?,
+                    key: plan.display_indent().to_string(),
+                    plan,
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(inputs) => inputs,
+            // Keep the written order if anything is unknown.
+            None => return Ok(None),
+        };
+
+        let reordered = match reorder(inputs, conditions)? {
+            Some(reordered) => reordered,
+            None => return Ok(None),
+        };
+        if &reordered == plan {
+            return Ok(None);
+        }
+
+        // Joins output the columns of both sides in order, restore the
+        // original column order.
+        let columns = plan
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| Expr::Column(field.qualified_column()))
+            .collect::<Vec<_>>();
+        let plan = LogicalPlanBuilder::from(reordered)
+            .project(columns)?
+            .build()?;
+        Ok(Some(plan))
+    }
+
+    fn name(&self) -> &str {
+        "join_reorder"
+    }
+
+    fn apply_order(&self) -> Option<ApplyOrder> {
+        Some(ApplyOrder::TopDown)
+    }
+}
+
+fn is_reorderable(join: &Join) -> bool {
+    join.join_type == JoinType::Inner
+        && join.join_constraint == JoinConstraint::On
+        && join.filter.is_none()
+        && !join.null_equals_null
+        && !join.on.is_empty()
+}
+
+/// Collect the inputs and conditions of nested reorderable joins.
+fn flatten(plan: &LogicalPlan, leaves: &mut Vec<LogicalPlan>, conditions: &mut Vec<(Expr, Expr)>) {
+    match plan {
+        LogicalPlan::Join(join) if is_reorderable(join) => {
+            flatten(&join.left, leaves, conditions);
+            flatten(&join.right, leaves, conditions);
+            conditions.extend(join.on.iter().cloned());
+        }
+        other => leaves.push(other.clone()),
+    }
+}
+
+/// Estimated size of a plan's output.
+#[derive(Debug, Clone)]
+struct Estimate {
+    rows: f64,
+    /// Number of distinct values for columns where it's known.
+    distinct: HashMap<Column, f64>,
+}
+
+impl Estimate {
+    fn with_rows(mut self, rows: f64) -> Self {
+        self.rows = rows.max(1.0);
+        for distinct in self.distinct.values_mut() {
+            *distinct = distinct.min(self.rows);
+        }
+        self
+    }
+
+    /// Number of distinct values for a join key, if it's a column.
+    fn distinct(&self, expr: &Expr) -> Option<f64> {
+        match expr {
+            Expr::Column(col) => self.distinct.get(col).copied(),
+            _ => None,
+        }
+    }
+}
+
+fn estimate(plan: &LogicalPlan) -> Option<Estimate> {
+    match plan {
+        LogicalPlan::TableScan(scan) => {
+            let stats = source_as_provider(&scan.source).ok()?.statistics()?;
+            let mut rows = stats.num_rows? as f64;
+            let columns = stats.column_statistics.unwrap_or_default();
+
+            let mut distinct = HashMap::new();
+            for (idx, field) in scan.projected_schema.fields().iter().enumerate() {
+                let table_idx = match &scan.projection {
+                    Some(projection) => projection[idx],
+                    None => idx,
+                };
+                if let Some(n) = columns.get(table_idx).and_then(|col| col.distinct_count) {
+                    distinct.insert(field.qualified_column(), n as f64);
+                }
+            }
+
+            if !scan.filters.is_empty() {
+                rows *= FILTER_SELECTIVITY;
+            }
+            if let Some(fetch) = scan.fetch {
+                rows = rows.min(fetch as f64);
+            }
+            Some(Estimate { rows, distinct }.with_rows(rows))
+        }
+        LogicalPlan::Filter(filter) => {
+            let input = estimate(&filter.input)?;
+            let rows = input.rows * FILTER_SELECTIVITY;
+            Some(input.with_rows(rows))
+        }
+        LogicalPlan::Limit(limit) => {
+            let input = estimate(&limit.input)?;
+            match limit.fetch {
+                Some(fetch) => {
+                    let rows = input.rows.min(fetch as f64);
+                    Some(input.with_rows(rows))
+                }
+                None => Some(input),
+            }
+        }
+        LogicalPlan::Projection(projection) => {
+            let input = estimate(&projection.input)?;
+            let distinct = projection
+                .expr
+                .iter()
+                .zip(projection.schema.fields())
+                .filter_map(|(expr, field)| {
+                    let col = match expr.clone().unalias() {
+                        Expr::Column(col) => col,
+                        _ => return None,
+                    };
+                    input
+                        .distinct
+                        .get(&col)
+                        .map(|n| (field.qualified_column(), *n))
+                })
+                .collect();
+            Some(Estimate {
+                rows: input.rows,
+                distinct,
+            })
+        }
+        LogicalPlan::SubqueryAlias(alias) => {
+            let input = estimate(&alias.input)?;
+            let distinct = alias
+                .input
+                .schema()
+                .fields()
+                .iter()
+                .zip(alias.schema.fields())
+                .filter_map(|(from, to)| {
+                    input
+                        .distinct
+                        .get(&from.qualified_column())
+                        .map(|n| (to.qualified_column(), *n))
+                })
+                .collect();
+            Some(Estimate {
+                rows: input.rows,
+                distinct,
+            })
+        }
+        _ => None,
+    }
+}
+
+struct Input {
+    plan: LogicalPlan,
+    estimate: Estimate,
+    /// Breaks ties between equally sized inputs so the chosen order doesn't
+    /// depend on the written one.
+    key: String,
+}
+
+impl Input {
+    fn cmp_size(&self, other: &Input) -> Ordering {
+        self.estimate
+            .rows
+            .total_cmp(&other.estimate.rows)
+            .then_with(|| self.key.cmp(&other.key))
+    }
+}
+
+/// Greedily join the inputs, returns `None` if they can't all be joined using
+/// the conditions.
+fn reorder(
+    mut inputs: Vec<Input>,
+    mut conditions: Vec<(Expr, Expr)>,
+) -> Result<Option<LogicalPlan>> {
+    let first = (0..inputs.len())
+        .min_by(|a, b| inputs[*a].cmp_size(&inputs[*b]))
+        .expect("joins have at least two inputs");
+    let mut acc = inputs.remove(first);
+
+    while !inputs.is_empty() {
+        // (input index, condition indexes, estimated rows)
+        let mut best: Option<(usize, Vec<usize>, f64)> = None;
+        for (idx, input) in inputs.iter().enumerate() {
+            let on = conditions
+                .iter()
+                .enumerate()
+                .filter(|(_, (left, right))| {
+                    orient(acc.plan.schema(), input.plan.schema(), left, right).is_some()
+                })
+                .map(|(cond_idx, _)| cond_idx)
+                .collect::<Vec<_>>();
+            if on.is_empty() {
+                continue;
+            }
+
+            let rows = join_rows(
+                &acc,
+                input,
+                on.iter().map(|cond_idx| &conditions[*cond_idx]),
+            );
+            let better = match &best {
+                Some((best_idx, _, best_rows)) => match rows.total_cmp(best_rows) {
+                    Ordering::Less => true,
+                    Ordering::Equal => input.key < inputs[*best_idx].key,
+                    Ordering::Greater => false,
+                },
+                None => true,
+            };
+            if better {
+                best = Some((idx, on, rows));
+            }
+        }
+
+        let (idx, on, rows) = match best {
+            Some(best) => best,
+            // Cross join required, leave it as written.
+            None => return Ok(None),
+        };
+
+        let next = inputs.remove(idx);
+        let mut pairs = Vec::with_capacity(on.len());
+        for cond_idx in on.into_iter().rev() {
+            let (left, right) = conditions.remove(cond_idx);
+            pairs.push(
+                orient(acc.plan.schema(), next.plan.schema(), &left, &right)
+                    .expect("condition connects inputs"),
+            );
+        }
+        pairs.reverse();
+
+        acc = join(acc, next, pairs, rows)?;
+    }
+
+    if !conditions.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(acc.plan))
+}
+
+/// Orient a join condition so that the first expression references the left
+/// schema and the second the right one.
+fn orient(left: &DFSchema, right: &DFSchema, a: &Expr, b: &Expr) -> Option<(Expr, Expr)> {
+    let references = |schema: &DFSchema, expr: &Expr| match expr.to_columns() {
+        Ok(cols) => !cols.is_empty() && cols.iter().all(|col| schema.index_of_column(col).is_ok()),
+        Err(_) => false,
+    };
+
+    if references(left, a) && references(right, b) {
+        Some((a.clone(), b.clone()))
+    } else if references(left, b) && references(right, a) {
+        Some((b.clone(), a.clone()))
+    } else {
+        None
+    }
+}
+
+/// Estimate the rows of an equi-join, assuming every key on the side with fewer
+/// distinct values has matches.
+fn join_rows<'a>(
+    left: &Input,
+    right: &Input,
+    conditions: impl Iterator<Item = &'a (Expr, Expr)>,
+) -> f64 {
+    let (left_rows, right_rows) = (left.estimate.rows, right.estimate.rows);
+
+    let mut selectivity = None::<f64>;
+    for (a, b) in conditions {
+        let distinct = [a, b]
+            .iter()
+            .filter_map(|expr| {
+                left.estimate
+                    .distinct(expr)
+                    .or_else(|| right.estimate.distinct(expr))
+            })
+            .fold(None::<f64>, |acc, n| Some(acc.map_or(n, |acc| acc.max(n))));
+        if let Some(distinct) = distinct {
+            selectivity = Some(selectivity.map_or(distinct, |s| s.max(distinct)));
+        }
+    }
+
+    // Without distinct counts, assume each row of the larger side matches one
+    // row of the smaller one.
+    let divisor = selectivity.unwrap_or_else(|| left_rows.min(right_rows));
+    left_rows * right_rows / divisor.max(1.0)
+}
+
+fn join(acc: Input, next: Input, on: Vec<(Expr, Expr)>, rows: f64) -> Result<Input> {
+    let mut distinct = acc.estimate.distinct;
+    distinct.extend(next.estimate.distinct);
+    let estimate = Estimate { rows, distinct }.with_rows(rows);
+    let key = format!("({} join {})", acc.key, next.key);
+
+    // Build the hash table from the smaller side.
+    let (left, right, on) = if next.estimate.rows < acc.estimate.rows {
+        let on = on.into_iter().map(|(a, b)| (b, a)).collect();
+        (next.plan, acc.plan, on)
+    } else {
+        (acc.plan, next.plan, on)
+    };
+
+    let schema = build_join_schema(left.schema(), right.schema(), &JoinType::Inner)?;
+    let plan = LogicalPlan::Join(Join {
+        left: Arc::new(left),
+        right: Arc::new(right),
+        on,
+        filter: None,
+        join_type: JoinType::Inner,
+        join_constraint: JoinConstraint::On,
+        schema: Arc::new(schema),
+        null_equals_null: false,
+    });
+
+    Ok(Input {
+        plan,
+        estimate,
+        key,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::datasource::empty::EmptyTable;
+    use datafusion::datasource::provider_as_source;
+    use datafusion::optimizer::OptimizerContext;
+    use datafusion::physical_plan::Statistics;
+    use protogen::metastore::types::catalog::RuntimePreference;
+
+    use super::*;
+    use crate::runtime::table_provider::RuntimeAwareTableProvider;
+
+    fn scan(name: &str, columns: &[&str], num_rows: Option<usize>) -> LogicalPlanBuilder {
+        let schema = Schema::new(
+            columns
+                .iter()
+                .map(|col| Field::new(*col, DataType::Int64, false))
+                .collect::<Vec<_>>(),
+        );
+        let mut provider = RuntimeAwareTableProvider::new(
+            RuntimePreference::Local,
+            Arc::new(EmptyTable::new(Arc::new(schema))),
+        );
+        if let Some(num_rows) = num_rows {
+            provider = provider.with_statistics(Statistics {
+                num_rows: Some(num_rows),
+                ..Default::default()
+            });
+        }
+        LogicalPlanBuilder::scan(name, provider_as_source(Arc::new(provider)), None).unwrap()
+    }
+
+    /// Build `big JOIN mid ON big.a = mid.a JOIN small ON mid.b = small.b`.
+    fn three_way_join(small_rows: Option<usize>) -> LogicalPlan {
+        scan("big", &["a"], Some(1000))
+            .join(
+                scan("mid", &["a", "b"], Some(100)).build().unwrap(),
+                JoinType::Inner,
+                (vec!["big.a"], vec!["mid.a"]),
+                None,
+            )
+            .unwrap()
+            .join(
+                scan("small", &["b"], small_rows).build().unwrap(),
+                JoinType::Inner,
+                (vec!["mid.b"], vec!["small.b"]),
+                None,
+            )
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    /// Table names in the order they're joined.
+    fn join_order(plan: &LogicalPlan) -> Vec<String> {
+        match plan {
+            LogicalPlan::TableScan(scan) => vec![scan.table_name.to_string()],
+            other => other.inputs().into_iter().flat_map(join_order).collect(),
+        }
+    }
+
+    #[test]
+    fn reorders_by_size() {
+        let plan = three_way_join(Some(10));
+        let optimized = JoinReorder::new()
+            .try_optimize(&plan, &OptimizerContext::new())
+            .unwrap()
+            .expect("plan should be reordered");
+
+        assert_eq!(vec!["small", "mid", "big"], join_order(&optimized));
+        assert!(optimized.schema().equivalent_names_and_types(plan.schema()));
+
+        // Already ordered.
+        let join = optimized.inputs()[0].clone();
+        let again = JoinReorder::new()
+            .try_optimize(&join, &OptimizerContext::new())
+            .unwrap();
+        assert!(again.is_none());
+    }
+
+    #[test]
+    fn keeps_order_without_statistics() {
+        let plan = three_way_join(None);
+        let optimized = JoinReorder::new()
+            .try_optimize(&plan, &OptimizerContext::new())
+            .unwrap();
+        assert!(optimized.is_none());
+    }
+}
@@ -1,10 +1,20 @@
+pub mod asof;
 pub mod cast;
 pub mod errors;
+pub mod gapfill;
+pub mod geometry;
+pub mod join_order;
 pub mod metrics;
 pub mod planner;
+pub mod recursive;
+pub mod reload;
 pub mod runtime;
+pub mod sample;
 pub mod session_metrics;
+pub mod suggest;
+pub mod types;
 pub mod vars;
+pub mod warnings;
 pub use planner::*;
 pub mod functions;
 pub mod transform;
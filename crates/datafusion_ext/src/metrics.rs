@@ -5,7 +5,10 @@ use datafusion::{
     execution::TaskContext,
     physical_expr::PhysicalSortExpr,
     physical_plan::{
-        metrics::{BaselineMetrics, ExecutionPlanMetricsSet, Gauge, MetricBuilder, MetricsSet},
+        metrics::{
+            BaselineMetrics, Count, ExecutionPlanMetricsSet, Gauge, Metric, MetricBuilder,
+            MetricValue, MetricsSet,
+        },
         DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
         SendableRecordBatchStream, Statistics,
     },
@@ -18,6 +21,7 @@ use std::{fmt::Debug, sync::Arc};
 
 const BYTES_READ_GAUGE_NAME: &str = "bytes_read";
 const BYTES_WRITTEN_GAUGE_NAME: &str = "bytes_written";
+const RETRIES_COUNTER_NAME: &str = "retries";
 
 #[derive(Debug, Default)]
 pub struct DataSourceMetricsOpts {
@@ -190,6 +194,10 @@ impl DataSourceMetricsOptsType for WriteOnlyDataSourceMetricsOptsType {
 pub struct DataSourceMetricsExecAdapter<T: DataSourceMetricsOptsType> {
     child: Arc<dyn ExecutionPlan>,
     metrics: ExecutionPlanMetricsSet,
+    /// Pruning done when building the scan, shown when explaining the plan.
+    pruning: Option<ScanPruning>,
+    /// Requests retried by the source after transient errors.
+    retries: Option<Count>,
 
     _phantom: PhantomData<T>,
 }
@@ -205,9 +213,36 @@ impl<T: DataSourceMetricsOptsType> DataSourceMetricsExecAdapter<T> {
         Self {
             child: plan,
             metrics: ExecutionPlanMetricsSet::new(),
+            pruning: None,
+            retries: None,
             _phantom: PhantomData,
         }
     }
+
+    pub fn with_pruning(mut self, pruning: ScanPruning) -> Self {
+        self.pruning = Some(pruning);
+        self
+    }
+
+    pub fn pruning(&self) -> Option<&ScanPruning> {
+        self.pruning.as_ref()
+    }
+
+    /// Report `retries` as the number of requests retried by the source.
+    ///
+    /// The count is shared with whatever retries the requests, and may be
+    /// updated while the plan executes.
+    pub fn with_retries(mut self, retries: Count) -> Self {
+        self.metrics.register(Arc::new(Metric::new(
+            MetricValue::Count {
+                name: RETRIES_COUNTER_NAME.into(),
+                count: retries.clone(),
+            },
+            None,
+        )));
+        self.retries = Some(retries);
+        self
+    }
 }
 
 impl<T: DataSourceMetricsOptsType> ExecutionPlan for DataSourceMetricsExecAdapter<T> {
@@ -235,7 +270,12 @@ impl<T: DataSourceMetricsOptsType> ExecutionPlan for DataSourceMetricsExecAdapte
         self: Arc<Self>,
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        Ok(Arc::new(Self::new(children[0].clone())))
+        let mut plan = Self::new(children[0].clone());
+        plan.pruning = self.pruning.clone();
+        if let Some(retries) = &self.retries {
+            plan = plan.with_retries(retries.clone());
+        }
+        Ok(Arc::new(plan))
     }
 
     fn execute(
@@ -263,7 +303,53 @@ impl<T: DataSourceMetricsOptsType> ExecutionPlan for DataSourceMetricsExecAdapte
 
 impl<T: DataSourceMetricsOptsType> DisplayAs for DataSourceMetricsExecAdapter<T> {
     fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}DataSourceMetricsExecAdapter", T::DISPLAY_NAME_PREFIX)
+        write!(f, "{}DataSourceMetricsExecAdapter", T::DISPLAY_NAME_PREFIX)?;
+        if let Some(pruning) = &self.pruning {
+            write!(f, ": {pruning}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Files and row groups skipped while building a scan.
+///
+/// Files are pruned using the min/max values of their columns. For parquet
+/// files, row groups are checked using the same and bloom filters are checked
+/// for equality and `IN` filters, files are skipped if none of their row
+/// groups can match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanPruning {
+    pub files: usize,
+    /// Files skipped because their min/max values can't match the filters.
+    pub files_pruned_by_statistics: usize,
+    /// Files skipped because their bloom filters don't contain the values
+    /// being looked up.
+    pub files_pruned_by_bloom_filter: usize,
+    /// Row groups in the files that are scanned, `None` if the format doesn't
+    /// have row groups.
+    pub row_groups: Option<usize>,
+    /// Row groups whose min/max values can't match the filters.
+    pub row_groups_pruned_by_statistics: usize,
+    /// Row groups whose bloom filters don't contain the values being looked
+    /// up.
+    pub row_groups_pruned_by_bloom_filter: usize,
+}
+
+impl fmt::Display for ScanPruning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "files={}, files_pruned_by_statistics={}, files_pruned_by_bloom_filter={}",
+            self.files, self.files_pruned_by_statistics, self.files_pruned_by_bloom_filter
+        )?;
+        if let Some(row_groups) = self.row_groups {
+            write!(
+                f,
+                ", row_groups={row_groups}, row_groups_pruned_by_statistics={}, row_groups_pruned_by_bloom_filter={}",
+                self.row_groups_pruned_by_statistics, self.row_groups_pruned_by_bloom_filter
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -382,6 +468,8 @@ pub struct AggregatedMetrics {
     pub bytes_read: u64,
     /// Total bytes written.
     pub bytes_written: Option<u64>,
+    /// Total requests retried after transient errors.
+    pub retries: u64,
 }
 
 impl AggregatedMetrics {
@@ -394,6 +482,7 @@ impl AggregatedMetrics {
             elapsed_compute_ns: 0,
             bytes_read: 0,
             bytes_written: None,
+            retries: 0,
         };
         agg.aggregate_recurse(plan);
         agg
@@ -406,6 +495,10 @@ impl AggregatedMetrics {
                 .sum_by_name(BYTES_READ_GAUGE_NAME)
                 .map(|m| m.as_usize() as u64)
                 .unwrap_or_default();
+            self.retries += metrics
+                .sum_by_name(RETRIES_COUNTER_NAME)
+                .map(|m| m.as_usize() as u64)
+                .unwrap_or_default();
 
             if self.bytes_written.is_none() {
                 // Only count bytes written if they were not counted before.
@@ -423,3 +516,50 @@ impl AggregatedMetrics {
         }
     }
 }
+
+/// Shared handle to the plan a session is executing, for reporting progress
+/// while the plan runs.
+///
+/// Plan metrics are updated during execution, so snapshots taken before the
+/// plan completes report partial values.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionProgress {
+    plan: Arc<parking_lot::Mutex<Option<Arc<dyn ExecutionPlan>>>>,
+}
+
+/// Metrics for a plan at some point during its execution.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProgressSnapshot {
+    /// Rows output by the root of the plan so far.
+    pub output_rows: usize,
+    /// Total bytes read so far.
+    pub bytes_read: u64,
+    /// Total compute time so far, summed across all operators.
+    pub elapsed_compute_ns: u64,
+}
+
+impl ExecutionProgress {
+    /// Set the plan that's being executed.
+    pub fn set_plan(&self, plan: Arc<dyn ExecutionPlan>) {
+        *self.plan.lock() = Some(plan);
+    }
+
+    /// Clear the plan, e.g. before planning the next query.
+    pub fn clear(&self) {
+        *self.plan.lock() = None;
+    }
+
+    /// Get the current metrics for the plan, if one is set.
+    pub fn snapshot(&self) -> Option<ProgressSnapshot> {
+        let plan = self.plan.lock().clone()?;
+        let agg = AggregatedMetrics::new_from_plan(plan.as_ref());
+        Some(ProgressSnapshot {
+            output_rows: plan
+                .metrics()
+                .and_then(|m| m.output_rows())
+                .unwrap_or_default(),
+            bytes_read: agg.bytes_read,
+            elapsed_compute_ns: agg.elapsed_compute_ns,
+        })
+    }
+}
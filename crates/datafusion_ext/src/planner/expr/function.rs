@@ -16,6 +16,7 @@
 // under the License.
 
 use crate::planner::{AsyncContextProvider, SqlQueryPlanner};
+use crate::suggest::{closest_matches, did_you_mean};
 use datafusion::common::{DFSchema, DataFusionError, Result};
 use datafusion::logical_expr::expr::ScalarFunction;
 use datafusion::logical_expr::utils::COUNT_STAR_EXPANSION;
@@ -169,7 +170,13 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
         }
 
         // Could not find the relevant function, so return an error
-        Err(DataFusionError::Plan(format!("Invalid function '{name}'.")))
+        let suggestions = closest_matches(&name, self.schema_provider.function_names());
+        match did_you_mean(&suggestions) {
+            Some(suggestion) => Err(DataFusionError::Plan(format!(
+                "Invalid function '{name}', {suggestion}"
+            ))),
+            None => Err(DataFusionError::Plan(format!("Invalid function '{name}'."))),
+        }
     }
 
     pub(super) async fn sql_named_function_to_expr(
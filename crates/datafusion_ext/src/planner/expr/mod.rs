@@ -26,9 +26,11 @@ mod substring;
 mod unary_op;
 mod value;
 
+use crate::planner::utils::is_geometry_sql_type;
 use crate::planner::{AsyncContextProvider, SqlQueryPlanner};
+use crate::types::{is_uuid_expr, text_to_geometry, text_to_uuid, uuid_to_text};
 use async_recursion::async_recursion;
-use datafusion::arrow::datatypes::DataType;
+use datafusion::arrow::datatypes::{DataType, TimeUnit};
 use datafusion::common::tree_node::{Transformed, TreeNode};
 use datafusion::common::{Column, DFSchema, DataFusionError, Result, ScalarValue};
 use datafusion::logical_expr::expr::ScalarFunction;
@@ -90,6 +92,7 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
                 StackEntry::Operator(op) => {
                     let right = eval_stack.pop().unwrap();
                     let left = eval_stack.pop().unwrap();
+                    let (left, right) = coerce_uuid_comparison(left, &op, right, schema);
                     let expr =
                         Expr::BinaryExpr(BinaryExpr::new(Box::new(left), op, Box::new(right)));
                     eval_stack.push(expr);
@@ -102,6 +105,39 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
         Ok(expr)
     }
 
+    /// Cast an expression to the given type.
+    ///
+    /// Arrow can't cast between text and UUIDs, so those casts parse and
+    /// format the UUIDs instead. Similarly, casting text to a geometry parses
+    /// the text as WKT.
+    fn cast_expr(
+        &self,
+        expr: Expr,
+        sql_type: &SQLDataType,
+        schema: &DFSchema,
+        safe: bool,
+    ) -> Result<Expr> {
+        let data_type = self.convert_data_type(sql_type)?;
+        // Type may not be known yet for placeholders.
+        if let Ok(expr_type) = expr.get_type(schema) {
+            if is_geometry_sql_type(sql_type) && expr_type == DataType::Utf8 {
+                return Ok(text_to_geometry(expr, safe));
+            }
+            if matches!(sql_type, SQLDataType::Uuid) && expr_type == DataType::Utf8 {
+                return Ok(text_to_uuid(expr, safe));
+            }
+            if is_uuid_expr(&expr, schema) && data_type == DataType::Utf8 {
+                return Ok(uuid_to_text(expr));
+            }
+        }
+
+        Ok(if safe {
+            Expr::TryCast(TryCast::new(Box::new(expr), data_type))
+        } else {
+            Expr::Cast(Cast::new(Box::new(expr), data_type))
+        })
+    }
+
     /// Generate a relational expression from a SQL expression
     pub async fn sql_to_expr(
         &mut self,
@@ -237,26 +273,49 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
                 .await
             }
 
-            SQLExpr::Cast { expr, data_type } => Ok(Expr::Cast(Cast::new(
-                Box::new(
-                    self.sql_expr_to_logical_expr(*expr, schema, planner_context)
-                        .await?,
-                ),
-                self.convert_data_type(&data_type)?,
-            ))),
+            SQLExpr::Cast { expr, data_type } => {
+                let expr = self
+                    .sql_expr_to_logical_expr(*expr, schema, planner_context)
+                    .await?;
+                self.cast_expr(expr, &data_type, schema, false)
+            }
 
-            SQLExpr::TryCast { expr, data_type } => Ok(Expr::TryCast(TryCast::new(
-                Box::new(
-                    self.sql_expr_to_logical_expr(*expr, schema, planner_context)
-                        .await?,
-                ),
-                self.convert_data_type(&data_type)?,
-            ))),
+            SQLExpr::TryCast { expr, data_type } => {
+                let expr = self
+                    .sql_expr_to_logical_expr(*expr, schema, planner_context)
+                    .await?;
+                self.cast_expr(expr, &data_type, schema, true)
+            }
 
-            SQLExpr::TypedString { data_type, value } => Ok(Expr::Cast(Cast::new(
-                Box::new(lit(value)),
-                self.convert_data_type(&data_type)?,
-            ))),
+            SQLExpr::TypedString { data_type, value } => {
+                self.cast_expr(lit(value), &data_type, schema, false)
+            }
+
+            SQLExpr::AtTimeZone {
+                timestamp,
+                time_zone,
+            } => {
+                let timestamp = self
+                    .sql_expr_to_logical_expr(*timestamp, schema, planner_context)
+                    .await?;
+                // Untyped strings are treated as timestamps with time zones,
+                // the same as Postgres.
+                let timestamp = match timestamp.get_type(schema)? {
+                    DataType::Utf8 => Expr::Cast(Cast::new(
+                        Box::new(timestamp),
+                        DataType::Timestamp(
+                            TimeUnit::Nanosecond,
+                            self.session_timezone().map(Into::into),
+                        ),
+                    )),
+                    _ => timestamp,
+                };
+                self.schema_provider
+                    .get_scalar_udf("timezone", vec![lit(time_zone), timestamp])
+                    .ok_or_else(|| {
+                        DataFusionError::Plan("Invalid function 'timezone'.".to_string())
+                    })
+            }
 
             SQLExpr::IsNull(expr) => Ok(Expr::IsNull(Box::new(
                 self.sql_expr_to_logical_expr(*expr, schema, planner_context)
@@ -780,6 +839,41 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
     }
 }
 
+/// Parse text that's compared with UUIDs as UUIDs.
+///
+/// Arrow doesn't coerce between text and UUIDs, so comparing a UUID column
+/// with a string literal would otherwise fail to plan.
+fn coerce_uuid_comparison(
+    left: Expr,
+    op: &Operator,
+    right: Expr,
+    schema: &DFSchema,
+) -> (Expr, Expr) {
+    if !matches!(
+        op,
+        Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq
+            | Operator::IsDistinctFrom
+            | Operator::IsNotDistinctFrom
+    ) {
+        return (left, right);
+    }
+
+    match (left.get_type(schema).ok(), right.get_type(schema).ok()) {
+        (_, Some(DataType::Utf8)) if is_uuid_expr(&left, schema) => {
+            (left, text_to_uuid(right, false))
+        }
+        (Some(DataType::Utf8), _) if is_uuid_expr(&right, schema) => {
+            (text_to_uuid(left, false), right)
+        }
+        _ => (left, right),
+    }
+}
+
 // modifies expr if it is a placeholder with datatype of right
 fn rewrite_placeholder(expr: &mut Expr, other: &Expr, schema: &DFSchema) -> Result<()> {
     if let Expr::Placeholder(Placeholder { id: _, data_type }) = expr {
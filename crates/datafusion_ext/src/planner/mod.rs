@@ -29,6 +29,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::functions::*;
+use crate::types::{GEOMETRY_DATA_TYPE, UUID_DATA_TYPE};
+use crate::vars::SessionVars;
 use async_trait::async_trait;
 use datafusion::arrow::datatypes::DataType;
 use datafusion::arrow::datatypes::Field;
@@ -50,8 +52,11 @@ use datafusion::sql::sqlparser::ast::ExactNumberInfo;
 use datafusion::sql::sqlparser::ast::TimezoneInfo;
 use datafusion::sql::sqlparser::ast::{ColumnDef as SQLColumnDef, ColumnOption};
 use datafusion::sql::sqlparser::ast::{DataType as SQLDataType, Ident, ObjectName, TableAlias};
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::parser::Parser;
+use datafusion::sql::sqlparser::tokenizer::Token;
 
-use crate::utils::make_decimal_type;
+use crate::utils::{is_geometry_sql_type, make_decimal_type};
 
 /// The ContextProvider trait allows the query planner to obtain meta-data about tables and
 /// functions referenced in SQL statements
@@ -83,6 +88,12 @@ pub trait AsyncContextProvider: Send + Sync {
 
     /// Get configuration options.
     fn options(&self) -> &ConfigOptions;
+
+    /// Get the names of all scalar and aggregate functions, used for
+    /// suggesting close matches when a function can't be found.
+    fn function_names(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 /// SQL query planner
@@ -209,101 +220,21 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
         }
     }
 
-    fn convert_simple_data_type(&self, sql_type: &SQLDataType) -> Result<DataType> {
-        match sql_type {
-            SQLDataType::Boolean | SQLDataType::Bool => Ok(DataType::Boolean),
-            SQLDataType::TinyInt(_) => Ok(DataType::Int8),
-            SQLDataType::SmallInt(_) | SQLDataType::Int2(_) => Ok(DataType::Int16),
-            SQLDataType::Int(_) | SQLDataType::Integer(_) | SQLDataType::Int4(_) => Ok(DataType::Int32),
-            SQLDataType::BigInt(_) | SQLDataType::Int8(_) => Ok(DataType::Int64),
-            SQLDataType::UnsignedTinyInt(_) => Ok(DataType::UInt8),
-            SQLDataType::UnsignedSmallInt(_) | SQLDataType::UnsignedInt2(_) => Ok(DataType::UInt16),
-            SQLDataType::UnsignedInt(_) | SQLDataType::UnsignedInteger(_) | SQLDataType::UnsignedInt4(_) => {
-                Ok(DataType::UInt32)
-            }
-            SQLDataType::UnsignedBigInt(_) | SQLDataType::UnsignedInt8(_) => Ok(DataType::UInt64),
-            SQLDataType::Float(_) => Ok(DataType::Float32),
-            SQLDataType::Real | SQLDataType::Float4 => Ok(DataType::Float32),
-            SQLDataType::Double | SQLDataType::DoublePrecision | SQLDataType::Float8 => Ok(DataType::Float64),
-            SQLDataType::Char(_)
-            | SQLDataType::Varchar(_)
-            | SQLDataType::Text
-            | SQLDataType::String => Ok(DataType::Utf8),
-            SQLDataType::Timestamp(None, tz_info) => {
-                let tz = if matches!(tz_info, TimezoneInfo::Tz)
-                    || matches!(tz_info, TimezoneInfo::WithTimeZone)
-                {
-                    // Timestamp With Time Zone
-                    // INPUT : [SQLDataType]   TimestampTz + [RuntimeConfig] Time Zone
-                    // OUTPUT: [ArrowDataType] Timestamp<TimeUnit, Some(Time Zone)>
-                    self.schema_provider.options().execution.time_zone.clone()
-                } else {
-                    // Timestamp Without Time zone
-                    None
-                };
-                Ok(DataType::Timestamp(TimeUnit::Nanosecond, tz.map(Into::into)))
-            }
-            SQLDataType::Date => Ok(DataType::Date32),
-            SQLDataType::Time(None, tz_info) => {
-                if matches!(tz_info, TimezoneInfo::None)
-                    || matches!(tz_info, TimezoneInfo::WithoutTimeZone)
-                {
-                    Ok(DataType::Time64(TimeUnit::Nanosecond))
-                } else {
-                    // We dont support TIMETZ and TIME WITH TIME ZONE for now
-                    Err(DataFusionError::NotImplemented(format!(
-                        "Unsupported SQL type {sql_type:?}"
-                    )))
-                }
-            }
-            SQLDataType::Numeric(exact_number_info)
-            | SQLDataType::Decimal(exact_number_info) => {
-                let (precision, scale) = match *exact_number_info {
-                    ExactNumberInfo::None => (None, None),
-                    ExactNumberInfo::Precision(precision) => (Some(precision), None),
-                    ExactNumberInfo::PrecisionAndScale(precision, scale) => {
-                        (Some(precision), Some(scale))
-                    }
-                };
-                make_decimal_type(precision, scale)
-            }
-            SQLDataType::Bytea => Ok(DataType::Binary),
-            SQLDataType::Interval => Ok(DataType::Interval(IntervalUnit::MonthDayNano)),
-            // Explicitly list all other types so that if sqlparser
-            // adds/changes the `SQLDataType` the compiler will tell us on upgrade
-            // and avoid bugs like https://github.com/apache/arrow-datafusion/issues/3059
-            SQLDataType::Nvarchar(_)
-            | SQLDataType::JSON
-            | SQLDataType::Uuid
-            | SQLDataType::Binary(_)
-            | SQLDataType::Varbinary(_)
-            | SQLDataType::Blob(_)
-            | SQLDataType::Datetime(_)
-            | SQLDataType::Regclass
-            | SQLDataType::Custom(_, _)
-            | SQLDataType::Array(_)
-            | SQLDataType::Enum(_)
-            | SQLDataType::Set(_)
-            | SQLDataType::MediumInt(_)
-            | SQLDataType::UnsignedMediumInt(_)
-            | SQLDataType::Character(_)
-            | SQLDataType::CharacterVarying(_)
-            | SQLDataType::CharVarying(_)
-            | SQLDataType::CharacterLargeObject(_)
-            | SQLDataType::CharLargeObject(_)
-            // precision is not supported
-            | SQLDataType::Timestamp(Some(_), _)
-            // precision is not supported
-            | SQLDataType::Time(Some(_), _)
-            | SQLDataType::Dec(_)
-            | SQLDataType::BigNumeric(_)
-            | SQLDataType::BigDecimal(_)
-            | SQLDataType::Clob(_) => Err(DataFusionError::NotImplemented(format!(
-                "Unsupported SQL type {sql_type:?}"
-            ))),
+    /// Get the time zone to use for timestamps with time zones.
+    ///
+    /// This is the session's 'TimeZone' variable if available.
+    fn session_timezone(&self) -> Option<String> {
+        let options = self.schema_provider.options();
+        match options.extensions.get::<SessionVars>() {
+            Some(vars) => Some(vars.timezone()),
+            None => options.execution.time_zone.clone(),
         }
     }
 
+    fn convert_simple_data_type(&self, sql_type: &SQLDataType) -> Result<DataType> {
+        convert_simple_data_type(sql_type, || self.session_timezone())
+    }
+
     pub(crate) fn object_name_to_table_reference(
         &self,
         object_name: ObjectName,
@@ -311,3 +242,137 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
         object_name_to_table_reference(object_name, self.options.enable_ident_normalization)
     }
 }
+
+/// Convert a (non-array) SQL data type to an arrow data type.
+///
+/// `timezone` is called to get the time zone of timestamps with time zones.
+pub fn convert_simple_data_type(
+    sql_type: &SQLDataType,
+    timezone: impl FnOnce() -> Option<String>,
+) -> Result<DataType> {
+    match sql_type {
+        SQLDataType::Boolean | SQLDataType::Bool => Ok(DataType::Boolean),
+        SQLDataType::TinyInt(_) => Ok(DataType::Int8),
+        SQLDataType::SmallInt(_) | SQLDataType::Int2(_) => Ok(DataType::Int16),
+        SQLDataType::Int(_) | SQLDataType::Integer(_) | SQLDataType::Int4(_) => Ok(DataType::Int32),
+        SQLDataType::BigInt(_) | SQLDataType::Int8(_) => Ok(DataType::Int64),
+        SQLDataType::UnsignedTinyInt(_) => Ok(DataType::UInt8),
+        SQLDataType::UnsignedSmallInt(_) | SQLDataType::UnsignedInt2(_) => Ok(DataType::UInt16),
+        SQLDataType::UnsignedInt(_) | SQLDataType::UnsignedInteger(_) | SQLDataType::UnsignedInt4(_) => {
+            Ok(DataType::UInt32)
+        }
+        SQLDataType::UnsignedBigInt(_) | SQLDataType::UnsignedInt8(_) => Ok(DataType::UInt64),
+        SQLDataType::Float(_) => Ok(DataType::Float32),
+        SQLDataType::Real | SQLDataType::Float4 => Ok(DataType::Float32),
+        SQLDataType::Double | SQLDataType::DoublePrecision | SQLDataType::Float8 => Ok(DataType::Float64),
+        SQLDataType::Char(_)
+        | SQLDataType::Varchar(_)
+        | SQLDataType::Text
+        | SQLDataType::String => Ok(DataType::Utf8),
+        SQLDataType::Timestamp(None, tz_info) => {
+            let tz = if matches!(tz_info, TimezoneInfo::Tz)
+                || matches!(tz_info, TimezoneInfo::WithTimeZone)
+            {
+                // Timestamp With Time Zone
+                // INPUT : [SQLDataType]   TimestampTz + [Session] Time Zone
+                // OUTPUT: [ArrowDataType] Timestamp<TimeUnit, Some(Time Zone)>
+                timezone()
+            } else {
+                // Timestamp Without Time zone
+                None
+            };
+            Ok(DataType::Timestamp(TimeUnit::Nanosecond, tz.map(Into::into)))
+        }
+        SQLDataType::Date => Ok(DataType::Date32),
+        SQLDataType::Time(None, tz_info) => {
+            if matches!(tz_info, TimezoneInfo::None)
+                || matches!(tz_info, TimezoneInfo::WithoutTimeZone)
+            {
+                Ok(DataType::Time64(TimeUnit::Nanosecond))
+            } else {
+                // We dont support TIMETZ and TIME WITH TIME ZONE for now
+                Err(DataFusionError::NotImplemented(format!(
+                    "Unsupported SQL type {sql_type:?}"
+                )))
+            }
+        }
+        SQLDataType::Numeric(exact_number_info)
+        | SQLDataType::Decimal(exact_number_info) => {
+            let (precision, scale) = match *exact_number_info {
+                ExactNumberInfo::None => (None, None),
+                ExactNumberInfo::Precision(precision) => (Some(precision), None),
+                ExactNumberInfo::PrecisionAndScale(precision, scale) => {
+                    (Some(precision), Some(scale))
+                }
+            };
+            make_decimal_type(precision, scale)
+        }
+        SQLDataType::Bytea => Ok(DataType::Binary),
+        SQLDataType::Interval => Ok(DataType::Interval(IntervalUnit::MonthDayNano)),
+        SQLDataType::Uuid => Ok(UUID_DATA_TYPE),
+        sql_type if is_geometry_sql_type(sql_type) => Ok(GEOMETRY_DATA_TYPE),
+        // Explicitly list all other types so that if sqlparser
+        // adds/changes the `SQLDataType` the compiler will tell us on upgrade
+        // and avoid bugs like https://github.com/apache/arrow-datafusion/issues/3059
+        SQLDataType::Nvarchar(_)
+        | SQLDataType::JSON
+        | SQLDataType::Binary(_)
+        | SQLDataType::Varbinary(_)
+        | SQLDataType::Blob(_)
+        | SQLDataType::Datetime(_)
+        | SQLDataType::Regclass
+        | SQLDataType::Custom(_, _)
+        | SQLDataType::Array(_)
+        | SQLDataType::Enum(_)
+        | SQLDataType::Set(_)
+        | SQLDataType::MediumInt(_)
+        | SQLDataType::UnsignedMediumInt(_)
+        | SQLDataType::Character(_)
+        | SQLDataType::CharacterVarying(_)
+        | SQLDataType::CharVarying(_)
+        | SQLDataType::CharacterLargeObject(_)
+        | SQLDataType::CharLargeObject(_)
+        // precision is not supported
+        | SQLDataType::Timestamp(Some(_), _)
+        // precision is not supported
+        | SQLDataType::Time(Some(_), _)
+        | SQLDataType::Dec(_)
+        | SQLDataType::BigNumeric(_)
+        | SQLDataType::BigDecimal(_)
+        | SQLDataType::Clob(_) => Err(DataFusionError::NotImplemented(format!(
+            "Unsupported SQL type {sql_type:?}"
+        ))),
+    }
+}
+
+/// Parse a list of column names and types, e.g. 'id bigint, name text', into
+/// arrow types.
+///
+/// `timezone` is called to get the time zone of timestamps with time zones.
+pub fn parse_column_types(
+    columns: &str,
+    timezone: impl Fn() -> Option<String>,
+) -> Result<Vec<(String, DataType)>> {
+    let mut parser = Parser::new(&GenericDialect {}).try_with_sql(columns)?;
+    let columns = parser.parse_comma_separated(|parser| {
+        let name = parser.parse_identifier()?;
+        let data_type = parser.parse_data_type()?;
+        Ok((name, data_type))
+    })?;
+    parser.expect_token(&Token::EOF)?;
+
+    columns
+        .into_iter()
+        .map(|(name, data_type)| {
+            let data_type = match &data_type {
+                SQLDataType::Array(Some(inner)) => DataType::List(Arc::new(Field::new(
+                    "field",
+                    convert_simple_data_type(inner, &timezone)?,
+                    true,
+                ))),
+                other => convert_simple_data_type(other, &timezone)?,
+            };
+            Ok((name.value, data_type))
+        })
+        .collect()
+}
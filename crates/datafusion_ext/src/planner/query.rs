@@ -15,14 +15,24 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use crate::planner::{AsyncContextProvider, SqlQueryPlanner};
+use crate::recursive::{RecursiveQuery, WorkTable, WorkTableProvider};
+use crate::vars::SessionVars;
 
 use async_recursion::async_recursion;
-use datafusion::common::{DataFusionError, Result, ScalarValue};
-use datafusion::logical_expr::{Expr, LogicalPlan, LogicalPlanBuilder};
+use datafusion::common::tree_node::{TreeNode, VisitRecursion};
+use datafusion::common::{
+    DFField, DFSchema, DataFusionError, OwnedTableReference, Result, ScalarValue,
+};
+use datafusion::datasource::provider_as_source;
+use datafusion::logical_expr::{cast, Expr, LogicalPlan, LogicalPlanBuilder};
 use datafusion::sql::planner::PlannerContext;
 use datafusion::sql::sqlparser::ast::{
-    Expr as SQLExpr, Offset as SQLOffset, OrderByExpr, Query, Value,
+    Expr as SQLExpr, Offset as SQLOffset, OrderByExpr, Query, SetExpr, SetOperator, SetQuantifier,
+    TableAlias, Value,
 };
 
 use datafusion::sql::sqlparser::parser::ParserError::ParserError;
@@ -54,13 +64,7 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
         let set_expr = query.body;
         if let Some(with) = query.with {
             // Process CTEs from top to bottom
-            // do not allow self-references
-            if with.recursive {
-                return Err(DataFusionError::NotImplemented(
-                    "Recursive CTEs are not supported".to_string(),
-                ));
-            }
-
+            // only allow self-references in recursive CTEs
             for cte in with.cte_tables {
                 // A `WITH` block can't use the same name more than once
                 let cte_name = self.normalizer.normalize(cte.alias.name.clone());
@@ -69,15 +73,25 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
                         "WITH query name {cte_name:?} specified more than once"
                     ))));
                 }
-                // create logical plan & pass backreferencing CTEs
-                // CTE expr don't need extend outer_query_schema
-                let logical_plan = self
-                    .query_to_plan_with_context(*cte.query, &mut planner_context.clone())
-                    .await?;
+                let logical_plan = if with.recursive {
+                    self.recursive_cte_to_plan(
+                        cte_name.clone(),
+                        *cte.query,
+                        cte.alias,
+                        planner_context,
+                    )
+                    .await?
+                } else {
+                    // create logical plan & pass backreferencing CTEs
+                    // CTE expr don't need extend outer_query_schema
+                    let logical_plan = self
+                        .query_to_plan_with_context(*cte.query, &mut planner_context.clone())
+                        .await?;
 
-                // Each `WITH` block can change the column names in the last
-                // projection (e.g. "WITH table(t1, t2) AS SELECT 1, 2").
-                let logical_plan = self.apply_table_alias(logical_plan, cte.alias)?;
+                    // Each `WITH` block can change the column names in the last
+                    // projection (e.g. "WITH table(t1, t2) AS SELECT 1, 2").
+                    self.apply_table_alias(logical_plan, cte.alias)?
+                };
 
                 planner_context.insert_cte(cte_name, logical_plan);
             }
@@ -87,6 +101,157 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
         self.limit(plan, query.offset, query.limit).await
     }
 
+    /// Generate a logical plan for a CTE in a `WITH RECURSIVE` block.
+    ///
+    /// Only CTEs of the form `<static term> UNION [ALL] <recursive term>` may
+    /// reference themselves, and only from the recursive term. Other CTEs are
+    /// planned like non-recursive CTEs.
+    async fn recursive_cte_to_plan(
+        &mut self,
+        cte_name: String,
+        query: Query,
+        alias: TableAlias,
+        planner_context: &mut PlannerContext,
+    ) -> Result<LogicalPlan> {
+        let (left, right, set_quantifier) = match *query.body {
+            SetExpr::SetOperation {
+                op: SetOperator::Union,
+                left,
+                right,
+                set_quantifier,
+            } if query.with.is_none()
+                && query.order_by.is_empty()
+                && query.limit.is_none()
+                && query.offset.is_none()
+                && query.fetch.is_none() =>
+            {
+                (left, right, set_quantifier)
+            }
+            body => {
+                let query = Query {
+                    body: Box::new(body),
+                    ..query
+                };
+                let logical_plan = self
+                    .query_to_plan_with_context(query, &mut planner_context.clone())
+                    .await?;
+                return self.apply_table_alias(logical_plan, alias);
+            }
+        };
+
+        let is_distinct = match set_quantifier {
+            SetQuantifier::All => false,
+            SetQuantifier::Distinct | SetQuantifier::None => true,
+            SetQuantifier::ByName | SetQuantifier::AllByName => {
+                return Err(DataFusionError::NotImplemented(
+                    "UNION BY NAME not implemented for recursive queries".to_string(),
+                ))
+            }
+        };
+
+        // The static term can't reference the CTE.
+        let static_plan = self
+            .set_expr_to_plan(*left, &mut planner_context.clone())
+            .await?;
+        let static_plan = self.apply_table_alias(static_plan, alias)?;
+
+        // Rows from either term may contain nulls, so all fields are nullable.
+        let table_ref = OwnedTableReference::bare(cte_name.clone());
+        let fields = static_plan
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| {
+                DFField::new(
+                    Some(table_ref.clone()),
+                    field.name(),
+                    field.data_type().clone(),
+                    true,
+                )
+            })
+            .collect();
+        let schema = Arc::new(DFSchema::new_with_metadata(fields, HashMap::new())?);
+
+        // References to the CTE from the recursive term scan the work table.
+        let work_table = Arc::new(WorkTable::default());
+        let provider = WorkTableProvider::new(
+            cte_name.clone(),
+            Arc::new(schema.as_ref().into()),
+            work_table.clone(),
+        );
+        let work_table_plan = LogicalPlanBuilder::scan(
+            table_ref.clone(),
+            provider_as_source(Arc::new(provider)),
+            None,
+        )?
+        .build()?;
+
+        let mut recursive_context = planner_context.clone();
+        recursive_context.insert_cte(cte_name.clone(), work_table_plan);
+        let recursive_plan = self
+            .set_expr_to_plan(*right, &mut recursive_context)
+            .await?;
+
+        if !references_work_table(&recursive_plan, &work_table)? {
+            // Not actually recursive, plan as a regular union.
+            let builder = LogicalPlanBuilder::from(static_plan);
+            let builder = if is_distinct {
+                builder.union_distinct(recursive_plan)?
+            } else {
+                builder.union(recursive_plan)?
+            };
+            return builder.alias(table_ref)?.build();
+        }
+
+        let recursive_fields = recursive_plan.schema().fields();
+        if recursive_fields.len() != schema.fields().len() {
+            return Err(DataFusionError::Plan(format!(
+                "Recursive query {cte_name:?} has {} columns in its non-recursive term but {} columns in its recursive term",
+                schema.fields().len(),
+                recursive_fields.len(),
+            )));
+        }
+
+        // Make the output of the recursive term match the static term.
+        let exprs = recursive_fields
+            .iter()
+            .zip(schema.fields())
+            .map(|(from, to)| {
+                let expr = Expr::Column(from.qualified_column());
+                let expr = if from.data_type() == to.data_type() {
+                    expr
+                } else {
+                    cast(expr, to.data_type().clone())
+                };
+                expr.alias(to.name())
+            })
+            .collect::<Vec<_>>();
+        let recursive_plan = LogicalPlanBuilder::from(recursive_plan)
+            .project(exprs)?
+            .build()?;
+
+        let max_depth = match self
+            .schema_provider
+            .options()
+            .extensions
+            .get::<SessionVars>()
+        {
+            Some(vars) => vars.max_recursion_depth(),
+            None => SessionVars::default().max_recursion_depth(),
+        };
+
+        Ok(RecursiveQuery {
+            name: cte_name,
+            static_term: static_plan,
+            recursive_term: recursive_plan,
+            work_table,
+            schema,
+            is_distinct,
+            max_depth,
+        }
+        .into_logical_plan())
+    }
+
     /// Wrap a plan in a limit
     async fn limit(
         &mut self,
@@ -154,3 +319,37 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
         LogicalPlanBuilder::from(plan).sort(order_by_rex)?.build()
     }
 }
+
+/// Check if a plan scans the given work table, including from subqueries.
+fn references_work_table(plan: &LogicalPlan, work_table: &Arc<WorkTable>) -> Result<bool> {
+    let mut found = false;
+    plan.apply(&mut |plan| {
+        if let LogicalPlan::TableScan(scan) = plan {
+            found = WorkTableProvider::is_source_for(&scan.source, work_table);
+        }
+        for expr in plan.expressions() {
+            if found {
+                break;
+            }
+            expr.apply(&mut |expr| {
+                let subquery = match expr {
+                    Expr::Exists(exists) => &exists.subquery,
+                    Expr::InSubquery(in_subquery) => &in_subquery.subquery,
+                    Expr::ScalarSubquery(subquery) => subquery,
+                    _ => return Ok(VisitRecursion::Continue),
+                };
+                if references_work_table(&subquery.subquery, work_table)? {
+                    found = true;
+                    return Ok(VisitRecursion::Stop);
+                }
+                Ok(VisitRecursion::Continue)
+            })?;
+        }
+        Ok(if found {
+            VisitRecursion::Stop
+        } else {
+            VisitRecursion::Continue
+        })
+    })?;
+    Ok(found)
+}
@@ -15,11 +15,15 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::asof::{AsofJoin, AsofJoinType, ASOF_JOIN_MARKER};
 use crate::planner::{AsyncContextProvider, SqlQueryPlanner};
-use datafusion::common::{Column, DataFusionError, Result};
+use datafusion::common::{Column, DFSchema, DataFusionError, Result};
 use datafusion::logical_expr::{JoinType, LogicalPlan, LogicalPlanBuilder};
 use datafusion::sql::planner::PlannerContext;
-use datafusion::sql::sqlparser::ast::{Join, JoinConstraint, JoinOperator, TableWithJoins};
+use datafusion::sql::sqlparser::ast::{
+    BinaryOperator, Expr as SQLExpr, FunctionArg, FunctionArgExpr, Join, JoinConstraint,
+    JoinOperator, TableWithJoins,
+};
 use std::collections::HashSet;
 
 impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
@@ -135,6 +139,19 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
     ) -> Result<LogicalPlan> {
         match constraint {
             JoinConstraint::On(sql_expr) => {
+                if let Some((tolerance, condition)) = split_asof_marker(&sql_expr) {
+                    return self
+                        .parse_asof_join(
+                            left,
+                            right,
+                            condition,
+                            tolerance,
+                            join_type,
+                            planner_context,
+                        )
+                        .await;
+                }
+
                 let join_schema = left.schema().join(right.schema())?;
                 // parse ON expression
                 let expr = self
@@ -186,4 +203,62 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
             )),
         }
     }
+
+    async fn parse_asof_join(
+        &mut self,
+        left: LogicalPlan,
+        right: LogicalPlan,
+        condition: SQLExpr,
+        tolerance: Option<SQLExpr>,
+        join_type: JoinType,
+        planner_context: &mut PlannerContext,
+    ) -> Result<LogicalPlan> {
+        let join_type = match join_type {
+            JoinType::Inner => AsofJoinType::Inner,
+            JoinType::Left => AsofJoinType::Left,
+            other => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Unsupported ASOF JOIN type {other}"
+                )))
+            }
+        };
+
+        let join_schema = left.schema().join(right.schema())?;
+        let condition = self
+            .sql_to_expr(condition, &join_schema, planner_context)
+            .await?;
+        let tolerance = match tolerance {
+            Some(tolerance) => Some(
+                self.sql_to_expr(tolerance, &DFSchema::empty(), planner_context)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        Ok(AsofJoin::try_new(left, right, condition, tolerance, join_type)?.into_logical_plan())
+    }
+}
+
+/// Split the marker added to the condition of `ASOF JOIN`s when parsing from
+/// the rest of the condition.
+///
+/// Returns the tolerance and the condition if the join is an ASOF join.
+fn split_asof_marker(expr: &SQLExpr) -> Option<(Option<SQLExpr>, SQLExpr)> {
+    let (marker, condition) = match expr {
+        SQLExpr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => (left.as_ref(), right.as_ref()),
+        _ => return None,
+    };
+    let func = match marker {
+        SQLExpr::Function(func) if func.name.to_string() == ASOF_JOIN_MARKER => func,
+        _ => return None,
+    };
+    let tolerance = match func.args.first() {
+        Some(FunctionArg::Unnamed(FunctionArgExpr::Expr(tolerance))) => Some(tolerance.clone()),
+        _ => None,
+    };
+    Some((tolerance, condition.clone()))
 }
@@ -17,14 +17,19 @@
 
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 
+use crate::errors::ExtensionError;
 use crate::functions::FuncParamValue;
 use crate::planner::{AsyncContextProvider, SqlQueryPlanner};
+use crate::sample::{Sample, SampleMethod, SampleSize, SampleSpec, TABLE_SAMPLE_HINT};
 
 use async_recursion::async_recursion;
-use datafusion::common::{DataFusionError, OwnedTableReference, Result};
+use datafusion::common::{DFSchema, DataFusionError, OwnedTableReference, Result};
 
-use datafusion::logical_expr::{LogicalPlan, LogicalPlanBuilder};
+use datafusion::logical_expr::{Expr, LogicalPlan, LogicalPlanBuilder};
+use datafusion::optimizer::simplify_expressions::{ExprSimplifier, SimplifyContext};
+use datafusion::physical_expr::execution_props::ExecutionProps;
 
 use datafusion::scalar::ScalarValue;
 use datafusion::sql::planner::PlannerContext;
@@ -45,9 +50,12 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
                 mut name,
                 alias,
                 args,
+                with_hints,
                 ..
             } => {
-                if name.0.len() == 1 && name.0[0].quote_style == Some('\'') {
+                let sample = self.get_table_sample(with_hints).await?;
+
+                let (plan, alias) = if name.0.len() == 1 && name.0[0].quote_style == Some('\'') {
                     // SELECT * FROM './my/file.csv'
                     //
                     // Infer the table function to use based on a file path.
@@ -82,7 +90,7 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
                             // Table factor has arguments, look up table returning
                             // function.
                             for arg in args {
-                                let (name, val) = self.get_constant_function_arg(arg)?;
+                                let (name, val) = self.get_constant_function_arg(arg).await?;
                                 if let Some(name) = name {
                                     named_args.insert(name, val);
                                 } else {
@@ -118,6 +126,11 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
                             (plan, alias)
                         }
                     }
+                };
+
+                match sample {
+                    Some(spec) => (Sample::new(plan, spec).into_logical_plan(), alias),
+                    None => (plan, alias),
                 }
             }
             ast::TableFactor::Derived {
@@ -150,23 +163,96 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
         }
     }
 
+    /// Get the sample to take of a table from its hints.
+    ///
+    /// `TABLESAMPLE` clauses are rewritten to a table hint when parsing, e.g.
+    /// `tablesample(method => 'system', size => 1, unit => 'percent')`.
+    async fn get_table_sample(&mut self, hints: Vec<ast::Expr>) -> Result<Option<SampleSpec>> {
+        let func =
+            hints.into_iter().find_map(|hint| match hint {
+                ast::Expr::Function(func)
+                    if func.name.0.last().is_some_and(|ident| {
+                        ident.value.eq_ignore_ascii_case(TABLE_SAMPLE_HINT)
+                    }) =>
+                {
+                    Some(func)
+                }
+                _ => None,
+            });
+        let func = match func {
+            Some(func) => func,
+            None => return Ok(None),
+        };
+
+        let mut args = HashMap::new();
+        for arg in func.args {
+            if let (Some(name), val) = self.get_constant_function_arg(arg).await? {
+                args.insert(name, val);
+            }
+        }
+
+        let arg_err =
+            |e: ExtensionError| DataFusionError::Plan(format!("Invalid TABLESAMPLE: {e}"));
+        let method: String = args
+            .remove("method")
+            .ok_or(ExtensionError::MissingNamedArgument("method"))
+            .and_then(String::try_from)
+            .map_err(arg_err)?;
+        let method: SampleMethod = method.parse()?;
+        let size: f64 = args
+            .remove("size")
+            .ok_or(ExtensionError::MissingNamedArgument("size"))
+            .and_then(f64::try_from)
+            .map_err(arg_err)?;
+        let unit = args
+            .remove("unit")
+            .map(String::try_from)
+            .transpose()
+            .map_err(arg_err)?;
+        let seed = args
+            .remove("seed")
+            .map(i64::try_from)
+            .transpose()
+            .map_err(arg_err)?;
+
+        let size = match unit.as_deref() {
+            Some("rows") => SampleSize::Rows(rows_from_size(size)?),
+            Some("percent") => SampleSize::Percent(size),
+            Some(other) => {
+                return Err(DataFusionError::Plan(format!(
+                    "Invalid TABLESAMPLE unit '{other}'"
+                )))
+            }
+            // Sizes are percentages unless an exact number of rows is being
+            // sampled.
+            None if method == SampleMethod::Reservoir => SampleSize::Rows(rows_from_size(size)?),
+            None => SampleSize::Percent(size),
+        };
+
+        Ok(Some(SampleSpec::try_new(
+            method,
+            size,
+            seed.map(|seed| seed as u64),
+        )?))
+    }
+
     /// Get a constant expression literal from a function argument.
     ///
     /// Returns an optional name for the argument.
-    fn get_constant_function_arg(
+    async fn get_constant_function_arg(
         &mut self,
         arg: ast::FunctionArg,
     ) -> Result<(Option<String>, FuncParamValue)> {
         match arg {
             ast::FunctionArg::Unnamed(ast::FunctionArgExpr::Expr(expr)) => {
-                Ok((None, self.get_param_val(expr)?))
+                Ok((None, self.get_param_val(expr).await?))
             }
             ast::FunctionArg::Named {
                 name,
                 arg: ast::FunctionArgExpr::Expr(expr),
             } => {
                 let name = self.normalizer.normalize(name);
-                Ok((Some(name), self.get_param_val(expr)?))
+                Ok((Some(name), self.get_param_val(expr).await?))
             }
             other => Err(DataFusionError::NotImplemented(format!(
                 "Non-constant function argument: {other:?}",
@@ -175,42 +261,42 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
     }
 
     /// Get the parameter value from expr.
-    fn get_param_val(&self, expr: ast::Expr) -> Result<FuncParamValue> {
+    #[async_recursion]
+    async fn get_param_val(&mut self, expr: ast::Expr) -> Result<FuncParamValue> {
         match expr {
             ast::Expr::Identifier(ident) => {
                 Ok(FuncParamValue::Ident(self.normalizer.normalize(ident)))
             }
             ast::Expr::Array(arr) => {
-                let arr = arr
-                    .elem
-                    .iter()
-                    .map(|e| self.get_param_val(e.clone()))
-                    .collect::<Result<Vec<FuncParamValue>>>();
-                Ok(FuncParamValue::Array(arr?))
+                let mut vals = Vec::with_capacity(arr.elem.len());
+                for e in arr.elem {
+                    vals.push(self.get_param_val(e).await?);
+                }
+                Ok(FuncParamValue::Array(vals))
             }
-            ast::Expr::UnaryOp { op, expr } => match op {
-                ast::UnaryOperator::Minus => {
-                    match *expr {
-                        // optimization: if it's a number literal, we apply the negative operator
-                        // here directly to calculate the new literal.
-                        ast::Expr::Value(ast::Value::Number(n, _)) => match n.parse::<i64>() {
-                            Ok(n) => Ok(FuncParamValue::Scalar(ScalarValue::Int64(Some(-n)))),
-                            Err(_) => {
-                                let n = n.parse::<f64>().map_err(|_e| {
-                                    DataFusionError::Internal(format!(
-                                        "negative operator can be only applied to integer and float operands, got: {n}"))
-                                })?;
-                                Ok(FuncParamValue::Scalar(ScalarValue::Float64(Some(-n))))
-                            }
-                        },
-                        other => Err(DataFusionError::NotImplemented(format!(
-                            "Non-constant function argument: {other:?}",
-                        ))),
+            ast::Expr::UnaryOp {
+                op: ast::UnaryOperator::Minus,
+                expr,
+            } => match *expr {
+                // optimization: if it's a number literal, we apply the negative operator
+                // here directly to calculate the new literal.
+                ast::Expr::Value(ast::Value::Number(n, _)) => match n.parse::<i64>() {
+                    Ok(n) => Ok(FuncParamValue::Scalar(ScalarValue::Int64(Some(-n)))),
+                    Err(_) => {
+                        let n = n.parse::<f64>().map_err(|_e| {
+                            DataFusionError::Internal(format!(
+                                "negative operator can be only applied to integer and float operands, got: {n}"))
+                        })?;
+                        Ok(FuncParamValue::Scalar(ScalarValue::Float64(Some(-n))))
                     }
+                },
+                other => {
+                    self.evaluate_param_val(ast::Expr::UnaryOp {
+                        op: ast::UnaryOperator::Minus,
+                        expr: Box::new(other),
+                    })
+                    .await
                 }
-                other => Err(DataFusionError::NotImplemented(format!(
-                    "Non-constant function argument: {other:?}",
-                ))),
             },
 
             ast::Expr::Value(v) => match self.parse_value(v, &[]) {
@@ -223,6 +309,22 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
                 Err(e) => Err(e),
             },
 
+            // Other expressions, e.g. `INTERVAL '1 day'` or `'2023-01-01'::date`,
+            // must evaluate to a constant.
+            other => self.evaluate_param_val(other).await,
+        }
+    }
+
+    /// Plan a constant expression and evaluate it to a scalar parameter value.
+    async fn evaluate_param_val(&mut self, expr: ast::Expr) -> Result<FuncParamValue> {
+        let schema = DFSchema::empty();
+        let expr = self
+            .sql_expr_to_logical_expr(expr, &schema, &mut PlannerContext::new())
+            .await?;
+        let props = ExecutionProps::new();
+        let info = SimplifyContext::new(&props).with_schema(Arc::new(schema));
+        match ExprSimplifier::new(info).simplify(expr)? {
+            Expr::Literal(lit) => Ok(FuncParamValue::Scalar(lit)),
             other => Err(DataFusionError::NotImplemented(format!(
                 "Non-constant function argument: {other:?}",
             ))),
@@ -230,6 +332,15 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
     }
 }
 
+fn rows_from_size(size: f64) -> Result<usize> {
+    if size < 0.0 || size.fract() != 0.0 {
+        return Err(DataFusionError::Plan(format!(
+            "Number of rows to sample must be a non-negative integer, got {size}"
+        )));
+    }
+    Ok(size as usize)
+}
+
 /// Returns a reference to table func by inferring which function to use from a
 /// given path.
 fn infer_func_for_file(path: &str) -> Result<OwnedTableReference> {
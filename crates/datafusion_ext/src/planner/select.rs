@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use crate::gapfill::GapFill;
 use crate::planner::{AsyncContextProvider, SqlQueryPlanner};
 use crate::utils::{
     check_columns_satisfy_exprs, extract_aliases, rebase_expr, resolve_aliases_to_exprs,
@@ -188,6 +189,9 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
             }
         };
 
+        // fill in missing time buckets if grouping by `time_bucket_gapfill`
+        let plan = GapFill::plan_for_aggregate(plan, &select_exprs_post_aggr)?;
+
         let plan = if let Some(having_expr_post_aggr) = having_expr_post_aggr {
             LogicalPlanBuilder::from(plan)
                 .filter(having_expr_post_aggr)?
@@ -1,6 +1,7 @@
 use std::{collections::BTreeMap, sync::Arc};
 
 use datafusion::{
+    arrow::datatypes::DataType,
     common::{DFField, DFSchema, DataFusionError, OwnedTableReference, Result, ToDFSchema},
     logical_expr::{
         builder::project, Analyze, Explain, ExprSchemable, LogicalPlan, PlanType, ToStringifiedPlan,
@@ -12,6 +13,7 @@ use datafusion::{
 };
 
 use crate::planner::{AsyncContextProvider, SqlQueryPlanner};
+use crate::types::{is_uuid_field, text_to_uuid};
 
 impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
     /// Generate a plan for EXPLAIN ... that will print out a plan
@@ -119,9 +121,16 @@ impl<'a, S: AsyncContextProvider> SqlQueryPlanner<'a, S> {
             .zip(source.schema().fields().iter())
             .map(|(target_field, source_field)| {
                 let expr =
-                    datafusion::logical_expr::Expr::Column(source_field.unqualified_column())
-                        .cast_to(target_field.data_type(), source.schema())?
-                        .alias(target_field.name());
+                    datafusion::logical_expr::Expr::Column(source_field.unqualified_column());
+                // Arrow can't cast text to UUIDs.
+                let expr = if is_uuid_field(target_field.field())
+                    && source_field.data_type() == &DataType::Utf8
+                {
+                    text_to_uuid(expr, false)
+                } else {
+                    expr.cast_to(target_field.data_type(), source.schema())?
+                };
+                let expr = expr.alias(target_field.name());
                 Ok(expr)
             })
             .collect::<Result<Vec<datafusion::logical_expr::Expr>>>()?;
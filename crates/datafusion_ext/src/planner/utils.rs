@@ -24,6 +24,7 @@ use datafusion::common::{DataFusionError, Result, ScalarValue};
 use datafusion::logical_expr::expr::{GroupingSet, WindowFunction};
 use datafusion::logical_expr::utils::{expr_as_column_expr, find_column_exprs};
 use datafusion::logical_expr::{expr::Alias, Expr, LogicalPlan};
+use datafusion::sql::sqlparser::ast::DataType as SQLDataType;
 use std::collections::HashMap;
 
 /// Make a best-effort attempt at resolving all columns in the expression tree
@@ -221,3 +222,15 @@ pub(crate) fn make_decimal_type(precision: Option<u64>, scale: Option<u64>) -> R
         Ok(DataType::Decimal128(precision, scale))
     }
 }
+
+/// Returns true if the SQL type is `GEOMETRY`, with or without a type
+/// modifier (e.g. `GEOMETRY(POINT, 4326)`).
+pub fn is_geometry_sql_type(sql_type: &SQLDataType) -> bool {
+    match sql_type {
+        SQLDataType::Custom(name, _) => name
+            .0
+            .last()
+            .is_some_and(|ident| ident.value.eq_ignore_ascii_case("geometry")),
+        _ => false,
+    }
+}
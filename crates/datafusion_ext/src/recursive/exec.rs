@@ -0,0 +1,318 @@
+use datafusion::arrow::array::BooleanArray;
+use datafusion::arrow::compute::filter_record_batch;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::row::{OwnedRow, RowConverter, SortField};
+use datafusion::common::tree_node::{Transformed, TreeNode};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    execute_stream, DisplayAs, DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use futures::StreamExt;
+use std::any::Any;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+use super::work_table::{WorkTable, WorkTableExec};
+
+/// Executes a recursive common table expression.
+///
+/// The static term is executed once, and its rows are made available to the
+/// recursive term through a work table. The recursive term is then executed
+/// repeatedly, each time reading the rows produced by the previous iteration,
+/// until an iteration produces no new rows.
+#[derive(Debug)]
+pub struct RecursiveQueryExec {
+    name: String,
+    static_term: Arc<dyn ExecutionPlan>,
+    recursive_term: Arc<dyn ExecutionPlan>,
+    /// Work table scanned by the recursive term. Replaced with a new work
+    /// table on every execution.
+    work_table: Arc<WorkTable>,
+    schema: SchemaRef,
+    is_distinct: bool,
+    max_depth: usize,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl RecursiveQueryExec {
+    pub fn new(
+        name: String,
+        static_term: Arc<dyn ExecutionPlan>,
+        recursive_term: Arc<dyn ExecutionPlan>,
+        work_table: Arc<WorkTable>,
+        schema: SchemaRef,
+        is_distinct: bool,
+        max_depth: usize,
+    ) -> Self {
+        RecursiveQueryExec {
+            name,
+            static_term,
+            recursive_term,
+            work_table,
+            schema,
+            is_distinct,
+            max_depth,
+            metrics: ExecutionPlanMetricsSet::new(),
+        }
+    }
+}
+
+impl ExecutionPlan for RecursiveQueryExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        vec![Distribution::SinglePartition, Distribution::SinglePartition]
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.static_term.clone(), self.recursive_term.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.len() != 2 {
+            return Err(DataFusionError::Execution(
+                "RecursiveQueryExec requires exactly two children".to_string(),
+            ));
+        }
+        Ok(Arc::new(RecursiveQueryExec::new(
+            self.name.clone(),
+            children[0].clone(),
+            children[1].clone(),
+            self.work_table.clone(),
+            self.schema.clone(),
+            self.is_distinct,
+            self.max_depth,
+        )))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Execution(format!(
+                "invalid partition {partition} for RecursiveQueryExec"
+            )));
+        }
+
+        let seen = if self.is_distinct {
+            Some(SeenRows::try_new(&self.schema)?)
+        } else {
+            None
+        };
+
+        let state = RecursiveQueryState {
+            name: self.name.clone(),
+            static_term: Some(self.static_term.clone()),
+            recursive_term: self.recursive_term.clone(),
+            work_table: self.work_table.clone(),
+            schema: self.schema.clone(),
+            max_depth: self.max_depth,
+            context,
+            current: None,
+            iteration_batches: Vec::new(),
+            depth: 0,
+            seen,
+            metrics: BaselineMetrics::new(&self.metrics, partition),
+        };
+
+        let stream = futures::stream::try_unfold(state, |mut state| async move {
+            let batch = state.next_batch().await?;
+            Ok::<_, DataFusionError>(batch.map(|batch| (batch, state)))
+        });
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema.clone(),
+            stream,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+}
+
+impl DisplayAs for RecursiveQueryExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "RecursiveQueryExec: name={}, is_distinct={}",
+            self.name, self.is_distinct
+        )
+    }
+}
+
+struct RecursiveQueryState {
+    name: String,
+    /// Static term, taken once it starts executing.
+    static_term: Option<Arc<dyn ExecutionPlan>>,
+    recursive_term: Arc<dyn ExecutionPlan>,
+    work_table: Arc<WorkTable>,
+    schema: SchemaRef,
+    max_depth: usize,
+    context: Arc<TaskContext>,
+    /// Stream for the term currently executing.
+    current: Option<SendableRecordBatchStream>,
+    /// Batches produced by the term currently executing. These become the
+    /// contents of the work table for the next iteration.
+    iteration_batches: Vec<RecordBatch>,
+    /// Number of times the recursive term has been executed.
+    depth: usize,
+    /// Rows already produced, only used for `UNION` (not `UNION ALL`).
+    seen: Option<SeenRows>,
+    metrics: BaselineMetrics,
+}
+
+impl RecursiveQueryState {
+    async fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        loop {
+            let next = match self.current.as_mut() {
+                Some(stream) => stream.next().await,
+                None => match self.start_next_term()? {
+                    Some(stream) => {
+                        self.current = Some(stream);
+                        continue;
+                    }
+                    None => {
+                        self.metrics.done();
+                        return Ok(None);
+                    }
+                },
+            };
+
+            let batch = match next {
+                Some(batch) => batch?,
+                None => {
+                    self.current = None;
+                    continue;
+                }
+            };
+
+            // Terms may produce fields with different names or nullability.
+            let batch = RecordBatch::try_new(self.schema.clone(), batch.columns().to_vec())?;
+            let batch = match &mut self.seen {
+                Some(seen) => seen.remove_seen(batch)?,
+                None => batch,
+            };
+            if batch.num_rows() == 0 {
+                continue;
+            }
+
+            self.iteration_batches.push(batch.clone());
+            self.metrics.record_output(batch.num_rows());
+            return Ok(Some(batch));
+        }
+    }
+
+    /// Start executing the next term, returning `None` once the previous
+    /// iteration didn't produce any rows.
+    fn start_next_term(&mut self) -> Result<Option<SendableRecordBatchStream>> {
+        if let Some(static_term) = self.static_term.take() {
+            return Ok(Some(execute_stream(static_term, self.context.clone())?));
+        }
+
+        if self.iteration_batches.is_empty() {
+            return Ok(None);
+        }
+        if self.depth >= self.max_depth {
+            return Err(DataFusionError::Execution(format!(
+                "Recursive query '{}' exceeded the max recursion depth of {}, the limit can be changed with 'SET max_recursion_depth'",
+                self.name, self.max_depth,
+            )));
+        }
+        self.depth += 1;
+
+        let work_table = Arc::new(WorkTable::default());
+        work_table.update(std::mem::take(&mut self.iteration_batches));
+        let plan = reset_plan(self.recursive_term.clone(), &self.work_table, work_table)?;
+
+        Ok(Some(execute_stream(plan, self.context.clone())?))
+    }
+}
+
+/// Get a fresh copy of the recursive term reading from `work_table`.
+///
+/// Every node is recreated since some execution plans hold on to state after
+/// being executed, and can't be executed again.
+fn reset_plan(
+    plan: Arc<dyn ExecutionPlan>,
+    placeholder: &Arc<WorkTable>,
+    work_table: Arc<WorkTable>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    plan.transform_up(&|plan| {
+        if let Some(exec) = plan.as_any().downcast_ref::<WorkTableExec>() {
+            return Ok(
+                match exec.with_work_table(placeholder, work_table.clone()) {
+                    Some(exec) => Transformed::Yes(Arc::new(exec)),
+                    None => Transformed::No(plan),
+                },
+            );
+        }
+
+        let children = plan.children();
+        if children.is_empty() {
+            return Ok(Transformed::No(plan));
+        }
+        Ok(Transformed::Yes(plan.with_new_children(children)?))
+    })
+}
+
+/// Tracks rows produced by a recursive query to remove duplicates.
+struct SeenRows {
+    converter: RowConverter,
+    rows: HashSet<OwnedRow>,
+}
+
+impl SeenRows {
+    fn try_new(schema: &SchemaRef) -> Result<Self> {
+        let fields = schema
+            .fields()
+            .iter()
+            .map(|field| SortField::new(field.data_type().clone()))
+            .collect();
+        Ok(SeenRows {
+            converter: RowConverter::new(fields)?,
+            rows: HashSet::new(),
+        })
+    }
+
+    /// Remove rows from the batch that have already been seen, including
+    /// duplicates within the batch.
+    fn remove_seen(&mut self, batch: RecordBatch) -> Result<RecordBatch> {
+        let rows = self.converter.convert_columns(batch.columns())?;
+        let keep: BooleanArray = rows
+            .iter()
+            .map(|row| Some(self.rows.insert(row.owned())))
+            .collect();
+        Ok(filter_record_batch(&batch, &keep)?)
+    }
+}
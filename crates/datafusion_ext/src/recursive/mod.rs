@@ -0,0 +1,138 @@
+//! Recursive common table expressions.
+//!
+//! A recursive CTE is planned as a `RecursiveQuery` logical node holding the
+//! static and recursive terms. References to the CTE from the recursive term
+//! scan a work table containing the rows produced by the previous iteration.
+
+mod exec;
+mod work_table;
+
+pub use exec::RecursiveQueryExec;
+pub use work_table::{WorkTable, WorkTableExec, WorkTableProvider};
+
+use async_trait::async_trait;
+use datafusion::common::DFSchemaRef;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::{
+    Expr, Extension, LogicalPlan, UserDefinedLogicalNode, UserDefinedLogicalNodeCore,
+};
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::physical_planner::{ExtensionPlanner, PhysicalPlanner};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// Logical node for a recursive common table expression.
+#[derive(Debug, Clone)]
+pub struct RecursiveQuery {
+    pub name: String,
+    pub static_term: LogicalPlan,
+    pub recursive_term: LogicalPlan,
+    /// Work table scanned by the recursive term.
+    pub work_table: Arc<WorkTable>,
+    pub schema: DFSchemaRef,
+    /// If duplicate rows should be removed (`UNION` instead of `UNION ALL`).
+    pub is_distinct: bool,
+    /// Max number of times the recursive term may be executed.
+    pub max_depth: usize,
+}
+
+impl RecursiveQuery {
+    pub const EXTENSION_NAME: &'static str = "RecursiveQuery";
+
+    pub fn into_logical_plan(self) -> LogicalPlan {
+        LogicalPlan::Extension(Extension {
+            node: Arc::new(self),
+        })
+    }
+}
+
+impl PartialEq for RecursiveQuery {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.static_term == other.static_term
+            && self.recursive_term == other.recursive_term
+            && Arc::ptr_eq(&self.work_table, &other.work_table)
+            && self.schema == other.schema
+            && self.is_distinct == other.is_distinct
+            && self.max_depth == other.max_depth
+    }
+}
+
+impl Eq for RecursiveQuery {}
+
+impl Hash for RecursiveQuery {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.static_term.hash(state);
+        self.recursive_term.hash(state);
+        self.is_distinct.hash(state);
+        self.max_depth.hash(state);
+    }
+}
+
+impl UserDefinedLogicalNodeCore for RecursiveQuery {
+    fn name(&self) -> &str {
+        Self::EXTENSION_NAME
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.static_term, &self.recursive_term]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        Vec::new()
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "RecursiveQuery: name={}, is_distinct={}",
+            self.name, self.is_distinct
+        )
+    }
+
+    fn from_template(&self, _exprs: &[Expr], inputs: &[LogicalPlan]) -> Self {
+        RecursiveQuery {
+            static_term: inputs[0].clone(),
+            recursive_term: inputs[1].clone(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Plans `RecursiveQuery` nodes.
+#[derive(Debug, Default)]
+pub struct RecursiveQueryPlanner;
+
+#[async_trait]
+impl ExtensionPlanner for RecursiveQueryPlanner {
+    async fn plan_extension(
+        &self,
+        _planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        _logical_inputs: &[&LogicalPlan],
+        physical_inputs: &[Arc<dyn ExecutionPlan>],
+        _session_state: &SessionState,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        let node = match node.as_any().downcast_ref::<RecursiveQuery>() {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Arc::new(RecursiveQueryExec::new(
+            node.name.clone(),
+            physical_inputs[0].clone(),
+            physical_inputs[1].clone(),
+            node.work_table.clone(),
+            Arc::new(node.schema.as_ref().into()),
+            node.is_distinct,
+            node.max_depth,
+        ))))
+    }
+}
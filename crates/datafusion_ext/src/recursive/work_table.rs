@@ -0,0 +1,191 @@
+use async_trait::async_trait;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::datasource::{DefaultTableSource, TableProvider};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionState;
+use datafusion::execution::TaskContext;
+use datafusion::logical_expr::{Expr, TableSource, TableType};
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::memory::MemoryStream;
+use datafusion::physical_plan::{
+    project_schema, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use parking_lot::Mutex;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+/// Rows produced by the previous iteration of a recursive query.
+#[derive(Debug, Default)]
+pub struct WorkTable {
+    batches: Mutex<Vec<RecordBatch>>,
+}
+
+impl WorkTable {
+    /// Replace the rows in the work table.
+    pub fn update(&self, batches: Vec<RecordBatch>) {
+        *self.batches.lock() = batches;
+    }
+
+    /// Get the rows currently in the work table.
+    pub fn batches(&self) -> Vec<RecordBatch> {
+        self.batches.lock().clone()
+    }
+}
+
+/// Table provider for recursive references to a common table expression.
+///
+/// The work table held here is only used to identify scans belonging to a
+/// recursive query. The recursive query replaces it with a new work table each
+/// time it's executed.
+#[derive(Debug)]
+pub struct WorkTableProvider {
+    name: String,
+    schema: SchemaRef,
+    work_table: Arc<WorkTable>,
+}
+
+impl WorkTableProvider {
+    pub fn new(name: String, schema: SchemaRef, work_table: Arc<WorkTable>) -> Self {
+        WorkTableProvider {
+            name,
+            schema,
+            work_table,
+        }
+    }
+
+    /// Check if a table source scans the given work table.
+    pub fn is_source_for(source: &Arc<dyn TableSource>, work_table: &Arc<WorkTable>) -> bool {
+        source
+            .as_any()
+            .downcast_ref::<DefaultTableSource>()
+            .and_then(|source| {
+                source
+                    .table_provider
+                    .as_any()
+                    .downcast_ref::<WorkTableProvider>()
+            })
+            .is_some_and(|provider| Arc::ptr_eq(&provider.work_table, work_table))
+    }
+}
+
+#[async_trait]
+impl TableProvider for WorkTableProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Temporary
+    }
+
+    async fn scan(
+        &self,
+        _ctx: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(WorkTableExec {
+            name: self.name.clone(),
+            projected_schema: project_schema(&self.schema, projection)?,
+            projection: projection.cloned(),
+            work_table: self.work_table.clone(),
+        }))
+    }
+}
+
+/// Scans the rows in a work table.
+#[derive(Debug, Clone)]
+pub struct WorkTableExec {
+    name: String,
+    projected_schema: SchemaRef,
+    projection: Option<Vec<usize>>,
+    work_table: Arc<WorkTable>,
+}
+
+impl WorkTableExec {
+    /// Get a copy of this exec that reads from `work_table` if it currently
+    /// reads from `placeholder`.
+    pub fn with_work_table(
+        &self,
+        placeholder: &Arc<WorkTable>,
+        work_table: Arc<WorkTable>,
+    ) -> Option<WorkTableExec> {
+        if !Arc::ptr_eq(&self.work_table, placeholder) {
+            return None;
+        }
+        Some(WorkTableExec {
+            work_table,
+            ..self.clone()
+        })
+    }
+}
+
+impl ExecutionPlan for WorkTableExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.projected_schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        Vec::new()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.is_empty() {
+            Ok(self)
+        } else {
+            Err(DataFusionError::Execution(
+                "cannot change children for WorkTableExec".to_string(),
+            ))
+        }
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        _context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Execution(format!(
+                "invalid partition {partition} for WorkTableExec"
+            )));
+        }
+        Ok(Box::pin(MemoryStream::try_new(
+            self.work_table.batches(),
+            self.projected_schema.clone(),
+            self.projection.clone(),
+        )?))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+impl DisplayAs for WorkTableExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WorkTableExec: name={}", self.name)
+    }
+}
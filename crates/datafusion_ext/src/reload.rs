@@ -0,0 +1,36 @@
+//! Reloading server configuration without restarting.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::errors::Result;
+
+/// Reloads the configuration of the server a session is running on.
+pub trait ReloadConfig: Sync + Send {
+    fn reload_config(&self) -> Result<()>;
+}
+
+/// Handle for reloading server configuration.
+///
+/// Added as an extension to the session config of sessions running on a server
+/// that supports reloading.
+#[derive(Clone)]
+pub struct ConfigReloader {
+    inner: Arc<dyn ReloadConfig>,
+}
+
+impl ConfigReloader {
+    pub fn new(inner: Arc<dyn ReloadConfig>) -> Self {
+        ConfigReloader { inner }
+    }
+
+    pub fn reload(&self) -> Result<()> {
+        self.inner.reload_config()
+    }
+}
+
+impl fmt::Debug for ConfigReloader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigReloader").finish_non_exhaustive()
+    }
+}
@@ -15,6 +15,7 @@ use datafusion::physical_plan::ExecutionPlan;
 use protogen::metastore::types::catalog::RuntimePreference;
 use std::sync::Arc;
 
+use crate::runtime::placement::PlacementCost;
 use crate::runtime::runtime_group::RuntimeGroupExec;
 
 /// Tries to pull up `RuntimeGroupExec`s as far as possible.
@@ -86,6 +87,75 @@ impl RuntimeGroupPullUp {
     }
 }
 
+impl RuntimeGroupPullUp {
+    /// Pick where to run a node reading from both local and remote inputs.
+    ///
+    /// Without pulling up, the node runs locally and every remote input is
+    /// sent to the local side. When the local inputs and the output are
+    /// estimated to be smaller than the remote inputs, it's cheaper to send
+    /// the local inputs to the remote side and run the node there instead.
+    ///
+    /// The inputs on the other side stay wrapped in their runtime group so the
+    /// remote planner can create the exchanges for them.
+    fn place_mixed(
+        self,
+        plan: Arc<dyn ExecutionPlan>,
+        children: Vec<RuntimeGroupExec>,
+    ) -> Result<Transformed<Arc<dyn ExecutionPlan>>> {
+        let has_preference = |preference| children.iter().any(|c| c.preference == preference);
+        if !has_preference(RuntimePreference::Local) || !has_preference(RuntimePreference::Remote) {
+            return Ok(Transformed::No(plan));
+        }
+
+        let cost = match PlacementCost::estimate(plan.as_ref(), &children) {
+            Some(cost) => cost,
+            None => return Ok(Transformed::No(plan)),
+        };
+        let preference = cost.preference();
+
+        // Local inputs that have remote parts themselves can't be sent to the
+        // remote side as they'd need their own exchange.
+        if preference == RuntimePreference::Remote
+            && children.iter().any(|c| {
+                c.preference == RuntimePreference::Local
+                    && contains_preference(&c.child, RuntimePreference::Remote)
+            })
+        {
+            return Ok(Transformed::No(plan));
+        }
+
+        let new_children = children
+            .into_iter()
+            .map(|c| {
+                if c.preference == preference {
+                    c.child
+                } else {
+                    Arc::new(c) as _
+                }
+            })
+            .collect();
+        let node = plan.with_new_children(new_children)?;
+
+        Ok(Transformed::Yes(Arc::new(RuntimeGroupExec {
+            preference,
+            child: node,
+            placement: Some(cost),
+        })))
+    }
+}
+
+/// Whether any runtime group in the plan has the given preference.
+fn contains_preference(plan: &Arc<dyn ExecutionPlan>, preference: RuntimePreference) -> bool {
+    if let Some(group) = plan.as_any().downcast_ref::<RuntimeGroupExec>() {
+        if group.preference == preference {
+            return true;
+        }
+    }
+    plan.children()
+        .iter()
+        .any(|child| contains_preference(child, preference))
+}
+
 impl PhysicalOptimizerRule for RuntimeGroupPullUp {
     fn optimize(
         &self,
@@ -121,7 +191,7 @@ impl PhysicalOptimizerRule for RuntimeGroupPullUp {
             // TODO: How do we want to handle "unspecified"? Allow those to run
             // anywhere?
             if !children.iter().all(|exec| exec.preference == preference) {
-                return Ok(Transformed::No(plan));
+                return self.place_mixed(plan, children);
             }
 
             // All children have the same preference. Swap them out for the
@@ -130,10 +200,9 @@ impl PhysicalOptimizerRule for RuntimeGroupPullUp {
             let swapped_children: Vec<_> = children.into_iter().map(|c| c.child).collect();
             let node = plan.with_new_children(swapped_children)?;
 
-            Ok(Transformed::Yes(Arc::new(RuntimeGroupExec {
-                preference,
-                child: node,
-            })))
+            Ok(Transformed::Yes(Arc::new(RuntimeGroupExec::new(
+                preference, node,
+            ))))
         })
     }
 
@@ -1,4 +1,5 @@
 //! Runtime aware table providers and execution plans.
 pub mod group_pull_up;
+pub mod placement;
 pub mod runtime_group;
 pub mod table_provider;
@@ -0,0 +1,160 @@
+//! Cost based placement of nodes reading from both local and remote inputs.
+use datafusion::arrow::datatypes::{DataType, Schema};
+use datafusion::physical_plan::ExecutionPlan;
+use protogen::metastore::types::catalog::RuntimePreference;
+use std::fmt;
+
+use super::runtime_group::RuntimeGroupExec;
+
+/// Assumed size of a variable width value (strings, binary) when estimating
+/// the size of a row.
+const VARIABLE_WIDTH_ESTIMATE: usize = 32;
+
+/// Assumed size of nested values (lists, structs) when estimating the size of
+/// a row.
+const NESTED_WIDTH_ESTIMATE: usize = 64;
+
+/// Estimated number of bytes transferred between the local and remote side
+/// for each placement of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlacementCost {
+    /// Bytes transferred when running locally, all remote inputs are sent to
+    /// the local side.
+    pub local: usize,
+    /// Bytes transferred when running remotely, all local inputs are sent to
+    /// the remote side and the output is sent back.
+    pub remote: usize,
+}
+
+impl PlacementCost {
+    /// Estimate the cost of running `plan` on either side, where `children`
+    /// are the runtime groups it reads from.
+    ///
+    /// Returns `None` if the size of any input or the output isn't known.
+    pub fn estimate(plan: &dyn ExecutionPlan, children: &[RuntimeGroupExec]) -> Option<Self> {
+        let mut cost = PlacementCost {
+            local: 0,
+            remote: estimated_bytes(plan)?,
+        };
+        for child in children {
+            match child.preference {
+                RuntimePreference::Remote => cost.local += estimated_bytes(child.child.as_ref())?,
+                RuntimePreference::Local => cost.remote += estimated_bytes(child.child.as_ref())?,
+                // Can run on either side.
+                RuntimePreference::Unspecified => (),
+            }
+        }
+        Some(cost)
+    }
+
+    /// The placement transferring the fewest bytes. Ties run locally, which is
+    /// what we'd do without a cost estimate.
+    pub fn preference(&self) -> RuntimePreference {
+        if self.remote < self.local {
+            RuntimePreference::Remote
+        } else {
+            RuntimePreference::Local
+        }
+    }
+}
+
+impl fmt::Display for PlacementCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[local_transfer_bytes={}, remote_transfer_bytes={}]",
+            self.local, self.remote
+        )
+    }
+}
+
+/// Estimate the number of bytes produced by a plan from its statistics.
+pub fn estimated_bytes(plan: &dyn ExecutionPlan) -> Option<usize> {
+    let statistics = plan.statistics();
+    if let Some(bytes) = statistics.total_byte_size {
+        return Some(bytes);
+    }
+    let rows = statistics.num_rows?;
+    Some(rows.saturating_mul(estimated_row_width(&plan.schema())))
+}
+
+fn estimated_row_width(schema: &Schema) -> usize {
+    schema
+        .fields()
+        .iter()
+        .map(|field| estimated_value_width(field.data_type()))
+        .sum::<usize>()
+        .max(1)
+}
+
+fn estimated_value_width(data_type: &DataType) -> usize {
+    if let Some(width) = data_type.primitive_width() {
+        return width;
+    }
+    match data_type {
+        DataType::Null => 0,
+        DataType::Boolean => 1,
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Binary | DataType::LargeBinary => {
+            VARIABLE_WIDTH_ESTIMATE
+        }
+        DataType::Dictionary(_, value) => estimated_value_width(value),
+        _ => NESTED_WIDTH_ESTIMATE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::arrow::array::Int64Array;
+    use datafusion::arrow::datatypes::Field;
+    use datafusion::arrow::record_batch::RecordBatch;
+    use datafusion::physical_plan::memory::MemoryExec;
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn memory_exec(rows: i64) -> Arc<dyn ExecutionPlan> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int64Array::from_iter_values(0..rows))],
+        )
+        .unwrap();
+        Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None).unwrap())
+    }
+
+    #[test]
+    fn row_width() {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Utf8, true),
+            Field::new("c", DataType::Boolean, true),
+        ]);
+        assert_eq!(
+            8 + VARIABLE_WIDTH_ESTIMATE + 1,
+            estimated_row_width(&schema)
+        );
+    }
+
+    #[test]
+    fn cheapest_placement() {
+        let small = memory_exec(10);
+        let large = memory_exec(10_000);
+
+        // Joining a large remote table with a small local one, producing a
+        // small output, is cheaper on the remote side.
+        let children = vec![
+            RuntimeGroupExec::new(RuntimePreference::Local, small.clone()),
+            RuntimeGroupExec::new(RuntimePreference::Remote, large.clone()),
+        ];
+        let cost = PlacementCost::estimate(small.as_ref(), &children).unwrap();
+        assert_eq!(RuntimePreference::Remote, cost.preference());
+
+        // Swapping the sides makes running locally cheaper.
+        let children = vec![
+            RuntimeGroupExec::new(RuntimePreference::Local, large),
+            RuntimeGroupExec::new(RuntimePreference::Remote, small.clone()),
+        ];
+        let cost = PlacementCost::estimate(small.as_ref(), &children).unwrap();
+        assert_eq!(RuntimePreference::Local, cost.preference());
+    }
+}
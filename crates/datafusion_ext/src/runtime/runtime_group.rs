@@ -12,6 +12,8 @@ use std::any::Any;
 use std::fmt;
 use std::sync::Arc;
 
+use super::placement::PlacementCost;
+
 /// An execution plan with an associated runtime preference.
 ///
 /// This does not alter execution of the plan in any way, and is just a way for
@@ -20,11 +22,18 @@ use std::sync::Arc;
 pub struct RuntimeGroupExec {
     pub preference: RuntimePreference,
     pub child: Arc<dyn ExecutionPlan>,
+    /// Estimated cost if the preference was picked by estimating transfer
+    /// sizes, shown in `EXPLAIN`.
+    pub placement: Option<PlacementCost>,
 }
 
 impl RuntimeGroupExec {
     pub fn new(preference: RuntimePreference, child: Arc<dyn ExecutionPlan>) -> Self {
-        Self { preference, child }
+        Self {
+            preference,
+            child,
+            placement: None,
+        }
     }
 }
 
@@ -56,6 +65,7 @@ impl ExecutionPlan for RuntimeGroupExec {
         Ok(Arc::new(Self {
             preference: self.preference,
             child: children[0].clone(),
+            placement: self.placement,
         }))
     }
 
@@ -82,6 +92,10 @@ impl DisplayAs for RuntimeGroupExec {
             f,
             "RuntimeGroupExec: runtime_preference={}",
             self.preference.as_str(),
-        )
+        )?;
+        if let Some(placement) = &self.placement {
+            write!(f, ", placement_cost={placement}")?;
+        }
+        Ok(())
     }
 }
@@ -21,6 +21,9 @@ use super::runtime_group::RuntimeGroupExec;
 pub struct RuntimeAwareTableProvider {
     pub preference: RuntimePreference,
     pub provider: Arc<dyn TableProvider>,
+    /// Statistics to use instead of the ones from the underlying provider,
+    /// e.g. statistics collected with `ANALYZE TABLE`.
+    pub statistics: Option<Statistics>,
 }
 
 impl RuntimeAwareTableProvider {
@@ -28,8 +31,14 @@ impl RuntimeAwareTableProvider {
         Self {
             preference,
             provider,
+            statistics: None,
         }
     }
+
+    pub fn with_statistics(mut self, statistics: Statistics) -> Self {
+        self.statistics = Some(statistics);
+        self
+    }
 }
 
 #[async_trait]
@@ -65,10 +74,7 @@ impl TableProvider for RuntimeAwareTableProvider {
             .provider
             .scan(state, projection, filters, limit)
             .await?;
-        Ok(Arc::new(RuntimeGroupExec {
-            preference: self.preference,
-            child: plan,
-        }))
+        Ok(Arc::new(RuntimeGroupExec::new(self.preference, plan)))
     }
 
     fn supports_filters_pushdown(
@@ -79,7 +85,9 @@ impl TableProvider for RuntimeAwareTableProvider {
     }
 
     fn statistics(&self) -> Option<Statistics> {
-        self.provider.statistics()
+        self.statistics
+            .clone()
+            .or_else(|| self.provider.statistics())
     }
 
     async fn insert_into(
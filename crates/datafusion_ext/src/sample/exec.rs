@@ -0,0 +1,262 @@
+use datafusion::arrow::array::{Array, ArrayRef, BooleanArray};
+use datafusion::arrow::compute::{filter_record_batch, interleave};
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use futures::{stream, StreamExt};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use super::{SampleMethod, SampleSize, SampleSpec};
+
+/// Number of input batches the reservoir may hold on to before the sampled
+/// rows are copied out of them.
+const RESERVOIR_MAX_BATCHES: usize = 64;
+
+/// Samples the rows of its input.
+///
+/// `SYSTEM` samples keep or drop entire batches, `BERNOULLI` samples keep or
+/// drop individual rows, and `RESERVOIR` samples produce an exact number of
+/// rows picked uniformly from all partitions of the input.
+#[derive(Debug)]
+pub struct SampleExec {
+    input: Arc<dyn ExecutionPlan>,
+    spec: SampleSpec,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl SampleExec {
+    pub fn new(input: Arc<dyn ExecutionPlan>, spec: SampleSpec) -> Self {
+        SampleExec {
+            input,
+            spec,
+            metrics: ExecutionPlanMetricsSet::new(),
+        }
+    }
+
+    pub fn spec(&self) -> &SampleSpec {
+        &self.spec
+    }
+
+    fn is_reservoir(&self) -> bool {
+        self.spec.method == SampleMethod::Reservoir
+    }
+}
+
+impl ExecutionPlan for SampleExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        if self.is_reservoir() {
+            Partitioning::UnknownPartitioning(1)
+        } else {
+            self.input.output_partitioning()
+        }
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        if self.is_reservoir() {
+            None
+        } else {
+            self.input.output_ordering()
+        }
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        if self.is_reservoir() {
+            vec![Distribution::SinglePartition]
+        } else {
+            vec![Distribution::UnspecifiedDistribution]
+        }
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![!self.is_reservoir()]
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Execution(
+                "SampleExec requires exactly one child".to_string(),
+            ));
+        }
+        Ok(Arc::new(SampleExec::new(children[0].clone(), self.spec)))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let input = self.input.execute(partition, context)?;
+        let schema = self.schema();
+        let mut rng = self.spec.rng(partition);
+        let metrics = BaselineMetrics::new(&self.metrics, partition);
+
+        match (self.spec.method, self.spec.size) {
+            (SampleMethod::System, SampleSize::Percent(p)) => {
+                let fraction = p / 100.0;
+                let stream = input.filter_map(move |batch| {
+                    let batch = match batch {
+                        Ok(batch) if rng.gen_bool(fraction) => {
+                            metrics.record_output(batch.num_rows());
+                            Some(Ok(batch))
+                        }
+                        Ok(_) => None,
+                        Err(e) => Some(Err(e)),
+                    };
+                    futures::future::ready(batch)
+                });
+                Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)))
+            }
+            (SampleMethod::Bernoulli, SampleSize::Percent(p)) => {
+                let fraction = p / 100.0;
+                let stream = input.map(move |batch| -> Result<RecordBatch> {
+                    let batch = batch?;
+                    let keep: BooleanArray = (0..batch.num_rows())
+                        .map(|_| Some(rng.gen_bool(fraction)))
+                        .collect();
+                    let batch = filter_record_batch(&batch, &keep)?;
+                    metrics.record_output(batch.num_rows());
+                    Ok(batch)
+                });
+                Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)))
+            }
+            (SampleMethod::Reservoir, SampleSize::Rows(n)) => {
+                let reservoir = Reservoir::new(schema.clone(), n, rng);
+                let stream = stream::once(async move {
+                    let batch = reservoir.fill(input).await?;
+                    metrics.record_output(batch.num_rows());
+                    metrics.done();
+                    Ok::<_, DataFusionError>(batch)
+                });
+                Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)))
+            }
+            (method, size) => Err(DataFusionError::Internal(format!(
+                "invalid sample size {size} for method {method}"
+            ))),
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+}
+
+impl DisplayAs for SampleExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SampleExec: {}", self.spec)
+    }
+}
+
+/// Picks a fixed number of rows uniformly from a stream of batches.
+struct Reservoir {
+    schema: SchemaRef,
+    size: usize,
+    rng: StdRng,
+    /// Number of rows seen so far.
+    seen: u64,
+    /// Batches that rows were picked from, until the picked rows are copied
+    /// out of them.
+    batches: Vec<RecordBatch>,
+    /// Picked rows, as (batch, row) indices into `batches`.
+    picked: Vec<(usize, usize)>,
+}
+
+impl Reservoir {
+    fn new(schema: SchemaRef, size: usize, rng: StdRng) -> Self {
+        Reservoir {
+            schema,
+            size,
+            rng,
+            seen: 0,
+            batches: Vec::new(),
+            picked: Vec::new(),
+        }
+    }
+
+    async fn fill(mut self, mut input: SendableRecordBatchStream) -> Result<RecordBatch> {
+        while let Some(batch) = input.next().await {
+            self.insert(batch?)?;
+        }
+        self.picked_rows()
+    }
+
+    fn insert(&mut self, batch: RecordBatch) -> Result<()> {
+        let batch_idx = self.batches.len();
+        let mut used = false;
+
+        for row_idx in 0..batch.num_rows() {
+            if self.picked.len() < self.size {
+                self.picked.push((batch_idx, row_idx));
+                used = true;
+            } else {
+                let idx = self.rng.gen_range(0..=self.seen);
+                if idx < self.size as u64 {
+                    self.picked[idx as usize] = (batch_idx, row_idx);
+                    used = true;
+                }
+            }
+            self.seen += 1;
+        }
+
+        if used {
+            self.batches.push(batch);
+        }
+        if self.batches.len() > RESERVOIR_MAX_BATCHES {
+            let compacted = self.picked_rows()?;
+            self.picked = (0..compacted.num_rows()).map(|row| (0, row)).collect();
+            self.batches = vec![compacted];
+        }
+
+        Ok(())
+    }
+
+    /// Copy the picked rows into a single batch.
+    fn picked_rows(&self) -> Result<RecordBatch> {
+        if self.picked.is_empty() {
+            return Ok(RecordBatch::new_empty(self.schema.clone()));
+        }
+
+        let columns = (0..self.schema.fields().len())
+            .map(|col| {
+                let arrays: Vec<&dyn Array> = self
+                    .batches
+                    .iter()
+                    .map(|batch| batch.column(col).as_ref())
+                    .collect();
+                interleave(&arrays, &self.picked)
+            })
+            .collect::<Result<Vec<ArrayRef>, _>>()?;
+
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
@@ -0,0 +1,269 @@
+//! Table sampling.
+//!
+//! Samples are planned as a `Sample` logical node on top of the sampled table.
+//! When planning a `SYSTEM` sample of a table backed by parquet files, the
+//! sample is pushed down into the parquet scans so that only a fraction of the
+//! row groups are read.
+
+mod exec;
+mod pushdown;
+
+pub use exec::SampleExec;
+
+use async_trait::async_trait;
+use datafusion::common::DFSchemaRef;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::{
+    Expr, Extension, LogicalPlan, UserDefinedLogicalNode, UserDefinedLogicalNodeCore,
+};
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::physical_planner::{ExtensionPlanner, PhysicalPlanner};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Name of the table hint `TABLESAMPLE` clauses are rewritten to when parsing.
+pub const TABLE_SAMPLE_HINT: &str = "tablesample";
+
+/// How rows are picked for a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SampleMethod {
+    /// Pick blocks of rows, each block with the given probability.
+    ///
+    /// For parquet files, blocks are row groups.
+    System,
+    /// Pick each row with the given probability.
+    Bernoulli,
+    /// Pick an exact number of rows.
+    Reservoir,
+}
+
+impl fmt::Display for SampleMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            SampleMethod::System => "system",
+            SampleMethod::Bernoulli => "bernoulli",
+            SampleMethod::Reservoir => "reservoir",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for SampleMethod {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "system" => SampleMethod::System,
+            "bernoulli" => SampleMethod::Bernoulli,
+            "reservoir" => SampleMethod::Reservoir,
+            other => {
+                return Err(DataFusionError::Plan(format!(
+                    "Unknown sampling method '{other}', expected one of 'system', 'bernoulli' or 'reservoir'"
+                )))
+            }
+        })
+    }
+}
+
+/// Size of a sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleSize {
+    /// Percentage of the input rows, between 0 and 100.
+    Percent(f64),
+    /// Exact number of rows.
+    Rows(usize),
+}
+
+impl fmt::Display for SampleSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SampleSize::Percent(p) => write!(f, "{p}%"),
+            SampleSize::Rows(n) => write!(f, "{n} rows"),
+        }
+    }
+}
+
+/// A validated sampling specification.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleSpec {
+    method: SampleMethod,
+    size: SampleSize,
+    /// Seed for repeatable samples.
+    seed: Option<u64>,
+}
+
+impl SampleSpec {
+    pub fn try_new(method: SampleMethod, size: SampleSize, seed: Option<u64>) -> Result<Self> {
+        match (method, size) {
+            (SampleMethod::System | SampleMethod::Bernoulli, SampleSize::Percent(p)) => {
+                if !(0.0..=100.0).contains(&p) {
+                    return Err(DataFusionError::Plan(format!(
+                        "Sample percentage must be between 0 and 100, got {p}"
+                    )));
+                }
+            }
+            (SampleMethod::System | SampleMethod::Bernoulli, SampleSize::Rows(_)) => {
+                return Err(DataFusionError::Plan(format!(
+                    "{} sampling requires a percentage, use 'reservoir' to sample a number of rows",
+                    method.to_string().to_uppercase(),
+                )))
+            }
+            (SampleMethod::Reservoir, SampleSize::Percent(_)) => {
+                return Err(DataFusionError::Plan(
+                    "RESERVOIR sampling requires a number of rows".to_string(),
+                ))
+            }
+            (SampleMethod::Reservoir, SampleSize::Rows(_)) => (),
+        }
+        Ok(SampleSpec { method, size, seed })
+    }
+
+    pub fn method(&self) -> SampleMethod {
+        self.method
+    }
+
+    pub fn size(&self) -> SampleSize {
+        self.size
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Probability of picking a block or row, if sampling by percentage.
+    fn fraction(&self) -> Option<f64> {
+        match self.size {
+            SampleSize::Percent(p) => Some(p / 100.0),
+            SampleSize::Rows(_) => None,
+        }
+    }
+
+    /// Create a random number generator for a partition of the sampled input.
+    fn rng(&self, partition: usize) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed.wrapping_add(partition as u64)),
+            None => StdRng::from_entropy(),
+        }
+    }
+}
+
+impl Eq for SampleSpec {}
+
+impl Hash for SampleSpec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.method.hash(state);
+        match self.size {
+            SampleSize::Percent(p) => p.to_bits().hash(state),
+            SampleSize::Rows(n) => n.hash(state),
+        }
+        self.seed.hash(state);
+    }
+}
+
+impl fmt::Display for SampleSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "method={}, size={}", self.method, self.size)?;
+        if let Some(seed) = self.seed {
+            write!(f, ", seed={seed}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Logical node sampling the rows of its input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Sample {
+    pub input: LogicalPlan,
+    pub spec: SampleSpec,
+}
+
+impl Sample {
+    pub const EXTENSION_NAME: &'static str = "Sample";
+
+    pub fn new(input: LogicalPlan, spec: SampleSpec) -> Self {
+        Sample { input, spec }
+    }
+
+    pub fn into_logical_plan(self) -> LogicalPlan {
+        LogicalPlan::Extension(Extension {
+            node: Arc::new(self),
+        })
+    }
+}
+
+impl UserDefinedLogicalNodeCore for Sample {
+    fn name(&self) -> &str {
+        Self::EXTENSION_NAME
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        self.input.schema()
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        Vec::new()
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sample: {}", self.spec)
+    }
+
+    fn from_template(&self, _exprs: &[Expr], inputs: &[LogicalPlan]) -> Self {
+        Sample {
+            input: inputs[0].clone(),
+            spec: self.spec,
+        }
+    }
+}
+
+/// Plans `Sample` nodes.
+#[derive(Debug, Default)]
+pub struct SamplePlanner;
+
+#[async_trait]
+impl ExtensionPlanner for SamplePlanner {
+    async fn plan_extension(
+        &self,
+        _planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        logical_inputs: &[&LogicalPlan],
+        physical_inputs: &[Arc<dyn ExecutionPlan>],
+        _session_state: &SessionState,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        let node = match node.as_any().downcast_ref::<Sample>() {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+
+        let input = physical_inputs[0].clone();
+
+        // Only push down into plans that scan a single table. Everything
+        // between the sample and the parquet scans is then part of scanning
+        // the table, and doesn't change which rows are read.
+        if node.spec.method == SampleMethod::System && is_table_scan(logical_inputs[0]) {
+            if let Some(plan) = pushdown::sample_parquet_scans(input.clone(), &node.spec)? {
+                return Ok(Some(plan));
+            }
+        }
+
+        Ok(Some(Arc::new(SampleExec::new(input, node.spec))))
+    }
+}
+
+fn is_table_scan(plan: &LogicalPlan) -> bool {
+    match plan {
+        LogicalPlan::TableScan(_) => true,
+        LogicalPlan::Projection(projection) => is_table_scan(&projection.input),
+        LogicalPlan::SubqueryAlias(alias) => is_table_scan(&alias.input),
+        _ => false,
+    }
+}
@@ -0,0 +1,107 @@
+use datafusion::datasource::listing::{FileRange, PartitionedFile};
+use datafusion::datasource::physical_plan::ParquetExec;
+use datafusion::error::Result;
+use datafusion::physical_plan::{ExecutionPlan, Statistics};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::sync::Arc;
+
+use super::SampleSpec;
+
+/// Size of the byte ranges of a file that are picked when sampling.
+///
+/// Parquet scans read the row groups with their midpoint in the scanned range,
+/// so each row group is picked with the sampling probability regardless of its
+/// size. Ranges larger than a row group pick neighbouring row groups together.
+const SAMPLE_RANGE_SIZE: i64 = 8 * 1024 * 1024;
+
+/// Sample the files read by the parquet scans in the plan.
+///
+/// Returns `None` if the plan reads from anything other than parquet scans,
+/// leaving sampling to `SampleExec`.
+pub fn sample_parquet_scans(
+    plan: Arc<dyn ExecutionPlan>,
+    spec: &SampleSpec,
+) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+    let fraction = match spec.fraction() {
+        Some(fraction) => fraction,
+        None => return Ok(None),
+    };
+    let mut rng = spec.rng(0);
+    sample_plan(plan, fraction, &mut rng)
+}
+
+fn sample_plan(
+    plan: Arc<dyn ExecutionPlan>,
+    fraction: f64,
+    rng: &mut StdRng,
+) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+    if let Some(exec) = plan.as_any().downcast_ref::<ParquetExec>() {
+        return Ok(Some(Arc::new(sample_parquet_exec(exec, fraction, rng))));
+    }
+
+    let children = plan.children();
+    if children.is_empty() {
+        return Ok(None);
+    }
+
+    let mut sampled = Vec::with_capacity(children.len());
+    for child in children {
+        match sample_plan(child, fraction, rng)? {
+            Some(child) => sampled.push(child),
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(plan.with_new_children(sampled)?))
+}
+
+fn sample_parquet_exec(exec: &ParquetExec, fraction: f64, rng: &mut StdRng) -> ParquetExec {
+    let mut config = exec.base_config().clone();
+    config.file_groups = config
+        .file_groups
+        .into_iter()
+        .map(|group| {
+            group
+                .into_iter()
+                .flat_map(|file| sample_file(file, fraction, rng))
+                .collect()
+        })
+        .collect();
+    // Statistics for the files no longer apply to the rows being read.
+    config.statistics = Statistics::default();
+
+    ParquetExec::new(config, exec.predicate().cloned(), None)
+}
+
+/// Split the file into ranges, returning a file for each run of picked ranges.
+fn sample_file(file: PartitionedFile, fraction: f64, rng: &mut StdRng) -> Vec<PartitionedFile> {
+    let (start, end) = match &file.range {
+        Some(range) => (range.start, range.end),
+        None => (0, file.object_meta.size as i64),
+    };
+
+    let mut picked: Vec<FileRange> = Vec::new();
+    let mut range_start = start;
+    while range_start < end {
+        let range_end = (range_start + SAMPLE_RANGE_SIZE).min(end);
+        if rng.gen_bool(fraction) {
+            match picked.last_mut() {
+                Some(last) if last.end == range_start => last.end = range_end,
+                _ => picked.push(FileRange {
+                    start: range_start,
+                    end: range_end,
+                }),
+            }
+        }
+        range_start = range_end;
+    }
+
+    picked
+        .into_iter()
+        .map(|range| PartitionedFile {
+            range: Some(range),
+            ..file.clone()
+        })
+        .collect()
+}
@@ -4,18 +4,27 @@ use datafusion::error::Result as DatafusionResult;
 use datafusion::physical_plan::{ExecutionPlan, RecordBatchStream, SendableRecordBatchStream};
 use futures::stream::{Stream, StreamExt};
 use serde_json::json;
+use telemetry::metrics::METRICS;
 use telemetry::Tracker;
 use uuid::Uuid;
 
+use std::fmt;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Instant;
 
 use crate::metrics::AggregatedMetrics;
 
 /// Result type used when we don't know the result of a query yet.
 const UNKNOWN_RESULT_TYPE: &str = "unknown";
 
+/// Receives the metrics for every query pushed through a session's metrics
+/// handler (e.g. for audit logging).
+pub trait QueryObserver: fmt::Debug + Send + Sync {
+    fn observe(&self, metric: &QueryMetrics);
+}
+
 /// Pushes metrics to the telemetry tracker for the open session.
 #[derive(Debug, Clone)]
 pub struct SessionMetricsHandler {
@@ -23,6 +32,7 @@ pub struct SessionMetricsHandler {
     database_id: Uuid,
     connection_id: Uuid,
     tracker: Arc<Tracker>,
+    observers: Vec<Arc<dyn QueryObserver>>,
 }
 
 impl SessionMetricsHandler {
@@ -37,13 +47,35 @@ impl SessionMetricsHandler {
             database_id,
             connection_id,
             tracker,
+            observers: Vec::new(),
         }
     }
 
+    /// Notify `observer` of every metric pushed through this handler.
+    ///
+    /// May be called multiple times, all observers are notified.
+    pub fn with_observer(mut self, observer: Arc<dyn QueryObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
     /// Push a metrics directly into the metrics vector.
     ///
-    /// This will also push the metric out to Segment.
+    /// This will also push the metric out to Segment, record the query in the
+    /// process metrics, and notify all observers.
     pub fn push_metric(&self, metric: QueryMetrics) {
+        for observer in &self.observers {
+            observer.observe(&metric);
+        }
+
+        METRICS
+            .queries
+            .with_label(metric.execution_status.as_str())
+            .inc();
+        METRICS
+            .query_duration
+            .observe_duration(metric.start.elapsed());
+
         self.tracker.track(
             "Execution metric",
             self.user_id,
@@ -61,6 +93,7 @@ impl SessionMetricsHandler {
                 "output_rows": metric.output_rows,
                 "bytes_read": metric.bytes_read,
                 "bytes_written": metric.bytes_written,
+                "retries": metric.retries,
             }),
         );
     }
@@ -104,6 +137,10 @@ pub struct QueryMetrics {
     pub bytes_read: Option<u64>,
     /// Number of bytes written during the execution of write operation.
     pub bytes_written: Option<u64>,
+    /// Number of datasource requests retried after transient errors.
+    pub retries: Option<u64>,
+    /// When execution of the query started.
+    pub start: Instant,
 }
 
 impl Default for QueryMetrics {
@@ -117,6 +154,8 @@ impl Default for QueryMetrics {
             output_rows: None,
             bytes_read: None,
             bytes_written: None,
+            retries: None,
+            start: Instant::now(),
         }
     }
 }
@@ -158,6 +197,7 @@ impl BatchStreamWithMetricSender {
         let agg_metrics = AggregatedMetrics::new_from_plan(self.plan.as_ref());
         metrics.bytes_read = Some(agg_metrics.bytes_read);
         metrics.bytes_written = agg_metrics.bytes_written;
+        metrics.retries = Some(agg_metrics.retries);
         metrics.elapsed_compute_ns = Some(agg_metrics.elapsed_compute_ns);
     }
 }
@@ -0,0 +1,120 @@
+//! Suggestions for misspelled names of tables, columns and functions.
+
+/// Max number of suggestions to return.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Get the candidates closest to `name`, best match first.
+///
+/// Candidates may be qualified (e.g. 'schema.table'), only the last part of
+/// the candidate is compared with `name`, and unqualified candidates are
+/// preferred when equally close. Names are compared case insensitively, and
+/// candidates that are too different aren't returned.
+pub fn closest_matches<I, S>(name: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let name = name.to_lowercase();
+    let len = name.chars().count();
+    // Allow roughly one edit for every three characters, but never replacing
+    // the whole name.
+    let max_distance = (len / 3).max(1).min(len.saturating_sub(1));
+
+    let mut matches: Vec<_> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let candidate = candidate.as_ref();
+            let unqualified = candidate.rsplit('.').next().unwrap_or(candidate);
+            let distance = edit_distance(&name, &unqualified.to_lowercase());
+            let qualified = unqualified.len() != candidate.len();
+            (distance <= max_distance).then(|| (distance, qualified, candidate.to_string()))
+        })
+        .collect();
+    matches.sort();
+    matches.dedup_by(|a, b| a.2 == b.2);
+
+    matches
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, _, candidate)| candidate)
+        .collect()
+}
+
+/// Format suggestions as "did you mean 'a', 'b' or 'c'?".
+///
+/// Returns `None` if there's nothing to suggest.
+pub fn did_you_mean(suggestions: &[String]) -> Option<String> {
+    suggestion_list(suggestions).map(|list| format!("did you mean {list}?"))
+}
+
+/// Format suggestions as "'a', 'b' or 'c'".
+///
+/// Returns `None` if there's nothing to suggest.
+pub fn suggestion_list(suggestions: &[String]) -> Option<String> {
+    let quoted: Vec<_> = suggestions.iter().map(|s| format!("'{s}'")).collect();
+    match quoted.as_slice() {
+        [] => None,
+        [only] => Some(only.clone()),
+        [rest @ .., last] => Some(format!("{} or {last}", rest.join(", "))),
+    }
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        assert_eq!(0, edit_distance("users", "users"));
+        assert_eq!(1, edit_distance("user", "users"));
+        assert_eq!(2, edit_distance("usres", "users"));
+        assert_eq!(5, edit_distance("", "users"));
+    }
+
+    #[test]
+    fn matches() {
+        let candidates = ["users", "ext.users", "orders", "user_events"];
+        assert_eq!(
+            vec!["users", "ext.users"],
+            closest_matches("user", candidates)
+        );
+        assert_eq!(vec!["orders"], closest_matches("ORDRES", candidates));
+        assert!(closest_matches("x", candidates).is_empty());
+        assert!(closest_matches("e", ["a", "b"]).is_empty());
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(None, did_you_mean(&[]));
+        assert_eq!(
+            Some("did you mean 'users'?".to_string()),
+            did_you_mean(&["users".to_string()])
+        );
+        assert_eq!(
+            Some("did you mean 'users', 'ext.users' or 'user'?".to_string()),
+            did_you_mean(&[
+                "users".to_string(),
+                "ext.users".to_string(),
+                "user".to_string()
+            ])
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Types without a native arrow representation.
+//!
+//! Values of these types are stored using an existing arrow type. Fields
+//! containing these values are tagged using the arrow extension type metadata
+//! so that the type is preserved when handing data to other arrow consumers.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, BinaryArray, FixedSizeBinaryArray, StringArray};
+use datafusion::arrow::datatypes::{DataType, Field};
+use datafusion::common::DFSchema;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::expr::{Alias, Cast, ScalarUDF as ScalarUDFExpr, TryCast};
+use datafusion::logical_expr::{Expr, ScalarUDF, Signature, Volatility};
+use datafusion::physical_plan::ColumnarValue;
+use datafusion::scalar::ScalarValue;
+use uuid::Uuid;
+
+use crate::geometry::Geometry;
+
+/// Field metadata key containing the name of the extension type.
+pub const EXTENSION_NAME_KEY: &str = "ARROW:extension:name";
+
+/// Extension name for UUIDs.
+pub const UUID_EXTENSION_NAME: &str = "arrow.uuid";
+
+/// Arrow type used to store UUIDs.
+pub const UUID_DATA_TYPE: DataType = DataType::FixedSizeBinary(16);
+
+/// Extension name for geometries stored as WKB, matching GeoArrow.
+pub const GEOMETRY_EXTENSION_NAME: &str = "geoarrow.wkb";
+
+/// Arrow type used to store geometries as WKB.
+pub const GEOMETRY_DATA_TYPE: DataType = DataType::Binary;
+
+/// Create a field for storing UUIDs.
+pub fn uuid_field(name: impl Into<String>, nullable: bool) -> Field {
+    Field::new(name, UUID_DATA_TYPE, nullable).with_metadata(HashMap::from([(
+        EXTENSION_NAME_KEY.to_string(),
+        UUID_EXTENSION_NAME.to_string(),
+    )]))
+}
+
+/// Create a field for storing geometries.
+pub fn geometry_field(name: impl Into<String>, nullable: bool) -> Field {
+    Field::new(name, GEOMETRY_DATA_TYPE, nullable).with_metadata(HashMap::from([(
+        EXTENSION_NAME_KEY.to_string(),
+        GEOMETRY_EXTENSION_NAME.to_string(),
+    )]))
+}
+
+/// Returns true if the field contains geometries.
+pub fn is_geometry_field(field: &Field) -> bool {
+    field.data_type() == &GEOMETRY_DATA_TYPE
+        && field
+            .metadata()
+            .get(EXTENSION_NAME_KEY)
+            .is_some_and(|name| name == GEOMETRY_EXTENSION_NAME)
+}
+
+/// Returns true if the field contains UUIDs.
+///
+/// Other 16 byte fixed size binaries, e.g. hashes read from parquet files,
+/// aren't UUIDs, so this relies on the field's extension metadata.
+pub fn is_uuid_field(field: &Field) -> bool {
+    field.data_type() == &UUID_DATA_TYPE
+        && field
+            .metadata()
+            .get(EXTENSION_NAME_KEY)
+            .is_some_and(|name| name == UUID_EXTENSION_NAME)
+}
+
+/// Returns true if the expression produces UUIDs.
+///
+/// Datafusion only carries field metadata through column references, so
+/// other expressions are UUIDs if they parse text into UUIDs or cast to a
+/// UUID.
+pub fn is_uuid_expr(expr: &Expr, schema: &DFSchema) -> bool {
+    match expr {
+        Expr::Column(col) => schema
+            .field_from_column(col)
+            .is_ok_and(|field| is_uuid_field(field.field())),
+        Expr::Alias(Alias { expr, .. }) => is_uuid_expr(expr, schema),
+        Expr::Cast(Cast { data_type, .. }) | Expr::TryCast(TryCast { data_type, .. }) => {
+            data_type == &UUID_DATA_TYPE
+        }
+        Expr::ScalarUDF(udf) => matches!(udf.fun.name.as_str(), "uuid_in" | "try_uuid_in"),
+        _ => false,
+    }
+}
+
+/// Create an expression parsing text into UUIDs.
+///
+/// If `safe` is true, text that isn't a valid UUID will be NULL instead of
+/// returning an error.
+pub fn text_to_uuid(expr: Expr, safe: bool) -> Expr {
+    let udf = ScalarUDF {
+        name: if safe { "try_uuid_in" } else { "uuid_in" }.to_string(),
+        signature: Signature::exact(vec![DataType::Utf8], Volatility::Immutable),
+        return_type: Arc::new(|_| Ok(Arc::new(UUID_DATA_TYPE))),
+        fun: Arc::new(move |args| parse_uuids(&args[0], safe)),
+    };
+    Expr::ScalarUDF(ScalarUDFExpr::new(Arc::new(udf), vec![expr]))
+}
+
+/// Create an expression formatting UUIDs as text.
+pub fn uuid_to_text(expr: Expr) -> Expr {
+    let udf = ScalarUDF {
+        name: "uuid_out".to_string(),
+        signature: Signature::exact(vec![UUID_DATA_TYPE], Volatility::Immutable),
+        return_type: Arc::new(|_| Ok(Arc::new(DataType::Utf8))),
+        fun: Arc::new(|args| format_uuids(&args[0])),
+    };
+    Expr::ScalarUDF(ScalarUDFExpr::new(Arc::new(udf), vec![expr]))
+}
+
+/// Create an expression parsing WKT into geometries.
+///
+/// If `safe` is true, text that isn't valid WKT will be NULL instead of
+/// returning an error.
+pub fn text_to_geometry(expr: Expr, safe: bool) -> Expr {
+    let udf = ScalarUDF {
+        name: if safe {
+            "try_geometry_in"
+        } else {
+            "geometry_in"
+        }
+        .to_string(),
+        signature: Signature::exact(vec![DataType::Utf8], Volatility::Immutable),
+        return_type: Arc::new(|_| Ok(Arc::new(GEOMETRY_DATA_TYPE))),
+        fun: Arc::new(move |args| parse_geometries(&args[0], safe)),
+    };
+    Expr::ScalarUDF(ScalarUDFExpr::new(Arc::new(udf), vec![expr]))
+}
+
+fn parse_geometry(s: &str, safe: bool) -> Result<Option<Vec<u8>>> {
+    match Geometry::from_wkt(s) {
+        Ok(geom) => Ok(Some(geom.to_wkb())),
+        Err(_) if safe => Ok(None),
+        Err(e) => Err(DataFusionError::Execution(e.to_string())),
+    }
+}
+
+fn parse_geometries(value: &ColumnarValue, safe: bool) -> Result<ColumnarValue> {
+    Ok(match value {
+        ColumnarValue::Scalar(ScalarValue::Utf8(v)) => {
+            let geom = match v {
+                Some(v) => parse_geometry(v, safe)?,
+                None => None,
+            };
+            ColumnarValue::Scalar(ScalarValue::Binary(geom))
+        }
+        ColumnarValue::Array(arr) => {
+            let arr = arr
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| unexpected_argument(value))?;
+            let geoms = arr
+                .iter()
+                .map(|v| match v {
+                    Some(v) => parse_geometry(v, safe),
+                    None => Ok(None),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            ColumnarValue::Array(Arc::new(BinaryArray::from_iter(geoms)))
+        }
+        other => return Err(unexpected_argument(other)),
+    })
+}
+
+fn parse_uuid(s: &str, safe: bool) -> Result<Option<[u8; 16]>> {
+    match Uuid::parse_str(s) {
+        Ok(uuid) => Ok(Some(uuid.into_bytes())),
+        Err(_) if safe => Ok(None),
+        Err(_) => Err(DataFusionError::Execution(format!(
+            "invalid input syntax for type uuid: \"{s}\""
+        ))),
+    }
+}
+
+fn format_uuid(b: &[u8]) -> Result<String> {
+    let uuid = Uuid::from_slice(b)
+        .map_err(|e| DataFusionError::Execution(format!("invalid uuid: {e}")))?;
+    Ok(uuid.hyphenated().to_string())
+}
+
+fn parse_uuids(value: &ColumnarValue, safe: bool) -> Result<ColumnarValue> {
+    Ok(match value {
+        ColumnarValue::Scalar(ScalarValue::Utf8(v)) => {
+            let uuid = match v {
+                Some(v) => parse_uuid(v, safe)?,
+                None => None,
+            };
+            ColumnarValue::Scalar(ScalarValue::FixedSizeBinary(16, uuid.map(|b| b.to_vec())))
+        }
+        ColumnarValue::Array(arr) => {
+            let arr = arr
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| unexpected_argument(value))?;
+            let mut uuids = Vec::with_capacity(arr.len());
+            for v in arr.iter() {
+                uuids.push(match v {
+                    Some(v) => parse_uuid(v, safe)?,
+                    None => None,
+                });
+            }
+            let arr = FixedSizeBinaryArray::try_from_sparse_iter_with_size(uuids.into_iter(), 16)?;
+            ColumnarValue::Array(Arc::new(arr))
+        }
+        other => return Err(unexpected_argument(other)),
+    })
+}
+
+fn format_uuids(value: &ColumnarValue) -> Result<ColumnarValue> {
+    Ok(match value {
+        ColumnarValue::Scalar(ScalarValue::FixedSizeBinary(16, v)) => {
+            let s = v.as_deref().map(format_uuid).transpose()?;
+            ColumnarValue::Scalar(ScalarValue::Utf8(s))
+        }
+        ColumnarValue::Array(arr) => {
+            let arr = arr
+                .as_any()
+                .downcast_ref::<FixedSizeBinaryArray>()
+                .ok_or_else(|| unexpected_argument(value))?;
+            let strings = arr
+                .iter()
+                .map(|v| v.map(format_uuid).transpose())
+                .collect::<Result<Vec<_>>>()?;
+            ColumnarValue::Array(Arc::new(StringArray::from(strings)))
+        }
+        other => return Err(unexpected_argument(other)),
+    })
+}
+
+fn unexpected_argument(value: &ColumnarValue) -> DataFusionError {
+    DataFusionError::Internal(format!(
+        "unexpected argument of type {} for type conversion",
+        value.data_type()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uuid_text_round_trip() {
+        let input = ColumnarValue::Array(Arc::new(StringArray::from(vec![
+            Some("292a485f-a56a-4938-8f1a-bbbbbbbbbbb1"),
+            None,
+            Some("{292A485F-A56A-4938-8F1A-BBBBBBBBBBB2}"),
+        ])));
+
+        let uuids = match parse_uuids(&input, false).unwrap() {
+            ColumnarValue::Array(arr) => arr,
+            other => panic!("unexpected value: {other:?}"),
+        };
+        assert_eq!(&UUID_DATA_TYPE, uuids.data_type());
+
+        let strings = match format_uuids(&ColumnarValue::Array(uuids)).unwrap() {
+            ColumnarValue::Array(arr) => arr,
+            other => panic!("unexpected value: {other:?}"),
+        };
+        let strings = strings.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(
+            vec![
+                Some("292a485f-a56a-4938-8f1a-bbbbbbbbbbb1"),
+                None,
+                Some("292a485f-a56a-4938-8f1a-bbbbbbbbbbb2"),
+            ],
+            strings.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn parse_wkt() {
+        let input = ColumnarValue::Array(Arc::new(StringArray::from(vec![
+            Some("POINT(1 2)"),
+            None,
+            Some("not a geometry"),
+        ])));
+        parse_geometries(&input, false).unwrap_err();
+
+        let geoms = match parse_geometries(&input, true).unwrap() {
+            ColumnarValue::Array(arr) => arr,
+            other => panic!("unexpected value: {other:?}"),
+        };
+        let geoms = geoms.as_any().downcast_ref::<BinaryArray>().unwrap();
+        let wkt: Vec<_> = geoms
+            .iter()
+            .map(|v| v.map(|v| Geometry::from_wkb(v).unwrap().to_string()))
+            .collect();
+        assert_eq!(vec![Some("POINT(1 2)".to_string()), None, None], wkt);
+    }
+
+    #[test]
+    fn parse_invalid_uuid() {
+        let input = ColumnarValue::Scalar(ScalarValue::Utf8(Some("not-a-uuid".to_string())));
+        parse_uuids(&input, false).unwrap_err();
+
+        match parse_uuids(&input, true).unwrap() {
+            ColumnarValue::Scalar(ScalarValue::FixedSizeBinary(16, None)) => (),
+            other => panic!("unexpected value: {other:?}"),
+        }
+    }
+}
@@ -14,16 +14,17 @@ use datafusion::variable::{VarProvider, VarType};
 use inner::*;
 use uuid::Uuid;
 
-pub use inner::Dialect;
 pub use inner::SessionVarsInner;
+pub use inner::{Dialect, RemoteCompression};
 use once_cell::sync::Lazy;
 use parking_lot::{RwLock, RwLockReadGuard};
 use std::borrow::ToOwned;
 use std::fmt::Display;
 use std::str::FromStr;
 use std::sync::Arc;
+pub use utils::normalize_timezone;
 
-use self::error::VarError;
+pub use self::error::VarError;
 
 #[derive(Debug, Clone)]
 pub struct SessionVars {
@@ -78,6 +79,7 @@ impl SessionVars {
      timezone: String,
      datestyle: String,
      transaction_isolation: String,
+     transaction_read_only: String,
      search_path: Vec<String>,
      enable_debug_datasources: bool,
      force_catalog_refresh: bool,
@@ -88,13 +90,33 @@ impl SessionVars {
      user_id: Uuid,
      user_name: String,
      database_name: String,
+     database: String,
      max_datasource_count: Option<usize>,
      memory_limit_bytes: Option<usize>,
      max_tunnel_count: Option<usize>,
      max_credentials_count: Option<usize>,
      is_cloud_instance: bool,
+     is_embedded: bool,
+     is_admin: bool,
      dialect: Dialect,
      enable_experimental_scheduler: bool,
+     numeric_fallback: String,
+     float_exponent_threshold: i32,
+     max_recursion_depth: usize,
+     external_schema_cache_ttl: usize,
+     object_store_download_part_size: usize,
+     object_store_download_concurrency: usize,
+     batch_size: usize,
+     exchange_buffer_size: usize,
+     remote_stream_retries: usize,
+     remote_compression: RemoteCompression,
+     remote_compression_level: i32,
+     stage_local_tables_max_bytes: usize,
+     external_insert_batch_size: usize,
+     datasource_max_retries: usize,
+     datasource_retry_backoff_ms: usize,
+     datasource_retry_max_backoff_ms: usize,
+     skip_corrupt_files: bool,
     }
 }
 
@@ -135,9 +157,20 @@ impl SessionVars {
         self.search_path().first().cloned()
     }
 
+    /// Returns whether the session can only read, and not modify the catalog
+    /// or any tables.
+    pub fn is_read_only(&self) -> bool {
+        self.transaction_read_only() == "on"
+    }
+
     pub fn set(&mut self, name: &str, val: &str, setter: VarType) -> datafusion::error::Result<()> {
         self.inner.write().set(name, val, setter)
     }
+
+    /// Set a value for a variable if it hasn't been set for the session yet.
+    pub fn set_default(&self, name: &str, val: &str) -> datafusion::error::Result<()> {
+        self.inner.write().set_default(name, val)
+    }
     pub fn with_server_version(self, value: String, setter: VarType) -> Self {
         with_property!(self, server_version, setter, value)
     }
@@ -162,6 +195,10 @@ impl SessionVars {
     pub fn with_transaction_isolation(self, value: String, setter: VarType) -> Self {
         with_property!(self, transaction_isolation, setter, value)
     }
+    pub fn with_transaction_read_only(self, value: bool, setter: VarType) -> Self {
+        let value = if value { "on" } else { "off" };
+        with_property!(self, transaction_read_only, setter, value)
+    }
     pub fn with_search_path(self, value: Vec<String>, setter: VarType) -> Self {
         with_property!(self, search_path, setter, value)
     }
@@ -207,6 +244,12 @@ impl SessionVars {
     pub fn with_is_cloud_instance(self, value: bool, setter: VarType) -> Self {
         with_property!(self, is_cloud_instance, setter, value)
     }
+    pub fn with_is_embedded(self, value: bool, setter: VarType) -> Self {
+        with_property!(self, is_embedded, setter, value)
+    }
+    pub fn with_is_admin(self, value: bool, setter: VarType) -> Self {
+        with_property!(self, is_admin, setter, value)
+    }
 }
 
 impl ConfigExtension for SessionVars {
@@ -65,6 +65,94 @@ pub(super) const TRANSACTION_ISOLATION: ServerVar<str> = ServerVar {
     description: "Transaction isolation level, defaults to 'read uncommitted'",
 };
 
+pub(super) const SERVER_ENCODING: ServerVar<str> = ServerVar {
+    name: "server_encoding",
+    value: "UTF8",
+    group: "postgres",
+    user_configurable: false,
+    description: "Encoding of the server",
+};
+
+pub(super) const STANDARD_CONFORMING_STRINGS: ServerVar<str> = ServerVar {
+    name: "standard_conforming_strings",
+    value: "on",
+    group: "postgres",
+    user_configurable: false,
+    description: "Backslashes in string literals are treated literally",
+};
+
+pub(super) const INTEGER_DATETIMES: ServerVar<str> = ServerVar {
+    name: "integer_datetimes",
+    value: "on",
+    group: "postgres",
+    user_configurable: false,
+    description: "Datetimes are stored as 64-bit integers",
+};
+
+pub(super) const INTERVALSTYLE: ServerVar<str> = ServerVar {
+    name: "IntervalStyle",
+    value: "postgres",
+    group: "postgres",
+    user_configurable: false,
+    description: "Interval style of the client, default postgres",
+};
+
+pub(super) const BYTEA_OUTPUT: ServerVar<str> = ServerVar {
+    name: "bytea_output",
+    value: "hex",
+    group: "postgres",
+    user_configurable: false,
+    description: "Output format for bytea values",
+};
+
+pub(super) const DEFAULT_TRANSACTION_ISOLATION: ServerVar<str> = ServerVar {
+    name: "default_transaction_isolation",
+    value: "read uncommitted",
+    group: "postgres",
+    user_configurable: false,
+    description: "Transaction isolation level of new transactions",
+};
+
+pub(super) const TRANSACTION_READ_ONLY: ServerVar<str> = ServerVar {
+    name: "transaction_read_only",
+    value: "off",
+    group: "postgres",
+    user_configurable: false,
+    description: "Whether the current transaction is read only",
+};
+
+pub(super) const DEFAULT_TRANSACTION_READ_ONLY: ServerVar<str> = ServerVar {
+    name: "default_transaction_read_only",
+    value: "off",
+    group: "postgres",
+    user_configurable: false,
+    description: "Whether new transactions are read only",
+};
+
+pub(super) const MAX_IDENTIFIER_LENGTH: ServerVar<i32> = ServerVar {
+    name: "max_identifier_length",
+    value: &63,
+    group: "postgres",
+    user_configurable: false,
+    description: "Maximum identifier length",
+};
+
+pub(super) const LOCK_TIMEOUT: ServerVar<i32> = ServerVar {
+    name: "lock_timeout",
+    value: &0,
+    group: "postgres",
+    user_configurable: true,
+    description: "Lock timeout in milliseconds, currently has no effect",
+};
+
+pub(super) const IDLE_IN_TRANSACTION_SESSION_TIMEOUT: ServerVar<i32> = ServerVar {
+    name: "idle_in_transaction_session_timeout",
+    value: &0,
+    group: "postgres",
+    user_configurable: true,
+    description: "Idle in transaction timeout in milliseconds, currently has no effect",
+};
+
 pub(super) static DEFAULT_SEARCH_PATH: Lazy<[String; 1]> = Lazy::new(|| ["public".to_owned()]);
 pub(super) static SEARCH_PATH: Lazy<ServerVar<[String]>> = Lazy::new(|| ServerVar {
     name: "search_path",
@@ -148,6 +236,14 @@ pub(super) const DATABASE_NAME: ServerVar<str> = ServerVar {
     description: "Database name",
 };
 
+pub(super) const DATABASE: ServerVar<str> = ServerVar {
+    name: "database",
+    value: "default",
+    group: "glaredb",
+    user_configurable: false,
+    description: "Native database to use for references without a database, set with USE",
+};
+
 pub(super) const MAX_DATASOURCE_COUNT: ServerVar<Option<usize>> = ServerVar {
     name: "max_datasource_count",
     value: &None,
@@ -188,6 +284,22 @@ pub(super) const IS_CLOUD_INSTANCE: ServerVar<bool> = ServerVar {
     description: "Determines if the server is local or cloud",
 };
 
+pub(super) const IS_EMBEDDED: ServerVar<bool> = ServerVar {
+    name: "is_embedded",
+    value: &false,
+    group: "glaredb",
+    user_configurable: false,
+    description: "Whether the session runs embedded in the client process, allowing access to the client's filesystem",
+};
+
+pub(super) const IS_ADMIN: ServerVar<bool> = ServerVar {
+    name: "is_admin",
+    value: &false,
+    group: "glaredb",
+    user_configurable: false,
+    description: "Whether the session's user can run administrative statements, e.g. restoring the catalog",
+};
+
 pub(super) const DIALECT: ServerVar<Dialect> = ServerVar {
     name: "dialect",
     value: &Dialect::Sql,
@@ -196,6 +308,144 @@ pub(super) const DIALECT: ServerVar<Dialect> = ServerVar {
     description: "Dialect of the sql engine",
 };
 
+pub(super) const NUMERIC_FALLBACK: ServerVar<str> = ServerVar {
+    name: "numeric_fallback",
+    value: "decimal256",
+    group: "glaredb",
+    user_configurable: true,
+    description: "How to read external numerics that don't fit in a 128 bit decimal, either 'decimal256' or 'text'",
+};
+
+pub(super) const FLOAT_EXPONENT_THRESHOLD: ServerVar<i32> = ServerVar {
+    name: "float_exponent_threshold",
+    value: &0,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Decimal exponent at or above which floats are output in scientific notation, 0 uses the precision of the float like postgres",
+};
+
+pub(super) const MAX_RECURSION_DEPTH: ServerVar<usize> = ServerVar {
+    name: "max_recursion_depth",
+    value: &1000,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Max number of iterations of a recursive query before erroring",
+};
+
+pub(super) const EXTERNAL_SCHEMA_CACHE_TTL: ServerVar<usize> = ServerVar {
+    name: "external_schema_cache_ttl",
+    value: &86400,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Seconds a cached external table schema is used for before it's inferred again, 0 always infers the schema",
+};
+
+pub(super) const OBJECT_STORE_DOWNLOAD_PART_SIZE: ServerVar<usize> = ServerVar {
+    name: "object_store_download_part_size",
+    value: &(8 * 1024 * 1024),
+    group: "glaredb",
+    user_configurable: true,
+    description: "Size in bytes of the parts large remote objects are downloaded in, 0 downloads objects in a single request",
+};
+
+pub(super) const OBJECT_STORE_DOWNLOAD_CONCURRENCY: ServerVar<usize> = ServerVar {
+    name: "object_store_download_concurrency",
+    value: &8,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Number of parts of a remote object downloaded at the same time",
+};
+
+pub(super) const BATCH_SIZE: ServerVar<usize> = ServerVar {
+    name: "batch_size",
+    value: &8192,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Target number of rows in record batches produced during execution",
+};
+
+pub(super) const EXCHANGE_BUFFER_SIZE: ServerVar<usize> = ServerVar {
+    name: "exchange_buffer_size",
+    value: &4,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Number of record batches received from remote execution that are buffered before the remote side is made to wait",
+};
+
+pub(super) const REMOTE_STREAM_RETRIES: ServerVar<usize> = ServerVar {
+    name: "remote_stream_retries",
+    value: &3,
+    group: "glaredb",
+    user_configurable: true,
+    description:
+        "Number of times a dropped remote execution stream is resumed before the query fails",
+};
+
+pub(super) const REMOTE_COMPRESSION: ServerVar<RemoteCompression> = ServerVar {
+    name: "remote_compression",
+    value: &RemoteCompression::None,
+    group: "glaredb",
+    user_configurable: true,
+    description:
+        "Compression for batches sent from remote execution, one of 'none', 'zstd' or 'lz4'",
+};
+
+pub(super) const REMOTE_COMPRESSION_LEVEL: ServerVar<i32> = ServerVar {
+    name: "remote_compression_level",
+    value: &3,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Compression level used with 'zstd' remote compression",
+};
+
+pub(super) const STAGE_LOCAL_TABLES_MAX_BYTES: ServerVar<usize> = ServerVar {
+    name: "stage_local_tables_max_bytes",
+    value: &(16 * 1024 * 1024),
+    group: "glaredb",
+    user_configurable: true,
+    description: "Local dataframes up to this size in bytes are uploaded for hybrid queries so joins with remote tables run remotely, 0 disables uploading",
+};
+
+pub(super) const EXTERNAL_INSERT_BATCH_SIZE: ServerVar<usize> = ServerVar {
+    name: "external_insert_batch_size",
+    value: &1000,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Max number of rows inserted into an external database table with a single statement, 0 inserts all rows with one statement",
+};
+
+pub(super) const DATASOURCE_MAX_RETRIES: ServerVar<usize> = ServerVar {
+    name: "datasource_max_retries",
+    value: &3,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Number of times requests to object storage and connections to external databases are retried after transient errors, 0 disables retrying",
+};
+
+pub(super) const DATASOURCE_RETRY_BACKOFF_MS: ServerVar<usize> = ServerVar {
+    name: "datasource_retry_backoff_ms",
+    value: &100,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Milliseconds to wait before the first retry of a datasource request, doubled for every following retry",
+};
+
+pub(super) const DATASOURCE_RETRY_MAX_BACKOFF_MS: ServerVar<usize> = ServerVar {
+    name: "datasource_retry_max_backoff_ms",
+    value: &5000,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Max milliseconds to wait between retries of a datasource request",
+};
+
+pub(super) const SKIP_CORRUPT_FILES: ServerVar<bool> = ServerVar {
+    name: "skip_corrupt_files",
+    value: &false,
+    group: "glaredb",
+    user_configurable: true,
+    description: "Skip files that can't be read when scanning multiple files, returning rows from the remaining files with a warning",
+};
+
 pub(super) const ENABLE_EXPERIMENTAL_SCHEDULER: ServerVar<bool> = ServerVar {
     name: "enable_experimental_scheduler",
     value: &false,
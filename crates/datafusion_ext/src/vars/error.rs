@@ -17,6 +17,7 @@ pub enum VarError {
 
 impl From<VarError> for DataFusionError {
     fn from(e: VarError) -> Self {
-        DataFusionError::Execution(e.to_string())
+        // Kept as is so that the kind of error isn't lost.
+        DataFusionError::External(Box::new(e))
     }
 }
@@ -5,21 +5,35 @@ use datafusion::config::ConfigEntry;
 use datafusion::error::Result;
 use datafusion::variable::VarType;
 use std::borrow::Borrow;
+use std::collections::HashMap;
 
 use super::constants::*;
 use super::error::VarError;
+use super::utils::{
+    normalize_client_encoding, normalize_datestyle, normalize_extra_float_digits,
+    normalize_numeric_fallback, normalize_timezone,
+};
 use super::value::Value;
 use std::sync::Arc;
 use tracing::error;
 use uuid::Uuid;
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Dialect {
     #[default]
     Sql,
     Prql,
 }
 
+/// Compression for batches sent from remote execution.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteCompression {
+    #[default]
+    None,
+    Zstd,
+    Lz4,
+}
+
 /// Variables for a session.
 #[derive(Debug)]
 pub struct SessionVarsInner {
@@ -31,6 +45,17 @@ pub struct SessionVarsInner {
     pub timezone: SessionVar<str>,
     pub datestyle: SessionVar<str>,
     pub transaction_isolation: SessionVar<str>,
+    pub server_encoding: SessionVar<str>,
+    pub standard_conforming_strings: SessionVar<str>,
+    pub integer_datetimes: SessionVar<str>,
+    pub intervalstyle: SessionVar<str>,
+    pub bytea_output: SessionVar<str>,
+    pub default_transaction_isolation: SessionVar<str>,
+    pub transaction_read_only: SessionVar<str>,
+    pub default_transaction_read_only: SessionVar<str>,
+    pub max_identifier_length: SessionVar<i32>,
+    pub lock_timeout: SessionVar<i32>,
+    pub idle_in_transaction_session_timeout: SessionVar<i32>,
     pub search_path: SessionVar<[String]>,
     pub enable_debug_datasources: SessionVar<bool>,
     pub force_catalog_refresh: SessionVar<bool>,
@@ -41,81 +66,190 @@ pub struct SessionVarsInner {
     pub user_id: SessionVar<Uuid>,
     pub user_name: SessionVar<str>,
     pub database_name: SessionVar<str>,
+    pub database: SessionVar<str>,
     pub max_datasource_count: SessionVar<Option<usize>>,
     pub memory_limit_bytes: SessionVar<Option<usize>>,
     pub max_tunnel_count: SessionVar<Option<usize>>,
     pub max_credentials_count: SessionVar<Option<usize>>,
     pub is_cloud_instance: SessionVar<bool>,
+    pub is_embedded: SessionVar<bool>,
+    pub is_admin: SessionVar<bool>,
     pub dialect: SessionVar<Dialect>,
     pub enable_experimental_scheduler: SessionVar<bool>,
+    pub numeric_fallback: SessionVar<str>,
+    pub float_exponent_threshold: SessionVar<i32>,
+    pub max_recursion_depth: SessionVar<usize>,
+    pub external_schema_cache_ttl: SessionVar<usize>,
+    pub object_store_download_part_size: SessionVar<usize>,
+    pub object_store_download_concurrency: SessionVar<usize>,
+    pub batch_size: SessionVar<usize>,
+    pub exchange_buffer_size: SessionVar<usize>,
+    pub remote_stream_retries: SessionVar<usize>,
+    pub remote_compression: SessionVar<RemoteCompression>,
+    pub remote_compression_level: SessionVar<i32>,
+    pub stage_local_tables_max_bytes: SessionVar<usize>,
+    pub external_insert_batch_size: SessionVar<usize>,
+    pub datasource_max_retries: SessionVar<usize>,
+    pub datasource_retry_backoff_ms: SessionVar<usize>,
+    pub datasource_retry_max_backoff_ms: SessionVar<usize>,
+    pub skip_corrupt_files: SessionVar<bool>,
+    /// Variables set by the client that we don't know about.
+    custom_vars: HashMap<String, CustomVar>,
+    /// Notices to send to the client as a result of setting variables.
+    notices: Vec<String>,
+    /// Names of reported variables that have changed since the client was
+    /// last sent their values.
+    changed_reported_vars: Vec<String>,
 }
 
 impl SessionVarsInner {
     /// Return an iterator to the variables that should be sent to the client on
     /// session start.
+    ///
+    /// These are also the variables that the client is notified about when
+    /// they change.
     pub fn startup_vars_iter(&self) -> impl Iterator<Item = &dyn AnyVar> {
-        let vars: [&dyn AnyVar; 3] = [
+        let vars: [&dyn AnyVar; 9] = [
             &self.server_version,
+            &self.server_encoding,
             &self.application_name,
             &self.client_encoding,
+            &self.timezone,
+            &self.datestyle,
+            &self.intervalstyle,
+            &self.integer_datetimes,
+            &self.standard_conforming_strings,
         ];
         vars.into_iter()
     }
 
     /// Get a value for a variable.
     pub fn get(&self, name: &str) -> datafusion::error::Result<&dyn AnyVar> {
+        if let Some(var) = self.get_builtin(name) {
+            return Ok(var);
+        }
+        match self.custom_vars.get(&name.to_lowercase()) {
+            Some(var) => Ok(var),
+            None => Err(VarError::UnknownVariable(name.to_string()).into()),
+        }
+    }
+
+    /// Get a variable defined by the system.
+    fn get_builtin(&self, name: &str) -> Option<&dyn AnyVar> {
         if name.eq_ignore_ascii_case(SERVER_VERSION.name) {
-            Ok(&self.server_version)
+            Some(&self.server_version)
         } else if name.eq_ignore_ascii_case(APPLICATION_NAME.name) {
-            Ok(&self.application_name)
+            Some(&self.application_name)
         } else if name.eq_ignore_ascii_case(CLIENT_ENCODING.name) {
-            Ok(&self.client_encoding)
+            Some(&self.client_encoding)
         } else if name.eq_ignore_ascii_case(EXTRA_FLOAT_DIGITS.name) {
-            Ok(&self.extra_floating_digits)
+            Some(&self.extra_floating_digits)
         } else if name.eq_ignore_ascii_case(STATEMENT_TIMEOUT.name) {
-            Ok(&self.statement_timeout)
+            Some(&self.statement_timeout)
         } else if name.eq_ignore_ascii_case(TIMEZONE.name) {
-            Ok(&self.timezone)
+            Some(&self.timezone)
         } else if name.eq_ignore_ascii_case(DATESTYLE.name) {
-            Ok(&self.datestyle)
+            Some(&self.datestyle)
         } else if name.eq_ignore_ascii_case(TRANSACTION_ISOLATION.name) {
-            Ok(&self.transaction_isolation)
+            Some(&self.transaction_isolation)
+        } else if name.eq_ignore_ascii_case(SERVER_ENCODING.name) {
+            Some(&self.server_encoding)
+        } else if name.eq_ignore_ascii_case(STANDARD_CONFORMING_STRINGS.name) {
+            Some(&self.standard_conforming_strings)
+        } else if name.eq_ignore_ascii_case(INTEGER_DATETIMES.name) {
+            Some(&self.integer_datetimes)
+        } else if name.eq_ignore_ascii_case(INTERVALSTYLE.name) {
+            Some(&self.intervalstyle)
+        } else if name.eq_ignore_ascii_case(BYTEA_OUTPUT.name) {
+            Some(&self.bytea_output)
+        } else if name.eq_ignore_ascii_case(DEFAULT_TRANSACTION_ISOLATION.name) {
+            Some(&self.default_transaction_isolation)
+        } else if name.eq_ignore_ascii_case(TRANSACTION_READ_ONLY.name) {
+            Some(&self.transaction_read_only)
+        } else if name.eq_ignore_ascii_case(DEFAULT_TRANSACTION_READ_ONLY.name) {
+            Some(&self.default_transaction_read_only)
+        } else if name.eq_ignore_ascii_case(MAX_IDENTIFIER_LENGTH.name) {
+            Some(&self.max_identifier_length)
+        } else if name.eq_ignore_ascii_case(LOCK_TIMEOUT.name) {
+            Some(&self.lock_timeout)
+        } else if name.eq_ignore_ascii_case(IDLE_IN_TRANSACTION_SESSION_TIMEOUT.name) {
+            Some(&self.idle_in_transaction_session_timeout)
         } else if name.eq_ignore_ascii_case(SEARCH_PATH.name) {
-            Ok(&self.search_path)
+            Some(&self.search_path)
         } else if name.eq_ignore_ascii_case(ENABLE_DEBUG_DATASOURCES.name) {
-            Ok(&self.enable_debug_datasources)
+            Some(&self.enable_debug_datasources)
         } else if name.eq_ignore_ascii_case(FORCE_CATALOG_REFRESH.name) {
-            Ok(&self.force_catalog_refresh)
+            Some(&self.force_catalog_refresh)
         } else if name.eq_ignore_ascii_case(GLAREDB_VERSION.name) {
-            Ok(&self.glaredb_version)
+            Some(&self.glaredb_version)
         } else if name.eq_ignore_ascii_case(DATABASE_ID.name) {
-            Ok(&self.database_id)
+            Some(&self.database_id)
         } else if name.eq_ignore_ascii_case(USER_ID.name) {
-            Ok(&self.user_id)
+            Some(&self.user_id)
         } else if name.eq_ignore_ascii_case(CONNECTION_ID.name) {
-            Ok(&self.connection_id)
+            Some(&self.connection_id)
         } else if name.eq_ignore_ascii_case(REMOTE_SESSION_ID.name) {
-            Ok(&self.remote_session_id)
+            Some(&self.remote_session_id)
         } else if name.eq_ignore_ascii_case(USER_NAME.name) {
-            Ok(&self.user_name)
+            Some(&self.user_name)
         } else if name.eq_ignore_ascii_case(DATABASE_NAME.name) {
-            Ok(&self.database_name)
+            Some(&self.database_name)
+        } else if name.eq_ignore_ascii_case(DATABASE.name) {
+            Some(&self.database)
         } else if name.eq_ignore_ascii_case(MAX_DATASOURCE_COUNT.name) {
-            Ok(&self.max_datasource_count)
+            Some(&self.max_datasource_count)
         } else if name.eq_ignore_ascii_case(MEMORY_LIMIT_BYTES.name) {
-            Ok(&self.memory_limit_bytes)
+            Some(&self.memory_limit_bytes)
         } else if name.eq_ignore_ascii_case(MAX_TUNNEL_COUNT.name) {
-            Ok(&self.max_tunnel_count)
+            Some(&self.max_tunnel_count)
         } else if name.eq_ignore_ascii_case(MAX_CREDENTIALS_COUNT.name) {
-            Ok(&self.max_credentials_count)
+            Some(&self.max_credentials_count)
         } else if name.eq_ignore_ascii_case(IS_CLOUD_INSTANCE.name) {
-            Ok(&self.is_cloud_instance)
+            Some(&self.is_cloud_instance)
+        } else if name.eq_ignore_ascii_case(IS_EMBEDDED.name) {
+            Some(&self.is_embedded)
+        } else if name.eq_ignore_ascii_case(IS_ADMIN.name) {
+            Some(&self.is_admin)
         } else if name.eq_ignore_ascii_case(DIALECT.name) {
-            Ok(&self.dialect)
+            Some(&self.dialect)
         } else if name.eq_ignore_ascii_case(ENABLE_EXPERIMENTAL_SCHEDULER.name) {
-            Ok(&self.enable_experimental_scheduler)
+            Some(&self.enable_experimental_scheduler)
+        } else if name.eq_ignore_ascii_case(NUMERIC_FALLBACK.name) {
+            Some(&self.numeric_fallback)
+        } else if name.eq_ignore_ascii_case(FLOAT_EXPONENT_THRESHOLD.name) {
+            Some(&self.float_exponent_threshold)
+        } else if name.eq_ignore_ascii_case(MAX_RECURSION_DEPTH.name) {
+            Some(&self.max_recursion_depth)
+        } else if name.eq_ignore_ascii_case(EXTERNAL_SCHEMA_CACHE_TTL.name) {
+            Some(&self.external_schema_cache_ttl)
+        } else if name.eq_ignore_ascii_case(OBJECT_STORE_DOWNLOAD_PART_SIZE.name) {
+            Some(&self.object_store_download_part_size)
+        } else if name.eq_ignore_ascii_case(OBJECT_STORE_DOWNLOAD_CONCURRENCY.name) {
+            Some(&self.object_store_download_concurrency)
+        } else if name.eq_ignore_ascii_case(BATCH_SIZE.name) {
+            Some(&self.batch_size)
+        } else if name.eq_ignore_ascii_case(EXCHANGE_BUFFER_SIZE.name) {
+            Some(&self.exchange_buffer_size)
+        } else if name.eq_ignore_ascii_case(REMOTE_STREAM_RETRIES.name) {
+            Some(&self.remote_stream_retries)
+        } else if name.eq_ignore_ascii_case(REMOTE_COMPRESSION.name) {
+            Some(&self.remote_compression)
+        } else if name.eq_ignore_ascii_case(REMOTE_COMPRESSION_LEVEL.name) {
+            Some(&self.remote_compression_level)
+        } else if name.eq_ignore_ascii_case(STAGE_LOCAL_TABLES_MAX_BYTES.name) {
+            Some(&self.stage_local_tables_max_bytes)
+        } else if name.eq_ignore_ascii_case(EXTERNAL_INSERT_BATCH_SIZE.name) {
+            Some(&self.external_insert_batch_size)
+        } else if name.eq_ignore_ascii_case(DATASOURCE_MAX_RETRIES.name) {
+            Some(&self.datasource_max_retries)
+        } else if name.eq_ignore_ascii_case(DATASOURCE_RETRY_BACKOFF_MS.name) {
+            Some(&self.datasource_retry_backoff_ms)
+        } else if name.eq_ignore_ascii_case(DATASOURCE_RETRY_MAX_BACKOFF_MS.name) {
+            Some(&self.datasource_retry_max_backoff_ms)
+        } else if name.eq_ignore_ascii_case(SKIP_CORRUPT_FILES.name) {
+            Some(&self.skip_corrupt_files)
         } else {
-            Err(VarError::UnknownVariable(name.to_string()).into())
+            None
         }
     }
 
@@ -126,17 +260,44 @@ impl SessionVarsInner {
         } else if name.eq_ignore_ascii_case(APPLICATION_NAME.name) {
             self.application_name.set_from_str(val, setter)
         } else if name.eq_ignore_ascii_case(CLIENT_ENCODING.name) {
-            self.client_encoding.set_from_str(val, setter)
+            let val = normalize_client_encoding(val).ok_or_else(|| invalid_value(name, val))?;
+            self.client_encoding.set_from_str(&val, setter)
         } else if name.eq_ignore_ascii_case(EXTRA_FLOAT_DIGITS.name) {
-            self.extra_floating_digits.set_from_str(val, setter)
+            let val = normalize_extra_float_digits(val).ok_or_else(|| invalid_value(name, val))?;
+            self.extra_floating_digits.set_from_str(&val, setter)
         } else if name.eq_ignore_ascii_case(STATEMENT_TIMEOUT.name) {
             self.statement_timeout.set_from_str(val, setter)
         } else if name.eq_ignore_ascii_case(TIMEZONE.name) {
-            self.timezone.set_from_str(val, setter)
+            let val = normalize_timezone(val).ok_or_else(|| invalid_value(name, val))?;
+            self.timezone.set_from_str(&val, setter)
         } else if name.eq_ignore_ascii_case(DATESTYLE.name) {
-            self.datestyle.set_from_str(val, setter)
+            let val = normalize_datestyle(val).ok_or_else(|| invalid_value(name, val))?;
+            self.datestyle.set_from_str(&val, setter)
         } else if name.eq_ignore_ascii_case(TRANSACTION_ISOLATION.name) {
             self.transaction_isolation.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(SERVER_ENCODING.name) {
+            self.server_encoding.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(STANDARD_CONFORMING_STRINGS.name) {
+            self.standard_conforming_strings.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(INTEGER_DATETIMES.name) {
+            self.integer_datetimes.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(INTERVALSTYLE.name) {
+            self.intervalstyle.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(BYTEA_OUTPUT.name) {
+            self.bytea_output.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(DEFAULT_TRANSACTION_ISOLATION.name) {
+            self.default_transaction_isolation.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(TRANSACTION_READ_ONLY.name) {
+            self.transaction_read_only.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(DEFAULT_TRANSACTION_READ_ONLY.name) {
+            self.default_transaction_read_only.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(MAX_IDENTIFIER_LENGTH.name) {
+            self.max_identifier_length.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(LOCK_TIMEOUT.name) {
+            self.lock_timeout.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(IDLE_IN_TRANSACTION_SESSION_TIMEOUT.name) {
+            self.idle_in_transaction_session_timeout
+                .set_from_str(val, setter)
         } else if name.eq_ignore_ascii_case(SEARCH_PATH.name) {
             self.search_path.set_from_str(val, setter)
         } else if name.eq_ignore_ascii_case(ENABLE_DEBUG_DATASOURCES.name) {
@@ -157,6 +318,8 @@ impl SessionVarsInner {
             self.user_name.set_from_str(val, setter)
         } else if name.eq_ignore_ascii_case(DATABASE_NAME.name) {
             self.database_name.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(DATABASE.name) {
+            self.database.set_from_str(val, setter)
         } else if name.eq_ignore_ascii_case(MAX_DATASOURCE_COUNT.name) {
             self.max_datasource_count.set_from_str(val, setter)
         } else if name.eq_ignore_ascii_case(MEMORY_LIMIT_BYTES.name) {
@@ -169,12 +332,129 @@ impl SessionVarsInner {
             self.dialect.set_from_str(val, setter)
         } else if name.eq_ignore_ascii_case(ENABLE_EXPERIMENTAL_SCHEDULER.name) {
             self.enable_experimental_scheduler.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(NUMERIC_FALLBACK.name) {
+            let val = normalize_numeric_fallback(val).ok_or_else(|| invalid_value(name, val))?;
+            self.numeric_fallback.set_from_str(&val, setter)
+        } else if name.eq_ignore_ascii_case(FLOAT_EXPONENT_THRESHOLD.name) {
+            self.float_exponent_threshold.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(MAX_RECURSION_DEPTH.name) {
+            self.max_recursion_depth.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(EXTERNAL_SCHEMA_CACHE_TTL.name) {
+            self.external_schema_cache_ttl.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(OBJECT_STORE_DOWNLOAD_PART_SIZE.name) {
+            self.object_store_download_part_size
+                .set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(OBJECT_STORE_DOWNLOAD_CONCURRENCY.name) {
+            self.object_store_download_concurrency
+                .set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(BATCH_SIZE.name) {
+            self.batch_size.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(EXCHANGE_BUFFER_SIZE.name) {
+            self.exchange_buffer_size.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(REMOTE_STREAM_RETRIES.name) {
+            self.remote_stream_retries.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(REMOTE_COMPRESSION.name) {
+            self.remote_compression.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(REMOTE_COMPRESSION_LEVEL.name) {
+            self.remote_compression_level.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(STAGE_LOCAL_TABLES_MAX_BYTES.name) {
+            self.stage_local_tables_max_bytes.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(EXTERNAL_INSERT_BATCH_SIZE.name) {
+            self.external_insert_batch_size.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(DATASOURCE_MAX_RETRIES.name) {
+            self.datasource_max_retries.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(DATASOURCE_RETRY_BACKOFF_MS.name) {
+            self.datasource_retry_backoff_ms.set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(DATASOURCE_RETRY_MAX_BACKOFF_MS.name) {
+            self.datasource_retry_max_backoff_ms
+                .set_from_str(val, setter)
+        } else if name.eq_ignore_ascii_case(SKIP_CORRUPT_FILES.name) {
+            self.skip_corrupt_files.set_from_str(val, setter)
         } else {
             Err(VarError::UnknownVariable(name.to_string()).into())
         }
     }
+
+    /// Set a value for a variable from a `SET` statement sent by the client.
+    ///
+    /// This is more lenient than `set` since drivers will often set variables
+    /// on connect that we either don't know about or can't change:
+    ///
+    /// - Unknown variables are stored so that they can be shown, but otherwise
+    ///   have no effect. A notice is queued to let the client know.
+    /// - Setting a readonly variable to its current value is a no-op.
+    ///
+    /// Changes to variables that are reported to the client on startup are
+    /// tracked so that the client can be sent the new values.
+    pub fn set_lenient(&mut self, name: &str, val: &str, setter: VarType) -> Result<()> {
+        let var = match self.get_builtin(name) {
+            Some(var) => var,
+            None => {
+                self.notices.push(format!(
+                    "unrecognized configuration parameter \"{name}\", the value will be stored but has no effect"
+                ));
+                let name = name.to_lowercase();
+                self.custom_vars.insert(
+                    name.clone(),
+                    CustomVar {
+                        name,
+                        value: val.to_string(),
+                    },
+                );
+                return Ok(());
+            }
+        };
+        let canonical_name = var.name().to_string();
+        let current = var.formatted_value();
+
+        if let Err(e) = self.set(name, val, setter) {
+            if current.eq_ignore_ascii_case(val) {
+                return Ok(());
+            }
+            return Err(e);
+        }
+
+        let is_reported = self
+            .startup_vars_iter()
+            .any(|var| var.name() == canonical_name);
+        if is_reported && !self.changed_reported_vars.contains(&canonical_name) {
+            self.changed_reported_vars.push(canonical_name);
+        }
+
+        Ok(())
+    }
+
+    /// Take the notices that have been generated from setting variables.
+    pub fn take_notices(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notices)
+    }
+
+    /// Take the reported variables that have changed, along with their new
+    /// values.
+    pub fn take_changed_reported_vars(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.changed_reported_vars)
+            .into_iter()
+            .filter_map(|name| {
+                let val = self.get(&name).ok()?.formatted_value();
+                Some((name, val))
+            })
+            .collect()
+    }
+
+    /// Set a value for a variable only if it hasn't already been set for the
+    /// session.
+    ///
+    /// Used for applying persisted defaults without overriding values
+    /// provided by the client.
+    pub fn set_default(&mut self, name: &str, val: &str) -> Result<()> {
+        if self.get(name)?.is_set() {
+            return Ok(());
+        }
+        self.set(name, val, VarType::UserDefined)
+    }
+
     pub(super) fn entries(&self) -> Vec<ConfigEntry> {
-        vec![
+        let mut entries = vec![
             self.server_version.config_entry(),
             self.application_name.config_entry(),
             self.client_encoding.config_entry(),
@@ -183,6 +463,17 @@ impl SessionVarsInner {
             self.timezone.config_entry(),
             self.datestyle.config_entry(),
             self.transaction_isolation.config_entry(),
+            self.server_encoding.config_entry(),
+            self.standard_conforming_strings.config_entry(),
+            self.integer_datetimes.config_entry(),
+            self.intervalstyle.config_entry(),
+            self.bytea_output.config_entry(),
+            self.default_transaction_isolation.config_entry(),
+            self.transaction_read_only.config_entry(),
+            self.default_transaction_read_only.config_entry(),
+            self.max_identifier_length.config_entry(),
+            self.lock_timeout.config_entry(),
+            self.idle_in_transaction_session_timeout.config_entry(),
             self.search_path.config_entry(),
             self.enable_debug_datasources.config_entry(),
             self.force_catalog_refresh.config_entry(),
@@ -193,13 +484,42 @@ impl SessionVarsInner {
             self.remote_session_id.config_entry(),
             self.user_name.config_entry(),
             self.database_name.config_entry(),
+            self.database.config_entry(),
             self.max_datasource_count.config_entry(),
             self.memory_limit_bytes.config_entry(),
             self.max_tunnel_count.config_entry(),
             self.max_credentials_count.config_entry(),
             self.is_cloud_instance.config_entry(),
+            self.is_embedded.config_entry(),
+            self.is_admin.config_entry(),
             self.dialect.config_entry(),
-        ]
+            self.numeric_fallback.config_entry(),
+            self.float_exponent_threshold.config_entry(),
+            self.max_recursion_depth.config_entry(),
+            self.external_schema_cache_ttl.config_entry(),
+            self.object_store_download_part_size.config_entry(),
+            self.object_store_download_concurrency.config_entry(),
+            self.batch_size.config_entry(),
+            self.exchange_buffer_size.config_entry(),
+            self.remote_stream_retries.config_entry(),
+            self.remote_compression.config_entry(),
+            self.remote_compression_level.config_entry(),
+            self.stage_local_tables_max_bytes.config_entry(),
+            self.external_insert_batch_size.config_entry(),
+            self.datasource_max_retries.config_entry(),
+            self.datasource_retry_backoff_ms.config_entry(),
+            self.datasource_retry_max_backoff_ms.config_entry(),
+            self.skip_corrupt_files.config_entry(),
+        ];
+        entries.extend(self.custom_vars.values().map(CustomVar::config_entry));
+        entries
+    }
+}
+
+fn invalid_value(name: &str, val: &str) -> VarError {
+    VarError::InvalidSessionVarValue {
+        name: name.to_string(),
+        val: val.to_string(),
     }
 }
 impl Default for SessionVarsInner {
@@ -213,6 +533,19 @@ impl Default for SessionVarsInner {
             timezone: SessionVar::new(&TIMEZONE),
             datestyle: SessionVar::new(&DATESTYLE),
             transaction_isolation: SessionVar::new(&TRANSACTION_ISOLATION),
+            server_encoding: SessionVar::new(&SERVER_ENCODING),
+            standard_conforming_strings: SessionVar::new(&STANDARD_CONFORMING_STRINGS),
+            integer_datetimes: SessionVar::new(&INTEGER_DATETIMES),
+            intervalstyle: SessionVar::new(&INTERVALSTYLE),
+            bytea_output: SessionVar::new(&BYTEA_OUTPUT),
+            default_transaction_isolation: SessionVar::new(&DEFAULT_TRANSACTION_ISOLATION),
+            transaction_read_only: SessionVar::new(&TRANSACTION_READ_ONLY),
+            default_transaction_read_only: SessionVar::new(&DEFAULT_TRANSACTION_READ_ONLY),
+            max_identifier_length: SessionVar::new(&MAX_IDENTIFIER_LENGTH),
+            lock_timeout: SessionVar::new(&LOCK_TIMEOUT),
+            idle_in_transaction_session_timeout: SessionVar::new(
+                &IDLE_IN_TRANSACTION_SESSION_TIMEOUT,
+            ),
             search_path: SessionVar::new(&SEARCH_PATH),
             enable_debug_datasources: SessionVar::new(&ENABLE_DEBUG_DATASOURCES),
             force_catalog_refresh: SessionVar::new(&FORCE_CATALOG_REFRESH),
@@ -223,24 +556,52 @@ impl Default for SessionVarsInner {
             remote_session_id: SessionVar::new(&REMOTE_SESSION_ID),
             user_name: SessionVar::new(&USER_NAME),
             database_name: SessionVar::new(&DATABASE_NAME),
+            database: SessionVar::new(&DATABASE),
             max_datasource_count: SessionVar::new(&MAX_DATASOURCE_COUNT),
             memory_limit_bytes: SessionVar::new(&MEMORY_LIMIT_BYTES),
             max_tunnel_count: SessionVar::new(&MAX_TUNNEL_COUNT),
             max_credentials_count: SessionVar::new(&MAX_CREDENTIALS_COUNT),
             is_cloud_instance: SessionVar::new(&IS_CLOUD_INSTANCE),
+            is_embedded: SessionVar::new(&IS_EMBEDDED),
+            is_admin: SessionVar::new(&IS_ADMIN),
             dialect: SessionVar::new(&DIALECT),
             enable_experimental_scheduler: SessionVar::new(&ENABLE_EXPERIMENTAL_SCHEDULER),
+            numeric_fallback: SessionVar::new(&NUMERIC_FALLBACK),
+            float_exponent_threshold: SessionVar::new(&FLOAT_EXPONENT_THRESHOLD),
+            max_recursion_depth: SessionVar::new(&MAX_RECURSION_DEPTH),
+            external_schema_cache_ttl: SessionVar::new(&EXTERNAL_SCHEMA_CACHE_TTL),
+            object_store_download_part_size: SessionVar::new(&OBJECT_STORE_DOWNLOAD_PART_SIZE),
+            object_store_download_concurrency: SessionVar::new(&OBJECT_STORE_DOWNLOAD_CONCURRENCY),
+            batch_size: SessionVar::new(&BATCH_SIZE),
+            exchange_buffer_size: SessionVar::new(&EXCHANGE_BUFFER_SIZE),
+            remote_stream_retries: SessionVar::new(&REMOTE_STREAM_RETRIES),
+            remote_compression: SessionVar::new(&REMOTE_COMPRESSION),
+            remote_compression_level: SessionVar::new(&REMOTE_COMPRESSION_LEVEL),
+            stage_local_tables_max_bytes: SessionVar::new(&STAGE_LOCAL_TABLES_MAX_BYTES),
+            external_insert_batch_size: SessionVar::new(&EXTERNAL_INSERT_BATCH_SIZE),
+            datasource_max_retries: SessionVar::new(&DATASOURCE_MAX_RETRIES),
+            datasource_retry_backoff_ms: SessionVar::new(&DATASOURCE_RETRY_BACKOFF_MS),
+            datasource_retry_max_backoff_ms: SessionVar::new(&DATASOURCE_RETRY_MAX_BACKOFF_MS),
+            skip_corrupt_files: SessionVar::new(&SKIP_CORRUPT_FILES),
+            custom_vars: HashMap::new(),
+            notices: Vec::new(),
+            changed_reported_vars: Vec::new(),
         }
     }
 }
 
 pub trait AnyVar {
     /// Return the name of the varaible.
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
 
     /// Return the stringified value for the variable.
     fn formatted_value(&self) -> String;
 
+    /// Whether or not a value has been explicitly set for the session.
+    fn is_set(&self) -> bool {
+        false
+    }
+
     /// Create a record batch containg one row with the string value.
     fn record_batch(&self) -> RecordBatch {
         let val = self.formatted_value();
@@ -291,7 +652,7 @@ impl<T> AnyVar for ServerVar<T>
 where
     T: Value + ?Sized + 'static,
 {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         self.name
     }
 
@@ -378,8 +739,8 @@ impl<T> AnyVar for SessionVar<T>
 where
     T: Value + ?Sized + 'static,
 {
-    fn name(&self) -> &'static str {
-        self.inherit.name()
+    fn name(&self) -> &str {
+        self.inherit.name
     }
 
     fn formatted_value(&self) -> String {
@@ -388,4 +749,39 @@ where
             None => self.inherit.formatted_value(),
         }
     }
+
+    fn is_set(&self) -> bool {
+        self.value.is_some()
+    }
+}
+
+/// A variable set by the client that isn't known to the system.
+#[derive(Debug, Clone)]
+pub struct CustomVar {
+    name: String,
+    value: String,
+}
+
+impl CustomVar {
+    fn config_entry(&self) -> ConfigEntry {
+        ConfigEntry {
+            key: self.name.clone(),
+            value: Some(self.value.clone()),
+            description: "Custom variable set by the client",
+        }
+    }
+}
+
+impl AnyVar for CustomVar {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn formatted_value(&self) -> String {
+        self.value.clone()
+    }
+
+    fn is_set(&self) -> bool {
+        true
+    }
 }
@@ -1,3 +1,4 @@
+use datafusion::arrow::array::timezone::Tz;
 use regex::Regex;
 
 use super::*;
@@ -18,6 +19,76 @@ pub(super) fn split_comma_delimited(text: &str) -> Vec<String> {
         .collect()
 }
 
+/// Normalize a time zone provided by the client.
+///
+/// Accepts IANA time zone names (e.g. 'America/New_York'), fixed offsets
+/// (e.g. '+05:30'), and whole hours offset from UTC (e.g. '-8'). 'UTC' and
+/// 'GMT' are matched case insensitively. Returns `None` if the time zone isn't
+/// valid.
+pub fn normalize_timezone(tz: &str) -> Option<String> {
+    let tz = tz.trim();
+    if ["utc", "gmt", "z"]
+        .iter()
+        .any(|s| tz.eq_ignore_ascii_case(s))
+    {
+        return Some("UTC".to_string());
+    }
+    if let Ok(hours) = tz.parse::<i32>() {
+        if !(-15..=15).contains(&hours) {
+            return None;
+        }
+        let sign = if hours < 0 { '-' } else { '+' };
+        return Some(format!("{sign}{:02}:00", hours.abs()));
+    }
+    Tz::from_str(tz).ok().map(|_| tz.to_string())
+}
+
+/// Normalize a client encoding. Only UTF8 is supported.
+pub(super) fn normalize_client_encoding(encoding: &str) -> Option<String> {
+    match encoding.trim().to_ascii_uppercase().as_str() {
+        "UTF8" | "UTF-8" | "UNICODE" => Some("UTF8".to_string()),
+        _ => None,
+    }
+}
+
+/// Normalize a date style provided by the client.
+///
+/// Values are always output using the ISO style, so only the ISO output style
+/// is accepted. The field ordering (e.g. 'MDY') is accepted and kept as is
+/// since it only affects interpreting ambiguous input.
+pub(super) fn normalize_datestyle(style: &str) -> Option<String> {
+    let mut order = None;
+    for part in style.split(',').map(str::trim) {
+        match part.to_ascii_uppercase().as_str() {
+            "ISO" => (),
+            part @ ("MDY" | "DMY" | "YMD") => order = Some(part.to_string()),
+            "US" | "NONEUROPEAN" => order = Some("MDY".to_string()),
+            "EURO" | "EUROPEAN" => order = Some("DMY".to_string()),
+            _ => return None,
+        }
+    }
+    Some(match order {
+        Some(order) => format!("ISO, {order}"),
+        None => "ISO".to_string(),
+    })
+}
+
+/// Normalize the representation to use for numerics that don't fit in a 128
+/// bit decimal.
+pub(super) fn normalize_numeric_fallback(fallback: &str) -> Option<String> {
+    match fallback.trim().to_ascii_lowercase().as_str() {
+        fallback @ ("decimal256" | "text") => Some(fallback.to_string()),
+        _ => None,
+    }
+}
+
+/// Normalize the number of extra float digits, which must be between -15 and
+/// 3 (same as postgres).
+pub(super) fn normalize_extra_float_digits(digits: &str) -> Option<String> {
+    let digits: i32 = digits.trim().parse().ok()?;
+    (-15..=3).contains(&digits).then(|| digits.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use datafusion::variable::VarType;
@@ -56,6 +127,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn normalize_timezones() {
+        assert_eq!(Some("UTC".to_string()), normalize_timezone("utc"));
+        assert_eq!(Some("UTC".to_string()), normalize_timezone("GMT"));
+        assert_eq!(
+            Some("America/New_York".to_string()),
+            normalize_timezone("America/New_York")
+        );
+        assert_eq!(Some("+05:30".to_string()), normalize_timezone("+05:30"));
+        assert_eq!(Some("-08:00".to_string()), normalize_timezone("-8"));
+        assert_eq!(Some("+03:00".to_string()), normalize_timezone("3"));
+        assert_eq!(None, normalize_timezone("Mars/Olympus_Mons"));
+        assert_eq!(None, normalize_timezone("24"));
+    }
+
+    #[test]
+    fn normalize_client_encodings() {
+        assert_eq!(Some("UTF8".to_string()), normalize_client_encoding("utf8"));
+        assert_eq!(Some("UTF8".to_string()), normalize_client_encoding("UTF-8"));
+        assert_eq!(
+            Some("UTF8".to_string()),
+            normalize_client_encoding("unicode")
+        );
+        assert_eq!(None, normalize_client_encoding("LATIN1"));
+    }
+
+    #[test]
+    fn normalize_datestyles() {
+        assert_eq!(Some("ISO".to_string()), normalize_datestyle("iso"));
+        assert_eq!(
+            Some("ISO, MDY".to_string()),
+            normalize_datestyle("ISO, MDY")
+        );
+        assert_eq!(
+            Some("ISO, DMY".to_string()),
+            normalize_datestyle("European")
+        );
+        assert_eq!(None, normalize_datestyle("German"));
+        assert_eq!(None, normalize_datestyle("SQL, DMY"));
+    }
+
+    #[test]
+    fn normalize_extra_float_digits_range() {
+        assert_eq!(Some("3".to_string()), normalize_extra_float_digits(" 3"));
+        assert_eq!(Some("-15".to_string()), normalize_extra_float_digits("-15"));
+        assert_eq!(None, normalize_extra_float_digits("4"));
+        assert_eq!(None, normalize_extra_float_digits("-16"));
+        assert_eq!(None, normalize_extra_float_digits("one"));
+    }
+
+    #[test]
+    fn normalize_numeric_fallbacks() {
+        assert_eq!(Some("text".to_string()), normalize_numeric_fallback("TEXT"));
+        assert_eq!(
+            Some("decimal256".to_string()),
+            normalize_numeric_fallback(" Decimal256 ")
+        );
+        assert_eq!(None, normalize_numeric_fallback("float8"));
+    }
+
     #[test]
     fn user_configurable() {
         const SETTABLE: ServerVar<str> = ServerVar {
@@ -93,4 +224,37 @@ mod tests {
         var.set_from_str("custom", VarType::System).unwrap();
         assert_eq!("custom", var.value());
     }
+
+    #[test]
+    fn set_lenient() {
+        let mut vars = SessionVarsInner::default();
+
+        // Unknown variables are stored with a notice.
+        vars.set_lenient("my_app.setting", "value", VarType::UserDefined)
+            .unwrap();
+        assert_eq!(
+            "value",
+            vars.get("MY_APP.setting").unwrap().formatted_value()
+        );
+        assert_eq!(1, vars.take_notices().len());
+        assert!(vars.take_notices().is_empty());
+
+        // Readonly variables can only be set to their current value.
+        vars.set_lenient("server_encoding", "utf8", VarType::UserDefined)
+            .unwrap();
+        vars.set_lenient("server_encoding", "SQL_ASCII", VarType::UserDefined)
+            .unwrap_err();
+
+        // Changes to reported variables are tracked.
+        vars.set_lenient("timezone", "America/New_York", VarType::UserDefined)
+            .unwrap();
+        vars.set_lenient("TimeZone", "Europe/Paris", VarType::UserDefined)
+            .unwrap();
+        vars.set_lenient("statement_timeout", "100", VarType::UserDefined)
+            .unwrap();
+        assert_eq!(
+            vec![("TimeZone".to_string(), "Europe/Paris".to_string())],
+            vars.take_changed_reported_vars()
+        );
+    }
 }
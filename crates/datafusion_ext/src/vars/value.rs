@@ -111,3 +111,22 @@ impl Value for Dialect {
         }
     }
 }
+
+impl Value for RemoteCompression {
+    fn try_parse(s: &str) -> Option<Self::Owned> {
+        match s {
+            "none" => Some(RemoteCompression::None),
+            "zstd" => Some(RemoteCompression::Zstd),
+            "lz4" => Some(RemoteCompression::Lz4),
+            _ => None,
+        }
+    }
+
+    fn format(&self) -> String {
+        match self {
+            RemoteCompression::None => "none".to_string(),
+            RemoteCompression::Zstd => "zstd".to_string(),
+            RemoteCompression::Lz4 => "lz4".to_string(),
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Warnings for queries that succeeded with partial results, e.g. because
+//! corrupt files were skipped while scanning.
+//!
+//! Warnings are collected per session in [`SessionWarnings`], which is
+//! registered as an extension on the session config. Frontends send new
+//! warnings to the client after a query completes, and the most recent
+//! warnings can be queried from a system table.
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionConfig;
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+    Statistics,
+};
+use futures::{stream, StreamExt};
+use parking_lot::Mutex;
+use tracing::warn;
+
+/// Max number of warnings kept for a session. Older warnings are dropped.
+pub const MAX_SESSION_WARNINGS: usize = 100;
+
+/// A warning about a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// When the warning was raised.
+    pub time: SystemTime,
+    /// What the warning is about, e.g. the location of a skipped file.
+    pub source: String,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(source: impl Into<String>, message: impl Into<String>) -> Self {
+        Warning {
+            time: SystemTime::now(),
+            source: source.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.source, self.message)
+    }
+}
+
+/// Warnings raised by queries in a session.
+#[derive(Debug, Default)]
+pub struct SessionWarnings {
+    state: Mutex<WarningsState>,
+}
+
+#[derive(Debug, Default)]
+struct WarningsState {
+    /// Most recent warnings, oldest first.
+    warnings: VecDeque<Warning>,
+    /// Number of warnings at the end of `warnings` that haven't been taken
+    /// with `take_new` yet.
+    new: usize,
+}
+
+impl SessionWarnings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, warning: Warning) {
+        warn!(source = %warning.source, message = %warning.message, "query warning");
+
+        let mut state = self.state.lock();
+        state.warnings.push_back(warning);
+        if state.warnings.len() > MAX_SESSION_WARNINGS {
+            state.warnings.pop_front();
+        }
+        state.new = (state.new + 1).min(state.warnings.len());
+    }
+
+    /// Take the warnings raised since the last call, e.g. to send them to the
+    /// client.
+    pub fn take_new(&self) -> Vec<Warning> {
+        let mut state = self.state.lock();
+        let skip = state.warnings.len() - state.new;
+        state.new = 0;
+        state.warnings.iter().skip(skip).cloned().collect()
+    }
+
+    /// Get the most recent warnings, oldest first.
+    pub fn recent(&self) -> Vec<Warning> {
+        self.state.lock().warnings.iter().cloned().collect()
+    }
+}
+
+/// Push a warning to the session warnings registered on the config.
+///
+/// The warning is only logged if the config has no session warnings, e.g.
+/// when executing on a remote node.
+pub fn push_warning(config: &SessionConfig, warning: Warning) {
+    push_or_log(
+        config.get_extension::<SessionWarnings>().as_deref(),
+        warning,
+    )
+}
+
+fn push_or_log(warnings: Option<&SessionWarnings>, warning: Warning) {
+    match warnings {
+        Some(warnings) => warnings.push(warning),
+        None => warn!(source = %warning.source, message = %warning.message, "query warning"),
+    }
+}
+
+/// Ends the stream of its input at the first error instead of failing the
+/// query, pushing a warning with the error to the session.
+///
+/// Used for scanning a single file when the session skips corrupt files, the
+/// rows read from the file before the error are still returned. Running out of
+/// resources still fails the query.
+#[derive(Debug)]
+pub struct SkipErrorsExec {
+    input: Arc<dyn ExecutionPlan>,
+    /// What's being read by the input, used as the source of warnings.
+    source: String,
+}
+
+impl SkipErrorsExec {
+    pub fn new(input: Arc<dyn ExecutionPlan>, source: impl Into<String>) -> Self {
+        SkipErrorsExec {
+            input,
+            source: source.into(),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl ExecutionPlan for SkipErrorsExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        self.input.output_ordering()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Execution(
+                "SkipErrorsExec requires exactly one child".to_string(),
+            ));
+        }
+        Ok(Arc::new(SkipErrorsExec::new(
+            children[0].clone(),
+            self.source.clone(),
+        )))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let schema = self.schema();
+        let source = self.source.clone();
+        let warnings = context.session_config().get_extension::<SessionWarnings>();
+
+        let skip = move |e: DataFusionError| -> Option<Result<RecordBatch>> {
+            match e {
+                e @ DataFusionError::ResourcesExhausted(_) => Some(Err(e)),
+                e => {
+                    let warning = Warning::new(&source, format!("skipped file: {e}"));
+                    push_or_log(warnings.as_deref(), warning);
+                    None
+                }
+            }
+        };
+
+        let input = match self.input.execute(partition, context) {
+            Ok(input) => input,
+            Err(e) => {
+                let stream = stream::iter(skip(e));
+                return Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)));
+            }
+        };
+
+        // Yield batches until the first error, then stop.
+        let stream = input
+            .scan((false, skip), |(failed, skip), batch| {
+                let item = match batch {
+                    _ if *failed => None,
+                    Ok(batch) => Some(Some(Ok(batch))),
+                    Err(e) => {
+                        *failed = true;
+                        Some(skip(e))
+                    }
+                };
+                futures::future::ready(item)
+            })
+            .filter_map(futures::future::ready);
+        Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)))
+    }
+
+    fn statistics(&self) -> Statistics {
+        // Rows may be skipped, so the statistics of the input are never exact.
+        Statistics {
+            is_exact: false,
+            ..self.input.statistics()
+        }
+    }
+}
+
+impl DisplayAs for SkipErrorsExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SkipErrorsExec: source={}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_new_warnings() {
+        let warnings = SessionWarnings::new();
+        warnings.push(Warning::new("a.parquet", "skipped file"));
+        warnings.push(Warning::new("b.parquet", "skipped file"));
+
+        let new = warnings.take_new();
+        assert_eq!(2, new.len());
+        assert_eq!("a.parquet", new[0].source);
+        assert!(warnings.take_new().is_empty());
+
+        warnings.push(Warning::new("c.parquet", "skipped file"));
+        let new = warnings.take_new();
+        assert_eq!(1, new.len());
+        assert_eq!("c.parquet", new[0].source);
+        assert_eq!(3, warnings.recent().len());
+    }
+
+    #[test]
+    fn keeps_most_recent() {
+        let warnings = SessionWarnings::new();
+        for i in 0..MAX_SESSION_WARNINGS + 10 {
+            warnings.push(Warning::new(i.to_string(), "skipped file"));
+        }
+
+        let recent = warnings.recent();
+        assert_eq!(MAX_SESSION_WARNINGS, recent.len());
+        assert_eq!("10", recent[0].source);
+        assert_eq!(MAX_SESSION_WARNINGS, warnings.take_new().len());
+    }
+}
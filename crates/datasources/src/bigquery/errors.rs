@@ -15,6 +15,30 @@ pub enum BigQueryError {
     #[error("Failed to use provided service account key: {0}")]
     AuthKey(#[from] std::io::Error),
 
+    #[error("Failed to get access token: {0}")]
+    AccessToken(#[from] bigquery_storage::yup_oauth2::Error),
+
+    #[error("Unsupported type for writing to BigQuery: {0}")]
+    UnsupportedArrowType(datafusion::arrow::datatypes::DataType),
+
+    #[error("Failed to write to BigQuery: {0}")]
+    WriteStream(String),
+
+    #[error(transparent)]
+    Transport(#[from] tonic::transport::Error),
+
+    #[error(transparent)]
+    Status(#[from] tonic::Status),
+
+    #[error(transparent)]
+    InvalidMetadataValue(#[from] tonic::metadata::errors::InvalidMetadataValue),
+
+    #[error(transparent)]
+    Arrow(#[from] datafusion::arrow::error::ArrowError),
+
+    #[error(transparent)]
+    Datafusion(#[from] datafusion::error::DataFusionError),
+
     #[error("Unknown or no read permissions for project_id {0}")]
     ProjectReadPerm(String),
 
@@ -1,5 +1,6 @@
 //! BigQuery external table implementation.
 pub mod errors;
+mod write;
 
 use crate::common::util;
 use async_channel::Receiver;
@@ -17,10 +18,12 @@ use datafusion::execution::context::TaskContext;
 use datafusion::logical_expr::Expr;
 use datafusion::logical_expr::{TableProviderFilterPushDown, TableType};
 use datafusion::physical_expr::PhysicalSortExpr;
-use datafusion::physical_plan::{DisplayAs, DisplayFormatType};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
 use datafusion::physical_plan::{
-    ExecutionPlan, Partitioning, RecordBatchStream, SendableRecordBatchStream, Statistics,
+    execute_stream, ExecutionPlan, Partitioning, RecordBatchStream, SendableRecordBatchStream,
+    Statistics,
 };
+use datafusion::physical_plan::{DisplayAs, DisplayFormatType};
 use datafusion::{
     arrow::datatypes::{
         DataType, Field, Schema as ArrowSchema, SchemaRef as ArrowSchemaRef, TimeUnit,
@@ -78,7 +81,7 @@ impl BigQueryAccessor {
     /// Connect to the bigquery instance.
     ///
     /// The service account should have 'BigQuery Data Viewer' and 'BigQuery Job
-    /// User' permissions.
+    /// User' permissions, and 'BigQuery Data Editor' for inserts.
     pub async fn connect(
         gcp_service_account_key_json: String,
         gcp_project_id: String,
@@ -368,6 +371,21 @@ impl TableProvider for BigQueryTableProvider {
             metrics: ExecutionPlanMetricsSet::new(),
         }))
     }
+
+    async fn insert_into(
+        &self,
+        _state: &SessionState,
+        input: Arc<dyn ExecutionPlan>,
+        _overwrite: bool,
+    ) -> DatafusionResult<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(BigQueryInsertExec {
+            input,
+            access: self.access.clone(),
+            gcp_service_account_key_json: self.gcp_service_account_key_json.clone(),
+            gcp_project_id: self.gcp_project_id.clone(),
+            metrics: ExecutionPlanMetricsSet::new(),
+        }))
+    }
 }
 
 struct BigQueryExec {
@@ -452,6 +470,112 @@ impl fmt::Debug for BigQueryExec {
     }
 }
 
+/// Streams rows into a table with the Storage Write API, producing the number
+/// of rows written.
+struct BigQueryInsertExec {
+    input: Arc<dyn ExecutionPlan>,
+    access: BigQueryTableAccess,
+    gcp_service_account_key_json: String,
+    gcp_project_id: String,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl ExecutionPlan for BigQueryInsertExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> ArrowSchemaRef {
+        util::COUNT_SCHEMA.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DatafusionResult<Arc<dyn ExecutionPlan>> {
+        match children.as_slice() {
+            [input] => Ok(Arc::new(BigQueryInsertExec {
+                input: input.clone(),
+                access: self.access.clone(),
+                gcp_service_account_key_json: self.gcp_service_account_key_json.clone(),
+                gcp_project_id: self.gcp_project_id.clone(),
+                metrics: ExecutionPlanMetricsSet::new(),
+            })),
+            _ => Err(DataFusionError::Execution(
+                "BigQueryInsertExec expects exactly one child".to_string(),
+            )),
+        }
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DatafusionResult<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Execution(
+                "only single partition supported".to_string(),
+            ));
+        }
+
+        let input = execute_stream(self.input.clone(), context)?;
+        let access = self.access.clone();
+        let key = self.gcp_service_account_key_json.clone();
+        let project_id = self.gcp_project_id.clone();
+
+        let stream = futures::stream::once(async move {
+            write::write_table(&key, &project_id, &access, input)
+                .await
+                .map(util::create_count_record_batch)
+                .map_err(|e| DataFusionError::External(Box::new(e)))
+        });
+
+        Ok(Box::pin(DataSourceMetricsStreamAdapter::new(
+            RecordBatchStreamAdapter::new(util::COUNT_SCHEMA.clone(), stream),
+            partition,
+            &self.metrics,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+}
+
+impl DisplayAs for BigQueryInsertExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "BigQueryInsertExec: table={}.{}",
+            self.access.dataset_id, self.access.table_id
+        )
+    }
+}
+
+impl fmt::Debug for BigQueryInsertExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BigQueryInsertExec")
+            .field("access", &self.access)
+            .finish_non_exhaustive()
+    }
+}
+
 struct BufferedIpcStream {
     schema: ArrowSchemaRef,
     inner: Pin<Box<dyn Stream<Item = DatafusionResult<RecordBatch>> + Send>>,
@@ -0,0 +1,694 @@
+//! Writing to BigQuery tables with the Storage Write API.
+//!
+//! Rows are appended to a pending write stream and committed once all rows
+//! have been appended, so either all or none of the rows of an insert are
+//! visible in the table.
+//!
+//! The API only accepts rows serialized as protobuf messages. Each row is
+//! encoded as a message with a field per column, described by a descriptor
+//! built from the arrow schema of the rows.
+//!
+//! API: <https://cloud.google.com/bigquery/docs/reference/storage/rpc/google.cloud.bigquery.storage.v1#bigquerywrite>
+use bigquery_storage::yup_oauth2::ServiceAccountAuthenticator;
+use datafusion::arrow::array::{Array, ArrayRef, AsArray};
+use datafusion::arrow::compute::{cast_with_options, CastOptions};
+use datafusion::arrow::datatypes::{
+    DataType, Float64Type, Int32Type, Int64Type, Schema as ArrowSchema, TimeUnit,
+};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use futures::channel::mpsc;
+use futures::{future, SinkExt, Stream, StreamExt};
+use prost::encoding::{self, WireType};
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::{DescriptorProto, FieldDescriptorProto};
+use tonic::client::Grpc;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::transport::{Channel, ClientTlsConfig};
+use tonic::Request;
+
+use super::errors::{BigQueryError, Result};
+use super::BigQueryTableAccess;
+
+const WRITE_ENDPOINT: &str = "https://bigquerystorage.googleapis.com";
+const WRITE_DOMAIN: &str = "bigquerystorage.googleapis.com";
+const WRITE_SCOPE: &str = "https://www.googleapis.com/auth/bigquery";
+
+const CREATE_WRITE_STREAM: &str =
+    "/google.cloud.bigquery.storage.v1.BigQueryWrite/CreateWriteStream";
+const APPEND_ROWS: &str = "/google.cloud.bigquery.storage.v1.BigQueryWrite/AppendRows";
+const FINALIZE_WRITE_STREAM: &str =
+    "/google.cloud.bigquery.storage.v1.BigQueryWrite/FinalizeWriteStream";
+const BATCH_COMMIT_WRITE_STREAMS: &str =
+    "/google.cloud.bigquery.storage.v1.BigQueryWrite/BatchCommitWriteStreams";
+
+/// `WriteStream.Type.PENDING`, rows aren't visible until the stream is
+/// committed.
+const PENDING_STREAM: i32 = 2;
+
+/// Serialized size of the rows sent in a single append request. Requests are
+/// limited to 10MB.
+const MAX_APPEND_BYTES: usize = 8 * 1024 * 1024;
+
+/// Number of encoded requests buffered while waiting for earlier appends.
+const APPEND_BUFFER: usize = 4;
+
+/// Write all rows of the stream to a table, returning the number of rows
+/// written.
+///
+/// The service account needs the 'BigQuery Data Editor' role on the table.
+pub async fn write_table(
+    gcp_service_account_key_json: &str,
+    gcp_project_id: &str,
+    access: &BigQueryTableAccess,
+    mut input: SendableRecordBatchStream,
+) -> Result<u64> {
+    let table = format!(
+        "projects/{}/datasets/{}/tables/{}",
+        gcp_project_id, access.dataset_id, access.table_id
+    );
+    let schema = input.schema();
+    let encodings = schema
+        .fields()
+        .iter()
+        .map(|field| ColumnEncoding::try_from_arrow(field.data_type()))
+        .collect::<Result<Vec<_>>>()?;
+    let writer_schema = ProtoSchema {
+        proto_descriptor: Some(row_descriptor(&schema, &encodings)),
+    };
+
+    let mut client = WriteClient::connect(gcp_service_account_key_json).await?;
+
+    // Rows are encoded while earlier requests are appended.
+    let (mut tx, mut rx) = mpsc::channel(APPEND_BUFFER);
+    let encode = async move {
+        let mut writer_schema = Some(writer_schema);
+        let mut rows = Vec::new();
+        let mut size = 0;
+        while let Some(batch) = input.next().await {
+            for row in encode_rows(&batch?, &encodings)? {
+                if size + row.len() > MAX_APPEND_BYTES && !rows.is_empty() {
+                    let data = ProtoData {
+                        writer_schema: writer_schema.take(),
+                        rows: Some(ProtoRows {
+                            serialized_rows: std::mem::take(&mut rows),
+                        }),
+                    };
+                    tx.send(data).await.map_err(|_| append_closed())?;
+                    size = 0;
+                }
+                size += row.len();
+                rows.push(row);
+            }
+        }
+        if !rows.is_empty() {
+            let data = ProtoData {
+                writer_schema,
+                rows: Some(ProtoRows {
+                    serialized_rows: rows,
+                }),
+            };
+            tx.send(data).await.map_err(|_| append_closed())?;
+        }
+        Ok::<_, BigQueryError>(())
+    };
+
+    let append = async {
+        // Nothing to write, avoid creating a stream.
+        let Some(first) = rx.next().await else {
+            return Ok(0);
+        };
+
+        let write_stream = client.create_write_stream(&table).await?;
+        let data = futures::stream::once(future::ready(first)).chain(rx);
+        client.append_rows(&write_stream, data).await?;
+        let row_count = client.finalize_write_stream(&write_stream).await?;
+        client
+            .batch_commit_write_streams(&table, &write_stream)
+            .await?;
+
+        Ok::<_, BigQueryError>(row_count as u64)
+    };
+
+    let ((), row_count) = futures::try_join!(encode, append)?;
+    Ok(row_count)
+}
+
+fn append_closed() -> BigQueryError {
+    BigQueryError::WriteStream("append stream closed unexpectedly".to_string())
+}
+
+/// Client for the BigQuery Write service.
+struct WriteClient {
+    grpc: Grpc<Channel>,
+    authorization: MetadataValue<Ascii>,
+}
+
+impl WriteClient {
+    async fn connect(gcp_service_account_key_json: &str) -> Result<Self> {
+        let key = serde_json::from_str(gcp_service_account_key_json)?;
+        let auth = ServiceAccountAuthenticator::builder(key)
+            .build()
+            .await
+            .map_err(BigQueryError::AuthKey)?;
+        let token = auth.token(&[WRITE_SCOPE]).await?;
+        let authorization = format!("Bearer {}", token.as_str()).parse()?;
+
+        let channel = Channel::from_static(WRITE_ENDPOINT)
+            .tls_config(ClientTlsConfig::new().domain_name(WRITE_DOMAIN))?
+            .connect()
+            .await?;
+
+        Ok(WriteClient {
+            grpc: Grpc::new(channel),
+            authorization,
+        })
+    }
+
+    /// Create a request with the auth and routing headers.
+    ///
+    /// Routing params are sent as `<field>=<value>`, naming the resource the
+    /// request is for.
+    fn request<T>(&self, message: T, routing_params: String) -> Result<Request<T>> {
+        let mut req = Request::new(message);
+        let metadata = req.metadata_mut();
+        metadata.insert("authorization", self.authorization.clone());
+        metadata.insert("x-goog-request-params", routing_params.parse()?);
+        Ok(req)
+    }
+
+    async fn unary<Req, Resp>(&mut self, path: &'static str, req: Request<Req>) -> Result<Resp>
+    where
+        Req: prost::Message + Send + Sync + 'static,
+        Resp: prost::Message + Default + Send + Sync + 'static,
+    {
+        self.grpc.ready().await?;
+        let resp = self
+            .grpc
+            .unary(req, PathAndQuery::from_static(path), ProstCodec::default())
+            .await?;
+        Ok(resp.into_inner())
+    }
+
+    /// Create a pending write stream for a table, returning its name.
+    async fn create_write_stream(&mut self, table: &str) -> Result<String> {
+        let req = self.request(
+            CreateWriteStreamRequest {
+                parent: table.to_string(),
+                write_stream: Some(WriteStream {
+                    name: String::new(),
+                    r#type: PENDING_STREAM,
+                }),
+            },
+            format!("parent={table}"),
+        )?;
+        let stream: WriteStream = self.unary(CREATE_WRITE_STREAM, req).await?;
+        Ok(stream.name)
+    }
+
+    /// Append rows to a write stream, failing on the first append that
+    /// failed.
+    async fn append_rows(
+        &mut self,
+        write_stream: &str,
+        data: impl Stream<Item = ProtoData> + Send + 'static,
+    ) -> Result<()> {
+        let name = write_stream.to_string();
+        let requests = data.map(move |data| AppendRowsRequest {
+            write_stream: name.clone(),
+            proto_rows: Some(data),
+        });
+        let req = self.request(requests, format!("write_stream={write_stream}"))?;
+
+        self.grpc.ready().await?;
+        let mut responses = self
+            .grpc
+            .streaming(
+                req,
+                PathAndQuery::from_static(APPEND_ROWS),
+                ProstCodec::<AppendRowsRequest, AppendRowsResponse>::default(),
+            )
+            .await?
+            .into_inner();
+
+        while let Some(resp) = responses.message().await? {
+            if let Some(row_error) = resp.row_errors.first() {
+                return Err(BigQueryError::WriteStream(format!(
+                    "row {}: {}",
+                    row_error.index, row_error.message
+                )));
+            }
+            if let Some(error) = resp.error {
+                return Err(BigQueryError::WriteStream(error.message));
+            }
+        }
+        Ok(())
+    }
+
+    /// Finalize a write stream so no more rows can be appended, returning the
+    /// number of rows in the stream.
+    async fn finalize_write_stream(&mut self, write_stream: &str) -> Result<i64> {
+        let req = self.request(
+            FinalizeWriteStreamRequest {
+                name: write_stream.to_string(),
+            },
+            format!("name={write_stream}"),
+        )?;
+        let resp: FinalizeWriteStreamResponse = self.unary(FINALIZE_WRITE_STREAM, req).await?;
+        Ok(resp.row_count)
+    }
+
+    /// Commit the rows of a finalized write stream to the table.
+    async fn batch_commit_write_streams(&mut self, table: &str, write_stream: &str) -> Result<()> {
+        let req = self.request(
+            BatchCommitWriteStreamsRequest {
+                parent: table.to_string(),
+                write_streams: vec![write_stream.to_string()],
+            },
+            format!("parent={table}"),
+        )?;
+        let resp: BatchCommitWriteStreamsResponse =
+            self.unary(BATCH_COMMIT_WRITE_STREAMS, req).await?;
+        match resp.stream_errors.first() {
+            Some(error) => Err(BigQueryError::WriteStream(format!(
+                "failed to commit {}: {}",
+                error.entity, error.error_message
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How values of a column are encoded in row messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnEncoding {
+    Bool,
+    Int32,
+    Int64,
+    Double,
+    String,
+    Bytes,
+}
+
+impl ColumnEncoding {
+    /// Get the encoding for values of an arrow type, following the types the
+    /// API accepts for each BigQuery type.
+    ///
+    /// See <https://cloud.google.com/bigquery/docs/write-api#data_type_conversions>
+    fn try_from_arrow(data_type: &DataType) -> Result<Self> {
+        Ok(match data_type {
+            DataType::Boolean => ColumnEncoding::Bool,
+            DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64 => ColumnEncoding::Int64,
+            DataType::Float16 | DataType::Float32 | DataType::Float64 => ColumnEncoding::Double,
+            // Days since the epoch.
+            DataType::Date32 | DataType::Date64 => ColumnEncoding::Int32,
+            // Microseconds since the epoch.
+            DataType::Timestamp(_, Some(_)) => ColumnEncoding::Int64,
+            // DATETIME, TIME, NUMERIC and BIGNUMERIC values are written in
+            // their canonical string format.
+            DataType::Timestamp(_, None)
+            | DataType::Time32(_)
+            | DataType::Time64(_)
+            | DataType::Decimal128(_, _)
+            | DataType::Decimal256(_, _)
+            | DataType::Utf8
+            | DataType::LargeUtf8 => ColumnEncoding::String,
+            DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => {
+                ColumnEncoding::Bytes
+            }
+            other => return Err(BigQueryError::UnsupportedArrowType(other.clone())),
+        })
+    }
+
+    fn proto_type(&self) -> Type {
+        match self {
+            ColumnEncoding::Bool => Type::Bool,
+            ColumnEncoding::Int32 => Type::Int32,
+            ColumnEncoding::Int64 => Type::Int64,
+            ColumnEncoding::Double => Type::Double,
+            ColumnEncoding::String => Type::String,
+            ColumnEncoding::Bytes => Type::Bytes,
+        }
+    }
+
+    /// Arrow type columns are cast to before encoding.
+    fn arrow_type(&self) -> DataType {
+        match self {
+            ColumnEncoding::Bool => DataType::Boolean,
+            ColumnEncoding::Int32 => DataType::Int32,
+            ColumnEncoding::Int64 => DataType::Int64,
+            ColumnEncoding::Double => DataType::Float64,
+            ColumnEncoding::String => DataType::Utf8,
+            ColumnEncoding::Bytes => DataType::Binary,
+        }
+    }
+
+    /// Cast a column to the arrow type of the encoding.
+    fn cast(&self, array: &ArrayRef) -> Result<ArrayRef> {
+        // Error instead of writing nulls for values that don't fit.
+        let opts = CastOptions {
+            safe: false,
+            ..Default::default()
+        };
+
+        // BigQuery stores times with microsecond precision.
+        let array = match array.data_type() {
+            DataType::Timestamp(_, tz) => cast_with_options(
+                array,
+                &DataType::Timestamp(TimeUnit::Microsecond, tz.clone()),
+                &opts,
+            )?,
+            DataType::Time32(_) | DataType::Time64(_) => {
+                cast_with_options(array, &DataType::Time64(TimeUnit::Microsecond), &opts)?
+            }
+            DataType::Date64 => cast_with_options(array, &DataType::Date32, &opts)?,
+            _ => array.clone(),
+        };
+
+        Ok(cast_with_options(&array, &self.arrow_type(), &opts)?)
+    }
+}
+
+/// Build the descriptor of row messages, with a field per column numbered in
+/// the order of the columns.
+fn row_descriptor(schema: &ArrowSchema, encodings: &[ColumnEncoding]) -> DescriptorProto {
+    let fields = schema
+        .fields()
+        .iter()
+        .zip(encodings)
+        .enumerate()
+        .map(|(idx, (field, encoding))| FieldDescriptorProto {
+            name: Some(field.name().clone()),
+            number: Some(idx as i32 + 1),
+            label: Some(Label::Optional as i32),
+            r#type: Some(encoding.proto_type() as i32),
+            ..Default::default()
+        })
+        .collect();
+
+    DescriptorProto {
+        name: Some("Row".to_string()),
+        field: fields,
+        ..Default::default()
+    }
+}
+
+/// Encode the rows of a batch as row messages. Null values are omitted.
+fn encode_rows(batch: &RecordBatch, encodings: &[ColumnEncoding]) -> Result<Vec<Vec<u8>>> {
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(encodings)
+        .map(|(array, col_encoding)| Ok((*col_encoding, col_encoding.cast(array)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut rows = Vec::with_capacity(batch.num_rows());
+    for row in 0..batch.num_rows() {
+        let mut buf = Vec::new();
+        for (idx, (col_encoding, array)) in columns.iter().enumerate() {
+            if array.is_null(row) {
+                continue;
+            }
+            let tag = idx as u32 + 1;
+            match col_encoding {
+                ColumnEncoding::Bool => {
+                    encoding::bool::encode(tag, &array.as_boolean().value(row), &mut buf)
+                }
+                ColumnEncoding::Int32 => encoding::int32::encode(
+                    tag,
+                    &array.as_primitive::<Int32Type>().value(row),
+                    &mut buf,
+                ),
+                ColumnEncoding::Int64 => encoding::int64::encode(
+                    tag,
+                    &array.as_primitive::<Int64Type>().value(row),
+                    &mut buf,
+                ),
+                ColumnEncoding::Double => encoding::double::encode(
+                    tag,
+                    &array.as_primitive::<Float64Type>().value(row),
+                    &mut buf,
+                ),
+                ColumnEncoding::String => encode_bytes(
+                    tag,
+                    array.as_string::<i32>().value(row).as_bytes(),
+                    &mut buf,
+                ),
+                ColumnEncoding::Bytes => {
+                    encode_bytes(tag, array.as_binary::<i32>().value(row), &mut buf)
+                }
+            }
+        }
+        rows.push(buf);
+    }
+    Ok(rows)
+}
+
+/// Encode a length delimited field, used for both strings and bytes.
+fn encode_bytes(tag: u32, value: &[u8], buf: &mut Vec<u8>) {
+    encoding::encode_key(tag, WireType::LengthDelimited, buf);
+    encoding::encode_varint(value.len() as u64, buf);
+    buf.extend_from_slice(value);
+}
+
+// Messages of the Write service, only containing the fields used. Oneofs
+// with fields that aren't used are declared as optional fields, which are
+// encoded the same.
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct CreateWriteStreamRequest {
+    #[prost(string, tag = "1")]
+    parent: String,
+    #[prost(message, optional, tag = "2")]
+    write_stream: Option<WriteStream>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct WriteStream {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(int32, tag = "2")]
+    r#type: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct AppendRowsRequest {
+    #[prost(string, tag = "1")]
+    write_stream: String,
+    #[prost(message, optional, tag = "4")]
+    proto_rows: Option<ProtoData>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ProtoData {
+    /// Only needs to be set for the first request of a connection.
+    #[prost(message, optional, tag = "1")]
+    writer_schema: Option<ProtoSchema>,
+    #[prost(message, optional, tag = "2")]
+    rows: Option<ProtoRows>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ProtoSchema {
+    #[prost(message, optional, tag = "1")]
+    proto_descriptor: Option<DescriptorProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ProtoRows {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    serialized_rows: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct AppendRowsResponse {
+    #[prost(message, optional, tag = "2")]
+    error: Option<RpcStatus>,
+    #[prost(message, repeated, tag = "4")]
+    row_errors: Vec<RowError>,
+}
+
+/// `google.rpc.Status`
+#[derive(Clone, PartialEq, prost::Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RowError {
+    #[prost(int64, tag = "1")]
+    index: i64,
+    #[prost(string, tag = "3")]
+    message: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FinalizeWriteStreamRequest {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FinalizeWriteStreamResponse {
+    #[prost(int64, tag = "1")]
+    row_count: i64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct BatchCommitWriteStreamsRequest {
+    #[prost(string, tag = "1")]
+    parent: String,
+    #[prost(string, repeated, tag = "2")]
+    write_streams: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct BatchCommitWriteStreamsResponse {
+    #[prost(message, repeated, tag = "2")]
+    stream_errors: Vec<StorageError>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct StorageError {
+    #[prost(string, tag = "2")]
+    entity: String,
+    #[prost(string, tag = "3")]
+    error_message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::{
+        BooleanArray, Date32Array, Int32Array, StringArray, TimestampNanosecondArray,
+    };
+    use datafusion::arrow::datatypes::Field;
+    use prost::Message;
+
+    use super::*;
+
+    /// Message matching the descriptor of the test schema.
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct TestRow {
+        #[prost(int64, optional, tag = "1")]
+        id: Option<i64>,
+        #[prost(string, optional, tag = "2")]
+        name: Option<String>,
+        #[prost(bool, optional, tag = "3")]
+        active: Option<bool>,
+        #[prost(int32, optional, tag = "4")]
+        day: Option<i32>,
+        #[prost(int64, optional, tag = "5")]
+        ts: Option<i64>,
+        #[prost(string, optional, tag = "6")]
+        datetime: Option<String>,
+    }
+
+    #[test]
+    fn encode_batch_rows() {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("active", DataType::Boolean, true),
+            Field::new("day", DataType::Date32, true),
+            Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into())),
+                true,
+            ),
+            Field::new(
+                "datetime",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                true,
+            ),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), Some(-2)])),
+                Arc::new(StringArray::from(vec![Some("a"), None])),
+                Arc::new(BooleanArray::from(vec![Some(true), Some(false)])),
+                Arc::new(Date32Array::from(vec![Some(19000), None])),
+                Arc::new(
+                    TimestampNanosecondArray::from(vec![Some(1_500_000), None])
+                        .with_timezone("UTC"),
+                ),
+                Arc::new(TimestampNanosecondArray::from(vec![
+                    Some(1_000_000_000),
+                    None,
+                ])),
+            ],
+        )
+        .unwrap();
+
+        let encodings = schema
+            .fields()
+            .iter()
+            .map(|f| ColumnEncoding::try_from_arrow(f.data_type()).unwrap())
+            .collect::<Vec<_>>();
+
+        let descriptor = row_descriptor(&schema, &encodings);
+        let types = descriptor
+            .field
+            .iter()
+            .map(|f| (f.name(), f.number(), f.r#type()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("id", 1, Type::Int64),
+                ("name", 2, Type::String),
+                ("active", 3, Type::Bool),
+                ("day", 4, Type::Int32),
+                ("ts", 5, Type::Int64),
+                ("datetime", 6, Type::String),
+            ],
+            types
+        );
+
+        let rows = encode_rows(&batch, &encodings)
+            .unwrap()
+            .into_iter()
+            .map(|row| TestRow::decode(row.as_slice()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                TestRow {
+                    id: Some(1),
+                    name: Some("a".to_string()),
+                    active: Some(true),
+                    day: Some(19000),
+                    ts: Some(1_500),
+                    datetime: Some("1970-01-01T00:00:01".to_string()),
+                },
+                TestRow {
+                    id: Some(-2),
+                    name: None,
+                    active: Some(false),
+                    day: None,
+                    ts: None,
+                    datetime: None,
+                },
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn unsupported_type() {
+        let data_type = DataType::new_list(DataType::Int64, true);
+        assert!(ColumnEncoding::try_from_arrow(&data_type).is_err());
+    }
+}
@@ -6,13 +6,14 @@ use bitvec::{order::Lsb0, vec::BitVec};
 use bson::{RawBsonRef, RawDocument};
 use datafusion::arrow::array::{
     Array, ArrayBuilder, ArrayRef, BinaryBuilder, BooleanBuilder, Date32Builder, Date64Builder,
-    Decimal128Builder, Float64Builder, Int32Builder, Int64Builder, LargeBinaryBuilder,
-    LargeStringBuilder, StringBuilder, StructArray, TimestampMicrosecondBuilder,
-    TimestampMillisecondBuilder, TimestampSecondBuilder,
+    Decimal128Builder, FixedSizeBinaryBuilder, Float64Builder, Int32Builder, Int64Builder,
+    LargeBinaryBuilder, LargeStringBuilder, StringBuilder, StructArray,
+    TimestampMicrosecondBuilder, TimestampMillisecondBuilder, TimestampSecondBuilder,
 };
 use datafusion::arrow::datatypes::{DataType, Field, Fields, TimeUnit};
 
 use crate::bson::errors::{BsonError, Result};
+use crate::bson::schema::is_uuid_binary;
 
 /// Similar to arrow's `StructBuilder`, but specific for "shredding" bson
 /// records.
@@ -82,7 +83,6 @@ impl RecordStructBuilder {
                         .field_index
                         .get(key)
                         .ok_or_else(|| BsonError::ColumnNotInInferredSchema(key.to_string()))?;
-                    println!("{}->{}", key, idx);
 
                     if *cols_set.get(idx).unwrap() {
                         continue;
@@ -274,6 +274,15 @@ fn append_value(val: RawBsonRef, typ: &DataType, col: &mut dyn ArrayBuilder) ->
 
         // Binary
         (RawBsonRef::Binary(v), DataType::Binary) => append_scalar!(BinaryBuilder, col, v.bytes),
+        (RawBsonRef::Binary(v), DataType::FixedSizeBinary(16))
+            if is_uuid_binary(v.subtype, v.bytes) =>
+        {
+            append_scalar!(FixedSizeBinaryBuilder, col, v.bytes)?
+        }
+        (RawBsonRef::Binary(v), DataType::Utf8) if is_uuid_binary(v.subtype, v.bytes) => {
+            let uuid = uuid::Uuid::from_slice(v.bytes).expect("binary should be a valid uuid");
+            append_scalar!(StringBuilder, col, uuid.hyphenated().to_string())
+        }
         (RawBsonRef::Binary(v), DataType::LargeBinary) => {
             append_scalar!(LargeBinaryBuilder, col, v.bytes)
         }
@@ -291,10 +300,14 @@ fn append_value(val: RawBsonRef, typ: &DataType, col: &mut dyn ArrayBuilder) ->
             append_scalar!(TimestampSecondBuilder, col, v.time as i64)
         }
         (RawBsonRef::Timestamp(v), DataType::Timestamp(TimeUnit::Millisecond, _)) => {
-            append_scalar!(TimestampSecondBuilder, col, v.time as i64 * 1000)
+            append_scalar!(TimestampMillisecondBuilder, col, v.time as i64 * 1000)
         }
         (RawBsonRef::Timestamp(v), DataType::Timestamp(TimeUnit::Microsecond, _)) => {
-            append_scalar!(TimestampSecondBuilder, col, v.time as i64 * 1000 * 1000)
+            append_scalar!(
+                TimestampMicrosecondBuilder,
+                col,
+                v.time as i64 * 1000 * 1000
+            )
         }
         (RawBsonRef::Timestamp(v), DataType::Date64) => {
             append_scalar!(Date64Builder, col, v.time as i64 * 1000)
@@ -313,6 +326,9 @@ fn append_value(val: RawBsonRef, typ: &DataType, col: &mut dyn ArrayBuilder) ->
         }
 
         // Datetime (actual timestamps that you'd actually use. in an application )
+        (RawBsonRef::DateTime(v), DataType::Timestamp(TimeUnit::Second, _)) => {
+            append_scalar!(TimestampSecondBuilder, col, v.timestamp_millis() / 1000)
+        }
         (RawBsonRef::DateTime(v), DataType::Timestamp(TimeUnit::Millisecond, _)) => {
             append_scalar!(TimestampMillisecondBuilder, col, v.timestamp_millis())
         }
@@ -356,6 +372,18 @@ fn append_value(val: RawBsonRef, typ: &DataType, col: &mut dyn ArrayBuilder) ->
             .unwrap()
             .append_value(i128::from_le_bytes(v.bytes())),
 
+        // Anything else read as a string, e.g. values in columns with mixed
+        // types, as extended JSON.
+        (v, DataType::Utf8) => {
+            let value = bson::Bson::try_from(v.to_raw_bson())
+                .map_err(|_| BsonError::FailedToReadRawBsonDocument)?;
+            append_scalar!(
+                StringBuilder,
+                col,
+                serde_json::Value::from(value).to_string()
+            )
+        }
+
         (bson_ref, dt) => {
             return Err(BsonError::UnhandledElementType(
                 bson_ref.element_type(),
@@ -392,9 +420,19 @@ fn append_null(typ: &DataType, col: &mut dyn ArrayBuilder) -> Result<()> {
             .downcast_mut::<Float64Builder>()
             .unwrap()
             .append_null(),
-        &DataType::Timestamp(_, _) => col
+        &DataType::Timestamp(TimeUnit::Second, _) => col
+            .as_any_mut()
+            .downcast_mut::<TimestampSecondBuilder>()
+            .unwrap()
+            .append_null(),
+        &DataType::Timestamp(TimeUnit::Millisecond, _) => col
+            .as_any_mut()
+            .downcast_mut::<TimestampMillisecondBuilder>()
+            .unwrap()
+            .append_null(),
+        &DataType::Timestamp(TimeUnit::Microsecond, _) => col
             .as_any_mut()
-            .downcast_mut::<TimestampMillisecondBuilder>() // TODO: Possibly change to nanosecond.
+            .downcast_mut::<TimestampMicrosecondBuilder>()
             .unwrap()
             .append_null(),
         &DataType::Utf8 => col
@@ -407,6 +445,11 @@ fn append_null(typ: &DataType, col: &mut dyn ArrayBuilder) -> Result<()> {
             .downcast_mut::<BinaryBuilder>()
             .unwrap()
             .append_null(),
+        &DataType::FixedSizeBinary(16) => col
+            .as_any_mut()
+            .downcast_mut::<FixedSizeBinaryBuilder>()
+            .unwrap()
+            .append_null(),
         &DataType::Struct(_) => col
             .as_any_mut()
             .downcast_mut::<RecordStructBuilder>()
@@ -434,11 +477,20 @@ fn column_builders_for_fields(
             DataType::Int32 => Box::new(Int32Builder::with_capacity(capacity)),
             DataType::Int64 => Box::new(Int64Builder::with_capacity(capacity)),
             DataType::Float64 => Box::new(Float64Builder::with_capacity(capacity)),
-            DataType::Timestamp(_, _) => {
-                Box::new(TimestampMicrosecondBuilder::with_capacity(capacity)) // TODO: Possibly change to nanosecond.
+            DataType::Timestamp(TimeUnit::Second, _) => {
+                Box::new(TimestampSecondBuilder::with_capacity(capacity))
+            }
+            DataType::Timestamp(TimeUnit::Millisecond, _) => {
+                Box::new(TimestampMillisecondBuilder::with_capacity(capacity))
+            }
+            DataType::Timestamp(TimeUnit::Microsecond, _) => {
+                Box::new(TimestampMicrosecondBuilder::with_capacity(capacity))
             }
             DataType::Utf8 => Box::new(StringBuilder::with_capacity(capacity, 10)), // TODO: Can collect avg when inferring schema.
             DataType::Binary => Box::new(BinaryBuilder::with_capacity(capacity, 10)), // TODO: Can collect avg when inferring schema.
+            DataType::FixedSizeBinary(16) => {
+                Box::new(FixedSizeBinaryBuilder::with_capacity(capacity, 16))
+            }
             DataType::Decimal128(_, _) => Box::new(Decimal128Builder::with_capacity(capacity)), // TODO: Can collect avg when inferring schema.
             DataType::Struct(fields) => {
                 let nested = column_builders_for_fields(fields.clone(), capacity)?;
@@ -459,6 +511,8 @@ fn column_builders_for_fields(
 #[cfg(test)]
 mod test {
     use bson::oid::ObjectId;
+    use datafusion::arrow::array::AsArray;
+    use datafusion::arrow::datatypes::Int64Type;
 
     use super::*;
 
@@ -498,6 +552,64 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_dump_value_types() {
+        let docs = [
+            bson::doc! {
+                "_id": ObjectId::new(),
+                "count": 1_i32,
+                "created": bson::DateTime::from_millis(1_700_000_000_000),
+                "tags": ["a", "b"],
+                "meta": {},
+                "ts": bson::Timestamp { time: 1_700_000_000, increment: 1 },
+            },
+            bson::doc! {
+                "_id": ObjectId::new(),
+                "count": 5_000_000_000_i64,
+                "tags": [],
+                "meta": { "source": "import" },
+            },
+        ];
+        let docs: Vec<_> = docs
+            .iter()
+            .map(|doc| bson::RawDocumentBuf::from_document(doc).unwrap())
+            .collect();
+
+        let schema = crate::bson::schema::merge_schemas(
+            docs.iter().map(crate::bson::schema::schema_from_document),
+        )
+        .unwrap();
+        assert_eq!(&DataType::Int64, schema.field(1).data_type());
+        assert_eq!(
+            &DataType::Timestamp(TimeUnit::Millisecond, None),
+            schema.field(2).data_type()
+        );
+        assert_eq!(&DataType::Utf8, schema.field(3).data_type());
+        // Empty documents don't determine the type.
+        assert!(matches!(schema.field(4).data_type(), DataType::Struct(_)));
+        assert_eq!(
+            &DataType::Timestamp(TimeUnit::Second, None),
+            schema.field(5).data_type()
+        );
+
+        let mut rsb = RecordStructBuilder::new_with_capacity(schema.fields().clone(), 2).unwrap();
+        for doc in &docs {
+            rsb.project_and_append(doc).unwrap();
+        }
+        let array = rsb.finish();
+        let array = array.as_any().downcast_ref::<StructArray>().unwrap();
+
+        let count = array.column(1).as_primitive::<Int64Type>();
+        assert_eq!(5_000_000_000, count.value(1));
+        let tags = array.column(3).as_string::<i32>();
+        assert_eq!(r#"["a","b"]"#, tags.value(0));
+        assert_eq!("[]", tags.value(1));
+        let source = array.column(4).as_struct().column(0).as_string::<i32>();
+        assert!(source.is_null(0));
+        assert_eq!("import", source.value(1));
+        assert!(array.column(5).is_null(1));
+    }
+
     #[test]
     fn test_unexpected_schema_change() {
         let fields = Fields::from_iter(vec![
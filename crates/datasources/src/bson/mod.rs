@@ -137,9 +137,13 @@ fn array_to_bson(array: &ArrayRef) -> Result<Vec<bson::Bson>, ArrowError> {
             .as_boolean()
             .iter()
             .for_each(|val| out.push(bson::Bson::Boolean(val.unwrap_or_default()))),
-        DataType::FixedSizeBinary(_) => array.as_fixed_size_binary().iter().for_each(|val| {
+        DataType::FixedSizeBinary(size) => array.as_fixed_size_binary().iter().for_each(|val| {
             out.push(bson::Bson::Binary(bson::Binary {
-                subtype: bson::spec::BinarySubtype::Generic,
+                subtype: if *size == 16 {
+                    bson::spec::BinarySubtype::Uuid
+                } else {
+                    bson::spec::BinarySubtype::Generic
+                },
                 bytes: val.unwrap_or_default().to_vec(),
             }))
         }),
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 use std::iter::IntoIterator;
 
+use bson::spec::BinarySubtype;
 use bson::{RawBsonRef, RawDocumentBuf};
-use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use datafusion_ext::types::{is_uuid_field, uuid_field, UUID_DATA_TYPE};
 
 use crate::bson::errors::{BsonError, Result};
 
@@ -34,8 +36,13 @@ fn fields_from_document<'a>(
         let (key, val) = item?;
         let arrow_typ = bson_to_arrow_type(depth, val)?;
 
-        // Assume everything is nullable.
-        fields.push(Field::new(key, arrow_typ, true));
+        // Assume everything is nullable. Only UUID binaries are read as fixed
+        // size binaries.
+        if arrow_typ == UUID_DATA_TYPE {
+            fields.push(uuid_field(key, true));
+        } else {
+            fields.push(Field::new(key, arrow_typ, true));
+        }
     }
 
     Ok(fields)
@@ -43,19 +50,11 @@ fn fields_from_document<'a>(
 
 fn bson_to_arrow_type(depth: usize, bson: RawBsonRef) -> Result<DataType> {
     Ok(match bson {
-        RawBsonRef::Array(array_doc) => DataType::new_list(
-            // TODO this should become a struct with numeric keys to
-            // allow for heterogeneous types
-            bson_to_arrow_type(
-                0,
-                array_doc
-                    .into_iter()
-                    .next()
-                    .map(|v| v.unwrap_or(RawBsonRef::Null))
-                    .unwrap(),
-            )?,
-            true,
-        ),
+        // Arrays may hold values of any type, and are read as JSON strings.
+        RawBsonRef::Array(_) => DataType::Utf8,
+        // Empty documents have no fields to build a struct from. Null is
+        // widened to a string when merging schemas.
+        RawBsonRef::Document(nested) if nested.iter().next().is_none() => DataType::Null,
         RawBsonRef::Document(nested) => DataType::Struct(
             fields_from_document(
                 depth + 1,
@@ -70,30 +69,31 @@ fn bson_to_arrow_type(depth: usize, bson: RawBsonRef) -> Result<DataType> {
         RawBsonRef::Undefined => DataType::Null,
         RawBsonRef::Int32(_) => DataType::Int32,
         RawBsonRef::Int64(_) => DataType::Int64,
+        RawBsonRef::Binary(b) if is_uuid_binary(b.subtype, b.bytes) => UUID_DATA_TYPE,
         RawBsonRef::Binary(_) => DataType::Binary,
         RawBsonRef::ObjectId(_) => DataType::Binary,
-        RawBsonRef::DateTime(_) => DataType::Date64,
+        RawBsonRef::DateTime(_) => DataType::Timestamp(TimeUnit::Millisecond, None),
+        // Replication timestamps, read as the seconds part.
+        RawBsonRef::Timestamp(_) => DataType::Timestamp(TimeUnit::Second, None),
         RawBsonRef::Symbol(_) => DataType::Utf8,
         RawBsonRef::Decimal128(_) => DataType::Decimal128(38, 10),
         RawBsonRef::RegularExpression(_) => DataType::Utf8,
         RawBsonRef::JavaScriptCode(_) => DataType::Utf8,
 
-        // storing these values (which exist to establish a total
-        // order of types for indexing in the MongoDB server,) in
-        // documents that GlareDB would interact with is probably
-        // always an error.
-        RawBsonRef::MaxKey => return Err(BsonError::UnspportedType("maxKey")),
-        RawBsonRef::MinKey => return Err(BsonError::UnspportedType("minKey")),
-
-        // Deprecated or MongoDB server intrenal types
-        RawBsonRef::JavaScriptCodeWithScope(_) => {
-            return Err(BsonError::UnspportedType("CodeWithScope"))
-        }
-        RawBsonRef::Timestamp(_) => return Err(BsonError::UnspportedType("OplogTimestamp")),
-        RawBsonRef::DbPointer(_) => return Err(BsonError::UnspportedType("DbPointer")),
+        // Types internal to the MongoDB server or deprecated, that can
+        // still show up in dumps. Read as extended JSON strings.
+        RawBsonRef::MaxKey
+        | RawBsonRef::MinKey
+        | RawBsonRef::JavaScriptCodeWithScope(_)
+        | RawBsonRef::DbPointer(_) => DataType::Utf8,
     })
 }
 
+/// Returns true if the bson binary contains a UUID.
+pub(crate) fn is_uuid_binary(subtype: BinarySubtype, bytes: &[u8]) -> bool {
+    matches!(subtype, BinarySubtype::Uuid | BinarySubtype::UuidOld) && bytes.len() == 16
+}
+
 #[derive(Debug, Clone)]
 struct OrderedField(usize, Field);
 
@@ -146,7 +146,10 @@ fn merge_field(left: &mut Field, right: &Field) -> Result<()> {
         (&DataType::Int32 | &DataType::Int64 | &DataType::Float64, &DataType::Float64) => {
             DataType::Float64
         }
+        (&DataType::Int32, &DataType::Int64) => DataType::Int64,
         (_, &DataType::Utf8) => DataType::Utf8,
+        // Not all binaries in the field are UUIDs.
+        (_, &DataType::Binary) if is_uuid_field(left) => DataType::Binary,
         _ => return Ok(()),
     };
 
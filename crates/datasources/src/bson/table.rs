@@ -1,6 +1,7 @@
 use std::collections::VecDeque;
 use std::sync::Arc;
 
+use async_compression::tokio::bufread::GzipDecoder;
 use bson::RawDocumentBuf;
 use bytes::BytesMut;
 use datafusion::datasource::streaming::StreamingTable;
@@ -8,6 +9,8 @@ use datafusion::datasource::TableProvider;
 use datafusion::parquet::data_type::AsBytes;
 use datafusion::physical_plan::streaming::PartitionStream;
 use futures::StreamExt;
+use object_store::{ObjectMeta, ObjectStore};
+use tokio::io::AsyncRead;
 use tokio_util::codec::LengthDelimitedCodec;
 
 use crate::bson::errors::BsonError;
@@ -62,11 +65,7 @@ pub async fn bson_streaming_table(
                 .num_skip(0) // send the prefix and payload to the bson library
                 // the prefix use the object_store buffered reader
                 // to stream data from the object store:
-                .new_read(object_store::buffered::BufReader::with_capacity(
-                    store.to_owned(),
-                    &obj,
-                    32 * 1024 * 1024, // 32 MB buffer, probably still too small.
-                ))
+                .new_read(object_reader(&store, &obj))
                 // convert the chunk of bytes to bson.
                 .map(
                     // TODO: this probably wants to be a raw document
@@ -154,3 +153,21 @@ pub async fn bson_streaming_table(
         streams,        // <= vector of partition streams
     )?))
 }
+
+/// Reader for the contents of a file, decompressing gzipped files (as written
+/// by `mongodump --gzip`).
+fn object_reader(
+    store: &Arc<dyn ObjectStore>,
+    obj: &ObjectMeta,
+) -> Box<dyn AsyncRead + Send + Unpin> {
+    let reader = object_store::buffered::BufReader::with_capacity(
+        store.to_owned(),
+        obj,
+        32 * 1024 * 1024, // 32 MB buffer, probably still too small.
+    );
+    if obj.location.extension() == Some("gz") {
+        Box::new(GzipDecoder::new(reader))
+    } else {
+        Box::new(reader)
+    }
+}
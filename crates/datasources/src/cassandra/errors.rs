@@ -6,6 +6,8 @@ pub enum CassandraError {
     QueryError(#[from] scylla::transport::errors::QueryError),
     #[error("Unsupported DataType: {0}")]
     UnsupportedDataType(String),
+    #[error(transparent)]
+    Common(#[from] crate::common::errors::DatasourceCommonError),
 }
 
 pub type Result<T, E = CassandraError> = std::result::Result<T, E>;
@@ -39,6 +39,9 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use self::exec::CassandraExec;
+use crate::common::egress::check_egress;
+
+const DEFAULT_CASSANDRA_PORT: u16 = 9042;
 
 struct CassandraAccess {
     session: Session,
@@ -68,6 +71,11 @@ fn try_convert_dtype(ty: &ColumnType) -> Result<DataType> {
 
 impl CassandraAccess {
     pub async fn try_new(conn_str: impl AsRef<str>) -> Result<Self> {
+        let (host, port) = match conn_str.as_ref().rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().unwrap_or(DEFAULT_CASSANDRA_PORT)),
+            None => (conn_str.as_ref(), DEFAULT_CASSANDRA_PORT),
+        };
+        check_egress(host, port).await?;
         let session = SessionBuilder::new().known_node(conn_str).build().await?;
         Ok(Self { session })
     }
@@ -6,6 +6,8 @@ pub enum ClickhouseError {
     UrlParse(#[from] url::ParseError),
     #[error(transparent)]
     Arrow(#[from] datafusion::arrow::error::ArrowError),
+    #[error(transparent)]
+    Common(#[from] crate::common::errors::DatasourceCommonError),
     #[error("{0}")]
     String(String),
 }
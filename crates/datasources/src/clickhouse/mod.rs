@@ -29,6 +29,10 @@ use std::sync::Arc;
 use url::Url;
 
 use crate::clickhouse::stream::BlockStream;
+use crate::common::egress::check_egress;
+
+/// Port of the native protocol.
+const DEFAULT_CLICKHOUSE_PORT: u16 = 9000;
 
 #[derive(Debug, Clone)]
 pub struct ClickhouseAccess {
@@ -69,7 +73,11 @@ struct ClickhouseAccessState {
 
 impl ClickhouseAccessState {
     async fn connect(conn_str: &str) -> Result<Self> {
-        let pool = Pool::new(Options::new(Url::parse(conn_str)?).pool_min(1).pool_max(1));
+        let url = Url::parse(conn_str)?;
+        if let Some(host) = url.host_str() {
+            check_egress(host, url.port().unwrap_or(DEFAULT_CLICKHOUSE_PORT)).await?;
+        }
+        let pool = Pool::new(Options::new(url).pool_min(1).pool_max(1));
         let mut client = pool.get_handle().await?;
         client.ping().await?;
 
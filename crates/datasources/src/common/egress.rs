@@ -0,0 +1,522 @@
+//! Network policy restricting which hosts data sources may connect to.
+//!
+//! The policy is process wide and checked by data sources before connecting
+//! to a host provided by a user, e.g. the host in a connection string or the
+//! endpoint of an object store. With the default policy any host is allowed.
+//!
+//! Rules have the form `<target>[:<port>[-<port>]]` where the target is one
+//! of:
+//!
+//! - A host name, e.g. `db.example.com`.
+//! - A wildcard domain matching any subdomain, e.g. `*.example.com`.
+//! - An IP address or CIDR range, e.g. `10.0.0.0/8`. IPv6 targets must be
+//!   wrapped in brackets when a port is given, e.g. `[fd00::]/8:5432`.
+//! - `*`, matching any host.
+//!
+//! A connection is allowed if it matches no deny rule, and either there are
+//! no allow rules or it matches at least one of them. Host names are resolved
+//! when checked against IP ranges, a host is only allowed by a range if all of
+//! its addresses are in the range, and is denied by a range if any of its
+//! addresses are in the range.
+//!
+//! Data sources that open their own connections (Postgres, SSH tunnels and
+//! proxies) use `resolve_egress` and connect to the addresses that were
+//! checked. Other drivers resolve the host again when connecting, so for those
+//! ranges don't protect against hosts whose DNS records change in between, and
+//! rules for host names should be preferred for allowing hosts.
+
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use url::Url;
+
+use super::errors::{DatasourceCommonError, Result};
+
+static GLOBAL_POLICY: Lazy<RwLock<Arc<EgressPolicy>>> =
+    Lazy::new(|| RwLock::new(Arc::new(EgressPolicy::default())));
+
+/// Check that connecting to `host` on `port` is allowed by the global policy.
+pub async fn check_egress(host: &str, port: u16) -> Result<()> {
+    EgressPolicy::global().check(host, port).await
+}
+
+/// Resolve `host`, checking that connecting to it on `port` is allowed by the
+/// global policy, and return the addresses to connect to.
+///
+/// Callers should connect to the returned addresses rather than `host`, so
+/// that the host can't resolve to a different address in between.
+pub async fn resolve_egress(host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+    EgressPolicy::global().resolve(host, port).await
+}
+
+/// Check that connecting to the host of `url` is allowed by the global
+/// policy.
+///
+/// URLs without a host (e.g. local files) are always allowed.
+pub async fn check_egress_url(url: &Url) -> Result<()> {
+    match url_host_port(url) {
+        Some((host, port)) => check_egress(host, port).await,
+        None => Ok(()),
+    }
+}
+
+/// Same as `check_egress_url`, but resolves host names on the current
+/// thread.
+pub fn check_egress_url_blocking(url: &Url) -> Result<()> {
+    match url_host_port(url) {
+        Some((host, port)) => EgressPolicy::global().check_blocking(host, port),
+        None => Ok(()),
+    }
+}
+
+fn url_host_port(url: &Url) -> Option<(&str, u16)> {
+    let host = url.host_str().filter(|host| !host.is_empty())?;
+    Some((host, url.port_or_known_default().unwrap_or(443)))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EgressPolicy {
+    allow: Vec<EgressRule>,
+    deny: Vec<EgressRule>,
+}
+
+impl EgressPolicy {
+    /// Create a policy from allow and deny rules.
+    pub fn try_new<S: AsRef<str>>(allow: &[S], deny: &[S]) -> Result<EgressPolicy> {
+        let parse = |rules: &[S]| -> Result<Vec<EgressRule>> {
+            rules.iter().map(|rule| rule.as_ref().parse()).collect()
+        };
+        Ok(EgressPolicy {
+            allow: parse(allow)?,
+            deny: parse(deny)?,
+        })
+    }
+
+    /// Get the policy used by data sources.
+    pub fn global() -> Arc<EgressPolicy> {
+        GLOBAL_POLICY.read().unwrap().clone()
+    }
+
+    /// Replace the policy used by data sources. Connections that are already
+    /// open aren't affected.
+    pub fn set_global(policy: EgressPolicy) {
+        *GLOBAL_POLICY.write().unwrap() = Arc::new(policy);
+    }
+
+    /// If any host is allowed.
+    pub fn allows_all(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Check that connecting to `host` on `port` is allowed.
+    pub async fn check(&self, host: &str, port: u16) -> Result<()> {
+        let host = normalize_host(host);
+        if !self.needs_addrs(host) {
+            return self.check_addrs(host, port, &[]);
+        }
+        let addrs = match tokio::net::lookup_host((host, port)).await {
+            Ok(addrs) => addrs.map(|addr| addr.ip()).collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        };
+        self.check_addrs(host, port, &addrs)
+    }
+
+    /// Resolve `host`, checking that connecting to it on `port` is allowed,
+    /// and return the addresses that were checked.
+    ///
+    /// Unlike `check`, the host is always resolved, and failing to resolve it
+    /// is an error.
+    pub async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+        let host = normalize_host(host);
+        let addrs = tokio::net::lookup_host((host, port))
+            .await?
+            .collect::<Vec<_>>();
+        let ips = addrs.iter().map(|addr| addr.ip()).collect::<Vec<_>>();
+        self.check_addrs(host, port, &ips)?;
+        Ok(addrs)
+    }
+
+    /// Same as `check`, but resolves host names on the current thread.
+    pub fn check_blocking(&self, host: &str, port: u16) -> Result<()> {
+        let host = normalize_host(host);
+        if !self.needs_addrs(host) {
+            return self.check_addrs(host, port, &[]);
+        }
+        let addrs = match (host, port).to_socket_addrs() {
+            Ok(addrs) => addrs.map(|addr| addr.ip()).collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        };
+        self.check_addrs(host, port, &addrs)
+    }
+
+    /// If checking `host` requires its resolved addresses.
+    fn needs_addrs(&self, host: &str) -> bool {
+        host.parse::<IpAddr>().is_err()
+            && self
+                .allow
+                .iter()
+                .chain(self.deny.iter())
+                .any(|rule| matches!(rule.target, EgressTarget::Net(_)))
+    }
+
+    fn check_addrs(&self, host: &str, port: u16, addrs: &[IpAddr]) -> Result<()> {
+        if self.allows_all() {
+            return Ok(());
+        }
+        let denied = |reason| DatasourceCommonError::EgressDenied {
+            addr: format_addr(host, port),
+            reason,
+        };
+
+        let addrs = match host.parse::<IpAddr>() {
+            Ok(ip) => vec![ip.to_canonical()],
+            Err(_) => addrs.iter().map(IpAddr::to_canonical).collect(),
+        };
+
+        if self
+            .deny
+            .iter()
+            .any(|rule| rule.matches(host, port, &addrs, false))
+        {
+            return Err(denied("the address is denied"));
+        }
+        if !self.allow.is_empty()
+            && !self
+                .allow
+                .iter()
+                .any(|rule| rule.matches(host, port, &addrs, true))
+        {
+            return Err(denied("the address is not in the allowlist"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EgressRule {
+    target: EgressTarget,
+    /// Ports the rule applies to, all ports if unset.
+    ports: Option<RangeInclusive<u16>>,
+}
+
+impl EgressRule {
+    /// Check if the rule matches a connection. `all_addrs` determines if a
+    /// range must contain all addresses of the host, or just one.
+    fn matches(&self, host: &str, port: u16, addrs: &[IpAddr], all_addrs: bool) -> bool {
+        if let Some(ports) = &self.ports {
+            if !ports.contains(&port) {
+                return false;
+            }
+        }
+        match &self.target {
+            EgressTarget::Any => true,
+            EgressTarget::Host(name) => host.eq_ignore_ascii_case(name),
+            EgressTarget::Domain(domain) => host
+                .len()
+                .checked_sub(domain.len() + 1)
+                .map(|idx| {
+                    host.as_bytes()[idx] == b'.' && host[idx + 1..].eq_ignore_ascii_case(domain)
+                })
+                .unwrap_or(false),
+            EgressTarget::Net(net) if all_addrs => {
+                !addrs.is_empty() && addrs.iter().all(|ip| net.contains(ip))
+            }
+            EgressTarget::Net(net) => addrs.iter().any(|ip| net.contains(ip)),
+        }
+    }
+}
+
+impl FromStr for EgressRule {
+    type Err = DatasourceCommonError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || DatasourceCommonError::InvalidEgressRule(s.to_string());
+
+        let (target, ports) = if let Some(rest) = s.strip_prefix('[') {
+            // Bracketed IPv6, optionally followed by a prefix length.
+            let (addr, rest) = rest.split_once(']').ok_or_else(invalid)?;
+            let (prefix, ports) = match rest.split_once(':') {
+                Some((prefix, ports)) => (prefix, Some(ports)),
+                None => (rest, None),
+            };
+            (format!("{addr}{prefix}"), ports)
+        } else if s.matches(':').count() > 1 {
+            // Bare IPv6 without a port.
+            (s.to_string(), None)
+        } else {
+            match s.split_once(':') {
+                Some((target, ports)) => (target.to_string(), Some(ports)),
+                None => (s.to_string(), None),
+            }
+        };
+
+        let ports = match ports {
+            Some(ports) => {
+                let (start, end) = ports.split_once('-').unwrap_or((ports, ports));
+                let start: u16 = start.parse().map_err(|_| invalid())?;
+                let end: u16 = end.parse().map_err(|_| invalid())?;
+                if start > end {
+                    return Err(invalid());
+                }
+                Some(start..=end)
+            }
+            None => None,
+        };
+
+        let target = if target == "*" {
+            EgressTarget::Any
+        } else if let Some(domain) = target.strip_prefix("*.") {
+            if !is_host_name(domain) {
+                return Err(invalid());
+            }
+            EgressTarget::Domain(domain.to_string())
+        } else if let Ok(net) = target.parse() {
+            EgressTarget::Net(net)
+        } else if is_host_name(&target) {
+            EgressTarget::Host(target)
+        } else {
+            return Err(invalid());
+        };
+
+        Ok(EgressRule { target, ports })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum EgressTarget {
+    Any,
+    Host(String),
+    Domain(String),
+    Net(IpNet),
+}
+
+/// An IP address range in CIDR notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IpNet {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNet {
+    fn contains(&self, ip: &IpAddr) -> bool {
+        fn prefix_eq(a: u128, b: u128, prefix_len: u8, bits: u8) -> bool {
+            if prefix_len == 0 {
+                return true;
+            }
+            let shift = bits - prefix_len;
+            (a >> shift) == (b >> shift)
+        }
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => prefix_eq(
+                u32::from(net) as u128,
+                u32::from(*ip) as u128,
+                self.prefix_len,
+                32,
+            ),
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                prefix_eq(u128::from(net), u128::from(*ip), self.prefix_len, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNet {
+    type Err = DatasourceCommonError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || DatasourceCommonError::InvalidEgressRule(s.to_string());
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, len)) => (addr, Some(len)),
+            None => (s, None),
+        };
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|_| invalid())?
+            .to_canonical();
+        let max_len = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(len) => len.parse::<u8>().map_err(|_| invalid())?,
+            None => max_len,
+        };
+        if prefix_len > max_len {
+            return Err(invalid());
+        }
+        Ok(IpNet { addr, prefix_len })
+    }
+}
+
+fn is_host_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Strip brackets from IPv6 hosts and trailing dots from host names.
+fn normalize_host(host: &str) -> &str {
+    let host = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+    host.strip_suffix('.').unwrap_or(host)
+}
+
+fn format_addr(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allow: &[&str], deny: &[&str]) -> EgressPolicy {
+        EgressPolicy::try_new(allow, deny).unwrap()
+    }
+
+    fn ips(addrs: &[&str]) -> Vec<IpAddr> {
+        addrs.iter().map(|addr| addr.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn parse_rules() {
+        let valid = [
+            "*",
+            "*:5432",
+            "db.example.com",
+            "db.example.com:5432",
+            "*.example.com:5432-5433",
+            "10.0.0.0/8",
+            "10.1.2.3:3306",
+            "::1",
+            "fd00::/8",
+            "[fd00::]/8:5432",
+            "[::1]:5432",
+        ];
+        for rule in valid {
+            rule.parse::<EgressRule>().unwrap();
+        }
+
+        let invalid = [
+            "",
+            "db.example.com:",
+            "db.example.com:99999",
+            "db.example.com:5433-5432",
+            "*.",
+            "10.0.0.0/33",
+            "[::1",
+            "db_example.com",
+            "http://db.example.com",
+        ];
+        for rule in invalid {
+            rule.parse::<EgressRule>().unwrap_err();
+        }
+    }
+
+    #[test]
+    fn default_allows_all() {
+        let policy = EgressPolicy::default();
+        policy.check_addrs("169.254.169.254", 80, &[]).unwrap();
+        policy.check_addrs("db.example.com", 5432, &[]).unwrap();
+    }
+
+    #[test]
+    fn allowlist() {
+        let policy = policy(
+            &["db.example.com:5432", "*.corp.example.com", "10.0.0.0/8"],
+            &[],
+        );
+
+        policy.check_addrs("db.example.com", 5432, &[]).unwrap();
+        policy.check_addrs("DB.Example.com", 5432, &[]).unwrap();
+        policy.check_addrs("a.corp.example.com", 3306, &[]).unwrap();
+        policy.check_addrs("10.1.2.3", 80, &[]).unwrap();
+        policy
+            .check_addrs("internal", 80, &ips(&["10.0.0.1", "10.0.0.2"]))
+            .unwrap();
+
+        policy.check_addrs("db.example.com", 5433, &[]).unwrap_err();
+        policy.check_addrs("corp.example.com", 80, &[]).unwrap_err();
+        policy
+            .check_addrs("evilcorp.example.com", 80, &[])
+            .unwrap_err();
+        policy.check_addrs("11.0.0.1", 80, &[]).unwrap_err();
+        // Only some addresses in the range.
+        policy
+            .check_addrs("internal", 80, &ips(&["10.0.0.1", "192.168.0.1"]))
+            .unwrap_err();
+        // Host names that can't be resolved aren't in any range.
+        policy.check_addrs("internal", 80, &[]).unwrap_err();
+    }
+
+    #[test]
+    fn denylist() {
+        let policy = policy(&[], &["169.254.0.0/16", "fd00::/8", "*:22"]);
+
+        policy
+            .check_addrs("db.example.com", 5432, &ips(&["93.184.216.34"]))
+            .unwrap();
+        policy.check_addrs("169.254.169.254", 80, &[]).unwrap_err();
+        policy
+            .check_addrs("metadata", 80, &ips(&["93.184.216.34", "169.254.169.254"]))
+            .unwrap_err();
+        policy.check_blocking("[fd00::1]", 80).unwrap_err();
+        policy.check_addrs("fd00::1", 80, &[]).unwrap_err();
+        // IPv4 mapped addresses are checked as IPv4.
+        policy
+            .check_addrs("::ffff:169.254.169.254", 80, &[])
+            .unwrap_err();
+        policy
+            .check_addrs("db.example.com", 22, &ips(&["93.184.216.34"]))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn deny_takes_precedence() {
+        let policy = policy(&["*.example.com"], &["admin.example.com"]);
+
+        policy.check_addrs("db.example.com", 5432, &[]).unwrap();
+        let err = policy
+            .check_addrs("admin.example.com", 5432, &[])
+            .unwrap_err();
+        assert_eq!(
+            "Connecting to 'admin.example.com:5432' is not allowed by the network policy: the address is denied",
+            err.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn check_resolves_hosts() {
+        let policy = policy(&["127.0.0.0/8", "::1"], &[]);
+        policy.check("localhost", 5432).await.unwrap();
+        policy.check_blocking("localhost", 5432).unwrap();
+
+        let policy = self::policy(&[], &["127.0.0.0/8", "::1"]);
+        policy.check("localhost", 5432).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn resolve_returns_checked_addrs() {
+        let policy = policy(&["127.0.0.0/8", "::1"], &[]);
+        let addrs = policy.resolve("localhost", 5432).await.unwrap();
+        assert!(!addrs.is_empty());
+        for addr in addrs {
+            assert!(addr.ip().is_loopback(), "{addr}");
+            assert_eq!(5432, addr.port());
+        }
+
+        let addrs = policy.resolve("[::1]", 5432).await.unwrap();
+        assert_eq!(vec!["[::1]:5432".parse::<SocketAddr>().unwrap()], addrs);
+
+        policy.resolve("10.0.0.1", 5432).await.unwrap_err();
+        let policy = self::policy(&[], &["127.0.0.0/8", "::1"]);
+        policy.resolve("localhost", 5432).await.unwrap_err();
+    }
+}
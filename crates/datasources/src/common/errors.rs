@@ -12,6 +12,12 @@ pub enum DatasourceCommonError {
     #[error("Invalid url: {0}")]
     InvalidUrl(String),
 
+    #[error("Connecting to '{addr}' is not allowed by the network policy: {reason}")]
+    EgressDenied { addr: String, reason: &'static str },
+
+    #[error("Invalid network policy rule: {0}")]
+    InvalidEgressRule(String),
+
     #[error(transparent)]
     ReprError(#[from] repr::error::ReprError),
 
@@ -9,9 +9,11 @@ use datafusion::{
     physical_expr::create_physical_expr, physical_plan::PhysicalExpr, prelude::Expr,
 };
 
+pub mod egress;
 pub mod errors;
 pub mod sink;
 pub mod ssh;
+pub mod tunnel;
 pub mod url;
 pub mod util;
 
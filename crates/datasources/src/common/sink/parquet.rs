@@ -1,6 +1,11 @@
 use async_trait::async_trait;
+use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::common::Result as DfResult;
 use datafusion::execution::TaskContext;
+use datafusion::parquet::basic::{BrotliLevel, Compression, GzipLevel, ZstdLevel};
+use datafusion::parquet::errors::{ParquetError, Result as ParquetResult};
+use datafusion::parquet::file::properties::WriterVersion;
+use datafusion::parquet::schema::types::ColumnPath;
 use datafusion::parquet::{arrow::AsyncArrowWriter, file::properties::WriterProperties};
 use datafusion::physical_plan::insert::DataSink;
 use datafusion::physical_plan::DisplayAs;
@@ -8,23 +13,94 @@ use datafusion::physical_plan::{DisplayFormatType, SendableRecordBatchStream};
 use futures::StreamExt;
 use object_store::{path::Path as ObjectPath, ObjectStore};
 use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::AsyncWrite;
 
 const BUFFER_SIZE: usize = 8 * 1024 * 1024;
 
 #[derive(Debug, Clone)]
 pub struct ParquetSinkOpts {
     pub row_group_size: usize,
+    /// Columns to write bloom filters for.
+    pub bloom_filter_columns: Vec<String>,
+    pub compression: Compression,
+    /// Whether columns are dictionary encoded, unless set for the column.
+    pub dictionary: bool,
+    pub dictionary_enabled_columns: Vec<String>,
+    pub dictionary_disabled_columns: Vec<String>,
+    pub writer_version: WriterVersion,
+    /// Split the output into multiple files of about this many bytes.
+    ///
+    /// Files are only split between row groups, so can be larger by up to a
+    /// row group.
+    pub target_file_size: Option<usize>,
 }
 
 impl Default for ParquetSinkOpts {
     fn default() -> Self {
         ParquetSinkOpts {
             row_group_size: 122880,
+            bloom_filter_columns: Vec::new(),
+            compression: Compression::UNCOMPRESSED,
+            dictionary: true,
+            dictionary_enabled_columns: Vec::new(),
+            dictionary_disabled_columns: Vec::new(),
+            writer_version: WriterVersion::PARQUET_1_0,
+            target_file_size: None,
         }
     }
 }
 
+/// Parse a compression codec, with an optional level for codecs supporting
+/// levels.
+pub fn parse_compression(codec: &str, level: Option<i32>) -> ParquetResult<Compression> {
+    let level_unsupported =
+        || ParquetError::General(format!("compression level is not supported for '{codec}'"));
+    let unsigned_level = |level: i32| {
+        u32::try_from(level)
+            .map_err(|_| ParquetError::General(format!("invalid compression level: {level}")))
+    };
+
+    Ok(match codec.to_lowercase().as_str() {
+        "uncompressed" | "none" if level.is_none() => Compression::UNCOMPRESSED,
+        "snappy" if level.is_none() => Compression::SNAPPY,
+        "lz4" | "lz4_raw" if level.is_none() => Compression::LZ4_RAW,
+        "uncompressed" | "none" | "snappy" | "lz4" | "lz4_raw" => return Err(level_unsupported()),
+        "zstd" => Compression::ZSTD(match level {
+            Some(level) => ZstdLevel::try_new(level)?,
+            None => ZstdLevel::default(),
+        }),
+        "gzip" => Compression::GZIP(match level {
+            Some(level) => GzipLevel::try_new(unsigned_level(level)?)?,
+            None => GzipLevel::default(),
+        }),
+        "brotli" => Compression::BROTLI(match level {
+            Some(level) => BrotliLevel::try_new(unsigned_level(level)?)?,
+            None => BrotliLevel::default(),
+        }),
+        _ => {
+            return Err(ParquetError::General(format!(
+                "unsupported compression codec '{codec}', expected one of uncompressed, snappy, lz4, zstd, gzip or brotli"
+            )))
+        }
+    })
+}
+
+/// Parse a parquet writer version, either '1.0' or '2.0'.
+pub fn parse_writer_version(version: &str) -> ParquetResult<WriterVersion> {
+    match version {
+        "1.0" | "1" => Ok(WriterVersion::PARQUET_1_0),
+        "2.0" | "2" => Ok(WriterVersion::PARQUET_2_0),
+        other => Err(ParquetError::General(format!(
+            "unsupported writer version '{other}', expected '1.0' or '2.0'"
+        ))),
+    }
+}
+
 /// Writes parquet files to object storage.
 #[derive(Debug, Clone)]
 pub struct ParquetSink {
@@ -61,26 +137,127 @@ impl ParquetSink {
         }
     }
 
-    async fn stream_into_inner(&self, mut stream: SendableRecordBatchStream) -> DfResult<usize> {
-        let schema = stream.schema();
-
-        let (_id, obj_handle) = self.store.put_multipart(&self.loc).await?;
-
-        let props = WriterProperties::builder()
+    fn writer_properties(&self) -> WriterProperties {
+        let mut props = WriterProperties::builder()
             .set_created_by("GlareDB".to_string())
             .set_max_row_group_size(self.opts.row_group_size)
-            .build();
+            .set_compression(self.opts.compression)
+            .set_dictionary_enabled(self.opts.dictionary)
+            .set_writer_version(self.opts.writer_version);
+        for col in &self.opts.bloom_filter_columns {
+            props = props.set_column_bloom_filter_enabled(ColumnPath::from(col.as_str()), true);
+        }
+        for col in &self.opts.dictionary_enabled_columns {
+            props = props.set_column_dictionary_enabled(ColumnPath::from(col.as_str()), true);
+        }
+        for col in &self.opts.dictionary_disabled_columns {
+            props = props.set_column_dictionary_enabled(ColumnPath::from(col.as_str()), false);
+        }
+        props.build()
+    }
+
+    /// Location of the nth file when splitting the output, adding a suffix to
+    /// the file stem.
+    fn split_location(&self, n: usize) -> ObjectPath {
+        let loc = self.loc.as_ref();
+        let name_start = loc.rfind('/').map(|idx| idx + 1).unwrap_or(0);
+        match loc[name_start..].rfind('.') {
+            Some(idx) if idx > 0 => {
+                let (stem, ext) = loc.split_at(name_start + idx);
+                ObjectPath::from(format!("{stem}_{n}{ext}"))
+            }
+            _ => ObjectPath::from(format!("{loc}_{n}")),
+        }
+    }
+
+    /// Create a writer for the nth file, returning the count of bytes written
+    /// to the file along with it.
+    async fn create_writer(
+        &self,
+        n: usize,
+        schema: SchemaRef,
+    ) -> DfResult<(AsyncArrowWriter<CountingWriter>, Arc<AtomicUsize>)> {
+        let loc = match self.opts.target_file_size {
+            Some(_) => self.split_location(n),
+            None => self.loc.clone(),
+        };
+        let (_id, obj_handle) = self.store.put_multipart(&loc).await?;
+
+        let written = Arc::new(AtomicUsize::new(0));
+        let obj_handle = CountingWriter {
+            inner: obj_handle,
+            written: written.clone(),
+        };
+
+        // Flush to the object store at least as often as the target size so
+        // that the bytes written are known at row group boundaries.
+        let buffer_size = match self.opts.target_file_size {
+            Some(size) => size.clamp(1, BUFFER_SIZE),
+            None => BUFFER_SIZE,
+        };
+        let writer = AsyncArrowWriter::try_new(
+            obj_handle,
+            schema,
+            buffer_size,
+            Some(self.writer_properties()),
+        )?;
+        Ok((writer, written))
+    }
+
+    async fn stream_into_inner(&self, mut stream: SendableRecordBatchStream) -> DfResult<usize> {
+        let schema = stream.schema();
 
-        let mut writer = AsyncArrowWriter::try_new(obj_handle, schema, BUFFER_SIZE, Some(props))?;
+        let mut num_rows = 0;
+        let mut num_files = 1;
+        let (mut writer, mut written) = self.create_writer(0, schema.clone()).await?;
         while let Some(batch) = stream.next().await {
             let batch = batch?;
+
+            if let Some(target) = self.opts.target_file_size {
+                if written.load(Ordering::Relaxed) >= target {
+                    // Calls `shutdown` internally.
+                    num_rows += writer.close().await?.num_rows as usize;
+                    (writer, written) = self.create_writer(num_files, schema.clone()).await?;
+                    num_files += 1;
+                }
+            }
+
             writer.write(&batch).await?;
         }
 
         // Calls `shutdown` internally.
         let stats = writer.close().await?;
+        num_rows += stats.num_rows as usize;
+
+        Ok(num_rows)
+    }
+}
+
+/// Counts the bytes written to the inner writer.
+struct CountingWriter {
+    inner: Box<dyn AsyncWrite + Send + Unpin>,
+    written: Arc<AtomicUsize>,
+}
+
+impl AsyncWrite for CountingWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            self.written.fetch_add(n, Ordering::Relaxed);
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
 
-        Ok(stats.num_rows as usize)
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
     }
 }
 
@@ -9,7 +9,10 @@ use tokio::fs::File;
 use tokio::net::TcpListener;
 use tracing::{debug, trace};
 
+use crate::common::egress::resolve_egress;
+use crate::common::errors::DatasourceCommonError;
 use crate::common::ssh::key::{SshKey, SshKeyError};
+use crate::common::ssh::SshConnectionParameters;
 
 #[derive(Debug, thiserror::Error)]
 pub enum SshTunnelError {
@@ -35,6 +38,9 @@ pub enum SshTunnelError {
     #[error(transparent)]
     SshKey(#[from] SshKeyError),
 
+    #[error(transparent)]
+    Common(#[from] DatasourceCommonError),
+
     #[error("Failed to find an open port to open the SSH tunnel")]
     NoOpenPorts,
 
@@ -80,6 +86,10 @@ impl SshTunnelAccess {
     /// Note that this will only work for macos and linux as it relies on
     /// openssh. Attempting to create a tunnel on windows will always return an
     /// error.
+    ///
+    /// The ssh host is checked against the network policy, and the connection
+    /// is made to the checked addresses. Callers should check `remote_addr`
+    /// themselves.
     pub async fn create_tunnel<T>(
         &self,
         remote_addr: &T,
@@ -87,12 +97,15 @@ impl SshTunnelAccess {
     where
         T: ToSocketAddrs,
     {
+        let params: SshConnectionParameters = self.connection_string.parse()?;
+        let ssh_addrs = resolve_egress(&params.host, params.port.unwrap_or(22)).await?;
+
         #[cfg(any(target_os = "linux", target_os = "macos"))]
         let (inner, addr) =
-            unix_impl::create_tunnel(remote_addr, &self.connection_string, &self.keypair).await?;
+            unix_impl::create_tunnel(remote_addr, &params.user, &ssh_addrs, &self.keypair).await?;
         #[cfg(not(any(target_os = "linux", target_os = "macos")))]
         let (inner, addr) =
-            not_unix_impl::create_tunnel(remote_addr, &self.connection_string, &self.keypair)
+            not_unix_impl::create_tunnel(remote_addr, &params.user, &ssh_addrs, &self.keypair)
                 .await?;
         Ok((SshTunnelSession { inner }, addr))
     }
@@ -121,7 +134,8 @@ mod unix_impl {
 
     pub(super) async fn create_tunnel<T>(
         remote_addr: &T,
-        connection_str: &str,
+        user: &str,
+        ssh_addrs: &[SocketAddr],
         keypair: &SshKey,
     ) -> Result<(SshTunnelSessionImpl, SocketAddr), SshTunnelError>
     where
@@ -134,7 +148,8 @@ mod unix_impl {
             .next()
             .ok_or(SshTunnelError::NoRemoteAddressesProvided)?;
 
-        let tunnel = SessionBuilder::default()
+        let mut builder = SessionBuilder::default();
+        builder
             .known_hosts_check(KnownHosts::Accept)
             .keyfile(temp_keyfile.path())
             // Set control directory explicitly. Otherwise we run the the
@@ -144,8 +159,26 @@ mod unix_impl {
             .control_directory(std::env::temp_dir())
             // Wait 15 seconds before timing out ssh connection attempt
             .connect_timeout(Duration::from_secs(15))
-            .connect(connection_str)
-            .await?;
+            .user(user.to_string());
+
+        // Connect to the addresses checked against the network policy instead
+        // of letting ssh resolve the host again.
+        let mut result = Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "ssh host did not resolve to any addresses",
+        )
+        .into());
+        for addr in ssh_addrs {
+            builder.port(addr.port());
+            result = builder
+                .connect(addr.ip().to_string())
+                .await
+                .map_err(SshTunnelError::from);
+            if result.is_ok() {
+                break;
+            }
+        }
+        let tunnel = result?;
 
         // Check the status of the connection before proceeding.
         tunnel.check().await?;
@@ -251,7 +284,8 @@ mod not_unix_impl {
 
     pub(super) async fn create_tunnel<T>(
         remote_addr: &T,
-        connection_str: &str,
+        user: &str,
+        ssh_addrs: &[SocketAddr],
         keypair: &SshKey,
     ) -> Result<(SshTunnelSessionImpl, SocketAddr), SshTunnelError>
     where
@@ -0,0 +1,744 @@
+//! Tunnels for connecting to data sources that aren't directly reachable.
+//!
+//! Every tunnel type works the same way for data sources: opening a tunnel to
+//! a remote address returns a local address that forwards to the remote
+//! address through the tunnel. Data sources connect to the local address
+//! instead of the remote one, so any TCP based data source can support every
+//! tunnel type.
+//!
+//! SSH tunnels use port forwarding through the SSH host. SOCKS5 and HTTP
+//! tunnels listen on a local port and open a new connection through the proxy
+//! for each connection accepted.
+//!
+//! HTTP clients that need to keep using the remote host name, e.g. to verify
+//! TLS certificates, can use a local HTTP proxy in front of the tunnel instead.
+
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use protogen::metastore::types::options::{TunnelOptions, TunnelOptionsProxy};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::common::egress::resolve_egress;
+use crate::common::errors::DatasourceCommonError;
+use crate::common::ssh::key::{SshKey, SshKeyError};
+use crate::common::ssh::session::{SshTunnelAccess, SshTunnelError, SshTunnelSession};
+
+/// Timeout for connecting to a proxy and asking it to connect to the remote
+/// address.
+const PROXY_CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Max length of the head of HTTP messages read from proxies and clients.
+const MAX_HTTP_HEAD_LEN: usize = 8 * 1024;
+
+const SOCKS5_VERSION: u8 = 5;
+const SOCKS5_NO_AUTH: u8 = 0;
+const SOCKS5_USER_PASS_AUTH: u8 = 2;
+const SOCKS5_CMD_CONNECT: u8 = 1;
+const SOCKS5_ATYP_IPV4: u8 = 1;
+const SOCKS5_ATYP_DOMAIN: u8 = 3;
+const SOCKS5_ATYP_IPV6: u8 = 4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TunnelError {
+    #[error(transparent)]
+    Ssh(#[from] SshTunnelError),
+
+    #[error(transparent)]
+    SshKey(#[from] SshKeyError),
+
+    #[error(transparent)]
+    Common(#[from] DatasourceCommonError),
+
+    #[error("Proxy '{proxy}' failed to connect to '{addr}': {reason}")]
+    Proxy {
+        proxy: String,
+        addr: String,
+        reason: String,
+    },
+
+    #[error("Timed out connecting through proxy '{0}'")]
+    ProxyTimeout(String),
+
+    #[error("Tunnel type '{0}' is not supported for connecting to data sources")]
+    Unsupported(String),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+pub type Result<T, E = TunnelError> = std::result::Result<T, E>;
+
+/// Handle to an open tunnel.
+///
+/// Dropping this will close the tunnel if `close` has not already been called.
+/// Connections already forwarded through a proxy tunnel stay open until either
+/// side closes them.
+#[derive(Debug)]
+pub struct TunnelSession {
+    inner: TunnelSessionInner,
+    /// Local HTTP proxy in front of the tunnel, see `open_http_proxy`.
+    http_proxy: Option<LocalListener>,
+}
+
+#[derive(Debug)]
+enum TunnelSessionInner {
+    Ssh(SshTunnelSession),
+    Proxy(LocalListener),
+}
+
+impl TunnelSession {
+    /// Open a tunnel to `host:port`, returning the session and the local
+    /// address to connect to.
+    ///
+    /// The returned session should be kept around for the desired lifetime of
+    /// the tunnel.
+    ///
+    /// The SSH host or proxy is checked against the network policy. Callers
+    /// should check `host:port` themselves.
+    pub async fn open(
+        tunnel: &TunnelOptions,
+        host: &str,
+        port: u16,
+    ) -> Result<(TunnelSession, SocketAddr)> {
+        let (inner, addr) = match tunnel {
+            TunnelOptions::Ssh(ssh_options) => {
+                let access = SshTunnelAccess {
+                    connection_string: ssh_options.connection_string.clone(),
+                    keypair: SshKey::from_bytes(&ssh_options.ssh_key)?,
+                };
+                let (session, addr) = access.create_tunnel(&(host, port)).await?;
+                (TunnelSessionInner::Ssh(session), addr)
+            }
+            TunnelOptions::Socks5(proxy) => {
+                let target = ProxyTarget::new(ProxyKind::Socks5, proxy, host, port);
+                let (listener, addr) = spawn_proxy_forwarder(target).await?;
+                (TunnelSessionInner::Proxy(listener), addr)
+            }
+            TunnelOptions::Http(proxy) => {
+                let target = ProxyTarget::new(ProxyKind::Http, proxy, host, port);
+                let (listener, addr) = spawn_proxy_forwarder(target).await?;
+                (TunnelSessionInner::Proxy(listener), addr)
+            }
+            other => return Err(TunnelError::Unsupported(other.to_string())),
+        };
+        let session = TunnelSession {
+            inner,
+            http_proxy: None,
+        };
+        Ok((session, addr))
+    }
+
+    /// Open a tunnel to `host:port`, returning the session and the address of
+    /// a local HTTP proxy to send requests for `host:port` through.
+    ///
+    /// This is for HTTP clients that need to keep using the original host,
+    /// e.g. to verify the server's TLS certificate. The proxy only accepts
+    /// `CONNECT` requests for `host:port`.
+    pub async fn open_http_proxy(
+        tunnel: &TunnelOptions,
+        host: &str,
+        port: u16,
+    ) -> Result<(TunnelSession, SocketAddr)> {
+        let (mut session, tunnel_addr) = Self::open(tunnel, host, port).await?;
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let local_addr = listener.local_addr()?;
+        let authority = format_authority(host.trim_start_matches('[').trim_end_matches(']'), port);
+        session.http_proxy = Some(LocalListener::spawn(listener, move |inbound| {
+            let authority = authority.clone();
+            async move {
+                if let Err(e) = serve_http_connect(inbound, &authority, tunnel_addr).await {
+                    debug!(%e, "tunnel http proxy connection errored");
+                }
+            }
+        }));
+
+        Ok((session, local_addr))
+    }
+
+    pub async fn close(self) -> Result<()> {
+        drop(self.http_proxy);
+        match self.inner {
+            TunnelSessionInner::Ssh(session) => session.close().await?,
+            TunnelSessionInner::Proxy(listener) => drop(listener),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ProxyKind {
+    Socks5,
+    Http,
+}
+
+/// A remote address to connect to through a proxy.
+#[derive(Debug, Clone)]
+struct ProxyTarget {
+    kind: ProxyKind,
+    proxy: TunnelOptionsProxy,
+    /// Addresses of the proxy checked against the network policy. Connections
+    /// are made to these instead of resolving the proxy host again.
+    proxy_addrs: Vec<SocketAddr>,
+    /// Host without brackets if it's an IPv6 address.
+    host: String,
+    port: u16,
+}
+
+impl ProxyTarget {
+    fn new(kind: ProxyKind, proxy: &TunnelOptionsProxy, host: &str, port: u16) -> ProxyTarget {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        ProxyTarget {
+            kind,
+            proxy: proxy.clone(),
+            proxy_addrs: Vec::new(),
+            host: host.to_string(),
+            port,
+        }
+    }
+
+    fn proxy_addr(&self) -> String {
+        format_authority(&self.proxy.host, self.proxy.port)
+    }
+
+    fn remote_addr(&self) -> String {
+        format_authority(&self.host, self.port)
+    }
+
+    fn refused(&self, reason: impl Into<String>) -> TunnelError {
+        TunnelError::Proxy {
+            proxy: self.proxy_addr(),
+            addr: self.remote_addr(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Connect to the proxy and ask it to connect to the remote address.
+    async fn connect(&self) -> Result<TcpStream> {
+        let connect = async {
+            let mut stream = TcpStream::connect(self.proxy_addrs.as_slice()).await?;
+            stream.set_nodelay(true)?;
+            match self.kind {
+                ProxyKind::Socks5 => self.socks5_handshake(&mut stream).await?,
+                ProxyKind::Http => self.http_handshake(&mut stream).await?,
+            }
+            Ok::<_, TunnelError>(stream)
+        };
+        tokio::time::timeout(PROXY_CONNECT_TIMEOUT, connect)
+            .await
+            .map_err(|_| TunnelError::ProxyTimeout(self.proxy_addr()))?
+    }
+
+    /// Negotiate a connection with a SOCKS5 proxy (RFC 1928), using
+    /// username/password authentication (RFC 1929) if a username is set.
+    async fn socks5_handshake(&self, stream: &mut TcpStream) -> Result<()> {
+        let creds = self
+            .proxy
+            .username
+            .as_deref()
+            .map(|username| (username, self.proxy.password.as_deref().unwrap_or_default()));
+
+        match creds {
+            Some(_) => {
+                stream
+                    .write_all(&[SOCKS5_VERSION, 2, SOCKS5_NO_AUTH, SOCKS5_USER_PASS_AUTH])
+                    .await?
+            }
+            None => {
+                stream
+                    .write_all(&[SOCKS5_VERSION, 1, SOCKS5_NO_AUTH])
+                    .await?
+            }
+        }
+
+        let mut buf = [0; 2];
+        stream.read_exact(&mut buf).await?;
+        if buf[0] != SOCKS5_VERSION {
+            return Err(self.refused("proxy is not a SOCKS5 proxy"));
+        }
+        match (buf[1], creds) {
+            (SOCKS5_NO_AUTH, _) => (),
+            (SOCKS5_USER_PASS_AUTH, Some((username, password))) => {
+                if username.len() > 255 || password.len() > 255 {
+                    return Err(self.refused("username and password must be at most 255 bytes"));
+                }
+                let mut req = Vec::with_capacity(3 + username.len() + password.len());
+                req.push(1);
+                req.push(username.len() as u8);
+                req.extend_from_slice(username.as_bytes());
+                req.push(password.len() as u8);
+                req.extend_from_slice(password.as_bytes());
+                stream.write_all(&req).await?;
+
+                stream.read_exact(&mut buf).await?;
+                if buf[1] != 0 {
+                    return Err(self.refused("authentication failed"));
+                }
+            }
+            _ => return Err(self.refused("no acceptable authentication method")),
+        }
+
+        // Let the proxy resolve host names so that hosts only resolvable from
+        // the proxy's network can be used.
+        let mut req = vec![SOCKS5_VERSION, SOCKS5_CMD_CONNECT, 0];
+        match self.host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                req.push(SOCKS5_ATYP_IPV4);
+                req.extend_from_slice(&ip.octets());
+            }
+            Ok(IpAddr::V6(ip)) => {
+                req.push(SOCKS5_ATYP_IPV6);
+                req.extend_from_slice(&ip.octets());
+            }
+            Err(_) => {
+                if self.host.len() > 255 {
+                    return Err(self.refused("host must be at most 255 bytes"));
+                }
+                req.push(SOCKS5_ATYP_DOMAIN);
+                req.push(self.host.len() as u8);
+                req.extend_from_slice(self.host.as_bytes());
+            }
+        }
+        req.extend_from_slice(&self.port.to_be_bytes());
+        stream.write_all(&req).await?;
+
+        let mut reply = [0; 4];
+        stream.read_exact(&mut reply).await?;
+        if reply[0] != SOCKS5_VERSION {
+            return Err(self.refused("proxy is not a SOCKS5 proxy"));
+        }
+        if reply[1] != 0 {
+            return Err(self.refused(socks5_reply_reason(reply[1])));
+        }
+
+        // Skip the address the proxy bound to.
+        let addr_len = match reply[3] {
+            SOCKS5_ATYP_IPV4 => 4,
+            SOCKS5_ATYP_IPV6 => 16,
+            SOCKS5_ATYP_DOMAIN => stream.read_u8().await? as usize,
+            _ => return Err(self.refused("invalid address type in reply")),
+        };
+        let mut bound = vec![0; addr_len + 2];
+        stream.read_exact(&mut bound).await?;
+
+        Ok(())
+    }
+
+    /// Ask an HTTP proxy to open a tunnel using `CONNECT`, using basic
+    /// authentication if a username is set.
+    async fn http_handshake(&self, stream: &mut TcpStream) -> Result<()> {
+        let authority = self.remote_addr();
+        let mut req = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+        if let Some(username) = &self.proxy.username {
+            let password = self.proxy.password.as_deref().unwrap_or_default();
+            let creds = BASE64.encode(format!("{username}:{password}"));
+            req.push_str(&format!("Proxy-Authorization: Basic {creds}\r\n"));
+        }
+        req.push_str("\r\n");
+        stream.write_all(req.as_bytes()).await?;
+
+        let resp = read_http_head(stream)
+            .await?
+            .ok_or_else(|| self.refused("response headers too long"))?;
+        let status_line = resp.lines().next().unwrap_or_default();
+        let mut parts = status_line.split_whitespace();
+        let status = match (parts.next(), parts.next()) {
+            (Some(version), Some(status)) if version.starts_with("HTTP/") => {
+                status.parse::<u16>().ok()
+            }
+            _ => None,
+        };
+        match status {
+            Some(200..=299) => Ok(()),
+            Some(_) => Err(self.refused(status_line)),
+            None => Err(self.refused("invalid response")),
+        }
+    }
+}
+
+/// Task accepting connections on a local port.
+///
+/// The task is stopped when this is dropped.
+#[derive(Debug)]
+struct LocalListener {
+    handle: JoinHandle<()>,
+}
+
+impl LocalListener {
+    /// Accept connections on `listener`, handling each connection in its own
+    /// task.
+    fn spawn<F, Fut>(listener: TcpListener, mut handle_conn: F) -> LocalListener
+    where
+        F: FnMut(TcpStream) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((inbound, _)) => {
+                        tokio::spawn(handle_conn(inbound));
+                    }
+                    Err(e) => {
+                        warn!(%e, "failed to accept tunnel connection");
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                }
+            }
+        });
+        LocalListener { handle }
+    }
+}
+
+impl Drop for LocalListener {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Listen on a local port, forwarding connections through a proxy.
+async fn spawn_proxy_forwarder(mut target: ProxyTarget) -> Result<(LocalListener, SocketAddr)> {
+    target.proxy_addrs = resolve_egress(&target.proxy.host, target.proxy.port).await?;
+
+    // Connect once up front so that errors from the proxy are returned here,
+    // instead of data sources seeing a closed connection.
+    let mut first = Some(target.connect().await?);
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+    let local_addr = listener.local_addr()?;
+    debug!(%local_addr, proxy = %target.proxy_addr(), remote = %target.remote_addr(), "opened proxy tunnel");
+
+    let listener = LocalListener::spawn(listener, move |mut inbound| {
+        let upstream = first.take();
+        let target = target.clone();
+        async move {
+            let mut upstream = match upstream {
+                Some(upstream) => upstream,
+                None => match target.connect().await {
+                    Ok(upstream) => upstream,
+                    Err(e) => {
+                        warn!(%e, "failed to connect through proxy tunnel");
+                        return;
+                    }
+                },
+            };
+            if let Err(e) = tokio::io::copy_bidirectional(&mut inbound, &mut upstream).await {
+                debug!(%e, "proxy tunnel connection errored");
+            }
+        }
+    });
+
+    Ok((listener, local_addr))
+}
+
+/// Serve a `CONNECT` request for `authority` by connecting to `tunnel_addr`.
+async fn serve_http_connect(
+    mut inbound: TcpStream,
+    authority: &str,
+    tunnel_addr: SocketAddr,
+) -> io::Result<()> {
+    let req = match read_http_head(&mut inbound).await? {
+        Some(req) => req,
+        None => {
+            inbound
+                .write_all(b"HTTP/1.1 431 Request Header Fields Too Large\r\n\r\n")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let target = req
+        .strip_prefix("CONNECT ")
+        .and_then(|req| req.split_whitespace().next());
+    match target {
+        Some(target) if target.eq_ignore_ascii_case(authority) => (),
+        _ => {
+            inbound.write_all(b"HTTP/1.1 403 Forbidden\r\n\r\n").await?;
+            return Ok(());
+        }
+    }
+
+    let mut upstream = TcpStream::connect(tunnel_addr).await?;
+    inbound
+        .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+        .await?;
+    tokio::io::copy_bidirectional(&mut inbound, &mut upstream).await?;
+    Ok(())
+}
+
+/// Read the head of an HTTP message.
+///
+/// Reads one byte at a time so that nothing after the head is consumed.
+/// Returns `None` if the head is longer than `MAX_HTTP_HEAD_LEN`.
+async fn read_http_head(stream: &mut TcpStream) -> io::Result<Option<String>> {
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() >= MAX_HTTP_HEAD_LEN {
+            return Ok(None);
+        }
+        head.push(stream.read_u8().await?);
+    }
+    Ok(Some(String::from_utf8_lossy(&head).into_owned()))
+}
+
+fn format_authority(host: &str, port: u16) -> String {
+    if host.parse::<Ipv6Addr>().is_ok() {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
+fn socks5_reply_reason(reply: u8) -> &'static str {
+    match reply {
+        1 => "general failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Start a server echoing back everything it receives.
+    async fn echo_server() -> SocketAddr {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut conn, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let (mut read, mut write) = conn.split();
+                    let _ = tokio::io::copy(&mut read, &mut write).await;
+                });
+            }
+        });
+        addr
+    }
+
+    /// Start a SOCKS5 proxy, requiring `creds` if provided.
+    async fn socks5_proxy(creds: Option<(&'static str, &'static str)>) -> SocketAddr {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut conn, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut greeting = [0; 2];
+                    conn.read_exact(&mut greeting).await.unwrap();
+                    let mut methods = vec![0; greeting[1] as usize];
+                    conn.read_exact(&mut methods).await.unwrap();
+
+                    if let Some((username, password)) = creds {
+                        assert!(methods.contains(&SOCKS5_USER_PASS_AUTH));
+                        conn.write_all(&[5, SOCKS5_USER_PASS_AUTH]).await.unwrap();
+                        let _ = conn.read_u8().await.unwrap();
+                        let mut got_username = vec![0; conn.read_u8().await.unwrap() as usize];
+                        conn.read_exact(&mut got_username).await.unwrap();
+                        let mut got_password = vec![0; conn.read_u8().await.unwrap() as usize];
+                        conn.read_exact(&mut got_password).await.unwrap();
+                        if got_username != username.as_bytes()
+                            || got_password != password.as_bytes()
+                        {
+                            conn.write_all(&[1, 1]).await.unwrap();
+                            return;
+                        }
+                        conn.write_all(&[1, 0]).await.unwrap();
+                    } else {
+                        conn.write_all(&[5, SOCKS5_NO_AUTH]).await.unwrap();
+                    }
+
+                    let mut req = [0; 4];
+                    conn.read_exact(&mut req).await.unwrap();
+                    assert_eq!(SOCKS5_ATYP_DOMAIN, req[3]);
+                    let mut host = vec![0; conn.read_u8().await.unwrap() as usize];
+                    conn.read_exact(&mut host).await.unwrap();
+                    let port = conn.read_u16().await.unwrap();
+
+                    let host = String::from_utf8(host).unwrap();
+                    let mut upstream = TcpStream::connect((host.as_str(), port)).await.unwrap();
+                    conn.write_all(&[5, 0, 0, SOCKS5_ATYP_IPV4, 127, 0, 0, 1, 0, 0])
+                        .await
+                        .unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut conn, &mut upstream).await;
+                });
+            }
+        });
+        addr
+    }
+
+    /// Start an HTTP proxy, responding with `status` to every request. Only
+    /// connects to the requested address when the status is 200.
+    async fn http_proxy(status: &'static str, expected_auth: Option<&'static str>) -> SocketAddr {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut conn, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut req = Vec::new();
+                    while !req.ends_with(b"\r\n\r\n") {
+                        req.push(conn.read_u8().await.unwrap());
+                    }
+                    let req = String::from_utf8(req).unwrap();
+                    let target = req
+                        .strip_prefix("CONNECT ")
+                        .and_then(|req| req.split_whitespace().next())
+                        .unwrap()
+                        .to_string();
+                    if let Some(auth) = expected_auth {
+                        assert!(
+                            req.contains(&format!("Proxy-Authorization: Basic {auth}\r\n")),
+                            "{req}"
+                        );
+                    }
+
+                    conn.write_all(format!("HTTP/1.1 {status}\r\n\r\n").as_bytes())
+                        .await
+                        .unwrap();
+                    if status.starts_with("200") {
+                        let mut upstream = TcpStream::connect(target).await.unwrap();
+                        let _ = tokio::io::copy_bidirectional(&mut conn, &mut upstream).await;
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    fn proxy_opts(addr: SocketAddr, creds: Option<(&str, &str)>) -> TunnelOptionsProxy {
+        TunnelOptionsProxy {
+            host: addr.ip().to_string(),
+            port: addr.port(),
+            username: creds.map(|(username, _)| username.to_string()),
+            password: creds.map(|(_, password)| password.to_string()),
+        }
+    }
+
+    /// Open a tunnel to the echo server and check that data makes it through,
+    /// twice to check that connections after the first work too.
+    async fn assert_echoes(tunnel: &TunnelOptions, echo: SocketAddr) {
+        let (session, addr) = TunnelSession::open(tunnel, "localhost", echo.port())
+            .await
+            .unwrap();
+        for msg in [b"hello", b"world"] {
+            let mut conn = TcpStream::connect(addr).await.unwrap();
+            conn.write_all(msg).await.unwrap();
+            let mut buf = [0; 5];
+            conn.read_exact(&mut buf).await.unwrap();
+            assert_eq!(msg, &buf);
+        }
+        session.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn socks5_tunnel() {
+        let echo = echo_server().await;
+
+        let proxy = socks5_proxy(None).await;
+        assert_echoes(&TunnelOptions::Socks5(proxy_opts(proxy, None)), echo).await;
+
+        let creds = Some(("glaredb", "hunter2"));
+        let proxy = socks5_proxy(creds).await;
+        assert_echoes(&TunnelOptions::Socks5(proxy_opts(proxy, creds)), echo).await;
+    }
+
+    #[tokio::test]
+    async fn socks5_tunnel_wrong_password() {
+        let echo = echo_server().await;
+        let proxy = socks5_proxy(Some(("glaredb", "hunter2"))).await;
+
+        let tunnel = TunnelOptions::Socks5(proxy_opts(proxy, Some(("glaredb", "hunter3"))));
+        let err = TunnelSession::open(&tunnel, "localhost", echo.port())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, TunnelError::Proxy { reason, .. } if reason == "authentication failed"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn http_tunnel() {
+        let echo = echo_server().await;
+
+        let proxy = http_proxy("200 Connection established", None).await;
+        assert_echoes(&TunnelOptions::Http(proxy_opts(proxy, None)), echo).await;
+
+        // "glaredb:hunter2"
+        let proxy = http_proxy("200 OK", Some("Z2xhcmVkYjpodW50ZXIy")).await;
+        let tunnel = TunnelOptions::Http(proxy_opts(proxy, Some(("glaredb", "hunter2"))));
+        assert_echoes(&tunnel, echo).await;
+    }
+
+    #[tokio::test]
+    async fn http_tunnel_error_status() {
+        let echo = echo_server().await;
+        let proxy = http_proxy("407 Proxy Authentication Required", None).await;
+
+        let tunnel = TunnelOptions::Http(proxy_opts(proxy, None));
+        let err = TunnelSession::open(&tunnel, "localhost", echo.port())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, TunnelError::Proxy { reason, .. } if reason.contains("407")),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn http_proxy_in_front_of_tunnel() {
+        let echo = echo_server().await;
+        let proxy = socks5_proxy(None).await;
+
+        let tunnel = TunnelOptions::Socks5(proxy_opts(proxy, None));
+        let (session, addr) = TunnelSession::open_http_proxy(&tunnel, "localhost", echo.port())
+            .await
+            .unwrap();
+
+        // Only requests for the tunneled address are accepted.
+        let mut conn = TcpStream::connect(addr).await.unwrap();
+        conn.write_all(b"CONNECT example.com:443 HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        let resp = read_http_head(&mut conn).await.unwrap().unwrap();
+        assert!(resp.starts_with("HTTP/1.1 403"), "{resp}");
+
+        let mut conn = TcpStream::connect(addr).await.unwrap();
+        let req = format!("CONNECT localhost:{} HTTP/1.1\r\n\r\n", echo.port());
+        conn.write_all(req.as_bytes()).await.unwrap();
+        let resp = read_http_head(&mut conn).await.unwrap().unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200"), "{resp}");
+
+        conn.write_all(b"hello").await.unwrap();
+        let mut buf = [0; 5];
+        conn.read_exact(&mut buf).await.unwrap();
+        assert_eq!(b"hello", &buf);
+
+        session.close().await.unwrap();
+    }
+
+    #[test]
+    fn authority_brackets_ipv6() {
+        assert_eq!("[::1]:5432", format_authority("::1", 5432));
+        assert_eq!("127.0.0.1:5432", format_authority("127.0.0.1", 5432));
+        assert_eq!(
+            "db.example.com:5432",
+            format_authority("db.example.com", 5432)
+        );
+    }
+}
@@ -5,14 +5,17 @@ use datafusion::{
     arrow::{
         array::{Array, ArrayRef, UInt64Array},
         compute::{cast_with_options, CastOptions},
-        datatypes::{DataType, Field, Schema, TimeUnit},
+        datatypes::{DataType, Decimal256Type, DecimalType, Field, Schema, TimeUnit},
         error::ArrowError,
         record_batch::RecordBatch,
         util::display::FormatOptions,
     },
+    execution::context::SessionState,
     scalar::ScalarValue,
 };
+use datafusion_ext::vars::SessionVars;
 use decimal::Decimal128;
+use object_store_util::retry::RetryPolicy;
 use once_cell::sync::Lazy;
 use repr::str::encode::*;
 
@@ -24,6 +27,7 @@ pub enum Datasource {
     MySql,
     BigQuery,
     Snowflake,
+    SqlServer,
 }
 
 /// Returns true if the literal expression encoding should be wrapped inside
@@ -36,8 +40,15 @@ fn is_literal_quotable(datasource: Datasource, lit: &ScalarValue) -> bool {
         | ScalarValue::Int64(_)
         | ScalarValue::Float32(_)
         | ScalarValue::Float64(_)
-        | ScalarValue::Decimal128(..) => false,
+        | ScalarValue::Decimal128(..)
+        | ScalarValue::Decimal256(..) => false,
         ScalarValue::Binary(_) if datasource == Datasource::MySql => false,
+        // Booleans are written as bits and strings with their own prefix.
+        ScalarValue::Boolean(_) | ScalarValue::Binary(_) | ScalarValue::Utf8(_)
+            if datasource == Datasource::SqlServer =>
+        {
+            false
+        }
         _ => true,
     }
 }
@@ -58,6 +69,9 @@ pub fn encode_literal_to_text(
         buf.write_str("'")?;
     }
     match lit {
+        ScalarValue::Boolean(Some(v)) if datasource == Datasource::SqlServer => {
+            buf.write_str(if *v { "1" } else { "0" })?;
+        }
         ScalarValue::Boolean(Some(v)) => {
             if *v {
                 buf.write_str("TRUE")?;
@@ -71,8 +85,15 @@ pub fn encode_literal_to_text(
         ScalarValue::Int64(Some(v)) => encode_int(buf, *v)?,
         ScalarValue::Float32(Some(v)) => encode_float(buf, *v)?,
         ScalarValue::Float64(Some(v)) => encode_float(buf, *v)?,
+        ScalarValue::Utf8(Some(v)) if datasource == Datasource::SqlServer => {
+            // Unicode literal, so that characters outside of the database's
+            // code page compare correctly against NVARCHAR columns.
+            write!(buf, "N'{}'", v.replace('\'', "''"))?;
+        }
         ScalarValue::Utf8(Some(v)) => encode_string(buf, v)?,
-        ScalarValue::Binary(Some(v)) if datasource == Datasource::MySql => {
+        ScalarValue::Binary(Some(v))
+            if datasource == Datasource::MySql || datasource == Datasource::SqlServer =>
+        {
             encode_binary_mysql(buf, v)?
         }
         ScalarValue::Binary(Some(v)) if datasource == Datasource::Snowflake => {
@@ -106,6 +127,14 @@ pub fn encode_literal_to_text(
             let decimal = Decimal128::new(*v, *scale).expect("value should be a valid decimal128");
             encode_decimal(buf, &decimal)?;
         }
+        ScalarValue::FixedSizeBinary(16, Some(v)) => {
+            // 16 byte binaries are UUIDs.
+            let uuid = uuid::Uuid::from_slice(v).expect("value should be a valid uuid");
+            write!(buf, "{}", uuid.hyphenated())?;
+        }
+        ScalarValue::Decimal256(Some(v), precision, scale) => {
+            buf.write_str(&Decimal256Type::format_decimal(*v, *precision, *scale))?;
+        }
         s => {
             return Err(DatasourceCommonError::UnsupportedDatafusionScalar(
                 s.data_type(),
@@ -162,6 +191,87 @@ pub fn normalize_batch(batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
     Ok(batch)
 }
 
+/// Returns the max number of rows to insert into an external table with a
+/// single statement, as set for the session. Returns 0, inserting all rows
+/// with one statement, if the session has no variables.
+pub fn external_insert_batch_size(state: &SessionState) -> usize {
+    state
+        .config_options()
+        .extensions
+        .get::<SessionVars>()
+        .map(|vars| vars.external_insert_batch_size())
+        .unwrap_or(0)
+}
+
+/// Returns the policy for retrying requests to datasources that fail with
+/// transient errors, as set for the session. Returns the default policy if the
+/// session has no variables.
+pub fn datasource_retry_policy(state: &SessionState) -> RetryPolicy {
+    match state.config_options().extensions.get::<SessionVars>() {
+        Some(vars) => RetryPolicy {
+            max_retries: vars.datasource_max_retries(),
+            initial_backoff: std::time::Duration::from_millis(
+                vars.datasource_retry_backoff_ms() as u64
+            ),
+            max_backoff: std::time::Duration::from_millis(
+                vars.datasource_retry_max_backoff_ms() as u64
+            ),
+        },
+        None => RetryPolicy::default(),
+    }
+}
+
+/// Returns whether the session skips files that can't be read when scanning
+/// multiple files.
+pub fn skip_corrupt_files(state: &SessionState) -> bool {
+    state
+        .config_options()
+        .extensions
+        .get::<SessionVars>()
+        .is_some_and(|vars| vars.skip_corrupt_files())
+}
+
+/// Encodes the rows of the batches as `VALUES` lists for inserting into the
+/// datasource, with at most `max_rows` rows in each list. All rows are put in
+/// a single list if `max_rows` is 0.
+pub fn encode_insert_values(
+    datasource: Datasource,
+    batches: &[RecordBatch],
+    max_rows: usize,
+) -> Result<Vec<String>> {
+    let mut lists = Vec::new();
+    let mut values = String::new();
+    let mut num_rows = 0;
+
+    for batch in batches {
+        for row_idx in 0..batch.num_rows() {
+            if num_rows > 0 {
+                values.write_str(",")?;
+            }
+            values.write_str("(")?;
+            for (col_idx, col) in batch.columns().iter().enumerate() {
+                if col_idx > 0 {
+                    values.write_str(",")?;
+                }
+                let val = ScalarValue::try_from_array(col.as_ref(), row_idx)?;
+                encode_literal_to_text(datasource, &mut values, &val)?;
+            }
+            values.write_str(")")?;
+
+            num_rows += 1;
+            if num_rows == max_rows {
+                lists.push(std::mem::take(&mut values));
+                num_rows = 0;
+            }
+        }
+    }
+
+    if num_rows > 0 {
+        lists.push(values);
+    }
+    Ok(lists)
+}
+
 pub static COUNT_SCHEMA: Lazy<Arc<Schema>> = Lazy::new(|| {
     Arc::new(Schema::new(vec![Field::new(
         "count",
@@ -184,10 +294,10 @@ pub fn create_count_record_batch(count: u64) -> RecordBatch {
 mod tests {
     use datafusion::arrow::{
         array::{
-            Int32Builder, Time64MicrosecondBuilder, Time64NanosecondBuilder,
-            TimestampMicrosecondBuilder, TimestampNanosecondBuilder,
+            Int32Array, Int32Builder, StringArray, Time64MicrosecondBuilder,
+            Time64NanosecondBuilder, TimestampMicrosecondBuilder, TimestampNanosecondBuilder,
         },
-        datatypes::Schema,
+        datatypes::{i256, Schema},
     };
 
     use super::*;
@@ -253,6 +363,21 @@ mod tests {
                 literal: ScalarValue::Binary(Some(b"abc".to_vec())),
                 expected: Some("'616263'"),
             },
+            TestCase {
+                datasource: SqlServer,
+                literal: ScalarValue::Binary(Some(b"abc".to_vec())),
+                expected: Some("0x616263"),
+            },
+            TestCase {
+                datasource: SqlServer,
+                literal: ScalarValue::Utf8(Some("Ålesund's".to_string())),
+                expected: Some("N'Ålesund''s'"),
+            },
+            TestCase {
+                datasource: SqlServer,
+                literal: ScalarValue::Boolean(Some(true)),
+                expected: Some("1"),
+            },
             TestCase {
                 datasource: Postgres,
                 literal: ScalarValue::TimestampNanosecond(Some(938709124 * 1_000_000_000), None),
@@ -299,6 +424,23 @@ mod tests {
                 literal: ScalarValue::Decimal128(Some(123456), 38, 3),
                 expected: Some("123.456"),
             },
+            TestCase {
+                datasource: Postgres,
+                literal: ScalarValue::Decimal256(Some(i256::from_i128(-123456)), 50, 4),
+                expected: Some("-12.3456"),
+            },
+            TestCase {
+                datasource: Postgres,
+                literal: ScalarValue::FixedSizeBinary(
+                    16,
+                    Some(
+                        uuid::uuid!("292a485f-a56a-4938-8f1a-bbbbbbbbbbb1")
+                            .as_bytes()
+                            .to_vec(),
+                    ),
+                ),
+                expected: Some("'292a485f-a56a-4938-8f1a-bbbbbbbbbbb1'"),
+            },
         ];
 
         cases.into_iter().for_each(|case| {
@@ -424,4 +566,39 @@ mod tests {
         assert_eq!(res_batch.schema().fields(), &expected_fields.into());
         assert_eq!(res_batch.columns(), &expected_arrays);
     }
+
+    #[test]
+    fn test_encode_insert_values() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let batches = [
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(vec![Some(1), None])),
+                    Arc::new(StringArray::from(vec![Some("a"), Some("b")])),
+                ],
+            )
+            .unwrap(),
+            RecordBatch::try_new(
+                schema,
+                vec![
+                    Arc::new(Int32Array::from(vec![Some(3)])),
+                    Arc::new(StringArray::from(vec![None::<&str>])),
+                ],
+            )
+            .unwrap(),
+        ];
+
+        let lists = encode_insert_values(Datasource::Postgres, &batches, 2).unwrap();
+        assert_eq!(lists, vec!["(1,'a'),(NULL,'b')", "(3,NULL)"]);
+
+        let lists = encode_insert_values(Datasource::Postgres, &batches, 0).unwrap();
+        assert_eq!(lists, vec!["(1,'a'),(NULL,'b'),(3,NULL)"]);
+
+        let lists = encode_insert_values(Datasource::Postgres, &[], 2).unwrap();
+        assert!(lists.is_empty());
+    }
 }
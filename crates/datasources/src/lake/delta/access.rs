@@ -1,3 +1,4 @@
+use crate::lake::check_storage_options_egress;
 use crate::lake::delta::catalog::{DataCatalog, UnityCatalog};
 use crate::lake::delta::errors::Result;
 use deltalake::DeltaTable;
@@ -55,6 +56,8 @@ impl DeltaLakeAccessor {
 
 /// Loads the table at the given location.
 pub async fn load_table_direct(location: &str, opts: StorageOptions) -> Result<DeltaTable> {
+    check_storage_options_egress(&opts)?;
+
     // Convert to delta-rs compatible options
     let opts = HashMap::from_iter(opts.inner.into_iter());
     let table = deltalake::open_table_with_storage_options(location, opts).await?;
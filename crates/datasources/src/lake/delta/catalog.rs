@@ -2,10 +2,12 @@
 //!
 //! Most of this was copied in from the `deltalake` crate to make some
 //! modifications with how we construct clients, and what errors get returned.
+use crate::common::egress::check_egress_url;
 use crate::lake::delta::errors::{DeltaError, Result};
 use async_trait::async_trait;
 use reqwest::header;
 use serde::Deserialize;
+use url::Url;
 
 #[async_trait]
 pub trait DataCatalog: Sync + Send {
@@ -31,6 +33,8 @@ impl UnityCatalog {
         workspace_url: &str,
         catalog_id: &str,
     ) -> Result<Self> {
+        check_egress_url(&Url::parse(workspace_url)?).await?;
+
         let auth_header_val = header::HeaderValue::from_str(&format!("Bearer {}", &access_token))
             .map_err(|_| DeltaError::Static("Invalid Databricks access token"))?;
 
@@ -18,6 +18,9 @@ pub enum DeltaError {
     #[error(transparent)]
     UrlParse(#[from] url::ParseError),
 
+    #[error(transparent)]
+    Common(#[from] crate::common::errors::DatasourceCommonError),
+
     #[error("{0}")]
     Static(&'static str),
 }
@@ -12,6 +12,10 @@ use protogen::metastore::types::options::StorageOptions;
 use std::str::FromStr;
 use std::sync::Arc;
 
+use url::Url;
+
+use crate::common::egress::check_egress_url_blocking;
+use crate::common::errors::DatasourceCommonError;
 use crate::common::url::{DatasourceUrl, DatasourceUrlType};
 
 #[derive(Debug, thiserror::Error)]
@@ -29,6 +33,24 @@ pub enum LakeStorageOptionsError {
     UnsupportedObjectStore(DatasourceUrl),
 }
 
+/// Check endpoints configured in the storage options against the network
+/// policy.
+///
+/// Object stores for cloud providers otherwise only connect to the
+/// provider's hosts.
+pub fn check_storage_options_egress(opts: &StorageOptions) -> Result<(), DatasourceCommonError> {
+    for (key, value) in &opts.inner {
+        if let Ok(AmazonS3ConfigKey::Endpoint | AmazonS3ConfigKey::MetadataEndpoint) =
+            AmazonS3ConfigKey::from_str(key)
+        {
+            let endpoint =
+                Url::parse(value).map_err(|e| DatasourceCommonError::InvalidUrl(e.to_string()))?;
+            check_egress_url_blocking(&endpoint)?;
+        }
+    }
+    Ok(())
+}
+
 /// Create an object store from the provided storage options.
 pub fn storage_options_into_object_store(
     url: &DatasourceUrl,
@@ -42,6 +64,7 @@ pub fn storage_options_into_object_store(
 
             let mut store = AmazonS3Builder::new().with_bucket_name(bucket);
 
+            check_storage_options_egress(opts)?;
             for (key, value) in &opts.inner {
                 if let Ok(s3_key) = AmazonS3ConfigKey::from_str(key) {
                     store = store.with_config(s3_key, value);
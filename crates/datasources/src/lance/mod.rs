@@ -1,8 +1,11 @@
-use datafusion::error::Result;
+use datafusion::error::{DataFusionError, Result};
 use lance::{dataset::builder::DatasetBuilder, Dataset};
 use protogen::metastore::types::options::StorageOptions;
 
+use crate::lake::check_storage_options_egress;
+
 pub async fn scan_lance_table(location: &str, options: StorageOptions) -> Result<Dataset> {
+    check_storage_options_egress(&options).map_err(|e| DataFusionError::External(Box::new(e)))?;
     DatasetBuilder::from_uri(location)
         .with_storage_options(options.inner.into_iter().collect())
         .load()
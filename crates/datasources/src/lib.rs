@@ -13,5 +13,7 @@ pub mod mysql;
 pub mod native;
 pub mod object_store;
 pub mod postgres;
+pub mod redis;
 pub mod snowflake;
 pub mod sqlserver;
+pub mod xml;
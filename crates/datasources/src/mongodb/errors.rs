@@ -20,6 +20,18 @@ pub enum MongoDbError {
 
     #[error(transparent)]
     RawBson(#[from] mongodb::bson::raw::Error),
+
+    #[error("Tunnels require a connection string with a single host")]
+    TunnelRequiresSingleHost,
+
+    #[error("Tunneling TLS connections to MongoDB is not supported")]
+    TunnelWithTls,
+
+    #[error(transparent)]
+    Common(#[from] crate::common::errors::DatasourceCommonError),
+
+    #[error(transparent)]
+    Tunnel(#[from] crate::common::tunnel::TunnelError),
 }
 
 pub type Result<T, E = MongoDbError> = std::result::Result<T, E>;
@@ -24,6 +24,7 @@ use datafusion_ext::metrics::DataSourceMetricsStreamAdapter;
 
 use super::errors::{MongoDbError, Result};
 use crate::bson::builder::RecordStructBuilder;
+use crate::common::tunnel::TunnelSession;
 
 #[derive(Debug)]
 pub struct MongoDbBsonExec {
@@ -31,6 +32,7 @@ pub struct MongoDbBsonExec {
     schema: Arc<ArrowSchema>,
     limit: Option<usize>,
     metrics: ExecutionPlanMetricsSet,
+    tunnel: Option<Arc<TunnelSession>>,
 }
 
 impl MongoDbBsonExec {
@@ -38,12 +40,14 @@ impl MongoDbBsonExec {
         cursor: Mutex<Option<Cursor<RawDocumentBuf>>>,
         schema: Arc<ArrowSchema>,
         limit: Option<usize>,
+        tunnel: Option<Arc<TunnelSession>>,
     ) -> MongoDbBsonExec {
         MongoDbBsonExec {
             cursor,
             schema,
             limit,
             metrics: ExecutionPlanMetricsSet::new(),
+            tunnel,
         }
     }
 }
@@ -99,7 +103,7 @@ impl ExecutionPlan for MongoDbBsonExec {
         };
 
         Ok(Box::pin(DataSourceMetricsStreamAdapter::new(
-            BsonStream::new(cursor, self.schema.clone(), self.limit),
+            BsonStream::new(cursor, self.schema.clone(), self.limit, self.tunnel.clone()),
             partition,
             &self.metrics,
         )))
@@ -123,10 +127,17 @@ impl DisplayAs for MongoDbBsonExec {
 struct BsonStream {
     schema: Arc<ArrowSchema>,
     inner: Pin<Box<dyn Stream<Item = DatafusionResult<RecordBatch>> + Send>>,
+    /// Keeps the tunnel open until the stream is dropped.
+    _tunnel: Option<Arc<TunnelSession>>,
 }
 
 impl BsonStream {
-    fn new(cursor: Cursor<RawDocumentBuf>, schema: Arc<ArrowSchema>, limit: Option<usize>) -> Self {
+    fn new(
+        cursor: Cursor<RawDocumentBuf>,
+        schema: Arc<ArrowSchema>,
+        limit: Option<usize>,
+        tunnel: Option<Arc<TunnelSession>>,
+    ) -> Self {
         let schema_stream = schema.clone();
         let mut row_count = 0;
         // Build "inner" stream.
@@ -156,6 +167,7 @@ impl BsonStream {
         BsonStream {
             schema,
             inner: Box::pin(stream),
+            _tunnel: tunnel,
         }
     }
 }
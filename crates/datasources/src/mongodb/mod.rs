@@ -4,6 +4,8 @@ pub mod errors;
 mod exec;
 mod infer;
 
+use crate::common::egress::check_egress;
+use crate::common::tunnel::TunnelSession;
 use bson::RawBson;
 use datafusion_ext::errors::ExtensionError;
 use datafusion_ext::functions::VirtualLister;
@@ -22,9 +24,10 @@ use datafusion::physical_plan::ExecutionPlan;
 use datafusion::scalar::ScalarValue;
 use mongodb::bson::spec::BinarySubtype;
 use mongodb::bson::{bson, Binary, Bson, Document, RawDocumentBuf};
-use mongodb::options::{ClientOptions, FindOptions};
+use mongodb::options::{ClientOptions, FindOptions, ServerAddress, Tls};
 use mongodb::Client;
 use mongodb::Collection;
+use protogen::metastore::types::options::TunnelOptions;
 use std::any::Any;
 use std::fmt::{Display, Write};
 use std::str::FromStr;
@@ -35,6 +38,8 @@ use tracing::debug;
 /// needs to be done with the field when projecting.
 const ID_FIELD_NAME: &str = "_id";
 
+const DEFAULT_MONGODB_PORT: u16 = 27017;
+
 #[derive(Debug)]
 pub enum MongoDbProtocol {
     MongoDb,
@@ -123,19 +128,72 @@ impl MongoDbConnection {
 #[derive(Debug, Clone)]
 pub struct MongoDbAccessor {
     client: Client,
+    /// Session for the underlying tunnel.
+    ///
+    /// Kept around for as long as the client is used.
+    tunnel: Option<Arc<TunnelSession>>,
 }
 
 impl MongoDbAccessor {
-    pub async fn connect(connection_string: &str) -> Result<MongoDbAccessor> {
+    pub async fn connect(
+        connection_string: &str,
+        tunnel: Option<TunnelOptions>,
+    ) -> Result<MongoDbAccessor> {
         let mut opts = ClientOptions::parse(connection_string).await?;
+        for host in &opts.hosts {
+            match host {
+                ServerAddress::Tcp { host, port } => {
+                    check_egress(host, port.unwrap_or(DEFAULT_MONGODB_PORT)).await?
+                }
+                _ => check_egress("localhost", DEFAULT_MONGODB_PORT).await?,
+            }
+        }
         opts.app_name = Some("GlareDB (MongoDB Data source)".to_string());
+
+        let tunnel = match tunnel {
+            Some(tunnel) => Some(Arc::new(Self::open_tunnel(&mut opts, &tunnel).await?)),
+            None => None,
+        };
         let client = Client::with_options(opts)?;
 
-        Ok(MongoDbAccessor { client })
+        Ok(MongoDbAccessor { client, tunnel })
     }
 
-    pub async fn validate_external_database(connection_string: &str) -> Result<()> {
-        let accessor = Self::connect(connection_string).await?;
+    /// Open a tunnel to the server and point the client options at it.
+    ///
+    /// A tunnel forwards to a single server, so the connection string must
+    /// contain a single host, and the client connects directly to that server
+    /// instead of discovering the rest of the replica set. TLS isn't supported
+    /// since the server's certificate wouldn't match the tunnel's address.
+    async fn open_tunnel(
+        opts: &mut ClientOptions,
+        tunnel: &TunnelOptions,
+    ) -> Result<TunnelSession> {
+        let (host, port) = match opts.hosts.as_slice() {
+            [ServerAddress::Tcp { host, port }] => {
+                (host.clone(), port.unwrap_or(DEFAULT_MONGODB_PORT))
+            }
+            _ => return Err(MongoDbError::TunnelRequiresSingleHost),
+        };
+        if matches!(opts.tls, Some(Tls::Enabled(_))) {
+            return Err(MongoDbError::TunnelWithTls);
+        }
+
+        let (session, addr) = TunnelSession::open(tunnel, &host, port).await?;
+        opts.hosts = vec![ServerAddress::Tcp {
+            host: addr.ip().to_string(),
+            port: Some(addr.port()),
+        }];
+        opts.direct_connection = Some(true);
+
+        Ok(session)
+    }
+
+    pub async fn validate_external_database(
+        connection_string: &str,
+        tunnel: Option<TunnelOptions>,
+    ) -> Result<()> {
+        let accessor = Self::connect(connection_string, tunnel).await?;
         let mut filter = Document::new();
         filter.insert("name".to_string(), Bson::String("glaredb".to_string()));
         let _ = accessor
@@ -149,6 +207,7 @@ impl MongoDbAccessor {
         MongoDbTableAccessor {
             info,
             client: self.client,
+            tunnel: self.tunnel,
         }
     }
 }
@@ -208,6 +267,7 @@ pub struct MongoDbTableAccessInfo {
 pub struct MongoDbTableAccessor {
     info: MongoDbTableAccessInfo,
     client: Client,
+    tunnel: Option<Arc<TunnelSession>>,
 }
 
 impl MongoDbTableAccessor {
@@ -238,6 +298,7 @@ impl MongoDbTableAccessor {
                 .client
                 .database(&self.info.database)
                 .collection(&self.info.collection),
+            tunnel: self.tunnel,
         })
     }
 }
@@ -245,6 +306,7 @@ impl MongoDbTableAccessor {
 pub struct MongoDbTableProvider {
     schema: Arc<ArrowSchema>,
     collection: Collection<RawDocumentBuf>,
+    tunnel: Option<Arc<TunnelSession>>,
 }
 
 #[async_trait]
@@ -318,7 +380,12 @@ impl TableProvider for MongoDbTableProvider {
                 .await
                 .map_err(|e| DataFusionError::External(Box::new(e)))?,
         ));
-        Ok(Arc::new(MongoDbBsonExec::new(cursor, schema, limit)))
+        Ok(Arc::new(MongoDbBsonExec::new(
+            cursor,
+            schema,
+            limit,
+            self.tunnel.clone(),
+        )))
     }
 }
 
@@ -390,6 +457,10 @@ fn df_to_bson(val: ScalarValue) -> Result<Bson, ExtensionError> {
             subtype: BinarySubtype::Generic,
             bytes: v.unwrap_or_default(),
         })),
+        ScalarValue::FixedSizeBinary(16, v) => Ok(Bson::Binary(Binary {
+            subtype: BinarySubtype::Uuid,
+            bytes: v.unwrap_or_default(),
+        })),
         ScalarValue::FixedSizeBinary(_, v) => Ok(Bson::Binary(Binary {
             subtype: BinarySubtype::Generic,
             bytes: v.unwrap_or_default(),
@@ -6,9 +6,6 @@ pub enum MysqlError {
     #[error("Unable to convert mysql row value for column {0}: {1}, datatype: {2}")]
     UnsupportedArrowType(usize, String, datafusion::arrow::datatypes::DataType),
 
-    #[error("Unsupported tunnel '{0}' for MySQL")]
-    UnsupportedTunnel(String),
-
     #[error(transparent)]
     Arrow(#[from] datafusion::arrow::error::ArrowError),
 
@@ -34,9 +31,7 @@ pub enum MysqlError {
     Common(#[from] crate::common::errors::DatasourceCommonError),
 
     #[error(transparent)]
-    SshKey(#[from] crate::common::ssh::key::SshKeyError),
-    #[error(transparent)]
-    SshTunnel(#[from] crate::common::ssh::session::SshTunnelError),
+    Tunnel(#[from] crate::common::tunnel::TunnelError),
 }
 
 pub type Result<T, E = MysqlError> = std::result::Result<T, E>;
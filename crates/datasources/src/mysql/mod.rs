@@ -6,8 +6,8 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use crate::common::ssh::session::SshTunnelSession;
-use crate::common::ssh::{key::SshKey, session::SshTunnelAccess};
+use crate::common::egress::check_egress;
+use crate::common::tunnel::TunnelSession;
 use crate::common::util::{self, create_count_record_batch, COUNT_SCHEMA};
 use async_stream::stream;
 use async_trait::async_trait;
@@ -24,10 +24,9 @@ use datafusion::physical_expr::PhysicalSortExpr;
 use datafusion::physical_plan::memory::MemoryExec;
 use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
 use datafusion::physical_plan::{
-    execute_stream, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
+    collect, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
     SendableRecordBatchStream, Statistics,
 };
-use datafusion::scalar::ScalarValue;
 use datafusion_ext::errors::ExtensionError;
 use datafusion_ext::functions::VirtualLister;
 use datafusion_ext::metrics::DataSourceMetricsStreamAdapter;
@@ -98,40 +97,35 @@ pub struct MysqlTableAccess {
 #[derive(Debug)]
 pub struct MysqlAccessor {
     conn: RwLock<Conn>,
-    /// `Session` for the underlying ssh tunnel
+    /// Session for the underlying tunnel.
     ///
-    /// Kept on struct to avoid dropping ssh tunnel
-    _ssh_tunnel: Option<SshTunnelSession>,
+    /// Kept on struct to avoid dropping the tunnel.
+    _tunnel: Option<TunnelSession>,
 }
 
 impl MysqlAccessor {
     /// Connect to a mysql instance.
     pub async fn connect(connection_string: &str, tunnel: Option<TunnelOptions>) -> Result<Self> {
-        let (conn, _ssh_tunnel) = Self::connect_internal(connection_string, tunnel).await?;
+        let (conn, _tunnel) = Self::connect_internal(connection_string, tunnel).await?;
         let conn = RwLock::new(conn);
 
-        Ok(Self { conn, _ssh_tunnel })
+        Ok(Self { conn, _tunnel })
     }
 
     async fn connect_internal(
         connection_string: &str,
         tunnel: Option<TunnelOptions>,
-    ) -> Result<(Conn, Option<SshTunnelSession>)> {
+    ) -> Result<(Conn, Option<TunnelSession>)> {
+        let opts = Opts::from_url(connection_string)?;
+        check_egress(opts.ip_or_hostname(), opts.tcp_port()).await?;
+
         match tunnel {
             None => Self::connect_direct(connection_string).await,
-            Some(TunnelOptions::Ssh(ssh_options)) => {
-                let keypair = SshKey::from_bytes(&ssh_options.ssh_key)?;
-                let access = SshTunnelAccess {
-                    connection_string: ssh_options.connection_string,
-                    keypair,
-                };
-                Self::connect_with_ssh_tunnel(connection_string, access).await
-            }
-            Some(opt) => Err(MysqlError::UnsupportedTunnel(opt.to_string())),
+            Some(tunnel) => Self::connect_with_tunnel(connection_string, &tunnel).await,
         }
     }
 
-    async fn connect_direct(connection_string: &str) -> Result<(Conn, Option<SshTunnelSession>)> {
+    async fn connect_direct(connection_string: &str) -> Result<(Conn, Option<TunnelSession>)> {
         let database_url = connection_string;
 
         let opts = Opts::from_url(database_url)?;
@@ -140,18 +134,17 @@ impl MysqlAccessor {
         Ok((conn, None))
     }
 
-    async fn connect_with_ssh_tunnel(
+    async fn connect_with_tunnel(
         connection_string: &str,
-        ssh_tunnel: SshTunnelAccess,
-    ) -> Result<(Conn, Option<SshTunnelSession>)> {
+        tunnel: &TunnelOptions,
+    ) -> Result<(Conn, Option<TunnelSession>)> {
         let database_url = connection_string;
         let opts = Opts::from_url(database_url)?;
 
         let mysql_host = opts.ip_or_hostname();
         let mysql_port = opts.tcp_port();
 
-        // Open ssh tunnel
-        let (session, tunnel_addr) = ssh_tunnel.create_tunnel(&(mysql_host, mysql_port)).await?;
+        let (session, tunnel_addr) = TunnelSession::open(tunnel, mysql_host, mysql_port).await?;
 
         let opts: Opts = OptsBuilder::from_opts(opts)
             .ip_or_hostname(tunnel_addr.ip().to_string())
@@ -169,7 +162,7 @@ impl MysqlAccessor {
         connection_string: &str,
         tunnel: Option<TunnelOptions>,
     ) -> Result<()> {
-        let (mut conn, _ssh_tunnel) = Self::connect_internal(connection_string, tunnel).await?;
+        let (mut conn, _tunnel) = Self::connect_internal(connection_string, tunnel).await?;
 
         conn.query_drop("SELECT 1").await?;
         Ok(())
@@ -181,7 +174,7 @@ impl MysqlAccessor {
         access: &MysqlTableAccess,
         tunnel: Option<TunnelOptions>,
     ) -> Result<()> {
-        let (mut conn, _ssh_tunnel) = Self::connect_internal(connection_string, tunnel).await?;
+        let (mut conn, _tunnel) = Self::connect_internal(connection_string, tunnel).await?;
 
         let query = format!(
             "SELECT * FROM {}.{} where false",
@@ -377,7 +370,7 @@ impl TableProvider for MysqlTableProvider {
             predicate: predicate_string,
             table_access: self.table_access.clone(),
             accessor: self.accessor.clone(),
-            query,
+            queries: vec![query],
             arrow_schema: projected_schema,
             metrics: ExecutionPlanMetricsSet::new(),
             query_type: QueryType::Dql,
@@ -390,30 +383,13 @@ impl TableProvider for MysqlTableProvider {
         input: Arc<dyn ExecutionPlan>,
         _overwrite: bool,
     ) -> DatafusionResult<Arc<dyn ExecutionPlan>> {
-        let mut values = String::new();
-
-        let mut input = execute_stream(input, state.task_ctx())?;
-        while let Some(batch) = input.next().await {
-            let batch = batch?;
-
-            let mut row_sep = "";
-            for row_idx in 0..batch.num_rows() {
-                write!(&mut values, "{row_sep}(")?;
-                row_sep = ",";
-
-                let mut col_sep = "";
-                for col in batch.columns() {
-                    write!(&mut values, "{col_sep}")?;
-                    col_sep = ",";
-
-                    let val = ScalarValue::try_from_array(col.as_ref(), row_idx)?;
-                    util::encode_literal_to_text(util::Datasource::MySql, &mut values, &val)
-                        .map_err(|e| DataFusionError::External(Box::new(e)))?;
-                }
-
-                write!(&mut values, ")")?;
-            }
-        }
+        let batches = collect(input, state.task_ctx()).await?;
+        let values = util::encode_insert_values(
+            util::Datasource::MySql,
+            &batches,
+            util::external_insert_batch_size(state),
+        )
+        .map_err(|e| DataFusionError::External(Box::new(e)))?;
 
         if values.is_empty() {
             let batch = create_count_record_batch(0);
@@ -421,18 +397,23 @@ impl TableProvider for MysqlTableProvider {
             return Ok(Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?));
         }
 
-        let query = format!(
-            "INSERT INTO {}.{} VALUES {}",
-            self.table_access.schema, self.table_access.name, values
-        );
+        let queries = values
+            .into_iter()
+            .map(|values| {
+                format!(
+                    "INSERT INTO {}.{} VALUES {}",
+                    self.table_access.schema, self.table_access.name, values
+                )
+            })
+            .collect::<Vec<_>>();
 
-        debug!(%query, "inserting into mysql datasource");
+        debug!(num_statements = %queries.len(), "inserting into mysql datasource");
 
         Ok(Arc::new(MysqlExec {
             predicate: "".to_string(),
             table_access: self.table_access.clone(),
             accessor: self.accessor.clone(),
-            query,
+            queries,
             arrow_schema: COUNT_SCHEMA.clone(),
             metrics: ExecutionPlanMetricsSet::new(),
             query_type: QueryType::Dml,
@@ -445,7 +426,8 @@ struct MysqlExec {
     predicate: String,
     table_access: MysqlTableAccess,
     accessor: Arc<MysqlAccessor>,
-    query: String,
+    /// Queries run in order. DML queries are run in a single transaction.
+    queries: Vec<String>,
     arrow_schema: ArrowSchemaRef,
     metrics: ExecutionPlanMetricsSet,
     query_type: QueryType,
@@ -487,7 +469,7 @@ impl ExecutionPlan for MysqlExec {
         _context: Arc<TaskContext>,
     ) -> DatafusionResult<SendableRecordBatchStream> {
         let stream = MysqlQueryStream::open(
-            self.query.clone(),
+            self.queries.clone(),
             self.accessor.clone(),
             self.arrow_schema.clone(),
             self.query_type.clone(),
@@ -537,7 +519,7 @@ impl MysqlQueryStream {
     const MYSQL_RECORD_BATCH_SIZE: usize = 1000;
 
     fn open(
-        query: String,
+        queries: Vec<String>,
         accessor: Arc<MysqlAccessor>,
         arrow_schema: ArrowSchemaRef,
         query_type: QueryType,
@@ -559,36 +541,50 @@ impl MysqlQueryStream {
                         .await
                         .map_err(|e| DataFusionError::External(Box::new(e)))?;
 
-                    let query_stream = tx
-                            .exec_stream::<MysqlRow, _, _>(query, ())
-                            .await
-                            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+                    for query in queries {
+                        let query_stream = tx
+                                .exec_stream::<MysqlRow, _, _>(query, ())
+                                .await
+                                .map_err(|e| DataFusionError::External(Box::new(e)))?;
 
-                    let mut chunks = query_stream.try_chunks(Self::MYSQL_RECORD_BATCH_SIZE).boxed();
+                        let mut chunks = query_stream.try_chunks(Self::MYSQL_RECORD_BATCH_SIZE).boxed();
 
-                    while let Some(rows) = chunks
-                        .try_next()
-                        .await
-                        .map_err(|e| DataFusionError::External(Box::new(e)))?
-                    {
-                        let record_batch = mysql_row_to_record_batch(rows, arrow_schema.clone())
-                            .map_err(|e| DataFusionError::External(Box::new(e)));
-                        yield record_batch;
+                        while let Some(rows) = chunks
+                            .try_next()
+                            .await
+                            .map_err(|e| DataFusionError::External(Box::new(e)))?
+                        {
+                            let record_batch = mysql_row_to_record_batch(rows, arrow_schema.clone())
+                                .map_err(|e| DataFusionError::External(Box::new(e)));
+                            yield record_batch;
+                        }
+
+                        // Drop the empty stream once all chunks are processed. This allows us to close
+                        // the MySQL transaction
+                        drop(chunks);
                     }
-
-                    // Drop the empty stream once all chunks are processed. This allows us to close
-                    // the MySQL transaction
-                    drop(chunks);
                     tx.commit()
                         .await
                         .map_err(|e| DataFusionError::External(Box::new(e)))?;
                 },
                 QueryType::Dml => {
-                    conn.exec::<MysqlRow, _, _>(query, ())
+                    let mut tx = conn
+                        .start_transaction(TxOpts::new())
+                        .await
+                        .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+                    let mut count = 0;
+                    for query in queries {
+                        tx.exec_drop(query, ())
+                            .await
+                            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+                        count += tx.affected_rows();
+                    }
+
+                    tx.commit()
                         .await
                         .map_err(|e| DataFusionError::External(Box::new(e)))?;
 
-                    let count = conn.affected_rows();
                     let record_batch = create_count_record_batch(count);
 
                     yield Ok(record_batch);
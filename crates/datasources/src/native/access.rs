@@ -2,6 +2,8 @@ use crate::native::errors::{NativeError, Result};
 use crate::native::insert::NativeTableInsertExec;
 use async_trait::async_trait;
 use datafusion::arrow::datatypes::{DataType, Schema as ArrowSchema, TimeUnit};
+use datafusion::common::Column;
+use datafusion::dataframe::DataFrame;
 use datafusion::datasource::TableProvider;
 use datafusion::error::Result as DataFusionResult;
 use datafusion::execution::context::SessionState;
@@ -9,10 +11,11 @@ use datafusion::execution::context::SessionState;
 use datafusion::logical_expr::{LogicalPlan, TableProviderFilterPushDown, TableType};
 use datafusion::physical_plan::empty::EmptyExec;
 use datafusion::physical_plan::{ExecutionPlan, Statistics};
-use datafusion::prelude::Expr;
+use datafusion::prelude::{col, count, lit, Expr};
 use datafusion_ext::metrics::ReadOnlyDataSourceMetricsExecAdapter;
 use deltalake::operations::create::CreateBuilder;
 use deltalake::operations::delete::DeleteBuilder;
+use deltalake::operations::merge::MergeBuilder;
 use deltalake::operations::update::UpdateBuilder;
 use deltalake::storage::DeltaObjectStore;
 use deltalake::{DeltaTable, DeltaTableConfig};
@@ -32,6 +35,10 @@ use uuid::Uuid;
 
 pub use deltalake::protocol::SaveMode;
 
+/// Qualifier for the inserted row in expressions passed to
+/// [`NativeTableStorage::upsert_rows`].
+pub const UPSERT_SOURCE_QUALIFIER: &str = "excluded";
+
 #[derive(Debug, Clone)]
 pub struct NativeTableStorage {
     db_id: Uuid,
@@ -221,6 +228,112 @@ impl NativeTableStorage {
         let updated_rows = builder.await?.1.num_updated_rows;
         Ok(updated_rows)
     }
+
+    /// Insert rows from `source`, or update the existing rows they conflict
+    /// with.
+    ///
+    /// Rows conflict if they're equal on all of the `conflict_columns`.
+    /// Conflicting rows are left as is if `updates` is `None`, and `where_expr`
+    /// limits which conflicting rows are updated. Expressions reference the
+    /// existing row qualified by the table's name, and the inserted row
+    /// qualified by [`UPSERT_SOURCE_QUALIFIER`].
+    ///
+    /// Returns the number of rows inserted or updated.
+    pub async fn upsert_rows(
+        &self,
+        table: &TableEntry,
+        source: DataFrame,
+        conflict_columns: &[String],
+        updates: Option<Vec<(String, Expr)>>,
+        where_expr: Option<Expr>,
+    ) -> Result<usize> {
+        let target = table.meta.name.as_str();
+        let column_names: Vec<_> = source
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect();
+
+        let predicate = conflict_columns
+            .iter()
+            .map(|name| {
+                col(Column::new(Some(target), name))
+                    .eq(col(Column::new(Some(UPSERT_SOURCE_QUALIFIER), name)))
+            })
+            .reduce(Expr::and)
+            .ok_or(NativeError::Static("Missing conflict columns for upsert"))?;
+
+        // Which of the duplicates would be inserted or applied last is
+        // undefined, reject them the same as Postgres.
+        if has_duplicate_keys(&source, conflict_columns).await? {
+            return Err(NativeError::Static(if updates.is_some() {
+                "ON CONFLICT DO UPDATE command cannot affect row a second time"
+            } else {
+                "ON CONFLICT DO NOTHING command cannot affect row a second time"
+            }));
+        }
+
+        let table = self.load_table(table).await?;
+        let mut builder = MergeBuilder::new(
+            table.delta.object_store(),
+            table.delta.state,
+            predicate,
+            source,
+        )
+        .with_source_alias(UPSERT_SOURCE_QUALIFIER)
+        .with_target_alias(target);
+
+        if let Some(updates) = updates {
+            builder = builder.when_matched_update(|mut update| {
+                for (column, expr) in updates {
+                    update = update.update(column, expr);
+                }
+                match where_expr {
+                    Some(where_expr) => update.predicate(where_expr),
+                    None => update,
+                }
+            })?;
+        }
+        builder = builder.when_not_matched_insert(|mut insert| {
+            for name in column_names {
+                let value = col(Column::new(Some(UPSERT_SOURCE_QUALIFIER), &name));
+                insert = insert.set(name, value);
+            }
+            insert
+        })?;
+
+        let metrics = builder.await?.1;
+        Ok(metrics.num_target_rows_inserted + metrics.num_target_rows_updated)
+    }
+}
+
+/// Check if any two rows of the source have the same values for the conflict
+/// columns.
+///
+/// Rows with a null conflict column never conflict, so they aren't
+/// duplicates either.
+async fn has_duplicate_keys(source: &DataFrame, conflict_columns: &[String]) -> Result<bool> {
+    let keys: Vec<_> = conflict_columns
+        .iter()
+        .map(|name| col(Column::from_name(name)))
+        .collect();
+    let not_null = keys
+        .iter()
+        .cloned()
+        .map(Expr::is_not_null)
+        .reduce(Expr::and)
+        .ok_or(NativeError::Static("Missing conflict columns for upsert"))?;
+
+    let num_duplicates = source
+        .clone()
+        .filter(not_null)?
+        .aggregate(keys, vec![count(lit(1)).alias("num_rows")])?
+        .filter(col("num_rows").gt(lit(1)))?
+        .limit(0, Some(1))?
+        .count()
+        .await?;
+    Ok(num_duplicates > 0)
 }
 
 #[derive(Debug)]
@@ -237,6 +350,18 @@ impl NativeTable {
         self.delta.table_uri()
     }
 
+    /// Version of the table this was loaded at.
+    pub fn version(&self) -> i64 {
+        self.delta.version()
+    }
+
+    /// Get the latest version of the table in storage, which may be newer
+    /// than the version this was loaded at.
+    pub async fn latest_version(&self) -> Result<i64> {
+        let mut delta = self.delta.clone();
+        Ok(delta.get_latest_version().await?)
+    }
+
     pub fn into_table_provider(self) -> Arc<dyn TableProvider> {
         Arc::new(self)
     }
@@ -334,6 +459,7 @@ mod tests {
         catalog::{EntryMeta, EntryType, SourceAccessMode, TableEntry},
         options::{InternalColumnDefinition, TableOptions, TableOptionsInternal},
     };
+    use std::collections::BTreeMap;
     use tempfile::tempdir;
     use url::Url;
     use uuid::Uuid;
@@ -375,6 +501,8 @@ mod tests {
             }),
             tunnel_id: None,
             access_mode: SourceAccessMode::ReadOnly,
+            column_comments: BTreeMap::new(),
+            tags: BTreeMap::new(),
         };
 
         // Create a table, load it, delete it and load it again!
@@ -0,0 +1,399 @@
+//! Reading CSV files that don't parse cleanly with the defaults.
+//!
+//! DataFusion's CSV reader handles custom delimiters and quoting, but fails
+//! the whole scan on the first malformed line and only reads UTF-8. Files
+//! that need more than that are decoded and cleaned up front, keeping the
+//! lines that parse, and read into memory.
+use std::io::Cursor;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{new_null_array, ArrayRef, StringArray};
+use datafusion::arrow::csv::reader::Format;
+use datafusion::arrow::csv::ReaderBuilder;
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::datasource::file_format::csv::CsvFormat;
+use datafusion::datasource::{MemTable, TableProvider};
+use datafusion::execution::context::SessionState;
+use datafusion_ext::warnings::{push_warning, Warning};
+use object_store::{ObjectMeta, ObjectStore};
+
+use super::errors::{ObjectStoreSourceError, Result};
+use super::inference::SchemaInference;
+
+/// Name of the column holding the raw text of bad lines when they're stored.
+pub const BAD_LINE_COLUMN: &str = "_bad_line";
+
+/// What to do with lines that can't be read, e.g. lines with the wrong number
+/// of fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnBadLines {
+    /// Fail the query.
+    #[default]
+    Error,
+    /// Skip the line.
+    Skip,
+    /// Keep the raw line in the `_bad_line` column, with all other columns
+    /// null.
+    Store,
+}
+
+impl FromStr for OnBadLines {
+    type Err = ObjectStoreSourceError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "error" => Self::Error,
+            "skip" => Self::Skip,
+            "store" => Self::Store,
+            other => {
+                return Err(ObjectStoreSourceError::InvalidCsv(format!(
+                    "invalid value for on_bad_lines: '{other}', expected 'error', 'skip' or 'store'"
+                )))
+            }
+        })
+    }
+}
+
+/// Character encoding of a CSV file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CsvEncoding {
+    #[default]
+    Utf8,
+    /// ISO-8859-1.
+    Latin1,
+    /// UTF-16 with the byte order taken from the byte order mark, little
+    /// endian if there is none.
+    Utf16,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl FromStr for CsvEncoding {
+    type Err = ObjectStoreSourceError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().replace('_', "-").as_str() {
+            "utf8" | "utf-8" => Self::Utf8,
+            "latin1" | "latin-1" | "iso-8859-1" => Self::Latin1,
+            "utf16" | "utf-16" => Self::Utf16,
+            "utf16le" | "utf-16le" => Self::Utf16Le,
+            "utf16be" | "utf-16be" => Self::Utf16Be,
+            other => {
+                return Err(ObjectStoreSourceError::InvalidCsv(format!(
+                    "unsupported encoding: '{other}', expected 'utf-8', 'latin-1' or 'utf-16'"
+                )))
+            }
+        })
+    }
+}
+
+/// Options for reading CSV files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvReadOptions {
+    pub delimiter: u8,
+    pub quote: u8,
+    /// Character escaping quotes inside quoted fields. Quotes are escaped by
+    /// doubling them if not set.
+    pub escape: Option<u8>,
+    pub on_bad_lines: OnBadLines,
+    /// Lines longer than this (in bytes) are bad lines. Guards against an
+    /// unterminated quote swallowing the rest of the file.
+    pub max_line_length: Option<usize>,
+    pub encoding: CsvEncoding,
+}
+
+impl Default for CsvReadOptions {
+    fn default() -> Self {
+        CsvReadOptions {
+            delimiter: b',',
+            quote: b'"',
+            escape: None,
+            on_bad_lines: OnBadLines::Error,
+            max_line_length: None,
+            encoding: CsvEncoding::Utf8,
+        }
+    }
+}
+
+impl CsvReadOptions {
+    /// Whether files have to be cleaned with [`read_csv_table`] instead of
+    /// being read with the [`CsvFormat`] from [`Self::with_format_options`].
+    pub fn requires_cleaning(&self) -> bool {
+        self.on_bad_lines != OnBadLines::Error
+            || self.max_line_length.is_some()
+            || self.encoding != CsvEncoding::Utf8
+    }
+
+    /// Set the options supported by DataFusion on the format.
+    pub fn with_format_options(&self, format: CsvFormat) -> CsvFormat {
+        format
+            .with_delimiter(self.delimiter)
+            .with_quote(self.quote)
+            .with_escape(self.escape)
+    }
+}
+
+/// Read (uncompressed) CSV files into memory, decoding and dropping bad lines
+/// according to `opts`.
+///
+/// When skipping bad lines, a warning with the number of skipped lines is
+/// pushed for every file that had any.
+pub async fn read_csv_table(
+    state: &SessionState,
+    store: &Arc<dyn ObjectStore>,
+    objects: &[ObjectMeta],
+    opts: &CsvReadOptions,
+    inference: &SchemaInference,
+) -> Result<Arc<dyn TableProvider>> {
+    let mut header: Option<::csv::ByteRecord> = None;
+    let mut records = Vec::new();
+    let mut bad_lines = Vec::new();
+
+    for object in objects {
+        let bytes = store.get(&object.location).await?.bytes().await?;
+        let text = decode(&bytes, opts.encoding)?;
+        let file = parse_records(&text, opts)?;
+
+        match (header.as_ref().map(|h| h.len()), file.header) {
+            (None, file_header) => header = file_header,
+            (Some(expected), Some(file_header)) if expected != file_header.len() => {
+                return Err(ObjectStoreSourceError::InvalidCsv(format!(
+                    "{} has {} columns, expected {expected}",
+                    object.location,
+                    file_header.len(),
+                )))
+            }
+            _ => {}
+        }
+
+        if opts.on_bad_lines == OnBadLines::Skip && !file.bad_lines.is_empty() {
+            push_warning(
+                state.config(),
+                Warning::new(
+                    object.location.to_string(),
+                    format!("skipped {} bad lines", file.bad_lines.len()),
+                ),
+            );
+        }
+        records.extend(file.records);
+        bad_lines.extend(file.bad_lines);
+    }
+
+    let header = header.ok_or(ObjectStoreSourceError::Static("CSV files are empty"))?;
+    let data = normalize(&header, &records)?;
+
+    let (schema, _) = Format::default()
+        .with_header(true)
+        .infer_schema(Cursor::new(&data), Some(inference.csv_infer_rows()))?;
+    let schema = inference.apply(&schema)?;
+    let batches = ReaderBuilder::new(schema.clone())
+        .with_header(true)
+        .build(Cursor::new(data))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (schema, batches) = match opts.on_bad_lines {
+        OnBadLines::Store => store_bad_lines(schema, batches, bad_lines)?,
+        _ => (schema, batches),
+    };
+
+    Ok(Arc::new(MemTable::try_new(schema, vec![batches])?))
+}
+
+/// Records of a CSV file.
+#[derive(Debug, Default)]
+struct ParsedRecords {
+    header: Option<::csv::ByteRecord>,
+    records: Vec<::csv::ByteRecord>,
+    /// Raw text of lines that couldn't be read.
+    bad_lines: Vec<String>,
+}
+
+/// Parse the records of a file, separating out bad lines.
+///
+/// A line is bad if it has a different number of fields than the header, or
+/// is longer than the max line length.
+fn parse_records(text: &str, opts: &CsvReadOptions) -> Result<ParsedRecords> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(opts.delimiter)
+        .quote(opts.quote)
+        .escape(opts.escape)
+        .double_quote(opts.escape.is_none())
+        .from_reader(text.as_bytes());
+
+    let mut parsed = ParsedRecords::default();
+    let mut record = ::csv::ByteRecord::new();
+    let mut start = 0;
+    while reader.read_byte_record(&mut record)? {
+        let end = reader.position().byte() as usize;
+        let line = text
+            .get(start..end)
+            .unwrap_or_default()
+            .trim_matches(['\r', '\n']);
+        start = end;
+
+        let header = match &parsed.header {
+            Some(header) => header,
+            None => {
+                parsed.header = Some(record.clone());
+                continue;
+            }
+        };
+
+        let problem = if record.len() != header.len() {
+            Some(format!(
+                "expected {} fields, found {}",
+                header.len(),
+                record.len()
+            ))
+        } else {
+            opts.max_line_length
+                .filter(|max| line.len() > *max)
+                .map(|max| format!("line is longer than {max} bytes"))
+        };
+
+        match problem {
+            None => parsed.records.push(record.clone()),
+            Some(problem) if opts.on_bad_lines == OnBadLines::Error => {
+                let line_num = record.position().map(|pos| pos.line()).unwrap_or_default();
+                return Err(ObjectStoreSourceError::InvalidCsv(format!(
+                    "bad line {line_num}: {problem}, set on_bad_lines to 'skip' or 'store' to read the other lines"
+                )));
+            }
+            Some(_) => parsed.bad_lines.push(line.to_string()),
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Write records as standard CSV that can be read by arrow.
+fn normalize(header: &::csv::ByteRecord, records: &[::csv::ByteRecord]) -> Result<Vec<u8>> {
+    let mut writer = ::csv::WriterBuilder::new().from_writer(Vec::new());
+    writer.write_byte_record(header)?;
+    for record in records {
+        writer.write_byte_record(record)?;
+    }
+    writer.into_inner().map_err(|e| e.into_error().into())
+}
+
+/// Add the `_bad_line` column to the batches, and a batch with the bad lines.
+fn store_bad_lines(
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+    bad_lines: Vec<String>,
+) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+    let mut fields: Vec<_> = schema.fields().iter().cloned().collect();
+    fields.push(Arc::new(Field::new(BAD_LINE_COLUMN, DataType::Utf8, true)));
+    let stored_schema = Arc::new(Schema::new(fields));
+
+    let mut stored = Vec::with_capacity(batches.len() + 1);
+    for batch in batches {
+        let mut columns = batch.columns().to_vec();
+        columns.push(new_null_array(&DataType::Utf8, batch.num_rows()));
+        stored.push(RecordBatch::try_new(stored_schema.clone(), columns)?);
+    }
+
+    if !bad_lines.is_empty() {
+        let mut columns: Vec<ArrayRef> = schema
+            .fields()
+            .iter()
+            .map(|f| new_null_array(f.data_type(), bad_lines.len()))
+            .collect();
+        columns.push(Arc::new(StringArray::from(bad_lines)));
+        stored.push(RecordBatch::try_new(stored_schema.clone(), columns)?);
+    }
+
+    Ok((stored_schema, stored))
+}
+
+/// Decode the contents of a file to UTF-8, dropping any byte order mark.
+fn decode(bytes: &[u8], encoding: CsvEncoding) -> Result<String> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        ObjectStoreSourceError::InvalidCsv(format!("file is not valid {encoding:?}: {e}"))
+    };
+
+    let (big_endian, body) = match (encoding, bytes) {
+        (CsvEncoding::Utf8, bytes) => {
+            let body = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+            return String::from_utf8(body.to_vec()).map_err(|e| invalid(&e));
+        }
+        (CsvEncoding::Latin1, bytes) => return Ok(bytes.iter().map(|b| *b as char).collect()),
+        (CsvEncoding::Utf16 | CsvEncoding::Utf16Le, [0xFF, 0xFE, body @ ..]) => (false, body),
+        (CsvEncoding::Utf16 | CsvEncoding::Utf16Be, [0xFE, 0xFF, body @ ..]) => (true, body),
+        (CsvEncoding::Utf16Be, body) => (true, body),
+        (_, body) => (false, body),
+    };
+
+    if body.len() % 2 != 0 {
+        return Err(invalid(&"odd number of bytes"));
+    }
+    let units = body.chunks_exact(2).map(|unit| {
+        let unit = [unit[0], unit[1]];
+        if big_endian {
+            u16::from_be_bytes(unit)
+        } else {
+            u16::from_le_bytes(unit)
+        }
+    });
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|e| invalid(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_encodings() {
+        assert_eq!(
+            "a,b",
+            decode(b"\xEF\xBB\xBFa,b", CsvEncoding::Utf8).unwrap()
+        );
+        assert_eq!("café", decode(b"caf\xE9", CsvEncoding::Latin1).unwrap());
+        assert!(decode(b"caf\xE9", CsvEncoding::Utf8).is_err());
+
+        let le: Vec<u8> = "é,b".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let be: Vec<u8> = "é,b".encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!("é,b", decode(&le, CsvEncoding::Utf16).unwrap());
+        assert_eq!("é,b", decode(&be, CsvEncoding::Utf16Be).unwrap());
+        let with_bom = [&[0xFE, 0xFF], be.as_slice()].concat();
+        assert_eq!("é,b", decode(&with_bom, CsvEncoding::Utf16).unwrap());
+    }
+
+    #[test]
+    fn separate_bad_lines() {
+        let text = "a,b\n1,2\n3\n4,5,6\n7,8\n";
+        let opts = CsvReadOptions {
+            on_bad_lines: OnBadLines::Skip,
+            ..Default::default()
+        };
+        let parsed = parse_records(text, &opts).unwrap();
+        assert_eq!(2, parsed.records.len());
+        assert_eq!(vec!["3", "4,5,6"], parsed.bad_lines);
+
+        let err = parse_records(text, &CsvReadOptions::default()).unwrap_err();
+        assert!(err.to_string().contains("bad line 3"), "{err}");
+    }
+
+    #[test]
+    fn quoting_and_max_line_length() {
+        let text = "a;b\n'x;y';\\'z\n'unterminated;1\n2;3\n";
+        let opts = CsvReadOptions {
+            delimiter: b';',
+            quote: b'\'',
+            escape: Some(b'\\'),
+            on_bad_lines: OnBadLines::Store,
+            max_line_length: Some(10),
+            ..Default::default()
+        };
+        let parsed = parse_records(text, &opts).unwrap();
+        assert_eq!(1, parsed.records.len());
+        assert_eq!(b"x;y", &parsed.records[0][0]);
+        assert_eq!(1, parsed.bad_lines.len());
+    }
+}
@@ -27,6 +27,21 @@ pub enum ObjectStoreSourceError {
     #[error(transparent)]
     IoError(#[from] std::io::Error),
 
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error("Invalid CSV: {0}")]
+    InvalidCsv(String),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("Invalid column types: {0}")]
+    InvalidSchemaOverride(String),
+
     #[error("No file extension provided")]
     NoFileExtension,
 
@@ -39,6 +54,9 @@ pub enum ObjectStoreSourceError {
 
     #[error("Failed to read object over http: {0}")]
     Reqwest(#[from] reqwest::Error),
+
+    #[error(transparent)]
+    Common(#[from] crate::common::errors::DatasourceCommonError),
 }
 
 pub type Result<T, E = ObjectStoreSourceError> = std::result::Result<T, E>;
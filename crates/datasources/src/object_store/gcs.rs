@@ -5,6 +5,7 @@ use datafusion::execution::object_store::ObjectStoreUrl;
 use object_store::gcp::GoogleCloudStorageBuilder;
 use object_store::path::Path as ObjectStorePath;
 use object_store::ObjectStore;
+use object_store_util::fixtures::fixture_store;
 
 use super::errors::Result;
 use super::ObjStoreAccess;
@@ -31,6 +32,16 @@ impl ObjStoreAccess for GcsStoreAccess {
     }
 
     fn create_store(&self) -> Result<Arc<dyn ObjectStore>> {
+        fixture_store("gs", || self.create_remote_store())
+    }
+
+    fn path(&self, location: &str) -> Result<ObjectStorePath> {
+        Ok(ObjectStorePath::from_url_path(location)?)
+    }
+}
+
+impl GcsStoreAccess {
+    fn create_remote_store(&self) -> Result<Arc<dyn ObjectStore>> {
         let builder = GoogleCloudStorageBuilder::new().with_bucket_name(&self.bucket);
         let builder = match &self.service_account_key {
             Some(key) => builder.with_service_account_key(key),
@@ -42,8 +53,4 @@ impl ObjStoreAccess for GcsStoreAccess {
         let build = builder.build()?;
         Ok(Arc::new(build))
     }
-
-    fn path(&self, location: &str) -> Result<ObjectStorePath> {
-        Ok(ObjectStorePath::from_url_path(location)?)
-    }
 }
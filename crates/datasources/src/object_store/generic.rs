@@ -2,7 +2,7 @@ use std::fmt::Display;
 use std::sync::Arc;
 
 use crate::common::url::DatasourceUrl;
-use crate::lake::storage_options_into_object_store;
+use crate::lake::{storage_options_into_object_store, LakeStorageOptionsError};
 use crate::object_store::errors::ObjectStoreSourceError;
 use datafusion::execution::object_store::ObjectStoreUrl;
 use object_store::path::Path as ObjectStorePath;
@@ -55,7 +55,10 @@ impl ObjStoreAccess for GenericStoreAccess {
         let datasource_url = DatasourceUrl::try_new(&self.base_url)
             .map_err(|_| ObjectStoreSourceError::Static("Couldn't parse base url"))?;
         let store = storage_options_into_object_store(&datasource_url, &self.storage_options)
-            .map_err(|_| ObjectStoreSourceError::Static("Couldn't create a object store"))?;
+            .map_err(|e| match e {
+                LakeStorageOptionsError::Common(e) => ObjectStoreSourceError::Common(e),
+                _ => ObjectStoreSourceError::Static("Couldn't create a object store"),
+            })?;
         Ok(store)
     }
 
@@ -12,11 +12,13 @@ use object_store::{http::HttpBuilder, path::Path as ObjectStorePath, ObjectMeta,
 use url::Url;
 
 use crate::{
+    common::egress::check_egress_url_blocking,
     common::url::DatasourceUrl,
     object_store::{errors::ObjectStoreSourceError, Result},
 };
 
-use super::{MultiSourceTableProvider, ObjStoreAccess, ObjStoreTableProvider};
+use super::inference::SchemaInference;
+use super::{table_schema, MultiSourceTableProvider, ObjStoreAccess, ObjStoreTableProvider};
 
 #[derive(Debug, Clone)]
 pub struct HttpStoreAccess {
@@ -50,6 +52,7 @@ impl ObjStoreAccess for HttpStoreAccess {
     }
 
     fn create_store(&self) -> Result<Arc<dyn ObjectStore>> {
+        check_egress_url_blocking(&self.url)?;
         let builder = HttpBuilder::new().with_url(self.url.to_string());
         let build = builder.build()?;
         Ok(Arc::new(build))
@@ -108,6 +111,7 @@ impl ObjStoreAccess for HttpStoreAccess {
         state: &SessionState,
         file_format: Arc<dyn FileFormat>,
         locations: Vec<DatasourceUrl>,
+        inference: &SchemaInference,
     ) -> Result<Arc<dyn TableProvider>> {
         let store = self.create_store()?;
         let mut providers: Vec<Arc<dyn TableProvider>> = Vec::new();
@@ -124,16 +128,18 @@ impl ObjStoreAccess for HttpStoreAccess {
             .map_err(|e| DataFusionError::Plan(e.to_string()))?;
 
         // this assumes that all locations have the same schema.
-        let arrow_schema = file_format
+        let inferred = file_format
             .clone()
             .infer_schema(state, &store, &objects)
             .await?;
+        let (arrow_schema, read_schema) = table_schema(&file_format, inferred, inference)?;
 
         let base_url = self.base_url()?;
 
         let prov = Arc::new(ObjStoreTableProvider {
             store: store.clone(),
             arrow_schema: arrow_schema.clone(),
+            read_schema: read_schema.clone(),
             file_format: file_format.clone(),
             base_url,
             objects,
@@ -143,9 +149,10 @@ impl ObjStoreAccess for HttpStoreAccess {
         for loc in locations {
             let store = store.clone();
             let arrow_schema = arrow_schema.clone();
+            let read_schema = read_schema.clone();
             let file_format = file_format.clone();
             let prov = self
-                .create_table_provider_single(loc, store, arrow_schema, file_format)
+                .create_table_provider_single(loc, store, arrow_schema, read_schema, file_format)
                 .await?;
 
             providers.push(Arc::new(prov));
@@ -160,6 +167,7 @@ impl HttpStoreAccess {
         url: DatasourceUrl,
         store: Arc<dyn ObjectStore>,
         arrow_schema: Arc<Schema>,
+        read_schema: Option<Arc<Schema>>,
         file_format: Arc<dyn FileFormat>,
     ) -> Result<ObjStoreTableProvider> {
         let base_url = self.base_url()?;
@@ -171,6 +179,7 @@ impl HttpStoreAccess {
         Ok(ObjStoreTableProvider {
             store,
             arrow_schema,
+            read_schema,
             base_url,
             objects,
             file_format,
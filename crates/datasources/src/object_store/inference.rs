@@ -0,0 +1,114 @@
+//! Options for inferring the schema of CSV and JSON files.
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+
+use super::errors::{ObjectStoreSourceError, Result};
+
+/// Max number of records read to infer the schema of CSV files if not set.
+pub const DEFAULT_CSV_INFER_ROWS: usize = 20480;
+
+/// Schema inference options, with the column types resolved to arrow types.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaInference {
+    /// Number of records read to infer the schema.
+    pub infer_rows: Option<usize>,
+    /// Column types used instead of the inferred types.
+    pub column_types: Vec<(String, DataType)>,
+    /// Read all columns as strings.
+    pub all_varchar: bool,
+}
+
+impl SchemaInference {
+    /// Number of records read to infer the schema of CSV files.
+    pub fn csv_infer_rows(&self) -> usize {
+        self.infer_rows.unwrap_or(DEFAULT_CSV_INFER_ROWS)
+    }
+
+    /// Apply the column types to an inferred schema.
+    ///
+    /// Columns are matched by name, falling back to matching case
+    /// insensitively. Errors if a column doesn't exist.
+    pub fn apply(&self, inferred: &Schema) -> Result<SchemaRef> {
+        let mut fields: Vec<Field> = inferred
+            .fields()
+            .iter()
+            .map(|field| {
+                let data_type = if self.all_varchar {
+                    DataType::Utf8
+                } else {
+                    field.data_type().clone()
+                };
+                field.as_ref().clone().with_data_type(data_type)
+            })
+            .collect();
+
+        for (name, data_type) in &self.column_types {
+            let idx = inferred
+                .index_of(name)
+                .ok()
+                .or_else(|| {
+                    inferred
+                        .fields()
+                        .iter()
+                        .position(|field| field.name().eq_ignore_ascii_case(name))
+                })
+                .ok_or_else(|| {
+                    ObjectStoreSourceError::InvalidSchemaOverride(format!(
+                        "column '{name}' not found in the inferred columns"
+                    ))
+                })?;
+            fields[idx] = fields[idx].clone().with_data_type(data_type.clone());
+        }
+
+        Ok(Arc::new(Schema::new_with_metadata(
+            fields,
+            inferred.metadata().clone(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_column_types() {
+        let inferred = Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("Name", DataType::Utf8, true),
+            Field::new("score", DataType::Float64, true),
+        ]);
+
+        let inference = SchemaInference {
+            column_types: vec![
+                ("id".to_string(), DataType::Utf8),
+                ("name".to_string(), DataType::LargeUtf8),
+            ],
+            ..Default::default()
+        };
+        let schema = inference.apply(&inferred).unwrap();
+        assert_eq!(&DataType::Utf8, schema.field(0).data_type());
+        assert_eq!(&DataType::LargeUtf8, schema.field(1).data_type());
+        assert_eq!("Name", schema.field(1).name());
+        assert_eq!(&DataType::Float64, schema.field(2).data_type());
+
+        let inference = SchemaInference {
+            column_types: vec![("score".to_string(), DataType::Float32)],
+            all_varchar: true,
+            ..Default::default()
+        };
+        let schema = inference.apply(&inferred).unwrap();
+        let types: Vec<_> = schema.fields().iter().map(|f| f.data_type()).collect();
+        assert_eq!(
+            vec![&DataType::Utf8, &DataType::Utf8, &DataType::Float32],
+            types
+        );
+
+        let inference = SchemaInference {
+            column_types: vec![("missing".to_string(), DataType::Utf8)],
+            ..Default::default()
+        };
+        assert!(inference.apply(&inferred).is_err());
+    }
+}
@@ -0,0 +1,344 @@
+//! Reading newline delimited JSON files with nested values.
+//!
+//! DataFusion infers nested objects as structs and arrays as lists, but fails
+//! when a value has different types across records and has no control over
+//! how deep values are nested. Files read with a max depth or a map inference
+//! threshold are parsed and inferred up front, and read into memory.
+use std::io::Cursor;
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::{DataType, Field, Fields, Schema};
+use datafusion::arrow::json::ReaderBuilder;
+use datafusion::datasource::{MemTable, TableProvider};
+use object_store::{ObjectMeta, ObjectStore};
+use serde_json::{Map, Value};
+
+use super::errors::{ObjectStoreSourceError, Result};
+use super::inference::SchemaInference;
+
+/// Options for inferring the types of nested JSON values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonReadOptions {
+    /// Levels of nesting inferred as structs and lists. Values nested deeper
+    /// are read as JSON strings.
+    pub max_depth: Option<usize>,
+    /// Objects with more distinct keys than this across records are inferred
+    /// as maps instead of structs.
+    pub map_inference_threshold: Option<usize>,
+}
+
+impl JsonReadOptions {
+    /// Whether files have to be read with [`read_json_table`] instead of
+    /// DataFusion's JSON format.
+    pub fn requires_inference(&self) -> bool {
+        self.max_depth.is_some() || self.map_inference_threshold.is_some()
+    }
+}
+
+/// Read (uncompressed) newline delimited JSON files into memory, inferring
+/// nested types according to `opts`.
+pub async fn read_json_table(
+    store: &Arc<dyn ObjectStore>,
+    objects: &[ObjectMeta],
+    opts: &JsonReadOptions,
+    inference: &SchemaInference,
+) -> Result<Arc<dyn TableProvider>> {
+    let mut records = Vec::new();
+    for object in objects {
+        let bytes = store.get(&object.location).await?.bytes().await?;
+        records.extend(parse_records(&bytes).map_err(|e| {
+            ObjectStoreSourceError::InvalidJson(format!("{}: {e}", object.location))
+        })?);
+    }
+
+    let sample = records
+        .iter()
+        .take(inference.infer_rows.unwrap_or(usize::MAX));
+    let inferred = infer_schema(sample, opts)?;
+    let schema = inference.apply(&inferred)?;
+
+    let mut data = Vec::new();
+    for record in records {
+        let record = normalize_record(record, schema.fields());
+        serde_json::to_writer(&mut data, &record)?;
+        data.push(b'\n');
+    }
+    let batches = ReaderBuilder::new(schema.clone())
+        .build(Cursor::new(data))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Arc::new(MemTable::try_new(schema, vec![batches])?))
+}
+
+/// Parse the records of a file, skipping empty lines.
+fn parse_records(bytes: &[u8]) -> Result<Vec<Map<String, Value>>> {
+    let mut records = Vec::new();
+    for (idx, line) in bytes.split(|b| *b == b'\n').enumerate() {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        match serde_json::from_slice(line)? {
+            Value::Object(record) => records.push(record),
+            _ => {
+                return Err(ObjectStoreSourceError::InvalidJson(format!(
+                    "line {} is not an object",
+                    idx + 1
+                )))
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// Type of a JSON value, merged across records.
+#[derive(Debug, Clone, PartialEq)]
+enum JsonType {
+    Null,
+    Boolean,
+    Int,
+    Float,
+    String,
+    List(Box<JsonType>),
+    /// Fields in the order they were first seen.
+    Object(Vec<(String, JsonType)>),
+    /// Value nested too deep, or with types that can't be merged. Read as a
+    /// JSON string.
+    Json,
+}
+
+impl JsonType {
+    fn of(value: &Value, depth: usize, opts: &JsonReadOptions) -> JsonType {
+        let nested_too_deep = opts.max_depth.is_some_and(|max| depth >= max);
+        match value {
+            Value::Null => JsonType::Null,
+            Value::Bool(_) => JsonType::Boolean,
+            Value::Number(n) if n.is_i64() => JsonType::Int,
+            Value::Number(_) => JsonType::Float,
+            Value::String(_) => JsonType::String,
+            Value::Array(_) | Value::Object(_) if nested_too_deep => JsonType::Json,
+            Value::Array(items) => JsonType::List(Box::new(
+                items
+                    .iter()
+                    .map(|item| JsonType::of(item, depth + 1, opts))
+                    .fold(JsonType::Null, JsonType::merge),
+            )),
+            Value::Object(fields) => JsonType::Object(
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), JsonType::of(value, depth + 1, opts)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn merge(self, other: JsonType) -> JsonType {
+        match (self, other) {
+            (JsonType::Null, t) | (t, JsonType::Null) => t,
+            (a, b) if a == b => a,
+            (JsonType::Int, JsonType::Float) | (JsonType::Float, JsonType::Int) => JsonType::Float,
+            (JsonType::List(a), JsonType::List(b)) => JsonType::List(Box::new(a.merge(*b))),
+            (JsonType::Object(mut a), JsonType::Object(b)) => {
+                for (key, b) in b {
+                    match a.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, a)) => *a = std::mem::replace(a, JsonType::Null).merge(b),
+                        None => a.push((key, b)),
+                    }
+                }
+                JsonType::Object(a)
+            }
+            _ => JsonType::Json,
+        }
+    }
+
+    fn into_data_type(self, opts: &JsonReadOptions) -> DataType {
+        match self {
+            // Columns that are always null are read as strings.
+            JsonType::Null | JsonType::String | JsonType::Json => DataType::Utf8,
+            JsonType::Boolean => DataType::Boolean,
+            JsonType::Int => DataType::Int64,
+            JsonType::Float => DataType::Float64,
+            JsonType::List(item) => DataType::List(Arc::new(Field::new(
+                "item",
+                item.into_data_type(opts),
+                true,
+            ))),
+            JsonType::Object(fields)
+                if opts
+                    .map_inference_threshold
+                    .is_some_and(|threshold| fields.len() > threshold) =>
+            {
+                let values = fields
+                    .into_iter()
+                    .map(|(_, t)| t)
+                    .fold(JsonType::Null, JsonType::merge);
+                let entries = Fields::from(vec![
+                    Field::new("keys", DataType::Utf8, false),
+                    Field::new("values", values.into_data_type(opts), true),
+                ]);
+                DataType::Map(
+                    Arc::new(Field::new("entries", DataType::Struct(entries), false)),
+                    false,
+                )
+            }
+            JsonType::Object(fields) => DataType::Struct(
+                fields
+                    .into_iter()
+                    .map(|(key, t)| Field::new(key, t.into_data_type(opts), true))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Infer the schema of records.
+pub(crate) fn infer_schema<'a>(
+    records: impl Iterator<Item = &'a Map<String, Value>>,
+    opts: &JsonReadOptions,
+) -> Result<Schema> {
+    let mut columns = JsonType::Object(Vec::new());
+    for record in records {
+        let fields = record
+            .iter()
+            .map(|(key, value)| (key.clone(), JsonType::of(value, 0, opts)))
+            .collect();
+        columns = columns.merge(JsonType::Object(fields));
+    }
+
+    match columns {
+        JsonType::Object(columns) if !columns.is_empty() => Ok(Schema::new(
+            columns
+                .into_iter()
+                .map(|(name, t)| Field::new(name, t.into_data_type(opts), true))
+                .collect::<Vec<_>>(),
+        )),
+        _ => Err(ObjectStoreSourceError::InvalidJson(
+            "no columns found in JSON files".to_string(),
+        )),
+    }
+}
+
+/// Rewrite a record so that it can be decoded as `fields`, turning values read
+/// as strings into JSON strings.
+pub(crate) fn normalize_record(mut record: Map<String, Value>, fields: &Fields) -> Value {
+    let normalized = fields
+        .iter()
+        .filter_map(|field| {
+            let value = record.remove(field.name())?;
+            Some((
+                field.name().clone(),
+                normalize_value(value, field.data_type()),
+            ))
+        })
+        .collect();
+    Value::Object(normalized)
+}
+
+fn normalize_value(value: Value, data_type: &DataType) -> Value {
+    match (value, data_type) {
+        (Value::Null, _) => Value::Null,
+        (value @ Value::String(_), DataType::Utf8) => value,
+        (value, DataType::Utf8) => Value::String(value.to_string()),
+        (Value::Array(items), DataType::List(field)) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| normalize_value(item, field.data_type()))
+                .collect(),
+        ),
+        (Value::Object(record), DataType::Struct(fields)) => normalize_record(record, fields),
+        (Value::Object(entries), DataType::Map(field, _)) => {
+            let values_type = match field.data_type() {
+                DataType::Struct(fields) if fields.len() == 2 => fields[1].data_type().clone(),
+                _ => DataType::Utf8,
+            };
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, normalize_value(value, &values_type)))
+                    .collect(),
+            )
+        }
+        (value, _) => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn infer(lines: &str, opts: JsonReadOptions) -> Schema {
+        let records = parse_records(lines.as_bytes()).unwrap();
+        infer_schema(records.iter(), &opts).unwrap()
+    }
+
+    #[test]
+    fn infer_nested_types() {
+        let lines = r#"
+            {"id": 1, "user": {"name": "a", "tags": ["x"]}, "score": 1}
+            {"id": 2, "user": {"name": "b", "age": 3}, "score": 1.5}
+        "#;
+        let schema = infer(lines, JsonReadOptions::default());
+
+        assert_eq!(&DataType::Int64, schema.field(0).data_type());
+        let user = DataType::Struct(Fields::from(vec![
+            Field::new("name", DataType::Utf8, true),
+            Field::new(
+                "tags",
+                DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+                true,
+            ),
+            Field::new("age", DataType::Int64, true),
+        ]));
+        assert_eq!(&user, schema.field(1).data_type());
+        assert_eq!(&DataType::Float64, schema.field(2).data_type());
+    }
+
+    #[test]
+    fn infer_max_depth_and_mixed_types() {
+        let lines = r#"
+            {"a": {"b": {"c": 1}}, "mixed": 1}
+            {"a": {"b": {"c": 2}}, "mixed": {"x": 1}}
+        "#;
+        let opts = JsonReadOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let schema = infer(lines, opts);
+
+        let a = DataType::Struct(Fields::from(vec![Field::new("b", DataType::Utf8, true)]));
+        assert_eq!(&a, schema.field(0).data_type());
+        assert_eq!(&DataType::Utf8, schema.field(1).data_type());
+
+        let record = parse_records(br#"{"a": {"b": {"c": 1}}, "mixed": {"x": 1}}"#)
+            .unwrap()
+            .remove(0);
+        let normalized = normalize_record(record, schema.fields());
+        assert_eq!(
+            serde_json::json!({"a": {"b": "{\"c\":1}"}, "mixed": "{\"x\":1}"}),
+            normalized
+        );
+    }
+
+    #[test]
+    fn infer_maps() {
+        let lines = r#"
+            {"counts": {"a": 1, "b": 2}}
+            {"counts": {"c": 3}}
+        "#;
+        let opts = JsonReadOptions {
+            map_inference_threshold: Some(2),
+            ..Default::default()
+        };
+        let schema = infer(lines, opts);
+
+        match schema.field(0).data_type() {
+            DataType::Map(entries, _) => match entries.data_type() {
+                DataType::Struct(fields) => {
+                    assert_eq!(&DataType::Utf8, fields[0].data_type());
+                    assert_eq!(&DataType::Int64, fields[1].data_type());
+                }
+                other => panic!("unexpected entries type: {other}"),
+            },
+            other => panic!("unexpected type: {other}"),
+        }
+    }
+}
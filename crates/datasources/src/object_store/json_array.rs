@@ -0,0 +1,289 @@
+//! Streaming JSON files holding a single top-level array of objects.
+//!
+//! Files are never loaded whole. The array is rewritten on the fly into
+//! whitespace separated objects, which arrow's JSON decoder reads
+//! incrementally, so memory use only depends on the batch size.
+use std::sync::Arc;
+
+use async_stream::try_stream;
+use datafusion::arrow::datatypes::{Schema, SchemaRef};
+use datafusion::arrow::json::reader::infer_json_schema_from_iterator;
+use datafusion::arrow::json::ReaderBuilder;
+use datafusion::datasource::streaming::StreamingTable;
+use datafusion::datasource::TableProvider;
+use datafusion::error::DataFusionError;
+use datafusion::execution::TaskContext;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::streaming::PartitionStream;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use futures::{StreamExt, TryStreamExt};
+use object_store::{ObjectMeta, ObjectStore};
+use serde_json::Value;
+
+use super::errors::{ObjectStoreSourceError, Result};
+use super::inference::SchemaInference;
+
+/// Number of objects read to infer the schema if not set.
+pub const DEFAULT_INFER_ROWS: usize = 1000;
+
+/// Create a table streaming the objects in the top-level arrays of JSON files,
+/// with one partition per file.
+///
+/// The schema is inferred from the first objects of the files.
+pub async fn json_array_table(
+    store: Arc<dyn ObjectStore>,
+    objects: Vec<ObjectMeta>,
+    inference: &SchemaInference,
+) -> Result<Arc<dyn TableProvider>> {
+    let infer_rows = inference.infer_rows.unwrap_or(DEFAULT_INFER_ROWS);
+    let inferred = infer_schema(&store, &objects, infer_rows).await?;
+    let schema = inference.apply(&inferred)?;
+
+    let partitions = objects
+        .into_iter()
+        .map(|object| {
+            Arc::new(JsonArrayPartitionStream {
+                schema: schema.clone(),
+                store: store.clone(),
+                object,
+            }) as Arc<dyn PartitionStream>
+        })
+        .collect();
+
+    Ok(Arc::new(StreamingTable::try_new(schema, partitions)?))
+}
+
+/// Infer the schema from the first `infer_rows` objects, reading only as much
+/// of the files as needed.
+async fn infer_schema(
+    store: &Arc<dyn ObjectStore>,
+    objects: &[ObjectMeta],
+    infer_rows: usize,
+) -> Result<Schema> {
+    let mut values = Vec::new();
+
+    'objects: for object in objects {
+        let mut splitter = ArraySplitter::default();
+        let mut buf = Vec::new();
+        let mut chunks = store.get(&object.location).await?.into_stream();
+        while let Some(chunk) = chunks.next().await {
+            splitter.push(&chunk?, &mut buf)?;
+
+            let mut parsed = serde_json::Deserializer::from_slice(&buf).into_iter::<Value>();
+            let mut consumed = 0;
+            loop {
+                match parsed.next() {
+                    Some(Ok(value)) => {
+                        values.push(value);
+                        consumed = parsed.byte_offset();
+                        if values.len() >= infer_rows {
+                            break 'objects;
+                        }
+                    }
+                    // Rest of the value is in the next chunk.
+                    Some(Err(e)) if e.is_eof() => break,
+                    Some(Err(e)) => return Err(e.into()),
+                    None => break,
+                }
+            }
+            buf.drain(..consumed);
+        }
+        splitter.finish()?;
+    }
+
+    if values.is_empty() {
+        return Err(ObjectStoreSourceError::InvalidJson(
+            "JSON arrays are empty".to_string(),
+        ));
+    }
+    Ok(infer_json_schema_from_iterator(values.into_iter().map(Ok))?)
+}
+
+/// Streams the objects in the top-level array of a single file.
+struct JsonArrayPartitionStream {
+    schema: SchemaRef,
+    store: Arc<dyn ObjectStore>,
+    object: ObjectMeta,
+}
+
+impl PartitionStream for JsonArrayPartitionStream {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let schema = self.schema.clone();
+        let store = self.store.clone();
+        let location = self.object.location.clone();
+        let batch_size = ctx.session_config().batch_size();
+
+        let stream = try_stream! {
+            let mut decoder = ReaderBuilder::new(schema.clone())
+                .with_batch_size(batch_size)
+                .with_coerce_primitive(true)
+                .build_decoder()?;
+            let mut splitter = ArraySplitter::default();
+            let mut chunks = store.get(&location).await?.into_stream();
+            let mut buf = Vec::new();
+
+            while let Some(chunk) = chunks.try_next().await? {
+                buf.clear();
+                splitter.push(&chunk, &mut buf)?;
+
+                let mut offset = 0;
+                while offset < buf.len() {
+                    let read = decoder.decode(&buf[offset..])?;
+                    offset += read;
+                    // The decoder stops reading once it has a full batch.
+                    if offset < buf.len() {
+                        if let Some(batch) = decoder.flush()? {
+                            yield batch;
+                        }
+                    }
+                }
+            }
+            splitter.finish()?;
+
+            if let Some(batch) = decoder.flush()? {
+                yield batch;
+            }
+        };
+
+        let source = self.object.location.to_string();
+        Box::pin(RecordBatchStreamAdapter::new(
+            self.schema.clone(),
+            stream.map_err(move |e: ObjectStoreSourceError| {
+                DataFusionError::External(format!("{source}: {e}").into())
+            }),
+        ))
+    }
+}
+
+/// Where the splitter is relative to the top-level array.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ArrayPosition {
+    #[default]
+    Before,
+    Inside,
+    After,
+}
+
+/// Rewrites a top-level JSON array as newline separated elements, a chunk at
+/// a time.
+#[derive(Debug, Default)]
+struct ArraySplitter {
+    position: ArrayPosition,
+    /// Depth of nesting within the array.
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl ArraySplitter {
+    /// Rewrite the next chunk of the file into `out`.
+    fn push(&mut self, chunk: &[u8], out: &mut Vec<u8>) -> Result<()> {
+        for &b in chunk {
+            match self.position {
+                ArrayPosition::Before => match b {
+                    b'[' => self.position = ArrayPosition::Inside,
+                    b if b.is_ascii_whitespace() => {}
+                    _ => {
+                        return Err(ObjectStoreSourceError::InvalidJson(
+                            "expected a top-level JSON array".to_string(),
+                        ))
+                    }
+                },
+                ArrayPosition::After => {
+                    if !b.is_ascii_whitespace() {
+                        return Err(ObjectStoreSourceError::InvalidJson(
+                            "unexpected data after the top-level JSON array".to_string(),
+                        ));
+                    }
+                }
+                ArrayPosition::Inside if self.in_string => {
+                    out.push(b);
+                    if self.escaped {
+                        self.escaped = false;
+                    } else if b == b'\\' {
+                        self.escaped = true;
+                    } else if b == b'"' {
+                        self.in_string = false;
+                    }
+                }
+                ArrayPosition::Inside => match b {
+                    b'"' => {
+                        self.in_string = true;
+                        out.push(b);
+                    }
+                    b'{' | b'[' => {
+                        self.depth += 1;
+                        out.push(b);
+                    }
+                    b']' if self.depth == 0 => {
+                        self.position = ArrayPosition::After;
+                        out.push(b'\n');
+                    }
+                    b'}' | b']' => {
+                        self.depth = self.depth.saturating_sub(1);
+                        out.push(b);
+                    }
+                    b',' if self.depth == 0 => out.push(b'\n'),
+                    b => out.push(b),
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the array was closed at the end of the file.
+    fn finish(&self) -> Result<()> {
+        match self.position {
+            ArrayPosition::After => Ok(()),
+            ArrayPosition::Before => Err(ObjectStoreSourceError::InvalidJson(
+                "expected a top-level JSON array".to_string(),
+            )),
+            ArrayPosition::Inside => Err(ObjectStoreSourceError::InvalidJson(
+                "top-level JSON array is not closed".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(chunks: &[&str]) -> Result<String> {
+        let mut splitter = ArraySplitter::default();
+        let mut out = Vec::new();
+        for chunk in chunks {
+            splitter.push(chunk.as_bytes(), &mut out)?;
+        }
+        splitter.finish()?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn split_array() {
+        let out = split(&[
+            r#" [{"a": [1, 2], "b": "x,]"}, "#,
+            r#"{"a": [], "b": "\"]"}] "#,
+        ])
+        .unwrap();
+        assert_eq!(
+            "{\"a\": [1, 2], \"b\": \"x,]\"}\n {\"a\": [], \"b\": \"\\\"]\"}\n",
+            out
+        );
+
+        assert_eq!("\n", split(&["[]"]).unwrap());
+        assert!(split(&[r#"{"a": 1}"#]).is_err());
+        assert!(split(&[r#"[{"a": 1}"#]).is_err());
+        assert!(split(&[r#"[{"a": 1}] {"b": 2}"#]).is_err());
+    }
+
+    #[test]
+    fn split_escapes_across_chunks() {
+        let out = split(&[r#"[{"a": "x\"#, r#""", "b": 1}]"#]).unwrap();
+        assert_eq!("{\"a\": \"x\\\"\", \"b\": 1}\n", out);
+    }
+}
@@ -1,43 +1,72 @@
 use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::common::FileType;
+use datafusion::datasource::file_format::csv::CsvFormat;
+use datafusion::datasource::file_format::parquet::ParquetFormat;
 use datafusion::datasource::file_format::FileFormat;
 use datafusion::datasource::listing::PartitionedFile;
-use datafusion::datasource::physical_plan::FileScanConfig;
+use datafusion::datasource::physical_plan::{FileScanConfig, ParquetExec};
 use datafusion::datasource::{get_statistics_with_limit, TableProvider};
 use datafusion::error::{DataFusionError, Result as DatafusionResult};
 use datafusion::execution::context::SessionState;
 use datafusion::execution::object_store::ObjectStoreUrl;
 use datafusion::execution::runtime_env::RuntimeEnv;
-use datafusion::logical_expr::TableType;
+use datafusion::logical_expr::{TableProviderFilterPushDown, TableType};
+use datafusion::parquet::arrow::async_reader::{
+    ParquetObjectReader, ParquetRecordBatchStreamBuilder,
+};
+use datafusion::physical_expr::expressions::{cast, Column};
+use datafusion::physical_expr::PhysicalExpr;
+use datafusion::physical_optimizer::pruning::PruningPredicate;
+use datafusion::physical_plan::metrics::Count;
+use datafusion::physical_plan::projection::ProjectionExec;
 use datafusion::physical_plan::union::UnionExec;
-use datafusion::physical_plan::ExecutionPlan;
+use datafusion::physical_plan::{ExecutionPlan, Statistics};
 use datafusion::prelude::Expr;
-use datafusion_ext::metrics::ReadOnlyDataSourceMetricsExecAdapter;
+use datafusion_ext::metrics::{ReadOnlyDataSourceMetricsExecAdapter, ScanPruning};
+use datafusion_ext::vars::SessionVars;
+use datafusion_ext::warnings::{push_warning, SkipErrorsExec, Warning};
 use errors::ObjectStoreSourceError;
 use errors::Result;
-use futures::StreamExt;
+use futures::{StreamExt, TryStreamExt};
 use glob::{MatchOptions, Pattern};
 use object_store::path::Path as ObjectStorePath;
 use object_store::{ObjectMeta, ObjectStore};
+use object_store_util::cache::CachingObjectStore;
+use object_store_util::metered::MeteredObjectStore;
+use object_store_util::parallel::ParallelGetObjectStore;
+use object_store_util::retry::RetryObjectStore;
 use protogen::metastore::types::options::{TableOptions, TableOptionsObjectStore};
+use telemetry::metrics::METRICS;
 
 use crate::common::exprs_to_phys_exprs;
 use crate::common::url::DatasourceUrl;
+use crate::common::util::{datasource_retry_policy, skip_corrupt_files};
+use crate::object_store::csv::CsvReadOptions;
 use crate::object_store::gcs::GcsStoreAccess;
 use crate::object_store::generic::GenericStoreAccess;
+use crate::object_store::inference::SchemaInference;
+use crate::object_store::json::JsonReadOptions;
 use crate::object_store::local::LocalStoreAccess;
+use crate::object_store::pruning::{BloomFilterPredicate, FileStatistics, RowGroupStatistics};
 use crate::object_store::s3::S3StoreAccess;
 
+pub mod csv;
 pub mod errors;
 pub mod gcs;
 pub mod generic;
 pub mod http;
+pub mod inference;
+pub mod json;
+pub mod json_array;
 pub mod local;
+pub mod pruning;
 pub mod s3;
 
 pub struct MultiSourceTableProvider {
@@ -180,17 +209,17 @@ pub trait ObjStoreAccess: Debug + Display + Send + Sync {
         Ok(store.head(location).await?)
     }
 
-    async fn create_table_provider(
+    /// Gets the objects matching all locations, erroring if nothing matches
+    /// a location.
+    async fn list_locations(
         &self,
-        state: &SessionState,
-        file_format: Arc<dyn FileFormat>,
+        store: &Arc<dyn ObjectStore>,
         locations: Vec<DatasourceUrl>,
-    ) -> Result<Arc<dyn TableProvider>> {
-        let store = self.create_store()?;
+    ) -> Result<Vec<ObjectMeta>> {
         let mut objects = Vec::new();
         for loc in locations {
             let list = self
-                .list_globbed(&store, &loc.path())
+                .list_globbed(store, &loc.path())
                 .await
                 .map_err(|e| DataFusionError::External(Box::new(e)))?;
             if list.is_empty() {
@@ -202,19 +231,174 @@ pub trait ObjStoreAccess: Debug + Display + Send + Sync {
 
             objects.push(list);
         }
-        let objects = objects.into_iter().flatten().collect::<Vec<_>>();
+        Ok(objects.into_iter().flatten().collect())
+    }
+
+    async fn create_table_provider(
+        &self,
+        state: &SessionState,
+        file_format: Arc<dyn FileFormat>,
+        locations: Vec<DatasourceUrl>,
+        inference: &SchemaInference,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let store = cached_store(self, self.create_store()?)?;
+        let objects = self.list_locations(&store, locations).await?;
 
-        let arrow_schema = file_format.infer_schema(state, &store, &objects).await?;
+        let (inferred, objects) = infer_schema(state, &file_format, &store, objects).await?;
+        let (arrow_schema, read_schema) = table_schema(&file_format, inferred, inference)?;
         let base_url = self.base_url()?;
 
         Ok(Arc::new(ObjStoreTableProvider {
             store,
             arrow_schema,
+            read_schema,
             base_url,
             objects,
             file_format,
         }))
     }
+
+    /// Creates a table provider for CSV files that have to be cleaned before
+    /// they can be read, see [`CsvReadOptions::requires_cleaning`].
+    async fn create_csv_table_provider(
+        &self,
+        state: &SessionState,
+        opts: &CsvReadOptions,
+        locations: Vec<DatasourceUrl>,
+        inference: &SchemaInference,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let store = cached_store(self, self.create_store()?)?;
+        let objects = self.list_locations(&store, locations).await?;
+        csv::read_csv_table(state, &store, &objects, opts, inference).await
+    }
+
+    /// Creates a table provider for newline delimited JSON files with nested
+    /// types inferred up front, see [`JsonReadOptions::requires_inference`].
+    async fn create_json_table_provider(
+        &self,
+        opts: &JsonReadOptions,
+        locations: Vec<DatasourceUrl>,
+        inference: &SchemaInference,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let store = cached_store(self, self.create_store()?)?;
+        let objects = self.list_locations(&store, locations).await?;
+        json::read_json_table(&store, &objects, opts, inference).await
+    }
+
+    /// Creates a table provider streaming the top-level arrays of JSON files.
+    async fn create_json_array_table_provider(
+        &self,
+        locations: Vec<DatasourceUrl>,
+        inference: &SchemaInference,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let store = cached_store(self, self.create_store()?)?;
+        let objects = self.list_locations(&store, locations).await?;
+        json_array::json_array_table(store, objects, inference).await
+    }
+}
+
+/// Infer the schema of the objects.
+///
+/// If the session skips corrupt files, objects that can't be read are skipped
+/// with a warning. Returns the schema with the objects that were read.
+async fn infer_schema(
+    state: &SessionState,
+    file_format: &Arc<dyn FileFormat>,
+    store: &Arc<dyn ObjectStore>,
+    objects: Vec<ObjectMeta>,
+) -> Result<(SchemaRef, Vec<ObjectMeta>)> {
+    if objects.len() < 2 || !skip_corrupt_files(state) {
+        let arrow_schema = file_format.infer_schema(state, store, &objects).await?;
+        return Ok((arrow_schema, objects));
+    }
+
+    // Check every object can be read on its own before inferring the schema
+    // from all of them, inferring the schema may merge types across objects.
+    let readable = futures::stream::iter(objects)
+        .map(|object| async move {
+            match file_format
+                .infer_schema(state, store, std::slice::from_ref(&object))
+                .await
+            {
+                Ok(_) => Some(object),
+                Err(e) => {
+                    push_warning(
+                        state.config(),
+                        Warning::new(object.location.to_string(), format!("skipped file: {e}")),
+                    );
+                    None
+                }
+            }
+        })
+        .buffered(state.config_options().execution.meta_fetch_concurrency)
+        .filter_map(futures::future::ready)
+        .collect::<Vec<_>>()
+        .await;
+    if readable.is_empty() {
+        return Err(ObjectStoreSourceError::Static(
+            "None of the files could be read",
+        ));
+    }
+
+    let arrow_schema = file_format.infer_schema(state, store, &readable).await?;
+    Ok((arrow_schema, readable))
+}
+
+/// Get the schema of a table from the inferred schema of its files.
+///
+/// Returns the table schema, and the schema to read the files with if it's
+/// different. CSV files are parsed as the table schema, other formats are read
+/// as inferred and cast.
+fn table_schema(
+    file_format: &Arc<dyn FileFormat>,
+    inferred: SchemaRef,
+    inference: &SchemaInference,
+) -> Result<(SchemaRef, Option<SchemaRef>)> {
+    let schema = inference.apply(&inferred)?;
+    if schema == inferred || file_format.as_any().is::<CsvFormat>() {
+        Ok((schema, None))
+    } else {
+        Ok((schema, Some(inferred)))
+    }
+}
+
+/// Wraps a store created from `access` in the shared object store cache.
+///
+/// The cache is namespaced by everything in the access, including
+/// credentials, so different users never see each other's cached objects.
+/// Local files are already cheap to read and aren't cached.
+fn cached_store<A: ObjStoreAccess + ?Sized>(
+    access: &A,
+    store: Arc<dyn ObjectStore>,
+) -> Result<Arc<dyn ObjectStore>> {
+    let base_url = access.base_url()?;
+    if base_url.as_str().starts_with("file://") {
+        return Ok(store);
+    }
+
+    let mut hasher = DefaultHasher::new();
+    format!("{access:?}").hash(&mut hasher);
+    Ok(Arc::new(CachingObjectStore::new(store, hasher.finish())))
+}
+
+/// Wrap a remote store so large objects are downloaded in parallel parts,
+/// using the part size and concurrency from the session.
+fn parallel_get_store(
+    ctx: &SessionState,
+    base_url: &ObjectStoreUrl,
+    store: Arc<dyn ObjectStore>,
+) -> Arc<dyn ObjectStore> {
+    if base_url.as_str().starts_with("file://") {
+        return store;
+    }
+    match ctx.config_options().extensions.get::<SessionVars>() {
+        Some(vars) => Arc::new(ParallelGetObjectStore::new(
+            store,
+            vars.object_store_download_part_size(),
+            vars.object_store_download_concurrency(),
+        )),
+        None => store,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -238,6 +422,13 @@ impl ObjStoreAccessor {
         })
     }
 
+    /// Cache listings and reads made through this accessor and the table
+    /// provider created from it.
+    pub fn with_cache(mut self) -> Result<Self> {
+        self.store = cached_store(self.access.as_ref(), self.store)?;
+        Ok(self)
+    }
+
     /// Returns a list of objects matching the globbed pattern.
     pub async fn list_globbed(&self, pattern: impl AsRef<str>) -> Result<Vec<ObjectMeta>> {
         self.access
@@ -251,14 +442,36 @@ impl ObjStoreAccessor {
         state: &SessionState,
         file_format: Arc<dyn FileFormat>,
         objects: Vec<ObjectMeta>,
+        inference: &SchemaInference,
     ) -> Result<Arc<dyn TableProvider>> {
-        let store = self.store;
-        let arrow_schema = file_format.infer_schema(state, &store, &objects).await?;
+        let (inferred, objects) = infer_schema(state, &file_format, &self.store, objects).await?;
+        let (arrow_schema, read_schema) = table_schema(&file_format, inferred, inference)?;
         let base_url = self.access.base_url()?;
 
         Ok(Arc::new(ObjStoreTableProvider {
-            store,
+            store: self.store,
             arrow_schema,
+            read_schema,
+            base_url,
+            objects,
+            file_format,
+        }))
+    }
+
+    /// Creates the table provider using a known schema instead of inferring
+    /// it from the objects.
+    pub fn into_table_provider_with_schema(
+        self,
+        file_format: Arc<dyn FileFormat>,
+        objects: Vec<ObjectMeta>,
+        arrow_schema: SchemaRef,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let base_url = self.access.base_url()?;
+
+        Ok(Arc::new(ObjStoreTableProvider {
+            store: self.store,
+            arrow_schema,
+            read_schema: None,
             base_url,
             objects,
             file_format,
@@ -270,6 +483,9 @@ impl ObjStoreAccessor {
 pub struct ObjStoreTableProvider {
     store: Arc<dyn ObjectStore>,
     arrow_schema: SchemaRef,
+    /// Schema the files are read with if different from the table schema,
+    /// columns are cast to the table schema after reading.
+    read_schema: Option<SchemaRef>,
     base_url: ObjectStoreUrl,
     objects: Vec<ObjectMeta>,
     file_format: Arc<dyn FileFormat>,
@@ -289,6 +505,14 @@ impl TableProvider for ObjStoreTableProvider {
         TableType::View
     }
 
+    fn supports_filter_pushdown(
+        &self,
+        _filter: &Expr,
+    ) -> DatafusionResult<TableProviderFilterPushDown> {
+        // Filters are only used for pruning files and row groups.
+        Ok(TableProviderFilterPushDown::Inexact)
+    }
+
     async fn scan(
         &self,
         ctx: &SessionState,
@@ -296,23 +520,71 @@ impl TableProvider for ObjStoreTableProvider {
         filters: &[Expr],
         limit: Option<usize>,
     ) -> DatafusionResult<Arc<dyn ExecutionPlan>> {
+        // Filters are in terms of the table schema, so they can't be used when
+        // the files are read with a different schema.
+        let file_schema = self
+            .read_schema
+            .clone()
+            .unwrap_or_else(|| self.arrow_schema.clone());
+        let physical_filters = match self.read_schema {
+            Some(_) => None,
+            None => exprs_to_phys_exprs(filters, ctx, &self.arrow_schema)?,
+        };
+        let predicate = physical_filters
+            .as_ref()
+            .and_then(|expr| PruningPredicate::try_new(expr.clone(), self.schema()).ok());
+
+        // Files that can't be read are skipped with a warning if the session
+        // skips corrupt files. A single file is never skipped, that would
+        // only hide the error.
+        let skip_corrupt = self.objects.len() > 1 && skip_corrupt_files(ctx);
+
         // See datafusion's `ListingTable::list_files_for_scan`.
         let files = futures::stream::iter(&self.objects)
-            .map(|object| async {
+            .map(|object| async move {
                 let file: PartitionedFile = object.clone().into();
-                let stats = self
+                match self
                     .file_format
-                    .infer_stats(ctx, &self.store, self.schema(), object)
-                    .await?;
-                Ok((file, stats))
+                    .infer_stats(ctx, &self.store, file_schema.clone(), object)
+                    .await
+                {
+                    Ok(stats) => Ok(Some((file, stats))),
+                    Err(e) if skip_corrupt => {
+                        push_warning(
+                            ctx.config(),
+                            Warning::new(object.location.to_string(), format!("skipped file: {e}")),
+                        );
+                        Ok(None)
+                    }
+                    Err(e) => Err(e),
+                }
             })
             .boxed()
-            .buffered(ctx.config_options().execution.meta_fetch_concurrency);
-        let (files, statistics) = get_statistics_with_limit(files, self.schema(), limit).await?;
+            .buffered(ctx.config_options().execution.meta_fetch_concurrency)
+            .try_filter_map(|file| futures::future::ready(Ok(file)));
+
+        let (files, statistics, pruning) = match &predicate {
+            Some(predicate) => {
+                let files = files.try_collect().await?;
+                let (files, pruning) = self.prune_files(ctx, predicate, filters, files).await?;
+                let (files, statistics) = get_statistics_with_limit(
+                    futures::stream::iter(files.into_iter().map(Ok)),
+                    file_schema.clone(),
+                    limit,
+                )
+                .await?;
+                (files, statistics, Some(pruning))
+            }
+            None => {
+                let (files, statistics) =
+                    get_statistics_with_limit(files, file_schema.clone(), limit).await?;
+                (files, statistics, None)
+            }
+        };
 
         let config = FileScanConfig {
             object_store_url: self.base_url.clone(),
-            file_schema: self.arrow_schema.clone(),
+            file_schema,
             file_groups: vec![files],
             statistics,
             projection: projection.cloned(),
@@ -321,20 +593,206 @@ impl TableProvider for ObjStoreTableProvider {
             output_ordering: Vec::new(),
             infinite_source: false,
         };
-        let filters = exprs_to_phys_exprs(filters, ctx, &self.arrow_schema)?;
 
         // We register the store at scan time so that it can be used by the
-        // exec plan.
+        // exec plan. Bytes read and requests retried by the plan are counted
+        // against the type of source (the url scheme).
+        let source = self
+            .base_url
+            .as_str()
+            .split_once("://")
+            .map(|(scheme, _)| scheme)
+            .unwrap_or("unknown");
+        let retries = Count::new();
+        let store = RetryObjectStore::new(self.store.clone(), datasource_retry_policy(ctx))
+            .with_on_retry({
+                let retries = retries.clone();
+                let retries_total = METRICS.datasource_retries.with_label(source);
+                move |_| {
+                    retries.add(1);
+                    retries_total.inc();
+                }
+            });
+        let store = MeteredObjectStore::new(
+            parallel_get_store(ctx, &self.base_url, Arc::new(store)),
+            METRICS.object_store_bytes_read.with_label(source),
+        );
         ctx.runtime_env()
-            .register_object_store(self.base_url.as_ref(), self.store.clone());
+            .register_object_store(self.base_url.as_ref(), Arc::new(store));
+
+        let plan: Arc<dyn ExecutionPlan> = if skip_corrupt && config.file_groups[0].len() > 1 {
+            // Scan every file separately so that an error in one file only
+            // ends the scan of that file.
+            let mut plans: Vec<Arc<dyn ExecutionPlan>> =
+                Vec::with_capacity(config.file_groups[0].len());
+            for file in &config.file_groups[0] {
+                let config = FileScanConfig {
+                    file_groups: vec![vec![file.clone()]],
+                    statistics: Statistics::default(),
+                    ..config.clone()
+                };
+                let plan = self
+                    .create_file_scan_plan(ctx, config, &physical_filters)
+                    .await?;
+                let location = file.object_meta.location.to_string();
+                plans.push(Arc::new(SkipErrorsExec::new(plan, location)));
+            }
+            Arc::new(UnionExec::new(plans))
+        } else {
+            self.create_file_scan_plan(ctx, config, &physical_filters)
+                .await?
+        };
 
-        let plan = self
-            .file_format
-            .create_physical_plan(ctx, config, filters.as_ref())
-            .await
-            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        let mut plan = ReadOnlyDataSourceMetricsExecAdapter::new(plan).with_retries(retries);
+        if let Some(pruning) = pruning {
+            plan = plan.with_pruning(pruning);
+        }
+        match self.read_schema {
+            Some(_) => self.cast_to_table_schema(Arc::new(plan), projection),
+            None => Ok(Arc::new(plan)),
+        }
+    }
+}
+
+impl ObjStoreTableProvider {
+    /// Cast the columns of a plan reading the files to the types of the
+    /// (projected) table schema.
+    fn cast_to_table_schema(
+        &self,
+        input: Arc<dyn ExecutionPlan>,
+        projection: Option<&Vec<usize>>,
+    ) -> DatafusionResult<Arc<dyn ExecutionPlan>> {
+        let indices = match projection {
+            Some(projection) => projection.clone(),
+            None => (0..self.arrow_schema.fields().len()).collect(),
+        };
+        let input_schema = input.schema();
+        let exprs = indices
+            .into_iter()
+            .enumerate()
+            .map(|(input_idx, table_idx)| {
+                let field = self.arrow_schema.field(table_idx);
+                let column = Arc::new(Column::new(field.name(), input_idx));
+                let expr = cast(column, &input_schema, field.data_type().clone())?;
+                Ok((expr, field.name().clone()))
+            })
+            .collect::<DatafusionResult<Vec<_>>>()?;
+        Ok(Arc::new(ProjectionExec::try_new(exprs, input)?))
+    }
+
+    /// Create the plan for scanning the files in `config`.
+    async fn create_file_scan_plan(
+        &self,
+        ctx: &SessionState,
+        config: FileScanConfig,
+        physical_filters: &Option<Arc<dyn PhysicalExpr>>,
+    ) -> DatafusionResult<Arc<dyn ExecutionPlan>> {
+        if self.file_format.as_any().is::<ParquetFormat>() {
+            // Page indexes are used for pruning pages within row groups when
+            // the files have them.
+            let metadata_size_hint = ctx.config_options().execution.parquet.metadata_size_hint;
+            Ok(Arc::new(
+                ParquetExec::new(config, physical_filters.clone(), metadata_size_hint)
+                    .with_enable_page_index(true),
+            ))
+        } else {
+            self.file_format
+                .create_physical_plan(ctx, config, physical_filters.as_ref())
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))
+        }
+    }
+
+    /// Skip files, and for parquet row groups, that can't contain rows matching
+    /// the filters.
+    async fn prune_files(
+        &self,
+        ctx: &SessionState,
+        predicate: &PruningPredicate,
+        filters: &[Expr],
+        files: Vec<(PartitionedFile, Statistics)>,
+    ) -> DatafusionResult<(Vec<(PartitionedFile, Statistics)>, ScanPruning)> {
+        let mut pruning = ScanPruning {
+            files: files.len(),
+            ..Default::default()
+        };
+
+        let statistics = files
+            .iter()
+            .map(|(_, stats)| stats.clone())
+            .collect::<Vec<_>>();
+        let keep = pruning::prune(
+            predicate,
+            &FileStatistics {
+                schema: &self.arrow_schema,
+                files: &statistics,
+            },
+        );
+        let mut files = files
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(file, keep)| keep.then_some(file))
+            .collect::<Vec<_>>();
+        pruning.files_pruned_by_statistics = pruning.files - files.len();
+
+        if self.file_format.as_any().is::<ParquetFormat>() {
+            let bloom_filter = BloomFilterPredicate::try_new(filters);
+            let row_groups = futures::stream::iter(&files)
+                .map(|(file, _)| async {
+                    let reader =
+                        ParquetObjectReader::new(self.store.clone(), file.object_meta.clone());
+                    let mut builder = ParquetRecordBatchStreamBuilder::new(reader).await?;
+                    let mut keep = pruning::prune(
+                        predicate,
+                        &RowGroupStatistics {
+                            schema: &self.arrow_schema,
+                            row_groups: builder.metadata().row_groups(),
+                        },
+                    );
+                    let pruned_by_statistics = keep.iter().filter(|keep| !**keep).count();
+
+                    if let Some(bloom_filter) = &bloom_filter {
+                        for (idx, keep) in keep.iter_mut().enumerate() {
+                            if *keep {
+                                *keep = bloom_filter.check(&mut builder, idx).await?;
+                            }
+                        }
+                    }
+
+                    Ok::<_, DataFusionError>((keep, pruned_by_statistics))
+                })
+                .buffered(ctx.config_options().execution.meta_fetch_concurrency)
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            let mut row_groups = row_groups.into_iter();
+            let mut num_row_groups = 0;
+            files.retain(|_| {
+                let (keep, pruned_by_statistics) = row_groups.next().unwrap_or_default();
+                let kept = keep.iter().filter(|keep| **keep).count();
+                let pruned_by_bloom_filter = keep.len() - pruned_by_statistics - kept;
+
+                num_row_groups += keep.len();
+                pruning.row_groups_pruned_by_statistics += pruned_by_statistics;
+                pruning.row_groups_pruned_by_bloom_filter += pruned_by_bloom_filter;
+
+                // Files where every row group was pruned don't need to be
+                // opened.
+                if keep.is_empty() || kept > 0 {
+                    true
+                } else {
+                    if pruned_by_bloom_filter > 0 {
+                        pruning.files_pruned_by_bloom_filter += 1;
+                    } else {
+                        pruning.files_pruned_by_statistics += 1;
+                    }
+                    false
+                }
+            });
+            pruning.row_groups = Some(num_row_groups);
+        }
 
-        Ok(Arc::new(ReadOnlyDataSourceMetricsExecAdapter::new(plan)))
+        Ok((files, pruning))
     }
 }
 
@@ -0,0 +1,377 @@
+//! Pruning of files and parquet row groups using their min/max values and
+//! bloom filters.
+
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, UInt64Array};
+use datafusion::arrow::datatypes::{DataType, Schema};
+use datafusion::common::Column;
+use datafusion::error::Result;
+use datafusion::logical_expr::expr::InList;
+use datafusion::logical_expr::{BinaryExpr, Expr, Operator};
+use datafusion::optimizer::utils::split_conjunction;
+use datafusion::parquet::arrow::async_reader::{AsyncFileReader, ParquetRecordBatchStreamBuilder};
+use datafusion::parquet::bloom_filter::Sbbf;
+use datafusion::parquet::file::metadata::RowGroupMetaData;
+use datafusion::parquet::file::statistics::Statistics as ParquetStatistics;
+use datafusion::physical_optimizer::pruning::{PruningPredicate, PruningStatistics};
+use datafusion::physical_plan::{ColumnStatistics, Statistics};
+use datafusion::scalar::ScalarValue;
+use tracing::debug;
+
+/// Returns for each container whether it may contain rows matching the
+/// predicate.
+///
+/// Containers are kept if the predicate can't be evaluated against the
+/// statistics.
+pub fn prune(predicate: &PruningPredicate, statistics: &impl PruningStatistics) -> Vec<bool> {
+    match predicate.prune(statistics) {
+        Ok(keep) => keep,
+        Err(e) => {
+            debug!(%e, "failed to evaluate pruning predicate");
+            vec![true; statistics.num_containers()]
+        }
+    }
+}
+
+/// Statistics of files as inferred by their file format.
+pub struct FileStatistics<'a> {
+    pub schema: &'a Schema,
+    pub files: &'a [Statistics],
+}
+
+impl FileStatistics<'_> {
+    fn values(
+        &self,
+        column: &Column,
+        value: impl Fn(&ColumnStatistics) -> Option<ScalarValue>,
+    ) -> Option<ArrayRef> {
+        let (idx, field) = self.schema.column_with_name(&column.name)?;
+        let null = ScalarValue::try_from(field.data_type()).ok()?;
+        let values = self.files.iter().map(|stats| {
+            stats
+                .column_statistics
+                .as_ref()
+                .and_then(|cols| cols.get(idx))
+                .and_then(&value)
+                .filter(|value| &value.data_type() == field.data_type())
+                .unwrap_or_else(|| null.clone())
+        });
+        ScalarValue::iter_to_array(values).ok()
+    }
+}
+
+impl PruningStatistics for FileStatistics<'_> {
+    fn min_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.values(column, |col| col.min_value.clone())
+    }
+
+    fn max_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.values(column, |col| col.max_value.clone())
+    }
+
+    fn num_containers(&self) -> usize {
+        self.files.len()
+    }
+
+    fn null_counts(&self, column: &Column) -> Option<ArrayRef> {
+        let (idx, _) = self.schema.column_with_name(&column.name)?;
+        let counts = self.files.iter().map(|stats| {
+            stats
+                .column_statistics
+                .as_ref()
+                .and_then(|cols| cols.get(idx))
+                .and_then(|col| col.null_count)
+                .map(|count| count as u64)
+        });
+        Some(Arc::new(UInt64Array::from_iter(counts)))
+    }
+}
+
+/// Statistics of the row groups of a parquet file.
+///
+/// Only top-level columns of common types are supported, row groups are kept
+/// for anything else.
+pub struct RowGroupStatistics<'a> {
+    pub schema: &'a Schema,
+    pub row_groups: &'a [RowGroupMetaData],
+}
+
+impl RowGroupStatistics<'_> {
+    fn column<'b>(row_group: &'b RowGroupMetaData, name: &str) -> Option<&'b ParquetStatistics> {
+        row_group
+            .columns()
+            .iter()
+            .find(|col| col.column_path().string() == name)?
+            .statistics()
+    }
+
+    fn values(&self, column: &Column, min: bool) -> Option<ArrayRef> {
+        let field = self.schema.field_with_name(&column.name).ok()?;
+        let null = ScalarValue::try_from(field.data_type()).ok()?;
+        let values = self.row_groups.iter().map(|row_group| {
+            Self::column(row_group, &column.name)
+                .and_then(|stats| parquet_scalar(stats, field.data_type(), min))
+                .unwrap_or_else(|| null.clone())
+        });
+        ScalarValue::iter_to_array(values).ok()
+    }
+}
+
+impl PruningStatistics for RowGroupStatistics<'_> {
+    fn min_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.values(column, true)
+    }
+
+    fn max_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.values(column, false)
+    }
+
+    fn num_containers(&self) -> usize {
+        self.row_groups.len()
+    }
+
+    fn null_counts(&self, column: &Column) -> Option<ArrayRef> {
+        let counts = self
+            .row_groups
+            .iter()
+            .map(|row_group| Self::column(row_group, &column.name).map(|stats| stats.null_count()));
+        Some(Arc::new(UInt64Array::from_iter(counts)))
+    }
+}
+
+/// Convert the min or max value of a parquet column chunk to a scalar of the
+/// arrow type it's read as.
+fn parquet_scalar(
+    stats: &ParquetStatistics,
+    data_type: &DataType,
+    min: bool,
+) -> Option<ScalarValue> {
+    if !stats.has_min_max_set() {
+        return None;
+    }
+
+    macro_rules! pick {
+        ($stats:expr) => {
+            if min {
+                $stats.min()
+            } else {
+                $stats.max()
+            }
+        };
+    }
+
+    let value = match (stats, data_type) {
+        (ParquetStatistics::Boolean(s), DataType::Boolean) => ScalarValue::Boolean(Some(*pick!(s))),
+        (ParquetStatistics::Int32(s), DataType::Int8) => ScalarValue::Int8(Some(*pick!(s) as i8)),
+        (ParquetStatistics::Int32(s), DataType::Int16) => {
+            ScalarValue::Int16(Some(*pick!(s) as i16))
+        }
+        (ParquetStatistics::Int32(s), DataType::Int32) => ScalarValue::Int32(Some(*pick!(s))),
+        (ParquetStatistics::Int32(s), DataType::Date32) => ScalarValue::Date32(Some(*pick!(s))),
+        (ParquetStatistics::Int64(s), DataType::Int64) => ScalarValue::Int64(Some(*pick!(s))),
+        (ParquetStatistics::Float(s), DataType::Float32) => ScalarValue::Float32(Some(*pick!(s))),
+        (ParquetStatistics::Double(s), DataType::Float64) => ScalarValue::Float64(Some(*pick!(s))),
+        (ParquetStatistics::ByteArray(s), DataType::Utf8) => {
+            ScalarValue::Utf8(Some(pick!(s).as_utf8().ok()?.to_string()))
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Equality and `IN` filters that can be checked against bloom filters.
+#[derive(Debug, Clone)]
+pub struct BloomFilterPredicate {
+    /// Each entry requires the column to be one of the values.
+    lookups: Vec<(String, Vec<ScalarValue>)>,
+}
+
+impl BloomFilterPredicate {
+    /// Create a predicate from filters, returns `None` if none of the filters
+    /// are point lookups.
+    pub fn try_new(filters: &[Expr]) -> Option<Self> {
+        let lookups = filters
+            .iter()
+            .flat_map(split_conjunction)
+            .filter_map(point_lookup)
+            .collect::<Vec<_>>();
+        if lookups.is_empty() {
+            None
+        } else {
+            Some(BloomFilterPredicate { lookups })
+        }
+    }
+
+    /// Check if a row group may contain matching rows. Columns without a bloom
+    /// filter are assumed to match.
+    pub async fn check<T>(
+        &self,
+        builder: &mut ParquetRecordBatchStreamBuilder<T>,
+        row_group: usize,
+    ) -> Result<bool>
+    where
+        T: AsyncFileReader + Send + 'static,
+    {
+        for (name, values) in &self.lookups {
+            let col_idx = match builder
+                .metadata()
+                .row_group(row_group)
+                .columns()
+                .iter()
+                .position(|col| &col.column_path().string() == name)
+            {
+                Some(idx) => idx,
+                None => continue,
+            };
+
+            let filter = match builder
+                .get_row_group_column_bloom_filter(row_group, col_idx)
+                .await?
+            {
+                Some(filter) => filter,
+                None => continue,
+            };
+
+            if !values.iter().any(|value| may_contain(&filter, value)) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Extract `col = value` and `col IN (values...)` filters.
+fn point_lookup(expr: &Expr) -> Option<(String, Vec<ScalarValue>)> {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Eq,
+            right,
+        }) => match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(col), Expr::Literal(value))
+            | (Expr::Literal(value), Expr::Column(col)) => {
+                Some((col.name.clone(), vec![value.clone()]))
+            }
+            _ => None,
+        },
+        Expr::InList(InList {
+            expr,
+            list,
+            negated: false,
+        }) => {
+            let col = match expr.as_ref() {
+                Expr::Column(col) => col,
+                _ => return None,
+            };
+            let values = list
+                .iter()
+                .map(|expr| match expr {
+                    Expr::Literal(value) => Some(value.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some((col.name.clone(), values))
+        }
+        _ => None,
+    }
+}
+
+/// Check a value against a bloom filter, hashing it the way it's stored in
+/// parquet.
+fn may_contain(filter: &Sbbf, value: &ScalarValue) -> bool {
+    match value {
+        // Nulls never compare equal.
+        value if value.is_null() => false,
+        ScalarValue::Int8(Some(v)) => filter.check(&(*v as i32)),
+        ScalarValue::Int16(Some(v)) => filter.check(&(*v as i32)),
+        ScalarValue::Int32(Some(v)) | ScalarValue::Date32(Some(v)) => filter.check(v),
+        ScalarValue::Int64(Some(v)) => filter.check(v),
+        ScalarValue::Float32(Some(v)) => filter.check(v),
+        ScalarValue::Float64(Some(v)) => filter.check(v),
+        ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => filter.check(v.as_str()),
+        // Unknown how it's stored.
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::arrow::datatypes::Field;
+    use datafusion::logical_expr::Operator;
+    use datafusion::physical_expr::expressions::{col, lit, BinaryExpr};
+
+    use super::*;
+
+    fn file_stats(min: i64, max: i64) -> Statistics {
+        Statistics {
+            num_rows: Some(10),
+            column_statistics: Some(vec![ColumnStatistics {
+                null_count: Some(0),
+                min_value: Some(ScalarValue::Int64(Some(min))),
+                max_value: Some(ScalarValue::Int64(Some(max))),
+                distinct_count: None,
+            }]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn prune_files_by_min_max() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+        let expr = Arc::new(PhysicalBinaryExpr::new(
+            col("a", &schema).unwrap(),
+            Operator::Gt,
+            lit(15_i64),
+        ));
+        let predicate = PruningPredicate::try_new(expr, schema.clone()).unwrap();
+
+        let files = vec![file_stats(0, 9), file_stats(10, 19), file_stats(20, 29)];
+        let keep = prune(
+            &predicate,
+            &FileStatistics {
+                schema: &schema,
+                files: &files,
+            },
+        );
+        assert_eq!(vec![false, true, true], keep);
+
+        // Files without statistics are always scanned.
+        let files = vec![Statistics::default(), file_stats(0, 9)];
+        let keep = prune(
+            &predicate,
+            &FileStatistics {
+                schema: &schema,
+                files: &files,
+            },
+        );
+        assert_eq!(vec![true, false], keep);
+    }
+
+    #[test]
+    fn bloom_filter_point_lookups() {
+        let filters = vec![
+            logical_col("a")
+                .eq(logical_lit(1_i64))
+                .and(logical_col("b").in_list(vec![logical_lit("x"), logical_lit("y")], false)),
+            logical_col("c").gt(logical_lit(1_i64)),
+        ];
+        let predicate = BloomFilterPredicate::try_new(&filters).unwrap();
+        assert_eq!(
+            vec![
+                ("a".to_string(), vec![ScalarValue::Int64(Some(1))]),
+                (
+                    "b".to_string(),
+                    vec![
+                        ScalarValue::Utf8(Some("x".to_string())),
+                        ScalarValue::Utf8(Some("y".to_string()))
+                    ]
+                ),
+            ],
+            predicate.lookups
+        );
+
+        let filters = vec![logical_col("b").in_list(vec![logical_lit("x")], true)];
+        assert!(BloomFilterPredicate::try_new(&filters).is_none());
+    }
+}
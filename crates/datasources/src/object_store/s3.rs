@@ -5,6 +5,7 @@ use datafusion::execution::object_store::ObjectStoreUrl;
 use object_store::aws::AmazonS3Builder;
 use object_store::path::Path as ObjectStorePath;
 use object_store::ObjectStore;
+use object_store_util::fixtures::fixture_store;
 
 use super::errors::{ObjectStoreSourceError, Result};
 use super::ObjStoreAccess;
@@ -35,6 +36,16 @@ impl ObjStoreAccess for S3StoreAccess {
     }
 
     fn create_store(&self) -> Result<Arc<dyn ObjectStore>> {
+        fixture_store("s3", || self.create_remote_store())
+    }
+
+    fn path(&self, location: &str) -> Result<ObjectStorePath> {
+        Ok(ObjectStorePath::from_url_path(location)?)
+    }
+}
+
+impl S3StoreAccess {
+    fn create_remote_store(&self) -> Result<Arc<dyn ObjectStore>> {
         let builder = AmazonS3Builder::new()
             .with_region(&self.region)
             .with_bucket_name(&self.bucket);
@@ -57,8 +68,4 @@ impl ObjStoreAccess for S3StoreAccess {
         let build = builder.build()?;
         Ok(Arc::new(build))
     }
-
-    fn path(&self, location: &str) -> Result<ObjectStorePath> {
-        Ok(ObjectStorePath::from_url_path(location)?)
-    }
 }
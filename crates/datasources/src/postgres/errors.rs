@@ -15,21 +15,33 @@ pub enum PostgresError {
     #[error("Too many ports provided. Provide one port or no ports (default port 5432 will be used): {0:?}")]
     TooManyPorts(Vec<u16>),
 
+    #[error("Invalid numeric fallback: '{0}', expected 'decimal256' or 'text'")]
+    InvalidNumericFallback(String),
+
+    #[error("Numeric value out of range for {datatype}: {value}")]
+    NumericOutOfRange {
+        value: String,
+        datatype: datafusion::arrow::datatypes::DataType,
+    },
+
     #[error("Unable to copy binary row value for datatype: {0}")]
     FailedBinaryCopy(datafusion::arrow::datatypes::DataType),
 
     #[error("Failed to connect to Postgres instance: {0}")]
     TokioPostgres(#[from] tokio_postgres::Error),
 
+    #[error("Invalid unload location: '{0}', expected an S3 location")]
+    InvalidUnloadLocation(String),
+
     #[error("Unuspported ssl mode: {0:?}")]
     UnsupportSslMode(tokio_postgres::config::SslMode),
 
-    #[error("Unsupported tunnel '{0}' for Postgres")]
-    UnsupportedTunnel(String),
-
     #[error(transparent)]
     Arrow(#[from] datafusion::arrow::error::ArrowError),
 
+    #[error(transparent)]
+    DataFusion(#[from] datafusion::error::DataFusionError),
+
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
@@ -49,9 +61,10 @@ pub enum PostgresError {
     ProtoConv(#[from] protogen::ProtoConvError),
 
     #[error(transparent)]
-    SshKey(#[from] crate::common::ssh::key::SshKeyError),
+    ObjectStore(#[from] crate::object_store::errors::ObjectStoreSourceError),
+
     #[error(transparent)]
-    SshTunnel(#[from] crate::common::ssh::session::SshTunnelError),
+    Tunnel(#[from] crate::common::tunnel::TunnelError),
 }
 
 pub type Result<T, E = PostgresError> = std::result::Result<T, E>;
@@ -1,17 +1,19 @@
 pub mod errors;
 
 mod query_exec;
-mod tls;
+pub mod redshift;
+pub mod tls;
 
-use crate::common::ssh::session::SshTunnelSession;
-use crate::common::ssh::{key::SshKey, session::SshTunnelAccess};
+use crate::common::egress::{check_egress, resolve_egress};
+use crate::common::tunnel::TunnelSession;
 use crate::common::util::{self, create_count_record_batch};
 use async_trait::async_trait;
 use chrono::naive::{NaiveDateTime, NaiveTime};
 use chrono::{DateTime, NaiveDate, Timelike, Utc};
-use datafusion::arrow::array::Decimal128Builder;
+use datafusion::arrow::array::{Decimal128Builder, Decimal256Builder};
 use datafusion::arrow::datatypes::{
-    DataType, Field, Fields, Schema as ArrowSchema, SchemaRef as ArrowSchemaRef, TimeUnit,
+    i256, DataType, Field, Fields, IntervalMonthDayNanoType, IntervalUnit, Schema as ArrowSchema,
+    SchemaRef as ArrowSchemaRef, TimeUnit, DECIMAL128_MAX_PRECISION, DECIMAL256_MAX_PRECISION,
 };
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::datasource::TableProvider;
@@ -24,13 +26,17 @@ use datafusion::physical_plan::memory::MemoryExec;
 use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
 use datafusion::physical_plan::metrics::MetricsSet;
 use datafusion::physical_plan::{
-    execute_stream, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
+    collect, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
     SendableRecordBatchStream, Statistics,
 };
-use datafusion::scalar::ScalarValue;
 use datafusion_ext::errors::ExtensionError;
 use datafusion_ext::functions::VirtualLister;
+use datafusion_ext::geometry::Geometry;
 use datafusion_ext::metrics::DataSourceMetricsStreamAdapter;
+use datafusion_ext::types::{
+    geometry_field, is_geometry_field, is_uuid_field, uuid_field, GEOMETRY_DATA_TYPE,
+    UUID_DATA_TYPE,
+};
 use errors::{PostgresError, Result};
 use futures::{future::BoxFuture, ready, stream::BoxStream, FutureExt, Stream, StreamExt};
 use protogen::metastore::types::options::TunnelOptions;
@@ -39,7 +45,10 @@ use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::borrow::{Borrow, Cow};
 use std::fmt::{self, Write};
+use std::io;
+use std::net::SocketAddr;
 use std::pin::Pin;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite};
@@ -48,8 +57,8 @@ use tokio::task::JoinHandle;
 use tokio_postgres::binary_copy::{BinaryCopyOutRow, BinaryCopyOutStream};
 use tokio_postgres::config::{Host, SslMode};
 use tokio_postgres::tls::MakeTlsConnect;
-use tokio_postgres::types::{FromSql, Type as PostgresType};
-use tokio_postgres::{Client, Config, Connection, CopyOutStream, NoTls, Socket};
+use tokio_postgres::types::{FromSql, Kind, Type as PostgresType};
+use tokio_postgres::{Client, Config, Connection, CopyOutStream, NoTls};
 use tracing::{debug, warn};
 
 use self::query_exec::PostgresQueryExec;
@@ -96,6 +105,45 @@ impl PostgresDbConnection {
     }
 }
 
+/// How to represent numeric columns with a precision too large for a
+/// `Decimal128`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumericFallback {
+    /// Use a `Decimal256`. Numerics with a precision larger than what's
+    /// supported by a `Decimal256` are passed through as text.
+    #[default]
+    Decimal256,
+    /// Pass values through as text.
+    Text,
+}
+
+impl NumericFallback {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Decimal256 => "decimal256",
+            Self::Text => "text",
+        }
+    }
+}
+
+impl fmt::Display for NumericFallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NumericFallback {
+    type Err = PostgresError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "decimal256" => Self::Decimal256,
+            "text" => Self::Text,
+            other => return Err(PostgresError::InvalidNumericFallback(other.to_string())),
+        })
+    }
+}
+
 /// Information needed for create a postgres client.
 #[derive(Debug, Clone)]
 pub struct PostgresAccess {
@@ -103,6 +151,8 @@ pub struct PostgresAccess {
     pub conn_str: PostgresDbConnection,
     /// Tunnel to use to access instance.
     pub tunnel: Option<TunnelOptions>,
+    /// Representation of numerics that don't fit in a `Decimal128`.
+    pub numeric_fallback: NumericFallback,
 }
 
 impl PostgresAccess {
@@ -113,14 +163,21 @@ impl PostgresAccess {
         PostgresAccess {
             conn_str: PostgresDbConnection::ConnectionString(conn_str.into()),
             tunnel,
+            numeric_fallback: NumericFallback::default(),
         }
     }
 
+    pub fn with_numeric_fallback(mut self, numeric_fallback: NumericFallback) -> Self {
+        self.numeric_fallback = numeric_fallback;
+        self
+    }
+
     /// Connect to an instance using these connection details.
     pub async fn connect(&self) -> Result<PostgresAccessState> {
-        let state =
+        let mut state =
             PostgresAccessState::connect(&self.conn_str.connection_string(), self.tunnel.clone())
                 .await?;
+        state.numeric_fallback = self.numeric_fallback;
         Ok(state)
     }
 
@@ -145,10 +202,19 @@ impl TryFrom<protogen::sqlexec::common::PostgresAccess> for PostgresAccess {
     fn try_from(
         value: protogen::sqlexec::common::PostgresAccess,
     ) -> std::result::Result<Self, Self::Error> {
-        Ok(Self::new_from_conn_str(
+        let access = Self::new_from_conn_str(
             value.conn_str,
             value.tunnel.map(|t| t.try_into()).transpose()?,
-        ))
+        );
+        // Empty for plans serialized before the fallback was configurable.
+        if value.numeric_fallback.is_empty() {
+            return Ok(access);
+        }
+        let numeric_fallback = value
+            .numeric_fallback
+            .parse()
+            .map_err(|e: PostgresError| ProtoConvError::ParseError(e.to_string()))?;
+        Ok(access.with_numeric_fallback(numeric_fallback))
     }
 }
 
@@ -157,6 +223,7 @@ impl From<PostgresAccess> for protogen::sqlexec::common::PostgresAccess {
         Self {
             conn_str: value.conn_str.connection_string(),
             tunnel: value.tunnel.map(|t| t.into()),
+            numeric_fallback: value.numeric_fallback.to_string(),
         }
     }
 }
@@ -166,11 +233,13 @@ pub struct PostgresAccessState {
     /// The Postgres client.
     client: tokio_postgres::Client,
     /// Handle for the underlying Postgres connection.
-    /// Also contains the `Session` for the underlying ssh tunnel
+    /// Also contains the session for the underlying tunnel.
     ///
-    /// Kept on struct to avoid dropping the postgres connection future and ssh tunnel.
+    /// Kept on struct to avoid dropping the postgres connection future and tunnel.
     #[allow(dead_code)]
     conn_handle: JoinHandle<()>,
+    /// Representation of numerics that don't fit in a `Decimal128`.
+    numeric_fallback: NumericFallback,
 }
 
 impl PostgresAccessState {
@@ -181,6 +250,7 @@ impl PostgresAccessState {
         Ok(PostgresAccessState {
             client,
             conn_handle,
+            numeric_fallback: NumericFallback::default(),
         })
     }
 
@@ -190,23 +260,66 @@ impl PostgresAccessState {
     ) -> Result<(Client, JoinHandle<()>)> {
         match tunnel {
             None => Self::connect_direct(connection_string).await,
-            Some(TunnelOptions::Ssh(ssh_options)) => {
-                let keypair = SshKey::from_bytes(&ssh_options.ssh_key)?;
-                let tunnel_access = SshTunnelAccess {
-                    connection_string: ssh_options.connection_string,
-                    keypair,
-                };
-                Self::connect_with_ssh_tunnel(connection_string, &tunnel_access).await
+            Some(tunnel) => {
+                Self::check_egress(&connection_string.parse()?).await?;
+                Self::connect_with_tunnel(connection_string, &tunnel).await
+            }
+        }
+    }
+
+    /// Check that the network policy allows connecting to all hosts in the
+    /// config. Unix sockets are checked as connections to localhost.
+    async fn check_egress(config: &Config) -> Result<()> {
+        let ports = config.get_ports();
+        for (idx, host) in config.get_hosts().iter().enumerate() {
+            let port = ports.get(idx).or(ports.first()).copied().unwrap_or(5432);
+            match host {
+                Host::Tcp(host) => check_egress(host, port).await?,
+                #[allow(unreachable_patterns)]
+                _ => check_egress("localhost", port).await?,
             }
-            Some(opt) => Err(PostgresError::UnsupportedTunnel(opt.to_string())),
         }
+        Ok(())
     }
 
+    /// Connect to the first host in the config accepting connections.
+    ///
+    /// Each host is resolved and checked against the network policy once, and
+    /// the connection is made to the checked addresses instead of letting
+    /// tokio-postgres resolve the host again. Unix sockets aren't supported.
     async fn connect_direct(connection_string: &str) -> Result<(Client, JoinHandle<()>)> {
         let config: Config = connection_string.parse()?;
+        let ports = config.get_ports();
+
+        let mut last_err = None;
+        for (idx, host) in config.get_hosts().iter().enumerate() {
+            let host = match host {
+                Host::Tcp(host) => host,
+                #[allow(unreachable_patterns)]
+                _ => continue,
+            };
+            let port = ports.get(idx).or(ports.first()).copied().unwrap_or(5432);
+            match Self::connect_host(&config, host, port).await {
+                Ok(conn) => return Ok(conn),
+                Err(e) => {
+                    debug!(%e, %host, "failed to connect to postgres host");
+                    last_err = Some(e);
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| PostgresError::InvalidPgHosts(config.get_hosts().to_vec())))
+    }
+
+    async fn connect_host(
+        config: &Config,
+        host: &str,
+        port: u16,
+    ) -> Result<(Client, JoinHandle<()>)> {
+        let addrs = resolve_egress(host, port).await?;
 
         fn spawn_conn<T: AsyncRead + AsyncWrite + Send + Unpin + 'static>(
-            conn: Connection<Socket, T>,
+            conn: Connection<TcpStream, T>,
         ) -> JoinHandle<()> {
             tokio::spawn(async move {
                 if let Err(e) = conn.await {
@@ -215,6 +328,33 @@ impl PostgresAccessState {
             })
         }
 
+        async fn connect_tcp(addrs: &[SocketAddr], config: &Config) -> Result<TcpStream> {
+            let connect = TcpStream::connect(addrs);
+            let stream = match config.get_connect_timeout() {
+                Some(timeout) => match tokio::time::timeout(*timeout, connect).await {
+                    Ok(stream) => stream?,
+                    Err(_) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "timed out connecting to postgres",
+                        )
+                        .into())
+                    }
+                },
+                None => connect.await?,
+            };
+            stream.set_nodelay(true)?;
+            Ok(stream)
+        }
+
+        let tcp_stream = connect_tcp(&addrs, config).await?;
+
+        // Rust doesn't feel like type inferring this for us.
+        let tls_connect = <tls::MakeRustlsConnect as MakeTlsConnect<TcpStream>>::make_tls_connect(
+            &mut tls::MakeRustlsConnect::default(),
+            host,
+        )?;
+
         // Configure tls depending on ssl mode.
         //
         // - Disable => no tls
@@ -223,14 +363,12 @@ impl PostgresAccessState {
         // - Any other => return unsupported
         let (client, handle) = match config.get_ssl_mode() {
             SslMode::Disable => {
-                let (client, conn) = tokio_postgres::connect(connection_string, NoTls).await?;
+                let (client, conn) = config.connect_raw(tcp_stream, NoTls).await?;
                 let handle = spawn_conn(conn);
                 (client, handle)
             }
             SslMode::Prefer => {
-                match tokio_postgres::connect(connection_string, tls::MakeRustlsConnect::default())
-                    .await
-                {
+                match config.connect_raw(tcp_stream, tls_connect).await {
                     Ok((client, conn)) => {
                         let handle = spawn_conn(conn);
                         (client, handle)
@@ -239,17 +377,16 @@ impl PostgresAccessState {
                         // The tokio postgres lib does discriminate between tls
                         // and other errors, but that's not made public.
                         debug!(%e, "pg conn falling back to no tls");
-                        let (client, conn) =
-                            tokio_postgres::connect(connection_string, NoTls).await?;
+                        // Reconnect to get a fresh stream.
+                        let tcp_stream = connect_tcp(&addrs, config).await?;
+                        let (client, conn) = config.connect_raw(tcp_stream, NoTls).await?;
                         let handle = spawn_conn(conn);
                         (client, handle)
                     }
                 }
             }
             SslMode::Require => {
-                let (client, conn) =
-                    tokio_postgres::connect(connection_string, tls::MakeRustlsConnect::default())
-                        .await?;
+                let (client, conn) = config.connect_raw(tcp_stream, tls_connect).await?;
                 let handle = spawn_conn(conn);
                 (client, handle)
             }
@@ -259,9 +396,9 @@ impl PostgresAccessState {
         Ok((client, handle))
     }
 
-    async fn connect_with_ssh_tunnel(
+    async fn connect_with_tunnel(
         connection_string: &str,
-        ssh_tunnel: &SshTunnelAccess,
+        tunnel: &TunnelOptions,
     ) -> Result<(Client, JoinHandle<()>)> {
         let config: Config = connection_string.parse()?;
 
@@ -276,22 +413,20 @@ impl PostgresAccessState {
 
         let postgres_port = config.get_ports().iter().next().cloned().unwrap_or(5432);
 
-        // Open ssh tunnel
-        let (session, tunnel_addr) = ssh_tunnel
-            .create_tunnel(&(postgres_host, postgres_port))
-            .await?;
+        let (session, tunnel_addr) =
+            TunnelSession::open(tunnel, &postgres_host, postgres_port).await?;
 
         fn spawn_conn<T: AsyncRead + AsyncWrite + Send + Unpin + 'static>(
             conn: Connection<TcpStream, T>,
-            session: SshTunnelSession,
+            session: TunnelSession,
         ) -> JoinHandle<()> {
             tokio::spawn(async move {
                 if let Err(e) = conn.await {
                     warn!(%e, "postgres connection errored");
                 }
-                // If postgres connection is complete, close ssh tunnel
+                // If postgres connection is complete, close the tunnel
                 if let Err(e) = session.close().await {
-                    warn!(%e, "closing ssh tunnel errored");
+                    warn!(%e, "closing tunnel errored");
                 }
             })
         }
@@ -301,8 +436,8 @@ impl PostgresAccessState {
         // Rust doesn't feel like type inferring this for us.
         let tls_connect = <tls::MakeRustlsConnect as MakeTlsConnect<TcpStream>>::make_tls_connect(
             &mut tls::MakeRustlsConnect::default(),
-            // TODO: Which host do we want to specify? Since this is being tunneled
-            // over ssh, I don't know if SNI actually matters.
+            // TODO: Which host do we want to specify? Since this is being
+            // tunneled, I don't know if SNI actually matters.
             "",
         )?;
 
@@ -393,9 +528,13 @@ WHERE nspname=$1 AND relname=$2;
                 "
 SELECT
     attname,
-    pg_type.oid
+    pg_type.oid,
+    atttypmod,
+    pg_type.typname,
+    pg_namespace.nspname
 FROM pg_attribute
     INNER JOIN pg_type ON atttypid=pg_type.oid
+    INNER JOIN pg_namespace ON pg_type.typnamespace=pg_namespace.oid
 WHERE attrelid=$1 AND attnum > 0
 ORDER BY attnum;
 ",
@@ -404,33 +543,35 @@ ORDER BY attnum;
             .await?;
 
         let mut names: Vec<String> = Vec::with_capacity(rows.len());
-        let mut type_oids: Vec<u32> = Vec::with_capacity(rows.len());
+        let mut pg_types: Vec<PostgresType> = Vec::with_capacity(rows.len());
+        let mut typmods: Vec<i32> = Vec::with_capacity(rows.len());
+        let mut unknown_type_oids: Vec<u32> = Vec::new();
         for row in rows {
             names.push(row.try_get(0)?);
-            type_oids.push(row.try_get(1)?);
+            let oid: u32 = row.try_get(1)?;
+            typmods.push(row.try_get(2)?);
+
+            // Types from extensions (e.g. PostGIS) don't have a fixed oid.
+            let typname: String = row.try_get(3)?;
+            let typ = match PostgresType::from_oid(oid) {
+                Some(typ) => typ,
+                None if typname == POSTGIS_GEOMETRY_TYPE_NAME => {
+                    PostgresType::new(typname, oid, Kind::Simple, row.try_get(4)?)
+                }
+                None => {
+                    unknown_type_oids.push(oid);
+                    continue;
+                }
+            };
+            pg_types.push(typ);
         }
 
-        let mut unknown_type_oids: Vec<u32> = Vec::new();
-
-        type_oids.iter().for_each(|oid| {
-            if PostgresType::from_oid(*oid).is_none() {
-                unknown_type_oids.push(*oid);
-            }
-        });
-
         if !unknown_type_oids.is_empty() {
             return Err(PostgresError::UnknownPostgresOids(unknown_type_oids));
         }
 
-        //will Proceed here ONLY if types are known for all oids else returns error with unknown_type_oids
-
-        let pg_types = type_oids
-            .iter()
-            .map(|oid| PostgresType::from_oid(*oid))
-            .collect::<Option<Vec<_>>>()
-            .ok_or(PostgresError::UnknownPostgresOids(type_oids))?;
-
-        let arrow_schema = try_create_arrow_schema(names, &pg_types)?;
+        let arrow_schema =
+            try_create_arrow_schema(names, &pg_types, &typmods, self.numeric_fallback)?;
         Ok((arrow_schema, pg_types))
     }
 }
@@ -668,30 +809,13 @@ impl TableProvider for PostgresTableProvider {
         input: Arc<dyn ExecutionPlan>,
         _overwrite: bool,
     ) -> DatafusionResult<Arc<dyn ExecutionPlan>> {
-        let mut values = String::new();
-
-        let mut input = execute_stream(input, state.task_ctx())?;
-        while let Some(batch) = input.next().await {
-            let batch = batch?;
-
-            let mut row_sep = "";
-            for row_idx in 0..batch.num_rows() {
-                write!(&mut values, "{row_sep}(")?;
-                row_sep = ",";
-
-                let mut col_sep = "";
-                for col in batch.columns() {
-                    write!(&mut values, "{col_sep}")?;
-                    col_sep = ",";
-
-                    let val = ScalarValue::try_from_array(col.as_ref(), row_idx)?;
-                    util::encode_literal_to_text(util::Datasource::Postgres, &mut values, &val)
-                        .map_err(|e| DataFusionError::External(Box::new(e)))?;
-                }
-
-                write!(&mut values, ")")?;
-            }
-        }
+        let batches = collect(input, state.task_ctx()).await?;
+        let values = util::encode_insert_values(
+            util::Datasource::Postgres,
+            &batches,
+            util::external_insert_batch_size(state),
+        )
+        .map_err(|e| DataFusionError::External(Box::new(e)))?;
 
         if values.is_empty() {
             let batch = create_count_record_batch(0);
@@ -699,14 +823,19 @@ impl TableProvider for PostgresTableProvider {
             return Ok(Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?));
         }
 
-        let query = format!(
-            "INSERT INTO {}.{} VALUES {}",
-            self.schema, self.table, values
-        );
+        let queries = values
+            .into_iter()
+            .map(|values| {
+                format!(
+                    "INSERT INTO {}.{} VALUES {}",
+                    self.schema, self.table, values
+                )
+            })
+            .collect::<Vec<_>>();
 
-        debug!(%query, "inserting into postgres datasource");
+        debug!(num_statements = %queries.len(), "inserting into postgres datasource");
 
-        let exec = PostgresQueryExec::new(query, self.state.clone());
+        let exec = PostgresQueryExec::new(queries, self.state.clone());
         Ok(Arc::new(exec))
     }
 }
@@ -1009,17 +1138,6 @@ impl<'a> From<&'a str> for Str<'a> {
     }
 }
 
-impl<'a> TryFrom<uuid::Uuid> for Str<'a> {
-    type Error = std::str::Utf8Error;
-
-    fn try_from(value: uuid::Uuid) -> Result<Self, Self::Error> {
-        let mut buf = [0; 36];
-        value.as_hyphenated().encode_lower(&mut buf);
-        let s = std::str::from_utf8(&buf)?;
-        Ok(Self(Cow::from(s.to_owned())))
-    }
-}
-
 impl<'a> From<serde_json::Value> for Str<'a> {
     fn from(value: serde_json::Value) -> Self {
         Self(Cow::from(format!("{value}")))
@@ -1030,9 +1148,9 @@ impl<'a> FromSql<'a> for Str<'a> {
     fn accepts(ty: &PostgresType) -> bool {
         type S<'a> = &'a str;
         S::accepts(ty)
-            || ty == &PostgresType::UUID
             || ty == &PostgresType::JSON
             || ty == &PostgresType::JSONB
+            || ty == &PostgresType::NUMERIC
     }
 
     fn from_sql(
@@ -1040,8 +1158,8 @@ impl<'a> FromSql<'a> for Str<'a> {
         raw: &'a [u8],
     ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
         match ty.name() {
-            "uuid" => Ok(uuid::Uuid::from_sql(ty, raw)?.try_into()?),
             "json" | "jsonb" => Ok(serde_json::Value::from_sql(ty, raw)?.into()),
+            "numeric" => Ok(Self(Cow::from(PgNumeric::from_sql(ty, raw)?.0))),
             _ => {
                 type S<'a> = &'a str;
                 Ok(S::from_sql(ty, raw)?.into())
@@ -1050,6 +1168,157 @@ impl<'a> FromSql<'a> for Str<'a> {
     }
 }
 
+/// Interval as encoded by postgres in binary.
+///
+/// tokio-postgres doesn't support intervals, so we need to decode them
+/// ourselves. Postgres intervals have microsecond precision.
+struct PgInterval {
+    months: i32,
+    days: i32,
+    micros: i64,
+}
+
+impl<'a> FromSql<'a> for PgInterval {
+    fn accepts(ty: &PostgresType) -> bool {
+        ty == &PostgresType::INTERVAL
+    }
+
+    fn from_sql(
+        _ty: &PostgresType,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        if raw.len() != 16 {
+            return Err(format!("invalid binary interval length: {}", raw.len()).into());
+        }
+        Ok(PgInterval {
+            micros: i64::from_be_bytes(raw[0..8].try_into()?),
+            days: i32::from_be_bytes(raw[8..12].try_into()?),
+            months: i32::from_be_bytes(raw[12..16].try_into()?),
+        })
+    }
+}
+
+/// Name of the geometry type added by the PostGIS extension.
+const POSTGIS_GEOMETRY_TYPE_NAME: &str = "geometry";
+
+/// PostGIS geometry converted to WKB.
+///
+/// PostGIS sends geometries as EWKB, which includes the SRID.
+struct PgGeometry(Vec<u8>);
+
+impl<'a> FromSql<'a> for PgGeometry {
+    fn accepts(ty: &PostgresType) -> bool {
+        ty.name() == POSTGIS_GEOMETRY_TYPE_NAME
+    }
+
+    fn from_sql(
+        _ty: &PostgresType,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(PgGeometry(Geometry::from_wkb(raw)?.to_wkb()))
+    }
+}
+
+/// Numeric as encoded by postgres in binary, converted to its text
+/// representation (e.g. "-1234.5600").
+///
+/// Numerics are decoded ourselves instead of through `rust_decimal` since that
+/// only supports up to 28 significant digits.
+struct PgNumeric(String);
+
+impl PgNumeric {
+    const POSITIVE: u16 = 0x0000;
+    const NEGATIVE: u16 = 0x4000;
+    const NAN: u16 = 0xC000;
+    const POSITIVE_INFINITY: u16 = 0xD000;
+    const NEGATIVE_INFINITY: u16 = 0xF000;
+
+    /// Get the mantissa of the numeric as a string of digits for the given
+    /// scale. Extra fractional digits are truncated.
+    ///
+    /// Returns `None` if the numeric isn't a finite number.
+    fn mantissa(&self, scale: usize) -> Option<String> {
+        let (int, frac) = self.0.split_once('.').unwrap_or((&self.0, ""));
+        if !int
+            .trim_start_matches('-')
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+        {
+            return None;
+        }
+        let mut mantissa = String::with_capacity(int.len() + scale);
+        mantissa.push_str(int);
+        mantissa.extend(frac.chars().chain(std::iter::repeat('0')).take(scale));
+        Some(mantissa)
+    }
+}
+
+impl<'a> FromSql<'a> for PgNumeric {
+    fn accepts(ty: &PostgresType) -> bool {
+        ty == &PostgresType::NUMERIC
+    }
+
+    fn from_sql(
+        _ty: &PostgresType,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        // Header is the number of digits, weight of the first digit, sign,
+        // and display scale, followed by the base 10000 digits.
+        let read_u16 = |idx: usize| {
+            raw.get(idx..idx + 2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                .ok_or_else(|| format!("invalid binary numeric length: {}", raw.len()))
+        };
+        let num_digits = read_u16(0)? as usize;
+        let weight = read_u16(2)? as i16 as i32;
+        let sign = read_u16(4)?;
+        let scale = read_u16(6)? as usize;
+        let digits = (0..num_digits)
+            .map(|idx| read_u16(8 + idx * 2))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Get the digit with the given weight.
+        let digit_at = |weight_idx: i32| {
+            usize::try_from(weight_idx)
+                .ok()
+                .and_then(|idx| digits.get(idx).copied())
+                .unwrap_or(0)
+        };
+
+        let mut s = String::new();
+        match sign {
+            Self::POSITIVE => (),
+            Self::NEGATIVE => s.push('-'),
+            Self::NAN => return Ok(PgNumeric("NaN".to_string())),
+            Self::POSITIVE_INFINITY => return Ok(PgNumeric("Infinity".to_string())),
+            Self::NEGATIVE_INFINITY => return Ok(PgNumeric("-Infinity".to_string())),
+            other => return Err(format!("invalid binary numeric sign: {other:#x}").into()),
+        }
+
+        if weight < 0 {
+            s.push('0');
+        } else {
+            write!(s, "{}", digit_at(0))?;
+            for idx in 1..=weight {
+                write!(s, "{:04}", digit_at(idx))?;
+            }
+        }
+
+        if scale > 0 {
+            let mut frac = String::with_capacity(scale + 4);
+            let mut idx = weight + 1;
+            while frac.len() < scale {
+                write!(frac, "{:04}", digit_at(idx))?;
+                idx += 1;
+            }
+            frac.truncate(scale);
+            s.push('.');
+            s.push_str(&frac);
+        }
+
+        Ok(PgNumeric(s))
+    }
+}
+
 /// Macro for generating the match arms when converting a binary row to a record
 /// batch.
 ///
@@ -1071,8 +1340,9 @@ fn binary_rows_to_record_batch<E: Into<PostgresError>>(
     schema: ArrowSchemaRef,
 ) -> Result<RecordBatch> {
     use datafusion::arrow::array::{
-        Array, BinaryBuilder, BooleanBuilder, Date32Builder, Float32Builder, Float64Builder,
-        Int16Builder, Int32Builder, Int64Builder, StringBuilder, Time64NanosecondBuilder,
+        Array, BinaryBuilder, BooleanBuilder, Date32Builder, FixedSizeBinaryBuilder,
+        Float32Builder, Float64Builder, Int16Builder, Int32Builder, Int64Builder,
+        IntervalMonthDayNanoBuilder, StringBuilder, Time64NanosecondBuilder,
         TimestampNanosecondBuilder,
     };
 
@@ -1106,6 +1376,14 @@ fn binary_rows_to_record_batch<E: Into<PostgresError>>(
                 }
                 Arc::new(arr.finish())
             }
+            DataType::Binary if is_geometry_field(field) => {
+                let mut arr = BinaryBuilder::with_capacity(rows.len(), rows.len() * 32);
+                for row in rows.iter() {
+                    let val: Option<PgGeometry> = row.try_get(col_idx)?;
+                    arr.append_option(val.map(|v| v.0));
+                }
+                Arc::new(arr.finish())
+            }
             DataType::Binary => {
                 // Assumes an average of 16 bytes per item.
                 let mut arr = BinaryBuilder::with_capacity(rows.len(), rows.len() * 16);
@@ -1115,18 +1393,51 @@ fn binary_rows_to_record_batch<E: Into<PostgresError>>(
                 }
                 Arc::new(arr.finish())
             }
+            DataType::FixedSizeBinary(16) if is_uuid_field(field) => {
+                let mut arr = FixedSizeBinaryBuilder::with_capacity(rows.len(), 16);
+                for row in rows.iter() {
+                    let val: Option<uuid::Uuid> = row.try_get(col_idx)?;
+                    match val {
+                        Some(v) => arr.append_value(v.as_bytes())?,
+                        None => arr.append_null(),
+                    }
+                }
+                Arc::new(arr.finish())
+            }
             dt @ DataType::Decimal128(_p, s) => {
                 let mut arr =
                     Decimal128Builder::with_capacity(rows.len()).with_data_type(dt.clone());
                 for row in rows.iter() {
-                    let val: Option<rust_decimal::Decimal> = row.try_get(col_idx)?;
+                    let val: Option<PgNumeric> = row.try_get(col_idx)?;
                     let val = match val {
-                        Some(v) => {
-                            let mut v =
-                                decimal::Decimal128::new(v.mantissa(), v.scale().try_into()?)?;
-                            v.rescale(*s);
-                            Some(v.mantissa())
-                        }
+                        Some(v) => Some(
+                            v.mantissa(*s as usize)
+                                .and_then(|m| m.parse::<i128>().ok())
+                                .ok_or_else(|| PostgresError::NumericOutOfRange {
+                                    value: v.0,
+                                    datatype: dt.clone(),
+                                })?,
+                        ),
+                        None => None,
+                    };
+                    arr.append_option(val);
+                }
+                Arc::new(arr.finish())
+            }
+            dt @ DataType::Decimal256(_p, s) => {
+                let mut arr =
+                    Decimal256Builder::with_capacity(rows.len()).with_data_type(dt.clone());
+                for row in rows.iter() {
+                    let val: Option<PgNumeric> = row.try_get(col_idx)?;
+                    let val = match val {
+                        Some(v) => Some(
+                            v.mantissa(*s as usize)
+                                .and_then(|m| i256::from_string(&m))
+                                .ok_or_else(|| PostgresError::NumericOutOfRange {
+                                    value: v.0,
+                                    datatype: dt.clone(),
+                                })?,
+                        ),
                         None => None,
                     };
                     arr.append_option(val);
@@ -1165,6 +1476,17 @@ fn binary_rows_to_record_batch<E: Into<PostgresError>>(
                 }
                 Arc::new(arr.finish())
             }
+            DataType::Interval(IntervalUnit::MonthDayNano) => {
+                let mut arr = IntervalMonthDayNanoBuilder::with_capacity(rows.len());
+                for row in rows.iter() {
+                    let val: Option<PgInterval> = row.try_get(col_idx)?;
+                    let val = val.map(|v| {
+                        IntervalMonthDayNanoType::make_value(v.months, v.days, v.micros * 1_000)
+                    });
+                    arr.append_option(val);
+                }
+                Arc::new(arr.finish())
+            }
             DataType::Date32 => {
                 let mut arr = Date32Builder::with_capacity(rows.len());
                 for row in rows.iter() {
@@ -1184,11 +1506,18 @@ fn binary_rows_to_record_batch<E: Into<PostgresError>>(
 }
 
 /// Create an arrow schema from a list of names and stringified postgres types.
-fn try_create_arrow_schema(names: Vec<String>, types: &Vec<PostgresType>) -> Result<ArrowSchema> {
+///
+/// Type modifiers are used to get the precision and scale of numerics.
+fn try_create_arrow_schema(
+    names: Vec<String>,
+    types: &Vec<PostgresType>,
+    typmods: &[i32],
+    numeric_fallback: NumericFallback,
+) -> Result<ArrowSchema> {
     let mut fields = Vec::with_capacity(names.len());
-    let iter = names.into_iter().zip(types);
+    let iter = names.into_iter().zip(types).zip(typmods);
 
-    for (name, typ) in iter {
+    for ((name, typ), typmod) in iter {
         let arrow_typ = match typ {
             &PostgresType::BOOL => DataType::Boolean,
             &PostgresType::INT2 => DataType::Int16,
@@ -1201,27 +1530,25 @@ fn try_create_arrow_schema(names: Vec<String>, types: &Vec<PostgresType>) -> Res
             | &PostgresType::VARCHAR
             | &PostgresType::TEXT
             | &PostgresType::JSONB
-            | &PostgresType::JSON
-            | &PostgresType::UUID => DataType::Utf8,
+            | &PostgresType::JSON => DataType::Utf8,
+            &PostgresType::UUID => UUID_DATA_TYPE,
             &PostgresType::BYTEA => DataType::Binary,
-            // While postgres numerics are "unconstrained" by default, we need
-            // to specify the precision and scale for the column. Setting these
-            // same as bigquery.
-            &PostgresType::NUMERIC => DataType::Decimal128(38, 9),
+            &PostgresType::NUMERIC => numeric_data_type(*typmod, numeric_fallback),
             &PostgresType::TIMESTAMP => DataType::Timestamp(TimeUnit::Nanosecond, None),
             &PostgresType::TIMESTAMPTZ => {
                 DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into()))
             }
             &PostgresType::TIME => DataType::Time64(TimeUnit::Nanosecond),
             &PostgresType::DATE => DataType::Date32,
-            // TODO: Time with timezone and interval data types in postgres are
-            // of 12 and 16 bytes respectively. This kind of size is not
-            // supported by datafusion. Moreover, these datatypes are not
-            // supported by the tokio-postgres library as well. What we need to
-            // do is implement a data type that can support it and cast it to
-            // datafusion `FixedSizeBinary` or something similar OR even cast
-            // it to existing datafusion types (which would be reasonable but
-            // might cause some data loss).
+            &PostgresType::INTERVAL => DataType::Interval(IntervalUnit::MonthDayNano),
+            typ if typ.name() == POSTGIS_GEOMETRY_TYPE_NAME => GEOMETRY_DATA_TYPE,
+            // TODO: Time with timezone data types in postgres are 12 bytes.
+            // This kind of size is not supported by datafusion. Moreover, this
+            // datatype is not supported by the tokio-postgres library as well.
+            // What we need to do is implement a data type that can support it
+            // and cast it to datafusion `FixedSizeBinary` or something similar
+            // OR even cast it to existing datafusion types (which would be
+            // reasonable but might cause some data loss).
             other => {
                 return Err(PostgresError::UnsupportedPostgresType(
                     other.name().to_owned(),
@@ -1230,13 +1557,48 @@ fn try_create_arrow_schema(names: Vec<String>, types: &Vec<PostgresType>) -> Res
         };
 
         // Assume all fields are nullable.
-        let field = Field::new(name, arrow_typ, true);
+        let field = if typ == &PostgresType::UUID {
+            uuid_field(name, true)
+        } else if typ.name() == POSTGIS_GEOMETRY_TYPE_NAME {
+            geometry_field(name, true)
+        } else {
+            Field::new(name, arrow_typ, true)
+        };
         fields.push(field);
     }
 
     Ok(ArrowSchema::new(fields))
 }
 
+/// Get the arrow data type for a numeric column with the given type modifier.
+fn numeric_data_type(typmod: i32, numeric_fallback: NumericFallback) -> DataType {
+    // While postgres numerics are "unconstrained" by default, we need to
+    // specify the precision and scale for the column. Setting these same as
+    // bigquery.
+    const UNCONSTRAINED: DataType = DataType::Decimal128(38, 9);
+
+    // The type modifier for numerics is `((precision << 16) | scale) + 4`, or
+    // -1 if unconstrained.
+    if typmod < 4 {
+        return UNCONSTRAINED;
+    }
+    let typmod = typmod - 4;
+    let precision = (typmod >> 16) & 0xffff;
+    // Scale is stored as an 11 bit signed integer. Negative scales (rounding
+    // to the left of the decimal point) don't need any fractional digits.
+    let scale = (((typmod & 0x7ff) ^ 0x400) - 0x400).clamp(0, precision);
+
+    if precision <= DECIMAL128_MAX_PRECISION as i32 {
+        return UNCONSTRAINED;
+    }
+    match numeric_fallback {
+        NumericFallback::Decimal256 if precision <= DECIMAL256_MAX_PRECISION as i32 => {
+            DataType::Decimal256(precision as u8, scale as i8)
+        }
+        _ => DataType::Utf8,
+    }
+}
+
 /// Convert filtering expressions to a predicate string usable with the
 /// generated Postgres query.
 fn exprs_to_predicate_string(exprs: &[Expr]) -> Result<String> {
@@ -1420,4 +1782,86 @@ mod tests {
         let out = exprs_to_predicate_string(&exprs).unwrap();
         assert_eq!(out, "a < b")
     }
+
+    #[test]
+    fn decode_binary_numeric() {
+        fn encode(weight: i16, sign: u16, scale: u16, digits: &[u16]) -> Vec<u8> {
+            let mut buf = Vec::new();
+            buf.extend_from_slice(&(digits.len() as u16).to_be_bytes());
+            buf.extend_from_slice(&weight.to_be_bytes());
+            buf.extend_from_slice(&sign.to_be_bytes());
+            buf.extend_from_slice(&scale.to_be_bytes());
+            for digit in digits {
+                buf.extend_from_slice(&digit.to_be_bytes());
+            }
+            buf
+        }
+
+        let test_cases = [
+            (encode(0, 0, 0, &[]), "0"),
+            (encode(1, 0, 0, &[1, 2345]), "12345"),
+            (encode(1, 0x4000, 4, &[1, 2345, 6700]), "-12345.6700"),
+            (encode(-1, 0, 5, &[500]), "0.05000"),
+            (encode(-2, 0, 8, &[12]), "0.00000012"),
+            (encode(2, 0, 2, &[1]), "100000000.00"),
+            (
+                encode(4, 0, 3, &[1, 2345, 6789, 123, 4567, 8900]),
+                "12345678901234567.890",
+            ),
+            (encode(0, 0xC000, 0, &[]), "NaN"),
+        ];
+
+        for (raw, expected) in test_cases {
+            let numeric = PgNumeric::from_sql(&PostgresType::NUMERIC, &raw).unwrap();
+            assert_eq!(expected, numeric.0);
+        }
+
+        PgNumeric::from_sql(&PostgresType::NUMERIC, &[0, 1]).unwrap_err();
+    }
+
+    #[test]
+    fn numeric_mantissa() {
+        let numeric = PgNumeric("-12345.6789".to_string());
+        assert_eq!(Some("-12345678"), numeric.mantissa(3).as_deref());
+        assert_eq!(Some("-123456789000"), numeric.mantissa(7).as_deref());
+        assert_eq!(Some("-12345"), numeric.mantissa(0).as_deref());
+
+        assert_eq!(None, PgNumeric("NaN".to_string()).mantissa(2));
+    }
+
+    #[test]
+    fn numeric_typmod_data_type() {
+        let typmod = |precision: i32, scale: i32| ((precision << 16) | (scale & 0x7ff)) + 4;
+
+        let test_cases = [
+            (-1, NumericFallback::Decimal256, DataType::Decimal128(38, 9)),
+            (
+                typmod(10, 2),
+                NumericFallback::Decimal256,
+                DataType::Decimal128(38, 9),
+            ),
+            (
+                typmod(50, 10),
+                NumericFallback::Decimal256,
+                DataType::Decimal256(50, 10),
+            ),
+            (
+                typmod(50, -2),
+                NumericFallback::Decimal256,
+                DataType::Decimal256(50, 0),
+            ),
+            (typmod(100, 10), NumericFallback::Decimal256, DataType::Utf8),
+            (typmod(50, 10), NumericFallback::Text, DataType::Utf8),
+        ];
+
+        for (typmod, fallback, expected) in test_cases {
+            assert_eq!(expected, numeric_data_type(typmod, fallback), "{typmod}");
+        }
+
+        assert_eq!(
+            NumericFallback::Text,
+            "TEXT".parse::<NumericFallback>().unwrap()
+        );
+        "float".parse::<NumericFallback>().unwrap_err();
+    }
 }
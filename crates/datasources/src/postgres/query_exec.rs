@@ -19,22 +19,25 @@ use datafusion::{
 };
 use datafusion_ext::metrics::DataSourceMetricsStreamAdapter;
 use futures::{future::BoxFuture, ready, FutureExt, Stream};
+use tokio_postgres::SimpleQueryMessage;
 
 use crate::common::util::{create_count_record_batch, COUNT_SCHEMA};
 
 use super::PostgresAccessState;
 
+/// Executes statements against Postgres in a single transaction, producing the
+/// total number of affected rows.
 #[derive(Debug)]
 pub struct PostgresQueryExec {
-    query: String,
+    queries: Vec<String>,
     state: Arc<PostgresAccessState>,
     metrics: ExecutionPlanMetricsSet,
 }
 
 impl PostgresQueryExec {
-    pub fn new(query: String, state: Arc<PostgresAccessState>) -> Self {
+    pub fn new(queries: Vec<String>, state: Arc<PostgresAccessState>) -> Self {
         PostgresQueryExec {
-            query,
+            queries,
             state,
             metrics: ExecutionPlanMetricsSet::new(),
         }
@@ -79,7 +82,7 @@ impl ExecutionPlan for PostgresQueryExec {
         let stream = QueryStream {
             state: QueryExecState::Idle,
             opener: QueryOpener {
-                query: self.query.clone(),
+                queries: self.queries.clone(),
                 state: self.state.clone(),
             },
         };
@@ -101,20 +104,38 @@ impl ExecutionPlan for PostgresQueryExec {
 
 impl DisplayAs for PostgresQueryExec {
     fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "PostgresQueryExec(query = {})", self.query)
+        write!(f, "PostgresQueryExec(statements = {})", self.queries.len())
     }
 }
 
 #[derive(Clone)]
 struct QueryOpener {
-    query: String,
+    queries: Vec<String>,
     state: Arc<PostgresAccessState>,
 }
 
 impl QueryOpener {
     fn open(&self) -> BoxFuture<'static, Result<u64, tokio_postgres::Error>> {
         let this = self.clone();
-        Box::pin(async move { this.state.client.execute(&this.query, &[]).await })
+        Box::pin(async move {
+            // Postgres runs all statements sent in a single simple query in
+            // an implicit transaction, so either every statement is applied or
+            // none are. Sending them together also keeps statements from other
+            // users of the client from being interleaved in the transaction.
+            let messages = this
+                .state
+                .client
+                .simple_query(&this.queries.join(";\n"))
+                .await?;
+            let count = messages
+                .iter()
+                .map(|msg| match msg {
+                    SimpleQueryMessage::CommandComplete(count) => *count,
+                    _ => 0,
+                })
+                .sum();
+            Ok(count)
+        })
     }
 }
 
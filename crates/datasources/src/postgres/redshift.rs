@@ -0,0 +1,449 @@
+//! Reading Redshift tables by unloading them to S3.
+//!
+//! Scanning a large table through the Postgres protocol is slow since all rows
+//! go through the leader node one at a time. `UNLOAD` instead has each slice
+//! of the cluster write its part of the table to S3 as parquet in parallel,
+//! and the files are then read in parallel as well.
+//!
+//! The table is only unloaded once the scan is executed, with the projection
+//! and filters of the scan pushed into the unloaded query.
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::SchemaRef as ArrowSchemaRef;
+use datafusion::arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use datafusion::datasource::file_format::parquet::ParquetFormat;
+use datafusion::datasource::TableProvider;
+use datafusion::error::{DataFusionError, Result as DatafusionResult};
+use datafusion::execution::context::{SessionState, TaskContext};
+use datafusion::logical_expr::{Expr, TableProviderFilterPushDown, TableType};
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    execute_stream, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use futures::{stream, StreamExt, TryStreamExt};
+use uuid::Uuid;
+
+use super::errors::{PostgresError, Result};
+use super::{exprs_to_predicate_string, PostgresAccess};
+use crate::common::url::{DatasourceUrl, DatasourceUrlType};
+use crate::object_store::inference::SchemaInference;
+use crate::object_store::s3::S3StoreAccess;
+use crate::object_store::ObjStoreAccess;
+
+/// Credentials Redshift uses to write to S3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnloadCredentials {
+    /// IAM role associated with the cluster. Files are read with the
+    /// credentials of the environment, e.g. an instance profile.
+    IamRole(String),
+    /// Access keys, also used to read the files.
+    AccessKey {
+        access_key_id: String,
+        secret_access_key: String,
+    },
+}
+
+/// Where and how a table is unloaded.
+#[derive(Debug, Clone)]
+pub struct UnloadOptions {
+    /// S3 location the table is unloaded under, e.g. `s3://bucket/unload/`.
+    pub location: DatasourceUrl,
+    /// Region of the bucket.
+    pub region: String,
+    pub credentials: UnloadCredentials,
+}
+
+impl UnloadOptions {
+    /// Bucket of the location, erroring if the location isn't in S3.
+    fn bucket(&self) -> Result<String> {
+        if self.location.datasource_url_type() != DatasourceUrlType::S3 {
+            return Err(PostgresError::InvalidUnloadLocation(
+                self.location.to_string(),
+            ));
+        }
+        self.location
+            .host()
+            .map(|host| host.to_string())
+            .ok_or_else(|| PostgresError::InvalidUnloadLocation(self.location.to_string()))
+    }
+}
+
+/// Table provider for a Redshift table that's read by unloading it to S3.
+///
+/// Only the schema is fetched when the provider is created. Every execution
+/// of a scan unloads to a new prefix under the location. The files aren't
+/// removed afterwards, a lifecycle rule should be used to expire them.
+pub struct RedshiftUnloadTableProvider {
+    access: PostgresAccess,
+    schema: String,
+    table: String,
+    opts: UnloadOptions,
+    arrow_schema: ArrowSchemaRef,
+}
+
+impl RedshiftUnloadTableProvider {
+    pub async fn try_new(
+        access: PostgresAccess,
+        schema: impl Into<String>,
+        table: impl Into<String>,
+        opts: UnloadOptions,
+    ) -> Result<Self> {
+        // Fail early on a bad location instead of after planning.
+        opts.bucket()?;
+
+        let schema = schema.into();
+        let table = table.into();
+        let state = access.connect().await?;
+        let (arrow_schema, _) = state.get_table_schema(&schema, &table).await?;
+
+        Ok(Self {
+            access,
+            schema,
+            table,
+            opts,
+            arrow_schema: Arc::new(arrow_schema),
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for RedshiftUnloadTableProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> ArrowSchemaRef {
+        self.arrow_schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filter_pushdown(
+        &self,
+        _filter: &Expr,
+    ) -> DatafusionResult<TableProviderFilterPushDown> {
+        Ok(TableProviderFilterPushDown::Inexact)
+    }
+
+    async fn scan(
+        &self,
+        _ctx: &SessionState,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> DatafusionResult<Arc<dyn ExecutionPlan>> {
+        let projected_schema = match projection {
+            Some(projection) => Arc::new(self.arrow_schema.project(projection)?),
+            None => self.arrow_schema.clone(),
+        };
+        let columns = projected_schema
+            .fields
+            .iter()
+            .map(|f| f.name().as_str())
+            .collect::<Vec<_>>();
+
+        let predicate = exprs_to_predicate_string(filters)
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+        // Limits aren't pushed down, Redshift doesn't allow `LIMIT` in the
+        // outermost query of an `UNLOAD`.
+        let select = select_query(&self.schema, &self.table, &columns, &predicate);
+
+        Ok(Arc::new(RedshiftUnloadExec {
+            access: self.access.clone(),
+            select,
+            opts: self.opts.clone(),
+            arrow_schema: projected_schema,
+        }))
+    }
+}
+
+/// Unloads the result of a query to S3 when executed, and reads back the
+/// unloaded files.
+pub struct RedshiftUnloadExec {
+    access: PostgresAccess,
+    /// Query that's unloaded.
+    select: String,
+    opts: UnloadOptions,
+    arrow_schema: ArrowSchemaRef,
+}
+
+impl ExecutionPlan for RedshiftUnloadExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> ArrowSchemaRef {
+        self.arrow_schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        Vec::new()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DatafusionResult<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::Execution(
+            "cannot replace children for RedshiftUnloadExec".to_string(),
+        ))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DatafusionResult<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Execution(format!(
+                "invalid partition {partition} for RedshiftUnloadExec"
+            )));
+        }
+
+        let access = self.access.clone();
+        let select = self.select.clone();
+        let opts = self.opts.clone();
+        let schema = self.arrow_schema.clone();
+
+        let stream = stream::once(async move {
+            let stream = unload_and_scan(&access, &select, &opts, context)
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+            let stream = stream.map(move |batch| cast_batch(batch?, &schema));
+            Ok(stream) as DatafusionResult<_>
+        })
+        .try_flatten();
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.arrow_schema.clone(),
+            stream,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+impl DisplayAs for RedshiftUnloadExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        // Don't include the unload statement, it may contain credentials.
+        write!(f, "RedshiftUnloadExec: select={}", self.select)
+    }
+}
+
+impl fmt::Debug for RedshiftUnloadExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedshiftUnloadExec")
+            .field("select", &self.select)
+            .field("location", &self.opts.location)
+            .field("arrow_schema", &self.arrow_schema)
+            .finish()
+    }
+}
+
+/// Unload the query to a new prefix under the location and return a stream
+/// over the unloaded files.
+async fn unload_and_scan(
+    access: &PostgresAccess,
+    select: &str,
+    opts: &UnloadOptions,
+    context: Arc<TaskContext>,
+) -> Result<SendableRecordBatchStream> {
+    let bucket = opts.bucket()?;
+    let prefix = format!(
+        "{}/{}/",
+        opts.location.to_string().trim_end_matches('/'),
+        Uuid::new_v4()
+    );
+
+    let pg_state = access.connect().await?;
+    pg_state
+        .client
+        .batch_execute(&unload_query(select, &prefix, opts))
+        .await?;
+
+    let (access_key_id, secret_access_key) = match &opts.credentials {
+        UnloadCredentials::IamRole(_) => (None, None),
+        UnloadCredentials::AccessKey {
+            access_key_id,
+            secret_access_key,
+        } => (Some(access_key_id.clone()), Some(secret_access_key.clone())),
+    };
+    let store_access = S3StoreAccess {
+        region: opts.region.clone(),
+        bucket,
+        access_key_id,
+        secret_access_key,
+    };
+
+    // The object store is registered on the runtime when scanning, so the
+    // session state needs to share the runtime of the task.
+    let state =
+        SessionState::new_with_config_rt(context.session_config().clone(), context.runtime_env());
+
+    let files = DatasourceUrl::try_new(format!("{prefix}*"))?;
+    let provider = store_access
+        .create_table_provider(
+            &state,
+            Arc::new(ParquetFormat::default()),
+            vec![files],
+            &SchemaInference::default(),
+        )
+        .await?;
+
+    let plan = provider.scan(&state, None, &[], None).await?;
+    Ok(execute_stream(plan, context)?)
+}
+
+/// Cast a batch read from the unloaded files to the schema of the table.
+///
+/// Columns are matched by position, since the unloaded files contain the
+/// columns in the order they were selected.
+fn cast_batch(batch: RecordBatch, schema: &ArrowSchemaRef) -> DatafusionResult<RecordBatch> {
+    let columns = schema
+        .fields
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, col)| cast(col, field.data_type()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let opts = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+    Ok(RecordBatch::try_new_with_options(
+        schema.clone(),
+        columns,
+        &opts,
+    )?)
+}
+
+/// Build the query selecting `columns` from the table.
+///
+/// An empty projection (e.g. for `count(*)`) selects a constant so that only
+/// the number of rows is unloaded.
+fn select_query(schema: &str, table: &str, columns: &[&str], predicate: &str) -> String {
+    let projection = if columns.is_empty() {
+        "1".to_string()
+    } else {
+        columns
+            .iter()
+            .map(|c| quote_identifier(c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut query = format!(
+        "SELECT {projection} FROM {}.{}",
+        quote_identifier(schema),
+        quote_identifier(table)
+    );
+    if !predicate.is_empty() {
+        query.push_str(" WHERE ");
+        query.push_str(predicate);
+    }
+    query
+}
+
+/// Build the `UNLOAD` statement writing the result of `select` as parquet
+/// files under `prefix`.
+fn unload_query(select: &str, prefix: &str, opts: &UnloadOptions) -> String {
+    let credentials = match &opts.credentials {
+        UnloadCredentials::IamRole(role) => format!("IAM_ROLE {}", quote_literal(role)),
+        UnloadCredentials::AccessKey {
+            access_key_id,
+            secret_access_key,
+        } => format!(
+            "ACCESS_KEY_ID {} SECRET_ACCESS_KEY {}",
+            quote_literal(access_key_id),
+            quote_literal(secret_access_key)
+        ),
+    };
+
+    format!(
+        "UNLOAD ({}) TO {} {credentials} FORMAT AS PARQUET REGION {}",
+        quote_literal(select),
+        quote_literal(prefix),
+        quote_literal(&opts.region),
+    )
+}
+
+fn quote_identifier(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn quote_literal(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_unload_query() {
+        let opts = UnloadOptions {
+            location: DatasourceUrl::try_new("s3://bucket/unload/").unwrap(),
+            region: "us-east-1".to_string(),
+            credentials: UnloadCredentials::IamRole(
+                "arn:aws:iam::123456789012:role/unload".to_string(),
+            ),
+        };
+        let select = select_query("public", "o'brien", &[], "");
+        let query = unload_query(&select, "s3://bucket/unload/id/", &opts);
+        assert_eq!(
+            query,
+            r#"UNLOAD ('SELECT 1 FROM "public"."o\'brien"') TO 's3://bucket/unload/id/' IAM_ROLE 'arn:aws:iam::123456789012:role/unload' FORMAT AS PARQUET REGION 'us-east-1'"#
+        );
+
+        let opts = UnloadOptions {
+            credentials: UnloadCredentials::AccessKey {
+                access_key_id: "id".to_string(),
+                secret_access_key: "secret".to_string(),
+            },
+            ..opts
+        };
+        let select = select_query("public", "events", &["id"], "");
+        let query = unload_query(&select, "s3://bucket/unload/id/", &opts);
+        assert_eq!(
+            query,
+            r#"UNLOAD ('SELECT "id" FROM "public"."events"') TO 's3://bucket/unload/id/' ACCESS_KEY_ID 'id' SECRET_ACCESS_KEY 'secret' FORMAT AS PARQUET REGION 'us-east-1'"#
+        );
+    }
+
+    #[test]
+    fn build_select_query() {
+        let query = select_query("public", "events", &["id", "kind"], "");
+        assert_eq!(query, r#"SELECT "id", "kind" FROM "public"."events""#);
+
+        let query = select_query("public", "events", &["id"], "kind = 'click' AND id > 10");
+        assert_eq!(
+            query,
+            r#"SELECT "id" FROM "public"."events" WHERE kind = 'click' AND id > 10"#
+        );
+
+        let opts = UnloadOptions {
+            location: DatasourceUrl::try_new("s3://bucket/unload/").unwrap(),
+            region: "us-east-1".to_string(),
+            credentials: UnloadCredentials::IamRole("role".to_string()),
+        };
+        let query = unload_query(&query, "s3://bucket/unload/id/", &opts);
+        assert_eq!(
+            query,
+            r#"UNLOAD ('SELECT "id" FROM "public"."events" WHERE kind = \'click\' AND id > 10') TO 's3://bucket/unload/id/' IAM_ROLE 'role' FORMAT AS PARQUET REGION 'us-east-1'"#
+        );
+    }
+}
@@ -0,0 +1,28 @@
+#[derive(Debug, thiserror::Error)]
+pub enum RedisError {
+    #[error(transparent)]
+    Redis(#[from] redis::RedisError),
+
+    #[error("Unsupported key type '{0}', expected one of string, hash, list or set")]
+    UnsupportedKeyType(String),
+
+    #[error("Unsupported connection string, expected a redis:// URL: {0}")]
+    UnsupportedConnectionString(String),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Arrow(#[from] datafusion::arrow::error::ArrowError),
+
+    #[error(transparent)]
+    Datafusion(#[from] datafusion::error::DataFusionError),
+
+    #[error(transparent)]
+    ObjectStore(#[from] crate::object_store::errors::ObjectStoreSourceError),
+
+    #[error(transparent)]
+    Common(#[from] crate::common::errors::DatasourceCommonError),
+}
+
+pub type Result<T, E = RedisError> = std::result::Result<T, E>;
@@ -0,0 +1,385 @@
+//! Reading keys from Redis.
+//!
+//! Keys matching a pattern are listed with `SCAN`, and the values of each page
+//! of keys are read into rows with a `key` column:
+//!
+//! - `string` keys have a `value` column.
+//! - `hash` keys have a column per field of the hashes.
+//! - `list` and `set` keys have a `value` list column.
+//!
+//! Values are read as strings, or decoded as JSON when possible if enabled.
+//! The schema is inferred from the first keys, types conflicting across keys
+//! are read as JSON strings.
+mod errors;
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_stream::try_stream;
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::json::ReaderBuilder;
+use datafusion::datasource::streaming::StreamingTable;
+use datafusion::datasource::TableProvider;
+use datafusion::error::DataFusionError;
+use datafusion::execution::TaskContext;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::streaming::PartitionStream;
+use datafusion::physical_plan::SendableRecordBatchStream;
+pub use errors::*;
+use futures::TryStreamExt;
+use redis::aio::MultiplexedConnection;
+use redis::{Client, ConnectionAddr};
+use serde_json::{Map, Value};
+
+use crate::common::egress::check_egress;
+use crate::object_store::json::{infer_schema, normalize_record, JsonReadOptions};
+
+/// Column holding the name of keys.
+pub const KEY_COLUMN: &str = "key";
+
+/// Column holding the value of string, list and set keys.
+pub const VALUE_COLUMN: &str = "value";
+
+/// Number of keys read to infer the schema if not set.
+pub const DEFAULT_SAMPLE_SIZE: usize = 100;
+
+/// Number of keys requested per `SCAN` call.
+const SCAN_COUNT: usize = 1000;
+
+/// Type of the keys read, keys of other types are skipped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RedisKeyType {
+    #[default]
+    String,
+    Hash,
+    List,
+    Set,
+}
+
+impl RedisKeyType {
+    /// Name of the type as returned by `TYPE`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RedisKeyType::String => "string",
+            RedisKeyType::Hash => "hash",
+            RedisKeyType::List => "list",
+            RedisKeyType::Set => "set",
+        }
+    }
+}
+
+impl FromStr for RedisKeyType {
+    type Err = RedisError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "string" => RedisKeyType::String,
+            "hash" => RedisKeyType::Hash,
+            "list" => RedisKeyType::List,
+            "set" => RedisKeyType::Set,
+            _ => return Err(RedisError::UnsupportedKeyType(s.to_string())),
+        })
+    }
+}
+
+impl fmt::Display for RedisKeyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Options for the keys read and how their values are read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedisScanOptions {
+    /// Glob-style pattern of the keys, as accepted by `SCAN MATCH`.
+    pub pattern: String,
+    pub key_type: RedisKeyType,
+    /// Decode values as JSON, values that aren't valid JSON are read as
+    /// strings.
+    pub json: bool,
+}
+
+impl Default for RedisScanOptions {
+    fn default() -> Self {
+        RedisScanOptions {
+            pattern: "*".to_string(),
+            key_type: RedisKeyType::default(),
+            json: false,
+        }
+    }
+}
+
+/// Create a table streaming the keys matching the scan options.
+///
+/// Keys are scanned with `SCAN`, so keys added or removed during the scan may
+/// or may not be read, and keys may be read more than once.
+pub async fn redis_streaming_table(
+    conn_str: &str,
+    opts: RedisScanOptions,
+    schema_inference_sample_size: Option<usize>,
+) -> Result<Arc<dyn TableProvider>> {
+    let sample_size = schema_inference_sample_size.unwrap_or(DEFAULT_SAMPLE_SIZE);
+
+    let client = Client::open(conn_str)?;
+    match &client.get_connection_info().addr {
+        ConnectionAddr::Tcp(host, port) | ConnectionAddr::TcpTls { host, port, .. } => {
+            check_egress(host, *port).await?
+        }
+        ConnectionAddr::Unix(_) => {
+            return Err(RedisError::UnsupportedConnectionString(
+                "unix sockets are not supported".to_string(),
+            ))
+        }
+    }
+    let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+    let mut sample = Vec::with_capacity(sample_size);
+    let mut cursor = 0;
+    loop {
+        let (next, keys) = scan_keys(&mut conn, cursor, &opts).await?;
+        sample.extend(read_rows(&mut conn, keys, &opts).await?);
+        cursor = next;
+        if cursor == 0 || sample.len() >= sample_size {
+            break;
+        }
+    }
+    sample.truncate(sample_size);
+
+    let schema = if sample.is_empty() {
+        empty_schema(opts.key_type)
+    } else {
+        infer_schema(sample.iter(), &JsonReadOptions::default())?
+    };
+    let schema = Arc::new(schema);
+
+    let partition = Arc::new(RedisPartitionStream {
+        schema: schema.clone(),
+        conn,
+        opts,
+    });
+
+    Ok(Arc::new(StreamingTable::try_new(schema, vec![partition])?))
+}
+
+/// Schema used when no keys match.
+fn empty_schema(key_type: RedisKeyType) -> Schema {
+    let key = Field::new(KEY_COLUMN, DataType::Utf8, true);
+    match key_type {
+        RedisKeyType::String => {
+            Schema::new(vec![key, Field::new(VALUE_COLUMN, DataType::Utf8, true)])
+        }
+        RedisKeyType::Hash => Schema::new(vec![key]),
+        RedisKeyType::List | RedisKeyType::Set => Schema::new(vec![
+            key,
+            Field::new(VALUE_COLUMN, DataType::new_list(DataType::Utf8, true), true),
+        ]),
+    }
+}
+
+/// Scan a page of keys, returning the cursor of the next page. The cursor is
+/// zero once all keys have been scanned.
+async fn scan_keys(
+    conn: &mut MultiplexedConnection,
+    cursor: u64,
+    opts: &RedisScanOptions,
+) -> Result<(u64, Vec<Vec<u8>>)> {
+    Ok(redis::cmd("SCAN")
+        .arg(cursor)
+        .arg("MATCH")
+        .arg(&opts.pattern)
+        .arg("COUNT")
+        .arg(SCAN_COUNT)
+        .arg("TYPE")
+        .arg(opts.key_type.as_str())
+        .query_async(conn)
+        .await?)
+}
+
+/// Read the values of keys into rows, skipping keys removed since they were
+/// scanned.
+async fn read_rows(
+    conn: &mut MultiplexedConnection,
+    keys: Vec<Vec<u8>>,
+    opts: &RedisScanOptions,
+) -> Result<Vec<Map<String, Value>>> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut rows = Vec::with_capacity(keys.len());
+    match opts.key_type {
+        RedisKeyType::String => {
+            let values: Vec<Option<Vec<u8>>> =
+                redis::cmd("MGET").arg(&keys).query_async(conn).await?;
+            for (key, value) in keys.iter().zip(values) {
+                if let Some(value) = value {
+                    let value = decode_value(&value, opts.json);
+                    rows.push(build_row(key, [(VALUE_COLUMN.to_string(), value)]));
+                }
+            }
+        }
+        RedisKeyType::Hash => {
+            let mut pipe = redis::pipe();
+            for key in &keys {
+                pipe.cmd("HGETALL").arg(key);
+            }
+            let hashes: Vec<Vec<(Vec<u8>, Vec<u8>)>> = pipe.query_async(conn).await?;
+            for (key, fields) in keys.iter().zip(hashes) {
+                if fields.is_empty() {
+                    continue;
+                }
+                let fields = fields.into_iter().map(|(field, value)| {
+                    (
+                        String::from_utf8_lossy(&field).into_owned(),
+                        decode_value(&value, opts.json),
+                    )
+                });
+                rows.push(build_row(key, fields));
+            }
+        }
+        RedisKeyType::List | RedisKeyType::Set => {
+            let mut pipe = redis::pipe();
+            for key in &keys {
+                match opts.key_type {
+                    RedisKeyType::List => pipe.cmd("LRANGE").arg(key).arg(0).arg(-1),
+                    _ => pipe.cmd("SMEMBERS").arg(key),
+                };
+            }
+            let members: Vec<Vec<Vec<u8>>> = pipe.query_async(conn).await?;
+            for (key, members) in keys.iter().zip(members) {
+                if members.is_empty() {
+                    continue;
+                }
+                let value = members
+                    .iter()
+                    .map(|member| decode_value(member, opts.json))
+                    .collect();
+                rows.push(build_row(
+                    key,
+                    [(VALUE_COLUMN.to_string(), Value::Array(value))],
+                ));
+            }
+        }
+    }
+    Ok(rows)
+}
+
+/// Build a row for a key. Fields named like the key column are dropped.
+fn build_row(key: &[u8], fields: impl IntoIterator<Item = (String, Value)>) -> Map<String, Value> {
+    let mut row = Map::new();
+    row.insert(
+        KEY_COLUMN.to_string(),
+        Value::String(String::from_utf8_lossy(key).into_owned()),
+    );
+    for (name, value) in fields {
+        if name != KEY_COLUMN {
+            row.insert(name, value);
+        }
+    }
+    row
+}
+
+/// Decode a value as JSON if enabled, falling back to a string.
+fn decode_value(value: &[u8], json: bool) -> Value {
+    if json {
+        if let Ok(value) = serde_json::from_slice(value) {
+            return value;
+        }
+    }
+    Value::String(String::from_utf8_lossy(value).into_owned())
+}
+
+/// Streams all keys matching the scan options.
+struct RedisPartitionStream {
+    schema: SchemaRef,
+    conn: MultiplexedConnection,
+    opts: RedisScanOptions,
+}
+
+impl PartitionStream for RedisPartitionStream {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let schema = self.schema.clone();
+        let mut conn = self.conn.clone();
+        let opts = self.opts.clone();
+        let batch_size = ctx.session_config().batch_size();
+
+        let stream = try_stream! {
+            let mut decoder = ReaderBuilder::new(schema.clone())
+                .with_batch_size(batch_size)
+                .with_coerce_primitive(true)
+                .build_decoder()?;
+            let mut buf = Vec::new();
+            let mut buffered = 0;
+            let mut cursor = 0;
+
+            loop {
+                let (next, keys) = scan_keys(&mut conn, cursor, &opts).await?;
+                for row in read_rows(&mut conn, keys, &opts).await? {
+                    serde_json::to_writer(&mut buf, &normalize_record(row, schema.fields()))?;
+                    buffered += 1;
+                    if buffered == batch_size {
+                        decoder.decode(&buf)?;
+                        if let Some(batch) = decoder.flush()? {
+                            yield batch;
+                        }
+                        buf.clear();
+                        buffered = 0;
+                    }
+                }
+
+                cursor = next;
+                if cursor == 0 {
+                    break;
+                }
+            }
+
+            decoder.decode(&buf)?;
+            if let Some(batch) = decoder.flush()? {
+                yield batch;
+            }
+        };
+
+        Box::pin(RecordBatchStreamAdapter::new(
+            self.schema.clone(),
+            stream.map_err(|e: RedisError| DataFusionError::External(Box::new(e))),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn build_rows() {
+        let row = build_row(
+            b"user:1",
+            [
+                ("name".to_string(), decode_value(b"alice", true)),
+                ("age".to_string(), decode_value(b"42", true)),
+                ("tags".to_string(), decode_value(br#"["a","b"]"#, true)),
+                ("raw".to_string(), decode_value(b"42", false)),
+                ("key".to_string(), decode_value(b"dropped", false)),
+            ],
+        );
+
+        assert_eq!(
+            json!({"key": "user:1", "name": "alice", "age": 42, "tags": ["a", "b"], "raw": "42"}),
+            Value::Object(row)
+        );
+    }
+
+    #[test]
+    fn parse_key_type() {
+        assert_eq!(RedisKeyType::Hash, "HASH".parse().unwrap());
+        assert_eq!("set", RedisKeyType::Set.to_string());
+        assert!("zset".parse::<RedisKeyType>().is_err());
+    }
+}
@@ -8,6 +8,9 @@ pub enum DatasourceSnowflakeError {
 
     #[error(transparent)]
     DatasourceCommonError(#[from] crate::common::errors::DatasourceCommonError),
+
+    #[error(transparent)]
+    Tunnel(#[from] crate::common::tunnel::TunnelError),
 }
 
 pub type Result<T, E = DatasourceSnowflakeError> = std::result::Result<T, E>;
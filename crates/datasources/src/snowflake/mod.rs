@@ -6,6 +6,9 @@ use std::sync::Mutex;
 use std::task::{Context, Poll};
 use std::{any::Any, sync::Arc};
 
+use crate::common::egress::check_egress_url;
+use crate::common::errors::DatasourceCommonError;
+use crate::common::tunnel::TunnelSession;
 use crate::common::util;
 use async_trait::async_trait;
 use datafusion::arrow::datatypes::Fields;
@@ -36,6 +39,8 @@ use snowflake_connector::{
 use snowflake_connector::{QueryResult, QueryResultChunkMeta};
 
 use errors::Result;
+use protogen::metastore::types::options::TunnelOptions;
+use url::Url;
 
 #[derive(Debug, Clone)]
 pub struct SnowflakeDbConnection {
@@ -55,15 +60,34 @@ pub struct SnowflakeTableAccess {
 
 pub struct SnowflakeAccessor {
     conn: SnowflakeConnection,
+    /// Session for the underlying tunnel.
+    ///
+    /// Kept on struct to avoid dropping the tunnel.
+    _tunnel: Option<TunnelSession>,
 }
 
 impl SnowflakeAccessor {
-    pub async fn connect(conn_params: SnowflakeDbConnection) -> Result<Self> {
-        let conn = Self::build_conn(conn_params).await?;
-        Ok(Self { conn })
+    pub async fn connect(
+        conn_params: SnowflakeDbConnection,
+        tunnel: Option<TunnelOptions>,
+    ) -> Result<Self> {
+        let (conn, _tunnel) = Self::build_conn(conn_params, tunnel).await?;
+        Ok(Self { conn, _tunnel })
     }
 
-    async fn build_conn(conn_params: SnowflakeDbConnection) -> Result<SnowflakeConnection> {
+    async fn build_conn(
+        conn_params: SnowflakeDbConnection,
+        tunnel: Option<TunnelOptions>,
+    ) -> Result<(SnowflakeConnection, Option<TunnelSession>)> {
+        // Check the url the connector will use since the account name isn't
+        // validated.
+        let url = Url::parse(&format!(
+            "https://{}.snowflakecomputing.com:443",
+            conn_params.account_name
+        ))
+        .map_err(|e| DatasourceCommonError::InvalidUrl(e.to_string()))?;
+        check_egress_url(&url).await?;
+
         let mut conn =
             SnowflakeConnection::builder(conn_params.account_name, conn_params.login_name)
                 .password(conn_params.password)
@@ -74,12 +98,28 @@ impl SnowflakeAccessor {
             conn = conn.role_name(role_name);
         }
 
+        // Requests go through a local HTTP proxy in front of the tunnel so
+        // that the client still verifies Snowflake's certificate.
+        let session = match (tunnel, url.host_str()) {
+            (Some(tunnel), Some(host)) => {
+                let port = url.port_or_known_default().unwrap_or(443);
+                let (session, proxy_addr) =
+                    TunnelSession::open_http_proxy(&tunnel, host, port).await?;
+                conn = conn.proxy(format!("http://{proxy_addr}"));
+                Some(session)
+            }
+            _ => None,
+        };
+
         let conn = conn.build().await?;
-        Ok(conn)
+        Ok((conn, session))
     }
 
-    pub async fn validate_external_database(conn_params: SnowflakeDbConnection) -> Result<()> {
-        let accessor = Self::connect(conn_params).await?;
+    pub async fn validate_external_database(
+        conn_params: SnowflakeDbConnection,
+        tunnel: Option<TunnelOptions>,
+    ) -> Result<()> {
+        let accessor = Self::connect(conn_params, tunnel).await?;
 
         // Validate if the connection is Ok
         let query = "SELECT 1".to_string();
@@ -91,8 +131,9 @@ impl SnowflakeAccessor {
     pub async fn validate_table_access(
         conn_params: SnowflakeDbConnection,
         table_access: &SnowflakeTableAccess,
+        tunnel: Option<TunnelOptions>,
     ) -> Result<ArrowSchema> {
-        let accessor = Self::connect(conn_params).await?;
+        let accessor = Self::connect(conn_params, tunnel).await?;
 
         // Validate if the connection is Ok
         let query = format!(
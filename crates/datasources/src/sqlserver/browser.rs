@@ -0,0 +1,143 @@
+//! Resolving the port of named instances with the SQL Server Browser service.
+//!
+//! Protocol: <https://learn.microsoft.com/en-us/openspecs/windows_protocols/mc-sqlr>
+
+use std::time::Duration;
+
+use tokio::net::UdpSocket;
+
+use super::errors::{Result, SqlServerError};
+
+/// Port the SQL Server Browser service listens on.
+pub const BROWSER_PORT: u16 = 1434;
+
+/// Timeout when waiting for a response from the browser service.
+const BROWSER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// CLNT_UCAST_INST, requests information about a single instance.
+const CLNT_UCAST_INST: u8 = 0x04;
+
+/// SVR_RESP, the response to a request.
+const SVR_RESP: u8 = 0x05;
+
+/// Get the named instance from the server of an ADO connection string, if the
+/// server doesn't also have a port.
+///
+/// Instances are given as `server=host\instance`. A port takes precedence over
+/// the instance name, e.g. `server=host\instance,1433`.
+pub fn instance_name_from_ado_string(conn_str: &str) -> Option<String> {
+    let server = conn_str.split(';').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        match key.trim().to_lowercase().as_str() {
+            "server" | "data source" | "address" | "addr" | "network address" => Some(value.trim()),
+            _ => None,
+        }
+    })?;
+
+    let server = server.strip_prefix("tcp:").unwrap_or(server);
+    if server.contains(',') {
+        return None;
+    }
+    let (_, instance) = server.split_once('\\')?;
+    let instance = instance.trim();
+    (!instance.is_empty()).then(|| instance.to_string())
+}
+
+/// Ask the browser service on `host` for the TCP port of an instance.
+pub async fn resolve_instance_port(host: &str, instance: &str) -> Result<u16> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect((host, BROWSER_PORT)).await?;
+
+    let mut req = Vec::with_capacity(instance.len() + 2);
+    req.push(CLNT_UCAST_INST);
+    req.extend_from_slice(instance.as_bytes());
+    req.push(0);
+    socket.send(&req).await?;
+
+    let mut buf = vec![0; 4096];
+    let n = match tokio::time::timeout(BROWSER_TIMEOUT, socket.recv(&mut buf)).await {
+        Ok(result) => result?,
+        Err(_) => {
+            return Err(SqlServerError::String(format!(
+                "timed out looking up instance '{instance}' with the SQL Server Browser service on {host}:{BROWSER_PORT}"
+            )))
+        }
+    };
+
+    parse_instance_port(&buf[..n], instance)
+}
+
+/// Parse the TCP port from a response to an instance request.
+///
+/// The response is a header followed by the properties of the instance as
+/// `key;value;` pairs, e.g.
+/// `ServerName;HOST;InstanceName;SQLEXPRESS;IsClustered;No;Version;16.0.1000.6;tcp;49721;;`
+fn parse_instance_port(resp: &[u8], instance: &str) -> Result<u16> {
+    let invalid = || {
+        SqlServerError::String(format!(
+            "invalid response from the SQL Server Browser service for instance '{instance}'"
+        ))
+    };
+
+    // Header is the response type followed by the length of the data.
+    if resp.len() < 3 || resp[0] != SVR_RESP {
+        return Err(invalid());
+    }
+    let data = String::from_utf8_lossy(&resp[3..]);
+
+    let mut parts = data.split(';');
+    while let Some(key) = parts.next() {
+        let value = parts.next().ok_or_else(invalid)?;
+        if key.eq_ignore_ascii_case("tcp") {
+            return value.parse().map_err(|_| invalid());
+        }
+    }
+
+    Err(SqlServerError::String(format!(
+        "instance '{instance}' is not listening on TCP"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instance_name() {
+        let conn_str = "server=tcp:db.example.com\\SQLEXPRESS;user=sa;password=pw";
+        assert_eq!(
+            Some("SQLEXPRESS".to_string()),
+            instance_name_from_ado_string(conn_str)
+        );
+
+        let conn_str = "Data Source=db.example.com\\SQLEXPRESS;IntegratedSecurity=false";
+        assert_eq!(
+            Some("SQLEXPRESS".to_string()),
+            instance_name_from_ado_string(conn_str)
+        );
+
+        // Port takes precedence.
+        let conn_str = "server=tcp:db.example.com\\SQLEXPRESS,1433;user=sa";
+        assert_eq!(None, instance_name_from_ado_string(conn_str));
+
+        let conn_str = "server=tcp:localhost,1433;user=sa";
+        assert_eq!(None, instance_name_from_ado_string(conn_str));
+    }
+
+    #[test]
+    fn instance_port() {
+        let data = b"ServerName;HOST;InstanceName;SQLEXPRESS;IsClustered;No;Version;16.0.1000.6;tcp;49721;;";
+        let mut resp = vec![SVR_RESP];
+        resp.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        resp.extend_from_slice(data);
+        assert_eq!(49721, parse_instance_port(&resp, "SQLEXPRESS").unwrap());
+
+        let data = b"ServerName;HOST;InstanceName;SQLEXPRESS;IsClustered;No;np;\\\\HOST\\pipe\\sql\\query;;";
+        let mut resp = vec![SVR_RESP];
+        resp.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        resp.extend_from_slice(data);
+        assert!(parse_instance_port(&resp, "SQLEXPRESS").is_err());
+
+        assert!(parse_instance_port(&[0x01], "SQLEXPRESS").is_err());
+    }
+}
@@ -7,7 +7,13 @@ pub enum SqlServerError {
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error(transparent)]
+    Fmt(#[from] std::fmt::Error),
+    #[error(transparent)]
     Arrow(#[from] datafusion::arrow::error::ArrowError),
+    #[error(transparent)]
+    Common(#[from] crate::common::errors::DatasourceCommonError),
+    #[error(transparent)]
+    Tunnel(#[from] crate::common::tunnel::TunnelError),
 }
 
 pub type Result<T, E = SqlServerError> = std::result::Result<T, E>;
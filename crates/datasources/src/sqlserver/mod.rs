@@ -1,21 +1,26 @@
 pub mod errors;
 
+mod browser;
 mod client;
 
 use chrono::{DateTime, Utc};
 use client::{Client, QueryStream};
 
+use crate::common::egress::check_egress;
+use crate::common::tunnel::TunnelSession;
+use crate::common::util;
 use async_trait::async_trait;
-use chrono::naive::NaiveDateTime;
+use chrono::naive::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::Timelike;
 use datafusion::arrow::datatypes::{
     DataType, Field, Fields, Schema as ArrowSchema, SchemaRef as ArrowSchemaRef, TimeUnit,
 };
-use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::record_batch::{RecordBatch, RecordBatchOptions};
 use datafusion::datasource::TableProvider;
 use datafusion::error::{DataFusionError, Result as DatafusionResult};
 use datafusion::execution::context::SessionState;
 use datafusion::execution::context::TaskContext;
-use datafusion::logical_expr::{Expr, TableProviderFilterPushDown, TableType};
+use datafusion::logical_expr::{Expr, Operator, TableProviderFilterPushDown, TableType};
 use datafusion::physical_expr::PhysicalSortExpr;
 use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
 use datafusion::physical_plan::metrics::MetricsSet;
@@ -28,8 +33,10 @@ use datafusion_ext::functions::VirtualLister;
 use datafusion_ext::metrics::DataSourceMetricsStreamAdapter;
 use errors::{Result, SqlServerError};
 use futures::{future::BoxFuture, ready, stream::BoxStream, FutureExt, Stream, StreamExt};
+use protogen::metastore::types::options::TunnelOptions;
 use std::any::Any;
-use std::fmt;
+use std::borrow::Cow;
+use std::fmt::{self, Write};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -38,7 +45,7 @@ use tiberius::FromSql;
 use tokio::net::TcpStream;
 use tokio::task::JoinHandle;
 use tokio_util::compat::TokioAsyncWriteCompatExt;
-use tracing::warn;
+use tracing::{debug, warn};
 
 /// Timeout when attempting to connecting to the remote server.
 pub const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
@@ -46,6 +53,9 @@ pub const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
 /// Configuration needed for accessing a sql server instance.
 pub struct SqlServerAccess {
     config: tiberius::Config,
+    /// Named instance to look up the port of when connecting.
+    instance_name: Option<String>,
+    tunnel: Option<TunnelOptions>,
 }
 
 impl SqlServerAccess {
@@ -53,27 +63,47 @@ impl SqlServerAccess {
     ///
     /// ADO connection strings: <https://docs.microsoft.com/en-us/dotnet/framework/data/adonet/connection-strings>
     /// Example: "server=tcp:localhost,1433;user=SA;password=<YourStrong@Passw0rd>;IntegratedSecurity=true;TrustServerCertificate=true"
+    ///
+    /// Named instances are given as `server=tcp:host\instance`, their port is
+    /// looked up with the SQL Server Browser service unless a port is also
+    /// given.
+    ///
+    /// Encryption is set with `Encrypt` (`true`, `false` or
+    /// `DANGER_PLAINTEXT`). The server certificate is validated against the
+    /// system's root certificates, or the certificates in the file given with
+    /// `TrustServerCertificateCA`, and not validated at all with
+    /// `TrustServerCertificate=true`.
     pub fn try_new_from_ado_string(conn_str: &str) -> Result<Self> {
         let config = tiberius::Config::from_ado_string(conn_str)?;
-        Ok(Self { config })
+        Ok(Self {
+            config,
+            instance_name: browser::instance_name_from_ado_string(conn_str),
+            tunnel: None,
+        })
+    }
+
+    /// Connect to the server through a tunnel.
+    pub fn with_tunnel(mut self, tunnel: Option<TunnelOptions>) -> Self {
+        self.tunnel = tunnel;
+        self
     }
 
     /// Validate that we can connect to server.
     pub async fn validate_access(&self) -> Result<()> {
-        let _state = SqlServerAccessState::connect(self.config.clone()).await?;
+        let _state = SqlServerAccessState::connect(self).await?;
         Ok(())
     }
 
     /// Validate that we can connect to a specific table.
     pub async fn validate_table_access(&self, schema: &str, table: &str) -> Result<()> {
-        let state = SqlServerAccessState::connect(self.config.clone()).await?;
+        let state = SqlServerAccessState::connect(self).await?;
         let _schema = state.get_table_schema(schema, table).await?;
         Ok(())
     }
 
     /// Connect to the server and return the access state.
     pub async fn connect(&self) -> Result<SqlServerAccessState> {
-        SqlServerAccessState::connect(self.config.clone()).await
+        SqlServerAccessState::connect(self).await
     }
 }
 
@@ -84,22 +114,57 @@ pub struct SqlServerAccessState {
     ///
     /// Kept on struct to avoid dropping the connection.
     _conn_handle: JoinHandle<()>,
+    /// Tunnel the connection goes through, if any.
+    ///
+    /// Kept on struct to avoid closing the tunnel.
+    _tunnel: Option<TunnelSession>,
 }
 
 impl SqlServerAccessState {
-    async fn connect(config: tiberius::Config) -> Result<Self> {
-        let socket =
-            match tokio::time::timeout(CONNECTION_TIMEOUT, TcpStream::connect(config.get_addr()))
-                .await
-            {
-                Ok(result) => result,
-                Err(_) => {
-                    return Err(SqlServerError::String(format!(
-                        "timed out connection to SQL Server after {} seconds",
-                        CONNECTION_TIMEOUT.as_secs(),
-                    )))
-                }
-            }?;
+    async fn connect(access: &SqlServerAccess) -> Result<Self> {
+        let mut config = access.config.clone();
+        let tunnel = access.tunnel.as_ref();
+
+        if let Some(instance) = &access.instance_name {
+            if tunnel.is_some() {
+                return Err(SqlServerError::String(format!(
+                    "a port is required to connect to instance '{instance}' through a tunnel"
+                )));
+            }
+            let host = config.get_addr();
+            let host = host.rsplit_once(':').map(|(host, _)| host).unwrap_or(&host);
+            check_egress(host, browser::BROWSER_PORT).await?;
+            let port = browser::resolve_instance_port(host, instance).await?;
+            config.port(port);
+        }
+
+        let addr = config.get_addr();
+        let (host, port) = addr
+            .rsplit_once(':')
+            .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+            .ok_or_else(|| SqlServerError::String(format!("invalid address: {addr}")))?;
+        check_egress(host, port).await?;
+
+        // Connect to the local end of the tunnel. TLS still uses the host from
+        // the config.
+        let (tunnel, addr) = match tunnel {
+            Some(tunnel) => {
+                let (session, local_addr) = TunnelSession::open(tunnel, host, port).await?;
+                (Some(session), local_addr.to_string())
+            }
+            None => (None, addr),
+        };
+
+        let socket = match tokio::time::timeout(CONNECTION_TIMEOUT, TcpStream::connect(addr)).await
+        {
+            Ok(result) => result,
+            Err(_) => {
+                return Err(SqlServerError::String(format!(
+                    "timed out connection to SQL Server after {} seconds",
+                    CONNECTION_TIMEOUT.as_secs(),
+                )))
+            }
+        }?;
         socket.set_nodelay(true)?;
         let (client, connection) = client::connect(config, socket.compat_write()).await?;
 
@@ -112,6 +177,7 @@ impl SqlServerAccessState {
         Ok(SqlServerAccessState {
             client,
             _conn_handle: handle,
+            _tunnel: tunnel,
         })
     }
 
@@ -119,7 +185,11 @@ impl SqlServerAccessState {
     async fn get_table_schema(&self, schema: &str, name: &str) -> Result<ArrowSchema> {
         let mut query = self
             .client
-            .query(format!("SELECT * FROM {schema}.{name} WHERE 1=0"))
+            .query(format!(
+                "SELECT * FROM {}.{} WHERE 1=0",
+                quote_identifier(schema),
+                quote_identifier(name)
+            ))
             .await?;
         let cols = query.columns().await?;
 
@@ -145,23 +215,31 @@ impl SqlServerAccessState {
             let arrow_typ = match col.column_type() {
                 ColumnType::Null => DataType::Null,
                 ColumnType::Bit | ColumnType::Bitn => DataType::Boolean,
-                ColumnType::Int1 => DataType::Int8,
+                // TINYINT is unsigned.
+                ColumnType::Int1 => DataType::UInt8,
                 ColumnType::Int2 => DataType::Int16,
                 ColumnType::Int4 => DataType::Int32,
                 ColumnType::Int8 | ColumnType::Intn => DataType::Int64,
                 ColumnType::Float4 => DataType::Float32,
-                ColumnType::Float8 | ColumnType::Floatn => DataType::Float64,
+                ColumnType::Float8
+                | ColumnType::Floatn
+                | ColumnType::Money
+                | ColumnType::Money4 => DataType::Float64,
+                ColumnType::Daten => DataType::Date32,
+                ColumnType::Timen => DataType::Time64(TimeUnit::Nanosecond),
                 // TODO: Double check that this mapping is correct.
                 ColumnType::Datetime
                 | ColumnType::Datetime2
                 | ColumnType::Datetime4
                 | ColumnType::Datetimen => DataType::Timestamp(TimeUnit::Nanosecond, None),
+                // Values are converted to UTC, the offsets aren't kept.
                 ColumnType::DatetimeOffsetn => {
                     DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into()))
                 }
-                ColumnType::Guid => DataType::Utf8,
-                // TODO: These actually have UTF-16 encoding...
-                ColumnType::Text
+                // NCHAR, NVARCHAR and NTEXT are decoded from UTF-16, and the
+                // others from the code page of their collation.
+                ColumnType::Guid
+                | ColumnType::Text
                 | ColumnType::NChar
                 | ColumnType::NText
                 | ColumnType::BigChar
@@ -256,7 +334,7 @@ pub struct SqlServerTableProvider {
 
 impl SqlServerTableProvider {
     pub async fn try_new(conf: SqlServerTableProviderConfig) -> Result<Self> {
-        let state = SqlServerAccessState::connect(conf.access.config).await?;
+        let state = SqlServerAccessState::connect(&conf.access).await?;
         let arrow_schema = state.get_table_schema(&conf.schema, &conf.table).await?;
 
         Ok(Self {
@@ -293,7 +371,7 @@ impl TableProvider for SqlServerTableProvider {
         &self,
         _ctx: &SessionState,
         projection: Option<&Vec<usize>>,
-        _filters: &[Expr],
+        filters: &[Expr],
         limit: Option<usize>,
     ) -> DatafusionResult<Arc<dyn ExecutionPlan>> {
         // Project the schema.
@@ -304,23 +382,37 @@ impl TableProvider for SqlServerTableProvider {
 
         // Get the projected columns, joined by a ','. This will be put in the
         // 'SELECT ...' portion of the query.
-        let projection_string = projected_schema
-            .fields
-            .iter()
-            .map(|f| f.name().clone())
-            .collect::<Vec<_>>()
-            .join(",");
-
-        let limit_string = match limit {
-            Some(limit) => format!("LIMIT {}", limit),
+        //
+        // Nothing needs to be read when no columns are projected, only the
+        // number of rows is needed.
+        let projection_string = if projected_schema.fields.is_empty() {
+            "1".to_string()
+        } else {
+            projected_schema
+                .fields
+                .iter()
+                .map(|f| quote_identifier(f.name()))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let top_string = match limit {
+            Some(limit) => format!("TOP {limit} "),
             None => String::new(),
         };
 
-        // TODO: Where/filters
+        let predicate_string = exprs_to_predicate_string(filters)
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        let where_string = if predicate_string.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {predicate_string}")
+        };
 
         let query = format!(
-            "SELECT {projection_string} FROM {}.{} {limit_string}",
-            self.schema, self.table
+            "SELECT {top_string}{projection_string} FROM {}.{}{where_string}",
+            quote_identifier(&self.schema),
+            quote_identifier(&self.table),
         );
 
         Ok(Arc::new(SqlServerExec {
@@ -522,12 +614,26 @@ fn rows_to_record_batch(
     schema: ArrowSchemaRef,
 ) -> Result<RecordBatch> {
     use datafusion::arrow::array::{
-        Array, BinaryBuilder, BooleanBuilder, Float32Builder, Float64Builder, Int16Builder,
-        Int32Builder, Int64Builder, StringBuilder, TimestampNanosecondBuilder,
+        Array, BinaryBuilder, BooleanBuilder, Date32Builder, Float32Builder, Float64Builder,
+        Int16Builder, Int32Builder, Int64Builder, StringBuilder, Time64NanosecondBuilder,
+        TimestampNanosecondBuilder, UInt8Builder,
     };
 
     let rows = rows.into_iter().collect::<Result<Vec<_>>>()?;
 
+    if schema.fields.is_empty() {
+        let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+        return Ok(RecordBatch::try_new_with_options(
+            schema,
+            Vec::new(),
+            &options,
+        )?);
+    }
+
+    let out_of_range = |v: &dyn fmt::Display| {
+        SqlServerError::String(format!("timestamp out of range for nanoseconds: {v}"))
+    };
+
     /// Macro for generating the match arms when converting rows to a record batch.
     macro_rules! make_column {
         ($builder:ty, $rows:expr, $col_idx:expr) => {{
@@ -543,6 +649,7 @@ fn rows_to_record_batch(
     for (col_idx, field) in schema.fields.iter().enumerate() {
         let col: Arc<dyn Array> = match field.data_type() {
             DataType::Boolean => make_column!(BooleanBuilder, rows, col_idx),
+            DataType::UInt8 => make_column!(UInt8Builder, rows, col_idx),
             DataType::Int16 => make_column!(Int16Builder, rows, col_idx),
             DataType::Int32 => make_column!(Int32Builder, rows, col_idx),
             DataType::Int64 => {
@@ -566,8 +673,8 @@ fn rows_to_record_batch(
                 // Assumes an average of 16 bytes per item.
                 let mut arr = StringBuilder::with_capacity(rows.len(), rows.len() * 16);
                 for row in rows.iter() {
-                    let val: Option<&str> = row.try_get(col_idx)?;
-                    arr.append_option(val);
+                    let val: Option<Text> = row.try_get(col_idx)?;
+                    arr.append_option(val.as_ref().map(|v| v.0.as_ref()));
                 }
                 Arc::new(arr.finish())
             }
@@ -584,7 +691,9 @@ fn rows_to_record_batch(
                 let mut arr = TimestampNanosecondBuilder::with_capacity(rows.len());
                 for row in rows.iter() {
                     let val: Option<NaiveDateTime> = row.try_get(col_idx)?;
-                    let val = val.map(|v| v.timestamp_nanos_opt().unwrap());
+                    let val = val
+                        .map(|v| v.timestamp_nanos_opt().ok_or_else(|| out_of_range(&v)))
+                        .transpose()?;
                     arr.append_option(val);
                 }
                 Arc::new(arr.finish())
@@ -594,7 +703,32 @@ fn rows_to_record_batch(
                     .with_data_type(dt.clone());
                 for row in rows.iter() {
                     let val: Option<DateTime<Utc>> = row.try_get(col_idx)?;
-                    let val = val.map(|v| v.timestamp_nanos_opt().unwrap());
+                    let val = val
+                        .map(|v| v.timestamp_nanos_opt().ok_or_else(|| out_of_range(&v)))
+                        .transpose()?;
+                    arr.append_option(val);
+                }
+                Arc::new(arr.finish())
+            }
+            DataType::Date32 => {
+                let mut arr = Date32Builder::with_capacity(rows.len());
+                for row in rows.iter() {
+                    let val: Option<NaiveDate> = row.try_get(col_idx)?;
+                    let epoch_date = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+                    let val = val.map(|v| v.signed_duration_since(epoch_date).num_days() as i32);
+                    arr.append_option(val);
+                }
+                Arc::new(arr.finish())
+            }
+            DataType::Time64(TimeUnit::Nanosecond) => {
+                let mut arr = Time64NanosecondBuilder::with_capacity(rows.len());
+                for row in rows.iter() {
+                    let val: Option<NaiveTime> = row.try_get(col_idx)?;
+                    let val = val.map(|v| {
+                        let nanos = v.nanosecond() as i64;
+                        let secs_since_midnight = v.num_seconds_from_midnight() as i64;
+                        (secs_since_midnight * 1_000_000_000) + nanos
+                    });
                     arr.append_option(val);
                 }
                 Arc::new(arr.finish())
@@ -660,3 +794,184 @@ impl<'a> FromSql<'a> for Floatn {
         })
     }
 }
+
+/// Read a string from a column value, formatting GUIDs as strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Text<'a>(Cow<'a, str>);
+
+impl<'a> FromSql<'a> for Text<'a> {
+    fn from_sql(value: &'a tiberius::ColumnData<'static>) -> tiberius::Result<Option<Self>> {
+        Ok(match value {
+            tiberius::ColumnData::String(v) => v.as_deref().map(|v| Text(Cow::Borrowed(v))),
+            tiberius::ColumnData::Guid(v) => v.map(|v| Text(Cow::Owned(v.to_string()))),
+            other => {
+                return Err(tiberius::error::Error::Conversion(
+                    format!("{other:?} to Text").into(),
+                ))
+            }
+        })
+    }
+}
+
+/// Quote an identifier with brackets.
+fn quote_identifier(ident: &str) -> String {
+    format!("[{}]", ident.replace(']', "]]"))
+}
+
+/// Convert filtering expressions to a predicate string usable with the
+/// generated SQL Server query.
+///
+/// Only comparisons are pushed down. Filters are still applied after the scan
+/// since comparing strings on the server depends on the collation, which is
+/// often case insensitive.
+fn exprs_to_predicate_string(exprs: &[Expr]) -> Result<String> {
+    let mut ss = Vec::new();
+    for expr in exprs {
+        let mut buf = String::new();
+        if write_expr(expr, &mut buf)? {
+            ss.push(buf);
+        }
+    }
+    Ok(ss.join(" AND "))
+}
+
+/// Try to write a boolean expression to the string, returning true if it was
+/// written.
+fn write_expr(expr: &Expr, buf: &mut String) -> Result<bool> {
+    match expr {
+        Expr::IsNull(expr) => {
+            if !write_operand(expr, buf)? {
+                return Ok(false);
+            }
+            write!(buf, " IS NULL")?;
+        }
+        Expr::IsNotNull(expr) => {
+            if !write_operand(expr, buf)? {
+                return Ok(false);
+            }
+            write!(buf, " IS NOT NULL")?;
+        }
+        Expr::Not(expr) => {
+            write!(buf, "NOT (")?;
+            if !write_expr(expr, buf)? {
+                return Ok(false);
+            }
+            write!(buf, ")")?;
+        }
+        Expr::BinaryExpr(binary) => {
+            let write_side: fn(&Expr, &mut String) -> Result<bool> = match binary.op {
+                Operator::And | Operator::Or => write_expr,
+                Operator::Eq
+                | Operator::NotEq
+                | Operator::Lt
+                | Operator::LtEq
+                | Operator::Gt
+                | Operator::GtEq => write_operand,
+                _ => {
+                    debug!(?expr, "Unsupported filter used");
+                    return Ok(false);
+                }
+            };
+            write!(buf, "(")?;
+            if !write_side(binary.left.as_ref(), buf)? {
+                return Ok(false);
+            }
+            write!(buf, " {} ", binary.op)?;
+            if !write_side(binary.right.as_ref(), buf)? {
+                return Ok(false);
+            }
+            write!(buf, ")")?;
+        }
+        expr => {
+            // Unsupported.
+            debug!(?expr, "Unsupported filter used");
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Try to write an operand of a comparison to the string, returning true if it
+/// was written.
+fn write_operand(expr: &Expr, buf: &mut String) -> Result<bool> {
+    use datafusion::scalar::ScalarValue;
+
+    match expr {
+        Expr::Column(col) => {
+            write!(buf, "{}", quote_identifier(&col.name))?;
+        }
+        Expr::Literal(
+            val @ (ScalarValue::Boolean(_)
+            | ScalarValue::Int8(_)
+            | ScalarValue::Int16(_)
+            | ScalarValue::Int32(_)
+            | ScalarValue::Int64(_)
+            | ScalarValue::Float32(_)
+            | ScalarValue::Float64(_)
+            | ScalarValue::Utf8(_)
+            | ScalarValue::Binary(_)
+            | ScalarValue::Date32(_)),
+        ) => {
+            util::encode_literal_to_text(util::Datasource::SqlServer, buf, val)?;
+        }
+        expr => {
+            // Unsupported, timestamp literals in particular can't be compared
+            // with DATETIME columns if they have more than 3 fractional digits.
+            debug!(?expr, "Unsupported filter used");
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::common::Column;
+    use datafusion::logical_expr::{col, lit, BinaryExpr};
+
+    use super::*;
+
+    #[test]
+    fn valid_expr_string() {
+        let exprs = vec![
+            col("name")
+                .eq(lit("Ålesund"))
+                .or(col("id").gt_eq(lit(10_i64))),
+            col("deleted").is_null(),
+            Expr::BinaryExpr(BinaryExpr {
+                left: Box::new(Expr::Column(Column {
+                    relation: None,
+                    name: "weird]name".to_string(),
+                })),
+                op: Operator::NotEq,
+                right: Box::new(lit(true)),
+            }),
+        ];
+
+        let out = exprs_to_predicate_string(&exprs).unwrap();
+        assert_eq!(
+            out,
+            "(([name] = N'Ålesund') OR ([id] >= 10)) AND [deleted] IS NULL AND ([weird]]name] != 1)"
+        )
+    }
+
+    #[test]
+    fn skip_unsupported_expr_string() {
+        let exprs = vec![
+            col("a").lt(col("b")),
+            // String concatenation isn't pushed down.
+            col("a").eq(Expr::BinaryExpr(BinaryExpr {
+                left: Box::new(col("b")),
+                op: Operator::StringConcat,
+                right: Box::new(lit("x")),
+            })),
+            // Nor is a part of a disjunction.
+            col("a").eq(lit(1_i32)).or(col("b").like(lit("x%"))),
+        ];
+
+        let out = exprs_to_predicate_string(&exprs).unwrap();
+        assert_eq!(out, "([a] < [b])")
+    }
+}
@@ -0,0 +1,51 @@
+use datafusion::error::DataFusionError;
+use datafusion_ext::errors::ExtensionError;
+
+use crate::object_store::errors::ObjectStoreSourceError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum XmlError {
+    #[error(transparent)]
+    Xml(#[from] quick_xml::Error),
+
+    #[error(transparent)]
+    Attribute(#[from] quick_xml::events::attributes::AttrError),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Datafusion(#[from] datafusion::error::DataFusionError),
+
+    #[error(transparent)]
+    Arrow(#[from] datafusion::arrow::error::ArrowError),
+
+    #[error(transparent)]
+    ObjectStore(#[from] ObjectStoreSourceError),
+
+    #[error(transparent)]
+    Store(#[from] object_store::Error),
+
+    #[error("Invalid XML: {0}")]
+    InvalidXml(String),
+
+    #[error("no '{0}' elements found")]
+    NoRows(String),
+
+    #[error("no objects found {0}")]
+    NotFound(String),
+}
+
+impl From<XmlError> for DataFusionError {
+    fn from(e: XmlError) -> Self {
+        DataFusionError::Execution(e.to_string())
+    }
+}
+
+impl From<XmlError> for ExtensionError {
+    fn from(e: XmlError) -> Self {
+        ExtensionError::String(e.to_string())
+    }
+}
+
+pub type Result<T, E = XmlError> = std::result::Result<T, E>;
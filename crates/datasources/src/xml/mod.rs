@@ -0,0 +1,5 @@
+//! Reading XML files, with each element named by a row tag read as a row.
+pub mod errors;
+pub mod rows;
+pub mod schema;
+pub mod table;
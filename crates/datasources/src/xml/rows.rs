@@ -0,0 +1,247 @@
+//! Conversion of XML elements into rows.
+//!
+//! Each row element becomes a JSON object: attributes and child elements are
+//! its fields, child elements with attributes or children of their own become
+//! nested objects, and repeated child elements become arrays. Text is typed as
+//! a number or a boolean when it looks like one.
+use quick_xml::events::{BytesStart, Event};
+use serde_json::{Map, Value};
+
+use super::errors::Result;
+
+/// Field holding the text of elements that also have attributes or children.
+pub const TEXT_FIELD: &str = "_text";
+
+/// An element that hasn't been closed yet.
+#[derive(Debug)]
+struct OpenElement {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+impl OpenElement {
+    fn into_value(self) -> Value {
+        if self.fields.is_empty() {
+            typed_value(&self.text)
+        } else {
+            Value::Object(self.into_fields())
+        }
+    }
+
+    fn into_fields(mut self) -> Map<String, Value> {
+        if !self.text.trim().is_empty() {
+            insert_field(
+                &mut self.fields,
+                TEXT_FIELD.to_string(),
+                typed_value(&self.text),
+            );
+        }
+        self.fields
+    }
+}
+
+/// Builds rows from the events of an XML reader.
+///
+/// Elements named `row_tag` are rows. Everything outside of them is skipped.
+/// Namespace prefixes are dropped from element and attribute names.
+#[derive(Debug)]
+pub struct RowBuilder {
+    row_tag: Vec<u8>,
+    /// Open elements, starting with the row element.
+    stack: Vec<OpenElement>,
+}
+
+impl RowBuilder {
+    pub fn new(row_tag: &str) -> Self {
+        RowBuilder {
+            row_tag: row_tag.as_bytes().to_vec(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Process the next event, returning a row once a row element is closed.
+    pub fn push(&mut self, event: Event<'_>) -> Result<Option<Map<String, Value>>> {
+        match event {
+            Event::Start(start) => {
+                self.open(&start)?;
+                Ok(None)
+            }
+            Event::Empty(start) => {
+                if self.open(&start)? {
+                    Ok(self.close())
+                } else {
+                    Ok(None)
+                }
+            }
+            Event::End(_) => Ok(self.close()),
+            Event::Text(text) => {
+                if let Some(element) = self.stack.last_mut() {
+                    element.text.push_str(&text.unescape()?);
+                }
+                Ok(None)
+            }
+            Event::CData(data) => {
+                if let Some(element) = self.stack.last_mut() {
+                    element.text.push_str(&String::from_utf8_lossy(&data));
+                }
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Open an element if it's inside of a row or starts one.
+    fn open(&mut self, start: &BytesStart<'_>) -> Result<bool> {
+        let name = start.local_name();
+        if self.stack.is_empty() && name.as_ref() != self.row_tag.as_slice() {
+            return Ok(false);
+        }
+
+        let mut fields = Map::new();
+        for attr in start.attributes() {
+            let attr = attr?;
+            if attr.key.as_namespace_binding().is_some() {
+                continue;
+            }
+            let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned();
+            insert_field(&mut fields, key, typed_value(&attr.unescape_value()?));
+        }
+
+        self.stack.push(OpenElement {
+            name: String::from_utf8_lossy(name.as_ref()).into_owned(),
+            fields,
+            text: String::new(),
+        });
+        Ok(true)
+    }
+
+    /// Close the innermost open element, returning the row if it was the row
+    /// element.
+    fn close(&mut self) -> Option<Map<String, Value>> {
+        let element = self.stack.pop()?;
+        match self.stack.last_mut() {
+            Some(parent) => {
+                let name = element.name.clone();
+                insert_field(&mut parent.fields, name, element.into_value());
+                None
+            }
+            None => Some(element.into_fields()),
+        }
+    }
+}
+
+/// Insert a field, collecting the values of repeated fields into an array.
+fn insert_field(fields: &mut Map<String, Value>, key: String, value: Value) {
+    match fields.get_mut(&key) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            fields.insert(key, value);
+        }
+    }
+}
+
+/// Type text as a number or boolean if possible. Blank text is null.
+///
+/// Numbers with leading zeros are kept as strings so that values like zip
+/// codes aren't altered.
+fn typed_value(text: &str) -> Value {
+    let text = text.trim();
+    if text.is_empty() {
+        return Value::Null;
+    }
+
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+    if !leading_zero && digits.starts_with(|c: char| c.is_ascii_digit()) {
+        if let Ok(n) = text.parse::<i64>() {
+            return Value::from(n);
+        }
+        if let Some(n) = text
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+        {
+            return Value::Number(n);
+        }
+    }
+
+    match text {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::String(text.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quick_xml::Reader;
+    use serde_json::json;
+
+    use super::*;
+
+    fn rows(xml: &str, row_tag: &str) -> Vec<Value> {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut builder = RowBuilder::new(row_tag);
+        let mut rows = Vec::new();
+        loop {
+            let event = reader.read_event().unwrap();
+            if matches!(event, Event::Eof) {
+                break;
+            }
+            if let Some(row) = builder.push(event).unwrap() {
+                rows.push(Value::Object(row));
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn build_rows() {
+        let xml = r#"<?xml version="1.0"?>
+            <feed xmlns:x="urn:x">
+              <meta><record>nested</record></meta>
+              <record id="1">
+                <name>a &amp; b</name>
+                <x:zip>0123</x:zip>
+                <address country="NO"><city>Oslo</city></address>
+                <tag>x</tag><tag>y</tag>
+              </record>
+              <record id="2" active="true"/>
+              <record><note lang="en"><![CDATA[<hi>]]></note></record>
+            </feed>"#;
+
+        assert_eq!(
+            vec![
+                json!({"_text": "nested"}),
+                json!({
+                    "id": 1,
+                    "name": "a & b",
+                    "zip": "0123",
+                    "address": {"country": "NO", "city": "Oslo"},
+                    "tag": ["x", "y"],
+                }),
+                json!({"id": 2, "active": true}),
+                json!({"note": {"lang": "en", "_text": "<hi>"}}),
+            ],
+            rows(xml, "record")
+        );
+    }
+
+    #[test]
+    fn type_text() {
+        assert_eq!(json!(-12), typed_value(" -12 "));
+        assert_eq!(json!(0), typed_value("0"));
+        assert_eq!(json!(0.5), typed_value("0.5"));
+        assert_eq!(json!(1.5e3), typed_value("1.5e3"));
+        assert_eq!(json!("007"), typed_value("007"));
+        assert_eq!(json!("inf"), typed_value("inf"));
+        assert_eq!(json!(false), typed_value("false"));
+        assert_eq!(Value::Null, typed_value("  "));
+    }
+}
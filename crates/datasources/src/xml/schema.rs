@@ -0,0 +1,200 @@
+//! Schema inference for rows read from XML.
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::{DataType, Field, Fields, Schema};
+use serde_json::{Map, Value};
+
+use super::errors::{Result, XmlError};
+use super::rows::TEXT_FIELD;
+
+/// Type of a value, merged across rows.
+#[derive(Debug, Clone, PartialEq)]
+enum XmlType {
+    Null,
+    Boolean,
+    Int,
+    Float,
+    String,
+    List(Box<XmlType>),
+    /// Fields in the order they were first seen.
+    Struct(Vec<(String, XmlType)>),
+}
+
+impl XmlType {
+    fn of(value: &Value) -> XmlType {
+        match value {
+            Value::Null => XmlType::Null,
+            Value::Bool(_) => XmlType::Boolean,
+            Value::Number(n) if n.is_i64() => XmlType::Int,
+            Value::Number(_) => XmlType::Float,
+            Value::String(_) => XmlType::String,
+            Value::Array(items) => XmlType::List(Box::new(
+                items
+                    .iter()
+                    .map(XmlType::of)
+                    .fold(XmlType::Null, XmlType::merge),
+            )),
+            Value::Object(fields) => XmlType::Struct(
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), XmlType::of(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn merge(self, other: XmlType) -> XmlType {
+        match (self, other) {
+            (XmlType::Null, t) | (t, XmlType::Null) => t,
+            (a, b) if a == b => a,
+            (XmlType::Int, XmlType::Float) | (XmlType::Float, XmlType::Int) => XmlType::Float,
+            (XmlType::List(a), XmlType::List(b)) => XmlType::List(Box::new(a.merge(*b))),
+            // Elements only repeated in some rows.
+            (XmlType::List(a), b) | (b, XmlType::List(a)) => XmlType::List(Box::new(a.merge(b))),
+            (XmlType::Struct(mut a), XmlType::Struct(b)) => {
+                for (key, b) in b {
+                    match a.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, a)) => *a = std::mem::replace(a, XmlType::Null).merge(b),
+                        None => a.push((key, b)),
+                    }
+                }
+                XmlType::Struct(a)
+            }
+            // Elements only having attributes or children in some rows, their
+            // text is read into the text field.
+            (XmlType::Struct(a), b) | (b, XmlType::Struct(a)) => {
+                XmlType::Struct(a).merge(XmlType::Struct(vec![(TEXT_FIELD.to_string(), b)]))
+            }
+            _ => XmlType::String,
+        }
+    }
+
+    fn into_data_type(self) -> DataType {
+        match self {
+            // Columns that are always null are read as strings.
+            XmlType::Null | XmlType::String => DataType::Utf8,
+            XmlType::Boolean => DataType::Boolean,
+            XmlType::Int => DataType::Int64,
+            XmlType::Float => DataType::Float64,
+            XmlType::List(item) => {
+                DataType::List(Arc::new(Field::new("item", item.into_data_type(), true)))
+            }
+            XmlType::Struct(fields) => DataType::Struct(
+                fields
+                    .into_iter()
+                    .map(|(key, t)| Field::new(key, t.into_data_type(), true))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Infer the schema of rows.
+pub fn infer_schema<'a>(rows: impl Iterator<Item = &'a Map<String, Value>>) -> Result<Schema> {
+    let mut columns = XmlType::Struct(Vec::new());
+    for row in rows {
+        let fields = row
+            .iter()
+            .map(|(key, value)| (key.clone(), XmlType::of(value)))
+            .collect();
+        columns = columns.merge(XmlType::Struct(fields));
+    }
+
+    match columns {
+        XmlType::Struct(columns) if !columns.is_empty() => Ok(Schema::new(
+            columns
+                .into_iter()
+                .map(|(name, t)| Field::new(name, t.into_data_type(), true))
+                .collect::<Vec<_>>(),
+        )),
+        _ => Err(XmlError::InvalidXml(
+            "no attributes, child elements or text found in rows".to_string(),
+        )),
+    }
+}
+
+/// Rewrite a row so that it can be decoded as `fields`.
+///
+/// Single values are wrapped in arrays for list columns, and text is moved
+/// into the text field for struct columns.
+pub fn normalize_row(mut row: Map<String, Value>, fields: &Fields) -> Value {
+    let normalized = fields
+        .iter()
+        .filter_map(|field| {
+            let value = row.remove(field.name())?;
+            Some((
+                field.name().clone(),
+                normalize_value(value, field.data_type()),
+            ))
+        })
+        .collect();
+    Value::Object(normalized)
+}
+
+fn normalize_value(value: Value, data_type: &DataType) -> Value {
+    match (value, data_type) {
+        (Value::Null, _) => Value::Null,
+        (Value::Array(items), DataType::List(field)) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| normalize_value(item, field.data_type()))
+                .collect(),
+        ),
+        (value, DataType::List(field)) => {
+            Value::Array(vec![normalize_value(value, field.data_type())])
+        }
+        (Value::Object(row), DataType::Struct(fields)) => normalize_row(row, fields),
+        (value, DataType::Struct(fields)) => {
+            normalize_row(Map::from_iter([(TEXT_FIELD.to_string(), value)]), fields)
+        }
+        (value @ (Value::Array(_) | Value::Object(_)), DataType::Utf8) => {
+            Value::String(value.to_string())
+        }
+        (value, _) => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn as_rows(values: Vec<Value>) -> Vec<Map<String, Value>> {
+        values
+            .into_iter()
+            .map(|value| match value {
+                Value::Object(row) => row,
+                other => panic!("not a row: {other}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn infer_and_normalize() {
+        let rows = as_rows(vec![
+            json!({"id": 1, "tag": "x", "price": "12", "note": "plain"}),
+            json!({"id": 2.5, "tag": ["y", "z"], "price": {"currency": "EUR", "_text": 10}}),
+            json!({"id": "n/a"}),
+        ]);
+        let schema = infer_schema(rows.iter()).unwrap();
+
+        assert_eq!(&DataType::Utf8, schema.field(0).data_type());
+        assert_eq!(
+            &DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+            schema.field(1).data_type()
+        );
+        let price = DataType::Struct(Fields::from(vec![
+            Field::new("currency", DataType::Utf8, true),
+            Field::new("_text", DataType::Utf8, true),
+        ]));
+        assert_eq!(&price, schema.field(2).data_type());
+        assert_eq!(&DataType::Utf8, schema.field(3).data_type());
+
+        let normalized = normalize_row(rows[0].clone(), schema.fields());
+        assert_eq!(
+            json!({"id": 1, "tag": ["x"], "price": {"_text": "12"}, "note": "plain"}),
+            normalized
+        );
+    }
+}
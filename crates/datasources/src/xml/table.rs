@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use async_stream::try_stream;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::json::ReaderBuilder;
+use datafusion::datasource::streaming::StreamingTable;
+use datafusion::datasource::TableProvider;
+use datafusion::error::DataFusionError;
+use datafusion::execution::TaskContext;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::streaming::PartitionStream;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use futures::{Stream, TryStreamExt};
+use object_store::buffered::BufReader;
+use object_store::{ObjectMeta, ObjectStore};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde_json::{Map, Value};
+
+use crate::common::url::DatasourceUrl;
+use crate::object_store::generic::GenericStoreAccess;
+use crate::object_store::ObjStoreAccess;
+use crate::xml::errors::{Result, XmlError};
+use crate::xml::rows::RowBuilder;
+use crate::xml::schema::{infer_schema, normalize_row};
+
+/// Number of rows read to infer the schema if not set.
+pub const DEFAULT_SAMPLE_SIZE: usize = 100;
+
+/// Size of the buffer used to read files from the object store.
+const READ_BUFFER_SIZE: usize = 8 * 1024 * 1024;
+
+/// Create a table streaming the `row_tag` elements of XML files, with one
+/// partition per file.
+///
+/// The schema is inferred from the first rows of the files.
+pub async fn xml_streaming_table(
+    store_access: GenericStoreAccess,
+    source_url: DatasourceUrl,
+    row_tag: String,
+    schema_inference_sample_size: Option<usize>,
+) -> Result<Arc<dyn TableProvider>> {
+    let sample_size = schema_inference_sample_size.unwrap_or(DEFAULT_SAMPLE_SIZE);
+
+    let path = source_url.path();
+    let store = store_access.create_store()?;
+    let mut list = store_access.list_globbed(&store, path.as_ref()).await?;
+    if list.is_empty() {
+        return Err(XmlError::NotFound(path.into_owned()));
+    }
+    // Sort for consistent results, particularly for the sample.
+    list.sort_by(|a, b| a.location.cmp(&b.location));
+
+    // Only read as much of the files as needed for the sample.
+    let mut sample = Vec::with_capacity(sample_size);
+    'objects: for object in &list {
+        let rows = read_rows(store.clone(), object, row_tag.clone());
+        futures::pin_mut!(rows);
+        while let Some(row) = rows.try_next().await? {
+            sample.push(row);
+            if sample.len() >= sample_size {
+                break 'objects;
+            }
+        }
+    }
+    if sample.is_empty() {
+        return Err(XmlError::NoRows(row_tag));
+    }
+    let schema = Arc::new(infer_schema(sample.iter())?);
+
+    let partitions = list
+        .into_iter()
+        .map(|object| {
+            Arc::new(XmlPartitionStream {
+                schema: schema.clone(),
+                store: store.clone(),
+                object,
+                row_tag: row_tag.clone(),
+            }) as Arc<dyn PartitionStream>
+        })
+        .collect();
+
+    Ok(Arc::new(StreamingTable::try_new(schema, partitions)?))
+}
+
+/// Stream the rows of a single file.
+fn read_rows(
+    store: Arc<dyn ObjectStore>,
+    object: &ObjectMeta,
+    row_tag: String,
+) -> impl Stream<Item = Result<Map<String, Value>>> + Send {
+    let reader = BufReader::with_capacity(store, object, READ_BUFFER_SIZE);
+
+    try_stream! {
+        let mut reader = Reader::from_reader(reader);
+        reader.trim_text(true);
+        let mut rows = RowBuilder::new(&row_tag);
+        let mut buf = Vec::new();
+
+        loop {
+            let event = match reader.read_event_into_async(&mut buf).await {
+                Ok(Event::Eof) => break,
+                Ok(event) => event,
+                Err(e) => Err(XmlError::InvalidXml(format!(
+                    "{e} at byte {}",
+                    reader.buffer_position()
+                )))?,
+            };
+            if let Some(row) = rows.push(event)? {
+                yield row;
+            }
+            buf.clear();
+        }
+    }
+}
+
+/// Streams the rows of a single file.
+struct XmlPartitionStream {
+    schema: SchemaRef,
+    store: Arc<dyn ObjectStore>,
+    object: ObjectMeta,
+    row_tag: String,
+}
+
+impl PartitionStream for XmlPartitionStream {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let schema = self.schema.clone();
+        let rows = read_rows(self.store.clone(), &self.object, self.row_tag.clone());
+        let batch_size = ctx.session_config().batch_size();
+
+        let stream = try_stream! {
+            let mut decoder = ReaderBuilder::new(schema.clone())
+                .with_batch_size(batch_size)
+                .with_coerce_primitive(true)
+                .build_decoder()?;
+            futures::pin_mut!(rows);
+            let mut buf = Vec::new();
+            let mut buffered = 0;
+
+            while let Some(row) = rows.try_next().await? {
+                serde_json::to_writer(&mut buf, &normalize_row(row, schema.fields()))?;
+                buffered += 1;
+                if buffered == batch_size {
+                    decoder.decode(&buf)?;
+                    if let Some(batch) = decoder.flush()? {
+                        yield batch;
+                    }
+                    buf.clear();
+                    buffered = 0;
+                }
+            }
+
+            decoder.decode(&buf)?;
+            if let Some(batch) = decoder.flush()? {
+                yield batch;
+            }
+        };
+
+        let source = self.object.location.to_string();
+        Box::pin(RecordBatchStreamAdapter::new(
+            self.schema.clone(),
+            stream.map_err(move |e: XmlError| {
+                DataFusionError::External(format!("{source}: {e}").into())
+            }),
+        ))
+    }
+}
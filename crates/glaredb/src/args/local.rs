@@ -1,4 +1,6 @@
+use arrow_util::pretty::{Alignment, DisplayOptions, TypeCategory};
 use clap::Args;
+use std::collections::BTreeMap;
 
 use super::*;
 
@@ -22,6 +24,19 @@ pub struct LocalArgs {
     #[arg(long, value_parser)]
     pub log_file: Option<PathBuf>,
 
+    /// Named profile from the config file to use.
+    ///
+    /// Profiles can set the cloud url, data directory, output mode, and
+    /// timing. Options provided on the command line take precedence.
+    #[arg(long, value_parser)]
+    pub profile: Option<String>,
+
+    /// Path to the config file containing profiles.
+    ///
+    /// Defaults to `~/.config/glaredb/config.toml`.
+    #[arg(long, value_parser, requires = "profile")]
+    pub config: Option<PathBuf>,
+
     /// Start the tokio console subscriber to debug runtime.
     #[cfg(not(release))]
     #[arg(long, value_parser, hide = true)]
@@ -56,6 +71,13 @@ pub struct LocalClientOpts {
     #[arg(long, default_value = "false", hide = true)]
     pub timing: bool,
 
+    /// Record executed statements in the `system.query_history` table.
+    ///
+    /// History is only recorded for local sessions, and is persisted across
+    /// sessions when used with `--data-dir`. Statements are kept for a week.
+    #[arg(long, default_value = "false")]
+    pub query_history: bool,
+
     /// Ignores the proxy and directly goes to the server for remote execution.
     ///
     /// (Internal)
@@ -67,6 +89,19 @@ pub struct LocalClientOpts {
     #[arg(long, hide = true)]
     pub ignore_rpc_auth: bool,
 
+    /// Additional servers to fail over to when opening the remote session.
+    ///
+    /// Can be given multiple times. Each URL should be equivalent to
+    /// `--cloud-url`, for example the other server of an HA pair. Only used
+    /// with `--ignore-rpc-auth`.
+    #[arg(long = "failover-url", value_parser, requires = "cloud_url")]
+    pub failover_urls: Vec<Url>,
+
+    /// Open sessions on the same server for as long as it's healthy instead
+    /// of balancing them across all servers.
+    #[arg(long, default_value = "false", requires = "failover_urls")]
+    pub sticky_sessions: bool,
+
     /// Display output mode.
     #[arg(long, value_enum, default_value_t=OutputMode::Table)]
     pub mode: OutputMode,
@@ -79,6 +114,32 @@ pub struct LocalClientOpts {
     #[arg(long)]
     pub max_rows: Option<usize>,
 
+    /// What to do when a statement fails while running a query or file.
+    ///
+    /// The exit code is non-zero if any statement failed.
+    #[arg(long, value_enum, default_value_t = OnError::Stop)]
+    pub on_error: OnError,
+
+    /// Format of errors written to stderr while running a query or file.
+    ///
+    /// With `json`, each error is written as an object on its own line with
+    /// `error`, `file`, `line`, and `statement` fields.
+    #[arg(long, value_enum, default_value_t = ErrorFormat::Text)]
+    pub error_format: ErrorFormat,
+
+    /// Text to display for NULL values.
+    #[arg(long, default_value = "NULL")]
+    pub null_display: String,
+
+    /// Max width of values in tables. Longer values are truncated with an
+    /// ellipsis.
+    #[arg(long, value_parser = parse_max_cell_width)]
+    pub max_cell_width: Option<usize>,
+
+    /// Alignment of values by type category, set with `\pset align`.
+    #[arg(skip)]
+    pub alignments: BTreeMap<TypeCategory, Alignment>,
+
     /// Disable RPC TLS
     ///
     /// (Internal)
@@ -99,12 +160,21 @@ pub struct LocalClientOpts {
 }
 
 impl LocalClientOpts {
+    /// Options for displaying values in the text output modes.
+    pub fn display_options(&self) -> DisplayOptions {
+        DisplayOptions {
+            null: self.null_display.clone(),
+            max_cell_width: self.max_cell_width,
+            alignments: self.alignments.clone(),
+        }
+    }
+
     pub fn help_string() -> Result<String> {
         let pairs = [
             ("\\help", "Show this help text"),
             (
                 "\\mode MODE",
-                "Set the output mode [table, json, ndjson, csv]",
+                "Set the output mode [table, json, ndjson, csv, markdown, latex, expanded, arrow]",
             ),
             ("\\max-rows NUM", "Max number of rows to display"),
             (
@@ -112,15 +182,52 @@ impl LocalClientOpts {
                 "Maximum width of the output table to display. Defaults to terminal size.",
             ),
             ("\\open PATH", "Open a database at the given path"),
+            (
+                "\\format [SQL]",
+                "Format the given SQL, or the previous query if none given",
+            ),
+            (
+                "\\o [FILE]",
+                "Write query output to FILE in the current mode, or back to stdout if none given",
+            ),
+            ("\\i FILE", "Run the statements and commands in FILE"),
+            (
+                "\\set [NAME VALUE]",
+                "Set a variable to substitute for :NAME, or list variables if none given",
+            ),
+            ("\\unset NAME", "Unset a variable"),
+            (
+                "\\copy SRC TO FILE",
+                "Write the rows of a table or (query) to a local FILE [csv, json, ndjson, arrow, ...]",
+            ),
+            (
+                "\\watch [SECONDS]",
+                "Re-execute the previous query every SECONDS, until Ctrl+C [default: 2]",
+            ),
+            (
+                "\\pset [NAME VALUE]",
+                "Set how values are displayed [null, max-cell-width, align], or show settings",
+            ),
             ("\\timing", "Toggle query execution runtime display"),
             ("\\quit", "Quit this session"),
         ];
 
         let mut buf = String::new();
         for (cmd, help) in pairs {
-            writeln!(&mut buf, "{cmd: <15} {help}")?;
+            writeln!(&mut buf, "{cmd: <18} {help}")?;
         }
 
         Ok(buf)
     }
 }
+
+/// Parse a max cell width, which must leave room for at least one character
+/// and the ellipsis.
+pub fn parse_max_cell_width(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(width) if width >= 2 => Ok(width),
+        _ => Err(format!(
+            "Invalid max cell width, expected a number of at least 2: {s}"
+        )),
+    }
+}
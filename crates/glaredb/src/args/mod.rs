@@ -11,12 +11,48 @@ pub mod server;
 
 pub use {local::*, server::*};
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum OutputMode {
     Table,
     Json,
     Ndjson,
     Csv,
+    /// Pipe tables for pasting into documents.
+    Markdown,
+    /// A LaTeX tabular environment.
+    Latex,
+    /// Each column of a row on its own line.
+    Expanded,
+    /// An Arrow IPC file.
+    Arrow,
+}
+
+impl OutputMode {
+    /// If output is only written once every batch has been received.
+    pub fn collects_batches(&self) -> bool {
+        matches!(
+            self,
+            OutputMode::Table | OutputMode::Markdown | OutputMode::Latex | OutputMode::Expanded
+        )
+    }
+}
+
+/// What to do when a statement fails while running a query or file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnError {
+    /// Stop at the first failing statement.
+    Stop,
+    /// Report the error and run the remaining statements.
+    Continue,
+}
+
+/// Format of errors written to stderr while running a query or file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// `Error: <message>`
+    Text,
+    /// A JSON object per line with the message and where the error occurred.
+    Json,
 }
 
 #[derive(Parser)]
@@ -37,6 +73,13 @@ pub struct MetastoreArgs {
     /// store).
     #[clap(short = 'f', long, value_parser)]
     pub local_file_path: Option<PathBuf>,
+
+    /// Postgres connection string for a database to store database catalogs
+    /// in.
+    ///
+    /// Conflicts with all other storage options.
+    #[clap(long, value_parser, conflicts_with_all = ["bucket", "service_account_path", "local_file_path"])]
+    pub postgres_url: Option<String>,
 }
 
 #[derive(Parser)]
@@ -91,6 +134,124 @@ pub struct PgProxyArgs {
     pub cloud_auth_code: String,
 }
 
+#[derive(Parser)]
+pub struct FmtArgs {
+    /// SQL files or directories to format.
+    ///
+    /// Directories are searched recursively for `.sql` files. If no paths are
+    /// provided, SQL is read from stdin and the formatted output is written
+    /// to stdout.
+    pub paths: Vec<PathBuf>,
+
+    /// Check if the input is formatted without writing any changes.
+    ///
+    /// Exits with an error if anything would be reformatted.
+    #[clap(long)]
+    pub check: bool,
+}
+
+/// Benchmark suites that can be run with `glaredb bench`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BenchSuite {
+    Tpch,
+}
+
+/// Format of the data files benchmarks are run against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BenchDataFormat {
+    Parquet,
+    Csv,
+}
+
+/// Format of benchmark reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BenchReportFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(Parser)]
+pub struct BenchArgs {
+    /// Benchmark suite to run.
+    #[clap(value_enum)]
+    pub suite: BenchSuite,
+
+    /// Scale factor of the data.
+    #[clap(long, default_value_t = 1.0)]
+    pub scale: f64,
+
+    /// Format of the data files.
+    #[clap(long, value_enum, default_value_t = BenchDataFormat::Parquet)]
+    pub format: BenchDataFormat,
+
+    /// Directory containing a file for each table of the suite, e.g.
+    /// `lineitem.parquet`.
+    ///
+    /// Missing files are generated. Defaults to
+    /// `./bench-data/<suite>/sf<scale>`.
+    #[clap(long)]
+    pub data_dir: Option<PathBuf>,
+
+    /// Path to the TPC-H `dbgen` binary used to generate data.
+    ///
+    /// Build it from the `benchmarks/tpch/tpch-dbgen` submodule. `dists.dss`
+    /// is read from `DSS_CONFIG` if set, otherwise from the directory
+    /// containing the binary.
+    #[clap(long, default_value = "dbgen")]
+    pub dbgen: PathBuf,
+
+    /// Queries to run by number, separated by commas. Runs every query if
+    /// not provided.
+    #[clap(long, value_delimiter = ',')]
+    pub queries: Vec<usize>,
+
+    /// Number of untimed runs of each query before timing it.
+    #[clap(long, default_value_t = 1)]
+    pub warmups: usize,
+
+    /// Number of timed runs of each query.
+    #[clap(long, default_value_t = 3)]
+    pub runs: usize,
+
+    /// Format of the report written to stdout.
+    #[clap(long, value_enum, default_value_t = BenchReportFormat::Markdown)]
+    pub report: BenchReportFormat,
+}
+
+#[derive(Parser)]
+pub struct DumpCatalogArgs {
+    /// File to write the catalog dump to.
+    pub output: PathBuf,
+
+    /// Include credentials in the dump.
+    ///
+    /// Requires `--encrypt` since credentials are stored as is.
+    #[clap(long, requires = "encrypt")]
+    pub include_credentials: bool,
+
+    /// Encrypt the dump with the key in the `GLAREDB_SECRET_DUMP_KEY`
+    /// environment variable.
+    #[clap(long)]
+    pub encrypt: bool,
+
+    #[clap(flatten)]
+    pub opts: LocalClientOpts,
+}
+
+#[derive(Parser)]
+pub struct LoadCatalogArgs {
+    /// File containing a catalog dump created with `dump-catalog`.
+    pub input: PathBuf,
+
+    /// Decrypt the dump with the key in the `GLAREDB_SECRET_DUMP_KEY`
+    /// environment variable.
+    #[clap(long)]
+    pub encrypted: bool,
+
+    #[clap(flatten)]
+    pub opts: LocalClientOpts,
+}
+
 #[derive(Debug, Clone, Parser)]
 pub struct StorageConfigArgs {
     /// URL of the object store in which to keep the data in.
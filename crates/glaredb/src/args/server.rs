@@ -1,6 +1,10 @@
 use clap::Args;
+use rpcsrv::jwt::JwtConfig;
+use sqlexec::admission::QueryLimits;
+use sqlexec::query_history::QueryHistory;
 
 use super::*;
+use crate::server::DEFAULT_SHUTDOWN_GRACE_PERIOD;
 
 #[derive(Args)]
 
@@ -19,6 +23,13 @@ pub struct ServerArgs {
     #[arg(id= "RPC_PORT", long="rpc-bind", value_parser, aliases=&["flight-bind"])]
     pub rpc_bind: Option<String>,
 
+    /// TCP address to bind to for serving Prometheus metrics.
+    ///
+    /// Metrics are served over HTTP on the `/metrics` path. Metrics are not
+    /// served if unset.
+    #[arg(long, value_parser)]
+    pub metrics_bind: Option<String>,
+
     /// Address to the Metastore.
     ///
     /// If not provided and `local` is set to a true, an in-process
@@ -26,9 +37,18 @@ pub struct ServerArgs {
     #[arg(short, long, hide = true, value_parser)]
     pub metastore_addr: Option<String>,
 
+    /// Postgres connection string for storing database catalogs.
+    ///
+    /// Starts an in-process metastore that stores catalogs in the Postgres
+    /// database instead of the data directory. Multiple servers may share
+    /// the same database. Connections use TLS if the server supports it, add
+    /// `sslmode=require` to require it.
+    #[arg(long, value_parser, conflicts_with = "metastore_addr")]
+    pub metastore_postgres_url: Option<String>,
+
     /// Set the user used for authentication.
     ///
-    /// Only has an affect if a password is also provided. If a password is
+    /// Only has an effect if a password is also provided. If a password is
     /// not provided, the GlareDB server will not prompt for a password.
     #[arg(short, long, value_parser, default_value_t = String::from("glaredb"), requires = "password")]
     pub user: String,
@@ -39,6 +59,15 @@ pub struct ServerArgs {
     #[arg(short, long, value_parser)]
     pub password: Option<String>,
 
+    /// Require SCRAM-SHA-256 authentication for Postgres protocol
+    /// connections.
+    ///
+    /// Users authenticate with passwords set using `ALTER ROLE ... PASSWORD`.
+    /// If a password is provided, the user can also authenticate with that
+    /// password, allowing passwords for other users to be set up.
+    #[arg(long, value_parser)]
+    pub scram_auth: bool,
+
     /// Optional file path for persisting data.
     ///
     /// Catalog data and user data will be stored in this directory.
@@ -59,6 +88,198 @@ pub struct ServerArgs {
     #[arg(long, value_parser)]
     pub spill_path: Option<PathBuf>,
 
+    /// Max number of queries executing at once across all sessions.
+    ///
+    /// Queries over the limit wait in a queue until a running query
+    /// completes. If unset, the number of queries isn't limited.
+    #[arg(long, value_parser)]
+    pub max_concurrent_queries: Option<usize>,
+
+    /// Max number of queries waiting to execute.
+    ///
+    /// Queries submitted when the queue is full error immediately. Only has
+    /// an effect if `--max-concurrent-queries` is set.
+    #[arg(long, value_parser, default_value_t = QueryLimits::DEFAULT_MAX_QUEUED_QUERIES)]
+    pub max_queued_queries: usize,
+
+    /// Number of seconds a query may wait in the queue before erroring.
+    ///
+    /// Only has an effect if `--max-concurrent-queries` is set.
+    #[arg(long, value_parser, default_value_t = QueryLimits::DEFAULT_QUEUE_TIMEOUT.as_secs())]
+    pub query_queue_timeout_secs: u64,
+
+    /// Max number of open connections across all users.
+    ///
+    /// Applies to Postgres protocol connections, flight sessions and simple
+    /// query rpcs. New connections over the limit are rejected.
+    #[arg(long, value_parser)]
+    pub max_connections: Option<usize>,
+
+    /// Max number of open connections for a single user.
+    #[arg(long, value_parser)]
+    pub max_connections_per_user: Option<usize>,
+
+    /// Number of seconds a connection may wait for the client to send a query
+    /// before it's closed.
+    ///
+    /// If unset, idle connections are never closed.
+    #[arg(long, value_parser)]
+    pub idle_session_timeout_secs: Option<u64>,
+
+    /// Record every executed statement to an audit log.
+    ///
+    /// Either 'table' to write to the `glare_catalog.audit_log` table, an
+    /// object store URL (e.g. 's3://bucket/audit') to write batches of JSON
+    /// lines objects using the provided storage options, or a path to a local
+    /// file to append JSON lines to.
+    #[arg(long, value_parser)]
+    pub audit_log: Option<String>,
+
+    /// Record a fingerprint of each statement with literals removed instead
+    /// of the full statement text.
+    ///
+    /// Only has an effect if `--audit-log` is set.
+    #[arg(long, value_parser)]
+    pub audit_log_fingerprint: bool,
+
+    /// Number of seconds to keep queries in `system.query_history`.
+    ///
+    /// Setting this to 0 disables recording the query history.
+    #[arg(long, value_parser, default_value_t = QueryHistory::DEFAULT_RETENTION.as_secs())]
+    pub query_history_retention_secs: u64,
+
+    /// Record changes made to the catalog in `glare_catalog.catalog_events`.
+    #[arg(long, value_parser)]
+    pub catalog_events: bool,
+
+    /// URL to additionally post batches of catalog events to as JSON.
+    ///
+    /// Implies `--catalog-events`.
+    #[arg(long, value_parser)]
+    pub catalog_events_webhook: Option<String>,
+
+    /// Number of seconds in-flight queries have to complete when shutting
+    /// down before they're canceled.
+    ///
+    /// New connections stop being accepted as soon as shutdown starts.
+    #[arg(long, value_parser, default_value_t = DEFAULT_SHUTDOWN_GRACE_PERIOD.as_secs())]
+    pub shutdown_grace_period_secs: u64,
+
+    /// Host data sources may connect to. May be provided multiple times.
+    ///
+    /// Rules are a host name ('db.example.com'), a wildcard domain
+    /// ('*.example.com'), an IP address or CIDR range ('10.0.0.0/8'), or '*',
+    /// optionally followed by a port or port range (':5432', ':5432-5433').
+    /// If set, external tables, databases and table functions can only
+    /// connect to matching hosts.
+    #[arg(long = "egress-allow", value_parser)]
+    pub egress_allow: Vec<String>,
+
+    /// Host data sources may not connect to, using the same rules as
+    /// `--egress-allow`. May be provided multiple times.
+    ///
+    /// Takes precedence over `--egress-allow`, e.g. '169.254.0.0/16' to deny
+    /// access to cloud metadata services.
+    #[arg(long = "egress-deny", value_parser)]
+    pub egress_deny: Vec<String>,
+
+    /// Address of an rpc service to distribute remote execution across
+    /// (e.g. 'http://worker-0:6443'). May be provided multiple times.
+    ///
+    /// Partitions of remote plans are split between this node and the
+    /// workers. Workers must be able to read the same storage as this node.
+    /// Workers can also be added, drained and removed at runtime with the
+    /// `RegisterWorker`, `DrainWorker` and `DeregisterWorker` rpcs, which
+    /// require a bearer token with a `--jwt-admin-role`.
+    #[arg(long = "rpc-worker", value_parser, requires = "rpc_bind")]
+    pub rpc_workers: Vec<String>,
+
+    /// Only serve reads, e.g. for a replica sharing storage with a primary.
+    ///
+    /// Statements that modify the catalog or tables are rejected in every
+    /// session. Individual roles can be made read-only with
+    /// `ALTER ROLE ... READ ONLY` instead.
+    #[arg(long, value_parser)]
+    pub read_only: bool,
+
+    /// Share a plan cache holding this many plans across all sessions.
+    ///
+    /// By default each session caches plans for statements it prepares on
+    /// its own.
+    #[arg(long, value_parser)]
+    pub shared_plan_cache_size: Option<usize>,
+
+    /// Path to a JSON file with settings that can be reloaded without
+    /// restarting.
+    ///
+    /// Supports 'log_filter', 'password', 'scram_auth',
+    /// 'max_concurrent_queries', 'max_queued_queries',
+    /// 'query_queue_timeout_secs', 'max_connections',
+    /// 'max_connections_per_user', 'idle_session_timeout_secs', 'egress_allow'
+    /// and 'egress_deny'. Settings in the file take precedence over the
+    /// equivalent arguments. The file is read
+    /// again when the server receives SIGHUP or a session runs
+    /// `select * from reload_config()`.
+    #[arg(long, value_parser)]
+    pub config: Option<PathBuf>,
+
+    /// Path to a PEM encoded certificate chain for encrypting Postgres
+    /// protocol connections with TLS.
+    ///
+    /// Clients requesting SSL are rejected if unset. Certificates are read
+    /// again when the server receives SIGHUP or a session runs
+    /// `select * from reload_config()`.
+    #[arg(long, value_parser, requires = "tls_key")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// Path to the PEM encoded PKCS8 private key for `--tls-cert`.
+    #[arg(long, value_parser, requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+
+    /// Path to PEM encoded CA certificates for verifying client
+    /// certificates.
+    ///
+    /// If set, clients must connect over TLS and present a certificate signed
+    /// by one of these CAs. Unencrypted connections are rejected.
+    #[arg(long, value_parser, requires = "tls_cert")]
+    pub tls_client_ca: Option<PathBuf>,
+
+    /// URL of the JWKS used to validate bearer tokens for the RPC/Flight SQL
+    /// interface and the metrics endpoint.
+    ///
+    /// If set, requests must include a JWT from `--jwt-issuer` in the
+    /// `authorization` header. Keys are refreshed periodically.
+    #[arg(long, value_parser, requires = "jwt_issuer")]
+    pub jwt_jwks_url: Option<String>,
+
+    /// Issuer bearer tokens must be from.
+    #[arg(long, value_parser, requires = "jwt_jwks_url")]
+    pub jwt_issuer: Option<String>,
+
+    /// Audience bearer tokens must be for. The audience isn't checked if
+    /// unset.
+    #[arg(long, value_parser, requires = "jwt_jwks_url")]
+    pub jwt_audience: Option<String>,
+
+    /// Claim containing the database user a bearer token maps to.
+    #[arg(long, value_parser, default_value_t = JwtConfig::DEFAULT_USER_CLAIM.to_string())]
+    pub jwt_user_claim: String,
+
+    /// Claim containing the roles of a bearer token, either an array of
+    /// strings or a space separated string.
+    #[arg(long, value_parser, requires = "jwt_jwks_url")]
+    pub jwt_roles_claim: Option<String>,
+
+    /// Role a bearer token must have to connect. May be provided multiple
+    /// times, tokens need at least one of the roles.
+    #[arg(long = "jwt-required-role", value_parser, requires = "jwt_roles_claim")]
+    pub jwt_required_roles: Vec<String>,
+
+    /// Role granting admin privileges to the sessions of a bearer token. May
+    /// be provided multiple times.
+    #[arg(long = "jwt-admin-role", value_parser, requires = "jwt_roles_claim")]
+    pub jwt_admin_roles: Vec<String>,
+
     /// Ignore authentication messages.
     ///
     /// (Internal)
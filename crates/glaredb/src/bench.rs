@@ -0,0 +1,556 @@
+//! Built-in benchmark suites, run with `glaredb bench`.
+//!
+//! Data for a suite is read from a directory containing a file per table
+//! (`lineitem.parquet`, ...). Missing files are generated with the suite's
+//! data generator, then each table is created as an external table in an
+//! in-memory database before the queries are run.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use datafusion::arrow::csv::{
+    ReaderBuilder as CsvReaderBuilder, WriterBuilder as CsvWriterBuilder,
+};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::parquet::arrow::ArrowWriter;
+use datafusion_ext::vars::SessionVars;
+use futures::StreamExt;
+use pgrepr::format::Format;
+use serde::Serialize;
+use sqlexec::engine::{Engine, SessionStorageConfig, TrackedSession};
+use sqlexec::session::ExecutionResult;
+
+use crate::args::{BenchArgs, BenchDataFormat, BenchReportFormat, BenchSuite};
+
+/// Number of rows read from generated data at a time.
+const CONVERT_BATCH_SIZE: usize = 64 * 1024;
+
+/// TPC-H queries, numbered from 1.
+const TPCH_QUERIES: [&str; 22] = [
+    include_str!("../../../testdata/tpch/1.sql"),
+    include_str!("../../../testdata/tpch/2.sql"),
+    include_str!("../../../testdata/tpch/3.sql"),
+    include_str!("../../../testdata/tpch/4.sql"),
+    include_str!("../../../testdata/tpch/5.sql"),
+    include_str!("../../../testdata/tpch/6.sql"),
+    include_str!("../../../testdata/tpch/7.sql"),
+    include_str!("../../../testdata/tpch/8.sql"),
+    include_str!("../../../testdata/tpch/9.sql"),
+    include_str!("../../../testdata/tpch/10.sql"),
+    include_str!("../../../testdata/tpch/11.sql"),
+    include_str!("../../../testdata/tpch/12.sql"),
+    include_str!("../../../testdata/tpch/13.sql"),
+    include_str!("../../../testdata/tpch/14.sql"),
+    include_str!("../../../testdata/tpch/15.sql"),
+    include_str!("../../../testdata/tpch/16.sql"),
+    include_str!("../../../testdata/tpch/17.sql"),
+    include_str!("../../../testdata/tpch/18.sql"),
+    include_str!("../../../testdata/tpch/19.sql"),
+    include_str!("../../../testdata/tpch/20.sql"),
+    include_str!("../../../testdata/tpch/21.sql"),
+    include_str!("../../../testdata/tpch/22.sql"),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum ColumnType {
+    Int,
+    Money,
+    Text,
+    Date,
+}
+
+impl From<ColumnType> for DataType {
+    fn from(ty: ColumnType) -> Self {
+        match ty {
+            ColumnType::Int => DataType::Int64,
+            ColumnType::Money => DataType::Decimal128(15, 2),
+            ColumnType::Text => DataType::Utf8,
+            ColumnType::Date => DataType::Date32,
+        }
+    }
+}
+
+/// TPC-H tables and their columns, in the order `dbgen` writes them.
+const TPCH_TABLES: &[(&str, &[(&str, ColumnType)])] = {
+    use ColumnType::*;
+    &[
+        (
+            "nation",
+            &[
+                ("n_nationkey", Int),
+                ("n_name", Text),
+                ("n_regionkey", Int),
+                ("n_comment", Text),
+            ],
+        ),
+        (
+            "region",
+            &[("r_regionkey", Int), ("r_name", Text), ("r_comment", Text)],
+        ),
+        (
+            "part",
+            &[
+                ("p_partkey", Int),
+                ("p_name", Text),
+                ("p_mfgr", Text),
+                ("p_brand", Text),
+                ("p_type", Text),
+                ("p_size", Int),
+                ("p_container", Text),
+                ("p_retailprice", Money),
+                ("p_comment", Text),
+            ],
+        ),
+        (
+            "supplier",
+            &[
+                ("s_suppkey", Int),
+                ("s_name", Text),
+                ("s_address", Text),
+                ("s_nationkey", Int),
+                ("s_phone", Text),
+                ("s_acctbal", Money),
+                ("s_comment", Text),
+            ],
+        ),
+        (
+            "partsupp",
+            &[
+                ("ps_partkey", Int),
+                ("ps_suppkey", Int),
+                ("ps_availqty", Int),
+                ("ps_supplycost", Money),
+                ("ps_comment", Text),
+            ],
+        ),
+        (
+            "customer",
+            &[
+                ("c_custkey", Int),
+                ("c_name", Text),
+                ("c_address", Text),
+                ("c_nationkey", Int),
+                ("c_phone", Text),
+                ("c_acctbal", Money),
+                ("c_mktsegment", Text),
+                ("c_comment", Text),
+            ],
+        ),
+        (
+            "orders",
+            &[
+                ("o_orderkey", Int),
+                ("o_custkey", Int),
+                ("o_orderstatus", Text),
+                ("o_totalprice", Money),
+                ("o_orderdate", Date),
+                ("o_orderpriority", Text),
+                ("o_clerk", Text),
+                ("o_shippriority", Int),
+                ("o_comment", Text),
+            ],
+        ),
+        (
+            "lineitem",
+            &[
+                ("l_orderkey", Int),
+                ("l_partkey", Int),
+                ("l_suppkey", Int),
+                ("l_linenumber", Int),
+                ("l_quantity", Money),
+                ("l_extendedprice", Money),
+                ("l_discount", Money),
+                ("l_tax", Money),
+                ("l_returnflag", Text),
+                ("l_linestatus", Text),
+                ("l_shipdate", Date),
+                ("l_commitdate", Date),
+                ("l_receiptdate", Date),
+                ("l_shipinstruct", Text),
+                ("l_shipmode", Text),
+                ("l_comment", Text),
+            ],
+        ),
+    ]
+};
+
+/// Timings for a single benchmark query.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryTiming {
+    pub query: usize,
+    /// Rows returned by the last statement of the query.
+    pub rows: usize,
+    /// Duration of each run in seconds, excluding warmups.
+    pub runs_secs: Vec<f64>,
+}
+
+impl QueryTiming {
+    fn min(&self) -> f64 {
+        self.runs_secs.iter().copied().fold(f64::INFINITY, f64::min)
+    }
+
+    fn max(&self) -> f64 {
+        self.runs_secs.iter().copied().fold(0.0, f64::max)
+    }
+
+    fn median(&self) -> f64 {
+        let mut runs = self.runs_secs.clone();
+        runs.sort_by(f64::total_cmp);
+        match runs.len() {
+            0 => 0.0,
+            n if n % 2 == 1 => runs[n / 2],
+            n => (runs[n / 2 - 1] + runs[n / 2]) / 2.0,
+        }
+    }
+}
+
+/// Report of a benchmark run.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub suite: &'static str,
+    pub scale: f64,
+    pub format: &'static str,
+    pub warmups: usize,
+    pub queries: Vec<QueryTiming>,
+}
+
+impl BenchReport {
+    /// Write the report in the given format.
+    pub fn write(&self, format: BenchReportFormat, mut out: impl Write) -> Result<()> {
+        match format {
+            BenchReportFormat::Json => {
+                serde_json::to_writer_pretty(&mut out, self)?;
+                writeln!(out)?;
+            }
+            BenchReportFormat::Markdown => {
+                writeln!(out, "| query | rows | min (s) | median (s) | max (s) |")?;
+                writeln!(out, "| ----: | ---: | ------: | ---------: | ------: |")?;
+                for timing in &self.queries {
+                    writeln!(
+                        out,
+                        "| {} | {} | {:.3} | {:.3} | {:.3} |",
+                        timing.query,
+                        timing.rows,
+                        timing.min(),
+                        timing.median(),
+                        timing.max()
+                    )?;
+                }
+                let total: f64 = self.queries.iter().map(QueryTiming::median).sum();
+                writeln!(out, "\nTotal (median): {total:.3}s")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BenchArgs {
+    /// Directory data files are read from and generated in.
+    fn data_dir(&self) -> PathBuf {
+        self.data_dir.clone().unwrap_or_else(|| {
+            PathBuf::from("bench-data")
+                .join(self.suite.name())
+                .join(format!("sf{}", self.scale))
+        })
+    }
+
+    /// Queries to run by number, validated against the suite.
+    fn query_numbers(&self) -> Result<Vec<usize>> {
+        if self.queries.is_empty() {
+            return Ok((1..=TPCH_QUERIES.len()).collect());
+        }
+        for &num in &self.queries {
+            if num == 0 || num > TPCH_QUERIES.len() {
+                return Err(anyhow!(
+                    "Invalid query {num}, expected 1 to {}",
+                    TPCH_QUERIES.len()
+                ));
+            }
+        }
+        Ok(self.queries.clone())
+    }
+
+    /// Generate any data files missing from the data dir.
+    pub fn prepare_data(&self) -> Result<PathBuf> {
+        let data_dir = self.data_dir();
+        let missing: Vec<_> = TPCH_TABLES
+            .iter()
+            .filter(|(name, _)| !table_path(&data_dir, name, self.format).exists())
+            .collect();
+        if missing.is_empty() {
+            return Ok(data_dir);
+        }
+
+        eprintln!(
+            "Generating TPC-H data at scale {} in {}",
+            self.scale,
+            data_dir.display()
+        );
+        ioutil::ensure_dir(&data_dir)?;
+        run_dbgen(&self.dbgen, self.scale, &data_dir)?;
+
+        for (name, columns) in missing {
+            let tbl_path = data_dir.join(format!("{name}.tbl"));
+            convert_tbl(
+                &tbl_path,
+                &table_path(&data_dir, name, self.format),
+                tbl_schema(columns),
+                self.format,
+            )
+            .with_context(|| format!("Failed to convert {}", tbl_path.display()))?;
+        }
+        // Remove every table's generated file, including ones that were
+        // overwritten for tables that already existed.
+        for (name, _) in TPCH_TABLES {
+            let _ = std::fs::remove_file(data_dir.join(format!("{name}.tbl")));
+        }
+
+        Ok(data_dir)
+    }
+
+    /// Load the data and run the queries, returning the timings.
+    pub async fn run_queries(&self, data_dir: &Path) -> Result<BenchReport> {
+        let queries = self.query_numbers()?;
+
+        let engine = Engine::from_data_dir(None).await?;
+        let mut sess = engine
+            .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+            .await?;
+
+        for (name, _) in TPCH_TABLES {
+            let path = table_path(data_dir, name, self.format);
+            let path = path.canonicalize().unwrap_or(path);
+            let location = path.to_string_lossy().replace('\'', "''");
+            let sql = format!(
+                "CREATE EXTERNAL TABLE {name} FROM local (location '{location}', file_type {})",
+                self.format.extension()
+            );
+            execute(&mut sess, &sql).await?;
+        }
+
+        let mut timings = Vec::with_capacity(queries.len());
+        for num in queries {
+            let sql = TPCH_QUERIES[num - 1];
+            for _ in 0..self.warmups {
+                execute(&mut sess, sql)
+                    .await
+                    .with_context(|| format!("Query {num} failed"))?;
+            }
+
+            let mut timing = QueryTiming {
+                query: num,
+                rows: 0,
+                runs_secs: Vec::with_capacity(self.runs),
+            };
+            for _ in 0..self.runs {
+                let start = Instant::now();
+                timing.rows = execute(&mut sess, sql)
+                    .await
+                    .with_context(|| format!("Query {num} failed"))?;
+                timing.runs_secs.push(start.elapsed().as_secs_f64());
+            }
+            eprintln!(
+                "Query {num}: {}",
+                format_secs(Duration::from_secs_f64(timing.median()))
+            );
+            timings.push(timing);
+        }
+
+        engine.shutdown().await;
+
+        Ok(BenchReport {
+            suite: self.suite.name(),
+            scale: self.scale,
+            format: self.format.extension(),
+            warmups: self.warmups,
+            queries: timings,
+        })
+    }
+}
+
+impl BenchSuite {
+    fn name(&self) -> &'static str {
+        match self {
+            BenchSuite::Tpch => "tpch",
+        }
+    }
+}
+
+impl BenchDataFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            BenchDataFormat::Parquet => "parquet",
+            BenchDataFormat::Csv => "csv",
+        }
+    }
+}
+
+fn table_path(data_dir: &Path, table: &str, format: BenchDataFormat) -> PathBuf {
+    data_dir.join(format!("{table}.{}", format.extension()))
+}
+
+fn format_secs(dur: Duration) -> String {
+    format!("{:.3}s", dur.as_secs_f64())
+}
+
+/// Run `dbgen`, writing `.tbl` files for every table to `out_dir`.
+///
+/// `dbgen` reads `dists.dss` from `DSS_CONFIG`, which defaults to the
+/// directory containing the binary.
+fn run_dbgen(dbgen: &Path, scale: f64, out_dir: &Path) -> Result<()> {
+    let mut cmd = Command::new(dbgen);
+    cmd.args(["-f", "-s", &scale.to_string()])
+        .env("DSS_PATH", out_dir)
+        .current_dir(out_dir);
+    if std::env::var_os("DSS_CONFIG").is_none() {
+        if let Some(dir) = dbgen.parent().filter(|dir| dir.join("dists.dss").exists()) {
+            cmd.env("DSS_CONFIG", dir.canonicalize()?);
+        }
+    }
+
+    let status = cmd.status().with_context(|| {
+        format!(
+            "Failed to run {}, build it from benchmarks/tpch/tpch-dbgen and pass it with --dbgen",
+            dbgen.display()
+        )
+    })?;
+    if !status.success() {
+        return Err(anyhow!("{} exited with {status}", dbgen.display()));
+    }
+    Ok(())
+}
+
+/// Schema for reading a `.tbl` file, which has a trailing delimiter on each
+/// line that's read into an extra column.
+fn tbl_schema(columns: &[(&str, ColumnType)]) -> SchemaRef {
+    let fields = columns
+        .iter()
+        .map(|(name, ty)| Field::new(*name, (*ty).into(), false))
+        .chain(std::iter::once(Field::new(
+            "_trailing",
+            DataType::Utf8,
+            true,
+        )))
+        .collect::<Vec<_>>();
+    Arc::new(Schema::new(fields))
+}
+
+/// Convert a `|` delimited `.tbl` file from `dbgen` to `format`.
+fn convert_tbl(src: &Path, dest: &Path, schema: SchemaRef, format: BenchDataFormat) -> Result<()> {
+    let projection: Vec<_> = (0..schema.fields().len() - 1).collect();
+    let reader = CsvReaderBuilder::new(schema.clone())
+        .has_header(false)
+        .with_delimiter(b'|')
+        .with_batch_size(CONVERT_BATCH_SIZE)
+        .with_projection(projection.clone())
+        .build(BufReader::new(File::open(src)?))?;
+    let out_schema = Arc::new(schema.project(&projection)?);
+
+    // Write to a temporary file first so an interrupted conversion isn't
+    // mistaken for complete data.
+    let tmp = dest.with_extension("tmp");
+    let out = BufWriter::new(File::create(&tmp)?);
+    match format {
+        BenchDataFormat::Parquet => {
+            let mut writer = ArrowWriter::try_new(out, out_schema, None)?;
+            for batch in reader {
+                writer.write(&batch?)?;
+            }
+            writer.close()?;
+        }
+        BenchDataFormat::Csv => {
+            let mut writer = CsvWriterBuilder::new().has_headers(true).build(out);
+            for batch in reader {
+                writer.write(&batch?)?;
+            }
+        }
+    }
+    std::fs::rename(&tmp, dest)?;
+
+    Ok(())
+}
+
+/// Execute every statement in `sql`, returning the number of rows returned
+/// by the last one.
+async fn execute(sess: &mut TrackedSession, sql: &str) -> Result<usize> {
+    const UNNAMED: String = String::new();
+
+    let mut rows = 0;
+    for stmt in sess.parse_query(sql)? {
+        sess.prepare_statement(UNNAMED, stmt, Vec::new()).await?;
+        let prepared = sess.get_prepared_statement(&UNNAMED)?;
+        let num_fields = prepared.output_fields().map(|f| f.len()).unwrap_or(0);
+        sess.bind_statement(
+            UNNAMED,
+            &UNNAMED,
+            Vec::new(),
+            vec![Format::Text; num_fields],
+        )?;
+
+        rows = match sess.execute_portal(&UNNAMED, 0).await? {
+            ExecutionResult::Query { mut stream, .. } => {
+                let mut rows = 0;
+                while let Some(batch) = stream.next().await {
+                    rows += batch?.num_rows();
+                }
+                rows
+            }
+            ExecutionResult::Error(e) => return Err(e.into()),
+            _ => 0,
+        };
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+
+    #[test]
+    fn convert_tbl_to_parquet() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("orders.tbl");
+        std::fs::write(
+            &src,
+            "1|36901|O|173665.47|1996-01-02|5-LOW|Clerk#000000951|0|nstructions sleep furiously among |\n\
+             2|78002|O|46929.18|1996-12-01|1-URGENT|Clerk#000000880|0| foxes. pending accounts at the pending|\n",
+        )
+        .unwrap();
+        let dest = dir.path().join("orders.parquet");
+        let (_, columns) = TPCH_TABLES
+            .iter()
+            .find(|(name, _)| *name == "orders")
+            .unwrap();
+
+        convert_tbl(&src, &dest, tbl_schema(columns), BenchDataFormat::Parquet).unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&dest).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(2, batches[0].num_rows());
+        assert_eq!(9, batches[0].num_columns());
+        assert_eq!(&DataType::Date32, batches[0].schema().field(4).data_type());
+        assert!(!dest.with_extension("tmp").exists());
+    }
+
+    #[test]
+    fn timing_stats() {
+        let timing = QueryTiming {
+            query: 1,
+            rows: 4,
+            runs_secs: vec![3.0, 1.0, 2.0, 10.0],
+        };
+        assert_eq!(1.0, timing.min());
+        assert_eq!(2.5, timing.median());
+        assert_eq!(10.0, timing.max());
+    }
+}
@@ -1,7 +1,8 @@
 use anyhow::Result;
-use clap::{Parser, ValueEnum};
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
 use glaredb::args::LocalArgs;
 use glaredb::commands::Commands;
+use glaredb::local::ErrorsReported;
 
 #[derive(Debug, Clone, Copy, ValueEnum, Default)]
 enum LoggingMode {
@@ -34,6 +35,14 @@ struct Cli {
     #[clap(long, value_enum)]
     log_mode: Option<LoggingMode>,
 
+    /// Export traces to an OpenTelemetry collector listening on this endpoint
+    /// (e.g. 'http://localhost:4317').
+    ///
+    /// Traces are exported using OTLP over gRPC. Only has an effect if logging
+    /// is enabled.
+    #[clap(long, value_parser)]
+    otlp_endpoint: Option<String>,
+
     #[clap(subcommand)]
     command: Option<Commands>,
 
@@ -43,15 +52,21 @@ struct Cli {
 }
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
     // If some one runs "glaredb", we want them to default to running the local
     // version. This pulls out the args and places them in a local command to
     // keep all the below logic.
-    let command = match cli.command {
+    let mut command = match cli.command {
         Some(command) => command,
         None => Commands::Local(cli.local_args),
     };
 
+    if let Commands::Local(local) = &mut command {
+        let matches = matches.subcommand_matches("local").unwrap_or(&matches);
+        local.apply_profile(matches)?;
+    }
+
     match (&command, cli.log_mode, cli.verbose) {
         (
             // User specified a log file, so we should use it.
@@ -66,12 +81,26 @@ fn main() -> Result<()> {
             // Use JSON logging by default when writing to a file.
             cli.log_mode.unwrap_or(LoggingMode::Json).into(),
             Some(log_file),
+            cli.otlp_endpoint.as_deref(),
         ),
         // Disable logging when running locally since it'll clobber the repl
         // _unless_ the user specified a logging related option.
         (Commands::Local { .. }, None, 0) => (),
-        _ => logutil::init(cli.verbose, cli.log_mode.unwrap_or_default().into(), None),
+        _ => logutil::init(
+            cli.verbose,
+            cli.log_mode.unwrap_or_default().into(),
+            None,
+            cli.otlp_endpoint.as_deref(),
+        ),
     }
 
-    command.run()
+    let result = command.run();
+    logutil::otel::shutdown();
+
+    // Errors were already written to stderr in the requested format, only
+    // exit with a failure.
+    if matches!(&result, Err(e) if e.is::<ErrorsReported>()) {
+        std::process::exit(1);
+    }
+    result
 }
@@ -1,6 +1,11 @@
 use crate::args::server::ServerArgs;
-use crate::args::{LocalArgs, MetastoreArgs, PgProxyArgs, RpcProxyArgs};
-use crate::local::LocalSession;
+use crate::args::{
+    BenchArgs, DumpCatalogArgs, ErrorFormat, FmtArgs, LoadCatalogArgs, LocalArgs, MetastoreArgs,
+    PgProxyArgs, RpcProxyArgs,
+};
+use crate::config::{ReloadableSettings, ServerConfig};
+use crate::formatter::{find_sql_files, format_sql};
+use crate::local::{report_error, ErrorsReported, LocalSession};
 use crate::metastore::Metastore;
 use crate::proxy::{PgProxy, RpcProxy};
 use crate::server::ComputeServer;
@@ -9,13 +14,21 @@ use atty::Stream;
 use clap::Subcommand;
 use ioutil::ensure_dir;
 use object_store_util::conf::StorageConfig;
-use pgsrv::auth::{LocalAuthenticator, PasswordlessAuthenticator, SingleUserAuthenticator};
+use pgsrv::ssl::SslConfig;
+use rpcsrv::jwt::{JwtAuthenticator, JwtConfig};
+use sqlexec::audit::{AuditLog, AuditQueryText, AuditSink};
+use sqlexec::change_feed::ChangeFeed;
+use sqlexec::plan_cache::PlanCache;
+use sqlexec::query_history::QueryHistory;
 use std::collections::HashMap;
+use std::io::Read;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::runtime::{Builder, Runtime};
 use tracing::info;
+use url::Url;
 
 #[derive(Subcommand)]
 pub enum Commands {
@@ -23,6 +36,14 @@ pub enum Commands {
     Local(LocalArgs),
     /// Starts the sql server portion of GlareDB.
     Server(ServerArgs),
+    /// Formats SQL files.
+    Fmt(FmtArgs),
+    /// Exports the catalog to a file.
+    DumpCatalog(DumpCatalogArgs),
+    /// Imports a catalog exported with `dump-catalog`.
+    LoadCatalog(LoadCatalogArgs),
+    /// Runs a benchmark suite, generating its data if needed.
+    Bench(BenchArgs),
     /// Starts an instance of the pgsrv proxy.
     #[clap(hide = true)]
     PgProxy(PgProxyArgs),
@@ -39,6 +60,10 @@ impl Commands {
         match self {
             Commands::Local(local) => local.run(),
             Commands::Server(server) => server.run(),
+            Commands::Fmt(fmt) => fmt.run(),
+            Commands::DumpCatalog(dump) => dump.run(),
+            Commands::LoadCatalog(load) => load.run(),
+            Commands::Bench(bench) => bench.run(),
             Commands::PgProxy(pg_proxy) => pg_proxy.run(),
             Commands::RpcProxy(rpc_proxy) => rpc_proxy.run(),
             Commands::Metastore(metastore) => metastore.run(),
@@ -67,8 +92,9 @@ impl RunCommand for LocalArgs {
         #[cfg(not(release))]
         self.start_tokio_debugger();
 
+        let error_format = self.opts.error_format;
         let runtime = build_runtime("local")?;
-        runtime.block_on(async move {
+        let result = runtime.block_on(async move {
             let query = match (self.file, self.query) {
                 (Some(_), Some(_)) => {
                     return Err(anyhow!(
@@ -122,7 +148,17 @@ impl RunCommand for LocalArgs {
 
             let local = LocalSession::connect(self.opts).await?;
             local.run(query).await
-        })
+        });
+
+        // Errors from statements have already been reported, report any
+        // others in the same format.
+        match result {
+            Err(e) if error_format == ErrorFormat::Json && !e.is::<ErrorsReported>() => {
+                report_error(error_format, &e);
+                Err(ErrorsReported { count: 1 }.into())
+            }
+            result => result,
+        }
     }
 }
 
@@ -131,13 +167,44 @@ impl RunCommand for ServerArgs {
         let Self {
             bind,
             rpc_bind,
+            metrics_bind,
             metastore_addr,
+            metastore_postgres_url,
             user,
             password,
+            scram_auth,
             data_dir,
             service_account_path,
             storage_config,
             spill_path,
+            max_concurrent_queries,
+            max_queued_queries,
+            query_queue_timeout_secs,
+            max_connections,
+            max_connections_per_user,
+            idle_session_timeout_secs,
+            audit_log,
+            audit_log_fingerprint,
+            query_history_retention_secs,
+            catalog_events,
+            catalog_events_webhook,
+            shutdown_grace_period_secs,
+            egress_allow,
+            egress_deny,
+            rpc_workers,
+            read_only,
+            shared_plan_cache_size,
+            config,
+            tls_cert,
+            tls_key,
+            tls_client_ca,
+            jwt_jwks_url,
+            jwt_issuer,
+            jwt_audience,
+            jwt_user_claim,
+            jwt_roles_claim,
+            jwt_required_roles,
+            jwt_admin_roles,
             ignore_pg_auth,
             disable_rpc_auth,
             segment_key,
@@ -159,16 +226,92 @@ impl RunCommand for ServerArgs {
             ));
         }
 
-        let auth: Box<dyn LocalAuthenticator> = match password {
-            Some(password) => Box::new(SingleUserAuthenticator { user, password }),
-            None => Box::new(PasswordlessAuthenticator {
-                drop_auth_messages: ignore_pg_auth,
+        let ssl_conf = match (tls_cert, tls_key) {
+            (Some(cert), Some(key)) => Some(SslConfig::from_files(
+                &cert,
+                &key,
+                tls_client_ca.as_deref(),
+            )?),
+            (None, None) => None,
+            _ => {
+                return Err(anyhow!(
+                    "both or neither of the TLS cert and key must be provided"
+                ))
+            }
+        };
+
+        let server_config = ServerConfig::new(
+            user,
+            ignore_pg_auth,
+            ReloadableSettings {
+                log_filter: None,
+                password,
+                scram_auth: Some(scram_auth),
+                max_concurrent_queries,
+                max_queued_queries: Some(max_queued_queries),
+                query_queue_timeout_secs: Some(query_queue_timeout_secs),
+                max_connections,
+                max_connections_per_user,
+                idle_session_timeout_secs,
+                egress_allow: Some(egress_allow),
+                egress_deny: Some(egress_deny),
+            },
+            config,
+            ssl_conf,
+        )?;
+
+        let jwt_config = match (jwt_jwks_url, jwt_issuer) {
+            (Some(jwks_url), Some(issuer)) => Some(JwtConfig {
+                jwks_url,
+                issuer,
+                audience: jwt_audience,
+                user_claim: jwt_user_claim,
+                roles_claim: jwt_roles_claim,
+                required_roles: jwt_required_roles,
+                admin_roles: jwt_admin_roles,
             }),
+            (None, None) => None,
+            _ => {
+                return Err(anyhow!(
+                    "both or neither of the JWKS URL and JWT issuer must be provided"
+                ))
+            }
+        };
+
+        let audit_sink = audit_log
+            .map(|target| {
+                AuditSink::try_from_target(
+                    &target,
+                    HashMap::from_iter(storage_config.storage_options.clone()),
+                )
+            })
+            .transpose()?;
+        let audit_query_text = if audit_log_fingerprint {
+            AuditQueryText::Fingerprint
+        } else {
+            AuditQueryText::Full
         };
 
+        let catalog_events_webhook = catalog_events_webhook
+            .map(|url| {
+                Url::parse(&url).map_err(|e| anyhow!("invalid catalog events webhook URL: {e}"))
+            })
+            .transpose()?;
+        let catalog_events = catalog_events || catalog_events_webhook.is_some();
+
         let runtime = build_runtime("server")?;
 
         runtime.block_on(async move {
+            // Spawns the writers, so needs to be created within the runtime.
+            let audit_log = audit_sink.map(|sink| AuditLog::new(sink, audit_query_text));
+            let query_history = (query_history_retention_secs > 0)
+                .then(|| QueryHistory::new(Duration::from_secs(query_history_retention_secs)));
+            let change_feed = catalog_events.then(|| ChangeFeed::new(catalog_events_webhook));
+            let jwt_authenticator = match jwt_config {
+                Some(config) => Some(JwtAuthenticator::new(config).await?),
+                None => None,
+            };
+
             let pg_listener = match bind {
                 Some(bind) => Some(TcpListener::bind(bind).await?),
                 None if disable_postgres_api => None,
@@ -179,18 +322,36 @@ impl RunCommand for ServerArgs {
                 None if enable_flight_api => Some(TcpListener::bind(DEFAULT_RPC_BIND_ADDR).await?),
                 None => None,
             };
+            let metrics_listener = match metrics_bind {
+                Some(bind) => Some(TcpListener::bind(bind).await?),
+                None => None,
+            };
 
             let server = ComputeServer::builder()
-                .with_authenticator(auth)
+                .with_authenticator(server_config.authenticator())
+                .with_ssl_config_opt(server_config.ssl_config())
                 .with_pg_listener_opt(pg_listener)
                 .with_rpc_listener_opt(rpc_listener)
+                .with_metrics_listener_opt(metrics_listener)
                 .with_metastore_addr_opt(metastore_addr)
+                .with_metastore_postgres_url_opt(metastore_postgres_url)
                 .with_segment_key_opt(segment_key)
                 .with_data_dir_opt(data_dir)
                 .with_service_account_path_opt(service_account_path)
                 .with_location_opt(storage_config.location)
                 .with_storage_options(HashMap::from_iter(storage_config.storage_options.clone()))
                 .with_spill_path_opt(spill_path)
+                .with_query_limiter(server_config.query_limiter())
+                .with_connection_limiter(server_config.connection_limiter())
+                .with_config_reloader_opt(server_config.reloader())
+                .with_audit_log_opt(audit_log)
+                .with_query_history_opt(query_history)
+                .with_change_feed_opt(change_feed)
+                .with_jwt_authenticator_opt(jwt_authenticator)
+                .with_shutdown_grace_period(Duration::from_secs(shutdown_grace_period_secs))
+                .with_rpc_workers(rpc_workers)
+                .with_read_only(read_only)
+                .with_shared_plan_cache_opt(shared_plan_cache_size.map(PlanCache::new))
                 .disable_rpc_auth(disable_rpc_auth)
                 .enable_simple_query_rpc(enable_simple_query_rpc)
                 .enable_flight_api(enable_flight_api)
@@ -202,6 +363,105 @@ impl RunCommand for ServerArgs {
     }
 }
 
+impl RunCommand for BenchArgs {
+    fn run(self) -> Result<()> {
+        if self.runs == 0 {
+            return Err(anyhow!("--runs must be at least 1"));
+        }
+        let data_dir = self.prepare_data()?;
+
+        let runtime = build_runtime("bench")?;
+        let report = runtime.block_on(self.run_queries(&data_dir))?;
+        report.write(self.report, std::io::stdout().lock())
+    }
+}
+
+impl RunCommand for FmtArgs {
+    fn run(self) -> Result<()> {
+        let Self { paths, check } = self;
+
+        if paths.is_empty() {
+            let mut sql = String::new();
+            std::io::stdin().read_to_string(&mut sql)?;
+            let formatted = format_sql(&sql)?;
+            if check {
+                if formatted != sql {
+                    return Err(anyhow!("input is not formatted"));
+                }
+            } else {
+                print!("{formatted}");
+            }
+            return Ok(());
+        }
+
+        let mut files = Vec::new();
+        for path in &paths {
+            find_sql_files(path, &mut files)?;
+        }
+
+        let mut unformatted = 0;
+        for file in files {
+            let sql = std::fs::read_to_string(&file)?;
+            let formatted = format_sql(&sql).map_err(|e| anyhow!("{}: {e}", file.display()))?;
+            if formatted == sql {
+                continue;
+            }
+
+            if check {
+                println!("{}", file.display());
+                unformatted += 1;
+            } else {
+                std::fs::write(&file, formatted)?;
+            }
+        }
+
+        if unformatted > 0 {
+            return Err(anyhow!("{unformatted} file(s) need formatting"));
+        }
+
+        Ok(())
+    }
+}
+
+impl RunCommand for DumpCatalogArgs {
+    fn run(self) -> Result<()> {
+        let mut query = format!("EXPORT CATALOG TO {}", quote_path(&self.output));
+        match (self.include_credentials, self.encrypt) {
+            (true, _) => query.push_str(
+                " OPTIONS (include_credentials = true, encryption_key = SECRET dump_key)",
+            ),
+            (false, true) => query.push_str(" OPTIONS (encryption_key = SECRET dump_key)"),
+            (false, false) => (),
+        }
+
+        let runtime = build_runtime("local")?;
+        runtime.block_on(async move {
+            let local = LocalSession::connect(self.opts).await?;
+            local.run(Some(query)).await
+        })
+    }
+}
+
+impl RunCommand for LoadCatalogArgs {
+    fn run(self) -> Result<()> {
+        let mut query = format!("IMPORT CATALOG FROM {}", quote_path(&self.input));
+        if self.encrypted {
+            query.push_str(" OPTIONS (encryption_key = SECRET dump_key)");
+        }
+
+        let runtime = build_runtime("local")?;
+        runtime.block_on(async move {
+            let local = LocalSession::connect(self.opts).await?;
+            local.run(Some(query)).await
+        })
+    }
+}
+
+/// Quote a path for use as a string literal in a query.
+fn quote_path(path: &std::path::Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', "''"))
+}
+
 impl RunCommand for PgProxyArgs {
     fn run(self) -> Result<()> {
         let runtime = build_runtime("pgsrv")?;
@@ -247,7 +507,20 @@ impl RunCommand for MetastoreArgs {
             bucket,
             service_account_path,
             local_file_path,
+            postgres_url,
         } = self;
+        let addr: SocketAddr = bind.parse()?;
+
+        if let Some(postgres_url) = postgres_url {
+            let runtime = build_runtime("metastore")?;
+            info!("starting Metastore with postgres storage");
+
+            return runtime.block_on(async move {
+                let metastore = Metastore::new_postgres(&postgres_url).await?;
+                metastore.serve(addr).await
+            });
+        }
+
         let conf = match (bucket, service_account_path, local_file_path) {
             (Some(bucket), Some(service_account_path), None) => {
                 let service_account_key = std::fs::read_to_string(service_account_path)?;
@@ -267,7 +540,6 @@ impl RunCommand for MetastoreArgs {
                 ))
             }
         };
-        let addr: SocketAddr = bind.parse()?;
         let runtime = build_runtime("metastore")?;
 
         info!(?conf, "starting Metastore with object store config");
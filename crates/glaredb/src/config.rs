@@ -0,0 +1,380 @@
+//! Server configuration that can be reloaded without restarting.
+//!
+//! Settings are read from a JSON file when the server starts, and read again
+//! when the server receives SIGHUP or a session calls `reload_config()`.
+//! Settings missing from the file fall back to the values provided on the
+//! command line. TLS certificates are read again at the same time. Sessions
+//! that are already connected keep running, changes apply to new connections
+//! and queries.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use datafusion_ext::errors::{ExtensionError, Result as ExtensionResult};
+use datafusion_ext::reload::{ConfigReloader, ReloadConfig};
+use datasources::common::egress::EgressPolicy;
+use pgsrv::auth::{
+    PasswordlessAuthenticator, ReloadableAuthenticator, ScramAuthenticator, SingleUserAuthenticator,
+};
+use pgsrv::ssl::SslConfig;
+use serde::Deserialize;
+use sqlexec::admission::{QueryLimiter, QueryLimits};
+use sqlexec::connections::{ConnectionLimiter, ConnectionLimits};
+use sqlexec::scram::ScramVerifier;
+use tracing::info;
+
+/// Settings that can be changed while the server is running.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReloadableSettings {
+    /// Log filter directives, using the same syntax as `RUST_LOG`.
+    pub log_filter: Option<String>,
+    /// Password used for authentication. A password isn't required if unset.
+    pub password: Option<String>,
+    /// Require SCRAM-SHA-256 authentication using passwords stored in the
+    /// catalog, with `password` as a fallback for the configured user.
+    pub scram_auth: Option<bool>,
+    /// Max number of queries executing at once. Queries aren't limited if
+    /// unset.
+    pub max_concurrent_queries: Option<usize>,
+    /// Max number of queries waiting to execute.
+    pub max_queued_queries: Option<usize>,
+    /// Number of seconds a query may wait in the queue.
+    pub query_queue_timeout_secs: Option<u64>,
+    /// Max number of open connections across all users.
+    pub max_connections: Option<usize>,
+    /// Max number of open connections for a single user.
+    pub max_connections_per_user: Option<usize>,
+    /// Number of seconds a connection may be idle before it's closed.
+    pub idle_session_timeout_secs: Option<u64>,
+    /// Hosts data sources may connect to. Any host is allowed if empty.
+    pub egress_allow: Option<Vec<String>>,
+    /// Hosts data sources may not connect to.
+    pub egress_deny: Option<Vec<String>>,
+}
+
+impl ReloadableSettings {
+    /// Read settings from a JSON file.
+    pub fn from_file(path: &Path) -> Result<ReloadableSettings> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    /// Use settings from `overrides` where they're set, falling back to
+    /// settings from `self`.
+    fn merge(&self, overrides: ReloadableSettings) -> ReloadableSettings {
+        ReloadableSettings {
+            log_filter: overrides.log_filter.or_else(|| self.log_filter.clone()),
+            password: overrides.password.or_else(|| self.password.clone()),
+            scram_auth: overrides.scram_auth.or(self.scram_auth),
+            max_concurrent_queries: overrides
+                .max_concurrent_queries
+                .or(self.max_concurrent_queries),
+            max_queued_queries: overrides.max_queued_queries.or(self.max_queued_queries),
+            query_queue_timeout_secs: overrides
+                .query_queue_timeout_secs
+                .or(self.query_queue_timeout_secs),
+            max_connections: overrides.max_connections.or(self.max_connections),
+            max_connections_per_user: overrides
+                .max_connections_per_user
+                .or(self.max_connections_per_user),
+            idle_session_timeout_secs: overrides
+                .idle_session_timeout_secs
+                .or(self.idle_session_timeout_secs),
+            egress_allow: overrides.egress_allow.or_else(|| self.egress_allow.clone()),
+            egress_deny: overrides.egress_deny.or_else(|| self.egress_deny.clone()),
+        }
+    }
+
+    fn query_limits(&self) -> Option<QueryLimits> {
+        self.max_concurrent_queries
+            .map(|max_concurrent_queries| QueryLimits {
+                max_concurrent_queries,
+                max_queued_queries: self
+                    .max_queued_queries
+                    .unwrap_or(QueryLimits::DEFAULT_MAX_QUEUED_QUERIES),
+                queue_timeout: self
+                    .query_queue_timeout_secs
+                    .map(Duration::from_secs)
+                    .unwrap_or(QueryLimits::DEFAULT_QUEUE_TIMEOUT),
+            })
+    }
+
+    fn connection_limits(&self) -> ConnectionLimits {
+        ConnectionLimits {
+            max_connections: self.max_connections,
+            max_connections_per_user: self.max_connections_per_user,
+            idle_session_timeout: self.idle_session_timeout_secs.map(Duration::from_secs),
+        }
+    }
+}
+
+/// Applies reloadable settings to a running server.
+pub struct ServerConfig {
+    /// User used for authentication when a password is set.
+    user: String,
+    /// Drop authentication messages when a password isn't set.
+    ignore_pg_auth: bool,
+    /// Settings provided on the command line.
+    defaults: ReloadableSettings,
+    /// Path to the config file, settings are only reloadable if set.
+    path: Option<PathBuf>,
+    /// TLS config for pg connections.
+    ssl: Option<Arc<SslConfig>>,
+    query_limiter: QueryLimiter,
+    connection_limiter: ConnectionLimiter,
+    authenticator: ReloadableAuthenticator,
+    /// Prevents concurrent reloads from interleaving.
+    reload_lock: Mutex<()>,
+}
+
+impl ServerConfig {
+    /// Create the config for a server, reading settings from the config file
+    /// if one is provided.
+    pub fn new(
+        user: String,
+        ignore_pg_auth: bool,
+        defaults: ReloadableSettings,
+        path: Option<PathBuf>,
+        ssl: Option<SslConfig>,
+    ) -> Result<Arc<ServerConfig>> {
+        let settings = match &path {
+            Some(path) => defaults.merge(ReloadableSettings::from_file(path)?),
+            None => defaults.clone(),
+        };
+
+        let config = ServerConfig {
+            authenticator: ReloadableAuthenticator::new(PasswordlessAuthenticator::default()),
+            query_limiter: QueryLimiter::unlimited(),
+            connection_limiter: ConnectionLimiter::unlimited(),
+            user,
+            ignore_pg_auth,
+            defaults,
+            path,
+            ssl: ssl.map(Arc::new),
+            reload_lock: Mutex::new(()),
+        };
+        config.apply(&settings)?;
+
+        Ok(Arc::new(config))
+    }
+
+    /// Authenticator for pg connections, replaced on reload.
+    pub fn authenticator(&self) -> ReloadableAuthenticator {
+        self.authenticator.clone()
+    }
+
+    /// Limiter for queries across all sessions, updated on reload.
+    pub fn query_limiter(&self) -> QueryLimiter {
+        self.query_limiter.clone()
+    }
+
+    /// Limiter for connections across all frontends, updated on reload.
+    pub fn connection_limiter(&self) -> ConnectionLimiter {
+        self.connection_limiter.clone()
+    }
+
+    /// TLS config for pg connections, certificates are read again on reload.
+    pub fn ssl_config(&self) -> Option<Arc<SslConfig>> {
+        self.ssl.clone()
+    }
+
+    /// Get a reloader for sessions to use, `None` if there's neither a config
+    /// file nor TLS certificates to reload.
+    pub fn reloader(self: &Arc<Self>) -> Option<ConfigReloader> {
+        if self.path.is_none() && self.ssl.is_none() {
+            return None;
+        }
+        Some(ConfigReloader::new(self.clone()))
+    }
+
+    /// Read the config file and TLS certificates again and apply them.
+    ///
+    /// Nothing is changed if the config file can't be read or contains
+    /// invalid settings. Certificates are only replaced if they're valid.
+    pub fn reload(&self) -> Result<()> {
+        if self.path.is_none() && self.ssl.is_none() {
+            return Err(anyhow!("No config file or TLS certificates to reload"));
+        }
+
+        let _guard = self.reload_lock.lock().unwrap();
+        let settings = match &self.path {
+            Some(path) => Some(self.defaults.merge(ReloadableSettings::from_file(path)?)),
+            None => None,
+        };
+        if let Some(ssl) = &self.ssl {
+            ssl.reload()?;
+        }
+        if let Some(settings) = settings {
+            self.apply(&settings)?;
+        }
+
+        info!(path = ?self.path, "reloaded configuration");
+        Ok(())
+    }
+
+    fn apply(&self, settings: &ReloadableSettings) -> Result<()> {
+        // Settings that can fail to apply go first so that nothing is changed
+        // if they're invalid.
+        let egress_policy = EgressPolicy::try_new(
+            settings.egress_allow.as_deref().unwrap_or_default(),
+            settings.egress_deny.as_deref().unwrap_or_default(),
+        )?;
+
+        // Only touch logging when using a config file so that running
+        // without one keeps the filter logging was initialized with.
+        if self.path.is_some() {
+            logutil::set_log_filter(settings.log_filter.as_deref())?;
+        }
+
+        EgressPolicy::set_global(egress_policy);
+
+        self.query_limiter.set_limits(settings.query_limits());
+        self.connection_limiter
+            .set_limits(settings.connection_limits());
+
+        match (&settings.password, settings.scram_auth.unwrap_or(false)) {
+            (password, true) => {
+                let default_user = password
+                    .as_deref()
+                    .map(|password| {
+                        ScramVerifier::new(password).map(|verifier| (self.user.clone(), verifier))
+                    })
+                    .transpose()?;
+                self.authenticator
+                    .replace(ScramAuthenticator { default_user })
+            }
+            (Some(password), false) => self.authenticator.replace(SingleUserAuthenticator {
+                user: self.user.clone(),
+                password: password.clone(),
+            }),
+            (None, false) => self.authenticator.replace(PasswordlessAuthenticator {
+                drop_auth_messages: self.ignore_pg_auth,
+            }),
+        }
+
+        Ok(())
+    }
+}
+
+impl ReloadConfig for ServerConfig {
+    fn reload_config(&self) -> ExtensionResult<()> {
+        self.reload()
+            .map_err(|e| ExtensionError::String(format!("Failed to reload configuration: {e:#}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn merge_falls_back_to_defaults() {
+        let defaults = ReloadableSettings {
+            password: Some("default".to_string()),
+            max_concurrent_queries: Some(4),
+            max_queued_queries: Some(8),
+            ..Default::default()
+        };
+        let merged = defaults.merge(ReloadableSettings {
+            max_concurrent_queries: Some(2),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            ReloadableSettings {
+                password: Some("default".to_string()),
+                max_concurrent_queries: Some(2),
+                max_queued_queries: Some(8),
+                ..Default::default()
+            },
+            merged
+        );
+    }
+
+    #[test]
+    fn reload_updates_limits() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(br#"{"max_concurrent_queries": 1}"#).unwrap();
+
+        let defaults = ReloadableSettings {
+            max_queued_queries: Some(8),
+            ..Default::default()
+        };
+        // Logging isn't initialized in tests, so avoid setting the filter by
+        // not providing the path initially.
+        let config = ServerConfig::new("glaredb".to_string(), false, defaults, None, None).unwrap();
+        assert_eq!(None, config.query_limiter().limits());
+
+        let settings = config
+            .defaults
+            .merge(ReloadableSettings::from_file(file.path()).unwrap());
+        config.apply(&settings).unwrap();
+        assert_eq!(
+            Some(QueryLimits {
+                max_concurrent_queries: 1,
+                max_queued_queries: 8,
+                queue_timeout: QueryLimits::DEFAULT_QUEUE_TIMEOUT,
+            }),
+            config.query_limiter().limits()
+        );
+    }
+
+    #[test]
+    fn reload_updates_connection_limits() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(br#"{"max_connections_per_user": 2, "idle_session_timeout_secs": 60}"#)
+            .unwrap();
+
+        let defaults = ReloadableSettings {
+            max_connections: Some(10),
+            ..Default::default()
+        };
+        let config = ServerConfig::new("glaredb".to_string(), false, defaults, None, None).unwrap();
+        assert_eq!(
+            ConnectionLimits {
+                max_connections: Some(10),
+                ..Default::default()
+            },
+            config.connection_limiter().limits()
+        );
+
+        let settings = config
+            .defaults
+            .merge(ReloadableSettings::from_file(file.path()).unwrap());
+        config.apply(&settings).unwrap();
+        assert_eq!(
+            ConnectionLimits {
+                max_connections: Some(10),
+                max_connections_per_user: Some(2),
+                idle_session_timeout: Some(Duration::from_secs(60)),
+            },
+            config.connection_limiter().limits()
+        );
+    }
+
+    #[test]
+    fn invalid_egress_rule() {
+        let defaults = ReloadableSettings {
+            egress_allow: Some(vec!["http://db.example.com".to_string()]),
+            ..Default::default()
+        };
+        ServerConfig::new("glaredb".to_string(), false, defaults, None, None)
+            .err()
+            .unwrap();
+    }
+
+    #[test]
+    fn invalid_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(br#"{"max_concurrent": 1}"#).unwrap();
+
+        ReloadableSettings::from_file(file.path()).unwrap_err();
+    }
+}
@@ -0,0 +1,885 @@
+//! Formatting of SQL text.
+//!
+//! The formatter works on the token stream instead of the parsed AST. This
+//! means GlareDB specific syntax (e.g. `CREATE EXTERNAL TABLE ... OPTIONS`),
+//! comments, and literals are all kept exactly as written. Only whitespace and
+//! the casing of keywords are changed.
+use anyhow::{anyhow, Result};
+use sqlexec::export::sqlparser::dialect::GenericDialect;
+use sqlexec::export::sqlparser::keywords::Keyword;
+use sqlexec::export::sqlparser::tokenizer::{
+    Token, TokenWithLocation, Tokenizer, Whitespace, Word,
+};
+use std::path::{Path, PathBuf};
+
+const INDENT: &str = "    ";
+
+/// Keywords that get uppercased when formatting.
+///
+/// This is intentionally a small set of reserved words. Uppercasing every
+/// keyword known to the parser would also uppercase common column names like
+/// `name` or `value`.
+const UPPERCASE_KEYWORDS: &[Keyword] = &[
+    Keyword::ALL,
+    Keyword::ALTER,
+    Keyword::ANALYZE,
+    Keyword::AND,
+    Keyword::AS,
+    Keyword::ASC,
+    Keyword::BETWEEN,
+    Keyword::BY,
+    Keyword::CASE,
+    Keyword::CAST,
+    Keyword::COPY,
+    Keyword::CREATE,
+    Keyword::CREDENTIALS,
+    Keyword::CROSS,
+    Keyword::DATABASE,
+    Keyword::DELETE,
+    Keyword::DESC,
+    Keyword::DESCRIBE,
+    Keyword::DISTINCT,
+    Keyword::DROP,
+    Keyword::ELSE,
+    Keyword::END,
+    Keyword::EXCEPT,
+    Keyword::EXCLUDE,
+    Keyword::EXISTS,
+    Keyword::EXPLAIN,
+    Keyword::EXTERNAL,
+    Keyword::FALSE,
+    Keyword::FROM,
+    Keyword::FULL,
+    Keyword::GROUP,
+    Keyword::HAVING,
+    Keyword::IF,
+    Keyword::ILIKE,
+    Keyword::IN,
+    Keyword::INNER,
+    Keyword::INSERT,
+    Keyword::INTERSECT,
+    Keyword::INTO,
+    Keyword::IS,
+    Keyword::JOIN,
+    Keyword::LEFT,
+    Keyword::LIKE,
+    Keyword::LIMIT,
+    Keyword::NATURAL,
+    Keyword::NOT,
+    Keyword::NULL,
+    Keyword::NULLS,
+    Keyword::OFFSET,
+    Keyword::ON,
+    Keyword::OPTIONS,
+    Keyword::OR,
+    Keyword::ORDER,
+    Keyword::OUTER,
+    Keyword::OVER,
+    Keyword::PARTITION,
+    Keyword::QUALIFY,
+    Keyword::RECURSIVE,
+    Keyword::RETURNING,
+    Keyword::RIGHT,
+    Keyword::SCHEMA,
+    Keyword::SELECT,
+    Keyword::SET,
+    Keyword::SHOW,
+    Keyword::TABLE,
+    Keyword::TEMP,
+    Keyword::TEMPORARY,
+    Keyword::THEN,
+    Keyword::TO,
+    Keyword::TRUE,
+    Keyword::UNION,
+    Keyword::UPDATE,
+    Keyword::USING,
+    Keyword::VALUES,
+    Keyword::VIEW,
+    Keyword::WHEN,
+    Keyword::WHERE,
+    Keyword::WINDOW,
+    Keyword::WITH,
+];
+
+/// Keywords that always have a space between them and a following paren, even
+/// if the paren directly followed the keyword in the input.
+const SPACED_BEFORE_PAREN: &[Keyword] = &[
+    Keyword::AND,
+    Keyword::AS,
+    Keyword::ELSE,
+    Keyword::FROM,
+    Keyword::IN,
+    Keyword::JOIN,
+    Keyword::NOT,
+    Keyword::ON,
+    Keyword::OPTIONS,
+    Keyword::OR,
+    Keyword::OVER,
+    Keyword::SELECT,
+    Keyword::THEN,
+    Keyword::USING,
+    Keyword::VALUES,
+    Keyword::WHEN,
+    Keyword::WHERE,
+];
+
+const JOIN_KEYWORDS: &[Keyword] = &[
+    Keyword::CROSS,
+    Keyword::FULL,
+    Keyword::INNER,
+    Keyword::JOIN,
+    Keyword::LEFT,
+    Keyword::NATURAL,
+    Keyword::OUTER,
+    Keyword::RIGHT,
+];
+
+/// Format a string containing zero or more SQL statements.
+///
+/// The returned string always ends with a newline unless the input contained
+/// no tokens.
+pub fn format_sql(sql: &str) -> Result<String> {
+    let dialect = GenericDialect;
+    let tokens = Tokenizer::new(&dialect, sql)
+        .tokenize_with_location()
+        .map_err(|e| anyhow!("Failed to tokenize SQL: {e}"))?;
+
+    let toks = annotate(sql, tokens);
+    let mut formatter = Formatter::new(&toks);
+    formatter.format();
+
+    Ok(formatter.finish())
+}
+
+/// Recursively find all `.sql` files for the given path.
+///
+/// If the path points to a file, that file is returned regardless of its
+/// extension.
+pub fn find_sql_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if !path.exists() {
+        return Err(anyhow!("path '{}' does not exist", path.display()));
+    }
+
+    if path.is_file() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let mut entries = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() {
+            find_sql_files(&entry, files)?;
+        } else if entry.extension().is_some_and(|ext| ext == "sql") {
+            files.push(entry);
+        }
+    }
+
+    Ok(())
+}
+
+/// A token along with the exact text it was parsed from.
+#[derive(Debug)]
+struct Tok<'a> {
+    token: Token,
+    text: &'a str,
+    /// Whitespace preceding this token contained a newline.
+    newline_before: bool,
+    /// This token directly follows the previous token with no whitespace in
+    /// between.
+    adjacent: bool,
+}
+
+impl<'a> Tok<'a> {
+    fn is_comment(&self) -> bool {
+        matches!(
+            self.token,
+            Token::Whitespace(Whitespace::SingleLineComment { .. })
+                | Token::Whitespace(Whitespace::MultiLineComment(_))
+        )
+    }
+
+    fn keyword(&self) -> Option<Keyword> {
+        match &self.token {
+            Token::Word(Word {
+                keyword,
+                quote_style: None,
+                ..
+            }) => Some(*keyword),
+            _ => None,
+        }
+    }
+}
+
+/// Pair up every token with its source text, dropping plain whitespace.
+fn annotate(sql: &str, tokens: Vec<TokenWithLocation>) -> Vec<Tok<'_>> {
+    // Token locations are reported as (line, column) in characters. Walk the
+    // input once to map those locations to byte offsets.
+    let mut offsets = Vec::with_capacity(tokens.len());
+    let mut locations = tokens
+        .iter()
+        .map(|t| (t.location.line, t.location.column))
+        .peekable();
+    let (mut line, mut column) = (1, 1);
+    for (idx, ch) in sql.char_indices() {
+        while locations.peek() == Some(&(line, column)) {
+            offsets.push(idx);
+            locations.next();
+        }
+        if ch == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    offsets.resize(tokens.len(), sql.len());
+
+    let mut toks = Vec::with_capacity(tokens.len());
+    let mut newline_before = false;
+    let mut adjacent = true;
+    for (idx, tok) in tokens.into_iter().enumerate() {
+        let end = offsets.get(idx + 1).copied().unwrap_or(sql.len());
+        let text = sql.get(offsets[idx]..end).unwrap_or_default();
+
+        match &tok.token {
+            Token::Whitespace(Whitespace::Space | Whitespace::Tab) => adjacent = false,
+            Token::Whitespace(Whitespace::Newline) => {
+                adjacent = false;
+                newline_before = true;
+            }
+            _ => {
+                let is_line_comment = matches!(
+                    tok.token,
+                    Token::Whitespace(Whitespace::SingleLineComment { .. })
+                );
+                toks.push(Tok {
+                    token: tok.token,
+                    text,
+                    newline_before,
+                    adjacent,
+                });
+                // Single line comments consume the trailing newline.
+                newline_before = is_line_comment;
+                adjacent = is_line_comment;
+            }
+        }
+    }
+
+    toks
+}
+
+/// The clause a block is currently in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Clause {
+    Other,
+    Select,
+    From,
+    Join,
+    Where,
+    GroupBy,
+    Having,
+    OrderBy,
+    Limit,
+    Values,
+    Set,
+    With,
+    Window,
+    Returning,
+    SetOperation,
+}
+
+impl Clause {
+    /// If commas in this clause separate items that should go on their own
+    /// line.
+    fn is_list(&self) -> bool {
+        matches!(
+            self,
+            Clause::Select
+                | Clause::From
+                | Clause::GroupBy
+                | Clause::OrderBy
+                | Clause::Values
+                | Clause::Set
+                | Clause::With
+                | Clause::Window
+                | Clause::Returning
+        )
+    }
+
+    /// If AND/OR in this clause should go on their own line.
+    fn is_predicate(&self) -> bool {
+        matches!(self, Clause::Where | Clause::Having)
+    }
+}
+
+/// A single query or subquery.
+#[derive(Debug)]
+struct Block {
+    /// Indentation level for clause keywords.
+    base: usize,
+    /// Indentation level for the closing paren of a subquery.
+    close_indent: usize,
+    /// Number of parens opened inline within this block.
+    depth: usize,
+    /// Whether or not we've seen a keyword indicating this block is a query.
+    query: bool,
+    clause: Clause,
+    /// Waiting on the first token of the clause body.
+    body_pending: bool,
+    /// Clause body is laid out with one item per line.
+    expanded: bool,
+    /// Number of BETWEENs waiting on their AND.
+    betweens: usize,
+}
+
+impl Block {
+    fn new(base: usize, close_indent: usize) -> Block {
+        Block {
+            base,
+            close_indent,
+            depth: 0,
+            query: false,
+            clause: Clause::Other,
+            body_pending: false,
+            expanded: false,
+            betweens: 0,
+        }
+    }
+}
+
+struct Formatter<'a, 'b> {
+    toks: &'b [Tok<'a>],
+    out: String,
+    blocks: Vec<Block>,
+    /// Indentation of the current line.
+    line_indent: usize,
+    /// Start a new line with this indentation before writing the next token.
+    pending_newline: Option<usize>,
+    /// Write an empty line before the next token (used between statements).
+    blank_line: bool,
+    /// Nothing has been written for the current statement.
+    stmt_empty: bool,
+    /// Index of the last token written, excluding comments.
+    last: Option<usize>,
+    /// Don't write a space before the next token (e.g. after a unary minus).
+    glue: bool,
+}
+
+impl<'a, 'b> Formatter<'a, 'b> {
+    fn new(toks: &'b [Tok<'a>]) -> Self {
+        Formatter {
+            toks,
+            out: String::new(),
+            blocks: vec![Block::new(0, 0)],
+            line_indent: 0,
+            pending_newline: None,
+            blank_line: false,
+            stmt_empty: true,
+            last: None,
+            glue: false,
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut out = self.out.trim_end().to_string();
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out
+    }
+
+    fn block(&self) -> &Block {
+        self.blocks.last().expect("at least one block")
+    }
+
+    fn block_mut(&mut self) -> &mut Block {
+        self.blocks.last_mut().expect("at least one block")
+    }
+
+    fn format(&mut self) {
+        let toks = self.toks;
+        for (idx, tok) in toks.iter().enumerate() {
+            if tok.is_comment() {
+                self.comment(idx);
+                continue;
+            }
+
+            match &tok.token {
+                Token::SemiColon => {
+                    self.write(idx, ";");
+                    self.end_statement();
+                }
+                Token::LParen => {
+                    self.resolve_body(idx);
+                    let starts_subquery = matches!(
+                        self.next_keyword(idx),
+                        Some(Keyword::SELECT | Keyword::WITH | Keyword::VALUES)
+                    );
+                    self.write(idx, "(");
+                    if starts_subquery {
+                        let close_indent = self.line_indent;
+                        self.blocks.push(Block::new(close_indent + 1, close_indent));
+                        self.newline(close_indent + 1);
+                    } else {
+                        self.block_mut().depth += 1;
+                    }
+                }
+                Token::RParen => {
+                    if self.block().depth > 0 {
+                        self.block_mut().depth -= 1;
+                    } else if self.blocks.len() > 1 {
+                        let block = self.blocks.pop().unwrap();
+                        self.newline(block.close_indent);
+                    }
+                    self.write(idx, ")");
+                }
+                Token::Comma => {
+                    self.resolve_body(idx);
+                    self.write(idx, ",");
+                    let block = self.block();
+                    if block.depth == 0 && block.expanded && block.clause.is_list() {
+                        let indent = block.base + 1;
+                        self.newline(indent);
+                    }
+                }
+                Token::Word(_) => self.word(idx),
+                Token::Minus | Token::Plus if self.is_unary(idx) => {
+                    self.resolve_body(idx);
+                    self.write(idx, tok.text);
+                    self.glue = true;
+                }
+                _ => {
+                    self.resolve_body(idx);
+                    self.write(idx, tok.text);
+                }
+            }
+        }
+    }
+
+    fn word(&mut self, idx: usize) {
+        let toks = self.toks;
+        let tok = &toks[idx];
+        let clause = if self.block().depth == 0 {
+            self.clause_start(idx)
+        } else {
+            None
+        };
+        let text = if self.should_uppercase(idx) {
+            tok.text.to_uppercase()
+        } else {
+            tok.text.to_string()
+        };
+
+        if let Some(clause) = clause {
+            let stmt_empty = self.stmt_empty;
+            let block = self.block_mut();
+            block.query = true;
+            if clause == Clause::Join {
+                block.clause = Clause::From;
+                if block.expanded {
+                    let indent = block.base + 1;
+                    self.newline(indent);
+                }
+            } else {
+                block.clause = clause;
+                block.body_pending = true;
+                block.expanded = false;
+                block.betweens = 0;
+                if !stmt_empty {
+                    let indent = block.base;
+                    self.newline(indent);
+                }
+            }
+            self.write(idx, &text);
+            return;
+        }
+
+        let keyword = tok.keyword();
+        if matches!(
+            keyword,
+            Some(Keyword::INSERT | Keyword::UPDATE | Keyword::DELETE)
+        ) {
+            self.block_mut().query = true;
+        }
+
+        let is_continuation = matches!(
+            keyword,
+            Some(Keyword::BY | Keyword::ALL | Keyword::DISTINCT | Keyword::RECURSIVE)
+        );
+        if !is_continuation {
+            self.resolve_body(idx);
+        }
+
+        let block = self.block_mut();
+        if block.depth == 0 {
+            match keyword {
+                Some(Keyword::BETWEEN) => block.betweens += 1,
+                Some(Keyword::AND) if block.betweens > 0 => block.betweens -= 1,
+                Some(Keyword::AND | Keyword::OR)
+                    if block.expanded && block.clause.is_predicate() =>
+                {
+                    let indent = block.base + 1;
+                    self.newline(indent);
+                }
+                _ => (),
+            }
+        }
+
+        self.write(idx, &text);
+    }
+
+    /// Determine if the body of the current clause starts at this token, and
+    /// if so, decide how the body should be laid out.
+    fn resolve_body(&mut self, idx: usize) {
+        if !self.block().body_pending {
+            return;
+        }
+        let expanded = self.body_expands(idx);
+        let block = self.block_mut();
+        block.body_pending = false;
+        block.expanded = expanded;
+        if expanded {
+            let indent = block.base + 1;
+            self.newline(indent);
+        }
+    }
+
+    /// Scan ahead to check if the clause body starting at `start` contains
+    /// multiple items.
+    fn body_expands(&self, start: usize) -> bool {
+        let clause = self.block().clause;
+        let mut depth = 0;
+        let mut betweens = 0;
+
+        for idx in start..self.toks.len() {
+            let tok = &self.toks[idx];
+            match &tok.token {
+                _ if tok.is_comment() => (),
+                Token::LParen => depth += 1,
+                Token::RParen if depth == 0 => return false,
+                Token::RParen => depth -= 1,
+                _ if depth > 0 => (),
+                Token::SemiColon => return false,
+                Token::Comma if clause.is_list() => return true,
+                Token::Word(_) => match tok.keyword() {
+                    Some(Keyword::BETWEEN) => betweens += 1,
+                    Some(Keyword::AND) if betweens > 0 => betweens -= 1,
+                    Some(Keyword::AND | Keyword::OR) if clause.is_predicate() => return true,
+                    _ => match self.clause_start(idx) {
+                        Some(Clause::Join) => return clause == Clause::From,
+                        Some(_) => return false,
+                        None => (),
+                    },
+                },
+                _ => (),
+            }
+        }
+
+        false
+    }
+
+    /// Check if the word at `idx` starts a new clause in the current block.
+    fn clause_start(&self, idx: usize) -> Option<Clause> {
+        let keyword = self.toks[idx].keyword()?;
+        let prev = self.prev_token(idx);
+        let prev_keyword = prev.and_then(|t| t.keyword());
+        let next = self.next_token(idx);
+        let next_keyword = next.and_then(|t| t.keyword());
+        let next_is_paren = matches!(next.map(|t| &t.token), Some(Token::LParen));
+
+        let clause = match keyword {
+            Keyword::SELECT => Clause::Select,
+            Keyword::VALUES => Clause::Values,
+            Keyword::WITH => {
+                let at_start = match prev {
+                    None => true,
+                    Some(tok) => {
+                        matches!(tok.token, Token::SemiColon | Token::LParen)
+                            || matches!(
+                                prev_keyword,
+                                Some(
+                                    Keyword::AS
+                                        | Keyword::EXPLAIN
+                                        | Keyword::ANALYZE
+                                        | Keyword::VERBOSE
+                                )
+                            )
+                    }
+                };
+                if !at_start || next_is_paren || next_keyword == Some(Keyword::TIME) {
+                    return None;
+                }
+                Clause::With
+            }
+            // Everything else only starts a clause if we know we're in a query.
+            _ if !self.block().query => return None,
+            Keyword::FROM if !matches!(prev_keyword, Some(Keyword::DELETE | Keyword::DISTINCT)) => {
+                Clause::From
+            }
+            Keyword::WHERE => Clause::Where,
+            Keyword::GROUP if next_keyword == Some(Keyword::BY) => Clause::GroupBy,
+            Keyword::HAVING | Keyword::QUALIFY => Clause::Having,
+            Keyword::ORDER if next_keyword == Some(Keyword::BY) => Clause::OrderBy,
+            Keyword::LIMIT | Keyword::OFFSET => Clause::Limit,
+            Keyword::SET => Clause::Set,
+            Keyword::WINDOW => Clause::Window,
+            Keyword::RETURNING => Clause::Returning,
+            Keyword::UNION | Keyword::INTERSECT => Clause::SetOperation,
+            // `SELECT * EXCEPT (...)`
+            Keyword::EXCEPT
+                if !next_is_paren && !matches!(prev.map(|t| &t.token), Some(Token::Mul)) =>
+            {
+                Clause::SetOperation
+            }
+            kw if JOIN_KEYWORDS.contains(&kw) => {
+                let in_from = matches!(self.block().clause, Clause::From | Clause::Join);
+                let is_func = matches!(kw, Keyword::LEFT | Keyword::RIGHT) && next_is_paren;
+                let continues_join = prev_keyword.is_some_and(|kw| JOIN_KEYWORDS.contains(&kw));
+                if !in_from || is_func || continues_join {
+                    return None;
+                }
+                Clause::Join
+            }
+            _ => return None,
+        };
+
+        Some(clause)
+    }
+
+    fn should_uppercase(&self, idx: usize) -> bool {
+        let Some(keyword) = self.toks[idx].keyword() else {
+            return false;
+        };
+        if !UPPERCASE_KEYWORDS.contains(&keyword) {
+            return false;
+        }
+        // Qualified names, e.g. `t.order`.
+        if matches!(self.prev_token(idx).map(|t| &t.token), Some(Token::Period)) {
+            return false;
+        }
+        // Functions sharing a name with a keyword, e.g. `left(s, 2)`.
+        let next = self.next_token(idx);
+        let is_call = matches!(
+            next,
+            Some(Tok {
+                token: Token::LParen,
+                adjacent: true,
+                ..
+            })
+        );
+        !(is_call && matches!(keyword, Keyword::LEFT | Keyword::RIGHT))
+    }
+
+    fn is_unary(&self, idx: usize) -> bool {
+        match self.prev_token(idx) {
+            None => true,
+            Some(tok) => match &tok.token {
+                Token::LParen
+                | Token::Comma
+                | Token::Eq
+                | Token::Neq
+                | Token::Lt
+                | Token::LtEq
+                | Token::Gt
+                | Token::GtEq
+                | Token::Plus
+                | Token::Minus
+                | Token::Mul
+                | Token::Div
+                | Token::Mod => true,
+                Token::Word(_) => tok
+                    .keyword()
+                    .is_some_and(|kw| UPPERCASE_KEYWORDS.contains(&kw)),
+                _ => false,
+            },
+        }
+    }
+
+    fn space_before(&self, idx: usize) -> bool {
+        let Some(last) = self.last else {
+            return false;
+        };
+        if self.glue {
+            return false;
+        }
+
+        let prev = &self.toks[last];
+        let cur = &self.toks[idx];
+        match (&prev.token, &cur.token) {
+            (
+                _,
+                Token::Comma
+                | Token::SemiColon
+                | Token::RParen
+                | Token::RBracket
+                | Token::Period
+                | Token::DoubleColon,
+            ) => false,
+            (Token::LParen | Token::LBracket | Token::Period | Token::DoubleColon, _) => false,
+            (Token::Word(_), Token::LParen) => {
+                !cur.adjacent
+                    || prev
+                        .keyword()
+                        .is_some_and(|kw| SPACED_BEFORE_PAREN.contains(&kw))
+            }
+            (_, Token::LParen | Token::LBracket) => !cur.adjacent,
+            _ => true,
+        }
+    }
+
+    fn comment(&mut self, idx: usize) {
+        let toks = self.toks;
+        let tok = &toks[idx];
+        let is_line_comment = matches!(
+            tok.token,
+            Token::Whitespace(Whitespace::SingleLineComment { .. })
+        );
+        let text = tok.text.trim_end();
+
+        if tok.newline_before || self.out.is_empty() {
+            // Comment on its own line, keep it that way.
+            let indent = self.pending_newline.unwrap_or(self.line_indent);
+            self.pending_newline = Some(indent);
+            self.push(text, false);
+        } else {
+            // Trailing comment, attach to the end of the current line.
+            self.out.push(' ');
+            self.out.push_str(text);
+        }
+
+        if is_line_comment && self.pending_newline.is_none() {
+            self.pending_newline = Some(self.line_indent);
+        }
+    }
+
+    fn end_statement(&mut self) {
+        self.blocks = vec![Block::new(0, 0)];
+        self.pending_newline = Some(0);
+        self.blank_line = true;
+        self.stmt_empty = true;
+        self.last = None;
+        self.glue = false;
+    }
+
+    /// Start a new line with the given indentation on the next write.
+    fn newline(&mut self, indent: usize) {
+        self.pending_newline = Some(indent);
+    }
+
+    fn write(&mut self, idx: usize, text: &str) {
+        let space = self.space_before(idx);
+        self.push(text, space);
+        self.last = Some(idx);
+        self.glue = false;
+        self.stmt_empty = false;
+    }
+
+    fn push(&mut self, text: &str, space: bool) {
+        if let Some(indent) = self.pending_newline.take() {
+            if !self.out.is_empty() {
+                self.out.push('\n');
+                if self.blank_line {
+                    self.out.push('\n');
+                }
+                for _ in 0..indent {
+                    self.out.push_str(INDENT);
+                }
+            }
+            self.blank_line = false;
+            self.line_indent = indent;
+        } else if space {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+    }
+
+    fn prev_token(&self, idx: usize) -> Option<&Tok<'a>> {
+        self.toks[..idx].iter().rev().find(|t| !t.is_comment())
+    }
+
+    fn next_token(&self, idx: usize) -> Option<&Tok<'a>> {
+        self.toks[idx + 1..].iter().find(|t| !t.is_comment())
+    }
+
+    fn next_keyword(&self, idx: usize) -> Option<Keyword> {
+        self.next_token(idx).and_then(|t| t.keyword())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_simple() {
+        let test_cases = [
+            ("select 1", "SELECT 1\n"),
+            ("select 1; select 2;", "SELECT 1;\n\nSELECT 2;\n"),
+            (
+                "select a, b from t where a > 1 and b < 2 order by a;",
+                "SELECT\n    a,\n    b\nFROM t\nWHERE\n    a > 1\n    AND b < 2\nORDER BY a;\n",
+            ),
+            (
+                "select * from (select a from t) s",
+                "SELECT *\nFROM (\n    SELECT a\n    FROM t\n) s\n",
+            ),
+            ("delete from t where a = 1", "DELETE FROM t\nWHERE a = 1\n"),
+            (
+                "select a from t where a between 1 and 2",
+                "SELECT a\nFROM t\nWHERE a BETWEEN 1 AND 2\n",
+            ),
+        ];
+
+        for (input, expected) in test_cases {
+            assert_eq!(expected, format_sql(input).unwrap(), "input: {input}");
+        }
+    }
+
+    #[test]
+    fn format_preserves_literals_and_comments() {
+        let test_cases = [
+            (
+                "select 'it''s', \"Col\" from t",
+                "SELECT\n    'it''s',\n    \"Col\"\nFROM t\n",
+            ),
+            (
+                "select a, -- first\n b from t",
+                "SELECT\n    a, -- first\n    b\nFROM t\n",
+            ),
+            (
+                "create external table t from postgres options (host = 'localhost', port = '5432');",
+                "CREATE EXTERNAL TABLE t FROM postgres OPTIONS (host = 'localhost', port = '5432');\n",
+            ),
+        ];
+
+        for (input, expected) in test_cases {
+            assert_eq!(expected, format_sql(input).unwrap(), "input: {input}");
+        }
+    }
+
+    #[test]
+    fn format_idempotent() {
+        let inputs = [
+            "select a, b from t where a > 1 and b < 2 order by a;",
+            "select * from (select a, count(*) from t group by a) s left join u on s.a = u.a",
+            "with cte as (select 1 as a) select -a from cte; select 2",
+            "select a, -- first\n b from t",
+        ];
+
+        for input in inputs {
+            let once = format_sql(input).unwrap();
+            let twice = format_sql(&once).unwrap();
+            assert_eq!(once, twice, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn format_empty() {
+        assert_eq!("", format_sql("").unwrap());
+        assert_eq!("", format_sql("   \n").unwrap());
+    }
+}
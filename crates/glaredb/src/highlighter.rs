@@ -7,7 +7,7 @@ use reedline::{Highlighter, Hinter, SearchQuery, StyledText, ValidationResult, V
 use sqlbuiltins::functions::FUNCTION_REGISTRY;
 use sqlexec::export::sqlparser::dialect::GenericDialect;
 use sqlexec::export::sqlparser::keywords::Keyword;
-use sqlexec::export::sqlparser::tokenizer::{Token, Tokenizer};
+use sqlexec::export::sqlparser::tokenizer::{Token, Tokenizer, Whitespace};
 
 pub(crate) struct SQLHighlighter;
 pub(crate) struct SQLValidator;
@@ -18,21 +18,88 @@ impl Validator for SQLValidator {
             return ValidationResult::Complete;
         }
 
-        let mut in_single_quote = false;
-        let mut in_double_quote = false;
+        // Keep reading lines until the last statement in the buffer is
+        // terminated.
+        let scan = SqlScan::scan(line);
+        if scan.open_quote.is_none()
+            && scan.open_comment.is_none()
+            && scan.terminated
+            && !scan.trailing_content
+        {
+            ValidationResult::Complete
+        } else {
+            ValidationResult::Incomplete
+        }
+    }
+}
+
+/// Result of scanning a (possibly partial) SQL buffer for quotes, comments,
+/// and statement terminators.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SqlScan {
+    /// Byte offset of a quoted string that isn't closed.
+    open_quote: Option<usize>,
+    /// Byte offset of a block comment that isn't closed.
+    open_comment: Option<usize>,
+    /// If there's at least one semicolon outside of quotes and comments.
+    terminated: bool,
+    /// If there's anything other than whitespace and comments after the last
+    /// semicolon.
+    trailing_content: bool,
+}
+
+impl SqlScan {
+    fn scan(text: &str) -> SqlScan {
+        let mut scan = SqlScan::default();
+        let mut quote: Option<(usize, char)> = None;
         let mut last_char = '\0';
+        let mut chars = text.char_indices().peekable();
 
-        for ch in line.chars() {
-            match ch {
-                '\'' if last_char != '\\' && !in_double_quote => in_single_quote = !in_single_quote,
-                '"' if last_char != '\\' && !in_single_quote => in_double_quote = !in_double_quote,
-                ';' if !in_single_quote && !in_double_quote => return ValidationResult::Complete,
-                _ => {}
+        while let Some((idx, ch)) = chars.next() {
+            if scan.open_comment.is_some() {
+                if ch == '*' && matches!(chars.peek(), Some((_, '/'))) {
+                    chars.next();
+                    scan.open_comment = None;
+                }
+                last_char = '\0';
+                continue;
+            }
+
+            match (quote, ch) {
+                (Some((_, q)), ch) if ch == q && last_char != '\\' => quote = None,
+                (Some(_), _) => (),
+                (None, '\'' | '"') if last_char != '\\' => {
+                    quote = Some((idx, ch));
+                    scan.trailing_content = true;
+                }
+                (None, '-') if matches!(chars.peek(), Some((_, '-'))) => {
+                    // Line comment, skip to the end of the line.
+                    for (_, ch) in chars.by_ref() {
+                        if ch == '\n' {
+                            break;
+                        }
+                    }
+                    last_char = '\0';
+                    continue;
+                }
+                (None, '/') if matches!(chars.peek(), Some((_, '*'))) => {
+                    chars.next();
+                    scan.open_comment = Some(idx);
+                    last_char = '\0';
+                    continue;
+                }
+                (None, ';') => {
+                    scan.terminated = true;
+                    scan.trailing_content = false;
+                }
+                (None, ch) if !ch.is_whitespace() => scan.trailing_content = true,
+                (None, _) => (),
             }
             last_char = ch;
         }
 
-        ValidationResult::Incomplete
+        scan.open_quote = quote.map(|(idx, _)| idx);
+        scan
     }
 }
 
@@ -55,24 +122,27 @@ fn colorize_sql(query: &str, st: &mut StyledText, is_hint: bool) {
         }
     };
     let colorize_function = || new_style().fg(Color::Cyan);
+    let colorize_string = || new_style().fg(Color::Yellow).italic();
+    let colorize_comment = || new_style().fg(Color::DarkGray);
 
-    // the tokenizer will error if the final character is an unescaped quote
-    // such as `select * from read_csv("
-    // in this case we will try to find the quote and colorize the rest of the query
-    // otherwise we will return the error
+    // The tokenizer will error while a string or block comment is still being
+    // typed, e.g. `select * from read_csv('`, possibly across several lines.
+    // In this case colorize everything before it, and the rest as the
+    // unterminated string or comment.
     if tokens.is_err() {
-        let pos = query.find(&['\'', '"'][..]);
-
-        match pos {
-            None => return,
-            Some(pos) => {
-                let (s1, s2) = query.split_at(pos);
-                colorize_sql(s1, st, is_hint);
-
-                st.push((new_style(), s2.to_string()));
+        let scan = SqlScan::scan(query);
+        let (pos, style) = match (scan.open_quote, scan.open_comment) {
+            (Some(pos), _) => (pos, colorize_string()),
+            (None, Some(pos)) => (pos, colorize_comment()),
+            (None, None) => {
+                st.push((new_style(), query.to_string()));
                 return;
             }
-        }
+        };
+        let (s1, s2) = query.split_at(pos);
+        colorize_sql(s1, st, is_hint);
+        st.push((style, s2.to_string()));
+        return;
     }
     let tokens = tokens.unwrap();
 
@@ -98,12 +168,16 @@ fn colorize_sql(query: &str, st: &mut StyledText, is_hint: bool) {
             | Token::Neq
             | Token::SemiColon) => st.push((new_style().fg(Color::Purple), format!("{token}"))),
             // Strings
-            Token::SingleQuotedString(s) => {
-                st.push((new_style().fg(Color::Yellow).italic(), format!("'{}'", s)))
-            }
-            Token::DoubleQuotedString(s) => {
-                st.push((new_style().fg(Color::Yellow).italic(), format!("\"{}\"", s)))
+            Token::SingleQuotedString(s) => st.push((colorize_string(), format!("'{}'", s))),
+            Token::DoubleQuotedString(s) => st.push((colorize_string(), format!("\"{}\"", s))),
+            // Numbers
+            token @ Token::Number(..) => {
+                st.push((new_style().fg(Color::LightBlue), format!("{token}")))
             }
+            // Comments
+            Token::Whitespace(
+                w @ (Whitespace::SingleLineComment { .. } | Whitespace::MultiLineComment(_)),
+            ) => st.push((colorize_comment(), format!("{w}"))),
             Token::Word(w) => match w.keyword {
                 // Keywords
                 Keyword::SELECT
@@ -324,6 +398,11 @@ mod tests {
             "select \"value; 'inner'\"",      // Nested single quote inside double quote
             "select 'value; \\\"incomplete",  // Escaped double quote inside single quote
             "select \"value; \\\'incomplete", // Escaped single quote inside double quote
+            "select 1; select 2",             // Last statement not terminated
+            "select 1 -- comment;",           // Semicolon inside line comment
+            "select 1 /* comment; */",        // Semicolon inside block comment
+            "select 1 /* comment;",           // Block comment not closed
+            "select 1\nfrom t",               // Multiple lines without semicolon
         ];
 
         let completes = vec![
@@ -339,6 +418,11 @@ mod tests {
             "select \"value; 'inner'\";", // Nested single quote inside double quote, semicolon outside
             "select 'value; \\'another\\'';", // Escaped single quote inside single quotes
             "select \"value; \\\"another\\\"\";", // Escaped double quote inside double quotes
+            "select 1; select 2;",        // Multiple terminated statements
+            "select 1; -- trailing comment", // Only a comment after the semicolon
+            "select 1 /* comment */;",    // Semicolon after block comment
+            "select 1\nfrom t\nwhere a = 1;", // Multiple lines
+            "select '--not a comment';",  // Comment marker inside quotes
         ];
 
         let validator = super::SQLValidator;
@@ -353,4 +437,21 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn highlight_preserves_partial_input() {
+        use reedline::Highlighter;
+
+        let cases = [
+            "select 'a' from t where b = 'unterminated",
+            "select 1,\n  'multi\nline",
+            "select 1 /* unterminated\ncomment",
+            "select 1 -- comment\nfrom t;",
+        ];
+        for case in cases {
+            let styled = super::SQLHighlighter.highlight(case, 0);
+            let text: String = styled.buffer.iter().map(|(_, s)| s.as_str()).collect();
+            assert_eq!(case, text);
+        }
+    }
 }
@@ -1,9 +1,15 @@
 pub mod args;
+pub mod bench;
 pub mod commands;
+pub mod config;
+mod formatter;
 mod highlighter;
 pub mod local;
 pub mod metastore;
+pub mod metrics_server;
+pub mod profile;
 mod prompt;
 pub mod proxy;
+mod query_history;
 
 pub mod server;
@@ -1,32 +1,67 @@
-use crate::args::{LocalClientOpts, OutputMode, StorageConfigArgs};
+use crate::args::{
+    parse_max_cell_width, ErrorFormat, LocalClientOpts, OnError, OutputMode, StorageConfigArgs,
+};
+use crate::formatter::format_sql;
 use crate::highlighter::{SQLHighlighter, SQLHinter, SQLValidator};
 use crate::prompt::SQLPrompt;
+use crate::query_history;
 use anyhow::{anyhow, Result};
-use arrow_util::pretty;
+use arrow_util::formats;
+use arrow_util::pretty::{self, Alignment, DisplayOptions, TypeCategory};
+use atty::Stream;
 use clap::ValueEnum;
 use colored::Colorize;
 use datafusion::arrow::csv::writer::WriterBuilder as CsvWriterBuilder;
+use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::arrow::error::ArrowError;
+use datafusion::arrow::ipc::writer::FileWriter as IpcFileWriter;
 use datafusion::arrow::json::writer::{
     JsonFormat, LineDelimited as JsonLineDelimted, Writer as JsonWriter,
 };
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::physical_plan::SendableRecordBatchStream;
-use futures::StreamExt;
+use futures::future::LocalBoxFuture;
+use futures::{FutureExt, StreamExt};
 use pgrepr::format::Format;
-use reedline::{FileBackedHistory, Reedline, Signal};
-use std::collections::HashMap;
+use reedline::{
+    FileBackedHistory, History, HistoryItem, Reedline, Signal, ValidationResult, Validator,
+};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
 
+use datafusion::variable::VarType;
+use datafusion_ext::metrics::ExecutionProgress;
 use datafusion_ext::vars::SessionVars;
 use sqlexec::engine::{Engine, SessionStorageConfig, TrackedSession};
+use sqlexec::parser::StatementWithExtensions;
+use sqlexec::query_history::QueryHistory;
 use sqlexec::remote::client::{RemoteClient, RemoteClientType};
+use sqlexec::remote::endpoints::EndpointOptions;
 use sqlexec::session::ExecutionResult;
 use std::env;
-use std::io::Write;
-use std::path::PathBuf;
-use std::time::Instant;
+use std::fmt::{self, Write as _};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use url::Url;
 
+/// Number of entries to keep in the REPL's history.
+const HISTORY_CAPACITY: usize = 100;
+
+/// Maximum depth of scripts including other scripts with `\i`.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Interval used by `\watch` if none is given.
+const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long a statement runs before its progress is shown.
+const PROGRESS_DELAY: Duration = Duration::from_millis(500);
+
+/// How often the progress of a running statement is redrawn.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Debug, Clone, Copy)]
 enum ClientCommandResult {
     /// Exit the program.
@@ -37,8 +72,91 @@ enum ClientCommandResult {
 
 pub struct LocalSession {
     sess: TrackedSession,
-    _engine: Engine,
+    engine: Engine,
     opts: LocalClientOpts,
+    /// The last query text executed, used by `\format`.
+    last_query: Option<String>,
+    /// Whether executed statements are recorded in the query history table.
+    record_history: bool,
+    /// Most recently recorded statements, used to seed the REPL's history.
+    recent_queries: Vec<String>,
+    /// File query output is redirected to with `\o`, if any.
+    output: Option<(PathBuf, File)>,
+    /// Variables set with `\set`, substituted for `:name` in statements.
+    variables: BTreeMap<String, String>,
+    /// Number of scripts currently being run with `\i`.
+    include_depth: usize,
+    /// Number of statements that failed and were skipped with
+    /// `--on-error continue`.
+    failed_statements: usize,
+}
+
+/// An error from a statement in a script, with where it occurred.
+#[derive(Debug)]
+pub struct StatementError {
+    /// Script the statement is in, if it was run with `\i`.
+    pub file: Option<PathBuf>,
+    /// Line the statement starts on.
+    pub line: usize,
+    /// Text of the statement or client command that failed.
+    pub statement: String,
+    pub error: anyhow::Error,
+}
+
+impl fmt::Display for StatementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}: {:#}", file.display(), self.line, self.error),
+            None => write!(f, "{:#}", self.error),
+        }
+    }
+}
+
+impl std::error::Error for StatementError {}
+
+/// Returned once errors have been written to stderr, so they shouldn't be
+/// printed again.
+#[derive(Debug)]
+pub struct ErrorsReported {
+    pub count: usize,
+}
+
+impl fmt::Display for ErrorsReported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} statement(s) failed", self.count)
+    }
+}
+
+impl std::error::Error for ErrorsReported {}
+
+/// Write an error to stderr in the given format.
+pub fn report_error(format: ErrorFormat, e: &anyhow::Error) {
+    match format {
+        ErrorFormat::Text => eprintln!("Error: {e:#}"),
+        ErrorFormat::Json => {
+            // Scripts run with `\i` wrap the errors of the scripts they
+            // include, report where the error actually occurred.
+            let mut located = e.downcast_ref::<StatementError>();
+            while let Some(inner) = located.and_then(|l| l.error.downcast_ref::<StatementError>()) {
+                located = Some(inner);
+            }
+            let obj = match located {
+                Some(located) => serde_json::json!({
+                    "error": format!("{:#}", located.error),
+                    "file": located.file.as_ref().map(|f| f.display().to_string()),
+                    "line": located.line,
+                    "statement": located.statement.trim(),
+                }),
+                None => serde_json::json!({
+                    "error": format!("{e:#}"),
+                    "file": null,
+                    "line": null,
+                    "statement": null,
+                }),
+            };
+            eprintln!("{obj}");
+        }
+    }
 }
 
 impl LocalSession {
@@ -58,11 +176,44 @@ impl LocalSession {
 
         engine = engine.with_spill_path(opts.spill_path.clone());
 
+        // Executing against a remote deployment would record history in the
+        // deployment's catalog instead of the local one.
+        let record_history = opts.query_history && opts.cloud_url.is_none();
+        let recent_queries = if record_history {
+            // Read the history before enabling it so that reading it isn't
+            // recorded as well.
+            let mut sess = engine
+                .new_local_session_context(
+                    SessionVars::default()
+                        .with_is_embedded(true, VarType::System)
+                        .with_is_admin(true, VarType::System),
+                    SessionStorageConfig::default(),
+                )
+                .await?;
+            let queries = query_history::recent_queries(&mut sess, HISTORY_CAPACITY).await?;
+            drop(sess);
+
+            engine = engine.with_query_history(QueryHistory::new(QueryHistory::DEFAULT_RETENTION));
+            queries
+        } else {
+            Vec::new()
+        };
+
         let sess = if let Some(url) = opts.cloud_url.clone() {
             let (exec_client, info_msg) = if opts.ignore_rpc_auth {
-                let u = &url.to_string();
+                let mut urls = vec![url];
+                urls.extend(opts.failover_urls.iter().cloned());
+                let u = urls
+                    .iter()
+                    .map(|u| u.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let endpoint_opts = EndpointOptions {
+                    sticky: opts.sticky_sessions,
+                    ..Default::default()
+                };
                 (
-                    RemoteClient::connect(url).await?,
+                    RemoteClient::connect_endpoints(urls, endpoint_opts).await?,
                     format!("Connected to remote GlareDB server: {}", u.cyan()),
                 )
             } else {
@@ -87,7 +238,12 @@ impl LocalSession {
                 (client, msg)
             };
             let mut sess = engine
-                .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+                .new_local_session_context(
+                    SessionVars::default()
+                        .with_is_embedded(true, VarType::System)
+                        .with_is_admin(true, VarType::System),
+                    SessionStorageConfig::default(),
+                )
                 .await?;
             sess.attach_remote_session(exec_client.clone(), None)
                 .await?;
@@ -97,23 +253,38 @@ impl LocalSession {
             sess
         } else {
             engine
-                .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+                .new_local_session_context(
+                    SessionVars::default()
+                        .with_is_embedded(true, VarType::System)
+                        .with_is_admin(true, VarType::System),
+                    SessionStorageConfig::default(),
+                )
                 .await?
         };
 
         Ok(LocalSession {
             sess,
-            _engine: engine,
+            engine,
             opts,
+            last_query: None,
+            record_history,
+            recent_queries,
+            output: None,
+            variables: BTreeMap::new(),
+            include_depth: 0,
+            failed_statements: 0,
         })
     }
 
     pub async fn run(mut self, query: Option<String>) -> Result<()> {
-        if let Some(query) = query {
+        let result = if let Some(query) = query {
             self.execute_one(&query).await
         } else {
             self.run_interactive().await
-        }
+        };
+        // Write out anything buffered, e.g. the query history, before exiting.
+        self.engine.shutdown().await;
+        result
     }
 
     async fn run_interactive(&mut self) -> Result<()> {
@@ -131,16 +302,29 @@ impl LocalSession {
 
         println!("Type {} for help.", "\\help".bold().italic());
 
-        let history = Box::new(
-            FileBackedHistory::with_file(100, get_history_path())
-                .expect("Error configuring history with file"),
-        );
+        let history: Box<dyn History> = if self.record_history {
+            // Search through the persisted query history instead of the
+            // history file.
+            let mut history = FileBackedHistory::new(HISTORY_CAPACITY);
+            for query in std::mem::take(&mut self.recent_queries) {
+                history.save(HistoryItem::from_command_line(query))?;
+            }
+            Box::new(history)
+        } else {
+            Box::new(
+                FileBackedHistory::with_file(HISTORY_CAPACITY, get_history_path())
+                    .expect("Error configuring history with file"),
+            )
+        };
 
         let mut line_editor = Reedline::create()
             .with_history(history)
             .with_hinter(Box::new(SQLHinter::new()))
             .with_highlighter(Box::new(SQLHighlighter))
-            .with_validator(Box::new(SQLValidator));
+            .with_validator(Box::new(SQLValidator))
+            // Insert pasted text as-is instead of executing each pasted line
+            // as it's entered.
+            .use_bracketed_paste(true);
 
         let prompt = SQLPrompt {};
 
@@ -173,8 +357,110 @@ impl LocalSession {
     }
 
     async fn execute_one(&mut self, query: &str) -> Result<()> {
-        self.execute(query).await?;
-        Ok(())
+        let result = self.run_script(query, None).await;
+        let failed = self.failed_statements;
+        match (result, self.opts.error_format) {
+            (Ok(_), _) if failed == 0 => Ok(()),
+            (Ok(_), _) => Err(ErrorsReported { count: failed }.into()),
+            (Err(e), ErrorFormat::Text) if failed == 0 => Err(e),
+            (Err(e), format) => {
+                report_error(format, &e);
+                Err(ErrorsReported { count: failed + 1 }.into())
+            }
+        }
+    }
+
+    /// Handle a statement failing in a script, either returning the error
+    /// or reporting it and continuing with the next statement.
+    fn statement_failed(&mut self, e: anyhow::Error) -> Result<()> {
+        match self.opts.on_error {
+            OnError::Stop => Err(e),
+            OnError::Continue => {
+                report_error(self.opts.error_format, &e);
+                self.failed_statements += 1;
+                Ok(())
+            }
+        }
+    }
+
+    /// Run a script, which may contain client commands on their own lines
+    /// between statements.
+    ///
+    /// Statements are executed one at a time as they're terminated, so
+    /// variables set partway through a script apply to the statements after.
+    /// Errors are returned as a [`StatementError`], which is prefixed with
+    /// the location in `path` if given. Whether the script stops at the first
+    /// error depends on `--on-error`.
+    fn run_script<'a>(
+        &'a mut self,
+        script: &'a str,
+        path: Option<&'a Path>,
+    ) -> LocalBoxFuture<'a, Result<ClientCommandResult>> {
+        let with_location = move |line: usize, statement: &str, error: anyhow::Error| {
+            anyhow::Error::new(StatementError {
+                file: path.map(Path::to_path_buf),
+                line,
+                statement: statement.to_string(),
+                error,
+            })
+        };
+
+        async move {
+            let mut buf = String::new();
+            let mut start_line = 1;
+
+            for (idx, line) in script.lines().enumerate() {
+                let line_num = idx + 1;
+                if buf.trim().is_empty() {
+                    if is_client_cmd(line.trim()) {
+                        match self.handle_client_cmd(line.trim()).await {
+                            Ok(ClientCommandResult::Exit) => return Ok(ClientCommandResult::Exit),
+                            Ok(ClientCommandResult::Continue) => (),
+                            Err(e) => self.statement_failed(with_location(line_num, line, e))?,
+                        }
+                        continue;
+                    }
+                    buf.clear();
+                    start_line = line_num;
+                }
+
+                buf.push_str(line);
+                buf.push('\n');
+                if matches!(SQLValidator.validate(&buf), ValidationResult::Complete) {
+                    if let Err(e) = self.execute(&buf).await {
+                        self.statement_failed(with_location(start_line, &buf, e))?;
+                    }
+                    buf.clear();
+                }
+            }
+
+            // Allow the last statement to be unterminated.
+            if !buf.trim().is_empty() {
+                if let Err(e) = self.execute(&buf).await {
+                    self.statement_failed(with_location(start_line, &buf, e))?;
+                }
+            }
+
+            Ok(ClientCommandResult::Continue)
+        }
+        .boxed_local()
+    }
+
+    /// Run the script at `path` with `\i`.
+    async fn include_file(&mut self, path: &Path) -> Result<ClientCommandResult> {
+        if self.include_depth >= MAX_INCLUDE_DEPTH {
+            return Err(anyhow!(
+                "Scripts nested too deeply, {MAX_INCLUDE_DEPTH} levels of \\i allowed"
+            ));
+        }
+        let script = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| anyhow!("Unable to read {}: {e}", path.display()))?;
+
+        self.include_depth += 1;
+        let result = self.run_script(&script, Some(path)).await;
+        self.include_depth -= 1;
+        result
     }
 
     async fn execute(&mut self, text: &str) -> Result<()> {
@@ -183,44 +469,270 @@ impl LocalSession {
             return Ok(());
         }
 
+        let text = substitute_variables(text, &self.variables);
+        let text = text.as_ref();
+
         let now = if self.opts.timing {
             Some(Instant::now())
         } else {
             None
         };
 
-        const UNNAMED: String = String::new();
-
+        self.last_query = Some(text.to_string());
         let statements = self.sess.parse_query(text)?;
         for stmt in statements {
-            self.sess
-                .prepare_statement(UNNAMED, stmt, Vec::new())
-                .await?;
-            let prepared = self.sess.get_prepared_statement(&UNNAMED)?;
-            let num_fields = prepared.output_fields().map(|f| f.len()).unwrap_or(0);
-            self.sess.bind_statement(
-                UNNAMED,
-                &UNNAMED,
-                Vec::new(),
-                vec![Format::Text; num_fields],
-            )?;
+            let result = self.execute_statement(stmt, now).await;
+            for warning in self.sess.take_new_warnings() {
+                eprintln!("Warning: {warning}");
+            }
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Execute a single statement, printing its output.
+    ///
+    /// Returns the number of rows returned or modified by the statement, if
+    /// known.
+    async fn execute_statement(
+        &mut self,
+        stmt: StatementWithExtensions,
+        now: Option<Instant>,
+    ) -> Result<Option<usize>> {
+        let mut progress = ProgressIndicator::new(self.sess.execution_progress());
+        let result = run_cancellable(self.execute_to_result(stmt), progress.as_mut()).await?;
+
+        let rows = match result {
+            ExecutionResult::Query { stream, .. } => {
+                let display = self.opts.display_options();
+                let rows = match &mut self.output {
+                    Some((_, file)) => {
+                        // Always write the full table to files.
+                        let write = write_stream(
+                            stream,
+                            self.opts.mode,
+                            file,
+                            self.opts.max_width,
+                            None,
+                            &display,
+                        );
+                        run_cancellable(write, progress.as_mut()).await?
+                    }
+                    None => {
+                        // If width not explicitly set by the user, try to get
+                        // the width of the terminal.
+                        let width = self.opts.max_width.unwrap_or(pretty::term_width());
+                        if self.opts.mode == OutputMode::Arrow && atty::is(Stream::Stdout) {
+                            return Err(anyhow!(
+                                "Arrow output is binary, write it to a file with \\o FILE"
+                            ));
+                        }
+                        if self.opts.mode.collects_batches() {
+                            // Output is printed once all rows are collected,
+                            // show progress until then.
+                            let schema = stream.schema();
+                            let batches =
+                                run_cancellable(process_stream(stream), progress.as_mut()).await?;
+                            write_batches(
+                                self.opts.mode,
+                                &schema,
+                                &batches,
+                                std::io::stdout(),
+                                Some(width),
+                                self.opts.max_rows,
+                                &display,
+                            )?
+                        } else {
+                            // Rows are printed as they arrive, drawing
+                            // progress would get in the way.
+                            let write = write_stream(
+                                stream,
+                                self.opts.mode,
+                                std::io::stdout(),
+                                Some(width),
+                                self.opts.max_rows,
+                                &display,
+                            );
+                            run_cancellable(write, None).await?
+                        }
+                    }
+                };
+                if let Some(now) = now {
+                    println!("Time: {:.3}s", now.elapsed().as_secs_f64())
+                }
+                Some(rows)
+            }
+            other => {
+                println!("{}", other);
+                match other {
+                    ExecutionResult::InsertSuccess { rows_inserted } => Some(rows_inserted),
+                    ExecutionResult::DeleteSuccess { deleted_rows } => Some(deleted_rows),
+                    ExecutionResult::UpdateSuccess { updated_rows } => Some(updated_rows),
+                    _ => None,
+                }
+            }
+        };
+
+        Ok(rows)
+    }
+
+    /// Execute a single statement, printing any notices it raised.
+    async fn execute_to_result(
+        &mut self,
+        stmt: StatementWithExtensions,
+    ) -> Result<ExecutionResult> {
+        const UNNAMED: String = String::new();
+
+        self.sess
+            .prepare_statement(UNNAMED, stmt, Vec::new())
+            .await?;
+        let prepared = self.sess.get_prepared_statement(&UNNAMED)?;
+        let num_fields = prepared.output_fields().map(|f| f.len()).unwrap_or(0);
+        self.sess.bind_statement(
+            UNNAMED,
+            &UNNAMED,
+            Vec::new(),
+            vec![Format::Text; num_fields],
+        )?;
+
+        let stream = self.sess.execute_portal(&UNNAMED, 0).await?;
+
+        for notice in self.sess.get_session_vars().write().take_notices() {
+            println!("NOTICE: {notice}");
+        }
+
+        Ok(stream)
+    }
 
-            let stream = self.sess.execute_portal(&UNNAMED, 0).await?;
-
-            match stream {
-                ExecutionResult::Query { stream, .. } => {
-                    print_stream(
-                        stream,
-                        self.opts.mode,
-                        self.opts.max_width,
-                        self.opts.max_rows,
-                        now,
-                    )
-                    .await?
+    /// Run a query for `\copy`, streaming its results to a local file.
+    async fn copy_to_file(&mut self, args: &CopyArgs) -> Result<()> {
+        let query = match &args.source {
+            CopySource::Query(query) => query.clone(),
+            CopySource::Table(table) => format!("SELECT * FROM {table}"),
+        };
+        let mut statements = self.sess.parse_query(&query)?;
+        if statements.len() != 1 {
+            return Err(anyhow!("\\copy expects exactly one query"));
+        }
+        let stmt = statements.pop_front().unwrap();
+
+        let mut progress = ProgressIndicator::new(self.sess.execution_progress());
+        let stream = match run_cancellable(self.execute_to_result(stmt), progress.as_mut()).await? {
+            ExecutionResult::Query { stream, .. } => stream,
+            other => return Err(anyhow!("\\copy source must return rows, got: {other}")),
+        };
+        let file = File::create(&args.path)?;
+        let display = self.opts.display_options();
+        let write = write_stream(stream, args.mode, file, None, None, &display);
+        let rows = run_cancellable(write, progress.as_mut()).await?;
+        println!("Copied {rows} rows to {}", args.path.display());
+
+        Ok(())
+    }
+
+    /// Re-execute the previous query every `interval` for `\watch`, until
+    /// interrupted with Ctrl+C.
+    ///
+    /// The screen is cleared before each execution when writing to a terminal,
+    /// so the output is redrawn in place.
+    async fn watch(&mut self, interval: Duration) -> Result<()> {
+        let query = self
+            .last_query
+            .clone()
+            .ok_or_else(|| anyhow!("No query to watch"))?;
+        let redraw = self.output.is_none() && atty::is(Stream::Stdout);
+
+        let cancel = ctrl_c();
+        tokio::pin!(cancel);
+
+        loop {
+            if redraw {
+                // Clear the screen and move the cursor to the top left.
+                print!("\x1B[2J\x1B[H");
+            }
+            println!(
+                "{}",
+                format!(
+                    "Every {:.1}s: {} (Ctrl+C to stop)",
+                    interval.as_secs_f64(),
+                    query.trim()
+                )
+                .bold()
+            );
+
+            let iteration = async {
+                self.execute(&query).await?;
+                tokio::time::sleep(interval).await;
+                Ok::<_, anyhow::Error>(())
+            };
+            tokio::select! {
+                // Check for Ctrl+C first so cancelling the query being
+                // executed stops watching instead of erroring.
+                biased;
+                _ = &mut cancel => break,
+                result = iteration => result?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Change or show how values are displayed with `\pset`.
+    fn pset(&mut self, args: &str) -> Result<()> {
+        const USAGE: &str = "Usage: \\pset [null TEXT | max-cell-width NUM|off | align CATEGORY left|right|center|default]";
+
+        let args = args.trim();
+        let (setting, value) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+        let value = value.trim();
+
+        match (setting, value) {
+            ("", _) => {
+                let display = self.opts.display_options();
+                println!("null = '{}'", display.null);
+                match display.max_cell_width {
+                    Some(width) => println!("max-cell-width = {width}"),
+                    None => println!("max-cell-width = off"),
+                }
+                for category in TypeCategory::ALL {
+                    let dtype_alignment = display
+                        .alignments
+                        .get(&category)
+                        .copied()
+                        .map(|a| a.as_str())
+                        .unwrap_or("default");
+                    println!("align {} = {dtype_alignment}", category.as_str());
+                }
+            }
+            ("null", value) => self.opts.null_display = unquote(value).to_string(),
+            ("max-cell-width", "off") => self.opts.max_cell_width = None,
+            ("max-cell-width", value) => {
+                self.opts.max_cell_width =
+                    Some(parse_max_cell_width(value).map_err(|e| anyhow!(e))?)
+            }
+            ("align", value) => {
+                let (category, alignment) = value
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| anyhow!(USAGE))?;
+                let category = TypeCategory::ALL
+                    .into_iter()
+                    .find(|c| c.as_str() == category)
+                    .ok_or_else(|| anyhow!("Unknown type category: {category}"))?;
+                match alignment.trim() {
+                    "default" => {
+                        self.opts.alignments.remove(&category);
+                    }
+                    alignment => {
+                        let alignment = Alignment::ALL
+                            .into_iter()
+                            .find(|a| a.as_str() == alignment)
+                            .ok_or_else(|| anyhow!("Unknown alignment: {alignment}"))?;
+                        self.opts.alignments.insert(category, alignment);
+                    }
                 }
-                other => println!("{}", other),
             }
+            _ => return Err(anyhow!(USAGE)),
         }
+
         Ok(())
     }
 
@@ -239,11 +751,16 @@ impl LocalSession {
             }
             ("\\max-rows", Some(val)) => self.opts.max_rows = Some(val.parse()?),
             ("\\max-width", Some(val)) => self.opts.max_width = Some(val.parse()?),
+            ("\\pset", _) => {
+                let args = text.trim_start().strip_prefix("\\pset").unwrap_or_default();
+                self.pset(args)?;
+            }
             ("\\open", Some(path)) => {
                 if let Ok(url) = Url::parse(path) {
                     let new_opts = LocalClientOpts {
                         data_dir: None,
                         cloud_url: Some(url),
+                        failover_urls: Vec::new(),
                         ..self.opts.clone()
                     };
                     let new_sess = LocalSession::connect(new_opts).await?;
@@ -252,12 +769,85 @@ impl LocalSession {
                     let new_opts = LocalClientOpts {
                         data_dir: Some(PathBuf::from(path)),
                         cloud_url: None,
+                        failover_urls: Vec::new(),
                         ..self.opts.clone()
                     };
                     let new_sess = LocalSession::connect(new_opts).await?;
                     *self = new_sess;
                 }
             }
+            ("\\format", _) => {
+                let sql = text
+                    .trim_start()
+                    .strip_prefix("\\format")
+                    .unwrap_or_default()
+                    .trim();
+                let sql = if sql.is_empty() {
+                    self.last_query
+                        .as_deref()
+                        .ok_or_else(|| anyhow!("No query to format"))?
+                } else {
+                    sql
+                };
+                print!("{}", format_sql(sql)?);
+            }
+            ("\\i", Some(_)) => {
+                let path = text
+                    .trim_start()
+                    .strip_prefix("\\i")
+                    .unwrap_or_default()
+                    .trim();
+                return self.include_file(Path::new(unquote(path))).await;
+            }
+            ("\\set", None) => {
+                for (name, value) in &self.variables {
+                    println!("{name} = '{value}'");
+                }
+            }
+            ("\\set", Some(name)) => {
+                if !is_valid_variable_name(name) {
+                    return Err(anyhow!("Invalid variable name: {name}"));
+                }
+                let value = text
+                    .trim_start()
+                    .strip_prefix("\\set")
+                    .unwrap_or_default()
+                    .trim_start()
+                    .strip_prefix(name)
+                    .unwrap_or_default()
+                    .trim();
+                self.variables
+                    .insert(name.to_string(), unquote(value).to_string());
+            }
+            ("\\unset", Some(name)) => {
+                self.variables.remove(name);
+            }
+            ("\\o", _) => {
+                let path = text
+                    .trim_start()
+                    .strip_prefix("\\o")
+                    .unwrap_or_default()
+                    .trim();
+                if path.is_empty() {
+                    if let Some((path, _)) = self.output.take() {
+                        println!("Output is no longer written to {}", path.display());
+                    }
+                } else {
+                    let path = PathBuf::from(unquote(path));
+                    let file = File::create(&path)
+                        .map_err(|e| anyhow!("Unable to open {}: {e}", path.display()))?;
+                    self.output = Some((path, file));
+                }
+            }
+            ("\\copy", _) => {
+                let args = text.trim_start().strip_prefix("\\copy").unwrap_or_default();
+                let args = CopyArgs::parse(args)?;
+                self.copy_to_file(&args).await?;
+            }
+            ("\\watch", _) => {
+                let interval = parse_watch_interval(val)?;
+                self.watch(interval).await?;
+            }
             ("\\timing", None) => {
                 self.opts.timing = !self.opts.timing;
                 println!("Timing is {}", if self.opts.timing { "on" } else { "off" })
@@ -281,54 +871,378 @@ async fn process_stream(stream: SendableRecordBatchStream) -> Result<Vec<RecordB
     Ok(batches)
 }
 
-async fn print_stream(
-    stream: SendableRecordBatchStream,
+/// Write the stream to `out` using the given output mode, returning the
+/// number of rows written.
+///
+/// Batches are written as they're received in modes that don't need every
+/// batch first, e.g. to size a table.
+async fn write_stream<W: Write + Send>(
+    mut stream: SendableRecordBatchStream,
     mode: OutputMode,
+    out: W,
     max_width: Option<usize>,
     max_rows: Option<usize>,
-    maybe_now: Option<Instant>,
-) -> Result<()> {
-    let schema = stream.schema();
-    let batches = process_stream(stream).await?;
-
-    fn write_json<F: JsonFormat>(batches: &[RecordBatch]) -> Result<()> {
-        let stdout = std::io::stdout();
-        let buf = std::io::BufWriter::new(stdout);
-        let mut writer = JsonWriter::<_, F>::new(buf);
-        for batch in batches {
-            writer.write(batch)?;
+    display: &DisplayOptions,
+) -> Result<usize> {
+    async fn write_json<F: JsonFormat, W: Write + Send>(
+        mut stream: SendableRecordBatchStream,
+        out: W,
+    ) -> Result<usize> {
+        let mut num_rows = 0;
+        let mut writer = JsonWriter::<_, F>::new(BufWriter::new(out));
+        while let Some(batch) = stream.next().await {
+            let batch = batch?;
+            num_rows += batch.num_rows();
+            writer.write(&batch)?;
         }
         writer.finish()?;
-        let mut buf = writer.into_inner();
-        buf.flush()?;
-        Ok(())
+        writer.into_inner().flush()?;
+        Ok(num_rows)
     }
 
+    let num_rows = match mode {
+        OutputMode::Table | OutputMode::Markdown | OutputMode::Latex | OutputMode::Expanded => {
+            let schema = stream.schema();
+            let batches = process_stream(stream).await?;
+            write_batches(mode, &schema, &batches, out, max_width, max_rows, display)?
+        }
+        OutputMode::Csv => {
+            let mut num_rows = 0;
+            let mut writer = CsvWriterBuilder::new()
+                .has_headers(true)
+                .build(BufWriter::new(out));
+            while let Some(batch) = stream.next().await {
+                let batch = batch?;
+                num_rows += batch.num_rows();
+                writer.write(&batch)?;
+            }
+            writer.into_inner().flush()?;
+            num_rows
+        }
+        OutputMode::Json => write_json::<JsonArrayNewLines, _>(stream, out).await?,
+        OutputMode::Ndjson => write_json::<JsonLineDelimted, _>(stream, out).await?,
+        OutputMode::Arrow => {
+            let mut num_rows = 0;
+            let mut writer = IpcFileWriter::try_new(BufWriter::new(out), &stream.schema())?;
+            while let Some(batch) = stream.next().await {
+                let batch = batch?;
+                num_rows += batch.num_rows();
+                writer.write(&batch)?;
+            }
+            writer.finish()?;
+            writer.into_inner()?.flush()?;
+            num_rows
+        }
+    };
+
+    Ok(num_rows)
+}
+
+/// Write collected batches in one of the modes that needs every batch,
+/// returning the number of rows written.
+///
+/// Only tables are limited to `max_width` and `max_rows`.
+fn write_batches<W: Write>(
+    mode: OutputMode,
+    schema: &SchemaRef,
+    batches: &[RecordBatch],
+    out: W,
+    max_width: Option<usize>,
+    max_rows: Option<usize>,
+    display: &DisplayOptions,
+) -> Result<usize> {
+    let mut out = BufWriter::new(out);
     match mode {
         OutputMode::Table => {
-            // If width not explicitly set by the user, try to get the width of ther
-            // terminal.
-            let width = max_width.unwrap_or(pretty::term_width());
-            let disp = pretty::pretty_format_batches(&schema, &batches, Some(width), max_rows)?;
-            println!("{disp}");
+            let disp = pretty::pretty_format_batches_with_options(
+                schema, batches, max_width, max_rows, display,
+            )?;
+            writeln!(out, "{disp}")?;
         }
-        OutputMode::Csv => {
-            let stdout = std::io::stdout();
-            let buf = std::io::BufWriter::new(stdout);
-            let mut writer = CsvWriterBuilder::new().has_headers(true).build(buf);
-            for batch in batches {
-                writer.write(&batch)?; // CSV writer flushes per write.
+        OutputMode::Markdown => {
+            let disp = formats::markdown_format_batches(schema, batches, display)?;
+            write!(out, "{disp}")?
+        }
+        OutputMode::Latex => {
+            let disp = formats::latex_format_batches(schema, batches, display)?;
+            write!(out, "{disp}")?
+        }
+        OutputMode::Expanded => {
+            let disp = formats::expanded_format_batches(schema, batches, display)?;
+            write!(out, "{disp}")?
+        }
+        mode => {
+            return Err(anyhow!(
+                "Output mode {mode:?} doesn't need collected batches"
+            ))
+        }
+    }
+    out.flush()?;
+    Ok(batches.iter().map(|batch| batch.num_rows()).sum())
+}
+
+/// Resolves when Ctrl+C is pressed.
+///
+/// Never resolves if a handler for Ctrl+C couldn't be installed.
+async fn ctrl_c() {
+    if tokio::signal::ctrl_c().await.is_err() {
+        futures::future::pending::<()>().await;
+    }
+}
+
+/// Run a future executing a statement, drawing `progress` while it runs.
+///
+/// Pressing Ctrl+C drops the future, cancelling the statement, and returns an
+/// error instead of exiting.
+async fn run_cancellable<T>(
+    fut: impl Future<Output = Result<T>>,
+    mut progress: Option<&mut ProgressIndicator>,
+) -> Result<T> {
+    let cancel = ctrl_c();
+    tokio::pin!(fut, cancel);
+    let mut ticker = tokio::time::interval(PROGRESS_INTERVAL);
+
+    let result = loop {
+        tokio::select! {
+            result = &mut fut => break result,
+            _ = &mut cancel => break Err(anyhow!("Query cancelled")),
+            _ = ticker.tick(), if progress.is_some() => {
+                if let Some(progress) = progress.as_mut() {
+                    progress.draw();
+                }
+            }
+        }
+    };
+
+    if let Some(progress) = progress {
+        progress.clear();
+    }
+    result
+}
+
+/// Elapsed time and metrics for the statement being executed, drawn on a
+/// single line on stderr.
+struct ProgressIndicator {
+    progress: ExecutionProgress,
+    start: Instant,
+    /// If the line is drawn and needs to be cleared.
+    drawn: bool,
+}
+
+impl ProgressIndicator {
+    /// Create an indicator if stderr is a terminal.
+    fn new(progress: ExecutionProgress) -> Option<Self> {
+        atty::is(Stream::Stderr).then(|| ProgressIndicator {
+            progress,
+            start: Instant::now(),
+            drawn: false,
+        })
+    }
+
+    fn draw(&mut self) {
+        let elapsed = self.start.elapsed();
+        if elapsed < PROGRESS_DELAY {
+            return;
+        }
+
+        let mut line = format!("{:.1}s", elapsed.as_secs_f64());
+        if let Some(snapshot) = self.progress.snapshot() {
+            let _ = write!(line, ", {} rows", snapshot.output_rows);
+            if snapshot.bytes_read > 0 {
+                let _ = write!(line, ", {} read", format_bytes(snapshot.bytes_read));
+            }
+        }
+        eprint!("\r\x1B[2K{}", format!("{line} (Ctrl+C to cancel)").dimmed());
+        self.drawn = true;
+    }
+
+    fn clear(&mut self) {
+        if self.drawn {
+            eprint!("\r\x1B[2K");
+            self.drawn = false;
+        }
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Where `\copy` reads rows from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CopySource {
+    Table(String),
+    Query(String),
+}
+
+/// Arguments to `\copy`, e.g. `\copy (select * from t) to 'out.csv' csv`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CopyArgs {
+    source: CopySource,
+    path: PathBuf,
+    mode: OutputMode,
+}
+
+impl CopyArgs {
+    fn parse(args: &str) -> Result<CopyArgs> {
+        const USAGE: &str = "Usage: \\copy {table | (query)} to 'file' [csv | json | ndjson]";
+
+        let args = args.trim();
+        // Find the `to` separating the source and destination, skipping over
+        // any `to` inside a parenthesized query.
+        let lower = args.to_lowercase();
+        let search_from = if args.starts_with('(') {
+            args.rfind(')').ok_or_else(|| anyhow!(USAGE))?
+        } else {
+            0
+        };
+        let to_pos = lower[search_from..]
+            .find(" to ")
+            .map(|pos| pos + search_from)
+            .ok_or_else(|| anyhow!(USAGE))?;
+
+        let source = args[..to_pos].trim();
+        let source = match source.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            Some(query) => CopySource::Query(query.trim().to_string()),
+            None if !source.is_empty() => CopySource::Table(source.to_string()),
+            None => return Err(anyhow!(USAGE)),
+        };
+
+        let dest = args[to_pos + " to ".len()..].trim();
+        let (path, format) = match dest.strip_prefix('\'') {
+            Some(rest) => {
+                let end = rest.find('\'').ok_or_else(|| anyhow!(USAGE))?;
+                (&rest[..end], rest[end + 1..].trim())
             }
+            None => match dest.split_once(char::is_whitespace) {
+                Some((path, format)) => (path, format.trim()),
+                None => (dest, ""),
+            },
+        };
+        if path.is_empty() {
+            return Err(anyhow!(USAGE));
         }
-        OutputMode::Json => write_json::<JsonArrayNewLines>(&batches)?,
-        OutputMode::Ndjson => write_json::<JsonLineDelimted>(&batches)?,
+        let path = PathBuf::from(path);
+
+        let mode = if format.is_empty() {
+            mode_for_path(&path)
+        } else {
+            OutputMode::from_str(format, true)
+                .map_err(|s| anyhow!("Unable to set output mode: {s}"))?
+        };
+
+        Ok(CopyArgs { source, path, mode })
+    }
+}
+
+/// Infer the output mode for a file from its extension, defaulting to csv.
+fn mode_for_path(path: &Path) -> OutputMode {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => OutputMode::Json,
+        Some("ndjson") | Some("jsonl") => OutputMode::Ndjson,
+        Some("arrow") | Some("ipc") | Some("feather") => OutputMode::Arrow,
+        Some("md") => OutputMode::Markdown,
+        Some("tex") => OutputMode::Latex,
+        _ => OutputMode::Csv,
+    }
+}
+
+/// Parse the interval in seconds given to `\watch`.
+fn parse_watch_interval(val: Option<&str>) -> Result<Duration> {
+    let Some(val) = val else {
+        return Ok(DEFAULT_WATCH_INTERVAL);
+    };
+    match val.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs > 0.0 => Ok(Duration::from_secs_f64(secs)),
+        _ => Err(anyhow!("Invalid watch interval, expected seconds: {val}")),
+    }
+}
+
+fn is_valid_variable_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Substitute variables set with `\set` into a statement.
+///
+/// Like psql, `:name` is replaced with the value as-is, `:'name'` with the
+/// value as a string literal, and `:"name"` with the value as a quoted
+/// identifier. Undefined variables, casts (`::`), and anything inside quotes
+/// are left alone.
+fn substitute_variables<'a>(text: &'a str, vars: &BTreeMap<String, String>) -> Cow<'a, str> {
+    if vars.is_empty() || !text.contains(':') {
+        return Cow::Borrowed(text);
     }
 
-    if let Some(now) = maybe_now {
-        println!("Time: {:.3}s", now.elapsed().as_secs_f64())
+    let bytes = text.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    let mut quote: Option<u8> = None;
+    // Start of the text that hasn't been copied to `out` yet.
+    let mut copied = 0;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let b = bytes[idx];
+        match (quote, b) {
+            (Some(q), b) if b == q => quote = None,
+            (Some(_), _) => (),
+            (None, b'\'' | b'"') => quote = Some(b),
+            (None, b':') if bytes.get(idx + 1) == Some(&b':') => idx += 1,
+            (None, b':') => {
+                let rest = &text[idx + 1..];
+                let (name, quote_with, len) = match rest.as_bytes().first() {
+                    Some(&q @ (b'\'' | b'"')) => match rest[1..].find(q as char) {
+                        Some(end) => (&rest[1..end + 1], Some(q as char), end + 2),
+                        None => ("", None, 0),
+                    },
+                    _ => {
+                        let end = rest
+                            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                            .unwrap_or(rest.len());
+                        (&rest[..end], None, end)
+                    }
+                };
+
+                if let Some(value) = vars.get(name) {
+                    out.push_str(&text[copied..idx]);
+                    match quote_with {
+                        Some(q) => {
+                            let escaped = value.replace(q, &format!("{q}{q}"));
+                            out.push(q);
+                            out.push_str(&escaped);
+                            out.push(q);
+                        }
+                        None => out.push_str(value),
+                    }
+                    idx += 1 + len;
+                    copied = idx;
+                    continue;
+                }
+            }
+            (None, _) => (),
+        }
+        idx += 1;
     }
 
-    Ok(())
+    out.push_str(&text[copied..]);
+    Cow::Owned(out)
+}
+
+/// Strip single quotes surrounding a path, if any.
+fn unquote(s: &str) -> &str {
+    s.strip_prefix('\'')
+        .and_then(|s| s.strip_suffix('\''))
+        .unwrap_or(s)
 }
 
 pub(crate) fn is_client_cmd(s: &str) -> bool {
@@ -379,3 +1293,100 @@ fn get_history_path() -> PathBuf {
     home_dir.push("history.txt");
     home_dir
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_copy_args() {
+        let args = CopyArgs::parse(" my_table to 'out.json'").unwrap();
+        assert_eq!(
+            CopyArgs {
+                source: CopySource::Table("my_table".to_string()),
+                path: PathBuf::from("out.json"),
+                mode: OutputMode::Json,
+            },
+            args
+        );
+
+        let args =
+            CopyArgs::parse("(select * from t where a = 'to ' ) TO '/tmp/my file.txt' ndjson")
+                .unwrap();
+        assert_eq!(
+            CopyArgs {
+                source: CopySource::Query("select * from t where a = 'to '".to_string()),
+                path: PathBuf::from("/tmp/my file.txt"),
+                mode: OutputMode::Ndjson,
+            },
+            args
+        );
+
+        let args = CopyArgs::parse("s.t to out").unwrap();
+        assert_eq!(OutputMode::Csv, args.mode);
+
+        let args = CopyArgs::parse("t to out.arrow").unwrap();
+        assert_eq!(OutputMode::Arrow, args.mode);
+
+        for invalid in [
+            "",
+            "my_table",
+            "to 'out.csv'",
+            "t to ''",
+            "t to out.csv xml",
+        ] {
+            CopyArgs::parse(invalid).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn substitute() {
+        let vars = BTreeMap::from([
+            ("tbl".to_string(), "my_table".to_string()),
+            ("n".to_string(), "10".to_string()),
+            ("name".to_string(), "o'brien".to_string()),
+        ]);
+
+        let cases = [
+            (
+                "select * from :tbl limit :n",
+                "select * from my_table limit 10",
+            ),
+            ("select :'name'", "select 'o''brien'"),
+            ("select * from :\"tbl\"", "select * from \"my_table\""),
+            // Casts, quoted text, and undefined variables are left alone.
+            ("select 1::int", "select 1::int"),
+            ("select ':n', \":n\"", "select ':n', \":n\""),
+            ("select :missing, :'missing'", "select :missing, :'missing'"),
+            ("select :n:n", "select 1010"),
+            ("select :n;", "select 10;"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(expected, substitute_variables(input, &vars), "{input}");
+        }
+    }
+
+    #[test]
+    fn watch_interval() {
+        assert_eq!(DEFAULT_WATCH_INTERVAL, parse_watch_interval(None).unwrap());
+        assert_eq!(
+            Duration::from_millis(500),
+            parse_watch_interval(Some("0.5")).unwrap()
+        );
+        assert_eq!(
+            Duration::from_secs(10),
+            parse_watch_interval(Some("10")).unwrap()
+        );
+
+        for invalid in ["0", "-1", "inf", "NaN", "fast"] {
+            parse_watch_interval(Some(invalid)).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!("512 B", format_bytes(512));
+        assert_eq!("1.5 KiB", format_bytes(1536));
+        assert_eq!("2.0 GiB", format_bytes(2 * 1024 * 1024 * 1024));
+    }
+}
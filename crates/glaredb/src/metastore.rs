@@ -18,6 +18,13 @@ impl Metastore {
         })
     }
 
+    /// Create a metastore storing catalogs in a Postgres database.
+    pub async fn new_postgres(conn_str: &str) -> Result<Self> {
+        Ok(Metastore {
+            service: Service::connect_postgres(conn_str).await?,
+        })
+    }
+
     pub async fn serve(self, addr: SocketAddr) -> Result<()> {
         info!(%addr, "starting metastore service");
         Server::builder()
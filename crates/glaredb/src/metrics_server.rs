@@ -0,0 +1,67 @@
+//! HTTP endpoint for scraping Prometheus metrics.
+
+use anyhow::Result;
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use rpcsrv::jwt::JwtAuthenticator;
+use std::convert::Infallible;
+use std::sync::Arc;
+use telemetry::metrics::{METRICS, PROMETHEUS_CONTENT_TYPE};
+use tokio::net::TcpListener;
+use tracing::{debug, info};
+
+/// Path metrics are served on.
+pub const METRICS_PATH: &str = "/metrics";
+
+/// Serve the process metrics on `METRICS_PATH` until the server errors.
+///
+/// Requests must have a valid bearer token if an authenticator is provided.
+pub async fn serve_metrics(
+    listener: TcpListener,
+    jwt_authenticator: Option<Arc<JwtAuthenticator>>,
+) -> Result<()> {
+    info!(addr = %listener.local_addr()?, "serving metrics");
+    let make_svc = make_service_fn(move |_conn| {
+        let jwt_authenticator = jwt_authenticator.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                handle_request(req, jwt_authenticator.clone())
+            }))
+        }
+    });
+    Server::from_tcp(listener.into_std()?)?
+        .serve(make_svc)
+        .await?;
+    Ok(())
+}
+
+async fn handle_request(
+    req: Request<Body>,
+    jwt_authenticator: Option<Arc<JwtAuthenticator>>,
+) -> Result<Response<Body>, Infallible> {
+    if let Some(auth) = jwt_authenticator {
+        let header = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok());
+        if let Err(e) = auth.authenticate_header(header) {
+            debug!(%e, "rejecting metrics request");
+            let resp = Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .header(WWW_AUTHENTICATE, "Bearer")
+                .body(Body::empty());
+            return Ok(resp.expect("response should be valid"));
+        }
+    }
+
+    let resp = match (req.method(), req.uri().path()) {
+        (&Method::GET, METRICS_PATH) => Response::builder()
+            .header(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)
+            .body(Body::from(METRICS.encode())),
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty()),
+    };
+    Ok(resp.expect("response should be valid"))
+}
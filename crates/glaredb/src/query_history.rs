@@ -0,0 +1,63 @@
+//! Reading the query history of a local session.
+//!
+//! When enabled, the engine records every statement in the persisted
+//! `system.query_history` table of the database. Since it's just a
+//! table, the history can be queried like anything else, and it's also used to
+//! seed the REPL's history search.
+use anyhow::Result;
+use datafusion::arrow::array::{Array, StringArray};
+use datafusion::arrow::record_batch::RecordBatch;
+use futures::StreamExt;
+use pgrepr::format::Format;
+use sqlexec::engine::TrackedSession;
+use sqlexec::session::ExecutionResult;
+
+/// Get the text of the most recently executed statements, oldest first.
+pub async fn recent_queries(sess: &mut TrackedSession, limit: usize) -> Result<Vec<String>> {
+    let batches = execute_sql(
+        sess,
+        &format!(
+            "SELECT query_text FROM system.query_history ORDER BY start_time DESC LIMIT {limit}"
+        ),
+    )
+    .await?;
+
+    let mut queries = Vec::new();
+    for batch in batches {
+        let col = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .ok_or_else(|| anyhow::anyhow!("query_text should be a string column"))?;
+        queries.extend(col.iter().flatten().map(String::from));
+    }
+    queries.reverse();
+
+    Ok(queries)
+}
+
+/// Execute a statement on the session, collecting any output.
+async fn execute_sql(sess: &mut TrackedSession, sql: &str) -> Result<Vec<RecordBatch>> {
+    const UNNAMED: String = String::new();
+
+    let mut batches = Vec::new();
+    for stmt in sess.parse_query(sql)? {
+        sess.prepare_statement(UNNAMED, stmt, Vec::new()).await?;
+        let prepared = sess.get_prepared_statement(&UNNAMED)?;
+        let num_fields = prepared.output_fields().map(|f| f.len()).unwrap_or(0);
+        sess.bind_statement(
+            UNNAMED,
+            &UNNAMED,
+            Vec::new(),
+            vec![Format::Text; num_fields],
+        )?;
+
+        if let ExecutionResult::Query { mut stream, .. } = sess.execute_portal(&UNNAMED, 0).await? {
+            while let Some(batch) = stream.next().await {
+                batches.push(batch?);
+            }
+        }
+    }
+
+    Ok(batches)
+}
@@ -1,25 +1,46 @@
+use crate::metrics_server::{serve_metrics, METRICS_PATH};
 use anyhow::{anyhow, Result};
+use datafusion_ext::reload::ConfigReloader;
 use metastore::util::MetastoreClientMode;
 use pgsrv::auth::LocalAuthenticator;
 use pgsrv::handler::{ProtocolHandler, ProtocolHandlerConfig};
+use pgsrv::ssl::SslConfig;
 use protogen::gen::rpcsrv::service::execution_service_server::ExecutionServiceServer;
 use protogen::gen::rpcsrv::simple::simple_service_server::SimpleServiceServer;
 use rpcsrv::flight::handler::{FlightServiceServer, FlightSessionHandler};
+use rpcsrv::jwt::{JwtAuthenticator, JwtInterceptor};
 use rpcsrv::{handler::RpcHandler, simple::SimpleHandler};
+use sqlexec::admission::{QueryLimiter, QueryLimits};
+use sqlexec::audit::AuditLog;
+use sqlexec::change_feed::ChangeFeed;
+use sqlexec::connections::ConnectionLimiter;
 use sqlexec::engine::{Engine, EngineStorageConfig};
+use sqlexec::plan_cache::PlanCache;
+use sqlexec::query_history::QueryHistory;
+use sqlexec::remote::distribute::Workers;
 use std::collections::HashMap;
+use std::future::Future;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use std::{env, fs};
 use telemetry::{SegmentTracker, Tracker};
 use tokio::net::TcpListener;
 use tokio::signal;
-use tokio::sync::oneshot;
+use tokio::sync::watch;
+use tokio::time::Instant;
 use tonic::transport::server::{Router, TcpIncoming};
 use tonic::transport::Server;
-use tracing::{debug, debug_span, error, info, Instrument};
+use tracing::{debug, debug_span, error, info, info_span, warn, Instrument};
 use uuid::Uuid;
 
+/// Default time given to in-flight queries to complete when shutting down.
+pub const DEFAULT_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// Time given to connections to let clients know they're being closed once the
+/// grace period has elapsed.
+const TERMINATE_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Configuration for initializing the postgres api
 pub struct PostgresProtocolConfig {
     /// Listener to use for pg handler.
@@ -36,6 +57,12 @@ pub struct ComputeServer {
     engine: Arc<Engine>,
     pg_config: Option<PostgresProtocolConfig>,
     rpc_listener: Option<TcpListener>,
+    metrics_listener: Option<TcpListener>,
+    shutdown_grace_period: Duration,
+    config_reloader: Option<ConfigReloader>,
+    jwt_authenticator: Option<Arc<JwtAuthenticator>>,
+    /// Pool of workers remote execution is distributed across.
+    rpc_workers: Arc<Workers>,
 }
 
 pub struct ComputeServerBuilder {
@@ -43,14 +70,34 @@ pub struct ComputeServerBuilder {
     pg_listener: Option<TcpListener>,
     /// Listener to use for rpc handler.
     rpc_listener: Option<TcpListener>,
+    /// Listener to use for serving metrics.
+    metrics_listener: Option<TcpListener>,
     metastore_addr: Option<String>,
+    /// Postgres database to store catalogs in, using an in-process metastore.
+    metastore_postgres_url: Option<String>,
     segment_key: Option<String>,
     authenticator: Option<Box<dyn LocalAuthenticator>>,
+    ssl_config: Option<Arc<SslConfig>>,
     data_dir: Option<PathBuf>,
     service_account_path: Option<String>,
     location: Option<String>,
     storage_options: HashMap<String, String>,
     spill_path: Option<PathBuf>,
+    query_limiter: QueryLimiter,
+    connection_limiter: ConnectionLimiter,
+    audit_log: Option<AuditLog>,
+    query_history: Option<QueryHistory>,
+    change_feed: Option<ChangeFeed>,
+    config_reloader: Option<ConfigReloader>,
+    /// Validates bearer tokens for the rpc and metrics endpoints.
+    jwt_authenticator: Option<Arc<JwtAuthenticator>>,
+    shutdown_grace_period: Duration,
+    /// Addresses of rpc services to distribute remote execution across.
+    rpc_workers: Vec<String>,
+    /// Reject statements that modify the catalog or tables.
+    read_only: bool,
+    /// Plan cache shared by all sessions.
+    shared_plan_cache: Option<PlanCache>,
     integration_testing: bool,
     disable_rpc_auth: bool,
     enable_simple_query_rpc: bool,
@@ -62,14 +109,28 @@ impl ComputeServerBuilder {
         ComputeServerBuilder {
             pg_listener: None,
             rpc_listener: None,
+            metrics_listener: None,
             metastore_addr: None,
+            metastore_postgres_url: None,
             segment_key: None,
             authenticator: None,
+            ssl_config: None,
             data_dir: None,
             service_account_path: None,
             location: None,
             storage_options: HashMap::new(),
             spill_path: None,
+            query_limiter: QueryLimiter::unlimited(),
+            connection_limiter: ConnectionLimiter::unlimited(),
+            audit_log: None,
+            query_history: None,
+            change_feed: None,
+            config_reloader: None,
+            jwt_authenticator: None,
+            shutdown_grace_period: DEFAULT_SHUTDOWN_GRACE_PERIOD,
+            rpc_workers: Vec::new(),
+            read_only: false,
+            shared_plan_cache: None,
             integration_testing: false,
             disable_rpc_auth: false,
             enable_simple_query_rpc: false,
@@ -81,6 +142,16 @@ impl ComputeServerBuilder {
         self.authenticator = Some(Box::new(authenticator));
         self
     }
+    /// Accept TLS connections on the pg handler.
+    pub fn with_ssl_config(mut self, ssl_config: Arc<SslConfig>) -> Self {
+        self.ssl_config = Some(ssl_config);
+        self
+    }
+    /// Optionally accept TLS connections on the pg handler.
+    pub fn with_ssl_config_opt(mut self, ssl_config: Option<Arc<SslConfig>>) -> Self {
+        self.ssl_config = ssl_config;
+        self
+    }
     /// Add a tcp listener to use for serving over the pg protocol.
     pub fn with_pg_listener(mut self, pg_listener: TcpListener) -> Self {
         self.pg_listener = Some(pg_listener);
@@ -101,6 +172,16 @@ impl ComputeServerBuilder {
         self.rpc_listener = rpc_listener;
         self
     }
+    /// Add a tcp listener to use for serving metrics.
+    pub fn with_metrics_listener(mut self, metrics_listener: TcpListener) -> Self {
+        self.metrics_listener = Some(metrics_listener);
+        self
+    }
+    /// Optionally add a tcp listener to use for serving metrics.
+    pub fn with_metrics_listener_opt(mut self, metrics_listener: Option<TcpListener>) -> Self {
+        self.metrics_listener = metrics_listener;
+        self
+    }
     /// Add a metastore address to use for connecting to a remote metastore.
     pub fn with_metastore_addr(mut self, metastore_addr: String) -> Self {
         self.metastore_addr = Some(metastore_addr);
@@ -111,6 +192,16 @@ impl ComputeServerBuilder {
         self.metastore_addr = metastore_addr;
         self
     }
+    /// Store catalogs in a Postgres database using an in-process metastore.
+    pub fn with_metastore_postgres_url(mut self, url: String) -> Self {
+        self.metastore_postgres_url = Some(url);
+        self
+    }
+    /// Optionally store catalogs in a Postgres database using an in-process metastore.
+    pub fn with_metastore_postgres_url_opt(mut self, url: Option<String>) -> Self {
+        self.metastore_postgres_url = url;
+        self
+    }
     pub fn with_segment_key(mut self, segment_key: String) -> Self {
         self.segment_key = Some(segment_key);
         self
@@ -157,6 +248,103 @@ impl ComputeServerBuilder {
         self.spill_path = spill_path;
         self
     }
+    pub fn with_query_limits(mut self, query_limits: QueryLimits) -> Self {
+        self.query_limiter = QueryLimiter::new(query_limits);
+        self
+    }
+    pub fn with_query_limits_opt(mut self, query_limits: Option<QueryLimits>) -> Self {
+        self.query_limiter = match query_limits {
+            Some(query_limits) => QueryLimiter::new(query_limits),
+            None => QueryLimiter::unlimited(),
+        };
+        self
+    }
+    /// Use an existing query limiter, allowing the limits to be changed while
+    /// the server is running.
+    pub fn with_query_limiter(mut self, query_limiter: QueryLimiter) -> Self {
+        self.query_limiter = query_limiter;
+        self
+    }
+    /// Limit connections across the pg and rpc frontends, and close idle
+    /// connections.
+    pub fn with_connection_limiter(mut self, connection_limiter: ConnectionLimiter) -> Self {
+        self.connection_limiter = connection_limiter;
+        self
+    }
+    pub fn with_audit_log(mut self, audit_log: AuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+    pub fn with_audit_log_opt(mut self, audit_log: Option<AuditLog>) -> Self {
+        self.audit_log = audit_log;
+        self
+    }
+    pub fn with_query_history(mut self, query_history: QueryHistory) -> Self {
+        self.query_history = Some(query_history);
+        self
+    }
+    pub fn with_query_history_opt(mut self, query_history: Option<QueryHistory>) -> Self {
+        self.query_history = query_history;
+        self
+    }
+    pub fn with_change_feed(mut self, change_feed: ChangeFeed) -> Self {
+        self.change_feed = Some(change_feed);
+        self
+    }
+    pub fn with_change_feed_opt(mut self, change_feed: Option<ChangeFeed>) -> Self {
+        self.change_feed = change_feed;
+        self
+    }
+    /// Reload configuration on SIGHUP and when sessions call
+    /// `reload_config()`.
+    pub fn with_config_reloader(mut self, config_reloader: ConfigReloader) -> Self {
+        self.config_reloader = Some(config_reloader);
+        self
+    }
+    pub fn with_config_reloader_opt(mut self, config_reloader: Option<ConfigReloader>) -> Self {
+        self.config_reloader = config_reloader;
+        self
+    }
+    /// Require bearer tokens validated by the authenticator for the rpc and
+    /// metrics endpoints.
+    pub fn with_jwt_authenticator(mut self, jwt_authenticator: Arc<JwtAuthenticator>) -> Self {
+        self.jwt_authenticator = Some(jwt_authenticator);
+        self
+    }
+    pub fn with_jwt_authenticator_opt(
+        mut self,
+        jwt_authenticator: Option<Arc<JwtAuthenticator>>,
+    ) -> Self {
+        self.jwt_authenticator = jwt_authenticator;
+        self
+    }
+    /// Set how long in-flight queries have to complete when shutting down
+    /// before they're canceled.
+    pub fn with_shutdown_grace_period(mut self, shutdown_grace_period: Duration) -> Self {
+        self.shutdown_grace_period = shutdown_grace_period;
+        self
+    }
+    /// Distribute remote execution across rpc services at these addresses.
+    pub fn with_rpc_workers(mut self, rpc_workers: Vec<String>) -> Self {
+        self.rpc_workers = rpc_workers;
+        self
+    }
+    /// Serve reads only, rejecting statements that modify the catalog or
+    /// tables in every session.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+    /// Share a plan cache across all sessions instead of each session caching
+    /// plans on its own.
+    pub fn with_shared_plan_cache(mut self, plan_cache: PlanCache) -> Self {
+        self.shared_plan_cache = Some(plan_cache);
+        self
+    }
+    pub fn with_shared_plan_cache_opt(mut self, plan_cache: Option<PlanCache>) -> Self {
+        self.shared_plan_cache = plan_cache;
+        self
+    }
     pub fn integration_testing_mode(mut self, integration_testing: bool) -> Self {
         self.integration_testing = integration_testing;
         self
@@ -177,18 +365,32 @@ impl ComputeServerBuilder {
     pub async fn connect(self) -> Result<ComputeServer> {
         let ComputeServerBuilder {
             metastore_addr,
+            metastore_postgres_url,
             segment_key,
             authenticator,
+            ssl_config,
             data_dir,
             service_account_path,
             location,
             storage_options,
             spill_path,
+            query_limiter,
+            connection_limiter,
+            audit_log,
+            query_history,
+            change_feed,
+            config_reloader,
+            jwt_authenticator,
+            shutdown_grace_period,
+            rpc_workers,
+            read_only,
+            shared_plan_cache,
             integration_testing,
             disable_rpc_auth,
             enable_simple_query_rpc,
             pg_listener,
             rpc_listener,
+            metrics_listener,
             enable_flight_api,
         } = self;
 
@@ -214,25 +416,37 @@ impl ComputeServerBuilder {
             }
         };
 
+        if !rpc_workers.is_empty() {
+            info!(workers = ?rpc_workers, "distributing remote execution across workers");
+        }
+        let rpc_workers = Arc::new(Workers::connect_lazy(&rpc_workers)?);
+
         // Create the `Engine` instance
         let engine = create_engine_from_opts(
             location,
             storage_options,
             tracker,
             metastore_addr,
+            metastore_postgres_url,
             data_dir,
             service_account_path,
             spill_path,
+            query_limiter,
+            connection_limiter,
+            audit_log,
+            query_history,
+            change_feed,
+            config_reloader.clone(),
+            rpc_workers.clone(),
+            read_only,
+            shared_plan_cache,
         )
         .await?;
 
         let pg_config = if let Some(listener) = pg_listener {
             let handler_conf = ProtocolHandlerConfig {
                 authenticator: authenticator.unwrap(),
-                // TODO: Allow specifying SSL/TLS on the GlareDB side as well. I
-                // want to hold off on doing that until we have a shared config
-                // between the proxy and GlareDB.
-                ssl_conf: None,
+                ssl_conf: ssl_config,
                 integration_testing,
             };
             let pg_handler = Arc::new(ProtocolHandler::new(engine.clone(), handler_conf));
@@ -253,35 +467,59 @@ impl ComputeServerBuilder {
             pg_config,
             engine,
             rpc_listener,
+            metrics_listener,
+            shutdown_grace_period,
+            config_reloader,
+            jwt_authenticator,
+            rpc_workers,
         })
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn create_engine_from_opts(
     location: Option<String>,
     storage_options: HashMap<String, String>,
     tracker: Tracker,
     metastore_addr: Option<String>,
+    metastore_postgres_url: Option<String>,
     data_dir: Option<PathBuf>,
     service_account_path: Option<String>,
     spill_path: Option<PathBuf>,
+    query_limiter: QueryLimiter,
+    connection_limiter: ConnectionLimiter,
+    audit_log: Option<AuditLog>,
+    query_history: Option<QueryHistory>,
+    change_feed: Option<ChangeFeed>,
+    config_reloader: Option<ConfigReloader>,
+    rpc_workers: Arc<Workers>,
+    read_only: bool,
+    shared_plan_cache: Option<PlanCache>,
 ) -> Result<Arc<Engine>, anyhow::Error> {
     let engine = if let Some(location) = location {
         // TODO: try to consolidate with --data-dir and --metastore-addr options
         let engine =
             Engine::from_storage_options(&location, &HashMap::from_iter(storage_options.clone()))
                 .await?;
-        Arc::new(engine.with_tracker(Arc::new(tracker)))
+        engine.with_tracker(Arc::new(tracker))
     } else {
         // Connect to metastore.
-        let mode = match (metastore_addr, &data_dir) {
-            (Some(_), Some(_)) => {
+        let mode = match (metastore_addr, metastore_postgres_url, &data_dir) {
+            (Some(_), Some(_), _) => {
+                return Err(anyhow!(
+                    "Only one of metastore address or metastore postgres url may be provided."
+                ))
+            }
+            (Some(_), None, Some(_)) => {
                 return Err(anyhow!(
                     "Only one of metastore address or metastore path may be provided."
                 ))
             }
-            (Some(addr), None) => MetastoreClientMode::Remote { addr },
-            _ => MetastoreClientMode::new_local(data_dir.clone()),
+            (Some(addr), None, None) => MetastoreClientMode::Remote { addr },
+            // Catalogs are stored in postgres, the data directory (if
+            // provided) is only used for table storage.
+            (None, Some(conn_str), _) => MetastoreClientMode::LocalPostgres { conn_str },
+            (None, None, _) => MetastoreClientMode::new_local(data_dir.clone()),
         };
         let metastore_client = mode.into_client().await?;
 
@@ -305,17 +543,43 @@ async fn create_engine_from_opts(
             }
         };
 
-        Arc::new(
-            Engine::new(
-                metastore_client,
-                storage_conf,
-                Arc::new(tracker),
-                spill_path,
-            )
-            .await?,
+        Engine::new(
+            metastore_client,
+            storage_conf,
+            Arc::new(tracker),
+            spill_path,
         )
+        .await?
     };
-    Ok(engine)
+
+    let engine = engine
+        .with_query_limiter(query_limiter)
+        .with_connection_limiter(connection_limiter);
+    let engine = match audit_log {
+        Some(audit_log) => engine.with_audit_log(audit_log),
+        None => engine,
+    };
+    let engine = match query_history {
+        Some(query_history) => engine.with_query_history(query_history),
+        None => engine,
+    };
+    let engine = match change_feed {
+        Some(change_feed) => engine.with_change_feed(change_feed),
+        None => engine,
+    };
+    let engine = match config_reloader {
+        Some(config_reloader) => engine.with_config_reloader(config_reloader),
+        None => engine,
+    };
+    let engine = match shared_plan_cache {
+        Some(plan_cache) => engine.with_shared_plan_cache(plan_cache),
+        None => engine,
+    };
+    if read_only {
+        info!("serving in read-only mode");
+    }
+    let engine = engine.with_workers(rpc_workers).with_read_only(read_only);
+    Ok(Arc::new(engine))
 }
 
 impl ComputeServer {
@@ -329,27 +593,54 @@ impl ComputeServer {
             self.engine.clone(),
             self.disable_rpc_auth,
             self.integration_testing,
-        );
+        )
+        .with_workers(self.rpc_workers.clone());
+        if self.jwt_authenticator.is_some() {
+            info!("requiring bearer tokens for rpc services");
+        }
+        let interceptor = JwtInterceptor::new(self.jwt_authenticator.clone());
         let mut server = Server::builder()
-            .trace_fn(|_| debug_span!("rpc_service_request"))
-            .add_service(ExecutionServiceServer::new(handler));
+            .trace_fn(|req| {
+                // Continue the trace from the client so remote execution shows
+                // up under the query that triggered it.
+                let span = info_span!("rpc_service_request", path = %req.uri().path());
+                logutil::otel::set_trace_parent(&span, req.headers());
+                span
+            })
+            .add_service(ExecutionServiceServer::with_interceptor(
+                handler,
+                interceptor.clone(),
+            ));
 
         if self.enable_flight_api {
             info!("enabling flight sql service");
             let flight_handler = FlightSessionHandler::new(self.engine.clone());
-            server = server.add_service(FlightServiceServer::new(flight_handler));
+            server = server.add_service(FlightServiceServer::with_interceptor(
+                flight_handler,
+                interceptor.clone(),
+            ));
         }
         // Add in the simple interface if requested.
         if self.enable_simple_query_rpc {
             info!("enabling simple query rpc service");
             let handler = SimpleHandler::new(self.engine.clone());
-            server = server.add_service(SimpleServiceServer::new(handler));
+            server =
+                server.add_service(SimpleServiceServer::with_interceptor(handler, interceptor));
         }
         server
     }
 
-    /// Serve using the provided config.
+    /// Serve using the provided config until the process is signaled to shut
+    /// down.
     pub async fn serve(self) -> Result<()> {
+        self.serve_with_shutdown(shutdown_signal()).await
+    }
+
+    /// Serve until `shutdown` resolves, then shut down gracefully.
+    ///
+    /// New connections stop being accepted, and in-flight queries are given
+    /// the grace period to complete before being canceled.
+    pub async fn serve_with_shutdown(self, shutdown: impl Future<Output = ()>) -> Result<()> {
         let rpc_msg = if let Some(listener) = &self.rpc_listener {
             format!("Connect via RPC: grpc://{}", listener.local_addr()?)
         } else {
@@ -365,111 +656,225 @@ impl ComputeServer {
             "".to_string()
         };
 
+        let metrics_msg = if let Some(listener) = &self.metrics_listener {
+            format!(
+                "Metrics available at: http://{}{METRICS_PATH}",
+                listener.local_addr()?
+            )
+        } else {
+            "".to_string()
+        };
+
         info!(
             "Starting GlareDB {}\n{}",
             env!("CARGO_PKG_VERSION"),
-            [rpc_msg, pg_msg].join("\n"),
+            [rpc_msg, pg_msg, metrics_msg].join("\n"),
         );
 
-        // Shutdown handler.
-
-        let engine = self.engine.clone();
-        let mut rx = spawn_shutdown_handler(engine, self.integration_testing);
-
         // Start rpc service.
-        if self.rpc_listener.is_some() {
+        let (stop_rpc_tx, mut stop_rpc_rx) = watch::channel(false);
+        let mut rpc_handle = if self.rpc_listener.is_some() {
             let server = self.build_rpc_service();
-            tokio::spawn(async move {
+            Some(tokio::spawn(async move {
                 let incoming =
                     TcpIncoming::from_listener(self.rpc_listener.unwrap(), true, None).unwrap();
 
-                if let Err(e) = server.serve_with_incoming(incoming).await {
+                // Stops accepting connections and waits for in-flight
+                // requests once signaled.
+                let stop = async move {
+                    let _ = stop_rpc_rx.wait_for(|stop| *stop).await;
+                };
+                if let Err(e) = server.serve_with_incoming_shutdown(incoming, stop).await {
                     // TODO: Maybe panic instead? Revisit once we have
                     // everything working.
                     error!(%e, "rpc service died");
                 }
+            }))
+        } else {
+            None
+        };
+
+        // Start metrics endpoint.
+        if let Some(listener) = self.metrics_listener {
+            let jwt_authenticator = self.jwt_authenticator.clone();
+            tokio::spawn(async move {
+                if let Err(e) = serve_metrics(listener, jwt_authenticator).await {
+                    error!(%e, "metrics service died");
+                }
             });
         }
 
-        if let Some(PostgresProtocolConfig { listener, handler }) = self.pg_config {
+        let reload_handle = self
+            .config_reloader
+            .map(|reloader| tokio::spawn(reload_on_signal(reloader)));
+
+        tokio::pin!(shutdown);
+        let pg_handler = if let Some(PostgresProtocolConfig { listener, handler }) = self.pg_config
+        {
             // Postgres handler loop.
             loop {
                 tokio::select! {
-                    _ = &mut rx => {
-                        info!("shutting down");
-                        return Ok(())
-                    }
+                    _ = &mut shutdown => break,
 
-                result = listener.accept() => {
-                    let (conn, client_addr) = result?;
+                    result = listener.accept() => {
+                        let (conn, client_addr) = result?;
 
-                    let pg_handler = handler.clone();
-                    let conn_id = Uuid::new_v4();
-                    let span = debug_span!("glaredb_connection", %conn_id);
+                        let pg_handler = handler.clone();
+                        let conn_id = Uuid::new_v4();
+                        let span = debug_span!("glaredb_connection", %conn_id);
 
-                    tokio::spawn(
-                        async move {
-                            debug!(%client_addr, "client connected (pg)");
-                            match pg_handler.handle_connection(conn_id, conn).await {
-                                Ok(_) => debug!(%client_addr, "client disconnected"),
-                                Err(e) => debug!(%e, %client_addr, "client disconnected with error"),
+                        tokio::spawn(
+                            async move {
+                                debug!(%client_addr, "client connected (pg)");
+                                match pg_handler.handle_connection(conn_id, conn).await {
+                                    Ok(_) => debug!(%client_addr, "client disconnected"),
+                                    Err(e) => debug!(%e, %client_addr, "client disconnected with error"),
+                                }
                             }
-                        }
-                        .instrument(span),
-                    );
-                }
+                            .instrument(span),
+                        );
+                    }
                 }
             }
+            // Listener dropped, no longer accepting connections.
+            Some(handler)
         } else {
             // No pg listener. Just wait for shutdown.
-            rx.await.map_err(|_| anyhow!("shutdown error"))
+            shutdown.await;
+            None
+        };
+
+        info!("shutting down, no longer accepting connections");
+        if let Some(handle) = reload_handle {
+            handle.abort();
+        }
+        let _ = stop_rpc_tx.send(true);
+        if let Some(handler) = &pg_handler {
+            handler.drain();
+        }
+
+        // Don't wait for active sessions when integration testing. This helps
+        // when doing "CTRL-C" during testing.
+        let grace_period = if self.integration_testing {
+            Duration::ZERO
+        } else {
+            self.shutdown_grace_period
+        };
+        let deadline = Instant::now() + grace_period;
+
+        if !wait_for_sessions(&self.engine, deadline).await {
+            warn!(
+                sess_count = %self.engine.session_count(),
+                "shutdown grace period elapsed, canceling remaining queries"
+            );
+            if let Some(handler) = &pg_handler {
+                handler.terminate();
+            }
+            wait_for_sessions(&self.engine, Instant::now() + TERMINATE_TIMEOUT).await;
+        }
+
+        if let Some(handle) = &mut rpc_handle {
+            if tokio::time::timeout_at(deadline, &mut *handle)
+                .await
+                .is_err()
+            {
+                warn!("shutdown grace period elapsed, canceling remaining rpc requests");
+                handle.abort();
+            }
         }
+
+        self.engine.shutdown().await;
+        info!("shutdown complete");
+
+        Ok(())
     }
 }
 
-fn spawn_shutdown_handler(
-    engine: Arc<Engine>,
-    is_integration_testing: bool,
-) -> oneshot::Receiver<()> {
-    let (tx, rx) = oneshot::channel();
-    tokio::spawn(async move {
-        match signal::ctrl_c().await {
-            Ok(()) => {
-                info!("shutdown triggered");
-
-                // Don't wait for active-sessions if integration testing is
-                // not set. This helps when doing "CTRL-C" during testing.
-                if !is_integration_testing {
-                    loop {
-                        let sess_count = engine.session_count();
-                        if sess_count == 0 {
-                            break;
-                        }
-
-                        info!(%sess_count, "shutdown prevented, active sessions");
-
-                        // Still have sessions. Keep looping with some sleep in
-                        // between.
-                        tokio::time::sleep(tokio::time::Duration::from_millis(5000)).await;
-                    }
-                }
+/// Wait for all sessions to end, returning false if there are still sessions
+/// at the deadline.
+async fn wait_for_sessions(engine: &Engine, deadline: Instant) -> bool {
+    let mut interval = tokio::time::interval(Duration::from_millis(100));
+    let mut logged = false;
+    loop {
+        let sess_count = engine.session_count();
+        if sess_count == 0 {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+
+        if !logged {
+            info!(%sess_count, "waiting for active sessions to finish");
+            logged = true;
+        }
+        interval.tick().await;
+    }
+}
+
+/// Resolves once the process is asked to shut down, either through Ctrl-C
+/// (SIGINT) or SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = signal::ctrl_c().await {
+            error!(%e, "unable to listen for ctrl-c");
+            std::future::pending::<()>().await
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(e) => {
+                error!(%e, "unable to listen for SIGTERM");
+                std::future::pending::<()>().await
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("received ctrl-c, shutdown triggered"),
+        _ = terminate => info!("received SIGTERM, shutdown triggered"),
+    }
+}
 
-                // Shutdown!
-                let _ = tx.send(());
+/// Reload configuration every time the process receives SIGHUP.
+async fn reload_on_signal(reloader: ConfigReloader) {
+    #[cfg(unix)]
+    {
+        let mut hangup = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+            Ok(sig) => sig,
+            Err(e) => {
+                error!(%e, "unable to listen for SIGHUP");
+                return;
             }
-            Err(err) => {
-                error!(%err, "unable to listen for shutdown signal");
+        };
+        while hangup.recv().await.is_some() {
+            info!("received SIGHUP, reloading configuration");
+            if let Err(e) = reloader.reload() {
+                error!(%e, "failed to reload configuration");
             }
         }
-    });
-    rx
+    }
+    #[cfg(not(unix))]
+    let _ = reloader;
 }
 
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
 
-    use pgsrv::auth::SingleUserAuthenticator;
+    use pgsrv::auth::{ScramAuthenticator, SingleUserAuthenticator};
+    use rpcsrv::jwt::JwtConfig;
+    use sqlexec::scram::ScramVerifier;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::oneshot;
+    use tokio_postgres::error::SqlState;
     use tokio_postgres::{Config as ClientConfig, NoTls};
 
     use super::*;
@@ -526,4 +931,420 @@ mod tests {
             .unwrap() // Timeout error
             .unwrap(); // Query error
     }
+
+    #[tokio::test]
+    async fn serves_metrics() {
+        let pg_listener = TcpListener::bind("localhost:0").await.unwrap();
+        let metrics_listener = TcpListener::bind("localhost:0").await.unwrap();
+
+        let pg_addr = pg_listener.local_addr().unwrap();
+        let metrics_addr = metrics_listener.local_addr().unwrap();
+
+        let server = ComputeServer::builder()
+            .with_authenticator(SingleUserAuthenticator {
+                user: "glaredb".to_string(),
+                password: "glaredb".to_string(),
+            })
+            .with_pg_listener(pg_listener)
+            .with_metrics_listener(metrics_listener)
+            .connect()
+            .await
+            .unwrap();
+
+        tokio::spawn(server.serve());
+
+        let (client, conn) = ClientConfig::new()
+            .user("glaredb")
+            .password("glaredb")
+            .dbname("glaredb")
+            .host("localhost")
+            .port(pg_addr.port())
+            .connect(NoTls)
+            .await
+            .unwrap();
+        tokio::spawn(conn);
+
+        client.simple_query("select 1").await.unwrap();
+
+        let mut stream = tokio::net::TcpStream::connect(metrics_addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut resp = String::new();
+        tokio::time::timeout(Duration::from_secs(5), stream.read_to_string(&mut resp))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(
+            resp.contains("glaredb_queries_total{status=\"success\"}"),
+            "{resp}"
+        );
+        assert!(resp.contains("glaredb_active_sessions"), "{resp}");
+    }
+
+    #[tokio::test]
+    async fn metrics_require_bearer_token() {
+        let metrics_listener = TcpListener::bind("localhost:0").await.unwrap();
+        let metrics_addr = metrics_listener.local_addr().unwrap();
+
+        let jwt_authenticator = JwtAuthenticator::with_keys(
+            JwtConfig {
+                jwks_url: "http://localhost/jwks".to_string(),
+                issuer: "https://sso.example.com".to_string(),
+                audience: None,
+                user_claim: JwtConfig::DEFAULT_USER_CLAIM.to_string(),
+                roles_claim: None,
+                required_roles: Vec::new(),
+                admin_roles: Vec::new(),
+            },
+            serde_json::from_str(r#"{"keys": []}"#).unwrap(),
+        );
+        let server = ComputeServer::builder()
+            .with_metrics_listener(metrics_listener)
+            .with_jwt_authenticator(Arc::new(jwt_authenticator))
+            .connect()
+            .await
+            .unwrap();
+        tokio::spawn(server.serve());
+
+        for request in [
+            "GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            "GET /metrics HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer abc\r\nConnection: close\r\n\r\n",
+        ] {
+            let mut stream = tokio::net::TcpStream::connect(metrics_addr).await.unwrap();
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut resp = String::new();
+            tokio::time::timeout(Duration::from_secs(5), stream.read_to_string(&mut resp))
+                .await
+                .unwrap()
+                .unwrap();
+
+            assert!(resp.starts_with("HTTP/1.1 401 Unauthorized"), "{resp}");
+        }
+    }
+
+    #[tokio::test]
+    async fn closes_connections_on_shutdown() {
+        let pg_listener = TcpListener::bind("localhost:0").await.unwrap();
+        let pg_addr = pg_listener.local_addr().unwrap();
+
+        let server = ComputeServer::builder()
+            .with_authenticator(SingleUserAuthenticator {
+                user: "glaredb".to_string(),
+                password: "glaredb".to_string(),
+            })
+            .with_pg_listener(pg_listener)
+            .with_shutdown_grace_period(Duration::from_secs(30))
+            .connect()
+            .await
+            .unwrap();
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server_handle = tokio::spawn(server.serve_with_shutdown(async move {
+            let _ = shutdown_rx.await;
+        }));
+
+        let (client, conn) = tokio::time::timeout(
+            Duration::from_secs(5),
+            ClientConfig::new()
+                .user("glaredb")
+                .password("glaredb")
+                .dbname("glaredb")
+                .host("localhost")
+                .port(pg_addr.port())
+                .connect(NoTls),
+        )
+        .await
+        .unwrap() // Timeout error
+        .unwrap(); // Connect error
+        let conn_handle = tokio::spawn(conn);
+
+        tokio::time::timeout(Duration::from_secs(5), client.simple_query("select 1"))
+            .await
+            .unwrap() // Timeout error
+            .unwrap(); // Query error
+
+        shutdown_tx.send(()).unwrap();
+
+        // Idle connections are closed right away, letting the client know why.
+        let err = tokio::time::timeout(Duration::from_secs(5), conn_handle)
+            .await
+            .unwrap() // Timeout error
+            .unwrap() // Join error
+            .unwrap_err();
+        assert_eq!(Some(&SqlState::ADMIN_SHUTDOWN), err.code());
+
+        // And the server exits without waiting for the grace period.
+        tokio::time::timeout(Duration::from_secs(5), server_handle)
+            .await
+            .unwrap() // Timeout error
+            .unwrap() // Join error
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn error_codes() {
+        let pg_listener = TcpListener::bind("localhost:0").await.unwrap();
+        let pg_addr = pg_listener.local_addr().unwrap();
+
+        let server = ComputeServer::builder()
+            .with_authenticator(SingleUserAuthenticator {
+                user: "glaredb".to_string(),
+                password: "glaredb".to_string(),
+            })
+            .with_pg_listener(pg_listener)
+            .connect()
+            .await
+            .unwrap();
+
+        tokio::spawn(server.serve());
+
+        let (client, conn) = tokio::time::timeout(
+            Duration::from_secs(5),
+            ClientConfig::new()
+                .user("glaredb")
+                .password("glaredb")
+                .dbname("glaredb")
+                .host("localhost")
+                .port(pg_addr.port())
+                .connect(NoTls),
+        )
+        .await
+        .unwrap() // Timeout error
+        .unwrap(); // Connect error
+        tokio::spawn(conn);
+
+        let cases = [
+            ("selec 1", SqlState::SYNTAX_ERROR),
+            ("select * from missing_table", SqlState::UNDEFINED_TABLE),
+            (
+                "select missing_column from (select 1 as a)",
+                SqlState::UNDEFINED_COLUMN,
+            ),
+            (
+                "select * from missing_db.public.t",
+                SqlState::INVALID_CATALOG_NAME,
+            ),
+            ("show missing_variable", SqlState::UNDEFINED_OBJECT),
+        ];
+        for (query, code) in cases {
+            let err = client.simple_query(query).await.unwrap_err();
+            assert_eq!(Some(&code), err.code(), "query: {query}, error: {err}");
+        }
+
+        // The connection is still usable after errors.
+        client.simple_query("select 1").await.unwrap();
+    }
+
+    /// Start a server authenticating with SCRAM, configured by `builder`.
+    ///
+    /// The default user 'glaredb' with password 'glaredb' is an admin, and can
+    /// set passwords for other users. Returns the port to connect to.
+    async fn start_scram_server(builder: ComputeServerBuilder) -> u16 {
+        let pg_listener = TcpListener::bind("localhost:0").await.unwrap();
+        let port = pg_listener.local_addr().unwrap().port();
+
+        let server = builder
+            .with_authenticator(ScramAuthenticator {
+                default_user: Some((
+                    "glaredb".to_string(),
+                    ScramVerifier::new("glaredb").unwrap(),
+                )),
+            })
+            .with_pg_listener(pg_listener)
+            .connect()
+            .await
+            .unwrap();
+
+        tokio::spawn(server.serve());
+        port
+    }
+
+    /// Connect to the server listening on `port` as `user`.
+    async fn connect(
+        port: u16,
+        user: &str,
+        password: &str,
+    ) -> Result<tokio_postgres::Client, tokio_postgres::Error> {
+        let (client, conn) = tokio::time::timeout(
+            Duration::from_secs(5),
+            ClientConfig::new()
+                .user(user)
+                .password(password)
+                .dbname("glaredb")
+                .host("localhost")
+                .port(port)
+                .connect(NoTls),
+        )
+        .await
+        .unwrap()?; // Timeout error
+        tokio::spawn(conn);
+        Ok(client)
+    }
+
+    #[tokio::test]
+    async fn scram_auth() {
+        let port = start_scram_server(ComputeServer::builder()).await;
+
+        // Default user can set up passwords for other users.
+        let client = connect(port, "glaredb", "glaredb").await.unwrap();
+        client
+            .simple_query("ALTER ROLE sam PASSWORD 'secret'")
+            .await
+            .unwrap();
+
+        let client = connect(port, "sam", "secret").await.unwrap();
+        client.simple_query("select 1").await.unwrap();
+
+        connect(port, "sam", "wrong").await.unwrap_err();
+        connect(port, "glaredb", "wrong").await.unwrap_err();
+        connect(port, "missing", "secret").await.unwrap_err();
+
+        // Users can't change other users' passwords, or their own access.
+        for query in [
+            "ALTER ROLE glaredb PASSWORD 'mine'",
+            "ALTER ROLE missing PASSWORD 'mine'",
+            "ALTER ROLE sam READ WRITE",
+        ] {
+            let err = client.simple_query(query).await.unwrap_err();
+            assert!(
+                err.to_string().contains("Permission denied"),
+                "unexpected error for '{query}': {err}"
+            );
+        }
+        connect(port, "missing", "mine").await.unwrap_err();
+
+        // Removing the password stops the user from authenticating.
+        client
+            .simple_query("ALTER ROLE sam PASSWORD NULL")
+            .await
+            .unwrap();
+        connect(port, "sam", "secret").await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn restore_catalog_requires_admin() {
+        let port = start_scram_server(ComputeServer::builder()).await;
+
+        let admin = connect(port, "glaredb", "glaredb").await.unwrap();
+        admin
+            .simple_query("ALTER ROLE sam PASSWORD 'secret'")
+            .await
+            .unwrap();
+
+        let query = "RESTORE CATALOG TO TIMESTAMP '2023-10-01 12:00:00'";
+        let user = connect(port, "sam", "secret").await.unwrap();
+        let err = user.simple_query(query).await.unwrap_err();
+        assert!(
+            err.to_string().contains("Permission denied"),
+            "unexpected error: {err}"
+        );
+
+        // The admin isn't rejected, though there's no catalog to restore that
+        // far back.
+        if let Err(err) = admin.simple_query(query).await {
+            assert!(
+                !err.to_string().contains("Permission denied"),
+                "unexpected error: {err}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn query_history_only_shows_own_queries_to_non_admins() {
+        let query_history = QueryHistory::new(QueryHistory::DEFAULT_RETENTION);
+        let port =
+            start_scram_server(ComputeServer::builder().with_query_history(query_history.clone()))
+                .await;
+
+        let admin = connect(port, "glaredb", "glaredb").await.unwrap();
+        admin
+            .simple_query("ALTER ROLE sam PASSWORD 'secret'")
+            .await
+            .unwrap();
+        admin.simple_query("SELECT 'admin query'").await.unwrap();
+
+        let user = connect(port, "sam", "secret").await.unwrap();
+        user.simple_query("SELECT 'user query'").await.unwrap();
+
+        query_history.flush().await;
+
+        let history = |client: tokio_postgres::Client| async move {
+            let messages = client
+                .simple_query("SELECT query_text FROM system.query_history")
+                .await
+                .unwrap();
+            messages
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    tokio_postgres::SimpleQueryMessage::Row(row) => {
+                        Some(row.get(0).unwrap().to_string())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let queries = history(user).await;
+        assert!(queries.iter().any(|q| q.contains("user query")));
+        assert!(
+            !queries.iter().any(|q| q.contains("admin query")),
+            "user sees admin queries: {queries:?}"
+        );
+
+        let queries = history(admin).await;
+        assert!(queries.iter().any(|q| q.contains("user query")));
+        assert!(queries.iter().any(|q| q.contains("admin query")));
+    }
+
+    #[tokio::test]
+    async fn plan_cache_keyed_by_timezone() {
+        let port =
+            start_scram_server(ComputeServer::builder().with_shared_plan_cache(PlanCache::new(16)))
+                .await;
+
+        let first = connect(port, "glaredb", "glaredb").await.unwrap();
+        first
+            .batch_execute(
+                "CREATE TABLE times (t TEXT);
+                 INSERT INTO times VALUES ('2024-01-01 00:00:00');
+                 SET TimeZone = 'UTC';",
+            )
+            .await
+            .unwrap();
+
+        // Prepared with the extended protocol, the same as clients using
+        // parameterized queries, so plans are cached.
+        async fn planned_type(client: &tokio_postgres::Client) -> String {
+            let rows = client
+                .query(
+                    "SELECT arrow_typeof(CAST(t AS TIMESTAMPTZ)) FROM times",
+                    &[],
+                )
+                .await
+                .unwrap();
+            rows[0].get(0)
+        }
+
+        assert!(planned_type(&first).await.contains("UTC"));
+        assert!(planned_type(&first).await.contains("UTC"));
+
+        // Changing the time zone needs a new plan.
+        first
+            .batch_execute("SET TimeZone = 'Europe/Berlin'")
+            .await
+            .unwrap();
+        let planned = planned_type(&first).await;
+        assert!(planned.contains("Europe/Berlin"), "{planned}");
+
+        // Other sessions sharing the cache don't get plans for a different
+        // time zone either.
+        let second = connect(port, "glaredb", "glaredb").await.unwrap();
+        second.batch_execute("SET TimeZone = 'UTC'").await.unwrap();
+        let planned = planned_type(&second).await;
+        assert!(planned.contains("UTC"), "{planned}");
+    }
 }
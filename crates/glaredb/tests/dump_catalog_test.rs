@@ -0,0 +1,54 @@
+mod setup;
+
+use crate::setup::{make_cli, DEFAULT_TIMEOUT};
+
+#[test]
+/// ./glaredb dump-catalog <FILE> -f <DIR>
+/// ./glaredb load-catalog <FILE> -f <DIR>
+fn test_dump_and_load_catalog() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+    let dump_dir = tempfile::tempdir().unwrap();
+    let dump = dump_dir.path().join("catalog.dump");
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("-f")
+        .arg(source_dir.path())
+        .args([
+            "-q",
+            "CREATE SCHEMA mushroom; CREATE VIEW mushroom.kingdom AS SELECT 42 AS answer;",
+        ])
+        .assert()
+        .success();
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("dump-catalog")
+        .arg(&dump)
+        .arg("-f")
+        .arg(source_dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Catalog exported"));
+    assert!(dump.exists());
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("load-catalog")
+        .arg(&dump)
+        .arg("-f")
+        .arg(dest_dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Catalog imported"));
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("-f")
+        .arg(dest_dir.path())
+        .args(["-q", "SELECT answer FROM mushroom.kingdom", "--mode", "csv"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("42"));
+}
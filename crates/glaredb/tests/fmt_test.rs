@@ -0,0 +1,51 @@
+mod setup;
+
+use crate::setup::{make_cli, DEFAULT_TIMEOUT};
+
+#[test]
+/// echo "<QUERY>" | ./glaredb fmt
+fn test_fmt_stdin() {
+    let mut cmd = make_cli();
+
+    let assert = cmd
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("fmt")
+        .write_stdin("select a, b from t where a > 1")
+        .assert();
+    assert
+        .success()
+        .stdout("SELECT\n    a,\n    b\nFROM t\nWHERE a > 1\n");
+}
+
+#[test]
+/// ./glaredb fmt [--check] <DIR>
+fn test_fmt_files() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file = temp_dir.path().join("query.sql");
+    std::fs::write(&file, "select 1;").unwrap();
+
+    // Checking shouldn't modify the file.
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .args(["fmt", "--check"])
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("query.sql"));
+    assert_eq!("select 1;", std::fs::read_to_string(&file).unwrap());
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("fmt")
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+    assert_eq!("SELECT 1;\n", std::fs::read_to_string(&file).unwrap());
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .args(["fmt", "--check"])
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+}
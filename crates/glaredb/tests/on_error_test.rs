@@ -0,0 +1,73 @@
+mod setup;
+
+use predicates::boolean::PredicateBooleanExt;
+
+use crate::setup::{make_cli, DEFAULT_TIMEOUT};
+
+const QUERY: &str = "select 1 as a;\nselect * from missing_table;\nselect 2 as b;";
+
+#[test]
+/// ./glaredb -q <QUERY>
+fn test_on_error_stop() {
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .args(["--mode", "csv", "-q", QUERY])
+        .assert()
+        .code(1)
+        .stdout("a\n1\n")
+        .stderr(predicates::str::contains("missing_table"));
+}
+
+#[test]
+/// ./glaredb --on-error continue -q <QUERY>
+fn test_on_error_continue() {
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .args(["--mode", "csv", "--on-error", "continue", "-q", QUERY])
+        .assert()
+        .code(1)
+        .stdout("a\n1\nb\n2\n")
+        .stderr(predicates::str::contains("missing_table"));
+}
+
+#[test]
+/// ./glaredb --on-error continue --error-format json -q <QUERY>
+fn test_error_format_json() {
+    let output = make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .args(["--on-error", "continue", "--error-format", "json", "-q"])
+        .arg(format!("{QUERY}\nselect * from other_missing_table;"))
+        .output()
+        .unwrap();
+    assert_eq!(Some(1), output.status.code());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let errors: Vec<serde_json::Value> = stderr
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(2, errors.len(), "{stderr}");
+
+    assert_eq!(2, errors[0]["line"]);
+    assert_eq!("select * from missing_table;", errors[0]["statement"]);
+    assert!(errors[0]["file"].is_null());
+    assert!(errors[0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("missing_table"));
+    assert_eq!(4, errors[1]["line"]);
+}
+
+#[test]
+/// Errors outside of statements use the same format.
+fn test_error_format_json_missing_file() {
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .args(["--error-format", "json", "missing_file.sql"])
+        .assert()
+        .code(1)
+        .stderr(
+            predicates::str::starts_with("{\"error\":")
+                .and(predicates::str::contains("does not exist")),
+        );
+}
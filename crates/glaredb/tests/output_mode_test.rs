@@ -68,3 +68,77 @@ fn test_output_mode_ndjson() {
     .trim_start();
     test_output_mode("ndjson", expected);
 }
+
+#[test]
+/// ./glaredb -q <QUERY> --mode markdown
+fn test_output_mode_markdown() {
+    let expected = r#"
+| Int64(1) |
+| -------: |
+|        1 |
+"#
+    .trim_start();
+    test_output_mode("markdown", expected);
+}
+
+#[test]
+/// ./glaredb -q <QUERY> --mode latex
+fn test_output_mode_latex() {
+    let expected = r#"
+\begin{tabular}{r}
+\hline
+Int64(1) \\
+\hline
+1 \\
+\hline
+\end{tabular}
+"#
+    .trim_start();
+    test_output_mode("latex", expected);
+}
+
+#[test]
+/// ./glaredb -q <QUERY> --mode expanded
+fn test_output_mode_expanded() {
+    let expected = r#"
+-[ RECORD 1 ]
+Int64(1) | 1
+"#
+    .trim_start();
+    test_output_mode("expanded", expected);
+}
+
+#[test]
+/// ./glaredb -q <QUERY> --mode arrow
+fn test_output_mode_arrow() {
+    let mut cmd = make_cli();
+
+    cmd.timeout(DEFAULT_TIMEOUT)
+        .arg("--mode")
+        .arg("arrow")
+        .arg("-q")
+        .arg("select 1;");
+    let output = cmd.output().expect("Failed to run command");
+
+    // Arrow IPC files start and end with the magic bytes.
+    assert!(output.stdout.starts_with(b"ARROW1"));
+    assert!(output.stdout.ends_with(b"ARROW1"));
+}
+
+#[test]
+/// ./glaredb -q <QUERY> --mode markdown --null-display <TEXT>
+fn test_null_display() {
+    let mut cmd = make_cli();
+
+    cmd.timeout(DEFAULT_TIMEOUT)
+        .arg("--mode")
+        .arg("markdown")
+        .arg("--null-display")
+        .arg("(null)")
+        .arg("-q")
+        .arg("select null as a;");
+    let output = cmd.output().expect("Failed to run command");
+    let stdout = String::from_utf8(output.stdout).expect("Failed to read stdout");
+
+    assert!(stdout.contains("| (null) |"), "{stdout}");
+}
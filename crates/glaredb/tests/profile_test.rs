@@ -0,0 +1,60 @@
+mod setup;
+
+use std::io::Write;
+
+use setup::DEFAULT_TIMEOUT;
+
+use crate::setup::make_cli;
+
+fn write_config(contents: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(contents.as_bytes()).unwrap();
+    file
+}
+
+#[test]
+/// ./glaredb --config <PATH> --profile <NAME> -q <QUERY>
+fn test_profile_sets_options() {
+    let config = write_config("[profiles.work]\nmode = \"csv\"\n");
+
+    let mut cmd = make_cli();
+    let assert = cmd
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("--config")
+        .arg(config.path())
+        .args(["--profile", "work", "-q", "select 1;"])
+        .assert();
+    assert.success().stdout("Int64(1)\n1\n");
+}
+
+#[test]
+/// ./glaredb --config <PATH> --profile <NAME> --mode <MODE> -q <QUERY>
+fn test_cli_overrides_profile() {
+    let config = write_config("[profiles.work]\nmode = \"csv\"\n");
+
+    let mut cmd = make_cli();
+    let assert = cmd
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("--config")
+        .arg(config.path())
+        .args(["--profile", "work", "--mode", "ndjson", "-q", "select 1;"])
+        .assert();
+    assert.success().stdout("{\"Int64(1)\":1}\n");
+}
+
+#[test]
+/// ./glaredb --config <PATH> --profile <MISSING> -q <QUERY>
+fn test_unknown_profile() {
+    let config = write_config("[profiles.work]\nmode = \"csv\"\n");
+
+    let mut cmd = make_cli();
+    let assert = cmd
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("--config")
+        .arg(config.path())
+        .args(["--profile", "home", "-q", "select 1;"])
+        .assert();
+    assert
+        .failure()
+        .stderr(predicates::str::contains("Unknown profile 'home'"));
+}
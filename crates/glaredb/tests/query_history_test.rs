@@ -0,0 +1,32 @@
+mod setup;
+
+use crate::setup::{make_cli, DEFAULT_TIMEOUT};
+
+#[test]
+/// ./glaredb -f <DIR> --query-history
+fn test_query_history_persisted() {
+    let data_dir = tempfile::tempdir().unwrap();
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("-f")
+        .arg(data_dir.path())
+        .args(["--query-history", "-q", "SELECT 'mushroom'"])
+        .assert()
+        .success();
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("-f")
+        .arg(data_dir.path())
+        .args([
+            "-q",
+            "SELECT query_text, execution_status FROM system.query_history",
+            "--mode",
+            "csv",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("mushroom"))
+        .stdout(predicates::str::contains("success"));
+}
@@ -0,0 +1,46 @@
+mod setup;
+
+use crate::setup::{make_cli, DEFAULT_TIMEOUT};
+
+#[test]
+/// ./glaredb -q "\i script.sql" --mode csv
+fn test_include_script_with_variables() {
+    let dir = tempfile::tempdir().unwrap();
+    let inner = dir.path().join("inner.sql");
+    std::fs::write(&inner, "select :n * 2 as doubled;\n").unwrap();
+
+    let outer = dir.path().join("outer.sql");
+    std::fs::write(
+        &outer,
+        format!(
+            "\\set n 3\nselect\n  :'greeting' as greeting;\n\\i {}\n",
+            inner.display()
+        ),
+    )
+    .unwrap();
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .args(["--mode", "csv", "-q"])
+        .arg(format!("\\set greeting hi\n\\i {}", outer.display()))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("greeting\nhi\n"))
+        .stdout(predicates::str::contains("doubled\n6\n"));
+}
+
+#[test]
+/// Errors in a script are reported with the line they occurred on.
+fn test_include_script_error_location() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("script.sql");
+    std::fs::write(&script, "select 1;\n\nselect * from missing_table;\n").unwrap();
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("-q")
+        .arg(format!("\\i {}", script.display()))
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("script.sql:3:"));
+}
@@ -0,0 +1,44 @@
+mod setup;
+
+use crate::setup::{make_cli, DEFAULT_TIMEOUT};
+use predicates::boolean::PredicateBooleanExt;
+
+#[test]
+/// ALTER DATABASE ... SET should apply to new sessions.
+fn test_database_session_var_defaults() {
+    let data_dir = tempfile::tempdir().unwrap();
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("-f")
+        .arg(data_dir.path())
+        .args(["-q", "ALTER DATABASE default SET statement_timeout = 1234"])
+        .assert()
+        .success();
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("-f")
+        .arg(data_dir.path())
+        .args(["-q", "SHOW statement_timeout", "--mode", "csv"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("1234"));
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("-f")
+        .arg(data_dir.path())
+        .args(["-q", "ALTER DATABASE default RESET statement_timeout"])
+        .assert()
+        .success();
+
+    make_cli()
+        .timeout(DEFAULT_TIMEOUT)
+        .arg("-f")
+        .arg(data_dir.path())
+        .args(["-q", "SHOW statement_timeout", "--mode", "csv"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("1234").not());
+}
@@ -1,17 +1,51 @@
 //! Utilities for logging and tracing.
-use std::{fs::File, path::PathBuf, sync::Arc};
+pub mod otel;
 
-use tracing::{subscriber, trace, Level};
+use std::{
+    fmt,
+    fs::File,
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
+
+use tracing::{
+    subscriber::{self, SetGlobalDefaultError},
+    trace, Level, Subscriber,
+};
 use tracing_subscriber::{
-    filter::EnvFilter,
+    filter::{EnvFilter, ParseError},
     fmt::{
         format::{Compact, DefaultFields, Format, Json, JsonFields, Pretty, Writer},
         time::FormatTime,
         SubscriberBuilder,
     },
-    FmtSubscriber,
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    reload, FmtSubscriber,
 };
 
+/// Replaces the filter of the global subscriber.
+type FilterReloader = Box<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>;
+
+/// Set when initializing the global subscriber, along with the level used
+/// when no directives are provided.
+static FILTER_RELOADER: OnceLock<(Level, FilterReloader)> = OnceLock::new();
+
+/// Enable reloading the filter of a subscriber builder, register the reload
+/// handle, and finish building the subscriber.
+///
+/// A macro since the type of the handle depends on the writer and formatter
+/// used.
+macro_rules! finish_reloadable {
+    ($builder:expr, $level:expr) => {{
+        let builder = $builder.with_filter_reloading();
+        let handle = builder.reload_handle();
+        let reloader: FilterReloader = Box::new(move |filter: EnvFilter| handle.reload(filter));
+        let _ = FILTER_RELOADER.set(($level, reloader));
+        builder.finish()
+    }};
+}
+
 #[derive(Debug)]
 pub enum Verbosity {
     Info,
@@ -72,7 +106,15 @@ pub fn init_test() {
 
 /// Initialize a trace subsriber printing to the console using the given
 /// verbosity count.
-pub fn init(verbosity: impl Into<Verbosity>, mode: LoggingMode, log_file: Option<&PathBuf>) {
+///
+/// If an OTLP endpoint is provided, spans will also be exported to that
+/// endpoint.
+pub fn init(
+    verbosity: impl Into<Verbosity>,
+    mode: LoggingMode,
+    log_file: Option<&PathBuf>,
+    otlp_endpoint: Option<&str>,
+) {
     let verbosity: Verbosity = verbosity.into();
     let level: Level = verbosity.into();
 
@@ -88,13 +130,20 @@ pub fn init(verbosity: impl Into<Verbosity>, mode: LoggingMode, log_file: Option
                     Ok(file) => Arc::new(file),
                     Err(_) => {
                         eprintln!("Failed to create file: {:#?}", file);
-                        return subscriber::set_global_default(subscriber.finish()).unwrap();
+                        return set_global_default(
+                            finish_reloadable!(subscriber, level),
+                            otlp_endpoint,
+                        )
+                        .unwrap();
                     }
                 };
 
-                subscriber::set_global_default(subscriber.with_writer(debug_log).finish())
+                set_global_default(
+                    finish_reloadable!(subscriber.with_writer(debug_log), level),
+                    otlp_endpoint,
+                )
             } else {
-                subscriber::set_global_default(subscriber.finish())
+                set_global_default(finish_reloadable!(subscriber, level), otlp_endpoint)
             }
         }
         LoggingMode::Full => {
@@ -105,13 +154,20 @@ pub fn init(verbosity: impl Into<Verbosity>, mode: LoggingMode, log_file: Option
                     Ok(file) => Arc::new(file),
                     Err(_) => {
                         eprintln!("Failed to create file: {:#?}", file);
-                        return subscriber::set_global_default(subscriber.finish()).unwrap();
+                        return set_global_default(
+                            finish_reloadable!(subscriber, level),
+                            otlp_endpoint,
+                        )
+                        .unwrap();
                     }
                 };
 
-                subscriber::set_global_default(subscriber.with_writer(debug_log).finish())
+                set_global_default(
+                    finish_reloadable!(subscriber.with_writer(debug_log), level),
+                    otlp_endpoint,
+                )
             } else {
-                subscriber::set_global_default(subscriber.finish())
+                set_global_default(finish_reloadable!(subscriber, level), otlp_endpoint)
             }
         }
         LoggingMode::Compact => {
@@ -121,13 +177,20 @@ pub fn init(verbosity: impl Into<Verbosity>, mode: LoggingMode, log_file: Option
                     Ok(file) => Arc::new(file),
                     Err(_) => {
                         eprintln!("Failed to create file: {:#?}", file);
-                        return subscriber::set_global_default(subscriber.finish()).unwrap();
+                        return set_global_default(
+                            finish_reloadable!(subscriber, level),
+                            otlp_endpoint,
+                        )
+                        .unwrap();
                     }
                 };
 
-                subscriber::set_global_default(subscriber.with_writer(debug_log).finish())
+                set_global_default(
+                    finish_reloadable!(subscriber.with_writer(debug_log), level),
+                    otlp_endpoint,
+                )
             } else {
-                subscriber::set_global_default(subscriber.finish())
+                set_global_default(finish_reloadable!(subscriber, level), otlp_endpoint)
             }
         }
     }
@@ -136,6 +199,88 @@ pub fn init(verbosity: impl Into<Verbosity>, mode: LoggingMode, log_file: Option
     trace!(set_level = %level, "log level set");
 }
 
+/// Replace the filter used for logging with one parsed from `directives`,
+/// using the same syntax as `RUST_LOG`.
+///
+/// If `directives` is `None`, the filter logging was initialized with is
+/// restored. Noisy dependencies are raised to INFO the same as during
+/// initialization.
+pub fn set_log_filter(directives: Option<&str>) -> Result<(), SetLogFilterError> {
+    let (level, reloader) = FILTER_RELOADER
+        .get()
+        .ok_or(SetLogFilterError::NotInitialized)?;
+
+    let filter = match directives {
+        Some(directives) => with_default_directives(
+            EnvFilter::builder()
+                .with_default_directive((*level).into())
+                .parse(directives)?,
+        ),
+        None => env_filter(*level),
+    };
+    reloader(filter)?;
+
+    trace!(?directives, "log filter set");
+    Ok(())
+}
+
+/// Error setting the log filter.
+#[derive(Debug)]
+pub enum SetLogFilterError {
+    /// Logging wasn't initialized with `init`.
+    NotInitialized,
+    /// Invalid filter directives.
+    Parse(ParseError),
+    /// Failed to replace the filter.
+    Reload(reload::Error),
+}
+
+impl fmt::Display for SetLogFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInitialized => write!(f, "Logging not initialized"),
+            Self::Parse(e) => write!(f, "Invalid log filter: {e}"),
+            Self::Reload(e) => write!(f, "Failed to set log filter: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SetLogFilterError {}
+
+impl From<ParseError> for SetLogFilterError {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<reload::Error> for SetLogFilterError {
+    fn from(e: reload::Error) -> Self {
+        Self::Reload(e)
+    }
+}
+
+/// Set the global default subscriber, adding a layer for exporting spans if an
+/// OTLP endpoint is provided.
+///
+/// Failing to set up exporting is reported on stderr, but doesn't prevent logging to the
+/// console.
+fn set_global_default<S>(
+    subscriber: S,
+    otlp_endpoint: Option<&str>,
+) -> Result<(), SetGlobalDefaultError>
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + Send + Sync + 'static,
+{
+    let otel = otlp_endpoint.and_then(|endpoint| match otel::layer(endpoint) {
+        Ok(layer) => Some(layer),
+        Err(e) => {
+            eprintln!("Failed to initialize OpenTelemetry exporter: {e}");
+            None
+        }
+    });
+    subscriber::set_global_default(subscriber.with(otel))
+}
+
 struct PrettyTime;
 impl FormatTime for PrettyTime {
     fn format_time(&self, w: &mut Writer<'_>) -> std::fmt::Result {
@@ -205,9 +350,15 @@ fn json_fmt(level: Level) -> SubscriberBuilder<JsonFields, Format<Json>> {
 /// - Raise h2 to INFO, since it's very noisy at lower levels.
 /// - Raise hyper to INFO, since it's very noisy at lower levels.
 fn env_filter(level: Level) -> EnvFilter {
-    EnvFilter::builder()
-        .with_default_directive(level.into())
-        .from_env_lossy()
+    with_default_directives(
+        EnvFilter::builder()
+            .with_default_directive(level.into())
+            .from_env_lossy(),
+    )
+}
+
+fn with_default_directives(filter: EnvFilter) -> EnvFilter {
+    filter
         .add_directive("h2=info".parse().unwrap())
         .add_directive("hyper=info".parse().unwrap())
 }
@@ -0,0 +1,144 @@
+//! Exporting traces to an OpenTelemetry collector.
+//!
+//! Spans are exported using OTLP over gRPC. Trace context is propagated across
+//! RPCs using the W3C `traceparent` header, allowing remote execution to show
+//! up under the same trace as the query that triggered it.
+
+use std::sync::OnceLock;
+
+use opentelemetry::global;
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::TraceError;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{self, Tracer};
+use opentelemetry_sdk::{runtime, Resource};
+use tokio::runtime::{Builder, Runtime};
+use tonic::codegen::http::HeaderMap;
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+use tracing::{Span, Subscriber};
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Runtime used for exporting spans.
+///
+/// Logging is initialized before the runtime for the command being run is
+/// created, so the exporter gets its own runtime.
+static EXPORT_RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// Create a layer exporting spans to the collector at `endpoint`.
+pub(crate) fn layer<S>(endpoint: &str) -> Result<OpenTelemetryLayer<S, Tracer>, TraceError>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let export_runtime = match EXPORT_RUNTIME.get() {
+        Some(export_runtime) => export_runtime,
+        None => {
+            let export_runtime = Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("otel-export")
+                .enable_all()
+                .build()
+                .map_err(|e| TraceError::Other(Box::new(e)))?;
+            EXPORT_RUNTIME.get_or_init(|| export_runtime)
+        }
+    };
+    let _guard = export_runtime.enter();
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            trace::config()
+                .with_resource(Resource::new([KeyValue::new("service.name", "glaredb")])),
+        )
+        .install_batch(runtime::Tokio)?;
+
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// Flush any pending spans and stop exporting.
+///
+/// Does nothing if spans aren't being exported.
+pub fn shutdown() {
+    if EXPORT_RUNTIME.get().is_some() {
+        global::shutdown_tracer_provider();
+    }
+}
+
+/// Add the trace context for the current span to the metadata of an outgoing
+/// request.
+pub fn inject_trace_context(metadata: &mut MetadataMap) {
+    let cx = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&cx, &mut MetadataInjector(metadata))
+    });
+}
+
+/// Set the parent of `span` to the trace context found in the headers of an
+/// incoming request.
+pub fn set_trace_parent(span: &Span, headers: &HeaderMap) {
+    let cx =
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    span.set_parent(cx);
+}
+
+struct MetadataInjector<'a>(&'a mut MetadataMap);
+
+impl Injector for MetadataInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(key), Ok(value)) = (
+            MetadataKey::from_bytes(key.as_bytes()),
+            MetadataValue::try_from(value),
+        ) {
+            self.0.insert(key, value);
+        }
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_trace_context() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+        let mut headers = HeaderMap::new();
+        headers.insert("traceparent", traceparent.parse().unwrap());
+        let cx = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(&headers))
+        });
+
+        let mut metadata = MetadataMap::new();
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&cx, &mut MetadataInjector(&mut metadata))
+        });
+
+        assert_eq!(
+            traceparent,
+            metadata.get("traceparent").unwrap().to_str().unwrap()
+        );
+    }
+}
@@ -1,16 +1,19 @@
 //! Module for handling the catalog for a single database.
 use crate::errors::{MetastoreError, Result};
-use crate::storage::persist::Storage;
+use crate::storage::CatalogStorage;
 use once_cell::sync::Lazy;
 use pgrepr::oid::FIRST_AVAILABLE_ID;
 use protogen::metastore::types::catalog::{
-    CatalogEntry, CatalogState, CredentialsEntry, DatabaseEntry, DeploymentMetadata, EntryMeta,
-    EntryType, SchemaEntry, SourceAccessMode, TableEntry, TunnelEntry, ViewEntry,
+    CachedTableSchema, CatalogEntry, CatalogState, CredentialsEntry, DatabaseEntry,
+    DeploymentMetadata, EntryMeta, EntryType, RolePassword, SchemaEntry, SessionVarDefault,
+    SourceAccessMode, TableEntry, TableStatistics, TunnelEntry, ViewEntry,
 };
 use protogen::metastore::types::options::{
     DatabaseOptions, DatabaseOptionsInternal, TableOptions, TunnelOptions,
 };
-use protogen::metastore::types::service::{AlterDatabaseOperation, AlterTableOperation, Mutation};
+use protogen::metastore::types::service::{
+    AlterDatabaseOperation, AlterRoleOperation, AlterSchemaOperation, AlterTableOperation, Mutation,
+};
 use protogen::metastore::types::storage::{ExtraState, PersistedCatalog};
 use sqlbuiltins::builtins::{
     BuiltinDatabase, BuiltinSchema, BuiltinTable, BuiltinView, DATABASE_DEFAULT, DEFAULT_SCHEMA,
@@ -20,9 +23,10 @@ use sqlbuiltins::functions::FUNCTION_REGISTRY;
 use sqlbuiltins::validation::{
     validate_database_tunnel_support, validate_object_name, validate_table_tunnel_support,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::sync::{Mutex, MutexGuard};
 use tracing::debug;
 use uuid::Uuid;
@@ -44,14 +48,15 @@ static BUILTIN_CATALOG: Lazy<BuiltinCatalog> = Lazy::new(|| BuiltinCatalog::new(
 /// Synchronization happens at two levels:
 ///
 /// 1. The in-memory catalog state is wrapped in a mutex.
-/// 2. Persistence is managed via leases in object storage.
+/// 2. Persistence is managed by the catalog storage, either via leases in
+///    object storage or via transactions in Postgres.
 ///
-/// The source of truth for a database catalog is always what's in object store.
+/// The source of truth for a database catalog is always what's in storage.
 pub struct DatabaseCatalog {
     db_id: Uuid,
 
     /// Reference to underlying persistant storage.
-    storage: Arc<Storage>,
+    storage: Arc<CatalogStorage>,
 
     /// A cached catalog state for a single database.
     cached: Mutex<State>,
@@ -64,7 +69,7 @@ pub struct DatabaseCatalog {
 
 impl DatabaseCatalog {
     /// Open the catalog for a database.
-    pub async fn open(db_id: Uuid, storage: Arc<Storage>) -> Result<DatabaseCatalog> {
+    pub async fn open(db_id: Uuid, storage: Arc<CatalogStorage>) -> Result<DatabaseCatalog> {
         // Always initialize, idempotent.
         storage.initialize(db_id).await?;
 
@@ -88,6 +93,17 @@ impl DatabaseCatalog {
         Ok(self.serializable_state(state))
     }
 
+    /// Get the password verifiers of all roles.
+    ///
+    /// These aren't part of the serializable state, which is sent on to
+    /// clients, and are only used for authenticating connections.
+    pub async fn get_role_passwords(&self) -> Result<Vec<RolePassword>> {
+        self.load_latest().await?;
+
+        let state = self.cached.lock().await;
+        Ok(state.role_passwords.clone())
+    }
+
     /// Try to mutate the catalog.
     ///
     /// Errors if the provided version doesn't match the version of the current
@@ -115,13 +131,24 @@ impl DatabaseCatalog {
         // version number when making a request to storage.
         let old_version = version;
 
+        // Restores replace the entire state and need to read from storage, so
+        // they're handled separately from other mutations.
+        let restore_to = match mutations.as_slice() {
+            [Mutation::RestoreCatalog(restore)] => Some(restore.timestamp),
+            _ => None,
+        };
+
         // TODO: Rollback on failed mutate.
         //
         // Currently don't have guarantees about what the state looks like on
         // failed mutates. Force a reload.
         //
         // Fixed with <https://github.com/GlareDB/glaredb/issues/547>.
-        if let Err(e) = state.mutate(mutations) {
+        let mutated = match restore_to {
+            Some(timestamp) => self.restore(&mut state, timestamp).await,
+            None => state.mutate(mutations),
+        };
+        if let Err(e) = mutated {
             self.require_full_load.store(true, Ordering::Relaxed);
             return Err(e);
         }
@@ -146,18 +173,75 @@ impl DatabaseCatalog {
             .await
         {
             self.require_full_load.store(true, Ordering::Relaxed);
+            if e.is_write_conflict() {
+                return Err(MetastoreError::ConcurrentCatalogWrite {
+                    db_id: self.db_id,
+                    source: e,
+                });
+            }
             return Err(e.into());
         }
 
         Ok(updated)
     }
 
+    /// Replace all user objects in the state with the objects from the latest
+    /// version of the catalog written at or before `timestamp`.
+    ///
+    /// The restored state is a new version of the catalog, older versions are
+    /// left untouched.
+    async fn restore(&self, state: &mut State, timestamp: SystemTime) -> Result<()> {
+        let snapshot = self
+            .storage
+            .read_catalog_at(self.db_id, timestamp)
+            .await?
+            .ok_or(MetastoreError::MissingCatalogSnapshot(timestamp))?;
+        debug!(db_id = %self.db_id, version = %state.version, snapshot_version = %snapshot.state.version, "restoring catalog");
+
+        let (version, _) = state.version.overflowing_add(1);
+        let restored = State::from_persisted(PersistedCatalog {
+            state: CatalogState {
+                version,
+                // Deployment metadata reflects the current deployment, not the
+                // restored catalog.
+                deployment: state.deployment.clone(),
+                entries: snapshot.state.entries,
+                session_var_defaults: snapshot.state.session_var_defaults,
+                // Keep current passwords so that restoring can't bring back
+                // a password that has since been changed or removed.
+                role_passwords: state.role_passwords.clone(),
+                table_statistics: snapshot.state.table_statistics,
+                table_schemas: snapshot.state.table_schemas,
+                // Same for read-only grants, restoring shouldn't be able to
+                // revoke a grant.
+                read_only_roles: state.read_only_roles.clone(),
+            },
+            extra: ExtraState {
+                // Keep the current counter so the oids of objects created
+                // after the snapshot are never reused.
+                oid_counter: state.oid_counter,
+                written_at: None,
+            },
+        })?;
+
+        *state = restored;
+
+        Ok(())
+    }
+
     /// Return the serializable state of the catalog at this version.
+    ///
+    /// Role passwords are left out, see [`DatabaseCatalog::get_role_passwords`].
     fn serializable_state(&self, guard: MutexGuard<State>) -> CatalogState {
         CatalogState {
             version: guard.version,
             entries: guard.entries.as_ref().clone(),
             deployment: guard.deployment.clone(),
+            session_var_defaults: guard.session_var_defaults.clone(),
+            role_passwords: Vec::new(),
+            table_statistics: guard.table_statistics.clone(),
+            table_schemas: guard.table_schemas.clone(),
+            read_only_roles: guard.read_only_roles.clone(),
         }
     }
 
@@ -315,10 +399,20 @@ struct State {
     tunnel_names: HashMap<String, u32>,
     /// Map credentials names to their ids.
     credentials_names: HashMap<String, u32>,
-    /// Map schema names to their ids.
-    schema_names: HashMap<String, u32>,
+    /// Map database IDs to the schemas in the database, keyed by name.
+    schema_names: HashMap<u32, HashMap<String, u32>>,
     /// Map schema IDs to objects in the schema.
     schema_objects: HashMap<u32, SchemaObjects>,
+    /// Default values for session variables.
+    session_var_defaults: Vec<SessionVarDefault>,
+    /// Passwords for roles.
+    role_passwords: Vec<RolePassword>,
+    /// Statistics for tables, keyed by table oid.
+    table_statistics: HashMap<u32, TableStatistics>,
+    /// Inferred schemas for external tables, keyed by table oid.
+    table_schemas: HashMap<u32, CachedTableSchema>,
+    /// Roles that have been granted read-only access.
+    read_only_roles: Vec<String>,
 }
 
 impl State {
@@ -349,7 +443,7 @@ impl State {
         let builtin = BUILTIN_CATALOG.clone();
         state.entries.extend(builtin.entries);
         database_names.extend(builtin.database_names);
-        schema_names.extend(builtin.schema_names);
+        schema_names.insert(DATABASE_DEFAULT.oid, builtin.schema_names);
         schema_objects.extend(builtin.schema_objects);
 
         // Rebuild name maps for user objects.
@@ -404,7 +498,10 @@ impl State {
                         });
                     }
 
-                    schema_names.insert(schema.meta.name.clone(), *oid);
+                    schema_names
+                        .entry(schema.meta.parent)
+                        .or_insert_with(HashMap::new)
+                        .insert(schema.meta.name.clone(), *oid);
                 }
                 entry @ CatalogEntry::View(_) | entry @ CatalogEntry::Table(_) => {
                     if entry.get_meta().parent == DATABASE_PARENT_ID {
@@ -463,6 +560,11 @@ impl State {
             credentials_names,
             schema_names,
             schema_objects,
+            session_var_defaults: state.session_var_defaults,
+            role_passwords: state.role_passwords,
+            table_statistics: state.table_statistics,
+            table_schemas: state.table_schemas,
+            read_only_roles: state.read_only_roles,
         };
 
         Ok(internal_state)
@@ -496,9 +598,15 @@ impl State {
                     .into_iter()
                     .filter(|(_, ent)| !ent.get_meta().builtin)
                     .collect(),
+                session_var_defaults: self.session_var_defaults.clone(),
+                role_passwords: self.role_passwords.clone(),
+                table_statistics: self.table_statistics.clone(),
+                table_schemas: self.table_schemas.clone(),
+                read_only_roles: self.read_only_roles.clone(),
             },
             extra: ExtraState {
                 oid_counter: self.oid_counter,
+                written_at: Some(SystemTime::now()),
             },
         }
     }
@@ -531,6 +639,13 @@ impl State {
             self.mutate_one(mutation)?;
         }
 
+        // Statistics and cached schemas are removed along with their tables.
+        let entries = self.entries.as_ref();
+        self.table_statistics
+            .retain(|oid, _| entries.contains_key(oid));
+        self.table_schemas
+            .retain(|oid, _| entries.contains_key(oid));
+
         Ok(())
     }
 
@@ -547,15 +662,35 @@ impl State {
                 };
 
                 self.entries.remove(&database_id)?.unwrap();
+
+                // Native databases own their schemas, and everything in them.
+                if let Some(schemas) = self.schema_names.remove(&database_id) {
+                    for schema_id in schemas.into_values() {
+                        if let Some(objs) = self.schema_objects.remove(&schema_id) {
+                            for child_oid in objs.iter_oids() {
+                                self.entries.remove(child_oid)?.unwrap(); // Bug if it doesn't exist.
+                            }
+                        }
+                        self.entries.remove(&schema_id)?.unwrap(); // Bug if it doesn't exist.
+                    }
+                }
+
+                self.session_var_defaults
+                    .retain(|default| default.database_id != Some(database_id));
             }
             Mutation::DropTunnel(drop_tunnel) => {
                 let if_exists = drop_tunnel.if_exists;
-                let tunnel_id = match self.tunnel_names.remove(&drop_tunnel.name) {
+                let tunnel_id = match self.tunnel_names.get(&drop_tunnel.name) {
                     None if if_exists => return Ok(()),
                     None => return Err(MetastoreError::MissingTunnel(drop_tunnel.name)),
-                    Some(id) => id,
+                    Some(id) => *id,
                 };
 
+                // Tables and databases using the tunnel have to be dropped
+                // first.
+                self.check_no_dependents(tunnel_id)?;
+
+                self.tunnel_names.remove(&drop_tunnel.name);
                 self.entries.remove(&tunnel_id)?.unwrap();
             }
             Mutation::DropCredentials(drop_credentials) => {
@@ -570,7 +705,12 @@ impl State {
             }
             Mutation::DropSchema(drop_schema) => {
                 let if_exists = drop_schema.if_exists;
-                let schema_id = match self.schema_names.remove(&drop_schema.name) {
+                let database_id = self.get_native_database_id(&drop_schema.database)?;
+                let schema_id = match self
+                    .schema_names
+                    .get_mut(&database_id)
+                    .and_then(|schemas| schemas.remove(&drop_schema.name))
+                {
                     None if if_exists => return Ok(()),
                     None => return Err(MetastoreError::MissingNamedSchema(drop_schema.name)),
                     Some(id) => id,
@@ -582,11 +722,15 @@ impl State {
                         self.schema_objects.remove(&schema_id);
                     }
                     Some(_) if drop_schema.cascade => {
-                        // Remove all child objects.
+                        // Remove all child objects, along with objects in
+                        // other schemas depending on them.
                         let objs = self.schema_objects.remove(&schema_id).unwrap(); // Checked above.
                         for child_oid in objs.iter_oids() {
-                            // TODO: Dependency checking.
-                            self.entries.remove(child_oid)?.unwrap(); // Bug if it doesn't exist.
+                            // May have already been dropped as a dependent
+                            // of another child.
+                            if self.entries.remove(child_oid)?.is_some() {
+                                self.drop_dependents(*child_oid)?;
+                            }
                         }
                     }
                     None => (), // Empty schema that never had any child objects
@@ -602,29 +746,22 @@ impl State {
             }
             // Can drop db objects like tables and views
             Mutation::DropObject(drop_object) => {
-                // TODO: Dependency checking (for child objects like tables, views, etc.)
                 let if_exists = drop_object.if_exists;
 
-                let schema_id = match self.schema_names.get(&drop_object.schema) {
-                    None if if_exists => return Ok(()),
-                    None => return Err(MetastoreError::MissingNamedSchema(drop_object.schema)),
-                    Some(id) => *id,
-                };
-
-                let objs = match self.schema_objects.get_mut(&schema_id) {
-                    None if if_exists => return Ok(()),
-                    None => {
-                        return Err(MetastoreError::MissingNamedObject {
-                            schema: drop_object.schema,
-                            name: drop_object.name,
-                        })
-                    }
-                    Some(objs) => objs,
+                let schema_id = match self.get_schema_id(&drop_object.database, &drop_object.schema)
+                {
+                    Err(MetastoreError::MissingNamedSchema(_)) if if_exists => return Ok(()),
+                    Err(e) => return Err(e),
+                    Ok(id) => id,
                 };
 
                 // TODO: This will need to be tweaked if/when we support
                 // dropping functions.
-                let ent_id = match objs.tables.remove(&drop_object.name) {
+                let ent_id = match self
+                    .schema_objects
+                    .get(&schema_id)
+                    .and_then(|objs| objs.tables.get(&drop_object.name))
+                {
                     None if if_exists => return Ok(()),
                     None => {
                         return Err(MetastoreError::MissingNamedObject {
@@ -632,10 +769,52 @@ impl State {
                             name: drop_object.name,
                         })
                     }
-                    Some(id) => id,
+                    Some(id) => *id,
                 };
 
+                if !drop_object.cascade {
+                    self.check_no_dependents(ent_id)?;
+                }
+
                 self.entries.remove(&ent_id)?.unwrap(); // Bug if doesn't exist.
+                if let Some(objs) = self.schema_objects.get_mut(&schema_id) {
+                    objs.tables.remove(&drop_object.name);
+                }
+                self.drop_dependents(ent_id)?;
+            }
+            Mutation::CreateDatabase(create_database) => {
+                validate_object_name(&create_database.name)?;
+                match self.database_names.get(&create_database.name) {
+                    Some(_) if create_database.if_not_exists => return Ok(()), // Already exists, nothing to do.
+                    Some(_) => return Err(MetastoreError::DuplicateName(create_database.name)),
+                    None => (),
+                }
+
+                // Create new entry
+                let oid = self.next_oid();
+                let ent = DatabaseEntry {
+                    meta: EntryMeta {
+                        entry_type: EntryType::Database,
+                        id: oid,
+                        parent: DATABASE_PARENT_ID,
+                        name: create_database.name.clone(),
+                        builtin: false,
+                        external: false,
+                        is_temp: false,
+                        sql_example: None,
+                        description: None,
+                    },
+                    options: DatabaseOptions::Internal(DatabaseOptionsInternal {}),
+                    tunnel_id: None,
+                    access_mode: SourceAccessMode::ReadWrite,
+                };
+                self.entries.insert(oid, CatalogEntry::Database(ent))?;
+
+                // Add to database map
+                self.database_names.insert(create_database.name, oid);
+
+                // New databases always start out with a default schema.
+                self.create_schema(oid, DEFAULT_SCHEMA.to_string())?;
             }
             Mutation::CreateExternalDatabase(create_database) => {
                 validate_object_name(&create_database.name)?;
@@ -785,7 +964,12 @@ impl State {
             Mutation::CreateSchema(create_schema) => {
                 validate_object_name(&create_schema.name)?;
 
-                if self.schema_names.contains_key(&create_schema.name) {
+                let database_id = self.get_native_database_id(&create_schema.database)?;
+                let exists = self
+                    .schema_names
+                    .get(&database_id)
+                    .is_some_and(|schemas| schemas.contains_key(&create_schema.name));
+                if exists {
                     if create_schema.if_not_exists {
                         return Ok(());
                     } else {
@@ -793,34 +977,33 @@ impl State {
                     }
                 }
 
-                // Create new entry
-                let oid = self.next_oid();
-                let ent = SchemaEntry {
-                    meta: EntryMeta {
-                        entry_type: EntryType::Schema,
-                        id: oid,
-                        // Schemas can only be created in the default builtin database for now.
-                        parent: DATABASE_DEFAULT.oid,
-                        name: create_schema.name.clone(),
-                        builtin: false,
-                        external: false,
-                        is_temp: false,
-                        sql_example: None,
-                        description: None,
-                    },
-                };
-                self.entries.insert(oid, CatalogEntry::Schema(ent))?;
-                // Add to name map
-                self.schema_names.insert(create_schema.name, oid);
+                self.create_schema(database_id, create_schema.name)?;
             }
             Mutation::CreateView(create_view) => {
                 validate_object_name(&create_view.name)?;
 
-                let schema_id = self.get_schema_id(&create_view.schema)?;
+                let schema_id = self.get_schema_id(&create_view.database, &create_view.schema)?;
 
                 // Create new entry
                 let oid = self.get_or_next_oid(schema_id, &create_view.name);
 
+                // Only user objects can be dropped, so only those are
+                // tracked.
+                let mut dependencies: Vec<_> = create_view
+                    .dependencies
+                    .into_iter()
+                    .filter(|dep| {
+                        *dep != oid
+                            && self
+                                .entries
+                                .as_ref()
+                                .get(dep)
+                                .is_some_and(|ent| !ent.get_meta().builtin)
+                    })
+                    .collect();
+                dependencies.sort_unstable();
+                dependencies.dedup();
+
                 let ent = ViewEntry {
                     meta: EntryMeta {
                         entry_type: EntryType::View,
@@ -835,6 +1018,7 @@ impl State {
                     },
                     sql: create_view.sql,
                     columns: create_view.columns,
+                    dependencies,
                 };
 
                 let policy = if create_view.or_replace {
@@ -848,7 +1032,7 @@ impl State {
             Mutation::CreateTable(create_table) => {
                 validate_object_name(&create_table.name)?;
 
-                let schema_id = self.get_schema_id(&create_table.schema)?;
+                let schema_id = self.get_schema_id(&create_table.database, &create_table.schema)?;
 
                 let oid = self.get_or_next_oid(schema_id, &create_table.name);
 
@@ -868,17 +1052,22 @@ impl State {
                     options: TableOptions::Internal(create_table.options),
                     tunnel_id: None,
                     access_mode: SourceAccessMode::ReadWrite,
+                    column_comments: BTreeMap::new(),
+                    tags: BTreeMap::new(),
                 };
 
                 let policy =
                     CreatePolicy::new(create_table.if_not_exists, create_table.or_replace)?;
 
                 self.try_insert_table_namespace(CatalogEntry::Table(ent), schema_id, oid, policy)?;
+                if create_table.or_replace {
+                    self.table_statistics.remove(&oid);
+                }
             }
 
             Mutation::CreateExternalTable(create_ext) => {
                 validate_object_name(&create_ext.name)?;
-                let schema_id = self.get_schema_id(&create_ext.schema)?;
+                let schema_id = self.get_schema_id(&create_ext.database, &create_ext.schema)?;
 
                 // Check if the tunnel exists and validate.
                 let tunnel_id = if let Some(tunnel_entry) =
@@ -911,17 +1100,21 @@ impl State {
                     options: create_ext.options,
                     tunnel_id,
                     access_mode: SourceAccessMode::ReadOnly,
+                    column_comments: BTreeMap::new(),
+                    tags: BTreeMap::new(),
                 };
 
                 let policy = CreatePolicy::new(create_ext.if_not_exists, create_ext.or_replace)?;
 
                 self.try_insert_table_namespace(CatalogEntry::Table(ent), schema_id, oid, policy)?;
+                if create_ext.or_replace {
+                    self.table_statistics.remove(&oid);
+                    self.table_schemas.remove(&oid);
+                }
             }
             Mutation::AlterTable(alter_table) => {
-                let schema_id = match self.schema_names.get(&alter_table.schema) {
-                    None => return Err(MetastoreError::MissingNamedSchema(alter_table.schema)),
-                    Some(id) => *id,
-                };
+                let database_id = self.get_native_database_id(&alter_table.database)?;
+                let schema_id = self.get_schema_id(&alter_table.database, &alter_table.schema)?;
 
                 let objs = match self.schema_objects.get_mut(&schema_id) {
                     None => {
@@ -936,7 +1129,11 @@ impl State {
                 match alter_table.operation {
                     AlterTableOperation::RenameTable { new_name } => {
                         validate_object_name(&new_name)?;
-                        if self.schema_names.contains_key(&new_name) {
+                        let schema_exists = self
+                            .schema_names
+                            .get(&database_id)
+                            .is_some_and(|schemas| schemas.contains_key(&new_name));
+                        if schema_exists {
                             return Err(MetastoreError::DuplicateName(new_name));
                         }
 
@@ -950,19 +1147,20 @@ impl State {
                             Some(id) => id,
                         };
 
-                        let mut table = match self.entries.remove(&oid)?.unwrap() {
-                            CatalogEntry::Table(ent) => ent,
+                        // Views refer to objects by name, and would break.
+                        self.check_no_dependents_for_rename(oid)?;
+
+                        // Views share the table namespace, and are renamed
+                        // the same way.
+                        let mut ent = self.entries.remove(&oid)?.unwrap();
+                        match &ent {
+                            CatalogEntry::Table(_) | CatalogEntry::View(_) => (),
                             other => unreachable!("unexpected entry type: {:?}", other),
-                        };
+                        }
 
-                        table.meta.name = new_name;
+                        ent.get_meta_mut().name = new_name;
 
-                        self.try_insert_table_namespace(
-                            CatalogEntry::Table(table.clone()),
-                            schema_id,
-                            table.meta.id,
-                            CreatePolicy::Create,
-                        )?;
+                        self.try_insert_table_namespace(ent, schema_id, oid, CreatePolicy::Create)?;
                     }
                     AlterTableOperation::SetAccessMode { access_mode } => {
                         let oid = match objs.tables.get(&alter_table.name) {
@@ -982,7 +1180,153 @@ impl State {
                             other => unreachable!("unexpected entry type: {:?}", other),
                         };
                     }
+                    AlterTableOperation::RefreshSchema { schema } => {
+                        let oid = match objs.tables.get(&alter_table.name) {
+                            None => {
+                                return Err(MetastoreError::MissingNamedObject {
+                                    schema: alter_table.schema,
+                                    name: alter_table.name,
+                                })
+                            }
+                            Some(id) => *id,
+                        };
+
+                        if !self.entries.get(&oid)?.unwrap().get_meta().external {
+                            return Err(MetastoreError::SchemaRefreshNotExternal(alter_table.name));
+                        }
+
+                        self.table_schemas.insert(oid, schema);
+                    }
+                    op @ (AlterTableOperation::SetTags { .. }
+                    | AlterTableOperation::UnsetTags { .. }) => {
+                        let oid = match objs.tables.get(&alter_table.name) {
+                            None => {
+                                return Err(MetastoreError::MissingNamedObject {
+                                    schema: alter_table.schema,
+                                    name: alter_table.name,
+                                })
+                            }
+                            Some(id) => id,
+                        };
+
+                        let tags = match self.entries.get_mut(oid)?.unwrap() {
+                            CatalogEntry::Table(ent) => &mut ent.tags,
+                            other => {
+                                return Err(MetastoreError::InvalidTagTarget {
+                                    name: alter_table.name,
+                                    entry_type: other.entry_type(),
+                                })
+                            }
+                        };
+                        match op {
+                            AlterTableOperation::SetTags { tags: new_tags } => {
+                                tags.extend(new_tags)
+                            }
+                            AlterTableOperation::UnsetTags { keys } => {
+                                for key in keys {
+                                    tags.remove(&key);
+                                }
+                            }
+                            _ => unreachable!(),
+                        }
+                    }
+                };
+            }
+            Mutation::AlterSchema(alter_schema) => {
+                let database_id = self.get_native_database_id(&alter_schema.database)?;
+                match alter_schema.operation {
+                    AlterSchemaOperation::RenameSchema { new_name } => {
+                        validate_object_name(&new_name)?;
+                        let schema_id =
+                            self.get_schema_id(&alter_schema.database, &alter_schema.name)?;
+
+                        // Views refer to objects by their qualified name, and
+                        // would break.
+                        if let Some(objs) = self.schema_objects.get(&schema_id) {
+                            for child_oid in objs.iter_oids() {
+                                self.check_no_dependents_for_rename(*child_oid)?;
+                            }
+                        }
+
+                        let schemas = self.schema_names.get_mut(&database_id).unwrap(); // Schema exists.
+                        if schemas.contains_key(&new_name) {
+                            return Err(MetastoreError::DuplicateName(new_name));
+                        }
+
+                        // Errors for builtin schemas.
+                        let ent = self.entries.get_mut(&schema_id)?.unwrap();
+                        ent.get_meta_mut().name = new_name.clone();
+
+                        let schemas = self.schema_names.get_mut(&database_id).unwrap();
+                        schemas.remove(&alter_schema.name);
+                        schemas.insert(new_name, schema_id);
+                    }
+                    AlterSchemaOperation::SetTags { tags } => {
+                        let schema_id =
+                            self.get_schema_id(&alter_schema.database, &alter_schema.name)?;
+                        // Errors for builtin schemas.
+                        match self.entries.get_mut(&schema_id)?.unwrap() {
+                            CatalogEntry::Schema(ent) => ent.tags.extend(tags),
+                            other => unreachable!("unexpected entry type: {:?}", other),
+                        }
+                    }
+                    AlterSchemaOperation::UnsetTags { keys } => {
+                        let schema_id =
+                            self.get_schema_id(&alter_schema.database, &alter_schema.name)?;
+                        match self.entries.get_mut(&schema_id)?.unwrap() {
+                            CatalogEntry::Schema(ent) => {
+                                for key in keys {
+                                    ent.tags.remove(&key);
+                                }
+                            }
+                            other => unreachable!("unexpected entry type: {:?}", other),
+                        }
+                    }
+                }
+            }
+            Mutation::CommentOn(comment_on) => {
+                let schema_id = self.get_schema_id(&comment_on.database, &comment_on.schema)?;
+                let name = match comment_on.name {
+                    Some(name) => name,
+                    None => {
+                        // Errors for builtin schemas.
+                        let ent = self.entries.get_mut(&schema_id)?.unwrap();
+                        ent.get_meta_mut().description = comment_on.comment;
+                        return Ok(());
+                    }
+                };
+
+                let oid = match self
+                    .schema_objects
+                    .get(&schema_id)
+                    .and_then(|objs| objs.tables.get(&name))
+                {
+                    None => {
+                        return Err(MetastoreError::MissingNamedObject {
+                            schema: comment_on.schema,
+                            name,
+                        })
+                    }
+                    Some(oid) => *oid,
                 };
+
+                match (self.entries.get_mut(&oid)?.unwrap(), comment_on.column) {
+                    (ent, None) => ent.get_meta_mut().description = comment_on.comment,
+                    (CatalogEntry::Table(table), Some(column)) => match comment_on.comment {
+                        Some(comment) => {
+                            table.column_comments.insert(column, comment);
+                        }
+                        None => {
+                            table.column_comments.remove(&column);
+                        }
+                    },
+                    (other, Some(_)) => {
+                        return Err(MetastoreError::InvalidColumnCommentTarget {
+                            name,
+                            entry_type: other.entry_type(),
+                        })
+                    }
+                }
             }
             Mutation::AlterDatabase(alter_database) => {
                 match alter_database.operation {
@@ -1020,7 +1364,83 @@ impl State {
                             other => unreachable!("unexpected entry type: {:?}", other),
                         };
                     }
+                    AlterDatabaseOperation::SetVariable { variable, value } => {
+                        let oid = self.get_database_id(&alter_database.name)?;
+                        self.set_session_var_default(Some(oid), None, variable, Some(value));
+                    }
+                    AlterDatabaseOperation::ResetVariable { variable } => {
+                        let oid = self.get_database_id(&alter_database.name)?;
+                        self.set_session_var_default(Some(oid), None, variable, None);
+                    }
+                };
+            }
+            Mutation::AlterRole(alter_role) => {
+                validate_object_name(&alter_role.name)?;
+                let database_id = alter_role
+                    .database
+                    .map(|name| self.get_database_id(&name))
+                    .transpose()?;
+                let role = Some(alter_role.name);
+
+                match alter_role.operation {
+                    AlterRoleOperation::SetVariable { variable, value } => {
+                        self.set_session_var_default(database_id, role, variable, Some(value));
+                    }
+                    AlterRoleOperation::ResetVariable { variable } => {
+                        self.set_session_var_default(database_id, role, variable, None);
+                    }
+                    AlterRoleOperation::SetPassword { scram_verifier } => {
+                        if database_id.is_some() {
+                            return Err(MetastoreError::RolePasswordForDatabase);
+                        }
+                        let role = role.unwrap_or_default();
+                        self.role_passwords.retain(|password| password.role != role);
+                        if let Some(scram_verifier) = scram_verifier {
+                            self.role_passwords.push(RolePassword {
+                                role,
+                                scram_verifier,
+                            });
+                        }
+                    }
+                    AlterRoleOperation::SetReadOnly { read_only } => {
+                        if database_id.is_some() {
+                            return Err(MetastoreError::RoleReadOnlyForDatabase);
+                        }
+                        let role = role.unwrap_or_default();
+                        self.read_only_roles.retain(|name| name != &role);
+                        if read_only {
+                            self.read_only_roles.push(role);
+                        }
+                    }
+                }
+            }
+            Mutation::UpdateTableStatistics(update) => {
+                let schema_id = self.get_schema_id(&update.database, &update.schema)?;
+                let oid = match self
+                    .schema_objects
+                    .get(&schema_id)
+                    .and_then(|objs| objs.tables.get(&update.name))
+                {
+                    Some(oid) => *oid,
+                    None => {
+                        return Err(MetastoreError::MissingNamedObject {
+                            schema: update.schema,
+                            name: update.name,
+                        })
+                    }
                 };
+
+                match self.entries.get(&oid)?.unwrap() {
+                    CatalogEntry::Table(_) => (),
+                    other => {
+                        return Err(MetastoreError::InvalidStatisticsTarget {
+                            name: update.name,
+                            entry_type: other.entry_type(),
+                        })
+                    }
+                }
+
+                self.table_statistics.insert(oid, update.statistics);
             }
             Mutation::AlterTunnelRotateKeys(alter_tunnel_rotate_keys) => {
                 let oid = match self.tunnel_names.get(&alter_tunnel_rotate_keys.name) {
@@ -1050,11 +1470,97 @@ impl State {
                 // Update the new storage size
                 self.deployment.storage_size = update_deployment_storage.new_storage_size;
             }
+            // Restores are handled by the database catalog since they require
+            // reading older versions from storage.
+            Mutation::RestoreCatalog(_) => return Err(MetastoreError::RestoreCatalogNotAlone),
         };
 
         Ok(())
     }
 
+    /// Get the objects depending on the object with the given oid, views
+    /// referencing it and external tables and databases using it as a
+    /// tunnel.
+    fn get_dependents(&self, oid: u32) -> Vec<u32> {
+        let mut dependents: Vec<_> = self
+            .entries
+            .as_ref()
+            .iter()
+            .filter(|(_, ent)| match ent {
+                CatalogEntry::View(view) => view.dependencies.contains(&oid),
+                CatalogEntry::Table(table) => table.tunnel_id == Some(oid),
+                CatalogEntry::Database(database) => database.tunnel_id == Some(oid),
+                _ => false,
+            })
+            .map(|(dependent, _)| *dependent)
+            .collect();
+        dependents.sort_unstable();
+        dependents
+    }
+
+    /// Errors with the names of the objects depending on the object with
+    /// the given oid if there are any.
+    fn check_no_dependents(&self, oid: u32) -> Result<()> {
+        let dependents = self.get_dependents(oid);
+        if dependents.is_empty() {
+            return Ok(());
+        }
+
+        let name_of = |oid: u32| self.qualified_name(oid).unwrap_or_else(|| oid.to_string());
+        Err(MetastoreError::ObjectHasDependents {
+            object: name_of(oid),
+            dependents: dependents.into_iter().map(name_of).collect(),
+        })
+    }
+
+    /// Errors with the names of the objects depending on the object with
+    /// the given oid if there are any, since renaming it would break them.
+    fn check_no_dependents_for_rename(&self, oid: u32) -> Result<()> {
+        let dependents = self.get_dependents(oid);
+        if dependents.is_empty() {
+            return Ok(());
+        }
+
+        let name_of = |oid: u32| self.qualified_name(oid).unwrap_or_else(|| oid.to_string());
+        Err(MetastoreError::RenamedObjectHasDependents {
+            object: name_of(oid),
+            dependents: dependents.into_iter().map(name_of).collect(),
+        })
+    }
+
+    /// Drop the objects depending on an already dropped object, and the
+    /// objects depending on those.
+    ///
+    /// Tunnels can't be dropped while in use, so the dependents are always
+    /// views.
+    fn drop_dependents(&mut self, oid: u32) -> Result<()> {
+        for dependent in self.get_dependents(oid) {
+            // May have been dropped as a dependent of an earlier dependent.
+            let ent = match self.entries.remove(&dependent)? {
+                Some(ent) => ent,
+                None => continue,
+            };
+            let meta = ent.get_meta();
+            if let Some(objs) = self.schema_objects.get_mut(&meta.parent) {
+                objs.tables.remove(&meta.name);
+            }
+            self.drop_dependents(dependent)?;
+        }
+        Ok(())
+    }
+
+    /// Get the name of an entry, qualified with its schema for objects in a
+    /// schema.
+    fn qualified_name(&self, oid: u32) -> Option<String> {
+        let meta = self.entries.as_ref().get(&oid)?.get_meta();
+        match self.entries.as_ref().get(&meta.parent) {
+            Some(CatalogEntry::Schema(schema)) => {
+                Some(format!("{}.{}", schema.meta.name, meta.name))
+            }
+            _ => Some(meta.name.clone()),
+        }
+    }
+
     /// Try to insert an entry for a schema within the "table" namespace.
     ///
     /// Errors depending on the create policy.
@@ -1097,10 +1603,94 @@ impl State {
         Ok(())
     }
 
-    fn get_schema_id(&self, name: &str) -> Result<u32> {
+    /// Create a new schema in a native database.
+    ///
+    /// Does not check if a schema with the same name already exists.
+    fn create_schema(&mut self, database_id: u32, name: String) -> Result<u32> {
+        let oid = self.next_oid();
+        let ent = SchemaEntry {
+            meta: EntryMeta {
+                entry_type: EntryType::Schema,
+                id: oid,
+                parent: database_id,
+                name: name.clone(),
+                builtin: false,
+                external: false,
+                is_temp: false,
+                sql_example: None,
+                description: None,
+            },
+            tags: BTreeMap::new(),
+        };
+        self.entries.insert(oid, CatalogEntry::Schema(ent))?;
+        // Add to name map
         self.schema_names
+            .entry(database_id)
+            .or_default()
+            .insert(name, oid);
+
+        Ok(oid)
+    }
+
+    /// Get the id of a database, native or external.
+    fn get_database_id(&self, name: &str) -> Result<u32> {
+        self.database_names
             .get(name)
             .cloned()
+            .ok_or_else(|| MetastoreError::MissingDatabase(name.to_string()))
+    }
+
+    /// Set the default value for a session variable, or remove the default if
+    /// `value` is `None`.
+    fn set_session_var_default(
+        &mut self,
+        database_id: Option<u32>,
+        role: Option<String>,
+        name: String,
+        value: Option<String>,
+    ) {
+        let default = SessionVarDefault {
+            database_id,
+            role,
+            name: name.to_lowercase(),
+            value: value.clone().unwrap_or_default(),
+        };
+        self.session_var_defaults
+            .retain(|existing| !existing.same_target(&default));
+        if value.is_some() {
+            self.session_var_defaults.push(default);
+        }
+    }
+
+    /// Get the id of a native database.
+    ///
+    /// An empty name refers to the default database. Schemas can't be created
+    /// in external databases, so those will error.
+    fn get_native_database_id(&self, name: &str) -> Result<u32> {
+        if name.is_empty() {
+            return Ok(DATABASE_DEFAULT.oid);
+        }
+
+        let oid = self
+            .database_names
+            .get(name)
+            .ok_or_else(|| MetastoreError::MissingDatabase(name.to_string()))?;
+
+        match self.entries.get(oid)? {
+            Some(CatalogEntry::Database(DatabaseEntry {
+                options: DatabaseOptions::Internal(_),
+                ..
+            })) => Ok(*oid),
+            _ => Err(MetastoreError::NotNativeDatabase(name.to_string())),
+        }
+    }
+
+    fn get_schema_id(&self, database: &str, name: &str) -> Result<u32> {
+        let database_id = self.get_native_database_id(database)?;
+        self.schema_names
+            .get(&database_id)
+            .and_then(|schemas| schemas.get(name))
+            .cloned()
             .ok_or_else(|| MetastoreError::MissingNamedSchema(name.to_string()))
     }
 
@@ -1229,6 +1819,7 @@ impl BuiltinCatalog {
                         sql_example: None,
                         description: None,
                     },
+                    tags: BTreeMap::new(),
                 }),
             )?;
         }
@@ -1254,6 +1845,8 @@ impl BuiltinCatalog {
                     options: TableOptions::new_internal(table.columns.clone()),
                     tunnel_id: None,
                     access_mode: SourceAccessMode::ReadOnly,
+                    column_comments: BTreeMap::new(),
+                    tags: BTreeMap::new(),
                 }),
             )?;
             schema_objects
@@ -1286,6 +1879,7 @@ impl BuiltinCatalog {
                     },
                     sql: view.sql.to_string(),
                     columns: Vec::new(),
+                    dependencies: Vec::new(),
                 }),
             )?;
             schema_objects
@@ -1351,6 +1945,24 @@ impl BuiltinCatalog {
 
             oid += 1;
         }
+        for func in FUNCTION_REGISTRY.aggregate_udfs() {
+            // Put them all in the default schema.
+            let schema_id = schema_names
+                .get(DEFAULT_SCHEMA)
+                .ok_or_else(|| MetastoreError::MissingNamedSchema(DEFAULT_SCHEMA.to_string()))?;
+
+            insert_entry(
+                oid,
+                CatalogEntry::Function(func.as_function_entry(oid, *schema_id)),
+            )?;
+            schema_objects
+                .get_mut(schema_id)
+                .unwrap()
+                .functions
+                .insert(func.name().to_string(), oid);
+
+            oid += 1;
+        }
 
         Ok(BuiltinCatalog {
             entries,
@@ -1365,13 +1977,20 @@ impl BuiltinCatalog {
 mod tests {
     use super::*;
     use crate::storage::persist::Storage;
+    use datafusion::arrow::datatypes::DataType;
     use object_store::memory::InMemory;
     use protogen::metastore::types::options::DatabaseOptionsDebug;
+    use protogen::metastore::types::options::InternalColumnDefinition;
     use protogen::metastore::types::options::TableOptionsDebug;
     use protogen::metastore::types::service::AlterDatabase;
+    use protogen::metastore::types::service::AlterRole;
+    use protogen::metastore::types::service::AlterSchema;
+    use protogen::metastore::types::service::AlterTable;
     use protogen::metastore::types::service::DropDatabase;
+    use protogen::metastore::types::service::UpdateTableStatistics;
     use protogen::metastore::types::service::{
-        CreateExternalDatabase, CreateExternalTable, CreateSchema, CreateView, DropSchema,
+        CommentOn, CreateDatabase, CreateExternalDatabase, CreateExternalTable, CreateSchema,
+        CreateView, DropObject, DropSchema, RestoreCatalog,
     };
     use sqlbuiltins::builtins::DEFAULT_CATALOG;
     use std::collections::HashSet;
@@ -1379,7 +1998,7 @@ mod tests {
     async fn new_catalog() -> DatabaseCatalog {
         logutil::init_test();
         let store = Arc::new(InMemory::new());
-        let storage = Arc::new(Storage::new(Uuid::new_v4(), store));
+        let storage = Arc::new(Storage::new(Uuid::new_v4(), store).into());
         DatabaseCatalog::open(Uuid::new_v4(), storage)
             .await
             .unwrap()
@@ -1398,166 +2017,1183 @@ mod tests {
     async fn drop_missing_schema() {
         let db = new_catalog().await;
 
-        db.try_mutate(
-            version(&db).await,
-            vec![Mutation::DropSchema(DropSchema {
-                name: "yoshi".to_string(),
-                if_exists: false,
-                cascade: false,
-            })],
-        )
-        .await
-        .unwrap_err();
-    }
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::DropSchema(DropSchema {
+                database: DEFAULT_CATALOG.to_string(),
+                name: "yoshi".to_string(),
+                if_exists: false,
+                cascade: false,
+            })],
+        )
+        .await
+        .unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn drop_missing_schema_if_exists() {
+        let db = new_catalog().await;
+
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::DropSchema(DropSchema {
+                database: DEFAULT_CATALOG.to_string(),
+                name: "yoshi".to_string(),
+                if_exists: true,
+                cascade: false,
+            })],
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn multiple_entries() {
+        let db = new_catalog().await;
+
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::CreateSchema(CreateSchema {
+                database: DEFAULT_CATALOG.to_string(),
+                name: "numbers".to_string(),
+                if_not_exists: false,
+            })],
+        )
+        .await
+        .unwrap();
+
+        let mutations: Vec<_> = (0..10)
+            .map(|i| {
+                Mutation::CreateView(CreateView {
+                    database: DEFAULT_CATALOG.to_string(),
+                    schema: "numbers".to_string(),
+                    name: i.to_string(),
+                    sql: format!("select {i}"),
+                    or_replace: false,
+                    columns: Vec::new(),
+                    dependencies: Vec::new(),
+                })
+            })
+            .collect();
+
+        db.try_mutate(version(&db).await, mutations).await.unwrap();
+
+        let state = db.get_state().await.unwrap();
+
+        let mut found = HashSet::new();
+        for (_, ent) in state.entries {
+            if !matches!(ent, CatalogEntry::Schema(_)) {
+                found.insert(ent.get_meta().name.clone());
+            }
+        }
+
+        for i in 0..10 {
+            assert!(found.contains(i.to_string().as_str()));
+        }
+    }
+
+    #[tokio::test]
+    async fn duplicate_schema_names() {
+        let db = new_catalog().await;
+
+        // First should pass.
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::CreateSchema(CreateSchema {
+                database: DEFAULT_CATALOG.to_string(),
+                name: "mario".to_string(),
+                if_not_exists: false,
+            })],
+        )
+        .await
+        .unwrap();
+
+        // Duplicate should fail.
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::CreateSchema(CreateSchema {
+                database: DEFAULT_CATALOG.to_string(),
+                name: "mario".to_string(),
+                if_not_exists: false,
+            })],
+        )
+        .await
+        .unwrap_err();
+
+        // Drop schema.
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::DropSchema(DropSchema {
+                database: DEFAULT_CATALOG.to_string(),
+                name: "mario".to_string(),
+                if_exists: false,
+                cascade: false,
+            })],
+        )
+        .await
+        .unwrap();
+
+        // Re-adding schema should pass.
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::CreateSchema(CreateSchema {
+                database: DEFAULT_CATALOG.to_string(),
+                name: "mario".to_string(),
+                if_not_exists: false,
+            })],
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn schema_names_per_database() {
+        let db = new_catalog().await;
+
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                Mutation::CreateDatabase(CreateDatabase {
+                    name: "analytics".to_string(),
+                    if_not_exists: false,
+                }),
+                Mutation::CreateSchema(CreateSchema {
+                    database: DEFAULT_CATALOG.to_string(),
+                    name: "mario".to_string(),
+                    if_not_exists: false,
+                }),
+                // Same name, different database.
+                Mutation::CreateSchema(CreateSchema {
+                    database: "analytics".to_string(),
+                    name: "mario".to_string(),
+                    if_not_exists: false,
+                }),
+                Mutation::CreateView(CreateView {
+                    database: "analytics".to_string(),
+                    schema: "mario".to_string(),
+                    name: "kart".to_string(),
+                    sql: "select 1".to_string(),
+                    or_replace: false,
+                    columns: Vec::new(),
+                    dependencies: Vec::new(),
+                }),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let state = db.get_state().await.unwrap();
+        let analytics = state
+            .entries
+            .values()
+            .find(|ent| ent.get_meta().name == "analytics")
+            .unwrap()
+            .get_meta()
+            .id;
+        let schemas: Vec<_> = state
+            .entries
+            .values()
+            .filter(|ent| ent.get_meta().parent == analytics)
+            .map(|ent| ent.get_meta().name.clone())
+            .collect();
+        assert_eq!(2, schemas.len());
+        assert!(schemas.contains(&"mario".to_string()));
+        assert!(schemas.contains(&DEFAULT_SCHEMA.to_string()));
+
+        // Schemas can't be created in external databases.
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                Mutation::CreateExternalDatabase(CreateExternalDatabase {
+                    name: "luigi".to_string(),
+                    options: DatabaseOptions::Debug(DatabaseOptionsDebug {}),
+                    if_not_exists: false,
+                    tunnel: None,
+                }),
+                Mutation::CreateSchema(CreateSchema {
+                    database: "luigi".to_string(),
+                    name: "mansion".to_string(),
+                    if_not_exists: false,
+                }),
+            ],
+        )
+        .await
+        .unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn drop_database_drops_schemas() {
+        let db = new_catalog().await;
+
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                Mutation::CreateDatabase(CreateDatabase {
+                    name: "analytics".to_string(),
+                    if_not_exists: false,
+                }),
+                Mutation::CreateView(CreateView {
+                    database: "analytics".to_string(),
+                    schema: DEFAULT_SCHEMA.to_string(),
+                    name: "kart".to_string(),
+                    sql: "select 1".to_string(),
+                    or_replace: false,
+                    columns: Vec::new(),
+                    dependencies: Vec::new(),
+                }),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let before = db.get_state().await.unwrap().entries.len();
+
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::DropDatabase(DropDatabase {
+                name: "analytics".to_string(),
+                if_exists: false,
+            })],
+        )
+        .await
+        .unwrap();
+
+        // Database, schema, and view.
+        let state = db.get_state().await.unwrap();
+        assert_eq!(before - 3, state.entries.len());
+
+        // Recreating the database starts from a clean slate.
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                Mutation::CreateDatabase(CreateDatabase {
+                    name: "analytics".to_string(),
+                    if_not_exists: false,
+                }),
+                Mutation::CreateView(CreateView {
+                    database: "analytics".to_string(),
+                    schema: DEFAULT_SCHEMA.to_string(),
+                    name: "kart".to_string(),
+                    sql: "select 1".to_string(),
+                    or_replace: false,
+                    columns: Vec::new(),
+                    dependencies: Vec::new(),
+                }),
+            ],
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn session_var_defaults() {
+        let db = new_catalog().await;
+
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                Mutation::CreateDatabase(CreateDatabase {
+                    name: "analytics".to_string(),
+                    if_not_exists: false,
+                }),
+                Mutation::AlterDatabase(AlterDatabase {
+                    name: "analytics".to_string(),
+                    operation: AlterDatabaseOperation::SetVariable {
+                        variable: "search_path".to_string(),
+                        value: "reports".to_string(),
+                    },
+                }),
+                // Replaces the previous default.
+                Mutation::AlterDatabase(AlterDatabase {
+                    name: "analytics".to_string(),
+                    operation: AlterDatabaseOperation::SetVariable {
+                        variable: "SEARCH_PATH".to_string(),
+                        value: "reports,public".to_string(),
+                    },
+                }),
+                Mutation::AlterRole(AlterRole {
+                    name: "sean".to_string(),
+                    database: None,
+                    operation: AlterRoleOperation::SetVariable {
+                        variable: "statement_timeout".to_string(),
+                        value: "1000".to_string(),
+                    },
+                }),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let state = db.get_state().await.unwrap();
+        let analytics = *state
+            .entries
+            .iter()
+            .find(|(_, ent)| ent.get_meta().name == "analytics")
+            .unwrap()
+            .0;
+        assert_eq!(
+            vec![
+                SessionVarDefault {
+                    database_id: Some(analytics),
+                    role: None,
+                    name: "search_path".to_string(),
+                    value: "reports,public".to_string(),
+                },
+                SessionVarDefault {
+                    database_id: None,
+                    role: Some("sean".to_string()),
+                    name: "statement_timeout".to_string(),
+                    value: "1000".to_string(),
+                },
+            ],
+            state.session_var_defaults
+        );
+
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                Mutation::AlterRole(AlterRole {
+                    name: "sean".to_string(),
+                    database: None,
+                    operation: AlterRoleOperation::ResetVariable {
+                        variable: "statement_timeout".to_string(),
+                    },
+                }),
+                Mutation::AlterRole(AlterRole {
+                    name: "sean".to_string(),
+                    database: Some("missing".to_string()),
+                    operation: AlterRoleOperation::SetVariable {
+                        variable: "statement_timeout".to_string(),
+                        value: "1000".to_string(),
+                    },
+                }),
+            ],
+        )
+        .await
+        .unwrap_err();
+
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::AlterRole(AlterRole {
+                name: "sean".to_string(),
+                database: None,
+                operation: AlterRoleOperation::ResetVariable {
+                    variable: "statement_timeout".to_string(),
+                },
+            })],
+        )
+        .await
+        .unwrap();
+        let state = db.get_state().await.unwrap();
+        assert_eq!(1, state.session_var_defaults.len());
+
+        // Defaults for a database go away with the database.
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::DropDatabase(DropDatabase {
+                name: "analytics".to_string(),
+                if_exists: false,
+            })],
+        )
+        .await
+        .unwrap();
+        let state = db.get_state().await.unwrap();
+        assert!(state.session_var_defaults.is_empty());
+    }
+
+    #[tokio::test]
+    async fn role_passwords() {
+        let db = new_catalog().await;
+
+        let set_password = |name: &str, scram_verifier: Option<&str>| {
+            Mutation::AlterRole(AlterRole {
+                name: name.to_string(),
+                database: None,
+                operation: AlterRoleOperation::SetPassword {
+                    scram_verifier: scram_verifier.map(String::from),
+                },
+            })
+        };
+
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                set_password("sean", Some("verifier1")),
+                set_password("sam", Some("verifier2")),
+                // Replaces the previous password.
+                set_password("sean", Some("verifier3")),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let passwords = db.get_role_passwords().await.unwrap();
+        assert_eq!(
+            vec![
+                RolePassword {
+                    role: "sam".to_string(),
+                    scram_verifier: "verifier2".to_string(),
+                },
+                RolePassword {
+                    role: "sean".to_string(),
+                    scram_verifier: "verifier3".to_string(),
+                },
+            ],
+            passwords
+        );
+        // Verifiers are never part of the state sent to clients.
+        let state = db.get_state().await.unwrap();
+        assert!(state.role_passwords.is_empty());
+
+        // Passwords can't be set for a single database.
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::AlterRole(AlterRole {
+                name: "sean".to_string(),
+                database: Some("default".to_string()),
+                operation: AlterRoleOperation::SetPassword {
+                    scram_verifier: Some("verifier4".to_string()),
+                },
+            })],
+        )
+        .await
+        .unwrap_err();
+
+        db.try_mutate(version(&db).await, vec![set_password("sean", None)])
+            .await
+            .unwrap();
+        let passwords = db.get_role_passwords().await.unwrap();
+        assert_eq!(1, passwords.len());
+        assert_eq!("sam", passwords[0].role);
+    }
+
+    #[tokio::test]
+    async fn read_only_roles() {
+        let db = new_catalog().await;
+
+        let set_read_only = |name: &str, read_only: bool| {
+            Mutation::AlterRole(AlterRole {
+                name: name.to_string(),
+                database: None,
+                operation: AlterRoleOperation::SetReadOnly { read_only },
+            })
+        };
+
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                set_read_only("sean", true),
+                set_read_only("sam", true),
+                // Granting twice doesn't duplicate the grant.
+                set_read_only("sean", true),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let state = db.get_state().await.unwrap();
+        assert_eq!(
+            vec!["sam".to_string(), "sean".to_string()],
+            state.read_only_roles
+        );
+
+        // Read-only access can't be granted for a single database.
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::AlterRole(AlterRole {
+                name: "sean".to_string(),
+                database: Some("default".to_string()),
+                operation: AlterRoleOperation::SetReadOnly { read_only: true },
+            })],
+        )
+        .await
+        .unwrap_err();
+
+        db.try_mutate(version(&db).await, vec![set_read_only("sam", false)])
+            .await
+            .unwrap();
+        let state = db.get_state().await.unwrap();
+        assert_eq!(vec!["sean".to_string()], state.read_only_roles);
+    }
+
+    #[tokio::test]
+    async fn table_statistics() {
+        let db = new_catalog().await;
+
+        let create_table = |name: &str, or_replace: bool| {
+            Mutation::CreateExternalTable(CreateExternalTable {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: "public".to_string(),
+                name: name.to_string(),
+                options: TableOptions::Debug(TableOptionsDebug {
+                    table_type: String::new(),
+                }),
+                if_not_exists: false,
+                or_replace,
+                tunnel: None,
+            })
+        };
+        let update_statistics = |name: &str, num_rows: u64| {
+            Mutation::UpdateTableStatistics(UpdateTableStatistics {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: "public".to_string(),
+                name: name.to_string(),
+                statistics: TableStatistics {
+                    num_rows,
+                    columns: Vec::new(),
+                },
+            })
+        };
+
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                create_table("t1", false),
+                create_table("t2", false),
+                update_statistics("t1", 10),
+                update_statistics("t2", 20),
+                // Replaces the previous statistics.
+                update_statistics("t1", 30),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let state = db.get_state().await.unwrap();
+        let mut num_rows: Vec<_> = state
+            .table_statistics
+            .values()
+            .map(|stats| stats.num_rows)
+            .collect();
+        num_rows.sort();
+        assert_eq!(vec![20, 30], num_rows);
+
+        // Statistics can only be collected for existing tables.
+        db.try_mutate(version(&db).await, vec![update_statistics("missing", 1)])
+            .await
+            .unwrap_err();
+
+        // Statistics are removed when a table is dropped or replaced.
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                Mutation::DropObject(DropObject {
+                    database: DEFAULT_CATALOG.to_string(),
+                    schema: "public".to_string(),
+                    name: "t1".to_string(),
+                    if_exists: false,
+                    cascade: false,
+                }),
+                create_table("t2", true),
+            ],
+        )
+        .await
+        .unwrap();
+        let state = db.get_state().await.unwrap();
+        assert!(state.table_statistics.is_empty());
+    }
+
+    #[tokio::test]
+    async fn table_schemas() {
+        let db = new_catalog().await;
+
+        let refresh_schema = |name: &str, inferred_at: i64| {
+            Mutation::AlterTable(AlterTable {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: "public".to_string(),
+                name: name.to_string(),
+                operation: AlterTableOperation::RefreshSchema {
+                    schema: CachedTableSchema {
+                        columns: InternalColumnDefinition::from_tuples([(
+                            "a",
+                            DataType::Int64,
+                            true,
+                        )]),
+                        inferred_at,
+                    },
+                },
+            })
+        };
+
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                Mutation::CreateExternalTable(CreateExternalTable {
+                    database: DEFAULT_CATALOG.to_string(),
+                    schema: "public".to_string(),
+                    name: "ext".to_string(),
+                    options: TableOptions::Debug(TableOptionsDebug {
+                        table_type: String::new(),
+                    }),
+                    if_not_exists: false,
+                    or_replace: false,
+                    tunnel: None,
+                }),
+                refresh_schema("ext", 10),
+                // Replaces the previous schema.
+                refresh_schema("ext", 20),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let state = db.get_state().await.unwrap();
+        let inferred_at: Vec<_> = state
+            .table_schemas
+            .values()
+            .map(|schema| schema.inferred_at)
+            .collect();
+        assert_eq!(vec![20], inferred_at);
+
+        db.try_mutate(version(&db).await, vec![refresh_schema("missing", 1)])
+            .await
+            .unwrap_err();
+
+        // Cached schemas are removed along with the table.
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::DropObject(DropObject {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: "public".to_string(),
+                name: "ext".to_string(),
+                if_exists: false,
+                cascade: false,
+            })],
+        )
+        .await
+        .unwrap();
+        let state = db.get_state().await.unwrap();
+        assert!(state.table_schemas.is_empty());
+    }
+
+    #[tokio::test]
+    async fn drop_schema_cascade() {
+        let db = new_catalog().await;
+
+        let state = db
+            .try_mutate(
+                version(&db).await,
+                vec![Mutation::CreateSchema(CreateSchema {
+                    database: DEFAULT_CATALOG.to_string(),
+                    name: "mushroom".to_string(),
+                    if_not_exists: false,
+                })],
+            )
+            .await
+            .unwrap();
+
+        let state = db
+            .try_mutate(
+                state.version,
+                vec![Mutation::CreateView(CreateView {
+                    database: DEFAULT_CATALOG.to_string(),
+                    schema: "mushroom".to_string(),
+                    name: "bowser".to_string(),
+                    sql: "select 1".to_string(),
+                    or_replace: false,
+                    columns: Vec::new(),
+                    dependencies: Vec::new(),
+                })],
+            )
+            .await
+            .unwrap();
+
+        // Drop schema cascade. containing a view.
+        db.try_mutate(
+            state.version,
+            vec![Mutation::DropSchema(DropSchema {
+                database: DEFAULT_CATALOG.to_string(),
+                name: "mushroom".to_string(),
+                if_exists: false,
+                cascade: true,
+            })],
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn drop_object_with_dependents() {
+        let db = new_catalog().await;
+
+        let create_view = |name: &str, dependencies: Vec<u32>| {
+            Mutation::CreateView(CreateView {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: "public".to_string(),
+                name: name.to_string(),
+                sql: "select 1".to_string(),
+                or_replace: false,
+                columns: Vec::new(),
+                dependencies,
+            })
+        };
+        let drop_view = |name: &str, cascade: bool| {
+            Mutation::DropObject(DropObject {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: "public".to_string(),
+                name: name.to_string(),
+                if_exists: false,
+                cascade,
+            })
+        };
+        let oid_of = |state: &CatalogState, name: &str| {
+            state
+                .entries
+                .iter()
+                .find(|(_, ent)| ent.get_meta().name == name)
+                .map(|(oid, _)| *oid)
+        };
+
+        // "peach" <- "daisy" <- "rosalina"
+        let state = db
+            .try_mutate(version(&db).await, vec![create_view("peach", Vec::new())])
+            .await
+            .unwrap();
+        let peach = oid_of(&state, "peach").unwrap();
+        let state = db
+            .try_mutate(state.version, vec![create_view("daisy", vec![peach])])
+            .await
+            .unwrap();
+        let daisy = oid_of(&state, "daisy").unwrap();
+        let state = db
+            .try_mutate(state.version, vec![create_view("rosalina", vec![daisy])])
+            .await
+            .unwrap();
+
+        let err = db
+            .try_mutate(state.version, vec![drop_view("peach", false)])
+            .await
+            .unwrap_err();
+        match err {
+            MetastoreError::ObjectHasDependents { object, dependents } => {
+                assert_eq!("public.peach", object);
+                assert_eq!(vec!["public.daisy".to_string()], dependents);
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+
+        // Dropping the last view doesn't need cascade.
+        let state = db
+            .try_mutate(state.version, vec![drop_view("rosalina", false)])
+            .await
+            .unwrap();
+        assert!(oid_of(&state, "rosalina").is_none());
+
+        let state = db
+            .try_mutate(state.version, vec![drop_view("peach", true)])
+            .await
+            .unwrap();
+        assert!(oid_of(&state, "peach").is_none());
+        assert!(oid_of(&state, "daisy").is_none());
+    }
+
+    #[tokio::test]
+    async fn rename_schema_and_view() {
+        let db = new_catalog().await;
+
+        let state = db
+            .try_mutate(
+                version(&db).await,
+                vec![
+                    Mutation::CreateSchema(CreateSchema {
+                        database: DEFAULT_CATALOG.to_string(),
+                        name: "mario".to_string(),
+                        if_not_exists: false,
+                    }),
+                    Mutation::CreateView(CreateView {
+                        database: DEFAULT_CATALOG.to_string(),
+                        schema: "mario".to_string(),
+                        name: "peach".to_string(),
+                        sql: "select 1".to_string(),
+                        or_replace: false,
+                        columns: Vec::new(),
+                        dependencies: Vec::new(),
+                    }),
+                ],
+            )
+            .await
+            .unwrap();
+        let peach = state
+            .entries
+            .iter()
+            .find(|(_, ent)| ent.get_meta().name == "peach")
+            .map(|(oid, _)| *oid)
+            .unwrap();
+
+        let rename_view = |schema: &str, name: &str, new_name: &str| {
+            Mutation::AlterTable(AlterTable {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: schema.to_string(),
+                name: name.to_string(),
+                operation: AlterTableOperation::RenameTable {
+                    new_name: new_name.to_string(),
+                },
+            })
+        };
+        let rename_schema = |name: &str, new_name: &str| {
+            Mutation::AlterSchema(AlterSchema {
+                database: DEFAULT_CATALOG.to_string(),
+                name: name.to_string(),
+                operation: AlterSchemaOperation::RenameSchema {
+                    new_name: new_name.to_string(),
+                },
+            })
+        };
+
+        let state = db
+            .try_mutate(
+                state.version,
+                vec![
+                    rename_view("mario", "peach", "daisy"),
+                    rename_schema("mario", "luigi"),
+                ],
+            )
+            .await
+            .unwrap();
+
+        // Same oid, new names.
+        let daisy = state.entries.get(&peach).unwrap().get_meta();
+        assert_eq!("daisy", daisy.name);
+        match state.entries.get(&daisy.parent).unwrap() {
+            CatalogEntry::Schema(schema) => assert_eq!("luigi", schema.meta.name),
+            other => panic!("unexpected entry: {other:?}"),
+        }
 
-    #[tokio::test]
-    async fn drop_missing_schema_if_exists() {
-        let db = new_catalog().await;
+        // Old schema name is free, and builtin schemas can't be renamed.
+        db.try_mutate(state.version, vec![rename_schema("mario", "toad")])
+            .await
+            .unwrap_err();
+        db.try_mutate(version(&db).await, vec![rename_schema("public", "toad")])
+            .await
+            .unwrap_err();
 
+        // Renaming objects other views depend on would break the views.
         db.try_mutate(
             version(&db).await,
-            vec![Mutation::DropSchema(DropSchema {
-                name: "yoshi".to_string(),
-                if_exists: true,
-                cascade: false,
+            vec![Mutation::CreateView(CreateView {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: "public".to_string(),
+                name: "rosalina".to_string(),
+                sql: "select * from luigi.daisy".to_string(),
+                or_replace: false,
+                columns: Vec::new(),
+                dependencies: vec![peach],
             })],
         )
         .await
         .unwrap();
+        let err = db
+            .try_mutate(version(&db).await, vec![rename_schema("luigi", "toad")])
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetastoreError::RenamedObjectHasDependents { .. }),
+            "unexpected error: {err}"
+        );
+        let err = db
+            .try_mutate(
+                version(&db).await,
+                vec![rename_view("luigi", "daisy", "toad")],
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetastoreError::RenamedObjectHasDependents { .. }),
+            "unexpected error: {err}"
+        );
     }
 
     #[tokio::test]
-    async fn multiple_entries() {
+    async fn comment_on_objects() {
         let db = new_catalog().await;
 
-        db.try_mutate(
-            version(&db).await,
-            vec![Mutation::CreateSchema(CreateSchema {
-                name: "numbers".to_string(),
-                if_not_exists: false,
-            })],
-        )
-        .await
-        .unwrap();
-
-        let mutations: Vec<_> = (0..10)
-            .map(|i| {
-                Mutation::CreateView(CreateView {
-                    schema: "numbers".to_string(),
-                    name: i.to_string(),
-                    sql: format!("select {i}"),
-                    or_replace: false,
-                    columns: Vec::new(),
-                })
+        let comment = |name: Option<&str>, column: Option<&str>, comment: Option<&str>| {
+            Mutation::CommentOn(CommentOn {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: "mario".to_string(),
+                name: name.map(String::from),
+                column: column.map(String::from),
+                comment: comment.map(String::from),
             })
-            .collect();
-
-        db.try_mutate(version(&db).await, mutations).await.unwrap();
+        };
 
-        let state = db.get_state().await.unwrap();
+        let state = db
+            .try_mutate(
+                version(&db).await,
+                vec![
+                    Mutation::CreateSchema(CreateSchema {
+                        database: DEFAULT_CATALOG.to_string(),
+                        name: "mario".to_string(),
+                        if_not_exists: false,
+                    }),
+                    Mutation::CreateExternalTable(CreateExternalTable {
+                        database: DEFAULT_CATALOG.to_string(),
+                        schema: "mario".to_string(),
+                        name: "kart".to_string(),
+                        options: TableOptions::Debug(TableOptionsDebug {
+                            table_type: String::new(),
+                        }),
+                        if_not_exists: false,
+                        or_replace: false,
+                        tunnel: None,
+                    }),
+                    Mutation::CreateView(CreateView {
+                        database: DEFAULT_CATALOG.to_string(),
+                        schema: "mario".to_string(),
+                        name: "party".to_string(),
+                        sql: "select 1".to_string(),
+                        or_replace: false,
+                        columns: Vec::new(),
+                        dependencies: Vec::new(),
+                    }),
+                    comment(None, None, Some("plumbers")),
+                    comment(Some("kart"), None, Some("racing")),
+                    comment(Some("kart"), Some("speed"), Some("fast")),
+                    comment(Some("party"), None, Some("minigames")),
+                ],
+            )
+            .await
+            .unwrap();
 
-        let mut found = HashSet::new();
-        for (_, ent) in state.entries {
-            if !matches!(ent, CatalogEntry::Schema(_)) {
-                found.insert(ent.get_meta().name.clone());
+        let find = |state: &CatalogState, name: &str| {
+            state
+                .entries
+                .values()
+                .find(|ent| ent.get_meta().name == name)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(
+            Some("plumbers"),
+            find(&state, "mario").get_meta().description.as_deref()
+        );
+        assert_eq!(
+            Some("minigames"),
+            find(&state, "party").get_meta().description.as_deref()
+        );
+        match find(&state, "kart") {
+            CatalogEntry::Table(table) => {
+                assert_eq!(Some("racing"), table.meta.description.as_deref());
+                assert_eq!(
+                    Some("fast"),
+                    table.column_comments.get("speed").map(String::as_str)
+                );
             }
+            other => panic!("unexpected entry: {other:?}"),
         }
 
-        for i in 0..10 {
-            assert!(found.contains(i.to_string().as_str()));
+        // Setting a null comment removes it.
+        let state = db
+            .try_mutate(
+                state.version,
+                vec![
+                    comment(Some("kart"), None, None),
+                    comment(Some("kart"), Some("speed"), None),
+                ],
+            )
+            .await
+            .unwrap();
+        match find(&state, "kart") {
+            CatalogEntry::Table(table) => {
+                assert_eq!(None, table.meta.description);
+                assert!(table.column_comments.is_empty());
+            }
+            other => panic!("unexpected entry: {other:?}"),
         }
-    }
-
-    #[tokio::test]
-    async fn duplicate_schema_names() {
-        let db = new_catalog().await;
 
-        // First should pass.
-        db.try_mutate(
-            version(&db).await,
-            vec![Mutation::CreateSchema(CreateSchema {
-                name: "mario".to_string(),
-                if_not_exists: false,
-            })],
-        )
-        .await
-        .unwrap();
+        // Only tables have column comments.
+        let err = db
+            .try_mutate(
+                state.version,
+                vec![comment(Some("party"), Some("a"), Some("nope"))],
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetastoreError::InvalidColumnCommentTarget { .. }),
+            "unexpected error: {err}"
+        );
 
-        // Duplicate should fail.
+        // Missing objects and builtin schemas.
         db.try_mutate(
             version(&db).await,
-            vec![Mutation::CreateSchema(CreateSchema {
-                name: "mario".to_string(),
-                if_not_exists: false,
-            })],
+            vec![comment(Some("toad"), None, Some("nope"))],
         )
         .await
         .unwrap_err();
-
-        // Drop schema.
-        db.try_mutate(
-            version(&db).await,
-            vec![Mutation::DropSchema(DropSchema {
-                name: "mario".to_string(),
-                if_exists: false,
-                cascade: false,
-            })],
-        )
-        .await
-        .unwrap();
-
-        // Re-adding schema should pass.
         db.try_mutate(
             version(&db).await,
-            vec![Mutation::CreateSchema(CreateSchema {
-                name: "mario".to_string(),
-                if_not_exists: false,
+            vec![Mutation::CommentOn(CommentOn {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: "public".to_string(),
+                name: None,
+                column: None,
+                comment: Some("nope".to_string()),
             })],
         )
         .await
-        .unwrap();
+        .unwrap_err();
     }
 
     #[tokio::test]
-    async fn drop_schema_cascade() {
+    async fn object_tags() {
         let db = new_catalog().await;
 
+        let alter_table = |name: &str, operation: AlterTableOperation| {
+            Mutation::AlterTable(AlterTable {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: "mario".to_string(),
+                name: name.to_string(),
+                operation,
+            })
+        };
+        let alter_schema = |name: &str, operation: AlterSchemaOperation| {
+            Mutation::AlterSchema(AlterSchema {
+                database: DEFAULT_CATALOG.to_string(),
+                name: name.to_string(),
+                operation,
+            })
+        };
+        let tags = |tags: &[(&str, &str)]| {
+            tags.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+
         let state = db
             .try_mutate(
                 version(&db).await,
-                vec![Mutation::CreateSchema(CreateSchema {
-                    name: "mushroom".to_string(),
-                    if_not_exists: false,
-                })],
+                vec![
+                    Mutation::CreateSchema(CreateSchema {
+                        database: DEFAULT_CATALOG.to_string(),
+                        name: "mario".to_string(),
+                        if_not_exists: false,
+                    }),
+                    Mutation::CreateExternalTable(CreateExternalTable {
+                        database: DEFAULT_CATALOG.to_string(),
+                        schema: "mario".to_string(),
+                        name: "kart".to_string(),
+                        options: TableOptions::Debug(TableOptionsDebug {
+                            table_type: String::new(),
+                        }),
+                        if_not_exists: false,
+                        or_replace: false,
+                        tunnel: None,
+                    }),
+                    Mutation::CreateView(CreateView {
+                        database: DEFAULT_CATALOG.to_string(),
+                        schema: "mario".to_string(),
+                        name: "party".to_string(),
+                        sql: "select 1".to_string(),
+                        or_replace: false,
+                        columns: Vec::new(),
+                        dependencies: Vec::new(),
+                    }),
+                    alter_schema(
+                        "mario",
+                        AlterSchemaOperation::SetTags {
+                            tags: tags(&[("team", "plumbers")]),
+                        },
+                    ),
+                    alter_table(
+                        "kart",
+                        AlterTableOperation::SetTags {
+                            tags: tags(&[("team", "racing"), ("tier", "gold")]),
+                        },
+                    ),
+                    // Setting an existing key replaces its value.
+                    alter_table(
+                        "kart",
+                        AlterTableOperation::SetTags {
+                            tags: tags(&[("tier", "silver")]),
+                        },
+                    ),
+                ],
             )
             .await
             .unwrap();
 
+        let find = |state: &CatalogState, name: &str| {
+            state
+                .entries
+                .values()
+                .find(|ent| ent.get_meta().name == name)
+                .cloned()
+                .unwrap()
+        };
+        match find(&state, "mario") {
+            CatalogEntry::Schema(schema) => {
+                assert_eq!(tags(&[("team", "plumbers")]), schema.tags)
+            }
+            other => panic!("unexpected entry: {other:?}"),
+        }
+        match find(&state, "kart") {
+            CatalogEntry::Table(table) => {
+                assert_eq!(tags(&[("team", "racing"), ("tier", "silver")]), table.tags)
+            }
+            other => panic!("unexpected entry: {other:?}"),
+        }
+
+        // Unsetting removes keys, ignoring missing ones.
         let state = db
             .try_mutate(
                 state.version,
-                vec![Mutation::CreateView(CreateView {
-                    schema: "mushroom".to_string(),
-                    name: "bowser".to_string(),
-                    sql: "select 1".to_string(),
-                    or_replace: false,
-                    columns: Vec::new(),
-                })],
+                vec![
+                    alter_schema(
+                        "mario",
+                        AlterSchemaOperation::UnsetTags {
+                            keys: vec!["team".to_string()],
+                        },
+                    ),
+                    alter_table(
+                        "kart",
+                        AlterTableOperation::UnsetTags {
+                            keys: vec!["tier".to_string(), "missing".to_string()],
+                        },
+                    ),
+                ],
             )
             .await
             .unwrap();
+        match find(&state, "mario") {
+            CatalogEntry::Schema(schema) => assert!(schema.tags.is_empty()),
+            other => panic!("unexpected entry: {other:?}"),
+        }
+        match find(&state, "kart") {
+            CatalogEntry::Table(table) => assert_eq!(tags(&[("team", "racing")]), table.tags),
+            other => panic!("unexpected entry: {other:?}"),
+        }
 
-        // Drop schema cascade. containing a view.
+        // Only tables and schemas can be tagged.
+        let err = db
+            .try_mutate(
+                state.version,
+                vec![alter_table(
+                    "party",
+                    AlterTableOperation::SetTags {
+                        tags: tags(&[("team", "nope")]),
+                    },
+                )],
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetastoreError::InvalidTagTarget { .. }),
+            "unexpected error: {err}"
+        );
+
+        // Builtin schemas can't be tagged.
         db.try_mutate(
-            state.version,
-            vec![Mutation::DropSchema(DropSchema {
-                name: "mushroom".to_string(),
-                if_exists: false,
-                cascade: true,
-            })],
+            version(&db).await,
+            vec![alter_schema(
+                "public",
+                AlterSchemaOperation::SetTags {
+                    tags: tags(&[("team", "nope")]),
+                },
+            )],
         )
         .await
-        .unwrap();
+        .unwrap_err();
     }
 
     #[tokio::test]
@@ -1568,6 +3204,7 @@ mod tests {
         db.try_mutate(
             version(&db).await,
             vec![Mutation::CreateSchema(CreateSchema {
+                database: DEFAULT_CATALOG.to_string(),
                 name: "luigi".to_string(),
                 if_not_exists: false,
             })],
@@ -1579,11 +3216,13 @@ mod tests {
         db.try_mutate(
             version(&db).await,
             vec![Mutation::CreateView(CreateView {
+                database: DEFAULT_CATALOG.to_string(),
                 schema: "luigi".to_string(),
                 name: "peach".to_string(),
                 sql: "select 1".to_string(),
                 or_replace: false,
                 columns: Vec::new(),
+                dependencies: Vec::new(),
             })],
         )
         .await
@@ -1593,11 +3232,13 @@ mod tests {
         db.try_mutate(
             version(&db).await,
             vec![Mutation::CreateView(CreateView {
+                database: DEFAULT_CATALOG.to_string(),
                 schema: "luigi".to_string(),
                 name: "peach".to_string(),
                 sql: "select 2".to_string(),
                 or_replace: false,
                 columns: Vec::new(),
+                dependencies: Vec::new(),
             })],
         )
         .await
@@ -1612,11 +3253,13 @@ mod tests {
         db.try_mutate(
             version(&db).await,
             vec![Mutation::CreateView(CreateView {
+                database: DEFAULT_CATALOG.to_string(),
                 schema: "public".to_string(),
                 name: "wario".to_string(),
                 sql: "select 1".to_string(),
                 or_replace: false,
                 columns: Vec::new(),
+                dependencies: Vec::new(),
             })],
         )
         .await
@@ -1626,11 +3269,13 @@ mod tests {
         db.try_mutate(
             version(&db).await,
             vec![Mutation::CreateView(CreateView {
+                database: DEFAULT_CATALOG.to_string(),
                 schema: "public".to_string(),
                 name: "wario".to_string(),
                 sql: "select 2".to_string(),
                 or_replace: false,
                 columns: Vec::new(),
+                dependencies: Vec::new(),
             })],
         )
         .await
@@ -1640,11 +3285,13 @@ mod tests {
         db.try_mutate(
             version(&db).await,
             vec![Mutation::CreateView(CreateView {
+                database: DEFAULT_CATALOG.to_string(),
                 schema: "public".to_string(),
                 name: "wario".to_string(),
                 sql: "select 3".to_string(),
                 or_replace: true,
                 columns: Vec::new(),
+                dependencies: Vec::new(),
             })],
         )
         .await
@@ -1670,11 +3317,13 @@ mod tests {
             .try_mutate(
                 initial,
                 vec![Mutation::CreateView(CreateView {
+                    database: DEFAULT_CATALOG.to_string(),
                     schema: "public".to_string(),
                     name: "bowser".to_string(),
                     sql: "select 1".to_string(),
                     or_replace: false,
                     columns: Vec::new(),
+                    dependencies: Vec::new(),
                 })],
             )
             .await
@@ -1685,11 +3334,13 @@ mod tests {
             .try_mutate(
                 state.version,
                 vec![Mutation::CreateView(CreateView {
+                    database: DEFAULT_CATALOG.to_string(),
                     schema: "public".to_string(),
                     name: "bowser".to_string(),
                     sql: "select 1".to_string(),
                     or_replace: false,
                     columns: Vec::new(),
+                    dependencies: Vec::new(),
                 })],
             )
             .await
@@ -1700,11 +3351,13 @@ mod tests {
             .try_mutate(
                 state.version,
                 vec![Mutation::CreateView(CreateView {
+                    database: DEFAULT_CATALOG.to_string(),
                     schema: "public".to_string(),
                     name: "bowser".to_string(),
                     sql: "select 1".to_string(),
                     or_replace: false,
                     columns: Vec::new(),
+                    dependencies: Vec::new(),
                 })],
             )
             .await
@@ -1736,6 +3389,7 @@ mod tests {
             .try_mutate(
                 initial,
                 vec![Mutation::CreateSchema(CreateSchema {
+                    database: DEFAULT_CATALOG.to_string(),
                     name: "mushroom".to_string(),
                     if_not_exists: false,
                 })],
@@ -1746,6 +3400,7 @@ mod tests {
         // Try to add two tables with the same name, each with "if not exists"
         // set to true.
         let mutation = Mutation::CreateExternalTable(CreateExternalTable {
+            database: DEFAULT_CATALOG.to_string(),
             schema: "mushroom".to_string(),
             name: "bowser".to_string(),
             options: TableOptions::Debug(TableOptionsDebug {
@@ -1903,6 +3558,7 @@ mod tests {
             .try_mutate(
                 initial,
                 vec![Mutation::CreateExternalTable(CreateExternalTable {
+                    database: DEFAULT_CATALOG.to_string(),
                     schema: "public".to_string(),
                     name: "read_postgres".to_string(),
                     options: TableOptions::Debug(TableOptionsDebug {
@@ -1916,4 +3572,118 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn restore_dropped_schema() {
+        let db = new_catalog().await;
+
+        db.try_mutate(
+            version(&db).await,
+            vec![
+                Mutation::CreateSchema(CreateSchema {
+                    database: DEFAULT_CATALOG.to_string(),
+                    name: "prod".to_string(),
+                    if_not_exists: false,
+                }),
+                Mutation::CreateView(CreateView {
+                    database: DEFAULT_CATALOG.to_string(),
+                    schema: "prod".to_string(),
+                    name: "numbers".to_string(),
+                    sql: "select 1".to_string(),
+                    or_replace: false,
+                    columns: Vec::new(),
+                    dependencies: Vec::new(),
+                }),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let before_drop = SystemTime::now();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+        let state = db
+            .try_mutate(
+                version(&db).await,
+                vec![Mutation::DropSchema(DropSchema {
+                    database: DEFAULT_CATALOG.to_string(),
+                    name: "prod".to_string(),
+                    if_exists: false,
+                    cascade: true,
+                })],
+            )
+            .await
+            .unwrap();
+        let dropped_version = state.version;
+
+        let state = db
+            .try_mutate(
+                dropped_version,
+                vec![Mutation::RestoreCatalog(RestoreCatalog {
+                    timestamp: before_drop,
+                })],
+            )
+            .await
+            .unwrap();
+
+        // Restoring creates a new version.
+        assert_eq!(dropped_version + 1, state.version);
+
+        let names: HashSet<_> = state
+            .entries
+            .values()
+            .filter(|ent| !ent.get_meta().builtin)
+            .map(|ent| ent.get_meta().name.clone())
+            .collect();
+        let expected: HashSet<_> = ["prod".to_string(), "numbers".to_string()]
+            .into_iter()
+            .collect();
+        assert_eq!(expected, names);
+
+        // Restored objects can be modified like any other objects.
+        db.try_mutate(
+            state.version,
+            vec![Mutation::DropObject(DropObject {
+                database: DEFAULT_CATALOG.to_string(),
+                schema: "prod".to_string(),
+                name: "numbers".to_string(),
+                if_exists: false,
+                cascade: false,
+            })],
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn restore_must_be_alone() {
+        let db = new_catalog().await;
+
+        let e = db
+            .try_mutate(
+                version(&db).await,
+                vec![
+                    Mutation::RestoreCatalog(RestoreCatalog {
+                        timestamp: SystemTime::now(),
+                    }),
+                    Mutation::CreateSchema(CreateSchema {
+                        database: DEFAULT_CATALOG.to_string(),
+                        name: "prod".to_string(),
+                        if_not_exists: false,
+                    }),
+                ],
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(e, MetastoreError::RestoreCatalogNotAlone), "{e}");
+
+        db.try_mutate(
+            version(&db).await,
+            vec![Mutation::RestoreCatalog(RestoreCatalog {
+                timestamp: SystemTime::UNIX_EPOCH,
+            })],
+        )
+        .await
+        .unwrap_err();
+    }
 }
@@ -8,6 +8,14 @@ pub enum MetastoreError {
     #[error("Catalog version mismatch; have: {have}, need: {need}")]
     VersionMismatch { have: u64, need: u64 },
 
+    #[error(
+        "Catalog for database '{db_id}' was concurrently modified by another writer: {source}"
+    )]
+    ConcurrentCatalogWrite {
+        db_id: uuid::Uuid,
+        source: crate::storage::StorageError,
+    },
+
     #[error("Duplicate name: {0}")]
     DuplicateName(String),
 
@@ -36,6 +44,9 @@ pub enum MetastoreError {
     #[error("Missing database: {0}")]
     MissingDatabase(String),
 
+    #[error("Database '{0}' is not a native database")]
+    NotNativeDatabase(String),
+
     #[error("Missing tunnel: {0}")]
     MissingTunnel(String),
 
@@ -70,6 +81,18 @@ pub enum MetastoreError {
     #[error("Schema {schema} has {num_objects} child objects")]
     SchemaHasChildren { schema: u32, num_objects: usize },
 
+    #[error("Cannot drop '{object}' because other objects depend on it: {}. Use CASCADE to drop them as well", .dependents.join(", "))]
+    ObjectHasDependents {
+        object: String,
+        dependents: Vec<String>,
+    },
+
+    #[error("Cannot rename '{object}' because other objects depend on it: {}", .dependents.join(", "))]
+    RenamedObjectHasDependents {
+        object: String,
+        dependents: Vec<String>,
+    },
+
     #[error("Object {object} of type '{object_type}' has invalid parent id: {parent}")]
     ObjectHasInvalidParentId {
         object: u32,
@@ -83,6 +106,33 @@ pub enum MetastoreError {
     #[error("Cannot modify builtin object: {0:?}")]
     CannotModifyBuiltin(protogen::metastore::types::catalog::CatalogEntry),
 
+    #[error("Role passwords apply to all databases and can't be set for a single database")]
+    RolePasswordForDatabase,
+
+    #[error("Read-only access applies to all databases and can't be set for a single database")]
+    RoleReadOnlyForDatabase,
+
+    #[error("Statistics can only be collected for tables, '{name}' is a {entry_type}")]
+    InvalidStatisticsTarget {
+        name: String,
+        entry_type: protogen::metastore::types::catalog::EntryType,
+    },
+
+    #[error("Column comments can only be set on tables, '{name}' is a {entry_type}")]
+    InvalidColumnCommentTarget {
+        name: String,
+        entry_type: protogen::metastore::types::catalog::EntryType,
+    },
+
+    #[error("Tags can only be set on tables and schemas, '{name}' is a {entry_type}")]
+    InvalidTagTarget {
+        name: String,
+        entry_type: protogen::metastore::types::catalog::EntryType,
+    },
+
+    #[error("Only external tables have a cached schema, '{0}' is not external")]
+    SchemaRefreshNotExternal(String),
+
     #[error("Cannot exceed {max} objects in a database")]
     MaxNumberOfObjects { max: usize },
 
@@ -104,6 +154,12 @@ pub enum MetastoreError {
     #[error("Cannot specify both 'IF NOT EXISTS' and 'OR REPLACE'")]
     InvalidCreatePolicy,
 
+    #[error("No catalog snapshot exists at or before {0:?}")]
+    MissingCatalogSnapshot(std::time::SystemTime),
+
+    #[error("Restoring the catalog cannot be combined with other mutations")]
+    RestoreCatalogNotAlone,
+
     #[error(transparent)]
     Io(#[from] std::io::Error),
 }
@@ -116,6 +172,9 @@ impl From<MetastoreError> for tonic::Status {
         // error itself without the user being notified.
         let strat = match &value {
             MetastoreError::VersionMismatch { .. } => ResolveErrorStrategy::FetchCatalogAndRetry,
+            MetastoreError::ConcurrentCatalogWrite { .. } => {
+                ResolveErrorStrategy::FetchCatalogAndRetry
+            }
             _ => ResolveErrorStrategy::Unknown,
         };
 
@@ -25,17 +25,30 @@ pub async fn start_inprocess_local(
     start_inprocess(Arc::new(local)).await
 }
 
+/// Starts an in-process metastore storing catalogs in a Postgres database.
+pub async fn start_inprocess_postgres(conn_str: &str) -> Result<MetastoreServiceClient<Channel>> {
+    info!("starting postgres backed metastore");
+    let service = Service::connect_postgres(conn_str).await?;
+    start_inprocess_service(service).await
+}
+
 /// Starts an in-process metastore service, returning a client for the service.
 ///
 /// Useful for tests, as well as when running GlareDB locally.
 pub async fn start_inprocess(
     store: Arc<dyn ObjectStore>,
 ) -> Result<MetastoreServiceClient<Channel>> {
+    start_inprocess_service(Service::new(store)).await
+}
+
+/// Starts serving an already created metastore service in-process, returning a
+/// client for the service.
+pub async fn start_inprocess_service(service: Service) -> Result<MetastoreServiceClient<Channel>> {
     let (client, server) = tokio::io::duplex(1024);
 
     tokio::spawn(async move {
         if let Err(e) = Server::builder()
-            .add_service(MetastoreServiceServer::new(Service::new(store)))
+            .add_service(MetastoreServiceServer::new(service))
             .serve_with_incoming(futures::stream::iter(vec![Ok::<_, MetastoreError>(server)]))
             .await
         {
@@ -1,12 +1,15 @@
 use crate::database::DatabaseCatalog;
 use crate::errors::MetastoreError;
 use crate::storage::persist::Storage;
+use crate::storage::postgres::PostgresStorage;
+use crate::storage::CatalogStorage;
 use async_trait::async_trait;
 use dashmap::DashMap;
 use object_store::ObjectStore;
 use protogen::gen::metastore::service::metastore_service_server::MetastoreService;
 use protogen::gen::metastore::service::{
-    self, FetchCatalogRequest, FetchCatalogResponse, MutateRequest, MutateResponse,
+    self, FetchCatalogRequest, FetchCatalogResponse, FetchRolePasswordsRequest,
+    FetchRolePasswordsResponse, MutateRequest, MutateResponse,
 };
 use protogen::metastore::types::service::Mutation;
 use std::sync::Arc;
@@ -16,8 +19,8 @@ use uuid::Uuid;
 
 /// Metastore GRPC service.
 pub struct Service {
-    /// Reference to underlying object storage.
-    storage: Arc<Storage>,
+    /// Reference to underlying catalog storage.
+    storage: Arc<CatalogStorage>,
     /// Database catalogs that this process knows about.
     ///
     /// This is filled on demand. There's currently no method for dropping
@@ -35,9 +38,22 @@ impl Service {
         let process_id = Uuid::new_v4();
         info!(%process_id, "Creating new Metastore service");
 
-        let storage = Arc::new(Storage::new(process_id, store));
+        Self::with_storage(Storage::new(process_id, store).into())
+    }
+
+    /// Create a new service storing catalogs in the Postgres database at
+    /// `conn_str`.
+    pub async fn connect_postgres(conn_str: &str) -> Result<Service, MetastoreError> {
+        let process_id = Uuid::new_v4();
+        info!(%process_id, "Creating new Metastore service backed by Postgres");
+
+        let storage = PostgresStorage::connect(process_id, conn_str).await?;
+        Ok(Self::with_storage(storage.into()))
+    }
+
+    pub fn with_storage(storage: CatalogStorage) -> Service {
         Service {
-            storage,
+            storage: Arc::new(storage),
             catalogs: DashMap::new(),
         }
     }
@@ -110,6 +126,23 @@ impl MetastoreService for Service {
             catalog: Some(updated.try_into().map_err(MetastoreError::from)?),
         }))
     }
+
+    async fn fetch_role_passwords(
+        &self,
+        request: Request<FetchRolePasswordsRequest>,
+    ) -> Result<Response<FetchRolePasswordsResponse>, Status> {
+        let req = request.into_inner();
+        debug!(?req, "fetch role passwords");
+        let id = Uuid::from_slice(&req.db_id)
+            .map_err(|_| MetastoreError::InvalidDatabaseId(req.db_id))?;
+
+        let catalog = self.get_or_load_catalog(id).await?;
+        let role_passwords = catalog.get_role_passwords().await?;
+
+        Ok(Response::new(FetchRolePasswordsResponse {
+            role_passwords: role_passwords.into_iter().map(Into::into).collect(),
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -117,7 +150,10 @@ mod tests {
     use super::*;
     use object_store::memory::InMemory;
     use protogen::metastore::types::catalog::{CatalogEntry, CatalogState};
-    use protogen::metastore::types::service::{CreateSchema, Mutation};
+    use protogen::metastore::types::service::{
+        AlterRole, AlterRoleOperation, CreateSchema, Mutation,
+    };
+    use sqlbuiltins::builtins::DEFAULT_CATALOG;
 
     fn new_service() -> Service {
         let store = Arc::new(InMemory::new());
@@ -154,6 +190,7 @@ mod tests {
             db_id: id_bs.clone(),
             catalog_version: resp.catalog.unwrap().version,
             mutations: vec![Mutation::CreateSchema(CreateSchema {
+                database: DEFAULT_CATALOG.to_string(),
                 name: "test_schema".to_string(),
                 if_not_exists: false,
             })
@@ -181,4 +218,53 @@ mod tests {
             .unwrap();
         assert!(matches!(ent, CatalogEntry::Schema(_)));
     }
+
+    #[tokio::test]
+    async fn role_passwords_not_in_catalog() {
+        let svc = new_service();
+        let id_bs = Uuid::new_v4().into_bytes().to_vec();
+
+        let resp = svc
+            .fetch_catalog(Request::new(FetchCatalogRequest {
+                db_id: id_bs.clone(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let resp = svc
+            .mutate_catalog(Request::new(MutateRequest {
+                db_id: id_bs.clone(),
+                catalog_version: resp.catalog.unwrap().version,
+                mutations: vec![Mutation::AlterRole(AlterRole {
+                    name: "sean".to_string(),
+                    database: None,
+                    operation: AlterRoleOperation::SetPassword {
+                        scram_verifier: Some("verifier".to_string()),
+                    },
+                })
+                .try_into()
+                .unwrap()],
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(resp.catalog.unwrap().role_passwords.is_empty());
+
+        let resp = svc
+            .fetch_catalog(Request::new(FetchCatalogRequest {
+                db_id: id_bs.clone(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(resp.catalog.unwrap().role_passwords.is_empty());
+
+        let resp = svc
+            .fetch_role_passwords(Request::new(FetchRolePasswordsRequest { db_id: id_bs }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(1, resp.role_passwords.len());
+        assert_eq!("sean", resp.role_passwords[0].role);
+    }
 }
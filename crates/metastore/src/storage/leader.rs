@@ -0,0 +1,167 @@
+//! Lease based leadership for background tasks.
+//!
+//! Multiple processes may share the same storage. Background tasks that should
+//! only be running in one of those processes at a time can use a
+//! `LeaderElection`, which continually tries to acquire a lease for the task.
+//! A process is only the leader while it holds a valid lease.
+//!
+//! Leadership is advisory. Leases can be lost (e.g. if renewing fails), so
+//! tasks should check `Leadership::is_leader` before each unit of work.
+
+use crate::storage::lease::RemoteLeaser;
+use crate::storage::Result;
+use object_store::ObjectStore;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{debug, debug_span, error, info, warn, Instrument};
+use uuid::Uuid;
+
+/// How often to try to acquire the lease when some other process is the
+/// leader.
+const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How often to check that the held lease is still valid.
+const VALIDITY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Elects a single leader across all processes using the same lease id.
+#[derive(Debug)]
+pub struct LeaderElection {
+    lease_id: Uuid,
+    leaser: RemoteLeaser,
+    retry_interval: Duration,
+}
+
+impl LeaderElection {
+    /// Create a new election for `lease_id`.
+    ///
+    /// Every process taking part in the election needs to use the same lease
+    /// id. Lease ids share a namespace with database ids, so a lease id
+    /// shouldn't be reused for anything else.
+    pub fn new(process_id: Uuid, store: Arc<dyn ObjectStore>, lease_id: Uuid) -> LeaderElection {
+        LeaderElection {
+            lease_id,
+            leaser: RemoteLeaser::new(process_id, store),
+            retry_interval: DEFAULT_RETRY_INTERVAL,
+        }
+    }
+
+    /// Set how often to try to acquire leadership when not the leader.
+    pub fn with_retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Start trying to become the leader in the background.
+    ///
+    /// The lease is released once every `Leadership` handle is dropped.
+    pub async fn start(self) -> Result<Leadership> {
+        self.leaser.initialize(&self.lease_id).await?;
+
+        let (leader_tx, leader_rx) = watch::channel(false);
+        let span = debug_span!("leader_election", lease_id = %self.lease_id);
+        tokio::spawn(self.run(leader_tx).instrument(span));
+
+        Ok(Leadership {
+            is_leader: leader_rx,
+        })
+    }
+
+    async fn run(self, leader_tx: watch::Sender<bool>) {
+        loop {
+            match self.leaser.acquire(self.lease_id).await {
+                Ok(lease) => {
+                    info!("acquired leadership");
+                    let _ = leader_tx.send(true);
+
+                    loop {
+                        tokio::select! {
+                            _ = tokio::time::sleep(VALIDITY_CHECK_INTERVAL) => {
+                                if !lease.is_valid() {
+                                    break;
+                                }
+                            }
+                            _ = leader_tx.closed() => {
+                                debug!("leadership no longer needed, dropping lease");
+                                if let Err(e) = lease.drop_lease().await {
+                                    error!(%e, "failed to drop leadership lease");
+                                }
+                                return;
+                            }
+                        }
+                    }
+
+                    warn!("lost leadership");
+                    let _ = leader_tx.send(false);
+                }
+                Err(e) if e.is_write_conflict() => {
+                    debug!(%e, "another process is the leader");
+                }
+                Err(e) => {
+                    error!(%e, "failed to acquire leadership");
+                }
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(self.retry_interval) => (),
+                _ = leader_tx.closed() => return,
+            }
+        }
+    }
+}
+
+/// Handle for checking if this process is the leader.
+#[derive(Debug, Clone)]
+pub struct Leadership {
+    is_leader: watch::Receiver<bool>,
+}
+
+impl Leadership {
+    /// Returns whether or not this process is currently the leader.
+    pub fn is_leader(&self) -> bool {
+        *self.is_leader.borrow()
+    }
+
+    /// Wait until this process becomes the leader.
+    pub async fn wait_for_leadership(&mut self) {
+        // Only errors if the election stopped, which can't happen while we
+        // have a handle.
+        let _ = self.is_leader.wait_for(|is_leader| *is_leader).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+
+    #[tokio::test]
+    async fn single_leader() {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let lease_id = Uuid::new_v4();
+
+        let mut first = LeaderElection::new(Uuid::new_v4(), store.clone(), lease_id)
+            .with_retry_interval(Duration::from_millis(10))
+            .start()
+            .await
+            .unwrap();
+        first.wait_for_leadership().await;
+
+        let mut second = LeaderElection::new(Uuid::new_v4(), store.clone(), lease_id)
+            .with_retry_interval(Duration::from_millis(10))
+            .start()
+            .await
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(first.is_leader());
+        assert!(!second.is_leader());
+
+        // Dropping the first handle releases the lease, allowing the second
+        // process to become the leader.
+        drop(first);
+        tokio::time::timeout(Duration::from_secs(5), second.wait_for_leadership())
+            .await
+            .unwrap();
+    }
+}
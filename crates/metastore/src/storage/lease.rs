@@ -266,6 +266,20 @@ impl LeaseRenewer {
 
         self.write_lease(new_lease).await?;
 
+        // Reading and writing the lease isn't atomic, another process may have
+        // acquired the lease at the same time. Read it back to make sure we're
+        // the one holding it.
+        let lease = self.read_lease(Some(generation)).await?;
+        if let Some(held_by) = lease.held_by {
+            if held_by != self.process_id {
+                return Err(StorageError::LeaseHeldByOtherProcess {
+                    db_id: self.db_id,
+                    other_process_id: held_by,
+                    current_process_id: self.process_id,
+                });
+            }
+        }
+
         Ok(generation)
     }
 
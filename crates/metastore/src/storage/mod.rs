@@ -1,10 +1,13 @@
 //! Metastore persistent storage.
 
+pub mod leader;
 pub mod persist;
+pub mod postgres;
 
 mod lease;
 
 use object_store::path::Path as ObjectPath;
+use protogen::metastore::types::storage::PersistedCatalog;
 use std::time::SystemTime;
 use uuid::Uuid;
 
@@ -48,6 +51,9 @@ pub enum StorageError {
     #[error("Lease renewer exited.")]
     LeaseRenewerExited,
 
+    #[error("Missing catalog for database: {db_id}")]
+    MissingCatalog { db_id: Uuid },
+
     #[error(transparent)]
     ProtoConv(#[from] protogen::errors::ProtoConvError),
 
@@ -59,10 +65,105 @@ pub enum StorageError {
 
     #[error(transparent)]
     ObjectStore(#[from] object_store::Error),
+
+    #[error(transparent)]
+    Postgres(#[from] tokio_postgres::Error),
+}
+
+impl StorageError {
+    /// Returns whether this error is the result of another process writing to
+    /// the same catalog.
+    ///
+    /// Nothing is written when these errors are returned, so the write can be
+    /// retried against the latest version of the catalog.
+    pub fn is_write_conflict(&self) -> bool {
+        matches!(
+            self,
+            StorageError::AttemptedOutOfDataCatalogWrite { .. }
+                | StorageError::LeaseHeldByOtherProcess { .. }
+                | StorageError::LeaseGenerationMismatch { .. }
+        )
+    }
 }
 
 pub type Result<T, E = StorageError> = std::result::Result<T, E>;
 
+/// Persistent storage for database catalogs.
+#[derive(Debug, Clone)]
+pub enum CatalogStorage {
+    /// Catalogs stored in an object store, with writes synchronized using
+    /// leases.
+    ObjectStore(persist::Storage),
+    /// Catalogs stored in Postgres, with writes synchronized using
+    /// transactions.
+    Postgres(postgres::PostgresStorage),
+}
+
+impl CatalogStorage {
+    /// Initialize a new catalog for a database if it doesn't exist.
+    pub async fn initialize(&self, db_id: Uuid) -> Result<()> {
+        match self {
+            Self::ObjectStore(storage) => storage.initialize(db_id).await,
+            Self::Postgres(storage) => storage.initialize(db_id).await,
+        }
+    }
+
+    pub async fn latest_version(&self, db_id: &Uuid) -> Result<u64> {
+        match self {
+            Self::ObjectStore(storage) => storage.latest_version(db_id).await,
+            Self::Postgres(storage) => storage.latest_version(db_id).await,
+        }
+    }
+
+    /// Read the latest version of some catalog.
+    pub async fn read_catalog(&self, db_id: Uuid) -> Result<PersistedCatalog> {
+        match self {
+            Self::ObjectStore(storage) => storage.read_catalog(db_id).await,
+            Self::Postgres(storage) => storage.read_catalog(db_id).await,
+        }
+    }
+
+    /// Read the latest version of some catalog that was written at or before
+    /// the provided timestamp.
+    pub async fn read_catalog_at(
+        &self,
+        db_id: Uuid,
+        timestamp: SystemTime,
+    ) -> Result<Option<PersistedCatalog>> {
+        match self {
+            Self::ObjectStore(storage) => storage.read_catalog_at(db_id, timestamp).await,
+            Self::Postgres(storage) => storage.read_catalog_at(db_id, timestamp).await,
+        }
+    }
+
+    /// Write a new version of the catalog.
+    ///
+    /// Errors if `old_version` isn't the latest version of the catalog.
+    pub async fn write_catalog(
+        &self,
+        db_id: Uuid,
+        old_version: u64,
+        catalog: PersistedCatalog,
+    ) -> Result<()> {
+        match self {
+            Self::ObjectStore(storage) => storage.write_catalog(db_id, old_version, catalog).await,
+            Self::Postgres(storage) => storage.write_catalog(db_id, old_version, catalog).await,
+        }
+    }
+}
+
+impl From<persist::Storage> for CatalogStorage {
+    fn from(value: persist::Storage) -> Self {
+        CatalogStorage::ObjectStore(value)
+    }
+}
+
+impl From<postgres::PostgresStorage> for CatalogStorage {
+    fn from(value: postgres::PostgresStorage) -> Self {
+        CatalogStorage::Postgres(value)
+    }
+}
+
 pub trait StorageObject<S: AsRef<str>> {
     /// The name of the storage object.
     fn object_name(&self) -> S;
@@ -11,6 +11,7 @@ use protogen::metastore::types::catalog::{CatalogState, DeploymentMetadata};
 use protogen::metastore::types::storage::{CatalogMetadata, ExtraState, PersistedCatalog};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::SystemTime;
 use tracing::{debug, error};
 use uuid::Uuid;
 
@@ -69,9 +70,15 @@ impl Storage {
                 version: 0,
                 entries: HashMap::new(),
                 deployment: DeploymentMetadata { storage_size: 0 },
+                session_var_defaults: Vec::new(),
+                role_passwords: Vec::new(),
+                table_statistics: HashMap::new(),
+                table_schemas: HashMap::new(),
+                read_only_roles: Vec::new(),
             },
             extra: ExtraState {
                 oid_counter: FIRST_AVAILABLE_ID,
+                written_at: Some(SystemTime::now()),
             },
         }
         .try_into()?;
@@ -120,20 +127,82 @@ impl Storage {
         // we'll be reading one version out of date.
 
         let metadata = self.read_metadata(&db_id).await?;
+        self.read_catalog_version(db_id, metadata.latest_version)
+            .await
+    }
+
+    /// Read the latest version of some catalog that was written at or before
+    /// the provided timestamp.
+    ///
+    /// Returns `None` if every version of the catalog was written after the
+    /// timestamp.
+    pub async fn read_catalog_at(
+        &self,
+        db_id: Uuid,
+        timestamp: SystemTime,
+    ) -> Result<Option<PersistedCatalog>> {
+        // Versions are written in order, so their timestamps are as well.
+        // Binary search for the last version written before the timestamp.
+        let mut lo = 0;
+        let mut lo_catalog = self.read_catalog_version(db_id, lo).await?;
+        if self.written_at(db_id, lo, &lo_catalog).await? > timestamp {
+            return Ok(None);
+        }
 
+        let mut hi = self.latest_version(&db_id).await?;
+        while lo < hi {
+            let mid = lo + (hi - lo + 1) / 2;
+            let catalog = self.read_catalog_version(db_id, mid).await?;
+            if self.written_at(db_id, mid, &catalog).await? <= timestamp {
+                lo = mid;
+                lo_catalog = catalog;
+            } else {
+                hi = mid - 1;
+            }
+        }
+
+        debug!(%db_id, version = %lo, "found catalog snapshot");
+
+        Ok(Some(lo_catalog))
+    }
+
+    /// Read a specific version of some catalog.
+    async fn read_catalog_version(&self, db_id: Uuid, version: u64) -> Result<PersistedCatalog> {
         let path = PERSISTENT_CATALOG_OBJECT
-            .with_version(metadata.latest_version)
+            .with_version(version)
             .visible_path(&db_id);
         let bs = self.store.get(&path).await?.bytes().await?;
 
         // Log we'll want to keep an eye on so we can monitor catalog size.
-        debug!(byte_len = %bs.len(), %db_id, "read catalog");
+        debug!(byte_len = %bs.len(), %db_id, %version, "read catalog");
 
         let proto = storage::PersistedCatalog::decode(bs)?;
 
         Ok(proto.try_into()?)
     }
 
+    /// Get the time a version of the catalog was written.
+    ///
+    /// Catalogs written before the write time was persisted fall back to the
+    /// last modified time of the object.
+    async fn written_at(
+        &self,
+        db_id: Uuid,
+        version: u64,
+        catalog: &PersistedCatalog,
+    ) -> Result<SystemTime> {
+        if let Some(written_at) = catalog.extra.written_at {
+            return Ok(written_at);
+        }
+
+        let path = PERSISTENT_CATALOG_OBJECT
+            .with_version(version)
+            .visible_path(&db_id);
+        let meta = self.store.head(&path).await?;
+
+        Ok(meta.last_modified.into())
+    }
+
     /// Write a new version of the catalog.
     ///
     /// The catalog must already exist.
@@ -219,6 +288,18 @@ impl Storage {
 
         // Move objects...
 
+        // Check the version again right before making anything visible. The
+        // lease should prevent concurrent writes, but writing the lease itself
+        // isn't atomic. If some other process managed to write a new version
+        // in the meantime, bail instead of overwriting it.
+        let current = self.read_metadata(&db_id).await?;
+        if current.latest_version != old_version {
+            return Err(StorageError::AttemptedOutOfDataCatalogWrite {
+                expected: current.latest_version,
+                have: old_version,
+            });
+        }
+
         // Blindly overwrite to prevent subsequent catalog writes from getting
         // stuck if we happen to fail on step 6 or fail the lease check.
         self.store
@@ -302,6 +383,80 @@ mod tests {
         storage.write_catalog(db_id, 0, catalog).await.unwrap_err();
     }
 
+    #[tokio::test]
+    async fn concurrent_writers_conflict() {
+        let first = new_storage();
+        // Second process sharing the same object store.
+        let second = Storage::new(Uuid::new_v4(), first.store.clone());
+
+        let db_id = Uuid::new_v4();
+        first.initialize(db_id).await.unwrap();
+        second.initialize(db_id).await.unwrap();
+
+        // Both processes read the same version.
+        let mut first_catalog = first.read_catalog(db_id).await.unwrap();
+        let mut second_catalog = second.read_catalog(db_id).await.unwrap();
+        first_catalog.state.version += 1;
+        second_catalog.state.version += 1;
+
+        first.write_catalog(db_id, 0, first_catalog).await.unwrap();
+
+        // Second write needs to fail instead of overwriting the first.
+        let err = second
+            .write_catalog(db_id, 0, second_catalog)
+            .await
+            .unwrap_err();
+        assert!(err.is_write_conflict(), "unexpected error: {err}");
+        assert_eq!(1, second.latest_version(&db_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn read_catalog_at_timestamp() {
+        let storage = new_storage();
+
+        let db_id = Uuid::new_v4();
+        storage.initialize(db_id).await.unwrap();
+
+        let before_init = SystemTime::UNIX_EPOCH;
+        assert!(storage
+            .read_catalog_at(db_id, before_init)
+            .await
+            .unwrap()
+            .is_none());
+
+        // Write a few versions, remembering the time in between each write.
+        let mut timestamps = Vec::new();
+        let mut catalog = storage.read_catalog(db_id).await.unwrap();
+        for _ in 0..5 {
+            timestamps.push(SystemTime::now());
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+            let old_version = catalog.state.version;
+            catalog.state.version += 1;
+            catalog.extra.written_at = Some(SystemTime::now());
+            storage
+                .write_catalog(db_id, old_version, catalog.clone())
+                .await
+                .unwrap();
+        }
+
+        for (version, timestamp) in timestamps.into_iter().enumerate() {
+            let got = storage
+                .read_catalog_at(db_id, timestamp)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(version as u64, got.state.version);
+        }
+
+        let latest = storage
+            .read_catalog_at(db_id, SystemTime::now())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(5, latest.state.version);
+    }
+
     #[tokio::test]
     async fn write_failed_lease() {
         let storage = new_storage();
@@ -0,0 +1,430 @@
+//! Catalog storage backed by tables in a Postgres database.
+//!
+//! Every version of a catalog is stored as a row in `glaredb_catalog_versions`,
+//! with `glaredb_catalog_metadata` pointing to the latest version of each
+//! catalog. Writes happen in a transaction that locks the catalog's metadata
+//! row, so any number of metastore processes can share the same database
+//! without needing leases.
+//!
+//! Only Postgres is supported as a relational backend. SQLite can't be
+//! shared between compute nodes on different hosts, so it offers nothing over
+//! storing catalogs in the data directory.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use bytes::BytesMut;
+use datasources::postgres::tls::MakeRustlsConnect;
+use pgrepr::oid::FIRST_AVAILABLE_ID;
+use prost::Message;
+use protogen::gen::metastore::storage;
+use protogen::metastore::types::catalog::{CatalogState, DeploymentMetadata};
+use protogen::metastore::types::storage::{ExtraState, PersistedCatalog};
+use tokio::sync::{Mutex, MutexGuard};
+use tokio_postgres::Client;
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use crate::storage::{Result, StorageError};
+
+/// Statements for creating the tables catalogs are stored in. Safe to run
+/// multiple times.
+const CREATE_TABLES: &str = "
+CREATE TABLE IF NOT EXISTS glaredb_catalog_versions (
+    db_id TEXT NOT NULL,
+    version BIGINT NOT NULL,
+    written_at TIMESTAMPTZ NOT NULL,
+    catalog BYTEA NOT NULL,
+    PRIMARY KEY (db_id, version)
+);
+CREATE TABLE IF NOT EXISTS glaredb_catalog_metadata (
+    db_id TEXT PRIMARY KEY,
+    latest_version BIGINT NOT NULL,
+    last_written_by TEXT NOT NULL
+);
+";
+
+/// Persistent storage for database catalogs in Postgres.
+///
+/// Connections use TLS depending on the `sslmode` of the connection string,
+/// with the server's certificate verified against the webpki roots.
+#[derive(Debug, Clone)]
+pub struct PostgresStorage {
+    process_id: Uuid,
+    conn_str: String,
+    /// Connection to the database, reconnected if the connection closes.
+    ///
+    /// Transactions need exclusive access to the client.
+    client: Arc<Mutex<Client>>,
+}
+
+impl PostgresStorage {
+    /// Connect to the Postgres database at `conn_str`, creating the tables
+    /// for storing catalogs if they don't exist.
+    pub async fn connect(process_id: Uuid, conn_str: impl Into<String>) -> Result<PostgresStorage> {
+        let conn_str = conn_str.into();
+        let client = connect(&conn_str).await?;
+        client.batch_execute(CREATE_TABLES).await?;
+
+        Ok(PostgresStorage {
+            process_id,
+            conn_str,
+            client: Arc::new(Mutex::new(client)),
+        })
+    }
+
+    /// Get the client, reconnecting if the connection was closed.
+    async fn client(&self) -> Result<MutexGuard<'_, Client>> {
+        let mut client = self.client.lock().await;
+        if client.is_closed() {
+            debug!("reconnecting to postgres catalog storage");
+            *client = connect(&self.conn_str).await?;
+        }
+        Ok(client)
+    }
+
+    /// Initialize a new catalog for a database.
+    ///
+    /// Idempotent, initializing a catalog that already exists does nothing.
+    pub async fn initialize(&self, db_id: Uuid) -> Result<()> {
+        let written_at = SystemTime::now();
+        let first_catalog = PersistedCatalog {
+            state: CatalogState {
+                version: 0,
+                entries: HashMap::new(),
+                deployment: DeploymentMetadata { storage_size: 0 },
+                session_var_defaults: Vec::new(),
+                role_passwords: Vec::new(),
+                table_statistics: HashMap::new(),
+                table_schemas: HashMap::new(),
+                read_only_roles: Vec::new(),
+            },
+            extra: ExtraState {
+                oid_counter: FIRST_AVAILABLE_ID,
+                written_at: Some(written_at),
+            },
+        };
+        let bs = encode_catalog(first_catalog)?;
+
+        let mut client = self.client().await?;
+        let tx = client.transaction().await?;
+        let inserted = tx
+            .execute(
+                "INSERT INTO glaredb_catalog_metadata (db_id, latest_version, last_written_by)
+                 VALUES ($1, 0, $2)
+                 ON CONFLICT (db_id) DO NOTHING",
+                &[&db_id.to_string(), &self.process_id.to_string()],
+            )
+            .await?;
+        if inserted == 0 {
+            // Already initialized.
+            return Ok(());
+        }
+
+        debug!(%db_id, "initializing new catalog for database");
+        tx.execute(
+            "INSERT INTO glaredb_catalog_versions (db_id, version, written_at, catalog)
+             VALUES ($1, 0, $2, $3)",
+            &[&db_id.to_string(), &written_at, &bs],
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn latest_version(&self, db_id: &Uuid) -> Result<u64> {
+        let client = self.client().await?;
+        let row = client
+            .query_opt(
+                "SELECT latest_version FROM glaredb_catalog_metadata WHERE db_id = $1",
+                &[&db_id.to_string()],
+            )
+            .await?
+            .ok_or(StorageError::MissingCatalog { db_id: *db_id })?;
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    /// Read the latest version of some catalog.
+    ///
+    /// The catalog must already exist.
+    pub async fn read_catalog(&self, db_id: Uuid) -> Result<PersistedCatalog> {
+        let client = self.client().await?;
+        let row = client
+            .query_opt(
+                "SELECT v.catalog
+                 FROM glaredb_catalog_metadata m
+                 INNER JOIN glaredb_catalog_versions v
+                   ON v.db_id = m.db_id AND v.version = m.latest_version
+                 WHERE m.db_id = $1",
+                &[&db_id.to_string()],
+            )
+            .await?
+            .ok_or(StorageError::MissingCatalog { db_id })?;
+        decode_catalog(row.get(0))
+    }
+
+    /// Read the latest version of some catalog that was written at or before
+    /// the provided timestamp.
+    ///
+    /// Returns `None` if every version of the catalog was written after the
+    /// timestamp.
+    pub async fn read_catalog_at(
+        &self,
+        db_id: Uuid,
+        timestamp: SystemTime,
+    ) -> Result<Option<PersistedCatalog>> {
+        let client = self.client().await?;
+        let row = client
+            .query_opt(
+                "SELECT catalog
+                 FROM glaredb_catalog_versions
+                 WHERE db_id = $1 AND written_at <= $2
+                 ORDER BY version DESC
+                 LIMIT 1",
+                &[&db_id.to_string(), &timestamp],
+            )
+            .await?;
+        row.map(|row| decode_catalog(row.get(0))).transpose()
+    }
+
+    /// Write a new version of the catalog.
+    ///
+    /// The catalog must already exist.
+    pub async fn write_catalog(
+        &self,
+        db_id: Uuid,
+        old_version: u64,
+        catalog: PersistedCatalog,
+    ) -> Result<()> {
+        let new_version = catalog.state.version;
+        let written_at = catalog.extra.written_at.unwrap_or_else(SystemTime::now);
+        let bs = encode_catalog(catalog)?;
+
+        let mut client = self.client().await?;
+        let tx = client.transaction().await?;
+
+        // Lock the metadata row, concurrent writes to the same catalog wait
+        // until this transaction completes.
+        let row = tx
+            .query_opt(
+                "SELECT latest_version FROM glaredb_catalog_metadata WHERE db_id = $1 FOR UPDATE",
+                &[&db_id.to_string()],
+            )
+            .await?
+            .ok_or(StorageError::MissingCatalog { db_id })?;
+        let latest_version = row.get::<_, i64>(0) as u64;
+        if latest_version != old_version {
+            return Err(StorageError::AttemptedOutOfDataCatalogWrite {
+                expected: latest_version,
+                have: old_version,
+            });
+        }
+
+        tx.execute(
+            "INSERT INTO glaredb_catalog_versions (db_id, version, written_at, catalog)
+             VALUES ($1, $2, $3, $4)",
+            &[&db_id.to_string(), &(new_version as i64), &written_at, &bs],
+        )
+        .await?;
+        tx.execute(
+            "UPDATE glaredb_catalog_metadata
+             SET latest_version = $2, last_written_by = $3
+             WHERE db_id = $1",
+            &[
+                &db_id.to_string(),
+                &(new_version as i64),
+                &self.process_id.to_string(),
+            ],
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+async fn connect(conn_str: &str) -> Result<Client> {
+    let (client, conn) = tokio_postgres::connect(conn_str, MakeRustlsConnect::default()).await?;
+    tokio::spawn(async move {
+        if let Err(e) = conn.await {
+            error!(%e, "postgres catalog storage connection errored");
+        }
+    });
+    Ok(client)
+}
+
+fn encode_catalog(catalog: PersistedCatalog) -> Result<Vec<u8>> {
+    let proto: storage::PersistedCatalog = catalog.try_into()?;
+    let mut bs = BytesMut::new();
+    proto.encode(&mut bs)?;
+    Ok(bs.to_vec())
+}
+
+fn decode_catalog(bs: &[u8]) -> Result<PersistedCatalog> {
+    let proto = storage::PersistedCatalog::decode(bs)?;
+    Ok(proto.try_into()?)
+}
+
+/// Tests run against the Postgres database at `POSTGRES_CONN_STRING`, and are
+/// skipped if it isn't set.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn new_storage() -> Option<PostgresStorage> {
+        let conn_str = match std::env::var("POSTGRES_CONN_STRING") {
+            Ok(conn_str) => conn_str,
+            Err(_) => {
+                eprintln!("POSTGRES_CONN_STRING not set, skipping");
+                return None;
+            }
+        };
+        Some(
+            PostgresStorage::connect(Uuid::new_v4(), conn_str)
+                .await
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn initialize_idempotent() {
+        let storage = match new_storage().await {
+            Some(storage) => storage,
+            None => return,
+        };
+
+        let db_id = Uuid::new_v4();
+        storage.initialize(db_id).await.unwrap();
+        storage.initialize(db_id).await.unwrap();
+
+        assert_eq!(0, storage.latest_version(&db_id).await.unwrap());
+        let catalog = storage.read_catalog(db_id).await.unwrap();
+        assert_eq!(0, catalog.state.version);
+        assert_eq!(FIRST_AVAILABLE_ID, catalog.extra.oid_counter);
+    }
+
+    #[tokio::test]
+    async fn missing_catalog() {
+        let storage = match new_storage().await {
+            Some(storage) => storage,
+            None => return,
+        };
+
+        let db_id = Uuid::new_v4();
+        let err = storage.read_catalog(db_id).await.unwrap_err();
+        assert!(matches!(err, StorageError::MissingCatalog { .. }), "{err}");
+        storage.latest_version(&db_id).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn write_simple() {
+        let storage = match new_storage().await {
+            Some(storage) => storage,
+            None => return,
+        };
+
+        let db_id = Uuid::new_v4();
+        storage.initialize(db_id).await.unwrap();
+
+        let mut catalog = storage.read_catalog(db_id).await.unwrap();
+
+        let old_version = catalog.state.version;
+        catalog.state.version += 1;
+        storage
+            .write_catalog(db_id, old_version, catalog.clone())
+            .await
+            .unwrap();
+
+        let updated = storage.read_catalog(db_id).await.unwrap();
+        assert_eq!(1, updated.state.version);
+
+        // Check that we can't write using out of date version.
+        storage.write_catalog(db_id, 0, catalog).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn concurrent_writers_conflict() {
+        let (first, second) = match (new_storage().await, new_storage().await) {
+            (Some(first), Some(second)) => (first, second),
+            _ => return,
+        };
+
+        let db_id = Uuid::new_v4();
+        first.initialize(db_id).await.unwrap();
+        second.initialize(db_id).await.unwrap();
+
+        // Both processes read the same version and write at the same time.
+        let mut first_catalog = first.read_catalog(db_id).await.unwrap();
+        let mut second_catalog = second.read_catalog(db_id).await.unwrap();
+        first_catalog.state.version += 1;
+        second_catalog.state.version += 1;
+
+        let (first_result, second_result) = tokio::join!(
+            first.write_catalog(db_id, 0, first_catalog),
+            second.write_catalog(db_id, 0, second_catalog),
+        );
+
+        // Exactly one of the writes succeeds, the other one needs to fail
+        // instead of overwriting it.
+        match (first_result, second_result) {
+            (Ok(()), Err(err)) | (Err(err), Ok(())) => {
+                assert!(err.is_write_conflict(), "unexpected error: {err}")
+            }
+            other => panic!("expected exactly one write to succeed: {other:?}"),
+        }
+        assert_eq!(1, first.latest_version(&db_id).await.unwrap());
+        assert_eq!(1, second.latest_version(&db_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn read_catalog_at_timestamp() {
+        let storage = match new_storage().await {
+            Some(storage) => storage,
+            None => return,
+        };
+
+        let db_id = Uuid::new_v4();
+        storage.initialize(db_id).await.unwrap();
+
+        let before_init = SystemTime::UNIX_EPOCH;
+        assert!(storage
+            .read_catalog_at(db_id, before_init)
+            .await
+            .unwrap()
+            .is_none());
+
+        // Write a few versions, remembering the time in between each write.
+        let mut timestamps = Vec::new();
+        let mut catalog = storage.read_catalog(db_id).await.unwrap();
+        for _ in 0..5 {
+            timestamps.push(SystemTime::now());
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+            let old_version = catalog.state.version;
+            catalog.state.version += 1;
+            catalog.extra.written_at = Some(SystemTime::now());
+            storage
+                .write_catalog(db_id, old_version, catalog.clone())
+                .await
+                .unwrap();
+        }
+
+        for (version, timestamp) in timestamps.into_iter().enumerate() {
+            let got = storage
+                .read_catalog_at(db_id, timestamp)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(version as u64, got.state.version);
+        }
+
+        let latest = storage
+            .read_catalog_at(db_id, SystemTime::now())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(5, latest.state.version);
+    }
+}
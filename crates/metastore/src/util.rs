@@ -1,5 +1,5 @@
 use crate::errors::Result;
-use crate::local::{start_inprocess_inmemory, start_inprocess_local};
+use crate::local::{start_inprocess_inmemory, start_inprocess_local, start_inprocess_postgres};
 use ioutil::ensure_dir;
 use protogen::gen::metastore::service::metastore_service_client::MetastoreServiceClient;
 use std::path::PathBuf;
@@ -16,6 +16,9 @@ pub enum MetastoreClientMode {
     LocalDisk { path: PathBuf },
     /// Start an in process metastore that persists nothing.
     LocalInMemory,
+    /// Start an in process metastore that stores catalogs in a Postgres
+    /// database.
+    LocalPostgres { conn_str: String },
 }
 
 impl MetastoreClientMode {
@@ -44,6 +47,7 @@ impl MetastoreClientMode {
                 start_inprocess_local(path).await
             }
             Self::LocalInMemory => start_inprocess_inmemory().await,
+            Self::LocalPostgres { conn_str } => start_inprocess_postgres(&conn_str).await,
         }
     }
 }
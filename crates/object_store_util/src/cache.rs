@@ -0,0 +1,421 @@
+//! Caching of object store listings, metadata and reads.
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use moka::future::Cache;
+use object_store::{path::Path, GetResult, ListResult, ObjectMeta, ObjectStore, Result};
+use object_store::{GetOptions, MultipartId};
+use once_cell::sync::Lazy;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWrite;
+
+/// Default limit on the total size of cached ranges.
+const DEFAULT_RANGE_CACHE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Default limit on the number of cached object metas (listed objects count
+/// once per listing they're in).
+const DEFAULT_META_CACHE_ENTRIES: u64 = 100_000;
+
+/// How long listings and object metas are trusted for.
+///
+/// Ranges are keyed by the version of the object they were read from, so they
+/// don't need to expire. Metas do, since that's the only way we notice that an
+/// object was changed by someone else.
+const DEFAULT_META_TTL: Duration = Duration::from_secs(60);
+
+/// Cache shared by all caching object stores in the process.
+pub static OBJECT_STORE_CACHE: Lazy<Arc<ObjectStoreCache>> = Lazy::new(|| {
+    Arc::new(ObjectStoreCache::new(
+        DEFAULT_RANGE_CACHE_BYTES,
+        DEFAULT_META_CACHE_ENTRIES,
+        DEFAULT_META_TTL,
+    ))
+});
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ObjectKey {
+    namespace: u64,
+    location: Path,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ListKey {
+    namespace: u64,
+    prefix: Option<Path>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RangeKey {
+    namespace: u64,
+    location: Path,
+    version: String,
+    range: Range<usize>,
+}
+
+/// In-memory cache for object listings, object metas and byte ranges.
+pub struct ObjectStoreCache {
+    metas: Cache<ObjectKey, ObjectMeta>,
+    listings: Cache<ListKey, Arc<Vec<ObjectMeta>>>,
+    ranges: Cache<RangeKey, Bytes>,
+}
+
+impl ObjectStoreCache {
+    /// Create a new cache holding at most `max_range_bytes` of read data and
+    /// `max_meta_entries` object metas, with metas expiring after `meta_ttl`.
+    pub fn new(max_range_bytes: u64, max_meta_entries: u64, meta_ttl: Duration) -> Self {
+        ObjectStoreCache {
+            metas: Cache::builder()
+                .max_capacity(max_meta_entries)
+                .time_to_live(meta_ttl)
+                .build(),
+            listings: Cache::builder()
+                .max_capacity(max_meta_entries)
+                .weigher(|_, objects: &Arc<Vec<ObjectMeta>>| {
+                    objects.len().try_into().unwrap_or(u32::MAX).max(1)
+                })
+                .time_to_live(meta_ttl)
+                .build(),
+            ranges: Cache::builder()
+                .max_capacity(max_range_bytes)
+                .weigher(|_, bytes: &Bytes| bytes.len().try_into().unwrap_or(u32::MAX))
+                .build(),
+        }
+    }
+}
+
+impl fmt::Debug for ObjectStoreCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObjectStoreCache")
+            .field("metas", &self.metas.entry_count())
+            .field("listings", &self.listings.entry_count())
+            .field("range_bytes", &self.ranges.weighted_size())
+            .finish()
+    }
+}
+
+/// Returns the version of the object that cached ranges are keyed by.
+///
+/// Not every store returns etags, the modification time and size are used
+/// instead for those.
+fn object_version(meta: &ObjectMeta) -> String {
+    match &meta.e_tag {
+        Some(e_tag) => e_tag.clone(),
+        None => format!("{}:{}", meta.last_modified, meta.size),
+    }
+}
+
+/// Caches listings, metas and ranges read through the inner store.
+///
+/// Stores for different buckets or credentials must use different namespaces
+/// since the underlying cache is shared.
+///
+/// Ranges are only served from the cache for the version of the object that's
+/// currently known, so parquet footers and column chunks are downloaded again
+/// once an object changes. Whole object reads (`get`) aren't cached.
+#[derive(Debug, Clone)]
+pub struct CachingObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    namespace: u64,
+    cache: Arc<ObjectStoreCache>,
+}
+
+impl CachingObjectStore {
+    /// Create a caching store using the process wide cache.
+    pub fn new(inner: Arc<dyn ObjectStore>, namespace: u64) -> Self {
+        Self::new_with_cache(inner, namespace, OBJECT_STORE_CACHE.clone())
+    }
+
+    pub fn new_with_cache(
+        inner: Arc<dyn ObjectStore>,
+        namespace: u64,
+        cache: Arc<ObjectStoreCache>,
+    ) -> Self {
+        CachingObjectStore {
+            inner,
+            namespace,
+            cache,
+        }
+    }
+
+    fn object_key(&self, location: &Path) -> ObjectKey {
+        ObjectKey {
+            namespace: self.namespace,
+            location: location.clone(),
+        }
+    }
+
+    fn range_key(&self, meta: &ObjectMeta, range: Range<usize>) -> RangeKey {
+        RangeKey {
+            namespace: self.namespace,
+            location: meta.location.clone(),
+            version: object_version(meta),
+            range,
+        }
+    }
+
+    /// Forget the meta for an object after it was written through this store.
+    async fn invalidate(&self, location: &Path) {
+        self.cache
+            .metas
+            .invalidate(&self.object_key(location))
+            .await;
+    }
+}
+
+impl fmt::Display for CachingObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CachingObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for CachingObjectStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        let result = self.inner.put(location, bytes).await;
+        self.invalidate(location).await;
+        result
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        // The object isn't visible until the writer is shut down, which we
+        // can't observe. Invalidating now still avoids serving an old meta
+        // for longer than the TTL.
+        self.invalidate(location).await;
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(&self, location: &Path, multipart_id: &MultipartId) -> Result<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+
+    async fn get(&self, location: &Path) -> Result<GetResult> {
+        self.inner.get(location).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        self.inner.get_opts(location, options).await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        let meta = self.head(location).await?;
+        let key = self.range_key(&meta, range.clone());
+        if let Some(bytes) = self.cache.ranges.get(&key).await {
+            return Ok(bytes);
+        }
+
+        let bytes = self.inner.get_range(location, range).await?;
+        self.cache.ranges.insert(key, bytes.clone()).await;
+        Ok(bytes)
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<usize>]) -> Result<Vec<Bytes>> {
+        let meta = self.head(location).await?;
+
+        let mut cached = Vec::with_capacity(ranges.len());
+        let mut missing = Vec::new();
+        for range in ranges {
+            let key = self.range_key(&meta, range.clone());
+            let bytes = self.cache.ranges.get(&key).await;
+            if bytes.is_none() {
+                missing.push(range.clone());
+            }
+            cached.push(bytes);
+        }
+
+        let mut fetched = if missing.is_empty() {
+            Vec::new()
+        } else {
+            self.inner.get_ranges(location, &missing).await?
+        }
+        .into_iter()
+        .zip(missing);
+
+        let mut out = Vec::with_capacity(ranges.len());
+        for bytes in cached {
+            let bytes = match bytes {
+                Some(bytes) => bytes,
+                None => {
+                    // Misses were fetched in order, so the next fetched range
+                    // is always the one for this miss.
+                    let (bytes, range) = fetched.next().expect("range fetched for miss");
+                    let key = self.range_key(&meta, range);
+                    self.cache.ranges.insert(key, bytes.clone()).await;
+                    bytes
+                }
+            };
+            out.push(bytes);
+        }
+
+        Ok(out)
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        let key = self.object_key(location);
+        if let Some(meta) = self.cache.metas.get(&key).await {
+            return Ok(meta);
+        }
+
+        let meta = self.inner.head(location).await?;
+        self.cache.metas.insert(key, meta.clone()).await;
+        Ok(meta)
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        let result = self.inner.delete(location).await;
+        self.invalidate(location).await;
+        result
+    }
+
+    async fn list(&self, prefix: Option<&Path>) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        let key = ListKey {
+            namespace: self.namespace,
+            prefix: prefix.cloned(),
+        };
+
+        let objects = match self.cache.listings.get(&key).await {
+            Some(objects) => objects,
+            None => {
+                let objects: Vec<_> = self.inner.list(prefix).await?.try_collect().await?;
+                for meta in &objects {
+                    self.cache
+                        .metas
+                        .insert(self.object_key(&meta.location), meta.clone())
+                        .await;
+                }
+                let objects = Arc::new(objects);
+                self.cache.listings.insert(key, objects.clone()).await;
+                objects
+            }
+        };
+
+        let objects = objects.as_ref().clone();
+        Ok(futures::stream::iter(objects.into_iter().map(Ok)).boxed())
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        let result = self.inner.copy(from, to).await;
+        self.invalidate(to).await;
+        result
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        let result = self.inner.rename(from, to).await;
+        self.invalidate(from).await;
+        self.invalidate(to).await;
+        result
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        let result = self.inner.copy_if_not_exists(from, to).await;
+        self.invalidate(to).await;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    fn new_store() -> (Arc<InMemory>, CachingObjectStore) {
+        let inner = Arc::new(InMemory::new());
+        let cache = Arc::new(ObjectStoreCache::new(1024, 16, DEFAULT_META_TTL));
+        let store = CachingObjectStore::new_with_cache(inner.clone(), 0, cache);
+        (inner, store)
+    }
+
+    #[tokio::test]
+    async fn ranges_cached_until_object_changes() {
+        let (inner, store) = new_store();
+        let location = Path::from("data/file");
+        store
+            .put(&location, Bytes::from_static(b"hello world"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            b"hello",
+            store.get_range(&location, 0..5).await.unwrap().as_ref()
+        );
+
+        // Changes made around the cache aren't seen while the meta is cached.
+        inner
+            .put(&location, Bytes::from_static(b"HELLO WORLD!"))
+            .await
+            .unwrap();
+        assert_eq!(
+            b"hello",
+            store.get_range(&location, 0..5).await.unwrap().as_ref()
+        );
+
+        // Writes through the cache invalidate the object's meta.
+        store
+            .put(&location, Bytes::from_static(b"goodbye"))
+            .await
+            .unwrap();
+        assert_eq!(
+            b"good",
+            store.get_range(&location, 0..4).await.unwrap().as_ref()
+        );
+        assert_eq!(
+            b"goodb",
+            store.get_range(&location, 0..5).await.unwrap().as_ref()
+        );
+    }
+
+    #[tokio::test]
+    async fn listings_cached() {
+        let (inner, store) = new_store();
+        let prefix = Path::from("data");
+        inner
+            .put(&Path::from("data/a"), Bytes::from_static(b"a"))
+            .await
+            .unwrap();
+
+        let listed: Vec<_> = store
+            .list(Some(&prefix))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(1, listed.len());
+
+        inner
+            .put(&Path::from("data/b"), Bytes::from_static(b"b"))
+            .await
+            .unwrap();
+
+        let listed: Vec<_> = store
+            .list(Some(&prefix))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(1, listed.len());
+
+        // A different store namespace doesn't share the listing.
+        let other = CachingObjectStore::new_with_cache(inner.clone(), 1, store.cache.clone());
+        let listed: Vec<_> = other
+            .list(Some(&prefix))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(2, listed.len());
+    }
+}
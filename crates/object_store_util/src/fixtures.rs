@@ -0,0 +1,293 @@
+//! Record and replay objects read from remote object stores.
+//!
+//! When recording, every object read from a remote store is first copied into
+//! a fixtures directory, and the read is served from the copy. When replaying,
+//! remote stores are replaced by the fixtures directory, so tests reading from
+//! remote stores can run without credentials or network access.
+//!
+//! Fixtures are laid out as `<dir>/<scheme>/<path>`. The bucket is left out so
+//! fixtures recorded from one bucket replay regardless of the bucket a test is
+//! configured with. Existing fixtures are never re-downloaded, delete them to
+//! record them again.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use object_store::{
+    local::LocalFileSystem, path::Path, Error, GetOptions, GetResult, ListResult, MultipartId,
+    ObjectMeta, ObjectStore, Result,
+};
+use once_cell::sync::OnceCell;
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::AsyncWrite;
+use tracing::debug;
+
+/// Whether remote objects are recorded into or replayed from a fixtures
+/// directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixtureMode {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+static FIXTURE_MODE: OnceCell<FixtureMode> = OnceCell::new();
+
+/// Set the fixture mode for all remote stores created afterwards in this
+/// process.
+///
+/// Errors with the provided mode if one was already set.
+pub fn set_fixture_mode(mode: FixtureMode) -> Result<(), FixtureMode> {
+    FIXTURE_MODE.set(mode)
+}
+
+/// Get the fixture mode, if one was set.
+pub fn fixture_mode() -> Option<&'static FixtureMode> {
+    FIXTURE_MODE.get()
+}
+
+/// Create a remote store for `scheme` (e.g. "s3") with `create`, recording
+/// or replaying its objects according to the fixture mode.
+///
+/// When replaying, `create` isn't called, so placeholder credentials don't
+/// need to be valid.
+pub fn fixture_store<E: From<Error>>(
+    scheme: &str,
+    create: impl FnOnce() -> Result<Arc<dyn ObjectStore>, E>,
+) -> Result<Arc<dyn ObjectStore>, E> {
+    let (remote, dir) = match FIXTURE_MODE.get() {
+        None => return create(),
+        Some(FixtureMode::Record(dir)) => (Some(create()?), dir),
+        Some(FixtureMode::Replay(dir)) => (None, dir),
+    };
+    Ok(Arc::new(FixtureObjectStore::try_new(
+        remote,
+        dir.join(scheme),
+    )?))
+}
+
+/// Object store serving reads from a fixtures directory, populating it from
+/// a remote store when recording.
+#[derive(Debug)]
+pub struct FixtureObjectStore {
+    /// The remote store when recording, `None` when replaying.
+    remote: Option<Arc<dyn ObjectStore>>,
+    fixtures: LocalFileSystem,
+}
+
+impl FixtureObjectStore {
+    pub fn try_new(remote: Option<Arc<dyn ObjectStore>>, dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&dir).map_err(|e| Error::Generic {
+            store: "Fixtures",
+            source: Box::new(e),
+        })?;
+        let fixtures = LocalFileSystem::new_with_prefix(dir)?;
+        Ok(FixtureObjectStore { remote, fixtures })
+    }
+
+    /// Copy the object at `location` into the fixtures if recording and it
+    /// hasn't been recorded yet.
+    async fn record(&self, location: &Path) -> Result<()> {
+        let Some(remote) = &self.remote else {
+            return Ok(());
+        };
+        match self.fixtures.head(location).await {
+            Ok(_) => return Ok(()),
+            Err(Error::NotFound { .. }) => (),
+            Err(e) => return Err(e),
+        }
+
+        debug!(%location, "recording object fixture");
+        let bytes = remote.get(location).await?.bytes().await?;
+        self.fixtures.put(location, bytes).await
+    }
+
+    /// Remove a recorded object after it's been modified in the remote store.
+    async fn invalidate(&self, location: &Path) -> Result<()> {
+        match self.fixtures.delete(location).await {
+            Ok(()) | Err(Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl fmt::Display for FixtureObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.remote {
+            Some(remote) => write!(f, "FixtureObjectStore(recording {remote})"),
+            None => write!(f, "FixtureObjectStore(replaying {})", self.fixtures),
+        }
+    }
+}
+
+/// Writes go to the remote store when recording, invalidating any recorded
+/// copy. When replaying, writes are accepted and dropped so the fixtures
+/// aren't modified, and reading a written object returns its recorded copy.
+#[async_trait]
+impl ObjectStore for FixtureObjectStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        let Some(remote) = &self.remote else {
+            return Ok(());
+        };
+        remote.put(location, bytes).await?;
+        self.invalidate(location).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        let Some(remote) = &self.remote else {
+            return Ok((MultipartId::new(), Box::new(tokio::io::sink())));
+        };
+        self.invalidate(location).await?;
+        remote.put_multipart(location).await
+    }
+
+    async fn abort_multipart(&self, location: &Path, multipart_id: &MultipartId) -> Result<()> {
+        match &self.remote {
+            Some(remote) => remote.abort_multipart(location, multipart_id).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn get(&self, location: &Path) -> Result<GetResult> {
+        self.record(location).await?;
+        self.fixtures.get(location).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        self.record(location).await?;
+        self.fixtures.get_opts(location, options).await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        self.record(location).await?;
+        self.fixtures.get_range(location, range).await
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<usize>]) -> Result<Vec<Bytes>> {
+        self.record(location).await?;
+        self.fixtures.get_ranges(location, ranges).await
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        self.record(location).await?;
+        self.fixtures.head(location).await
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        let Some(remote) = &self.remote else {
+            return Ok(());
+        };
+        remote.delete(location).await?;
+        self.invalidate(location).await
+    }
+
+    /// Lists the remote store when recording, and the recorded objects when
+    /// replaying.
+    ///
+    /// Listed objects are only recorded once they're read.
+    async fn list(&self, prefix: Option<&Path>) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        match &self.remote {
+            Some(remote) => remote.list(prefix).await,
+            None => self.fixtures.list(prefix).await,
+        }
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        match &self.remote {
+            Some(remote) => remote.list_with_delimiter(prefix).await,
+            None => self.fixtures.list_with_delimiter(prefix).await,
+        }
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        let Some(remote) = &self.remote else {
+            return Ok(());
+        };
+        remote.copy(from, to).await?;
+        self.invalidate(to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        let Some(remote) = &self.remote else {
+            return Ok(());
+        };
+        remote.copy_if_not_exists(from, to).await?;
+        self.invalidate(to).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        let Some(remote) = &self.remote else {
+            return Ok(());
+        };
+        remote.rename(from, to).await?;
+        self.invalidate(from).await?;
+        self.invalidate(to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn replays_recorded_objects() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = Arc::new(InMemory::new());
+        let location = Path::from("data/file.csv");
+        remote
+            .put(&location, Bytes::from_static(b"a,b\n1,2\n"))
+            .await
+            .unwrap();
+
+        let recording =
+            FixtureObjectStore::try_new(Some(remote.clone()), dir.path().to_path_buf()).unwrap();
+        assert_eq!(
+            Bytes::from_static(b"a,b"),
+            recording.get_range(&location, 0..3).await.unwrap()
+        );
+
+        // Changes to the remote object aren't seen once it's been recorded.
+        remote
+            .put(&location, Bytes::from_static(b"changed"))
+            .await
+            .unwrap();
+
+        let replaying = FixtureObjectStore::try_new(None, dir.path().to_path_buf()).unwrap();
+        assert_eq!(8, replaying.head(&location).await.unwrap().size);
+        let listed = replaying
+            .list_with_delimiter(Some(&Path::from("data")))
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![location.clone()],
+            listed
+                .objects
+                .into_iter()
+                .map(|o| o.location)
+                .collect::<Vec<_>>()
+        );
+
+        // Writes while replaying leave the fixtures alone.
+        replaying
+            .put(&location, Bytes::from_static(b"dropped"))
+            .await
+            .unwrap();
+        assert_eq!(
+            Bytes::from_static(b"a,b\n1,2\n"),
+            replaying
+                .get(&location)
+                .await
+                .unwrap()
+                .bytes()
+                .await
+                .unwrap()
+        );
+    }
+}
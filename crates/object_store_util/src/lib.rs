@@ -1,4 +1,9 @@
 //! Utilities for the object store crate.
+pub mod cache;
 pub mod conf;
+pub mod fixtures;
+pub mod metered;
+pub mod parallel;
+pub mod retry;
 pub mod shared;
 pub mod temp;
@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use object_store::{path::Path, GetResult, ListResult, ObjectMeta, ObjectStore, Result};
+use object_store::{GetOptions, MultipartId};
+use std::ops::Range;
+use std::sync::Arc;
+use telemetry::metrics::Counter;
+use tokio::io::AsyncWrite;
+
+/// Counts the bytes read through an object store.
+///
+/// Bytes are counted when a read is requested, so a `get` whose payload is
+/// only partially consumed still counts the full range.
+#[derive(Debug, Clone)]
+pub struct MeteredObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    bytes_read: Arc<Counter>,
+}
+
+impl MeteredObjectStore {
+    pub fn new(inner: Arc<dyn ObjectStore>, bytes_read: Arc<Counter>) -> Self {
+        MeteredObjectStore { inner, bytes_read }
+    }
+
+    fn record_get(&self, result: &GetResult) {
+        self.bytes_read
+            .inc_by((result.range.end - result.range.start) as u64);
+    }
+}
+
+impl std::fmt::Display for MeteredObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MeteredObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for MeteredObjectStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        self.inner.put(location, bytes).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(&self, location: &Path, multipart_id: &MultipartId) -> Result<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+
+    async fn get(&self, location: &Path) -> Result<GetResult> {
+        let result = self.inner.get(location).await?;
+        self.record_get(&result);
+        Ok(result)
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        let result = self.inner.get_opts(location, options).await?;
+        self.record_get(&result);
+        Ok(result)
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        let bytes = self.inner.get_range(location, range).await?;
+        self.bytes_read.inc_by(bytes.len() as u64);
+        Ok(bytes)
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<usize>]) -> Result<Vec<Bytes>> {
+        let bytes = self.inner.get_ranges(location, ranges).await?;
+        self.bytes_read
+            .inc_by(bytes.iter().map(|b| b.len() as u64).sum());
+        Ok(bytes)
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        self.inner.head(location).await
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        self.inner.delete(location).await
+    }
+
+    async fn list(&self, prefix: Option<&Path>) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        self.inner.list(prefix).await
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.rename(from, to).await
+    }
+}
@@ -0,0 +1,210 @@
+//! Downloading large objects in parts.
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::{FutureExt, StreamExt};
+use object_store::{path::Path, GetResult, ListResult, ObjectMeta, ObjectStore, Result};
+use object_store::{GetOptions, GetResultPayload, MultipartId};
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use tokio::io::AsyncWrite;
+
+/// Splits whole object reads (`get`) into concurrent ranged reads.
+///
+/// A single GET is limited by the throughput of one connection, which for
+/// large CSV and json files is much lower than what's available. Reads larger
+/// than `part_size` are instead fetched as `part_size` ranges with at most
+/// `concurrency` of them in flight. The returned stream still yields the bytes
+/// in order.
+///
+/// Parts are read with `if_match` set to the object's etag (when the store
+/// returns one) so an object modified mid read errors instead of returning a
+/// mix of both versions.
+#[derive(Debug, Clone)]
+pub struct ParallelGetObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    part_size: usize,
+    concurrency: usize,
+}
+
+impl ParallelGetObjectStore {
+    /// Create a new store. A `part_size` of 0 or a `concurrency` of 1 or less
+    /// reads objects with a single request.
+    pub fn new(inner: Arc<dyn ObjectStore>, part_size: usize, concurrency: usize) -> Self {
+        ParallelGetObjectStore {
+            inner,
+            part_size,
+            concurrency,
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.part_size > 0 && self.concurrency > 1
+    }
+}
+
+/// Split `range` into consecutive ranges of at most `part_size` bytes.
+fn split_range(range: Range<usize>, part_size: usize) -> Vec<Range<usize>> {
+    (range.start..range.end)
+        .step_by(part_size)
+        .map(|start| start..(start + part_size).min(range.end))
+        .collect()
+}
+
+impl fmt::Display for ParallelGetObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ParallelGetObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for ParallelGetObjectStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        self.inner.put(location, bytes).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(&self, location: &Path, multipart_id: &MultipartId) -> Result<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+
+    async fn get(&self, location: &Path) -> Result<GetResult> {
+        self.get_opts(location, GetOptions::default()).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        if !self.enabled() || options.head {
+            return self.inner.get_opts(location, options).await;
+        }
+
+        // Without a range we need the size of the object to know how many
+        // parts there are.
+        let (range, if_match) = match &options.range {
+            Some(range) => (range.clone(), options.if_match.clone()),
+            None => {
+                let meta = self.inner.head(location).await?;
+                (0..meta.size, options.if_match.clone().or(meta.e_tag))
+            }
+        };
+        if range.len() <= self.part_size {
+            return self.inner.get_opts(location, options).await;
+        }
+
+        let mut parts = split_range(range.clone(), self.part_size).into_iter();
+        let part_options = |part: Range<usize>, if_match: Option<String>| GetOptions {
+            if_match,
+            if_none_match: options.if_none_match.clone(),
+            if_modified_since: options.if_modified_since,
+            if_unmodified_since: options.if_unmodified_since,
+            range: Some(part),
+            head: false,
+        };
+
+        // The first part is requested up front for the meta of the object.
+        let first = parts.next().expect("range split into multiple parts");
+        let first = self
+            .inner
+            .get_opts(location, part_options(first, if_match.clone()))
+            .await?;
+        let meta = first.meta.clone();
+        let if_match = if_match.or_else(|| meta.e_tag.clone());
+
+        let mut downloads: Vec<BoxFuture<'static, Result<Bytes>>> = vec![first.bytes().boxed()];
+        for part in parts {
+            let inner = self.inner.clone();
+            let location = location.clone();
+            let options = part_options(part, if_match.clone());
+            downloads.push(
+                async move { inner.get_opts(&location, options).await?.bytes().await }.boxed(),
+            );
+        }
+
+        let stream = futures::stream::iter(downloads)
+            .buffered(self.concurrency)
+            .boxed();
+
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(stream),
+            meta,
+            range,
+        })
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        self.inner.get_range(location, range).await
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<usize>]) -> Result<Vec<Bytes>> {
+        self.inner.get_ranges(location, ranges).await
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        self.inner.head(location).await
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        self.inner.delete(location).await
+    }
+
+    async fn list(&self, prefix: Option<&Path>) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        self.inner.list(prefix).await
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.rename(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[test]
+    fn split_ranges() {
+        assert_eq!(vec![0..4, 4..8, 8..10], split_range(0..10, 4));
+        assert_eq!(vec![3..7], split_range(3..7, 4));
+    }
+
+    #[tokio::test]
+    async fn get_in_parts() {
+        let inner = Arc::new(InMemory::new());
+        let location = Path::from("data/file");
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        inner.put(&location, data.clone().into()).await.unwrap();
+
+        let store = ParallelGetObjectStore::new(inner, 64, 4);
+
+        let got = store.get(&location).await.unwrap();
+        assert_eq!(0..1000, got.range);
+        assert_eq!(data, got.bytes().await.unwrap().as_ref());
+
+        let options = GetOptions {
+            range: Some(100..900),
+            ..Default::default()
+        };
+        let got = store.get_opts(&location, options).await.unwrap();
+        assert_eq!(&data[100..900], got.bytes().await.unwrap().as_ref());
+    }
+}
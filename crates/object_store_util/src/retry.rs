@@ -0,0 +1,342 @@
+//! Retrying requests that fail with transient errors.
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use object_store::{path::Path, GetResult, ListResult, ObjectMeta, ObjectStore, Result};
+use object_store::{GetOptions, MultipartId};
+use std::fmt;
+use std::future::Future;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWrite;
+use tracing::debug;
+
+/// Substrings of (lowercased) error messages indicating the request may
+/// succeed if tried again, e.g. throttling, unavailable services and network
+/// errors.
+const TRANSIENT_ERROR_PATTERNS: &[&str] = &[
+    "timed out",
+    "timeout",
+    "connection reset",
+    "connection refused",
+    "connection closed",
+    "broken pipe",
+    "error sending request",
+    "temporarily unavailable",
+    "service unavailable",
+    "bad gateway",
+    "internal server error",
+    "too many requests",
+    "slow down",
+    "server error (5",
+];
+
+/// How many times, and how often, a failed request is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Max number of retries after the first attempt.
+    pub max_retries: usize,
+    /// How long to wait before the first retry. Doubled for every following
+    /// retry.
+    pub initial_backoff: Duration,
+    /// Max time to wait between retries.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub const fn none() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+
+    /// Time to wait before retry number `retry` (starting at 0).
+    pub fn backoff(&self, retry: usize) -> Duration {
+        let factor = 1_u32.checked_shl(retry as u32).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Run `op` until it succeeds, fails with an error that isn't retryable
+    /// or runs out of retries.
+    ///
+    /// `on_retry` is called with the error for every retry.
+    pub async fn retry<T, E, F, Fut>(
+        &self,
+        mut op: F,
+        is_retryable: impl Fn(&E) -> bool,
+        mut on_retry: impl FnMut(&E),
+    ) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut retry = 0;
+        loop {
+            match op().await {
+                Ok(v) => return Ok(v),
+                Err(e) if retry < self.max_retries && is_retryable(&e) => {
+                    on_retry(&e);
+                    tokio::time::sleep(self.backoff(retry)).await;
+                    retry += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Returns whether an error message indicates a transient failure.
+pub fn is_transient_message(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    TRANSIENT_ERROR_PATTERNS
+        .iter()
+        .any(|pattern| msg.contains(pattern))
+}
+
+/// Returns whether an object store error is transient.
+///
+/// Only errors returned by the store's client are considered, errors like
+/// missing objects or failed preconditions are never transient.
+pub fn is_transient_error(err: &object_store::Error) -> bool {
+    match err {
+        object_store::Error::Generic { source, .. } => is_transient_message(&source.to_string()),
+        _ => false,
+    }
+}
+
+/// Retries reads that fail with transient errors.
+///
+/// Writes are passed through as is. Listing only retries the initial request,
+/// errors in later pages of the listing are returned as is.
+#[derive(Clone)]
+pub struct RetryObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    policy: RetryPolicy,
+    /// Called for every retry.
+    on_retry: Arc<dyn Fn(&object_store::Error) + Send + Sync>,
+}
+
+impl RetryObjectStore {
+    pub fn new(inner: Arc<dyn ObjectStore>, policy: RetryPolicy) -> Self {
+        RetryObjectStore {
+            inner,
+            policy,
+            on_retry: Arc::new(|_| {}),
+        }
+    }
+
+    /// Call `on_retry` for every retried request, e.g. to count retries.
+    pub fn with_on_retry(
+        mut self,
+        on_retry: impl Fn(&object_store::Error) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_retry = Arc::new(on_retry);
+        self
+    }
+
+    async fn retry<T, F, Fut>(&self, op: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.policy
+            .retry(op, is_transient_error, |e| {
+                debug!(%e, "retrying object store request");
+                (self.on_retry)(e)
+            })
+            .await
+    }
+}
+
+impl fmt::Debug for RetryObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryObjectStore")
+            .field("inner", &self.inner)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for RetryObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RetryObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for RetryObjectStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        self.inner.put(location, bytes).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(&self, location: &Path, multipart_id: &MultipartId) -> Result<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+
+    async fn get(&self, location: &Path) -> Result<GetResult> {
+        self.retry(|| self.inner.get(location)).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        self.retry(|| {
+            let options = GetOptions {
+                if_match: options.if_match.clone(),
+                if_none_match: options.if_none_match.clone(),
+                if_modified_since: options.if_modified_since,
+                if_unmodified_since: options.if_unmodified_since,
+                range: options.range.clone(),
+                head: options.head,
+            };
+            self.inner.get_opts(location, options)
+        })
+        .await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        self.retry(|| self.inner.get_range(location, range.clone()))
+            .await
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<usize>]) -> Result<Vec<Bytes>> {
+        self.retry(|| self.inner.get_ranges(location, ranges)).await
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        self.retry(|| self.inner.head(location)).await
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        self.inner.delete(location).await
+    }
+
+    async fn list(&self, prefix: Option<&Path>) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        self.retry(|| self.inner.list(prefix)).await
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        self.retry(|| self.inner.list_with_delimiter(prefix)).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.rename(from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn unavailable() -> object_store::Error {
+        object_store::Error::Generic {
+            store: "test",
+            source: "HTTP status server error (503 Service Unavailable)".into(),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+        };
+        assert_eq!(Duration::from_millis(100), policy.backoff(0));
+        assert_eq!(Duration::from_millis(200), policy.backoff(1));
+        assert_eq!(Duration::from_millis(400), policy.backoff(2));
+        assert_eq!(Duration::from_millis(500), policy.backoff(3));
+        assert_eq!(Duration::from_millis(500), policy.backoff(100));
+    }
+
+    #[test]
+    fn classify_errors() {
+        assert!(is_transient_error(&unavailable()));
+        assert!(is_transient_message("operation timed out"));
+        assert!(!is_transient_error(&object_store::Error::NotFound {
+            path: "file".to_string(),
+            source: "connection reset".into(),
+        }));
+        assert!(!is_transient_message("permission denied"));
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        };
+        let attempts = &AtomicUsize::new(0);
+        let mut retries = 0;
+
+        let result = policy
+            .retry(
+                || async move {
+                    match attempts.fetch_add(1, Ordering::Relaxed) {
+                        0 | 1 => Err(unavailable()),
+                        _ => Ok(()),
+                    }
+                },
+                is_transient_error,
+                |_| retries += 1,
+            )
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(2, retries);
+
+        // Gives up after running out of retries.
+        let result: Result<()> = policy
+            .retry(|| async { Err(unavailable()) }, is_transient_error, |_| {})
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_other_errors() {
+        let attempts = &AtomicUsize::new(0);
+        let result: Result<()> = RetryPolicy::default()
+            .retry(
+                || async move {
+                    attempts.fetch_add(1, Ordering::Relaxed);
+                    Err(object_store::Error::NotImplemented)
+                },
+                is_transient_error,
+                |_| {},
+            )
+            .await;
+        assert!(result.is_err());
+        assert_eq!(1, attempts.load(Ordering::Relaxed));
+    }
+}
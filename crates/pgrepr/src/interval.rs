@@ -0,0 +1,241 @@
+use std::str::FromStr;
+
+use datafusion::arrow::datatypes::{
+    IntervalDayTimeType, IntervalMonthDayNanoType, IntervalUnit, IntervalYearMonthType,
+};
+use datafusion::scalar::ScalarValue as DfScalar;
+
+const NANOS_PER_MICRO: i64 = 1_000;
+const NANOS_PER_MILLI: i64 = 1_000_000;
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+const NANOS_PER_MINUTE: i64 = 60 * NANOS_PER_SEC;
+const NANOS_PER_HOUR: i64 = 60 * NANOS_PER_MINUTE;
+
+/// An interval made up of months, days, and nanoseconds.
+///
+/// This matches arrow's month-day-nano interval. Postgres intervals only have
+/// microsecond precision, so nanoseconds are truncated when encoding.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub nanos: i64,
+}
+
+impl Interval {
+    pub fn new(months: i32, days: i32, nanos: i64) -> Self {
+        Interval {
+            months,
+            days,
+            nanos,
+        }
+    }
+
+    /// Get the interval from a non-null arrow interval scalar.
+    pub fn from_datafusion(scalar: &DfScalar) -> Option<Self> {
+        Some(match scalar {
+            DfScalar::IntervalYearMonth(Some(v)) => {
+                Self::new(IntervalYearMonthType::to_months(*v), 0, 0)
+            }
+            DfScalar::IntervalDayTime(Some(v)) => {
+                let (days, millis) = IntervalDayTimeType::to_parts(*v);
+                Self::new(0, days, millis as i64 * NANOS_PER_MILLI)
+            }
+            DfScalar::IntervalMonthDayNano(Some(v)) => {
+                let (months, days, nanos) = IntervalMonthDayNanoType::to_parts(*v);
+                Self::new(months, days, nanos)
+            }
+            _ => return None,
+        })
+    }
+
+    /// Convert the interval to an arrow interval scalar with the given unit.
+    ///
+    /// Returns `None` if the interval can't be represented using the unit
+    /// without losing precision.
+    pub fn into_datafusion(self, unit: &IntervalUnit) -> Option<DfScalar> {
+        Some(match unit {
+            IntervalUnit::YearMonth if self.days == 0 && self.nanos == 0 => {
+                DfScalar::IntervalYearMonth(Some(self.months))
+            }
+            IntervalUnit::DayTime if self.months == 0 && self.nanos % NANOS_PER_MILLI == 0 => {
+                let millis = i32::try_from(self.nanos / NANOS_PER_MILLI).ok()?;
+                DfScalar::IntervalDayTime(Some(IntervalDayTimeType::make_value(self.days, millis)))
+            }
+            IntervalUnit::MonthDayNano => DfScalar::IntervalMonthDayNano(Some(
+                IntervalMonthDayNanoType::make_value(self.months, self.days, self.nanos),
+            )),
+            _ => return None,
+        })
+    }
+
+    /// Get the number of microseconds for the time part of the interval.
+    pub fn micros(&self) -> i64 {
+        self.nanos / NANOS_PER_MICRO
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid input syntax for type interval: \"{0}\"")]
+pub struct ParseIntervalError(String);
+
+impl FromStr for Interval {
+    type Err = ParseIntervalError;
+
+    /// Parse an interval as written by postgres using the "postgres" interval
+    /// style (e.g. "1 year 2 mons 3 days 04:05:06.789"). Units may be singular
+    /// or plural, and a trailing "ago" negates the interval.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseIntervalError(s.to_string());
+
+        let mut interval = Interval::default();
+        let mut tokens = s.split_whitespace().peekable();
+        let mut ago = false;
+        let mut empty = true;
+
+        while let Some(token) = tokens.next() {
+            empty = false;
+            if token.eq_ignore_ascii_case("ago") && tokens.peek().is_none() {
+                ago = true;
+                break;
+            }
+            if token.contains(':') {
+                interval.nanos = interval
+                    .nanos
+                    .checked_add(parse_time(token).ok_or_else(err)?)
+                    .ok_or_else(err)?;
+                continue;
+            }
+
+            let unit = tokens.next().ok_or_else(err)?.to_ascii_lowercase();
+            let unit = unit.strip_suffix('s').unwrap_or(&unit);
+            match unit {
+                "year" | "yr" | "mon" | "month" | "week" | "day" => {
+                    let value: i32 = token.parse().map_err(|_| err())?;
+                    let (months, days) = match unit {
+                        "year" | "yr" => (value.checked_mul(12), Some(0)),
+                        "mon" | "month" => (Some(value), Some(0)),
+                        "week" => (Some(0), value.checked_mul(7)),
+                        _ => (Some(0), Some(value)),
+                    };
+                    interval.months = interval
+                        .months
+                        .checked_add(months.ok_or_else(err)?)
+                        .ok_or_else(err)?;
+                    interval.days = interval
+                        .days
+                        .checked_add(days.ok_or_else(err)?)
+                        .ok_or_else(err)?;
+                }
+                _ => {
+                    let per_unit = match unit {
+                        "hour" | "hr" => NANOS_PER_HOUR,
+                        "minute" | "min" => NANOS_PER_MINUTE,
+                        "second" | "sec" => NANOS_PER_SEC,
+                        "millisecond" | "m" => NANOS_PER_MILLI,
+                        "microsecond" | "u" => NANOS_PER_MICRO,
+                        _ => return Err(err()),
+                    };
+                    let value: f64 = token.parse().map_err(|_| err())?;
+                    let nanos = value * per_unit as f64;
+                    if !nanos.is_finite() || nanos.abs() >= i64::MAX as f64 {
+                        return Err(err());
+                    }
+                    interval.nanos = interval
+                        .nanos
+                        .checked_add(nanos.round() as i64)
+                        .ok_or_else(err)?;
+                }
+            }
+        }
+
+        if empty {
+            return Err(err());
+        }
+        if ago {
+            interval = Interval::new(-interval.months, -interval.days, -interval.nanos);
+        }
+
+        Ok(interval)
+    }
+}
+
+/// Parse a time in the form of "[+-]HH:MM[:SS[.FFFFFF]]", returning the number
+/// of nanoseconds.
+fn parse_time(s: &str) -> Option<i64> {
+    let (negative, s) = match s.as_bytes().first()? {
+        b'-' => (true, &s[1..]),
+        b'+' => (false, &s[1..]),
+        _ => (false, s),
+    };
+
+    let mut parts = s.split(':');
+    let hours: i64 = parts.next()?.parse().ok()?;
+    let minutes: i64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = match parts.next() {
+        Some(secs) => secs.parse().ok()?,
+        None => 0.0,
+    };
+    if parts.next().is_some() || !(0..60).contains(&minutes) || !(0.0..60.0).contains(&seconds) {
+        return None;
+    }
+
+    let nanos = hours
+        .checked_mul(NANOS_PER_HOUR)?
+        .checked_add(minutes * NANOS_PER_MINUTE)?
+        .checked_add((seconds * NANOS_PER_SEC as f64).round() as i64)?;
+
+    Some(if negative { -nanos } else { nanos })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_interval() {
+        let test_cases = [
+            ("00:00:00", Interval::new(0, 0, 0)),
+            ("1 day", Interval::new(0, 1, 0)),
+            ("2 days 01:00:00", Interval::new(0, 2, NANOS_PER_HOUR)),
+            (
+                "1 year 2 mons 3 days 04:05:06.789",
+                Interval::new(14, 3, 14_706_789_000_000),
+            ),
+            ("-1 years -2 mons", Interval::new(-14, 0, 0)),
+            ("-1 days +01:00:00", Interval::new(0, -1, NANOS_PER_HOUR)),
+            ("1 day -01:00:00", Interval::new(0, 1, -NANOS_PER_HOUR)),
+            ("-00:00:00.000001", Interval::new(0, 0, -1_000)),
+            ("36:00:00", Interval::new(0, 0, 36 * NANOS_PER_HOUR)),
+            (
+                "1 week 1.5 hours",
+                Interval::new(0, 7, 90 * NANOS_PER_MINUTE),
+            ),
+            ("3 Months ago", Interval::new(-3, 0, 0)),
+        ];
+
+        for (input, expected) in test_cases {
+            assert_eq!(expected, input.parse::<Interval>().unwrap(), "{input}");
+        }
+
+        for input in ["", "1", "1 fortnight", "1.5 days", "12:60:00", "ago 1 day"] {
+            input.parse::<Interval>().unwrap_err();
+        }
+    }
+
+    #[test]
+    fn interval_datafusion_round_trip() {
+        let interval = Interval::new(14, 3, 14_706_789_000_000);
+        let scalar = interval
+            .into_datafusion(&IntervalUnit::MonthDayNano)
+            .unwrap();
+        assert_eq!(Some(interval), Interval::from_datafusion(&scalar));
+
+        // Can't be represented in year-month.
+        assert_eq!(None, interval.into_datafusion(&IntervalUnit::YearMonth));
+
+        let interval = Interval::new(0, 3, 1_500 * NANOS_PER_MILLI);
+        let scalar = interval.into_datafusion(&IntervalUnit::DayTime).unwrap();
+        assert_eq!(Some(interval), Interval::from_datafusion(&scalar));
+    }
+}
@@ -1,5 +1,6 @@
 pub mod error;
 pub mod format;
+pub mod interval;
 pub mod oid;
 pub mod reader;
 pub mod scalar;
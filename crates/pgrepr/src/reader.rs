@@ -1,5 +1,7 @@
 use crate::error::{PgReprError, Result};
+use crate::interval::Interval;
 use std::str::FromStr;
+use uuid::Uuid;
 
 /// Reader defines the interface for the different kinds of values that can be
 /// decoded as a postgres type.
@@ -13,6 +15,10 @@ pub trait Reader {
     fn read_float8(buf: &[u8]) -> Result<f64>;
 
     fn read_text(buf: &[u8]) -> Result<String>;
+
+    fn read_uuid(buf: &[u8]) -> Result<Uuid>;
+
+    fn read_interval(buf: &[u8]) -> Result<Interval>;
 }
 
 #[derive(Debug)]
@@ -56,6 +62,14 @@ impl Reader for TextReader {
     fn read_text(buf: &[u8]) -> Result<String> {
         Self::parse(buf)
     }
+
+    fn read_uuid(buf: &[u8]) -> Result<Uuid> {
+        Self::parse(buf)
+    }
+
+    fn read_interval(buf: &[u8]) -> Result<Interval> {
+        Self::parse(buf)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -95,4 +109,20 @@ mod tests {
 
         let _ = TextReader::read_bool("none".as_bytes()).unwrap_err();
     }
+
+    #[test]
+    fn read_uuid() {
+        let v = TextReader::read_uuid("292a485f-a56a-4938-8f1a-bbbbbbbbbbb1".as_bytes()).unwrap();
+        assert_eq!(Uuid::from_u128(0x292a485f_a56a_4938_8f1a_bbbbbbbbbbb1), v);
+
+        let _ = TextReader::read_uuid("292a485f".as_bytes()).unwrap_err();
+    }
+
+    #[test]
+    fn read_interval() {
+        let v = TextReader::read_interval("1 year 2 mons 3 days 04:05:06".as_bytes()).unwrap();
+        assert_eq!(Interval::new(14, 3, 14_706_000_000_000), v);
+
+        let _ = TextReader::read_interval("1 fortnight".as_bytes()).unwrap_err();
+    }
 }
@@ -2,22 +2,24 @@ use std::sync::Arc;
 
 use bytes::BytesMut;
 use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
-use chrono_tz::{Tz, TZ_VARIANTS};
 use datafusion::{
     arrow::{
-        array::{Array, Float16Array},
-        datatypes::{DataType as ArrowType, TimeUnit},
+        array::{timezone::Tz, Array, Float16Array},
+        datatypes::{DataType as ArrowType, Decimal256Type, DecimalType, TimeUnit},
+        util::display::{ArrayFormatter, FormatOptions},
     },
     scalar::ScalarValue as DfScalar,
 };
 use decimal::Decimal128;
 use tokio_postgres::types::Type as PgType;
+use uuid::Uuid;
 
 use crate::{
     error::{PgReprError, Result},
     format::Format,
+    interval::Interval,
     reader::TextReader,
-    writer::{BinaryWriter, TextWriter},
+    writer::{BinaryWriter, FloatFormat, TextWriter},
 };
 
 /// Scalasentation of Postgres value. This can be used as interface
@@ -38,11 +40,13 @@ pub enum Scalar {
     Float8(f64),
     Text(String),
     Bytea(Vec<u8>),
+    Uuid(Uuid),
     Timestamp(NaiveDateTime),
     TimestampTz(DateTime<Tz>),
     Time(NaiveTime),
     Date(NaiveDate),
     Decimal(Decimal128),
+    Interval(Interval),
     // A datafusion value that isn't yet supported by us. Ultimately we want to
     // remove this and error in case we don't support something explicitly.
     Other(DfScalar),
@@ -69,6 +73,20 @@ impl Scalar {
                             false => Scalar::Float4(array.value(row_idx).to_f32()),
                         })
                     }
+                    // Maps don't have a scalar representation, and there's no
+                    // postgres equivalent. Send them as text instead (e.g.
+                    // "{a: 1, b: NULL}").
+                    &ArrowType::Map(_, _) => {
+                        if array.is_null(row_idx) {
+                            return Ok(Scalar::Null);
+                        }
+                        let options = FormatOptions::default().with_null("NULL");
+                        let formatter =
+                            ArrayFormatter::try_new(array.as_ref(), &options).map_err(|_| {
+                                PgReprError::UnsupportedArrowType(array.data_type().to_owned())
+                            })?;
+                        Ok(Scalar::Text(formatter.value(row_idx).to_string()))
+                    }
                     _ => Err(PgReprError::UnsupportedArrowType(
                         array.data_type().to_owned(),
                     )),
@@ -77,6 +95,18 @@ impl Scalar {
         }
     }
 
+    /// Convert a timestamp with a time zone to the given time zone. The
+    /// timestamp will still refer to the same instant.
+    ///
+    /// This is used for rendering timestamps in the session's time zone. Other
+    /// values are returned as is.
+    pub fn with_timezone(self, tz: &Tz) -> Self {
+        match self {
+            Self::TimestampTz(v) => Self::TimestampTz(v.with_timezone(tz)),
+            other => other,
+        }
+    }
+
     /// Returns true if the underlaying value is null.
     pub fn is_null(&self) -> bool {
         matches!(self, &Self::Null)
@@ -89,6 +119,28 @@ impl Scalar {
         }
     }
 
+    /// Same as `encode_with_format`, but uses the given float format when
+    /// encoding floats as text.
+    ///
+    /// This is used for rendering floats according to the session's
+    /// 'extra_float_digits' and 'float_exponent_threshold' variables.
+    pub fn encode_with_float_format(
+        &self,
+        format: Format,
+        float_format: &FloatFormat,
+        buf: &mut BytesMut,
+    ) -> Result<()> {
+        match (self, format) {
+            (Self::Float4(v), Format::Text) => {
+                TextWriter::write_float4_with_format(buf, *v, float_format)
+            }
+            (Self::Float8(v), Format::Text) => {
+                TextWriter::write_float8_with_format(buf, *v, float_format)
+            }
+            _ => self.encode_with_format(format, buf),
+        }
+    }
+
     /// Encodes the scalar using the specified writer.
     pub fn encode<W>(&self, buf: &mut BytesMut) -> Result<()>
     where
@@ -104,11 +156,13 @@ impl Scalar {
             Self::Float8(v) => W::write_float8(buf, *v),
             Self::Text(v) => W::write_text(buf, v),
             Self::Bytea(v) => W::write_bytea(buf, v),
+            Self::Uuid(v) => W::write_uuid(buf, v),
             Self::Timestamp(v) => W::write_timestamp(buf, v),
             Self::TimestampTz(v) => W::write_timestamptz(buf, v),
             Self::Time(v) => W::write_time(buf, v),
             Self::Date(v) => W::write_date(buf, v),
             Self::Decimal(v) => W::write_decimal(buf, v),
+            Self::Interval(v) => W::write_interval(buf, v),
             // If a type is not supported, we try to encode it as text.
             Self::Other(other) => W::write_any(buf, other),
         }
@@ -133,6 +187,8 @@ impl Scalar {
             PgType::FLOAT4 => Self::Float4(R::read_float4(buf)?),
             PgType::FLOAT8 => Self::Float8(R::read_float8(buf)?),
             PgType::TEXT => Self::Text(R::read_text(buf)?),
+            PgType::UUID => Self::Uuid(R::read_uuid(buf)?),
+            PgType::INTERVAL => Self::Interval(R::read_interval(buf)?),
             _ => return Err(PgReprError::UnsupportedPgTypeForDecode(as_type.clone())),
         };
         Ok(scalar)
@@ -156,6 +212,9 @@ impl Scalar {
             DfScalar::Float64(Some(v)) => Self::Float8(v),
             DfScalar::Utf8(Some(v)) => Self::Text(v),
             DfScalar::Binary(Some(v)) => Self::Bytea(v),
+            DfScalar::FixedSizeBinary(16, Some(v)) => {
+                Self::Uuid(Uuid::from_slice(&v).expect("value should be a valid uuid"))
+            }
             DfScalar::TimestampMicrosecond(Some(v), None) => {
                 Self::Timestamp(get_naive_date_time_nano(v * 1_000))
             }
@@ -182,6 +241,18 @@ impl Scalar {
                     Decimal128::new(v, scale).expect("value should be a valid decimal128");
                 Self::Decimal(decimal)
             }
+            // Postgres numerics support a larger precision, but we don't
+            // have a decimal type that's large enough. These are sent as
+            // text.
+            DfScalar::Decimal256(Some(v), precision, scale) => {
+                Self::Text(Decimal256Type::format_decimal(v, precision, scale))
+            }
+
+            DfScalar::IntervalYearMonth(_)
+            | DfScalar::IntervalDayTime(_)
+            | DfScalar::IntervalMonthDayNano(_) => Self::Interval(
+                Interval::from_datafusion(&value).expect("scalar value should be an interval"),
+            ),
 
             other => {
                 debug_assert!(!other.is_null());
@@ -205,6 +276,9 @@ impl Scalar {
             (Self::Float8(v), ArrowType::Float64) => DfScalar::Float64(Some(v)),
             (Self::Text(v), ArrowType::Utf8) => DfScalar::Utf8(Some(v)),
             (Self::Bytea(v), ArrowType::Binary) => DfScalar::Binary(Some(v)),
+            (Self::Uuid(v), ArrowType::FixedSizeBinary(16)) => {
+                DfScalar::FixedSizeBinary(16, Some(v.as_bytes().to_vec()))
+            }
             (Self::Timestamp(v), ArrowType::Timestamp(TimeUnit::Microsecond, None)) => {
                 let nanos = v.timestamp_nanos_opt().unwrap();
                 let micros = nanos_to_micros(nanos);
@@ -214,30 +288,14 @@ impl Scalar {
                 let nanos = v.timestamp_nanos_opt().unwrap();
                 DfScalar::TimestampNanosecond(Some(nanos), None)
             }
-            (
-                Self::TimestampTz(v),
-                arrow_type @ ArrowType::Timestamp(TimeUnit::Microsecond, Some(tz)),
-            ) => {
-                if tz.as_ref() != v.timezone().name() {
-                    return Err(PgReprError::InternalError(format!(
-                        "cannot convert from {:?} to arrow type {:?}",
-                        v, arrow_type
-                    )));
-                }
+            // Timestamps with time zones refer to an instant, so the time zone
+            // of the value doesn't need to match the time zone of the type.
+            (Self::TimestampTz(v), ArrowType::Timestamp(TimeUnit::Microsecond, Some(tz))) => {
                 let nanos = v.timestamp_nanos_opt().unwrap();
                 let micros = nanos_to_micros(nanos);
                 DfScalar::TimestampMicrosecond(Some(micros), Some(tz.clone()))
             }
-            (
-                Self::TimestampTz(v),
-                arrow_type @ ArrowType::Timestamp(TimeUnit::Nanosecond, Some(tz)),
-            ) => {
-                if tz.as_ref() != v.timezone().name() {
-                    return Err(PgReprError::InternalError(format!(
-                        "cannot convert from {:?} to arrow type {:?}",
-                        v, arrow_type
-                    )));
-                }
+            (Self::TimestampTz(v), ArrowType::Timestamp(TimeUnit::Nanosecond, Some(tz))) => {
                 let nanos = v.timestamp_nanos_opt().unwrap();
                 DfScalar::TimestampNanosecond(Some(nanos), Some(tz.clone()))
             }
@@ -264,6 +322,14 @@ impl Scalar {
                 }
                 DfScalar::Decimal128(Some(v.mantissa()), *precision, *scale)
             }
+            (Self::Interval(v), arrow_type @ ArrowType::Interval(unit)) => {
+                v.into_datafusion(unit).ok_or_else(|| {
+                    PgReprError::InternalError(format!(
+                        "cannot convert from {:?} to arrow type {:?}",
+                        v, arrow_type
+                    ))
+                })?
+            }
             (scalar, arrow_type) => {
                 return Err(PgReprError::InternalError(format!(
                     "cannot convert from scalar {:?} to arrow type {:?}",
@@ -280,14 +346,12 @@ fn get_naive_date_time_nano(nanos: i64) -> NaiveDateTime {
     Utc.timestamp_nanos(nanos).naive_utc()
 }
 
-// TODO: Figure out if this should be parsing time zone names like
-// 'Australia/Melbourne' or offsets like '+03:00'.
-fn get_timezone(tz: &str) -> Tz {
-    // TODO: Make a map at compile time to use (to speed this up).
-    *TZ_VARIANTS
-        .iter()
-        .find(|&v| v.name() == tz)
-        .unwrap_or(&chrono_tz::UTC)
+/// Get a time zone from either a time zone name (e.g. 'Australia/Melbourne')
+/// or an offset (e.g. '+03:00'). Falls back to UTC if the time zone isn't
+/// valid.
+pub fn get_timezone(tz: &str) -> Tz {
+    tz.parse()
+        .unwrap_or_else(|_| "+00:00".parse().expect("UTC offset should be valid"))
 }
 
 fn get_date_time_nano(nanos: i64, tz: &str) -> DateTime<Tz> {
@@ -311,11 +375,124 @@ fn get_nanos_from_time<T: Timelike>(t: &T) -> i64 {
 
 #[cfg(test)]
 mod tests {
+    use chrono::Offset;
+    use datafusion::arrow::array::{Int32Builder, MapBuilder, StringBuilder};
+    use datafusion::arrow::datatypes::i256;
+
     use super::*;
 
+    fn utc_offset(tz: &str, nanos: i64) -> i32 {
+        get_date_time_nano(nanos, tz)
+            .offset()
+            .fix()
+            .local_minus_utc()
+    }
+
     #[test]
     fn test_get_timezone() {
-        let tz = get_timezone("+00:00");
-        assert_eq!(chrono_tz::UTC, tz);
+        assert_eq!(0, utc_offset("+00:00", 0));
+        assert_eq!(0, utc_offset("UTC", 0));
+        assert_eq!(0, utc_offset("not_a_timezone", 0));
+        assert_eq!(3 * 3600, utc_offset("+03:00", 0));
+        assert_eq!(5 * 3600 + 1800, utc_offset("+05:30", 0));
+        // 2023-01-01 00:00:00 UTC
+        assert_eq!(
+            -5 * 3600,
+            utc_offset("America/New_York", 1_672_531_200_000_000_000)
+        );
+    }
+
+    #[test]
+    fn test_timestamptz_with_timezone() {
+        let scalar = Scalar::from_datafusion(
+            DfScalar::TimestampMicrosecond(Some(1_672_531_200_000_000), Some("UTC".into())),
+            &PgType::TIMESTAMPTZ,
+        );
+        let scalar = scalar.with_timezone(&get_timezone("America/New_York"));
+
+        let mut buf = BytesMut::new();
+        scalar.encode_with_format(Format::Text, &mut buf).unwrap();
+        assert_eq!(b"2022-12-31 19:00:00-05", buf.as_ref());
+
+        // Converting back keeps the same instant.
+        assert_eq!(
+            DfScalar::TimestampMicrosecond(Some(1_672_531_200_000_000), Some("UTC".into())),
+            scalar
+                .into_datafusion(&ArrowType::Timestamp(
+                    TimeUnit::Microsecond,
+                    Some("UTC".into())
+                ))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_uuid_round_trip() {
+        let uuid = Uuid::parse_str("292a485f-a56a-4938-8f1a-bbbbbbbbbbb1").unwrap();
+        let value = DfScalar::FixedSizeBinary(16, Some(uuid.as_bytes().to_vec()));
+
+        let scalar = Scalar::from_datafusion(value.clone(), &PgType::UUID);
+        assert_eq!(Scalar::Uuid(uuid), scalar);
+        assert_eq!(
+            value,
+            scalar
+                .into_datafusion(&ArrowType::FixedSizeBinary(16))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_map_as_text() {
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        builder.keys().append_value("a");
+        builder.values().append_value(1);
+        builder.keys().append_value("b");
+        builder.values().append_null();
+        builder.append(true).unwrap();
+        builder.append(false).unwrap();
+        let array: Arc<dyn Array> = Arc::new(builder.finish());
+
+        assert_eq!(
+            Scalar::Text("{a: 1, b: NULL}".to_string()),
+            Scalar::try_from_array(&array, 0, &PgType::TEXT).unwrap()
+        );
+        assert_eq!(
+            Scalar::Null,
+            Scalar::try_from_array(&array, 1, &PgType::TEXT).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_float_format() {
+        let format = FloatFormat {
+            extra_float_digits: 0,
+            exponent_threshold: 0,
+        };
+
+        let mut buf = BytesMut::new();
+        Scalar::Float8(0.1 + 0.2)
+            .encode_with_float_format(Format::Text, &format, &mut buf)
+            .unwrap();
+        assert_eq!(b"0.3", buf.as_ref());
+
+        // Binary encoding is unaffected.
+        let mut buf = BytesMut::new();
+        Scalar::Float8(0.1 + 0.2)
+            .encode_with_float_format(Format::Binary, &format, &mut buf)
+            .unwrap();
+        assert_eq!(&(0.1_f64 + 0.2).to_be_bytes(), buf.as_ref());
+    }
+
+    #[test]
+    fn test_decimal256_as_text() {
+        let mantissa = i256::from_string("-123456789012345678901234567890123456789012345");
+        let scalar = Scalar::from_datafusion(DfScalar::Decimal256(mantissa, 50, 10), &PgType::TEXT);
+
+        let mut buf = BytesMut::new();
+        scalar.encode_with_format(Format::Text, &mut buf).unwrap();
+        assert_eq!(
+            b"-12345678901234567890123456789012345.6789012345",
+            buf.as_ref()
+        );
     }
 }
@@ -14,11 +14,13 @@ pub fn arrow_to_pg_type(df_type: &ArrowType, type_hint: Option<PgType>) -> PgTyp
         &ArrowType::Float64 => PgType::FLOAT8,
         &ArrowType::Utf8 => PgType::TEXT,
         &ArrowType::Binary => PgType::BYTEA,
+        &ArrowType::FixedSizeBinary(16) => PgType::UUID,
         &ArrowType::Timestamp(_, None) => PgType::TIMESTAMP,
         &ArrowType::Timestamp(_, Some(_)) => PgType::TIMESTAMPTZ,
         &ArrowType::Time64(_) => PgType::TIME,
         &ArrowType::Date32 => PgType::DATE,
-        // TODO: Intervals, numerics: They are a little complicated since not
+        &ArrowType::Interval(_) => PgType::INTERVAL,
+        // TODO: Numerics: They are a little complicated since not
         // directly supported by the tokio-postgres library, need to implement
         // them explicitly. We might be better having our own `ScalarValue`
         // enum so as to move away from Arrow restrictions. Shouldn't be much
@@ -1,12 +1,16 @@
 use std::fmt::Display;
 
 use crate::error::{PgReprError, Result};
-use bytes::BytesMut;
+use crate::interval::Interval;
+use bytes::{BufMut, BytesMut};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
-use chrono_tz::Tz;
+use datafusion::arrow::array::timezone::Tz;
 use decimal::Decimal128;
 use repr::str::encode::*;
 use tokio_postgres::types::{IsNull, ToSql, Type as PgType};
+use uuid::Uuid;
+
+pub use repr::str::encode::FloatFormat;
 
 /// Writer defines the interface for the different kinds of values that can be
 /// encoded as a postgres type.
@@ -22,6 +26,7 @@ pub trait Writer {
 
     fn write_text(buf: &mut BytesMut, v: &str) -> Result<()>;
     fn write_bytea(buf: &mut BytesMut, v: &[u8]) -> Result<()>;
+    fn write_uuid(buf: &mut BytesMut, v: &Uuid) -> Result<()>;
 
     fn write_timestamp(buf: &mut BytesMut, v: &NaiveDateTime) -> Result<()>;
     fn write_timestamptz(buf: &mut BytesMut, v: &DateTime<Tz>) -> Result<()>;
@@ -30,6 +35,8 @@ pub trait Writer {
 
     fn write_decimal(buf: &mut BytesMut, v: &Decimal128) -> Result<()>;
 
+    fn write_interval(buf: &mut BytesMut, v: &Interval) -> Result<()>;
+
     fn write_any<T: Display>(buf: &mut BytesMut, v: &T) -> Result<()> {
         encode_string(buf, v)?;
         Ok(())
@@ -39,6 +46,26 @@ pub trait Writer {
 #[derive(Debug)]
 pub struct TextWriter;
 
+impl TextWriter {
+    pub fn write_float4_with_format(
+        buf: &mut BytesMut,
+        v: f32,
+        format: &FloatFormat,
+    ) -> Result<()> {
+        encode_float_with_format(buf, v, format)?;
+        Ok(())
+    }
+
+    pub fn write_float8_with_format(
+        buf: &mut BytesMut,
+        v: f64,
+        format: &FloatFormat,
+    ) -> Result<()> {
+        encode_float_with_format(buf, v, format)?;
+        Ok(())
+    }
+}
+
 impl Writer for TextWriter {
     fn write_bool(buf: &mut BytesMut, v: bool) -> Result<()> {
         encode_bool(buf, v)?;
@@ -80,13 +107,18 @@ impl Writer for TextWriter {
         Ok(())
     }
 
+    fn write_uuid(buf: &mut BytesMut, v: &Uuid) -> Result<()> {
+        encode_string(buf, v.hyphenated())?;
+        Ok(())
+    }
+
     fn write_timestamp(buf: &mut BytesMut, v: &NaiveDateTime) -> Result<()> {
         encode_utc_timestamp(buf, v, false)?;
         Ok(())
     }
 
     fn write_timestamptz(buf: &mut BytesMut, v: &DateTime<Tz>) -> Result<()> {
-        encode_utc_timestamp(buf, v, true)?;
+        encode_timestamptz(buf, v)?;
         Ok(())
     }
 
@@ -104,6 +136,11 @@ impl Writer for TextWriter {
         encode_decimal(buf, v)?;
         Ok(())
     }
+
+    fn write_interval(buf: &mut BytesMut, v: &Interval) -> Result<()> {
+        encode_interval(buf, v.months, v.days, v.nanos)?;
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -157,6 +194,10 @@ impl Writer for BinaryWriter {
         put_to_sql!(buf, BYTEA, v)
     }
 
+    fn write_uuid(buf: &mut BytesMut, v: &Uuid) -> Result<()> {
+        put_to_sql!(buf, UUID, v)
+    }
+
     fn write_timestamp(buf: &mut BytesMut, v: &NaiveDateTime) -> Result<()> {
         put_to_sql!(buf, TIMESTAMP, v)
     }
@@ -179,15 +220,27 @@ impl Writer for BinaryWriter {
             "cannot encode decimal (numeric) value into PG binary".to_string(),
         ))
     }
+
+    fn write_interval(buf: &mut BytesMut, v: &Interval) -> Result<()> {
+        // Not supported by tokio-postgres, so write it out manually. Binary
+        // intervals are microseconds, days, then months.
+        buf.put_i64(v.micros());
+        buf.put_i32(v.days);
+        buf.put_i32(v.months);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use chrono::TimeZone;
-    use chrono_tz::UTC;
 
     use super::*;
 
+    fn tz(name: &str) -> Tz {
+        name.parse().unwrap()
+    }
+
     fn assert_buf(buf: &BytesMut, val: &[u8]) {
         let slice = buf.as_ref();
         assert_eq!(
@@ -249,7 +302,7 @@ mod tests {
 
         buf.clear();
         Writer::write_float4(buf, 0.000000001).unwrap();
-        assert_buf(buf, b"1e-9");
+        assert_buf(buf, b"1e-09");
 
         buf.clear();
         Writer::write_float4(buf, 1234000000000000000000.0).unwrap();
@@ -281,7 +334,7 @@ mod tests {
 
         buf.clear();
         Writer::write_float8(buf, 0.000000001).unwrap();
-        assert_buf(buf, b"1e-9");
+        assert_buf(buf, b"1e-09");
 
         buf.clear();
         Writer::write_float8(buf, 1234000000000000000000.0).unwrap();
@@ -319,6 +372,11 @@ mod tests {
         Writer::write_bytea(buf, &[23, 13, 255, 0, 130]).unwrap();
         assert_buf(buf, b"\\x170dff0082");
 
+        buf.clear();
+        let uuid = Uuid::parse_str("292a485f-a56a-4938-8f1a-bbbbbbbbbbb1").unwrap();
+        Writer::write_uuid(buf, &uuid).unwrap();
+        assert_buf(buf, b"292a485f-a56a-4938-8f1a-bbbbbbbbbbb1");
+
         buf.clear();
         let nt = NaiveDateTime::from_timestamp_opt(938689324, 0).unwrap();
         Writer::write_timestamp(buf, &nt).unwrap();
@@ -360,7 +418,7 @@ mod tests {
         );
 
         buf.clear();
-        let dt = UTC.timestamp_opt(938689324, 0).unwrap();
+        let dt = tz("UTC").timestamp_opt(938689324, 0).unwrap();
         Writer::write_timestamptz(buf, &dt).unwrap();
         assert_buf(
             buf,
@@ -368,7 +426,7 @@ mod tests {
         );
 
         buf.clear();
-        let dt = UTC.timestamp_opt(938689324, 123567).unwrap();
+        let dt = tz("UTC").timestamp_opt(938689324, 123567).unwrap();
         Writer::write_timestamptz(buf, &dt).unwrap();
         assert_buf(
             buf,
@@ -376,7 +434,7 @@ mod tests {
         );
 
         buf.clear();
-        let dt = UTC.timestamp_opt(938689324, 123_400_000).unwrap();
+        let dt = tz("UTC").timestamp_opt(938689324, 123_400_000).unwrap();
         Writer::write_timestamptz(buf, &dt).unwrap();
         assert_buf(
             buf,
@@ -384,7 +442,7 @@ mod tests {
         );
 
         buf.clear();
-        let dt = UTC.timestamp_opt(-197199051, 0).unwrap();
+        let dt = tz("UTC").timestamp_opt(-197199051, 0).unwrap();
         Writer::write_timestamptz(buf, &dt).unwrap();
         assert_buf(
             buf,
@@ -392,13 +450,23 @@ mod tests {
         );
 
         buf.clear();
-        let dt = UTC.timestamp_opt(-62143593684, 0).unwrap();
+        let dt = tz("UTC").timestamp_opt(-62143593684, 0).unwrap();
         Writer::write_timestamptz(buf, &dt).unwrap();
         assert_buf(
             buf,
             format!("1-{}+00 BC", dt.format("%m-%d %H:%M:%S")).as_bytes(),
         );
 
+        buf.clear();
+        let dt = tz("America/New_York").timestamp_opt(938689324, 0).unwrap();
+        Writer::write_timestamptz(buf, &dt).unwrap();
+        assert_buf(buf, b"1999-09-30 07:02:04-04");
+
+        buf.clear();
+        let dt = tz("+05:30").timestamp_opt(938689324, 0).unwrap();
+        Writer::write_timestamptz(buf, &dt).unwrap();
+        assert_buf(buf, b"1999-09-30 16:32:04+05:30");
+
         buf.clear();
         let nt = NaiveTime::from_hms_nano_opt(16, 32, 4, 0).unwrap();
         Writer::write_time(buf, &nt).unwrap();
@@ -433,6 +501,11 @@ mod tests {
         let decimal = Decimal128::new(3950123456, 6).unwrap();
         Writer::write_decimal(buf, &decimal).unwrap();
         assert_buf(buf, b"3950.123456");
+
+        buf.clear();
+        let interval = Interval::new(14, 3, 14_706_789_000_000);
+        Writer::write_interval(buf, &interval).unwrap();
+        assert_buf(buf, b"1 year 2 mons 3 days 04:05:06.789");
     }
 
     #[test]
@@ -478,6 +551,11 @@ mod tests {
         Writer::write_bytea(buf, &[23, 13, 255, 0, 130]).unwrap();
         assert_buf(buf, &[23, 13, 255, 0, 130]);
 
+        buf.clear();
+        let uuid = Uuid::parse_str("292a485f-a56a-4938-8f1a-bbbbbbbbbbb1").unwrap();
+        Writer::write_uuid(buf, &uuid).unwrap();
+        assert_buf(buf, uuid.as_bytes());
+
         buf.clear();
         let nt = NaiveDateTime::from_timestamp_opt(938689324, 123567).unwrap();
         Writer::write_timestamp(buf, &nt).unwrap();
@@ -485,7 +563,7 @@ mod tests {
         assert_buf(buf, (-7_995_475_999_876_i64).to_be_bytes().as_ref());
 
         buf.clear();
-        let dt = UTC.timestamp_opt(938689324, 123567).unwrap();
+        let dt = tz("UTC").timestamp_opt(938689324, 123567).unwrap();
         Writer::write_timestamptz(buf, &dt).unwrap();
         assert_buf(buf, (-7_995_475_999_876_i64).to_be_bytes().as_ref());
 
@@ -501,6 +579,15 @@ mod tests {
         // Days since Jan 1, 2000
         assert_buf(buf, (-93_i32).to_be_bytes().as_ref());
 
+        buf.clear();
+        let interval = Interval::new(14, 3, 14_706_789_123);
+        Writer::write_interval(buf, &interval).unwrap();
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&14_706_789_i64.to_be_bytes());
+        expected.extend_from_slice(&3_i32.to_be_bytes());
+        expected.extend_from_slice(&14_i32.to_be_bytes());
+        assert_buf(buf, &expected);
+
         // buf.clear();
         // let decimal = Decimal128::new(3950123456, 6).unwrap();
         // Writer::write_decimal(buf, &decimal).unwrap();
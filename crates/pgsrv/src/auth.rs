@@ -1,3 +1,7 @@
+use std::sync::{Arc, RwLock};
+
+use sqlexec::scram::ScramVerifier;
+
 use crate::errors::{PgSrvError, Result};
 
 #[derive(Debug, Clone, Copy)]
@@ -7,6 +11,12 @@ pub enum PasswordMode {
     /// Should error if no password is provided.
     RequireCleartext,
 
+    /// SCRAM-SHA-256 authentication is required.
+    ///
+    /// Users are authenticated using the password verifiers stored in the
+    /// catalog, falling back to the authenticator's default verifier.
+    RequireScram,
+
     /// No password is required.
     NoPassword {
         /// Drop any authentication messages as well.
@@ -23,6 +33,18 @@ pub enum PasswordMode {
 pub trait LocalAuthenticator: Sync + Send {
     fn password_mode(&self) -> PasswordMode;
     fn authenticate(&self, user: &str, password: &str, db_name: &str) -> Result<()>;
+
+    /// Get the verifier to use for SCRAM authentication if the user doesn't
+    /// have a password in the catalog.
+    fn default_scram_verifier(&self, _user: &str) -> Option<ScramVerifier> {
+        None
+    }
+
+    /// Whether an authenticated user can run administrative statements, e.g.
+    /// restoring the catalog.
+    fn is_admin(&self, _user: &str) -> bool {
+        false
+    }
 }
 impl<B> LocalAuthenticator for Box<B>
 where
@@ -35,6 +57,14 @@ where
     fn authenticate(&self, user: &str, password: &str, db_name: &str) -> Result<()> {
         (**self).authenticate(user, password, db_name)
     }
+
+    fn default_scram_verifier(&self, user: &str) -> Option<ScramVerifier> {
+        (**self).default_scram_verifier(user)
+    }
+
+    fn is_admin(&self, user: &str) -> bool {
+        (**self).is_admin(user)
+    }
 }
 
 /// A simple single user authenticator.
@@ -59,6 +89,10 @@ impl LocalAuthenticator for SingleUserAuthenticator {
         }
         Ok(())
     }
+
+    fn is_admin(&self, user: &str) -> bool {
+        user == self.user
+    }
 }
 
 /// Require no password provided.
@@ -77,4 +111,87 @@ impl LocalAuthenticator for PasswordlessAuthenticator {
     fn authenticate(&self, _user: &str, _password: &str, _db_name: &str) -> Result<()> {
         Ok(())
     }
+
+    /// Anyone can connect as any user, so every user is trusted.
+    fn is_admin(&self, _user: &str) -> bool {
+        true
+    }
+}
+
+/// Require SCRAM-SHA-256 authentication.
+///
+/// Passwords are set for users with `ALTER ROLE ... PASSWORD`.
+#[derive(Debug, Clone, Default)]
+pub struct ScramAuthenticator {
+    /// User and verifier to use when the user doesn't have a password in the
+    /// catalog. Allows connecting to set up passwords for other users.
+    pub default_user: Option<(String, ScramVerifier)>,
+}
+
+impl LocalAuthenticator for ScramAuthenticator {
+    fn password_mode(&self) -> PasswordMode {
+        PasswordMode::RequireScram
+    }
+
+    fn authenticate(&self, _user: &str, _password: &str, _db_name: &str) -> Result<()> {
+        // Cleartext passwords aren't accepted.
+        Err(PgSrvError::InvalidUserOrPassword)
+    }
+
+    fn default_scram_verifier(&self, user: &str) -> Option<ScramVerifier> {
+        match &self.default_user {
+            Some((default_user, verifier)) if default_user == user => Some(verifier.clone()),
+            _ => None,
+        }
+    }
+
+    /// Only the default user is an admin.
+    fn is_admin(&self, user: &str) -> bool {
+        matches!(&self.default_user, Some((default_user, _)) if default_user == user)
+    }
+}
+
+/// An authenticator that can be replaced while the server is running.
+///
+/// Cheaply cloneable, all clones share the same underlying authenticator.
+/// Connections that have already authenticated aren't affected by replacing
+/// the authenticator.
+#[derive(Clone)]
+pub struct ReloadableAuthenticator {
+    inner: Arc<RwLock<Arc<dyn LocalAuthenticator>>>,
+}
+
+impl ReloadableAuthenticator {
+    pub fn new<A: LocalAuthenticator + 'static>(authenticator: A) -> Self {
+        ReloadableAuthenticator {
+            inner: Arc::new(RwLock::new(Arc::new(authenticator))),
+        }
+    }
+
+    /// Replace the authenticator used for new connections.
+    pub fn replace<A: LocalAuthenticator + 'static>(&self, authenticator: A) {
+        *self.inner.write().unwrap() = Arc::new(authenticator);
+    }
+
+    fn current(&self) -> Arc<dyn LocalAuthenticator> {
+        self.inner.read().unwrap().clone()
+    }
+}
+
+impl LocalAuthenticator for ReloadableAuthenticator {
+    fn password_mode(&self) -> PasswordMode {
+        self.current().password_mode()
+    }
+
+    fn authenticate(&self, user: &str, password: &str, db_name: &str) -> Result<()> {
+        self.current().authenticate(user, password, db_name)
+    }
+
+    fn default_scram_verifier(&self, user: &str) -> Option<ScramVerifier> {
+        self.current().default_scram_verifier(user)
+    }
+
+    fn is_admin(&self, user: &str) -> bool {
+        self.current().is_admin(user)
+    }
 }
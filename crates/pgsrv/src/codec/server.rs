@@ -6,9 +6,11 @@ use crate::messages::{
 use crate::ssl::Connection;
 use bytes::{Buf, BufMut, BytesMut};
 use bytesutil::{BufStringMut, Cursor};
+use datafusion::arrow::array::timezone::Tz;
 use futures::{sink::Buffer, SinkExt, TryStreamExt};
 use pgrepr::format::Format;
-use pgrepr::scalar::Scalar;
+use pgrepr::scalar::{get_timezone, Scalar};
+use pgrepr::writer::FloatFormat;
 use std::collections::HashMap;
 use std::mem::{size_of, size_of_val};
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
@@ -90,20 +92,57 @@ where
         self.conn.into_inner()
     }
 
+    /// Sets how password messages that are read next should be decoded.
+    ///
+    /// The format of these messages depends on the stage of authentication.
+    pub fn set_password_message_kind(&mut self, kind: PasswordMessageKind) {
+        self.conn.get_mut().codec_mut().password_message_kind = kind;
+    }
+
     /// Sets the encoding state for current connection.
     pub fn set_encoding_state(&mut self, s: Vec<(PgType, Format)>) {
         self.conn.get_mut().codec_mut().encoding_state = s;
     }
+
+    /// Sets the time zone to use when encoding timestamps with time zones.
+    ///
+    /// This should be kept in sync with the session's 'TimeZone' variable.
+    pub fn set_timezone(&mut self, tz: &str) {
+        self.conn.get_mut().codec_mut().timezone = get_timezone(tz);
+    }
+
+    /// Sets the format to use when encoding floats as text.
+    ///
+    /// This should be kept in sync with the session's 'extra_float_digits'
+    /// and 'float_exponent_threshold' variables.
+    pub fn set_float_format(&mut self, format: FloatFormat) {
+        self.conn.get_mut().codec_mut().float_format = format;
+    }
+}
+
+/// Frontend messages that share the password message type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PasswordMessageKind {
+    #[default]
+    Password,
+    SASLInitialResponse,
+    SASLResponse,
 }
 
 pub struct PgCodec {
     encoding_state: Vec<(PgType, Format)>,
+    timezone: Tz,
+    float_format: FloatFormat,
+    password_message_kind: PasswordMessageKind,
 }
 
 impl PgCodec {
     fn new() -> Self {
         Self {
             encoding_state: Vec::new(),
+            timezone: get_timezone("UTC"),
+            float_format: FloatFormat::default(),
+            password_message_kind: PasswordMessageKind::default(),
         }
     }
 
@@ -164,6 +203,28 @@ impl PgCodec {
         })
     }
 
+    fn decode_sasl_initial_response(buf: &mut Cursor<'_>) -> Result<FrontendMessage> {
+        let mechanism = buf.read_cstring()?.to_string();
+        // Length of -1 indicates no data.
+        let data = match buf.get_i32() {
+            len if len < 0 => None,
+            len => {
+                let len = len as usize;
+                if buf.remaining() < len {
+                    return Err(PgSrvError::InvalidMsgLength(len as i32));
+                }
+                Some(buf.copy_to_bytes(len).to_vec())
+            }
+        };
+        Ok(FrontendMessage::SASLInitialResponse { mechanism, data })
+    }
+
+    fn decode_sasl_response(buf: &mut Cursor<'_>) -> Result<FrontendMessage> {
+        Ok(FrontendMessage::SASLResponse {
+            data: buf.copy_to_bytes(buf.remaining()).to_vec(),
+        })
+    }
+
     fn decode_parse(buf: &mut Cursor<'_>) -> Result<FrontendMessage> {
         let name = buf.read_cstring()?.to_string();
         let sql = buf.read_cstring()?.to_string();
@@ -266,6 +327,9 @@ impl Encoder<BackendMessage> for PgCodec {
         let byte = match &item {
             BackendMessage::AuthenticationOk => b'R',
             BackendMessage::AuthenticationCleartextPassword => b'R',
+            BackendMessage::AuthenticationSASL { .. } => b'R',
+            BackendMessage::AuthenticationSASLContinue { .. } => b'R',
+            BackendMessage::AuthenticationSASLFinal { .. } => b'R',
             BackendMessage::EmptyQueryResponse => b'I',
             BackendMessage::ParameterStatus { .. } => b'S',
             BackendMessage::ReadyForQuery(_) => b'Z',
@@ -289,6 +353,21 @@ impl Encoder<BackendMessage> for PgCodec {
         match item {
             BackendMessage::AuthenticationOk => dst.put_i32(0),
             BackendMessage::AuthenticationCleartextPassword => dst.put_i32(3),
+            BackendMessage::AuthenticationSASL { mechanisms } => {
+                dst.put_i32(10);
+                for mechanism in mechanisms {
+                    dst.put_cstring(&mechanism);
+                }
+                dst.put_u8(0);
+            }
+            BackendMessage::AuthenticationSASLContinue { data } => {
+                dst.put_i32(11);
+                dst.put_slice(&data);
+            }
+            BackendMessage::AuthenticationSASLFinal { data } => {
+                dst.put_i32(12);
+                dst.put_slice(&data);
+            }
             BackendMessage::EmptyQueryResponse => (),
             BackendMessage::ParseComplete => (),
             BackendMessage::BindComplete => (),
@@ -321,7 +400,8 @@ impl Encoder<BackendMessage> for PgCodec {
                 for (col, (pg_type, format)) in
                     batch.columns().iter().zip(self.encoding_state.iter())
                 {
-                    let scalar = Scalar::try_from_array(col, row_idx, pg_type)?;
+                    let scalar = Scalar::try_from_array(col, row_idx, pg_type)?
+                        .with_timezone(&self.timezone);
 
                     if scalar.is_null() {
                         dst.put_i32(-1);
@@ -330,7 +410,7 @@ impl Encoder<BackendMessage> for PgCodec {
                         let len_idx = dst.len();
                         dst.put_i32(0);
 
-                        scalar.encode_with_format(*format, dst)?;
+                        scalar.encode_with_float_format(*format, &self.float_format, dst)?;
 
                         // Note the value of length does not include itself.
                         let val_len = dst.len() - len_idx - size_of::<i32>();
@@ -415,7 +495,13 @@ impl Decoder for PgCodec {
 
         let msg = match msg_type {
             b'Q' => Self::decode_query(&mut buf)?,
-            b'p' => Self::decode_password(&mut buf)?,
+            b'p' => match self.password_message_kind {
+                PasswordMessageKind::Password => Self::decode_password(&mut buf)?,
+                PasswordMessageKind::SASLInitialResponse => {
+                    Self::decode_sasl_initial_response(&mut buf)?
+                }
+                PasswordMessageKind::SASLResponse => Self::decode_sasl_response(&mut buf)?,
+            },
             b'P' => Self::decode_parse(&mut buf)?,
             b'B' => Self::decode_bind(&mut buf)?,
             b'D' => Self::decode_describe(&mut buf)?,
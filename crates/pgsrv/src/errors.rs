@@ -41,6 +41,9 @@ pub enum PgSrvError {
     #[error("Invalid user or password")]
     InvalidUserOrPassword,
 
+    #[error("Invalid SCRAM message: {0}")]
+    InvalidScramMessage(&'static str),
+
     /// A stringified error from cloud.
     #[error("cloud: {0}")]
     CloudResponse(String),
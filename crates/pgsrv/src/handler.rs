@@ -1,26 +1,30 @@
 use crate::auth::{LocalAuthenticator, PasswordMode};
-use crate::codec::server::{FramedConn, PgCodec};
+use crate::codec::server::{FramedConn, PasswordMessageKind, PgCodec};
 use crate::errors::{PgSrvError, Result};
 use crate::messages::{
     BackendMessage, DescribeObjectType, ErrorResponse, FieldDescriptionBuilder, FrontendMessage,
-    SqlState, StartupMessage, TransactionStatus,
+    NoticeResponse, SqlState, StartupMessage, TransactionStatus,
 };
 use crate::proxy::{
     ProxyKey, GLAREDB_DATABASE_ID_KEY, GLAREDB_GCS_STORAGE_BUCKET_KEY,
     GLAREDB_MAX_CREDENTIALS_COUNT_KEY, GLAREDB_MAX_DATASOURCE_COUNT_KEY,
     GLAREDB_MAX_TUNNEL_COUNT_KEY, GLAREDB_MEMORY_LIMIT_BYTES_KEY, GLAREDB_USER_ID_KEY,
 };
+use crate::scram::{ScramExchange, SCRAM_SHA_256};
 use crate::ssl::{Connection, SslConfig};
 use datafusion::arrow::datatypes::DataType;
 use datafusion::physical_plan::SendableRecordBatchStream;
 use datafusion::scalar::ScalarValue;
 use datafusion::variable::VarType;
 use datafusion_ext::vars::{Dialect, SessionVars};
+use datafusion_ext::warnings::Warning;
 use futures::StreamExt;
 use pgrepr::format::Format;
 use pgrepr::scalar::Scalar;
+use pgrepr::writer::FloatFormat;
 use sqlexec::context::local::{OutputFields, Portal, PreparedStatement};
 use sqlexec::engine::SessionStorageConfig;
+use sqlexec::scram::ScramVerifier;
 use sqlexec::{
     engine::Engine,
     parser::{self, StatementWithExtensions},
@@ -30,16 +34,20 @@ use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::ops::DerefMut;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::sync::watch;
 use tokio_postgres::types::Type as PgType;
-use tracing::{debug, debug_span, warn, Instrument};
+use tracing::{debug, debug_span, info_span, warn, Instrument};
 use uuid::Uuid;
 
 pub struct ProtocolHandlerConfig {
     /// Authenticor to use on the server side.
     pub authenticator: Box<dyn LocalAuthenticator>,
     /// SSL configuration to use on the server side.
-    pub ssl_conf: Option<SslConfig>,
+    ///
+    /// Shared so that certificates can be reloaded while serving.
+    pub ssl_conf: Option<Arc<SslConfig>>,
     /// If the server should be configured for integration tests. This is only
     /// applicable for local databases.
     pub integration_testing: bool,
@@ -50,11 +58,47 @@ pub struct ProtocolHandlerConfig {
 pub struct ProtocolHandler {
     engine: Arc<Engine>,
     conf: ProtocolHandlerConfig,
+    /// How far along shutting down the server is, watched by every
+    /// connection.
+    shutdown: watch::Sender<ShutdownStage>,
+}
+
+/// Stages of shutting down, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ShutdownStage {
+    Running,
+    /// Close connections once they're idle.
+    Draining,
+    /// Close all connections, canceling running queries.
+    Terminating,
 }
 
 impl ProtocolHandler {
     pub fn new(engine: Arc<Engine>, conf: ProtocolHandlerConfig) -> Self {
-        ProtocolHandler { engine, conf }
+        let (shutdown, _) = watch::channel(ShutdownStage::Running);
+        ProtocolHandler {
+            engine,
+            conf,
+            shutdown,
+        }
+    }
+
+    /// Start closing connections for shutdown.
+    ///
+    /// Connections waiting for a query are closed immediately. Connections
+    /// running a query are closed once the query completes.
+    pub fn drain(&self) {
+        self.advance_shutdown(ShutdownStage::Draining);
+    }
+
+    /// Close all connections, canceling any running queries.
+    pub fn terminate(&self) {
+        self.advance_shutdown(ShutdownStage::Terminating);
+    }
+
+    fn advance_shutdown(&self, stage: ShutdownStage) {
+        self.shutdown
+            .send_modify(|current| *current = (*current).max(stage));
     }
 
     pub async fn handle_connection<C>(&self, id: Uuid, conn: C) -> Result<()>
@@ -68,6 +112,15 @@ impl ProtocolHandler {
 
             match startup {
                 StartupMessage::StartupRequest { params, .. } => {
+                    if matches!(conn, Connection::Unencrypted(_)) && self.requires_ssl() {
+                        debug!("rejecting unencrypted connection");
+                        let mut framed = FramedConn::new(conn);
+                        framed
+                            .send(ErrorResponse::fatal_ssl_required().into())
+                            .await?;
+                        framed.flush().await?;
+                        return Ok(());
+                    }
                     self.begin(id, conn, params).await?;
                     return Ok(());
                 }
@@ -78,7 +131,7 @@ impl ProtocolHandler {
                             // SSL supported, send back that we support it and
                             // start encrypting.
                             conn.write_all(&[b'S']).await?;
-                            Connection::new_encrypted(conn, conf.config.clone()).await?
+                            Connection::new_encrypted(conn, conf.server_config()).await?
                         }
                         (mut conn, _) => {
                             debug!("rejecting ssl request");
@@ -97,6 +150,18 @@ impl ProtocolHandler {
         }
     }
 
+    /// Whether connections must be encrypted.
+    ///
+    /// Client certificates are only verified during the TLS handshake, so
+    /// unencrypted connections have to be rejected when they're required.
+    fn requires_ssl(&self) -> bool {
+        self.conf
+            .ssl_conf
+            .as_ref()
+            .map(|conf| conf.requires_client_cert())
+            .unwrap_or(false)
+    }
+
     /// Read a value from the startup params that's been placed by pgsrv.
     ///
     /// This will also write any errors to the connection.
@@ -128,6 +193,79 @@ impl ProtocolHandler {
         self.conf.integration_testing
     }
 
+    /// Run a SCRAM-SHA-256 exchange with the frontend.
+    ///
+    /// Returns `false` if the connection closed before the exchange finished.
+    /// Users without a password still go through the full exchange before
+    /// failing so that the frontend can't tell which users exist.
+    async fn authenticate_scram<C>(
+        &self,
+        framed: &mut FramedConn<C>,
+        user_name: &str,
+        db_id: Uuid,
+    ) -> Result<bool>
+    where
+        C: AsyncRead + AsyncWrite + Unpin,
+    {
+        let verifier = match self
+            .engine
+            .get_role_scram_verifier(db_id, user_name)
+            .await?
+        {
+            Some(verifier) => verifier,
+            None => match self.conf.authenticator.default_scram_verifier(user_name) {
+                Some(verifier) => verifier,
+                None => ScramVerifier::mock()?,
+            },
+        };
+
+        framed
+            .send(BackendMessage::AuthenticationSASL {
+                mechanisms: vec![SCRAM_SHA_256.to_string()],
+            })
+            .await?;
+
+        framed.set_password_message_kind(PasswordMessageKind::SASLInitialResponse);
+        let exchange = match framed.read().await? {
+            Some(FrontendMessage::SASLInitialResponse { mechanism, data }) => {
+                if mechanism != SCRAM_SHA_256 {
+                    return Err(PgSrvError::InvalidScramMessage(
+                        "unsupported SASL mechanism",
+                    ));
+                }
+                let data = data.ok_or(PgSrvError::InvalidScramMessage(
+                    "missing client first message",
+                ))?;
+                ScramExchange::start(verifier, &data)?
+            }
+            Some(other) => return Err(PgSrvError::UnexpectedFrontendMessage(Box::new(other))),
+            None => return Ok(false),
+        };
+
+        framed
+            .send(BackendMessage::AuthenticationSASLContinue {
+                data: exchange.server_first().as_bytes().to_vec(),
+            })
+            .await?;
+
+        framed.set_password_message_kind(PasswordMessageKind::SASLResponse);
+        let msg = framed.read().await?;
+        framed.set_password_message_kind(PasswordMessageKind::Password);
+        let server_final = match msg {
+            Some(FrontendMessage::SASLResponse { data }) => exchange.finish(&data)?,
+            Some(other) => return Err(PgSrvError::UnexpectedFrontendMessage(Box::new(other))),
+            None => return Ok(false),
+        };
+
+        framed
+            .send(BackendMessage::AuthenticationSASLFinal {
+                data: server_final.into_bytes(),
+            })
+            .await?;
+
+        Ok(true)
+    }
+
     /// Runs the postgres protocol for a connection to completion.
     async fn begin<C>(
         &self,
@@ -215,6 +353,28 @@ impl ProtocolHandler {
                     None => return Ok(()),
                 }
             }
+            PasswordMode::RequireScram => {
+                match self
+                    .authenticate_scram(&mut framed, &user_name, db_id)
+                    .await
+                {
+                    Ok(true) => (),
+                    Ok(false) => return Ok(()), // Connection closed
+                    Err(e) => {
+                        framed
+                            .send(
+                                ErrorResponse::fatal_internal(format!(
+                                    "Failed to authenticate: {}",
+                                    e
+                                ))
+                                .into(),
+                            )
+                            .await?;
+                        return Err(e);
+                    }
+                }
+                framed.send(BackendMessage::AuthenticationOk).await?;
+            }
             PasswordMode::NoPassword { drop_auth_messages } => {
                 if drop_auth_messages {
                     // Send the message to frontend to ask for an auth message.
@@ -240,6 +400,23 @@ impl ProtocolHandler {
                 framed.send(BackendMessage::AuthenticationOk).await?;
             }
         }
+        // Held until the connection closes.
+        let _permit = match self.engine.connection_limiter().try_acquire(&user_name) {
+            Ok(permit) => permit,
+            Err(e) => {
+                debug!(%e, %user_name, "rejecting connection");
+                framed
+                    .send(ErrorResponse::fatal_too_many_connections(e.to_string()).into())
+                    .await?;
+                framed.flush().await?;
+                return Ok(());
+            }
+        };
+
+        // Cloud users are authenticated by the proxy, which doesn't pass
+        // along any roles.
+        let is_admin = !is_cloud_instance && self.conf.authenticator.is_admin(&user_name);
+
         let mut vars = SessionVars::default()
             .with_user_id(user_id, VarType::System)
             .with_user_name(user_name, VarType::System)
@@ -250,14 +427,19 @@ impl ProtocolHandler {
             .with_memory_limit_bytes(memory_limit_bytes, VarType::System)
             .with_max_tunnel_count(max_tunnel_count, VarType::System)
             .with_max_credentials_count(max_credentials_count, VarType::System)
-            .with_is_cloud_instance(is_cloud_instance, VarType::System);
+            .with_is_cloud_instance(is_cloud_instance, VarType::System)
+            .with_is_admin(is_admin, VarType::System);
 
         // Set other params provided on startup. Note that these are all set as
         // the "user" since these include values set in options.
         //
         // Note that we're ignoring unknown params, or params that we're unable
-        // to set as a user.
+        // to set as a user. The user and database params are skipped since
+        // they've already been handled above.
         for (key, val) in &params {
+            if key == "user" || key == "database" {
+                continue;
+            }
             if let Err(e) = vars.set(key, val, VarType::UserDefined) {
                 debug!(%e, %key, %val, "unable to set session variable from startup param");
             }
@@ -299,8 +481,9 @@ impl ProtocolHandler {
             framed.send(msg).await?;
         }
 
+        let idle_timeout = self.engine.connection_limiter().idle_session_timeout();
         let cs = ClientSession::new(sess, framed);
-        cs.run().await
+        cs.run(self.shutdown.subscribe(), idle_timeout).await
     }
 
     /// Cancel a connection.
@@ -347,10 +530,38 @@ where
         ClientSession { session, conn }
     }
 
-    async fn run(mut self) -> Result<()> {
+    /// Handle messages until the connection closes.
+    ///
+    /// The connection is closed if the client doesn't send a query within
+    /// `idle_timeout` of the previous one completing.
+    async fn run(
+        mut self,
+        mut shutdown: watch::Receiver<ShutdownStage>,
+        idle_timeout: Option<Duration>,
+    ) -> Result<()> {
         self.ready_for_query().await?;
+        // Whether the client is waiting on a query after a "ready for query".
+        // Connections are only closed when draining if they're idle.
+        let mut idle = true;
         loop {
-            let msg = self.conn.read().await?;
+            if idle && *shutdown.borrow() >= ShutdownStage::Draining {
+                return self.terminate_for_shutdown().await;
+            }
+
+            let close_stage = if idle {
+                ShutdownStage::Draining
+            } else {
+                ShutdownStage::Terminating
+            };
+            let msg = tokio::select! {
+                msg = self.conn.read() => msg?,
+                _ = wait_for_shutdown(&mut shutdown, close_stage) => {
+                    return self.terminate_for_shutdown().await;
+                }
+                _ = wait_for_idle_timeout(idle_timeout), if idle => {
+                    return self.terminate_for_idle_timeout().await;
+                }
+            };
 
             let msg = match msg {
                 Some(msg) => msg,
@@ -361,64 +572,101 @@ where
                 }
             };
 
+            if let FrontendMessage::Terminate = msg {
+                return Ok(());
+            }
+
+            // Everything but the messages within an extended query flow ends
+            // with a "ready for query".
+            idle = !matches!(
+                msg,
+                FrontendMessage::Parse { .. }
+                    | FrontendMessage::Bind { .. }
+                    | FrontendMessage::Describe { .. }
+                    | FrontendMessage::Execute { .. }
+                    | FrontendMessage::Close { .. }
+                    | FrontendMessage::Flush
+            );
+
             let span = debug_span!("pg_protocol_message", name = msg.name());
             span.follows_from(tracing::Span::current());
 
-            match msg {
-                FrontendMessage::Query { sql } => self.query(sql).instrument(span).await?,
-                FrontendMessage::Parse {
-                    name,
-                    sql,
-                    param_types,
-                } => self.parse(name, sql, param_types).instrument(span).await?,
-                FrontendMessage::Bind {
+            // Dropping the message future cancels anything it's executing.
+            tokio::select! {
+                result = self.handle_message(msg).instrument(span) => result?,
+                _ = wait_for_shutdown(&mut shutdown, ShutdownStage::Terminating) => {
+                    return self.terminate_for_shutdown().await;
+                }
+            }
+        }
+    }
+
+    async fn handle_message(&mut self, msg: FrontendMessage) -> Result<()> {
+        match msg {
+            FrontendMessage::Query { sql } => self.query(sql).await,
+            FrontendMessage::Parse {
+                name,
+                sql,
+                param_types,
+            } => self.parse(name, sql, param_types).await,
+            FrontendMessage::Bind {
+                portal,
+                statement,
+                param_formats,
+                param_values,
+                result_formats,
+            } => {
+                self.bind(
                     portal,
                     statement,
                     param_formats,
                     param_values,
                     result_formats,
-                } => {
-                    self.bind(
-                        portal,
-                        statement,
-                        param_formats,
-                        param_values,
-                        result_formats,
+                )
+                .await
+            }
+            FrontendMessage::Describe { object_type, name } => {
+                self.describe(object_type, name).await
+            }
+            FrontendMessage::Execute { portal, max_rows } => self.execute(portal, max_rows).await,
+            FrontendMessage::Close { object_type, name } => {
+                self.close_object(object_type, name).await
+            }
+            FrontendMessage::Sync => self.sync().await,
+            FrontendMessage::Flush => self.flush().await,
+            other => {
+                warn!(?other, "unsupported frontend message");
+                self.conn
+                    .send(
+                        ErrorResponse::feature_not_supported(format!(
+                            "unsupported frontend message: {:?}",
+                            other
+                        ))
+                        .into(),
                     )
-                    .instrument(span)
-                    .await?
-                }
-                FrontendMessage::Describe { object_type, name } => {
-                    self.describe(object_type, name).instrument(span).await?
-                }
-                FrontendMessage::Execute { portal, max_rows } => {
-                    self.execute(portal, max_rows).instrument(span).await?
-                }
-                FrontendMessage::Close { object_type, name } => {
-                    self.close_object(object_type, name)
-                        .instrument(span)
-                        .await?
-                }
-                FrontendMessage::Sync => self.sync().instrument(span).await?,
-                FrontendMessage::Flush => self.flush().instrument(span).await?,
-                FrontendMessage::Terminate => return Ok(()),
-                other => {
-                    warn!(?other, "unsupported frontend message");
-                    self.conn
-                        .send(
-                            ErrorResponse::feature_not_supported(format!(
-                                "unsupported frontend message: {:?}",
-                                other
-                            ))
-                            .into(),
-                        )
-                        .await?;
-                    self.ready_for_query().await?;
-                }
+                    .await?;
+                self.ready_for_query().await
             }
         }
     }
 
+    /// Let the client know the server is shutting down before the connection
+    /// is closed.
+    async fn terminate_for_shutdown(&mut self) -> Result<()> {
+        debug!("closing connection for shutdown");
+        self.send_error(ErrorResponse::fatal_admin_shutdown())
+            .await?;
+        self.flush().await
+    }
+
+    /// Let the client know the connection is being closed for being idle.
+    async fn terminate_for_idle_timeout(&mut self) -> Result<()> {
+        debug!("closing idle connection");
+        self.send_error(ErrorResponse::fatal_idle_session_timeout())
+            .await?;
+        self.flush().await
+    }
+
     /// Send an error response to the client.
     async fn send_error(&mut self, err: ErrorResponse) -> Result<()> {
         self.conn.send(err.into()).await?;
@@ -460,8 +708,16 @@ where
 
             const UNNAMED: String = String::new();
 
+            // Covers planning, execution, and streaming results back for this
+            // statement.
+            let span = info_span!("statement", sql = %stmt);
+
             // Parse...
-            if let Err(e) = session.prepare_statement(UNNAMED, stmt, Vec::new()).await {
+            if let Err(e) = session
+                .prepare_statement(UNNAMED, stmt, Vec::new())
+                .instrument(span.clone())
+                .await
+            {
                 self.send_error(e.into()).await?;
                 return self.ready_for_query().await;
             };
@@ -487,7 +743,11 @@ where
             }
 
             // Execute...
-            let stream = match session.execute_portal(&UNNAMED, 0).await {
+            let stream = match session
+                .execute_portal(&UNNAMED, 0)
+                .instrument(span.clone())
+                .await
+            {
                 Ok(stream) => stream,
                 Err(e) => {
                     self.send_error(e.into()).await?;
@@ -495,6 +755,10 @@ where
                 }
             };
 
+            Self::send_session_var_changes(conn, session.get_session_vars()).await?;
+            conn.set_timezone(&session.get_session_vars().timezone());
+            conn.set_float_format(float_format(session.get_session_vars()));
+
             // If we're returning data (SELECT), send back the output fields
             // before sending back actual data.
             if let ExecutionResult::Query { .. } = stream {
@@ -510,7 +774,9 @@ where
                 stream,
                 session_do!(self, session, get_portal, &UNNAMED, get_encoding_state),
             )
+            .instrument(span)
             .await?;
+            Self::send_warnings(conn, session.take_new_warnings()).await?;
         }
 
         if num_statements == 0 {
@@ -523,6 +789,18 @@ where
     /// Parse the provided SQL statement and store it in the session.
     async fn parse(&mut self, name: String, sql: String, param_types: Vec<i32>) -> Result<()> {
         // TODO: Ensure in transaction.
+
+        // Skip parsing and planning if this statement was planned before.
+        match self
+            .session
+            .prepare_cached_statement(name.clone(), &sql)
+            .await
+        {
+            Ok(true) => return self.conn.send(BackendMessage::ParseComplete).await,
+            Ok(false) => (),
+            Err(e) => return self.send_error(e.into()).await,
+        }
+
         let vars = self.session.get_session_vars();
         let mut stmts = match parse_sql(vars, &sql) {
             Ok(stmts) => stmts,
@@ -543,10 +821,13 @@ where
         // Store statement for future use.
         match self
             .session
-            .prepare_statement(name, stmts.pop_front(), param_types)
+            .prepare_statement(name.clone(), stmts.pop_front(), param_types)
             .await
         {
-            Ok(_) => self.conn.send(BackendMessage::ParseComplete).await,
+            Ok(_) => {
+                self.session.cache_prepared_statement(&name, &sql);
+                self.conn.send(BackendMessage::ParseComplete).await
+            }
             Err(e) => self.send_error(e.into()).await,
         }
     }
@@ -646,13 +927,23 @@ where
     async fn execute(&mut self, portal: String, max_rows: i32) -> Result<()> {
         // TODO: Ensure in transaction.
 
+        let span = info_span!("statement", %portal);
+
         let conn = &mut self.conn;
         let session = &mut self.session;
-        let stream = match session.execute_portal(&portal, max_rows).await {
+        let stream = match session
+            .execute_portal(&portal, max_rows)
+            .instrument(span.clone())
+            .await
+        {
             Ok(r) => r,
             Err(e) => return self.send_error(e.into()).await,
         };
 
+        Self::send_session_var_changes(conn, session.get_session_vars()).await?;
+        conn.set_timezone(&session.get_session_vars().timezone());
+        conn.set_float_format(float_format(session.get_session_vars()));
+
         // TODO: This seems to be missing sending back row description. Is it
         // needed? If not, a comment needs to go here.
 
@@ -661,7 +952,9 @@ where
             stream,
             session_do!(self, session, get_portal, &portal, get_encoding_state),
         )
-        .await
+        .instrument(span)
+        .await?;
+        Self::send_warnings(conn, session.take_new_warnings()).await
     }
 
     async fn close_object(&mut self, object_type: DescribeObjectType, name: String) -> Result<()> {
@@ -727,12 +1020,16 @@ where
             ExecutionResult::CreateSchema => Self::command_complete(conn, "CREATE SCHEMA").await?,
             ExecutionResult::CreateView => Self::command_complete(conn, "CREATE VIEW").await?,
             ExecutionResult::AlterTable => Self::command_complete(conn, "ALTER TABLE").await?,
+            ExecutionResult::AlterSchema => Self::command_complete(conn, "ALTER SCHEMA").await?,
             ExecutionResult::AlterDatabase => {
                 Self::command_complete(conn, "ALTER DATABASE").await?
             }
+            ExecutionResult::CommentOn => Self::command_complete(conn, "COMMENT").await?,
+            ExecutionResult::AlterRole => Self::command_complete(conn, "ALTER ROLE").await?,
             ExecutionResult::AlterTunnelRotateKeys => {
                 Self::command_complete(conn, "ALTER TUNNEL").await?
             }
+            ExecutionResult::AnalyzeTable => Self::command_complete(conn, "ANALYZE").await?,
             ExecutionResult::Set => Self::command_complete(conn, "SET").await?,
             ExecutionResult::DropTables => Self::command_complete(conn, "DROP TABLE").await?,
             ExecutionResult::DropViews => Self::command_complete(conn, "DROP VIEW").await?,
@@ -742,6 +1039,15 @@ where
             ExecutionResult::DropCredentials => {
                 Self::command_complete(conn, "DROP CREDENTIALS").await?
             }
+            ExecutionResult::ExportCatalog => {
+                Self::command_complete(conn, "EXPORT CATALOG").await?
+            }
+            ExecutionResult::ImportCatalog => {
+                Self::command_complete(conn, "IMPORT CATALOG").await?
+            }
+            ExecutionResult::RestoreCatalog => {
+                Self::command_complete(conn, "RESTORE CATALOG").await?
+            }
         };
         Ok(())
     }
@@ -774,8 +1080,7 @@ where
             let batch = match result {
                 Ok(r) => r,
                 Err(e) => {
-                    conn.send(ErrorResponse::error(SqlState::InternalError, e.to_string()).into())
-                        .await?;
+                    conn.send(ErrorResponse::from(e).into()).await?;
                     return Ok(None);
                 }
             };
@@ -789,6 +1094,33 @@ where
         Ok(Some(num_rows))
     }
 
+    /// Send notices and updated parameter values resulting from setting
+    /// session variables.
+    async fn send_session_var_changes(conn: &mut FramedConn<C>, vars: SessionVars) -> Result<()> {
+        let (notices, changed) = {
+            let mut vars = vars.write();
+            (vars.take_notices(), vars.take_changed_reported_vars())
+        };
+        for notice in notices {
+            conn.send(NoticeResponse::notice(notice).into()).await?;
+        }
+        for (key, val) in changed {
+            conn.send(BackendMessage::ParameterStatus { key, val })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Send warnings raised while executing a statement, e.g. for files
+    /// skipped while scanning.
+    async fn send_warnings(conn: &mut FramedConn<C>, warnings: Vec<Warning>) -> Result<()> {
+        for warning in warnings {
+            conn.send(NoticeResponse::warning(warning.to_string()).into())
+                .await?;
+        }
+        Ok(())
+    }
+
     async fn command_complete(conn: &mut FramedConn<C>, tag: impl Into<String>) -> Result<()> {
         conn.send(BackendMessage::CommandComplete { tag: tag.into() })
             .await
@@ -796,6 +1128,26 @@ where
 }
 
 /// Parse a sql string, returning an error response if failed to parse.
+/// Wait until shutting down has reached `stage`.
+async fn wait_for_shutdown(shutdown: &mut watch::Receiver<ShutdownStage>, stage: ShutdownStage) {
+    if shutdown
+        .wait_for(|current| *current >= stage)
+        .await
+        .is_err()
+    {
+        // Handler dropped, nothing left to shut down.
+        futures::future::pending::<()>().await
+    }
+}
+
+/// Wait for the idle timeout to elapse, forever if there's no timeout.
+async fn wait_for_idle_timeout(timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => tokio::time::sleep(timeout).await,
+        None => futures::future::pending::<()>().await,
+    }
+}
+
 fn parse_sql(
     session_vars: SessionVars,
     sql: &str,
@@ -894,6 +1246,14 @@ fn get_encoding_state(portal: &Portal) -> Vec<(PgType, Format)> {
     }
 }
 
+/// Returns the format to use when encoding floats as text for the session.
+fn float_format(vars: SessionVars) -> FloatFormat {
+    FloatFormat {
+        extra_float_digits: vars.extra_floating_digits(),
+        exponent_threshold: vars.float_exponent_threshold(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -980,4 +1340,44 @@ mod tests {
             decode_param_scalars(Vec::new(), test_case.values, &types).unwrap_err();
         }
     }
+
+    #[tokio::test]
+    async fn reject_unencrypted_when_client_cert_required() {
+        use crate::auth::PasswordlessAuthenticator;
+        use bytes::BytesMut;
+        use tokio::io::AsyncReadExt;
+
+        let engine = Engine::from_data_dir(None).await.unwrap();
+        let handler = ProtocolHandler::new(
+            Arc::new(engine),
+            ProtocolHandlerConfig {
+                authenticator: Box::<PasswordlessAuthenticator>::default(),
+                ssl_conf: Some(Arc::new(crate::ssl::tests::client_ca_config())),
+                integration_testing: false,
+            },
+        );
+
+        let (mut client, server) = tokio::io::duplex(4096);
+        let mut startup = BytesMut::new();
+        postgres_protocol::message::frontend::startup_message(
+            [("user", "glaredb"), ("database", "default")],
+            &mut startup,
+        )
+        .unwrap();
+        client.write_all(&startup).await.unwrap();
+
+        handler
+            .handle_connection(Uuid::new_v4(), server)
+            .await
+            .unwrap();
+
+        let mut resp = Vec::new();
+        client.read_to_end(&mut resp).await.unwrap();
+        // Only a fatal error response should be sent, with no authentication
+        // request.
+        assert_eq!(b'E', resp[0]);
+        let resp = String::from_utf8_lossy(&resp);
+        assert!(resp.contains("FATAL"), "{resp}");
+        assert!(resp.contains("28000"), "{resp}");
+    }
 }
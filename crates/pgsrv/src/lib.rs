@@ -13,6 +13,7 @@ pub mod auth;
 pub mod errors;
 pub mod handler;
 pub mod proxy;
+pub mod scram;
 pub mod ssl;
 
 mod codec;
@@ -1,7 +1,8 @@
 use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::DataFusionError;
 use pgrepr::error::PgReprError;
 use pgrepr::format::Format;
-use sqlexec::errors::ExecError;
+use sqlexec::errors::{datafusion_error_class, ErrorClass, ExecError};
 use std::collections::HashMap;
 use tokio_postgres::types::Type as PgType;
 
@@ -37,6 +38,16 @@ pub enum FrontendMessage {
     Query { sql: String },
     /// An encrypted or unencrypted password.
     PasswordMessage { password: String },
+    /// The first message sent by the frontend during SASL authentication.
+    SASLInitialResponse {
+        /// Name of the mechanism selected by the frontend.
+        mechanism: String,
+        /// Mechanism specific data, `None` if the frontend didn't send any.
+        data: Option<Vec<u8>>,
+    },
+    /// Any following messages sent by the frontend during SASL
+    /// authentication.
+    SASLResponse { data: Vec<u8> },
     /// An extended query parse message.
     Parse {
         /// The name of the prepared statement. An empty string denotes the
@@ -110,6 +121,8 @@ impl FrontendMessage {
         match self {
             FrontendMessage::Query { .. } => "query",
             FrontendMessage::PasswordMessage { .. } => "password",
+            FrontendMessage::SASLInitialResponse { .. } => "sasl_initial_response",
+            FrontendMessage::SASLResponse { .. } => "sasl_response",
             FrontendMessage::Parse { .. } => "parse",
             FrontendMessage::Bind { .. } => "bind",
             FrontendMessage::Describe { .. } => "describe",
@@ -122,7 +135,12 @@ impl FrontendMessage {
     }
 
     pub(crate) fn is_auth_message(&self) -> bool {
-        matches!(self, FrontendMessage::PasswordMessage { .. })
+        matches!(
+            self,
+            FrontendMessage::PasswordMessage { .. }
+                | FrontendMessage::SASLInitialResponse { .. }
+                | FrontendMessage::SASLResponse { .. }
+        )
     }
 }
 
@@ -139,10 +157,27 @@ pub enum BackendMessage {
     NoticeResponse(NoticeResponse),
     AuthenticationOk,
     AuthenticationCleartextPassword,
-    ParameterStatus { key: String, val: String },
+    /// Start SASL authentication using one of the mechanisms.
+    AuthenticationSASL {
+        mechanisms: Vec<String>,
+    },
+    /// A SASL challenge, data is mechanism specific.
+    AuthenticationSASLContinue {
+        data: Vec<u8>,
+    },
+    /// SASL authentication completed, data is mechanism specific.
+    AuthenticationSASLFinal {
+        data: Vec<u8>,
+    },
+    ParameterStatus {
+        key: String,
+        val: String,
+    },
     EmptyQueryResponse,
     ReadyForQuery(TransactionStatus),
-    CommandComplete { tag: String },
+    CommandComplete {
+        tag: String,
+    },
     RowDescription(Vec<FieldDescription>),
     DataRow(RecordBatch, usize),
     ParseComplete,
@@ -192,11 +227,50 @@ pub enum SqlState {
     // Class 01 — Warning
     Warning,
 
+    // Class 08 — Connection Exception
+    SqlClientUnableToEstablishSqlConnection,
+
     // Class 0A — Feature Not Supported
     FeatureNotSupported,
 
+    // Class 22 — Data Exception
+    DataException,
+    DivisionByZero,
+    InvalidParameterValue,
+
+    // Class 25 — Invalid Transaction State
+    ReadOnlySqlTransaction,
+
+    // Class 26 — Invalid SQL Statement Name
+    InvalidSqlStatementName,
+
+    // Class 28 — Invalid Authorization Specification
+    InvalidAuthorizationSpecification,
+    InvalidPassword,
+
+    // Class 34 — Invalid Cursor Name
+    InvalidCursorName,
+
+    // Class 3D — Invalid Catalog Name
+    InvalidCatalogName,
+
     // Class 42 — Syntax Error or Access Rule Violation
+    SyntaxErrorOrAccessRuleViolation,
     SyntaxError,
+    InsufficientPrivilege,
+    UndefinedColumn,
+    UndefinedFunction,
+    UndefinedTable,
+    UndefinedObject,
+    DuplicateObject,
+
+    // Class 53 — Insufficient Resources
+    TooManyConnections,
+    ConfigurationLimitExceeded,
+
+    // Class 57 — Operator Intervention
+    AdminShutdown,
+    IdleSessionTimeout,
 
     // Class XX — Internal Error
     InternalError,
@@ -207,13 +281,63 @@ impl SqlState {
         match self {
             SqlState::Successful => "00000",
             SqlState::Warning => "01000",
+            SqlState::SqlClientUnableToEstablishSqlConnection => "08001",
             SqlState::FeatureNotSupported => "0A000",
+            SqlState::DataException => "22000",
+            SqlState::DivisionByZero => "22012",
+            SqlState::InvalidParameterValue => "22023",
+            SqlState::ReadOnlySqlTransaction => "25006",
+            SqlState::InvalidSqlStatementName => "26000",
+            SqlState::InvalidAuthorizationSpecification => "28000",
+            SqlState::InvalidPassword => "28P01",
+            SqlState::InvalidCursorName => "34000",
+            SqlState::InvalidCatalogName => "3D000",
+            SqlState::SyntaxErrorOrAccessRuleViolation => "42000",
             SqlState::SyntaxError => "42601",
+            SqlState::InsufficientPrivilege => "42501",
+            SqlState::UndefinedColumn => "42703",
+            SqlState::UndefinedFunction => "42883",
+            SqlState::UndefinedTable => "42P01",
+            SqlState::UndefinedObject => "42704",
+            SqlState::DuplicateObject => "42710",
+            SqlState::TooManyConnections => "53300",
+            SqlState::ConfigurationLimitExceeded => "53400",
+            SqlState::AdminShutdown => "57P01",
+            SqlState::IdleSessionTimeout => "57P05",
             SqlState::InternalError => "XX000",
         }
     }
 }
 
+impl From<ErrorClass> for SqlState {
+    fn from(class: ErrorClass) -> Self {
+        match class {
+            ErrorClass::Syntax => SqlState::SyntaxError,
+            ErrorClass::InvalidStatement => SqlState::SyntaxErrorOrAccessRuleViolation,
+            ErrorClass::UndefinedTable => SqlState::UndefinedTable,
+            ErrorClass::UndefinedColumn => SqlState::UndefinedColumn,
+            ErrorClass::UndefinedFunction => SqlState::UndefinedFunction,
+            ErrorClass::UndefinedDatabase => SqlState::InvalidCatalogName,
+            ErrorClass::UndefinedObject => SqlState::UndefinedObject,
+            ErrorClass::DuplicateObject => SqlState::DuplicateObject,
+            ErrorClass::PermissionDenied => SqlState::InsufficientPrivilege,
+            ErrorClass::ReadOnly => SqlState::ReadOnlySqlTransaction,
+            ErrorClass::InvalidParameter => SqlState::InvalidParameterValue,
+            ErrorClass::InvalidData => SqlState::DataException,
+            ErrorClass::DivisionByZero => SqlState::DivisionByZero,
+            ErrorClass::UndefinedPreparedStatement => SqlState::InvalidSqlStatementName,
+            ErrorClass::UndefinedPortal => SqlState::InvalidCursorName,
+            ErrorClass::FeatureNotSupported => SqlState::FeatureNotSupported,
+            ErrorClass::ResourceLimit => SqlState::ConfigurationLimitExceeded,
+            ErrorClass::TooManyConnections => SqlState::TooManyConnections,
+            ErrorClass::ExternalSourceUnreachable => {
+                SqlState::SqlClientUnableToEstablishSqlConnection
+            }
+            ErrorClass::Internal => SqlState::InternalError,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ErrorResponse {
     pub severity: ErrorSeverity,
@@ -245,19 +369,67 @@ impl ErrorResponse {
             message: msg.into(),
         }
     }
+
+    /// Error sent when closing a connection because the server is shutting
+    /// down.
+    pub fn fatal_admin_shutdown() -> ErrorResponse {
+        ErrorResponse {
+            severity: ErrorSeverity::Fatal,
+            code: SqlState::AdminShutdown,
+            message: "terminating connection due to administrator command".to_string(),
+        }
+    }
+
+    /// Error sent when rejecting a connection because of connection limits.
+    pub fn fatal_too_many_connections(msg: impl Into<String>) -> ErrorResponse {
+        ErrorResponse {
+            severity: ErrorSeverity::Fatal,
+            code: SqlState::TooManyConnections,
+            message: msg.into(),
+        }
+    }
+
+    /// Error sent when rejecting a connection that didn't request SSL when
+    /// the server requires it.
+    pub fn fatal_ssl_required() -> ErrorResponse {
+        ErrorResponse {
+            severity: ErrorSeverity::Fatal,
+            code: SqlState::InvalidAuthorizationSpecification,
+            message: "connection requires SSL with a client certificate".to_string(),
+        }
+    }
+
+    /// Error sent when closing a connection that's been idle for too long.
+    pub fn fatal_idle_session_timeout() -> ErrorResponse {
+        ErrorResponse {
+            severity: ErrorSeverity::Fatal,
+            code: SqlState::IdleSessionTimeout,
+            message: "terminating connection due to idle-session timeout".to_string(),
+        }
+    }
 }
 
 impl From<ExecError> for ErrorResponse {
     fn from(e: ExecError) -> Self {
-        // TODO: Actually set appropriate codes.
-        ErrorResponse::error_internal(e.to_string())
+        ErrorResponse::error(e.class().into(), e.to_string())
+    }
+}
+
+impl From<DataFusionError> for ErrorResponse {
+    fn from(e: DataFusionError) -> Self {
+        ErrorResponse::error(datafusion_error_class(&e).into(), e.to_string())
     }
 }
 
 impl From<&PgSrvError> for ErrorResponse {
     fn from(e: &PgSrvError) -> Self {
-        // TODO: Actually set appropriate codes.
-        ErrorResponse::error_internal(e.to_string())
+        let code = match e {
+            PgSrvError::SqlExec(e) => e.class().into(),
+            PgSrvError::Datafusion(e) => datafusion_error_class(e).into(),
+            PgSrvError::InvalidUserOrPassword => SqlState::InvalidPassword,
+            _ => SqlState::InternalError,
+        };
+        ErrorResponse::error(code, e.to_string())
     }
 }
 
@@ -304,6 +476,22 @@ impl NoticeResponse {
             message: msg.into(),
         }
     }
+
+    pub fn notice(msg: impl Into<String>) -> NoticeResponse {
+        NoticeResponse {
+            severity: NoticeSeverity::Notice,
+            code: SqlState::Successful,
+            message: msg.into(),
+        }
+    }
+
+    pub fn warning(msg: impl Into<String>) -> NoticeResponse {
+        NoticeResponse {
+            severity: NoticeSeverity::Warning,
+            code: SqlState::Warning,
+            message: msg.into(),
+        }
+    }
 }
 
 #[derive(Debug)]
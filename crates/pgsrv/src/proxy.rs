@@ -185,7 +185,7 @@ impl<A: ProxyAuthenticator> ProxyHandler<A> {
                             // SSL supported, send back that we support it and
                             // start encrypting.
                             conn.write_all(&[b'S']).await?;
-                            Connection::new_encrypted(conn, conf.config.clone()).await?
+                            Connection::new_encrypted(conn, conf.server_config()).await?
                         }
                         (mut conn, _) => {
                             debug!("rejecting ssl request");
@@ -0,0 +1,219 @@
+//! Server side of SCRAM-SHA-256 authentication (RFC 5802, RFC 7677).
+//!
+//! Channel binding isn't supported, so only `SCRAM-SHA-256` is advertised and
+//! clients requesting channel binding are rejected.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use sqlexec::scram::{random_bytes, ScramVerifier};
+
+use crate::errors::{PgSrvError, Result};
+
+/// Name of the SASL mechanism.
+pub const SCRAM_SHA_256: &str = "SCRAM-SHA-256";
+
+/// Length of the nonce generated by the server, same as Postgres.
+const SERVER_NONCE_LEN: usize = 18;
+
+/// An in progress SCRAM exchange, started once the client first message is
+/// received.
+#[derive(Debug)]
+pub struct ScramExchange {
+    verifier: ScramVerifier,
+    /// GS2 header sent by the client, echoed back in the client final
+    /// message.
+    gs2_header: String,
+    /// Combined client and server nonce.
+    nonce: String,
+    client_first_bare: String,
+    server_first: String,
+}
+
+impl ScramExchange {
+    /// Start an exchange using the client first message.
+    ///
+    /// The username in the message is ignored, the verifier should be for the
+    /// user provided in the startup message.
+    pub fn start(verifier: ScramVerifier, client_first: &[u8]) -> Result<ScramExchange> {
+        let client_first = std::str::from_utf8(client_first)
+            .map_err(|_| invalid("client first message is not valid utf8"))?;
+
+        let (gs2_header, client_first_bare) = split_gs2_header(client_first)?;
+        let mut attrs = client_first_bare.split(',');
+        match attrs.next() {
+            Some(user) if user.starts_with("n=") => (),
+            Some(ext) if ext.starts_with("m=") => {
+                return Err(invalid("mandatory extensions are not supported"))
+            }
+            _ => return Err(invalid("missing username")),
+        }
+        let client_nonce = attrs
+            .next()
+            .and_then(|attr| attr.strip_prefix("r="))
+            .filter(|nonce| !nonce.is_empty() && nonce.chars().all(is_printable))
+            .ok_or_else(|| invalid("missing or invalid nonce"))?;
+
+        let server_nonce = BASE64.encode(random_bytes::<SERVER_NONCE_LEN>()?);
+        let nonce = format!("{client_nonce}{server_nonce}");
+        let server_first = format!(
+            "r={nonce},s={},i={}",
+            BASE64.encode(&verifier.salt),
+            verifier.iterations
+        );
+
+        Ok(ScramExchange {
+            verifier,
+            gs2_header: gs2_header.to_string(),
+            nonce,
+            client_first_bare: client_first_bare.to_string(),
+            server_first,
+        })
+    }
+
+    /// The server first message to send to the client.
+    pub fn server_first(&self) -> &str {
+        &self.server_first
+    }
+
+    /// Finish the exchange using the client final message, returning the
+    /// server final message to send to the client.
+    ///
+    /// Errors if the client's proof doesn't match the verifier.
+    pub fn finish(self, client_final: &[u8]) -> Result<String> {
+        let client_final = std::str::from_utf8(client_final)
+            .map_err(|_| invalid("client final message is not valid utf8"))?;
+
+        let (without_proof, proof) = client_final
+            .rsplit_once(",p=")
+            .ok_or_else(|| invalid("missing proof"))?;
+
+        let mut attrs = without_proof.split(',');
+        let channel_binding = attrs
+            .next()
+            .and_then(|attr| attr.strip_prefix("c="))
+            .and_then(|binding| BASE64.decode(binding).ok())
+            .ok_or_else(|| invalid("missing or invalid channel binding"))?;
+        if channel_binding != self.gs2_header.as_bytes() {
+            return Err(invalid("channel binding doesn't match"));
+        }
+        let nonce = attrs
+            .next()
+            .and_then(|attr| attr.strip_prefix("r="))
+            .ok_or_else(|| invalid("missing nonce"))?;
+        if nonce != self.nonce {
+            return Err(invalid("nonce doesn't match"));
+        }
+
+        let proof = BASE64
+            .decode(proof)
+            .map_err(|_| invalid("invalid proof encoding"))?;
+        let auth_message = format!(
+            "{},{},{without_proof}",
+            self.client_first_bare, self.server_first
+        );
+        if !self
+            .verifier
+            .verify_client_proof(auth_message.as_bytes(), &proof)
+        {
+            return Err(PgSrvError::InvalidUserOrPassword);
+        }
+
+        let signature = self.verifier.server_signature(auth_message.as_bytes());
+        Ok(format!("v={}", BASE64.encode(signature)))
+    }
+}
+
+/// Split the GS2 header (including the trailing comma) from the rest of the
+/// client first message.
+fn split_gs2_header(client_first: &str) -> Result<(&str, &str)> {
+    // `y` indicates the client supports channel binding but thinks the server
+    // doesn't, which is correct.
+    match client_first.as_bytes().first() {
+        Some(b'n') | Some(b'y') => (),
+        Some(b'p') => return Err(invalid("channel binding is not supported")),
+        _ => return Err(invalid("invalid channel binding flag")),
+    }
+    // Flag, authzid, then the bare message.
+    let mut parts = client_first.splitn(3, ',');
+    let flag = parts.next().unwrap_or_default();
+    let authzid = parts.next().ok_or_else(|| invalid("missing gs2 header"))?;
+    let bare = parts.next().ok_or_else(|| invalid("missing gs2 header"))?;
+    if flag.len() != 1 {
+        return Err(invalid("invalid channel binding flag"));
+    }
+    if !authzid.is_empty() {
+        return Err(invalid("authorization identities are not supported"));
+    }
+    let header_len = flag.len() + authzid.len() + 2;
+    Ok((&client_first[..header_len], bare))
+}
+
+fn is_printable(c: char) -> bool {
+    c.is_ascii_graphic() && c != ','
+}
+
+fn invalid(msg: &'static str) -> PgSrvError {
+    PgSrvError::InvalidScramMessage(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use postgres_protocol::authentication::sasl::{ChannelBinding, ScramSha256};
+
+    use super::*;
+
+    /// Run an exchange with a client using `password`, returning the result
+    /// of finishing the exchange on the server.
+    fn exchange(verifier: ScramVerifier, password: &str) -> Result<()> {
+        let mut client = ScramSha256::new(password.as_bytes(), ChannelBinding::unsupported());
+        let server = ScramExchange::start(verifier, client.message()).unwrap();
+
+        client.update(server.server_first().as_bytes()).unwrap();
+        let server_final = server.finish(client.message())?;
+
+        // Client checks that the server knows the verifier.
+        client.finish(server_final.as_bytes()).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn authenticates_with_password() {
+        let verifier = ScramVerifier::new("hunter2").unwrap();
+        exchange(verifier, "hunter2").unwrap();
+    }
+
+    #[test]
+    fn rejects_wrong_password() {
+        let verifier = ScramVerifier::new("hunter2").unwrap();
+        let err = exchange(verifier, "hunter3").unwrap_err();
+        assert!(matches!(err, PgSrvError::InvalidUserOrPassword), "{err}");
+
+        let err = exchange(ScramVerifier::mock().unwrap(), "hunter2").unwrap_err();
+        assert!(matches!(err, PgSrvError::InvalidUserOrPassword), "{err}");
+    }
+
+    #[test]
+    fn rejects_invalid_client_first() {
+        let invalid: [&[u8]; 6] = [
+            b"",
+            b"p=tls-server-end-point,,n=,r=abc",
+            b"n,a=admin,n=,r=abc",
+            b"n,,n=,r=",
+            b"n,,m=ext,n=,r=abc",
+            b"n,,r=abc",
+        ];
+        for client_first in invalid {
+            ScramExchange::start(ScramVerifier::mock().unwrap(), client_first).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn rejects_mismatched_nonce() {
+        let verifier = ScramVerifier::new("hunter2").unwrap();
+        let server = ScramExchange::start(verifier, b"n,,n=,r=abc").unwrap();
+        let err = server
+            .finish(b"c=biws,r=abcdef,p=AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=")
+            .unwrap_err();
+        assert!(matches!(err, PgSrvError::InvalidScramMessage(_)), "{err}");
+    }
+}
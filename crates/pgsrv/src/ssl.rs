@@ -1,53 +1,127 @@
 use crate::errors::{PgSrvError, Result};
-use rustls::{server, sign, Certificate, PrivateKey, ServerConfig};
-use std::path::Path;
+use rustls::server::AllowAnyAuthenticatedClient;
+use rustls::{server, sign, Certificate, PrivateKey, RootCertStore, ServerConfig};
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::task::{Context, Poll};
-use tokio::fs;
 use tokio::io::{self, AsyncRead, AsyncWrite, ReadBuf};
 use tokio_rustls::{server::TlsStream, TlsAcceptor};
-use tracing::debug;
+use tracing::{debug, info};
 
 /// Configuration for creating encrypted connections using SSL/TLS.
+///
+/// Certificates are read from files, and can be read again with
+/// [`SslConfig::reload`] to pick up rotated certificates without restarting.
+/// Connections that are already encrypted keep using the certificates they
+/// were established with.
 #[derive(Debug)]
 pub struct SslConfig {
-    pub config: Arc<ServerConfig>,
+    /// Path to the server certificate chain.
+    cert: PathBuf,
+    /// Path to the server private key.
+    key: PathBuf,
+    /// Path to CA certificates for verifying client certificates. Client
+    /// certificates aren't requested if unset.
+    client_ca: Option<PathBuf>,
+    config: RwLock<Arc<ServerConfig>>,
 }
 
 impl SslConfig {
     /// Create a new ssl config using the provided cert and key files.
     pub async fn new<P: AsRef<Path>>(cert: P, key: P) -> Result<SslConfig> {
-        let cert_bs = fs::read(cert).await?;
-        let mut chain = Vec::new();
-        for cert in rustls_pemfile::certs(&mut cert_bs.as_slice()) {
-            chain.push(Certificate(cert?.to_vec()))
-        }
+        Self::from_files(cert.as_ref(), key.as_ref(), None)
+    }
 
-        let key_bs = fs::read(key).await?;
-        let mut keys = Vec::new();
-        for key in rustls_pemfile::pkcs8_private_keys(&mut key_bs.as_slice()) {
-            keys.push(key?.secret_pkcs8_der().to_vec());
-        }
-        let key = match keys.len() {
-            0 => return Err(PgSrvError::ReadCertsAndKeys("No keys found")),
-            1 => PrivateKey(keys.pop().unwrap()),
-            _ => return Err(PgSrvError::ReadCertsAndKeys("Expected exactly one key")),
-        };
+    /// Create a new ssl config using the provided cert and key files.
+    ///
+    /// If `client_ca` is provided, clients are required to present a
+    /// certificate signed by one of the CA certificates in the file.
+    pub fn from_files(cert: &Path, key: &Path, client_ca: Option<&Path>) -> Result<SslConfig> {
+        let config = load_server_config(cert, key, client_ca)?;
+        Ok(SslConfig {
+            cert: cert.to_path_buf(),
+            key: key.to_path_buf(),
+            client_ca: client_ca.map(Path::to_path_buf),
+            config: RwLock::new(config),
+        })
+    }
 
-        let resolver = CertResolver::new(chain, &key)?;
+    /// Whether clients must present a certificate. Unencrypted connections
+    /// are rejected if so, since they'd skip verifying the certificate.
+    pub fn requires_client_cert(&self) -> bool {
+        self.client_ca.is_some()
+    }
 
-        let config = ServerConfig::builder()
-            .with_safe_default_cipher_suites()
-            .with_safe_default_kx_groups()
-            .with_safe_default_protocol_versions()?
-            .with_no_client_auth()
-            .with_cert_resolver(Arc::new(resolver));
+    /// Get the config to use for new connections.
+    pub fn server_config(&self) -> Arc<ServerConfig> {
+        self.config.read().unwrap().clone()
+    }
 
-        Ok(SslConfig {
-            config: Arc::new(config),
-        })
+    /// Read the certificate and key files again, using them for new
+    /// connections.
+    ///
+    /// The current config continues to be used if the files are invalid.
+    pub fn reload(&self) -> Result<()> {
+        let config = load_server_config(&self.cert, &self.key, self.client_ca.as_deref())?;
+        *self.config.write().unwrap() = config;
+        info!(cert = %self.cert.display(), "reloaded ssl certificates");
+        Ok(())
+    }
+}
+
+fn load_server_config(
+    cert: &Path,
+    key: &Path,
+    client_ca: Option<&Path>,
+) -> Result<Arc<ServerConfig>> {
+    let chain = read_certs(cert)?;
+    if chain.is_empty() {
+        return Err(PgSrvError::ReadCertsAndKeys("No certs found"));
+    }
+
+    let key_bs = std::fs::read(key)?;
+    let mut keys = Vec::new();
+    for key in rustls_pemfile::pkcs8_private_keys(&mut key_bs.as_slice()) {
+        keys.push(key?.secret_pkcs8_der().to_vec());
+    }
+    let key = match keys.len() {
+        0 => return Err(PgSrvError::ReadCertsAndKeys("No keys found")),
+        1 => PrivateKey(keys.pop().unwrap()),
+        _ => return Err(PgSrvError::ReadCertsAndKeys("Expected exactly one key")),
+    };
+
+    let resolver = CertResolver::new(chain, &key)?;
+
+    let builder = ServerConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_safe_default_protocol_versions()?;
+    let config = match client_ca {
+        Some(client_ca) => {
+            let mut roots = RootCertStore::empty();
+            for cert in read_certs(client_ca)? {
+                roots.add(&cert)?;
+            }
+            if roots.is_empty() {
+                return Err(PgSrvError::ReadCertsAndKeys("No client CA certs found"));
+            }
+            builder.with_client_cert_verifier(Arc::new(AllowAnyAuthenticatedClient::new(roots)))
+        }
+        None => builder.with_no_client_auth(),
     }
+    .with_cert_resolver(Arc::new(resolver));
+
+    Ok(Arc::new(config))
+}
+
+fn read_certs(path: &Path) -> Result<Vec<Certificate>> {
+    let bs = std::fs::read(path)?;
+    let mut certs = Vec::new();
+    for cert in rustls_pemfile::certs(&mut bs.as_slice()) {
+        certs.push(Certificate(cert?.to_vec()))
+    }
+    Ok(certs)
 }
 
 struct CertResolver {
@@ -144,9 +218,9 @@ where
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    use std::io::Write;
+    use std::io::{Seek, Write};
     use tempfile::NamedTempFile;
 
     const TEST_CERT: &str = r#"
@@ -175,6 +249,15 @@ MC4CAQAwBQYDK2VwBCIEIDGe13glRciPej49XvEZqqq4oZ5yUuL9HD2Pw1rSue2j
         temp
     }
 
+    /// Create a config requiring client certificates signed by the test
+    /// certificate.
+    pub(crate) fn client_ca_config() -> SslConfig {
+        let cert = create_file(TEST_CERT);
+        let key = create_file(TEST_KEY);
+        let ca = create_file(TEST_CERT);
+        SslConfig::from_files(cert.path(), key.path(), Some(ca.path())).unwrap()
+    }
+
     #[tokio::test]
     async fn create_with_invalid_cert() {
         let cert = create_file("invalid");
@@ -190,4 +273,36 @@ MC4CAQAwBQYDK2VwBCIEIDGe13glRciPej49XvEZqqq4oZ5yUuL9HD2Pw1rSue2j
 
         let _ = SslConfig::new(cert.path(), key.path()).await.unwrap();
     }
+
+    #[test]
+    fn create_with_client_ca() {
+        let cert = create_file(TEST_CERT);
+        let key = create_file(TEST_KEY);
+        let ca = create_file(TEST_CERT);
+
+        let conf = SslConfig::from_files(cert.path(), key.path(), Some(ca.path())).unwrap();
+        assert!(conf.requires_client_cert());
+
+        let invalid_ca = create_file("invalid");
+        let _ =
+            SslConfig::from_files(cert.path(), key.path(), Some(invalid_ca.path())).unwrap_err();
+    }
+
+    #[test]
+    fn reload_keeps_config_on_error() {
+        let mut cert = create_file(TEST_CERT);
+        let key = create_file(TEST_KEY);
+
+        let conf = SslConfig::from_files(cert.path(), key.path(), None).unwrap();
+        let before = conf.server_config();
+
+        cert.as_file_mut().set_len(0).unwrap();
+        cert.rewind().unwrap();
+        conf.reload().unwrap_err();
+        assert!(Arc::ptr_eq(&before, &conf.server_config()));
+
+        cert.write_all(TEST_CERT.as_bytes()).unwrap();
+        conf.reload().unwrap();
+        assert!(!Arc::ptr_eq(&before, &conf.server_config()));
+    }
 }
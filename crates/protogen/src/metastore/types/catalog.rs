@@ -8,7 +8,7 @@ use crate::{FromOptionalField, ProtoConvError};
 use datafusion::arrow::datatypes::DataType;
 use datafusion::logical_expr::{Signature, TypeSignature, Volatility};
 use proptest_derive::Arbitrary;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{self, Display};
 use std::str::FromStr;
 
@@ -17,6 +17,16 @@ pub struct CatalogState {
     pub version: u64,
     pub entries: HashMap<u32, CatalogEntry>,
     pub deployment: DeploymentMetadata,
+    pub session_var_defaults: Vec<SessionVarDefault>,
+    /// Password verifiers of roles. Only set for persisted catalogs, states
+    /// returned by metastore never include them.
+    pub role_passwords: Vec<RolePassword>,
+    /// Statistics for tables, keyed by table oid.
+    pub table_statistics: HashMap<u32, TableStatistics>,
+    /// Inferred schemas for external tables, keyed by table oid.
+    pub table_schemas: HashMap<u32, CachedTableSchema>,
+    /// Roles that may only read from the database.
+    pub read_only_roles: Vec<String>,
 }
 
 impl TryFrom<catalog::CatalogState> for CatalogState {
@@ -37,10 +47,32 @@ impl TryFrom<catalog::CatalogState> for CatalogState {
             .transpose()?
             .unwrap_or_default();
 
+        let mut table_schemas = HashMap::with_capacity(value.table_schemas.len());
+        for (id, schema) in value.table_schemas {
+            table_schemas.insert(id, schema.try_into()?);
+        }
+
         Ok(CatalogState {
             version: value.version,
             entries,
             deployment,
+            session_var_defaults: value
+                .session_var_defaults
+                .into_iter()
+                .map(SessionVarDefault::from)
+                .collect(),
+            role_passwords: value
+                .role_passwords
+                .into_iter()
+                .map(RolePassword::from)
+                .collect(),
+            table_statistics: value
+                .table_statistics
+                .into_iter()
+                .map(|(id, stats)| (id, stats.into()))
+                .collect(),
+            table_schemas,
+            read_only_roles: value.read_only_roles,
         })
     }
 }
@@ -59,6 +91,27 @@ impl TryFrom<CatalogState> for catalog::CatalogState {
                 })
                 .collect::<Result<_, _>>()?,
             deployment: Some(value.deployment.try_into()?),
+            session_var_defaults: value
+                .session_var_defaults
+                .into_iter()
+                .map(catalog::SessionVarDefault::from)
+                .collect(),
+            role_passwords: value
+                .role_passwords
+                .into_iter()
+                .map(catalog::RolePassword::from)
+                .collect(),
+            table_statistics: value
+                .table_statistics
+                .into_iter()
+                .map(|(id, stats)| (id, stats.into()))
+                .collect(),
+            table_schemas: value
+                .table_schemas
+                .into_iter()
+                .map(|(id, schema)| Ok((id, schema.try_into()?)))
+                .collect::<Result<_, ProtoConvError>>()?,
+            read_only_roles: value.read_only_roles,
         })
     }
 }
@@ -87,6 +140,181 @@ impl TryFrom<DeploymentMetadata> for catalog::DeploymentMetadata {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SessionVarDefault {
+    /// Database the default applies to, all databases if `None`.
+    pub database_id: Option<u32>,
+    /// Role the default applies to, all roles if `None`.
+    pub role: Option<String>,
+    pub name: String,
+    pub value: String,
+}
+
+impl SessionVarDefault {
+    /// Check if this default applies to sessions connecting to a database as
+    /// some role.
+    pub fn applies_to(&self, database_id: u32, role: &str) -> bool {
+        self.database_id.map(|id| id == database_id).unwrap_or(true)
+            && self.role.as_deref().map(|r| r == role).unwrap_or(true)
+    }
+
+    /// Check if this default is for the same variable and target as another.
+    pub fn same_target(&self, other: &SessionVarDefault) -> bool {
+        self.database_id == other.database_id
+            && self.role == other.role
+            && self.name.eq_ignore_ascii_case(&other.name)
+    }
+}
+
+impl From<catalog::SessionVarDefault> for SessionVarDefault {
+    fn from(value: catalog::SessionVarDefault) -> Self {
+        Self {
+            database_id: value.database_id,
+            role: value.role,
+            name: value.name,
+            value: value.value,
+        }
+    }
+}
+
+impl From<SessionVarDefault> for catalog::SessionVarDefault {
+    fn from(value: SessionVarDefault) -> Self {
+        Self {
+            database_id: value.database_id,
+            role: value.role,
+            name: value.name,
+            value: value.value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RolePassword {
+    pub role: String,
+    /// SCRAM-SHA-256 verifier for the password.
+    pub scram_verifier: String,
+}
+
+impl From<catalog::RolePassword> for RolePassword {
+    fn from(value: catalog::RolePassword) -> Self {
+        Self {
+            role: value.role,
+            scram_verifier: value.scram_verifier,
+        }
+    }
+}
+
+impl From<RolePassword> for catalog::RolePassword {
+    fn from(value: RolePassword) -> Self {
+        Self {
+            role: value.role,
+            scram_verifier: value.scram_verifier,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Arbitrary, PartialEq, Eq)]
+pub struct TableStatistics {
+    pub num_rows: u64,
+    pub columns: Vec<ColumnStatistics>,
+}
+
+impl From<catalog::TableStatistics> for TableStatistics {
+    fn from(value: catalog::TableStatistics) -> Self {
+        Self {
+            num_rows: value.num_rows,
+            columns: value
+                .columns
+                .into_iter()
+                .map(ColumnStatistics::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<TableStatistics> for catalog::TableStatistics {
+    fn from(value: TableStatistics) -> Self {
+        Self {
+            num_rows: value.num_rows,
+            columns: value
+                .columns
+                .into_iter()
+                .map(catalog::ColumnStatistics::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Arbitrary, PartialEq, Eq, Hash)]
+pub struct CachedTableSchema {
+    pub columns: Vec<InternalColumnDefinition>,
+    /// Unix timestamp (seconds) of when the schema was inferred.
+    pub inferred_at: i64,
+}
+
+impl TryFrom<catalog::CachedTableSchema> for CachedTableSchema {
+    type Error = ProtoConvError;
+    fn try_from(value: catalog::CachedTableSchema) -> Result<Self, Self::Error> {
+        Ok(Self {
+            columns: value
+                .columns
+                .into_iter()
+                .map(InternalColumnDefinition::try_from)
+                .collect::<Result<_, _>>()?,
+            inferred_at: value.inferred_at,
+        })
+    }
+}
+
+impl TryFrom<CachedTableSchema> for catalog::CachedTableSchema {
+    type Error = ProtoConvError;
+    fn try_from(value: CachedTableSchema) -> Result<Self, Self::Error> {
+        Ok(Self {
+            columns: value
+                .columns
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            inferred_at: value.inferred_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Arbitrary, PartialEq, Eq)]
+pub struct ColumnStatistics {
+    pub name: String,
+    pub null_count: u64,
+    /// Estimated number of distinct non-null values.
+    pub distinct_count: Option<u64>,
+    /// Min and max values cast to strings.
+    pub min_value: Option<String>,
+    pub max_value: Option<String>,
+}
+
+impl From<catalog::ColumnStatistics> for ColumnStatistics {
+    fn from(value: catalog::ColumnStatistics) -> Self {
+        Self {
+            name: value.name,
+            null_count: value.null_count,
+            distinct_count: value.distinct_count,
+            min_value: value.min_value,
+            max_value: value.max_value,
+        }
+    }
+}
+
+impl From<ColumnStatistics> for catalog::ColumnStatistics {
+    fn from(value: ColumnStatistics) -> Self {
+        Self {
+            name: value.name,
+            null_count: value.null_count,
+            distinct_count: value.distinct_count,
+            min_value: value.min_value,
+            max_value: value.max_value,
+        }
+    }
+}
+
 // TODO: Implement Arbitrary and add test. This would require implementing
 // Arbitrary for arrow's DataType.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -417,13 +645,18 @@ impl From<DatabaseEntry> for catalog::DatabaseEntry {
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
 pub struct SchemaEntry {
     pub meta: EntryMeta,
+    /// Key-value tags on the schema.
+    pub tags: BTreeMap<String, String>,
 }
 
 impl TryFrom<catalog::SchemaEntry> for SchemaEntry {
     type Error = ProtoConvError;
     fn try_from(value: catalog::SchemaEntry) -> Result<Self, Self::Error> {
         let meta: EntryMeta = value.meta.required("meta")?;
-        Ok(SchemaEntry { meta })
+        Ok(SchemaEntry {
+            meta,
+            tags: value.tags.into_iter().collect(),
+        })
     }
 }
 
@@ -431,6 +664,7 @@ impl From<SchemaEntry> for catalog::SchemaEntry {
     fn from(value: SchemaEntry) -> Self {
         catalog::SchemaEntry {
             meta: Some(value.meta.into()),
+            tags: value.tags.into_iter().collect(),
         }
     }
 }
@@ -441,6 +675,10 @@ pub struct TableEntry {
     pub options: TableOptions,
     pub tunnel_id: Option<u32>,
     pub access_mode: SourceAccessMode,
+    /// Comments on columns, keyed by column name.
+    pub column_comments: BTreeMap<String, String>,
+    /// Key-value tags on the table.
+    pub tags: BTreeMap<String, String>,
 }
 
 impl TableEntry {
@@ -462,6 +700,8 @@ impl TryFrom<catalog::TableEntry> for TableEntry {
             options: value.options.required("options".to_string())?,
             tunnel_id: value.tunnel_id,
             access_mode: value.access_mode.try_into()?,
+            column_comments: value.column_comments.into_iter().collect(),
+            tags: value.tags.into_iter().collect(),
         })
     }
 }
@@ -474,6 +714,8 @@ impl TryFrom<TableEntry> for catalog::TableEntry {
             options: Some(value.options.try_into()?),
             tunnel_id: value.tunnel_id,
             access_mode: value.access_mode.into(),
+            column_comments: value.column_comments.into_iter().collect(),
+            tags: value.tags.into_iter().collect(),
         })
     }
 }
@@ -489,6 +731,8 @@ pub struct ViewEntry {
     pub meta: EntryMeta,
     pub sql: String,
     pub columns: Vec<String>,
+    /// Oids of the tables and views referenced by the view.
+    pub dependencies: Vec<u32>,
 }
 
 impl TryFrom<catalog::ViewEntry> for ViewEntry {
@@ -499,6 +743,7 @@ impl TryFrom<catalog::ViewEntry> for ViewEntry {
             meta,
             sql: value.sql,
             columns: value.columns,
+            dependencies: value.dependencies,
         })
     }
 }
@@ -509,6 +754,7 @@ impl From<ViewEntry> for catalog::ViewEntry {
             meta: Some(value.meta.into()),
             sql: value.sql,
             columns: value.columns,
+            dependencies: value.dependencies,
         }
     }
 }
@@ -871,6 +1117,11 @@ mod tests {
             version: 4,
             entries: HashMap::new(),
             deployment: None,
+            session_var_defaults: Vec::new(),
+            role_passwords: Vec::new(),
+            table_statistics: HashMap::new(),
+            table_schemas: HashMap::new(),
+            read_only_roles: Vec::new(),
         };
 
         let converted: CatalogState = state.try_into().unwrap();
@@ -878,6 +1129,11 @@ mod tests {
             version: 4,
             entries: HashMap::new(),
             deployment: DeploymentMetadata { storage_size: 0 },
+            session_var_defaults: Vec::new(),
+            role_passwords: Vec::new(),
+            table_statistics: HashMap::new(),
+            table_schemas: HashMap::new(),
+            read_only_roles: Vec::new(),
         };
 
         assert_eq!(expected, converted);
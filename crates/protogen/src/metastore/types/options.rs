@@ -843,11 +843,44 @@ impl From<TableOptionsMysql> for options::TableOptionsMysql {
     }
 }
 
+/// Options for inferring the schema of CSV and JSON files.
+#[derive(Debug, Clone, Default, Arbitrary, PartialEq, Eq, Hash)]
+pub struct SchemaInferenceOptions {
+    /// Number of records read to infer the schema.
+    pub infer_rows: Option<u64>,
+    /// Column types used instead of the inferred types, e.g. 'id bigint, name
+    /// text'.
+    pub columns: Option<String>,
+    /// Read all columns as strings.
+    pub all_varchar: bool,
+}
+
+impl From<options::SchemaInferenceOptions> for SchemaInferenceOptions {
+    fn from(value: options::SchemaInferenceOptions) -> Self {
+        SchemaInferenceOptions {
+            infer_rows: value.infer_rows,
+            columns: value.columns,
+            all_varchar: value.all_varchar,
+        }
+    }
+}
+
+impl From<SchemaInferenceOptions> for options::SchemaInferenceOptions {
+    fn from(value: SchemaInferenceOptions) -> Self {
+        options::SchemaInferenceOptions {
+            infer_rows: value.infer_rows,
+            columns: value.columns,
+            all_varchar: value.all_varchar,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq, Hash)]
 pub struct TableOptionsLocal {
     pub location: String,
     pub file_type: String,
     pub compression: Option<String>,
+    pub inference: SchemaInferenceOptions,
 }
 
 impl TryFrom<options::TableOptionsLocal> for TableOptionsLocal {
@@ -857,6 +890,7 @@ impl TryFrom<options::TableOptionsLocal> for TableOptionsLocal {
             location: value.location,
             file_type: value.file_type,
             compression: value.compression,
+            inference: value.inference.map(Into::into).unwrap_or_default(),
         })
     }
 }
@@ -867,6 +901,7 @@ impl From<TableOptionsLocal> for options::TableOptionsLocal {
             location: value.location,
             file_type: value.file_type,
             compression: value.compression,
+            inference: Some(value.inference.into()),
         }
     }
 }
@@ -878,6 +913,8 @@ pub struct TableOptionsGcs {
     pub location: String,
     pub file_type: String,
     pub compression: Option<String>,
+    pub cache: bool,
+    pub inference: SchemaInferenceOptions,
 }
 
 impl TryFrom<options::TableOptionsGcs> for TableOptionsGcs {
@@ -889,6 +926,8 @@ impl TryFrom<options::TableOptionsGcs> for TableOptionsGcs {
             location: value.location,
             file_type: value.file_type,
             compression: value.compression,
+            cache: value.cache.unwrap_or(true),
+            inference: value.inference.map(Into::into).unwrap_or_default(),
         })
     }
 }
@@ -901,6 +940,8 @@ impl From<TableOptionsGcs> for options::TableOptionsGcs {
             location: value.location,
             file_type: value.file_type,
             compression: value.compression,
+            cache: Some(value.cache),
+            inference: Some(value.inference.into()),
         }
     }
 }
@@ -914,6 +955,8 @@ pub struct TableOptionsS3 {
     pub location: String,
     pub file_type: String,
     pub compression: Option<String>,
+    pub cache: bool,
+    pub inference: SchemaInferenceOptions,
 }
 
 impl TryFrom<options::TableOptionsS3> for TableOptionsS3 {
@@ -927,6 +970,8 @@ impl TryFrom<options::TableOptionsS3> for TableOptionsS3 {
             location: value.location,
             file_type: value.file_type,
             compression: value.compression,
+            cache: value.cache.unwrap_or(true),
+            inference: value.inference.map(Into::into).unwrap_or_default(),
         })
     }
 }
@@ -941,6 +986,8 @@ impl From<TableOptionsS3> for options::TableOptionsS3 {
             location: value.location,
             file_type: value.file_type,
             compression: value.compression,
+            cache: Some(value.cache),
+            inference: Some(value.inference.into()),
         }
     }
 }
@@ -1075,6 +1122,7 @@ pub struct TableOptionsObjectStore {
     pub file_type: Option<String>,
     pub compression: Option<String>,
     pub schema_sample_size: Option<i64>,
+    pub inference: SchemaInferenceOptions,
 }
 
 impl TryFrom<options::TableOptionsObjectStore> for TableOptionsObjectStore {
@@ -1086,6 +1134,7 @@ impl TryFrom<options::TableOptionsObjectStore> for TableOptionsObjectStore {
             file_type: value.file_type,
             compression: value.compression,
             schema_sample_size: value.schema_sample_size,
+            inference: value.inference.map(Into::into).unwrap_or_default(),
         })
     }
 }
@@ -1098,6 +1147,7 @@ impl From<TableOptionsObjectStore> for options::TableOptionsObjectStore {
             file_type: value.file_type,
             compression: value.compression,
             schema_sample_size: value.schema_sample_size,
+            inference: Some(value.inference.into()),
         }
     }
 }
@@ -1107,18 +1157,24 @@ pub enum TunnelOptions {
     Internal(TunnelOptionsInternal),
     Debug(TunnelOptionsDebug),
     Ssh(TunnelOptionsSsh),
+    Socks5(TunnelOptionsProxy),
+    Http(TunnelOptionsProxy),
 }
 
 impl TunnelOptions {
     pub const INTERNAL: &'static str = "internal";
     pub const DEBUG: &'static str = "debug";
     pub const SSH: &'static str = "ssh";
+    pub const SOCKS5: &'static str = "socks5";
+    pub const HTTP: &'static str = "http";
 
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Internal(_) => Self::INTERNAL,
             Self::Debug(_) => Self::DEBUG,
             Self::Ssh(_) => Self::SSH,
+            Self::Socks5(_) => Self::SOCKS5,
+            Self::Http(_) => Self::HTTP,
         }
     }
 }
@@ -1136,6 +1192,8 @@ impl TryFrom<options::tunnel_options::Options> for TunnelOptions {
             options::tunnel_options::Options::Internal(v) => Self::Internal(v.try_into()?),
             options::tunnel_options::Options::Debug(v) => Self::Debug(v.try_into()?),
             options::tunnel_options::Options::Ssh(v) => Self::Ssh(v.try_into()?),
+            options::tunnel_options::Options::Socks5(v) => Self::Socks5(v.try_into()?),
+            options::tunnel_options::Options::Http(v) => Self::Http(v.try_into()?),
         })
     }
 }
@@ -1153,6 +1211,8 @@ impl From<TunnelOptions> for options::tunnel_options::Options {
             TunnelOptions::Internal(v) => options::tunnel_options::Options::Internal(v.into()),
             TunnelOptions::Debug(v) => options::tunnel_options::Options::Debug(v.into()),
             TunnelOptions::Ssh(v) => options::tunnel_options::Options::Ssh(v.into()),
+            TunnelOptions::Socks5(v) => options::tunnel_options::Options::Socks5(v.into()),
+            TunnelOptions::Http(v) => options::tunnel_options::Options::Http(v.into()),
         }
     }
 }
@@ -1222,6 +1282,38 @@ impl From<TunnelOptionsSsh> for options::TunnelOptionsSsh {
     }
 }
 
+/// Address and credentials for a SOCKS5 or HTTP CONNECT proxy.
+#[derive(Debug, Clone, Arbitrary, PartialEq, Eq, Hash)]
+pub struct TunnelOptionsProxy {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl TryFrom<options::TunnelOptionsProxy> for TunnelOptionsProxy {
+    type Error = ProtoConvError;
+    fn try_from(value: options::TunnelOptionsProxy) -> Result<Self, Self::Error> {
+        Ok(TunnelOptionsProxy {
+            host: value.host,
+            port: value.port.try_into()?,
+            username: value.username,
+            password: value.password,
+        })
+    }
+}
+
+impl From<TunnelOptionsProxy> for options::TunnelOptionsProxy {
+    fn from(value: TunnelOptionsProxy) -> Self {
+        options::TunnelOptionsProxy {
+            host: value.host,
+            port: value.port.into(),
+            username: value.username,
+            password: value.password,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1245,6 +1337,15 @@ mod tests {
             assert_eq!(expected, got);
         }
     }
+
+    proptest! {
+        #[test]
+        fn roundtrip_tunnel_options(expected in any::<TunnelOptions>()) {
+            let p: options::TunnelOptions = expected.clone().into();
+            let got: TunnelOptions = p.try_into().unwrap();
+            assert_eq!(expected, got);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq, Hash)]
@@ -1512,6 +1613,19 @@ pub struct CopyToFormatOptionsCsv {
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct CopyToFormatOptionsParquet {
     pub row_group_size: usize,
+    /// Columns to write bloom filters for.
+    pub bloom_filter_columns: Vec<String>,
+    /// Compression codec, uncompressed if not set.
+    pub compression: Option<String>,
+    pub compression_level: Option<i32>,
+    /// Whether columns are dictionary encoded, unless set for the column.
+    pub dictionary: bool,
+    pub dictionary_enabled_columns: Vec<String>,
+    pub dictionary_disabled_columns: Vec<String>,
+    /// Parquet writer version, '1.0' if not set.
+    pub writer_version: Option<String>,
+    /// Split the output into multiple files of about this many bytes.
+    pub target_file_size: Option<usize>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -1,10 +1,12 @@
-use super::catalog::SourceAccessMode;
+use super::catalog::{CachedTableSchema, SourceAccessMode, TableStatistics};
 use super::options::{
     CredentialsOptions, DatabaseOptions, TableOptions, TableOptionsInternal, TunnelOptions,
 };
 use crate::gen::metastore::service;
 use crate::{FromOptionalField, ProtoConvError};
 use proptest_derive::Arbitrary;
+use std::collections::BTreeMap;
+use std::time::SystemTime;
 
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
 pub enum Mutation {
@@ -16,8 +18,13 @@ pub enum Mutation {
     CreateTable(CreateTable),
     CreateExternalTable(CreateExternalTable),
     CreateExternalDatabase(CreateExternalDatabase),
+    CreateDatabase(CreateDatabase),
     AlterTable(AlterTable),
+    AlterSchema(AlterSchema),
     AlterDatabase(AlterDatabase),
+    CommentOn(CommentOn),
+    AlterRole(AlterRole),
+    UpdateTableStatistics(UpdateTableStatistics),
     CreateTunnel(CreateTunnel),
     DropTunnel(DropTunnel),
     AlterTunnelRotateKeys(AlterTunnelRotateKeys),
@@ -26,6 +33,7 @@ pub enum Mutation {
     DropCredentials(DropCredentials),
     // Deployment metadata updates
     UpdateDeploymentStorage(UpdateDeploymentStorage),
+    RestoreCatalog(RestoreCatalog),
 }
 
 impl TryFrom<service::Mutation> for Mutation {
@@ -51,8 +59,17 @@ impl TryFrom<service::mutation::Mutation> for Mutation {
             service::mutation::Mutation::CreateExternalDatabase(v) => {
                 Mutation::CreateExternalDatabase(v.try_into()?)
             }
+            service::mutation::Mutation::CreateDatabase(v) => {
+                Mutation::CreateDatabase(v.try_into()?)
+            }
             service::mutation::Mutation::AlterTable(v) => Mutation::AlterTable(v.try_into()?),
+            service::mutation::Mutation::AlterSchema(v) => Mutation::AlterSchema(v.try_into()?),
+            service::mutation::Mutation::CommentOn(v) => Mutation::CommentOn(v.into()),
             service::mutation::Mutation::AlterDatabase(v) => Mutation::AlterDatabase(v.try_into()?),
+            service::mutation::Mutation::AlterRole(v) => Mutation::AlterRole(v.try_into()?),
+            service::mutation::Mutation::UpdateTableStatistics(v) => {
+                Mutation::UpdateTableStatistics(v.try_into()?)
+            }
             service::mutation::Mutation::CreateTunnel(v) => Mutation::CreateTunnel(v.try_into()?),
             service::mutation::Mutation::DropTunnel(v) => Mutation::DropTunnel(v.try_into()?),
             service::mutation::Mutation::AlterTunnelRotateKeys(v) => {
@@ -70,6 +87,9 @@ impl TryFrom<service::mutation::Mutation> for Mutation {
             service::mutation::Mutation::UpdateDeploymentStorage(v) => {
                 Mutation::UpdateDeploymentStorage(v.try_into()?)
             }
+            service::mutation::Mutation::RestoreCatalog(v) => {
+                Mutation::RestoreCatalog(v.try_into()?)
+            }
         })
     }
 }
@@ -90,8 +110,15 @@ impl TryFrom<Mutation> for service::mutation::Mutation {
             Mutation::CreateExternalDatabase(v) => {
                 service::mutation::Mutation::CreateExternalDatabase(v.into())
             }
-            Mutation::AlterTable(v) => service::mutation::Mutation::AlterTable(v.into()),
+            Mutation::CreateDatabase(v) => service::mutation::Mutation::CreateDatabase(v.into()),
+            Mutation::AlterTable(v) => service::mutation::Mutation::AlterTable(v.try_into()?),
+            Mutation::AlterSchema(v) => service::mutation::Mutation::AlterSchema(v.into()),
+            Mutation::CommentOn(v) => service::mutation::Mutation::CommentOn(v.into()),
             Mutation::AlterDatabase(v) => service::mutation::Mutation::AlterDatabase(v.into()),
+            Mutation::AlterRole(v) => service::mutation::Mutation::AlterRole(v.into()),
+            Mutation::UpdateTableStatistics(v) => {
+                service::mutation::Mutation::UpdateTableStatistics(v.into())
+            }
             Mutation::CreateTunnel(v) => service::mutation::Mutation::CreateTunnel(v.into()),
             Mutation::DropTunnel(v) => service::mutation::Mutation::DropTunnel(v.into()),
             Mutation::AlterTunnelRotateKeys(v) => {
@@ -107,6 +134,7 @@ impl TryFrom<Mutation> for service::mutation::Mutation {
             Mutation::UpdateDeploymentStorage(v) => {
                 service::mutation::Mutation::UpdateDeploymentStorage(v.into())
             }
+            Mutation::RestoreCatalog(v) => service::mutation::Mutation::RestoreCatalog(v.into()),
         })
     }
 }
@@ -148,6 +176,7 @@ impl From<DropDatabase> for service::DropDatabase {
 
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
 pub struct DropSchema {
+    pub database: String,
     pub name: String,
     pub if_exists: bool,
     pub cascade: bool,
@@ -158,6 +187,7 @@ impl TryFrom<service::DropSchema> for DropSchema {
     fn try_from(value: service::DropSchema) -> Result<Self, Self::Error> {
         // TODO: Check if string is zero value.
         Ok(DropSchema {
+            database: value.database,
             name: value.name,
             if_exists: value.if_exists,
             cascade: value.cascade,
@@ -168,6 +198,7 @@ impl TryFrom<service::DropSchema> for DropSchema {
 impl From<DropSchema> for service::DropSchema {
     fn from(value: DropSchema) -> Self {
         service::DropSchema {
+            database: value.database,
             name: value.name,
             if_exists: value.if_exists,
             cascade: value.cascade,
@@ -177,9 +208,11 @@ impl From<DropSchema> for service::DropSchema {
 
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
 pub struct DropObject {
+    pub database: String,
     pub schema: String,
     pub name: String,
     pub if_exists: bool,
+    pub cascade: bool,
 }
 
 impl TryFrom<service::DropObject> for DropObject {
@@ -187,9 +220,11 @@ impl TryFrom<service::DropObject> for DropObject {
     fn try_from(value: service::DropObject) -> Result<Self, Self::Error> {
         // TODO: Check if strings are zero value.
         Ok(DropObject {
+            database: value.database,
             schema: value.schema,
             name: value.name,
             if_exists: value.if_exists,
+            cascade: value.cascade,
         })
     }
 }
@@ -197,15 +232,18 @@ impl TryFrom<service::DropObject> for DropObject {
 impl From<DropObject> for service::DropObject {
     fn from(value: DropObject) -> Self {
         service::DropObject {
+            database: value.database,
             schema: value.schema,
             name: value.name,
             if_exists: value.if_exists,
+            cascade: value.cascade,
         }
     }
 }
 
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
 pub struct CreateSchema {
+    pub database: String,
     pub name: String,
     pub if_not_exists: bool,
 }
@@ -215,6 +253,7 @@ impl TryFrom<service::CreateSchema> for CreateSchema {
     fn try_from(value: service::CreateSchema) -> Result<Self, Self::Error> {
         // TODO: Check if string are zero value.
         Ok(CreateSchema {
+            database: value.database,
             name: value.name,
             if_not_exists: value.if_not_exists,
         })
@@ -224,6 +263,7 @@ impl TryFrom<service::CreateSchema> for CreateSchema {
 impl From<CreateSchema> for service::CreateSchema {
     fn from(value: CreateSchema) -> Self {
         service::CreateSchema {
+            database: value.database,
             name: value.name,
             if_not_exists: value.if_not_exists,
         }
@@ -231,11 +271,13 @@ impl From<CreateSchema> for service::CreateSchema {
 }
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
 pub struct CreateView {
+    pub database: String,
     pub schema: String,
     pub name: String,
     pub sql: String,
     pub or_replace: bool,
     pub columns: Vec<String>,
+    pub dependencies: Vec<u32>,
 }
 
 impl TryFrom<service::CreateView> for CreateView {
@@ -243,11 +285,13 @@ impl TryFrom<service::CreateView> for CreateView {
     fn try_from(value: service::CreateView) -> Result<Self, Self::Error> {
         // TODO: Check if string are zero value.
         Ok(CreateView {
+            database: value.database,
             schema: value.schema,
             name: value.name,
             sql: value.sql,
             or_replace: value.or_replace,
             columns: value.columns,
+            dependencies: value.dependencies,
         })
     }
 }
@@ -255,17 +299,20 @@ impl TryFrom<service::CreateView> for CreateView {
 impl From<CreateView> for service::CreateView {
     fn from(value: CreateView) -> Self {
         service::CreateView {
+            database: value.database,
             schema: value.schema,
             name: value.name,
             sql: value.sql,
             or_replace: value.or_replace,
             columns: value.columns,
+            dependencies: value.dependencies,
         }
     }
 }
 
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
 pub struct CreateTable {
+    pub database: String,
     pub schema: String,
     pub name: String,
     pub options: TableOptionsInternal,
@@ -278,6 +325,7 @@ impl TryFrom<service::CreateTable> for CreateTable {
     fn try_from(value: service::CreateTable) -> Result<Self, Self::Error> {
         let options: TableOptionsInternal = value.options.required("options")?;
         Ok(CreateTable {
+            database: value.database,
             schema: value.schema,
             name: value.name,
             options,
@@ -291,6 +339,7 @@ impl TryFrom<CreateTable> for service::CreateTable {
     type Error = ProtoConvError;
     fn try_from(value: CreateTable) -> Result<service::CreateTable, Self::Error> {
         Ok(service::CreateTable {
+            database: value.database,
             schema: value.schema,
             name: value.name,
             options: Some(value.options.try_into()?),
@@ -302,6 +351,7 @@ impl TryFrom<CreateTable> for service::CreateTable {
 
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
 pub struct CreateExternalTable {
+    pub database: String,
     pub schema: String,
     pub name: String,
     pub options: TableOptions,
@@ -315,6 +365,7 @@ impl TryFrom<service::CreateExternalTable> for CreateExternalTable {
     fn try_from(value: service::CreateExternalTable) -> Result<Self, Self::Error> {
         // TODO: Check if string are zero value.
         Ok(CreateExternalTable {
+            database: value.database,
             schema: value.schema,
             name: value.name,
             options: value.options.required("options")?,
@@ -329,6 +380,7 @@ impl TryFrom<CreateExternalTable> for service::CreateExternalTable {
     type Error = ProtoConvError;
     fn try_from(value: CreateExternalTable) -> Result<Self, Self::Error> {
         Ok(service::CreateExternalTable {
+            database: value.database,
             schema: value.schema,
             name: value.name,
             options: Some(value.options.try_into()?),
@@ -339,6 +391,31 @@ impl TryFrom<CreateExternalTable> for service::CreateExternalTable {
     }
 }
 
+#[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
+pub struct CreateDatabase {
+    pub name: String,
+    pub if_not_exists: bool,
+}
+
+impl TryFrom<service::CreateDatabase> for CreateDatabase {
+    type Error = ProtoConvError;
+    fn try_from(value: service::CreateDatabase) -> Result<Self, Self::Error> {
+        Ok(CreateDatabase {
+            name: value.name,
+            if_not_exists: value.if_not_exists,
+        })
+    }
+}
+
+impl From<CreateDatabase> for service::CreateDatabase {
+    fn from(value: CreateDatabase) -> Self {
+        service::CreateDatabase {
+            name: value.name,
+            if_not_exists: value.if_not_exists,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
 pub struct CreateExternalDatabase {
     pub name: String,
@@ -374,6 +451,9 @@ impl From<CreateExternalDatabase> for service::CreateExternalDatabase {
 pub enum AlterTableOperation {
     RenameTable { new_name: String },
     SetAccessMode { access_mode: SourceAccessMode },
+    RefreshSchema { schema: CachedTableSchema },
+    SetTags { tags: BTreeMap<String, String> },
+    UnsetTags { keys: Vec<String> },
 }
 
 impl TryFrom<service::alter_table_operation::Operation> for AlterTableOperation {
@@ -388,13 +468,27 @@ impl TryFrom<service::alter_table_operation::Operation> for AlterTableOperation
             ) => Self::SetAccessMode {
                 access_mode: access_mode.try_into()?,
             },
+            service::alter_table_operation::Operation::AlterTableOperationRefreshSchema(
+                service::AlterTableOperationRefreshSchema { schema },
+            ) => Self::RefreshSchema {
+                schema: schema.required("schema")?,
+            },
+            service::alter_table_operation::Operation::AlterTableOperationSetTags(
+                service::AlterTableOperationSetTags { tags },
+            ) => Self::SetTags {
+                tags: tags.into_iter().collect(),
+            },
+            service::alter_table_operation::Operation::AlterTableOperationUnsetTags(
+                service::AlterTableOperationUnsetTags { keys },
+            ) => Self::UnsetTags { keys },
         })
     }
 }
 
-impl From<AlterTableOperation> for service::alter_table_operation::Operation {
-    fn from(value: AlterTableOperation) -> Self {
-        match value {
+impl TryFrom<AlterTableOperation> for service::alter_table_operation::Operation {
+    type Error = ProtoConvError;
+    fn try_from(value: AlterTableOperation) -> Result<Self, Self::Error> {
+        Ok(match value {
             AlterTableOperation::RenameTable { new_name } => {
                 service::alter_table_operation::Operation::AlterTableOperationRename(
                     service::AlterTableOperationRename { new_name },
@@ -407,7 +501,26 @@ impl From<AlterTableOperation> for service::alter_table_operation::Operation {
                     },
                 )
             }
-        }
+            AlterTableOperation::RefreshSchema { schema } => {
+                service::alter_table_operation::Operation::AlterTableOperationRefreshSchema(
+                    service::AlterTableOperationRefreshSchema {
+                        schema: Some(schema.try_into()?),
+                    },
+                )
+            }
+            AlterTableOperation::SetTags { tags } => {
+                service::alter_table_operation::Operation::AlterTableOperationSetTags(
+                    service::AlterTableOperationSetTags {
+                        tags: tags.into_iter().collect(),
+                    },
+                )
+            }
+            AlterTableOperation::UnsetTags { keys } => {
+                service::alter_table_operation::Operation::AlterTableOperationUnsetTags(
+                    service::AlterTableOperationUnsetTags { keys },
+                )
+            }
+        })
     }
 }
 
@@ -418,16 +531,18 @@ impl TryFrom<service::AlterTableOperation> for AlterTableOperation {
     }
 }
 
-impl From<AlterTableOperation> for service::AlterTableOperation {
-    fn from(value: AlterTableOperation) -> Self {
-        Self {
-            operation: Some(value.into()),
-        }
+impl TryFrom<AlterTableOperation> for service::AlterTableOperation {
+    type Error = ProtoConvError;
+    fn try_from(value: AlterTableOperation) -> Result<Self, Self::Error> {
+        Ok(Self {
+            operation: Some(value.try_into()?),
+        })
     }
 }
 
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
 pub struct AlterTable {
+    pub database: String,
     pub schema: String,
     pub name: String,
     pub operation: AlterTableOperation,
@@ -437,6 +552,7 @@ impl TryFrom<service::AlterTable> for AlterTable {
     type Error = ProtoConvError;
     fn try_from(value: service::AlterTable) -> Result<Self, Self::Error> {
         Ok(AlterTable {
+            database: value.database,
             schema: value.schema,
             name: value.name,
             operation: value.operation.required("alter table operation")?,
@@ -444,20 +560,166 @@ impl TryFrom<service::AlterTable> for AlterTable {
     }
 }
 
-impl From<AlterTable> for service::AlterTable {
-    fn from(value: AlterTable) -> Self {
-        service::AlterTable {
+impl TryFrom<AlterTable> for service::AlterTable {
+    type Error = ProtoConvError;
+    fn try_from(value: AlterTable) -> Result<Self, Self::Error> {
+        Ok(service::AlterTable {
+            database: value.database,
             schema: value.schema,
             name: value.name,
+            operation: Some(value.operation.try_into()?),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Arbitrary, PartialEq, Eq, Hash)]
+pub enum AlterSchemaOperation {
+    RenameSchema { new_name: String },
+    SetTags { tags: BTreeMap<String, String> },
+    UnsetTags { keys: Vec<String> },
+}
+
+impl TryFrom<service::alter_schema_operation::Operation> for AlterSchemaOperation {
+    type Error = ProtoConvError;
+    fn try_from(value: service::alter_schema_operation::Operation) -> Result<Self, Self::Error> {
+        Ok(match value {
+            service::alter_schema_operation::Operation::AlterSchemaOperationRename(
+                service::AlterSchemaOperationRename { new_name },
+            ) => Self::RenameSchema { new_name },
+            service::alter_schema_operation::Operation::AlterSchemaOperationSetTags(
+                service::AlterSchemaOperationSetTags { tags },
+            ) => Self::SetTags {
+                tags: tags.into_iter().collect(),
+            },
+            service::alter_schema_operation::Operation::AlterSchemaOperationUnsetTags(
+                service::AlterSchemaOperationUnsetTags { keys },
+            ) => Self::UnsetTags { keys },
+        })
+    }
+}
+
+impl From<AlterSchemaOperation> for service::alter_schema_operation::Operation {
+    fn from(value: AlterSchemaOperation) -> Self {
+        match value {
+            AlterSchemaOperation::RenameSchema { new_name } => {
+                service::alter_schema_operation::Operation::AlterSchemaOperationRename(
+                    service::AlterSchemaOperationRename { new_name },
+                )
+            }
+            AlterSchemaOperation::SetTags { tags } => {
+                service::alter_schema_operation::Operation::AlterSchemaOperationSetTags(
+                    service::AlterSchemaOperationSetTags {
+                        tags: tags.into_iter().collect(),
+                    },
+                )
+            }
+            AlterSchemaOperation::UnsetTags { keys } => {
+                service::alter_schema_operation::Operation::AlterSchemaOperationUnsetTags(
+                    service::AlterSchemaOperationUnsetTags { keys },
+                )
+            }
+        }
+    }
+}
+
+impl TryFrom<service::AlterSchemaOperation> for AlterSchemaOperation {
+    type Error = ProtoConvError;
+    fn try_from(value: service::AlterSchemaOperation) -> Result<Self, Self::Error> {
+        value.operation.required("alter schema operation")
+    }
+}
+
+impl From<AlterSchemaOperation> for service::AlterSchemaOperation {
+    fn from(value: AlterSchemaOperation) -> Self {
+        Self {
+            operation: Some(value.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
+pub struct AlterSchema {
+    pub database: String,
+    pub name: String,
+    pub operation: AlterSchemaOperation,
+}
+
+impl TryFrom<service::AlterSchema> for AlterSchema {
+    type Error = ProtoConvError;
+    fn try_from(value: service::AlterSchema) -> Result<Self, Self::Error> {
+        Ok(AlterSchema {
+            database: value.database,
+            name: value.name,
+            operation: value.operation.required("alter schema operation")?,
+        })
+    }
+}
+
+impl From<AlterSchema> for service::AlterSchema {
+    fn from(value: AlterSchema) -> Self {
+        service::AlterSchema {
+            database: value.database,
+            name: value.name,
             operation: Some(value.operation.into()),
         }
     }
 }
 
+/// Set or remove the comment on a schema, table, view or column.
+#[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
+pub struct CommentOn {
+    pub database: String,
+    pub schema: String,
+    /// Table or view in the schema, `None` when commenting on the schema.
+    pub name: Option<String>,
+    /// Column of the table, `None` when not commenting on a column.
+    pub column: Option<String>,
+    /// `None` removes the comment.
+    pub comment: Option<String>,
+}
+
+impl From<service::CommentOn> for CommentOn {
+    fn from(value: service::CommentOn) -> Self {
+        CommentOn {
+            database: value.database,
+            schema: value.schema,
+            name: value.name,
+            column: value.column,
+            comment: value.comment,
+        }
+    }
+}
+
+impl From<CommentOn> for service::CommentOn {
+    fn from(value: CommentOn) -> Self {
+        service::CommentOn {
+            database: value.database,
+            schema: value.schema,
+            name: value.name,
+            column: value.column,
+            comment: value.comment,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq, Hash)]
 pub enum AlterDatabaseOperation {
-    RenameDatabase { new_name: String },
-    SetAccessMode { access_mode: SourceAccessMode },
+    RenameDatabase {
+        new_name: String,
+    },
+    SetAccessMode {
+        access_mode: SourceAccessMode,
+    },
+    /// Set the default value of a session variable for sessions using the
+    /// database.
+    SetVariable {
+        variable: String,
+        value: String,
+    },
+    /// Remove a default set with `SetVariable`.
+    ResetVariable {
+        variable: String,
+    },
 }
 
 impl TryFrom<service::alter_database_operation::Operation> for AlterDatabaseOperation {
@@ -472,6 +734,12 @@ impl TryFrom<service::alter_database_operation::Operation> for AlterDatabaseOper
             ) => Self::SetAccessMode {
                 access_mode: access_mode.try_into()?,
             },
+            service::alter_database_operation::Operation::AlterDatabaseOperationSetVariable(
+                service::AlterDatabaseOperationSetVariable { variable, value },
+            ) => Self::SetVariable { variable, value },
+            service::alter_database_operation::Operation::AlterDatabaseOperationResetVariable(
+                service::AlterDatabaseOperationResetVariable { variable },
+            ) => Self::ResetVariable { variable },
         })
     }
 }
@@ -491,6 +759,16 @@ impl From<AlterDatabaseOperation> for service::alter_database_operation::Operati
                     },
                 )
             }
+            AlterDatabaseOperation::SetVariable { variable, value } => {
+                service::alter_database_operation::Operation::AlterDatabaseOperationSetVariable(
+                    service::AlterDatabaseOperationSetVariable { variable, value },
+                )
+            }
+            AlterDatabaseOperation::ResetVariable { variable } => {
+                service::alter_database_operation::Operation::AlterDatabaseOperationResetVariable(
+                    service::AlterDatabaseOperationResetVariable { variable },
+                )
+            }
         }
     }
 }
@@ -535,6 +813,143 @@ impl From<AlterDatabase> for service::AlterDatabase {
     }
 }
 
+#[derive(Debug, Clone, Arbitrary, PartialEq, Eq, Hash)]
+pub enum AlterRoleOperation {
+    /// Set the default value of a session variable for sessions connecting
+    /// as the role.
+    SetVariable { variable: String, value: String },
+    /// Remove a default set with `SetVariable`.
+    ResetVariable { variable: String },
+    /// Set the SCRAM-SHA-256 verifier used to authenticate the role, or remove
+    /// the password if `None`.
+    SetPassword { scram_verifier: Option<String> },
+    /// Grant or revoke read-only access for the role. Sessions for read-only
+    /// roles can't modify the catalog or any tables.
+    SetReadOnly { read_only: bool },
+}
+
+impl TryFrom<service::alter_role_operation::Operation> for AlterRoleOperation {
+    type Error = ProtoConvError;
+    fn try_from(value: service::alter_role_operation::Operation) -> Result<Self, Self::Error> {
+        Ok(match value {
+            service::alter_role_operation::Operation::AlterRoleOperationSetVariable(
+                service::AlterRoleOperationSetVariable { variable, value },
+            ) => Self::SetVariable { variable, value },
+            service::alter_role_operation::Operation::AlterRoleOperationResetVariable(
+                service::AlterRoleOperationResetVariable { variable },
+            ) => Self::ResetVariable { variable },
+            service::alter_role_operation::Operation::AlterRoleOperationSetPassword(
+                service::AlterRoleOperationSetPassword { scram_verifier },
+            ) => Self::SetPassword { scram_verifier },
+            service::alter_role_operation::Operation::AlterRoleOperationSetReadOnly(
+                service::AlterRoleOperationSetReadOnly { read_only },
+            ) => Self::SetReadOnly { read_only },
+        })
+    }
+}
+
+impl From<AlterRoleOperation> for service::alter_role_operation::Operation {
+    fn from(value: AlterRoleOperation) -> Self {
+        match value {
+            AlterRoleOperation::SetVariable { variable, value } => {
+                service::alter_role_operation::Operation::AlterRoleOperationSetVariable(
+                    service::AlterRoleOperationSetVariable { variable, value },
+                )
+            }
+            AlterRoleOperation::ResetVariable { variable } => {
+                service::alter_role_operation::Operation::AlterRoleOperationResetVariable(
+                    service::AlterRoleOperationResetVariable { variable },
+                )
+            }
+            AlterRoleOperation::SetPassword { scram_verifier } => {
+                service::alter_role_operation::Operation::AlterRoleOperationSetPassword(
+                    service::AlterRoleOperationSetPassword { scram_verifier },
+                )
+            }
+            AlterRoleOperation::SetReadOnly { read_only } => {
+                service::alter_role_operation::Operation::AlterRoleOperationSetReadOnly(
+                    service::AlterRoleOperationSetReadOnly { read_only },
+                )
+            }
+        }
+    }
+}
+
+impl TryFrom<service::AlterRoleOperation> for AlterRoleOperation {
+    type Error = ProtoConvError;
+    fn try_from(value: service::AlterRoleOperation) -> Result<Self, Self::Error> {
+        value.operation.required("alter role operation")
+    }
+}
+
+impl From<AlterRoleOperation> for service::AlterRoleOperation {
+    fn from(value: AlterRoleOperation) -> Self {
+        Self {
+            operation: Some(value.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
+pub struct AlterRole {
+    pub name: String,
+    /// Only apply to sessions connecting to this database.
+    pub database: Option<String>,
+    pub operation: AlterRoleOperation,
+}
+
+impl TryFrom<service::AlterRole> for AlterRole {
+    type Error = ProtoConvError;
+    fn try_from(value: service::AlterRole) -> Result<Self, Self::Error> {
+        Ok(AlterRole {
+            name: value.name,
+            database: value.database,
+            operation: value.operation.required("alter role operation")?,
+        })
+    }
+}
+
+impl From<AlterRole> for service::AlterRole {
+    fn from(value: AlterRole) -> Self {
+        service::AlterRole {
+            name: value.name,
+            database: value.database,
+            operation: Some(value.operation.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
+pub struct UpdateTableStatistics {
+    pub database: String,
+    pub schema: String,
+    pub name: String,
+    pub statistics: TableStatistics,
+}
+
+impl TryFrom<service::UpdateTableStatistics> for UpdateTableStatistics {
+    type Error = ProtoConvError;
+    fn try_from(value: service::UpdateTableStatistics) -> Result<Self, Self::Error> {
+        Ok(UpdateTableStatistics {
+            database: value.database,
+            schema: value.schema,
+            name: value.name,
+            statistics: value.statistics.required("statistics")?,
+        })
+    }
+}
+
+impl From<UpdateTableStatistics> for service::UpdateTableStatistics {
+    fn from(value: UpdateTableStatistics) -> Self {
+        service::UpdateTableStatistics {
+            database: value.database,
+            schema: value.schema,
+            name: value.name,
+            statistics: Some(value.statistics.into()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
 pub struct CreateTunnel {
     pub name: String,
@@ -725,6 +1140,29 @@ impl From<UpdateDeploymentStorage> for service::UpdateDeploymentStorage {
     }
 }
 
+#[derive(Debug, Clone, Arbitrary, PartialEq, Eq)]
+pub struct RestoreCatalog {
+    #[proptest(value("SystemTime::UNIX_EPOCH"))]
+    pub timestamp: SystemTime,
+}
+
+impl TryFrom<service::RestoreCatalog> for RestoreCatalog {
+    type Error = ProtoConvError;
+    fn try_from(value: service::RestoreCatalog) -> Result<Self, Self::Error> {
+        Ok(RestoreCatalog {
+            timestamp: value.timestamp.required("timestamp")?,
+        })
+    }
+}
+
+impl From<RestoreCatalog> for service::RestoreCatalog {
+    fn from(value: RestoreCatalog) -> Self {
+        service::RestoreCatalog {
+            timestamp: Some(value.timestamp.into()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,6 +147,7 @@ impl TryFrom<PersistedCatalog> for storage::PersistedCatalog {
 #[derive(Debug, Clone)]
 pub struct ExtraState {
     pub oid_counter: u32,
+    pub written_at: Option<SystemTime>,
 }
 
 impl TryFrom<storage::ExtraState> for ExtraState {
@@ -154,6 +155,7 @@ impl TryFrom<storage::ExtraState> for ExtraState {
     fn try_from(value: storage::ExtraState) -> Result<Self, Self::Error> {
         Ok(ExtraState {
             oid_counter: value.oid_counter,
+            written_at: value.written_at.optional()?,
         })
     }
 }
@@ -162,6 +164,7 @@ impl From<ExtraState> for storage::ExtraState {
     fn from(value: ExtraState) -> Self {
         storage::ExtraState {
             oid_counter: value.oid_counter,
+            written_at: value.written_at.map(|t| t.into()),
         }
     }
 }
@@ -6,6 +6,7 @@ use uuid::Uuid;
 
 use crate::{
     errors::ProtoConvError,
+    gen::rpcsrv::common,
     gen::rpcsrv::service::{self, ExternalTableReference, InternalTableReference},
     metastore::types::{catalog::CatalogState, FromOptionalField},
 };
@@ -111,6 +112,7 @@ pub struct InitializeSessionResponse {
     pub database_id: Uuid,
     pub catalog: CatalogState,
     pub user_id: Option<Uuid>,
+    pub supported_compression: Vec<common::CompressionCodec>,
 }
 
 impl TryFrom<service::InitializeSessionResponse> for InitializeSessionResponse {
@@ -120,6 +122,12 @@ impl TryFrom<service::InitializeSessionResponse> for InitializeSessionResponse {
             database_id: Uuid::from_slice(&value.database_id)?,
             catalog: value.catalog.required("catalog state")?,
             user_id: Uuid::from_slice(&value.user_id).ok(),
+            // Skip codecs this version doesn't know about.
+            supported_compression: value
+                .supported_compression
+                .into_iter()
+                .filter_map(|codec| common::CompressionCodec::try_from(codec).ok())
+                .collect(),
         })
     }
 }
@@ -134,6 +142,11 @@ impl TryFrom<InitializeSessionResponse> for service::InitializeSessionResponse {
                 .user_id
                 .map(|v| v.into_bytes().into())
                 .unwrap_or_default(),
+            supported_compression: value
+                .supported_compression
+                .into_iter()
+                .map(|codec| codec as i32)
+                .collect(),
         })
     }
 }
@@ -188,6 +201,7 @@ pub struct DispatchAccessRequest {
     pub args: Option<Vec<FuncParamValue>>,
 
     pub opts: Option<HashMap<String, FuncParamValue>>,
+    pub provider_id: Option<Uuid>,
 }
 
 impl TryFrom<service::DispatchAccessRequest> for DispatchAccessRequest {
@@ -217,6 +231,7 @@ impl TryFrom<service::DispatchAccessRequest> for DispatchAccessRequest {
             table_ref: value.table_ref.required("table reference")?,
             args,
             opts,
+            provider_id: Uuid::from_slice(&value.provider_id).ok(),
         })
     }
 }
@@ -242,6 +257,10 @@ impl From<DispatchAccessRequest> for service::DispatchAccessRequest {
             table_ref: Some(value.table_ref.into()),
             args,
             options,
+            provider_id: value
+                .provider_id
+                .map(|v| v.into_bytes().into())
+                .unwrap_or_default(),
         }
     }
 }
@@ -251,6 +270,9 @@ pub struct PhysicalPlanExecuteRequest {
     pub physical_plan: Vec<u8>,
     pub user_id: Option<Uuid>,
     pub query_text: String,
+    pub execution_id: Uuid,
+    pub result_compression: common::CompressionCodec,
+    pub compression_level: i32,
 }
 
 impl TryFrom<service::PhysicalPlanExecuteRequest> for PhysicalPlanExecuteRequest {
@@ -261,6 +283,12 @@ impl TryFrom<service::PhysicalPlanExecuteRequest> for PhysicalPlanExecuteRequest
             physical_plan: value.physical_plan,
             user_id: Uuid::from_slice(&value.user_id).ok(),
             query_text: value.query_text,
+            execution_id: Uuid::from_slice(&value.execution_id)?,
+            result_compression: common::CompressionCodec::try_from(value.result_compression)
+                .map_err(|_| {
+                    ProtoConvError::UnknownEnumVariant("CompressionCodec", value.result_compression)
+                })?,
+            compression_level: value.compression_level,
         })
     }
 }
@@ -275,6 +303,87 @@ impl From<PhysicalPlanExecuteRequest> for service::PhysicalPlanExecuteRequest {
                 .map(|v| v.into_bytes().into())
                 .unwrap_or_default(),
             query_text: value.query_text,
+            execution_id: value.execution_id.into_bytes().into(),
+            result_compression: value.result_compression as i32,
+            compression_level: value.compression_level,
+        }
+    }
+}
+
+pub struct ExecutePartitionRequest {
+    pub database_id: Uuid,
+    pub physical_plan: Vec<u8>,
+    pub partition: usize,
+}
+
+impl TryFrom<service::ExecutePartitionRequest> for ExecutePartitionRequest {
+    type Error = ProtoConvError;
+    fn try_from(value: service::ExecutePartitionRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            database_id: Uuid::from_slice(&value.database_id)?,
+            physical_plan: value.physical_plan,
+            partition: value.partition as usize,
+        })
+    }
+}
+
+impl From<ExecutePartitionRequest> for service::ExecutePartitionRequest {
+    fn from(value: ExecutePartitionRequest) -> Self {
+        Self {
+            database_id: value.database_id.into_bytes().into(),
+            physical_plan: value.physical_plan,
+            partition: value.partition as u32,
+        }
+    }
+}
+
+pub struct ResumeExecutionRequest {
+    pub database_id: Uuid,
+    pub execution_id: Uuid,
+    pub sequence: u64,
+}
+
+impl TryFrom<service::ResumeExecutionRequest> for ResumeExecutionRequest {
+    type Error = ProtoConvError;
+    fn try_from(value: service::ResumeExecutionRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            database_id: Uuid::from_slice(&value.database_id)?,
+            execution_id: Uuid::from_slice(&value.execution_id)?,
+            sequence: value.sequence,
+        })
+    }
+}
+
+impl From<ResumeExecutionRequest> for service::ResumeExecutionRequest {
+    fn from(value: ResumeExecutionRequest) -> Self {
+        Self {
+            database_id: value.database_id.into_bytes().into(),
+            execution_id: value.execution_id.into_bytes().into(),
+            sequence: value.sequence,
+        }
+    }
+}
+
+pub struct CancelExecutionRequest {
+    pub database_id: Uuid,
+    pub execution_id: Uuid,
+}
+
+impl TryFrom<service::CancelExecutionRequest> for CancelExecutionRequest {
+    type Error = ProtoConvError;
+    fn try_from(value: service::CancelExecutionRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            database_id: Uuid::from_slice(&value.database_id)?,
+            execution_id: Uuid::from_slice(&value.execution_id)?,
+        })
+    }
+}
+
+impl From<CancelExecutionRequest> for service::CancelExecutionRequest {
+    fn from(value: CancelExecutionRequest) -> Self {
+        Self {
+            database_id: value.database_id.into_bytes().into(),
+            execution_id: value.execution_id.into_bytes().into(),
         }
     }
 }
@@ -9,6 +9,8 @@ pub struct PostgresAccess {
     pub conn_str: String,
     #[prost(message, optional, tag = "2")]
     pub tunnel: Option<TunnelOptions>,
+    #[prost(string, tag = "3")]
+    pub numeric_fallback: String,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -94,6 +94,22 @@ pub struct CopyToFormatOptionsJson {
 pub struct CopyToFormatOptionsParquet {
     #[prost(uint64, tag = "1")]
     pub row_group_size: u64,
+    #[prost(string, repeated, tag = "2")]
+    pub bloom_filter_columns: Vec<String>,
+    #[prost(string, optional, tag = "3")]
+    pub compression: Option<String>,
+    #[prost(int32, optional, tag = "4")]
+    pub compression_level: Option<i32>,
+    #[prost(bool, optional, tag = "5")]
+    pub dictionary: Option<bool>,
+    #[prost(string, repeated, tag = "6")]
+    pub dictionary_enabled_columns: Vec<String>,
+    #[prost(string, repeated, tag = "7")]
+    pub dictionary_disabled_columns: Vec<String>,
+    #[prost(string, optional, tag = "8")]
+    pub writer_version: Option<String>,
+    #[prost(uint64, optional, tag = "9")]
+    pub target_file_size: Option<u64>,
 }
 
 impl TryFrom<crate::metastore::types::options::CopyToFormatOptions> for CopyToFormatOptions {
@@ -127,6 +143,14 @@ impl TryFrom<crate::metastore::types::options::CopyToFormatOptions> for CopyToFo
                     copy_to_format_options_enum: Some(CopyToFormatOptionsEnum::Parquet(
                         CopyToFormatOptionsParquet {
                             row_group_size: parquet.row_group_size as u64,
+                            bloom_filter_columns: parquet.bloom_filter_columns,
+                            compression: parquet.compression,
+                            compression_level: parquet.compression_level,
+                            dictionary: Some(parquet.dictionary),
+                            dictionary_enabled_columns: parquet.dictionary_enabled_columns,
+                            dictionary_disabled_columns: parquet.dictionary_disabled_columns,
+                            writer_version: parquet.writer_version,
+                            target_file_size: parquet.target_file_size.map(|size| size as u64),
                         },
                     )),
                 })
@@ -164,6 +188,14 @@ impl TryFrom<CopyToFormatOptions> for crate::metastore::types::options::CopyToFo
                 crate::metastore::types::options::CopyToFormatOptions::Parquet(
                     crate::metastore::types::options::CopyToFormatOptionsParquet {
                         row_group_size: parquet.row_group_size as usize,
+                        bloom_filter_columns: parquet.bloom_filter_columns,
+                        compression: parquet.compression,
+                        compression_level: parquet.compression_level,
+                        dictionary: parquet.dictionary.unwrap_or(true),
+                        dictionary_enabled_columns: parquet.dictionary_enabled_columns,
+                        dictionary_disabled_columns: parquet.dictionary_disabled_columns,
+                        writer_version: parquet.writer_version,
+                        target_file_size: parquet.target_file_size.map(|size| size as usize),
                     },
                 ),
             ),
@@ -2,6 +2,7 @@ mod postgres;
 pub use postgres::*;
 
 use crate::gen::metastore::catalog::TableEntry;
+use crate::gen::metastore::service::Mutation;
 use datafusion_proto::protobuf::{LogicalExprNode, Schema};
 use prost::{Message, Oneof};
 
@@ -84,6 +85,56 @@ pub struct AlterDatabaseExec {
     pub operation: Option<crate::gen::metastore::service::AlterDatabaseOperation>,
 }
 
+#[derive(Clone, PartialEq, Message)]
+pub struct AlterSchemaExec {
+    #[prost(uint64, tag = "1")]
+    pub catalog_version: u64,
+    #[prost(string, tag = "2")]
+    pub database: String,
+    #[prost(string, tag = "3")]
+    pub name: String,
+    #[prost(message, tag = "4")]
+    pub operation: Option<crate::gen::metastore::service::AlterSchemaOperation>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CommentOnExec {
+    #[prost(uint64, tag = "1")]
+    pub catalog_version: u64,
+    #[prost(string, tag = "2")]
+    pub database: String,
+    #[prost(string, tag = "3")]
+    pub schema: String,
+    #[prost(string, optional, tag = "4")]
+    pub name: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub column: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub comment: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct AlterRoleExec {
+    #[prost(uint64, tag = "1")]
+    pub catalog_version: u64,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, optional, tag = "3")]
+    pub database: Option<String>,
+    #[prost(message, tag = "4")]
+    pub operation: Option<crate::gen::metastore::service::AlterRoleOperation>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct AnalyzeTableExec {
+    #[prost(uint64, tag = "1")]
+    pub catalog_version: u64,
+    #[prost(message, tag = "2")]
+    pub tbl_reference: Option<FullObjectReference>,
+    #[prost(string, repeated, tag = "3")]
+    pub columns: Vec<String>,
+}
+
 #[derive(Clone, PartialEq, Message)]
 pub struct AlterTableExec {
     #[prost(uint64, tag = "1")]
@@ -94,6 +145,8 @@ pub struct AlterTableExec {
     pub name: String,
     #[prost(message, tag = "4")]
     pub operation: Option<crate::gen::metastore::service::AlterTableOperation>,
+    #[prost(string, tag = "5")]
+    pub database: String,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -124,6 +177,32 @@ pub struct DropDatabaseExec {
     pub if_exists: bool,
 }
 
+#[derive(Clone, PartialEq, Message)]
+pub struct ExportCatalogExec {
+    #[prost(string, tag = "1")]
+    pub path: String,
+    #[prost(message, repeated, tag = "2")]
+    pub mutations: Vec<Mutation>,
+    #[prost(string, optional, tag = "3")]
+    pub encryption_key: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ImportCatalogExec {
+    #[prost(uint64, tag = "1")]
+    pub catalog_version: u64,
+    #[prost(message, repeated, tag = "2")]
+    pub mutations: Vec<Mutation>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct RestoreCatalogExec {
+    #[prost(uint64, tag = "1")]
+    pub catalog_version: u64,
+    #[prost(message, optional, tag = "2")]
+    pub timestamp: Option<prost_types::Timestamp>,
+}
+
 #[derive(Clone, PartialEq, Message)]
 pub struct DropSchemasExec {
     #[prost(uint64, tag = "1")]
@@ -154,6 +233,18 @@ pub struct DropViewsExec {
     pub view_references: Vec<FullObjectReference>,
     #[prost(bool, tag = "3")]
     pub if_exists: bool,
+    #[prost(bool, tag = "4")]
+    pub cascade: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CreateDatabaseExec {
+    #[prost(uint64, tag = "1")]
+    pub catalog_version: u64,
+    #[prost(string, tag = "2")]
+    pub database_name: String,
+    #[prost(bool, tag = "3")]
+    pub if_not_exists: bool,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -206,6 +297,8 @@ pub struct CreateExternalTableExec {
     pub tunnel: Option<String>,
     #[prost(bool, tag = "6")]
     pub or_replace: bool,
+    #[prost(message, optional, tag = "7")]
+    pub schema: Option<crate::gen::metastore::catalog::CachedTableSchema>,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -232,6 +325,8 @@ pub struct CreateViewExec {
     pub columns: Vec<String>,
     #[prost(bool, tag = "5")]
     pub or_replace: bool,
+    #[prost(uint32, repeated, tag = "6")]
+    pub dependencies: Vec<u32>,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -252,6 +347,8 @@ pub struct DropTablesExec {
     pub tbl_references: Vec<FullObjectReference>,
     #[prost(bool, tag = "3")]
     pub if_exists: bool,
+    #[prost(bool, tag = "4")]
+    pub cascade: bool,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -262,6 +359,12 @@ pub struct SetVarExec {
     pub values: String,
 }
 
+#[derive(Clone, PartialEq, Message)]
+pub struct UseDatabaseExec {
+    #[prost(string, tag = "1")]
+    pub database: String,
+}
+
 #[derive(Clone, PartialEq, Message)]
 pub struct ShowVarExec {
     #[prost(string, tag = "1")]
@@ -286,6 +389,22 @@ pub struct UpdateExec {
     pub where_expr: Option<LogicalExprNode>,
 }
 
+#[derive(Clone, PartialEq, Message)]
+pub struct UpsertExec {
+    #[prost(message, tag = "1")]
+    pub table: Option<TableEntry>,
+    #[prost(string, repeated, tag = "2")]
+    pub conflict_columns: Vec<String>,
+    /// Whether conflicting rows are updated (`DO UPDATE`) rather than left
+    /// as is (`DO NOTHING`).
+    #[prost(bool, tag = "3")]
+    pub do_update: bool,
+    #[prost(message, repeated, tag = "4")]
+    pub updates: Vec<UpdateSelector>,
+    #[prost(message, optional, tag = "5")]
+    pub where_expr: Option<LogicalExprNode>,
+}
+
 #[derive(Clone, PartialEq, Message)]
 pub struct DeleteExec {
     #[prost(message, tag = "1")]
@@ -326,6 +445,24 @@ pub struct RuntimeGroupExec {}
 pub struct DataSourceMetricsExecAdapter {
     #[prost(bool, tag = "1")]
     pub track_writes: bool,
+    #[prost(message, optional, tag = "2")]
+    pub pruning: Option<ScanPruning>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ScanPruning {
+    #[prost(uint64, tag = "1")]
+    pub files: u64,
+    #[prost(uint64, tag = "2")]
+    pub files_pruned_by_statistics: u64,
+    #[prost(uint64, optional, tag = "3")]
+    pub row_groups: Option<u64>,
+    #[prost(uint64, tag = "4")]
+    pub row_groups_pruned_by_statistics: u64,
+    #[prost(uint64, tag = "5")]
+    pub files_pruned_by_bloom_filter: u64,
+    #[prost(uint64, tag = "6")]
+    pub row_groups_pruned_by_bloom_filter: u64,
 }
 
 #[derive(Clone, PartialEq, Message)]
@@ -338,11 +475,29 @@ pub struct AnalyzeExec {
     pub schema: Option<Schema>,
 }
 
+#[derive(Clone, PartialEq, Message)]
+pub struct SkipErrorsExec {
+    #[prost(string, tag = "1")]
+    pub source: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SampleExec {
+    #[prost(string, tag = "1")]
+    pub method: String,
+    #[prost(double, optional, tag = "2")]
+    pub percent: Option<f64>,
+    #[prost(uint64, optional, tag = "3")]
+    pub rows: Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    pub seed: Option<u64>,
+}
+
 #[derive(Clone, PartialEq, Message)]
 pub struct ExecutionPlanExtension {
     #[prost(
         oneof = "ExecutionPlanExtensionType",
-        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31"
+        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44"
     )]
     pub inner: Option<ExecutionPlanExtensionType>,
 }
@@ -418,4 +573,28 @@ pub enum ExecutionPlanExtensionType {
     DescribeTable(DescribeTableExec),
     #[prost(message, tag = "32")]
     CreateCredentialExec(CreateCredentialExec),
+    #[prost(message, tag = "33")]
+    ExportCatalogExec(ExportCatalogExec),
+    #[prost(message, tag = "34")]
+    ImportCatalogExec(ImportCatalogExec),
+    #[prost(message, tag = "35")]
+    RestoreCatalogExec(RestoreCatalogExec),
+    #[prost(message, tag = "36")]
+    CreateDatabaseExec(CreateDatabaseExec),
+    #[prost(message, tag = "37")]
+    AlterRoleExec(AlterRoleExec),
+    #[prost(message, tag = "38")]
+    SampleExec(SampleExec),
+    #[prost(message, tag = "39")]
+    AnalyzeTableExec(AnalyzeTableExec),
+    #[prost(message, tag = "40")]
+    UpsertExec(UpsertExec),
+    #[prost(message, tag = "41")]
+    AlterSchemaExec(AlterSchemaExec),
+    #[prost(message, tag = "42")]
+    CommentOnExec(CommentOnExec),
+    #[prost(message, tag = "43")]
+    SkipErrorsExec(SkipErrorsExec),
+    #[prost(message, tag = "44")]
+    UseDatabaseExec(UseDatabaseExec),
 }
@@ -1,8 +1,7 @@
-use chrono::{Datelike, Timelike};
+use chrono::{DateTime, Datelike, Offset, TimeZone, Timelike};
 use decimal::{Decimal, DecimalType};
-use dtoa::{Buffer as DtoaBuffer, Float as DtoaFloat};
 use num_traits::{Float as NumFloat, PrimInt as NumInt};
-use std::fmt::{Display, Write};
+use std::fmt::{Display, LowerExp, Write};
 
 use crate::error::{ReprError, Result};
 
@@ -30,11 +29,56 @@ where
     put_fmt!(buf, "{v}")
 }
 
+/// Options for encoding floating point values as strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatFormat {
+    /// Number of digits to add to the standard precision of the float, same
+    /// as postgres' "extra_float_digits". Values greater than 0 output the
+    /// shortest representation that round trips.
+    pub extra_float_digits: i32,
+    /// Decimal exponent at or above which values are encoded in scientific
+    /// notation. If 0, the precision of the float is used, matching postgres.
+    pub exponent_threshold: i32,
+}
+
+impl Default for FloatFormat {
+    fn default() -> Self {
+        Self {
+            extra_float_digits: 1,
+            exponent_threshold: 0,
+        }
+    }
+}
+
+/// Floating point values that can be encoded as strings.
+pub trait EncodeFloat: NumFloat + LowerExp {
+    /// Number of decimal digits that can be represented without loss of
+    /// precision (`FLT_DIG` and `DBL_DIG` in C).
+    const DIGITS: i32;
+}
+
+impl EncodeFloat for f32 {
+    const DIGITS: i32 = f32::DIGITS as i32;
+}
+
+impl EncodeFloat for f64 {
+    const DIGITS: i32 = f64::DIGITS as i32;
+}
+
 /// Encode floating point value as a string.
 pub fn encode_float<B, F>(buf: &mut B, v: F) -> Result<()>
 where
     B: Write,
-    F: NumFloat + DtoaFloat,
+    F: EncodeFloat,
+{
+    encode_float_with_format(buf, v, &FloatFormat::default())
+}
+
+/// Encode floating point value as a string using the given format.
+pub fn encode_float_with_format<B, F>(buf: &mut B, v: F, format: &FloatFormat) -> Result<()>
+where
+    B: Write,
+    F: EncodeFloat,
 {
     if v.is_nan() {
         return put_fmt!(buf, "NaN");
@@ -52,7 +96,6 @@ where
         return put_fmt!(buf, "-0");
     }
 
-    // We don't use the standard library to get the formatting for floats.
     // Postgres uses the C style "%g" formatting, which states that:
     //
     //     The double argument is converted in style f or e (or F or E
@@ -64,33 +107,61 @@ where
     //     removed from the fractional part of the result; a decimal
     //     point appears only if it is followed by at least one digit.
     //
-    // Dtoa is the closest we have to this in Rust. Maybe in future we can look
-    // at using the libc bindings to get it working exactly!
-
-    // Creating this buffer is very cheap, nothing to worry.
-    let mut dtoa_buf = DtoaBuffer::new();
-
-    let v = dtoa_buf
-        .format_finite(v)
-        // Remove any trailing ".0" so as to get whole numbers in case of when
-        // fractional part is 0.
-        .trim_end_matches(".0");
-
-    let mut iter = v.chars().peekable();
-    while let Some(c) = iter.next() {
-        buf.write_char(c)?;
-
-        if c == 'e' {
-            // Postgres represents exponential values as "1e+9" and "1e-9".
-            // Adding a '+' sign in the case of positive exponent since the
-            // library doesn't (it returns "1e9").
-            if iter.peek() != Some(&'-') {
-                buf.write_char('+')?;
-            }
+    // When extra float digits is greater than 0, the shortest digits that
+    // round trip are used instead, but the same rules apply for choosing
+    // between the two styles.
+    let precision = (F::DIGITS + format.extra_float_digits.min(0)).max(1);
+    let sci = if format.extra_float_digits > 0 {
+        format!("{v:e}")
+    } else {
+        format!("{:.*e}", (precision - 1) as usize, v)
+    };
+
+    let (mantissa, exp) = sci
+        .split_once('e')
+        .expect("scientific notation should contain an exponent");
+    let exp: i32 = exp.parse().expect("exponent should be an integer");
+    let mantissa = match mantissa.strip_prefix('-') {
+        Some(mantissa) => {
+            buf.write_char('-')?;
+            mantissa
         }
-    }
+        None => mantissa,
+    };
 
-    Ok(())
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let digits = match digits.trim_end_matches('0') {
+        "" => "0",
+        digits => digits,
+    };
+
+    let threshold = match format.exponent_threshold {
+        0 => precision,
+        threshold => threshold,
+    };
+
+    if exp < -4 || exp >= threshold {
+        let (first, rest) = digits.split_at(1);
+        buf.write_str(first)?;
+        if !rest.is_empty() {
+            buf.write_char('.')?;
+            buf.write_str(rest)?;
+        }
+        // Exponents always have at least two digits (e.g. "1e-09").
+        let sign = if exp < 0 { '-' } else { '+' };
+        put_fmt!(buf, "e{sign}{:02}", exp.abs())
+    } else if exp < 0 {
+        let zeros = (-exp - 1) as usize;
+        put_fmt!(buf, "0.{:0>zeros$}{digits}", "")
+    } else {
+        let int_len = exp as usize + 1;
+        if digits.len() > int_len {
+            let (int, frac) = digits.split_at(int_len);
+            put_fmt!(buf, "{int}.{frac}")
+        } else {
+            put_fmt!(buf, "{digits:0<int_len$}")
+        }
+    }
 }
 
 /// Encode a string value.
@@ -225,6 +296,87 @@ where
     Ok(())
 }
 
+/// Encode a timestamp in its time zone as a string, followed by the offset from
+/// UTC (e.g. "1999-09-30 07:02:04-04").
+pub fn encode_timestamptz<B, Tz>(buf: &mut B, v: &DateTime<Tz>) -> Result<()>
+where
+    B: Write,
+    Tz: TimeZone,
+{
+    let local = v.naive_local();
+    let is_ad = put_only_date(buf, &local)?;
+    buf.write_char(' ')?;
+    encode_time(buf, &local, false)?;
+    put_utc_offset(buf, v.offset().fix().local_minus_utc())?;
+    put_year_ce(buf, is_ad)?;
+    Ok(())
+}
+
+/// Write an offset from UTC in the same format as postgres. Minutes and seconds
+/// are only included if non-zero (e.g. "+05", "+05:30", "-00:25:21").
+fn put_utc_offset<B: Write>(buf: &mut B, secs: i32) -> Result<()> {
+    let sign = if secs < 0 { '-' } else { '+' };
+    let secs = secs.abs();
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    put_fmt!(buf, "{sign}{hours:02}")?;
+    if minutes != 0 || seconds != 0 {
+        put_fmt!(buf, ":{minutes:02}")?;
+    }
+    if seconds != 0 {
+        put_fmt!(buf, ":{seconds:02}")?;
+    }
+    Ok(())
+}
+
+/// Encode an interval using the "postgres" interval style (e.g. "1 year 2 mons
+/// 3 days 04:05:06.789").
+///
+/// Postgres only stores intervals with microsecond precision, so any remaining
+/// nanoseconds are truncated.
+pub fn encode_interval<B: Write>(buf: &mut B, months: i32, days: i32, nanos: i64) -> Result<()> {
+    // Nothing has been written yet.
+    let mut is_zero = true;
+    // The previously written field was negative. Postgres prefixes positive
+    // fields following a negative field with a '+'.
+    let mut is_before = false;
+
+    for (value, unit) in [(months / 12, "year"), (months % 12, "mon"), (days, "day")] {
+        if value == 0 {
+            continue;
+        }
+        let sep = if is_zero { "" } else { " " };
+        let sign = if is_before && value > 0 { "+" } else { "" };
+        let plural = if value != 1 { "s" } else { "" };
+        put_fmt!(buf, "{sep}{sign}{value} {unit}{plural}")?;
+        is_before = value < 0;
+        is_zero = false;
+    }
+
+    let micros = nanos / 1_000;
+    if is_zero || micros != 0 {
+        let sep = if is_zero { "" } else { " " };
+        let sign = if micros < 0 {
+            "-"
+        } else if is_before {
+            "+"
+        } else {
+            ""
+        };
+        let micros = micros.unsigned_abs();
+        let secs = micros / 1_000_000;
+        let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+        put_fmt!(buf, "{sep}{sign}{hours:02}:{minutes:02}:{seconds:02}")?;
+
+        let frac = micros % 1_000_000;
+        if frac != 0 {
+            let frac = format!("{frac:06}");
+            put_fmt!(buf, ".{}", frac.trim_end_matches('0'))?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Encode a precision decimal with scale.
 pub fn encode_decimal<B, D>(buf: &mut B, v: &Decimal<D>) -> Result<()>
 where
@@ -236,7 +388,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+    use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
     use decimal::Decimal128;
 
     use super::*;
@@ -280,7 +432,7 @@ mod tests {
 
         assert_encode!("123.456", encode_float, 123.456_f32);
         assert_encode!("-123.456", encode_float, -123.456_f32);
-        assert_encode!("1e-9", encode_float, 0.000000001_f32);
+        assert_encode!("1e-09", encode_float, 0.000000001_f32);
         assert_encode!("1.234e+21", encode_float, 1234000000000000000000.0_f32);
         assert_encode!("NaN", encode_float, f32::NAN);
         assert_encode!("Infinity", encode_float, f32::INFINITY);
@@ -288,7 +440,7 @@ mod tests {
         assert_encode!("-0", encode_float, -0.0_f32);
         assert_encode!("0", encode_float, 0.0_f32);
         assert_encode!("123.0456789", encode_float, 123.0456789_f64);
-        assert_encode!("1e-9", encode_float, 0.000000001_f64);
+        assert_encode!("1e-09", encode_float, 0.000000001_f64);
         assert_encode!("1.234e+21", encode_float, 1234000000000000000000.0_f64);
         assert_encode!("-123.0456789", encode_float, -123.0456789_f64);
         assert_encode!("NaN", encode_float, f64::NAN);
@@ -296,6 +448,33 @@ mod tests {
         assert_encode!("-Infinity", encode_float, f64::NEG_INFINITY);
         assert_encode!("-0", encode_float, -0.0_f64);
         assert_encode!("0", encode_float, 0.0_f64);
+        assert_encode!("0.0001", encode_float, 0.0001_f64);
+        assert_encode!("100000000000000", encode_float, 1e14_f64);
+        assert_encode!("1e+15", encode_float, 1e15_f64);
+        assert_encode!("1.234567e+06", encode_float, 1234567.0_f32);
+        assert_encode!("0.30000000000000004", encode_float, 0.1_f64 + 0.2_f64);
+
+        let format = FloatFormat {
+            extra_float_digits: 0,
+            exponent_threshold: 0,
+        };
+        assert_encode!("0.3", encode_float_with_format, 0.1_f64 + 0.2_f64, &format);
+        assert_encode!("3.14159", encode_float_with_format, 3.14159265_f32, &format);
+        let format = FloatFormat {
+            extra_float_digits: -12,
+            exponent_threshold: 0,
+        };
+        assert_encode!("1.23e+03", encode_float_with_format, 1234.5_f64, &format);
+        let format = FloatFormat {
+            extra_float_digits: 1,
+            exponent_threshold: 21,
+        };
+        assert_encode!(
+            "1000000000000000",
+            encode_float_with_format,
+            1e15_f64,
+            &format
+        );
 
         assert_encode!("abcdefghij", encode_string, "abcdefghij");
 
@@ -389,6 +568,24 @@ mod tests {
             true,
         );
 
+        let dt = Utc.timestamp_opt(938689324, 123567).unwrap();
+        assert_encode!("1999-09-30 11:02:04.000124+00", encode_timestamptz, &dt);
+
+        let offset = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+        let dt = offset.timestamp_opt(938689324, 0).unwrap();
+        assert_encode!("1999-09-30 16:32:04+05:30", encode_timestamptz, &dt);
+
+        let offset = FixedOffset::west_opt(4 * 3600).unwrap();
+        let dt = offset.timestamp_opt(938689324, 0).unwrap();
+        assert_encode!("1999-09-30 07:02:04-04", encode_timestamptz, &dt);
+
+        let dt = Utc.timestamp_opt(-62143593684, 0).unwrap();
+        assert_encode!(
+            format!("1-{}+00 BC", dt.format("%m-%d %H:%M:%S")),
+            encode_timestamptz,
+            &dt
+        );
+
         let nt = NaiveTime::from_hms_nano_opt(16, 32, 4, 0).unwrap();
         assert_encode!("16:32:04", encode_time, &nt, false);
 
@@ -416,6 +613,28 @@ mod tests {
         let nd = NaiveDate::from_ymd_opt(0, 9, 30).unwrap();
         assert_encode!("1-09-30 BC", encode_date, &nd);
 
+        assert_encode!("00:00:00", encode_interval, 0, 0, 0);
+        assert_encode!("1 day", encode_interval, 0, 1, 0);
+        assert_encode!("2 days 01:00:00", encode_interval, 0, 2, 3_600_000_000_000);
+        assert_encode!(
+            "1 year 2 mons 3 days 04:05:06.789",
+            encode_interval,
+            14,
+            3,
+            14_706_789_000_000,
+        );
+        assert_encode!("-1 years -2 mons", encode_interval, -14, 0, 0);
+        assert_encode!(
+            "-1 days +01:00:00",
+            encode_interval,
+            0,
+            -1,
+            3_600_000_000_000
+        );
+        assert_encode!("1 day -01:00:00", encode_interval, 0, 1, -3_600_000_000_000);
+        assert_encode!("-00:00:00.000001", encode_interval, 0, 0, -1_999);
+        assert_encode!("36:00:00", encode_interval, 0, 0, 129_600_000_000_000);
+
         assert_encode!(
             "123.456",
             encode_decimal,
@@ -14,6 +14,15 @@ pub enum RpcsrvError {
     #[error("Missing physical plan for id: {0}")]
     MissingPhysicalPlan(uuid::Uuid),
 
+    #[error("Missing execution for id: {0}")]
+    MissingExecution(uuid::Uuid),
+
+    #[error("Cannot resume execution {execution_id} from batch {sequence}")]
+    ExecutionNotResumable {
+        execution_id: uuid::Uuid,
+        sequence: u64,
+    },
+
     #[error("Executing physical plans is not currently supported")]
     PhysicalPlansNotSupported,
 
@@ -53,6 +62,15 @@ pub enum RpcsrvError {
     #[error(transparent)]
     TonicStatus(#[from] tonic::Status),
 
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+
+    #[error(transparent)]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+
     #[error("{0}")]
     Internal(String),
 
@@ -1,5 +1,6 @@
 use crate::{
     errors::{Result, RpcsrvError},
+    jwt::JwtIdentity,
     util::ConnKey,
 };
 
@@ -8,11 +9,12 @@ use datafusion::{arrow::ipc::writer::IpcWriteOptions, logical_expr::LogicalPlan}
 use datafusion_ext::vars::SessionVars;
 use once_cell::sync::Lazy;
 use sqlexec::{
+    connections::ConnectionPermit,
     engine::{Engine, SessionStorageConfig},
     session::Session,
     OperationInfo,
 };
-use std::{pin::Pin, sync::Arc};
+use std::{pin::Pin, sync::Arc, time::Instant};
 use tokio::sync::{Mutex, MutexGuard};
 use uuid::Uuid;
 
@@ -51,12 +53,21 @@ pub struct FlightSessionHandler {
     engine: Arc<Engine>,
     // since plans can be tied to any session, we can't use a single session to store them.
     logical_plans: DashMap<String, LogicalPlan>,
-    // TODO: currently, we aren't removing these sessions, so this will grow forever.
-    // there's no close/shutdown hook, so the sessions can at most only be tied to a single transaction, not a connection.
-    // We'll want to implement a time based eviction policy, or a max size.
+    // TODO: there's no close/shutdown hook, so the sessions can at most only be tied to a single transaction, not a connection.
+    // Sessions are only removed once they've been idle for longer than the idle session timeout,
+    // so without a timeout this will grow forever.
     // We use [`Session`] instead of [`TrackedSession`] because tracked sessions need to be
     // explicitly closed, and we don't have a way to do that yet.
-    sessions: DashMap<ConnKey, Arc<Mutex<Session>>>,
+    sessions: DashMap<ConnKey, FlightSession>,
+}
+
+/// A session for a flight connection.
+struct FlightSession {
+    session: Arc<Mutex<Session>>,
+    /// When the session was last used by a request.
+    last_used: Instant,
+    /// Counts the session towards the connection limits until it's removed.
+    _permit: ConnectionPermit,
 }
 
 impl FlightSessionHandler {
@@ -119,9 +130,26 @@ impl FlightSessionHandler {
         let port = remote.port().to_string();
         let conn_key = ConnKey { ip, port };
 
-        if self.sessions.contains_key(&conn_key) {
-            let sess = self.sessions.get(&conn_key).unwrap().clone();
-            return Ok(sess);
+        let idle_timeout = self.engine.connection_limiter().idle_session_timeout();
+        if let Some(mut sess) = self.sessions.get_mut(&conn_key) {
+            match idle_timeout {
+                Some(timeout) if sess.last_used.elapsed() >= timeout => (),
+                _ => {
+                    sess.last_used = Instant::now();
+                    return Ok(sess.session.clone());
+                }
+            }
+        }
+        if let Some(timeout) = idle_timeout {
+            // Let the client know its session is gone, the next request
+            // creates a new one.
+            if self.sessions.remove(&conn_key).is_some() {
+                return Err(Status::unavailable(
+                    "terminating session due to idle-session timeout",
+                ));
+            }
+            self.sessions
+                .retain(|_, sess| sess.last_used.elapsed() < timeout);
         }
 
         let db_id = request
@@ -139,12 +167,23 @@ impl FlightSessionHandler {
                 "database id must be specified when using a gcs bucket".to_string(),
             ));
         }
-        let session_vars = SessionVars::default()
+        let mut session_vars = SessionVars::default()
             .with_database_id(
                 db_id.unwrap_or_else(Uuid::nil),
                 datafusion::variable::VarType::System,
             )
             .with_force_catalog_refresh(true, datafusion::variable::VarType::System);
+        if let Some(identity) = request.extensions().get::<JwtIdentity>() {
+            session_vars = session_vars
+                .with_user_name(&identity.user, datafusion::variable::VarType::System)
+                .with_is_admin(identity.is_admin, datafusion::variable::VarType::System);
+        }
+
+        let permit = self
+            .engine
+            .connection_limiter()
+            .try_acquire(&session_vars.user_name())
+            .map_err(|e| Status::resource_exhausted(e.to_string()))?;
 
         let sess = self
             .engine
@@ -153,7 +192,14 @@ impl FlightSessionHandler {
             .map_err(RpcsrvError::from)?;
 
         let sess = Arc::new(Mutex::new(sess));
-        self.sessions.insert(conn_key.clone(), sess.clone());
+        self.sessions.insert(
+            conn_key.clone(),
+            FlightSession {
+                session: sess.clone(),
+                last_used: Instant::now(),
+                _permit: permit,
+            },
+        );
 
         Ok(sess)
     }
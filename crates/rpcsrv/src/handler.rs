@@ -1,35 +1,34 @@
 use crate::{
     errors::{Result, RpcsrvError},
+    jwt::JwtIdentity,
+    resumable::{ResumableExecutions, ResumableResponseStream},
     session::RemoteSession,
 };
 use async_trait::async_trait;
 use dashmap::DashMap;
-use datafusion::arrow::ipc::writer::FileWriter as IpcFileWriter;
-use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::variable::VarType;
 use datafusion_ext::session_metrics::{
     BatchStreamWithMetricSender, QueryMetrics, SessionMetricsHandler,
 };
-use futures::{Stream, StreamExt};
+use datafusion_ext::vars::SessionVars;
+use futures::Stream;
 use protogen::{
     gen::rpcsrv::common,
     gen::rpcsrv::service,
     rpcsrv::types::service::{
-        DispatchAccessRequest, FetchCatalogRequest, FetchCatalogResponse, InitializeSessionRequest,
-        InitializeSessionResponse, PhysicalPlanExecuteRequest, TableProviderResponse,
+        CancelExecutionRequest, DispatchAccessRequest, ExecutePartitionRequest,
+        FetchCatalogRequest, FetchCatalogResponse, InitializeSessionRequest,
+        InitializeSessionResponse, PhysicalPlanExecuteRequest, ResumeExecutionRequest,
+        TableProviderResponse,
     },
 };
 use sqlexec::{
     engine::{Engine, SessionStorageConfig},
-    remote::batch_stream::ExecutionBatchStream,
-};
-use std::{
-    collections::HashMap,
-    pin::Pin,
-    sync::Arc,
-    task::{Context, Poll},
+    remote::{batch_stream::ExecutionBatchStream, compression, distribute::Workers},
 };
+use std::{collections::HashMap, pin::Pin, sync::Arc};
 use tonic::{Request, Response, Status, Streaming};
-use tracing::info;
+use tracing::{debug, info, Span};
 use uuid::Uuid;
 
 pub struct RpcHandler {
@@ -38,8 +37,12 @@ pub struct RpcHandler {
 
     /// Open sessions.
     ///
-    /// Keyed by database id.
-    sessions: DashMap<Uuid, RemoteSession>,
+    /// Keyed by database id and the identity of the client, so clients
+    /// authenticated as different users don't share sessions.
+    sessions: DashMap<SessionKey, RemoteSession>,
+
+    /// Executions with result streams that can be resumed.
+    executions: ResumableExecutions,
 
     /// Allow initialize session messages from client.
     ///
@@ -48,36 +51,59 @@ pub struct RpcHandler {
 
     /// Whether we're running in itegration testing mode.
     integration_testing: bool,
+
+    /// Workers to distribute execution across.
+    ///
+    /// Empty unless workers are provided on startup or registered later.
+    workers: Arc<Workers>,
 }
 
+type SessionKey = (Uuid, Option<JwtIdentity>);
+
 impl RpcHandler {
     pub fn new(engine: Arc<Engine>, allow_client_init: bool, integration_testing: bool) -> Self {
         RpcHandler {
             engine,
             sessions: DashMap::new(),
+            executions: ResumableExecutions::default(),
             allow_client_init,
             integration_testing,
+            workers: Arc::new(Workers::default()),
         }
     }
 
+    /// Distribute execution of physical plans across workers in this pool.
+    pub fn with_workers(mut self, workers: Arc<Workers>) -> Self {
+        self.workers = workers;
+        self
+    }
+
     /// Get an existing session for a database, or creates a new one using the
     /// provided configuration.
     async fn get_or_initialize_session(
         &self,
         db_id: Uuid,
+        identity: Option<JwtIdentity>,
         storage_conf: SessionStorageConfig,
     ) -> Result<RemoteSession> {
-        let sess = match self.sessions.get(&db_id) {
+        let key = (db_id, identity);
+        let sess = match self.sessions.get(&key) {
             Some(sess) => sess.clone(),
             None => {
                 info!(session_id=%db_id, "initializing remote session");
+                let mut vars = SessionVars::default();
+                if let Some(identity) = &key.1 {
+                    vars = vars
+                        .with_user_name(&identity.user, VarType::System)
+                        .with_is_admin(identity.is_admin, VarType::System);
+                }
                 let context = self
                     .engine
-                    .new_remote_session_context(db_id, storage_conf)
+                    .new_remote_session_context(db_id, storage_conf, vars)
                     .await?;
 
                 let sess = RemoteSession::new(context);
-                self.sessions.insert(db_id, sess.clone());
+                self.sessions.insert(key, sess.clone());
                 sess
             }
         };
@@ -88,6 +114,7 @@ impl RpcHandler {
     async fn initialize_session_inner(
         &self,
         req: InitializeSessionRequest,
+        identity: Option<JwtIdentity>,
     ) -> Result<InitializeSessionResponse> {
         // Get db id and storage config from the request.
         //
@@ -115,18 +142,25 @@ impl RpcHandler {
             }
         };
 
-        let sess = self.get_or_initialize_session(db_id, storage_conf).await?;
+        let sess = self
+            .get_or_initialize_session(db_id, identity, storage_conf)
+            .await?;
         let initial_state = sess.get_refreshed_catalog_state().await?;
 
         Ok(InitializeSessionResponse {
             database_id: db_id,
             catalog: initial_state,
             user_id,
+            supported_compression: compression::SUPPORTED_CODECS.to_vec(),
         })
     }
 
-    async fn fetch_catalog_inner(&self, req: FetchCatalogRequest) -> Result<FetchCatalogResponse> {
-        let session = self.get_session(req.database_id)?;
+    async fn fetch_catalog_inner(
+        &self,
+        req: FetchCatalogRequest,
+        identity: Option<JwtIdentity>,
+    ) -> Result<FetchCatalogResponse> {
+        let session = self.get_session(req.database_id, identity)?;
         let catalog = session.get_refreshed_catalog_state().await?;
 
         info!(database_id=%req.database_id, version = %catalog.version, "fetching catalog");
@@ -137,6 +171,7 @@ impl RpcHandler {
     async fn dispatch_access_inner(
         &self,
         req: DispatchAccessRequest,
+        identity: Option<JwtIdentity>,
     ) -> Result<TableProviderResponse> {
         info!(database_id=%req.database_id, table_ref=%req.table_ref, "dispatching table access");
         let args = req
@@ -157,19 +192,24 @@ impl RpcHandler {
             })
             .transpose()?;
 
-        let session = self.get_session(req.database_id)?;
-        let (id, schema) = session.dispatch_access(req.table_ref, args, opts).await?;
+        let session = self.get_session(req.database_id, identity)?;
+        let (id, schema) = session
+            .dispatch_access(req.table_ref, args, opts, req.provider_id)
+            .await?;
         Ok(TableProviderResponse { id, schema })
     }
 
     async fn physical_plan_execute_inner(
         &self,
         req: PhysicalPlanExecuteRequest,
-    ) -> Result<ExecutionResponseBatchStream> {
-        info!(database_id=%req.database_id, "executing physical plan");
+        identity: Option<JwtIdentity>,
+    ) -> Result<ResumableResponseStream> {
+        info!(database_id=%req.database_id, execution_id=%req.execution_id, "executing physical plan");
 
-        let session = self.get_session(req.database_id)?;
-        let (plan, batches) = session.physical_plan_execute(req.physical_plan).await?;
+        let session = self.get_session(req.database_id, identity)?;
+        let (plan, batches) = session
+            .physical_plan_execute(req.database_id, req.physical_plan, &self.workers)
+            .await?;
 
         let session_metrics_handler = SessionMetricsHandler::new(
             req.user_id.unwrap_or_default(),
@@ -186,22 +226,72 @@ impl RpcHandler {
         let batches =
             BatchStreamWithMetricSender::new(batches, plan, query_metrics, session_metrics_handler);
 
-        Ok(ExecutionResponseBatchStream {
-            batches,
-            buf: Vec::new(),
-        })
+        Ok(self.executions.register(
+            req.database_id,
+            req.execution_id,
+            Box::pin(batches),
+            req.result_compression,
+            req.compression_level,
+            Span::current(),
+        ))
+    }
+
+    async fn resume_execution_inner(
+        &self,
+        req: ResumeExecutionRequest,
+        identity: Option<JwtIdentity>,
+    ) -> Result<ResumableResponseStream> {
+        info!(database_id=%req.database_id, execution_id=%req.execution_id, sequence=%req.sequence, "resuming execution");
+
+        // Only resume executions for sessions that still exist.
+        let _ = self.get_session(req.database_id, identity)?;
+
+        self.executions
+            .resume(req.database_id, req.execution_id, req.sequence)
+    }
+
+    fn cancel_execution_inner(&self, req: CancelExecutionRequest) {
+        info!(database_id=%req.database_id, execution_id=%req.execution_id, "cancelling execution");
+        if !self.executions.cancel(req.database_id, req.execution_id) {
+            debug!(execution_id=%req.execution_id, "execution to cancel not found");
+        }
+    }
+
+    async fn execute_partition_inner(
+        &self,
+        req: ExecutePartitionRequest,
+        identity: Option<JwtIdentity>,
+    ) -> Result<ResumableResponseStream> {
+        info!(database_id=%req.database_id, partition=%req.partition, "executing plan partition");
+
+        let session = self.get_session(req.database_id, identity)?;
+        let (_, batches) = session
+            .execute_partition(req.physical_plan, req.partition)
+            .await?;
+
+        // Partitions are registered under a new id since the coordinator
+        // doesn't resume them.
+        Ok(self.executions.register(
+            req.database_id,
+            Uuid::new_v4(),
+            Box::pin(batches),
+            common::CompressionCodec::Uncompressed,
+            0,
+            Span::current(),
+        ))
     }
 
     async fn broadcast_exchange_inner(
         &self,
         req: Streaming<common::ExecutionResultBatch>,
+        identity: Option<JwtIdentity>,
     ) -> Result<service::BroadcastExchangeResponse> {
         let stream = ExecutionBatchStream::try_new(req).await?;
         let database_id = stream.database_id();
 
         info!(database_id=%database_id, work_id=%stream.work_id(), "beginning client exchange stream");
 
-        let session = self.get_session(database_id)?;
+        let session = self.get_session(database_id, identity)?;
 
         session.register_broadcast_stream(stream).await?;
 
@@ -210,9 +300,39 @@ impl RpcHandler {
         Ok(service::BroadcastExchangeResponse {})
     }
 
-    fn get_session(&self, db_id: Uuid) -> Result<RemoteSession> {
+    async fn stage_table_inner(
+        &self,
+        req: Streaming<common::ExecutionResultBatch>,
+        identity: Option<JwtIdentity>,
+    ) -> Result<TableProviderResponse> {
+        let stream = ExecutionBatchStream::try_new(req).await?;
+        let database_id = stream.database_id();
+
+        info!(database_id=%database_id, work_id=%stream.work_id(), "staging client table");
+
+        let session = self.get_session(database_id, identity)?;
+        let (id, schema) = session.stage_table(stream).await?;
+        Ok(TableProviderResponse { id, schema })
+    }
+
+    fn list_workers_inner(&self) -> service::ListWorkersResponse {
+        let workers = self
+            .workers
+            .workers()
+            .into_iter()
+            .map(|worker| service::WorkerStatus {
+                address: worker.address().to_string(),
+                state: worker.state().to_string(),
+                running_partitions: worker.running_partitions() as u64,
+                completed_partitions: worker.completed_partitions(),
+            })
+            .collect();
+        service::ListWorkersResponse { workers }
+    }
+
+    fn get_session(&self, db_id: Uuid, identity: Option<JwtIdentity>) -> Result<RemoteSession> {
         self.sessions
-            .get(&db_id)
+            .get(&(db_id, identity))
             .ok_or_else(|| RpcsrvError::MissingSession(db_id))
             .map(|s| s.value().clone())
     }
@@ -222,14 +342,23 @@ impl RpcHandler {
 impl service::execution_service_server::ExecutionService for RpcHandler {
     type PhysicalPlanExecuteStream =
         Pin<Box<dyn Stream<Item = Result<service::RecordBatchResponse, Status>> + Send>>;
+    type ResumeExecutionStream =
+        Pin<Box<dyn Stream<Item = Result<service::RecordBatchResponse, Status>> + Send>>;
+    type ExecutePartitionStream =
+        Pin<Box<dyn Stream<Item = Result<service::RecordBatchResponse, Status>> + Send>>;
 
     async fn initialize_session(
         &self,
         request: Request<service::InitializeSessionRequest>,
     ) -> Result<Response<service::InitializeSessionResponse>, Status> {
+        let identity = request.extensions().get::<JwtIdentity>().cloned();
+        let request = request.into_inner();
         let resp = self
-            .initialize_session_inner(request.into_inner().try_into()?)
+            .initialize_session_inner(request.clone().try_into()?, identity)
             .await?;
+        self.workers
+            .initialize_session(resp.database_id, request)
+            .await;
         Ok(Response::new(resp.try_into()?))
     }
 
@@ -237,8 +366,9 @@ impl service::execution_service_server::ExecutionService for RpcHandler {
         &self,
         request: Request<service::FetchCatalogRequest>,
     ) -> Result<Response<service::FetchCatalogResponse>, Status> {
+        let identity = request.extensions().get::<JwtIdentity>().cloned();
         let resp = self
-            .fetch_catalog_inner(request.into_inner().try_into()?)
+            .fetch_catalog_inner(request.into_inner().try_into()?, identity)
             .await?;
         Ok(Response::new(resp.try_into()?))
     }
@@ -247,9 +377,12 @@ impl service::execution_service_server::ExecutionService for RpcHandler {
         &self,
         request: Request<service::DispatchAccessRequest>,
     ) -> Result<Response<service::TableProviderResponse>, Status> {
+        let identity = request.extensions().get::<JwtIdentity>().cloned();
+        let request = request.into_inner();
         let resp = self
-            .dispatch_access_inner(request.into_inner().try_into()?)
+            .dispatch_access_inner(request.clone().try_into()?, identity)
             .await?;
+        self.workers.dispatch_access(resp.id, request).await;
         Ok(Response::new(resp.try_into()?))
     }
 
@@ -257,59 +390,144 @@ impl service::execution_service_server::ExecutionService for RpcHandler {
         &self,
         request: Request<service::PhysicalPlanExecuteRequest>,
     ) -> Result<Response<Self::PhysicalPlanExecuteStream>, Status> {
+        let identity = request.extensions().get::<JwtIdentity>().cloned();
         let resp = self
-            .physical_plan_execute_inner(request.into_inner().try_into()?)
+            .physical_plan_execute_inner(request.into_inner().try_into()?, identity)
             .await?;
         Ok(Response::new(Box::pin(resp)))
     }
 
+    async fn resume_execution(
+        &self,
+        request: Request<service::ResumeExecutionRequest>,
+    ) -> Result<Response<Self::ResumeExecutionStream>, Status> {
+        let identity = request.extensions().get::<JwtIdentity>().cloned();
+        let resp = self
+            .resume_execution_inner(request.into_inner().try_into()?, identity)
+            .await?;
+        Ok(Response::new(Box::pin(resp)))
+    }
+
+    async fn execute_partition(
+        &self,
+        request: Request<service::ExecutePartitionRequest>,
+    ) -> Result<Response<Self::ExecutePartitionStream>, Status> {
+        let identity = request.extensions().get::<JwtIdentity>().cloned();
+        let resp = self
+            .execute_partition_inner(request.into_inner().try_into()?, identity)
+            .await?;
+        Ok(Response::new(Box::pin(resp)))
+    }
+
+    async fn cancel_execution(
+        &self,
+        request: Request<service::CancelExecutionRequest>,
+    ) -> Result<Response<service::CancelExecutionResponse>, Status> {
+        self.cancel_execution_inner(request.into_inner().try_into()?);
+        Ok(Response::new(service::CancelExecutionResponse {}))
+    }
+
     async fn broadcast_exchange(
         &self,
         request: Request<Streaming<common::ExecutionResultBatch>>,
     ) -> Result<Response<service::BroadcastExchangeResponse>, Status> {
-        let resp = self.broadcast_exchange_inner(request.into_inner()).await?;
+        let identity = request.extensions().get::<JwtIdentity>().cloned();
+        let resp = self
+            .broadcast_exchange_inner(request.into_inner(), identity)
+            .await?;
         Ok(Response::new(resp))
     }
-}
 
-/// Convert a record batch stream into a stream of execution responses
-/// containing ipc serialized batches.
-// TODO: StreamWriter
-// TODO: Possibly buffer record batches.
-struct ExecutionResponseBatchStream {
-    batches: BatchStreamWithMetricSender,
-    buf: Vec<u8>,
-}
+    async fn stage_table(
+        &self,
+        request: Request<Streaming<common::ExecutionResultBatch>>,
+    ) -> Result<Response<service::TableProviderResponse>, Status> {
+        let identity = request.extensions().get::<JwtIdentity>().cloned();
+        let resp = self
+            .stage_table_inner(request.into_inner(), identity)
+            .await?;
+        Ok(Response::new(resp.try_into()?))
+    }
 
-impl ExecutionResponseBatchStream {
-    fn write_batch(&mut self, batch: &RecordBatch) -> Result<service::RecordBatchResponse> {
-        self.buf.clear();
+    async fn register_worker(
+        &self,
+        request: Request<service::WorkerRequest>,
+    ) -> Result<Response<service::WorkerResponse>, Status> {
+        check_manage_workers(&request)?;
+        self.workers
+            .register(request.into_inner().address)
+            .await
+            .map_err(RpcsrvError::from)?;
+        Ok(Response::new(service::WorkerResponse {}))
+    }
 
-        let schema = batch.schema();
-        let mut writer = IpcFileWriter::try_new(&mut self.buf, &schema)?;
-        writer.write(batch)?;
-        writer.finish()?;
+    async fn deregister_worker(
+        &self,
+        request: Request<service::WorkerRequest>,
+    ) -> Result<Response<service::WorkerResponse>, Status> {
+        check_manage_workers(&request)?;
+        self.workers
+            .deregister(&request.into_inner().address)
+            .map_err(RpcsrvError::from)?;
+        Ok(Response::new(service::WorkerResponse {}))
+    }
 
-        let _ = writer.into_inner()?;
+    async fn drain_worker(
+        &self,
+        request: Request<service::WorkerRequest>,
+    ) -> Result<Response<service::WorkerResponse>, Status> {
+        check_manage_workers(&request)?;
+        self.workers
+            .drain(&request.into_inner().address)
+            .map_err(RpcsrvError::from)?;
+        Ok(Response::new(service::WorkerResponse {}))
+    }
 
-        Ok(service::RecordBatchResponse {
-            arrow_ipc: self.buf.clone(),
-        })
+    async fn list_workers(
+        &self,
+        _request: Request<service::ListWorkersRequest>,
+    ) -> Result<Response<service::ListWorkersResponse>, Status> {
+        Ok(Response::new(self.list_workers_inner()))
     }
 }
 
-impl Stream for ExecutionResponseBatchStream {
-    type Item = Result<service::RecordBatchResponse, Status>;
-
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match self.batches.poll_next_unpin(cx) {
-            Poll::Ready(Some(Ok(batch))) => match self.write_batch(&batch) {
-                Ok(resp) => Poll::Ready(Some(Ok(resp))),
-                Err(e) => Poll::Ready(Some(Err(e.into()))),
-            },
-            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(RpcsrvError::from(e).into()))),
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
-        }
+/// Check that the client is allowed to add, drain or remove workers.
+///
+/// Workers receive plans and dispatched table access, including credentials,
+/// so managing the pool requires a bearer token with an admin role. Without
+/// bearer token authentication, workers can only be provided on startup.
+fn check_manage_workers<T>(request: &Request<T>) -> Result<(), Status> {
+    match request.extensions().get::<JwtIdentity>() {
+        Some(identity) if identity.is_admin => Ok(()),
+        _ => Err(Status::permission_denied(
+            "managing workers requires a bearer token with an admin role",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manage_workers_requires_admin() {
+        let request = |identity: Option<JwtIdentity>| {
+            let mut request = Request::new(());
+            if let Some(identity) = identity {
+                request.extensions_mut().insert(identity);
+            }
+            request
+        };
+        let identity = |is_admin| JwtIdentity {
+            user: "sam".to_string(),
+            roles: Vec::new(),
+            is_admin,
+        };
+
+        let status = check_manage_workers(&request(None)).unwrap_err();
+        assert_eq!(tonic::Code::PermissionDenied, status.code());
+        let status = check_manage_workers(&request(Some(identity(false)))).unwrap_err();
+        assert_eq!(tonic::Code::PermissionDenied, status.code());
+        check_manage_workers(&request(Some(identity(true)))).unwrap();
     }
 }
@@ -0,0 +1,455 @@
+//! Authentication of RPC and HTTP requests using JWTs.
+//!
+//! Clients send a token from the configured issuer as a bearer token in the
+//! `authorization` header. Tokens are checked against the keys published in
+//! the issuer's JWKS, which are fetched when the authenticator is created and
+//! refreshed periodically. A token signed by an unknown key triggers an early
+//! refresh so that key rotations are picked up quickly.
+//!
+//! Only asymmetric algorithms are accepted since a JWKS is public.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde_json::Value;
+use tokio::sync::Notify;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+use tracing::{debug, warn};
+
+use crate::errors::{Result, RpcsrvError};
+
+/// How often keys are fetched from the JWKS URL.
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Minimum time between fetches triggered by tokens signed by unknown keys.
+const JWKS_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Timeout for fetching keys.
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Configuration for validating tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtConfig {
+    /// URL the issuer publishes its JWKS on.
+    pub jwks_url: String,
+    /// Expected `iss` claim.
+    pub issuer: String,
+    /// Expected `aud` claim. The audience isn't checked if unset.
+    pub audience: Option<String>,
+    /// Claim containing the database user the token maps to.
+    pub user_claim: String,
+    /// Claim containing the token's roles, either an array of strings or a
+    /// space separated string.
+    pub roles_claim: Option<String>,
+    /// Roles allowed to connect. A token needs at least one of them, any
+    /// token from the issuer is accepted if empty.
+    pub required_roles: Vec<String>,
+    /// Roles granting admin privileges to the token's sessions.
+    pub admin_roles: Vec<String>,
+}
+
+impl JwtConfig {
+    pub const DEFAULT_USER_CLAIM: &'static str = "sub";
+}
+
+/// The identity of a client with a valid token.
+///
+/// Inserted into the extensions of authenticated RPC requests.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JwtIdentity {
+    /// The database user to use for the client's sessions.
+    pub user: String,
+    pub roles: Vec<String>,
+    /// If the token has one of the configured admin roles.
+    pub is_admin: bool,
+}
+
+/// Validates tokens using keys from the configured JWKS.
+#[derive(Debug)]
+pub struct JwtAuthenticator {
+    config: JwtConfig,
+    keys: RwLock<Arc<JwkSet>>,
+    /// Notified when a token is signed by an unknown key.
+    refresh: Notify,
+}
+
+impl JwtAuthenticator {
+    /// Create an authenticator, fetching the initial keys from the JWKS URL.
+    ///
+    /// This spawns a background task that refreshes the keys periodically,
+    /// and must be called from within a tokio runtime. The task stops once the
+    /// authenticator is dropped.
+    pub async fn new(config: JwtConfig) -> Result<Arc<JwtAuthenticator>> {
+        let client = reqwest::Client::builder()
+            .timeout(JWKS_FETCH_TIMEOUT)
+            .build()?;
+        let keys = fetch_jwks(&client, &config.jwks_url).await?;
+
+        let auth = Arc::new(Self::with_keys(config, keys));
+        tokio::spawn(refresh_keys(Arc::downgrade(&auth), client));
+        Ok(auth)
+    }
+
+    /// Create an authenticator using a fixed set of keys.
+    pub fn with_keys(config: JwtConfig, keys: JwkSet) -> JwtAuthenticator {
+        JwtAuthenticator {
+            config,
+            keys: RwLock::new(Arc::new(keys)),
+            refresh: Notify::new(),
+        }
+    }
+
+    /// Authenticate using the value of an `authorization` header.
+    pub fn authenticate_header(&self, header: Option<&str>) -> Result<JwtIdentity> {
+        let header = header.ok_or_else(|| invalid("missing bearer token"))?;
+        let token = match header.split_once(' ') {
+            Some((scheme, token)) if scheme.eq_ignore_ascii_case("bearer") => token.trim(),
+            _ => return Err(invalid("expected a bearer token")),
+        };
+        self.authenticate(token)
+    }
+
+    /// Validate a token, returning the identity it maps to.
+    pub fn authenticate(&self, token: &str) -> Result<JwtIdentity> {
+        let header = jsonwebtoken::decode_header(token)?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(invalid("symmetric signing algorithms are not supported"));
+        }
+        let key = {
+            let keys = self.keys.read().unwrap().clone();
+            let jwk = match &header.kid {
+                Some(kid) => keys.find(kid),
+                // Allow omitting the key id if the issuer only has one key.
+                None if keys.keys.len() == 1 => keys.keys.first(),
+                None => return Err(invalid("missing key id")),
+            };
+            match jwk {
+                Some(jwk) => DecodingKey::from_jwk(jwk)?,
+                None => {
+                    self.refresh.notify_one();
+                    return Err(invalid("token signed by an unknown key"));
+                }
+            }
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.config.issuer]);
+        match &self.config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        let claims =
+            jsonwebtoken::decode::<HashMap<String, Value>>(token, &key, &validation)?.claims;
+
+        let user = claims
+            .get(&self.config.user_claim)
+            .and_then(Value::as_str)
+            .filter(|user| !user.is_empty())
+            .ok_or_else(|| {
+                RpcsrvError::InvalidToken(format!(
+                    "missing user claim '{}'",
+                    self.config.user_claim
+                ))
+            })?
+            .to_string();
+
+        let roles = match &self.config.roles_claim {
+            Some(claim) => match claims.get(claim) {
+                Some(Value::String(roles)) => roles.split_whitespace().map(String::from).collect(),
+                Some(Value::Array(roles)) => roles
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect(),
+                _ => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+        if !self.config.required_roles.is_empty()
+            && !roles
+                .iter()
+                .any(|role| self.config.required_roles.contains(role))
+        {
+            return Err(invalid("token doesn't have a required role"));
+        }
+
+        let is_admin = roles
+            .iter()
+            .any(|role| self.config.admin_roles.contains(role));
+
+        Ok(JwtIdentity {
+            user,
+            roles,
+            is_admin,
+        })
+    }
+}
+
+/// Interceptor for RPC services requiring a valid token when an authenticator
+/// is configured.
+///
+/// The identity of an authenticated client is inserted into the request's
+/// extensions. Requests are passed through unchanged without an
+/// authenticator.
+#[derive(Debug, Clone, Default)]
+pub struct JwtInterceptor {
+    auth: Option<Arc<JwtAuthenticator>>,
+}
+
+impl JwtInterceptor {
+    pub fn new(auth: Option<Arc<JwtAuthenticator>>) -> JwtInterceptor {
+        JwtInterceptor { auth }
+    }
+}
+
+impl Interceptor for JwtInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let auth = match &self.auth {
+            Some(auth) => auth,
+            None => return Ok(request),
+        };
+        let header = request
+            .metadata()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok());
+        let identity = auth.authenticate_header(header).map_err(|e| {
+            debug!(%e, "rejecting rpc request");
+            Status::unauthenticated(e.to_string())
+        })?;
+        request.extensions_mut().insert(identity);
+        Ok(request)
+    }
+}
+
+async fn fetch_jwks(client: &reqwest::Client, url: &str) -> Result<JwkSet> {
+    Ok(client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// Refresh keys periodically, or when requested by the authenticator, until
+/// the authenticator is dropped.
+async fn refresh_keys(auth: Weak<JwtAuthenticator>, client: reqwest::Client) {
+    let mut last_refresh = Instant::now();
+    loop {
+        let (url, requested) = {
+            let auth = match auth.upgrade() {
+                Some(auth) => auth,
+                None => return,
+            };
+            let requested = tokio::select! {
+                _ = auth.refresh.notified() => true,
+                _ = tokio::time::sleep(JWKS_REFRESH_INTERVAL) => false,
+            };
+            (auth.config.jwks_url.clone(), requested)
+        };
+        if requested && last_refresh.elapsed() < JWKS_MIN_REFRESH_INTERVAL {
+            continue;
+        }
+        last_refresh = Instant::now();
+
+        match fetch_jwks(&client, &url).await {
+            Ok(keys) => match auth.upgrade() {
+                Some(auth) => {
+                    debug!(num_keys = keys.keys.len(), "refreshed jwks");
+                    *auth.keys.write().unwrap() = Arc::new(keys);
+                }
+                None => return,
+            },
+            // Keep using the previous keys.
+            Err(e) => warn!(%e, %url, "failed to refresh jwks"),
+        }
+    }
+}
+
+fn invalid(msg: &str) -> RpcsrvError {
+    RpcsrvError::InvalidToken(msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use jsonwebtoken::{EncodingKey, Header};
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use serde_json::json;
+
+    use super::*;
+
+    struct TestIssuer {
+        key: EncodingKey,
+        keys: JwkSet,
+    }
+
+    impl TestIssuer {
+        fn new() -> TestIssuer {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+            let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+            let keys = serde_json::from_value(json!({
+                "keys": [{
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "kid": "test",
+                    "alg": "EdDSA",
+                    "x": URL_SAFE_NO_PAD.encode(pair.public_key().as_ref()),
+                }]
+            }))
+            .unwrap();
+            TestIssuer {
+                key: EncodingKey::from_ed_der(pkcs8.as_ref()),
+                keys,
+            }
+        }
+
+        fn token(&self, kid: &str, claims: Value) -> String {
+            let mut header = Header::new(Algorithm::EdDSA);
+            header.kid = Some(kid.to_string());
+            jsonwebtoken::encode(&header, &claims, &self.key).unwrap()
+        }
+    }
+
+    fn config() -> JwtConfig {
+        JwtConfig {
+            jwks_url: "http://localhost/jwks".to_string(),
+            issuer: "https://sso.example.com".to_string(),
+            audience: Some("glaredb".to_string()),
+            user_claim: JwtConfig::DEFAULT_USER_CLAIM.to_string(),
+            roles_claim: Some("roles".to_string()),
+            required_roles: Vec::new(),
+            admin_roles: vec!["admin".to_string()],
+        }
+    }
+
+    fn claims() -> Value {
+        json!({
+            "iss": "https://sso.example.com",
+            "aud": "glaredb",
+            "sub": "sam",
+            "roles": ["analyst", "admin"],
+            "exp": jsonwebtoken::get_current_timestamp() + 60,
+        })
+    }
+
+    #[test]
+    fn valid_token() {
+        let issuer = TestIssuer::new();
+        let auth = JwtAuthenticator::with_keys(config(), issuer.keys.clone());
+
+        let token = issuer.token("test", claims());
+        let identity = auth
+            .authenticate_header(Some(&format!("Bearer {token}")))
+            .unwrap();
+        assert_eq!(
+            JwtIdentity {
+                user: "sam".to_string(),
+                roles: vec!["analyst".to_string(), "admin".to_string()],
+                is_admin: true,
+            },
+            identity
+        );
+    }
+
+    #[test]
+    fn maps_configured_claims() {
+        let issuer = TestIssuer::new();
+        let auth = JwtAuthenticator::with_keys(
+            JwtConfig {
+                user_claim: "preferred_username".to_string(),
+                roles_claim: Some("scope".to_string()),
+                required_roles: vec!["db:write".to_string()],
+                ..config()
+            },
+            issuer.keys.clone(),
+        );
+
+        let mut claims = claims();
+        claims["preferred_username"] = json!("alex");
+        claims["scope"] = json!("db:read db:write");
+        let identity = auth.authenticate(&issuer.token("test", claims)).unwrap();
+        assert_eq!("alex", identity.user);
+        assert_eq!(vec!["db:read", "db:write"], identity.roles);
+        assert!(!identity.is_admin);
+
+        // Missing the required role.
+        let token = issuer.token("test", self::claims());
+        auth.authenticate(&token).unwrap_err();
+    }
+
+    #[test]
+    fn invalid_tokens() {
+        let issuer = TestIssuer::new();
+        let auth = JwtAuthenticator::with_keys(config(), issuer.keys.clone());
+
+        let with = |key: &str, value: Value| {
+            let mut claims = claims();
+            claims[key] = value;
+            issuer.token("test", claims)
+        };
+        let invalid = [
+            with("iss", json!("https://other.example.com")),
+            with("aud", json!("other")),
+            with("exp", json!(jsonwebtoken::get_current_timestamp() - 600)),
+            with("sub", json!("")),
+            issuer.token("unknown", claims()),
+            // Signed by a different key with the same id.
+            TestIssuer::new().token("test", claims()),
+            // Symmetric algorithms aren't accepted.
+            jsonwebtoken::encode(
+                &Header::new(Algorithm::HS256),
+                &claims(),
+                &EncodingKey::from_secret(b"secret"),
+            )
+            .unwrap(),
+            "not a token".to_string(),
+        ];
+        for token in invalid {
+            let err = auth.authenticate(&token).unwrap_err();
+            assert!(
+                matches!(err, RpcsrvError::InvalidToken(_) | RpcsrvError::Jwt(_)),
+                "{err}"
+            );
+        }
+
+        auth.authenticate_header(None).unwrap_err();
+        let token = issuer.token("test", claims());
+        auth.authenticate_header(Some(&format!("Basic {token}")))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn interceptor_inserts_identity() {
+        let issuer = TestIssuer::new();
+        let auth = Arc::new(JwtAuthenticator::with_keys(config(), issuer.keys.clone()));
+        let mut interceptor = JwtInterceptor::new(Some(auth));
+
+        let status = interceptor.call(Request::new(())).unwrap_err();
+        assert_eq!(tonic::Code::Unauthenticated, status.code());
+
+        let mut request = Request::new(());
+        let token = issuer.token("test", claims());
+        request
+            .metadata_mut()
+            .insert("authorization", format!("Bearer {token}").parse().unwrap());
+        let request = interceptor.call(request).unwrap();
+        assert_eq!(
+            "sam",
+            request.extensions().get::<JwtIdentity>().unwrap().user
+        );
+
+        // No authenticator, everything passes through.
+        let request = JwtInterceptor::default().call(Request::new(())).unwrap();
+        assert!(request.extensions().get::<JwtIdentity>().is_none());
+    }
+}
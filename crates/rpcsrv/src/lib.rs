@@ -1,9 +1,11 @@
 pub mod errors;
 pub mod flight;
 pub mod handler;
+pub mod jwt;
 pub mod proxy;
 pub mod simple;
 
+mod resumable;
 mod session;
 mod util;
 
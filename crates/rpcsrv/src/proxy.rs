@@ -163,6 +163,8 @@ impl<A: ProxyAuthenticator + 'static> service::execution_service_server::Executi
     for ProxyHandler<A, ExecutionServiceClient<Channel>>
 {
     type PhysicalPlanExecuteStream = Streaming<service::RecordBatchResponse>;
+    type ResumeExecutionStream = Streaming<service::RecordBatchResponse>;
+    type ExecutePartitionStream = Streaming<service::RecordBatchResponse>;
 
     async fn initialize_session(
         &self,
@@ -203,6 +205,69 @@ impl<A: ProxyAuthenticator + 'static> service::execution_service_server::Executi
         client.physical_plan_execute(request).await
     }
 
+    async fn resume_execution(
+        &self,
+        request: Request<service::ResumeExecutionRequest>,
+    ) -> Result<Response<Self::ResumeExecutionStream>, Status> {
+        info!("resume execution (proxy)");
+        let (_, mut client) = self.connect(request.metadata()).await?;
+        client.resume_execution(request).await
+    }
+
+    async fn cancel_execution(
+        &self,
+        request: Request<service::CancelExecutionRequest>,
+    ) -> Result<Response<service::CancelExecutionResponse>, Status> {
+        info!("cancel execution (proxy)");
+        let (_, mut client) = self.connect(request.metadata()).await?;
+        client.cancel_execution(request).await
+    }
+
+    async fn execute_partition(
+        &self,
+        request: Request<service::ExecutePartitionRequest>,
+    ) -> Result<Response<Self::ExecutePartitionStream>, Status> {
+        info!("execute partition (proxy)");
+        let (_, mut client) = self.connect(request.metadata()).await?;
+        client.execute_partition(request).await
+    }
+
+    async fn register_worker(
+        &self,
+        _request: Request<service::WorkerRequest>,
+    ) -> Result<Response<service::WorkerResponse>, Status> {
+        Err(Status::unimplemented(
+            "worker pools are managed directly on the rpc service",
+        ))
+    }
+
+    async fn deregister_worker(
+        &self,
+        _request: Request<service::WorkerRequest>,
+    ) -> Result<Response<service::WorkerResponse>, Status> {
+        Err(Status::unimplemented(
+            "worker pools are managed directly on the rpc service",
+        ))
+    }
+
+    async fn drain_worker(
+        &self,
+        _request: Request<service::WorkerRequest>,
+    ) -> Result<Response<service::WorkerResponse>, Status> {
+        Err(Status::unimplemented(
+            "worker pools are managed directly on the rpc service",
+        ))
+    }
+
+    async fn list_workers(
+        &self,
+        _request: Request<service::ListWorkersRequest>,
+    ) -> Result<Response<service::ListWorkersResponse>, Status> {
+        Err(Status::unimplemented(
+            "worker pools are managed directly on the rpc service",
+        ))
+    }
+
     async fn broadcast_exchange(
         &self,
         request: Request<Streaming<common::ExecutionResultBatch>>,
@@ -215,6 +280,17 @@ impl<A: ProxyAuthenticator + 'static> service::execution_service_server::Executi
             .broadcast_exchange(ProxiedRequestStream::new(request))
             .await
     }
+
+    async fn stage_table(
+        &self,
+        request: Request<Streaming<common::ExecutionResultBatch>>,
+    ) -> Result<Response<service::TableProviderResponse>, Status> {
+        info!("stage table (proxy)");
+        let metadata = request.metadata();
+        let (_, mut client) = self.connect(metadata).await?;
+        let request = request.into_inner();
+        client.stage_table(ProxiedRequestStream::new(request)).await
+    }
 }
 
 /// Adapater stream for proxying streaming requests.
@@ -0,0 +1,401 @@
+//! Result streams that can be resumed after the client reconnects.
+use crate::errors::{Result, RpcsrvError};
+use dashmap::DashMap;
+use datafusion::arrow::ipc::writer::FileWriter as IpcFileWriter;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::Result as DataFusionResult;
+use futures::{Stream, StreamExt};
+use protogen::gen::rpcsrv::common::CompressionCodec;
+use protogen::gen::rpcsrv::service;
+use sqlexec::remote::compression;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tonic::Status;
+use tracing::{debug, Span};
+use uuid::Uuid;
+
+/// How long an execution without a connected client is kept around for the
+/// client to resume it.
+pub const RESUME_GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+/// Number of sent batches kept per execution for replaying to a client that
+/// reconnected.
+pub const RESUME_BUFFER_BATCHES: usize = 16;
+
+type BatchStream = Pin<Box<dyn Stream<Item = DataFusionResult<RecordBatch>> + Send>>;
+
+/// In-flight executions that can be resumed.
+///
+/// Executions are only pulled from while a client is connected, so a
+/// disconnected execution doesn't make any progress until it's resumed.
+/// Executions that have been disconnected for longer than the grace period
+/// are dropped, cancelling the query.
+pub struct ResumableExecutions {
+    executions: DashMap<Uuid, Arc<Mutex<Execution>>>,
+    grace_period: Duration,
+    buffer_batches: usize,
+}
+
+impl Default for ResumableExecutions {
+    fn default() -> Self {
+        Self::new(RESUME_GRACE_PERIOD, RESUME_BUFFER_BATCHES)
+    }
+}
+
+impl ResumableExecutions {
+    pub fn new(grace_period: Duration, buffer_batches: usize) -> Self {
+        ResumableExecutions {
+            executions: DashMap::new(),
+            grace_period,
+            buffer_batches: buffer_batches.max(1),
+        }
+    }
+
+    /// Register a new execution, returning a response stream starting at the
+    /// first batch.
+    pub fn register(
+        &self,
+        database_id: Uuid,
+        execution_id: Uuid,
+        batches: BatchStream,
+        compression: CompressionCodec,
+        compression_level: i32,
+        span: Span,
+    ) -> ResumableResponseStream {
+        self.remove_expired();
+
+        let execution = Arc::new(Mutex::new(Execution {
+            database_id,
+            batches,
+            compression,
+            compression_level,
+            span,
+            sent: VecDeque::new(),
+            first_sequence: 0,
+            next_sequence: 0,
+            finished: false,
+            attached: 0,
+            detached_at: Instant::now(),
+        }));
+        self.executions.insert(execution_id, execution.clone());
+
+        ResumableResponseStream::attach(execution, 0, self.buffer_batches)
+    }
+
+    /// Resume an execution, returning a response stream starting at the
+    /// batch with the given sequence number.
+    pub fn resume(
+        &self,
+        database_id: Uuid,
+        execution_id: Uuid,
+        sequence: u64,
+    ) -> Result<ResumableResponseStream> {
+        self.remove_expired();
+
+        let execution = self
+            .executions
+            .get(&execution_id)
+            .map(|ent| ent.value().clone())
+            .ok_or(RpcsrvError::MissingExecution(execution_id))?;
+
+        {
+            let execution = execution.lock().unwrap();
+            if execution.database_id != database_id {
+                return Err(RpcsrvError::MissingExecution(execution_id));
+            }
+            if sequence < execution.first_sequence || sequence > execution.next_sequence {
+                return Err(RpcsrvError::ExecutionNotResumable {
+                    execution_id,
+                    sequence,
+                });
+            }
+        }
+
+        Ok(ResumableResponseStream::attach(
+            execution,
+            sequence,
+            self.buffer_batches,
+        ))
+    }
+
+    /// Cancel an execution, dropping its stream of batches.
+    ///
+    /// Response streams still attached to the execution end. Returns false if
+    /// there's no execution with the id for the database, e.g. because it
+    /// already expired.
+    pub fn cancel(&self, database_id: Uuid, execution_id: Uuid) -> bool {
+        let removed = self.executions.remove_if(&execution_id, |_, execution| {
+            execution.lock().unwrap().database_id == database_id
+        });
+
+        match removed {
+            Some((_, execution)) => {
+                let mut execution = execution.lock().unwrap();
+                execution.batches = Box::pin(futures::stream::empty());
+                execution.finished = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove_expired(&self) {
+        self.executions.retain(|id, execution| {
+            let execution = execution.lock().unwrap();
+            let expired =
+                execution.attached == 0 && execution.detached_at.elapsed() > self.grace_period;
+            if expired {
+                debug!(execution_id=%id, "dropping resumable execution");
+            }
+            !expired
+        });
+    }
+}
+
+struct Execution {
+    /// Database the execution is for, only sessions for the same database can
+    /// resume it.
+    database_id: Uuid,
+    batches: BatchStream,
+    /// Compression applied to encoded batches.
+    compression: CompressionCodec,
+    compression_level: i32,
+    /// Span for the request that started the execution.
+    span: Span,
+    /// Encoded responses that have already been sent, oldest first.
+    sent: VecDeque<service::RecordBatchResponse>,
+    /// Sequence number of the front of `sent`.
+    first_sequence: u64,
+    /// Sequence number of the next batch pulled from `batches`.
+    next_sequence: u64,
+    /// If `batches` has ended, or errored.
+    finished: bool,
+    /// Number of response streams reading from this execution.
+    attached: usize,
+    /// When the last response stream was dropped.
+    detached_at: Instant,
+}
+
+impl Execution {
+    fn write_batch(&self, batch: &RecordBatch) -> Result<service::RecordBatchResponse> {
+        let mut buf = Vec::new();
+
+        let schema = batch.schema();
+        let mut writer = IpcFileWriter::try_new(&mut buf, &schema)?;
+        writer.write(batch)?;
+        writer.finish()?;
+
+        let _ = writer.into_inner()?;
+
+        Ok(service::RecordBatchResponse {
+            arrow_ipc: compression::compress(self.compression, self.compression_level, buf)?,
+            sequence: self.next_sequence,
+            compression: self.compression as i32,
+        })
+    }
+}
+
+/// Stream of sequence numbered responses for an execution.
+///
+/// Replays buffered batches first if we're resuming from an earlier point
+/// than what's already been pulled from the execution.
+pub struct ResumableResponseStream {
+    execution: Arc<Mutex<Execution>>,
+    /// Sequence number of the next batch to send.
+    sequence: u64,
+    buffer_batches: usize,
+}
+
+impl ResumableResponseStream {
+    fn attach(execution: Arc<Mutex<Execution>>, sequence: u64, buffer_batches: usize) -> Self {
+        execution.lock().unwrap().attached += 1;
+        ResumableResponseStream {
+            execution,
+            sequence,
+            buffer_batches,
+        }
+    }
+}
+
+impl Stream for ResumableResponseStream {
+    type Item = Result<service::RecordBatchResponse, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let sequence = self.sequence;
+        let buffer_batches = self.buffer_batches;
+        let mut execution = self.execution.lock().unwrap();
+
+        // Replay from buffer.
+        if sequence < execution.next_sequence {
+            let idx = sequence.checked_sub(execution.first_sequence);
+            let resp = match idx.and_then(|idx| execution.sent.get(idx as usize)) {
+                Some(resp) => resp.clone(),
+                None => {
+                    return Poll::Ready(Some(Err(Status::out_of_range(format!(
+                        "batch {sequence} is no longer buffered"
+                    )))))
+                }
+            };
+            drop(execution);
+            self.sequence += 1;
+            return Poll::Ready(Some(Ok(resp)));
+        }
+
+        if execution.finished {
+            return Poll::Ready(None);
+        }
+
+        // Batches are pulled after the request handler returns, so execution
+        // needs to explicitly happen within the request's span.
+        let span = execution.span.clone();
+        let _entered = span.enter();
+        match execution.batches.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(batch))) => {
+                let resp = match execution.write_batch(&batch) {
+                    Ok(resp) => resp,
+                    Err(e) => {
+                        execution.finished = true;
+                        return Poll::Ready(Some(Err(e.into())));
+                    }
+                };
+                execution.sent.push_back(resp.clone());
+                if execution.sent.len() > buffer_batches {
+                    execution.sent.pop_front();
+                    execution.first_sequence += 1;
+                }
+                execution.next_sequence += 1;
+                drop(execution);
+                self.sequence += 1;
+                Poll::Ready(Some(Ok(resp)))
+            }
+            Poll::Ready(Some(Err(e))) => {
+                execution.finished = true;
+                Poll::Ready(Some(Err(RpcsrvError::from(e).into())))
+            }
+            Poll::Ready(None) => {
+                execution.finished = true;
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for ResumableResponseStream {
+    fn drop(&mut self) {
+        let mut execution = self.execution.lock().unwrap();
+        execution.attached -= 1;
+        execution.detached_at = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::arrow::array::Int64Array;
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+
+    use super::*;
+
+    fn batches(n: i64) -> BatchStream {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let batches: Vec<_> = (0..n)
+            .map(|i| {
+                Ok(
+                    RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![i]))])
+                        .unwrap(),
+                )
+            })
+            .collect();
+        futures::stream::iter(batches).boxed()
+    }
+
+    async fn sequences(stream: ResumableResponseStream) -> Vec<u64> {
+        stream
+            .map(|resp| resp.unwrap().sequence)
+            .collect::<Vec<_>>()
+            .await
+    }
+
+    #[tokio::test]
+    async fn resume_replays_buffered() {
+        let executions = ResumableExecutions::new(Duration::from_secs(60), 2);
+        let db = Uuid::new_v4();
+        let id = Uuid::new_v4();
+
+        // Client reads three batches then disconnects.
+        let mut stream = executions.register(
+            db,
+            id,
+            batches(5),
+            CompressionCodec::Uncompressed,
+            0,
+            Span::none(),
+        );
+        for expected in 0..3 {
+            let resp = stream.next().await.unwrap().unwrap();
+            assert_eq!(expected, resp.sequence);
+        }
+        drop(stream);
+
+        // Batch 1 is still buffered, batch 0 isn't.
+        assert!(executions.resume(db, id, 0).is_err());
+        let stream = executions.resume(db, id, 1).unwrap();
+        assert_eq!(vec![1, 2, 3, 4], sequences(stream).await);
+
+        // Execution for a different database.
+        assert!(executions.resume(Uuid::new_v4(), id, 1).is_err());
+
+        // Unknown execution.
+        assert!(executions.resume(db, Uuid::new_v4(), 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn cancel_ends_attached_streams() {
+        let executions = ResumableExecutions::new(Duration::from_secs(60), 2);
+        let db = Uuid::new_v4();
+        let id = Uuid::new_v4();
+
+        let mut stream = executions.register(
+            db,
+            id,
+            batches(5),
+            CompressionCodec::Uncompressed,
+            0,
+            Span::none(),
+        );
+        assert_eq!(0, stream.next().await.unwrap().unwrap().sequence);
+
+        // Wrong database.
+        assert!(!executions.cancel(Uuid::new_v4(), id));
+
+        assert!(executions.cancel(db, id));
+        assert!(stream.next().await.is_none());
+        assert!(executions.resume(db, id, 1).is_err());
+
+        // Already cancelled.
+        assert!(!executions.cancel(db, id));
+    }
+
+    #[tokio::test]
+    async fn expired_executions_removed() {
+        let executions = ResumableExecutions::new(Duration::ZERO, 2);
+        let db = Uuid::new_v4();
+        let id = Uuid::new_v4();
+
+        let stream = executions.register(
+            db,
+            id,
+            batches(2),
+            CompressionCodec::Uncompressed,
+            0,
+            Span::none(),
+        );
+        drop(stream);
+
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(executions.resume(db, id, 0).is_err());
+    }
+}
@@ -8,6 +8,7 @@ use protogen::metastore::types::catalog::CatalogState;
 use protogen::rpcsrv::types::service::ResolvedTableReference;
 use sqlexec::context::remote::RemoteSessionContext;
 use sqlexec::remote::batch_stream::ExecutionBatchStream;
+use sqlexec::remote::distribute::Workers;
 use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -38,31 +39,60 @@ impl RemoteSession {
         table_ref: ResolvedTableReference,
         args: Option<Vec<FuncParamValue>>,
         opts: Option<HashMap<String, FuncParamValue>>,
+        provider_id: Option<Uuid>,
     ) -> Result<(Uuid, Schema)> {
         let (id, prov) = self
             .session
-            .load_and_cache_table(table_ref, args, opts)
+            .load_and_cache_table(table_ref, args, opts, provider_id)
             .await?;
         let schema = prov.schema().as_ref().clone();
 
         Ok((id, schema))
     }
 
+    /// Execute a physical plan, distributing its partitions across `workers`.
     pub async fn physical_plan_execute(
         &self,
+        database_id: Uuid,
         physical_plan: impl AsRef<[u8]>,
+        workers: &Workers,
     ) -> Result<(Arc<dyn ExecutionPlan>, SendableRecordBatchStream)> {
+        let plan = self.decode_physical_plan(physical_plan)?;
+        let plan = workers.distribute(&self.session, database_id, plan);
+        let stream = self.session.execute_physical(plan.clone())?;
+        Ok((plan, stream))
+    }
+
+    /// Execute a single partition of a plan.
+    pub async fn execute_partition(
+        &self,
+        physical_plan: impl AsRef<[u8]>,
+        partition: usize,
+    ) -> Result<(Arc<dyn ExecutionPlan>, SendableRecordBatchStream)> {
+        let plan = self.decode_physical_plan(physical_plan)?;
+        let stream = self.session.execute_partition(plan.clone(), partition)?;
+        Ok((plan, stream))
+    }
+
+    fn decode_physical_plan(
+        &self,
+        physical_plan: impl AsRef<[u8]>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
         let codec = self.session.extension_codec();
         let plan = PhysicalPlanNode::try_decode(physical_plan.as_ref())?;
 
-        let plan = plan.try_into_physical_plan(
+        Ok(plan.try_into_physical_plan(
             self.session.get_datafusion_context(),
             self.session.get_datafusion_context().runtime_env().as_ref(),
             &codec,
-        )?;
+        )?)
+    }
 
-        let stream = self.session.execute_physical(plan.clone())?;
-        Ok((plan, stream))
+    pub async fn stage_table(&self, stream: ExecutionBatchStream) -> Result<(Uuid, Schema)> {
+        let (id, prov) = self.session.stage_table(stream).await?;
+        let schema = prov.schema().as_ref().clone();
+
+        Ok((id, schema))
     }
 
     pub async fn register_broadcast_stream(&self, stream: ExecutionBatchStream) -> Result<()> {
@@ -1,4 +1,5 @@
 use crate::errors::{Result, RpcsrvError};
+use crate::jwt::JwtIdentity;
 use async_trait::async_trait;
 use datafusion::physical_plan::SendableRecordBatchStream;
 use datafusion::variable::VarType;
@@ -10,7 +11,7 @@ use protogen::{
         ExecuteQueryRequest, ExecuteQueryResponse, QueryResultError, QueryResultSuccess,
     },
 };
-use sqlexec::{engine::Engine, OperationInfo};
+use sqlexec::{connections::ConnectionPermit, engine::Engine, OperationInfo};
 use std::{
     pin::Pin,
     sync::Arc,
@@ -46,8 +47,22 @@ impl simple::simple_service_server::SimpleService for SimpleHandler {
         // use the dist exec scheduler).
         //
         // This may be something we change (into what?)
+        let identity = request.extensions().get::<JwtIdentity>().cloned();
         let request = ExecuteQueryRequest::try_from(request.into_inner())?;
-        let vars = SessionVars::default().with_database_id(request.database_id, VarType::System);
+        let mut vars =
+            SessionVars::default().with_database_id(request.database_id, VarType::System);
+        if let Some(identity) = identity {
+            vars = vars
+                .with_user_name(identity.user, VarType::System)
+                .with_is_admin(identity.is_admin, VarType::System);
+        }
+        // Sessions only last for the query, so count them as connections
+        // until the result stream completes.
+        let permit = self
+            .engine
+            .connection_limiter()
+            .try_acquire(&vars.user_name())
+            .map_err(|e| Status::resource_exhausted(e.to_string()))?;
         let mut session = self
             .engine
             .new_local_session_context(vars, request.config.into())
@@ -68,7 +83,9 @@ impl simple::simple_service_server::SimpleService for SimpleHandler {
             .await
             .map_err(RpcsrvError::from)?;
 
-        Ok(Response::new(SimpleExecuteQueryStream::new(stream)))
+        Ok(Response::new(
+            SimpleExecuteQueryStream::new(stream).with_connection_permit(permit),
+        ))
     }
 }
 
@@ -79,6 +96,8 @@ impl simple::simple_service_server::SimpleService for SimpleHandler {
 pub struct SimpleExecuteQueryStream {
     inner: SendableRecordBatchStream,
     done: bool,
+    /// Released once the stream is dropped.
+    _permit: Option<ConnectionPermit>,
 }
 
 impl SimpleExecuteQueryStream {
@@ -86,8 +105,15 @@ impl SimpleExecuteQueryStream {
         SimpleExecuteQueryStream {
             inner: stream,
             done: false,
+            _permit: None,
         }
     }
+
+    /// Hold the permit until the stream is dropped.
+    pub fn with_connection_permit(mut self, permit: ConnectionPermit) -> Self {
+        self._permit = Some(permit);
+        self
+    }
 }
 
 impl Stream for SimpleExecuteQueryStream {
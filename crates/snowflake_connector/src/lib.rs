@@ -25,6 +25,7 @@ pub struct ConnectionBuilder {
     schema_name: Option<String>,
     warehouse: Option<String>,
     role_name: Option<String>,
+    proxy: Option<String>,
 }
 
 macro_rules! builder_fn {
@@ -47,6 +48,7 @@ impl ConnectionBuilder {
             schema_name: None,
             warehouse: None,
             role_name: None,
+            proxy: None,
         }
     }
 
@@ -55,6 +57,7 @@ impl ConnectionBuilder {
     builder_fn! {schema_name, String}
     builder_fn! {warehouse, String}
     builder_fn! {role_name, String}
+    builder_fn! {proxy, String}
 
     pub async fn build(self) -> Result<Connection> {
         if self.account_name.is_empty() || self.login_name.is_empty() {
@@ -64,7 +67,11 @@ impl ConnectionBuilder {
         }
 
         let url = format!("https://{}.snowflakecomputing.com:443", self.account_name);
-        let client = SnowflakeClient::builder().build(url)?;
+        let mut client = SnowflakeClient::builder();
+        if let Some(proxy) = self.proxy {
+            client = client.proxy(proxy);
+        }
+        let client = client.build(url)?;
 
         let password = self
             .password
@@ -3,7 +3,7 @@ use std::{collections::HashMap, io::Read, time::Duration};
 use flate2::read::GzDecoder;
 use reqwest::{
     header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE},
-    Client, IntoUrl, StatusCode, Url,
+    Client, IntoUrl, Proxy, StatusCode, Url,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tracing::{trace, warn};
@@ -45,6 +45,7 @@ impl Serialize for RequestId {
 pub struct SnowflakeClientBuilder {
     timeout: Option<Duration>,
     connect_timeout: Option<Duration>,
+    proxy: Option<String>,
 }
 
 impl SnowflakeClientBuilder {
@@ -60,7 +61,18 @@ impl SnowflakeClientBuilder {
         self
     }
 
+    /// Send requests through an HTTP proxy.
+    ///
+    /// Result chunks aren't downloaded using this client, so they don't go
+    /// through the proxy.
+    pub fn proxy(mut self, proxy: String) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
     pub fn build<U: IntoUrl>(self, base_url: U) -> Result<SnowflakeClient> {
+        let base_url = base_url.into_url()?;
+
         let mut default_headers = HeaderMap::new();
         default_headers.insert(CONTENT_TYPE, HeaderValue::from_static(BODY_CONTENT_TYPE));
         default_headers.insert(ACCEPT, HeaderValue::from_static(REQ_ACCEPT));
@@ -77,9 +89,13 @@ impl SnowflakeClientBuilder {
             builder = builder.connect_timeout(connect_timeout);
         }
 
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(Proxy::all(proxy)?);
+        }
+
         let client = builder.build()?;
         Ok(SnowflakeClient {
-            base_url: base_url.into_url()?,
+            base_url,
             inner: client,
         })
     }
@@ -13,7 +13,9 @@
 //! database node will be able to see it, but will not be able to execute
 //! appropriately. We can revisit this if this isn't acceptable long-term.
 
-use datafusion::arrow::datatypes::{DataType, Field as ArrowField, Schema as ArrowSchema};
+use datafusion::arrow::datatypes::{
+    DataType, Field as ArrowField, Schema as ArrowSchema, TimeUnit,
+};
 use once_cell::sync::Lazy;
 use pgrepr::oid::FIRST_GLAREDB_BUILTIN_ID;
 use protogen::metastore::types::options::InternalColumnDefinition;
@@ -34,6 +36,9 @@ pub const POSTGRES_SCHEMA: &str = "pg_catalog";
 /// Schema to store temporary objects (only valid for current session).
 pub const CURRENT_SESSION_SCHEMA: &str = "current_session";
 
+/// Schema for tables recording activity on the node, e.g. executed queries.
+pub const SYSTEM_SCHEMA: &str = "system";
+
 /// First oid available for other builtin objects that don't have a stable OID.
 ///
 /// Builtin schemas have stable OIDs since everything (builtin and user objects)
@@ -137,6 +142,7 @@ pub static GLARE_SCHEMAS: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
         ("database_name", DataType::Utf8, false),
         ("schema_name", DataType::Utf8, false),
         ("builtin", DataType::Boolean, false),
+        ("description", DataType::Utf8, true),
     ]),
     oid: 16404,
 });
@@ -154,6 +160,7 @@ pub static GLARE_TABLES: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
         ("external", DataType::Boolean, false),
         ("datasource", DataType::Utf8, false),
         ("access_mode", DataType::Utf8, false), // `SourceAccessMode::as_str()`
+        ("description", DataType::Utf8, true),
     ]),
     oid: 16405,
 });
@@ -169,6 +176,7 @@ pub static GLARE_VIEWS: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
         ("view_name", DataType::Utf8, false),
         ("builtin", DataType::Boolean, false),
         ("sql", DataType::Utf8, false),
+        ("description", DataType::Utf8, true),
     ]),
     oid: 16406,
 });
@@ -184,6 +192,7 @@ pub static GLARE_COLUMNS: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
         ("column_ordinal", DataType::UInt32, false),
         ("data_type", DataType::Utf8, false),
         ("is_nullable", DataType::Boolean, false),
+        ("description", DataType::Utf8, true),
     ]),
     oid: 16407,
 });
@@ -253,6 +262,169 @@ pub static GLARE_CACHED_EXTERNAL_DATABASE_TABLES: Lazy<BuiltinTable> = Lazy::new
     oid: 16411,
 });
 
+/// Memory currently reserved by sessions connected to this node, and the
+/// queries they're running.
+///
+/// Rows with a NULL `query_id` contain the totals for the session.
+pub static GLARE_MEMORY_USAGE: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
+    schema: INTERNAL_SCHEMA,
+    name: "memory_usage",
+    columns: InternalColumnDefinition::from_tuples([
+        ("connection_id", DataType::Utf8, false),
+        ("user_name", DataType::Utf8, false),
+        ("query_id", DataType::UInt64, true),
+        ("query_text", DataType::Utf8, true),
+        ("current_bytes", DataType::UInt64, false),
+        ("peak_bytes", DataType::UInt64, false),
+    ]),
+    oid: 16412,
+});
+
+/// Statements executed against this database.
+///
+/// Only written to if the node is configured to write the audit log to a
+/// table. Like the cached external tables, this lives in an on-disk (delta)
+/// table alongside user table data.
+pub static GLARE_AUDIT_LOG: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
+    schema: INTERNAL_SCHEMA,
+    name: "audit_log",
+    columns: InternalColumnDefinition::from_tuples([
+        (
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+        ("user_name", DataType::Utf8, false),
+        ("connection_id", DataType::Utf8, false),
+        // Only one of query text or fingerprint is set depending on how the
+        // audit log is configured.
+        ("query_text", DataType::Utf8, true),
+        ("query_fingerprint", DataType::Utf8, true),
+        ("execution_status", DataType::Utf8, false),
+        ("duration_ms", DataType::Int64, false),
+        ("output_rows", DataType::Int64, true),
+        ("error_message", DataType::Utf8, true),
+    ]),
+    oid: 16413,
+});
+
+/// Recently executed queries, pruned according to the configured retention.
+///
+/// Only admins see the queries of other users.
+pub static SYSTEM_QUERY_HISTORY: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
+    schema: SYSTEM_SCHEMA,
+    name: "query_history",
+    columns: InternalColumnDefinition::from_tuples([
+        (
+            "start_time",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+        ("user_name", DataType::Utf8, false),
+        ("connection_id", DataType::Utf8, false),
+        ("query_text", DataType::Utf8, false),
+        ("result_type", DataType::Utf8, false),
+        ("execution_status", DataType::Utf8, false),
+        ("duration_ms", DataType::Int64, false),
+        ("output_rows", DataType::Int64, true),
+        ("bytes_read", DataType::Int64, true),
+        ("bytes_written", DataType::Int64, true),
+        ("error_message", DataType::Utf8, true),
+    ]),
+    oid: 16414,
+});
+
+/// Workers remote execution on this node is distributed across, and their
+/// load.
+pub static GLARE_RPC_WORKERS: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
+    schema: INTERNAL_SCHEMA,
+    name: "rpc_workers",
+    columns: InternalColumnDefinition::from_tuples([
+        ("address", DataType::Utf8, false),
+        // One of 'active', 'draining' or 'drained'.
+        ("state", DataType::Utf8, false),
+        ("running_partitions", DataType::UInt64, false),
+        ("completed_partitions", DataType::UInt64, false),
+    ]),
+    oid: 16415,
+});
+
+/// Key-value tags set on schemas and tables.
+pub static GLARE_OBJECT_TAGS: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
+    schema: INTERNAL_SCHEMA,
+    name: "object_tags",
+    columns: InternalColumnDefinition::from_tuples([
+        ("oid", DataType::UInt32, false),
+        // One of 'schema' or 'table'.
+        ("object_type", DataType::Utf8, false),
+        ("database_name", DataType::Utf8, false),
+        ("schema_name", DataType::Utf8, false),
+        // Null for schemas.
+        ("table_name", DataType::Utf8, true),
+        ("key", DataType::Utf8, false),
+        ("value", DataType::Utf8, false),
+    ]),
+    oid: 16416,
+});
+
+/// Changes made to the catalog, such as objects being created, altered or
+/// dropped.
+pub static GLARE_CATALOG_EVENTS: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
+    schema: INTERNAL_SCHEMA,
+    name: "catalog_events",
+    columns: InternalColumnDefinition::from_tuples([
+        (
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+        // Version of the catalog after the change.
+        ("catalog_version", DataType::UInt64, false),
+        ("user_name", DataType::Utf8, false),
+        ("connection_id", DataType::Utf8, false),
+        // E.g. 'create', 'alter' or 'drop'.
+        ("event_type", DataType::Utf8, false),
+        // E.g. 'schema' or 'table'.
+        ("object_type", DataType::Utf8, false),
+        ("database_name", DataType::Utf8, true),
+        ("schema_name", DataType::Utf8, true),
+        ("object_name", DataType::Utf8, true),
+        ("details", DataType::Utf8, true),
+    ]),
+    oid: 16417,
+});
+
+/// Statistics for the current session.
+pub static GLARE_SESSION_STATS: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
+    schema: INTERNAL_SCHEMA,
+    name: "session_stats",
+    columns: InternalColumnDefinition::from_tuples([
+        ("plan_cache_hits", DataType::UInt64, false),
+        ("plan_cache_misses", DataType::UInt64, false),
+        // Number of plans in the cache, which may be shared with other
+        // sessions.
+        ("plan_cache_entries", DataType::UInt64, false),
+    ]),
+    oid: 16418,
+});
+
+/// Most recent warnings raised by queries in the current session.
+pub static GLARE_WARNINGS: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
+    schema: INTERNAL_SCHEMA,
+    name: "warnings",
+    columns: InternalColumnDefinition::from_tuples([
+        (
+            "time",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+        // E.g. the location of a skipped file.
+        ("source", DataType::Utf8, false),
+        ("message", DataType::Utf8, false),
+    ]),
+    oid: 16419,
+});
+
 impl BuiltinTable {
     /// Check if this table matches the provided schema and name.
     pub fn matches(&self, schema: &str, name: &str) -> bool {
@@ -283,6 +455,14 @@ impl BuiltinTable {
             &GLARE_SSH_KEYS,
             &GLARE_DEPLOYMENT_METADATA,
             &GLARE_CACHED_EXTERNAL_DATABASE_TABLES,
+            &GLARE_MEMORY_USAGE,
+            &GLARE_AUDIT_LOG,
+            &SYSTEM_QUERY_HISTORY,
+            &GLARE_RPC_WORKERS,
+            &GLARE_OBJECT_TAGS,
+            &GLARE_CATALOG_EVENTS,
+            &GLARE_SESSION_STATS,
+            &GLARE_WARNINGS,
         ]
     }
 }
@@ -319,6 +499,11 @@ pub static SCHEMA_CURRENT_SESSION: Lazy<BuiltinSchema> = Lazy::new(|| BuiltinSch
     oid: 16389,
 });
 
+pub static SCHEMA_SYSTEM: Lazy<BuiltinSchema> = Lazy::new(|| BuiltinSchema {
+    name: SYSTEM_SCHEMA,
+    oid: 16390,
+});
+
 impl BuiltinSchema {
     pub fn builtins() -> Vec<&'static BuiltinSchema> {
         vec![
@@ -327,6 +512,7 @@ impl BuiltinSchema {
             &SCHEMA_INFORMATION,
             &SCHEMA_POSTGRES,
             &SCHEMA_CURRENT_SESSION,
+            &SCHEMA_SYSTEM,
         ]
     }
 }
@@ -378,7 +564,8 @@ SELECT
     null AS default_character_set_catalog,
     null AS default_character_set_schema,
     null AS default_character_set_name,
-    null AS sql_path
+    null AS sql_path,
+    description AS schema_comment
 FROM glare_catalog.schemas",
 });
 
@@ -400,7 +587,8 @@ FROM (
         null AS user_defined_type_name,
         'NO' AS is_insertable_into,
         'NO' AS is_typed,
-        null AS commit_action
+        null AS commit_action,
+        t.description AS table_comment
     FROM glare_catalog.tables t INNER JOIN glare_catalog.databases d ON t.database_oid = d.oid
     UNION ALL
     SELECT
@@ -415,7 +603,8 @@ FROM (
         null AS user_defined_type_name,
         'NO' AS is_insertable_into,
         'NO' AS is_typed,
-        null AS commit_action
+        null AS commit_action,
+        v.description AS table_comment
     FROM glare_catalog.views v INNER JOIN glare_catalog.databases d ON v.database_oid = d.oid
 )",
 });
@@ -467,7 +656,8 @@ SELECT
     null AS identity_cyle,
     null AS is_generated,
     null AS generation_expression,
-    'NO' AS is_updateable
+    'NO' AS is_updateable,
+    c.description AS column_comment
 FROM glare_catalog.columns c
 INNER JOIN glare_catalog.schemas s ON c.schema_oid = s.oid
 INNER JOIN glare_catalog.databases d ON s.database_oid = d.oid
@@ -580,12 +770,22 @@ pub static PG_DESCRIPTION: Lazy<BuiltinView> = Lazy::new(|| BuiltinView {
     schema: POSTGRES_SCHEMA,
     name: "pg_description",
     sql: "
-SELECT
-    1 AS objoid,
-    2 AS classoid,
-    3 AS objsubid,
-    4 AS description
-FROM (VALUES (NULL, NULL, NULL, NULL)) WHERE false", // Create empty table for now.
+SELECT *
+FROM (
+    -- 1259 is the oid of pg_class, 2615 the oid of pg_namespace.
+    SELECT t.oid AS objoid, 1259 AS classoid, 0 AS objsubid, t.description AS description
+    FROM glare_catalog.tables t
+    UNION ALL
+    SELECT v.oid, 1259, 0, v.description
+    FROM glare_catalog.views v
+    UNION ALL
+    SELECT c.table_oid, 1259, c.column_ordinal + 1, c.description
+    FROM glare_catalog.columns c
+    UNION ALL
+    SELECT s.oid, 2615, 0, s.description
+    FROM glare_catalog.schemas s
+)
+WHERE description IS NOT NULL",
 });
 
 pub static PG_DATABASE: Lazy<BuiltinView> = Lazy::new(|| BuiltinView {
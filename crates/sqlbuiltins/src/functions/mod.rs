@@ -1,20 +1,30 @@
 //! Builtin table returning functions.
 mod aggregates;
 mod scalars;
+mod sketches;
 mod table;
 
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use datafusion::logical_expr::{AggregateFunction, BuiltinScalarFunction, Expr, Signature};
+use datafusion::logical_expr::{
+    AggregateFunction, AggregateUDF, BuiltinScalarFunction, Expr, Signature,
+};
 use once_cell::sync::Lazy;
 
 use protogen::metastore::types::catalog::{EntryMeta, EntryType, FunctionEntry, FunctionType};
 use scalars::df_scalars::ArrowCastFunction;
+use scalars::geospatial::{StAsText, StDistance, StGeomFromText, StPoint, StWithin};
 use scalars::hashing::{FnvHash, PartitionResults, SipHash};
 use scalars::kdl::{KDLMatches, KDLSelect};
+use scalars::map::{MapExtract, MapKeys, MapValues};
 use scalars::postgres::*;
+use scalars::timeseries::{Interpolate, Locf, TimeBucket, TimeBucketGapfill};
 use scalars::{ConnectionId, Version};
+use sketches::{
+    ApproxCountDistinct, ApproxQuantile, HllCount, HllMerge, HllSketch, TDigestMerge,
+    TDigestQuantile, TDigestSketch,
+};
 use table::{BuiltinTableFuncs, TableFunc};
 
 /// Builtin table returning functions available for all sessions.
@@ -122,6 +132,14 @@ pub trait BuiltinScalarUDF: BuiltinFunction {
     }
 }
 
+/// A custom builtin aggregate function provided by GlareDB.
+///
+/// Like [`BuiltinScalarUDF`], this contains the implementation of the
+/// function rather than just a catalog entry.
+pub trait BuiltinAggregateUDF: BuiltinFunction {
+    fn as_aggregate_udf(&self) -> AggregateUDF;
+}
+
 impl<T> BuiltinFunction for T
 where
     T: ConstBuiltinFunction + Sized,
@@ -150,6 +168,7 @@ where
 pub struct FunctionRegistry {
     funcs: HashMap<String, Arc<dyn BuiltinFunction>>,
     udfs: HashMap<String, Arc<dyn BuiltinScalarUDF>>,
+    udafs: HashMap<String, Arc<dyn BuiltinAggregateUDF>>,
 }
 
 impl FunctionRegistry {
@@ -186,16 +205,36 @@ impl FunctionRegistry {
             Arc::new(PgTableIsVisible),
             Arc::new(PgEncodingToChar),
             Arc::new(PgArrayToString),
+            Arc::new(PgTimezone),
+            Arc::new(PgGenRandomUuid),
             // System functions
             Arc::new(ConnectionId),
             Arc::new(Version),
             // KDL functions
             Arc::new(KDLMatches),
             Arc::new(KDLSelect),
+            // Geospatial functions
+            Arc::new(StPoint),
+            Arc::new(StGeomFromText),
+            Arc::new(StAsText),
+            Arc::new(StDistance),
+            Arc::new(StWithin),
+            // Map functions
+            Arc::new(MapExtract),
+            Arc::new(MapKeys),
+            Arc::new(MapValues),
             // Hashing/Partitioning
             Arc::new(SipHash),
             Arc::new(FnvHash),
             Arc::new(PartitionResults),
+            // Sketches
+            Arc::new(HllCount),
+            Arc::new(TDigestQuantile),
+            // Time series
+            Arc::new(TimeBucket),
+            Arc::new(TimeBucketGapfill),
+            Arc::new(Locf),
+            Arc::new(Interpolate),
         ];
         let udfs = udfs
             .into_iter()
@@ -223,20 +262,45 @@ impl FunctionRegistry {
             })
             .collect::<HashMap<_, _>>();
 
+        let udafs: Vec<Arc<dyn BuiltinAggregateUDF>> = vec![
+            // Sketches
+            Arc::new(ApproxCountDistinct),
+            Arc::new(ApproxQuantile),
+            Arc::new(HllSketch),
+            Arc::new(HllMerge),
+            Arc::new(TDigestSketch),
+            Arc::new(TDigestMerge),
+        ];
+        let udafs = udafs
+            .into_iter()
+            .map(|f| (f.name().to_string(), f))
+            .collect::<HashMap<_, _>>();
+
         let funcs: HashMap<String, Arc<dyn BuiltinFunction>> =
             scalars.chain(aggregates).chain(arrow_cast).collect();
 
-        FunctionRegistry { funcs, udfs }
+        FunctionRegistry { funcs, udfs, udafs }
     }
 
     pub fn contains(&self, name: impl AsRef<str>) -> bool {
         self.funcs
             .keys()
             .chain(self.udfs.keys())
+            .chain(self.udafs.keys())
             .chain(BUILTIN_TABLE_FUNCS.keys())
             .any(|k| k.to_lowercase() == name.as_ref().to_lowercase())
     }
 
+    /// Return an iterator over the names of all scalar and aggregate
+    /// functions, including namespaced names.
+    pub fn function_names(&self) -> impl Iterator<Item = &str> {
+        self.funcs
+            .keys()
+            .chain(self.udfs.keys())
+            .chain(self.udafs.keys())
+            .map(|k| k.as_str())
+    }
+
     pub fn find_function(&self, name: &str) -> Option<Arc<dyn BuiltinFunction>> {
         self.funcs.get(name).cloned()
     }
@@ -248,6 +312,11 @@ impl FunctionRegistry {
         self.udfs.get(name).cloned()
     }
 
+    /// Find an aggregate UDF by name.
+    pub fn get_aggregate_udf(&self, name: &str) -> Option<Arc<dyn BuiltinAggregateUDF>> {
+        self.udafs.get(name).cloned()
+    }
+
     pub fn scalar_functions(&self) -> impl Iterator<Item = &Arc<dyn BuiltinFunction>> {
         self.funcs.values()
     }
@@ -255,6 +324,11 @@ impl FunctionRegistry {
     pub fn scalar_udfs(&self) -> impl Iterator<Item = &Arc<dyn BuiltinScalarUDF>> {
         self.udfs.values()
     }
+
+    pub fn aggregate_udfs(&self) -> impl Iterator<Item = &Arc<dyn BuiltinAggregateUDF>> {
+        self.udafs.values()
+    }
+
     /// Return an iterator over all builtin table functions.
     pub fn table_funcs(&self) -> impl Iterator<Item = &Arc<dyn TableFunc>> {
         BUILTIN_TABLE_FUNCS.iter_funcs()
@@ -0,0 +1,583 @@
+//! Geospatial functions operating on geometries stored as WKB.
+//!
+//! Everything is computed in a cartesian plane using the geometries'
+//! coordinates as is. SRIDs aren't taken into account.
+
+use datafusion::arrow::array::new_empty_array;
+use datafusion_ext::geometry::{Coord, Geometry};
+use datafusion_ext::types::GEOMETRY_DATA_TYPE;
+
+use super::*;
+
+pub struct StPoint;
+
+impl ConstBuiltinFunction for StPoint {
+    const NAME: &'static str = "st_point";
+    const DESCRIPTION: &'static str = "Creates a point geometry with the given coordinates";
+    const EXAMPLE: &'static str = "st_point(-71.06, 42.36)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::exact(
+            // args: <x>, <y>
+            vec![DataType::Float64, DataType::Float64],
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for StPoint {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|_| Ok(Arc::new(GEOMETRY_DATA_TYPE))),
+            fun: Arc::new(move |input| {
+                Ok(map_rows(input, &GEOMETRY_DATA_TYPE, &|args| {
+                    let point = match (&args[0], &args[1]) {
+                        (ScalarValue::Float64(Some(x)), ScalarValue::Float64(Some(y))) => {
+                            Some(Geometry::Point(Coord::new(*x, *y)).to_wkb())
+                        }
+                        _ => None,
+                    };
+                    Ok(ScalarValue::Binary(point))
+                })?)
+            }),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+}
+
+pub struct StGeomFromText;
+
+impl ConstBuiltinFunction for StGeomFromText {
+    const NAME: &'static str = "st_geomfromtext";
+    const DESCRIPTION: &'static str = "Creates a geometry from its well-known text (WKT)";
+    const EXAMPLE: &'static str = "st_geomfromtext('POLYGON((0 0,1 0,1 1,0 1,0 0))')";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::exact(
+            vec![DataType::Utf8],
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for StGeomFromText {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|_| Ok(Arc::new(GEOMETRY_DATA_TYPE))),
+            fun: Arc::new(move |input| {
+                Ok(map_rows(input, &GEOMETRY_DATA_TYPE, &|args| {
+                    let geom = match &args[0] {
+                        ScalarValue::Utf8(Some(wkt)) => Some(
+                            Geometry::from_wkt(wkt)
+                                .map_err(|e| BuiltinError::InvalidValue(e.to_string()))?
+                                .to_wkb(),
+                        ),
+                        _ => None,
+                    };
+                    Ok(ScalarValue::Binary(geom))
+                })?)
+            }),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+}
+
+pub struct StAsText;
+
+impl ConstBuiltinFunction for StAsText {
+    const NAME: &'static str = "st_astext";
+    const DESCRIPTION: &'static str = "Returns the well-known text (WKT) of a geometry";
+    const EXAMPLE: &'static str = "st_astext(st_point(1, 2))";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::exact(
+            vec![GEOMETRY_DATA_TYPE],
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for StAsText {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|_| Ok(Arc::new(DataType::Utf8))),
+            fun: Arc::new(move |input| {
+                Ok(map_rows(input, &DataType::Utf8, &|args| {
+                    let geom = get_geometry(&args[0])?;
+                    Ok(ScalarValue::Utf8(geom.map(|g| g.to_string())))
+                })?)
+            }),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+}
+
+pub struct StDistance;
+
+impl ConstBuiltinFunction for StDistance {
+    const NAME: &'static str = "st_distance";
+    const DESCRIPTION: &'static str =
+        "Returns the minimum cartesian distance between two geometries";
+    const EXAMPLE: &'static str = "st_distance(st_point(0, 0), st_point(3, 4))";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::exact(
+            vec![GEOMETRY_DATA_TYPE, GEOMETRY_DATA_TYPE],
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for StDistance {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|_| Ok(Arc::new(DataType::Float64))),
+            fun: Arc::new(move |input| {
+                Ok(map_rows(input, &DataType::Float64, &|args| {
+                    let distance = match (get_geometry(&args[0])?, get_geometry(&args[1])?) {
+                        (Some(a), Some(b)) => distance(&Parts::new(&a), &Parts::new(&b)),
+                        _ => None,
+                    };
+                    Ok(ScalarValue::Float64(distance))
+                })?)
+            }),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+}
+
+pub struct StWithin;
+
+impl ConstBuiltinFunction for StWithin {
+    const NAME: &'static str = "st_within";
+    const DESCRIPTION: &'static str =
+        "Returns true if the first geometry is completely inside the second geometry";
+    const EXAMPLE: &'static str =
+        "st_within(st_point(1, 1), st_geomfromtext('POLYGON((0 0,2 0,2 2,0 2,0 0))'))";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::exact(
+            vec![GEOMETRY_DATA_TYPE, GEOMETRY_DATA_TYPE],
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for StWithin {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|_| Ok(Arc::new(DataType::Boolean))),
+            fun: Arc::new(move |input| {
+                Ok(map_rows(input, &DataType::Boolean, &|args| {
+                    let within = match (get_geometry(&args[0])?, get_geometry(&args[1])?) {
+                        (Some(a), Some(b)) => Some(within(&Parts::new(&a), &Parts::new(&b))),
+                        _ => None,
+                    };
+                    Ok(ScalarValue::Boolean(within))
+                })?)
+            }),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+}
+
+/// Apply `op` to each row of the input.
+///
+/// If all inputs are scalars, the output will be a scalar as well.
+fn map_rows(
+    input: &[ColumnarValue],
+    return_type: &DataType,
+    op: &dyn Fn(&[ScalarValue]) -> Result<ScalarValue, BuiltinError>,
+) -> Result<ColumnarValue, BuiltinError> {
+    let num_rows = input.iter().find_map(|v| match v {
+        ColumnarValue::Array(arr) => Some(arr.len()),
+        ColumnarValue::Scalar(_) => None,
+    });
+
+    let num_rows = match num_rows {
+        Some(0) => return Ok(ColumnarValue::Array(new_empty_array(return_type))),
+        Some(num_rows) => num_rows,
+        None => {
+            let args: Vec<_> = input
+                .iter()
+                .map(|v| match v {
+                    ColumnarValue::Scalar(v) => v.clone(),
+                    ColumnarValue::Array(_) => unreachable!("all inputs are scalars"),
+                })
+                .collect();
+            return Ok(ColumnarValue::Scalar(op(&args)?));
+        }
+    };
+
+    let arr = scalar_iter_to_array((0..num_rows).map(|idx| -> Result<_, ExtensionError> {
+        let args = input
+            .iter()
+            .map(|v| match v {
+                ColumnarValue::Scalar(v) => Ok(v.clone()),
+                ColumnarValue::Array(arr) => ScalarValue::try_from_array(arr, idx),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(op(&args)?)
+    }))?;
+    Ok(ColumnarValue::Array(arr))
+}
+
+fn get_geometry(value: &ScalarValue) -> Result<Option<Geometry>, BuiltinError> {
+    match value {
+        ScalarValue::Binary(v) | ScalarValue::LargeBinary(v) => v
+            .as_deref()
+            .map(Geometry::from_wkb)
+            .transpose()
+            .map_err(|e| BuiltinError::InvalidValue(e.to_string())),
+        other => Err(BuiltinError::IncorrectType(
+            other.data_type(),
+            GEOMETRY_DATA_TYPE,
+        )),
+    }
+}
+
+/// A geometry broken down into points, line segments, and polygons.
+///
+/// Polygon rings are included in the segments as well.
+#[derive(Debug, Default)]
+struct Parts {
+    points: Vec<Coord>,
+    segments: Vec<(Coord, Coord)>,
+    polygons: Vec<Vec<Vec<Coord>>>,
+}
+
+impl Parts {
+    fn new(geom: &Geometry) -> Self {
+        let mut parts = Parts::default();
+        parts.push(geom);
+        parts
+    }
+
+    fn push(&mut self, geom: &Geometry) {
+        match geom {
+            Geometry::Point(c) => self.push_point(*c),
+            Geometry::MultiPoint(points) => points.iter().for_each(|c| self.push_point(*c)),
+            Geometry::LineString(coords) => self.push_line(coords),
+            Geometry::MultiLineString(lines) => lines.iter().for_each(|l| self.push_line(l)),
+            Geometry::Polygon(rings) => self.push_polygon(rings),
+            Geometry::MultiPolygon(polygons) => polygons.iter().for_each(|p| self.push_polygon(p)),
+            Geometry::GeometryCollection(geoms) => geoms.iter().for_each(|g| self.push(g)),
+        }
+    }
+
+    fn push_point(&mut self, c: Coord) {
+        // Skip empty points.
+        if !c.x.is_nan() && !c.y.is_nan() {
+            self.points.push(c);
+        }
+    }
+
+    fn push_line(&mut self, coords: &[Coord]) {
+        match coords {
+            [c] => self.push_point(*c),
+            coords => self
+                .segments
+                .extend(coords.windows(2).map(|w| (w[0], w[1]))),
+        }
+    }
+
+    fn push_polygon(&mut self, rings: &[Vec<Coord>]) {
+        if rings.is_empty() {
+            return;
+        }
+        rings.iter().for_each(|ring| self.push_line(ring));
+        self.polygons.push(rings.to_vec());
+    }
+
+    fn is_empty(&self) -> bool {
+        self.points.is_empty() && self.segments.is_empty()
+    }
+
+    /// All coordinates used by the geometry.
+    fn vertices(&self) -> impl Iterator<Item = Coord> + '_ {
+        self.points
+            .iter()
+            .copied()
+            .chain(self.segments.iter().flat_map(|(a, b)| [*a, *b]))
+    }
+
+    /// Get the location of a point relative to the geometry.
+    fn locate(&self, p: Coord) -> Location {
+        if self.points.iter().any(|c| *c == p)
+            || self.segments.iter().any(|(a, b)| on_segment(p, *a, *b))
+        {
+            return Location::Boundary;
+        }
+        if self.polygons.iter().any(|rings| in_polygon(p, rings)) {
+            return Location::Interior;
+        }
+        Location::Exterior
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Location {
+    Interior,
+    Boundary,
+    Exterior,
+}
+
+/// Minimum distance between two geometries. Returns `None` if either is empty.
+fn distance(a: &Parts, b: &Parts) -> Option<f64> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    if intersects(a, b) {
+        return Some(0.0);
+    }
+
+    // Geometries don't intersect, so the closest points will be between a
+    // vertex of one geometry and a part of the other.
+    let to_parts = |p: Coord, other: &Parts| {
+        let points = other.points.iter().map(|c| point_distance(p, *c));
+        let segments = other
+            .segments
+            .iter()
+            .map(|(s, e)| segment_distance(p, *s, *e));
+        points.chain(segments).fold(f64::INFINITY, f64::min)
+    };
+    let a_to_b = a.vertices().map(|p| to_parts(p, b));
+    let b_to_a = b.vertices().map(|p| to_parts(p, a));
+
+    Some(a_to_b.chain(b_to_a).fold(f64::INFINITY, f64::min))
+}
+
+fn intersects(a: &Parts, b: &Parts) -> bool {
+    let segments_cross = a.segments.iter().any(|(s1, e1)| {
+        b.segments
+            .iter()
+            .any(|(s2, e2)| segments_intersect(*s1, *e1, *s2, *e2))
+    });
+    segments_cross
+        || a.vertices().any(|p| b.locate(p) != Location::Exterior)
+        || b.vertices().any(|p| a.locate(p) != Location::Exterior)
+}
+
+/// Returns true if `a` is within `b`.
+///
+/// Every point of `a` must be inside `b` (including its boundary), and at
+/// least one point of `a` must be in the interior of `b`, e.g. a point on the
+/// edge of a polygon isn't within the polygon.
+fn within(a: &Parts, b: &Parts) -> bool {
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+
+    // Check vertices and the midpoints of segments, the latter catches
+    // segments leaving a concave polygon between two vertices.
+    let midpoints = a
+        .segments
+        .iter()
+        .map(|(s, e)| Coord::new((s.x + e.x) / 2.0, (s.y + e.y) / 2.0));
+    // Polygons sharing their entire boundary with b also need a point from
+    // their interior to check. This uses the average of the exterior ring's
+    // vertices, which is only used if it's actually inside the polygon.
+    let interior_points = a.polygons.iter().filter_map(|rings| {
+        let exterior = rings.first()?;
+        let vertices = exterior.split_last().map(|(_, v)| v).unwrap_or_default();
+        if vertices.is_empty() {
+            return None;
+        }
+        let n = vertices.len() as f64;
+        let center = Coord::new(
+            vertices.iter().map(|c| c.x).sum::<f64>() / n,
+            vertices.iter().map(|c| c.y).sum::<f64>() / n,
+        );
+        in_polygon(center, rings).then_some(center)
+    });
+    let locations: Vec<_> = a
+        .vertices()
+        .chain(midpoints)
+        .chain(interior_points)
+        .map(|p| b.locate(p))
+        .collect();
+    if locations.contains(&Location::Exterior) {
+        return false;
+    }
+
+    // Segments passing through the boundary of b must leave it.
+    let crosses_boundary = a.segments.iter().any(|(s1, e1)| {
+        b.segments
+            .iter()
+            .any(|(s2, e2)| segments_cross_properly(*s1, *e1, *s2, *e2))
+    });
+    if crosses_boundary && !b.polygons.is_empty() {
+        return false;
+    }
+
+    if b.polygons.is_empty() {
+        // Points and lines don't have an interior area, so everything being
+        // on them is enough.
+        true
+    } else {
+        locations.contains(&Location::Interior)
+    }
+}
+
+fn point_distance(a: Coord, b: Coord) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+/// Distance from `p` to the segment between `s` and `e`.
+fn segment_distance(p: Coord, s: Coord, e: Coord) -> f64 {
+    let (dx, dy) = (e.x - s.x, e.y - s.y);
+    let len_sq = dx * dx + dy * dy;
+    if len_sq == 0.0 {
+        return point_distance(p, s);
+    }
+    let t = (((p.x - s.x) * dx + (p.y - s.y) * dy) / len_sq).clamp(0.0, 1.0);
+    point_distance(p, Coord::new(s.x + t * dx, s.y + t * dy))
+}
+
+/// Cross product of `a - o` and `b - o`. Positive if `o`, `a`, `b` are
+/// counter clockwise, negative if clockwise, and zero if collinear.
+fn orientation(o: Coord, a: Coord, b: Coord) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+fn on_segment(p: Coord, s: Coord, e: Coord) -> bool {
+    orientation(s, e, p) == 0.0
+        && p.x >= s.x.min(e.x)
+        && p.x <= s.x.max(e.x)
+        && p.y >= s.y.min(e.y)
+        && p.y <= s.y.max(e.y)
+}
+
+/// Returns true if the segments intersect, including touching at an end point.
+fn segments_intersect(s1: Coord, e1: Coord, s2: Coord, e2: Coord) -> bool {
+    segments_cross_properly(s1, e1, s2, e2)
+        || on_segment(s2, s1, e1)
+        || on_segment(e2, s1, e1)
+        || on_segment(s1, s2, e2)
+        || on_segment(e1, s2, e2)
+}
+
+/// Returns true if the segments cross at a single point that isn't an end
+/// point of either segment.
+fn segments_cross_properly(s1: Coord, e1: Coord, s2: Coord, e2: Coord) -> bool {
+    let d1 = orientation(s2, e2, s1);
+    let d2 = orientation(s2, e2, e1);
+    let d3 = orientation(s1, e1, s2);
+    let d4 = orientation(s1, e1, e2);
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+/// Returns true if `p` is strictly inside the polygon. Points on the boundary
+/// should be checked separately.
+fn in_polygon(p: Coord, rings: &[Vec<Coord>]) -> bool {
+    let in_ring = |ring: &[Coord]| {
+        // Ray casting.
+        let mut inside = false;
+        for w in ring.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+                inside = !inside;
+            }
+        }
+        inside
+    };
+
+    match rings.split_first() {
+        Some((exterior, holes)) => in_ring(exterior) && !holes.iter().any(|h| in_ring(h)),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(wkt: &str) -> Parts {
+        Parts::new(&Geometry::from_wkt(wkt).unwrap())
+    }
+
+    #[test]
+    fn geometry_distance() {
+        let test_cases = [
+            ("POINT(0 0)", "POINT(3 4)", 5.0),
+            ("POINT(0 2)", "LINESTRING(-1 0,1 0)", 2.0),
+            ("POINT(1 1)", "POLYGON((0 0,2 0,2 2,0 2,0 0))", 0.0),
+            ("POINT(5 1)", "POLYGON((0 0,2 0,2 2,0 2,0 0))", 3.0),
+            ("LINESTRING(0 0,2 2)", "LINESTRING(0 2,2 0)", 0.0),
+            ("LINESTRING(0 0,1 0)", "LINESTRING(0 1,1 1)", 1.0),
+            // Inside the hole.
+            (
+                "POINT(2 2)",
+                "POLYGON((0 0,4 0,4 4,0 4,0 0),(1 1,3 1,3 3,1 3,1 1))",
+                1.0,
+            ),
+        ];
+
+        for (a, b, expected) in test_cases {
+            assert_eq!(Some(expected), distance(&parts(a), &parts(b)), "{a}, {b}");
+            assert_eq!(Some(expected), distance(&parts(b), &parts(a)), "{b}, {a}");
+        }
+
+        assert_eq!(None, distance(&parts("POINT EMPTY"), &parts("POINT(1 1)")));
+    }
+
+    #[test]
+    fn geometry_within() {
+        let square = "POLYGON((0 0,4 0,4 4,0 4,0 0))";
+        // Concave polygon shaped like a 'U'.
+        let concave = "POLYGON((0 0,3 0,3 3,2 3,2 1,1 1,1 3,0 3,0 0))";
+
+        let test_cases = [
+            ("POINT(1 1)", square, true),
+            ("POINT(5 5)", square, false),
+            // On the boundary.
+            ("POINT(0 2)", square, false),
+            ("LINESTRING(1 1,3 3)", square, true),
+            ("LINESTRING(1 1,5 5)", square, false),
+            ("POLYGON((1 1,2 1,2 2,1 1))", square, true),
+            (square, square, true),
+            ("POINT(0.5 2)", concave, true),
+            ("POINT(1.5 2)", concave, false),
+            // Both end points are inside, but it crosses the gap.
+            ("LINESTRING(0.5 2,2.5 2)", concave, false),
+            ("POINT(1 1)", "LINESTRING(0 0,2 2)", true),
+            ("POINT(1 2)", "LINESTRING(0 0,2 2)", false),
+            ("POINT(1 1)", "POINT(1 1)", true),
+            ("POINT EMPTY", square, false),
+        ];
+
+        for (a, b, expected) in test_cases {
+            assert_eq!(expected, within(&parts(a), &parts(b)), "{a}, {b}");
+        }
+    }
+}
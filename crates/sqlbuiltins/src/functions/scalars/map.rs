@@ -0,0 +1,303 @@
+//! Functions for accessing the entries of map values.
+//!
+//! Map values don't have a scalar representation, so these functions operate
+//! on the underlying map arrays directly.
+
+use datafusion::arrow::array::{ArrayRef, BooleanArray, ListArray, MapArray, Scalar, UInt32Array};
+use datafusion::arrow::buffer::OffsetBuffer;
+use datafusion::arrow::compute::kernels::cmp::eq;
+use datafusion::arrow::compute::{cast, take};
+use datafusion::arrow::datatypes::FieldRef;
+
+use super::*;
+
+pub struct MapExtract;
+
+impl ConstBuiltinFunction for MapExtract {
+    const NAME: &'static str = "map_extract";
+    const DESCRIPTION: &'static str =
+        "Returns a list containing the value for the given key, or an empty list if the key isn't in the map";
+    const EXAMPLE: &'static str = "map_extract(my_map, 'key')";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::new(
+            // args: <map>, <key>
+            TypeSignature::Any(2),
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for MapExtract {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|types| {
+                let (_, value) = map_entry_fields(&types[0])?;
+                Ok(Arc::new(DataType::List(value)))
+            }),
+            fun: Arc::new(move |input| {
+                let map = map_array(&input[0])?;
+                let (_, value_field) = map_entry_fields(map.data_type())?;
+
+                let key = input[1].clone().into_array(map.len());
+                let key = cast(&key, map.keys().data_type()).map_err(|_| {
+                    BuiltinError::IncorrectType(
+                        input[1].data_type(),
+                        map.keys().data_type().clone(),
+                    )
+                })?;
+
+                let mut indices = Vec::new();
+                let mut offsets = Vec::with_capacity(map.len() + 1);
+                offsets.push(0);
+                for row in 0..map.len() {
+                    if map.is_valid(row) && key.is_valid(row) {
+                        let start = map.value_offsets()[row] as usize;
+                        let len = map.value_length(row) as usize;
+                        let matches: BooleanArray = eq(
+                            &map.keys().slice(start, len),
+                            &Scalar::new(key.slice(row, 1)),
+                        )?;
+                        indices.extend(
+                            matches
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, m)| m.unwrap_or(false))
+                                .map(|(idx, _)| (start + idx) as u32),
+                        );
+                    }
+                    offsets.push(indices.len() as i32);
+                }
+
+                let values = take(map.values().as_ref(), &UInt32Array::from(indices), None)?;
+                let list = ListArray::new(
+                    value_field,
+                    OffsetBuffer::new(offsets.into()),
+                    values,
+                    map.nulls().cloned(),
+                );
+                Ok(ColumnarValue::Array(Arc::new(list)))
+            }),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+}
+
+pub struct MapKeys;
+
+impl ConstBuiltinFunction for MapKeys {
+    const NAME: &'static str = "map_keys";
+    const DESCRIPTION: &'static str = "Returns a list of all keys in the map";
+    const EXAMPLE: &'static str = "map_keys(my_map)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::new(
+            // args: <map>
+            TypeSignature::Any(1),
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for MapKeys {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|types| {
+                let (key, _) = map_entry_fields(&types[0])?;
+                Ok(Arc::new(DataType::List(key)))
+            }),
+            fun: Arc::new(move |input| {
+                let map = map_array(&input[0])?;
+                let (key_field, _) = map_entry_fields(map.data_type())?;
+                Ok(ColumnarValue::Array(map_to_list(
+                    map,
+                    key_field,
+                    map.keys().clone(),
+                )))
+            }),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+}
+
+pub struct MapValues;
+
+impl ConstBuiltinFunction for MapValues {
+    const NAME: &'static str = "map_values";
+    const DESCRIPTION: &'static str = "Returns a list of all values in the map";
+    const EXAMPLE: &'static str = "map_values(my_map)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::new(
+            // args: <map>
+            TypeSignature::Any(1),
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for MapValues {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|types| {
+                let (_, value) = map_entry_fields(&types[0])?;
+                Ok(Arc::new(DataType::List(value)))
+            }),
+            fun: Arc::new(move |input| {
+                let map = map_array(&input[0])?;
+                let (_, value_field) = map_entry_fields(map.data_type())?;
+                Ok(ColumnarValue::Array(map_to_list(
+                    map,
+                    value_field,
+                    map.values().clone(),
+                )))
+            }),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+}
+
+/// Get the key and value fields for a map type.
+///
+/// The returned fields are suitable for use as list item fields.
+fn map_entry_fields(data_type: &DataType) -> Result<(FieldRef, FieldRef), BuiltinError> {
+    let expected =
+        || BuiltinError::InvalidValue(format!("expected a map argument, got {data_type}"));
+    match data_type {
+        DataType::Map(entries, _) => match entries.data_type() {
+            DataType::Struct(fields) if fields.len() == 2 => Ok((
+                Arc::new(Field::new("item", fields[0].data_type().clone(), false)),
+                Arc::new(Field::new("item", fields[1].data_type().clone(), true)),
+            )),
+            _ => Err(expected()),
+        },
+        _ => Err(expected()),
+    }
+}
+
+fn map_array(value: &ColumnarValue) -> Result<&MapArray, BuiltinError> {
+    match value {
+        ColumnarValue::Array(arr) => arr.as_any().downcast_ref::<MapArray>().ok_or_else(|| {
+            BuiltinError::InvalidValue(format!("expected a map argument, got {}", arr.data_type()))
+        }),
+        ColumnarValue::Scalar(scalar) => Err(BuiltinError::InvalidValue(format!(
+            "expected a map argument, got {}",
+            scalar.data_type()
+        ))),
+    }
+}
+
+/// Create a list array sharing the offsets and validity of the map.
+fn map_to_list(map: &MapArray, field: FieldRef, values: ArrayRef) -> ArrayRef {
+    Arc::new(ListArray::new(
+        field,
+        map.offsets().clone(),
+        values,
+        map.nulls().cloned(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::arrow::array::{AsArray, Int32Builder, MapBuilder, StringBuilder};
+    use datafusion::arrow::datatypes::Int32Type;
+
+    use super::*;
+
+    fn test_map() -> ArrayRef {
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        builder.keys().append_value("a");
+        builder.values().append_value(1);
+        builder.keys().append_value("b");
+        builder.values().append_value(2);
+        builder.append(true).unwrap();
+        builder.append(false).unwrap();
+        builder.keys().append_value("b");
+        builder.values().append_null();
+        builder.append(true).unwrap();
+        Arc::new(builder.finish())
+    }
+
+    fn invoke(expr: Expr, args: &[ColumnarValue]) -> ArrayRef {
+        let udf = match expr {
+            Expr::ScalarUDF(expr) => expr.fun,
+            other => panic!("unexpected expression: {other}"),
+        };
+        match (udf.fun)(args).unwrap() {
+            ColumnarValue::Array(arr) => arr,
+            other => panic!("unexpected value: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn map_extract() {
+        let out = invoke(
+            MapExtract.as_expr(vec![]),
+            &[
+                ColumnarValue::Array(test_map()),
+                ColumnarValue::Scalar(ScalarValue::Utf8(Some("b".to_string()))),
+            ],
+        );
+        let out = out.as_list::<i32>();
+
+        let values: Vec<_> = out
+            .iter()
+            .map(|v| v.map(|v| v.as_primitive::<Int32Type>().iter().collect::<Vec<_>>()))
+            .collect();
+        assert_eq!(vec![Some(vec![Some(2)]), None, Some(vec![None])], values);
+    }
+
+    #[test]
+    fn map_keys_and_values() {
+        let keys = invoke(
+            MapKeys.as_expr(vec![]),
+            &[ColumnarValue::Array(test_map().slice(1, 2))],
+        );
+        let keys: Vec<_> = keys
+            .as_list::<i32>()
+            .iter()
+            .map(|v| {
+                v.map(|v| {
+                    v.as_string::<i32>()
+                        .iter()
+                        .flatten()
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+            })
+            .collect();
+        assert_eq!(vec![None, Some("b".to_string())], keys);
+
+        let values = invoke(
+            MapValues.as_expr(vec![]),
+            &[ColumnarValue::Array(test_map())],
+        );
+        let values: Vec<_> = values
+            .as_list::<i32>()
+            .iter()
+            .map(|v| v.map(|v| v.as_primitive::<Int32Type>().iter().collect::<Vec<_>>()))
+            .collect();
+        assert_eq!(
+            vec![Some(vec![Some(1), Some(2)]), None, Some(vec![None])],
+            values
+        );
+    }
+}
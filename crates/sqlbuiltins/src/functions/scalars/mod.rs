@@ -1,7 +1,10 @@
 pub mod df_scalars;
+pub mod geospatial;
 pub mod hashing;
 pub mod kdl;
+pub mod map;
 pub mod postgres;
+pub mod timeseries;
 
 use std::sync::Arc;
 
@@ -1,4 +1,9 @@
+use chrono::{NaiveDateTime, TimeZone};
+use datafusion::arrow::array::timezone::Tz;
+use datafusion::arrow::datatypes::TimeUnit;
 use datafusion::logical_expr::expr::ScalarFunction;
+use datafusion_ext::types::text_to_uuid;
+use datafusion_ext::vars::normalize_timezone;
 
 use crate::functions::FunctionNamespace;
 
@@ -434,3 +439,150 @@ impl BuiltinScalarUDF for PgArrayToString {
         FunctionNamespace::Required("pg_catalog")
     }
 }
+
+#[derive(Clone)]
+pub struct PgTimezone;
+
+impl ConstBuiltinFunction for PgTimezone {
+    const NAME: &'static str = "timezone";
+    const DESCRIPTION: &'static str =
+        "Postgres `timezone` function, equivalent to `<timestamp> AT TIME ZONE <zone>`";
+    const EXAMPLE: &'static str = "timezone('America/New_York', now())";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::new(
+            // args: <zone>, <timestamp>
+            TypeSignature::Any(2),
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for PgTimezone {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|types| match types.get(1) {
+                Some(DataType::Timestamp(_, None)) => Ok(Arc::new(DataType::Timestamp(
+                    TimeUnit::Nanosecond,
+                    Some("UTC".into()),
+                ))),
+                Some(DataType::Timestamp(_, Some(_))) => {
+                    Ok(Arc::new(DataType::Timestamp(TimeUnit::Nanosecond, None)))
+                }
+                other => Err(DataFusionError::Plan(format!(
+                    "expected a timestamp for AT TIME ZONE, got {other:?}"
+                ))),
+            }),
+            fun: Arc::new(move |input| {
+                let zone = match input.first() {
+                    Some(ColumnarValue::Scalar(ScalarValue::Utf8(Some(zone)))) => zone,
+                    Some(ColumnarValue::Scalar(other)) => {
+                        return Err(
+                            BuiltinError::IncorrectType(other.data_type(), DataType::Utf8).into(),
+                        )
+                    }
+                    Some(ColumnarValue::Array(_)) => {
+                        return Err(BuiltinError::InvalidColumnarValue(0).into())
+                    }
+                    None => return Err(BuiltinError::MissingValueAtIndex(0).into()),
+                };
+                let tz: Tz = normalize_timezone(zone)
+                    .and_then(|tz| tz.parse().ok())
+                    .ok_or_else(|| {
+                        BuiltinError::InvalidValue(format!("time zone \"{zone}\" not recognized"))
+                    })?;
+
+                Ok(get_nth_scalar_value(input, 1, &|value| {
+                    convert_timezone(value, &tz)
+                })?)
+            }),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+    fn namespace(&self) -> FunctionNamespace {
+        PG_CATALOG_NAMESPACE
+    }
+}
+
+/// Convert a timestamp to the given time zone.
+///
+/// Timestamps without a time zone are treated as local time in `tz`, producing
+/// a timestamp with a time zone. Timestamps with a time zone produce the local
+/// time in `tz`, without a time zone.
+fn convert_timezone(value: ScalarValue, tz: &Tz) -> Result<ScalarValue, BuiltinError> {
+    let (nanos, value_tz) = match value {
+        ScalarValue::TimestampSecond(v, value_tz) => (v.map(|v| v * 1_000_000_000), value_tz),
+        ScalarValue::TimestampMillisecond(v, value_tz) => (v.map(|v| v * 1_000_000), value_tz),
+        ScalarValue::TimestampMicrosecond(v, value_tz) => (v.map(|v| v * 1_000), value_tz),
+        ScalarValue::TimestampNanosecond(v, value_tz) => (v, value_tz),
+        other => {
+            return Err(BuiltinError::IncorrectType(
+                other.data_type(),
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+            ))
+        }
+    };
+
+    let nanos = match (nanos, value_tz) {
+        (None, None) => return Ok(ScalarValue::TimestampNanosecond(None, Some("UTC".into()))),
+        (None, Some(_)) => return Ok(ScalarValue::TimestampNanosecond(None, None)),
+        (Some(nanos), None) => {
+            let local = NaiveDateTime::from_timestamp_opt(
+                nanos.div_euclid(1_000_000_000),
+                nanos.rem_euclid(1_000_000_000) as u32,
+            )
+            .ok_or(BuiltinError::InvalidValue(format!(
+                "timestamp out of range: {nanos}"
+            )))?;
+            // Ambiguous local times (during DST transitions) resolve to the
+            // earliest instant, matching Postgres.
+            let utc = tz.from_local_datetime(&local).earliest().ok_or_else(|| {
+                BuiltinError::InvalidValue(format!("local time {local} does not exist in {tz}"))
+            })?;
+            return Ok(ScalarValue::TimestampNanosecond(
+                utc.timestamp_nanos_opt(),
+                Some("UTC".into()),
+            ));
+        }
+        (Some(nanos), Some(_)) => nanos,
+    };
+
+    let local = tz.timestamp_nanos(nanos).naive_local();
+    Ok(ScalarValue::TimestampNanosecond(
+        local.timestamp_nanos_opt(),
+        None,
+    ))
+}
+
+#[derive(Clone)]
+pub struct PgGenRandomUuid;
+
+impl ConstBuiltinFunction for PgGenRandomUuid {
+    const NAME: &'static str = "gen_random_uuid";
+    const DESCRIPTION: &'static str =
+        "Postgres `gen_random_uuid` function, returns a random (v4) UUID";
+    const EXAMPLE: &'static str = "gen_random_uuid()";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::exact(vec![], Volatility::Volatile))
+    }
+}
+
+impl BuiltinScalarUDF for PgGenRandomUuid {
+    fn as_expr(&self, _: Vec<Expr>) -> Expr {
+        // UDFs without arguments can't produce a value per row, so build on
+        // top of datafusion's `uuid` which does.
+        text_to_uuid(
+            Expr::ScalarFunction(ScalarFunction::new(BuiltinScalarFunction::Uuid, vec![])),
+            false,
+        )
+    }
+    fn namespace(&self) -> FunctionNamespace {
+        PG_CATALOG_NAMESPACE
+    }
+}
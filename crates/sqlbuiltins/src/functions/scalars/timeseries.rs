@@ -0,0 +1,143 @@
+//! Functions for downsampling time series.
+//!
+//! `time_bucket` truncates timestamps to buckets of a fixed width.
+//! `time_bucket_gapfill` does the same, but grouping by it also outputs rows for
+//! buckets without any data, with aggregates wrapped in `locf` or
+//! `interpolate` filled in from the surrounding buckets. Gap filling itself
+//! happens when planning the aggregate, see [`datafusion_ext::gapfill`].
+
+use datafusion::arrow::datatypes::{IntervalUnit, TimeUnit};
+use datafusion::logical_expr::expr::ScalarFunction;
+use datafusion::physical_expr::datetime_expressions::date_bin;
+use datafusion_ext::gapfill::{INTERPOLATE, LOCF, TIME_BUCKET_GAPFILL};
+
+use super::*;
+
+const TIMESTAMP: DataType = DataType::Timestamp(TimeUnit::Nanosecond, None);
+const INTERVALS: [DataType; 2] = [
+    DataType::Interval(IntervalUnit::MonthDayNano),
+    DataType::Interval(IntervalUnit::DayTime),
+];
+
+/// Signatures taking an interval and `num_timestamps` timestamps.
+fn interval_and_timestamps(num_timestamps: usize) -> Vec<TypeSignature> {
+    INTERVALS
+        .iter()
+        .map(|interval| {
+            let mut types = vec![interval.clone()];
+            types.extend(std::iter::repeat(TIMESTAMP).take(num_timestamps));
+            TypeSignature::Exact(types)
+        })
+        .collect()
+}
+
+pub struct TimeBucket;
+
+impl ConstBuiltinFunction for TimeBucket {
+    const NAME: &'static str = "time_bucket";
+    const DESCRIPTION: &'static str =
+        "Truncates a timestamp to the start of the bucket of the given width containing it, optionally relative to an origin";
+    const EXAMPLE: &'static str = "time_bucket(INTERVAL '15 minutes', ts)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        let mut signatures = interval_and_timestamps(1);
+        signatures.extend(interval_and_timestamps(2));
+        Some(Signature::one_of(signatures, Volatility::Immutable))
+    }
+}
+
+impl BuiltinScalarUDF for TimeBucket {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        Expr::ScalarFunction(ScalarFunction::new(BuiltinScalarFunction::DateBin, args))
+    }
+}
+
+pub struct TimeBucketGapfill;
+
+impl ConstBuiltinFunction for TimeBucketGapfill {
+    const NAME: &'static str = TIME_BUCKET_GAPFILL;
+    const DESCRIPTION: &'static str =
+        "Truncates a timestamp like time_bucket. Grouping by it outputs a row for every bucket between start and finish";
+    const EXAMPLE: &'static str =
+        "time_bucket_gapfill(INTERVAL '1 hour', ts, '2023-01-01', '2023-01-02')";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::one_of(
+            // args: <stride>, <ts>, <start>, <finish>
+            interval_and_timestamps(3),
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for TimeBucketGapfill {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|_| Ok(Arc::new(TIMESTAMP))),
+            // The range only matters for gap filling, bucket like `date_bin`.
+            fun: Arc::new(|input| date_bin(&input[..2])),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+}
+
+/// Build an identity function marking aggregates for gap filling.
+fn fill_marker(name: &str, args: Vec<Expr>) -> Expr {
+    let udf = ScalarUDF {
+        name: name.to_string(),
+        signature: Signature::new(TypeSignature::Any(1), Volatility::Immutable),
+        return_type: Arc::new(|types| Ok(Arc::new(types[0].clone()))),
+        fun: Arc::new(|input| Ok(input[0].clone())),
+    };
+    Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+        Arc::new(udf),
+        args,
+    ))
+}
+
+pub struct Locf;
+
+impl ConstBuiltinFunction for Locf {
+    const NAME: &'static str = LOCF;
+    const DESCRIPTION: &'static str =
+        "Fills an aggregate in buckets added by time_bucket_gapfill with the last observed value";
+    const EXAMPLE: &'static str = "locf(avg(value))";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::new(TypeSignature::Any(1), Volatility::Immutable))
+    }
+}
+
+impl BuiltinScalarUDF for Locf {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        fill_marker(Self::NAME, args)
+    }
+}
+
+pub struct Interpolate;
+
+impl ConstBuiltinFunction for Interpolate {
+    const NAME: &'static str = INTERPOLATE;
+    const DESCRIPTION: &'static str =
+        "Fills a numeric aggregate in buckets added by time_bucket_gapfill by linear interpolation between the surrounding observed values";
+    const EXAMPLE: &'static str = "interpolate(avg(value))";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::new(TypeSignature::Any(1), Volatility::Immutable))
+    }
+}
+
+impl BuiltinScalarUDF for Interpolate {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        fill_marker(Self::NAME, args)
+    }
+}
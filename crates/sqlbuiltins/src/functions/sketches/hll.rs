@@ -0,0 +1,190 @@
+//! HyperLogLog sketches for estimating the number of distinct values.
+
+use crate::errors::{BuiltinError, Result};
+
+/// Magic bytes at the start of a serialized sketch.
+const MAGIC: &[u8; 4] = b"HLL1";
+
+/// Number of bits of the hash used to pick a register.
+///
+/// 2^14 registers gives a standard error of about 0.8%.
+pub const DEFAULT_PRECISION: u8 = 14;
+
+/// A HyperLogLog sketch.
+///
+/// Values are added by their 64 bit hash. Sketches with the same precision can
+/// be merged, giving the same estimate as a single sketch of all the values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_PRECISION)
+    }
+}
+
+impl HyperLogLog {
+    pub fn new(precision: u8) -> Self {
+        HyperLogLog {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    }
+
+    /// Add a hashed value to the sketch.
+    pub fn add_hash(&mut self, hash: u64) {
+        let idx = (hash >> (64 - self.precision)) as usize;
+        // Position of the first set bit in the remaining bits. A sentinel bit
+        // bounds the rank when all remaining bits are zero.
+        let rest = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        if rank > self.registers[idx] {
+            self.registers[idx] = rank;
+        }
+    }
+
+    /// Merge another sketch into this one.
+    pub fn merge(&mut self, other: &HyperLogLog) -> Result<()> {
+        if self.precision != other.precision {
+            return Err(BuiltinError::InvalidValue(format!(
+                "cannot merge HyperLogLog sketches with precisions {} and {}",
+                self.precision, other.precision
+            )));
+        }
+        for (register, other) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other);
+        }
+        Ok(())
+    }
+
+    /// Estimate the number of distinct values added to the sketch.
+    pub fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+
+        let mut sum = 0.0;
+        let mut zeros = 0;
+        for &register in &self.registers {
+            sum += 1.0 / (1u64 << register) as f64;
+            if register == 0 {
+                zeros += 1;
+            }
+        }
+
+        let estimate = alpha * m * m / sum;
+        // Use linear counting for small cardinalities, where the raw estimate
+        // is biased.
+        if estimate <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(MAGIC.len() + 1 + self.registers.len());
+        buf.extend_from_slice(MAGIC);
+        buf.push(self.precision);
+        buf.extend_from_slice(&self.registers);
+        buf
+    }
+
+    pub fn try_from_bytes(buf: &[u8]) -> Result<Self> {
+        let invalid = || BuiltinError::InvalidValue("invalid HyperLogLog sketch".to_string());
+
+        let buf = buf.strip_prefix(MAGIC.as_slice()).ok_or_else(invalid)?;
+        let (&precision, registers) = buf.split_first().ok_or_else(invalid)?;
+        if !(4..=18).contains(&precision) || registers.len() != 1 << precision {
+            return Err(invalid());
+        }
+
+        Ok(HyperLogLog {
+            precision,
+            registers: registers.to_vec(),
+        })
+    }
+
+    /// Approximate size in bytes.
+    pub fn size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.registers.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use siphasher::sip::SipHasher24;
+    use std::hash::Hasher;
+
+    fn hash(v: u64) -> u64 {
+        let mut hasher = SipHasher24::new();
+        hasher.write(&v.to_le_bytes());
+        hasher.finish()
+    }
+
+    fn assert_close(expected: u64, actual: u64) {
+        let err = (actual as f64 - expected as f64).abs() / expected as f64;
+        assert!(err < 0.03, "expected about {expected}, got {actual}");
+    }
+
+    #[test]
+    fn estimates() {
+        for n in [10, 1000, 100_000] {
+            let mut hll = HyperLogLog::default();
+            for v in 0..n {
+                hll.add_hash(hash(v));
+                // Duplicates don't change the estimate.
+                hll.add_hash(hash(v));
+            }
+            assert_close(n, hll.estimate());
+        }
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(0, HyperLogLog::default().estimate());
+    }
+
+    #[test]
+    fn merge() {
+        let mut a = HyperLogLog::default();
+        let mut b = HyperLogLog::default();
+        let mut all = HyperLogLog::default();
+        for v in 0..50_000 {
+            a.add_hash(hash(v));
+            all.add_hash(hash(v));
+        }
+        for v in 25_000..75_000 {
+            b.add_hash(hash(v));
+            all.add_hash(hash(v));
+        }
+
+        a.merge(&b).unwrap();
+        assert_eq!(all, a);
+        assert_close(75_000, a.estimate());
+
+        assert!(a.merge(&HyperLogLog::new(10)).is_err());
+    }
+
+    #[test]
+    fn roundtrip_bytes() {
+        let mut hll = HyperLogLog::default();
+        for v in 0..100 {
+            hll.add_hash(hash(v));
+        }
+
+        let bytes = hll.to_bytes();
+        assert_eq!(hll, HyperLogLog::try_from_bytes(&bytes).unwrap());
+
+        assert!(HyperLogLog::try_from_bytes(&bytes[..100]).is_err());
+        assert!(HyperLogLog::try_from_bytes(b"not a sketch").is_err());
+    }
+}
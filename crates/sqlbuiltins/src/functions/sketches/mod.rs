@@ -0,0 +1,584 @@
+//! Approximate aggregates backed by mergeable sketches.
+//!
+//! Besides returning estimates directly, sketches can be returned as binary
+//! values to be stored and later merged or queried.
+mod hll;
+mod tdigest;
+
+use std::hash::Hasher;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{new_empty_array, Array, ArrayRef, AsArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Float64Type, Int64Type, UInt64Type};
+use datafusion::error::Result;
+use datafusion::logical_expr::type_coercion::aggregates::NUMERICS;
+use datafusion::logical_expr::{
+    Accumulator, AggregateUDF, Expr, ScalarUDF, Signature, TypeSignature, Volatility,
+};
+use datafusion::physical_plan::ColumnarValue;
+use datafusion::scalar::ScalarValue;
+use protogen::metastore::types::catalog::FunctionType;
+use siphasher::sip::SipHasher24;
+
+use crate::errors::BuiltinError;
+use crate::functions::{BuiltinAggregateUDF, BuiltinScalarUDF, ConstBuiltinFunction};
+use hll::HyperLogLog;
+use tdigest::TDigest;
+
+pub struct ApproxCountDistinct;
+
+impl ConstBuiltinFunction for ApproxCountDistinct {
+    const NAME: &'static str = "approx_count_distinct";
+    const DESCRIPTION: &'static str =
+        "Estimates the number of distinct non-null values using a HyperLogLog sketch.";
+    const EXAMPLE: &'static str = "approx_count_distinct(<value>)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Aggregate;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::any(1, Volatility::Immutable))
+    }
+}
+
+impl BuiltinAggregateUDF for ApproxCountDistinct {
+    fn as_aggregate_udf(&self) -> AggregateUDF {
+        aggregate_udf(
+            Self::NAME,
+            ConstBuiltinFunction::signature(self).unwrap(),
+            DataType::UInt64,
+            vec![DataType::Binary],
+            || Box::new(HllAccumulator::new(false, false)),
+        )
+    }
+}
+
+pub struct HllSketch;
+
+impl ConstBuiltinFunction for HllSketch {
+    const NAME: &'static str = "hll_sketch";
+    const DESCRIPTION: &'static str =
+        "Builds a HyperLogLog sketch of the non-null values, returned as binary.";
+    const EXAMPLE: &'static str = "hll_sketch(<value>)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Aggregate;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::any(1, Volatility::Immutable))
+    }
+}
+
+impl BuiltinAggregateUDF for HllSketch {
+    fn as_aggregate_udf(&self) -> AggregateUDF {
+        aggregate_udf(
+            Self::NAME,
+            ConstBuiltinFunction::signature(self).unwrap(),
+            DataType::Binary,
+            vec![DataType::Binary],
+            || Box::new(HllAccumulator::new(false, true)),
+        )
+    }
+}
+
+pub struct HllMerge;
+
+impl ConstBuiltinFunction for HllMerge {
+    const NAME: &'static str = "hll_merge";
+    const DESCRIPTION: &'static str = "Merges HyperLogLog sketches into a single sketch.";
+    const EXAMPLE: &'static str = "hll_merge(<sketch>)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Aggregate;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::exact(
+            vec![DataType::Binary],
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinAggregateUDF for HllMerge {
+    fn as_aggregate_udf(&self) -> AggregateUDF {
+        aggregate_udf(
+            Self::NAME,
+            ConstBuiltinFunction::signature(self).unwrap(),
+            DataType::Binary,
+            vec![DataType::Binary],
+            || Box::new(HllAccumulator::new(true, true)),
+        )
+    }
+}
+
+pub struct HllCount;
+
+impl ConstBuiltinFunction for HllCount {
+    const NAME: &'static str = "hll_count";
+    const DESCRIPTION: &'static str =
+        "Estimates the number of distinct values in a HyperLogLog sketch.";
+    const EXAMPLE: &'static str = "hll_count(hll_sketch(<value>))";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::exact(
+            vec![DataType::Binary],
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for HllCount {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|_| Ok(Arc::new(DataType::UInt64))),
+            fun: Arc::new(|input| {
+                map_rows(input, &DataType::UInt64, |row| match &row[0] {
+                    ScalarValue::Binary(sketch) => Ok(ScalarValue::UInt64(
+                        sketch
+                            .as_ref()
+                            .map(|sketch| HyperLogLog::try_from_bytes(sketch))
+                            .transpose()?
+                            .map(|hll| hll.estimate()),
+                    )),
+                    other => {
+                        Err(BuiltinError::IncorrectType(other.data_type(), DataType::Binary).into())
+                    }
+                })
+            }),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+}
+
+pub struct ApproxQuantile;
+
+impl ConstBuiltinFunction for ApproxQuantile {
+    const NAME: &'static str = "approx_quantile";
+    const DESCRIPTION: &'static str =
+        "Estimates the value at the given quantile (between 0 and 1) using a t-digest sketch.";
+    const EXAMPLE: &'static str = "approx_quantile(<value>, 0.5)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Aggregate;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::one_of(
+            NUMERICS
+                .iter()
+                .map(|t| TypeSignature::Exact(vec![t.clone(), DataType::Float64]))
+                .collect(),
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinAggregateUDF for ApproxQuantile {
+    fn as_aggregate_udf(&self) -> AggregateUDF {
+        aggregate_udf(
+            Self::NAME,
+            ConstBuiltinFunction::signature(self).unwrap(),
+            DataType::Float64,
+            vec![DataType::Binary, DataType::Float64],
+            || {
+                Box::new(TDigestAccumulator::new(
+                    false,
+                    TDigestOutput::Quantile(None),
+                ))
+            },
+        )
+    }
+}
+
+pub struct TDigestSketch;
+
+impl ConstBuiltinFunction for TDigestSketch {
+    const NAME: &'static str = "tdigest_sketch";
+    const DESCRIPTION: &'static str =
+        "Builds a t-digest sketch of the non-null values, returned as binary.";
+    const EXAMPLE: &'static str = "tdigest_sketch(<value>)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Aggregate;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::uniform(
+            1,
+            NUMERICS.to_vec(),
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinAggregateUDF for TDigestSketch {
+    fn as_aggregate_udf(&self) -> AggregateUDF {
+        aggregate_udf(
+            Self::NAME,
+            ConstBuiltinFunction::signature(self).unwrap(),
+            DataType::Binary,
+            vec![DataType::Binary],
+            || Box::new(TDigestAccumulator::new(false, TDigestOutput::Sketch)),
+        )
+    }
+}
+
+pub struct TDigestMerge;
+
+impl ConstBuiltinFunction for TDigestMerge {
+    const NAME: &'static str = "tdigest_merge";
+    const DESCRIPTION: &'static str = "Merges t-digest sketches into a single sketch.";
+    const EXAMPLE: &'static str = "tdigest_merge(<sketch>)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Aggregate;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::exact(
+            vec![DataType::Binary],
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinAggregateUDF for TDigestMerge {
+    fn as_aggregate_udf(&self) -> AggregateUDF {
+        aggregate_udf(
+            Self::NAME,
+            ConstBuiltinFunction::signature(self).unwrap(),
+            DataType::Binary,
+            vec![DataType::Binary],
+            || Box::new(TDigestAccumulator::new(true, TDigestOutput::Sketch)),
+        )
+    }
+}
+
+pub struct TDigestQuantile;
+
+impl ConstBuiltinFunction for TDigestQuantile {
+    const NAME: &'static str = "tdigest_quantile";
+    const DESCRIPTION: &'static str =
+        "Estimates the value at the given quantile (between 0 and 1) of a t-digest sketch.";
+    const EXAMPLE: &'static str = "tdigest_quantile(tdigest_sketch(<value>), 0.5)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::Scalar;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::exact(
+            vec![DataType::Binary, DataType::Float64],
+            Volatility::Immutable,
+        ))
+    }
+}
+
+impl BuiltinScalarUDF for TDigestQuantile {
+    fn as_expr(&self, args: Vec<Expr>) -> Expr {
+        let udf = ScalarUDF {
+            name: Self::NAME.to_string(),
+            signature: ConstBuiltinFunction::signature(self).unwrap(),
+            return_type: Arc::new(|_| Ok(Arc::new(DataType::Float64))),
+            fun: Arc::new(|input| {
+                map_rows(input, &DataType::Float64, |row| match (&row[0], &row[1]) {
+                    (ScalarValue::Binary(Some(sketch)), ScalarValue::Float64(Some(q))) => {
+                        let q = check_quantile(*q)?;
+                        let digest = TDigest::try_from_bytes(sketch)?;
+                        Ok(ScalarValue::Float64(digest.quantile(q)))
+                    }
+                    (ScalarValue::Binary(_), ScalarValue::Float64(_)) => {
+                        Ok(ScalarValue::Float64(None))
+                    }
+                    (ScalarValue::Binary(_), other) => Err(BuiltinError::IncorrectType(
+                        other.data_type(),
+                        DataType::Float64,
+                    )
+                    .into()),
+                    (other, _) => {
+                        Err(BuiltinError::IncorrectType(other.data_type(), DataType::Binary).into())
+                    }
+                })
+            }),
+        };
+        Expr::ScalarUDF(datafusion::logical_expr::expr::ScalarUDF::new(
+            Arc::new(udf),
+            args,
+        ))
+    }
+}
+
+/// Create an aggregate UDF with fixed return and state types.
+fn aggregate_udf(
+    name: &str,
+    signature: Signature,
+    return_type: DataType,
+    state_type: Vec<DataType>,
+    accumulator: impl Fn() -> Box<dyn Accumulator> + Send + Sync + 'static,
+) -> AggregateUDF {
+    let return_type = Arc::new(return_type);
+    let state_type = Arc::new(state_type);
+    AggregateUDF {
+        name: name.to_string(),
+        signature,
+        return_type: Arc::new(move |_| Ok(return_type.clone())),
+        accumulator: Arc::new(move |_| Ok(accumulator())),
+        state_type: Arc::new(move |_| Ok(state_type.clone())),
+    }
+}
+
+#[derive(Debug)]
+struct HllAccumulator {
+    hll: HyperLogLog,
+    /// Whether the inputs are sketches to merge rather than values to add.
+    merge_inputs: bool,
+    /// Whether to return the sketch rather than the estimate.
+    return_sketch: bool,
+}
+
+impl HllAccumulator {
+    fn new(merge_inputs: bool, return_sketch: bool) -> Self {
+        HllAccumulator {
+            hll: HyperLogLog::default(),
+            merge_inputs,
+            return_sketch,
+        }
+    }
+}
+
+impl Accumulator for HllAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::Binary(Some(self.hll.to_bytes()))])
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if self.merge_inputs {
+            return self.merge_batch(values);
+        }
+        let hll = &mut self.hll;
+        for_each_hash(&values[0], |hash| hll.add_hash(hash))
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        for_each_sketch(&states[0], |sketch| {
+            Ok(self.hll.merge(&HyperLogLog::try_from_bytes(sketch)?)?)
+        })
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        Ok(if self.return_sketch {
+            ScalarValue::Binary(Some(self.hll.to_bytes()))
+        } else {
+            ScalarValue::UInt64(Some(self.hll.estimate()))
+        })
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.hll.size()
+    }
+}
+
+#[derive(Debug)]
+enum TDigestOutput {
+    Sketch,
+    /// Estimate the value at a quantile. The quantile is taken from the
+    /// arguments on the first update.
+    Quantile(Option<f64>),
+}
+
+#[derive(Debug)]
+struct TDigestAccumulator {
+    digest: TDigest,
+    /// Whether the inputs are sketches to merge rather than values to add.
+    merge_inputs: bool,
+    output: TDigestOutput,
+}
+
+impl TDigestAccumulator {
+    fn new(merge_inputs: bool, output: TDigestOutput) -> Self {
+        TDigestAccumulator {
+            digest: TDigest::default(),
+            merge_inputs,
+            output,
+        }
+    }
+
+    fn set_quantile(&mut self, quantiles: &ArrayRef) -> Result<()> {
+        if let TDigestOutput::Quantile(q @ None) = &mut self.output {
+            let quantiles = cast(quantiles, &DataType::Float64)?;
+            if let Some(v) = quantiles
+                .as_primitive::<Float64Type>()
+                .iter()
+                .flatten()
+                .next()
+            {
+                *q = Some(check_quantile(v)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Accumulator for TDigestAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        let sketch = ScalarValue::Binary(Some(self.digest.to_bytes()));
+        Ok(match self.output {
+            TDigestOutput::Sketch => vec![sketch],
+            TDigestOutput::Quantile(q) => vec![sketch, ScalarValue::Float64(q)],
+        })
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if self.merge_inputs {
+            return for_each_sketch(&values[0], |sketch| {
+                self.digest.merge(&TDigest::try_from_bytes(sketch)?);
+                Ok(())
+            });
+        }
+        if let Some(quantiles) = values.get(1) {
+            self.set_quantile(quantiles)?;
+        }
+
+        let values = cast(&values[0], &DataType::Float64)?;
+        for v in values.as_primitive::<Float64Type>().iter().flatten() {
+            self.digest.add(v);
+        }
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        if let Some(quantiles) = states.get(1) {
+            self.set_quantile(quantiles)?;
+        }
+        for_each_sketch(&states[0], |sketch| {
+            self.digest.merge(&TDigest::try_from_bytes(sketch)?);
+            Ok(())
+        })
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        Ok(match self.output {
+            TDigestOutput::Sketch => ScalarValue::Binary(Some(self.digest.to_bytes())),
+            TDigestOutput::Quantile(q) => {
+                ScalarValue::Float64(q.and_then(|q| self.digest.quantile(q)))
+            }
+        })
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.digest.size()
+    }
+}
+
+fn check_quantile(q: f64) -> Result<f64, BuiltinError> {
+    if (0.0..=1.0).contains(&q) {
+        Ok(q)
+    } else {
+        Err(BuiltinError::InvalidValue(format!(
+            "quantile must be between 0 and 1, got {q}"
+        )))
+    }
+}
+
+/// Call `f` with each non-null sketch in the array.
+fn for_each_sketch(sketches: &ArrayRef, mut f: impl FnMut(&[u8]) -> Result<()>) -> Result<()> {
+    let sketches = cast(sketches, &DataType::Binary)?;
+    for sketch in sketches.as_binary::<i32>().iter().flatten() {
+        f(sketch)?;
+    }
+    Ok(())
+}
+
+/// Call `f` with the hash of each non-null value in the array.
+///
+/// Values are normalized before hashing so that equal values of different
+/// types (e.g. an Int32 and an Int64) hash the same, keeping sketches built
+/// from differently typed columns mergeable.
+fn for_each_hash(values: &ArrayRef, mut f: impl FnMut(u64)) -> Result<()> {
+    match values.data_type() {
+        DataType::Dictionary(_, value_type) => {
+            let values = cast(values, value_type)?;
+            for_each_hash(&values, f)?;
+        }
+        // Non-negative signed and unsigned integers have the same little
+        // endian bytes.
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+            let values = cast(values, &DataType::Int64)?;
+            for v in values.as_primitive::<Int64Type>().iter().flatten() {
+                f(hash_bytes(&v.to_le_bytes()));
+            }
+        }
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            let values = cast(values, &DataType::UInt64)?;
+            for v in values.as_primitive::<UInt64Type>().iter().flatten() {
+                f(hash_bytes(&v.to_le_bytes()));
+            }
+        }
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+            let values = cast(values, &DataType::Float64)?;
+            for v in values.as_primitive::<Float64Type>().iter().flatten() {
+                // -0.0 and 0.0 are the same value, as are all NaNs.
+                let v = if v == 0.0 {
+                    0.0
+                } else if v.is_nan() {
+                    f64::NAN
+                } else {
+                    v
+                };
+                f(hash_bytes(&v.to_bits().to_le_bytes()));
+            }
+        }
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => {
+            let values = cast(values, &DataType::Binary)?;
+            for v in values.as_binary::<i32>().iter().flatten() {
+                f(hash_bytes(v));
+            }
+        }
+        _ => {
+            let values = cast(values, &DataType::Utf8)?;
+            for v in values.as_string::<i32>().iter().flatten() {
+                f(hash_bytes(v.as_bytes()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = SipHasher24::new();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// Apply `op` to each row of the arguments.
+///
+/// Returns a scalar if all arguments are scalars.
+fn map_rows(
+    args: &[ColumnarValue],
+    return_type: &DataType,
+    op: impl Fn(&[ScalarValue]) -> Result<ScalarValue>,
+) -> Result<ColumnarValue> {
+    let num_rows = args.iter().find_map(|arg| match arg {
+        ColumnarValue::Array(arr) => Some(arr.len()),
+        ColumnarValue::Scalar(_) => None,
+    });
+
+    let num_rows = match num_rows {
+        Some(0) => return Ok(ColumnarValue::Array(new_empty_array(return_type))),
+        Some(num_rows) => num_rows,
+        None => {
+            let row: Vec<_> = args
+                .iter()
+                .map(|arg| match arg {
+                    ColumnarValue::Scalar(scalar) => scalar.clone(),
+                    ColumnarValue::Array(_) => unreachable!("all arguments are scalars"),
+                })
+                .collect();
+            return Ok(ColumnarValue::Scalar(op(&row)?));
+        }
+    };
+
+    let rows = (0..num_rows)
+        .map(|idx| {
+            let row = args
+                .iter()
+                .map(|arg| match arg {
+                    ColumnarValue::Array(arr) => ScalarValue::try_from_array(arr, idx),
+                    ColumnarValue::Scalar(scalar) => Ok(scalar.clone()),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            op(&row)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(ColumnarValue::Array(ScalarValue::iter_to_array(rows)?))
+}
@@ -0,0 +1,339 @@
+//! t-digest sketches for estimating quantiles.
+
+use std::f64::consts::PI;
+
+use crate::errors::{BuiltinError, Result};
+
+/// Magic bytes at the start of a serialized sketch.
+const MAGIC: &[u8; 4] = b"TDG1";
+
+/// Default compression, bounding the number of centroids kept.
+pub const DEFAULT_COMPRESSION: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A merging t-digest.
+///
+/// Values are buffered and merged into centroids once enough have been added.
+/// Centroids near the tails hold fewer values than those near the median,
+/// keeping the estimates for extreme quantiles accurate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    /// Values and centroids not yet merged into `centroids`.
+    unmerged: Vec<Centroid>,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(DEFAULT_COMPRESSION)
+    }
+}
+
+impl TDigest {
+    pub fn new(compression: f64) -> Self {
+        TDigest {
+            compression,
+            centroids: Vec::new(),
+            unmerged: Vec::new(),
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.centroids.is_empty() && self.unmerged.is_empty()
+    }
+
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.add_centroid(Centroid {
+            mean: value,
+            weight: 1.0,
+        });
+    }
+
+    fn add_centroid(&mut self, centroid: Centroid) {
+        self.min = self.min.min(centroid.mean);
+        self.max = self.max.max(centroid.mean);
+        self.unmerged.push(centroid);
+        if self.unmerged.len() >= self.max_unmerged() {
+            self.compress();
+        }
+    }
+
+    fn max_unmerged(&self) -> usize {
+        (self.compression as usize * 5).max(16)
+    }
+
+    /// Merge another sketch into this one.
+    pub fn merge(&mut self, other: &TDigest) {
+        for centroid in other.centroids.iter().chain(&other.unmerged) {
+            self.add_centroid(*centroid);
+        }
+    }
+
+    /// Merge buffered values into the centroids.
+    pub fn compress(&mut self) {
+        if self.unmerged.is_empty() {
+            return;
+        }
+
+        let mut all = std::mem::take(&mut self.centroids);
+        all.append(&mut self.unmerged);
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let total: f64 = all.iter().map(|c| c.weight).sum();
+        let mut merged = Vec::with_capacity(all.len().min(self.compression as usize * 2));
+        let mut all = all.into_iter();
+        let mut current = all.next().unwrap();
+        let mut weight_before = 0.0;
+        let mut limit = total * self.q_limit(0.0);
+
+        for centroid in all {
+            if weight_before + current.weight + centroid.weight <= limit {
+                let weight = current.weight + centroid.weight;
+                current.mean += (centroid.mean - current.mean) * centroid.weight / weight;
+                current.weight = weight;
+            } else {
+                weight_before += current.weight;
+                merged.push(current);
+                limit = total * self.q_limit(weight_before / total);
+                current = centroid;
+            }
+        }
+        merged.push(current);
+
+        self.centroids = merged;
+    }
+
+    /// Largest quantile a centroid starting at quantile `q` may extend to,
+    /// using the `k1` scale function.
+    fn q_limit(&self, q: f64) -> f64 {
+        let k = self.compression / (2.0 * PI) * (2.0 * q - 1.0).asin() + 1.0;
+        if k >= self.compression / 4.0 {
+            return 1.0;
+        }
+        (((k * 2.0 * PI / self.compression).sin() + 1.0) / 2.0).min(1.0)
+    }
+
+    /// Estimate the value at quantile `q`, between 0 and 1.
+    ///
+    /// Returns `None` if no values have been added.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let mut digest = self.clone();
+        digest.compress();
+        digest.quantile_compressed(q)
+    }
+
+    fn quantile_compressed(&self, q: f64) -> Option<f64> {
+        let centroids = &self.centroids;
+        if centroids.is_empty() {
+            return None;
+        }
+        if q <= 0.0 {
+            return Some(self.min);
+        }
+        if q >= 1.0 {
+            return Some(self.max);
+        }
+        if centroids.len() == 1 {
+            return Some(centroids[0].mean);
+        }
+
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let target = q * total;
+
+        // Interpolate between the centers of neighbouring centroids, treating
+        // the min and max as centroids at the very ends.
+        let mut prev_position = 0.0;
+        let mut prev_value = self.min;
+        let mut weight_before = 0.0;
+        for centroid in centroids {
+            let position = weight_before + centroid.weight / 2.0;
+            if target < position {
+                return Some(interpolate(
+                    prev_position,
+                    prev_value,
+                    position,
+                    centroid.mean,
+                    target,
+                ));
+            }
+            prev_position = position;
+            prev_value = centroid.mean;
+            weight_before += centroid.weight;
+        }
+
+        Some(interpolate(
+            prev_position,
+            prev_value,
+            total,
+            self.max,
+            target,
+        ))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut digest = self.clone();
+        digest.compress();
+
+        let mut buf = Vec::with_capacity(MAGIC.len() + 8 * 3 + 4 + digest.centroids.len() * 16);
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&digest.compression.to_le_bytes());
+        buf.extend_from_slice(&digest.min.to_le_bytes());
+        buf.extend_from_slice(&digest.max.to_le_bytes());
+        buf.extend_from_slice(&(digest.centroids.len() as u32).to_le_bytes());
+        for centroid in &digest.centroids {
+            buf.extend_from_slice(&centroid.mean.to_le_bytes());
+            buf.extend_from_slice(&centroid.weight.to_le_bytes());
+        }
+        buf
+    }
+
+    pub fn try_from_bytes(buf: &[u8]) -> Result<Self> {
+        let invalid = || BuiltinError::InvalidValue("invalid t-digest sketch".to_string());
+
+        let buf = buf.strip_prefix(MAGIC.as_slice()).ok_or_else(invalid)?;
+        let mut reader = Reader { buf };
+        let compression = reader.f64().ok_or_else(invalid)?;
+        let min = reader.f64().ok_or_else(invalid)?;
+        let max = reader.f64().ok_or_else(invalid)?;
+        let len = reader.u32().ok_or_else(invalid)? as usize;
+        if compression.is_nan() || compression < 1.0 || reader.buf.len() != len * 16 {
+            return Err(invalid());
+        }
+
+        let mut centroids = Vec::with_capacity(len);
+        for _ in 0..len {
+            let mean = reader.f64().ok_or_else(invalid)?;
+            let weight = reader.f64().ok_or_else(invalid)?;
+            centroids.push(Centroid { mean, weight });
+        }
+
+        Ok(TDigest {
+            compression,
+            centroids,
+            unmerged: Vec::new(),
+            min,
+            max,
+        })
+    }
+
+    /// Approximate size in bytes.
+    pub fn size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + (self.centroids.capacity() + self.unmerged.capacity())
+                * std::mem::size_of::<Centroid>()
+    }
+}
+
+fn interpolate(x0: f64, y0: f64, x1: f64, y1: f64, x: f64) -> f64 {
+    if x1 <= x0 {
+        return y1;
+    }
+    y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        if self.buf.len() < N {
+            return None;
+        }
+        let (bytes, rest) = self.buf.split_at(N);
+        self.buf = rest;
+        bytes.try_into().ok()
+    }
+
+    fn f64(&mut self) -> Option<f64> {
+        self.take().map(f64::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+        assert!(
+            (expected - actual).abs() <= tolerance,
+            "expected about {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn quantiles() {
+        let mut digest = TDigest::default();
+        // Add out of order to make sure values are sorted.
+        for v in (0..10_000).rev() {
+            digest.add(v as f64);
+        }
+
+        assert_eq!(Some(0.0), digest.quantile(0.0));
+        assert_eq!(Some(9999.0), digest.quantile(1.0));
+        assert_close(5000.0, digest.quantile(0.5).unwrap(), 50.0);
+        assert_close(9900.0, digest.quantile(0.99).unwrap(), 10.0);
+        assert_close(100.0, digest.quantile(0.01).unwrap(), 10.0);
+    }
+
+    #[test]
+    fn empty_and_single() {
+        let mut digest = TDigest::default();
+        assert_eq!(None, digest.quantile(0.5));
+
+        digest.add(f64::NAN);
+        assert!(digest.is_empty());
+
+        digest.add(3.0);
+        assert_eq!(Some(3.0), digest.quantile(0.5));
+    }
+
+    #[test]
+    fn merge() {
+        let mut a = TDigest::default();
+        let mut b = TDigest::default();
+        for v in 0..5000 {
+            a.add(v as f64);
+        }
+        for v in 5000..10_000 {
+            b.add(v as f64);
+        }
+
+        a.merge(&b);
+        assert_eq!(Some(0.0), a.quantile(0.0));
+        assert_eq!(Some(9999.0), a.quantile(1.0));
+        assert_close(5000.0, a.quantile(0.5).unwrap(), 50.0);
+    }
+
+    #[test]
+    fn roundtrip_bytes() {
+        let mut digest = TDigest::default();
+        for v in 0..1000 {
+            digest.add(v as f64);
+        }
+        digest.compress();
+
+        let bytes = digest.to_bytes();
+        assert_eq!(digest, TDigest::try_from_bytes(&bytes).unwrap());
+
+        assert!(TDigest::try_from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(TDigest::try_from_bytes(b"not a sketch").is_err());
+    }
+}
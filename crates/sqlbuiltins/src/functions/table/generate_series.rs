@@ -6,8 +6,13 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use async_trait::async_trait;
-use datafusion::arrow::array::{Array, Decimal128Array, Int64Array};
-use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use chrono::{Duration, Months, NaiveDate, NaiveDateTime};
+use datafusion::arrow::array::{
+    Array, Date32Array, Decimal128Array, Int64Array, TimestampNanosecondArray,
+};
+use datafusion::arrow::datatypes::{
+    DataType, Field, IntervalDayTimeType, IntervalMonthDayNanoType, IntervalUnit, Schema, TimeUnit,
+};
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::common::Result as DataFusionResult;
 use datafusion::datasource::streaming::StreamingTable;
@@ -16,6 +21,7 @@ use datafusion::execution::TaskContext;
 use datafusion::logical_expr::{Signature, TypeSignature, Volatility};
 use datafusion::physical_plan::streaming::PartitionStream;
 use datafusion::physical_plan::{RecordBatchStream, SendableRecordBatchStream};
+use datafusion::scalar::ScalarValue;
 use datafusion_ext::errors::{ExtensionError, Result};
 use datafusion_ext::functions::{FuncParamValue, TableFuncContextProvider};
 use decimal::Decimal128;
@@ -31,17 +37,12 @@ pub struct GenerateSeries;
 
 impl ConstBuiltinFunction for GenerateSeries {
     const NAME: &'static str = "generate_series";
-    const DESCRIPTION: &'static str = "Generate a series of values";
+    const DESCRIPTION: &'static str =
+        "Generate a series of values from start to stop inclusive, with an interval step for timestamps and dates";
     const EXAMPLE: &'static str = "SELECT * FROM generate_series(1, 10, 2)";
     const FUNCTION_TYPE: FunctionType = FunctionType::TableReturning;
     fn signature(&self) -> Option<Signature> {
-        Some(Signature::new(
-            TypeSignature::OneOf(vec![
-                TypeSignature::Uniform(2, vec![DataType::Int64, DataType::Decimal128(38, 0)]),
-                TypeSignature::Uniform(3, vec![DataType::Int64, DataType::Decimal128(38, 0)]),
-            ]),
-            Volatility::Immutable,
-        ))
+        Some(series_signature(false))
     }
 }
 
@@ -64,73 +65,160 @@ impl TableFunc for GenerateSeries {
         args: Vec<FuncParamValue>,
         _: HashMap<String, FuncParamValue>,
     ) -> Result<Arc<dyn TableProvider>> {
-        match args.len() {
-            2 => {
-                let mut args = args.into_iter();
-                let start = args.next().unwrap();
-                let stop = args.next().unwrap();
-
-                if start.is_valid::<i64>() || stop.is_valid::<i64>() {
-                    create_straming_table::<GenerateSeriesTypeInt>(
-                        GenerateSeriesTypeInt,
-                        start.try_into()?,
-                        stop.try_into()?,
-                        1,
-                    )
-                } else if start.is_valid::<Decimal128>() && stop.is_valid::<Decimal128>() {
-                    let start: Decimal128 = start.try_into()?;
-                    let stop: Decimal128 = stop.try_into()?;
-                    let step = Decimal128::new(1, 0)?;
-                    let scale = [start, stop, step].iter().map(|s| s.scale()).max().unwrap();
-                    create_straming_table::<GenerateSeriesTypeDecimal128>(
-                        GenerateSeriesTypeDecimal128 { scale },
-                        start,
-                        stop,
-                        step,
-                    )
-                } else {
-                    return Err(ExtensionError::InvalidParamValue {
-                        param: format!("({start}, {stop})"),
-                        expected: "integers or floats",
-                    });
-                }
+        create_series(args, Self::NAME, true)
+    }
+}
+
+/// Like `generate_series`, but excluding the stop value.
+#[derive(Debug, Clone, Copy)]
+pub struct Range;
+
+impl ConstBuiltinFunction for Range {
+    const NAME: &'static str = "range";
+    const DESCRIPTION: &'static str =
+        "Generate a series of values from start (default 0) up to but excluding stop, with an interval step for timestamps and dates";
+    const EXAMPLE: &'static str = "SELECT * FROM range(0, 10, 2)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::TableReturning;
+    fn signature(&self) -> Option<Signature> {
+        Some(series_signature(true))
+    }
+}
+
+#[async_trait]
+impl TableFunc for Range {
+    fn detect_runtime(
+        &self,
+        _: &[FuncParamValue],
+        parent: RuntimePreference,
+    ) -> Result<RuntimePreference> {
+        Ok(match parent {
+            RuntimePreference::Unspecified => RuntimePreference::Local,
+            other => other,
+        })
+    }
+
+    async fn create_provider(
+        &self,
+        _: &dyn TableFuncContextProvider,
+        mut args: Vec<FuncParamValue>,
+        _: HashMap<String, FuncParamValue>,
+    ) -> Result<Arc<dyn TableProvider>> {
+        if args.len() == 1 {
+            args.insert(0, FuncParamValue::Scalar(ScalarValue::Int64(Some(0))));
+        }
+        create_series(args, Self::NAME, false)
+    }
+}
+
+fn series_signature(allow_stop_only: bool) -> Signature {
+    let numeric = vec![DataType::Int64, DataType::Decimal128(38, 0)];
+    let interval = DataType::Interval(IntervalUnit::MonthDayNano);
+    let timestamp = DataType::Timestamp(TimeUnit::Nanosecond, None);
+    let mut signatures = Vec::new();
+    if allow_stop_only {
+        signatures.push(TypeSignature::Uniform(1, numeric.clone()));
+    }
+    signatures.extend([
+        TypeSignature::Uniform(2, numeric.clone()),
+        TypeSignature::Uniform(3, numeric),
+        TypeSignature::Exact(vec![timestamp.clone(), timestamp, interval.clone()]),
+        TypeSignature::Exact(vec![DataType::Date32, DataType::Date32, interval]),
+    ]);
+    Signature::new(TypeSignature::OneOf(signatures), Volatility::Immutable)
+}
+
+/// Create the table for a series named `name` from the function arguments.
+fn create_series(
+    args: Vec<FuncParamValue>,
+    name: &'static str,
+    inclusive: bool,
+) -> Result<Arc<dyn TableProvider>> {
+    match args.len() {
+        2 => {
+            let mut args = args.into_iter();
+            let start = args.next().unwrap();
+            let stop = args.next().unwrap();
+
+            if start.is_valid::<i64>() || stop.is_valid::<i64>() {
+                create_straming_table::<GenerateSeriesTypeInt>(
+                    GenerateSeriesTypeInt,
+                    start.try_into()?,
+                    stop.try_into()?,
+                    1,
+                    name,
+                    inclusive,
+                )
+            } else if start.is_valid::<Decimal128>() && stop.is_valid::<Decimal128>() {
+                let start: Decimal128 = start.try_into()?;
+                let stop: Decimal128 = stop.try_into()?;
+                let step = Decimal128::new(1, 0)?;
+                let scale = [start, stop, step].iter().map(|s| s.scale()).max().unwrap();
+                create_straming_table::<GenerateSeriesTypeDecimal128>(
+                    GenerateSeriesTypeDecimal128 { scale },
+                    start,
+                    stop,
+                    step,
+                    name,
+                    inclusive,
+                )
+            } else if TemporalValue::try_from(start.clone()).is_ok() {
+                Err(ExtensionError::String(
+                    "'step' is required for timestamps and dates".to_string(),
+                ))
+            } else {
+                Err(ExtensionError::InvalidParamValue {
+                    param: format!("({start}, {stop})"),
+                    expected: "integers or floats",
+                })
             }
-            3 => {
-                let mut args = args.into_iter();
-                let start = args.next().unwrap();
-                let stop = args.next().unwrap();
-                let step = args.next().unwrap();
-
-                if start.is_valid::<i64>() && stop.is_valid::<i64>() && step.is_valid::<i64>() {
-                    create_straming_table::<GenerateSeriesTypeInt>(
-                        GenerateSeriesTypeInt,
-                        start.try_into()?,
-                        stop.try_into()?,
-                        step.try_into()?,
-                    )
-                } else if start.is_valid::<Decimal128>()
-                    && stop.is_valid::<Decimal128>()
-                    && step.is_valid::<Decimal128>()
-                {
-                    let start: Decimal128 = start.try_into()?;
-                    let stop: Decimal128 = stop.try_into()?;
-                    let step: Decimal128 = step.try_into()?;
-                    let scale = [start, stop, step].iter().map(|s| s.scale()).max().unwrap();
-                    create_straming_table::<GenerateSeriesTypeDecimal128>(
-                        GenerateSeriesTypeDecimal128 { scale },
-                        start,
-                        stop,
-                        step,
-                    )
-                } else {
-                    return Err(ExtensionError::InvalidParamValue {
-                        param: format!("({start}, {stop}, {step})"),
-                        expected: "integers or floats",
-                    });
-                }
+        }
+        3 => {
+            let mut args = args.into_iter();
+            let start = args.next().unwrap();
+            let stop = args.next().unwrap();
+            let step = args.next().unwrap();
+
+            if start.is_valid::<i64>() && stop.is_valid::<i64>() && step.is_valid::<i64>() {
+                create_straming_table::<GenerateSeriesTypeInt>(
+                    GenerateSeriesTypeInt,
+                    start.try_into()?,
+                    stop.try_into()?,
+                    step.try_into()?,
+                    name,
+                    inclusive,
+                )
+            } else if start.is_valid::<Decimal128>()
+                && stop.is_valid::<Decimal128>()
+                && step.is_valid::<Decimal128>()
+            {
+                let start: Decimal128 = start.try_into()?;
+                let stop: Decimal128 = stop.try_into()?;
+                let step: Decimal128 = step.try_into()?;
+                let scale = [start, stop, step].iter().map(|s| s.scale()).max().unwrap();
+                create_straming_table::<GenerateSeriesTypeDecimal128>(
+                    GenerateSeriesTypeDecimal128 { scale },
+                    start,
+                    stop,
+                    step,
+                    name,
+                    inclusive,
+                )
+            } else if step.is_valid::<IntervalStep>() {
+                create_temporal_table(
+                    start.try_into()?,
+                    stop.try_into()?,
+                    step.try_into()?,
+                    name,
+                    inclusive,
+                )
+            } else {
+                Err(ExtensionError::InvalidParamValue {
+                    param: format!("({start}, {stop}, {step})"),
+                    expected: "integers, floats, or timestamps or dates with an interval",
+                })
             }
-            _ => return Err(ExtensionError::InvalidNumArgs),
         }
+        _ => Err(ExtensionError::InvalidNumArgs),
     }
 }
 
@@ -139,13 +227,15 @@ fn create_straming_table<T: GenerateSeriesType>(
     start: T::PrimType,
     stop: T::PrimType,
     step: T::PrimType,
+    name: &'static str,
+    inclusive: bool,
 ) -> Result<Arc<dyn TableProvider>> {
     if step.is_zero() {
         return Err(ExtensionError::String("'step' may not be zero".to_string()));
     }
 
     let partition: GenerateSeriesPartition<T> =
-        GenerateSeriesPartition::new(gen_series_type, start, stop, step);
+        GenerateSeriesPartition::new(gen_series_type, start, stop, step, name, inclusive);
     let table = StreamingTable::try_new(partition.schema().clone(), vec![Arc::new(partition)])?;
 
     Ok(Arc::new(table))
@@ -200,24 +290,33 @@ struct GenerateSeriesPartition<T: GenerateSeriesType> {
     start: T::PrimType,
     stop: T::PrimType,
     step: T::PrimType,
+    inclusive: bool,
 }
 
 impl<T: GenerateSeriesType> GenerateSeriesPartition<T> {
-    fn new(gen_series_type: T, start: T::PrimType, stop: T::PrimType, step: T::PrimType) -> Self {
+    fn new(
+        gen_series_type: T,
+        start: T::PrimType,
+        stop: T::PrimType,
+        step: T::PrimType,
+        name: &'static str,
+        inclusive: bool,
+    ) -> Self {
         GenerateSeriesPartition {
-            schema: Arc::new(Schema::new([Arc::new(Field::new(
-                "generate_series",
-                gen_series_type.arrow_type(),
-                false,
-            ))])),
+            schema: series_schema(name, gen_series_type.arrow_type()),
             start,
             stop,
             step,
+            inclusive,
             gen_series_type: Arc::new(gen_series_type),
         }
     }
 }
 
+fn series_schema(name: &str, data_type: DataType) -> Arc<Schema> {
+    Arc::new(Schema::new([Arc::new(Field::new(name, data_type, false))]))
+}
+
 impl<T: GenerateSeriesType> PartitionStream for GenerateSeriesPartition<T> {
     fn schema(&self) -> &Arc<Schema> {
         &self.schema
@@ -231,6 +330,7 @@ impl<T: GenerateSeriesType> PartitionStream for GenerateSeriesPartition<T> {
             curr: self.start,
             stop: self.stop,
             step: self.step,
+            inclusive: self.inclusive,
         })
     }
 }
@@ -242,9 +342,22 @@ struct GenerateSeriesStream<T: GenerateSeriesType> {
     curr: T::PrimType,
     stop: T::PrimType,
     step: T::PrimType,
+    inclusive: bool,
 }
 
 impl<T: GenerateSeriesType> GenerateSeriesStream<T> {
+    /// Whether the current value is part of the series.
+    fn in_range(&self) -> bool {
+        if self.curr == self.stop {
+            return self.inclusive;
+        }
+        if self.step > T::PrimType::zero() {
+            self.curr < self.stop
+        } else {
+            self.curr > self.stop
+        }
+    }
+
     fn generate_next(&mut self) -> Option<RecordBatch> {
         if self.exhausted {
             return None;
@@ -253,33 +366,15 @@ impl<T: GenerateSeriesType> GenerateSeriesStream<T> {
         const BATCH_SIZE: usize = 1000;
 
         let mut series: Vec<_> = Vec::new();
-        if self.curr < self.stop && self.step > T::PrimType::zero() {
-            // Going up.
-            let mut count = 0;
-            while self.curr <= self.stop && count < BATCH_SIZE {
-                series.push(self.curr);
-                self.curr += self.step;
-                count += 1;
-            }
-        } else if self.curr > self.stop && self.step < T::PrimType::zero() {
-            // Going down.
-            let mut count = 0;
-            while self.curr >= self.stop && count < BATCH_SIZE {
-                series.push(self.curr);
-                self.curr += self.step;
-                count += 1;
-            }
+        while series.len() < BATCH_SIZE && self.in_range() {
+            series.push(self.curr);
+            self.curr += self.step;
         }
 
         if series.len() < BATCH_SIZE {
             self.exhausted = true
         }
 
-        // Calculate the start value for the next iteration.
-        if let Some(last) = series.last() {
-            self.curr = *last + self.step;
-        }
-
         let arrow_dt = self.gen_series_type.arrow_type();
         let arr = self.gen_series_type.collect_array(series);
         assert_eq!(arr.data_type(), &arrow_dt);
@@ -300,3 +395,278 @@ impl<T: GenerateSeriesType> RecordBatchStream for GenerateSeriesStream<T> {
         self.schema.clone()
     }
 }
+
+/// A timestamp or date start or stop value of a series.
+#[derive(Debug, Clone)]
+enum TemporalValue {
+    /// Days since the epoch.
+    Date(i32),
+    /// Nanoseconds since the epoch, with an optional time zone.
+    Timestamp(i64, Option<Arc<str>>),
+}
+
+impl TemporalValue {
+    fn to_datetime(&self) -> Result<NaiveDateTime> {
+        let datetime = match self {
+            Self::Date(days) => NaiveDate::default()
+                .checked_add_signed(Duration::days(*days as i64))
+                .map(|date| date.and_time(Default::default())),
+            Self::Timestamp(nanos, _) => NaiveDateTime::from_timestamp_opt(
+                nanos.div_euclid(1_000_000_000),
+                nanos.rem_euclid(1_000_000_000) as u32,
+            ),
+        };
+        datetime.ok_or_else(|| ExtensionError::String(format!("{self:?} is out of range")))
+    }
+}
+
+impl TryFrom<FuncParamValue> for TemporalValue {
+    type Error = ExtensionError;
+
+    fn try_from(value: FuncParamValue) -> Result<Self> {
+        let invalid = |param: String| ExtensionError::InvalidParamValue {
+            param,
+            expected: "timestamp or date",
+        };
+        let scalar = match value {
+            FuncParamValue::Scalar(scalar) => scalar,
+            other => return Err(invalid(other.to_string())),
+        };
+        let timezone = match &scalar {
+            ScalarValue::Date32(Some(days)) => return Ok(Self::Date(*days)),
+            ScalarValue::Date64(Some(millis)) => {
+                return Ok(Self::Date(millis.div_euclid(86_400_000) as i32))
+            }
+            ScalarValue::TimestampSecond(Some(_), tz)
+            | ScalarValue::TimestampMillisecond(Some(_), tz)
+            | ScalarValue::TimestampMicrosecond(Some(_), tz)
+            | ScalarValue::TimestampNanosecond(Some(_), tz) => tz.clone(),
+            ScalarValue::Utf8(Some(_)) | ScalarValue::LargeUtf8(Some(_)) => None,
+            other => return Err(invalid(other.to_string())),
+        };
+        match scalar.cast_to(&DataType::Timestamp(TimeUnit::Nanosecond, timezone)) {
+            Ok(ScalarValue::TimestampNanosecond(Some(nanos), tz)) => Ok(Self::Timestamp(nanos, tz)),
+            _ => Err(invalid(scalar.to_string())),
+        }
+    }
+}
+
+/// An interval step of a timestamp or date series.
+#[derive(Debug, Clone, Copy)]
+struct IntervalStep {
+    months: i32,
+    days: i32,
+    nanos: i64,
+}
+
+impl IntervalStep {
+    /// Get the value `n` steps after `start`, if it's in range.
+    fn nth_after(&self, start: NaiveDateTime, n: i64) -> Option<NaiveDateTime> {
+        let months = (self.months as i64).checked_mul(n)?;
+        let datetime = if months >= 0 {
+            start.checked_add_months(Months::new(u32::try_from(months).ok()?))?
+        } else {
+            start.checked_sub_months(Months::new(u32::try_from(-months).ok()?))?
+        };
+        let datetime =
+            datetime.checked_add_signed(Duration::days((self.days as i64).checked_mul(n)?))?;
+        datetime.checked_add_signed(Duration::nanoseconds(self.nanos.checked_mul(n)?))
+    }
+}
+
+impl TryFrom<FuncParamValue> for IntervalStep {
+    type Error = ExtensionError;
+
+    fn try_from(value: FuncParamValue) -> Result<Self> {
+        match value {
+            FuncParamValue::Scalar(ScalarValue::IntervalYearMonth(Some(months))) => {
+                Ok(IntervalStep {
+                    months,
+                    days: 0,
+                    nanos: 0,
+                })
+            }
+            FuncParamValue::Scalar(ScalarValue::IntervalDayTime(Some(v))) => {
+                let (days, millis) = IntervalDayTimeType::to_parts(v);
+                Ok(IntervalStep {
+                    months: 0,
+                    days,
+                    nanos: millis as i64 * 1_000_000,
+                })
+            }
+            FuncParamValue::Scalar(ScalarValue::IntervalMonthDayNano(Some(v))) => {
+                let (months, days, nanos) = IntervalMonthDayNanoType::to_parts(v);
+                Ok(IntervalStep {
+                    months,
+                    days,
+                    nanos,
+                })
+            }
+            other => Err(ExtensionError::InvalidParamValue {
+                param: other.to_string(),
+                expected: "interval",
+            }),
+        }
+    }
+}
+
+fn create_temporal_table(
+    start: TemporalValue,
+    stop: TemporalValue,
+    step: IntervalStep,
+    name: &'static str,
+    inclusive: bool,
+) -> Result<Arc<dyn TableProvider>> {
+    let parts = [step.months as i64, step.days as i64, step.nanos];
+    let ascending = if parts.iter().all(|part| *part == 0) {
+        return Err(ExtensionError::String("'step' may not be zero".to_string()));
+    } else if parts.iter().all(|part| *part >= 0) {
+        true
+    } else if parts.iter().all(|part| *part <= 0) {
+        false
+    } else {
+        return Err(ExtensionError::String(
+            "'step' may not mix positive and negative parts".to_string(),
+        ));
+    };
+
+    // Series of dates stay dates, unless stepping by part of a day.
+    let output = match (&start, &stop) {
+        (TemporalValue::Date(_), TemporalValue::Date(_)) if step.nanos == 0 => DataType::Date32,
+        (TemporalValue::Timestamp(_, tz), _) | (_, TemporalValue::Timestamp(_, tz)) => {
+            DataType::Timestamp(TimeUnit::Nanosecond, tz.clone())
+        }
+        _ => DataType::Timestamp(TimeUnit::Nanosecond, None),
+    };
+
+    let partition = TemporalSeriesPartition {
+        schema: series_schema(name, output),
+        start: start.to_datetime()?,
+        stop: stop.to_datetime()?,
+        step,
+        ascending,
+        inclusive,
+    };
+    let table = StreamingTable::try_new(partition.schema().clone(), vec![Arc::new(partition)])?;
+
+    Ok(Arc::new(table))
+}
+
+struct TemporalSeriesPartition {
+    schema: Arc<Schema>,
+    start: NaiveDateTime,
+    stop: NaiveDateTime,
+    step: IntervalStep,
+    ascending: bool,
+    inclusive: bool,
+}
+
+impl PartitionStream for TemporalSeriesPartition {
+    fn schema(&self) -> &Arc<Schema> {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        Box::pin(TemporalSeriesStream {
+            schema: self.schema.clone(),
+            exhausted: false,
+            start: self.start,
+            stop: self.stop,
+            step: self.step,
+            ascending: self.ascending,
+            inclusive: self.inclusive,
+            next: 0,
+        })
+    }
+}
+
+struct TemporalSeriesStream {
+    schema: Arc<Schema>,
+    exhausted: bool,
+    start: NaiveDateTime,
+    stop: NaiveDateTime,
+    step: IntervalStep,
+    ascending: bool,
+    inclusive: bool,
+    /// Number of steps from the start to the next value.
+    next: i64,
+}
+
+impl TemporalSeriesStream {
+    /// Get the next value of the series, if there is one.
+    ///
+    /// Values are computed from the start rather than the previous value so
+    /// month steps don't drift, e.g. stepping by a month from January 31st
+    /// goes to February 28th and then March 31st.
+    fn next_value(&mut self) -> Option<NaiveDateTime> {
+        let value = self.step.nth_after(self.start, self.next)?;
+        let in_range = match value.cmp(&self.stop) {
+            std::cmp::Ordering::Equal => self.inclusive,
+            std::cmp::Ordering::Less => self.ascending,
+            std::cmp::Ordering::Greater => !self.ascending,
+        };
+        // Timestamps are stored as nanoseconds, limiting their range.
+        let representable = matches!(self.schema.field(0).data_type(), DataType::Date32)
+            || value.timestamp_nanos_opt().is_some();
+        if !in_range || !representable {
+            return None;
+        }
+        self.next += 1;
+        Some(value)
+    }
+
+    fn generate_next(&mut self) -> Option<RecordBatch> {
+        if self.exhausted {
+            return None;
+        }
+
+        const BATCH_SIZE: usize = 1000;
+
+        let mut series = Vec::new();
+        while series.len() < BATCH_SIZE {
+            match self.next_value() {
+                Some(value) => series.push(value),
+                None => break,
+            }
+        }
+
+        if series.len() < BATCH_SIZE {
+            self.exhausted = true
+        }
+
+        let arr: Arc<dyn Array> = match self.schema.field(0).data_type() {
+            DataType::Date32 => {
+                let epoch = NaiveDate::default();
+                Arc::new(Date32Array::from_iter_values(
+                    series
+                        .iter()
+                        .map(|value| (value.date() - epoch).num_days() as i32),
+                ))
+            }
+            DataType::Timestamp(_, tz) => Arc::new(
+                TimestampNanosecondArray::from_iter_values(
+                    series
+                        .iter()
+                        .map(|value| value.timestamp_nanos_opt().unwrap()),
+                )
+                .with_timezone_opt(tz.clone()),
+            ),
+            other => unreachable!("unexpected series type {other}"),
+        };
+        let batch = RecordBatch::try_new(self.schema.clone(), vec![arr]).unwrap();
+        Some(batch)
+    }
+}
+
+impl Stream for TemporalSeriesStream {
+    type Item = DataFusionResult<RecordBatch>;
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().generate_next().map(Ok))
+    }
+}
+
+impl RecordBatchStream for TemporalSeriesStream {
+    fn schema(&self) -> Arc<Schema> {
+        self.schema.clone()
+    }
+}
@@ -12,10 +12,15 @@ mod mongodb;
 mod mysql;
 mod object_store;
 mod postgres;
+mod redis;
+mod redshift;
+mod sample;
+mod search_catalog;
 mod snowflake;
 mod sqlserver;
 mod system;
 mod virtual_listing;
+mod xml;
 
 use ::object_store::aws::AmazonS3ConfigKey;
 use ::object_store::azure::AzureConfigKey;
@@ -36,17 +41,25 @@ use self::cassandra::ReadCassandra;
 use self::clickhouse::ReadClickhouse;
 use self::delta::DeltaScan;
 use self::excel::ExcelScan;
-use self::generate_series::GenerateSeries;
+use self::generate_series::{GenerateSeries, Range};
 use self::iceberg::{data_files::IcebergDataFiles, scan::IcebergScan, snapshots::IcebergSnapshots};
 use self::lance::LanceScan;
 use self::mongodb::ReadMongoDb;
 use self::mysql::ReadMysql;
-use self::object_store::{CSV_SCAN, JSON_SCAN, PARQUET_SCAN, READ_CSV, READ_JSON, READ_PARQUET};
+use self::object_store::{
+    CSV_SCAN, JSON_SCAN, PARQUET_SCAN, READ_CSV, READ_JSON, READ_JSON_WITH_FORMAT, READ_PARQUET,
+};
 use self::postgres::ReadPostgres;
+use self::redis::ReadRedis;
+use self::redshift::ReadRedshift;
+use self::sample::SampleScan;
+use self::search_catalog::SearchCatalog;
 use self::snowflake::ReadSnowflake;
 use self::sqlserver::ReadSqlServer;
 use self::system::cache_external_tables::CacheExternalDatabaseTables;
+use self::system::reload_config::ReloadConfig;
 use self::virtual_listing::{ListColumns, ListSchemas, ListTables};
+use self::xml::XmlScan;
 
 use super::BuiltinFunction;
 
@@ -82,6 +95,7 @@ impl BuiltinTableFuncs {
         let funcs: Vec<Arc<dyn TableFunc>> = vec![
             // Databases/warehouses
             Arc::new(ReadPostgres),
+            Arc::new(ReadRedshift),
             Arc::new(ReadBigQuery),
             Arc::new(ReadMongoDb),
             Arc::new(ReadMysql),
@@ -89,6 +103,7 @@ impl BuiltinTableFuncs {
             Arc::new(ReadClickhouse),
             Arc::new(ReadSqlServer),
             Arc::new(ReadCassandra),
+            Arc::new(ReadRedis),
             // Object store
             Arc::new(PARQUET_SCAN),
             Arc::new(READ_PARQUET),
@@ -96,7 +111,10 @@ impl BuiltinTableFuncs {
             Arc::new(READ_CSV),
             Arc::new(JSON_SCAN),
             Arc::new(READ_JSON),
+            Arc::new(READ_JSON_WITH_FORMAT),
+            Arc::new(SampleScan),
             Arc::new(BsonScan),
+            Arc::new(XmlScan),
             // Data lakes
             Arc::new(DeltaScan),
             Arc::new(IcebergScan),
@@ -108,10 +126,13 @@ impl BuiltinTableFuncs {
             Arc::new(ListSchemas),
             Arc::new(ListTables),
             Arc::new(ListColumns),
+            Arc::new(SearchCatalog),
             // Series generating
             Arc::new(GenerateSeries),
+            Arc::new(Range),
             // System operations
             Arc::new(CacheExternalDatabaseTables),
+            Arc::new(ReloadConfig),
         ];
         let funcs: HashMap<String, Arc<dyn TableFunc>> = funcs
             .into_iter()
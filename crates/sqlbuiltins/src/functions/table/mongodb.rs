@@ -55,7 +55,7 @@ impl TableFunc for ReadMongoDb {
                 let database: String = args.next().unwrap().try_into()?;
                 let collection: String = args.next().unwrap().try_into()?;
 
-                let access = MongoDbAccessor::connect(&conn_str)
+                let access = MongoDbAccessor::connect(&conn_str, None)
                     .await
                     .map_err(|e| ExtensionError::Access(Box::new(e)))?;
                 let prov = access
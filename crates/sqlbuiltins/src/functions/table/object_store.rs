@@ -14,11 +14,15 @@ use datafusion::execution::object_store::ObjectStoreUrl;
 use datafusion::logical_expr::{Signature, Volatility};
 use datafusion_ext::errors::{ExtensionError, Result};
 use datafusion_ext::functions::{FuncParamValue, IdentValue, TableFuncContextProvider};
+use datafusion_ext::planner::parse_column_types;
 
 use datasources::common::url::{DatasourceUrl, DatasourceUrlType};
+use datasources::object_store::csv::CsvReadOptions;
 use datasources::object_store::gcs::GcsStoreAccess;
 use datasources::object_store::generic::GenericStoreAccess;
 use datasources::object_store::http::HttpStoreAccess;
+use datasources::object_store::inference::SchemaInference;
+use datasources::object_store::json::JsonReadOptions;
 use datasources::object_store::local::LocalStoreAccess;
 use datasources::object_store::s3::S3StoreAccess;
 use datasources::object_store::{MultiSourceTableProvider, ObjStoreAccess};
@@ -39,6 +43,8 @@ pub const READ_CSV: ObjScanTableFunc = ObjScanTableFunc(FileType::CSV, "read_csv
 
 pub const JSON_SCAN: ObjScanTableFunc = ObjScanTableFunc(FileType::JSON, "ndjson_scan");
 pub const READ_JSON: ObjScanTableFunc = ObjScanTableFunc(FileType::JSON, "read_ndjson");
+/// Reads newline delimited JSON, or a top-level array with `format => 'array'`.
+pub const READ_JSON_WITH_FORMAT: ObjScanTableFunc = ObjScanTableFunc(FileType::JSON, "read_json");
 
 #[derive(Debug, Clone)]
 pub struct ObjScanTableFunc(FileType, &'static str);
@@ -155,21 +161,70 @@ impl TableFunc for ObjScanTableFunc {
         };
 
         let Self(ft, _) = self;
-        let ft: Arc<dyn FileFormat> = match ft {
-            FileType::CSV => Arc::new(
-                CsvFormat::default()
-                    .with_file_compression_type(file_compression)
-                    .with_schema_infer_max_rec(Some(20480)),
-            ),
-            FileType::PARQUET => Arc::new(ParquetFormat::default()),
-            FileType::JSON => {
-                Arc::new(JsonFormat::default().with_file_compression_type(file_compression))
+        let csv_opts = match ft {
+            FileType::CSV => csv_read_options(&mut opts)?,
+            _ => CsvReadOptions::default(),
+        };
+        let (json_array, json_opts) = match ft {
+            FileType::JSON => (json_array_format(&mut opts)?, json_read_options(&mut opts)?),
+            _ => (false, JsonReadOptions::default()),
+        };
+        let inference = match ft {
+            FileType::CSV | FileType::JSON => schema_inference(ctx, &mut opts)?,
+            _ => SchemaInference::default(),
+        };
+
+        let unsupported_compressed = |opts: &str| {
+            ExtensionError::String(format!("{opts} not supported for compressed files"))
+        };
+        // Files are only read up front if DataFusion can't read them as is.
+        let reader = if csv_opts.requires_cleaning() {
+            if file_compression.is_compressed() {
+                return Err(unsupported_compressed(
+                    "on_bad_lines, max_line_length and encoding are",
+                ));
+            }
+            FileReader::CleanedCsv(csv_opts)
+        } else if json_array {
+            if json_opts.requires_inference() {
+                return Err(ExtensionError::String(
+                    "max_depth and map_inference_threshold are not supported for JSON arrays"
+                        .to_string(),
+                ));
             }
-            ft => {
-                return Err(ExtensionError::String(format!(
-                    "Unsuppored file type: {ft:?}"
-                )))
+            if file_compression.is_compressed() {
+                return Err(unsupported_compressed("JSON arrays are"));
             }
+            FileReader::JsonArray
+        } else if json_opts.requires_inference() {
+            if file_compression.is_compressed() {
+                return Err(unsupported_compressed(
+                    "max_depth and map_inference_threshold are",
+                ));
+            }
+            FileReader::NestedJson(json_opts)
+        } else {
+            let format: Arc<dyn FileFormat> = match ft {
+                FileType::CSV => Arc::new(
+                    csv_opts.with_format_options(
+                        CsvFormat::default()
+                            .with_file_compression_type(file_compression)
+                            .with_schema_infer_max_rec(Some(inference.csv_infer_rows())),
+                    ),
+                ),
+                FileType::PARQUET => Arc::new(ParquetFormat::default()),
+                FileType::JSON => Arc::new(
+                    JsonFormat::default()
+                        .with_file_compression_type(file_compression)
+                        .with_schema_infer_max_rec(inference.infer_rows),
+                ),
+                ft => {
+                    return Err(ExtensionError::String(format!(
+                        "Unsuppored file type: {ft:?}"
+                    )))
+                }
+            };
+            FileReader::Format(format)
         };
 
         // Optimize creating a table provider for objects by clubbing the same
@@ -197,7 +252,8 @@ impl TableFunc for ObjScanTableFunc {
         let o = fn_registry
             .into_values()
             .map(|(access, locations)| {
-                let provider = get_table_provider(ctx, ft.clone(), access, locations.into_iter());
+                let provider =
+                    get_table_provider(ctx, &reader, &inference, access, locations.into_iter());
                 provider
             })
             .collect::<futures::stream::FuturesUnordered<_>>()
@@ -209,25 +265,173 @@ impl TableFunc for ObjScanTableFunc {
     }
 }
 
+/// How the files of a table function are read.
+#[derive(Debug, Clone)]
+enum FileReader {
+    /// Read by DataFusion with the file format.
+    Format(Arc<dyn FileFormat>),
+    /// CSV files cleaned up front, see [`CsvReadOptions::requires_cleaning`].
+    CleanedCsv(CsvReadOptions),
+    /// Newline delimited JSON files with nested types inferred up front.
+    NestedJson(JsonReadOptions),
+    /// JSON files holding a single top-level array, streamed.
+    JsonArray,
+}
+
 /// Gets a table provider for the files at location.
 ///
 /// If the file is detected to be local, the table provider will be wrapped in a
 /// local table hint.
 async fn get_table_provider(
     ctx: &dyn TableFuncContextProvider,
-    ft: Arc<dyn FileFormat>,
+    reader: &FileReader,
+    inference: &SchemaInference,
     access: Arc<dyn ObjStoreAccess>,
     locations: impl Iterator<Item = DatasourceUrl>,
 ) -> Result<Arc<dyn TableProvider>> {
     let state = ctx.get_session_state();
-    let prov = access
-        .create_table_provider(&state, ft, locations.collect())
-        .await
-        .map_err(|e| ExtensionError::Access(Box::new(e)))?;
+    let locations = locations.collect();
+    let prov = match reader {
+        FileReader::Format(ft) => {
+            access
+                .create_table_provider(&state, ft.clone(), locations, inference)
+                .await
+        }
+        FileReader::CleanedCsv(opts) => {
+            access
+                .create_csv_table_provider(&state, opts, locations, inference)
+                .await
+        }
+        FileReader::NestedJson(opts) => {
+            access
+                .create_json_table_provider(opts, locations, inference)
+                .await
+        }
+        FileReader::JsonArray => {
+            access
+                .create_json_array_table_provider(locations, inference)
+                .await
+        }
+    }
+    .map_err(|e| ExtensionError::Access(Box::new(e)))?;
 
     Ok(prov)
 }
 
+/// Takes the options for reading CSV files from the named arguments.
+fn csv_read_options(opts: &mut HashMap<String, FuncParamValue>) -> Result<CsvReadOptions> {
+    let mut csv_opts = CsvReadOptions::default();
+    if let Some(delimiter) = take_char_opt(opts, "delimiter")? {
+        csv_opts.delimiter = delimiter;
+    }
+    if let Some(quote) = take_char_opt(opts, "quote")? {
+        csv_opts.quote = quote;
+    }
+    csv_opts.escape = take_char_opt(opts, "escape")?;
+    if let Some(on_bad_lines) = opts.remove("on_bad_lines") {
+        let on_bad_lines: String = on_bad_lines.try_into()?;
+        csv_opts.on_bad_lines = on_bad_lines.parse()?;
+    }
+    if let Some(max_line_length) = opts.remove("max_line_length") {
+        let max_line_length: i64 = max_line_length.try_into()?;
+        let max_line_length = usize::try_from(max_line_length)
+            .ok()
+            .filter(|len| *len > 0)
+            .ok_or_else(|| {
+                ExtensionError::String("max_line_length must be greater than zero".to_string())
+            })?;
+        csv_opts.max_line_length = Some(max_line_length);
+    }
+    if let Some(encoding) = opts.remove("encoding") {
+        let encoding: String = encoding.try_into()?;
+        csv_opts.encoding = encoding.parse()?;
+    }
+    Ok(csv_opts)
+}
+
+/// Takes the `format` of JSON files from the named arguments, returning
+/// whether the files hold a top-level array instead of newline delimited
+/// objects.
+fn json_array_format(opts: &mut HashMap<String, FuncParamValue>) -> Result<bool> {
+    let Some(format) = opts.remove("format") else {
+        return Ok(false);
+    };
+    let format: String = format.try_into()?;
+    match format.to_lowercase().as_str() {
+        "newline_delimited" | "ndjson" => Ok(false),
+        "array" => Ok(true),
+        _ => Err(ExtensionError::String(format!(
+            "format must be 'newline_delimited' or 'array', got '{format}'"
+        ))),
+    }
+}
+
+/// Takes the options for inferring nested JSON types from the named arguments.
+fn json_read_options(opts: &mut HashMap<String, FuncParamValue>) -> Result<JsonReadOptions> {
+    let mut json_opts = JsonReadOptions::default();
+    if let Some(max_depth) = opts.remove("max_depth") {
+        let max_depth: i64 = max_depth.try_into()?;
+        let max_depth = usize::try_from(max_depth)
+            .map_err(|_| ExtensionError::String("max_depth must not be negative".to_string()))?;
+        json_opts.max_depth = Some(max_depth);
+    }
+    if let Some(threshold) = opts.remove("map_inference_threshold") {
+        let threshold: i64 = threshold.try_into()?;
+        let threshold = usize::try_from(threshold)
+            .ok()
+            .filter(|threshold| *threshold > 0)
+            .ok_or_else(|| {
+                ExtensionError::String(
+                    "map_inference_threshold must be greater than zero".to_string(),
+                )
+            })?;
+        json_opts.map_inference_threshold = Some(threshold);
+    }
+    Ok(json_opts)
+}
+
+/// Takes the options for inferring the schema of CSV and JSON files from the
+/// named arguments.
+fn schema_inference(
+    ctx: &dyn TableFuncContextProvider,
+    opts: &mut HashMap<String, FuncParamValue>,
+) -> Result<SchemaInference> {
+    let mut inference = SchemaInference::default();
+    if let Some(infer_rows) = opts.remove("infer_rows") {
+        let infer_rows: i64 = infer_rows.try_into()?;
+        let infer_rows = usize::try_from(infer_rows)
+            .ok()
+            .filter(|rows| *rows > 0)
+            .ok_or_else(|| {
+                ExtensionError::String("infer_rows must be greater than zero".to_string())
+            })?;
+        inference.infer_rows = Some(infer_rows);
+    }
+    if let Some(columns) = opts.remove("columns") {
+        let columns: String = columns.try_into()?;
+        let vars = ctx.get_session_vars();
+        inference.column_types = parse_column_types(&columns, || Some(vars.timezone()))?;
+    }
+    if let Some(all_varchar) = opts.remove("all_varchar") {
+        inference.all_varchar = all_varchar.try_into()?;
+    }
+    Ok(inference)
+}
+
+/// Takes a named argument that must be a single ASCII character.
+fn take_char_opt(opts: &mut HashMap<String, FuncParamValue>, name: &str) -> Result<Option<u8>> {
+    let Some(value) = opts.remove(name) else {
+        return Ok(None);
+    };
+    let value: String = value.try_into()?;
+    match value.as_bytes() {
+        [c] if c.is_ascii() => Ok(Some(*c)),
+        _ => Err(ExtensionError::String(format!(
+            "{name} must be a single ASCII character, got '{value}'"
+        ))),
+    }
+}
+
 fn get_store_access(
     ctx: &dyn TableFuncContextProvider,
     source_url: &DatasourceUrl,
@@ -7,7 +7,9 @@ use datafusion::datasource::TableProvider;
 use datafusion::logical_expr::{Signature, Volatility};
 use datafusion_ext::errors::{ExtensionError, Result};
 use datafusion_ext::functions::{FuncParamValue, TableFuncContextProvider};
-use datasources::postgres::{PostgresAccess, PostgresTableProvider, PostgresTableProviderConfig};
+use datasources::postgres::{
+    NumericFallback, PostgresAccess, PostgresTableProvider, PostgresTableProviderConfig,
+};
 use protogen::metastore::types::catalog::{FunctionType, RuntimePreference};
 
 use super::TableFunc;
@@ -43,7 +45,7 @@ impl TableFunc for ReadPostgres {
 
     async fn create_provider(
         &self,
-        _: &dyn TableFuncContextProvider,
+        ctx: &dyn TableFuncContextProvider,
         args: Vec<FuncParamValue>,
         _opts: HashMap<String, FuncParamValue>,
     ) -> Result<Arc<dyn TableProvider>> {
@@ -54,7 +56,13 @@ impl TableFunc for ReadPostgres {
                 let schema: String = args.next().unwrap().try_into()?;
                 let table: String = args.next().unwrap().try_into()?;
 
-                let access = PostgresAccess::new_from_conn_str(conn_str, None);
+                let numeric_fallback = ctx
+                    .get_session_vars()
+                    .numeric_fallback()
+                    .parse::<NumericFallback>()
+                    .map_err(|e| ExtensionError::Access(Box::new(e)))?;
+                let access = PostgresAccess::new_from_conn_str(conn_str, None)
+                    .with_numeric_fallback(numeric_fallback);
                 let prov_conf = PostgresTableProviderConfig {
                     access,
                     schema,
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::datasource::TableProvider;
+use datafusion_ext::errors::{ExtensionError, Result};
+use datafusion_ext::functions::{FuncParamValue, TableFuncContextProvider};
+use datasources::redis::{redis_streaming_table, RedisKeyType, RedisScanOptions};
+use protogen::metastore::types::catalog::{FunctionType, RuntimePreference};
+
+use super::TableFunc;
+use crate::functions::ConstBuiltinFunction;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReadRedis;
+
+impl ConstBuiltinFunction for ReadRedis {
+    const NAME: &'static str = "read_redis";
+    const DESCRIPTION: &'static str =
+        "Read the keys matching a pattern from Redis. Supports string, hash, list and set keys.";
+    const EXAMPLE: &'static str =
+        "SELECT * FROM read_redis('redis://localhost:6379', pattern => 'user:*', type => 'hash')";
+    const FUNCTION_TYPE: FunctionType = FunctionType::TableReturning;
+}
+
+#[async_trait]
+impl TableFunc for ReadRedis {
+    fn detect_runtime(
+        &self,
+        _args: &[FuncParamValue],
+        _parent: RuntimePreference,
+    ) -> Result<RuntimePreference> {
+        Ok(RuntimePreference::Remote)
+    }
+
+    async fn create_provider(
+        &self,
+        _: &dyn TableFuncContextProvider,
+        args: Vec<FuncParamValue>,
+        mut opts: HashMap<String, FuncParamValue>,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let conn_string: String = match args.len() {
+            1 => args.into_iter().next().unwrap().try_into()?,
+            _ => return Err(ExtensionError::InvalidNumArgs),
+        };
+
+        let mut scan_opts = RedisScanOptions::default();
+        if let Some(pattern) = opts.remove("pattern") {
+            scan_opts.pattern = pattern.try_into()?;
+        }
+        if let Some(key_type) = opts.remove("type") {
+            let key_type: String = key_type.try_into()?;
+            scan_opts.key_type = key_type
+                .parse::<RedisKeyType>()
+                .map_err(|e| ExtensionError::Access(Box::new(e)))?;
+        }
+        if let Some(json) = opts.remove("json") {
+            scan_opts.json = json.try_into()?;
+        }
+
+        let sample_size = match opts.remove("schema_sample_size") {
+            Some(v) => {
+                let size: i64 = v.try_into()?;
+                let size = usize::try_from(size)
+                    .ok()
+                    .filter(|size| *size > 0)
+                    .ok_or_else(|| {
+                        ExtensionError::String(
+                            "schema_sample_size must be greater than zero".to_string(),
+                        )
+                    })?;
+                Some(size)
+            }
+            None => None,
+        };
+
+        redis_streaming_table(&conn_string, scan_opts, sample_size)
+            .await
+            .map_err(|e| ExtensionError::Access(Box::new(e)))
+    }
+}
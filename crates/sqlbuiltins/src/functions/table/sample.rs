@@ -0,0 +1,164 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::datatypes::{DataType, Field, SchemaRef};
+use datafusion::common::Result as DataFusionResult;
+use datafusion::datasource::TableProvider;
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::{Signature, TableType, TypeSignature, Volatility};
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::prelude::Expr;
+use datafusion_ext::errors::{ExtensionError, Result};
+use datafusion_ext::functions::{FuncParamValue, TableFuncContextProvider};
+use datafusion_ext::sample::{SampleExec, SampleMethod, SampleSize, SampleSpec};
+use datasources::common::url::DatasourceUrl;
+use protogen::metastore::types::catalog::{FunctionType, RuntimePreference};
+
+use super::object_store::{ObjScanTableFunc, READ_CSV, READ_JSON, READ_PARQUET};
+use super::TableFunc;
+use crate::functions::ConstBuiltinFunction;
+
+/// Scan an exact number of rows picked at random from files.
+#[derive(Debug, Clone, Copy)]
+pub struct SampleScan;
+
+impl ConstBuiltinFunction for SampleScan {
+    const NAME: &'static str = "sample";
+    const DESCRIPTION: &'static str =
+        "Returns the given number of rows picked at random from the given file(s).";
+    const EXAMPLE: &'static str = "SELECT * FROM sample('./my_data.parquet', 100)";
+    const FUNCTION_TYPE: FunctionType = FunctionType::TableReturning;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::new(
+            TypeSignature::OneOf(vec![
+                TypeSignature::Exact(vec![DataType::Utf8, DataType::Int64]),
+                TypeSignature::Exact(vec![
+                    DataType::List(Arc::new(Field::new("item", DataType::Utf8, false))),
+                    DataType::Int64,
+                ]),
+            ]),
+            Volatility::Volatile,
+        ))
+    }
+}
+
+impl SampleScan {
+    /// Get the function for scanning the files, based on the extension of the
+    /// first url.
+    fn scan_func(url_arg: &FuncParamValue) -> Result<ObjScanTableFunc> {
+        let url: DatasourceUrl = if url_arg.is_valid::<DatasourceUrl>() {
+            url_arg.clone().try_into()?
+        } else {
+            let urls: Vec<DatasourceUrl> = url_arg.clone().try_into()?;
+            urls.into_iter()
+                .next()
+                .ok_or_else(|| ExtensionError::String("at least one url expected".to_owned()))?
+        };
+
+        let path = url.path();
+        let ext = Path::new(path.as_ref())
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        Ok(match ext.as_str() {
+            "parquet" => READ_PARQUET,
+            "csv" => READ_CSV,
+            "json" | "jsonl" | "ndjson" => READ_JSON,
+            other => {
+                return Err(ExtensionError::String(format!(
+                    "unable to infer how to sample files with extension: '{other}'"
+                )))
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl TableFunc for SampleScan {
+    fn detect_runtime(
+        &self,
+        args: &[FuncParamValue],
+        parent: RuntimePreference,
+    ) -> Result<RuntimePreference> {
+        let url_arg = args.first().ok_or(ExtensionError::InvalidNumArgs)?;
+        Self::scan_func(url_arg)?.detect_runtime(&args[..1], parent)
+    }
+
+    async fn create_provider(
+        &self,
+        ctx: &dyn TableFuncContextProvider,
+        args: Vec<FuncParamValue>,
+        mut opts: HashMap<String, FuncParamValue>,
+    ) -> Result<Arc<dyn TableProvider>> {
+        if args.len() < 2 {
+            return Err(ExtensionError::InvalidNumArgs);
+        }
+        let mut args = args.into_iter();
+        let url_arg = args.next().unwrap();
+
+        let rows: i64 = args.next().unwrap().try_into()?;
+        if rows < 0 {
+            return Err(ExtensionError::String(format!(
+                "number of rows to sample must not be negative, got {rows}"
+            )));
+        }
+        let seed = opts
+            .remove("seed")
+            .map(i64::try_from)
+            .transpose()?
+            .map(|seed| seed as u64);
+        let spec = SampleSpec::try_new(
+            SampleMethod::Reservoir,
+            SampleSize::Rows(rows as usize),
+            seed,
+        )?;
+
+        // Remaining args and options are for accessing the files.
+        let mut scan_args = vec![url_arg.clone()];
+        scan_args.extend(args);
+        let inner = Self::scan_func(&url_arg)?
+            .create_provider(ctx, scan_args, opts)
+            .await?;
+
+        Ok(Arc::new(SampleTableProvider { inner, spec }))
+    }
+}
+
+/// Table provider sampling the rows of another table provider.
+struct SampleTableProvider {
+    inner: Arc<dyn TableProvider>,
+    spec: SampleSpec,
+}
+
+#[async_trait]
+impl TableProvider for SampleTableProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+
+    fn table_type(&self) -> TableType {
+        self.inner.table_type()
+    }
+
+    async fn scan(
+        &self,
+        ctx: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        // Filters and limits apply to the sampled rows, so they can't be pushed
+        // down into the scan.
+        let plan = self.inner.scan(ctx, projection, &[], None).await?;
+        Ok(Arc::new(SampleExec::new(plan, self.spec)))
+    }
+}
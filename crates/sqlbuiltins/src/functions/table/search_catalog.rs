@@ -0,0 +1,378 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use datafusion::arrow::array::{Float64Builder, StringBuilder};
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::datasource::{MemTable, TableProvider};
+use datafusion::logical_expr::{Signature, Volatility};
+use datafusion_ext::errors::{ExtensionError, Result};
+use datafusion_ext::functions::{FuncParamValue, TableFuncContextProvider};
+use futures::{stream, StreamExt};
+use protogen::metastore::types::catalog::{
+    CatalogEntry, DatabaseEntry, FunctionType, RuntimePreference,
+};
+use protogen::metastore::types::options::{DatabaseOptions, TableOptions, TunnelOptions};
+use tracing::debug;
+
+use super::virtual_listing::{get_tunnel_for_db, get_virtual_lister_for_external_db};
+use super::TableFunc;
+use crate::builtins::DEFAULT_CATALOG;
+use crate::functions::ConstBuiltinFunction;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SearchCatalog;
+
+impl ConstBuiltinFunction for SearchCatalog {
+    const NAME: &'static str = "search_catalog";
+    const DESCRIPTION: &'static str =
+        "Searches for databases, schemas, tables and columns with names similar to the search term";
+    const EXAMPLE: &'static str = "SELECT * FROM search_catalog('revenue')";
+    const FUNCTION_TYPE: FunctionType = FunctionType::TableReturning;
+
+    fn signature(&self) -> Option<Signature> {
+        Some(Signature::uniform(
+            1,
+            vec![DataType::Utf8],
+            Volatility::Stable,
+        ))
+    }
+}
+
+#[async_trait]
+impl TableFunc for SearchCatalog {
+    fn detect_runtime(
+        &self,
+        _args: &[FuncParamValue],
+        _parent: RuntimePreference,
+    ) -> Result<RuntimePreference> {
+        // External databases are listed as part of the search, which we always
+        // want to happen remotely.
+        Ok(RuntimePreference::Remote)
+    }
+
+    async fn create_provider(
+        &self,
+        ctx: &dyn TableFuncContextProvider,
+        args: Vec<FuncParamValue>,
+        _opts: HashMap<String, FuncParamValue>,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let term: String = match args.len() {
+            1 => args.into_iter().next().unwrap().try_into()?,
+            _ => return Err(ExtensionError::InvalidNumArgs),
+        };
+
+        let mut results = SearchResults::new(&term);
+        let mut external_dbs = Vec::new();
+
+        let catalog = ctx.get_session_catalog();
+        // Get the name of the database containing a schema.
+        let database_of = |schema_oid: u32| {
+            catalog
+                .get_by_oid(schema_oid)
+                .and_then(|schema| catalog.get_by_oid(schema.get_meta().parent))
+                .map(|db| db.get_meta().name.as_str())
+                .unwrap_or(DEFAULT_CATALOG)
+        };
+
+        for ent in catalog.iter_entries() {
+            if ent.builtin {
+                continue;
+            }
+            let parent_name = ent.parent_entry.map(|p| p.get_meta().name.as_str());
+
+            match ent.entry {
+                CatalogEntry::Database(db) => {
+                    results.push(&db.meta.name, None, None, None, "database");
+                    if !matches!(db.options, DatabaseOptions::Internal(_)) {
+                        external_dbs.push(db.clone());
+                    }
+                }
+                CatalogEntry::Schema(schema) => {
+                    results.push(
+                        parent_name.unwrap_or(DEFAULT_CATALOG),
+                        Some(&schema.meta.name),
+                        None,
+                        None,
+                        "schema",
+                    );
+                }
+                CatalogEntry::Table(table) => {
+                    let name = &table.meta.name;
+                    let database = database_of(table.meta.parent);
+                    results.push(database, parent_name, Some(name), None, "table");
+                    // Column definitions are only stored for native tables.
+                    if let TableOptions::Internal(opts) = &table.options {
+                        for col in &opts.columns {
+                            results.push(
+                                database,
+                                parent_name,
+                                Some(name),
+                                Some(&col.name),
+                                "column",
+                            );
+                        }
+                    }
+                }
+                CatalogEntry::View(view) => {
+                    let database = database_of(view.meta.parent);
+                    results.push(database, parent_name, Some(&view.meta.name), None, "view");
+                    for col in &view.columns {
+                        results.push(
+                            database,
+                            parent_name,
+                            Some(&view.meta.name),
+                            Some(col),
+                            "column",
+                        );
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let external_dbs = external_dbs
+            .into_iter()
+            .map(|db| {
+                let tunnel = get_tunnel_for_db(ctx, &db);
+                (db, tunnel)
+            })
+            .collect::<Vec<_>>();
+
+        // External databases are listed concurrently, with a limit on how many
+        // are connected to at once and on how long each may take.
+        let listings = stream::iter(external_dbs)
+            .map(|(db, tunnel)| async move {
+                let listing = match tunnel {
+                    Ok(tunnel) => tokio::time::timeout(
+                        EXTERNAL_DATABASE_TIMEOUT,
+                        list_external_database(&db, tunnel),
+                    )
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(ExtensionError::String(format!(
+                            "listing timed out after {}s",
+                            EXTERNAL_DATABASE_TIMEOUT.as_secs()
+                        )))
+                    }),
+                    Err(e) => Err(e),
+                };
+                (db, listing)
+            })
+            .buffer_unordered(EXTERNAL_DATABASE_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+
+        for (db, listing) in listings {
+            match listing {
+                Ok(listing) => {
+                    for (schema, tables) in listing {
+                        results.push(&db.meta.name, Some(&schema), None, None, "schema");
+                        for table in tables {
+                            results.push(&db.meta.name, Some(&schema), Some(&table), None, "table");
+                        }
+                    }
+                }
+                // An unreachable database shouldn't fail the entire search.
+                Err(e) => {
+                    debug!(database = %db.meta.name, %e, "skipping database in catalog search")
+                }
+            }
+        }
+
+        results.into_provider()
+    }
+}
+
+/// Max number of external databases listed at once.
+const EXTERNAL_DATABASE_CONCURRENCY: usize = 8;
+
+/// How long listing a single external database may take before it's left out
+/// of the results.
+const EXTERNAL_DATABASE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// List the schemas of an external database along with the tables in each,
+/// connecting through the database's tunnel if it has one.
+///
+/// Columns aren't listed since that would require a round trip per table.
+async fn list_external_database(
+    db: &DatabaseEntry,
+    tunnel: Option<TunnelOptions>,
+) -> Result<Vec<(String, Vec<String>)>> {
+    let lister = get_virtual_lister_for_external_db(&db.options, tunnel).await?;
+    let schemas = lister
+        .list_schemas()
+        .await
+        .map_err(|e| ExtensionError::Access(Box::new(e)))?;
+
+    let mut listing = Vec::with_capacity(schemas.len());
+    for schema in schemas {
+        let tables = lister
+            .list_tables(&schema)
+            .await
+            .map_err(|e| ExtensionError::Access(Box::new(e)))?;
+        listing.push((schema, tables));
+    }
+
+    Ok(listing)
+}
+
+#[derive(Debug)]
+struct SearchResult {
+    database_name: String,
+    schema_name: Option<String>,
+    table_name: Option<String>,
+    column_name: Option<String>,
+    object_type: &'static str,
+    score: f64,
+}
+
+/// Collects objects matching a search term.
+struct SearchResults<'a> {
+    term: &'a str,
+    results: Vec<SearchResult>,
+}
+
+impl<'a> SearchResults<'a> {
+    fn new(term: &'a str) -> Self {
+        SearchResults {
+            term,
+            results: Vec::new(),
+        }
+    }
+
+    /// Add an object to the results if it matches the search term.
+    ///
+    /// The most specific name provided is the name that's matched on.
+    fn push(
+        &mut self,
+        database_name: &str,
+        schema_name: Option<&str>,
+        table_name: Option<&str>,
+        column_name: Option<&str>,
+        object_type: &'static str,
+    ) {
+        let name = column_name
+            .or(table_name)
+            .or(schema_name)
+            .unwrap_or(database_name);
+        if let Some(score) = match_score(self.term, name) {
+            self.results.push(SearchResult {
+                database_name: database_name.to_string(),
+                schema_name: schema_name.map(String::from),
+                table_name: table_name.map(String::from),
+                column_name: column_name.map(String::from),
+                object_type,
+                score,
+            });
+        }
+    }
+
+    /// Create a table provider from the results, best matches first.
+    fn into_provider(mut self) -> Result<Arc<dyn TableProvider>> {
+        self.results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.database_name.cmp(&b.database_name))
+                .then_with(|| a.schema_name.cmp(&b.schema_name))
+                .then_with(|| a.table_name.cmp(&b.table_name))
+                .then_with(|| a.column_name.cmp(&b.column_name))
+        });
+
+        let mut database_names = StringBuilder::new();
+        let mut schema_names = StringBuilder::new();
+        let mut table_names = StringBuilder::new();
+        let mut column_names = StringBuilder::new();
+        let mut object_types = StringBuilder::new();
+        let mut scores = Float64Builder::new();
+        for result in self.results {
+            database_names.append_value(result.database_name);
+            schema_names.append_option(result.schema_name);
+            table_names.append_option(result.table_name);
+            column_names.append_option(result.column_name);
+            object_types.append_value(result.object_type);
+            scores.append_value(result.score);
+        }
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("database_name", DataType::Utf8, false),
+            Field::new("schema_name", DataType::Utf8, true),
+            Field::new("table_name", DataType::Utf8, true),
+            Field::new("column_name", DataType::Utf8, true),
+            Field::new("object_type", DataType::Utf8, false),
+            Field::new("score", DataType::Float64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(database_names.finish()),
+                Arc::new(schema_names.finish()),
+                Arc::new(table_names.finish()),
+                Arc::new(column_names.finish()),
+                Arc::new(object_types.finish()),
+                Arc::new(scores.finish()),
+            ],
+        )
+        .map_err(|e| ExtensionError::Access(Box::new(e)))?;
+
+        let provider = MemTable::try_new(schema, vec![vec![batch]])
+            .map_err(|e| ExtensionError::Access(Box::new(e)))?;
+
+        Ok(Arc::new(provider))
+    }
+}
+
+/// Score how well a name matches the search term, between 0 and 1.
+///
+/// Matching is case insensitive. Exact matches score highest, followed by
+/// prefix and substring matches. Names containing all characters of the term
+/// in order (e.g. 'rvn' for 'revenue') are treated as weak matches. Returns
+/// `None` if the name doesn't match at all.
+fn match_score(term: &str, name: &str) -> Option<f64> {
+    let term = term.to_lowercase();
+    let name = name.to_lowercase();
+
+    if term == name {
+        return Some(1.0);
+    }
+    if name.starts_with(&term) {
+        return Some(0.9);
+    }
+    if let Some(pos) = name.find(&term) {
+        // Matches closer to the start of the name rank higher.
+        return Some(0.8 - 0.2 * (pos as f64 / name.len() as f64));
+    }
+
+    let mut chars = name.chars();
+    if term.chars().all(|c| chars.any(|n| n == c)) {
+        let ratio = term.chars().count() as f64 / name.chars().count() as f64;
+        return Some(0.5 * ratio);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_score_ordering() {
+        let exact = match_score("revenue", "Revenue").unwrap();
+        let prefix = match_score("revenue", "revenue_2023").unwrap();
+        let substring = match_score("revenue", "total_revenue").unwrap();
+        let subsequence = match_score("rvn", "revenue").unwrap();
+
+        assert!(exact > prefix);
+        assert!(prefix > substring);
+        assert!(substring > subsequence);
+        assert!(subsequence > 0.0);
+
+        assert_eq!(None, match_score("revenue", "costs"));
+        assert_eq!(None, match_score("nvr", "revenue"));
+    }
+}
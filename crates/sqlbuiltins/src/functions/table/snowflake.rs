@@ -72,7 +72,7 @@ impl TableFunc for ReadSnowflake {
                     schema_name: schema,
                     table_name: table,
                 };
-                let accessor = SnowflakeAccessor::connect(conn_params)
+                let accessor = SnowflakeAccessor::connect(conn_params, None)
                     .await
                     .map_err(|e| ExtensionError::Access(Box::new(e)))?;
                 let prov = accessor
@@ -27,7 +27,9 @@ use tracing::warn;
 
 use super::{SystemOperation, SystemOperationTableProvider};
 use crate::builtins::GLARE_CACHED_EXTERNAL_DATABASE_TABLES;
-use crate::functions::table::virtual_listing::get_virtual_lister_for_external_db;
+use crate::functions::table::virtual_listing::{
+    get_tunnel_for_db, get_virtual_lister_for_external_db,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct CacheExternalDatabaseTables;
@@ -70,7 +72,14 @@ impl TableFunc for CacheExternalDatabaseTables {
 
         let listers: Vec<ListerForDatabase> = stream::iter(external_db_ents.into_iter())
             .filter_map(|ent| async {
-                match get_virtual_lister_for_external_db(&ent.options).await {
+                let tunnel = match get_tunnel_for_db(context, ent) {
+                    Ok(tunnel) => tunnel,
+                    Err(e) => {
+                        warn!(%e, oid = %ent.meta.id, "failed to get tunnel for database");
+                        return None;
+                    }
+                };
+                match get_virtual_lister_for_external_db(&ent.options, tunnel).await {
                     Ok(lister) => Some(ListerForDatabase {
                         oid: ent.meta.id,
                         lister: lister.into(),
@@ -1,6 +1,7 @@
 //! Table functions for triggering system-related functionality. Users are
 //! unlikely to use these, but there's no harm if they do.
 pub mod cache_external_tables;
+pub mod reload_config;
 
 use async_trait::async_trait;
 use cache_external_tables::CacheExternalDatabaseTablesOperation;
@@ -20,6 +21,7 @@ use datafusion::physical_plan::{
 use datafusion::prelude::Expr;
 use futures::stream;
 use once_cell::sync::Lazy;
+use reload_config::ReloadConfigOperation;
 use std::any::Any;
 use std::fmt;
 use std::sync::Arc;
@@ -32,6 +34,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 #[derive(Clone)]
 pub enum SystemOperation {
     CacheExternalTables(CacheExternalDatabaseTablesOperation),
+    ReloadConfig(ReloadConfigOperation),
 }
 
 impl SystemOperation {
@@ -40,6 +43,7 @@ impl SystemOperation {
     pub fn name(&self) -> &'static str {
         match self {
             Self::CacheExternalTables(inner) => inner.name(),
+            Self::ReloadConfig(inner) => inner.name(),
         }
     }
 
@@ -50,6 +54,7 @@ impl SystemOperation {
     pub async fn execute(&self, context: Arc<TaskContext>) -> Result<(), DataFusionError> {
         match self {
             Self::CacheExternalTables(inner) => inner.execute(context).await?,
+            Self::ReloadConfig(inner) => inner.execute().await?,
         }
         Ok(())
     }
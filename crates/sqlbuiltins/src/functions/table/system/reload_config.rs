@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::datasource::TableProvider;
+use datafusion::error::DataFusionError;
+use datafusion_ext::errors::{ExtensionError, Result};
+use datafusion_ext::functions::{FuncParamValue, TableFuncContextProvider};
+use datafusion_ext::reload::ConfigReloader;
+use protogen::metastore::types::catalog::{FunctionType, RuntimePreference};
+
+use super::{SystemOperation, SystemOperationTableProvider};
+use crate::functions::table::TableFunc;
+use crate::functions::ConstBuiltinFunction;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReloadConfig;
+
+impl ConstBuiltinFunction for ReloadConfig {
+    const NAME: &'static str = "reload_config";
+    const DESCRIPTION: &'static str =
+        "Reload the server configuration file without restarting the server.";
+    const EXAMPLE: &'static str = "select * from reload_config();";
+    const FUNCTION_TYPE: FunctionType = FunctionType::TableReturning;
+}
+
+#[async_trait]
+impl TableFunc for ReloadConfig {
+    fn detect_runtime(
+        &self,
+        _args: &[FuncParamValue],
+        _parent: RuntimePreference,
+    ) -> Result<RuntimePreference> {
+        // Reloads the configuration of the server the session is connected
+        // to.
+        Ok(RuntimePreference::Local)
+    }
+
+    async fn create_provider(
+        &self,
+        context: &dyn TableFuncContextProvider,
+        args: Vec<FuncParamValue>,
+        _opts: HashMap<String, FuncParamValue>,
+    ) -> Result<Arc<dyn TableProvider>> {
+        if !args.is_empty() {
+            return Err(ExtensionError::InvalidNumArgs);
+        }
+
+        // Reloading affects every session on the server.
+        if !context.get_session_vars().is_admin() {
+            return Err(ExtensionError::String(
+                "Permission denied: reload_config requires an admin".to_string(),
+            ));
+        }
+
+        let reloader = context
+            .get_session_state()
+            .config()
+            .get_extension::<ConfigReloader>()
+            .ok_or_else(|| {
+                ExtensionError::String(
+                    "Reloading configuration is not supported by this server".to_string(),
+                )
+            })?;
+
+        Ok(Arc::new(SystemOperationTableProvider {
+            operation: SystemOperation::ReloadConfig(ReloadConfigOperation {
+                reloader: reloader.as_ref().clone(),
+            }),
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReloadConfigOperation {
+    reloader: ConfigReloader,
+}
+
+impl ReloadConfigOperation {
+    pub fn name(&self) -> &'static str {
+        "reload_config"
+    }
+
+    pub async fn execute(&self) -> Result<(), DataFusionError> {
+        self.reloader
+            .reload()
+            .map_err(|e| DataFusionError::External(Box::new(e)))
+    }
+}
@@ -18,10 +18,12 @@ use datasources::mysql::MysqlAccessor;
 use datasources::postgres::PostgresAccess;
 use datasources::snowflake::{SnowflakeAccessor, SnowflakeDbConnection};
 use datasources::sqlserver::SqlServerAccess;
-use protogen::metastore::types::catalog::{FunctionType, RuntimePreference};
+use protogen::metastore::types::catalog::{
+    CatalogEntry, DatabaseEntry, FunctionType, RuntimePreference,
+};
 use protogen::metastore::types::options::{
     DatabaseOptions, DatabaseOptionsBigQuery, DatabaseOptionsMongoDb, DatabaseOptionsMysql,
-    DatabaseOptionsPostgres, DatabaseOptionsSnowflake, DatabaseOptionsSqlServer,
+    DatabaseOptionsPostgres, DatabaseOptionsSnowflake, DatabaseOptionsSqlServer, TunnelOptions,
 };
 
 use super::TableFunc;
@@ -249,7 +251,7 @@ async fn get_virtual_lister_from_context(
         },
     )?;
 
-    let lister = get_virtual_lister_for_db(ctx, &db.options).await?;
+    let lister = get_virtual_lister_for_db(ctx, db).await?;
     Ok(lister)
 }
 
@@ -261,26 +263,45 @@ async fn get_virtual_lister_from_context(
 /// a client for an external database (unbounded lifetime).
 pub(crate) async fn get_virtual_lister_for_db<'a, 'b: 'a, 'c: 'b>(
     ctx: &'c dyn TableFuncContextProvider,
-    opts: &'b DatabaseOptions,
+    db: &'b DatabaseEntry,
 ) -> Result<Box<dyn VirtualLister + 'a>> {
-    match opts {
+    match &db.options {
         DatabaseOptions::Internal(_) => Ok(ctx.get_catalog_lister()),
-        other => get_virtual_lister_for_external_db(other).await,
+        other => get_virtual_lister_for_external_db(other, get_tunnel_for_db(ctx, db)?).await,
+    }
+}
+
+/// Get the options for the tunnel the database connects through, if any.
+pub(crate) fn get_tunnel_for_db(
+    ctx: &dyn TableFuncContextProvider,
+    db: &DatabaseEntry,
+) -> Result<Option<TunnelOptions>> {
+    let tunnel_id = match db.tunnel_id {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    match ctx.get_session_catalog().get_by_oid(tunnel_id) {
+        Some(CatalogEntry::Tunnel(ent)) => Ok(Some(ent.options.clone())),
+        _ => Err(ExtensionError::MissingObject {
+            obj_typ: "tunnel",
+            name: tunnel_id.to_string(),
+        }),
     }
 }
 
-/// Gets a lister for an external database using the provided options.
+/// Gets a lister for an external database using the provided options,
+/// connecting through `tunnel` if provided.
 ///
 /// Will panic if attempting to get a lister for an internal database.
 pub(crate) async fn get_virtual_lister_for_external_db(
     opts: &DatabaseOptions,
+    tunnel: Option<TunnelOptions>,
 ) -> Result<Box<dyn VirtualLister>> {
     let lister: Box<dyn VirtualLister> = match opts {
         DatabaseOptions::Internal(_) => panic!("attempted to get lister for internal db"),
         DatabaseOptions::Debug(_) => Box::new(DebugVirtualLister),
         DatabaseOptions::Postgres(DatabaseOptionsPostgres { connection_string }) => {
-            // TODO: We're not using the configured tunnel?
-            let access = PostgresAccess::new_from_conn_str(connection_string.clone(), None);
+            let access = PostgresAccess::new_from_conn_str(connection_string.clone(), tunnel);
             let state = access
                 .connect()
                 .await
@@ -298,13 +319,13 @@ pub(crate) async fn get_virtual_lister_for_external_db(
             Box::new(accessor)
         }
         DatabaseOptions::Mysql(DatabaseOptionsMysql { connection_string }) => {
-            let accessor = MysqlAccessor::connect(connection_string, None)
+            let accessor = MysqlAccessor::connect(connection_string, tunnel)
                 .await
                 .map_err(|e| ExtensionError::Access(Box::new(e)))?;
             Box::new(accessor)
         }
         DatabaseOptions::MongoDb(DatabaseOptionsMongoDb { connection_string }) => {
-            let accessor = MongoDbAccessor::connect(connection_string)
+            let accessor = MongoDbAccessor::connect(connection_string, tunnel)
                 .await
                 .map_err(|e| ExtensionError::Access(Box::new(e)))?;
             Box::new(accessor)
@@ -330,14 +351,15 @@ pub(crate) async fn get_virtual_lister_for_external_db(
                 warehouse: warehouse.clone(),
                 role_name,
             };
-            let accessor = SnowflakeAccessor::connect(conn_params)
+            let accessor = SnowflakeAccessor::connect(conn_params, tunnel)
                 .await
                 .map_err(|e| ExtensionError::Access(Box::new(e)))?;
             Box::new(accessor)
         }
         DatabaseOptions::SqlServer(DatabaseOptionsSqlServer { connection_string }) => {
             let access = SqlServerAccess::try_new_from_ado_string(connection_string)
-                .map_err(ExtensionError::access)?;
+                .map_err(ExtensionError::access)?
+                .with_tunnel(tunnel);
             let state = access.connect().await.map_err(ExtensionError::access)?;
             Box::new(state)
         }
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::datasource::TableProvider;
+
+use datafusion_ext::errors::ExtensionError;
+use datafusion_ext::functions::{FuncParamValue, TableFuncContextProvider};
+use datasources::common::url::{DatasourceUrl, DatasourceUrlType};
+use datasources::object_store::generic::GenericStoreAccess;
+use datasources::xml::table::xml_streaming_table;
+use protogen::metastore::types::catalog::RuntimePreference;
+
+use crate::functions::table::{table_location_and_opts, TableFunc};
+use crate::functions::{ConstBuiltinFunction, FunctionType};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XmlScan;
+
+impl ConstBuiltinFunction for XmlScan {
+    const NAME: &'static str = "read_xml";
+    const DESCRIPTION: &'static str =
+        "Reads the elements with the given row tag from one or more XML files. Supports globbing.";
+    const EXAMPLE: &'static str =
+        "SELECT * FROM read_xml('./path/to/feed*.xml', row_tag => 'record')";
+    const FUNCTION_TYPE: FunctionType = FunctionType::TableReturning;
+}
+
+#[async_trait]
+impl TableFunc for XmlScan {
+    fn detect_runtime(
+        &self,
+        args: &[FuncParamValue],
+        _parent: RuntimePreference,
+    ) -> Result<RuntimePreference, ExtensionError> {
+        if let Some(arg) = args.first() {
+            let url: String = arg.clone().try_into()?;
+            let source_url =
+                DatasourceUrl::try_new(url).map_err(|e| ExtensionError::Access(Box::new(e)))?;
+            Ok(match source_url.datasource_url_type() {
+                DatasourceUrlType::File => RuntimePreference::Local,
+                _ => RuntimePreference::Remote,
+            })
+        } else {
+            Err(ExtensionError::ExpectedIndexedArgument {
+                index: 0,
+                what: "location of the table".to_string(),
+            })
+        }
+    }
+
+    async fn create_provider(
+        &self,
+        ctx: &dyn TableFuncContextProvider,
+        args: Vec<FuncParamValue>,
+        mut opts: HashMap<String, FuncParamValue>,
+    ) -> Result<Arc<dyn TableProvider>, ExtensionError> {
+        let row_tag: String = opts
+            .remove("row_tag")
+            .ok_or(ExtensionError::MissingNamedArgument("row_tag"))?
+            .try_into()?;
+        if row_tag.is_empty() {
+            return Err(ExtensionError::String(
+                "row_tag must not be empty".to_string(),
+            ));
+        }
+
+        let sample_size = match opts.remove("schema_sample_size") {
+            Some(v) => {
+                let size: i64 = v.try_into()?;
+                let size = usize::try_from(size)
+                    .ok()
+                    .filter(|size| *size > 0)
+                    .ok_or_else(|| {
+                        ExtensionError::String(
+                            "schema_sample_size must be greater than zero".to_string(),
+                        )
+                    })?;
+                Some(size)
+            }
+            None => None,
+        };
+
+        let (source_url, storage_options) = table_location_and_opts(ctx, args, &mut opts)?;
+
+        let store_access = GenericStoreAccess::new_from_location_and_opts(
+            source_url.to_string().as_str(),
+            storage_options,
+        )?;
+
+        Ok(xml_streaming_table(store_access, source_url, row_tag, sample_size).await?)
+    }
+}
@@ -42,10 +42,15 @@ pub fn validate_database_tunnel_support(database: &str, tunnel: &str) -> Result<
         (database, tunnel),
         // Debug
         (DatabaseOptions::DEBUG, TunnelOptions::DEBUG)
-        // Postgres
-        | (DatabaseOptions::POSTGRES, TunnelOptions::SSH)
-        // MySQL
-        | (DatabaseOptions::MYSQL, TunnelOptions::SSH)
+        // TCP datasources
+        | (
+            DatabaseOptions::POSTGRES
+                | DatabaseOptions::MYSQL
+                | DatabaseOptions::MONGODB
+                | DatabaseOptions::SNOWFLAKE
+                | DatabaseOptions::SQL_SERVER,
+            TunnelOptions::SSH | TunnelOptions::SOCKS5 | TunnelOptions::HTTP
+        )
     ) {
         Ok(())
     } else {
@@ -62,10 +67,15 @@ pub fn validate_table_tunnel_support(table: &str, tunnel: &str) -> Result<()> {
         (table, tunnel),
         // Debug
         (TableOptions::DEBUG, TunnelOptions::DEBUG)
-        // Postgres
-        | (TableOptions::POSTGRES, TunnelOptions::SSH)
-        // MySQL
-        | (TableOptions::MYSQL, TunnelOptions::SSH)
+        // TCP datasources
+        | (
+            TableOptions::POSTGRES
+                | TableOptions::MYSQL
+                | TableOptions::MONGODB
+                | TableOptions::SNOWFLAKE
+                | TableOptions::SQL_SERVER,
+            TunnelOptions::SSH | TunnelOptions::SOCKS5 | TunnelOptions::HTTP
+        )
     ) {
         Ok(())
     } else {
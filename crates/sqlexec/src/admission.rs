@@ -0,0 +1,498 @@
+//! Admission control for queries.
+//!
+//! Limits the number of queries executing at once across all sessions on an
+//! engine. Queries that can't run immediately wait in a bounded queue, and
+//! waiting queries are admitted round robin across users so that a single user
+//! submitting a burst of queries can't starve everyone else.
+
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::Result as DataFusionResult;
+use datafusion::physical_plan::{RecordBatchStream, SendableRecordBatchStream};
+use futures::{Stream, StreamExt};
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+use tracing::debug;
+
+use crate::errors::{internal, ExecError, Result};
+
+/// Limits for concurrently executing queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryLimits {
+    /// Max number of queries executing at once.
+    pub max_concurrent_queries: usize,
+    /// Max number of queries waiting to execute. Queries submitted when the
+    /// queue is full are rejected immediately.
+    pub max_queued_queries: usize,
+    /// How long a query may wait in the queue before being rejected.
+    pub queue_timeout: Duration,
+}
+
+impl QueryLimits {
+    pub const DEFAULT_MAX_QUEUED_QUERIES: usize = 128;
+    pub const DEFAULT_QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+    pub fn new(max_concurrent_queries: usize) -> Self {
+        QueryLimits {
+            max_concurrent_queries,
+            max_queued_queries: Self::DEFAULT_MAX_QUEUED_QUERIES,
+            queue_timeout: Self::DEFAULT_QUEUE_TIMEOUT,
+        }
+    }
+}
+
+/// Admits queries for execution according to the configured limits.
+///
+/// Cheaply cloneable, all clones share the same limits and queue. Limits may be
+/// changed while queries are running with [`QueryLimiter::set_limits`].
+#[derive(Debug, Clone, Default)]
+pub struct QueryLimiter {
+    inner: Arc<Limiter>,
+}
+
+impl QueryLimiter {
+    pub fn new(limits: QueryLimits) -> Self {
+        let limiter = Self::default();
+        limiter.set_limits(Some(limits));
+        limiter
+    }
+
+    /// A limiter that admits every query immediately.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Get the current limits, `None` if queries are unlimited.
+    pub fn limits(&self) -> Option<QueryLimits> {
+        self.inner.state.lock().limits
+    }
+
+    /// Change the limits for admitting queries.
+    ///
+    /// Queued queries are admitted immediately if the new limits allow it.
+    /// Lowering the max number of concurrent queries doesn't affect queries
+    /// that are already executing, new queries are queued until enough of them
+    /// complete. Queries that were admitted while unlimited aren't counted
+    /// towards the new limits.
+    pub fn set_limits(&self, limits: Option<QueryLimits>) {
+        let admitted = {
+            let mut state = self.inner.state.lock();
+            state.limits = limits;
+            let mut admitted = Vec::new();
+            while state.has_capacity() {
+                match state.dequeue() {
+                    Some(next) => {
+                        state.running += 1;
+                        admitted.push(next);
+                    }
+                    None => break,
+                }
+            }
+            admitted
+        };
+
+        for next in admitted {
+            // If the query stopped waiting, the returned permit is dropped
+            // and releases the slot.
+            let _ = next.send(QueryPermit {
+                limiter: Some(self.inner.clone()),
+            });
+        }
+    }
+
+    /// Wait for a query owned by `user` to be admitted.
+    ///
+    /// The returned permit must be held for the duration of the query.
+    pub async fn acquire(&self, user: &str) -> Result<QueryPermit> {
+        let limiter = &self.inner;
+
+        let (limits, id, rx) = {
+            let mut state = limiter.state.lock();
+            let limits = match state.limits {
+                Some(limits) => limits,
+                None => return Ok(QueryPermit { limiter: None }),
+            };
+            if state.has_capacity() && state.queued == 0 {
+                state.running += 1;
+                return Ok(QueryPermit {
+                    limiter: Some(limiter.clone()),
+                });
+            }
+            if state.queued >= limits.max_queued_queries {
+                return Err(queue_full(&limits, Duration::ZERO));
+            }
+            let (id, rx) = state.enqueue(user);
+            (limits, id, rx)
+        };
+
+        debug!(%user, "query queued");
+        let mut waiting = Waiting {
+            limiter: limiter.clone(),
+            user,
+            id,
+            rx,
+        };
+
+        match tokio::time::timeout(limits.queue_timeout, &mut waiting.rx).await {
+            Ok(Ok(permit)) => Ok(permit),
+            Ok(Err(_)) => Err(internal!("query limiter dropped a queued query")),
+            Err(_) => {
+                if limiter.state.lock().remove(user, id) {
+                    return Err(queue_full(&limits, limits.queue_timeout));
+                }
+                // We were admitted right as the timeout elapsed, the permit
+                // is already waiting for us.
+                (&mut waiting.rx)
+                    .await
+                    .map_err(|_| internal!("query limiter dropped a queued query"))
+            }
+        }
+    }
+
+    /// Get the number of executing queries.
+    pub fn running(&self) -> usize {
+        self.inner.state.lock().running
+    }
+
+    /// Get the number of queries waiting to execute.
+    pub fn queued(&self) -> usize {
+        self.inner.state.lock().queued
+    }
+}
+
+fn queue_full(limits: &QueryLimits, waited: Duration) -> ExecError {
+    ExecError::QueryQueueFull {
+        max_concurrent: limits.max_concurrent_queries,
+        waited,
+    }
+}
+
+#[derive(Debug, Default)]
+struct Limiter {
+    state: Mutex<LimiterState>,
+}
+
+impl Limiter {
+    /// Release a query slot, handing it to the next queued query if there is
+    /// one and the limits still allow it.
+    fn release(self: &Arc<Self>) {
+        loop {
+            let next = {
+                let mut state = self.state.lock();
+                state.running -= 1;
+                if !state.has_capacity() {
+                    return;
+                }
+                match state.dequeue() {
+                    Some(next) => {
+                        state.running += 1;
+                        next
+                    }
+                    None => return,
+                }
+            };
+
+            let permit = QueryPermit {
+                limiter: Some(self.clone()),
+            };
+            match next.send(permit) {
+                Ok(_) => return,
+                // Receiver went away, try the next query. Note that dropping
+                // the returned permit would recursively release.
+                Err(mut permit) => permit.limiter = None,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    /// Current limits, `None` if queries are unlimited.
+    limits: Option<QueryLimits>,
+    /// Number of executing queries.
+    running: usize,
+    /// Total number of queued queries across all users.
+    queued: usize,
+    /// Id to use for the next queued query.
+    next_id: u64,
+    /// Queued queries per user.
+    queues: HashMap<String, VecDeque<(u64, oneshot::Sender<QueryPermit>)>>,
+    /// Users with queued queries in the order they should be admitted.
+    users: VecDeque<String>,
+}
+
+impl LimiterState {
+    /// Check if another query can start executing.
+    fn has_capacity(&self) -> bool {
+        match &self.limits {
+            Some(limits) => self.running < limits.max_concurrent_queries,
+            None => true,
+        }
+    }
+
+    fn enqueue(&mut self, user: &str) -> (u64, oneshot::Receiver<QueryPermit>) {
+        let (tx, rx) = oneshot::channel();
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let queue = self.queues.entry(user.to_string()).or_default();
+        if queue.is_empty() {
+            self.users.push_back(user.to_string());
+        }
+        queue.push_back((id, tx));
+        self.queued += 1;
+
+        (id, rx)
+    }
+
+    /// Get the next query to admit, rotating through the users.
+    fn dequeue(&mut self) -> Option<oneshot::Sender<QueryPermit>> {
+        let user = self.users.pop_front()?;
+        let queue = self
+            .queues
+            .get_mut(&user)
+            .expect("user with queued queries should have a queue");
+        let (_, tx) = queue
+            .pop_front()
+            .expect("user with queued queries should have a non-empty queue");
+        if queue.is_empty() {
+            self.queues.remove(&user);
+        } else {
+            self.users.push_back(user);
+        }
+        self.queued -= 1;
+
+        Some(tx)
+    }
+
+    /// Remove a queued query, returning false if it was already admitted.
+    fn remove(&mut self, user: &str, id: u64) -> bool {
+        let queue = match self.queues.get_mut(user) {
+            Some(queue) => queue,
+            None => return false,
+        };
+        let idx = match queue.iter().position(|(queued_id, _)| *queued_id == id) {
+            Some(idx) => idx,
+            None => return false,
+        };
+        queue.remove(idx);
+        if queue.is_empty() {
+            self.queues.remove(user);
+            self.users.retain(|u| u != user);
+        }
+        self.queued -= 1;
+
+        true
+    }
+}
+
+/// Removes a query from the queue if it stops waiting before being admitted
+/// (e.g. the client disconnected).
+struct Waiting<'a> {
+    limiter: Arc<Limiter>,
+    user: &'a str,
+    id: u64,
+    rx: oneshot::Receiver<QueryPermit>,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        // Any permit sent after removal is released when the receiver is
+        // dropped.
+        self.limiter.state.lock().remove(self.user, self.id);
+    }
+}
+
+/// Permission to execute a query. The query slot is released on drop.
+#[derive(Debug)]
+pub struct QueryPermit {
+    limiter: Option<Arc<Limiter>>,
+}
+
+impl Drop for QueryPermit {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.limiter.take() {
+            limiter.release();
+        }
+    }
+}
+
+/// A stream holding a query permit until the stream completes or is dropped.
+pub struct AdmittedStream {
+    stream: SendableRecordBatchStream,
+    permit: Option<QueryPermit>,
+}
+
+impl AdmittedStream {
+    pub fn new(stream: SendableRecordBatchStream, permit: QueryPermit) -> Self {
+        AdmittedStream {
+            stream,
+            permit: Some(permit),
+        }
+    }
+}
+
+impl Stream for AdmittedStream {
+    type Item = DataFusionResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.stream.poll_next_unpin(cx);
+        if let Poll::Ready(None) = poll {
+            self.permit = None;
+        }
+        poll
+    }
+}
+
+impl RecordBatchStream for AdmittedStream {
+    fn schema(&self) -> SchemaRef {
+        self.stream.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(max_concurrent_queries: usize, max_queued_queries: usize) -> QueryLimiter {
+        QueryLimiter::new(QueryLimits {
+            max_concurrent_queries,
+            max_queued_queries,
+            queue_timeout: Duration::from_secs(5),
+        })
+    }
+
+    #[tokio::test]
+    async fn unlimited() {
+        let limiter = QueryLimiter::unlimited();
+        let _permits: Vec<_> = futures::future::join_all((0..10).map(|_| limiter.acquire("a")))
+            .await
+            .into_iter()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(0, limiter.running());
+    }
+
+    #[tokio::test]
+    async fn queue_full() {
+        let limiter = limiter(1, 1);
+        let permit = limiter.acquire("a").await.unwrap();
+
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire("a").await.map(|_| ()) }
+        });
+        while limiter.queued() != 1 {
+            tokio::task::yield_now().await;
+        }
+
+        let err = limiter.acquire("b").await.unwrap_err();
+        assert!(matches!(err, ExecError::QueryQueueFull { .. }), "{err}");
+
+        drop(permit);
+        queued.await.unwrap().unwrap();
+        assert_eq!(0, limiter.running());
+        assert_eq!(0, limiter.queued());
+    }
+
+    #[tokio::test]
+    async fn queue_timeout() {
+        let limiter = QueryLimiter::new(QueryLimits {
+            max_concurrent_queries: 1,
+            max_queued_queries: 1,
+            queue_timeout: Duration::from_millis(10),
+        });
+        let _permit = limiter.acquire("a").await.unwrap();
+
+        let err = limiter.acquire("a").await.unwrap_err();
+        assert!(matches!(err, ExecError::QueryQueueFull { .. }), "{err}");
+        assert_eq!(1, limiter.running());
+        assert_eq!(0, limiter.queued());
+    }
+
+    #[tokio::test]
+    async fn fair_across_users() {
+        let limiter = limiter(1, 10);
+        let permit = limiter.acquire("a").await.unwrap();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for user in ["a", "a", "a", "b"] {
+            let limiter = limiter.clone();
+            let order = order.clone();
+            let queued = limiter.queued();
+            handles.push(tokio::spawn(async move {
+                let _permit = limiter.acquire(user).await.unwrap();
+                order.lock().push(user);
+            }));
+            while limiter.queued() == queued {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        drop(permit);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        // "b" is admitted before the rest of the queries from "a".
+        assert_eq!(vec!["a", "b", "a", "a"], *order.lock());
+        assert_eq!(0, limiter.running());
+    }
+
+    #[tokio::test]
+    async fn set_limits_admits_queued() {
+        let limiter = limiter(1, 10);
+        let _permit = limiter.acquire("a").await.unwrap();
+
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire("b").await.map(|_| ()) }
+        });
+        while limiter.queued() != 1 {
+            tokio::task::yield_now().await;
+        }
+
+        limiter.set_limits(Some(QueryLimits {
+            max_concurrent_queries: 2,
+            ..limiter.limits().unwrap()
+        }));
+        queued.await.unwrap().unwrap();
+        assert_eq!(1, limiter.running());
+        assert_eq!(0, limiter.queued());
+
+        limiter.set_limits(None);
+        let _permits: Vec<_> = futures::future::join_all((0..10).map(|_| limiter.acquire("a")))
+            .await
+            .into_iter()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(1, limiter.running());
+    }
+
+    #[tokio::test]
+    async fn cancelled_while_queued() {
+        let limiter = limiter(1, 10);
+        let permit = limiter.acquire("a").await.unwrap();
+
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire("b").await.map(|_| ()) }
+        });
+        while limiter.queued() != 1 {
+            tokio::task::yield_now().await;
+        }
+        queued.abort();
+        let _ = queued.await;
+
+        assert_eq!(0, limiter.queued());
+        drop(permit);
+        assert_eq!(0, limiter.running());
+    }
+}
@@ -0,0 +1,552 @@
+//! Audit logging for executed statements.
+//!
+//! Every statement executed by a session is recorded along with who executed
+//! it, how long it took, how many rows it returned, and any error it hit.
+//! Records are written to the configured sink in the background so that
+//! auditing never blocks query execution.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use catalog::session_catalog::SessionCatalog;
+use chrono::{DateTime, SecondsFormat, Utc};
+use datafusion::arrow::array::{Int64Builder, StringBuilder, TimestampMicrosecondBuilder};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::tokenizer::{Token, Tokenizer};
+use datafusion_ext::session_metrics::{QueryMetrics, QueryObserver};
+use datafusion_ext::vars::SessionVars;
+use datasources::native::access::NativeTableStorage;
+use object_store::path::Path as ObjectPath;
+use object_store::ObjectStore;
+use serde_json::json;
+use sqlbuiltins::builtins::GLARE_AUDIT_LOG;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::engine::EngineStorageConfig;
+use crate::errors::Result;
+use crate::persisted::PersistedTable;
+
+/// How often buffered records are written out to sinks that batch records.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Number of buffered records that triggers writing out records before the
+/// flush interval elapses.
+const MAX_BUFFERED_RECORDS: usize = 1024;
+
+/// Max number of records to hold on to while the sink is failing. The oldest
+/// records are dropped once this is exceeded.
+const MAX_RETAINED_RECORDS: usize = 100 * MAX_BUFFERED_RECORDS;
+
+/// Where audit records are written to.
+#[derive(Debug, Clone)]
+pub enum AuditSink {
+    /// Append records as JSON lines to a local file.
+    File(PathBuf),
+    /// Write batches of records as JSON lines objects in an object store.
+    ///
+    /// Each batch is written to a new object at the root of the store.
+    ObjectStore(Arc<dyn ObjectStore>),
+    /// Append records to the `glare_catalog.audit_log` table of the database
+    /// the statement was executed in.
+    Table,
+}
+
+impl AuditSink {
+    /// Parse a sink from a user provided target.
+    ///
+    /// "table" writes to the audit log table, anything that looks like a URL
+    /// (e.g. "s3://bucket/audit") writes to an object store using the provided
+    /// storage options, and everything else is treated as a path to a local
+    /// file.
+    pub fn try_from_target(target: &str, opts: HashMap<String, String>) -> Result<AuditSink> {
+        if target == "table" {
+            return Ok(AuditSink::Table);
+        }
+        if target.contains("://") {
+            let conf = EngineStorageConfig::try_from_options(target, opts)?;
+            return Ok(AuditSink::ObjectStore(conf.new_object_store()?));
+        }
+        Ok(AuditSink::File(PathBuf::from(target)))
+    }
+}
+
+/// How the text of a statement is recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AuditQueryText {
+    /// Record the statement as is.
+    #[default]
+    Full,
+    /// Record a fingerprint of the statement with all literals replaced,
+    /// avoiding writing potentially sensitive values to the audit log.
+    Fingerprint,
+}
+
+/// Handle for recording statements to the audit log.
+///
+/// Cheaply cloneable, all clones write to the same sink. The default audit log
+/// is disabled and records nothing.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    inner: Option<Arc<AuditLogInner>>,
+}
+
+#[derive(Debug)]
+struct AuditLogInner {
+    send: mpsc::UnboundedSender<AuditMessage>,
+    query_text: AuditQueryText,
+    write_to_table: bool,
+}
+
+impl AuditLog {
+    /// Create a new audit log writing to `sink`.
+    ///
+    /// This spawns a background task for writing records and must be called
+    /// from within a tokio runtime.
+    pub fn new(sink: AuditSink, query_text: AuditQueryText) -> AuditLog {
+        let (send, recv) = mpsc::unbounded_channel();
+        let write_to_table = matches!(sink, AuditSink::Table);
+
+        let worker = AuditWorker {
+            sink,
+            pending: Vec::new(),
+        };
+        tokio::spawn(worker.run(recv));
+
+        AuditLog {
+            inner: Some(Arc::new(AuditLogInner {
+                send,
+                query_text,
+                write_to_table,
+            })),
+        }
+    }
+
+    /// Wait for all records received so far to be written.
+    ///
+    /// Records that fail to be written are kept and retried on the next
+    /// flush.
+    pub async fn flush(&self) {
+        if let Some(inner) = &self.inner {
+            let (tx, rx) = oneshot::channel();
+            if inner.send.send(AuditMessage::Flush(tx)).is_err() {
+                error!("audit log writer stopped, unable to flush");
+                return;
+            }
+            let _ = rx.await;
+        }
+    }
+
+    /// An audit log that records nothing.
+    pub fn disabled() -> AuditLog {
+        Self::default()
+    }
+
+    /// Create an observer recording queries executed by a session.
+    ///
+    /// Returns `None` if the audit log is disabled.
+    pub(crate) fn session_observer(
+        &self,
+        vars: &SessionVars,
+        catalog: &SessionCatalog,
+        native_tables: &NativeTableStorage,
+    ) -> Option<Arc<dyn QueryObserver>> {
+        let inner = self.inner.as_ref()?;
+
+        let table = if inner.write_to_table {
+            match PersistedTable::from_catalog(&GLARE_AUDIT_LOG, catalog, native_tables) {
+                Some(table) => Some(Arc::new(table)),
+                None => {
+                    warn!("audit log table missing from catalog, not recording statements for session");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Some(Arc::new(SessionAuditor {
+            log: inner.clone(),
+            user_name: vars.user_name(),
+            connection_id: vars.connection_id(),
+            database_id: vars.database_id(),
+            table,
+        }))
+    }
+}
+
+/// A single executed statement.
+#[derive(Debug, Clone, PartialEq)]
+struct AuditRecord {
+    timestamp: DateTime<Utc>,
+    user_name: String,
+    connection_id: Uuid,
+    database_id: Uuid,
+    query_text: Option<String>,
+    query_fingerprint: Option<String>,
+    execution_status: &'static str,
+    duration: Duration,
+    output_rows: Option<u64>,
+    error_message: Option<String>,
+}
+
+impl AuditRecord {
+    fn to_json_line(&self) -> String {
+        let mut line = json!({
+            "timestamp": self.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+            "user_name": self.user_name,
+            "connection_id": self.connection_id.to_string(),
+            "database_id": self.database_id.to_string(),
+            "query_text": self.query_text,
+            "query_fingerprint": self.query_fingerprint,
+            "execution_status": self.execution_status,
+            "duration_ms": self.duration.as_millis() as u64,
+            "output_rows": self.output_rows,
+            "error_message": self.error_message,
+        })
+        .to_string();
+        line.push('\n');
+        line
+    }
+}
+
+/// Create a fingerprint for a statement.
+///
+/// Literals are replaced with '?' and whitespace (including comments) is
+/// collapsed, so statements that only differ in the values used share a
+/// fingerprint.
+pub fn fingerprint(query: &str) -> String {
+    let tokens = match Tokenizer::new(&GenericDialect {}, query).tokenize() {
+        Ok(tokens) => tokens,
+        // Don't fall back to the raw text, it may contain the values we're
+        // trying to keep out of the log.
+        Err(_) => return "?".to_string(),
+    };
+
+    let mut out = String::with_capacity(query.len());
+    for token in tokens {
+        match token {
+            Token::Whitespace(_) => {
+                if !out.is_empty() && !out.ends_with(' ') {
+                    out.push(' ');
+                }
+            }
+            Token::Number(_, _)
+            | Token::SingleQuotedString(_)
+            | Token::DoubleQuotedString(_)
+            | Token::DollarQuotedString(_)
+            | Token::NationalStringLiteral(_)
+            | Token::EscapedStringLiteral(_)
+            | Token::HexStringLiteral(_)
+            | Token::SingleQuotedByteStringLiteral(_)
+            | Token::DoubleQuotedByteStringLiteral(_)
+            | Token::RawStringLiteral(_) => out.push('?'),
+            other => out.push_str(&other.to_string()),
+        }
+    }
+    out.trim_end().to_string()
+}
+
+/// Records queries for a single session.
+#[derive(Debug)]
+struct SessionAuditor {
+    log: Arc<AuditLogInner>,
+    user_name: String,
+    connection_id: Uuid,
+    database_id: Uuid,
+    /// Only set when writing to a table.
+    table: Option<Arc<PersistedTable>>,
+}
+
+impl QueryObserver for SessionAuditor {
+    fn observe(&self, metric: &QueryMetrics) {
+        if self.log.write_to_table && self.table.is_none() {
+            // Already warned about when creating the auditor.
+            return;
+        }
+
+        let (query_text, query_fingerprint) = match self.log.query_text {
+            AuditQueryText::Full => (Some(metric.query_text.clone()), None),
+            AuditQueryText::Fingerprint => (None, Some(fingerprint(&metric.query_text))),
+        };
+
+        let record = AuditRecord {
+            timestamp: Utc::now(),
+            user_name: self.user_name.clone(),
+            connection_id: self.connection_id,
+            database_id: self.database_id,
+            query_text,
+            query_fingerprint,
+            execution_status: metric.execution_status.as_str(),
+            duration: metric.start.elapsed(),
+            output_rows: metric.output_rows,
+            error_message: metric.error_message.clone(),
+        };
+
+        let pending = PendingRecord {
+            record,
+            table: self.table.clone(),
+        };
+        if self.log.send.send(AuditMessage::Record(pending)).is_err() {
+            error!("audit log writer stopped, statement not recorded");
+        }
+    }
+}
+
+#[derive(Debug)]
+enum AuditMessage {
+    Record(PendingRecord),
+    /// Write all pending records, notifying the sender once done.
+    Flush(oneshot::Sender<()>),
+}
+
+#[derive(Debug)]
+struct PendingRecord {
+    record: AuditRecord,
+    table: Option<Arc<PersistedTable>>,
+}
+
+/// Writes records to the sink in the background.
+struct AuditWorker {
+    sink: AuditSink,
+    /// Records received but not yet written.
+    pending: Vec<PendingRecord>,
+}
+
+impl AuditWorker {
+    async fn run(mut self, mut recv: mpsc::UnboundedReceiver<AuditMessage>) {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            tokio::select! {
+                msg = recv.recv() => match msg {
+                    Some(msg) => {
+                        let mut flushes = Vec::new();
+                        self.handle_message(msg, &mut flushes);
+                        while let Ok(msg) = recv.try_recv() {
+                            self.handle_message(msg, &mut flushes);
+                        }
+                        // Appending to a file is cheap, so write immediately
+                        // to avoid losing records if the process goes away.
+                        if matches!(self.sink, AuditSink::File(_))
+                            || self.pending.len() >= MAX_BUFFERED_RECORDS
+                            || !flushes.is_empty()
+                        {
+                            self.flush().await;
+                        }
+                        for tx in flushes {
+                            let _ = tx.send(());
+                        }
+                    }
+                    None => {
+                        // All handles dropped.
+                        self.flush().await;
+                        return;
+                    }
+                },
+                _ = interval.tick() => self.flush().await,
+            }
+        }
+    }
+
+    fn handle_message(&mut self, msg: AuditMessage, flushes: &mut Vec<oneshot::Sender<()>>) {
+        match msg {
+            AuditMessage::Record(pending) => self.pending.push(pending),
+            AuditMessage::Flush(tx) => flushes.push(tx),
+        }
+    }
+
+    /// Write out all pending records.
+    ///
+    /// Records are retained on failure so that writing can be retried on the
+    /// next flush.
+    async fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        match self.write_pending().await {
+            Ok(()) => self.pending.clear(),
+            Err(e) => {
+                error!(%e, num_records = self.pending.len(), "failed to write audit log records");
+                if self.pending.len() > MAX_RETAINED_RECORDS {
+                    let num_dropped = self.pending.len() - MAX_RETAINED_RECORDS;
+                    error!(%num_dropped, "dropping oldest audit log records");
+                    self.pending.drain(..num_dropped);
+                }
+            }
+        }
+    }
+
+    async fn write_pending(&self) -> Result<()> {
+        match &self.sink {
+            AuditSink::File(path) => {
+                let mut file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await?;
+                file.write_all(json_lines(&self.pending).as_bytes()).await?;
+                file.flush().await?;
+            }
+            AuditSink::ObjectStore(store) => {
+                let now = Utc::now();
+                let location = ObjectPath::from(format!(
+                    "{}-{}.jsonl",
+                    now.format("%Y%m%dT%H%M%S%.6fZ"),
+                    Uuid::new_v4()
+                ));
+                store
+                    .put(&location, Bytes::from(json_lines(&self.pending)))
+                    .await?;
+            }
+            AuditSink::Table => {
+                // Records from sessions in different databases need to go to
+                // different tables.
+                let mut by_database: HashMap<Uuid, (Arc<PersistedTable>, Vec<&AuditRecord>)> =
+                    HashMap::new();
+                for pending in &self.pending {
+                    if let Some(table) = &pending.table {
+                        by_database
+                            .entry(pending.record.database_id)
+                            .or_insert_with(|| (table.clone(), Vec::new()))
+                            .1
+                            .push(&pending.record);
+                    }
+                }
+
+                // Note that a failure writing to one database's table will
+                // cause records for other databases already written to be
+                // written again on retry.
+                for (table, records) in by_database.values() {
+                    table.append(records_to_batch(records)?).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn json_lines(pending: &[PendingRecord]) -> String {
+    pending
+        .iter()
+        .map(|pending| pending.record.to_json_line())
+        .collect()
+}
+
+fn records_to_batch(records: &[&AuditRecord]) -> Result<RecordBatch> {
+    let mut timestamp = TimestampMicrosecondBuilder::new().with_timezone("UTC");
+    let mut user_name = StringBuilder::new();
+    let mut connection_id = StringBuilder::new();
+    let mut query_text = StringBuilder::new();
+    let mut query_fingerprint = StringBuilder::new();
+    let mut execution_status = StringBuilder::new();
+    let mut duration_ms = Int64Builder::new();
+    let mut output_rows = Int64Builder::new();
+    let mut error_message = StringBuilder::new();
+
+    for record in records {
+        timestamp.append_value(record.timestamp.timestamp_micros());
+        user_name.append_value(&record.user_name);
+        connection_id.append_value(record.connection_id.to_string());
+        query_text.append_option(record.query_text.as_ref());
+        query_fingerprint.append_option(record.query_fingerprint.as_ref());
+        execution_status.append_value(record.execution_status);
+        duration_ms.append_value(record.duration.as_millis() as i64);
+        output_rows.append_option(record.output_rows.map(|rows| rows as i64));
+        error_message.append_option(record.error_message.as_ref());
+    }
+
+    Ok(RecordBatch::try_new(
+        Arc::new(GLARE_AUDIT_LOG.arrow_schema()),
+        vec![
+            Arc::new(timestamp.finish()),
+            Arc::new(user_name.finish()),
+            Arc::new(connection_id.finish()),
+            Arc::new(query_text.finish()),
+            Arc::new(query_fingerprint.finish()),
+            Arc::new(execution_status.finish()),
+            Arc::new(duration_ms.finish()),
+            Arc::new(output_rows.finish()),
+            Arc::new(error_message.finish()),
+        ],
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion_ext::session_metrics::ExecutionStatus;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn fingerprint_replaces_literals() {
+        assert_eq!(
+            "select * from users where name = ? and age > ?",
+            fingerprint("select *   from users\n where name = 'bob' and age > 30 -- comment")
+        );
+        assert_eq!(
+            fingerprint("insert into t values (1, 'a')"),
+            fingerprint("insert into t values (2, 'secret')")
+        );
+    }
+
+    #[tokio::test]
+    async fn writes_to_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+
+        let log = AuditLog::new(AuditSink::File(path.clone()), AuditQueryText::Full);
+        let auditor = SessionAuditor {
+            log: log.inner.clone().unwrap(),
+            user_name: "glaredb".to_string(),
+            connection_id: Uuid::nil(),
+            database_id: Uuid::nil(),
+            table: None,
+        };
+
+        auditor.observe(&QueryMetrics {
+            query_text: "select 1".to_string(),
+            execution_status: ExecutionStatus::Success,
+            output_rows: Some(1),
+            ..Default::default()
+        });
+        auditor.observe(&QueryMetrics {
+            query_text: "select bad".to_string(),
+            execution_status: ExecutionStatus::Fail,
+            error_message: Some("column not found".to_string()),
+            ..Default::default()
+        });
+
+        // Dropping all handles causes the worker to flush and exit.
+        drop(auditor);
+        drop(log);
+
+        let mut lines = Vec::new();
+        for _ in 0..50 {
+            let contents = tokio::fs::read_to_string(&path).await.unwrap_or_default();
+            lines = contents.lines().map(|l| l.to_string()).collect();
+            if lines.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(2, lines.len(), "{lines:?}");
+
+        let first: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!("glaredb", first["user_name"]);
+        assert_eq!("select 1", first["query_text"]);
+        assert_eq!("success", first["execution_status"]);
+        assert_eq!(1, first["output_rows"]);
+
+        let second: serde_json::Value = serde_json::from_str(&lines[1]).unwrap();
+        assert_eq!("fail", second["execution_status"]);
+        assert_eq!("column not found", second["error_message"]);
+    }
+}
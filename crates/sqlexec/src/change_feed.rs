@@ -0,0 +1,648 @@
+//! Feed of changes made to the catalog.
+//!
+//! Every mutation applied to the catalog by a session (objects created,
+//! altered, dropped, ...) is recorded as an event along with who made the
+//! change. Events are appended to the `glare_catalog.catalog_events` table of
+//! the database they happened in, and optionally sent to a webhook so external
+//! systems can react to schema changes.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use catalog::mutator::MutationObserver;
+use catalog::session_catalog::SessionCatalog;
+use chrono::{DateTime, SecondsFormat, Utc};
+use datafusion::arrow::array::{StringBuilder, TimestampMicrosecondBuilder, UInt64Builder};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion_ext::vars::SessionVars;
+use datasources::native::access::NativeTableStorage;
+use protogen::metastore::types::catalog::CatalogState;
+use protogen::metastore::types::service::{
+    AlterDatabaseOperation, AlterRoleOperation, AlterSchemaOperation, AlterTableOperation, Mutation,
+};
+use serde_json::json;
+use sqlbuiltins::builtins::GLARE_CATALOG_EVENTS;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, warn};
+use url::Url;
+use uuid::Uuid;
+
+use crate::errors::{ExecError, Result};
+use crate::persisted::PersistedTable;
+
+/// How often buffered events are written out.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Timeout for a single request to the webhook.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Max number of events to hold on to per destination while it's failing. The
+/// oldest events are dropped once this is exceeded.
+const MAX_RETAINED_EVENTS: usize = 10_000;
+
+/// Handle for recording changes made to the catalog.
+///
+/// Cheaply cloneable, all clones share the same writer. The default change
+/// feed is disabled and records nothing.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeFeed {
+    send: Option<mpsc::UnboundedSender<FeedMessage>>,
+}
+
+impl ChangeFeed {
+    /// Create a new change feed, additionally posting events to `webhook` if
+    /// provided.
+    ///
+    /// Events are posted as JSON objects with an "events" array, in the order
+    /// they happened.
+    ///
+    /// This spawns a background task for writing events and must be called
+    /// from within a tokio runtime.
+    pub fn new(webhook: Option<Url>) -> ChangeFeed {
+        let (send, recv) = mpsc::unbounded_channel();
+        let worker = FeedWorker {
+            webhook: webhook.map(|url| Webhook {
+                url,
+                client: reqwest::Client::new(),
+                pending: Vec::new(),
+            }),
+            databases: HashMap::new(),
+        };
+        tokio::spawn(worker.run(recv));
+        ChangeFeed { send: Some(send) }
+    }
+
+    /// Wait for all events received so far to be written.
+    pub async fn flush(&self) {
+        if let Some(send) = &self.send {
+            let (tx, rx) = oneshot::channel();
+            if send.send(FeedMessage::Flush(tx)).is_err() {
+                error!("change feed writer stopped, unable to flush");
+                return;
+            }
+            let _ = rx.await;
+        }
+    }
+
+    /// A change feed that records nothing.
+    pub fn disabled() -> ChangeFeed {
+        Self::default()
+    }
+
+    /// Create an observer recording catalog changes made by a session.
+    ///
+    /// Returns `None` if the change feed is disabled.
+    pub(crate) fn session_observer(
+        &self,
+        vars: &SessionVars,
+        catalog: &SessionCatalog,
+        native_tables: &NativeTableStorage,
+    ) -> Option<Arc<dyn MutationObserver>> {
+        let send = self.send.as_ref()?;
+        let table = match PersistedTable::from_catalog(
+            &GLARE_CATALOG_EVENTS,
+            catalog,
+            native_tables,
+        ) {
+            Some(table) => Arc::new(table),
+            None => {
+                warn!("catalog events table missing from catalog, not recording catalog changes for session");
+                return None;
+            }
+        };
+
+        Some(Arc::new(SessionChangeFeed {
+            send: send.clone(),
+            user_name: vars.user_name(),
+            connection_id: vars.connection_id(),
+            database_id: vars.database_id(),
+            table,
+        }))
+    }
+}
+
+/// Records catalog changes for a single session.
+#[derive(Debug)]
+struct SessionChangeFeed {
+    send: mpsc::UnboundedSender<FeedMessage>,
+    user_name: String,
+    connection_id: Uuid,
+    database_id: Uuid,
+    table: Arc<PersistedTable>,
+}
+
+impl MutationObserver for SessionChangeFeed {
+    fn observe(&self, mutations: &[Mutation], state: &CatalogState) {
+        let timestamp = Utc::now();
+        let events: Vec<_> = mutations
+            .iter()
+            .filter_map(describe_mutation)
+            .map(|change| CatalogEvent {
+                timestamp,
+                catalog_version: state.version,
+                user_name: self.user_name.clone(),
+                connection_id: self.connection_id,
+                change,
+            })
+            .collect();
+        if events.is_empty() {
+            return;
+        }
+
+        let msg = FeedMessage::Events {
+            database_id: self.database_id,
+            table: self.table.clone(),
+            events,
+        };
+        if self.send.send(msg).is_err() {
+            error!("change feed writer stopped, catalog change not recorded");
+        }
+    }
+}
+
+/// What changed in the catalog.
+#[derive(Debug, Clone, PartialEq)]
+struct CatalogChange {
+    event_type: &'static str,
+    object_type: &'static str,
+    /// Database containing the object, `None` for objects not in a database
+    /// (including databases themselves).
+    database_name: Option<String>,
+    /// Schema containing the object, `None` for objects not in a schema.
+    schema_name: Option<String>,
+    object_name: Option<String>,
+    details: Option<String>,
+}
+
+impl CatalogChange {
+    fn new(event_type: &'static str, object_type: &'static str) -> CatalogChange {
+        CatalogChange {
+            event_type,
+            object_type,
+            database_name: None,
+            schema_name: None,
+            object_name: None,
+            details: None,
+        }
+    }
+
+    fn in_database(mut self, database: &str) -> CatalogChange {
+        self.database_name = Some(database.to_string());
+        self
+    }
+
+    fn in_schema(mut self, database: &str, schema: &str) -> CatalogChange {
+        self.database_name = Some(database.to_string());
+        self.schema_name = Some(schema.to_string());
+        self
+    }
+
+    fn named(mut self, name: &str) -> CatalogChange {
+        self.object_name = Some(name.to_string());
+        self
+    }
+
+    fn with_details(mut self, details: impl Into<String>) -> CatalogChange {
+        self.details = Some(details.into());
+        self
+    }
+}
+
+/// Describe the change a mutation makes.
+///
+/// Returns `None` for mutations that don't change objects in the catalog,
+/// like updating table statistics.
+fn describe_mutation(mutation: &Mutation) -> Option<CatalogChange> {
+    Some(match mutation {
+        Mutation::CreateDatabase(m) => CatalogChange::new("create", "database").named(&m.name),
+        Mutation::CreateExternalDatabase(m) => CatalogChange::new("create", "database")
+            .named(&m.name)
+            .with_details(m.options.as_str()),
+        Mutation::AlterDatabase(m) => {
+            let details = match &m.operation {
+                AlterDatabaseOperation::RenameDatabase { new_name } => {
+                    format!("rename to {new_name}")
+                }
+                AlterDatabaseOperation::SetAccessMode { access_mode } => {
+                    format!("set access mode {access_mode}")
+                }
+                AlterDatabaseOperation::SetVariable { variable, .. } => format!("set {variable}"),
+                AlterDatabaseOperation::ResetVariable { variable } => format!("reset {variable}"),
+            };
+            CatalogChange::new("alter", "database")
+                .named(&m.name)
+                .with_details(details)
+        }
+        Mutation::DropDatabase(m) => CatalogChange::new("drop", "database").named(&m.name),
+        Mutation::CreateSchema(m) => CatalogChange::new("create", "schema")
+            .in_database(&m.database)
+            .named(&m.name),
+        Mutation::AlterSchema(m) => {
+            let details = match &m.operation {
+                AlterSchemaOperation::RenameSchema { new_name } => format!("rename to {new_name}"),
+                AlterSchemaOperation::SetTags { tags } => {
+                    format!("set tags {}", join(tags.keys()))
+                }
+                AlterSchemaOperation::UnsetTags { keys } => format!("unset tags {}", join(keys)),
+            };
+            CatalogChange::new("alter", "schema")
+                .in_database(&m.database)
+                .named(&m.name)
+                .with_details(details)
+        }
+        Mutation::DropSchema(m) => {
+            let change = CatalogChange::new("drop", "schema")
+                .in_database(&m.database)
+                .named(&m.name);
+            if m.cascade {
+                change.with_details("cascade")
+            } else {
+                change
+            }
+        }
+        Mutation::CreateTable(m) => CatalogChange::new("create", "table")
+            .in_schema(&m.database, &m.schema)
+            .named(&m.name),
+        Mutation::CreateExternalTable(m) => CatalogChange::new("create", "table")
+            .in_schema(&m.database, &m.schema)
+            .named(&m.name)
+            .with_details(m.options.as_str()),
+        Mutation::CreateView(m) => CatalogChange::new("create", "view")
+            .in_schema(&m.database, &m.schema)
+            .named(&m.name),
+        Mutation::AlterTable(m) => {
+            let details = match &m.operation {
+                AlterTableOperation::RenameTable { new_name } => format!("rename to {new_name}"),
+                AlterTableOperation::SetAccessMode { access_mode } => {
+                    format!("set access mode {access_mode}")
+                }
+                AlterTableOperation::RefreshSchema { .. } => "refresh schema".to_string(),
+                AlterTableOperation::SetTags { tags } => format!("set tags {}", join(tags.keys())),
+                AlterTableOperation::UnsetTags { keys } => format!("unset tags {}", join(keys)),
+            };
+            CatalogChange::new("alter", "table")
+                .in_schema(&m.database, &m.schema)
+                .named(&m.name)
+                .with_details(details)
+        }
+        // The mutation doesn't say whether it's a table or a view.
+        Mutation::DropObject(m) => {
+            let change = CatalogChange::new("drop", "object")
+                .in_schema(&m.database, &m.schema)
+                .named(&m.name);
+            if m.cascade {
+                change.with_details("cascade")
+            } else {
+                change
+            }
+        }
+        Mutation::CommentOn(m) => {
+            let change = match (&m.name, &m.column) {
+                (Some(name), Some(column)) => CatalogChange::new("comment", "column")
+                    .in_schema(&m.database, &m.schema)
+                    .named(&format!("{name}.{column}")),
+                (Some(name), None) => CatalogChange::new("comment", "object")
+                    .in_schema(&m.database, &m.schema)
+                    .named(name),
+                (None, _) => CatalogChange::new("comment", "schema")
+                    .in_database(&m.database)
+                    .named(&m.schema),
+            };
+            if m.comment.is_some() {
+                change
+            } else {
+                change.with_details("removed")
+            }
+        }
+        Mutation::AlterRole(m) => {
+            let details = match &m.operation {
+                AlterRoleOperation::SetVariable { variable, .. } => format!("set {variable}"),
+                AlterRoleOperation::ResetVariable { variable } => format!("reset {variable}"),
+                AlterRoleOperation::SetPassword {
+                    scram_verifier: Some(_),
+                } => "set password".to_string(),
+                AlterRoleOperation::SetPassword {
+                    scram_verifier: None,
+                } => "remove password".to_string(),
+                AlterRoleOperation::SetReadOnly { read_only: true } => "read only".to_string(),
+                AlterRoleOperation::SetReadOnly { read_only: false } => "read write".to_string(),
+            };
+            let change = CatalogChange::new("alter", "role")
+                .named(&m.name)
+                .with_details(details);
+            match &m.database {
+                Some(database) => change.in_database(database),
+                None => change,
+            }
+        }
+        Mutation::CreateTunnel(m) => CatalogChange::new("create", "tunnel")
+            .named(&m.name)
+            .with_details(m.options.as_str()),
+        Mutation::AlterTunnelRotateKeys(m) => CatalogChange::new("alter", "tunnel")
+            .named(&m.name)
+            .with_details("rotate keys"),
+        Mutation::DropTunnel(m) => CatalogChange::new("drop", "tunnel").named(&m.name),
+        Mutation::CreateCredentials(m) => CatalogChange::new("create", "credentials")
+            .named(&m.name)
+            .with_details(m.options.as_str()),
+        Mutation::CreateCredential(m) => CatalogChange::new("create", "credentials")
+            .named(&m.name)
+            .with_details(m.options.as_str()),
+        Mutation::DropCredentials(m) => CatalogChange::new("drop", "credentials").named(&m.name),
+        Mutation::RestoreCatalog(_) => CatalogChange::new("restore", "catalog"),
+        Mutation::UpdateTableStatistics(_) | Mutation::UpdateDeploymentStorage(_) => return None,
+    })
+}
+
+fn join<'a>(items: impl IntoIterator<Item = &'a String>) -> String {
+    items
+        .into_iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A single change made to the catalog.
+#[derive(Debug, Clone, PartialEq)]
+struct CatalogEvent {
+    timestamp: DateTime<Utc>,
+    catalog_version: u64,
+    user_name: String,
+    connection_id: Uuid,
+    change: CatalogChange,
+}
+
+impl CatalogEvent {
+    fn to_json(&self, database_id: Uuid) -> serde_json::Value {
+        json!({
+            "timestamp": self.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+            "database_id": database_id.to_string(),
+            "catalog_version": self.catalog_version,
+            "user_name": self.user_name,
+            "connection_id": self.connection_id.to_string(),
+            "event_type": self.change.event_type,
+            "object_type": self.change.object_type,
+            "database_name": self.change.database_name,
+            "schema_name": self.change.schema_name,
+            "object_name": self.change.object_name,
+            "details": self.change.details,
+        })
+    }
+}
+
+#[derive(Debug)]
+enum FeedMessage {
+    Events {
+        database_id: Uuid,
+        table: Arc<PersistedTable>,
+        events: Vec<CatalogEvent>,
+    },
+    /// Write all pending events, notifying the sender once done.
+    Flush(oneshot::Sender<()>),
+}
+
+/// Events pending for a single database's table.
+struct DatabaseEvents {
+    table: Arc<PersistedTable>,
+    pending: Vec<CatalogEvent>,
+}
+
+struct Webhook {
+    url: Url,
+    client: reqwest::Client,
+    /// Events with the id of the database they happened in.
+    pending: Vec<(Uuid, CatalogEvent)>,
+}
+
+impl Webhook {
+    async fn post(&self) -> Result<()> {
+        let events: Vec<_> = self
+            .pending
+            .iter()
+            .map(|(database_id, event)| event.to_json(*database_id))
+            .collect();
+        self.client
+            .post(self.url.clone())
+            .timeout(WEBHOOK_TIMEOUT)
+            .json(&json!({ "events": events }))
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|e| ExecError::String(format!("failed to post catalog events: {e}")))?;
+        Ok(())
+    }
+}
+
+/// Writes events to tables and the webhook in the background.
+struct FeedWorker {
+    webhook: Option<Webhook>,
+    databases: HashMap<Uuid, DatabaseEvents>,
+}
+
+impl FeedWorker {
+    async fn run(mut self, mut recv: mpsc::UnboundedReceiver<FeedMessage>) {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            tokio::select! {
+                msg = recv.recv() => match msg {
+                    Some(FeedMessage::Events { database_id, table, events }) => {
+                        self.push(database_id, table, events)
+                    }
+                    Some(FeedMessage::Flush(tx)) => {
+                        self.flush().await;
+                        let _ = tx.send(());
+                    }
+                    None => {
+                        // All handles dropped.
+                        self.flush().await;
+                        return;
+                    }
+                },
+                _ = interval.tick() => self.flush().await,
+            }
+        }
+    }
+
+    fn push(&mut self, database_id: Uuid, table: Arc<PersistedTable>, events: Vec<CatalogEvent>) {
+        if let Some(webhook) = &mut self.webhook {
+            webhook
+                .pending
+                .extend(events.iter().map(|event| (database_id, event.clone())));
+            truncate_oldest(&mut webhook.pending, "webhook");
+        }
+
+        let db = self
+            .databases
+            .entry(database_id)
+            .or_insert_with(|| DatabaseEvents {
+                table: table.clone(),
+                pending: Vec::new(),
+            });
+        // Use the most recent table since the catalog the events came from
+        // may be newer.
+        db.table = table;
+        db.pending.extend(events);
+        truncate_oldest(&mut db.pending, "table");
+    }
+
+    /// Write out pending events to all destinations.
+    ///
+    /// Events are retained on failure so that writing can be retried on the
+    /// next flush. Destinations are retried independently, so a failing
+    /// webhook doesn't cause events to be written to tables more than once.
+    async fn flush(&mut self) {
+        for (database_id, db) in self.databases.iter_mut() {
+            if db.pending.is_empty() {
+                continue;
+            }
+            match events_to_batch(&db.pending) {
+                Ok(batch) => match db.table.append(batch).await {
+                    Ok(()) => db.pending.clear(),
+                    Err(e) => {
+                        error!(%e, %database_id, num_events = db.pending.len(), "failed to write catalog events")
+                    }
+                },
+                Err(e) => {
+                    // Same events would fail again, don't hold on to them.
+                    error!(%e, %database_id, "failed to build catalog events batch");
+                    db.pending.clear();
+                }
+            }
+        }
+
+        if let Some(webhook) = &mut self.webhook {
+            if !webhook.pending.is_empty() {
+                match webhook.post().await {
+                    Ok(()) => webhook.pending.clear(),
+                    Err(e) => {
+                        error!(%e, num_events = webhook.pending.len(), "failed to send catalog events to webhook")
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn truncate_oldest<T>(pending: &mut Vec<T>, destination: &str) {
+    if pending.len() > MAX_RETAINED_EVENTS {
+        let num_dropped = pending.len() - MAX_RETAINED_EVENTS;
+        error!(%num_dropped, %destination, "dropping oldest catalog events");
+        pending.drain(..num_dropped);
+    }
+}
+
+fn events_to_batch(events: &[CatalogEvent]) -> Result<RecordBatch> {
+    let mut timestamp = TimestampMicrosecondBuilder::new().with_timezone("UTC");
+    let mut catalog_version = UInt64Builder::new();
+    let mut user_name = StringBuilder::new();
+    let mut connection_id = StringBuilder::new();
+    let mut event_type = StringBuilder::new();
+    let mut object_type = StringBuilder::new();
+    let mut database_name = StringBuilder::new();
+    let mut schema_name = StringBuilder::new();
+    let mut object_name = StringBuilder::new();
+    let mut details = StringBuilder::new();
+
+    for event in events {
+        timestamp.append_value(event.timestamp.timestamp_micros());
+        catalog_version.append_value(event.catalog_version);
+        user_name.append_value(&event.user_name);
+        connection_id.append_value(event.connection_id.to_string());
+        event_type.append_value(event.change.event_type);
+        object_type.append_value(event.change.object_type);
+        database_name.append_option(event.change.database_name.as_ref());
+        schema_name.append_option(event.change.schema_name.as_ref());
+        object_name.append_option(event.change.object_name.as_ref());
+        details.append_option(event.change.details.as_ref());
+    }
+
+    Ok(RecordBatch::try_new(
+        Arc::new(GLARE_CATALOG_EVENTS.arrow_schema()),
+        vec![
+            Arc::new(timestamp.finish()),
+            Arc::new(catalog_version.finish()),
+            Arc::new(user_name.finish()),
+            Arc::new(connection_id.finish()),
+            Arc::new(event_type.finish()),
+            Arc::new(object_type.finish()),
+            Arc::new(database_name.finish()),
+            Arc::new(schema_name.finish()),
+            Arc::new(object_name.finish()),
+            Arc::new(details.finish()),
+        ],
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use protogen::metastore::types::service::{AlterTable, CreateSchema, DropObject};
+
+    use super::*;
+
+    #[test]
+    fn describes_mutations() {
+        let change = describe_mutation(&Mutation::CreateSchema(CreateSchema {
+            database: "default".to_string(),
+            name: "mario".to_string(),
+            if_not_exists: false,
+        }))
+        .unwrap();
+        assert_eq!(
+            CatalogChange::new("create", "schema")
+                .in_database("default")
+                .named("mario"),
+            change
+        );
+
+        let change = describe_mutation(&Mutation::AlterTable(AlterTable {
+            database: "default".to_string(),
+            schema: "mario".to_string(),
+            name: "kart".to_string(),
+            operation: AlterTableOperation::SetTags {
+                tags: BTreeMap::from([
+                    ("team".to_string(), "racing".to_string()),
+                    ("tier".to_string(), "gold".to_string()),
+                ]),
+            },
+        }))
+        .unwrap();
+        assert_eq!(
+            CatalogChange::new("alter", "table")
+                .in_schema("default", "mario")
+                .named("kart")
+                .with_details("set tags team, tier"),
+            change
+        );
+
+        let change = describe_mutation(&Mutation::DropObject(DropObject {
+            database: "default".to_string(),
+            schema: "mario".to_string(),
+            name: "kart".to_string(),
+            if_exists: false,
+            cascade: true,
+        }))
+        .unwrap();
+        assert_eq!(Some("cascade"), change.details.as_deref());
+    }
+
+    #[test]
+    fn batch_matches_table_schema() {
+        let event = CatalogEvent {
+            timestamp: Utc::now(),
+            catalog_version: 4,
+            user_name: "glaredb".to_string(),
+            connection_id: Uuid::nil(),
+            change: CatalogChange::new("drop", "tunnel").named("ssh"),
+        };
+
+        let batch = events_to_batch(&[event.clone(), event]).unwrap();
+        assert_eq!(2, batch.num_rows());
+        assert_eq!(
+            GLARE_CATALOG_EVENTS.arrow_schema(),
+            batch.schema().as_ref().clone()
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Limits for client connections.
+//!
+//! Frontends acquire a permit for every connection (or connection-like
+//! session) they open, and hold it until the connection closes. Connections
+//! that are waiting for the client for longer than the idle session timeout
+//! should be closed by the frontend.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tracing::debug;
+
+use crate::errors::{ExecError, Result};
+
+/// Limits for client connections. Nothing is limited by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionLimits {
+    /// Max number of open connections across all users.
+    pub max_connections: Option<usize>,
+    /// Max number of open connections for a single user.
+    pub max_connections_per_user: Option<usize>,
+    /// How long a connection may wait for the client before being closed.
+    pub idle_session_timeout: Option<Duration>,
+}
+
+/// Tracks open connections and admits new ones according to the configured
+/// limits.
+///
+/// Cheaply cloneable, all clones share the same limits and counts. Limits may
+/// be changed while connections are open with
+/// [`ConnectionLimiter::set_limits`].
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionLimiter {
+    inner: Arc<Mutex<LimiterState>>,
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    limits: ConnectionLimits,
+    /// Number of open connections.
+    open: usize,
+    /// Number of open connections per user.
+    open_per_user: HashMap<String, usize>,
+}
+
+impl ConnectionLimiter {
+    pub fn new(limits: ConnectionLimits) -> Self {
+        let limiter = Self::default();
+        limiter.set_limits(limits);
+        limiter
+    }
+
+    /// A limiter that admits every connection, and never closes idle ones.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Get the current limits.
+    pub fn limits(&self) -> ConnectionLimits {
+        self.inner.lock().limits
+    }
+
+    /// Change the limits.
+    ///
+    /// Lowering the max number of connections doesn't close connections that
+    /// are already open, new connections are rejected until enough of them
+    /// close.
+    pub fn set_limits(&self, limits: ConnectionLimits) {
+        self.inner.lock().limits = limits;
+    }
+
+    /// How long connections may wait for the client before being closed,
+    /// `None` if idle connections are never closed.
+    pub fn idle_session_timeout(&self) -> Option<Duration> {
+        self.inner.lock().limits.idle_session_timeout
+    }
+
+    /// Admit a new connection for `user`, erroring if it would exceed the
+    /// limits.
+    ///
+    /// The returned permit must be held for as long as the connection is open.
+    pub fn try_acquire(&self, user: &str) -> Result<ConnectionPermit> {
+        let mut state = self.inner.lock();
+
+        if let Some(max) = state.limits.max_connections {
+            if state.open >= max {
+                return Err(ExecError::TooManyConnections { max });
+            }
+        }
+        let user_open = state.open_per_user.get(user).copied().unwrap_or(0);
+        if let Some(max) = state.limits.max_connections_per_user {
+            if user_open >= max {
+                return Err(ExecError::TooManyConnectionsForUser {
+                    user: user.to_string(),
+                    max,
+                });
+            }
+        }
+
+        state.open += 1;
+        state.open_per_user.insert(user.to_string(), user_open + 1);
+        debug!(%user, open = state.open, "connection admitted");
+
+        Ok(ConnectionPermit {
+            limiter: self.inner.clone(),
+            user: user.to_string(),
+        })
+    }
+
+    /// Get the number of open connections.
+    pub fn open(&self) -> usize {
+        self.inner.lock().open
+    }
+}
+
+/// Permission to keep a connection open. The connection is released on drop.
+#[derive(Debug)]
+pub struct ConnectionPermit {
+    limiter: Arc<Mutex<LimiterState>>,
+    user: String,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        let mut state = self.limiter.lock();
+        state.open -= 1;
+        if let Some(count) = state.open_per_user.get_mut(&self.user) {
+            *count -= 1;
+            if *count == 0 {
+                state.open_per_user.remove(&self.user);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlimited() {
+        let limiter = ConnectionLimiter::unlimited();
+        let permits: Vec<_> = (0..10).map(|_| limiter.try_acquire("a").unwrap()).collect();
+        assert_eq!(10, limiter.open());
+
+        drop(permits);
+        assert_eq!(0, limiter.open());
+    }
+
+    #[test]
+    fn max_connections() {
+        let limiter = ConnectionLimiter::new(ConnectionLimits {
+            max_connections: Some(2),
+            ..Default::default()
+        });
+
+        let first = limiter.try_acquire("a").unwrap();
+        let _second = limiter.try_acquire("b").unwrap();
+        let err = limiter.try_acquire("c").unwrap_err();
+        assert!(
+            matches!(err, ExecError::TooManyConnections { max: 2 }),
+            "{err}"
+        );
+
+        drop(first);
+        limiter.try_acquire("c").unwrap();
+    }
+
+    #[test]
+    fn max_connections_per_user() {
+        let limiter = ConnectionLimiter::new(ConnectionLimits {
+            max_connections_per_user: Some(1),
+            ..Default::default()
+        });
+
+        let first = limiter.try_acquire("a").unwrap();
+        let _other_user = limiter.try_acquire("b").unwrap();
+        let err = limiter.try_acquire("a").unwrap_err();
+        assert!(
+            matches!(err, ExecError::TooManyConnectionsForUser { max: 1, .. }),
+            "{err}"
+        );
+
+        drop(first);
+        limiter.try_acquire("a").unwrap();
+    }
+
+    #[test]
+    fn lowering_limits_keeps_open_connections() {
+        let limiter = ConnectionLimiter::unlimited();
+        let _permits: Vec<_> = (0..3).map(|_| limiter.try_acquire("a").unwrap()).collect();
+
+        limiter.set_limits(ConnectionLimits {
+            max_connections: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(3, limiter.open());
+        limiter.try_acquire("a").unwrap_err();
+    }
+}
@@ -1,10 +1,15 @@
+use crate::admission::QueryLimiter;
 use crate::distexec::scheduler::Scheduler;
 use crate::environment::EnvironmentReader;
 use crate::errors::{internal, ExecError, Result};
+use crate::memory::{MemoryTracker, SessionMemory};
 use crate::parser::StatementWithExtensions;
+use crate::plan_cache::{PlanCache, PlanCacheKey, SessionPlanCache};
 use crate::planner::logical_plan::*;
 use crate::planner::session_planner::SessionPlanner;
 use crate::remote::client::{RemoteClient, RemoteSessionClient};
+use crate::remote::distribute::Workers;
+use crate::user_functions::{UserTableFunc, UserTableFuncs};
 use catalog::mutator::CatalogMutator;
 use catalog::session_catalog::SessionCatalog;
 use datafusion::arrow::datatypes::{DataType, Field as ArrowField, Schema as ArrowSchema};
@@ -12,24 +17,29 @@ use datafusion::common::SchemaReference;
 use datafusion::execution::context::{
     SessionConfig, SessionContext as DfSessionContext, SessionState, TaskContext,
 };
+use datafusion::execution::runtime_env::RuntimeEnv;
 use datafusion::scalar::ScalarValue;
 use datafusion::sql::TableReference;
+use datafusion_ext::join_order::JoinReorder;
+use datafusion_ext::reload::ConfigReloader;
 use datafusion_ext::session_metrics::SessionMetricsHandler;
 use datafusion_ext::vars::SessionVars;
+use datafusion_ext::warnings::SessionWarnings;
 use datasources::native::access::NativeTableStorage;
 use pgrepr::format::Format;
 use pgrepr::types::arrow_to_pg_type;
 
 use datafusion::variable::VarType;
+use protogen::metastore::types::catalog::RolePassword;
 use protogen::rpcsrv::types::service::{
     InitializeSessionRequest, InitializeSessionRequestFromClient,
 };
-use sqlbuiltins::builtins::DEFAULT_CATALOG;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::slice;
 use std::sync::Arc;
 use tokio_postgres::types::Type as PgType;
+use tracing::{info_span, Instrument};
 
 use datafusion_ext::runtime::group_pull_up::RuntimeGroupPullUp;
 use uuid::Uuid;
@@ -61,8 +71,22 @@ pub struct LocalSessionContext {
     df_ctx: DfSessionContext,
     /// Read tables from the environment.
     env_reader: Option<Box<dyn EnvironmentReader>>,
+    /// Table functions registered on the engine.
+    engine_table_funcs: UserTableFuncs,
+    /// Table functions registered on only this session.
+    table_funcs: UserTableFuncs,
     /// Task scheduler.
     task_scheduler: Scheduler,
+    /// Limiter for queries executing across all sessions.
+    query_limiter: QueryLimiter,
+    /// Memory usage for all sessions on the engine.
+    memory_tracker: MemoryTracker,
+    /// Memory usage for this session.
+    memory: Arc<SessionMemory>,
+    /// Cache of planned statements, possibly shared with other sessions.
+    plan_cache: Arc<SessionPlanCache>,
+    /// Warnings raised by queries in this session.
+    warnings: Arc<SessionWarnings>,
 }
 
 impl LocalSessionContext {
@@ -76,8 +100,17 @@ impl LocalSessionContext {
         metrics_handler: SessionMetricsHandler,
         spill_path: Option<PathBuf>,
         task_scheduler: Scheduler,
+        query_limiter: QueryLimiter,
+        memory_tracker: MemoryTracker,
+        config_reloader: Option<ConfigReloader>,
+        workers: Option<Arc<Workers>>,
+        engine_table_funcs: UserTableFuncs,
+        plan_cache: PlanCache,
     ) -> Result<LocalSessionContext> {
         let database_id = vars.database_id();
+        let memory = memory_tracker.register_session(vars.connection_id(), vars.user_name());
+        let plan_cache = Arc::new(SessionPlanCache::new(plan_cache));
+        let warnings = Arc::new(SessionWarnings::new());
         let runtime = new_datafusion_runtime_env(&vars, &catalog, spill_path)?;
         let opts = new_datafusion_session_config_opts(&vars);
 
@@ -87,9 +120,18 @@ impl LocalSessionContext {
         conf = conf
             .with_extension(Arc::new(catalog_mutator))
             .with_extension(Arc::new(native_tables.clone()))
-            .with_extension(Arc::new(catalog.get_temp_catalog().clone()));
+            .with_extension(Arc::new(catalog.get_temp_catalog().clone()))
+            .with_extension(plan_cache.clone())
+            .with_extension(warnings.clone());
+        if let Some(config_reloader) = config_reloader {
+            conf = conf.with_extension(Arc::new(config_reloader));
+        }
+        if let Some(workers) = workers {
+            conf = conf.with_extension(workers);
+        }
 
         let state = SessionState::new_with_config_rt(conf, Arc::new(runtime))
+            .add_optimizer_rule(Arc::new(JoinReorder {}))
             .add_physical_optimizer_rule(Arc::new(RuntimeGroupPullUp {}));
 
         let df_ctx = DfSessionContext::new_with_state(state);
@@ -105,7 +147,14 @@ impl LocalSessionContext {
             metrics_handler,
             df_ctx,
             env_reader: None,
+            engine_table_funcs,
+            table_funcs: UserTableFuncs::default(),
             task_scheduler,
+            query_limiter,
+            memory_tracker,
+            memory,
+            plan_cache,
+            warnings,
         })
     }
 
@@ -138,9 +187,12 @@ impl LocalSessionContext {
         conf = conf
             .with_extension(Arc::new(CatalogMutator::empty()))
             .with_extension(Arc::new(self.get_native_tables().clone()))
-            .with_extension(Arc::new(catalog.get_temp_catalog().clone()));
+            .with_extension(Arc::new(catalog.get_temp_catalog().clone()))
+            .with_extension(self.plan_cache.clone())
+            .with_extension(self.warnings.clone());
 
         let state = SessionState::new_with_config_rt(conf, runtime)
+            .add_optimizer_rule(Arc::new(JoinReorder {}))
             .add_physical_optimizer_rule(Arc::new(RuntimeGroupPullUp {}));
 
         let df_ctx = DfSessionContext::new_with_state(state);
@@ -161,6 +213,18 @@ impl LocalSessionContext {
         self.env_reader.as_deref()
     }
 
+    /// Register a table function for only this session. Functions registered
+    /// on the session take precedence over ones registered on the engine.
+    pub fn register_table_function(&mut self, func: UserTableFunc) -> Result<()> {
+        self.table_funcs.register(func)
+    }
+
+    pub fn get_table_function(&self, name: &str) -> Option<Arc<UserTableFunc>> {
+        self.table_funcs
+            .get(name)
+            .or_else(|| self.engine_table_funcs.get(name))
+    }
+
     pub fn get_metrics_handler(&self) -> SessionMetricsHandler {
         self.metrics_handler.clone()
     }
@@ -177,6 +241,14 @@ impl LocalSessionContext {
         self.task_scheduler.clone()
     }
 
+    pub fn get_query_limiter(&self) -> &QueryLimiter {
+        &self.query_limiter
+    }
+
+    pub fn get_memory_tracker(&self) -> &MemoryTracker {
+        &self.memory_tracker
+    }
+
     /// Return the DF session context.
     pub fn df_ctx(&self) -> &DfSessionContext {
         &self.df_ctx
@@ -214,6 +286,17 @@ impl LocalSessionContext {
         &mut self.catalog
     }
 
+    /// Get the password verifiers of all roles.
+    ///
+    /// Verifiers are kept out of the session catalog. Sessions attached to a
+    /// remote server have no access to them and always get an empty list.
+    pub async fn get_role_passwords(&self) -> Result<Vec<RolePassword>> {
+        match self.catalog_mutator().get_metastore_client() {
+            Some(client) => Ok(client.get_role_passwords().await?),
+            None => Ok(Vec::new()),
+        }
+    }
+
     pub async fn maybe_refresh_state(&mut self) -> Result<()> {
         let mutator = self.catalog_mutator();
         let client = mutator.get_metastore_client();
@@ -232,20 +315,89 @@ impl LocalSessionContext {
     ) -> Result<()> {
         // Refresh the cached catalog state if necessary
         self.maybe_refresh_state().await?;
+        self.check_prepared_statement_name(&name)?;
+
+        let stmt = PreparedStatement::build(stmt, self)
+            .instrument(info_span!("plan_statement"))
+            .await?;
+        self.prepared.insert(name, stmt);
+
+        Ok(())
+    }
+
+    /// Create a prepared statement from a cached plan for the same SQL text.
+    ///
+    /// Returns false if there's no usable cached plan, in which case the
+    /// statement should be parsed and prepared with `prepare_statement`.
+    pub async fn prepare_cached_statement(&mut self, name: String, sql: &str) -> Result<bool> {
+        self.maybe_refresh_state().await?;
+
+        let key = match self.plan_cache_key(sql) {
+            Some(key) => key,
+            None => return Ok(false),
+        };
+        let stmt = match self.plan_cache.get(&key, self.catalog.version()).await {
+            Some(stmt) => stmt,
+            None => return Ok(false),
+        };
+
+        self.check_prepared_statement_name(&name)?;
+        self.prepared.insert(name, stmt);
+
+        Ok(true)
+    }
+
+    /// Cache the plan of a prepared statement created from `sql`, allowing
+    /// later prepares of the same SQL text to skip planning.
+    ///
+    /// Statements that can't be cached are ignored.
+    pub fn cache_prepared_statement(&self, name: &str, sql: &str) {
+        if let (Some(key), Some(stmt)) = (self.plan_cache_key(sql), self.prepared.get(name)) {
+            self.plan_cache
+                .cache()
+                .insert(key, self.catalog.version(), stmt);
+        }
+    }
+
+    /// Get the plan cache for this session.
+    pub fn get_plan_cache(&self) -> &SessionPlanCache {
+        &self.plan_cache
+    }
+
+    /// Get the warnings raised by queries in this session.
+    pub fn get_warnings(&self) -> &SessionWarnings {
+        &self.warnings
+    }
 
-        // Unnamed (empty string) prepared statements can be overwritten
-        // whenever. Named prepared statements must be explicitly removed before
-        // being used again.
-        if !name.is_empty() && self.prepared.contains_key(&name) {
+    /// Key for caching the plan for `sql`, `None` if plans can't currently be
+    /// cached for this session.
+    fn plan_cache_key(&self, sql: &str) -> Option<PlanCacheKey> {
+        // Remote sessions plan using stub table providers, and temp tables
+        // may shadow native tables with the same name.
+        if self.exec_client.is_some() || !self.catalog.get_temp_catalog().is_empty() {
+            return None;
+        }
+        let vars = self.get_session_vars();
+        Some(PlanCacheKey {
+            database_id: self.database_id,
+            database: vars.database(),
+            dialect: vars.dialect(),
+            search_path: vars.search_path(),
+            timezone: vars.timezone(),
+            sql: sql.to_string(),
+        })
+    }
+
+    /// Unnamed (empty string) prepared statements can be overwritten
+    /// whenever. Named prepared statements must be explicitly removed before
+    /// being used again.
+    fn check_prepared_statement_name(&self, name: &str) -> Result<()> {
+        if !name.is_empty() && self.prepared.contains_key(name) {
             return Err(internal!(
                 "named prepared statments must be deallocated before reuse, name: {}",
                 name
             ));
         }
-
-        let stmt = PreparedStatement::build(stmt, self).await?;
-        self.prepared.insert(name, stmt);
-
         Ok(())
     }
 
@@ -320,16 +472,47 @@ impl LocalSessionContext {
         self.portals.remove(name);
     }
 
-    /// Get a datafusion task context to use for physical plan execution.
-    pub(crate) fn task_context(&self) -> Arc<TaskContext> {
-        self.df_ctx.task_ctx()
+    /// Get the datafusion session state to plan a query with.
+    ///
+    /// Session variables that map to datafusion options may have been changed
+    /// since the context was created, those are applied here.
+    pub(crate) fn df_state(&self) -> SessionState {
+        let mut state = self.df_ctx.state();
+        state.config_mut().options_mut().execution.batch_size =
+            self.get_session_vars().batch_size().max(1);
+        state
+    }
+
+    /// Get a datafusion task context to use for executing a single query.
+    ///
+    /// Memory reserved during execution is attributed to the query in the
+    /// engine's memory tracker.
+    pub(crate) fn task_context(&self, query_text: &str) -> Arc<TaskContext> {
+        let state = self.df_state();
+        let runtime = state.runtime_env();
+        let runtime = RuntimeEnv {
+            memory_pool: self
+                .memory
+                .new_query_pool(runtime.memory_pool.clone(), query_text),
+            ..runtime.as_ref().clone()
+        };
+
+        Arc::new(TaskContext::new(
+            None,
+            state.session_id().to_string(),
+            state.config().clone(),
+            state.scalar_functions().clone(),
+            state.aggregate_functions().clone(),
+            state.window_functions().clone(),
+            Arc::new(runtime),
+        ))
     }
 
     /// Resolve schema reference.
     pub fn resolve_schema_ref(&self, r: SchemaReference<'_>) -> OwnedFullSchemaReference {
         match r {
             SchemaReference::Bare { schema } => FullSchemaReference {
-                database: DEFAULT_CATALOG.into(),
+                database: self.get_session_vars().database().into(),
                 schema: schema.into_owned().into(),
             },
             SchemaReference::Full { catalog, schema } => FullSchemaReference {
@@ -344,13 +527,13 @@ impl LocalSessionContext {
             TableReference::Bare { table } => {
                 let schema = self.first_nonimplicit_schema()?;
                 FullObjectReference {
-                    database: DEFAULT_CATALOG.into(),
+                    database: self.get_session_vars().database().into(),
                     schema: schema.into(),
                     name: table.into_owned().into(),
                 }
             }
             TableReference::Partial { schema, table } => FullObjectReference {
-                database: DEFAULT_CATALOG.into(),
+                database: self.get_session_vars().database().into(),
                 schema: schema.into_owned().into(),
                 name: table.into_owned().into(),
             },
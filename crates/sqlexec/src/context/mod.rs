@@ -91,6 +91,7 @@ pub(crate) fn new_datafusion_session_config_opts(vars: &SessionVars) -> ConfigOp
 
     config_opts.catalog = catalog_opts;
     config_opts.optimizer = optimizer_opts;
+    config_opts.execution.batch_size = vars.batch_size().max(1);
 
     // Insert extensions common to both local and remote sessions.
     let mut e = Extensions::new();
@@ -1,12 +1,13 @@
 use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use datafusion::{
-    datasource::TableProvider,
+    datasource::{MemTable, TableProvider},
     execution::context::{SessionConfig, SessionContext as DfSessionContext},
-    physical_plan::{execute_stream, ExecutionPlan, SendableRecordBatchStream},
+    physical_plan::{execute_stream, ExecutionPlan, RecordBatchStream, SendableRecordBatchStream},
 };
 use datafusion_ext::{functions::FuncParamValue, vars::SessionVars};
 use datasources::native::access::NativeTableStorage;
+use futures::TryStreamExt;
 use protogen::{
     metastore::types::catalog::{CatalogEntry, CatalogState},
     rpcsrv::types::service::ResolvedTableReference,
@@ -18,7 +19,11 @@ use crate::{
     dispatch::external::ExternalDispatcher,
     errors::{ExecError, Result},
     extension_codec::GlareDBExtensionCodec,
-    remote::{provider_cache::ProviderCache, staged_stream::StagedClientStreams},
+    planner::{errors::PlanError, physical_plan::is_write_plan},
+    remote::{
+        batch_stream::ExecutionBatchStream, provider_cache::ProviderCache,
+        staged_stream::StagedClientStreams,
+    },
 };
 use catalog::mutator::CatalogMutator;
 use catalog::session_catalog::SessionCatalog;
@@ -44,20 +49,21 @@ pub struct RemoteSessionContext {
     df_ctx: DfSessionContext,
     /// Cached table providers.
     provider_cache: ProviderCache,
+    /// If plans modifying the catalog or tables should be rejected.
+    read_only: bool,
 }
 
 impl RemoteSessionContext {
     /// Create a new remote session context.
+    ///
+    /// The vars identify the client the plans are executed for.
     pub fn new(
+        vars: SessionVars,
         catalog: SessionCatalog,
         catalog_mutator: CatalogMutator,
         native_tables: NativeTableStorage,
         spill_path: Option<PathBuf>,
     ) -> Result<Self> {
-        // TODO: We'll want to remove this eventually. We should be able to
-        // create a datafusion context/runtime without needing these vars.
-        let vars = SessionVars::default();
-
         let runtime = new_datafusion_runtime_env(&vars, &catalog, spill_path)?;
         let opts = new_datafusion_session_config_opts(&vars);
         let mut conf: SessionConfig = opts.into();
@@ -78,9 +84,16 @@ impl RemoteSessionContext {
             tables: native_tables,
             df_ctx,
             provider_cache: ProviderCache::default(),
+            read_only: false,
         })
     }
 
+    /// Reject plans that modify the catalog or tables.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
     pub fn get_datafusion_context(&self) -> &DfSessionContext {
         &self.df_ctx
     }
@@ -105,6 +118,11 @@ impl RemoteSessionContext {
         GlareDBExtensionCodec::new_decoder(&self.provider_cache)
     }
 
+    /// Get the id a table provider is cached under on this context.
+    pub fn cached_provider_id(&self, provider: &Arc<dyn TableProvider>) -> Option<Uuid> {
+        self.provider_cache.id_of(provider)
+    }
+
     fn catalog_mutator(&self) -> Arc<CatalogMutator> {
         self.df_ctx
             .state()
@@ -121,22 +139,45 @@ impl RemoteSessionContext {
             .expect("remote contexts should have streams registered")
     }
 
+    /// Execute a single partition of a physical plan.
+    pub fn execute_partition(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        partition: usize,
+    ) -> Result<SendableRecordBatchStream> {
+        self.check_read_only(&plan)?;
+        let context = self.df_ctx.task_ctx();
+        let stream = plan.execute(partition, context)?;
+        Ok(stream)
+    }
+
     /// Execute a physical plan.
     pub fn execute_physical(
         &self,
         plan: Arc<dyn ExecutionPlan>,
     ) -> Result<SendableRecordBatchStream> {
+        self.check_read_only(&plan)?;
         let context = self.df_ctx.task_ctx();
         let stream = execute_stream(plan, context)?;
         Ok(stream)
     }
 
+    fn check_read_only(&self, plan: &Arc<dyn ExecutionPlan>) -> Result<()> {
+        if self.read_only && is_write_plan(plan) {
+            return Err(PlanError::ReadOnlySession.into());
+        }
+        Ok(())
+    }
+
     /// Load a table provider, and cache it on the context.
     ///
     /// This will only attempt to load "native" tables and external tables.
     ///
     /// All parts of the table reference must be provided. It's expected that
     /// entry resolution happens client-side.
+    ///
+    /// The provider is cached under `provider_id` if provided, otherwise under
+    /// a new id.
     // TODO: We should be providing the catalog version as well to ensure we're
     // getting the correct entries from the catalog.
     pub async fn load_and_cache_table(
@@ -144,6 +185,7 @@ impl RemoteSessionContext {
         table_ref: ResolvedTableReference,
         args: Option<Vec<FuncParamValue>>,
         opts: Option<HashMap<String, FuncParamValue>>,
+        provider_id: Option<Uuid>,
     ) -> Result<(Uuid, Arc<dyn TableProvider>)> {
         // TODO: Remove this lock so we're not holding it for the duration of
         // the table load. We can do that by:
@@ -190,7 +232,26 @@ impl RemoteSessionContext {
             }
         };
 
-        let id = Uuid::new_v4();
+        let id = provider_id.unwrap_or_else(Uuid::new_v4);
+        self.provider_cache.put(id, prov.clone());
+
+        Ok((id, prov))
+    }
+
+    /// Collect batches uploaded by the client into an in-memory table, and
+    /// cache it on the context.
+    ///
+    /// The table lives for as long as the cached provider, and is used for
+    /// running joins against local data on the remote side.
+    pub async fn stage_table(
+        &self,
+        stream: ExecutionBatchStream,
+    ) -> Result<(Uuid, Arc<dyn TableProvider>)> {
+        let id = stream.work_id();
+        let schema = stream.schema();
+        let batches: Vec<_> = stream.try_collect().await?;
+
+        let prov: Arc<dyn TableProvider> = Arc::new(MemTable::try_new(schema, vec![batches])?);
         self.provider_cache.put(id, prov.clone());
 
         Ok((id, prov))
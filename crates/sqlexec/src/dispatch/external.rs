@@ -1,7 +1,10 @@
 use std::collections::HashMap;
+use std::future::Future;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use datafusion::arrow::datatypes::{Field, Schema, SchemaRef};
 use datafusion::common::FileType;
 use datafusion::datasource::file_format::csv::CsvFormat;
 use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
@@ -11,10 +14,13 @@ use datafusion::datasource::file_format::FileFormat;
 use datafusion::datasource::TableProvider;
 use datafusion::prelude::SessionContext;
 use datafusion_ext::functions::{DefaultTableContextProvider, FuncParamValue};
+use datafusion_ext::planner::parse_column_types;
+use datafusion_ext::vars::SessionVars;
 use datasources::bigquery::{BigQueryAccessor, BigQueryTableAccess};
 use datasources::bson::table::bson_streaming_table;
 use datasources::clickhouse::{ClickhouseAccess, ClickhouseTableProvider};
 use datasources::common::url::DatasourceUrl;
+use datasources::common::util::datasource_retry_policy;
 use datasources::debug::DebugTableType;
 use datasources::lake::delta::access::{load_table_direct, DeltaLakeAccessor};
 use datasources::lake::iceberg::table::IcebergTable;
@@ -23,26 +29,31 @@ use datasources::mongodb::{MongoDbAccessor, MongoDbTableAccessInfo};
 use datasources::mysql::{MysqlAccessor, MysqlTableAccess};
 use datasources::object_store::gcs::GcsStoreAccess;
 use datasources::object_store::generic::GenericStoreAccess;
+use datasources::object_store::inference::SchemaInference;
 use datasources::object_store::local::LocalStoreAccess;
 use datasources::object_store::s3::S3StoreAccess;
 use datasources::object_store::{ObjStoreAccess, ObjStoreAccessor};
-use datasources::postgres::{PostgresAccess, PostgresTableProvider, PostgresTableProviderConfig};
+use datasources::postgres::{
+    NumericFallback, PostgresAccess, PostgresTableProvider, PostgresTableProviderConfig,
+};
 use datasources::snowflake::{SnowflakeAccessor, SnowflakeDbConnection, SnowflakeTableAccess};
 use datasources::sqlserver::{
     SqlServerAccess, SqlServerTableProvider, SqlServerTableProviderConfig,
 };
+use object_store_util::retry::is_transient_message;
 use protogen::metastore::types::catalog::{CatalogEntry, DatabaseEntry, FunctionEntry, TableEntry};
 use protogen::metastore::types::options::{
     DatabaseOptions, DatabaseOptionsBigQuery, DatabaseOptionsClickhouse, DatabaseOptionsDebug,
     DatabaseOptionsDeltaLake, DatabaseOptionsMongoDb, DatabaseOptionsMysql,
-    DatabaseOptionsPostgres, DatabaseOptionsSnowflake, DatabaseOptionsSqlServer, TableOptions,
-    TableOptionsBigQuery, TableOptionsClickhouse, TableOptionsDebug, TableOptionsGcs,
-    TableOptionsInternal, TableOptionsLocal, TableOptionsMongoDb, TableOptionsMysql,
-    TableOptionsObjectStore, TableOptionsPostgres, TableOptionsS3, TableOptionsSnowflake,
-    TableOptionsSqlServer, TunnelOptions,
+    DatabaseOptionsPostgres, DatabaseOptionsSnowflake, DatabaseOptionsSqlServer,
+    SchemaInferenceOptions, TableOptions, TableOptionsBigQuery, TableOptionsClickhouse,
+    TableOptionsDebug, TableOptionsGcs, TableOptionsInternal, TableOptionsLocal,
+    TableOptionsMongoDb, TableOptionsMysql, TableOptionsObjectStore, TableOptionsPostgres,
+    TableOptionsS3, TableOptionsSnowflake, TableOptionsSqlServer, TunnelOptions,
 };
-use sqlbuiltins::builtins::DEFAULT_CATALOG;
 use sqlbuiltins::functions::FUNCTION_REGISTRY;
+use telemetry::metrics::METRICS;
+use tracing::debug;
 
 use catalog::session_catalog::SessionCatalog;
 
@@ -55,6 +66,9 @@ pub struct ExternalDispatcher<'a> {
     df_ctx: &'a SessionContext,
     /// Whether or not local file system access should be disabled.
     disable_local_fs_access: bool,
+    /// Whether schemas cached in the catalog can be used instead of inferring
+    /// them.
+    use_schema_cache: bool,
 }
 
 impl<'a> ExternalDispatcher<'a> {
@@ -67,7 +81,51 @@ impl<'a> ExternalDispatcher<'a> {
             catalog,
             df_ctx,
             disable_local_fs_access,
+            use_schema_cache: true,
+        }
+    }
+
+    /// Always infer schemas, ignoring any schema cached for the table.
+    pub fn without_schema_cache(mut self) -> Self {
+        self.use_schema_cache = false;
+        self
+    }
+
+    /// Get the schema cached for a table if it's still fresh according to
+    /// the `external_schema_cache_ttl` session variable.
+    fn cached_schema(&self, table: &TableEntry) -> Option<SchemaRef> {
+        if !self.use_schema_cache {
+            return None;
         }
+        let cached = self.catalog.table_schema(table.meta.id)?;
+
+        let cfg = self.df_ctx.copied_config();
+        let ttl = cfg
+            .options()
+            .extensions
+            .get::<SessionVars>()?
+            .external_schema_cache_ttl();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+        if now.saturating_sub(cached.inferred_at) >= ttl as i64 {
+            return None;
+        }
+
+        let fields: Vec<_> = cached
+            .columns
+            .iter()
+            .map(|col| Field::new(&col.name, col.arrow_type.clone(), col.nullable))
+            .collect();
+        Some(Arc::new(Schema::new(fields)))
+    }
+
+    /// Get the representation to use for external numerics that don't fit in
+    /// a `Decimal128`.
+    fn numeric_fallback(&self) -> Result<NumericFallback> {
+        let cfg = self.df_ctx.copied_config();
+        Ok(match cfg.options().extensions.get::<SessionVars>() {
+            Some(vars) => vars.numeric_fallback().parse::<NumericFallback>()?,
+            None => NumericFallback::default(),
+        })
     }
 
     pub async fn dispatch_external(
@@ -76,15 +134,15 @@ impl<'a> ExternalDispatcher<'a> {
         schema: &str,
         name: &str,
     ) -> Result<Arc<dyn TableProvider>> {
-        if database != DEFAULT_CATALOG {
-            let db = match self.catalog.resolve_database(database) {
-                Some(db) => db,
-                None => {
-                    return Err(DispatchError::MissingDatabase {
-                        database: database.to_string(),
-                    })
-                }
-            };
+        let db = match self.catalog.resolve_database(database) {
+            Some(db) => db,
+            None => {
+                return Err(DispatchError::MissingDatabase {
+                    database: database.to_string(),
+                })
+            }
+        };
+        if !matches!(db.options, DatabaseOptions::Internal(_)) {
             return self.dispatch_external_database(db, schema, name).await;
         }
 
@@ -108,11 +166,25 @@ impl<'a> ExternalDispatcher<'a> {
         self.dispatch_external_table(table).await
     }
 
+    /// Dispatch to a table in an external database, retrying if connecting to
+    /// the database fails with a transient error.
     pub async fn dispatch_external_database(
         &self,
         db: &DatabaseEntry,
         schema: &str,
         name: &str,
+    ) -> Result<Arc<dyn TableProvider>> {
+        self.retry_transient(db.options.as_str(), || {
+            self.dispatch_external_database_once(db, schema, name)
+        })
+        .await
+    }
+
+    async fn dispatch_external_database_once(
+        &self,
+        db: &DatabaseEntry,
+        schema: &str,
+        name: &str,
     ) -> Result<Arc<dyn TableProvider>> {
         let tunnel = self.get_tunnel_opts(db.tunnel_id)?;
 
@@ -124,7 +196,8 @@ impl<'a> ExternalDispatcher<'a> {
                 Ok(provider.into_table_provider(tunnel.as_ref()))
             }
             DatabaseOptions::Postgres(DatabaseOptionsPostgres { connection_string }) => {
-                let access = PostgresAccess::new_from_conn_str(connection_string, tunnel);
+                let access = PostgresAccess::new_from_conn_str(connection_string, tunnel)
+                    .with_numeric_fallback(self.numeric_fallback()?);
                 let prov_conf = PostgresTableProviderConfig {
                     access,
                     schema: schema.to_owned(),
@@ -163,7 +236,7 @@ impl<'a> ExternalDispatcher<'a> {
                     database: schema.to_string(), // A mongodb database is pretty much a schema.
                     collection: name.to_string(),
                 };
-                let accessor = MongoDbAccessor::connect(connection_string).await?;
+                let accessor = MongoDbAccessor::connect(connection_string, tunnel).await?;
                 let table_accessor = accessor.into_table_accessor(table_info);
                 let provider = table_accessor.into_table_provider().await?;
                 Ok(Arc::new(provider))
@@ -196,7 +269,7 @@ impl<'a> ExternalDispatcher<'a> {
                     schema_name,
                     table_name,
                 };
-                let accessor = SnowflakeAccessor::connect(conn_params).await?;
+                let accessor = SnowflakeAccessor::connect(conn_params, tunnel).await?;
                 let provider = accessor
                     .into_table_provider(access_info, /* predicate_pushdown = */ true)
                     .await?;
@@ -211,7 +284,8 @@ impl<'a> ExternalDispatcher<'a> {
                 Ok(Arc::new(table))
             }
             DatabaseOptions::SqlServer(DatabaseOptionsSqlServer { connection_string }) => {
-                let access = SqlServerAccess::try_new_from_ado_string(connection_string)?;
+                let access = SqlServerAccess::try_new_from_ado_string(connection_string)?
+                    .with_tunnel(tunnel);
                 let table = SqlServerTableProvider::try_new(SqlServerTableProviderConfig {
                     access,
                     schema: schema.to_string(),
@@ -233,9 +307,38 @@ impl<'a> ExternalDispatcher<'a> {
         &self,
         table: &TableEntry,
     ) -> Result<Arc<dyn TableProvider>> {
-        let tunnel = self.get_tunnel_opts(table.tunnel_id)?;
+        let schema = self.cached_schema(table);
+        self.dispatch_table_options(&table.options, table.tunnel_id, schema)
+            .await
+    }
+
+    /// Dispatch to a table using its options, which don't need to belong to
+    /// a table in the catalog yet.
+    ///
+    /// Object store tables use `schema` instead of inferring one if provided.
+    ///
+    /// Retries if accessing the source fails with a transient error.
+    pub async fn dispatch_table_options(
+        &self,
+        options: &TableOptions,
+        tunnel_id: Option<u32>,
+        schema: Option<SchemaRef>,
+    ) -> Result<Arc<dyn TableProvider>> {
+        self.retry_transient(options.as_str(), || {
+            self.dispatch_table_options_once(options, tunnel_id, schema.clone())
+        })
+        .await
+    }
+
+    async fn dispatch_table_options_once(
+        &self,
+        options: &TableOptions,
+        tunnel_id: Option<u32>,
+        schema: Option<SchemaRef>,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let tunnel = self.get_tunnel_opts(tunnel_id)?;
 
-        match &table.options {
+        match options {
             TableOptions::Internal(TableOptionsInternal { .. }) => unimplemented!(), // Purposely unimplemented.
             TableOptions::Debug(TableOptionsDebug { table_type }) => {
                 let provider = DebugTableType::from_str(table_type)?;
@@ -246,7 +349,8 @@ impl<'a> ExternalDispatcher<'a> {
                 schema,
                 table,
             }) => {
-                let access = PostgresAccess::new_from_conn_str(connection_string, tunnel);
+                let access = PostgresAccess::new_from_conn_str(connection_string, tunnel)
+                    .with_numeric_fallback(self.numeric_fallback()?);
                 let prov_conf = PostgresTableProviderConfig {
                     access,
                     schema: schema.to_owned(),
@@ -295,7 +399,7 @@ impl<'a> ExternalDispatcher<'a> {
                     database: database.to_string(),
                     collection: collection.to_string(),
                 };
-                let accessor = MongoDbAccessor::connect(connection_string).await?;
+                let accessor = MongoDbAccessor::connect(connection_string, tunnel).await?;
                 let table_accessor = accessor.into_table_accessor(table_info);
                 let provider = table_accessor.into_table_provider().await?;
                 Ok(Arc::new(provider))
@@ -328,7 +432,7 @@ impl<'a> ExternalDispatcher<'a> {
                     schema_name: schema_name.clone(),
                     table_name: table_name.clone(),
                 };
-                let accessor = SnowflakeAccessor::connect(conn_params).await?;
+                let accessor = SnowflakeAccessor::connect(conn_params, tunnel).await?;
                 let provider = accessor
                     .into_table_provider(access_info, /* predicate_pushdown = */ true)
                     .await?;
@@ -338,6 +442,7 @@ impl<'a> ExternalDispatcher<'a> {
                 location,
                 file_type,
                 compression,
+                inference,
             }) => {
                 if self.disable_local_fs_access {
                     return Err(DispatchError::InvalidDispatch(
@@ -350,6 +455,9 @@ impl<'a> ExternalDispatcher<'a> {
                     location,
                     file_type,
                     compression.as_ref(),
+                    inference,
+                    false,
+                    schema.clone(),
                 )
                 .await
             }
@@ -359,6 +467,8 @@ impl<'a> ExternalDispatcher<'a> {
                 location,
                 file_type,
                 compression,
+                cache,
+                inference,
             }) => {
                 let access = Arc::new(GcsStoreAccess {
                     service_account_key: service_account_key.clone(),
@@ -369,6 +479,9 @@ impl<'a> ExternalDispatcher<'a> {
                     location,
                     file_type,
                     compression.as_ref(),
+                    inference,
+                    *cache,
+                    schema.clone(),
                 )
                 .await
             }
@@ -380,6 +493,8 @@ impl<'a> ExternalDispatcher<'a> {
                 location,
                 file_type,
                 compression,
+                cache,
+                inference,
             }) => {
                 let access = Arc::new(S3StoreAccess {
                     region: region.clone(),
@@ -392,6 +507,9 @@ impl<'a> ExternalDispatcher<'a> {
                     location,
                     file_type,
                     compression.as_ref(),
+                    inference,
+                    *cache,
+                    schema.clone(),
                 )
                 .await
             }
@@ -400,6 +518,7 @@ impl<'a> ExternalDispatcher<'a> {
                 storage_options,
                 file_type,
                 compression,
+                inference,
                 ..
             }) => {
                 // File type should be known at this point since creating the
@@ -423,6 +542,9 @@ impl<'a> ExternalDispatcher<'a> {
                     DatasourceUrl::try_new(location)?.path(), // TODO: Workaround again
                     file_type,
                     compression.as_ref(),
+                    inference,
+                    true,
+                    schema.clone(),
                 )
                 .await
             }
@@ -455,7 +577,8 @@ impl<'a> ExternalDispatcher<'a> {
                 schema,
                 table,
             }) => {
-                let access = SqlServerAccess::try_new_from_ado_string(connection_string)?;
+                let access = SqlServerAccess::try_new_from_ado_string(connection_string)?
+                    .with_tunnel(tunnel);
                 let table = SqlServerTableProvider::try_new(SqlServerTableProviderConfig {
                     access,
                     schema: schema.to_string(),
@@ -506,6 +629,9 @@ impl<'a> ExternalDispatcher<'a> {
         path: impl AsRef<str>,
         file_type: &str,
         compression: Option<&String>,
+        inference: &SchemaInferenceOptions,
+        cache: bool,
+        schema: Option<SchemaRef>,
     ) -> Result<Arc<dyn TableProvider>> {
         let path = path.as_ref();
         let compression = compression
@@ -514,28 +640,93 @@ impl<'a> ExternalDispatcher<'a> {
             .unwrap_or(FileCompressionType::UNCOMPRESSED);
 
         let ft: FileType = file_type.parse()?;
+        let inference = match ft {
+            FileType::CSV | FileType::JSON => self.schema_inference(inference)?,
+            _ => SchemaInference::default(),
+        };
+        // Other formats are read as inferred and cast to the cached schema,
+        // which needs the inferred schema.
+        let schema =
+            schema.filter(|_| ft == FileType::CSV || inference == SchemaInference::default());
+
         let ft: Arc<dyn FileFormat> = match ft {
             FileType::CSV => Arc::new(
                 CsvFormat::default()
                     .with_file_compression_type(compression)
-                    .with_schema_infer_max_rec(Some(20480)),
+                    .with_schema_infer_max_rec(Some(inference.csv_infer_rows())),
             ),
             FileType::PARQUET => Arc::new(ParquetFormat::default()),
-            FileType::JSON => {
-                Arc::new(JsonFormat::default().with_file_compression_type(compression))
-            }
+            FileType::JSON => Arc::new(
+                JsonFormat::default()
+                    .with_file_compression_type(compression)
+                    .with_schema_infer_max_rec(inference.infer_rows),
+            ),
             _ => return Err(DispatchError::InvalidDispatch("Unsupported file type")),
         };
 
-        let accessor = ObjStoreAccessor::new(access)?;
+        let mut accessor = ObjStoreAccessor::new(access)?;
+        if cache {
+            accessor = accessor.with_cache()?;
+        }
         let objects = accessor.list_globbed(path).await?;
 
-        let state = self.df_ctx.state();
-        let provider = accessor.into_table_provider(&state, ft, objects).await?;
+        let provider = match schema {
+            Some(schema) => accessor.into_table_provider_with_schema(ft, objects, schema)?,
+            None => {
+                let state = self.df_ctx.state();
+                accessor
+                    .into_table_provider(&state, ft, objects, &inference)
+                    .await?
+            }
+        };
 
         Ok(provider)
     }
 
+    /// Resolve the schema inference options of a table, parsing the column
+    /// types with the session time zone.
+    fn schema_inference(&self, opts: &SchemaInferenceOptions) -> Result<SchemaInference> {
+        let column_types = match &opts.columns {
+            Some(columns) => {
+                let cfg = self.df_ctx.copied_config();
+                let vars = cfg.options().extensions.get::<SessionVars>();
+                parse_column_types(columns, || vars.map(|vars| vars.timezone()))?
+            }
+            None => Vec::new(),
+        };
+        Ok(SchemaInference {
+            infer_rows: opts.infer_rows.map(|rows| rows as usize),
+            column_types,
+            all_varchar: opts.all_varchar,
+        })
+    }
+
+    /// Run `dispatch` using the retry policy of the session, retrying errors
+    /// that look transient (timeouts, unavailable services, etc).
+    ///
+    /// Retries are counted against `source`.
+    async fn retry_transient<F, Fut>(
+        &self,
+        source: &str,
+        dispatch: F,
+    ) -> Result<Arc<dyn TableProvider>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<Arc<dyn TableProvider>>>,
+    {
+        let policy = datasource_retry_policy(&self.df_ctx.state());
+        policy
+            .retry(
+                dispatch,
+                |e: &DispatchError| is_transient_message(&e.to_string()),
+                |e| {
+                    debug!(%e, %source, "retrying dispatch to datasource");
+                    METRICS.datasource_retries.with_label(source).inc();
+                },
+            )
+            .await
+    }
+
     pub async fn dispatch_function(
         &self,
         func: &FunctionEntry,
@@ -11,17 +11,26 @@ use datafusion::logical_expr::{LogicalPlan, LogicalPlanBuilder};
 use datafusion::prelude::SessionContext as DfSessionContext;
 use datafusion::prelude::{Column, Expr};
 use datafusion_ext::functions::{DefaultTableContextProvider, FuncParamValue};
+use datafusion_ext::vars::SessionVars;
+use datafusion_ext::warnings::SessionWarnings;
 use datasources::native::access::NativeTableStorage;
 use protogen::metastore::types::catalog::{
     CatalogEntry, DatabaseEntry, EntryMeta, EntryType, FunctionEntry, ViewEntry,
 };
 use sqlbuiltins::functions::FUNCTION_REGISTRY;
+use tracing::{info_span, Instrument};
 
 use crate::context::local::LocalSessionContext;
 use crate::dispatch::system::SystemTableDispatcher;
+use crate::errors::{
+    datafusion_error_class, extension_error_class, external_error_class, ErrorClass,
+};
+use crate::memory::MemoryTracker;
 use crate::parser::CustomParser;
+use crate::plan_cache::SessionPlanCache;
 use crate::planner::errors::PlanError;
 use crate::planner::session_planner::SessionPlanner;
+use crate::remote::distribute::Workers;
 use catalog::session_catalog::SessionCatalog;
 
 use self::external::ExternalDispatcher;
@@ -100,6 +109,27 @@ pub enum DispatchError {
 }
 
 impl DispatchError {
+    /// Get the class of this error.
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            DispatchError::MissingDatabase { .. } => ErrorClass::UndefinedDatabase,
+            DispatchError::MissingEntry { .. }
+            | DispatchError::MissingBuiltinTable { .. }
+            | DispatchError::MissingTempTable { .. } => ErrorClass::UndefinedTable,
+            DispatchError::MissingObjectWithOid(_) | DispatchError::MissingTunnel(_) => {
+                ErrorClass::UndefinedObject
+            }
+            DispatchError::ViewPlanning(e) => e.class(),
+            DispatchError::Datafusion(e) => datafusion_error_class(e),
+            DispatchError::ExtensionError(e) => extension_error_class(e),
+            DispatchError::InvalidEntryTypeForDispatch(_)
+            | DispatchError::UnhandledEntry(_)
+            | DispatchError::InvalidDispatch(_) => ErrorClass::Internal,
+            // Errors from external sources.
+            other => external_error_class(&other.to_string()),
+        }
+    }
+
     /// Whether or not this error should indicate to the planner to try looking
     /// in a different schema for the requested object.
     ///
@@ -170,6 +200,8 @@ pub struct Dispatcher<'a> {
     df_ctx: &'a DfSessionContext,
     /// Whether or not local file system access should be disabled.
     disable_local_fs_access: bool,
+    /// Memory usage for all sessions, used for the memory usage system table.
+    memory_tracker: &'a MemoryTracker,
 }
 
 impl<'a> Dispatcher<'a> {
@@ -179,6 +211,7 @@ impl<'a> Dispatcher<'a> {
         view_planner: &'a dyn ViewPlanner,
         df_ctx: &'a DfSessionContext,
         disable_local_fs_access: bool,
+        memory_tracker: &'a MemoryTracker,
     ) -> Self {
         Dispatcher {
             catalog,
@@ -186,6 +219,7 @@ impl<'a> Dispatcher<'a> {
             view_planner,
             df_ctx,
             disable_local_fs_access,
+            memory_tracker,
         }
     }
 
@@ -212,14 +246,28 @@ impl<'a> Dispatcher<'a> {
             }
             // Dispatch to builtin tables.
             CatalogEntry::Table(tbl) if tbl.meta.builtin => {
-                SystemTableDispatcher::new(self.catalog, self.tables)
-                    .dispatch(&tbl)
-                    .await
+                let state = self.df_ctx.state();
+                let workers = state.config().get_extension::<Workers>();
+                let plan_cache = state.config().get_extension::<SessionPlanCache>();
+                let warnings = state.config().get_extension::<SessionWarnings>();
+                let vars = state.config().options().extensions.get::<SessionVars>();
+                SystemTableDispatcher::new(
+                    self.catalog,
+                    self.tables,
+                    self.memory_tracker,
+                    workers.as_deref(),
+                    plan_cache.as_deref(),
+                    warnings.as_deref(),
+                    vars,
+                )
+                .dispatch(&tbl)
+                .await
             }
             // Dispatch to external tables.
             CatalogEntry::Table(tbl) if tbl.meta.external => {
                 ExternalDispatcher::new(self.catalog, self.df_ctx, self.disable_local_fs_access)
                     .dispatch_external_table(&tbl)
+                    .instrument(info_span!("dispatch_external_table", table = %tbl.meta.name))
                     .await
             }
             // Dispatch to native tables.
@@ -240,6 +288,12 @@ impl<'a> Dispatcher<'a> {
     ) -> Result<Arc<dyn TableProvider>> {
         ExternalDispatcher::new(self.catalog, self.df_ctx, self.disable_local_fs_access)
             .dispatch_external(&db_ent.meta.name, schema, name)
+            .instrument(info_span!(
+                "dispatch_external",
+                database = %db_ent.meta.name,
+                %schema,
+                %name
+            ))
             .await
     }
 
@@ -271,6 +325,7 @@ impl<'a> Dispatcher<'a> {
                 args,
                 opts,
             )
+            .instrument(info_span!("dispatch_function", function = %func.meta.name))
             .await?;
         Ok(prov)
     }
@@ -1,32 +1,70 @@
 use std::sync::Arc;
+use std::time::UNIX_EPOCH;
 
 use catalog::session_catalog::SessionCatalog;
-use datafusion::arrow::array::{BooleanBuilder, ListBuilder, StringBuilder, UInt32Builder};
+use datafusion::arrow::array::{
+    BooleanBuilder, ListBuilder, StringBuilder, TimestampMicrosecondBuilder, UInt32Builder,
+    UInt64Array, UInt64Builder,
+};
 use datafusion::arrow::record_batch::RecordBatch;
-use datafusion::datasource::{MemTable, TableProvider};
-use datafusion::logical_expr::TypeSignature;
+use datafusion::datasource::{provider_as_source, MemTable, TableProvider, ViewTable};
+use datafusion::logical_expr::{col, lit, LogicalPlanBuilder, TypeSignature};
+use datafusion_ext::vars::SessionVars;
+use datafusion_ext::warnings::SessionWarnings;
 use datasources::common::ssh::key::SshKey;
 use datasources::common::ssh::SshConnectionParameters;
 use datasources::native::access::NativeTableStorage;
 use protogen::metastore::types::catalog::{CatalogEntry, EntryType, SourceAccessMode, TableEntry};
 use protogen::metastore::types::options::TunnelOptions;
 use sqlbuiltins::builtins::{
-    BuiltinTable, DATABASE_DEFAULT, GLARE_CACHED_EXTERNAL_DATABASE_TABLES, GLARE_COLUMNS,
-    GLARE_CREDENTIALS, GLARE_DATABASES, GLARE_DEPLOYMENT_METADATA, GLARE_FUNCTIONS, GLARE_SCHEMAS,
-    GLARE_SSH_KEYS, GLARE_TABLES, GLARE_TUNNELS, GLARE_VIEWS, SCHEMA_CURRENT_SESSION,
+    BuiltinTable, DATABASE_DEFAULT, GLARE_AUDIT_LOG, GLARE_CACHED_EXTERNAL_DATABASE_TABLES,
+    GLARE_CATALOG_EVENTS, GLARE_COLUMNS, GLARE_CREDENTIALS, GLARE_DATABASES,
+    GLARE_DEPLOYMENT_METADATA, GLARE_FUNCTIONS, GLARE_MEMORY_USAGE, GLARE_OBJECT_TAGS,
+    GLARE_RPC_WORKERS, GLARE_SCHEMAS, GLARE_SESSION_STATS, GLARE_SSH_KEYS, GLARE_TABLES,
+    GLARE_TUNNELS, GLARE_VIEWS, GLARE_WARNINGS, SCHEMA_CURRENT_SESSION, SYSTEM_QUERY_HISTORY,
 };
 
 use super::{DispatchError, Result};
+use crate::memory::MemoryTracker;
+use crate::plan_cache::SessionPlanCache;
+use crate::remote::distribute::Workers;
 
 /// Dispatch to builtin system tables.
 pub struct SystemTableDispatcher<'a> {
     catalog: &'a SessionCatalog,
     tables: &'a NativeTableStorage,
+    memory_tracker: &'a MemoryTracker,
+    /// Workers remote execution on this node is distributed across, if this
+    /// node serves remote execution.
+    workers: Option<&'a Workers>,
+    /// Plan cache for the current session.
+    plan_cache: Option<&'a SessionPlanCache>,
+    /// Warnings raised by queries in the current session.
+    warnings: Option<&'a SessionWarnings>,
+    /// Variables of the current session, used to restrict what non-admin
+    /// users can see.
+    vars: Option<&'a SessionVars>,
 }
 
 impl<'a> SystemTableDispatcher<'a> {
-    pub fn new(catalog: &'a SessionCatalog, tables: &'a NativeTableStorage) -> Self {
-        SystemTableDispatcher { catalog, tables }
+    pub fn new(
+        catalog: &'a SessionCatalog,
+        tables: &'a NativeTableStorage,
+        memory_tracker: &'a MemoryTracker,
+        workers: Option<&'a Workers>,
+        plan_cache: Option<&'a SessionPlanCache>,
+        warnings: Option<&'a SessionWarnings>,
+        vars: Option<&'a SessionVars>,
+    ) -> Self {
+        SystemTableDispatcher {
+            catalog,
+            tables,
+            memory_tracker,
+            workers,
+            plan_cache,
+            warnings,
+            vars,
+        }
     }
 
     pub async fn dispatch(&self, ent: &TableEntry) -> Result<Arc<dyn TableProvider>> {
@@ -50,15 +88,31 @@ impl<'a> SystemTableDispatcher<'a> {
             Arc::new(self.build_glare_views())
         } else if GLARE_SCHEMAS.matches(schema, name) {
             Arc::new(self.build_glare_schemas())
+        } else if GLARE_OBJECT_TAGS.matches(schema, name) {
+            Arc::new(self.build_glare_object_tags())
         } else if GLARE_FUNCTIONS.matches(schema, name) {
             Arc::new(self.build_glare_functions())
         } else if GLARE_SSH_KEYS.matches(schema, name) {
             Arc::new(self.build_ssh_keys()?)
         } else if GLARE_DEPLOYMENT_METADATA.matches(schema, name) {
             Arc::new(self.build_glare_deployment_metadata()?)
+        } else if GLARE_MEMORY_USAGE.matches(schema, name) {
+            Arc::new(self.build_glare_memory_usage())
+        } else if GLARE_RPC_WORKERS.matches(schema, name) {
+            Arc::new(self.build_glare_rpc_workers())
+        } else if GLARE_SESSION_STATS.matches(schema, name) {
+            Arc::new(self.build_glare_session_stats())
+        } else if GLARE_WARNINGS.matches(schema, name) {
+            Arc::new(self.build_glare_warnings())
         } else if GLARE_CACHED_EXTERNAL_DATABASE_TABLES.matches(schema, name) {
             self.load_persisted_table(&GLARE_CACHED_EXTERNAL_DATABASE_TABLES)
                 .await?
+        } else if GLARE_AUDIT_LOG.matches(schema, name) {
+            self.load_persisted_table(&GLARE_AUDIT_LOG).await?
+        } else if SYSTEM_QUERY_HISTORY.matches(schema, name) {
+            self.load_query_history().await?
+        } else if GLARE_CATALOG_EVENTS.matches(schema, name) {
+            self.load_persisted_table(&GLARE_CATALOG_EVENTS).await?
         } else {
             return Err(DispatchError::MissingBuiltinTable {
                 schema: schema.to_string(),
@@ -95,6 +149,22 @@ impl<'a> SystemTableDispatcher<'a> {
         }
     }
 
+    /// Load the query history, only keeping the queries of the current user
+    /// unless the session is an admin.
+    async fn load_query_history(&self) -> Result<Arc<dyn TableProvider>> {
+        let table = self.load_persisted_table(&SYSTEM_QUERY_HISTORY).await?;
+        let vars = self.vars.cloned().unwrap_or_default();
+        if vars.is_admin() {
+            return Ok(table);
+        }
+
+        let plan =
+            LogicalPlanBuilder::scan(SYSTEM_QUERY_HISTORY.name, provider_as_source(table), None)?
+                .filter(col("user_name").eq(lit(vars.user_name())))?
+                .build()?;
+        Ok(Arc::new(ViewTable::try_new(plan, None)?))
+    }
+
     fn build_glare_databases(&self) -> MemTable {
         let arrow_schema = Arc::new(GLARE_DATABASES.arrow_schema());
 
@@ -226,6 +296,7 @@ impl<'a> SystemTableDispatcher<'a> {
         let mut database_name = StringBuilder::new();
         let mut schema_name = StringBuilder::new();
         let mut builtin = BooleanBuilder::new();
+        let mut description = StringBuilder::new();
 
         for schema in self
             .catalog
@@ -242,6 +313,7 @@ impl<'a> SystemTableDispatcher<'a> {
             );
             schema_name.append_value(&schema.entry.get_meta().name);
             builtin.append_value(schema.builtin);
+            description.append_option(schema.entry.get_meta().description.as_ref());
         }
         let batch = RecordBatch::try_new(
             arrow_schema.clone(),
@@ -251,6 +323,7 @@ impl<'a> SystemTableDispatcher<'a> {
                 Arc::new(database_name.finish()),
                 Arc::new(schema_name.finish()),
                 Arc::new(builtin.finish()),
+                Arc::new(description.finish()),
             ],
         )
         .unwrap();
@@ -269,6 +342,7 @@ impl<'a> SystemTableDispatcher<'a> {
         let mut external = BooleanBuilder::new();
         let mut datasource = StringBuilder::new();
         let mut access_mode = StringBuilder::new();
+        let mut description = StringBuilder::new();
 
         for table in self
             .catalog
@@ -300,6 +374,7 @@ impl<'a> SystemTableDispatcher<'a> {
 
             datasource.append_value(table.options.as_str());
             access_mode.append_value(table.access_mode.as_str());
+            description.append_option(table.meta.description.as_ref());
         }
 
         // Append temporary tables.
@@ -314,6 +389,7 @@ impl<'a> SystemTableDispatcher<'a> {
             external.append_value(table.meta.external);
             datasource.append_value(table.options.as_str());
             access_mode.append_value(SourceAccessMode::ReadWrite.as_str());
+            description.append_null();
         }
 
         let batch = RecordBatch::try_new(
@@ -328,6 +404,7 @@ impl<'a> SystemTableDispatcher<'a> {
                 Arc::new(external.finish()),
                 Arc::new(datasource.finish()),
                 Arc::new(access_mode.finish()),
+                Arc::new(description.finish()),
             ],
         )
         .unwrap();
@@ -345,6 +422,7 @@ impl<'a> SystemTableDispatcher<'a> {
         let mut column_ordinal = UInt32Builder::new();
         let mut data_type = StringBuilder::new();
         let mut is_nullable = BooleanBuilder::new();
+        let mut description = StringBuilder::new();
 
         for table in self
             .catalog
@@ -374,6 +452,7 @@ impl<'a> SystemTableDispatcher<'a> {
                 column_ordinal.append_value(i as u32);
                 data_type.append_value(col.arrow_type.to_string());
                 is_nullable.append_value(col.nullable);
+                description.append_option(ent.column_comments.get(&col.name));
             }
         }
 
@@ -387,6 +466,7 @@ impl<'a> SystemTableDispatcher<'a> {
                 Arc::new(column_ordinal.finish()),
                 Arc::new(data_type.finish()),
                 Arc::new(is_nullable.finish()),
+                Arc::new(description.finish()),
             ],
         )
         .unwrap();
@@ -404,6 +484,7 @@ impl<'a> SystemTableDispatcher<'a> {
         let mut view_name = StringBuilder::new();
         let mut builtin = BooleanBuilder::new();
         let mut sql = StringBuilder::new();
+        let mut description = StringBuilder::new();
 
         for view in self
             .catalog
@@ -430,6 +511,7 @@ impl<'a> SystemTableDispatcher<'a> {
             view_name.append_value(&view.entry.get_meta().name);
             builtin.append_value(view.builtin);
             sql.append_value(&ent.sql);
+            description.append_option(ent.meta.description.as_ref());
         }
 
         let batch = RecordBatch::try_new(
@@ -442,6 +524,73 @@ impl<'a> SystemTableDispatcher<'a> {
                 Arc::new(view_name.finish()),
                 Arc::new(builtin.finish()),
                 Arc::new(sql.finish()),
+                Arc::new(description.finish()),
+            ],
+        )
+        .unwrap();
+
+        MemTable::try_new(arrow_schema, vec![vec![batch]]).unwrap()
+    }
+
+    fn build_glare_object_tags(&self) -> MemTable {
+        let arrow_schema = Arc::new(GLARE_OBJECT_TAGS.arrow_schema());
+
+        let mut oid = UInt32Builder::new();
+        let mut object_type = StringBuilder::new();
+        let mut database_name = StringBuilder::new();
+        let mut schema_name = StringBuilder::new();
+        let mut table_name = StringBuilder::new();
+        let mut key = StringBuilder::new();
+        let mut value = StringBuilder::new();
+
+        let parent_name = |ent: Option<&CatalogEntry>| {
+            ent.map(|ent| ent.get_meta().name.clone())
+                .unwrap_or_else(|| "<invalid>".to_string())
+        };
+
+        for ent in self.catalog.iter_entries() {
+            let (typ, db, schema, table, tags) = match ent.entry {
+                CatalogEntry::Schema(schema) => (
+                    "schema",
+                    parent_name(ent.parent_entry),
+                    schema.meta.name.clone(),
+                    None,
+                    &schema.tags,
+                ),
+                CatalogEntry::Table(table) => (
+                    "table",
+                    parent_name(
+                        ent.parent_entry
+                            .and_then(|schema| self.catalog.get_by_oid(schema.get_meta().parent)),
+                    ),
+                    parent_name(ent.parent_entry),
+                    Some(table.meta.name.as_str()),
+                    &table.tags,
+                ),
+                _ => continue,
+            };
+
+            for (k, v) in tags {
+                oid.append_value(ent.oid);
+                object_type.append_value(typ);
+                database_name.append_value(&db);
+                schema_name.append_value(&schema);
+                table_name.append_option(table);
+                key.append_value(k);
+                value.append_value(v);
+            }
+        }
+
+        let batch = RecordBatch::try_new(
+            arrow_schema.clone(),
+            vec![
+                Arc::new(oid.finish()),
+                Arc::new(object_type.finish()),
+                Arc::new(database_name.finish()),
+                Arc::new(schema_name.finish()),
+                Arc::new(table_name.finish()),
+                Arc::new(key.finish()),
+                Arc::new(value.finish()),
             ],
         )
         .unwrap();
@@ -574,6 +723,127 @@ impl<'a> SystemTableDispatcher<'a> {
 
         Ok(MemTable::try_new(arrow_schema, vec![vec![batch]]).unwrap())
     }
+
+    fn build_glare_memory_usage(&self) -> MemTable {
+        let arrow_schema = Arc::new(GLARE_MEMORY_USAGE.arrow_schema());
+
+        let mut connection_id = StringBuilder::new();
+        let mut user_name = StringBuilder::new();
+        let mut query_id = UInt64Builder::new();
+        let mut query_text = StringBuilder::new();
+        let mut current_bytes = UInt64Builder::new();
+        let mut peak_bytes = UInt64Builder::new();
+
+        for usage in self.memory_tracker.usage() {
+            connection_id.append_value(usage.connection_id.to_string());
+            user_name.append_value(usage.user_name);
+            query_id.append_option(usage.query_id);
+            query_text.append_option(usage.query_text);
+            current_bytes.append_value(usage.current_bytes as u64);
+            peak_bytes.append_value(usage.peak_bytes as u64);
+        }
+
+        let batch = RecordBatch::try_new(
+            arrow_schema.clone(),
+            vec![
+                Arc::new(connection_id.finish()),
+                Arc::new(user_name.finish()),
+                Arc::new(query_id.finish()),
+                Arc::new(query_text.finish()),
+                Arc::new(current_bytes.finish()),
+                Arc::new(peak_bytes.finish()),
+            ],
+        )
+        .unwrap();
+
+        MemTable::try_new(arrow_schema, vec![vec![batch]]).unwrap()
+    }
+
+    fn build_glare_rpc_workers(&self) -> MemTable {
+        let arrow_schema = Arc::new(GLARE_RPC_WORKERS.arrow_schema());
+
+        let mut address = StringBuilder::new();
+        let mut state = StringBuilder::new();
+        let mut running_partitions = UInt64Builder::new();
+        let mut completed_partitions = UInt64Builder::new();
+
+        for worker in self.workers.map(Workers::workers).unwrap_or_default() {
+            address.append_value(worker.address());
+            state.append_value(worker.state().as_str());
+            running_partitions.append_value(worker.running_partitions() as u64);
+            completed_partitions.append_value(worker.completed_partitions());
+        }
+
+        let batch = RecordBatch::try_new(
+            arrow_schema.clone(),
+            vec![
+                Arc::new(address.finish()),
+                Arc::new(state.finish()),
+                Arc::new(running_partitions.finish()),
+                Arc::new(completed_partitions.finish()),
+            ],
+        )
+        .unwrap();
+
+        MemTable::try_new(arrow_schema, vec![vec![batch]]).unwrap()
+    }
+
+    fn build_glare_session_stats(&self) -> MemTable {
+        let arrow_schema = Arc::new(GLARE_SESSION_STATS.arrow_schema());
+
+        let (hits, misses, entries) = match self.plan_cache {
+            Some(plan_cache) => (
+                plan_cache.hits(),
+                plan_cache.misses(),
+                plan_cache.cache().len() as u64,
+            ),
+            None => (0, 0, 0),
+        };
+
+        let batch = RecordBatch::try_new(
+            arrow_schema.clone(),
+            vec![
+                Arc::new(UInt64Array::from(vec![hits])),
+                Arc::new(UInt64Array::from(vec![misses])),
+                Arc::new(UInt64Array::from(vec![entries])),
+            ],
+        )
+        .unwrap();
+
+        MemTable::try_new(arrow_schema, vec![vec![batch]]).unwrap()
+    }
+
+    fn build_glare_warnings(&self) -> MemTable {
+        let arrow_schema = Arc::new(GLARE_WARNINGS.arrow_schema());
+
+        let warnings = self.warnings.map(|w| w.recent()).unwrap_or_default();
+        let mut time =
+            TimestampMicrosecondBuilder::with_capacity(warnings.len()).with_timezone("UTC");
+        let mut source = StringBuilder::new();
+        let mut message = StringBuilder::new();
+        for warning in warnings {
+            let micros = warning
+                .time
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_micros() as i64)
+                .unwrap_or_default();
+            time.append_value(micros);
+            source.append_value(warning.source);
+            message.append_value(warning.message);
+        }
+
+        let batch = RecordBatch::try_new(
+            arrow_schema.clone(),
+            vec![
+                Arc::new(time.finish()),
+                Arc::new(source.finish()),
+                Arc::new(message.finish()),
+            ],
+        )
+        .unwrap();
+
+        MemTable::try_new(arrow_schema, vec![vec![batch]]).unwrap()
+    }
 }
 fn sig_to_string_repr(sig: &TypeSignature) -> Vec<String> {
     match sig {
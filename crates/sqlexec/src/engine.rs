@@ -1,8 +1,18 @@
+use crate::admission::{QueryLimiter, QueryLimits};
+use crate::audit::AuditLog;
+use crate::change_feed::ChangeFeed;
+use crate::connections::ConnectionLimiter;
 use crate::context::remote::RemoteSessionContext;
 use crate::distexec::executor::TaskExecutor;
 use crate::distexec::scheduler::Scheduler;
 use crate::errors::{ExecError, Result};
+use crate::memory::MemoryTracker;
+use crate::plan_cache::{PlanCache, DEFAULT_PLAN_CACHE_CAPACITY};
+use crate::query_history::QueryHistory;
+use crate::remote::distribute::Workers;
+use crate::scram::ScramVerifier;
 use crate::session::Session;
+use crate::user_functions::{UserTableFunc, UserTableFuncs};
 use catalog::client::{MetastoreClientSupervisor, DEFAULT_METASTORE_CLIENT_CONFIG};
 use object_store::azure::AzureConfigKey;
 use sqlbuiltins::builtins::{SCHEMA_CURRENT_SESSION, SCHEMA_DEFAULT};
@@ -20,6 +30,8 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use catalog::session_catalog::{ResolveConfig, SessionCatalog};
+use datafusion::variable::VarType;
+use datafusion_ext::reload::ConfigReloader;
 use datafusion_ext::vars::SessionVars;
 use datasources::common::errors::DatasourceCommonError;
 use datasources::common::url::{DatasourceUrl, DatasourceUrlType};
@@ -30,9 +42,10 @@ use object_store_util::conf::StorageConfig;
 use object_store_util::shared::SharedObjectStore;
 use protogen::gen::metastore::service::metastore_service_client::MetastoreServiceClient;
 use protogen::rpcsrv::types::common;
+use telemetry::metrics::METRICS;
 use telemetry::Tracker;
 use tonic::transport::Channel;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 use url::Url;
 use uuid::Uuid;
 
@@ -321,6 +334,29 @@ pub struct Engine {
     task_scheduler: Scheduler,
     /// Task executors.
     _task_executors: Vec<TaskExecutor>,
+    /// Limiter for queries executing across all sessions.
+    query_limiter: QueryLimiter,
+    /// Memory usage for all sessions.
+    memory_tracker: MemoryTracker,
+    /// Audit log for statements executed by all sessions.
+    audit_log: AuditLog,
+    /// History of queries executed by all sessions.
+    query_history: QueryHistory,
+    /// Feed of catalog changes made by all sessions.
+    change_feed: ChangeFeed,
+    /// Reloads the configuration of the server the engine is running in.
+    config_reloader: Option<ConfigReloader>,
+    /// Workers remote execution on this node is distributed across.
+    workers: Option<Arc<Workers>>,
+    /// Table functions registered by the embedding application.
+    table_funcs: UserTableFuncs,
+    /// If all sessions should be read-only.
+    read_only: bool,
+    /// Limits for connections opened by frontends.
+    connection_limiter: ConnectionLimiter,
+    /// Plan cache shared by all sessions. Each session gets its own cache if
+    /// unset.
+    shared_plan_cache: Option<PlanCache>,
 }
 
 impl Engine {
@@ -351,6 +387,17 @@ impl Engine {
             session_counter: Arc::new(AtomicU64::new(0)),
             task_scheduler,
             _task_executors: task_executors,
+            query_limiter: QueryLimiter::unlimited(),
+            memory_tracker: MemoryTracker::default(),
+            audit_log: AuditLog::disabled(),
+            query_history: QueryHistory::disabled(),
+            change_feed: ChangeFeed::disabled(),
+            config_reloader: None,
+            workers: None,
+            table_funcs: UserTableFuncs::default(),
+            read_only: false,
+            connection_limiter: ConnectionLimiter::unlimited(),
+            shared_plan_cache: None,
         })
     }
 
@@ -405,11 +452,131 @@ impl Engine {
         self
     }
 
+    /// Limit the number of queries executing at once across all sessions.
+    ///
+    /// Only applies to sessions created after this is set.
+    pub fn with_query_limits(mut self, limits: QueryLimits) -> Engine {
+        self.query_limiter = QueryLimiter::new(limits);
+        self
+    }
+
+    /// Use an existing limiter for queries executing across all sessions.
+    ///
+    /// Allows the limits to be changed while the engine is running. Only
+    /// applies to sessions created after this is set.
+    pub fn with_query_limiter(mut self, query_limiter: QueryLimiter) -> Engine {
+        self.query_limiter = query_limiter;
+        self
+    }
+
+    /// Get the limiter for queries executing across all sessions.
+    pub fn query_limiter(&self) -> &QueryLimiter {
+        &self.query_limiter
+    }
+
+    /// Record statements executed by sessions to an audit log.
+    ///
+    /// Only applies to sessions created after this is set.
+    pub fn with_audit_log(mut self, audit_log: AuditLog) -> Engine {
+        self.audit_log = audit_log;
+        self
+    }
+
+    /// Record queries executed by sessions to the query history table.
+    ///
+    /// Only applies to sessions created after this is set.
+    pub fn with_query_history(mut self, query_history: QueryHistory) -> Engine {
+        self.query_history = query_history;
+        self
+    }
+
+    /// Record catalog changes made by sessions to the change feed.
+    ///
+    /// Only applies to sessions created after this is set.
+    pub fn with_change_feed(mut self, change_feed: ChangeFeed) -> Engine {
+        self.change_feed = change_feed;
+        self
+    }
+
+    /// Allow sessions to reload the server configuration with
+    /// `reload_config()`.
+    ///
+    /// Only applies to sessions created after this is set.
+    pub fn with_config_reloader(mut self, config_reloader: ConfigReloader) -> Engine {
+        self.config_reloader = Some(config_reloader);
+        self
+    }
+
+    /// Expose the pool of workers remote execution is distributed across to
+    /// sessions, for the `rpc_workers` system table.
+    ///
+    /// Only applies to sessions created after this is set.
+    pub fn with_workers(mut self, workers: Arc<Workers>) -> Engine {
+        self.workers = Some(workers);
+        self
+    }
+
+    /// Limit the number of connections frontends may open, and close idle
+    /// connections.
+    pub fn with_connection_limiter(mut self, connection_limiter: ConnectionLimiter) -> Engine {
+        self.connection_limiter = connection_limiter;
+        self
+    }
+
+    /// Get the limiter frontends should acquire a permit from for every
+    /// connection.
+    pub fn connection_limiter(&self) -> &ConnectionLimiter {
+        &self.connection_limiter
+    }
+
+    /// Share a single plan cache across all sessions, instead of each session
+    /// caching plans on its own.
+    ///
+    /// Only applies to sessions created after this is set.
+    pub fn with_shared_plan_cache(mut self, plan_cache: PlanCache) -> Engine {
+        self.shared_plan_cache = Some(plan_cache);
+        self
+    }
+
+    /// Reject statements that modify the catalog or tables in all sessions.
+    ///
+    /// Only applies to sessions created after this is set.
+    pub fn with_read_only(mut self, read_only: bool) -> Engine {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Register a table function that can be called from any session,
+    /// including sessions that already exist.
+    ///
+    /// See [`UserTableFunc`].
+    pub fn register_table_function(&self, func: UserTableFunc) -> Result<()> {
+        self.table_funcs.register(func)
+    }
+
+    /// Remove a table function registered with `register_table_function`.
+    pub fn deregister_table_function(&self, name: &str) -> bool {
+        self.table_funcs.deregister(name)
+    }
+
     /// Get the current number of sessions.
     pub fn session_count(&self) -> u64 {
         self.session_counter.load(Ordering::Relaxed)
     }
 
+    /// Flush everything recorded by sessions and stop background workers.
+    ///
+    /// Should only be called once all sessions have ended. Telemetry events,
+    /// audit records, query history and catalog events are written out before
+    /// the metastore workers are stopped.
+    pub async fn shutdown(&self) {
+        self.tracker.flush().await;
+        self.audit_log.flush().await;
+        self.query_history.flush().await;
+        self.change_feed.flush().await;
+        self.supervisor.shutdown().await;
+    }
+
     /// Create a new local session, initializing it with the provided session
     /// variables.
     // TODO: This is _very_ easy to mess up with the vars since we implement
@@ -423,6 +590,7 @@ impl Engine {
         let session = self.new_untracked_session(vars, storage).await?;
 
         let prev = self.session_counter.fetch_add(1, Ordering::Relaxed);
+        METRICS.active_sessions.inc();
         debug!(session_count = prev + 1, "new session opened");
 
         Ok(TrackedSession {
@@ -431,6 +599,25 @@ impl Engine {
         })
     }
 
+    /// Get the SCRAM-SHA-256 verifier for the password of a role, if the role
+    /// has a password.
+    ///
+    /// Used for authenticating connections before a session is created.
+    pub async fn get_role_scram_verifier(
+        &self,
+        database_id: Uuid,
+        role: &str,
+    ) -> Result<Option<ScramVerifier>> {
+        let metastore = self.supervisor.init_client(database_id).await?;
+        metastore
+            .get_role_passwords()
+            .await?
+            .iter()
+            .find(|password| password.role == role)
+            .map(|password| password.scram_verifier.parse())
+            .transpose()
+    }
+
     /// Create a new untracked session.
     ///
     /// This does not increment the session counter.
@@ -454,6 +641,26 @@ impl Engine {
             },
         );
 
+        // Apply defaults set with `ALTER DATABASE ... SET` and `ALTER ROLE ...
+        // SET`. Values provided by the client take precedence, and more
+        // specific defaults take precedence over less specific ones.
+        for default in catalog.session_var_defaults(&vars.database(), &vars.user_name()) {
+            if let Err(e) = vars.set_default(&default.name, &default.value) {
+                warn!(%e, name = %default.name, "failed to apply session variable default");
+            }
+        }
+
+        let vars = if self.read_only || catalog.is_read_only_role(&vars.user_name()) {
+            vars.with_transaction_read_only(true, VarType::System)
+        } else {
+            vars
+        };
+
+        let plan_cache = self
+            .shared_plan_cache
+            .clone()
+            .unwrap_or_else(|| PlanCache::new(DEFAULT_PLAN_CACHE_CAPACITY));
+
         Session::new(
             vars,
             catalog,
@@ -462,6 +669,15 @@ impl Engine {
             self.tracker.clone(),
             self.spill_path.clone(),
             self.task_scheduler.clone(),
+            self.query_limiter.clone(),
+            self.memory_tracker.clone(),
+            self.audit_log.clone(),
+            self.query_history.clone(),
+            self.change_feed.clone(),
+            self.config_reloader.clone(),
+            self.workers.clone(),
+            self.table_funcs.clone(),
+            plan_cache,
         )
     }
 
@@ -473,6 +689,7 @@ impl Engine {
         &self,
         database_id: Uuid,
         storage: SessionStorageConfig,
+        vars: SessionVars,
     ) -> Result<RemoteSessionContext> {
         let metastore = self.supervisor.init_client(database_id).await?;
         let native = self
@@ -488,8 +705,15 @@ impl Engine {
             },
         );
 
-        let context =
-            RemoteSessionContext::new(catalog, metastore.into(), native, self.spill_path.clone())?;
+        let read_only = self.read_only || catalog.is_read_only_role(&vars.user_name());
+        let context = RemoteSessionContext::new(
+            vars,
+            catalog,
+            metastore.into(),
+            native,
+            self.spill_path.clone(),
+        )?
+        .with_read_only(read_only);
 
         Ok(context)
     }
@@ -522,6 +746,7 @@ impl DerefMut for TrackedSession {
 impl Drop for TrackedSession {
     fn drop(&mut self) {
         let prev = self.session_counter.fetch_sub(1, Ordering::Relaxed);
+        METRICS.active_sessions.dec();
         debug!(session_counter = prev - 1, "session closed");
     }
 }
@@ -543,10 +768,341 @@ pub fn ensure_spill_path<P: AsRef<Path>>(path: Option<P>) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use crate::engine::{EngineStorageConfig, SessionStorageConfig};
-    use crate::errors::Result;
+    use crate::context::remote::RemoteSessionContext;
+    use crate::engine::{Engine, EngineStorageConfig, SessionStorageConfig, TrackedSession};
+    use crate::errors::{ExecError, Result};
+    use crate::planner::errors::PlanError;
+    use crate::planner::logical_plan::OwnedFullSchemaReference;
+    use crate::planner::physical_plan::alter_database::AlterDatabaseExec;
+    use crate::planner::physical_plan::create_schema::CreateSchemaExec;
+    use crate::planner::physical_plan::import_catalog::ImportCatalogExec;
+    use crate::planner::physical_plan::set_var::SetVarExec;
+    use crate::planner::physical_plan::use_database::UseDatabaseExec;
+    use crate::session::ExecutionResult;
+    use crate::OperationInfo;
+    use datafusion::arrow::datatypes::Schema;
+    use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+    use datafusion::physical_plan::empty::EmptyExec;
+    use datafusion::physical_plan::ExecutionPlan;
+    use datafusion::variable::VarType;
+    use datafusion_ext::reload::{ConfigReloader, ReloadConfig};
+    use datafusion_ext::vars::SessionVars;
+    use futures::TryStreamExt;
     use object_store_util::conf::StorageConfig;
+    use protogen::metastore::types::service::AlterDatabaseOperation;
     use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    async fn execute(sess: &mut TrackedSession, sql: &str) -> Result<()> {
+        let plan = sess.create_logical_plan(sql).await?;
+        let op = OperationInfo::new().with_query_text(sql.to_string());
+        match sess.execute_logical_plan(plan, &op).await? {
+            (_, ExecutionResult::Query { stream }) => {
+                stream.try_collect::<Vec<_>>().await?;
+                Ok(())
+            }
+            (_, ExecutionResult::Error(e)) => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn assert_read_only_err(result: Result<()>) {
+        match result {
+            Err(ExecError::PlanError(PlanError::ReadOnlySession)) => (),
+            other => panic!("expected read-only error, got: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn read_only_engine_rejects_writes() {
+        let engine = Engine::from_data_dir(None)
+            .await
+            .unwrap()
+            .with_read_only(true);
+        let mut sess = engine
+            .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+            .await
+            .unwrap();
+
+        execute(&mut sess, "SELECT 1").await.unwrap();
+        for sql in [
+            "CREATE TABLE t (a INT)",
+            "CREATE SCHEMA s",
+            "INSERT INTO t VALUES (1)",
+            "DROP TABLE t",
+            "EXPLAIN ANALYZE INSERT INTO t VALUES (1)",
+        ] {
+            assert_read_only_err(execute(&mut sess, sql).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn read_only_role_rejects_writes() {
+        let engine = Engine::from_data_dir(None).await.unwrap();
+        let mut admin = engine
+            .new_local_session_context(
+                SessionVars::default().with_is_admin(true, VarType::System),
+                SessionStorageConfig::default(),
+            )
+            .await
+            .unwrap();
+        execute(&mut admin, "CREATE TABLE t (a INT)").await.unwrap();
+        execute(&mut admin, "ALTER ROLE sam READ ONLY")
+            .await
+            .unwrap();
+
+        let mut sess = engine
+            .new_local_session_context(
+                SessionVars::default().with_user_name("sam", VarType::System),
+                SessionStorageConfig::default(),
+            )
+            .await
+            .unwrap();
+        execute(&mut sess, "SELECT * FROM t").await.unwrap();
+        for sql in [
+            "CREATE TABLE u (a INT)",
+            "INSERT INTO t VALUES (1)",
+            "DROP TABLE t",
+        ] {
+            assert_read_only_err(execute(&mut sess, sql).await);
+        }
+
+        // Other roles can still write.
+        execute(&mut admin, "INSERT INTO t VALUES (1)")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn read_only_remote_context_rejects_write_plans() {
+        let create_schema: Arc<dyn ExecutionPlan> = Arc::new(CreateSchemaExec {
+            catalog_version: 0,
+            schema_reference: OwnedFullSchemaReference {
+                database: "default".into(),
+                schema: "s".into(),
+            },
+            if_not_exists: false,
+        });
+        // Writes nested in other plans are rejected as well.
+        let nested: Arc<dyn ExecutionPlan> =
+            Arc::new(CoalescePartitionsExec::new(create_schema.clone()));
+        let read: Arc<dyn ExecutionPlan> =
+            Arc::new(EmptyExec::new(false, Arc::new(Schema::empty())));
+
+        let read_only_engine = Engine::from_data_dir(None)
+            .await
+            .unwrap()
+            .with_read_only(true);
+        let context = read_only_engine
+            .new_remote_session_context(
+                Uuid::nil(),
+                SessionStorageConfig::default(),
+                SessionVars::default(),
+            )
+            .await
+            .unwrap();
+        for plan in [&create_schema, &nested] {
+            assert_read_only_err(context.execute_physical(plan.clone()).map(|_| ()));
+            assert_read_only_err(context.execute_partition(plan.clone(), 0).map(|_| ()));
+        }
+        context.execute_physical(read.clone()).unwrap();
+
+        // Read-only roles are rejected by writable engines.
+        let engine = Engine::from_data_dir(None).await.unwrap();
+        let mut admin = engine
+            .new_local_session_context(
+                SessionVars::default().with_is_admin(true, VarType::System),
+                SessionStorageConfig::default(),
+            )
+            .await
+            .unwrap();
+        execute(&mut admin, "ALTER ROLE sam READ ONLY")
+            .await
+            .unwrap();
+        let context = engine
+            .new_remote_session_context(
+                Uuid::nil(),
+                SessionStorageConfig::default(),
+                SessionVars::default().with_user_name("sam", VarType::System),
+            )
+            .await
+            .unwrap();
+        assert_read_only_err(context.execute_physical(nested.clone()).map(|_| ()));
+        context.execute_physical(read).unwrap();
+    }
+
+    #[tokio::test]
+    async fn remote_context_set_var_as_user() {
+        let engine = Engine::from_data_dir(None).await.unwrap();
+        let vars = SessionVars::default();
+        let context = engine
+            .new_remote_session_context(Uuid::nil(), SessionStorageConfig::default(), vars.clone())
+            .await
+            .unwrap();
+
+        async fn run(context: &RemoteSessionContext, plan: Arc<dyn ExecutionPlan>) -> Result<()> {
+            context
+                .execute_physical(plan)?
+                .try_collect::<Vec<_>>()
+                .await?;
+            Ok(())
+        }
+
+        // Plans sent by clients can't set variables reserved for the system.
+        for (variable, values) in [("is_admin", "true"), ("user_name", "admin")] {
+            let plan = Arc::new(SetVarExec {
+                variable: variable.to_string(),
+                values: values.to_string(),
+            });
+            run(&context, plan).await.unwrap_err();
+        }
+        assert!(!vars.is_admin());
+
+        // The database can only be changed to a native database.
+        let plan = Arc::new(UseDatabaseExec {
+            database: "missing".to_string(),
+        });
+        run(&context, plan).await.unwrap_err();
+        let plan = Arc::new(UseDatabaseExec {
+            database: "default".to_string(),
+        });
+        run(&context, plan).await.unwrap();
+        assert_eq!(vars.database(), "default");
+    }
+
+    #[tokio::test]
+    async fn import_catalog_requires_admin() {
+        let engine = Engine::from_data_dir(None).await.unwrap();
+        let mut sess = engine
+            .new_local_session_context(
+                SessionVars::default().with_is_embedded(true, VarType::System),
+                SessionStorageConfig::default(),
+            )
+            .await
+            .unwrap();
+        let err = execute(&mut sess, "IMPORT CATALOG FROM './catalog.dump'")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Permission denied"), "{err}");
+
+        // Plans sent by remote clients are checked as well.
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(ImportCatalogExec {
+            catalog_version: 0,
+            mutations: Vec::new(),
+        });
+        for (is_admin, allowed) in [(false, false), (true, true)] {
+            let context = engine
+                .new_remote_session_context(
+                    Uuid::nil(),
+                    SessionStorageConfig::default(),
+                    SessionVars::default().with_is_admin(is_admin, VarType::System),
+                )
+                .await
+                .unwrap();
+            let result = context
+                .execute_physical(plan.clone())
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await;
+            assert_eq!(result.is_ok(), allowed, "{result:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn alter_database_defaults_requires_admin() {
+        let engine = Engine::from_data_dir(None).await.unwrap();
+        let mut sess = engine
+            .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+            .await
+            .unwrap();
+        for sql in [
+            "alter database default set statement_timeout = 1000",
+            "alter database default reset statement_timeout",
+        ] {
+            let err = execute(&mut sess, sql).await.unwrap_err();
+            assert!(err.to_string().contains("Permission denied"), "{err}");
+        }
+
+        let mut sess = engine
+            .new_local_session_context(
+                SessionVars::default().with_is_admin(true, VarType::System),
+                SessionStorageConfig::default(),
+            )
+            .await
+            .unwrap();
+        execute(
+            &mut sess,
+            "alter database default set statement_timeout = 1000",
+        )
+        .await
+        .unwrap();
+
+        // Plans sent by remote clients are checked as well.
+        let context = engine
+            .new_remote_session_context(
+                Uuid::nil(),
+                SessionStorageConfig::default(),
+                SessionVars::default(),
+            )
+            .await
+            .unwrap();
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(AlterDatabaseExec {
+            catalog_version: 0,
+            name: "default".to_string(),
+            operation: AlterDatabaseOperation::ResetVariable {
+                variable: "statement_timeout".to_string(),
+            },
+        });
+        let err = context
+            .execute_physical(plan)
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Permission denied"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn reload_config_requires_admin() {
+        struct CountReloads(AtomicUsize);
+
+        impl ReloadConfig for CountReloads {
+            fn reload_config(&self) -> datafusion_ext::errors::Result<()> {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+        }
+
+        let reloads = Arc::new(CountReloads(AtomicUsize::new(0)));
+        let engine = Engine::from_data_dir(None)
+            .await
+            .unwrap()
+            .with_config_reloader(ConfigReloader::new(reloads.clone()));
+
+        let mut sess = engine
+            .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+            .await
+            .unwrap();
+        let err = execute(&mut sess, "select * from reload_config()")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Permission denied"), "{err}");
+        assert_eq!(0, reloads.0.load(Ordering::Relaxed));
+
+        let mut sess = engine
+            .new_local_session_context(
+                SessionVars::default().with_is_admin(true, VarType::System),
+                SessionStorageConfig::default(),
+            )
+            .await
+            .unwrap();
+        execute(&mut sess, "select * from reload_config()")
+            .await
+            .unwrap();
+        assert_eq!(1, reloads.0.load(Ordering::Relaxed));
+    }
 
     #[test]
     fn merged_conf_session_bucket() -> Result<()> {
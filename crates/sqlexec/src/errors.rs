@@ -1,3 +1,13 @@
+use datafusion::arrow::error::ArrowError;
+use datafusion::common::SchemaError;
+use datafusion::error::DataFusionError;
+use datafusion_ext::errors::ExtensionError;
+use datafusion_ext::vars::VarError as SessionVarError;
+use object_store_util::retry::is_transient_message;
+
+use crate::dispatch::DispatchError;
+use crate::planner::errors::PlanError;
+
 #[derive(Debug, thiserror::Error)]
 pub enum ExecError {
     #[error("SQL statement currently unsupported: {0}")]
@@ -27,6 +37,9 @@ pub enum ExecError {
     #[error("Unknown portal with name: {0}")]
     UnknownPortal(String),
 
+    #[error("Unknown worker: {0}")]
+    UnknownWorker(String),
+
     #[error("Empty search path, unable to resolve schema")]
     EmptySearchPath,
 
@@ -103,12 +116,33 @@ pub enum ExecError {
         current: usize,
     },
 
+    #[error("Query queue full: no query slot became available after waiting {waited:?} ({max_concurrent} concurrent queries allowed), try again later")]
+    QueryQueueFull {
+        max_concurrent: usize,
+        waited: std::time::Duration,
+    },
+
+    #[error("Too many connections: {max} connections allowed")]
+    TooManyConnections { max: usize },
+
+    #[error("Too many connections for user '{user}': {max} connections allowed")]
+    TooManyConnectionsForUser { user: String, max: usize },
+
+    #[error("Invalid SCRAM-SHA-256 password verifier")]
+    InvalidScramVerifier,
+
     #[error("Invalid storage configuration: {0}")]
     InvalidStorageConfig(&'static str),
 
     #[error("Failed to read table from environment: {0}")]
     EnvironmentTableRead(Box<dyn std::error::Error + Send + Sync>),
 
+    #[error("Table function '{0}' conflicts with a builtin function")]
+    TableFunctionConflictsWithBuiltin(String),
+
+    #[error("Invalid argument for table function '{func}': {reason}")]
+    InvalidTableFunctionArgument { func: String, reason: String },
+
     #[error("Unable to send message over channel: {0}")]
     ChannelSendError(Box<dyn std::error::Error + Send + Sync>),
 
@@ -166,6 +200,225 @@ pub enum ExecError {
 
 pub type Result<T, E = ExecError> = std::result::Result<T, E>;
 
+/// Broad class of an error.
+///
+/// Classes are stable so that clients can branch on them instead of parsing
+/// error messages. Frontends map them to the codes of their protocol (e.g.
+/// SQLSTATE for pgwire).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The statement couldn't be parsed.
+    Syntax,
+    /// The statement parsed, but is invalid (e.g. wrong number of aliases).
+    InvalidStatement,
+    /// A referenced table doesn't exist.
+    UndefinedTable,
+    /// A referenced column doesn't exist.
+    UndefinedColumn,
+    /// A referenced function doesn't exist.
+    UndefinedFunction,
+    /// A referenced database doesn't exist.
+    UndefinedDatabase,
+    /// Some other referenced object (connection, variable, file, etc) doesn't
+    /// exist.
+    UndefinedObject,
+    /// An object with the same name already exists.
+    DuplicateObject,
+    /// Not allowed to access the object.
+    PermissionDenied,
+    /// Attempted to modify something in a read-only session.
+    ReadOnly,
+    /// An argument, option or variable has an invalid value.
+    InvalidParameter,
+    /// Data couldn't be converted to the requested type.
+    InvalidData,
+    DivisionByZero,
+    /// Prepared statement doesn't exist.
+    UndefinedPreparedStatement,
+    /// Portal doesn't exist.
+    UndefinedPortal,
+    FeatureNotSupported,
+    /// A configured limit was hit, retrying later may succeed.
+    ResourceLimit,
+    TooManyConnections,
+    /// An external database or object store couldn't be reached.
+    ExternalSourceUnreachable,
+    /// Anything else, including bugs.
+    Internal,
+}
+
+/// Substrings of (lowercased) error messages indicating that a source
+/// couldn't be reached, in addition to the transient errors that are retried.
+const UNREACHABLE_ERROR_PATTERNS: &[&str] = &[
+    "failed to connect",
+    "could not connect",
+    "unable to connect",
+    "error connecting",
+    "dns error",
+    "no route to host",
+    "name or service not known",
+    "failed to lookup address",
+];
+
+/// Classify an error from an external source by its message, the only thing
+/// we have for most client libraries.
+pub(crate) fn external_error_class(msg: &str) -> ErrorClass {
+    let lower = msg.to_lowercase();
+    if is_transient_message(&lower)
+        || UNREACHABLE_ERROR_PATTERNS
+            .iter()
+            .any(|pattern| lower.contains(pattern))
+    {
+        ErrorClass::ExternalSourceUnreachable
+    } else {
+        ErrorClass::Internal
+    }
+}
+
+/// Classify an error returned by datafusion.
+///
+/// Errors from glaredb that are passed through datafusion are classified as
+/// if they weren't wrapped.
+pub fn datafusion_error_class(err: &DataFusionError) -> ErrorClass {
+    match err {
+        DataFusionError::SQL(_) => ErrorClass::Syntax,
+        DataFusionError::SchemaError(SchemaError::FieldNotFound { .. }) => {
+            ErrorClass::UndefinedColumn
+        }
+        DataFusionError::SchemaError(_) => ErrorClass::InvalidStatement,
+        DataFusionError::Plan(msg) if msg.starts_with("Invalid function") => {
+            ErrorClass::UndefinedFunction
+        }
+        DataFusionError::Plan(_) => ErrorClass::InvalidStatement,
+        DataFusionError::NotImplemented(_) => ErrorClass::FeatureNotSupported,
+        DataFusionError::Configuration(_) => ErrorClass::InvalidParameter,
+        DataFusionError::ResourcesExhausted(_) => ErrorClass::ResourceLimit,
+        DataFusionError::ArrowError(e) => arrow_error_class(e),
+        DataFusionError::ObjectStore(e) => object_store_error_class(e),
+        DataFusionError::Context(_, e) => datafusion_error_class(e),
+        DataFusionError::External(e) => {
+            if let Some(e) = e.downcast_ref::<ExecError>() {
+                e.class()
+            } else if let Some(e) = e.downcast_ref::<PlanError>() {
+                e.class()
+            } else if let Some(e) = e.downcast_ref::<DispatchError>() {
+                e.class()
+            } else if let Some(e) = e.downcast_ref::<ExtensionError>() {
+                extension_error_class(e)
+            } else if let Some(e) = e.downcast_ref::<SessionVarError>() {
+                match e {
+                    SessionVarError::UnknownVariable(_) => ErrorClass::UndefinedObject,
+                    _ => ErrorClass::InvalidParameter,
+                }
+            } else if let Some(e) = e.downcast_ref::<DataFusionError>() {
+                datafusion_error_class(e)
+            } else if let Some(e) = e.downcast_ref::<object_store::Error>() {
+                object_store_error_class(e)
+            } else {
+                external_error_class(&e.to_string())
+            }
+        }
+        DataFusionError::Execution(msg) => external_error_class(msg),
+        _ => ErrorClass::Internal,
+    }
+}
+
+fn arrow_error_class(err: &ArrowError) -> ErrorClass {
+    match err {
+        ArrowError::DivideByZero => ErrorClass::DivisionByZero,
+        ArrowError::CastError(_) | ArrowError::ParseError(_) => ErrorClass::InvalidData,
+        _ => ErrorClass::Internal,
+    }
+}
+
+pub(crate) fn object_store_error_class(err: &object_store::Error) -> ErrorClass {
+    match err {
+        object_store::Error::NotFound { .. } => ErrorClass::UndefinedObject,
+        object_store::Error::AlreadyExists { .. } => ErrorClass::DuplicateObject,
+        object_store::Error::NotSupported { .. } | object_store::Error::NotImplemented => {
+            ErrorClass::FeatureNotSupported
+        }
+        other => external_error_class(&other.to_string()),
+    }
+}
+
+pub(crate) fn extension_error_class(err: &ExtensionError) -> ErrorClass {
+    match err {
+        ExtensionError::MissingObject { .. } => ErrorClass::UndefinedObject,
+        ExtensionError::InvalidNumArgs
+        | ExtensionError::ExpectedIndexedArgument { .. }
+        | ExtensionError::MissingNamedArgument(_)
+        | ExtensionError::InvalidParamValue { .. } => ErrorClass::InvalidParameter,
+        ExtensionError::Unimplemented(_) => ErrorClass::FeatureNotSupported,
+        ExtensionError::DataFusion(e) => datafusion_error_class(e),
+        other => external_error_class(&other.to_string()),
+    }
+}
+
+impl ExecError {
+    /// Get the class of this error.
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            ExecError::UnsupportedSQLStatement(_)
+            | ExecError::UnsupportedFeature(_)
+            | ExecError::ExternalTableWithSsh => ErrorClass::FeatureNotSupported,
+            ExecError::InvalidSessionVarValue { .. }
+            | ExecError::VariableReadonly(_)
+            | ExecError::InvalidConnectionType { .. }
+            | ExecError::NonSshConnection
+            | ExecError::InvalidTableFunctionArgument { .. }
+            | ExecError::InvalidTempTable { .. }
+            | ExecError::InvalidRemoteExecUrl(_) => ErrorClass::InvalidParameter,
+            ExecError::UnknownVariable(_)
+            | ExecError::MissingConnectionByName { .. }
+            | ExecError::MissingConnectionByOid { .. }
+            | ExecError::MissingRemoteId(..)
+            | ExecError::MissingObject { .. } => ErrorClass::UndefinedObject,
+            ExecError::UnknownPreparedStatement(_) => ErrorClass::UndefinedPreparedStatement,
+            ExecError::UnknownPortal(_) => ErrorClass::UndefinedPortal,
+            ExecError::DuplicateObjectName(_) | ExecError::TableFunctionConflictsWithBuiltin(_) => {
+                ErrorClass::DuplicateObject
+            }
+            ExecError::MaxObjectCount { .. } | ExecError::QueryQueueFull { .. } => {
+                ErrorClass::ResourceLimit
+            }
+            ExecError::TooManyConnections { .. } | ExecError::TooManyConnectionsForUser { .. } => {
+                ErrorClass::TooManyConnections
+            }
+            ExecError::ParseError(_) => ErrorClass::Syntax,
+            ExecError::DataFusion(e) => datafusion_error_class(e),
+            ExecError::Arrow(e) => arrow_error_class(e),
+            ExecError::ObjectStore(e) => object_store_error_class(e),
+            ExecError::ExtensionError(e) => extension_error_class(e),
+            ExecError::PlanError(e) => e.class(),
+            ExecError::DispatchError(e) => e.class(),
+            ExecError::MissingSshTunnel(e) => e.class(),
+            ExecError::DatasourceObjectStore(e) => external_error_class(&e.to_string()),
+            ExecError::DatasourceCommon(e) => external_error_class(&e.to_string()),
+            ExecError::RemoteSession(msg) => external_error_class(msg),
+            ExecError::TonicTransport(_) => ErrorClass::ExternalSourceUnreachable,
+            ExecError::TonicStatus(status) => match status.code() {
+                tonic::Code::Unavailable | tonic::Code::DeadlineExceeded => {
+                    ErrorClass::ExternalSourceUnreachable
+                }
+                tonic::Code::NotFound => ErrorClass::UndefinedObject,
+                tonic::Code::AlreadyExists => ErrorClass::DuplicateObject,
+                tonic::Code::PermissionDenied | tonic::Code::Unauthenticated => {
+                    ErrorClass::PermissionDenied
+                }
+                tonic::Code::InvalidArgument => ErrorClass::InvalidParameter,
+                tonic::Code::ResourceExhausted => ErrorClass::ResourceLimit,
+                tonic::Code::Unimplemented => ErrorClass::FeatureNotSupported,
+                _ => ErrorClass::Internal,
+            },
+            ExecError::ReqwestError(e) if e.is_connect() || e.is_timeout() => {
+                ErrorClass::ExternalSourceUnreachable
+            }
+            _ => ErrorClass::Internal,
+        }
+    }
+}
+
 #[allow(unused_macros)]
 macro_rules! internal {
     ($($arg:tt)*) => {
@@ -173,3 +426,51 @@ macro_rules! internal {
     };
 }
 pub(crate) use internal;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolve::ResolveError;
+
+    #[test]
+    fn classify_wrapped_errors() {
+        // Planning errors passed through datafusion keep their class.
+        let err = ExecError::DataFusion(DataFusionError::External(Box::new(
+            PlanError::UnableToFetchTableProvider {
+                reference: "missing".to_string(),
+                source: Box::new(PlanError::ResolveError(ResolveError::MissingTable {
+                    reference: "missing".to_string(),
+                    suggestions: Vec::new(),
+                })),
+            },
+        )));
+        assert_eq!(ErrorClass::UndefinedTable, err.class());
+
+        let err = ExecError::PlanError(PlanError::Dispatch(DispatchError::MissingDatabase {
+            database: "missing".to_string(),
+        }));
+        assert_eq!(ErrorClass::UndefinedDatabase, err.class());
+
+        let err = ExecError::DataFusion(DataFusionError::ArrowError(ArrowError::DivideByZero));
+        assert_eq!(ErrorClass::DivisionByZero, err.class());
+    }
+
+    #[test]
+    fn classify_external_errors() {
+        assert_eq!(
+            ErrorClass::ExternalSourceUnreachable,
+            external_error_class("error connecting to server: Connection refused (os error 111)")
+        );
+        assert_eq!(
+            ErrorClass::ExternalSourceUnreachable,
+            object_store_error_class(&object_store::Error::Generic {
+                store: "S3",
+                source: "HTTP status server error (503 Service Unavailable)".into(),
+            })
+        );
+        assert_eq!(
+            ErrorClass::Internal,
+            external_error_class("relation \"foo\" already has a primary key")
+        );
+    }
+}
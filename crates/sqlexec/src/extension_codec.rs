@@ -11,12 +11,14 @@ use datafusion::execution::{FunctionRegistry, TaskContext};
 use datafusion::physical_plan::analyze::AnalyzeExec;
 use datafusion::physical_plan::union::InterleaveExec;
 use datafusion::physical_plan::values::ValuesExec;
-use datafusion::physical_plan::{displayable, ExecutionPlan};
+use datafusion::physical_plan::{displayable, ExecutionPlan, Statistics};
 use datafusion::prelude::Expr;
 use datafusion_ext::metrics::{
-    ReadOnlyDataSourceMetricsExecAdapter, WriteOnlyDataSourceMetricsExecAdapter,
+    ReadOnlyDataSourceMetricsExecAdapter, ScanPruning, WriteOnlyDataSourceMetricsExecAdapter,
 };
 use datafusion_ext::runtime::runtime_group::RuntimeGroupExec;
+use datafusion_ext::sample::{SampleExec, SampleSize, SampleSpec};
+use datafusion_ext::warnings::SkipErrorsExec;
 use datafusion_proto::logical_plan::from_proto::parse_expr;
 use datafusion_proto::physical_plan::PhysicalExtensionCodec;
 use protogen::export::prost::Message;
@@ -24,11 +26,16 @@ use protogen::metastore::types::catalog::RuntimePreference;
 use uuid::Uuid;
 
 use crate::planner::physical_plan::alter_database::AlterDatabaseExec;
+use crate::planner::physical_plan::alter_role::AlterRoleExec;
+use crate::planner::physical_plan::alter_schema::AlterSchemaExec;
 use crate::planner::physical_plan::alter_table::AlterTableExec;
 use crate::planner::physical_plan::alter_tunnel_rotate_keys::AlterTunnelRotateKeysExec;
+use crate::planner::physical_plan::analyze_table::AnalyzeTableExec;
+use crate::planner::physical_plan::comment_on::CommentOnExec;
 use crate::planner::physical_plan::copy_to::CopyToExec;
 use crate::planner::physical_plan::create_credential::CreateCredentialExec;
 use crate::planner::physical_plan::create_credentials::CreateCredentialsExec;
+use crate::planner::physical_plan::create_database::CreateDatabaseExec;
 use crate::planner::physical_plan::create_external_database::CreateExternalDatabaseExec;
 use crate::planner::physical_plan::create_external_table::CreateExternalTableExec;
 use crate::planner::physical_plan::create_schema::CreateSchemaExec;
@@ -44,11 +51,16 @@ use crate::planner::physical_plan::drop_schemas::DropSchemasExec;
 use crate::planner::physical_plan::drop_tables::DropTablesExec;
 use crate::planner::physical_plan::drop_tunnel::DropTunnelExec;
 use crate::planner::physical_plan::drop_views::DropViewsExec;
+use crate::planner::physical_plan::export_catalog::ExportCatalogExec;
+use crate::planner::physical_plan::import_catalog::ImportCatalogExec;
 use crate::planner::physical_plan::insert::InsertExec;
 use crate::planner::physical_plan::remote_scan::ProviderReference;
+use crate::planner::physical_plan::restore_catalog::RestoreCatalogExec;
 use crate::planner::physical_plan::set_var::SetVarExec;
 use crate::planner::physical_plan::show_var::ShowVarExec;
 use crate::planner::physical_plan::update::UpdateExec;
+use crate::planner::physical_plan::upsert::UpsertExec;
+use crate::planner::physical_plan::use_database::UseDatabaseExec;
 use crate::planner::physical_plan::values::ExtValuesExec;
 use crate::planner::physical_plan::{
     client_recv::ClientExchangeRecvExec, remote_scan::RemoteScanExec,
@@ -148,8 +160,16 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                     projection,
                     filters,
                     limit,
+                    Statistics::default(),
                 ))
             }
+            proto::ExecutionPlanExtensionType::CreateDatabaseExec(ext) => {
+                Arc::new(CreateDatabaseExec {
+                    catalog_version: ext.catalog_version,
+                    database_name: ext.database_name,
+                    if_not_exists: ext.if_not_exists,
+                })
+            }
             proto::ExecutionPlanExtensionType::CreateSchema(ext) => Arc::new(CreateSchemaExec {
                 catalog_version: ext.catalog_version,
                 schema_reference: ext
@@ -206,8 +226,39 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                         .try_into()?,
                 })
             }
+            proto::ExecutionPlanExtensionType::AlterSchemaExec(ext) => Arc::new(AlterSchemaExec {
+                catalog_version: ext.catalog_version,
+                database: ext.database,
+                name: ext.name,
+                operation: ext
+                    .operation
+                    .ok_or_else(|| {
+                        DataFusionError::Internal("missing alter schema operation".to_string())
+                    })?
+                    .try_into()?,
+            }),
+            proto::ExecutionPlanExtensionType::CommentOnExec(ext) => Arc::new(CommentOnExec {
+                catalog_version: ext.catalog_version,
+                database: ext.database,
+                schema: ext.schema,
+                name: ext.name,
+                column: ext.column,
+                comment: ext.comment,
+            }),
+            proto::ExecutionPlanExtensionType::AlterRoleExec(ext) => Arc::new(AlterRoleExec {
+                catalog_version: ext.catalog_version,
+                name: ext.name,
+                database: ext.database,
+                operation: ext
+                    .operation
+                    .ok_or_else(|| {
+                        DataFusionError::Internal("missing alter role operation".to_string())
+                    })?
+                    .try_into()?,
+            }),
             proto::ExecutionPlanExtensionType::AlterTableExec(ext) => Arc::new(AlterTableExec {
                 catalog_version: ext.catalog_version,
+                database: ext.database,
                 schema: ext.schema,
                 name: ext.name,
                 operation: ext
@@ -217,6 +268,22 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                     })?
                     .try_into()?,
             }),
+            proto::ExecutionPlanExtensionType::AnalyzeTableExec(ext) => {
+                Arc::new(AnalyzeTableExec {
+                    catalog_version: ext.catalog_version,
+                    tbl_reference: ext
+                        .tbl_reference
+                        .ok_or_else(|| {
+                            DataFusionError::Internal("missing table references".to_string())
+                        })?
+                        .into(),
+                    columns: ext.columns,
+                    source: inputs
+                        .first()
+                        .ok_or_else(|| DataFusionError::Internal("missing source".to_string()))?
+                        .clone(),
+                })
+            }
             proto::ExecutionPlanExtensionType::AlterTunnelRotateKeysExec(ext) => {
                 Arc::new(AlterTunnelRotateKeysExec {
                     catalog_version: ext.catalog_version,
@@ -232,6 +299,38 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                     if_exists: ext.if_exists,
                 })
             }
+            proto::ExecutionPlanExtensionType::ExportCatalogExec(ext) => {
+                Arc::new(ExportCatalogExec {
+                    path: ext.path,
+                    mutations: ext
+                        .mutations
+                        .into_iter()
+                        .map(|m| m.try_into())
+                        .collect::<Result<_, _>>()?,
+                    encryption_key: ext.encryption_key,
+                })
+            }
+            proto::ExecutionPlanExtensionType::ImportCatalogExec(ext) => {
+                Arc::new(ImportCatalogExec {
+                    catalog_version: ext.catalog_version,
+                    mutations: ext
+                        .mutations
+                        .into_iter()
+                        .map(|m| m.try_into())
+                        .collect::<Result<_, _>>()?,
+                })
+            }
+            proto::ExecutionPlanExtensionType::RestoreCatalogExec(ext) => {
+                let timestamp = ext
+                    .timestamp
+                    .ok_or_else(|| DataFusionError::Plan("missing timestamp".to_string()))?;
+                Arc::new(RestoreCatalogExec {
+                    catalog_version: ext.catalog_version,
+                    timestamp: timestamp
+                        .try_into()
+                        .map_err(|e| DataFusionError::Plan(format!("invalid timestamp: {e}")))?,
+                })
+            }
             proto::ExecutionPlanExtensionType::CreateTableExec(ext) => {
                 let schema = ext
                     .arrow_schema
@@ -290,6 +389,7 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                 catalog_version: ext.catalog_version,
                 view_references: ext.view_references.into_iter().map(|r| r.into()).collect(),
                 if_exists: ext.if_exists,
+                cascade: ext.cascade,
             }),
             proto::ExecutionPlanExtensionType::CreateExternalDatabaseExec(ext) => {
                 let options = ext.options.ok_or(protogen::ProtoConvError::RequiredField(
@@ -321,6 +421,7 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                     if_not_exists: ext.if_not_exists,
                     table_options: table_options.try_into()?,
                     tunnel: ext.tunnel,
+                    schema: ext.schema.map(TryInto::try_into).transpose()?,
                 })
             }
             proto::ExecutionPlanExtensionType::CreateTunnelExec(ext) => {
@@ -343,6 +444,7 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                 sql: ext.sql,
                 columns: ext.columns,
                 or_replace: ext.or_replace,
+                dependencies: ext.dependencies,
             }),
             proto::ExecutionPlanExtensionType::DropCredentialsExec(ext) => {
                 Arc::new(DropCredentialsExec {
@@ -355,6 +457,7 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                 catalog_version: ext.catalog_version,
                 tbl_references: ext.tbl_references.into_iter().map(|r| r.into()).collect(),
                 if_exists: ext.if_exists,
+                cascade: ext.cascade,
             }),
             proto::ExecutionPlanExtensionType::SetVarExec(ext) => Arc::new(SetVarExec {
                 variable: ext.variable,
@@ -363,6 +466,9 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
             proto::ExecutionPlanExtensionType::ShowVarExec(ext) => Arc::new(ShowVarExec {
                 variable: ext.variable,
             }),
+            proto::ExecutionPlanExtensionType::UseDatabaseExec(ext) => Arc::new(UseDatabaseExec {
+                database: ext.database,
+            }),
             proto::ExecutionPlanExtensionType::UpdateExec(ext) => {
                 let mut updates = Vec::with_capacity(ext.updates.len());
                 for update in ext.updates {
@@ -412,6 +518,42 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                     )),
                 })
             }
+            proto::ExecutionPlanExtensionType::UpsertExec(ext) => {
+                let updates = if ext.do_update {
+                    let mut updates = Vec::with_capacity(ext.updates.len());
+                    for update in ext.updates {
+                        let expr = update.expr.ok_or_else(|| {
+                            DataFusionError::Internal("missing expression".to_string())
+                        })?;
+                        let expr = parse_expr(&expr, registry)?;
+                        updates.push((update.column.clone(), expr));
+                    }
+                    Some(updates)
+                } else {
+                    None
+                };
+                let where_expr: Option<Expr> = ext
+                    .where_expr
+                    .map(|expr| parse_expr(&expr, registry))
+                    .transpose()?;
+                Arc::new(UpsertExec {
+                    table: ext
+                        .table
+                        .ok_or_else(|| DataFusionError::Internal("missing table".to_string()))?
+                        .try_into()?,
+                    source: Arc::new(WriteOnlyDataSourceMetricsExecAdapter::new(
+                        inputs
+                            .first()
+                            .ok_or_else(|| {
+                                DataFusionError::Internal("missing input source".to_string())
+                            })?
+                            .clone(),
+                    )),
+                    conflict_columns: ext.conflict_columns,
+                    updates,
+                    where_expr,
+                })
+            }
             proto::ExecutionPlanExtensionType::DeleteExec(ext) => {
                 let where_expr: Option<Expr> = ext
                     .where_expr
@@ -491,9 +633,48 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                 if ext.track_writes {
                     Arc::new(WriteOnlyDataSourceMetricsExecAdapter::new(source))
                 } else {
-                    Arc::new(ReadOnlyDataSourceMetricsExecAdapter::new(source))
+                    let mut exec = ReadOnlyDataSourceMetricsExecAdapter::new(source);
+                    if let Some(pruning) = ext.pruning {
+                        exec = exec.with_pruning(ScanPruning {
+                            files: pruning.files as usize,
+                            files_pruned_by_statistics: pruning.files_pruned_by_statistics as usize,
+                            files_pruned_by_bloom_filter: pruning.files_pruned_by_bloom_filter
+                                as usize,
+                            row_groups: pruning.row_groups.map(|n| n as usize),
+                            row_groups_pruned_by_statistics: pruning.row_groups_pruned_by_statistics
+                                as usize,
+                            row_groups_pruned_by_bloom_filter: pruning
+                                .row_groups_pruned_by_bloom_filter
+                                as usize,
+                        });
+                    }
+                    Arc::new(exec)
                 }
             }
+            proto::ExecutionPlanExtensionType::SampleExec(ext) => {
+                let input = inputs
+                    .first()
+                    .ok_or_else(|| DataFusionError::Internal("missing input".to_string()))?
+                    .clone();
+                let size = match (ext.percent, ext.rows) {
+                    (Some(percent), None) => SampleSize::Percent(percent),
+                    (None, Some(rows)) => SampleSize::Rows(rows as usize),
+                    _ => {
+                        return Err(DataFusionError::Internal(
+                            "expected one of percent or rows for sample".to_string(),
+                        ))
+                    }
+                };
+                let spec = SampleSpec::try_new(ext.method.parse()?, size, ext.seed)?;
+                Arc::new(SampleExec::new(input, spec))
+            }
+            proto::ExecutionPlanExtensionType::SkipErrorsExec(ext) => {
+                let input = inputs
+                    .first()
+                    .ok_or_else(|| DataFusionError::Internal("missing input".to_string()))?
+                    .clone();
+                Arc::new(SkipErrorsExec::new(input, ext.source))
+            }
         };
 
         Ok(plan)
@@ -536,6 +717,12 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                     .collect::<Result<_, _>>()?,
                 limit: exec.limit.map(|u| u as u64),
             })
+        } else if let Some(exec) = node.as_any().downcast_ref::<CreateDatabaseExec>() {
+            proto::ExecutionPlanExtensionType::CreateDatabaseExec(proto::CreateDatabaseExec {
+                catalog_version: exec.catalog_version,
+                database_name: exec.database_name.clone(),
+                if_not_exists: exec.if_not_exists,
+            })
         } else if let Some(exec) = node.as_any().downcast_ref::<CreateSchemaExec>() {
             proto::ExecutionPlanExtensionType::CreateSchema(proto::CreateSchema {
                 catalog_version: exec.catalog_version,
@@ -571,12 +758,42 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                 name: exec.name.clone(),
                 operation: Some(exec.operation.clone().into()),
             })
+        } else if let Some(exec) = node.as_any().downcast_ref::<AlterSchemaExec>() {
+            proto::ExecutionPlanExtensionType::AlterSchemaExec(proto::AlterSchemaExec {
+                catalog_version: exec.catalog_version,
+                database: exec.database.clone(),
+                name: exec.name.clone(),
+                operation: Some(exec.operation.clone().into()),
+            })
+        } else if let Some(exec) = node.as_any().downcast_ref::<CommentOnExec>() {
+            proto::ExecutionPlanExtensionType::CommentOnExec(proto::CommentOnExec {
+                catalog_version: exec.catalog_version,
+                database: exec.database.clone(),
+                schema: exec.schema.clone(),
+                name: exec.name.clone(),
+                column: exec.column.clone(),
+                comment: exec.comment.clone(),
+            })
+        } else if let Some(exec) = node.as_any().downcast_ref::<AlterRoleExec>() {
+            proto::ExecutionPlanExtensionType::AlterRoleExec(proto::AlterRoleExec {
+                catalog_version: exec.catalog_version,
+                name: exec.name.clone(),
+                database: exec.database.clone(),
+                operation: Some(exec.operation.clone().into()),
+            })
         } else if let Some(exec) = node.as_any().downcast_ref::<AlterTableExec>() {
             proto::ExecutionPlanExtensionType::AlterTableExec(proto::AlterTableExec {
                 catalog_version: exec.catalog_version,
+                database: exec.database.to_owned(),
                 schema: exec.schema.to_owned(),
                 name: exec.name.to_owned(),
-                operation: Some(exec.operation.clone().into()),
+                operation: Some(exec.operation.clone().try_into()?),
+            })
+        } else if let Some(exec) = node.as_any().downcast_ref::<AnalyzeTableExec>() {
+            proto::ExecutionPlanExtensionType::AnalyzeTableExec(proto::AnalyzeTableExec {
+                catalog_version: exec.catalog_version,
+                tbl_reference: Some(exec.tbl_reference.clone().into()),
+                columns: exec.columns.clone(),
             })
         } else if let Some(exec) = node.as_any().downcast_ref::<AlterTunnelRotateKeysExec>() {
             proto::ExecutionPlanExtensionType::AlterTunnelRotateKeysExec(
@@ -593,6 +810,30 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                 names: exec.names.clone(),
                 if_exists: exec.if_exists,
             })
+        } else if let Some(exec) = node.as_any().downcast_ref::<ExportCatalogExec>() {
+            proto::ExecutionPlanExtensionType::ExportCatalogExec(proto::ExportCatalogExec {
+                path: exec.path.clone(),
+                mutations: exec
+                    .mutations
+                    .iter()
+                    .map(|m| m.clone().try_into())
+                    .collect::<Result<_, _>>()?,
+                encryption_key: exec.encryption_key.clone(),
+            })
+        } else if let Some(exec) = node.as_any().downcast_ref::<ImportCatalogExec>() {
+            proto::ExecutionPlanExtensionType::ImportCatalogExec(proto::ImportCatalogExec {
+                catalog_version: exec.catalog_version,
+                mutations: exec
+                    .mutations
+                    .iter()
+                    .map(|m| m.clone().try_into())
+                    .collect::<Result<_, _>>()?,
+            })
+        } else if let Some(exec) = node.as_any().downcast_ref::<RestoreCatalogExec>() {
+            proto::ExecutionPlanExtensionType::RestoreCatalogExec(proto::RestoreCatalogExec {
+                catalog_version: exec.catalog_version,
+                timestamp: Some(exec.timestamp.into()),
+            })
         } else if let Some(exec) = node.as_any().downcast_ref::<DropSchemasExec>() {
             proto::ExecutionPlanExtensionType::DropSchemasExec(proto::DropSchemasExec {
                 catalog_version: exec.catalog_version,
@@ -621,6 +862,7 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                     .map(|r| r.into())
                     .collect(),
                 if_exists: exec.if_exists,
+                cascade: exec.cascade,
             })
         } else if let Some(exec) = node.as_any().downcast_ref::<CreateExternalDatabaseExec>() {
             proto::ExecutionPlanExtensionType::CreateExternalDatabaseExec(
@@ -641,6 +883,7 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                     if_not_exists: exec.if_not_exists,
                     table_options: Some(exec.table_options.clone().try_into()?),
                     tunnel: exec.tunnel.clone(),
+                    schema: exec.schema.clone().map(TryInto::try_into).transpose()?,
                 },
             )
         } else if let Some(exec) = node.as_any().downcast_ref::<CreateTunnelExec>() {
@@ -657,6 +900,7 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                 sql: exec.sql.clone(),
                 columns: exec.columns.clone(),
                 or_replace: exec.or_replace,
+                dependencies: exec.dependencies.clone(),
             })
         } else if let Some(exec) = node.as_any().downcast_ref::<DescribeTableExec>() {
             proto::ExecutionPlanExtensionType::DescribeTable(proto::DescribeTableExec {
@@ -678,12 +922,17 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                     .map(|r| r.into())
                     .collect(),
                 if_exists: exec.if_exists,
+                cascade: exec.cascade,
             })
         } else if let Some(exec) = node.as_any().downcast_ref::<SetVarExec>() {
             proto::ExecutionPlanExtensionType::SetVarExec(proto::SetVarExec {
                 variable: exec.variable.clone(),
                 values: exec.values.clone(),
             })
+        } else if let Some(exec) = node.as_any().downcast_ref::<UseDatabaseExec>() {
+            proto::ExecutionPlanExtensionType::UseDatabaseExec(proto::UseDatabaseExec {
+                database: exec.database.clone(),
+            })
         } else if let Some(exec) = node.as_any().downcast_ref::<ShowVarExec>() {
             proto::ExecutionPlanExtensionType::ShowVarExec(proto::ShowVarExec {
                 variable: exec.variable.clone(),
@@ -719,6 +968,26 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
             proto::ExecutionPlanExtensionType::InsertExec(proto::InsertExec {
                 provider_id: id.into_bytes().to_vec(),
             })
+        } else if let Some(exec) = node.as_any().downcast_ref::<UpsertExec>() {
+            let mut updates = Vec::new();
+            for (col, expr) in exec.updates.iter().flatten() {
+                updates.push(proto::UpdateSelector {
+                    column: col.clone(),
+                    expr: Some(expr.try_into()?),
+                });
+            }
+
+            proto::ExecutionPlanExtensionType::UpsertExec(proto::UpsertExec {
+                table: Some(exec.table.clone().try_into()?),
+                conflict_columns: exec.conflict_columns.clone(),
+                do_update: exec.updates.is_some(),
+                updates,
+                where_expr: exec
+                    .where_expr
+                    .as_ref()
+                    .map(|expr| expr.try_into())
+                    .transpose()?,
+            })
         } else if let Some(exec) = node.as_any().downcast_ref::<DeleteExec>() {
             proto::ExecutionPlanExtensionType::DeleteExec(proto::DeleteExec {
                 table: Some(exec.table.clone().try_into()?),
@@ -777,13 +1046,23 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
                 show_statistics: true,
                 schema: Some(exec.schema().try_into()?),
             })
-        } else if let Some(_exec) = node
+        } else if let Some(exec) = node
             .as_any()
             .downcast_ref::<ReadOnlyDataSourceMetricsExecAdapter>()
         {
             proto::ExecutionPlanExtensionType::DataSourceMetricsExecAdapter(
                 proto::DataSourceMetricsExecAdapter {
                     track_writes: false,
+                    pruning: exec.pruning().map(|pruning| proto::ScanPruning {
+                        files: pruning.files as u64,
+                        files_pruned_by_statistics: pruning.files_pruned_by_statistics as u64,
+                        files_pruned_by_bloom_filter: pruning.files_pruned_by_bloom_filter as u64,
+                        row_groups: pruning.row_groups.map(|n| n as u64),
+                        row_groups_pruned_by_statistics: pruning.row_groups_pruned_by_statistics
+                            as u64,
+                        row_groups_pruned_by_bloom_filter: pruning.row_groups_pruned_by_bloom_filter
+                            as u64,
+                    }),
                 },
             )
         } else if let Some(_exec) = node
@@ -791,8 +1070,27 @@ impl<'a> PhysicalExtensionCodec for GlareDBExtensionCodec<'a> {
             .downcast_ref::<WriteOnlyDataSourceMetricsExecAdapter>()
         {
             proto::ExecutionPlanExtensionType::DataSourceMetricsExecAdapter(
-                proto::DataSourceMetricsExecAdapter { track_writes: true },
+                proto::DataSourceMetricsExecAdapter {
+                    track_writes: true,
+                    pruning: None,
+                },
             )
+        } else if let Some(exec) = node.as_any().downcast_ref::<SampleExec>() {
+            let spec = exec.spec();
+            let (percent, rows) = match spec.size() {
+                SampleSize::Percent(percent) => (Some(percent), None),
+                SampleSize::Rows(rows) => (None, Some(rows as u64)),
+            };
+            proto::ExecutionPlanExtensionType::SampleExec(proto::SampleExec {
+                method: spec.method().to_string(),
+                percent,
+                rows,
+                seed: spec.seed(),
+            })
+        } else if let Some(exec) = node.as_any().downcast_ref::<SkipErrorsExec>() {
+            proto::ExecutionPlanExtensionType::SkipErrorsExec(proto::SkipErrorsExec {
+                source: exec.source().to_string(),
+            })
         } else {
             return Err(DataFusionError::NotImplemented(format!(
                 "encoding not implemented for physical plan: {}",
@@ -1,15 +1,25 @@
 //! SQL execution.
+pub mod admission;
+pub mod audit;
+pub mod change_feed;
+pub mod connections;
 pub mod context;
 pub mod distexec;
 pub mod engine;
 pub mod environment;
 pub mod errors;
 pub mod extension_codec;
+pub mod memory;
 pub mod parser;
+pub mod plan_cache;
+pub mod query_history;
 pub mod remote;
+pub mod scram;
 pub mod session;
+pub mod user_functions;
 
 mod dispatch;
+mod persisted;
 mod planner;
 mod resolve;
 
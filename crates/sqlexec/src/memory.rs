@@ -0,0 +1,289 @@
+//! Memory accounting for queries and sessions.
+//!
+//! Every query executes with its own memory pool wrapping the session's pool.
+//! Reservations made through the wrapper are attributed to the query and its
+//! session, allowing for finding which query is using all the memory on a
+//! node.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+
+use datafusion::error::Result as DataFusionResult;
+use datafusion::execution::memory_pool::{MemoryConsumer, MemoryPool, MemoryReservation};
+use parking_lot::Mutex;
+use uuid::Uuid;
+
+/// Tracks memory reserved by sessions on an engine.
+///
+/// Cheaply cloneable, all clones share the same set of sessions.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTracker {
+    sessions: Arc<Mutex<Vec<Weak<SessionMemory>>>>,
+}
+
+impl MemoryTracker {
+    /// Start tracking memory for a session.
+    ///
+    /// The session will stop being tracked once the returned value is
+    /// dropped.
+    pub fn register_session(&self, connection_id: Uuid, user_name: String) -> Arc<SessionMemory> {
+        let session = Arc::new(SessionMemory {
+            connection_id,
+            user_name,
+            usage: Usage::default(),
+            next_query_id: AtomicU64::new(0),
+            queries: Mutex::new(Vec::new()),
+        });
+
+        let mut sessions = self.sessions.lock();
+        sessions.retain(|s| s.strong_count() > 0);
+        sessions.push(Arc::downgrade(&session));
+
+        session
+    }
+
+    /// Get the memory usage for all sessions and their running queries.
+    ///
+    /// Each session is followed by the queries that it's currently running.
+    pub fn usage(&self) -> Vec<MemoryUsage> {
+        let sessions: Vec<_> = {
+            let mut sessions = self.sessions.lock();
+            sessions.retain(|s| s.strong_count() > 0);
+            sessions.iter().filter_map(Weak::upgrade).collect()
+        };
+
+        let mut usage = Vec::new();
+        for session in sessions {
+            usage.push(MemoryUsage {
+                connection_id: session.connection_id,
+                user_name: session.user_name.clone(),
+                query_id: None,
+                query_text: None,
+                current_bytes: session.usage.current(),
+                peak_bytes: session.usage.peak(),
+            });
+
+            let queries: Vec<_> = {
+                let mut queries = session.queries.lock();
+                queries.retain(|q| q.strong_count() > 0);
+                queries.iter().filter_map(Weak::upgrade).collect()
+            };
+            for query in queries {
+                usage.push(MemoryUsage {
+                    connection_id: session.connection_id,
+                    user_name: session.user_name.clone(),
+                    query_id: Some(query.query_id),
+                    query_text: query.query_text.clone(),
+                    current_bytes: query.usage.current(),
+                    peak_bytes: query.usage.peak(),
+                });
+            }
+        }
+
+        usage
+    }
+}
+
+/// A point-in-time view of the memory used by a session or query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub connection_id: Uuid,
+    pub user_name: String,
+    /// Id of the query within the session, `None` if this is the usage for the
+    /// entire session.
+    pub query_id: Option<u64>,
+    pub query_text: Option<String>,
+    pub current_bytes: usize,
+    pub peak_bytes: usize,
+}
+
+/// Memory used by a single session across all its queries.
+#[derive(Debug)]
+pub struct SessionMemory {
+    connection_id: Uuid,
+    user_name: String,
+    usage: Usage,
+    next_query_id: AtomicU64,
+    queries: Mutex<Vec<Weak<QueryMemory>>>,
+}
+
+impl SessionMemory {
+    /// Create a memory pool for executing a query.
+    ///
+    /// All reservations are forwarded to `inner`. The query is tracked until
+    /// the pool is dropped.
+    pub fn new_query_pool(
+        self: &Arc<Self>,
+        inner: Arc<dyn MemoryPool>,
+        query_text: &str,
+    ) -> Arc<dyn MemoryPool> {
+        let query = Arc::new(QueryMemory {
+            query_id: self.next_query_id.fetch_add(1, Ordering::Relaxed),
+            query_text: (!query_text.is_empty()).then(|| query_text.to_string()),
+            usage: Usage::default(),
+            session: self.clone(),
+        });
+
+        let mut queries = self.queries.lock();
+        queries.retain(|q| q.strong_count() > 0);
+        queries.push(Arc::downgrade(&query));
+
+        Arc::new(QueryMemoryPool { inner, query })
+    }
+}
+
+#[derive(Debug)]
+struct QueryMemory {
+    query_id: u64,
+    query_text: Option<String>,
+    usage: Usage,
+    session: Arc<SessionMemory>,
+}
+
+impl QueryMemory {
+    fn grow(&self, additional: usize) {
+        self.usage.grow(additional);
+        self.session.usage.grow(additional);
+    }
+
+    fn shrink(&self, shrink: usize) {
+        self.usage.shrink(shrink);
+        self.session.usage.shrink(shrink);
+    }
+}
+
+impl Drop for QueryMemory {
+    fn drop(&mut self) {
+        // Reservations should all be freed by now, but make sure the session
+        // doesn't keep accounting for memory from a query that's gone.
+        self.session.usage.shrink(self.usage.current());
+    }
+}
+
+/// Current and peak bytes reserved.
+#[derive(Debug, Default)]
+struct Usage {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl Usage {
+    fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    fn grow(&self, additional: usize) {
+        let current = self.current.fetch_add(additional, Ordering::Relaxed) + additional;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn shrink(&self, shrink: usize) {
+        let _ = self
+            .current
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+                Some(current.saturating_sub(shrink))
+            });
+    }
+}
+
+/// Memory pool attributing reservations to a query.
+#[derive(Debug)]
+struct QueryMemoryPool {
+    inner: Arc<dyn MemoryPool>,
+    query: Arc<QueryMemory>,
+}
+
+impl MemoryPool for QueryMemoryPool {
+    fn register(&self, consumer: &MemoryConsumer) {
+        self.inner.register(consumer)
+    }
+
+    fn unregister(&self, consumer: &MemoryConsumer) {
+        self.inner.unregister(consumer)
+    }
+
+    fn grow(&self, reservation: &MemoryReservation, additional: usize) {
+        self.inner.grow(reservation, additional);
+        self.query.grow(additional);
+    }
+
+    fn shrink(&self, reservation: &MemoryReservation, shrink: usize) {
+        self.inner.shrink(reservation, shrink);
+        self.query.shrink(shrink);
+    }
+
+    fn try_grow(&self, reservation: &MemoryReservation, additional: usize) -> DataFusionResult<()> {
+        self.inner.try_grow(reservation, additional)?;
+        self.query.grow(additional);
+        Ok(())
+    }
+
+    fn reserved(&self) -> usize {
+        self.inner.reserved()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::execution::memory_pool::{GreedyMemoryPool, UnboundedMemoryPool};
+
+    use super::*;
+
+    #[test]
+    fn query_and_session_usage() {
+        let tracker = MemoryTracker::default();
+        let session = tracker.register_session(Uuid::nil(), "glaredb".to_string());
+        let inner: Arc<dyn MemoryPool> = Arc::new(UnboundedMemoryPool::default());
+
+        let pool = session.new_query_pool(inner.clone(), "select 1");
+        let mut reservation = MemoryConsumer::new("test").register(&pool);
+        reservation.grow(100);
+        reservation.shrink(60);
+
+        let usage = tracker.usage();
+        assert_eq!(2, usage.len());
+        assert_eq!(
+            (None, 40, 100),
+            (
+                usage[0].query_id,
+                usage[0].current_bytes,
+                usage[0].peak_bytes
+            )
+        );
+        assert_eq!(Some(0), usage[1].query_id);
+        assert_eq!(Some("select 1".to_string()), usage[1].query_text);
+        assert_eq!((40, 100), (usage[1].current_bytes, usage[1].peak_bytes));
+
+        drop(reservation);
+        drop(pool);
+
+        // Query no longer shows up, session keeps its peak.
+        let usage = tracker.usage();
+        assert_eq!(1, usage.len());
+        assert_eq!((0, 100), (usage[0].current_bytes, usage[0].peak_bytes));
+
+        drop(session);
+        assert!(tracker.usage().is_empty());
+    }
+
+    #[test]
+    fn inner_pool_limits_apply() {
+        let tracker = MemoryTracker::default();
+        let session = tracker.register_session(Uuid::nil(), "glaredb".to_string());
+        let inner: Arc<dyn MemoryPool> = Arc::new(GreedyMemoryPool::new(50));
+
+        let pool = session.new_query_pool(inner.clone(), "");
+        let mut reservation = MemoryConsumer::new("test").register(&pool);
+        reservation.try_grow(40).unwrap();
+        reservation.try_grow(40).unwrap_err();
+
+        assert_eq!(40, inner.reserved());
+        let usage = tracker.usage();
+        assert_eq!(None, usage[1].query_text);
+        assert_eq!((40, 40), (usage[1].current_bytes, usage[1].peak_bytes));
+    }
+}
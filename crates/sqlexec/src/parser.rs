@@ -6,13 +6,15 @@ use datafusion::sql::sqlparser::dialect::GenericDialect;
 use datafusion::sql::sqlparser::keywords::Keyword;
 use datafusion::sql::sqlparser::parser::{Parser, ParserError, ParserOptions};
 use datafusion::sql::sqlparser::tokenizer::{Token, Tokenizer, Word};
+use datafusion_ext::asof::ASOF_JOIN_MARKER;
+use datafusion_ext::sample::TABLE_SAMPLE_HINT;
 use datafusion_ext::vars::Dialect;
 use prql_compiler::{compile, sql::Dialect as PrqlDialect, Options, Target};
 use std::collections::BTreeMap;
 use std::collections::VecDeque;
 use std::fmt;
 
-use self::options::{OptionValue, StmtOptions};
+use self::options::{OptionValue, StmtOptions, REDACTED_LITERAL};
 
 /// Wrapper around our custom parse for parsing a sql statement.
 pub fn parse_sql(sql: &str) -> Result<VecDeque<StatementWithExtensions>> {
@@ -128,8 +130,19 @@ impl fmt::Display for DropDatabaseStmt {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AlterDatabaseOperation {
-    RenameDatabase { new_name: Ident },
-    SetAccessMode { access_mode: Ident },
+    RenameDatabase {
+        new_name: Ident,
+    },
+    SetAccessMode {
+        access_mode: Ident,
+    },
+    SetVariable {
+        variable: Ident,
+        values: Vec<ast::Expr>,
+    },
+    ResetVariable {
+        variable: Ident,
+    },
 }
 
 impl fmt::Display for AlterDatabaseOperation {
@@ -141,6 +154,13 @@ impl fmt::Display for AlterDatabaseOperation {
             Self::SetAccessMode { access_mode } => {
                 write!(f, "SET ACCESS_MODE TO {access_mode}")
             }
+            Self::SetVariable { variable, values } => {
+                let values: Vec<_> = values.iter().map(ToString::to_string).collect();
+                write!(f, "SET {variable} = {}", values.join(", "))
+            }
+            Self::ResetVariable { variable } => {
+                write!(f, "RESET {variable}")
+            }
         }
     }
 }
@@ -157,9 +177,78 @@ impl fmt::Display for AlterDatabaseStmt {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlterRoleOperation {
+    SetVariable {
+        variable: Ident,
+        values: Vec<ast::Expr>,
+    },
+    ResetVariable {
+        variable: Ident,
+    },
+    /// Set the password for the role, removing it if `None`.
+    SetPassword {
+        password: Option<String>,
+    },
+    /// Grant (`READ ONLY`) or revoke (`READ WRITE`) read-only access.
+    SetReadOnly {
+        read_only: bool,
+    },
+}
+
+impl fmt::Display for AlterRoleOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SetVariable { variable, values } => {
+                let values: Vec<_> = values.iter().map(ToString::to_string).collect();
+                write!(f, "SET {variable} = {}", values.join(", "))
+            }
+            Self::ResetVariable { variable } => {
+                write!(f, "RESET {variable}")
+            }
+            Self::SetPassword { password } => match password {
+                // Statements are recorded as text in the audit log and query
+                // history, don't include the password.
+                Some(_) => write!(f, "PASSWORD {REDACTED_LITERAL}"),
+                None => write!(f, "PASSWORD NULL"),
+            },
+            Self::SetReadOnly { read_only: true } => write!(f, "READ ONLY"),
+            Self::SetReadOnly { read_only: false } => write!(f, "READ WRITE"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlterRoleStmt {
+    pub name: Ident,
+    /// Only apply to sessions connecting to this database.
+    pub database: Option<Ident>,
+    pub operation: AlterRoleOperation,
+}
+
+impl fmt::Display for AlterRoleStmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ALTER ROLE {}", self.name)?;
+        if let Some(database) = &self.database {
+            write!(f, " IN DATABASE {database}")?;
+        }
+        write!(f, " {}", self.operation)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AlterTableOperationExtension {
-    SetAccessMode { access_mode: Ident },
+    SetAccessMode {
+        access_mode: Ident,
+    },
+    /// Infer the schema of an external table again.
+    RefreshSchema,
+    SetTags {
+        tags: Vec<(String, String)>,
+    },
+    UnsetTags {
+        keys: Vec<String>,
+    },
 }
 
 impl fmt::Display for AlterTableOperationExtension {
@@ -168,10 +257,45 @@ impl fmt::Display for AlterTableOperationExtension {
             Self::SetAccessMode { access_mode } => {
                 write!(f, "SET ACCESS_MODE TO {access_mode}")
             }
+            Self::RefreshSchema => write!(f, "REFRESH SCHEMA"),
+            Self::SetTags { tags } => write!(f, "SET TAG {}", DisplayTags(tags)),
+            Self::UnsetTags { keys } => write!(f, "UNSET TAG {}", DisplayTagKeys(keys)),
         }
     }
 }
 
+/// Displays tags as `('key' = 'value', ...)`.
+struct DisplayTags<'a>(&'a [(String, String)]);
+
+impl fmt::Display for DisplayTags<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tags = self
+            .0
+            .iter()
+            .map(|(key, value)| {
+                let key = ast::Value::SingleQuotedString(key.clone());
+                let value = ast::Value::SingleQuotedString(value.clone());
+                format!("{key} = {value}")
+            })
+            .collect::<Vec<_>>();
+        write!(f, "({})", tags.join(", "))
+    }
+}
+
+/// Displays tag keys as `('key', ...)`.
+struct DisplayTagKeys<'a>(&'a [String]);
+
+impl fmt::Display for DisplayTagKeys<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys = self
+            .0
+            .iter()
+            .map(|key| ast::Value::SingleQuotedString(key.clone()).to_string())
+            .collect::<Vec<_>>();
+        write!(f, "({})", keys.join(", "))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AlterTableStmtExtension {
     pub name: ObjectName,
@@ -184,6 +308,99 @@ impl fmt::Display for AlterTableStmtExtension {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlterSchemaOperation {
+    RenameSchema { new_name: Ident },
+    SetTags { tags: Vec<(String, String)> },
+    UnsetTags { keys: Vec<String> },
+}
+
+impl fmt::Display for AlterSchemaOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RenameSchema { new_name } => write!(f, "RENAME TO {new_name}"),
+            Self::SetTags { tags } => write!(f, "SET TAG {}", DisplayTags(tags)),
+            Self::UnsetTags { keys } => write!(f, "UNSET TAG {}", DisplayTagKeys(keys)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlterSchemaStmt {
+    pub name: ObjectName,
+    pub operation: AlterSchemaOperation,
+}
+
+impl fmt::Display for AlterSchemaStmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ALTER SCHEMA {} {}", self.name, self.operation)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlterViewOperation {
+    RenameView { new_name: Ident },
+}
+
+impl fmt::Display for AlterViewOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RenameView { new_name } => write!(f, "RENAME TO {new_name}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlterViewStmt {
+    pub name: ObjectName,
+    pub operation: AlterViewOperation,
+}
+
+impl fmt::Display for AlterViewStmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ALTER VIEW {} {}", self.name, self.operation)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentObjectType {
+    Schema,
+    Table,
+    View,
+    Column,
+}
+
+impl fmt::Display for CommentObjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Schema => "SCHEMA",
+            Self::Table => "TABLE",
+            Self::View => "VIEW",
+            Self::Column => "COLUMN",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentOnStmt {
+    pub object_type: CommentObjectType,
+    /// Name of the object. For columns, the last part is the column name.
+    pub name: ObjectName,
+    /// New comment, `None` removes the comment.
+    pub comment: Option<String>,
+}
+
+impl fmt::Display for CommentOnStmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "COMMENT ON {} {} IS ", self.object_type, self.name)?;
+        match &self.comment {
+            Some(comment) => write!(f, "'{}'", comment.replace('\'', "''")),
+            None => write!(f, "NULL"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CreateTunnelStmt {
     /// Name of the tunnel as it exists in GlareDB.
@@ -395,6 +612,61 @@ impl fmt::Display for CopyToStmt {
     }
 }
 
+/// Options of `EXPORT CATALOG` and `IMPORT CATALOG` redacted when displaying
+/// the statements.
+const CATALOG_DUMP_REDACTED_OPTIONS: &[&str] = &["encryption_key"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportCatalogStmt {
+    /// Path of the file to write the catalog dump to.
+    pub dest: Ident,
+    /// EXPORT CATALOG specific options.
+    pub options: StmtOptions,
+}
+
+impl fmt::Display for ExportCatalogStmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EXPORT CATALOG TO {}", self.dest)?;
+        if !self.options.is_empty() {
+            let options = self.options.redacted(CATALOG_DUMP_REDACTED_OPTIONS);
+            write!(f, " {options}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportCatalogStmt {
+    /// Path of the file to read the catalog dump from.
+    pub source: Ident,
+    /// IMPORT CATALOG specific options.
+    pub options: StmtOptions,
+}
+
+impl fmt::Display for ImportCatalogStmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IMPORT CATALOG FROM {}", self.source)?;
+        if !self.options.is_empty() {
+            let options = self.options.redacted(CATALOG_DUMP_REDACTED_OPTIONS);
+            write!(f, " {options}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreCatalogStmt {
+    /// Point in time to restore the catalog to.
+    pub timestamp: String,
+}
+
+impl fmt::Display for RestoreCatalogStmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let timestamp = ast::Value::SingleQuotedString(self.timestamp.clone());
+        write!(f, "RESTORE CATALOG TO TIMESTAMP {timestamp}")
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StatementWithExtensions {
     /// Statement parsed by `sqlparser`.
@@ -407,8 +679,16 @@ pub enum StatementWithExtensions {
     DropDatabase(DropDatabaseStmt),
     // Alter database extension.
     AlterDatabase(AlterDatabaseStmt),
+    /// Alter role extension.
+    AlterRole(AlterRoleStmt),
     // Alter table extension.
     AlterTableExtension(AlterTableStmtExtension),
+    /// Alter schema extension.
+    AlterSchema(AlterSchemaStmt),
+    /// Alter view extension.
+    AlterView(AlterViewStmt),
+    /// Comment on extension.
+    CommentOn(CommentOnStmt),
     /// Create tunnel extension.
     CreateTunnel(CreateTunnelStmt),
     /// Drop tunnel extension.
@@ -423,6 +703,12 @@ pub enum StatementWithExtensions {
     DropCredentials(DropCredentialsStmt),
     /// Copy To extension.
     CopyTo(CopyToStmt),
+    /// Export catalog extension.
+    ExportCatalog(ExportCatalogStmt),
+    /// Import catalog extension.
+    ImportCatalog(ImportCatalogStmt),
+    /// Restore catalog extension.
+    RestoreCatalog(RestoreCatalogStmt),
 }
 
 impl fmt::Display for StatementWithExtensions {
@@ -433,7 +719,11 @@ impl fmt::Display for StatementWithExtensions {
             StatementWithExtensions::CreateExternalDatabase(stmt) => write!(f, "{}", stmt),
             StatementWithExtensions::DropDatabase(stmt) => write!(f, "{}", stmt),
             StatementWithExtensions::AlterDatabase(stmt) => write!(f, "{}", stmt),
+            StatementWithExtensions::AlterRole(stmt) => write!(f, "{}", stmt),
             StatementWithExtensions::AlterTableExtension(stmt) => write!(f, "{}", stmt),
+            StatementWithExtensions::AlterSchema(stmt) => write!(f, "{}", stmt),
+            StatementWithExtensions::AlterView(stmt) => write!(f, "{}", stmt),
+            StatementWithExtensions::CommentOn(stmt) => write!(f, "{}", stmt),
             StatementWithExtensions::CreateTunnel(stmt) => write!(f, "{}", stmt),
             StatementWithExtensions::DropTunnel(stmt) => write!(f, "{}", stmt),
             StatementWithExtensions::AlterTunnel(stmt) => write!(f, "{}", stmt),
@@ -441,6 +731,9 @@ impl fmt::Display for StatementWithExtensions {
             StatementWithExtensions::CreateCredentials(stmt) => write!(f, "{}", stmt),
             StatementWithExtensions::DropCredentials(stmt) => write!(f, "{}", stmt),
             StatementWithExtensions::CopyTo(stmt) => write!(f, "{}", stmt),
+            StatementWithExtensions::ExportCatalog(stmt) => write!(f, "{}", stmt),
+            StatementWithExtensions::ImportCatalog(stmt) => write!(f, "{}", stmt),
+            StatementWithExtensions::RestoreCatalog(stmt) => write!(f, "{}", stmt),
         }
     }
 }
@@ -460,6 +753,8 @@ impl CustomParser<'_> {
 
     pub fn new(mut sql: &str, dialect: Dialect) -> Result<CustomParser<'_>, ParserError> {
         let tokens = Tokenizer::new(Self::SQL_DIALECT, sql).tokenize()?;
+        let tokens = rewrite_table_samples(tokens)?;
+        let tokens = rewrite_asof_joins(tokens)?;
         let mut parser = Parser::new(Self::SQL_DIALECT)
             .with_options(ParserOptions {
                 trailing_commas: true,
@@ -541,6 +836,22 @@ impl<'a> CustomParser<'a> {
                     self.parser.next_token();
                     self.parse_copy()
                 }
+                Keyword::COMMENT => {
+                    self.parser.next_token();
+                    self.parse_comment()
+                }
+                _ if w.value.eq_ignore_ascii_case("EXPORT") => {
+                    self.parser.next_token();
+                    self.parse_export()
+                }
+                _ if w.value.eq_ignore_ascii_case("IMPORT") => {
+                    self.parser.next_token();
+                    self.parse_import()
+                }
+                _ if w.value.eq_ignore_ascii_case("RESTORE") => {
+                    self.parser.next_token();
+                    self.parse_restore()
+                }
                 _ => Ok(StatementWithExtensions::Statement(
                     self.parser.parse_statement()?,
                 )),
@@ -599,6 +910,19 @@ impl<'a> CustomParser<'a> {
             self.parse_alter_database()
         } else if self.parser.parse_keyword(Keyword::TABLE) {
             self.parse_alter_table()
+        } else if self.parser.parse_keyword(Keyword::SCHEMA) {
+            // ALTER SCHEMA ...
+            self.parse_alter_schema()
+        } else if self.parser.parse_keyword(Keyword::VIEW) {
+            // ALTER VIEW ...
+            self.parse_alter_view()
+        } else if self
+            .parser
+            .parse_one_of_keywords(&[Keyword::ROLE, Keyword::USER])
+            .is_some()
+        {
+            // ALTER { ROLE | USER } ...
+            self.parse_alter_role()
         } else if self.consume_token(&Token::make_keyword("TUNNEL")) {
             // ALTER TUNNEL ...
             self.parse_alter_tunnel()
@@ -647,6 +971,93 @@ impl<'a> CustomParser<'a> {
         }))
     }
 
+    /// Parse a SQL EXPORT statement.
+    fn parse_export(&mut self) -> Result<StatementWithExtensions, ParserError> {
+        // EXPORT CATALOG TO 'dest'
+        self.expect_token(&Token::make_keyword("CATALOG"))?;
+        self.parser.expect_keyword(Keyword::TO)?;
+        let dest = self.parser.parse_identifier()?;
+
+        // OPTIONS (..)
+        let options = self.parse_options()?;
+
+        Ok(StatementWithExtensions::ExportCatalog(ExportCatalogStmt {
+            dest,
+            options,
+        }))
+    }
+
+    /// Parse a SQL IMPORT statement.
+    fn parse_import(&mut self) -> Result<StatementWithExtensions, ParserError> {
+        // IMPORT CATALOG FROM 'source'
+        self.expect_token(&Token::make_keyword("CATALOG"))?;
+        self.parser.expect_keyword(Keyword::FROM)?;
+        let source = self.parser.parse_identifier()?;
+
+        // OPTIONS (..)
+        let options = self.parse_options()?;
+
+        Ok(StatementWithExtensions::ImportCatalog(ImportCatalogStmt {
+            source,
+            options,
+        }))
+    }
+
+    /// Parse a SQL RESTORE statement.
+    fn parse_restore(&mut self) -> Result<StatementWithExtensions, ParserError> {
+        // RESTORE CATALOG TO TIMESTAMP '2023-10-01 12:00:00'
+        self.expect_token(&Token::make_keyword("CATALOG"))?;
+        self.parser.expect_keyword(Keyword::TO)?;
+        self.parser.expect_keyword(Keyword::TIMESTAMP)?;
+        let timestamp = self.parser.parse_literal_string()?;
+
+        Ok(StatementWithExtensions::RestoreCatalog(
+            RestoreCatalogStmt { timestamp },
+        ))
+    }
+
+    fn parse_comment(&mut self) -> Result<StatementWithExtensions, ParserError> {
+        // COMMENT ON TABLE my_table IS 'comment'
+        self.parser.expect_keyword(Keyword::ON)?;
+        let object_type = match self.parser.parse_one_of_keywords(&[
+            Keyword::SCHEMA,
+            Keyword::TABLE,
+            Keyword::VIEW,
+            Keyword::COLUMN,
+        ]) {
+            Some(Keyword::SCHEMA) => CommentObjectType::Schema,
+            Some(Keyword::TABLE) => CommentObjectType::Table,
+            Some(Keyword::VIEW) => CommentObjectType::View,
+            Some(Keyword::COLUMN) => CommentObjectType::Column,
+            _ => {
+                return self.expected(
+                    "SCHEMA, TABLE, VIEW or COLUMN",
+                    self.parser.peek_token().token,
+                )
+            }
+        };
+
+        let name = self.parser.parse_object_name()?;
+        if object_type == CommentObjectType::Column && name.0.len() < 2 {
+            return Err(ParserError::ParserError(format!(
+                "Expected a column name qualified with its table, found: {name}"
+            )));
+        }
+
+        self.parser.expect_keyword(Keyword::IS)?;
+        let comment = if self.parser.parse_keyword(Keyword::NULL) {
+            None
+        } else {
+            Some(self.parser.parse_literal_string()?)
+        };
+
+        Ok(StatementWithExtensions::CommentOn(CommentOnStmt {
+            object_type,
+            name,
+            comment,
+        }))
+    }
+
     /// Report unexpected token.
     fn expected<T>(&self, expected: &str, found: Token) -> Result<T, ParserError> {
         Err(ParserError::ParserError(format!(
@@ -996,11 +1407,18 @@ impl<'a> CustomParser<'a> {
             validate_ident(&new_name)?;
             AlterDatabaseOperation::RenameDatabase { new_name }
         } else if self.parser.parse_keyword(Keyword::SET) {
-            self.expect_token(&Token::make_keyword("ACCESS_MODE"))?;
-            self.expect_token(&Token::make_keyword("TO"))?;
+            if self.consume_token(&Token::make_keyword("ACCESS_MODE")) {
+                self.expect_token(&Token::make_keyword("TO"))?;
 
-            let access_mode = self.parser.parse_identifier()?;
-            AlterDatabaseOperation::SetAccessMode { access_mode }
+                let access_mode = self.parser.parse_identifier()?;
+                AlterDatabaseOperation::SetAccessMode { access_mode }
+            } else {
+                let (variable, values) = self.parse_set_variable()?;
+                AlterDatabaseOperation::SetVariable { variable, values }
+            }
+        } else if self.consume_token(&Token::make_keyword("RESET")) {
+            let variable = self.parser.parse_identifier()?;
+            AlterDatabaseOperation::ResetVariable { variable }
         } else {
             return self.expected(
                 "an alter database operation",
@@ -1014,17 +1432,84 @@ impl<'a> CustomParser<'a> {
         }))
     }
 
+    fn parse_alter_role(&mut self) -> Result<StatementWithExtensions, ParserError> {
+        let name = self.parser.parse_identifier()?;
+        validate_ident(&name)?;
+
+        let database = if self
+            .parser
+            .parse_keywords(&[Keyword::IN, Keyword::DATABASE])
+        {
+            Some(self.parser.parse_identifier()?)
+        } else {
+            None
+        };
+
+        let operation = if self.parser.parse_keyword(Keyword::SET) {
+            let (variable, values) = self.parse_set_variable()?;
+            AlterRoleOperation::SetVariable { variable, values }
+        } else if self.consume_token(&Token::make_keyword("RESET")) {
+            let variable = self.parser.parse_identifier()?;
+            AlterRoleOperation::ResetVariable { variable }
+        } else if self.parser.parse_keyword(Keyword::PASSWORD)
+            || self
+                .parser
+                .parse_keywords(&[Keyword::WITH, Keyword::PASSWORD])
+        {
+            let password = if self.parser.parse_keyword(Keyword::NULL) {
+                None
+            } else {
+                Some(self.parser.parse_literal_string()?)
+            };
+            AlterRoleOperation::SetPassword { password }
+        } else if self.parser.parse_keywords(&[Keyword::READ, Keyword::ONLY]) {
+            AlterRoleOperation::SetReadOnly { read_only: true }
+        } else if self.parser.parse_keywords(&[Keyword::READ, Keyword::WRITE]) {
+            AlterRoleOperation::SetReadOnly { read_only: false }
+        } else {
+            return self.expected("an alter role operation", self.parser.peek_token().token);
+        };
+
+        Ok(StatementWithExtensions::AlterRole(AlterRoleStmt {
+            name,
+            database,
+            operation,
+        }))
+    }
+
+    /// Parse `<variable> { = | TO } <value> [, ...]`, following a `SET`.
+    fn parse_set_variable(&mut self) -> Result<(Ident, Vec<ast::Expr>), ParserError> {
+        let variable = self.parser.parse_identifier()?;
+        if !self.parser.consume_token(&Token::Eq) && !self.parser.parse_keyword(Keyword::TO) {
+            return self.expected("= or TO", self.parser.peek_token().token);
+        }
+        let values = self.parser.parse_comma_separated(Parser::parse_expr)?;
+        Ok((variable, values))
+    }
+
     fn parse_alter_table(&mut self) -> Result<StatementWithExtensions, ParserError> {
         let if_exists = self.parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
         let only = self.parser.parse_keyword(Keyword::ONLY);
         let name = self.parser.parse_object_name()?;
 
         let operation = if self.parser.parse_keyword(Keyword::SET) {
-            self.expect_token(&Token::make_keyword("ACCESS_MODE"))?;
-            self.expect_token(&Token::make_keyword("TO"))?;
+            if self.consume_token(&Token::make_keyword("TAG")) {
+                let tags = self.parse_tags()?;
+                AlterTableOperationExtension::SetTags { tags }
+            } else {
+                self.expect_token(&Token::make_keyword("ACCESS_MODE"))?;
+                self.expect_token(&Token::make_keyword("TO"))?;
 
-            let access_mode = self.parser.parse_identifier()?;
-            AlterTableOperationExtension::SetAccessMode { access_mode }
+                let access_mode = self.parser.parse_identifier()?;
+                AlterTableOperationExtension::SetAccessMode { access_mode }
+            }
+        } else if self.consume_token(&Token::make_keyword("UNSET")) {
+            self.expect_token(&Token::make_keyword("TAG"))?;
+            let keys = self.parse_tag_keys()?;
+            AlterTableOperationExtension::UnsetTags { keys }
+        } else if self.consume_token(&Token::make_keyword("REFRESH")) {
+            self.parser.expect_keyword(Keyword::SCHEMA)?;
+            AlterTableOperationExtension::RefreshSchema
         } else {
             let operations = self
                 .parser
@@ -1044,6 +1529,71 @@ impl<'a> CustomParser<'a> {
         ))
     }
 
+    fn parse_alter_schema(&mut self) -> Result<StatementWithExtensions, ParserError> {
+        let name = self.parser.parse_object_name()?;
+
+        let operation = if self.parser.parse_keywords(&[Keyword::RENAME, Keyword::TO]) {
+            let new_name = self.parser.parse_identifier()?;
+            validate_ident(&new_name)?;
+            AlterSchemaOperation::RenameSchema { new_name }
+        } else if self.parser.parse_keyword(Keyword::SET) {
+            self.expect_token(&Token::make_keyword("TAG"))?;
+            let tags = self.parse_tags()?;
+            AlterSchemaOperation::SetTags { tags }
+        } else if self.consume_token(&Token::make_keyword("UNSET")) {
+            self.expect_token(&Token::make_keyword("TAG"))?;
+            let keys = self.parse_tag_keys()?;
+            AlterSchemaOperation::UnsetTags { keys }
+        } else {
+            return self.expected("an alter schema operation", self.parser.peek_token().token);
+        };
+
+        Ok(StatementWithExtensions::AlterSchema(AlterSchemaStmt {
+            name,
+            operation,
+        }))
+    }
+
+    /// Parse `('key' = 'value', ...)`.
+    fn parse_tags(&mut self) -> Result<Vec<(String, String)>, ParserError> {
+        self.parser.expect_token(&Token::LParen)?;
+        let tags = self.parser.parse_comma_separated(|parser| {
+            let key = parser.parse_literal_string()?;
+            parser.expect_token(&Token::Eq)?;
+            let value = parser.parse_literal_string()?;
+            Ok((key, value))
+        })?;
+        self.parser.expect_token(&Token::RParen)?;
+        Ok(tags)
+    }
+
+    /// Parse `('key', ...)`.
+    fn parse_tag_keys(&mut self) -> Result<Vec<String>, ParserError> {
+        self.parser.expect_token(&Token::LParen)?;
+        let keys = self
+            .parser
+            .parse_comma_separated(Parser::parse_literal_string)?;
+        self.parser.expect_token(&Token::RParen)?;
+        Ok(keys)
+    }
+
+    fn parse_alter_view(&mut self) -> Result<StatementWithExtensions, ParserError> {
+        let name = self.parser.parse_object_name()?;
+
+        let operation = if self.parser.parse_keywords(&[Keyword::RENAME, Keyword::TO]) {
+            let new_name = self.parser.parse_identifier()?;
+            validate_ident(&new_name)?;
+            AlterViewOperation::RenameView { new_name }
+        } else {
+            return self.expected("an alter view operation", self.parser.peek_token().token);
+        };
+
+        Ok(StatementWithExtensions::AlterView(AlterViewStmt {
+            name,
+            operation,
+        }))
+    }
+
     fn parse_alter_tunnel(&mut self) -> Result<StatementWithExtensions, ParserError> {
         let if_exists = self.parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
 
@@ -1084,6 +1634,311 @@ pub fn validate_object_name(name: &ast::ObjectName) -> Result<(), ParserError> {
     Ok(())
 }
 
+/// Check if the token is the given unquoted word, ignoring case.
+fn is_word(token: Option<&Token>, word: &str) -> bool {
+    matches!(token, Some(Token::Word(w)) if w.quote_style.is_none() && w.value.eq_ignore_ascii_case(word))
+}
+
+/// Get the index of the first non-whitespace token at or after `idx`.
+fn skip_whitespace(tokens: &[Token], mut idx: usize) -> usize {
+    while matches!(tokens.get(idx), Some(Token::Whitespace(_))) {
+        idx += 1;
+    }
+    idx
+}
+
+/// Rewrite `TABLESAMPLE` clauses into table hints.
+///
+/// The sql parser doesn't know about `TABLESAMPLE`, so a clause like
+///
+/// `TABLESAMPLE <method> (<size> [PERCENT | ROWS]) [REPEATABLE (<seed>)]`
+///
+/// is rewritten to
+///
+/// `WITH (tablesample(method => '<method>', size => <size>[, unit => '<unit>'][, seed => <seed>]))`
+///
+/// which the parser accepts as a hint for the preceding table. The planner
+/// then turns the hint into a sample of the table.
+fn rewrite_table_samples(tokens: Vec<Token>) -> Result<Vec<Token>, ParserError> {
+    /// Get the tokens between the parentheses starting at `idx`, along with
+    /// the index following the closing parenthesis.
+    fn parenthesized(tokens: &[Token], idx: usize) -> Option<(Vec<Token>, usize)> {
+        if tokens.get(idx) != Some(&Token::LParen) {
+            return None;
+        }
+        let mut depth = 0;
+        for (end, token) in tokens.iter().enumerate().skip(idx) {
+            match token {
+                Token::LParen => depth += 1,
+                Token::RParen => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some((tokens[idx + 1..end].to_vec(), end + 1));
+                    }
+                }
+                _ => (),
+            }
+        }
+        None
+    }
+
+    fn named_arg(name: &str) -> [Token; 2] {
+        [Token::make_word(name, None), Token::RArrow]
+    }
+
+    if !tokens
+        .iter()
+        .any(|token| is_word(Some(token), "TABLESAMPLE"))
+    {
+        return Ok(tokens);
+    }
+
+    let mut rewritten = Vec::with_capacity(tokens.len());
+    let mut idx = 0;
+    while idx < tokens.len() {
+        if !is_word(tokens.get(idx), "TABLESAMPLE") {
+            rewritten.push(tokens[idx].clone());
+            idx += 1;
+            continue;
+        }
+
+        // Leave anything that doesn't look like a sampling clause alone, e.g.
+        // a column named "tablesample".
+        let method_idx = skip_whitespace(&tokens, idx + 1);
+        let method = match tokens.get(method_idx) {
+            Some(Token::Word(w)) if w.quote_style.is_none() => w.value.to_lowercase(),
+            _ => {
+                rewritten.push(tokens[idx].clone());
+                idx += 1;
+                continue;
+            }
+        };
+        let (mut size, after_size) =
+            match parenthesized(&tokens, skip_whitespace(&tokens, method_idx + 1)) {
+                Some(size) => size,
+                None => {
+                    rewritten.push(tokens[idx].clone());
+                    idx += 1;
+                    continue;
+                }
+            };
+
+        while matches!(size.last(), Some(Token::Whitespace(_))) {
+            size.pop();
+        }
+        let unit = if is_word(size.last(), "PERCENT") || size.last() == Some(&Token::Mod) {
+            size.pop();
+            Some("percent")
+        } else if is_word(size.last(), "ROWS") {
+            size.pop();
+            Some("rows")
+        } else {
+            None
+        };
+        if size
+            .iter()
+            .all(|token| matches!(token, Token::Whitespace(_)))
+        {
+            return Err(ParserError::ParserError(format!(
+                "Expected a sample size for TABLESAMPLE {}",
+                method.to_uppercase()
+            )));
+        }
+
+        idx = after_size;
+        let mut seed = None;
+        let repeatable_idx = skip_whitespace(&tokens, idx);
+        if is_word(tokens.get(repeatable_idx), "REPEATABLE") {
+            match parenthesized(&tokens, skip_whitespace(&tokens, repeatable_idx + 1)) {
+                Some((seed_tokens, after_seed)) => {
+                    seed = Some(seed_tokens);
+                    idx = after_seed;
+                }
+                None => {
+                    return Err(ParserError::ParserError(
+                        "Expected a seed for REPEATABLE".to_string(),
+                    ))
+                }
+            }
+        }
+
+        rewritten.extend([
+            Token::make_keyword("WITH"),
+            Token::LParen,
+            Token::make_word(TABLE_SAMPLE_HINT, None),
+            Token::LParen,
+        ]);
+        rewritten.extend(named_arg("method"));
+        rewritten.extend([Token::SingleQuotedString(method), Token::Comma]);
+        rewritten.extend(named_arg("size"));
+        rewritten.extend(size);
+        if let Some(unit) = unit {
+            rewritten.push(Token::Comma);
+            rewritten.extend(named_arg("unit"));
+            rewritten.push(Token::SingleQuotedString(unit.to_string()));
+        }
+        if let Some(seed) = seed {
+            rewritten.push(Token::Comma);
+            rewritten.extend(named_arg("seed"));
+            rewritten.extend(seed);
+        }
+        rewritten.extend([Token::RParen, Token::RParen]);
+    }
+
+    Ok(rewritten)
+}
+
+/// Rewrite `ASOF JOIN`s into joins with a marker in their condition.
+///
+/// The sql parser doesn't know about `ASOF JOIN`, so a join like
+///
+/// `ASOF [LEFT [OUTER]] JOIN <relation> [TOLERANCE <tolerance>] ON <condition>`
+///
+/// is rewritten to
+///
+/// `[LEFT] JOIN <relation> ON __glaredb_asof_join([<tolerance>]) AND (<condition>)`
+///
+/// which the planner then plans as an ASOF join.
+fn rewrite_asof_joins(tokens: Vec<Token>) -> Result<Vec<Token>, ParserError> {
+    /// Find the first token at or after `start` matching `pred`, skipping
+    /// anything in parentheses. An unmatched closing parenthesis also counts
+    /// as a match.
+    fn find_unnested(
+        tokens: &[Token],
+        start: usize,
+        pred: impl Fn(usize) -> bool,
+    ) -> Option<usize> {
+        let mut depth = 0;
+        for (idx, token) in tokens.iter().enumerate().skip(start) {
+            match token {
+                Token::LParen => depth += 1,
+                Token::RParen if depth == 0 => return Some(idx),
+                Token::RParen => depth -= 1,
+                _ if depth == 0 && pred(idx) => return Some(idx),
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Check if the token ends a join condition.
+    fn ends_condition(tokens: &[Token], idx: usize) -> bool {
+        const KEYWORDS: &[&str] = &[
+            "JOIN",
+            "INNER",
+            "LEFT",
+            "RIGHT",
+            "FULL",
+            "CROSS",
+            "NATURAL",
+            "ASOF",
+            "WHERE",
+            "GROUP",
+            "HAVING",
+            "WINDOW",
+            "QUALIFY",
+            "ORDER",
+            "LIMIT",
+            "OFFSET",
+            "FETCH",
+            "UNION",
+            "EXCEPT",
+            "INTERSECT",
+        ];
+        match &tokens[idx] {
+            Token::Comma | Token::SemiColon => true,
+            // Words followed by parentheses are function calls, e.g. `left(s, 1)`.
+            token => {
+                KEYWORDS.iter().any(|kw| is_word(Some(token), kw))
+                    && tokens.get(skip_whitespace(tokens, idx + 1)) != Some(&Token::LParen)
+            }
+        }
+    }
+
+    if !tokens.iter().any(|token| is_word(Some(token), "ASOF")) {
+        return Ok(tokens);
+    }
+
+    let mut rewritten = Vec::with_capacity(tokens.len() + 8);
+    let mut idx = 0;
+    while idx < tokens.len() {
+        if !is_word(tokens.get(idx), "ASOF") {
+            rewritten.push(tokens[idx].clone());
+            idx += 1;
+            continue;
+        }
+
+        // Leave anything that isn't followed by a join alone, e.g. a column
+        // named "asof".
+        let mut join_idx = skip_whitespace(&tokens, idx + 1);
+        let left = is_word(tokens.get(join_idx), "LEFT");
+        if left {
+            join_idx = skip_whitespace(&tokens, join_idx + 1);
+            if is_word(tokens.get(join_idx), "OUTER") {
+                join_idx = skip_whitespace(&tokens, join_idx + 1);
+            }
+        }
+        if !is_word(tokens.get(join_idx), "JOIN") {
+            rewritten.push(tokens[idx].clone());
+            idx += 1;
+            continue;
+        }
+
+        let is_on_or_tolerance =
+            |idx: usize| is_word(tokens.get(idx), "ON") || is_word(tokens.get(idx), "TOLERANCE");
+        let missing_condition =
+            || ParserError::ParserError("Expected an ON condition for ASOF JOIN".to_string());
+
+        let relation_end = find_unnested(&tokens, join_idx + 1, is_on_or_tolerance)
+            .filter(|&idx| is_on_or_tolerance(idx))
+            .ok_or_else(missing_condition)?;
+        let (tolerance, on_idx) = if is_word(tokens.get(relation_end), "TOLERANCE") {
+            let on_idx = find_unnested(&tokens, relation_end + 1, |idx| {
+                is_word(tokens.get(idx), "ON")
+            })
+            .filter(|&idx| is_word(tokens.get(idx), "ON"))
+            .ok_or_else(missing_condition)?;
+            let tolerance = &tokens[relation_end + 1..on_idx];
+            if tolerance
+                .iter()
+                .all(|token| matches!(token, Token::Whitespace(_)))
+            {
+                return Err(ParserError::ParserError(
+                    "Expected a tolerance for ASOF JOIN".to_string(),
+                ));
+            }
+            (tolerance.to_vec(), on_idx)
+        } else {
+            (Vec::new(), relation_end)
+        };
+        let condition_end = find_unnested(&tokens, on_idx + 1, |idx| ends_condition(&tokens, idx))
+            .unwrap_or(tokens.len());
+
+        if left {
+            rewritten.push(Token::make_keyword("LEFT"));
+        }
+        rewritten.push(Token::make_keyword("JOIN"));
+        rewritten.extend(rewrite_asof_joins(
+            tokens[join_idx + 1..relation_end].to_vec(),
+        )?);
+        rewritten.extend([
+            Token::make_keyword("ON"),
+            Token::make_word(ASOF_JOIN_MARKER, None),
+            Token::LParen,
+        ]);
+        rewritten.extend(tolerance);
+        rewritten.extend([Token::RParen, Token::make_keyword("AND"), Token::LParen]);
+        rewritten.extend(rewrite_asof_joins(
+            tokens[on_idx + 1..condition_end].to_vec(),
+        )?);
+        rewritten.push(Token::RParen);
+
+        idx = condition_end;
+    }
+
+    Ok(rewritten)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1309,6 +2164,9 @@ mod tests {
         let test_cases = [
             "ALTER DATABASE my_db RENAME TO your_db",
             "ALTER DATABASE my_db SET ACCESS_MODE TO readwrite",
+            "ALTER DATABASE my_db SET search_path = public, reports",
+            "ALTER DATABASE my_db SET timezone = 'UTC'",
+            "ALTER DATABASE my_db RESET search_path",
         ];
 
         for test_case in test_cases {
@@ -1320,9 +2178,115 @@ mod tests {
         }
     }
 
+    #[test]
+    fn alter_schema_and_view_roundtrips() {
+        let test_cases = [
+            "ALTER SCHEMA my_schema RENAME TO your_schema",
+            "ALTER SCHEMA my_db.my_schema RENAME TO your_schema",
+            "ALTER VIEW my_view RENAME TO your_view",
+            "ALTER VIEW my_schema.my_view RENAME TO your_view",
+            "ALTER SCHEMA my_schema SET TAG ('team' = 'growth')",
+            "ALTER SCHEMA my_schema UNSET TAG ('team', 'owner')",
+        ];
+
+        for test_case in test_cases {
+            let stmt = CustomParser::parse_sql(test_case)
+                .unwrap()
+                .pop_front()
+                .unwrap();
+            assert_eq!(test_case, stmt.to_string().as_str());
+        }
+    }
+
+    #[test]
+    fn comment_on_roundtrips() {
+        let test_cases = [
+            "COMMENT ON SCHEMA my_schema IS 'reports'",
+            "COMMENT ON TABLE my_schema.my_table IS 'it''s a table'",
+            "COMMENT ON VIEW my_view IS NULL",
+            "COMMENT ON COLUMN my_table.my_column IS 'id'",
+            "COMMENT ON COLUMN my_schema.my_table.my_column IS NULL",
+        ];
+
+        for test_case in test_cases {
+            let stmt = CustomParser::parse_sql(test_case)
+                .unwrap()
+                .pop_front()
+                .unwrap();
+            assert_eq!(test_case, stmt.to_string().as_str());
+        }
+
+        CustomParser::parse_sql("COMMENT ON COLUMN my_column IS 'id'").unwrap_err();
+    }
+
+    #[test]
+    fn alter_role_roundtrips() {
+        let test_cases = [
+            "ALTER ROLE sean SET statement_timeout = 1000",
+            "ALTER ROLE sean IN DATABASE my_db SET search_path = reports",
+            "ALTER ROLE sean RESET statement_timeout",
+            "ALTER ROLE sean PASSWORD NULL",
+            "ALTER ROLE sean READ ONLY",
+            "ALTER ROLE sean READ WRITE",
+        ];
+
+        for test_case in test_cases {
+            let stmt = CustomParser::parse_sql(test_case)
+                .unwrap()
+                .pop_front()
+                .unwrap();
+            assert_eq!(test_case, stmt.to_string().as_str());
+        }
+    }
+
+    #[test]
+    fn sensitive_values_redacted() {
+        let test_cases = [
+            (
+                "ALTER ROLE sean PASSWORD 'hunter2'",
+                "ALTER ROLE sean PASSWORD '********'",
+            ),
+            (
+                "ALTER ROLE sean WITH PASSWORD 'it''s'",
+                "ALTER ROLE sean PASSWORD '********'",
+            ),
+            (
+                "EXPORT CATALOG TO './catalog.dump' OPTIONS (encryption_key = 'hunter2')",
+                "EXPORT CATALOG TO './catalog.dump' OPTIONS (encryption_key = '********')",
+            ),
+            (
+                "IMPORT CATALOG FROM './catalog.dump' OPTIONS (encryption_key = 'hunter2')",
+                "IMPORT CATALOG FROM './catalog.dump' OPTIONS (encryption_key = '********')",
+            ),
+        ];
+
+        for (input, expected) in test_cases {
+            let stmt = CustomParser::parse_sql(input).unwrap().pop_front().unwrap();
+            assert_eq!(expected, stmt.to_string().as_str());
+        }
+
+        // The password is still parsed.
+        let stmt = CustomParser::parse_sql("ALTER ROLE sean PASSWORD 'hunter2'")
+            .unwrap()
+            .pop_front()
+            .unwrap();
+        match stmt {
+            StatementWithExtensions::AlterRole(AlterRoleStmt {
+                operation: AlterRoleOperation::SetPassword { password },
+                ..
+            }) => assert_eq!(Some("hunter2".to_string()), password),
+            other => panic!("unexpected statement: {other:?}"),
+        }
+    }
+
     #[test]
     fn alter_table_extension_roundtrips() {
-        let test_cases = ["ALTER TABLE my_db SET ACCESS_MODE TO readonly"];
+        let test_cases = [
+            "ALTER TABLE my_db SET ACCESS_MODE TO readonly",
+            "ALTER TABLE my_table REFRESH SCHEMA",
+            "ALTER TABLE my_table SET TAG ('team' = 'growth', 'cost_center' = 'it''s 42')",
+            "ALTER TABLE my_table UNSET TAG ('team')",
+        ];
 
         for test_case in test_cases {
             let stmt = CustomParser::parse_sql(test_case)
@@ -1333,6 +2297,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn table_sample_rewrites() {
+        let test_cases = [
+            (
+                "SELECT * FROM t TABLESAMPLE SYSTEM (1)",
+                "SELECT * FROM t WITH (tablesample(method => 'system', size => 1))",
+            ),
+            (
+                "SELECT * FROM t AS s TABLESAMPLE bernoulli (10 PERCENT) REPEATABLE (42)",
+                "SELECT * FROM t AS s WITH (tablesample(method => 'bernoulli', size => 10, unit => 'percent', seed => 42))",
+            ),
+            (
+                "SELECT * FROM read_parquet('f.parquet') TABLESAMPLE RESERVOIR(100 ROWS)",
+                "SELECT * FROM read_parquet('f.parquet') WITH (tablesample(method => 'reservoir', size => 100, unit => 'rows'))",
+            ),
+            (
+                "SELECT tablesample FROM t",
+                "SELECT tablesample FROM t",
+            ),
+        ];
+
+        for (sql, expected) in test_cases {
+            let stmt = CustomParser::parse_sql(sql).unwrap().pop_front().unwrap();
+            assert_eq!(expected, stmt.to_string().as_str());
+        }
+    }
+
+    #[test]
+    fn asof_join_rewrites() {
+        let test_cases = [
+            (
+                "SELECT * FROM t ASOF JOIN q ON t.ts >= q.ts",
+                "SELECT * FROM t JOIN q ON __glaredb_asof_join() AND (t.ts >= q.ts)",
+            ),
+            (
+                "SELECT * FROM t ASOF LEFT JOIN q TOLERANCE INTERVAL '1 minute' ON t.sym = q.sym AND t.ts >= q.ts WHERE t.sym = 'a'",
+                "SELECT * FROM t LEFT JOIN q ON __glaredb_asof_join(INTERVAL '1 minute') AND (t.sym = q.sym AND t.ts >= q.ts) WHERE t.sym = 'a'",
+            ),
+            (
+                "SELECT * FROM t ASOF JOIN (SELECT * FROM q) AS q ON left(t.sym, 1) = q.sym AND t.ts > q.ts JOIN r ON t.id = r.id",
+                "SELECT * FROM t JOIN (SELECT * FROM q) AS q ON __glaredb_asof_join() AND (left(t.sym, 1) = q.sym AND t.ts > q.ts) JOIN r ON t.id = r.id",
+            ),
+            (
+                "SELECT * FROM (SELECT * FROM t ASOF JOIN q ON t.ts >= q.ts) AS j",
+                "SELECT * FROM (SELECT * FROM t JOIN q ON __glaredb_asof_join() AND (t.ts >= q.ts)) AS j",
+            ),
+            ("SELECT asof FROM t", "SELECT asof FROM t"),
+        ];
+
+        for (sql, expected) in test_cases {
+            let stmt = CustomParser::parse_sql(sql).unwrap().pop_front().unwrap();
+            assert_eq!(expected, stmt.to_string().as_str());
+        }
+
+        CustomParser::parse_sql("SELECT * FROM t ASOF JOIN q USING (ts)").unwrap_err();
+    }
+
     #[test]
     fn copy_to_roundtrips() {
         let test_cases = [
@@ -1356,6 +2377,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn export_import_restore_catalog_roundtrips() {
+        let test_cases = [
+            "EXPORT CATALOG TO './catalog.dump'",
+            "EXPORT CATALOG TO './catalog.dump' OPTIONS (encryption_key = SECRET dump_key, include_credentials = TRUE)",
+            "IMPORT CATALOG FROM './catalog.dump'",
+            "RESTORE CATALOG TO TIMESTAMP '2023-10-01 12:00:00'",
+            "RESTORE CATALOG TO TIMESTAMP '2023-10-01T12:00:00Z'",
+        ];
+
+        for test_case in test_cases {
+            let stmt = CustomParser::parse_sql(test_case)
+                .unwrap()
+                .pop_front()
+                .unwrap();
+            assert_eq!(test_case, stmt.to_string().as_str());
+        }
+    }
+
     #[test]
     fn options_parse() {
         let mut options = BTreeMap::new();
@@ -62,8 +62,8 @@ impl ParseOptionValue<bool> for OptionValue {
     fn parse_opt(self) -> Result<bool, ParserError> {
         let opt = match self {
             Self::QuotedLiteral(s) | Self::UnquotedLiteral(s) => match s.as_str() {
-                "t" | "true" | "T" | "TRUE" => true,
-                "f" | "false" | "F" | "FALSE" => false,
+                "t" | "true" | "T" | "TRUE" | "on" | "ON" => true,
+                "f" | "false" | "F" | "FALSE" | "off" | "OFF" => false,
                 o => return Err(unexpected_type_err!("boolean", o)),
             },
             Self::Number(n) => {
@@ -115,6 +115,18 @@ impl ParseOptionValue<usize> for OptionValue {
     }
 }
 
+impl ParseOptionValue<i32> for OptionValue {
+    fn parse_opt(self) -> Result<i32, ParserError> {
+        let opt = match self {
+            Self::QuotedLiteral(s) | Self::UnquotedLiteral(s) | Self::Number(s) => {
+                s.parse().map_err(|e| parser_err!("{e}"))?
+            }
+            o => return Err(unexpected_type_err!("int", o)),
+        };
+        Ok(opt)
+    }
+}
+
 impl ParseOptionValue<char> for OptionValue {
     fn parse_opt(self) -> Result<char, ParserError> {
         let opt = match self {
@@ -190,12 +202,36 @@ pub struct StmtOptions {
     m: BTreeMap<String, OptionValue>,
 }
 
+/// Literal displayed in place of sensitive values, e.g. passwords, so that
+/// they don't end up in the text of recorded statements.
+pub const REDACTED_LITERAL: &str = "'********'";
+
 impl fmt::Display for StmtOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.redacted(&[]).fmt(f)
+    }
+}
+
+/// Displays statement options with the literal values of some keys redacted.
+///
+/// Secrets are still displayed since only the name of the secret is part of
+/// the statement.
+pub struct RedactedStmtOptions<'a> {
+    options: &'a StmtOptions,
+    keys: &'a [&'a str],
+}
+
+impl fmt::Display for RedactedStmtOptions<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "OPTIONS (")?;
         let mut sep = "";
-        for (k, v) in self.m.iter() {
-            write!(f, "{sep}{k} = {v}")?;
+        for (k, v) in self.options.m.iter() {
+            let redact = !matches!(v, OptionValue::Secret(_)) && self.keys.contains(&k.as_str());
+            if redact {
+                write!(f, "{sep}{k} = {REDACTED_LITERAL}")?;
+            } else {
+                write!(f, "{sep}{k} = {v}")?;
+            }
             sep = ", ";
         }
         write!(f, ")")
@@ -227,6 +263,14 @@ impl StmtOptions {
         self.m.is_empty()
     }
 
+    /// Display the options with the literal values of `keys` redacted.
+    pub fn redacted<'a>(&'a self, keys: &'a [&'a str]) -> RedactedStmtOptions<'a> {
+        RedactedStmtOptions {
+            options: self,
+            keys,
+        }
+    }
+
     pub fn remove_optional<T>(&mut self, k: &str) -> Result<Option<T>, ParserError>
     where
         OptionValue: ParseOptionValue<T>,
@@ -0,0 +1,81 @@
+//! Writing to builtin tables that are persisted as native tables.
+
+use std::sync::Arc;
+
+use catalog::session_catalog::SessionCatalog;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::execution::TaskContext;
+use datafusion::logical_expr::Expr;
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+use datasources::native::access::{NativeTableStorage, SaveMode};
+use futures::StreamExt;
+use protogen::metastore::types::catalog::{CatalogEntry, TableEntry};
+use sqlbuiltins::builtins::BuiltinTable;
+
+use crate::errors::Result;
+
+/// A persisted builtin table for a single database.
+#[derive(Debug)]
+pub(crate) struct PersistedTable {
+    builtin: &'static BuiltinTable,
+    storage: NativeTableStorage,
+    entry: TableEntry,
+}
+
+impl PersistedTable {
+    /// Get the persisted table for the database the catalog is for.
+    ///
+    /// Returns `None` if the builtin table isn't in the catalog.
+    pub(crate) fn from_catalog(
+        builtin: &'static BuiltinTable,
+        catalog: &SessionCatalog,
+        storage: &NativeTableStorage,
+    ) -> Option<PersistedTable> {
+        match catalog.get_by_oid(builtin.oid) {
+            Some(CatalogEntry::Table(ent)) => Some(PersistedTable {
+                builtin,
+                storage: storage.clone(),
+                entry: ent.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Append a batch to the table, creating the table if it doesn't exist.
+    ///
+    /// The batch must have the builtin table's schema.
+    pub(crate) async fn append(&self, batch: RecordBatch) -> Result<()> {
+        let input = Arc::new(MemoryExec::try_new(
+            &[vec![batch]],
+            Arc::new(self.builtin.arrow_schema()),
+            None,
+        )?);
+
+        let native = self
+            .storage
+            .create_table(&self.entry, SaveMode::Ignore)
+            .await?;
+        let exec = native.insert_exec(input, false);
+        let mut stream = exec.execute(0, Arc::new(TaskContext::default()))?;
+        while let Some(result) = stream.next().await {
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete rows matching `predicate`, returning the number of rows
+    /// deleted.
+    ///
+    /// Does nothing if the table hasn't been created yet.
+    pub(crate) async fn delete_where(&self, predicate: Expr) -> Result<usize> {
+        if !self.storage.table_exists(&self.entry).await? {
+            return Ok(0);
+        }
+        Ok(self
+            .storage
+            .delete_rows_where(&self.entry, Some(predicate))
+            .await?)
+    }
+}
@@ -0,0 +1,433 @@
+//! Cache for planned statements.
+//!
+//! Clients repeatedly preparing the same statement (e.g. parameterized queries
+//! over the extended query protocol) can reuse a previously planned statement,
+//! skipping both parsing and planning.
+//!
+//! Table providers are resolved during planning, and most of them capture
+//! state at that point (builtin tables are materialized, external tables may
+//! fetch schemas, etc). Only queries exclusively reading from native tables are
+//! cached. Native tables are loaded at a specific version, so cached plans are
+//! only reused if none of their tables changed since the plan was created.
+//! Cached plans are invalidated whenever the catalog changes.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use datafusion::common::tree_node::{TreeNode, VisitRecursion};
+use datafusion::datasource::{source_as_provider, TableProvider};
+use datafusion::logical_expr::{Exists, Expr, InSubquery, LogicalPlan as DfLogicalPlan};
+use datafusion::sql::sqlparser::ast;
+use datafusion_ext::runtime::table_provider::RuntimeAwareTableProvider;
+use datafusion_ext::vars::Dialect;
+use datasources::native::access::NativeTable;
+use parking_lot::Mutex;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::context::local::PreparedStatement;
+use crate::parser::StatementWithExtensions;
+use crate::planner::logical_plan::LogicalPlan;
+
+/// Default number of plans kept in a cache.
+pub const DEFAULT_PLAN_CACHE_CAPACITY: usize = 128;
+
+/// Identifies statements that are planned the same way.
+///
+/// Includes every session variable the plan depends on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlanCacheKey {
+    pub database_id: Uuid,
+    /// Name of the database used to resolve partially qualified names.
+    pub database: String,
+    pub dialect: Dialect,
+    /// Search path used to resolve unqualified names.
+    pub search_path: Vec<String>,
+    /// Time zone of `timestamptz` casts and `AT TIME ZONE`.
+    pub timezone: String,
+    /// The text of the statement.
+    pub sql: String,
+}
+
+#[derive(Clone)]
+struct CachedPlan {
+    /// Version of the catalog the statement was planned with.
+    catalog_version: u64,
+    stmt: PreparedStatement,
+    /// Native tables read by the statement.
+    tables: Vec<Arc<dyn TableProvider>>,
+}
+
+impl fmt::Debug for CachedPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedPlan")
+            .field("catalog_version", &self.catalog_version)
+            .field("stmt", &self.stmt)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Bounded cache of planned statements.
+///
+/// Cheaply cloneable, all clones share the same plans. Once full, the oldest
+/// plans are evicted first.
+#[derive(Debug, Clone)]
+pub struct PlanCache {
+    inner: Arc<Mutex<CacheState>>,
+}
+
+#[derive(Debug)]
+struct CacheState {
+    capacity: usize,
+    plans: HashMap<PlanCacheKey, CachedPlan>,
+    /// Keys in the order they were inserted.
+    order: VecDeque<PlanCacheKey>,
+}
+
+impl PlanCache {
+    /// Create a new cache holding at most `capacity` plans. A capacity of zero
+    /// disables caching.
+    pub fn new(capacity: usize) -> Self {
+        PlanCache {
+            inner: Arc::new(Mutex::new(CacheState {
+                capacity,
+                plans: HashMap::new(),
+                order: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// Number of plans in the cache.
+    pub fn len(&self) -> usize {
+        self.inner.lock().plans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get a cached statement for `key`.
+    ///
+    /// Returns `None` if the statement was planned with a different version
+    /// of the catalog, or if any of the tables it reads have changed.
+    pub async fn get(&self, key: &PlanCacheKey, catalog_version: u64) -> Option<PreparedStatement> {
+        let cached = self.inner.lock().plans.get(key).cloned()?;
+
+        // The plan may have been cached by a session with a newer catalog, in
+        // which case it's still useful for others.
+        let stale = if cached.catalog_version < catalog_version {
+            true
+        } else if cached.catalog_version > catalog_version {
+            return None;
+        } else {
+            !tables_unchanged(&cached.tables).await
+        };
+        if stale {
+            debug!(sql = %key.sql, "removing stale plan from cache");
+            self.remove(key);
+            return None;
+        }
+
+        Some(cached.stmt)
+    }
+
+    /// Cache a planned statement.
+    ///
+    /// Returns false if the statement can't be cached.
+    pub fn insert(
+        &self,
+        key: PlanCacheKey,
+        catalog_version: u64,
+        stmt: &PreparedStatement,
+    ) -> bool {
+        let tables = match cacheable_tables(stmt) {
+            Some(tables) => tables,
+            None => return false,
+        };
+
+        let mut state = self.inner.lock();
+        if state.capacity == 0 {
+            return false;
+        }
+
+        let cached = CachedPlan {
+            catalog_version,
+            stmt: stmt.clone(),
+            tables,
+        };
+        if state.plans.insert(key.clone(), cached).is_none() {
+            state.order.push_back(key);
+        }
+        while state.plans.len() > state.capacity {
+            match state.order.pop_front() {
+                Some(oldest) => {
+                    state.plans.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+
+        true
+    }
+
+    fn remove(&self, key: &PlanCacheKey) {
+        let mut state = self.inner.lock();
+        if state.plans.remove(key).is_some() {
+            state.order.retain(|k| k != key);
+        }
+    }
+}
+
+/// A plan cache used by a single session, along with the session's hit and
+/// miss counts.
+///
+/// The cache itself may be shared with other sessions.
+#[derive(Debug)]
+pub struct SessionPlanCache {
+    cache: PlanCache,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl SessionPlanCache {
+    pub fn new(cache: PlanCache) -> Self {
+        SessionPlanCache {
+            cache,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn cache(&self) -> &PlanCache {
+        &self.cache
+    }
+
+    /// Get a cached statement, counting the lookup as a hit or a miss.
+    pub async fn get(&self, key: &PlanCacheKey, catalog_version: u64) -> Option<PreparedStatement> {
+        let stmt = self.cache.get(key, catalog_version).await;
+        let counter = if stmt.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        stmt
+    }
+
+    /// Number of lookups that found a usable plan.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of lookups that didn't find a usable plan.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+/// Get the native tables read by a statement, or `None` if the statement
+/// can't be cached.
+fn cacheable_tables(stmt: &PreparedStatement) -> Option<Vec<Arc<dyn TableProvider>>> {
+    if !matches!(
+        stmt.stmt,
+        Some(StatementWithExtensions::Statement(ast::Statement::Query(_)))
+    ) {
+        return None;
+    }
+    let plan = match &stmt.plan {
+        Some(LogicalPlan::Datafusion(plan)) => plan,
+        _ => return None,
+    };
+
+    let mut tables = Vec::new();
+    collect_native_tables(plan, &mut tables).then_some(tables)
+}
+
+/// Collect the native tables scanned by a plan, including ones scanned in
+/// subqueries.
+///
+/// Returns false if the plan reads anything other than native tables, or
+/// modifies anything.
+fn collect_native_tables(plan: &DfLogicalPlan, tables: &mut Vec<Arc<dyn TableProvider>>) -> bool {
+    match plan {
+        DfLogicalPlan::TableScan(scan) => {
+            let provider = match source_as_provider(&scan.source) {
+                Ok(provider) => provider,
+                Err(_) => return false,
+            };
+            let provider = match provider
+                .as_any()
+                .downcast_ref::<RuntimeAwareTableProvider>()
+            {
+                Some(runtime_aware) => runtime_aware.provider.clone(),
+                None => return false,
+            };
+            if !provider.as_any().is::<NativeTable>() {
+                return false;
+            }
+            tables.push(provider);
+        }
+        DfLogicalPlan::Extension(_)
+        | DfLogicalPlan::Dml(_)
+        | DfLogicalPlan::Ddl(_)
+        | DfLogicalPlan::Copy(_) => return false,
+        _ => (),
+    }
+
+    let mut cacheable = true;
+    let _ = plan.inspect_expressions(|expr| {
+        expr.apply(&mut |expr| {
+            let subquery = match expr {
+                Expr::ScalarSubquery(subquery)
+                | Expr::Exists(Exists { subquery, .. })
+                | Expr::InSubquery(InSubquery { subquery, .. }) => subquery,
+                _ => return Ok(VisitRecursion::Continue),
+            };
+            if collect_native_tables(&subquery.subquery, tables) {
+                Ok(VisitRecursion::Continue)
+            } else {
+                cacheable = false;
+                Ok(VisitRecursion::Stop)
+            }
+        })
+        .map(|_| ())
+    });
+
+    cacheable
+        && plan
+            .inputs()
+            .into_iter()
+            .all(|input| collect_native_tables(input, tables))
+}
+
+/// Check that none of the tables have been written to since they were loaded.
+async fn tables_unchanged(tables: &[Arc<dyn TableProvider>]) -> bool {
+    for table in tables {
+        let table = match table.as_any().downcast_ref::<NativeTable>() {
+            Some(table) => table,
+            None => return false,
+        };
+        match table.latest_version().await {
+            Ok(latest) if latest == table.version() => (),
+            Ok(_) => return false,
+            Err(e) => {
+                debug!(%e, "failed to get latest version of native table");
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::logical_expr::{lit, LogicalPlanBuilder};
+    use datafusion::sql::sqlparser::dialect::GenericDialect;
+    use datafusion::sql::sqlparser::parser::Parser;
+
+    fn key(sql: &str) -> PlanCacheKey {
+        PlanCacheKey {
+            database_id: Uuid::nil(),
+            database: "default".to_string(),
+            dialect: Dialect::Sql,
+            search_path: vec!["public".to_string()],
+            timezone: "UTC".to_string(),
+            sql: sql.to_string(),
+        }
+    }
+
+    /// Statement for 'select 1', which doesn't read any tables.
+    fn select_one() -> PreparedStatement {
+        let stmt = Parser::parse_sql(&GenericDialect {}, "select 1")
+            .unwrap()
+            .pop()
+            .unwrap();
+        let plan = LogicalPlanBuilder::empty(true)
+            .project(vec![lit(1)])
+            .unwrap()
+            .build()
+            .unwrap();
+        PreparedStatement {
+            stmt: Some(StatementWithExtensions::Statement(stmt)),
+            plan: Some(LogicalPlan::Datafusion(plan)),
+            parameter_types: Some(HashMap::new()),
+            output_schema: None,
+            output_pg_types: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn invalidated_by_catalog_version() {
+        let cache = PlanCache::new(DEFAULT_PLAN_CACHE_CAPACITY);
+        assert!(cache.insert(key("select 1"), 1, &select_one()));
+
+        assert!(cache.get(&key("select 1"), 1).await.is_some());
+        assert!(cache.get(&key("select 2"), 1).await.is_none());
+
+        // Sessions with an older catalog don't remove the plan.
+        assert!(cache.get(&key("select 1"), 0).await.is_none());
+        assert_eq!(1, cache.len());
+
+        assert!(cache.get(&key("select 1"), 2).await.is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn evicts_oldest() {
+        let cache = PlanCache::new(2);
+        for sql in ["a", "b", "c"] {
+            assert!(cache.insert(key(sql), 1, &select_one()));
+        }
+
+        assert_eq!(2, cache.len());
+        assert!(cache.get(&key("a"), 1).await.is_none());
+        assert!(cache.get(&key("b"), 1).await.is_some());
+        assert!(cache.get(&key("c"), 1).await.is_some());
+    }
+
+    #[test]
+    fn only_caches_queries() {
+        let cache = PlanCache::new(DEFAULT_PLAN_CACHE_CAPACITY);
+        let empty = PreparedStatement {
+            stmt: None,
+            plan: None,
+            parameter_types: None,
+            output_schema: None,
+            output_pg_types: Vec::new(),
+        };
+        assert!(!cache.insert(key(""), 1, &empty));
+
+        let mut show = select_one();
+        show.stmt = Some(StatementWithExtensions::Statement(
+            Parser::parse_sql(&GenericDialect {}, "show timezone")
+                .unwrap()
+                .pop()
+                .unwrap(),
+        ));
+        assert!(!cache.insert(key("show timezone"), 1, &show));
+
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn session_counts_hits_and_misses() {
+        let session = SessionPlanCache::new(PlanCache::new(DEFAULT_PLAN_CACHE_CAPACITY));
+        assert!(session.get(&key("select 1"), 1).await.is_none());
+
+        session.cache().insert(key("select 1"), 1, &select_one());
+        assert!(session.get(&key("select 1"), 1).await.is_some());
+        assert!(session.get(&key("select 1"), 1).await.is_some());
+
+        assert_eq!(2, session.hits());
+        assert_eq!(1, session.misses());
+    }
+
+    #[test]
+    fn zero_capacity_disables_cache() {
+        let cache = PlanCache::new(0);
+        assert!(!cache.insert(key("select 1"), 1, &select_one()));
+    }
+}
@@ -4,25 +4,28 @@ use crate::dispatch::Dispatcher;
 use crate::errors::ExecError;
 use crate::planner::errors::PlanError;
 use crate::remote::client::RemoteSessionClient;
+use crate::remote::table::StubRemoteTableProvider;
 use crate::resolve::EntryResolver;
 use crate::resolve::ResolvedEntry;
 use async_trait::async_trait;
-use datafusion::arrow::datatypes::DataType;
+use datafusion::arrow::datatypes::{DataType, Schema};
 use datafusion::common::OwnedTableReference;
 use datafusion::config::ConfigOptions;
-use datafusion::datasource::DefaultTableSource;
+use datafusion::datasource::{DefaultTableSource, TableProvider};
 use datafusion::error::{DataFusionError, Result as DataFusionResult};
 use datafusion::execution::context::SessionState;
 use datafusion::logical_expr::AggregateUDF;
 use datafusion::logical_expr::TableSource;
+use datafusion::physical_plan::{collect, ColumnStatistics, Statistics};
 use datafusion::prelude::Expr;
+use datafusion::scalar::ScalarValue;
 use datafusion::sql::TableReference;
 use datafusion_ext::functions::FuncParamValue;
 use datafusion_ext::planner::AsyncContextProvider;
 
 use datafusion_ext::runtime::table_provider::RuntimeAwareTableProvider;
 use protogen::metastore::types::catalog::{
-    CatalogEntry, DatabaseEntry, FunctionEntry, RuntimePreference, TableEntry,
+    CatalogEntry, DatabaseEntry, FunctionEntry, RuntimePreference, TableEntry, TableStatistics,
 };
 use protogen::metastore::types::options::TableOptions;
 use protogen::rpcsrv::types::service::ResolvedTableReference;
@@ -48,6 +51,8 @@ pub struct PartialContextProvider<'a> {
     /// Entry resolver to use to resolve tables and other objects.
     resolver: EntryResolver<'a>,
     runtime_preference: RuntimePreference,
+    /// Oids of the catalog tables and views resolved so far.
+    dependencies: Vec<u32>,
 }
 
 impl<'a> PartialContextProvider<'a> {
@@ -59,9 +64,16 @@ impl<'a> PartialContextProvider<'a> {
             ctx,
             resolver,
             runtime_preference: RuntimePreference::Unspecified,
+            dependencies: Vec::new(),
         })
     }
 
+    /// Get the oids of the tables and views in the catalog that have been
+    /// resolved so far, excluding builtin and temp objects.
+    pub fn dependencies(&self) -> &[u32] {
+        &self.dependencies
+    }
+
     fn new_dispatcher(&self) -> Dispatcher {
         Dispatcher::new(
             self.ctx.get_session_catalog(),
@@ -69,6 +81,7 @@ impl<'a> PartialContextProvider<'a> {
             self.ctx,
             self.ctx.df_ctx(),
             self.ctx.get_session_vars().is_cloud_instance(),
+            self.ctx.get_memory_tracker(),
         )
     }
 
@@ -168,6 +181,17 @@ impl<'a> PartialContextProvider<'a> {
                     .resolve_table(table)
                     .map_err(ExecError::EnvironmentTableRead)?
                 {
+                    // Small tables are uploaded when running hybrid so that
+                    // joins with remote tables can run remotely.
+                    if let Some(client) = self.ctx.exec_client() {
+                        if let Some(staged) = self.stage_local_table(&table, client).await? {
+                            return Ok(RuntimeAwareTableProvider::new(
+                                RuntimePreference::Remote,
+                                staged,
+                            ));
+                        }
+                    }
+
                     // Hint that the table being scanned from the environment
                     // should be scanned client-side.
                     return Ok(RuntimeAwareTableProvider::new(
@@ -184,7 +208,19 @@ impl<'a> PartialContextProvider<'a> {
 
         use ResolvedEntry::*;
 
-        let provider = match (ent, self.ctx.exec_client()) {
+        let table_oid = match &ent {
+            Entry(CatalogEntry::Table(table)) if !table.meta.is_temp => Some(table.meta.id),
+            _ => None,
+        };
+
+        if let Entry(ent @ (CatalogEntry::Table(_) | CatalogEntry::View(_))) = &ent {
+            let meta = ent.get_meta();
+            if !meta.builtin && !meta.is_temp {
+                self.dependencies.push(meta.id);
+            }
+        }
+
+        let mut provider = match (ent, self.ctx.exec_client()) {
             // (view, _)
             // Rely on further planning to determine how to handle views.
             (Entry(ent @ CatalogEntry::View(_)), _) => RuntimeAwareTableProvider::new(
@@ -258,7 +294,55 @@ impl<'a> PartialContextProvider<'a> {
             ),
         };
 
-        Ok(provider)
+        // Use statistics collected with `ANALYZE TABLE` when available.
+        let statistics =
+            table_oid.and_then(|oid| self.ctx.get_session_catalog().table_statistics(oid));
+        Ok(match statistics {
+            Some(statistics) => {
+                let statistics = provider_statistics(statistics, &provider.provider.schema());
+                // Remote scans carry the statistics into the physical plan
+                // for deciding where to run the parts of a hybrid query.
+                if let Some(stub) = provider
+                    .provider
+                    .as_any()
+                    .downcast_ref::<StubRemoteTableProvider>()
+                {
+                    provider.provider = Arc::new(stub.with_statistics(statistics.clone()));
+                }
+                provider.with_statistics(statistics)
+            }
+            None => provider,
+        })
+    }
+
+    /// Upload a local table to the remote side if it's known to be smaller
+    /// than `stage_local_tables_max_bytes`.
+    ///
+    /// Returns `None` if the table should be scanned locally instead.
+    async fn stage_local_table(
+        &self,
+        table: &Arc<dyn TableProvider>,
+        mut client: RemoteSessionClient,
+    ) -> Result<Option<Arc<dyn TableProvider>>, PlanError> {
+        let max_bytes = self.ctx.get_session_vars().stage_local_tables_max_bytes();
+        if max_bytes == 0 {
+            return Ok(None);
+        }
+
+        // Only tables with an exact size are staged, we don't want to
+        // discover a table is too large after reading it.
+        let plan = table.scan(self.state, None, &[], None).await?;
+        let statistics = plan.statistics();
+        match statistics.total_byte_size {
+            Some(bytes) if statistics.is_exact && bytes <= max_bytes => (),
+            _ => return Ok(None),
+        }
+
+        let batches = collect(plan, self.state.task_ctx()).await?;
+        let staged = client
+            .stage_table(table.schema(), batches, statistics)
+            .await?;
+        Ok(Some(staged))
     }
 
     async fn handle_catalog_entry_dispatch(
@@ -366,7 +450,12 @@ impl<'a> AsyncContextProvider for PartialContextProvider<'a> {
             .table_provider(name.to_owned_reference())
             .await
             .map_err(|e| {
-                DataFusionError::Plan(format!("Unable to fetch table provider for '{name}': {e}"))
+                // Keep the error as is so that it can still be classified
+                // once it's returned from datafusion.
+                DataFusionError::External(Box::new(PlanError::UnableToFetchTableProvider {
+                    reference: name.to_string(),
+                    source: Box::new(e),
+                }))
             })?;
         Ok(Arc::new(DefaultTableSource::new(Arc::new(provider))))
     }
@@ -381,8 +470,10 @@ impl<'a> AsyncContextProvider for PartialContextProvider<'a> {
         None
     }
 
-    async fn get_aggregate_meta(&mut self, _name: &str) -> Option<Arc<AggregateUDF>> {
-        None
+    async fn get_aggregate_meta(&mut self, name: &str) -> Option<Arc<AggregateUDF>> {
+        FUNCTION_REGISTRY
+            .get_aggregate_udf(name)
+            .map(|f| Arc::new(f.as_aggregate_udf()))
     }
 
     async fn get_table_func(
@@ -391,6 +482,18 @@ impl<'a> AsyncContextProvider for PartialContextProvider<'a> {
         args: Vec<FuncParamValue>,
         opts: HashMap<String, FuncParamValue>,
     ) -> DataFusionResult<Arc<dyn TableSource>> {
+        // Functions registered by the embedding application are always
+        // executed locally.
+        if let TableReference::Bare { table } = &name {
+            if let Some(func) = self.ctx.get_table_function(table) {
+                let provider = func
+                    .create_provider(args, opts)
+                    .map_err(|e| DataFusionError::External(Box::new(e)))?;
+                let provider = RuntimeAwareTableProvider::new(RuntimePreference::Local, provider);
+                return Ok(Arc::new(DefaultTableSource::new(Arc::new(provider))));
+            }
+        }
+
         self.resolve_reference(name.to_owned_reference(), Some(args), Some(opts))
             .await
             .map(|p| Arc::new(DefaultTableSource::new(Arc::new(p))) as _)
@@ -400,4 +503,51 @@ impl<'a> AsyncContextProvider for PartialContextProvider<'a> {
     fn options(&self) -> &ConfigOptions {
         self.state.config_options()
     }
+    fn function_names(&self) -> Vec<String> {
+        FUNCTION_REGISTRY
+            .function_names()
+            .map(|name| name.to_string())
+            .collect()
+    }
+}
+
+/// Convert statistics stored in the catalog to statistics for a provider with
+/// the given schema.
+///
+/// Columns without stored statistics, e.g. ones added to an external table's
+/// source after it was analyzed, get unknown statistics.
+fn provider_statistics(statistics: &TableStatistics, schema: &Schema) -> Statistics {
+    let scalar = |value: &Option<String>, data_type: &DataType| {
+        value
+            .as_ref()
+            .and_then(|value| ScalarValue::try_from_string(value.clone(), data_type).ok())
+    };
+
+    let column_statistics = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            match statistics
+                .columns
+                .iter()
+                .find(|col| &col.name == field.name())
+            {
+                Some(col) => ColumnStatistics {
+                    null_count: Some(col.null_count as usize),
+                    max_value: scalar(&col.max_value, field.data_type()),
+                    min_value: scalar(&col.min_value, field.data_type()),
+                    distinct_count: col.distinct_count.map(|n| n as usize),
+                },
+                None => ColumnStatistics::default(),
+            }
+        })
+        .collect();
+
+    Statistics {
+        num_rows: Some(statistics.num_rows as usize),
+        total_byte_size: None,
+        column_statistics: Some(column_statistics),
+        // The table may have changed since it was analyzed.
+        is_exact: false,
+    }
 }
@@ -15,8 +15,30 @@ pub enum PlanError {
     #[error("Failed to find table for reference: {reference}")]
     FailedToFindTableForReference { reference: String },
 
+    #[error("Unable to fetch table provider for '{reference}': {source}")]
+    UnableToFetchTableProvider {
+        reference: String,
+        source: Box<PlanError>,
+    },
+
+    #[error("Unsupported {typ}: {name}")]
+    Unsupported { typ: &'static str, name: String },
+
+    #[error("Invalid data type: {msg}")]
+    InvalidDataType { msg: String },
+
     #[error(transparent)]
-    DataFusion(#[from] datafusion::common::DataFusionError),
+    DataFusion(datafusion::common::DataFusionError),
+
+    #[error(
+        "{source}{}",
+        suggestion_list(.suggestions).map(|s| format!(" Did you mean {s}?")).unwrap_or_default()
+    )]
+    ColumnNotFound {
+        source: datafusion::common::DataFusionError,
+        /// Close matches from the valid fields.
+        suggestions: Vec<String>,
+    },
 
     #[error(transparent)]
     Preprocess(#[from] crate::planner::preprocess::PreprocessError),
@@ -52,6 +74,12 @@ pub enum PlanError {
     #[error("Invalid alter statement: {msg}")]
     InvalidAlterStatement { msg: &'static str },
 
+    #[error("Cannot modify the catalog or tables in a read-only session")]
+    ReadOnlySession,
+
+    #[error("Invalid create table statement: {msg}")]
+    InvalidCreateTableStatement { msg: &'static str },
+
     #[error("Invalid copy to statement: {source}")]
     InvalidCopyToStatement {
         source: Box<dyn std::error::Error + Send + Sync>,
@@ -60,6 +88,9 @@ pub enum PlanError {
     #[error("Invalid number of column aliases for view body; sql: {sql}, aliases: {aliases:?}")]
     InvalidNumberOfAliasesForView { sql: String, aliases: Vec<String> },
 
+    #[error("Invalid number of query parameters, expected {expected}, got {got}")]
+    InvalidNumberOfParameters { expected: usize, got: usize },
+
     #[error("An ssh connection is not supported datasource for CREATE EXTERNAL TABLE. An ssh connection must be provided as an optional ssh_tunnel with another connection type")]
     ExternalTableWithSsh,
 
@@ -100,6 +131,25 @@ pub enum PlanError {
     String(String),
 }
 
+impl From<datafusion::common::DataFusionError> for PlanError {
+    fn from(value: datafusion::common::DataFusionError) -> Self {
+        match &value {
+            datafusion::common::DataFusionError::SchemaError(SchemaError::FieldNotFound {
+                field,
+                valid_fields,
+            }) => {
+                let suggestions =
+                    closest_matches(&field.name, valid_fields.iter().map(|f| f.flat_name()));
+                PlanError::ColumnNotFound {
+                    source: value,
+                    suggestions,
+                }
+            }
+            _ => PlanError::DataFusion(value),
+        }
+    }
+}
+
 impl From<PlanError> for datafusion::error::DataFusionError {
     fn from(value: PlanError) -> Self {
         datafusion::error::DataFusionError::Plan(value.to_string())
@@ -108,6 +158,47 @@ impl From<PlanError> for datafusion::error::DataFusionError {
 
 pub type Result<T, E = PlanError> = std::result::Result<T, E>;
 
+impl PlanError {
+    /// Get the class of this error.
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            PlanError::UnsupportedFeature(_)
+            | PlanError::UnsupportedSQLStatement(_)
+            | PlanError::Unsupported { .. }
+            | PlanError::ExternalTableWithSsh => ErrorClass::FeatureNotSupported,
+            PlanError::FailedToCreateTableProvider { e, .. } => e.class(),
+            PlanError::FailedToFindTableForReference { .. } => ErrorClass::UndefinedTable,
+            PlanError::UnableToFetchTableProvider { source, .. } => source.class(),
+            PlanError::InvalidDataType { .. }
+            | PlanError::InvalidTunnel { .. }
+            | PlanError::InvalidCredentials { .. } => ErrorClass::InvalidParameter,
+            PlanError::InvalidViewStatement { .. }
+            | PlanError::InvalidDeleteStatement { .. }
+            | PlanError::InvalidInsertStatement { .. }
+            | PlanError::InvalidAlterStatement { .. }
+            | PlanError::InvalidCreateTableStatement { .. }
+            | PlanError::InvalidCopyToStatement { .. }
+            | PlanError::InvalidNumberOfAliasesForView { .. }
+            | PlanError::InvalidNumberOfParameters { .. }
+            | PlanError::ExpectedExactlyOneStatement(_) => ErrorClass::InvalidStatement,
+            PlanError::InvalidExternalDatabase { source }
+            | PlanError::InvalidExternalTable { source } => {
+                external_error_class(&source.to_string())
+            }
+            PlanError::ReadOnlySession => ErrorClass::ReadOnly,
+            PlanError::ObjectNotAllowedToWriteInto(_) => ErrorClass::PermissionDenied,
+            PlanError::DataFusion(e) => datafusion_error_class(e),
+            PlanError::ColumnNotFound { .. } => ErrorClass::UndefinedColumn,
+            PlanError::Dispatch(e) => e.class(),
+            PlanError::Exec(e) => e.class(),
+            PlanError::ResolveError(e) => e.class(),
+            PlanError::ParseError(_) => ErrorClass::Syntax,
+            PlanError::DatasourceCommon(e) => external_error_class(&e.to_string()),
+            _ => ErrorClass::Internal,
+        }
+    }
+}
+
 impl From<crate::errors::ExecError> for PlanError {
     fn from(value: crate::errors::ExecError) -> Self {
         PlanError::Exec(Box::new(value))
@@ -136,5 +227,8 @@ macro_rules! internal {
         crate::planner::errors::PlanError::Internal(std::format!($($arg)*))
     };
 }
-use datafusion::common::OwnedTableReference;
+use datafusion::common::{OwnedTableReference, SchemaError};
+use datafusion_ext::suggest::{closest_matches, suggestion_list};
 pub(crate) use internal;
+
+use crate::errors::{datafusion_error_class, external_error_class, ErrorClass};
@@ -7,10 +7,12 @@ use crate::{
 use datafusion::logical_expr::{Extension as LogicalPlanExtension, UserDefinedLogicalNodeCore};
 
 use super::logical_plan::{
-    AlterDatabase, AlterTable, AlterTunnelRotateKeys, CopyTo, CreateCredential, CreateCredentials,
-    CreateExternalDatabase, CreateExternalTable, CreateSchema, CreateTable, CreateTempTable,
-    CreateTunnel, CreateView, Delete, DescribeTable, DropCredentials, DropDatabase, DropSchemas,
-    DropTables, DropTunnel, DropViews, Insert, SetVariable, ShowVariable, Update,
+    AlterDatabase, AlterRole, AlterSchema, AlterTable, AlterTunnelRotateKeys, AnalyzeTable,
+    CommentOn, CopyTo, CreateCredential, CreateCredentials, CreateDatabase, CreateExternalDatabase,
+    CreateExternalTable, CreateSchema, CreateTable, CreateTempTable, CreateTunnel, CreateView,
+    Delete, DescribeTable, DropCredentials, DropDatabase, DropSchemas, DropTables, DropTunnel,
+    DropViews, ExportCatalog, ImportCatalog, Insert, RestoreCatalog, SetVariable, ShowVariable,
+    Update, Upsert, UseDatabase,
 };
 
 /// This tracks all of our extensions so that we can ensure an exhaustive match on anywhere that uses the extension
@@ -19,10 +21,15 @@ use super::logical_plan::{
 #[derive(Debug)]
 pub enum ExtensionType {
     AlterDatabase,
+    AlterRole,
+    AlterSchema,
     AlterTable,
     AlterTunnelRotateKeys,
+    AnalyzeTable,
+    CommentOn,
     CreateCredential,
     CreateCredentials,
+    CreateDatabase,
     CreateExternalDatabase,
     CreateExternalTable,
     CreateSchema,
@@ -37,12 +44,17 @@ pub enum ExtensionType {
     DropSchemas,
     DropTunnel,
     DropViews,
+    ExportCatalog,
+    ImportCatalog,
+    RestoreCatalog,
     SetVariable,
     ShowVariable,
     CopyTo,
     Update,
     Insert,
+    Upsert,
     Delete,
+    UseDatabase,
 }
 
 impl FromStr for ExtensionType {
@@ -50,10 +62,15 @@ impl FromStr for ExtensionType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
             AlterDatabase::EXTENSION_NAME => Self::AlterDatabase,
+            AlterRole::EXTENSION_NAME => Self::AlterRole,
+            AlterSchema::EXTENSION_NAME => Self::AlterSchema,
             AlterTable::EXTENSION_NAME => Self::AlterTable,
             AlterTunnelRotateKeys::EXTENSION_NAME => Self::AlterTunnelRotateKeys,
+            AnalyzeTable::EXTENSION_NAME => Self::AnalyzeTable,
+            CommentOn::EXTENSION_NAME => Self::CommentOn,
             CreateCredential::EXTENSION_NAME => Self::CreateCredential,
             CreateCredentials::EXTENSION_NAME => Self::CreateCredentials,
+            CreateDatabase::EXTENSION_NAME => Self::CreateDatabase,
             CreateExternalDatabase::EXTENSION_NAME => Self::CreateExternalDatabase,
             CreateExternalTable::EXTENSION_NAME => Self::CreateExternalTable,
             CreateSchema::EXTENSION_NAME => Self::CreateSchema,
@@ -68,12 +85,17 @@ impl FromStr for ExtensionType {
             DropSchemas::EXTENSION_NAME => Self::DropSchemas,
             DropTunnel::EXTENSION_NAME => Self::DropTunnel,
             DropViews::EXTENSION_NAME => Self::DropViews,
+            ExportCatalog::EXTENSION_NAME => Self::ExportCatalog,
+            ImportCatalog::EXTENSION_NAME => Self::ImportCatalog,
+            RestoreCatalog::EXTENSION_NAME => Self::RestoreCatalog,
             SetVariable::EXTENSION_NAME => Self::SetVariable,
             ShowVariable::EXTENSION_NAME => Self::ShowVariable,
             CopyTo::EXTENSION_NAME => Self::CopyTo,
             Update::EXTENSION_NAME => Self::Update,
             Insert::EXTENSION_NAME => Self::Insert,
+            Upsert::EXTENSION_NAME => Self::Upsert,
             Delete::EXTENSION_NAME => Self::Delete,
+            UseDatabase::EXTENSION_NAME => Self::UseDatabase,
             _ => return Err(internal!("unknown extension type: {}", s)),
         })
     }
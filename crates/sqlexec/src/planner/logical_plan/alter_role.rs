@@ -0,0 +1,44 @@
+use protogen::metastore::types::service::AlterRoleOperation;
+
+use super::*;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct AlterRole {
+    pub name: String,
+    pub database: Option<String>,
+    pub operation: AlterRoleOperation,
+}
+
+impl UserDefinedLogicalNodeCore for AlterRole {
+    fn name(&self) -> &str {
+        Self::EXTENSION_NAME
+    }
+
+    fn inputs(&self) -> Vec<&DfLogicalPlan> {
+        vec![]
+    }
+
+    fn schema(&self) -> &datafusion::common::DFSchemaRef {
+        &GENERIC_OPERATION_LOGICAL_SCHEMA
+    }
+
+    fn expressions(&self) -> Vec<datafusion::prelude::Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", Self::EXTENSION_NAME)
+    }
+
+    fn from_template(
+        &self,
+        _exprs: &[datafusion::prelude::Expr],
+        _inputs: &[DfLogicalPlan],
+    ) -> Self {
+        self.clone()
+    }
+}
+
+impl ExtensionNode for AlterRole {
+    const EXTENSION_NAME: &'static str = "AlterRole";
+}
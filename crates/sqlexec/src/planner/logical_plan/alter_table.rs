@@ -4,6 +4,7 @@ use super::*;
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct AlterTable {
+    pub database: String,
     pub schema: String,
     pub name: String,
     pub operation: AlterTableOperation,
@@ -0,0 +1,51 @@
+use super::*;
+
+/// Collect statistics for a table.
+///
+/// The source is an aggregate over the table producing a single row of
+/// statistics, see `SessionPlanner::plan_analyze_table`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct AnalyzeTable {
+    pub tbl_reference: OwnedFullObjectReference,
+    /// Names of the table's columns, in order.
+    pub columns: Vec<String>,
+    pub source: DfLogicalPlan,
+}
+
+impl UserDefinedLogicalNodeCore for AnalyzeTable {
+    fn name(&self) -> &str {
+        Self::EXTENSION_NAME
+    }
+
+    fn inputs(&self) -> Vec<&DfLogicalPlan> {
+        vec![&self.source]
+    }
+
+    fn schema(&self) -> &datafusion::common::DFSchemaRef {
+        &GENERIC_OPERATION_LOGICAL_SCHEMA
+    }
+
+    fn expressions(&self) -> Vec<datafusion::prelude::Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", Self::EXTENSION_NAME, self.tbl_reference)
+    }
+
+    fn from_template(
+        &self,
+        _exprs: &[datafusion::prelude::Expr],
+        inputs: &[DfLogicalPlan],
+    ) -> Self {
+        Self {
+            tbl_reference: self.tbl_reference.clone(),
+            columns: self.columns.clone(),
+            source: inputs[0].clone(),
+        }
+    }
+}
+
+impl ExtensionNode for AnalyzeTable {
+    const EXTENSION_NAME: &'static str = "AnalyzeTable";
+}
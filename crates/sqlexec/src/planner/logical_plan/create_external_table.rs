@@ -7,6 +7,8 @@ pub struct CreateExternalTable {
     pub if_not_exists: bool,
     pub table_options: TableOptions,
     pub tunnel: Option<String>,
+    /// Inferred schema to cache for the table.
+    pub schema: Option<CachedTableSchema>,
 }
 
 impl UserDefinedLogicalNodeCore for CreateExternalTable {
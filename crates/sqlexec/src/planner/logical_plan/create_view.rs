@@ -5,6 +5,8 @@ pub struct CreateView {
     pub sql: String,
     pub columns: Vec<String>,
     pub or_replace: bool,
+    /// Oids of the tables and views referenced by the view.
+    pub dependencies: Vec<u32>,
 }
 
 impl UserDefinedLogicalNodeCore for CreateView {
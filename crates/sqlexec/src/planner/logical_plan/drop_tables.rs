@@ -3,6 +3,7 @@ use super::*;
 pub struct DropTables {
     pub tbl_references: Vec<OwnedFullObjectReference>,
     pub if_exists: bool,
+    pub cascade: bool,
 }
 
 impl UserDefinedLogicalNodeCore for DropTables {
@@ -4,6 +4,7 @@ use super::*;
 pub struct DropViews {
     pub view_references: Vec<OwnedFullObjectReference>,
     pub if_exists: bool,
+    pub cascade: bool,
 }
 
 impl UserDefinedLogicalNodeCore for DropViews {
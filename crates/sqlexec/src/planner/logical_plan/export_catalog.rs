@@ -0,0 +1,47 @@
+use protogen::metastore::types::catalog::RolePassword;
+
+use super::*;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ExportCatalog {
+    pub path: String,
+    pub include_credentials: bool,
+    /// Password verifiers of roles, only fetched when including credentials.
+    pub role_passwords: Vec<RolePassword>,
+    /// Key to encrypt the dump with.
+    pub encryption_key: Option<String>,
+}
+
+impl UserDefinedLogicalNodeCore for ExportCatalog {
+    fn name(&self) -> &str {
+        Self::EXTENSION_NAME
+    }
+
+    fn inputs(&self) -> Vec<&DfLogicalPlan> {
+        vec![]
+    }
+
+    fn schema(&self) -> &datafusion::common::DFSchemaRef {
+        &GENERIC_OPERATION_LOGICAL_SCHEMA
+    }
+
+    fn expressions(&self) -> Vec<datafusion::prelude::Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "ExportCatalog")
+    }
+
+    fn from_template(
+        &self,
+        _exprs: &[datafusion::prelude::Expr],
+        _inputs: &[DfLogicalPlan],
+    ) -> Self {
+        self.clone()
+    }
+}
+
+impl ExtensionNode for ExportCatalog {
+    const EXTENSION_NAME: &'static str = "ExportCatalog";
+}
@@ -1,9 +1,14 @@
 mod alter_database;
+mod alter_role;
+mod alter_schema;
 mod alter_table;
 mod alter_tunnel_rotate_keys;
+mod analyze_table;
+mod comment_on;
 mod copy_to;
 mod create_credential;
 mod create_credentials;
+mod create_database;
 mod create_external_database;
 mod create_external_table;
 mod create_schema;
@@ -19,12 +24,18 @@ mod drop_schemas;
 mod drop_tables;
 mod drop_tunnel;
 mod drop_views;
+mod export_catalog;
+mod import_catalog;
 mod insert;
+mod restore_catalog;
 mod set_variable;
 mod show_variable;
 mod update;
+mod upsert;
+mod use_database;
 
 use crate::errors::{internal, Result};
+use crate::planner::errors::PlanError;
 use crate::planner::extension::ExtensionNode;
 
 use datafusion::arrow::datatypes::{DataType, Schema as ArrowSchema};
@@ -35,6 +46,7 @@ use datafusion::scalar::ScalarValue;
 use datafusion::sql::sqlparser::ast;
 use datafusion::sql::TableReference;
 use once_cell::sync::Lazy;
+use protogen::metastore::types::catalog::CachedTableSchema;
 use protogen::metastore::types::options::{CopyToDestinationOptions, CopyToFormatOptions};
 use protogen::metastore::types::options::{
     CredentialsOptions, DatabaseOptions, TableOptions, TunnelOptions,
@@ -45,11 +57,16 @@ use std::fmt;
 use std::sync::Arc;
 
 pub use alter_database::*;
+pub use alter_role::*;
+pub use alter_schema::*;
 pub use alter_table::*;
 pub use alter_tunnel_rotate_keys::*;
+pub use analyze_table::*;
+pub use comment_on::*;
 pub use copy_to::*;
 pub use create_credential::*;
 pub use create_credentials::*;
+pub use create_database::*;
 pub use create_external_database::*;
 pub use create_external_table::*;
 pub use create_schema::*;
@@ -65,10 +82,15 @@ pub use drop_schemas::*;
 pub use drop_tables::*;
 pub use drop_tunnel::*;
 pub use drop_views::*;
+pub use export_catalog::*;
+pub use import_catalog::*;
 pub use insert::*;
+pub use restore_catalog::*;
 pub use set_variable::*;
 pub use show_variable::*;
 pub use update::*;
+pub use upsert::*;
+pub use use_database::*;
 
 use super::physical_plan::{
     GENERIC_OPERATION_AND_COUNT_PHYSICAL_SCHEMA, GENERIC_OPERATION_PHYSICAL_SCHEMA,
@@ -186,6 +208,33 @@ impl LogicalPlan {
 
         Ok(())
     }
+
+    /// Bind values to the `$1`, `$2`, ... placeholders in this plan.
+    ///
+    /// Values are cast to the placeholder's type if it could be inferred
+    /// from the query, letting clients pass values without knowing the exact
+    /// column types.
+    pub fn bind_parameters(&mut self, params: Vec<ScalarValue>) -> Result<()> {
+        let types = self.get_parameter_types()?;
+        if types.len() != params.len() {
+            return Err(PlanError::InvalidNumberOfParameters {
+                expected: types.len(),
+                got: params.len(),
+            }
+            .into());
+        }
+
+        let params = params
+            .into_iter()
+            .enumerate()
+            .map(|(idx, param)| match types.get(&format!("${}", idx + 1)) {
+                Some(Some(data_type)) if !param.is_null() => param.cast_to(data_type),
+                _ => Ok(param),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.replace_placeholders(params)
+    }
 }
 
 impl From<DfLogicalPlan> for LogicalPlan {
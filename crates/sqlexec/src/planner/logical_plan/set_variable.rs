@@ -15,6 +15,12 @@ impl SetVariable {
                 ast::Expr::Value(ast::Value::DoubleQuotedString(s)) => format!("\"{}\"", s),
                 ast::Expr::Value(ast::Value::UnQuotedString(s)) => s.clone(),
                 ast::Expr::Value(ast::Value::Number(s, _)) => s.clone(),
+                ast::Expr::UnaryOp {
+                    op: ast::UnaryOperator::Minus,
+                    expr,
+                } if matches!(**expr, ast::Expr::Value(ast::Value::Number(_, _))) => {
+                    format!("-{expr}")
+                }
                 ast::Expr::Value(v) => v.to_string(),
                 other => return Err(internal!("invalid expression for SET var: {:}", other)),
             })
@@ -0,0 +1,54 @@
+use protogen::metastore::types::catalog::TableEntry;
+
+use super::*;
+
+/// `INSERT ... ON CONFLICT` into a native table.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Upsert {
+    pub table: TableEntry,
+    pub source: DfLogicalPlan,
+    /// Columns identifying rows that conflict with inserted rows.
+    pub conflict_columns: Vec<String>,
+    /// Updates to conflicting rows, or `None` to leave them as is (`DO
+    /// NOTHING`).
+    ///
+    /// Expressions reference the existing row qualified by the table name and
+    /// the inserted row qualified by `excluded`.
+    pub updates: Option<Vec<(String, Expr)>>,
+    /// Only update conflicting rows matching this expression.
+    pub where_expr: Option<Expr>,
+}
+
+impl UserDefinedLogicalNodeCore for Upsert {
+    fn name(&self) -> &str {
+        Self::EXTENSION_NAME
+    }
+
+    fn inputs(&self) -> Vec<&DfLogicalPlan> {
+        vec![&self.source]
+    }
+
+    fn schema(&self) -> &datafusion::common::DFSchemaRef {
+        &GENERIC_OPERATION_AND_COUNT_LOGICAL_SCHEMA
+    }
+
+    fn expressions(&self) -> Vec<datafusion::prelude::Expr> {
+        Vec::new()
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", Self::EXTENSION_NAME)
+    }
+
+    fn from_template(
+        &self,
+        _exprs: &[datafusion::prelude::Expr],
+        _inputs: &[DfLogicalPlan],
+    ) -> Self {
+        self.clone()
+    }
+}
+
+impl ExtensionNode for Upsert {
+    const EXTENSION_NAME: &'static str = "Upsert";
+}
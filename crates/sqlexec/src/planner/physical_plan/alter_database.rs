@@ -8,6 +8,7 @@ use datafusion::physical_plan::{
     stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning,
     SendableRecordBatchStream, Statistics,
 };
+use datafusion_ext::vars::SessionVars;
 use futures::stream;
 use protogen::metastore::types::service::{self, AlterDatabaseOperation, Mutation};
 use std::any::Any;
@@ -64,6 +65,18 @@ impl ExecutionPlan for AlterDatabaseExec {
             ));
         }
 
+        // Checked again since the plan may have been sent by a remote
+        // client.
+        let vars = context
+            .session_config()
+            .options()
+            .extensions
+            .get::<SessionVars>()
+            .cloned()
+            .unwrap_or_default();
+        check_alter_database_permission(&vars, &self.operation)
+            .map_err(DataFusionError::Execution)?;
+
         let mutator = context
             .session_config()
             .get_extension::<CatalogMutator>()
@@ -88,6 +101,26 @@ impl DisplayAs for AlterDatabaseExec {
     }
 }
 
+/// Check that the session is allowed to alter the database, returning the
+/// error message if not.
+///
+/// Variable defaults apply to every user of the database, so changing them
+/// requires an admin.
+pub fn check_alter_database_permission(
+    vars: &SessionVars,
+    operation: &AlterDatabaseOperation,
+) -> Result<(), String> {
+    match operation {
+        AlterDatabaseOperation::SetVariable { .. }
+        | AlterDatabaseOperation::ResetVariable { .. }
+            if !vars.is_admin() =>
+        {
+            Err("Permission denied: changing database defaults requires an admin".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
 async fn alter_database(
     mutator: Arc<CatalogMutator>,
     plan: AlterDatabaseExec,
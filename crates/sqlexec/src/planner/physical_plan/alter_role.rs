@@ -0,0 +1,147 @@
+use catalog::mutator::CatalogMutator;
+use datafusion::arrow::datatypes::Schema;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::{
+    stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use datafusion_ext::vars::SessionVars;
+use futures::stream;
+use protogen::metastore::types::service::{self, AlterRoleOperation, Mutation};
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use super::{new_operation_batch, GENERIC_OPERATION_PHYSICAL_SCHEMA};
+
+#[derive(Debug, Clone)]
+pub struct AlterRoleExec {
+    pub catalog_version: u64,
+    pub name: String,
+    pub database: Option<String>,
+    pub operation: AlterRoleOperation,
+}
+
+impl ExecutionPlan for AlterRoleExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Arc<Schema> {
+        GENERIC_OPERATION_PHYSICAL_SCHEMA.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        Vec::new()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::Plan(
+            "Cannot change children for AlterRoleExec".to_string(),
+        ))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Execution(
+                "AlterRoleExec only supports 1 partition".to_string(),
+            ));
+        }
+
+        // Checked again since the plan may have been sent by a remote
+        // client, whose session isn't necessarily allowed to alter the role
+        // on this server.
+        let vars = context
+            .session_config()
+            .options()
+            .extensions
+            .get::<SessionVars>()
+            .cloned()
+            .unwrap_or_default();
+        check_alter_role_permission(&vars, &self.name, &self.operation)
+            .map_err(DataFusionError::Execution)?;
+
+        let mutator = context
+            .session_config()
+            .get_extension::<CatalogMutator>()
+            .expect("context should have catalog mutator");
+
+        let stream = stream::once(alter_role(mutator, self.clone()));
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+impl DisplayAs for AlterRoleExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AlterRoleExec")
+    }
+}
+
+/// Check that the session is allowed to alter the role, returning the error
+/// message if not.
+///
+/// Users may change their own password and defaults. Changing other roles, or
+/// changing read-only access, requires an admin.
+pub fn check_alter_role_permission(
+    vars: &SessionVars,
+    name: &str,
+    operation: &AlterRoleOperation,
+) -> Result<(), String> {
+    if vars.is_admin() {
+        return Ok(());
+    }
+    match operation {
+        AlterRoleOperation::SetReadOnly { .. } => Err(
+            "Permission denied: changing read-only access of a role requires an admin".to_string(),
+        ),
+        _ if vars.user_name() != name => Err(format!(
+            "Permission denied: altering role '{name}' requires an admin"
+        )),
+        _ => Ok(()),
+    }
+}
+
+async fn alter_role(
+    mutator: Arc<CatalogMutator>,
+    plan: AlterRoleExec,
+) -> DataFusionResult<RecordBatch> {
+    mutator
+        .mutate(
+            plan.catalog_version,
+            [Mutation::AlterRole(service::AlterRole {
+                name: plan.name,
+                database: plan.database,
+                operation: plan.operation,
+            })],
+        )
+        .await
+        .map_err(|e| DataFusionError::Execution(format!("failed to alter role: {e}")))?;
+
+    Ok(new_operation_batch("alter_role"))
+}
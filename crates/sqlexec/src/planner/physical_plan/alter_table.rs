@@ -19,6 +19,7 @@ use super::{new_operation_batch, GENERIC_OPERATION_PHYSICAL_SCHEMA};
 #[derive(Debug, Clone)]
 pub struct AlterTableExec {
     pub catalog_version: u64,
+    pub database: String,
     pub schema: String,
     pub name: String,
     pub operation: AlterTableOperation,
@@ -98,6 +99,7 @@ async fn alter_table_rename(
         .mutate(
             plan.catalog_version,
             [Mutation::AlterTable(service::AlterTable {
+                database: plan.database,
                 schema: plan.schema,
                 name: plan.name,
                 operation: plan.operation,
@@ -0,0 +1,208 @@
+use catalog::mutator::CatalogMutator;
+use datafusion::arrow::array::{Array, ArrayRef, AsArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Schema, UInt64Type};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+use datafusion::physical_plan::{
+    stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use futures::{stream, TryStreamExt};
+use protogen::metastore::types::catalog::{ColumnStatistics, TableStatistics};
+use protogen::metastore::types::service::{self, Mutation};
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use super::{new_operation_batch, GENERIC_OPERATION_PHYSICAL_SCHEMA};
+use crate::planner::logical_plan::OwnedFullObjectReference;
+
+/// Name of the source column holding the number of rows in the table.
+pub const NUM_ROWS: &str = "num_rows";
+/// Number of non-null values.
+pub const COUNT: &str = "count";
+/// Estimated number of distinct values.
+pub const DISTINCT: &str = "distinct";
+pub const MIN: &str = "min";
+pub const MAX: &str = "max";
+
+/// Name of the source column holding a statistic for the table column at
+/// `idx`.
+pub fn column_stat_name(idx: usize, stat: &str) -> String {
+    format!("{idx}:{stat}")
+}
+
+/// Store the statistics computed by the source in the catalog.
+///
+/// The source should produce a single row with a column for the number of
+/// rows, and a column for each statistic of each table column. Statistics
+/// other than the count may be left out for columns where they can't be
+/// computed.
+#[derive(Debug, Clone)]
+pub struct AnalyzeTableExec {
+    pub catalog_version: u64,
+    pub tbl_reference: OwnedFullObjectReference,
+    pub columns: Vec<String>,
+    pub source: Arc<dyn ExecutionPlan>,
+}
+
+impl ExecutionPlan for AnalyzeTableExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Arc<Schema> {
+        GENERIC_OPERATION_PHYSICAL_SCHEMA.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.source.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Plan(
+                "AnalyzeTableExec requires exactly one child".to_string(),
+            ));
+        }
+        Ok(Arc::new(AnalyzeTableExec {
+            catalog_version: self.catalog_version,
+            tbl_reference: self.tbl_reference.clone(),
+            columns: self.columns.clone(),
+            source: children[0].clone(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Execution(
+                "AnalyzeTableExec only supports 1 partition".to_string(),
+            ));
+        }
+
+        let mutator = context
+            .session_config()
+            .get_extension::<CatalogMutator>()
+            .expect("context should have catalog mutator");
+
+        let stream = stream::once(analyze_table(mutator, self.clone(), context));
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+impl DisplayAs for AnalyzeTableExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AnalyzeTableExec: {}", self.tbl_reference)
+    }
+}
+
+async fn analyze_table(
+    mutator: Arc<CatalogMutator>,
+    plan: AnalyzeTableExec,
+    context: Arc<TaskContext>,
+) -> DataFusionResult<RecordBatch> {
+    let source = if plan.source.output_partitioning().partition_count() != 1 {
+        Arc::new(CoalescePartitionsExec::new(plan.source))
+    } else {
+        plan.source
+    };
+
+    let batches: Vec<_> = source.execute(0, context)?.try_collect().await?;
+    let batch = match batches.iter().find(|batch| batch.num_rows() > 0) {
+        Some(batch) => batch,
+        None => {
+            return Err(DataFusionError::Internal(
+                "table statistics should have one row".to_string(),
+            ))
+        }
+    };
+
+    let num_rows = stat_u64(batch, NUM_ROWS)?.unwrap_or_default();
+    let columns = plan
+        .columns
+        .into_iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            let count = stat_u64(batch, &column_stat_name(idx, COUNT))?.unwrap_or_default();
+            Ok(ColumnStatistics {
+                name,
+                null_count: num_rows.saturating_sub(count),
+                distinct_count: stat_u64(batch, &column_stat_name(idx, DISTINCT))?,
+                min_value: stat_string(batch, &column_stat_name(idx, MIN))?,
+                max_value: stat_string(batch, &column_stat_name(idx, MAX))?,
+            })
+        })
+        .collect::<DataFusionResult<_>>()?;
+
+    mutator
+        .mutate(
+            plan.catalog_version,
+            [Mutation::UpdateTableStatistics(
+                service::UpdateTableStatistics {
+                    database: plan.tbl_reference.database.into_owned(),
+                    schema: plan.tbl_reference.schema.into_owned(),
+                    name: plan.tbl_reference.name.into_owned(),
+                    statistics: TableStatistics { num_rows, columns },
+                },
+            )],
+        )
+        .await
+        .map_err(|e| DataFusionError::Execution(format!("failed to analyze table: {e}")))?;
+
+    Ok(new_operation_batch("analyze_table"))
+}
+
+/// Get the first value of a statistic, cast to `data_type`.
+///
+/// Returns `None` if the source doesn't have the statistic.
+fn stat(
+    batch: &RecordBatch,
+    name: &str,
+    data_type: &DataType,
+) -> DataFusionResult<Option<ArrayRef>> {
+    match batch.schema().index_of(name) {
+        Ok(idx) => Ok(Some(cast(&batch.column(idx).slice(0, 1), data_type)?)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn stat_u64(batch: &RecordBatch, name: &str) -> DataFusionResult<Option<u64>> {
+    Ok(stat(batch, name, &DataType::UInt64)?.and_then(|arr| {
+        let arr = arr.as_primitive::<UInt64Type>();
+        arr.is_valid(0).then(|| arr.value(0))
+    }))
+}
+
+fn stat_string(batch: &RecordBatch, name: &str) -> DataFusionResult<Option<String>> {
+    Ok(stat(batch, name, &DataType::Utf8)?.and_then(|arr| {
+        let arr = arr.as_string::<i32>();
+        arr.is_valid(0).then(|| arr.value(0).to_string())
+    }))
+}
@@ -2,6 +2,8 @@ use datafusion::arrow::datatypes::Schema;
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::error::{DataFusionError, Result as DataFusionResult};
 use datafusion::execution::TaskContext;
+use datafusion::parquet::basic::Compression;
+use datafusion::parquet::file::properties::WriterVersion;
 use datafusion::physical_expr::PhysicalSortExpr;
 use datafusion::physical_plan::execute_stream;
 use datafusion::physical_plan::insert::DataSink;
@@ -13,7 +15,9 @@ use datafusion_ext::metrics::WriteOnlyDataSourceMetricsExecAdapter;
 use datasources::common::sink::bson::BsonSink;
 use datasources::common::sink::csv::{CsvSink, CsvSinkOpts};
 use datasources::common::sink::json::{JsonSink, JsonSinkOpts};
-use datasources::common::sink::parquet::{ParquetSink, ParquetSinkOpts};
+use datasources::common::sink::parquet::{
+    parse_compression, parse_writer_version, ParquetSink, ParquetSinkOpts,
+};
 use datasources::common::url::DatasourceUrl;
 use datasources::object_store::gcs::GcsStoreAccess;
 use datasources::object_store::generic::GenericStoreAccess;
@@ -190,13 +194,30 @@ fn get_sink_for_obj(
                 header: csv_opts.header,
             },
         )),
-        CopyToFormatOptions::Parquet(parquet_opts) => Box::new(ParquetSink::from_obj_store(
-            store,
-            path,
-            ParquetSinkOpts {
-                row_group_size: parquet_opts.row_group_size,
-            },
-        )),
+        CopyToFormatOptions::Parquet(parquet_opts) => {
+            let compression = match &parquet_opts.compression {
+                Some(codec) => parse_compression(codec, parquet_opts.compression_level)?,
+                None => Compression::UNCOMPRESSED,
+            };
+            let writer_version = match &parquet_opts.writer_version {
+                Some(version) => parse_writer_version(version)?,
+                None => WriterVersion::PARQUET_1_0,
+            };
+            Box::new(ParquetSink::from_obj_store(
+                store,
+                path,
+                ParquetSinkOpts {
+                    row_group_size: parquet_opts.row_group_size,
+                    bloom_filter_columns: parquet_opts.bloom_filter_columns,
+                    compression,
+                    dictionary: parquet_opts.dictionary,
+                    dictionary_enabled_columns: parquet_opts.dictionary_enabled_columns,
+                    dictionary_disabled_columns: parquet_opts.dictionary_disabled_columns,
+                    writer_version,
+                    target_file_size: parquet_opts.target_file_size,
+                },
+            ))
+        }
         CopyToFormatOptions::Json(json_opts) => Box::new(JsonSink::from_obj_store(
             store,
             path,
@@ -10,8 +10,9 @@ use datafusion::physical_plan::{
     SendableRecordBatchStream, Statistics,
 };
 use futures::stream;
+use protogen::metastore::types::catalog::CachedTableSchema;
 use protogen::metastore::types::options::TableOptions;
-use protogen::metastore::types::service::{self, Mutation};
+use protogen::metastore::types::service::{self, AlterTableOperation, Mutation};
 use std::any::Any;
 use std::fmt;
 use std::sync::Arc;
@@ -26,6 +27,7 @@ pub struct CreateExternalTableExec {
     pub if_not_exists: bool,
     pub table_options: TableOptions,
     pub tunnel: Option<String>,
+    pub schema: Option<CachedTableSchema>,
 }
 
 impl ExecutionPlan for CreateExternalTableExec {
@@ -97,20 +99,34 @@ async fn create_external_table(
     mutator: Arc<CatalogMutator>,
     plan: CreateExternalTableExec,
 ) -> DataFusionResult<RecordBatch> {
+    let database = plan.tbl_reference.database.into_owned();
+    let schema = plan.tbl_reference.schema.into_owned();
+    let name = plan.tbl_reference.name.into_owned();
+
+    let mut mutations = vec![Mutation::CreateExternalTable(
+        service::CreateExternalTable {
+            database: database.clone(),
+            schema: schema.clone(),
+            name: name.clone(),
+            options: plan.table_options,
+            or_replace: plan.or_replace,
+            if_not_exists: plan.if_not_exists,
+            tunnel: plan.tunnel,
+        },
+    )];
+    // Cache the schema inferred while planning so that scans don't need to
+    // infer it again.
+    if let Some(cached) = plan.schema {
+        mutations.push(Mutation::AlterTable(service::AlterTable {
+            database,
+            schema,
+            name,
+            operation: AlterTableOperation::RefreshSchema { schema: cached },
+        }));
+    }
+
     mutator
-        .mutate(
-            plan.catalog_version,
-            [Mutation::CreateExternalTable(
-                service::CreateExternalTable {
-                    schema: plan.tbl_reference.schema.into_owned(),
-                    name: plan.tbl_reference.name.into_owned(),
-                    options: plan.table_options,
-                    or_replace: plan.or_replace,
-                    if_not_exists: plan.if_not_exists,
-                    tunnel: plan.tunnel,
-                },
-            )],
-        )
+        .mutate(plan.catalog_version, mutations)
         .await
         .map_err(|e| DataFusionError::Execution(format!("failed to create external table: {e}")))?;
 
@@ -97,6 +97,7 @@ async fn create_schema(
         .mutate(
             plan.catalog_version,
             [Mutation::CreateSchema(service::CreateSchema {
+                database: plan.schema_reference.database.into_owned(),
                 name: plan.schema_reference.schema.into_owned(),
                 if_not_exists: plan.if_not_exists,
             })],
@@ -19,7 +19,6 @@ use datafusion::{
 use datasources::native::access::{NativeTable, NativeTableStorage, SaveMode};
 use futures::stream;
 use protogen::metastore::types::{service, service::Mutation};
-use sqlbuiltins::builtins::DEFAULT_CATALOG;
 use tracing::debug;
 
 use super::GENERIC_OPERATION_PHYSICAL_SCHEMA;
@@ -131,6 +130,7 @@ impl CreateTableExec {
             .mutate(
                 self.catalog_version,
                 [Mutation::CreateTable(service::CreateTable {
+                    database: self.tbl_reference.database.clone().into_owned(),
                     schema: self.tbl_reference.schema.clone().into_owned(),
                     name: self.tbl_reference.name.clone().into_owned(),
                     options: self.arrow_schema.into(),
@@ -167,7 +167,7 @@ impl CreateTableExec {
 
         let ent = new_catalog
             .resolve_table(
-                DEFAULT_CATALOG,
+                &self.tbl_reference.database,
                 &self.tbl_reference.schema,
                 &self.tbl_reference.name,
             )
@@ -24,6 +24,7 @@ pub struct CreateViewExec {
     pub sql: String,
     pub columns: Vec<String>,
     pub or_replace: bool,
+    pub dependencies: Vec<u32>,
 }
 
 impl ExecutionPlan for CreateViewExec {
@@ -99,11 +100,13 @@ async fn create_view(
         .mutate(
             plan.catalog_version,
             [Mutation::CreateView(service::CreateView {
+                database: plan.view_reference.database.into_owned(),
                 schema: plan.view_reference.schema.into_owned(),
                 name: plan.view_reference.name.into_owned(),
                 sql: plan.sql,
                 or_replace: plan.or_replace,
                 columns: plan.columns,
+                dependencies: plan.dependencies,
             })],
         )
         .await
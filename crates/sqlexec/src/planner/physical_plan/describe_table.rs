@@ -78,6 +78,7 @@ impl ExecutionPlan for DescribeTableExec {
             let mut column_names = StringBuilder::new();
             let mut data_types = StringBuilder::new();
             let mut is_nullables = BooleanBuilder::new();
+            let mut comments = StringBuilder::new();
 
             for col in internal_cols {
                 let name = col.name.clone();
@@ -88,6 +89,8 @@ impl ExecutionPlan for DescribeTableExec {
                 data_types.append_value(fmt_dtype(&data_type));
 
                 is_nullables.append_value(col.nullable);
+
+                comments.append_option(entry.column_comments.get(&col.name));
             }
 
             let output_schema = DESCRIBE_TABLE_SCHEMA.clone();
@@ -98,6 +101,7 @@ impl ExecutionPlan for DescribeTableExec {
                     Arc::new(column_names.finish()),
                     Arc::new(data_types.finish()),
                     Arc::new(is_nullables.finish()),
+                    Arc::new(comments.finish()),
                 ],
             )?;
             Ok(record_batch)
@@ -0,0 +1,187 @@
+use datafusion::arrow::datatypes::Schema;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::TaskContext;
+use datafusion::physical_plan::expressions::PhysicalSortExpr;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+    Statistics,
+};
+use futures::{stream, StreamExt, TryStreamExt};
+use protogen::gen::rpcsrv::service;
+use protogen::rpcsrv::types::service::ExecutePartitionRequest;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::remote_exec::read_arrow_ipc;
+use crate::remote::distribute::Worker;
+
+/// Executes the partitions of a plan across this node and a set of workers.
+///
+/// Partitions are assigned round robin, with this node taking the first
+/// partition of every round. Workers are sent the encoded plan and execute
+/// only the partition they were assigned, so every partition of `input` must
+/// contain the same rows no matter which node executes it.
+#[derive(Debug, Clone)]
+pub struct DistributedExec {
+    /// Plan executed for partitions assigned to this node.
+    input: Arc<dyn ExecutionPlan>,
+    /// Encoded form of `input` sent to workers. Scans in the encoded plan
+    /// reference providers cached on the workers.
+    encoded: Arc<Vec<u8>>,
+    /// Database the workers should execute in.
+    database_id: Uuid,
+    workers: Vec<Arc<Worker>>,
+}
+
+impl DistributedExec {
+    pub fn new(
+        input: Arc<dyn ExecutionPlan>,
+        encoded: Vec<u8>,
+        database_id: Uuid,
+        workers: Vec<Arc<Worker>>,
+    ) -> Self {
+        DistributedExec {
+            input,
+            encoded: Arc::new(encoded),
+            database_id,
+            workers,
+        }
+    }
+
+    /// Get the worker a partition is assigned to, or `None` if it's executed
+    /// on this node.
+    fn worker_for(&self, partition: usize) -> Option<usize> {
+        match partition % (self.workers.len() + 1) {
+            0 => None,
+            n => Some(n - 1),
+        }
+    }
+
+    fn execute_on_worker(
+        &self,
+        worker: usize,
+        partition: usize,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let worker = &self.workers[worker];
+        let mut client = worker.client();
+        let running = worker.start_partition();
+        let request = service::ExecutePartitionRequest::from(ExecutePartitionRequest {
+            database_id: self.database_id,
+            physical_plan: self.encoded.as_ref().clone(),
+            partition,
+        });
+
+        let stream = stream::once(async move {
+            let resp = client.execute_partition(request).await.map_err(|e| {
+                DataFusionError::Execution(format!(
+                    "failed to execute partition {partition} on worker: {e}"
+                ))
+            })?;
+
+            let batches = resp
+                .into_inner()
+                .map(|result| {
+                    let resp = result.map_err(|e| {
+                        DataFusionError::Execution(format!(
+                            "failed to read batch for partition {partition} from worker: {e}"
+                        ))
+                    })?;
+                    read_arrow_ipc(resp.compression, resp.arrow_ipc)
+                })
+                .map_ok(|batches| stream::iter(batches.into_iter().map(Ok)))
+                .try_flatten()
+                // Count the partition as running until the stream is done
+                // with.
+                .map(move |result| {
+                    let _running = &running;
+                    result
+                });
+            Ok::<_, DataFusionError>(batches)
+        })
+        .try_flatten();
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream,
+        )))
+    }
+}
+
+impl ExecutionPlan for DistributedExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Arc<Schema> {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        self.input.output_ordering()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::Plan(
+            "Cannot replace children for DistributedExec".to_string(),
+        ))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        match self.worker_for(partition) {
+            Some(worker) => self.execute_on_worker(worker, partition),
+            None => self.input.execute(partition, context),
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        self.input.statistics()
+    }
+}
+
+impl DisplayAs for DistributedExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DistributedExec: workers={}", self.workers.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::arrow::datatypes::{DataType, Field};
+    use datafusion::physical_plan::empty::EmptyExec;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn partitions_assigned_round_robin() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let input = Arc::new(EmptyExec::new(false, schema));
+        let workers = (0..2)
+            .map(|i| Arc::new(Worker::connect_lazy(format!("http://worker-{i}:6443")).unwrap()))
+            .collect();
+        let exec = DistributedExec::new(input, Vec::new(), Uuid::new_v4(), workers);
+
+        let assigned: Vec<_> = (0..6).map(|p| exec.worker_for(p)).collect();
+        assert_eq!(
+            vec![None, Some(0), Some(1), None, Some(0), Some(1)],
+            assigned
+        );
+    }
+}
@@ -99,6 +99,7 @@ async fn drop_schemas(
         .into_iter()
         .map(|r| {
             Mutation::DropSchema(service::DropSchema {
+                database: r.database.into_owned(),
                 name: r.schema.into_owned(),
                 if_exists: plan.if_exists,
                 cascade: plan.cascade,
@@ -22,6 +22,7 @@ pub struct DropTablesExec {
     pub catalog_version: u64,
     pub tbl_references: Vec<OwnedFullObjectReference>,
     pub if_exists: bool,
+    pub cascade: bool,
 }
 
 impl ExecutionPlan for DropTablesExec {
@@ -95,9 +96,11 @@ async fn drop_tables(
 ) -> DataFusionResult<RecordBatch> {
     let drops = plan.tbl_references.into_iter().map(|r| {
         Mutation::DropObject(service::DropObject {
+            database: r.database.into_owned(),
             schema: r.schema.into_owned(),
             name: r.name.into_owned(),
             if_exists: plan.if_exists,
+            cascade: plan.cascade,
         })
     });
 
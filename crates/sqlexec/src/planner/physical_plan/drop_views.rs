@@ -22,6 +22,7 @@ pub struct DropViewsExec {
     pub catalog_version: u64,
     pub view_references: Vec<OwnedFullObjectReference>,
     pub if_exists: bool,
+    pub cascade: bool,
 }
 
 impl ExecutionPlan for DropViewsExec {
@@ -98,9 +99,11 @@ async fn drop_views(
         .into_iter()
         .map(|r| {
             Mutation::DropObject(service::DropObject {
+                database: r.database.into_owned(),
                 name: r.name.into_owned(),
                 schema: r.schema.into_owned(),
                 if_exists: plan.if_exists,
+                cascade: plan.cascade,
             })
         })
         .collect();
@@ -0,0 +1,113 @@
+use datafusion::arrow::datatypes::Schema;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::{
+    stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use datafusion_ext::vars::SessionVars;
+use futures::stream;
+use protogen::metastore::types::service::Mutation;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use super::{new_operation_batch, GENERIC_OPERATION_PHYSICAL_SCHEMA};
+
+/// Writes a catalog dump to a local file.
+#[derive(Debug, Clone)]
+pub struct ExportCatalogExec {
+    pub path: String,
+    /// Mutations making up the dump, see [`catalog::dump::dump_catalog`].
+    pub mutations: Vec<Mutation>,
+    /// Key to encrypt the dump with.
+    pub encryption_key: Option<String>,
+}
+
+impl ExecutionPlan for ExportCatalogExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Arc<Schema> {
+        GENERIC_OPERATION_PHYSICAL_SCHEMA.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        Vec::new()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::Plan(
+            "Cannot change children for ExportCatalogExec".to_string(),
+        ))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Execution(
+                "ExportCatalogExec only supports 1 partition".to_string(),
+            ));
+        }
+
+        // The planner only allows exporting from embedded sessions, but a
+        // plan may also be sent to a remote server directly.
+        let is_embedded = context
+            .session_config()
+            .options()
+            .extensions
+            .get::<SessionVars>()
+            .map(|vars| vars.is_embedded())
+            .unwrap_or(false);
+        if !is_embedded {
+            return Err(DataFusionError::Execution(
+                "Exporting the catalog is only supported when running GlareDB locally".to_string(),
+            ));
+        }
+
+        let stream = stream::once(export_catalog(self.clone()));
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+impl DisplayAs for ExportCatalogExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ExportCatalogExec")
+    }
+}
+
+async fn export_catalog(plan: ExportCatalogExec) -> DataFusionResult<RecordBatch> {
+    let buf = catalog::dump::encode_dump(plan.mutations, plan.encryption_key.as_deref())
+        .map_err(|e| DataFusionError::Execution(format!("failed to encode catalog: {e}")))?;
+
+    tokio::fs::write(&plan.path, buf)
+        .await
+        .map_err(|e| DataFusionError::Execution(format!("failed to export catalog: {e}")))?;
+
+    Ok(new_operation_batch("export_catalog"))
+}
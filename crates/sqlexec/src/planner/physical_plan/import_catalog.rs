@@ -0,0 +1,163 @@
+use catalog::mutator::CatalogMutator;
+use catalog::session_catalog::{ResolveConfig, SessionCatalog};
+use datafusion::arrow::datatypes::Schema;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::{
+    stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use datafusion_ext::vars::SessionVars;
+use datasources::native::access::{NativeTableStorage, SaveMode};
+use futures::stream;
+use protogen::metastore::types::service::Mutation;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use super::{new_operation_batch, GENERIC_OPERATION_PHYSICAL_SCHEMA};
+
+/// Replays the mutations from a catalog dump.
+///
+/// Requires an admin since the dump may alter roles and create credentials.
+#[derive(Debug, Clone)]
+pub struct ImportCatalogExec {
+    pub catalog_version: u64,
+    pub mutations: Vec<Mutation>,
+}
+
+impl ExecutionPlan for ImportCatalogExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Arc<Schema> {
+        GENERIC_OPERATION_PHYSICAL_SCHEMA.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        Vec::new()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::Plan(
+            "Cannot change children for ImportCatalogExec".to_string(),
+        ))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Execution(
+                "ImportCatalogExec only supports 1 partition".to_string(),
+            ));
+        }
+
+        // Checked again since the plan may have been sent by a remote
+        // client, whose session isn't necessarily an admin on this server.
+        let is_admin = context
+            .session_config()
+            .options()
+            .extensions
+            .get::<SessionVars>()
+            .map(|vars| vars.is_admin())
+            .unwrap_or(false);
+        if !is_admin {
+            return Err(DataFusionError::Execution(
+                "Permission denied: IMPORT CATALOG requires an admin".to_string(),
+            ));
+        }
+
+        let mutator = context
+            .session_config()
+            .get_extension::<CatalogMutator>()
+            .expect("context should have catalog mutator");
+        let storage = context
+            .session_config()
+            .get_extension::<NativeTableStorage>()
+            .expect("context should have native table storage");
+
+        let stream = stream::once(import_catalog(mutator, storage, self.clone()));
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+impl DisplayAs for ImportCatalogExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ImportCatalogExec")
+    }
+}
+
+async fn import_catalog(
+    mutator: Arc<CatalogMutator>,
+    storage: Arc<NativeTableStorage>,
+    plan: ImportCatalogExec,
+) -> DataFusionResult<RecordBatch> {
+    let native_tables: Vec<_> = plan
+        .mutations
+        .iter()
+        .filter_map(|m| match m {
+            Mutation::CreateTable(create) => Some(create.clone()),
+            _ => None,
+        })
+        .collect();
+
+    // All mutations are applied at once, so a failing import doesn't leave the
+    // catalog partially restored.
+    let state = mutator
+        .mutate(plan.catalog_version, plan.mutations)
+        .await
+        .map_err(|e| DataFusionError::Execution(format!("failed to import catalog: {e}")))?;
+
+    // Dumps only contain the definitions of native tables, create empty
+    // tables in storage for them.
+    let catalog = SessionCatalog::new(
+        state,
+        ResolveConfig {
+            default_schema_oid: 0,
+            session_schema_oid: 0,
+        },
+    );
+    for create in native_tables {
+        let ent = catalog
+            .resolve_table(&create.database, &create.schema, &create.name)
+            .ok_or_else(|| {
+                DataFusionError::Internal(format!(
+                    "missing table '{}.{}.{}' after import",
+                    create.database, create.schema, create.name
+                ))
+            })?;
+        storage
+            .create_table(ent, SaveMode::ErrorIfExists)
+            .await
+            .map_err(|e| {
+                DataFusionError::Execution(format!("failed to create table in storage: {e}"))
+            })?;
+    }
+
+    Ok(new_operation_batch("import_catalog"))
+}
@@ -1,11 +1,16 @@
 pub mod alter_database;
+pub mod alter_role;
+pub mod alter_schema;
 pub mod alter_table;
 pub mod alter_tunnel_rotate_keys;
+pub mod analyze_table;
 pub mod client_recv;
 pub mod client_send;
+pub mod comment_on;
 pub mod copy_to;
 pub mod create_credential;
 pub mod create_credentials;
+pub mod create_database;
 pub mod create_external_database;
 pub mod create_external_table;
 pub mod create_schema;
@@ -15,6 +20,7 @@ pub mod create_tunnel;
 pub mod create_view;
 pub mod delete;
 pub mod describe_table;
+pub mod distributed;
 pub mod drop_credentials;
 pub mod drop_database;
 pub mod drop_schemas;
@@ -22,13 +28,18 @@ pub mod drop_tables;
 pub mod drop_temp_tables;
 pub mod drop_tunnel;
 pub mod drop_views;
+pub mod export_catalog;
+pub mod import_catalog;
 pub mod insert;
 pub mod remote_exec;
 pub mod remote_scan;
+pub mod restore_catalog;
 pub mod send_recv;
 pub mod set_var;
 pub mod show_var;
 pub mod update;
+pub mod upsert;
+pub mod use_database;
 pub mod values;
 
 use datafusion::arrow::array::{StringArray, UInt64Array};
@@ -104,3 +115,41 @@ pub fn get_count_from_batch(batch: &RecordBatch) -> Option<u64> {
     }
     None
 }
+
+/// Returns whether or not executing the plan would modify the catalog or any
+/// tables.
+pub fn is_write_plan(plan: &Arc<dyn ExecutionPlan>) -> bool {
+    let any = plan.as_any();
+    let is_write = any.is::<alter_database::AlterDatabaseExec>()
+        || any.is::<alter_role::AlterRoleExec>()
+        || any.is::<alter_schema::AlterSchemaExec>()
+        || any.is::<alter_table::AlterTableExec>()
+        || any.is::<alter_tunnel_rotate_keys::AlterTunnelRotateKeysExec>()
+        || any.is::<analyze_table::AnalyzeTableExec>()
+        || any.is::<comment_on::CommentOnExec>()
+        || any.is::<create_credential::CreateCredentialExec>()
+        || any.is::<create_credentials::CreateCredentialsExec>()
+        || any.is::<create_database::CreateDatabaseExec>()
+        || any.is::<create_external_database::CreateExternalDatabaseExec>()
+        || any.is::<create_external_table::CreateExternalTableExec>()
+        || any.is::<create_schema::CreateSchemaExec>()
+        || any.is::<create_table::CreateTableExec>()
+        || any.is::<create_temp_table::CreateTempTableExec>()
+        || any.is::<create_tunnel::CreateTunnelExec>()
+        || any.is::<create_view::CreateViewExec>()
+        || any.is::<delete::DeleteExec>()
+        || any.is::<drop_credentials::DropCredentialsExec>()
+        || any.is::<drop_database::DropDatabaseExec>()
+        || any.is::<drop_schemas::DropSchemasExec>()
+        || any.is::<drop_tables::DropTablesExec>()
+        || any.is::<drop_temp_tables::DropTempTablesExec>()
+        || any.is::<drop_tunnel::DropTunnelExec>()
+        || any.is::<drop_views::DropViewsExec>()
+        || any.is::<import_catalog::ImportCatalogExec>()
+        || any.is::<insert::InsertExec>()
+        || any.is::<restore_catalog::RestoreCatalogExec>()
+        || any.is::<update::UpdateExec>()
+        || any.is::<upsert::UpsertExec>();
+
+    is_write || plan.children().iter().any(is_write_plan)
+}
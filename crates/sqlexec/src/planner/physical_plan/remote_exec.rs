@@ -9,18 +9,25 @@ use datafusion::physical_plan::{
     DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
     Statistics,
 };
+use datafusion_ext::vars::{RemoteCompression, SessionVars};
 use futures::{stream, Stream, StreamExt, TryStreamExt};
+use protogen::gen::rpcsrv::common::CompressionCodec;
 use protogen::gen::rpcsrv::service::RecordBatchResponse;
 use std::any::Any;
-use std::collections::VecDeque;
 use std::fmt;
 use std::io::Cursor;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use tonic::Streaming;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tonic::{Code, Status, Streaming};
+use tracing::warn;
+use uuid::Uuid;
 
 use crate::remote::client::RemoteSessionClient;
+use crate::remote::compression;
 
 /// Execute a physical plan on a remote service.
 #[derive(Debug, Clone)]
@@ -81,7 +88,7 @@ impl ExecutionPlan for RemoteExecutionExec {
     fn execute(
         &self,
         partition: usize,
-        _context: Arc<TaskContext>,
+        context: Arc<TaskContext>,
     ) -> DataFusionResult<SendableRecordBatchStream> {
         // TODO: Behavior is unknown when executing with more than one
         // partition.
@@ -89,15 +96,33 @@ impl ExecutionPlan for RemoteExecutionExec {
             return Err(DataFusionError::Execution(format!("RemoteExecutionExec only supports 1 partition, got request for partition {partition}")));
         }
 
-        let stream = stream::once(execute_remote(
+        let vars = context
+            .session_config()
+            .options()
+            .extensions
+            .get::<SessionVars>();
+        let buffer_size = vars
+            .map(|vars| vars.exchange_buffer_size())
+            .unwrap_or(1)
+            .max(1);
+        let opts = vars
+            .map(|vars| ResultStreamOptions {
+                retries: vars.remote_stream_retries(),
+                compression: vars.remote_compression(),
+                compression_level: vars.remote_compression_level(),
+            })
+            .unwrap_or_default();
+
+        let stream = execute_remote(
             self.client.clone(),
             self.plan.clone(),
             self.query_text.clone(),
-        ))
-        .try_flatten();
+            opts,
+        );
+
         Ok(Box::pin(RecordBatchStreamAdapter::new(
             self.schema(),
-            stream,
+            BoundedExchangeStream::new(stream, buffer_size),
         )))
     }
 
@@ -116,82 +141,254 @@ impl DisplayAs for RemoteExecutionExec {
     }
 }
 
-/// Execute the encoded logical plan on the remote service.
-async fn execute_remote(
-    mut client: RemoteSessionClient,
+/// Reads batches from the remote stream in a separate task, holding at most
+/// `buffer_size` batches that haven't been consumed yet.
+///
+/// Once the buffer is full we stop pulling from the remote stream, and grpc
+/// flow control makes the remote side wait for us. Without this a slow local
+/// side would let decoded batches pile up in memory.
+struct BoundedExchangeStream {
+    recv: mpsc::Receiver<DataFusionResult<RecordBatch>>,
+    /// Task reading from the remote stream, aborted when this stream is
+    /// dropped.
+    handle: JoinHandle<()>,
+}
+
+impl BoundedExchangeStream {
+    fn new<S>(stream: S, buffer_size: usize) -> Self
+    where
+        S: Stream<Item = DataFusionResult<RecordBatch>> + Send + 'static,
+    {
+        let (send, recv) = mpsc::channel(buffer_size);
+        let handle = tokio::spawn(async move {
+            let mut stream = Box::pin(stream);
+            while let Some(result) = stream.next().await {
+                let is_err = result.is_err();
+                if send.send(result).await.is_err() || is_err {
+                    // Receiver dropped, or we've sent the error that ends the
+                    // stream.
+                    return;
+                }
+            }
+        });
+
+        BoundedExchangeStream { recv, handle }
+    }
+}
+
+impl Stream for BoundedExchangeStream {
+    type Item = DataFusionResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.recv.poll_recv(cx)
+    }
+}
+
+impl Drop for BoundedExchangeStream {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Execute the physical plan on the remote service.
+///
+/// If the connection drops while reading results, the execution is resumed
+/// from the first batch we haven't received yet. The remote side keeps the
+/// execution around for a short grace period for this.
+fn execute_remote(
+    client: RemoteSessionClient,
     plan: Arc<dyn ExecutionPlan>,
     query_text: String,
-) -> DataFusionResult<ExecutionResponseBatchStream> {
-    let stream = client
-        .physical_plan_execute(plan, query_text)
-        .await
-        .map_err(|e| {
-            DataFusionError::Execution(format!(
-                "failed to execute physical plan on remote service: {e}"
-            ))
-        })?;
-
-    Ok(ExecutionResponseBatchStream {
-        stream,
-        buf: VecDeque::new(),
-    })
+    opts: ResultStreamOptions,
+) -> impl Stream<Item = DataFusionResult<RecordBatch>> + Send {
+    stream::once(ResumableResults::start(client, plan, query_text, opts))
+        .map_ok(|results| {
+            stream::try_unfold(results, |mut results| async move {
+                let batches = results.next_batches().await?;
+                Ok(batches.map(|batches| (batches, results)))
+            })
+        })
+        .try_flatten()
+        .map_ok(|batches| stream::iter(batches.into_iter().map(Ok::<_, DataFusionError>)))
+        .try_flatten()
 }
 
-/// Converts a response stream from the service into a record batch stream.
-// TODO: StreamReader instead of FileReader.
-struct ExecutionResponseBatchStream {
+/// Options for reading results from the remote service.
+#[derive(Debug, Clone, Copy, Default)]
+struct ResultStreamOptions {
+    /// Maximum number of attempts at resuming without receiving anything
+    /// new.
+    retries: usize,
+    /// Compression to request for result batches.
+    compression: RemoteCompression,
+    compression_level: i32,
+}
+
+/// Reads sequence numbered responses from the remote service.
+struct ResumableResults {
+    client: RemoteSessionClient,
+    execution_id: Uuid,
     /// Stream we're reading from.
     stream: Streaming<RecordBatchResponse>,
-
-    /// Buffer in case the ipc message contains more than one batch.
-    buf: VecDeque<DataFusionResult<RecordBatch>>,
+    /// Sequence number of the next response we expect.
+    next_sequence: u64,
+    /// Maximum number of attempts at resuming without receiving anything
+    /// new.
+    retries: usize,
+    /// Attempts at resuming since the last new response.
+    attempts: usize,
+    /// If the execution completed or errored. Unfinished executions are
+    /// cancelled on the remote side when dropped.
+    finished: bool,
 }
 
-impl Stream for ExecutionResponseBatchStream {
-    type Item = DataFusionResult<RecordBatch>;
+impl ResumableResults {
+    async fn start(
+        mut client: RemoteSessionClient,
+        plan: Arc<dyn ExecutionPlan>,
+        query_text: String,
+        opts: ResultStreamOptions,
+    ) -> DataFusionResult<Self> {
+        let execution_id = Uuid::new_v4();
+        let stream = client
+            .physical_plan_execute(
+                plan,
+                query_text,
+                execution_id,
+                opts.compression,
+                opts.compression_level,
+            )
+            .await
+            .map_err(|e| {
+                DataFusionError::Execution(format!(
+                    "failed to execute physical plan on remote service: {e}"
+                ))
+            })?;
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        // Check buffer first.
-        if let Some(batch) = self.buf.pop_front() {
-            return Poll::Ready(Some(batch));
+        Ok(ResumableResults {
+            client,
+            execution_id,
+            stream,
+            next_sequence: 0,
+            retries: opts.retries,
+            attempts: 0,
+            finished: false,
+        })
+    }
+
+    /// Get the batches in the next response, returning `None` once the
+    /// execution completes.
+    async fn next_batches(&mut self) -> DataFusionResult<Option<Vec<RecordBatch>>> {
+        let result = self.next_response().await;
+        if !matches!(result, Ok(Some(_))) {
+            self.finished = true;
         }
+        result
+    }
 
-        // Pull from stream.
-        match self.stream.poll_next_unpin(cx) {
-            Poll::Ready(Some(resp)) => match resp {
-                Ok(resp) => {
-                    let cursor = Cursor::new(resp.arrow_ipc);
-                    let reader = match IpcFileReader::try_new(cursor, None) {
-                        Ok(reader) => reader,
-                        Err(e) => {
-                            return Poll::Ready(Some(Err(DataFusionError::Execution(format!(
-                                "failed to create arrow ipc reader: {e}"
-                            )))))
-                        }
-                    };
-
-                    // Extend out buffer with batches from the ipc reader.
-                    self.buf
-                        .extend(reader.into_iter().map(|result| match result {
-                            Ok(batch) => Ok(batch),
-                            Err(e) => Err(DataFusionError::ArrowError(e)),
-                        }));
-
-                    // See if we got anything.
-                    match self.buf.pop_front() {
-                        Some(result) => Poll::Ready(Some(result)),
-                        None => Poll::Ready(None),
+    async fn next_response(&mut self) -> DataFusionResult<Option<Vec<RecordBatch>>> {
+        loop {
+            match self.stream.message().await {
+                Ok(Some(resp)) => {
+                    // Responses we've already received get replayed when
+                    // resuming from a point earlier than what the remote side
+                    // has buffered.
+                    if resp.sequence < self.next_sequence {
+                        continue;
                     }
+                    self.next_sequence = resp.sequence + 1;
+                    self.attempts = 0;
+                    return read_arrow_ipc(resp.compression, resp.arrow_ipc).map(Some);
                 }
-                Err(e) => {
-                    let msg = e.message();
-                    Poll::Ready(Some(Err(DataFusionError::Execution(format!(
+                Ok(None) => return Ok(None),
+                Err(status) if is_disconnect(&status) => self.resume(status).await?,
+                Err(status) => {
+                    let msg = status.message();
+                    return Err(DataFusionError::Execution(format!(
                         "Remote node error: {msg}"
-                    )))))
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Reconnect to the remote side, continuing from the next response.
+    async fn resume(&mut self, status: Status) -> DataFusionResult<()> {
+        let mut last_err = status.to_string();
+        while self.attempts < self.retries {
+            tokio::time::sleep(RESUME_BACKOFF * 2_u32.pow(self.attempts as u32)).await;
+            self.attempts += 1;
+
+            warn!(
+                execution_id=%self.execution_id,
+                sequence=%self.next_sequence,
+                attempt=%self.attempts,
+                %last_err,
+                "resuming remote execution",
+            );
+            match self
+                .client
+                .resume_execution(self.execution_id, self.next_sequence)
+                .await
+            {
+                Ok(stream) => {
+                    self.stream = stream;
+                    return Ok(());
                 }
-            },
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(None) => Poll::Ready(None),
+                Err(e) => last_err = e.to_string(),
+            }
         }
+
+        Err(DataFusionError::Execution(format!(
+            "Remote node error: {last_err}"
+        )))
     }
 }
+
+impl Drop for ResumableResults {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        // Dropped before reading all results, e.g. the query was cancelled or
+        // had a limit. Tell the remote side instead of having it hold on to
+        // the execution until it expires.
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let mut client = self.client.clone();
+        let execution_id = self.execution_id;
+        handle.spawn(async move {
+            if let Err(e) = client.cancel_execution(execution_id).await {
+                warn!(%execution_id, %e, "failed to cancel remote execution");
+            }
+        });
+    }
+}
+
+/// Delay before the first attempt at resuming, doubled for every subsequent
+/// attempt.
+const RESUME_BACKOFF: Duration = Duration::from_millis(500);
+
+/// If the error is from the connection to the remote side, and not an error
+/// sent by the remote side.
+fn is_disconnect(status: &Status) -> bool {
+    status.code() == Code::Unavailable || std::error::Error::source(status).is_some()
+}
+
+// TODO: StreamReader instead of FileReader.
+pub(crate) fn read_arrow_ipc(compression: i32, buf: Vec<u8>) -> DataFusionResult<Vec<RecordBatch>> {
+    let compression = CompressionCodec::try_from(compression).map_err(|_| {
+        DataFusionError::Execution(format!("unknown result compression: {compression}"))
+    })?;
+    let buf = compression::decompress(compression, buf)
+        .map_err(|e| DataFusionError::Execution(format!("failed to decompress batch: {e}")))?;
+
+    let reader = IpcFileReader::try_new(Cursor::new(buf), None).map_err(|e| {
+        DataFusionError::Execution(format!("failed to create arrow ipc reader: {e}"))
+    })?;
+    reader
+        .into_iter()
+        .map(|result| result.map_err(DataFusionError::ArrowError))
+        .collect()
+}
@@ -76,6 +76,9 @@ pub struct RemoteScanExec {
     pub projection: Option<Vec<usize>>,
     pub filters: Vec<Expr>,
     pub limit: Option<usize>,
+    /// Estimated statistics for the scan, only used for planning on the local
+    /// side.
+    pub statistics: Statistics,
     metrics: ExecutionPlanMetricsSet,
 }
 
@@ -89,6 +92,7 @@ impl RemoteScanExec {
         projection: Option<Vec<usize>>,
         filters: Vec<Expr>,
         limit: Option<usize>,
+        statistics: Statistics,
     ) -> RuntimeGroupExec {
         RuntimeGroupExec::new(
             RuntimePreference::Remote,
@@ -98,10 +102,24 @@ impl RemoteScanExec {
                 projection,
                 filters,
                 limit,
+                statistics,
                 metrics: ExecutionPlanMetricsSet::new(),
             }),
         )
     }
+
+    /// Create a copy of this scan reading from a different provider.
+    pub fn with_provider(&self, provider: ProviderReference) -> Self {
+        RemoteScanExec {
+            provider,
+            projected_schema: self.projected_schema.clone(),
+            projection: self.projection.clone(),
+            filters: self.filters.clone(),
+            limit: self.limit,
+            statistics: self.statistics.clone(),
+            metrics: ExecutionPlanMetricsSet::new(),
+        }
+    }
 }
 
 impl ExecutionPlan for RemoteScanExec {
@@ -192,7 +210,7 @@ impl ExecutionPlan for RemoteScanExec {
     }
 
     fn statistics(&self) -> Statistics {
-        Statistics::default()
+        self.statistics.clone()
     }
 
     fn metrics(&self) -> Option<MetricsSet> {
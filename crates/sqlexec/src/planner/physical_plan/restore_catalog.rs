@@ -0,0 +1,123 @@
+use catalog::mutator::CatalogMutator;
+use datafusion::arrow::datatypes::Schema;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::{
+    stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use datafusion_ext::vars::SessionVars;
+use futures::stream;
+use protogen::metastore::types::service::{self, Mutation};
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use super::{new_operation_batch, GENERIC_OPERATION_PHYSICAL_SCHEMA};
+
+/// Restores the catalog to how it was at some point in time.
+#[derive(Debug, Clone)]
+pub struct RestoreCatalogExec {
+    pub catalog_version: u64,
+    pub timestamp: SystemTime,
+}
+
+impl ExecutionPlan for RestoreCatalogExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Arc<Schema> {
+        GENERIC_OPERATION_PHYSICAL_SCHEMA.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        Vec::new()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::Plan(
+            "Cannot change children for RestoreCatalogExec".to_string(),
+        ))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Execution(
+                "RestoreCatalogExec only supports 1 partition".to_string(),
+            ));
+        }
+
+        // Checked again since the plan may have been sent by a remote
+        // client, whose session isn't necessarily an admin on this server.
+        let is_admin = context
+            .session_config()
+            .options()
+            .extensions
+            .get::<SessionVars>()
+            .map(|vars| vars.is_admin())
+            .unwrap_or(false);
+        if !is_admin {
+            return Err(DataFusionError::Execution(
+                "Permission denied: RESTORE CATALOG requires an admin".to_string(),
+            ));
+        }
+
+        let mutator = context
+            .session_config()
+            .get_extension::<CatalogMutator>()
+            .expect("context should have catalog mutator");
+
+        let stream = stream::once(restore_catalog(mutator, self.clone()));
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+impl DisplayAs for RestoreCatalogExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RestoreCatalogExec")
+    }
+}
+
+async fn restore_catalog(
+    mutator: Arc<CatalogMutator>,
+    plan: RestoreCatalogExec,
+) -> DataFusionResult<RecordBatch> {
+    mutator
+        .mutate(
+            plan.catalog_version,
+            [Mutation::RestoreCatalog(service::RestoreCatalog {
+                timestamp: plan.timestamp,
+            })],
+        )
+        .await
+        .map_err(|e| DataFusionError::Execution(format!("failed to restore catalog: {e}")))?;
+
+    Ok(new_operation_batch("restore_catalog"))
+}
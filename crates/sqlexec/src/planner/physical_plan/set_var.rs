@@ -71,8 +71,10 @@ impl ExecutionPlan for SetVarExec {
                 .get::<SessionVars>()
                 .expect("context should have SessionVars extension");
 
+            // Always set as the user, the plan may have been sent by a remote
+            // client.
             let mut vars = vars.write();
-            vars.set(&this.variable, &this.values, VarType::UserDefined)?;
+            vars.set_lenient(&this.variable, &this.values, VarType::UserDefined)?;
 
             Ok(new_operation_batch("set"))
         });
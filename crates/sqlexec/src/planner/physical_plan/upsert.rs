@@ -0,0 +1,127 @@
+use datafusion::arrow::datatypes::Schema;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::context::{SessionContext, SessionState};
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::{
+    stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use datafusion::prelude::Expr;
+use datafusion_ext::metrics::WriteOnlyDataSourceMetricsExecAdapter;
+use datasources::native::access::NativeTableStorage;
+use futures::stream;
+use protogen::metastore::types::catalog::TableEntry;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use super::{new_operation_with_count_batch, GENERIC_OPERATION_AND_COUNT_PHYSICAL_SCHEMA};
+
+#[derive(Debug, Clone)]
+pub struct UpsertExec {
+    pub table: TableEntry,
+    pub source: Arc<WriteOnlyDataSourceMetricsExecAdapter>,
+    pub conflict_columns: Vec<String>,
+    pub updates: Option<Vec<(String, Expr)>>,
+    pub where_expr: Option<Expr>,
+}
+
+impl ExecutionPlan for UpsertExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Arc<Schema> {
+        GENERIC_OPERATION_AND_COUNT_PHYSICAL_SCHEMA.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.source.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(UpsertExec {
+            source: Arc::new(WriteOnlyDataSourceMetricsExecAdapter::new(
+                children.first().unwrap().clone(),
+            )),
+            ..self.as_ref().clone()
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Execution(
+                "UpsertExec only supports 1 partition".to_string(),
+            ));
+        }
+
+        let storage = context
+            .session_config()
+            .get_extension::<NativeTableStorage>()
+            .expect("context should have native table storage");
+
+        let stream = stream::once(upsert(self.clone(), storage, context));
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+impl DisplayAs for UpsertExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "UpsertExec")
+    }
+}
+
+async fn upsert(
+    plan: UpsertExec,
+    storage: impl AsRef<NativeTableStorage>,
+    context: Arc<TaskContext>,
+) -> DataFusionResult<RecordBatch> {
+    let storage = storage.as_ref();
+
+    let batches = datafusion::physical_plan::collect(plan.source.clone(), context.clone()).await?;
+    if batches.iter().all(|batch| batch.num_rows() == 0) {
+        return Ok(new_operation_with_count_batch("insert", 0));
+    }
+
+    let state =
+        SessionState::new_with_config_rt(context.session_config().clone(), context.runtime_env());
+    let source = SessionContext::new_with_state(state).read_batches(batches)?;
+
+    let num_rows = storage
+        .upsert_rows(
+            &plan.table,
+            source,
+            &plan.conflict_columns,
+            plan.updates,
+            plan.where_expr,
+        )
+        .await
+        .map_err(|e| DataFusionError::Execution(format!("failed to upsert: {e}")))?;
+
+    Ok(new_operation_with_count_batch("insert", num_rows as u64))
+}
@@ -0,0 +1,147 @@
+use catalog::mutator::CatalogMutator;
+use catalog::session_catalog::{ResolveConfig, SessionCatalog};
+use datafusion::arrow::datatypes::Schema;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::TaskContext;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::{
+    stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use datafusion::variable::VarType;
+use datafusion_ext::vars::SessionVars;
+use futures::stream;
+use protogen::metastore::types::catalog::DatabaseEntry;
+use protogen::metastore::types::options::DatabaseOptions;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use super::{new_operation_batch, GENERIC_OPERATION_PHYSICAL_SCHEMA};
+
+/// Set the native database of the session for `USE <database>`.
+///
+/// The `database` variable can't be set by users directly. The database is
+/// checked to be a native database when executed rather than when planned,
+/// since the plan may have been sent by a remote client.
+#[derive(Debug, Clone)]
+pub struct UseDatabaseExec {
+    pub database: String,
+}
+
+impl ExecutionPlan for UseDatabaseExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> Arc<Schema> {
+        GENERIC_OPERATION_PHYSICAL_SCHEMA.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        Vec::new()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::Plan(
+            "cannot change children for UseDatabaseExec".to_string(),
+        ))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Execution(
+                "UseDatabaseExec only supports 1 partition".to_string(),
+            ));
+        }
+
+        let vars = context
+            .session_config()
+            .options()
+            .extensions
+            .get::<SessionVars>()
+            .expect("context should have SessionVars extension")
+            .clone();
+        let mutator = context
+            .session_config()
+            .get_extension::<CatalogMutator>()
+            .expect("context should have catalog mutator");
+
+        let stream = stream::once(use_database(mutator, vars, self.database.clone()));
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream,
+        )))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+impl DisplayAs for UseDatabaseExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "UseDatabaseExec")
+    }
+}
+
+async fn use_database(
+    mutator: Arc<CatalogMutator>,
+    vars: SessionVars,
+    database: String,
+) -> DataFusionResult<RecordBatch> {
+    // Sessions attached to a remote server don't have a metastore client, and
+    // only execute plans they planned themselves, where the database has
+    // already been checked against the catalog from the remote.
+    if let Some(client) = mutator.get_metastore_client() {
+        let state = client
+            .get_cached_state()
+            .await
+            .map_err(|e| DataFusionError::Execution(format!("failed to get catalog: {e}")))?;
+        let catalog = SessionCatalog::new(
+            state,
+            ResolveConfig {
+                default_schema_oid: 0,
+                session_schema_oid: 0,
+            },
+        );
+        check_native_database(&catalog, &database)?;
+    }
+
+    vars.write().set("database", &database, VarType::System)?;
+
+    Ok(new_operation_batch("use"))
+}
+
+/// Check that the database exists and is a native database.
+fn check_native_database(catalog: &SessionCatalog, database: &str) -> DataFusionResult<()> {
+    match catalog.resolve_database(database) {
+        Some(DatabaseEntry {
+            options: DatabaseOptions::Internal(_),
+            ..
+        }) => Ok(()),
+        Some(_) => Err(DataFusionError::Execution(format!(
+            "'{database}' is an external database, only native databases can be used"
+        ))),
+        None => Err(DataFusionError::Execution(format!(
+            "database '{database}' does not exist"
+        ))),
+    }
+}
@@ -1,7 +1,6 @@
 //! AST visitors for preprocessing queries before planning.
 use crate::context::local::LocalSessionContext;
 use datafusion::sql::sqlparser::ast::{self, VisitMut, VisitorMut};
-use sqlbuiltins::builtins::DEFAULT_CATALOG;
 use std::ops::ControlFlow;
 
 #[derive(Debug, thiserror::Error)]
@@ -35,9 +34,10 @@ impl<'a> ast::VisitorMut for CastRegclassReplacer<'a> {
     fn post_visit_expr(&mut self, expr: &mut ast::Expr) -> ControlFlow<Self::Break> {
         fn find_oid(ctx: &LocalSessionContext, rel: &str) -> Option<u32> {
             let catalog = ctx.get_session_catalog();
+            let database = ctx.get_session_vars().database();
             for schema in ctx.implicit_search_paths() {
                 // TODO
-                if let Some(ent) = catalog.resolve_entry(DEFAULT_CATALOG, &schema, rel) {
+                if let Some(ent) = catalog.resolve_entry(&database, &schema, rel) {
                     // Table found.
                     return Some(ent.get_meta().id);
                 }
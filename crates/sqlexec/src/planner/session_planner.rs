@@ -1,20 +1,31 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
+use datafusion::arrow::compute::can_cast_types;
+use datafusion::arrow::compute::kernels::cast_utils::string_to_timestamp_nanos;
 use datafusion::arrow::datatypes::{
     DataType, Field, Schema, TimeUnit, DECIMAL128_MAX_PRECISION, DECIMAL_DEFAULT_SCALE,
 };
 use datafusion::common::parsers::CompressionTypeVariant;
-use datafusion::common::{FileType, OwnedSchemaReference, OwnedTableReference, ToDFSchema};
-use datafusion::logical_expr::{cast, col, LogicalPlanBuilder};
+use datafusion::common::{
+    DFSchema, FileType, OwnedSchemaReference, OwnedTableReference, ToDFSchema,
+};
+use datafusion::logical_expr::{cast, col, count, expr, lit, max, min, Expr, LogicalPlanBuilder};
 use datafusion::sql::planner::{object_name_to_table_reference, IdentNormalizer, PlannerContext};
 use datafusion::sql::sqlparser::ast::{self, Ident, ObjectName, ObjectType};
 use datafusion::sql::TableReference;
-use datafusion_ext::planner::SqlQueryPlanner;
+use datafusion::variable::VarType;
+use datafusion_ext::planner::utils::is_geometry_sql_type;
+use datafusion_ext::planner::{parse_column_types, SqlQueryPlanner};
+use datafusion_ext::types::{uuid_field, GEOMETRY_DATA_TYPE, UUID_DATA_TYPE};
+use datafusion_ext::vars::SessionVars;
 use datafusion_ext::AsyncContextProvider;
 use datasources::bigquery::{BigQueryAccessor, BigQueryTableAccess};
 use datasources::clickhouse::ClickhouseAccess;
+use datasources::common::sink::parquet;
 use datasources::common::ssh::{key::SshKey, SshConnection, SshConnectionParameters};
 use datasources::common::url::{DatasourceUrl, DatasourceUrlType};
 use datasources::debug::DebugTableType;
@@ -23,6 +34,7 @@ use datasources::lake::iceberg::table::IcebergTable;
 use datasources::lance::scan_lance_table;
 use datasources::mongodb::{MongoDbAccessor, MongoDbConnection};
 use datasources::mysql::{MysqlAccessor, MysqlDbConnection, MysqlTableAccess};
+use datasources::native::access::UPSERT_SOURCE_QUALIFIER;
 use datasources::object_store::gcs::GcsStoreAccess;
 use datasources::object_store::generic::GenericStoreAccess;
 use datasources::object_store::local::LocalStoreAccess;
@@ -35,7 +47,7 @@ use object_store::aws::AmazonS3ConfigKey;
 use object_store::azure::AzureConfigKey;
 use object_store::gcp::GoogleConfigKey;
 use protogen::metastore::types::catalog::{
-    CatalogEntry, DatabaseEntry, RuntimePreference, SourceAccessMode, TableEntry,
+    CachedTableSchema, CatalogEntry, DatabaseEntry, RuntimePreference, SourceAccessMode, TableEntry,
 };
 use protogen::metastore::types::options::{
     CopyToDestinationOptions, CopyToDestinationOptionsAzure, CopyToDestinationOptionsGcs,
@@ -45,13 +57,15 @@ use protogen::metastore::types::options::{
     CredentialsOptionsGcp, DatabaseOptions, DatabaseOptionsBigQuery, DatabaseOptionsClickhouse,
     DatabaseOptionsDebug, DatabaseOptionsDeltaLake, DatabaseOptionsMongoDb, DatabaseOptionsMysql,
     DatabaseOptionsPostgres, DatabaseOptionsSnowflake, DatabaseOptionsSqlServer, DeltaLakeCatalog,
-    DeltaLakeUnityCatalog, StorageOptions, TableOptions, TableOptionsBigQuery,
-    TableOptionsClickhouse, TableOptionsDebug, TableOptionsGcs, TableOptionsLocal,
-    TableOptionsMongoDb, TableOptionsMysql, TableOptionsObjectStore, TableOptionsPostgres,
-    TableOptionsS3, TableOptionsSnowflake, TableOptionsSqlServer, TunnelOptions,
-    TunnelOptionsDebug, TunnelOptionsInternal, TunnelOptionsSsh,
+    DeltaLakeUnityCatalog, InternalColumnDefinition, SchemaInferenceOptions, StorageOptions,
+    TableOptions, TableOptionsBigQuery, TableOptionsClickhouse, TableOptionsDebug, TableOptionsGcs,
+    TableOptionsLocal, TableOptionsMongoDb, TableOptionsMysql, TableOptionsObjectStore,
+    TableOptionsPostgres, TableOptionsS3, TableOptionsSnowflake, TableOptionsSqlServer,
+    TunnelOptions, TunnelOptionsDebug, TunnelOptionsInternal, TunnelOptionsProxy, TunnelOptionsSsh,
+};
+use protogen::metastore::types::service::{
+    AlterDatabaseOperation, AlterRoleOperation, AlterSchemaOperation, AlterTableOperation,
 };
-use protogen::metastore::types::service::{AlterDatabaseOperation, AlterTableOperation};
 use sqlbuiltins::builtins::{CURRENT_SESSION_SCHEMA, DEFAULT_CATALOG};
 use sqlbuiltins::validation::{
     validate_copyto_dest_creds_support, validate_copyto_dest_format_support,
@@ -61,21 +75,28 @@ use sqlbuiltins::validation::{
 use tracing::debug;
 
 use crate::context::local::LocalSessionContext;
+use crate::dispatch::external::ExternalDispatcher;
 use crate::parser::options::StmtOptions;
 use crate::parser::{
-    self, validate_ident, validate_object_name, AlterDatabaseStmt, AlterTableStmtExtension,
-    AlterTunnelAction, AlterTunnelStmt, CopyToSource, CopyToStmt, CreateCredentialStmt,
-    CreateCredentialsStmt, CreateExternalDatabaseStmt, CreateExternalTableStmt, CreateTunnelStmt,
-    DropCredentialsStmt, DropDatabaseStmt, DropTunnelStmt, StatementWithExtensions,
+    self, validate_ident, validate_object_name, AlterDatabaseStmt, AlterRoleStmt, AlterSchemaStmt,
+    AlterTableStmtExtension, AlterTunnelAction, AlterTunnelStmt, AlterViewStmt, CommentObjectType,
+    CommentOnStmt, CopyToSource, CopyToStmt, CreateCredentialStmt, CreateCredentialsStmt,
+    CreateExternalDatabaseStmt, CreateExternalTableStmt, CreateTunnelStmt, DropCredentialsStmt,
+    DropDatabaseStmt, DropTunnelStmt, ExportCatalogStmt, ImportCatalogStmt, RestoreCatalogStmt,
+    StatementWithExtensions,
 };
 use crate::planner::errors::{internal, PlanError, Result};
 use crate::planner::logical_plan::*;
 use crate::planner::preprocess::{preprocess, CastRegclassReplacer, EscapedStringToDoubleQuoted};
 use crate::remote::table::StubRemoteTableProvider;
 use crate::resolve::{EntryResolver, ResolvedEntry};
+use crate::scram::ScramVerifier;
 
 use super::context_builder::PartialContextProvider;
 use super::extension::ExtensionNode;
+use super::physical_plan::alter_database::check_alter_database_permission;
+use super::physical_plan::alter_role::check_alter_role_permission;
+use super::physical_plan::analyze_table::{self, column_stat_name};
 use super::physical_plan::remote_scan::ProviderReference;
 
 /// Plan SQL statements for a session.
@@ -127,6 +148,10 @@ impl<'a> SessionPlanner<'a> {
     pub async fn plan_ast(&self, mut statement: StatementWithExtensions) -> Result<LogicalPlan> {
         debug!(%statement, "planning sql statement");
 
+        if self.ctx.get_session_vars().is_read_only() && is_write_statement(&statement) {
+            return Err(PlanError::ReadOnlySession);
+        }
+
         // Run replacers as needed.
         if let StatementWithExtensions::Statement(inner) = &mut statement {
             preprocess(inner, &mut CastRegclassReplacer { ctx: self.ctx })?;
@@ -143,9 +168,13 @@ impl<'a> SessionPlanner<'a> {
             }
             StatementWithExtensions::DropDatabase(stmt) => self.plan_drop_database(stmt),
             StatementWithExtensions::AlterDatabase(stmt) => self.plan_alter_database(stmt),
+            StatementWithExtensions::AlterRole(stmt) => self.plan_alter_role(stmt),
             StatementWithExtensions::AlterTableExtension(stmt) => {
-                self.plan_alter_table_extension(stmt)
+                self.plan_alter_table_extension(stmt).await
             }
+            StatementWithExtensions::AlterSchema(stmt) => self.plan_alter_schema(stmt),
+            StatementWithExtensions::AlterView(stmt) => self.plan_alter_view(stmt),
+            StatementWithExtensions::CommentOn(stmt) => self.plan_comment_on(stmt),
             StatementWithExtensions::CreateTunnel(stmt) => self.plan_create_tunnel(stmt),
             StatementWithExtensions::DropTunnel(stmt) => self.plan_drop_tunnel(stmt),
             StatementWithExtensions::AlterTunnel(stmt) => self.plan_alter_tunnel(stmt),
@@ -157,6 +186,9 @@ impl<'a> SessionPlanner<'a> {
             }
             StatementWithExtensions::DropCredentials(stmt) => self.plan_drop_credentials(stmt),
             StatementWithExtensions::CopyTo(stmt) => self.plan_copy_to(stmt).await,
+            StatementWithExtensions::ExportCatalog(stmt) => self.plan_export_catalog(stmt).await,
+            StatementWithExtensions::ImportCatalog(stmt) => self.plan_import_catalog(stmt).await,
+            StatementWithExtensions::RestoreCatalog(stmt) => self.plan_restore_catalog(stmt),
         }
     }
 
@@ -236,11 +268,14 @@ impl<'a> SessionPlanner<'a> {
             DatabaseOptions::MONGODB => {
                 let connection_string = get_mongodb_conn_str(m)?;
                 // Validate the accessor
-                MongoDbAccessor::validate_external_database(connection_string.as_str())
-                    .await
-                    .map_err(|e| PlanError::InvalidExternalDatabase {
-                        source: Box::new(e),
-                    })?;
+                MongoDbAccessor::validate_external_database(
+                    connection_string.as_str(),
+                    tunnel_options,
+                )
+                .await
+                .map_err(|e| PlanError::InvalidExternalDatabase {
+                    source: Box::new(e),
+                })?;
                 DatabaseOptions::MongoDb(DatabaseOptionsMongoDb { connection_string })
             }
             DatabaseOptions::SNOWFLAKE => {
@@ -250,14 +285,17 @@ impl<'a> SessionPlanner<'a> {
                 let database_name: String = m.remove_required("database")?;
                 let warehouse: String = m.remove_required("warehouse")?;
                 let role_name: Option<String> = m.remove_optional("role")?;
-                SnowflakeAccessor::validate_external_database(SnowflakeDbConnection {
-                    account_name: account_name.clone(),
-                    login_name: login_name.clone(),
-                    password: password.clone(),
-                    database_name: database_name.clone(),
-                    warehouse: warehouse.clone(),
-                    role_name: role_name.clone(),
-                })
+                SnowflakeAccessor::validate_external_database(
+                    SnowflakeDbConnection {
+                        account_name: account_name.clone(),
+                        login_name: login_name.clone(),
+                        password: password.clone(),
+                        database_name: database_name.clone(),
+                        warehouse: warehouse.clone(),
+                        role_name: role_name.clone(),
+                    },
+                    tunnel_options,
+                )
                 .await
                 .map_err(|e| PlanError::InvalidExternalDatabase {
                     source: Box::new(e),
@@ -278,7 +316,12 @@ impl<'a> SessionPlanner<'a> {
                         databricks_access_token: m.remove_required("access_token")?,
                         workspace_url: m.remove_required("workspace_url")?,
                     }),
-                    other => return Err(internal!("Unknown catalog type: {}", other)),
+                    other => {
+                        return Err(PlanError::Unsupported {
+                            typ: "catalog type",
+                            name: other.to_string(),
+                        })
+                    }
                 };
 
                 let mut storage_options = StorageOptions::try_from(m)?;
@@ -302,7 +345,8 @@ impl<'a> SessionPlanner<'a> {
                 let connection_string: String = m.remove_required("connection_string")?;
 
                 // Validate
-                let access = SqlServerAccess::try_new_from_ado_string(&connection_string)?;
+                let access = SqlServerAccess::try_new_from_ado_string(&connection_string)?
+                    .with_tunnel(tunnel_options);
                 access.validate_access().await?;
 
                 DatabaseOptions::SqlServer(DatabaseOptionsSqlServer { connection_string })
@@ -321,7 +365,12 @@ impl<'a> SessionPlanner<'a> {
                 datasources::debug::validate_tunnel_connections(tunnel_options.as_ref())?;
                 DatabaseOptions::Debug(DatabaseOptionsDebug {})
             }
-            other => return Err(internal!("unsupported datasource: {}", other)),
+            other => {
+                return Err(PlanError::Unsupported {
+                    typ: "datasource",
+                    name: other.to_string(),
+                })
+            }
         };
 
         let database_name = normalize_ident(stmt.name);
@@ -476,11 +525,15 @@ impl<'a> SessionPlanner<'a> {
                     table_name,
                 };
 
-                let _ = SnowflakeAccessor::validate_table_access(conn_params, &access_info)
-                    .await
-                    .map_err(|e| PlanError::InvalidExternalTable {
-                        source: Box::new(e),
-                    })?;
+                let _ = SnowflakeAccessor::validate_table_access(
+                    conn_params,
+                    &access_info,
+                    tunnel_options,
+                )
+                .await
+                .map_err(|e| PlanError::InvalidExternalTable {
+                    source: Box::new(e),
+                })?;
 
                 TableOptions::Snowflake(TableOptionsSnowflake {
                     account_name,
@@ -499,7 +552,8 @@ impl<'a> SessionPlanner<'a> {
                 let table_name: String = m.remove_required("table")?;
 
                 // Validate
-                let access = SqlServerAccess::try_new_from_ado_string(&connection_string)?;
+                let access = SqlServerAccess::try_new_from_ado_string(&connection_string)?
+                    .with_tunnel(tunnel_options);
                 access
                     .validate_table_access(&schema_name, &table_name)
                     .await?;
@@ -530,11 +584,13 @@ impl<'a> SessionPlanner<'a> {
                 let access = Arc::new(LocalStoreAccess);
                 let (file_type, compression) =
                     validate_and_get_file_type_and_compression(access, &location, m).await?;
+                let inference = schema_inference_options(m)?;
 
                 TableOptions::Local(TableOptionsLocal {
                     location,
                     file_type: format!("{file_type:?}").to_lowercase(),
                     compression: compression.map(|c| c.to_string()),
+                    inference,
                 })
             }
             TableOptions::GCS => {
@@ -548,6 +604,7 @@ impl<'a> SessionPlanner<'a> {
 
                 let bucket: String = m.remove_required("bucket")?;
                 let location: String = m.remove_required("location")?;
+                let cache: bool = m.remove_optional("cache")?.unwrap_or(true);
 
                 let access = Arc::new(GcsStoreAccess {
                     bucket: bucket.clone(),
@@ -555,6 +612,7 @@ impl<'a> SessionPlanner<'a> {
                 });
                 let (file_type, compression) =
                     validate_and_get_file_type_and_compression(access, &location, m).await?;
+                let inference = schema_inference_options(m)?;
 
                 TableOptions::Gcs(TableOptionsGcs {
                     bucket,
@@ -562,6 +620,8 @@ impl<'a> SessionPlanner<'a> {
                     location,
                     file_type: file_type.to_string(),
                     compression: compression.map(|c| c.to_string()),
+                    cache,
+                    inference,
                 })
             }
             TableOptions::S3_STORAGE => {
@@ -585,6 +645,7 @@ impl<'a> SessionPlanner<'a> {
                 let region: String = m.remove_required("region")?;
                 let bucket: String = m.remove_required("bucket")?;
                 let location: String = m.remove_required("location")?;
+                let cache: bool = m.remove_optional("cache")?.unwrap_or(true);
 
                 let access = Arc::new(S3StoreAccess {
                     region: region.clone(),
@@ -594,6 +655,7 @@ impl<'a> SessionPlanner<'a> {
                 });
                 let (file_type, compression) =
                     validate_and_get_file_type_and_compression(access, &location, m).await?;
+                let inference = schema_inference_options(m)?;
 
                 TableOptions::S3(TableOptionsS3 {
                     region,
@@ -603,6 +665,8 @@ impl<'a> SessionPlanner<'a> {
                     location,
                     file_type: file_type.to_string(),
                     compression: compression.map(|c| c.to_string()),
+                    cache,
+                    inference,
                 })
             }
             TableOptions::AZURE => {
@@ -645,6 +709,7 @@ impl<'a> SessionPlanner<'a> {
                     m,
                 )
                 .await?;
+                let inference = schema_inference_options(m)?;
 
                 TableOptions::Azure(TableOptionsObjectStore {
                     location,
@@ -652,6 +717,7 @@ impl<'a> SessionPlanner<'a> {
                     file_type: Some(file_type.to_string()),
                     compression: compression.map(|c| c.to_string()),
                     schema_sample_size: None,
+                    inference,
                 })
             }
             TableOptions::DELTA | TableOptions::ICEBERG => {
@@ -671,6 +737,7 @@ impl<'a> SessionPlanner<'a> {
                         file_type: None,
                         compression: None,
                         schema_sample_size: None,
+                        inference: Default::default(),
                     })
                 } else {
                     let url = DatasourceUrl::try_new(&location)?;
@@ -687,6 +754,7 @@ impl<'a> SessionPlanner<'a> {
                         file_type: None,
                         compression: None,
                         schema_sample_size: None,
+                        inference: Default::default(),
                     })
                 }
             }
@@ -718,6 +786,7 @@ impl<'a> SessionPlanner<'a> {
                     file_type: None,
                     compression: None,
                     schema_sample_size: None,
+                    inference: Default::default(),
                 })
             }
             TableOptions::BSON => {
@@ -739,24 +808,93 @@ impl<'a> SessionPlanner<'a> {
                     file_type: None,
                     compression: None,
                     schema_sample_size,
+                    inference: Default::default(),
+                })
+            }
+            other => {
+                return Err(PlanError::Unsupported {
+                    typ: "datasource",
+                    name: other.to_string(),
                 })
             }
-            other => return Err(internal!("unsupported datasource: {}", other)),
         };
 
         let table_name = object_name_to_table_ref(stmt.name)?;
+        let tbl_reference = self.ctx.resolve_table_ref(table_name)?;
+
+        // An existing table that's kept shouldn't get a schema inferred from
+        // these options.
+        let kept_existing = stmt.if_not_exists
+            && self
+                .ctx
+                .get_session_catalog()
+                .resolve_entry(
+                    &tbl_reference.database,
+                    &tbl_reference.schema,
+                    &tbl_reference.name,
+                )
+                .is_some();
+        let schema = if kept_existing {
+            None
+        } else {
+            self.infer_object_store_table_schema(&external_table_options)
+                .await
+        };
 
         let plan = CreateExternalTable {
-            tbl_reference: self.ctx.resolve_table_ref(table_name)?,
+            tbl_reference,
             or_replace: stmt.or_replace,
             if_not_exists: stmt.if_not_exists,
             table_options: external_table_options,
             tunnel,
+            schema,
         };
 
         Ok(plan.into_logical_plan())
     }
 
+    /// Infer the schema of a new object store table so it can be cached in
+    /// the catalog.
+    ///
+    /// Inference errors aren't fatal, the table is created without a cached
+    /// schema and scans infer it like they would otherwise.
+    async fn infer_object_store_table_schema(
+        &self,
+        options: &TableOptions,
+    ) -> Option<CachedTableSchema> {
+        if !matches!(
+            options,
+            TableOptions::Local(_)
+                | TableOptions::Gcs(_)
+                | TableOptions::S3(_)
+                | TableOptions::Azure(_)
+        ) {
+            return None;
+        }
+
+        match self
+            .new_external_dispatcher()
+            .dispatch_table_options(options, None, None)
+            .await
+        {
+            Ok(provider) => Some(cached_table_schema(&provider.schema())),
+            Err(e) => {
+                debug!(%e, "failed to infer schema for new external table");
+                None
+            }
+        }
+    }
+
+    /// Create a dispatcher for external tables that always infers schemas.
+    fn new_external_dispatcher(&self) -> ExternalDispatcher {
+        ExternalDispatcher::new(
+            self.ctx.get_session_catalog(),
+            self.ctx.df_ctx(),
+            self.ctx.get_session_vars().is_cloud_instance(),
+        )
+        .without_schema_cache()
+    }
+
     fn plan_create_tunnel(&self, mut stmt: CreateTunnelStmt) -> Result<LogicalPlan> {
         let m = &mut stmt.options;
 
@@ -774,7 +912,14 @@ impl<'a> SessionPlanner<'a> {
                     ssh_key: ssh_key.to_bytes()?,
                 })
             }
-            other => return Err(internal!("unsupported tunnel: {other}")),
+            TunnelOptions::SOCKS5 => TunnelOptions::Socks5(get_proxy_opts(m)?),
+            TunnelOptions::HTTP => TunnelOptions::Http(get_proxy_opts(m)?),
+            other => {
+                return Err(PlanError::Unsupported {
+                    typ: "tunnel",
+                    name: other.to_string(),
+                })
+            }
         };
 
         let name = normalize_ident(stmt.name);
@@ -826,7 +971,12 @@ impl<'a> SessionPlanner<'a> {
                     access_key,
                 })
             }
-            other => return Err(internal!("unsupported credentials provider: {other}")),
+            other => {
+                return Err(PlanError::Unsupported {
+                    typ: "credentials provider",
+                    name: other.to_string(),
+                })
+            }
         };
 
         let name = normalize_ident(stmt.name);
@@ -926,9 +1076,39 @@ impl<'a> SessionPlanner<'a> {
                 .into_logical_plan())
             }
 
+            // "CREATE DATABASE <name>"
+            //
+            // Native databases have their own set of schemas.
+            ast::Statement::CreateDatabase {
+                db_name,
+                if_not_exists,
+                location: None,
+                managed_location: None,
+            } => {
+                let database_name = match db_name {
+                    ObjectName(mut idents) if idents.len() == 1 => idents.pop().unwrap(),
+                    other => {
+                        return Err(PlanError::String(format!(
+                            "database name should be a single identifier, got: {other}"
+                        )))
+                    }
+                };
+                validate_ident(&database_name)?;
+
+                Ok(CreateDatabase {
+                    database_name: normalize_ident(database_name),
+                    if_not_exists,
+                }
+                .into_logical_plan())
+            }
+
             // Normal tables OR Tables generated from a source query.
             // CREATE TABLE
             // CREATE TABLE table2 AS (SELECT * FROM table1);
+            // CREATE TABLE table2 (a INT, b TEXT) AS (SELECT * FROM table1);
+            //
+            // Declared columns rename and cast the leading columns of the
+            // source query, any remaining columns are kept as is.
             ast::Statement::CreateTable {
                 external: false,
                 if_not_exists,
@@ -943,6 +1123,12 @@ impl<'a> SessionPlanner<'a> {
                 validate_object_name(&name)?;
                 let table_name = object_name_to_table_ref(name)?;
 
+                if if_not_exists && or_replace {
+                    return Err(PlanError::InvalidCreateTableStatement {
+                        msg: "cannot specify both IF NOT EXISTS and OR REPLACE",
+                    });
+                }
+
                 let (source, arrow_cols) = if let Some(q) = query {
                     let mut ctx = context_provider;
 
@@ -951,6 +1137,12 @@ impl<'a> SessionPlanner<'a> {
                     let source = planner.query_to_plan(*q).await?;
                     let df_fields = source.schema().fields();
 
+                    if columns.len() > df_fields.len() {
+                        return Err(PlanError::InvalidCreateTableStatement {
+                            msg: "more columns declared than returned by the source query",
+                        });
+                    }
+
                     let mut columns = columns.into_iter();
                     let mut fields = Vec::with_capacity(df_fields.len());
                     for df_field in df_fields {
@@ -959,8 +1151,17 @@ impl<'a> SessionPlanner<'a> {
                             // If we have a cast for the column, we can update the schema.
                             validate_ident(&column.name)?;
                             let name = normalize_ident(column.name);
-                            let data_type = convert_data_type(&column.data_type)?;
-                            field.with_name(name).with_data_type(data_type)
+                            let declared =
+                                column_field(name, &column.data_type, field.is_nullable())?;
+                            if !can_cast_types(field.data_type(), declared.data_type()) {
+                                return Err(PlanError::String(format!(
+                                    "cannot cast column '{}' of type {} to the declared type {}",
+                                    field.name(),
+                                    field.data_type(),
+                                    declared.data_type(),
+                                )));
+                            }
+                            declared
                         } else {
                             field
                         };
@@ -990,8 +1191,8 @@ impl<'a> SessionPlanner<'a> {
                     for column in columns.into_iter() {
                         validate_ident(&column.name)?;
                         let name = normalize_ident(column.name);
-                        let data_type = convert_data_type(&column.data_type)?;
-                        let field = Field::new(name, data_type, /* nullable = */ true);
+                        let field =
+                            column_field(name, &column.data_type, /* nullable = */ true)?;
                         arrow_cols.push(field);
                     }
                     (None, arrow_cols)
@@ -1000,7 +1201,11 @@ impl<'a> SessionPlanner<'a> {
                 if temporary {
                     let table_name = match table_name {
                         TableReference::Bare { table } => table.into_owned(),
-                        _ => return Err(internal!("cannot specify schema with temporary tables")),
+                        _ => {
+                            return Err(PlanError::InvalidCreateTableStatement {
+                                msg: "cannot specify schema with temporary tables",
+                            })
+                        }
                     };
                     let df_schema = Schema::new(arrow_cols.clone());
                     let df_schema = df_schema.to_dfschema_ref()?;
@@ -1019,10 +1224,28 @@ impl<'a> SessionPlanner<'a> {
 
                     Ok(plan.into_logical_plan())
                 } else {
+                    let tbl_reference = self.ctx.resolve_table_ref(table_name)?;
+
+                    // Don't insert the source rows into an existing table.
+                    let exists = self
+                        .ctx
+                        .get_session_catalog()
+                        .resolve_table(
+                            &tbl_reference.database,
+                            &tbl_reference.schema,
+                            &tbl_reference.name,
+                        )
+                        .is_some();
+                    let source = if if_not_exists && exists {
+                        None
+                    } else {
+                        source
+                    };
+
                     let df_schema = Schema::new(arrow_cols.clone());
                     let df_schema = df_schema.to_dfschema_ref()?;
                     let create_table = CreateTable {
-                        tbl_reference: self.ctx.resolve_table_ref(table_name)?,
+                        tbl_reference,
                         schema: df_schema,
                         if_not_exists,
                         or_replace,
@@ -1067,6 +1290,7 @@ impl<'a> SessionPlanner<'a> {
                 // TODO: Avoid cloning.
                 let mut planner = SqlQueryPlanner::new(&mut context_provider);
                 let input = planner.query_to_plan(*query).await?;
+                let dependencies = context_provider.dependencies().to_vec();
 
                 let columns: Vec<_> = columns.into_iter().map(normalize_ident).collect();
                 // Only validate number of aliases equals number of fields in
@@ -1082,11 +1306,141 @@ impl<'a> SessionPlanner<'a> {
                         sql: query_string,
                         columns,
                         or_replace,
+                        dependencies,
                     }
                     .into_logical_plan())
                 }
             }
 
+            // "INSERT INTO <table_name> ... ON CONFLICT (<columns>) DO NOTHING"
+            // "INSERT INTO <table_name> ... ON CONFLICT (<columns>) DO UPDATE SET <col1> = <value_expression> WHERE <expression>"
+            //
+            // Insert rows, updating or skipping existing rows with the same
+            // values for the conflict columns. There are no unique constraints
+            // yet, so the columns only need to exist.
+            ast::Statement::Insert {
+                or: None,
+                into: _,
+                table_name,
+                columns,
+                overwrite: false,
+                source,
+                partitioned: None,
+                after_columns,
+                table: false,
+                on: Some(ast::OnInsert::OnConflict(on_conflict)),
+                returning: None,
+            } if after_columns.is_empty() => {
+                validate_object_name(&table_name)?;
+                let table_name = object_name_to_table_ref(table_name)?;
+
+                let columns = columns
+                    .into_iter()
+                    .map(|col| {
+                        validate_ident(&col)?;
+                        Ok(normalize_ident(col))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                let conflict_columns = match on_conflict.conflict_target {
+                    Some(ast::ConflictTarget::Columns(cols)) if !cols.is_empty() => cols
+                        .into_iter()
+                        .map(|col| {
+                            validate_ident(&col)?;
+                            Ok(normalize_ident(col))
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                    _ => {
+                        return Err(PlanError::InvalidInsertStatement {
+                            msg: "ON CONFLICT requires a list of conflict columns",
+                        })
+                    }
+                };
+
+                let resolver = EntryResolver::from_context(self.ctx);
+                let ent = resolver
+                    .resolve_entry_from_reference(table_name.clone())?
+                    .try_into_table_entry()?;
+                if ent.meta.external || ent.meta.is_temp {
+                    return Err(PlanError::UnsupportedFeature(
+                        "ON CONFLICT with external or temporary tables",
+                    ));
+                }
+
+                let table_schema = context_provider
+                    .get_table_provider(table_name.clone())
+                    .await?
+                    .schema();
+                for col in &conflict_columns {
+                    if table_schema.field_with_name(col).is_err() {
+                        return Err(PlanError::String(format!(
+                            "Conflict column '{col}' does not exist in '{table_name}'"
+                        )));
+                    }
+                }
+
+                let mut planner = SqlQueryPlanner::new(&mut context_provider);
+                let source = planner
+                    .insert_to_source_plan(&table_name, &columns, source)
+                    .await?;
+
+                let (updates, where_expr) = match on_conflict.action {
+                    ast::OnConflictAction::DoNothing => (None, None),
+                    ast::OnConflictAction::DoUpdate(do_update) => {
+                        // Existing rows are referenced by the table's name,
+                        // and inserted rows by "excluded".
+                        let mut schema = DFSchema::try_from_qualified_schema(
+                            TableReference::bare(ent.meta.name.as_str()),
+                            &table_schema,
+                        )?;
+                        schema.merge(&DFSchema::try_from_qualified_schema(
+                            UPSERT_SOURCE_QUALIFIER,
+                            &table_schema,
+                        )?);
+
+                        let mut updates = Vec::with_capacity(do_update.assignments.len());
+                        for assignment in do_update.assignments {
+                            if assignment.id.len() == 1 {
+                                let column = normalize_ident(assignment.id[0].clone());
+                                let update_value = planner
+                                    .sql_to_expr(
+                                        assignment.value,
+                                        &schema,
+                                        &mut PlannerContext::new(),
+                                    )
+                                    .await?;
+                                updates.push((column, update_value));
+                            } else {
+                                return Err(PlanError::UnsupportedSQLStatement(
+                                    "ON CONFLICT DO UPDATE with table reference in column name"
+                                        .to_string(),
+                                ));
+                            }
+                        }
+
+                        let where_expr = match do_update.selection {
+                            Some(where_expr) => Some(
+                                planner
+                                    .sql_to_expr(where_expr, &schema, &mut PlannerContext::new())
+                                    .await?,
+                            ),
+                            None => None,
+                        };
+
+                        (Some(updates), where_expr)
+                    }
+                };
+
+                Ok(Upsert {
+                    table: ent,
+                    source,
+                    conflict_columns,
+                    updates,
+                    where_expr,
+                }
+                .into_logical_plan())
+            }
+
             ast::Statement::Insert {
                 or: None,
                 into: _,
@@ -1156,6 +1510,20 @@ impl<'a> SessionPlanner<'a> {
                 .into_logical_plan())
             }
 
+            // ANALYZE TABLE <table_name>
+            ast::Statement::Analyze {
+                table_name,
+                partitions: None,
+                for_columns: false,
+                columns,
+                cache_metadata: false,
+                noscan: false,
+                compute_statistics: false,
+            } if columns.is_empty() => {
+                self.plan_analyze_table(&mut context_provider, table_name)
+                    .await
+            }
+
             ast::Statement::AlterTable {
                 name,
                 mut operations,
@@ -1174,6 +1542,7 @@ impl<'a> SessionPlanner<'a> {
                         let name = object_name_to_table_ref(name)?;
                         let name = self.ctx.resolve_table_ref(name)?;
 
+                        let database = name.database.into_owned();
                         let schema = name.schema.into_owned();
                         let name = name.name.into_owned();
 
@@ -1189,6 +1558,7 @@ impl<'a> SessionPlanner<'a> {
                         let new_name = normalize_ident(new_name);
 
                         Ok(AlterTable {
+                            database,
                             schema,
                             name,
                             operation: AlterTableOperation::RenameTable { new_name },
@@ -1203,6 +1573,7 @@ impl<'a> SessionPlanner<'a> {
             ast::Statement::Drop {
                 object_type: ObjectType::Table,
                 if_exists,
+                cascade,
                 names,
                 ..
             } => {
@@ -1215,6 +1586,7 @@ impl<'a> SessionPlanner<'a> {
 
                 let plan = DropTables {
                     if_exists,
+                    cascade,
                     tbl_references: refs,
                 };
                 Ok(plan.into_logical_plan())
@@ -1224,6 +1596,7 @@ impl<'a> SessionPlanner<'a> {
             ast::Statement::Drop {
                 object_type: ObjectType::View,
                 if_exists,
+                cascade,
                 names,
                 ..
             } => {
@@ -1235,6 +1608,7 @@ impl<'a> SessionPlanner<'a> {
                 }
                 Ok(DropViews {
                     if_exists,
+                    cascade,
                     view_references: refs,
                 }
                 .into_logical_plan())
@@ -1272,9 +1646,62 @@ impl<'a> SessionPlanner<'a> {
                 variable,
                 value,
                 ..
-            } => {
-                let plan = SetVariable::try_new(variable.to_string(), value)?;
-                Ok(plan.into_logical_plan())
+            } => Ok(plan_set_variable(variable.to_string(), value)?.into_logical_plan()),
+            // "SET TIME ZONE ..."
+            //
+            // Alias of "SET TimeZone = ...".
+            ast::Statement::SetTimeZone { value, .. } => {
+                let value = match value {
+                    // Local time is always UTC.
+                    ast::Expr::Identifier(ident)
+                        if ident.quote_style.is_none()
+                            && ident.value.eq_ignore_ascii_case("local") =>
+                    {
+                        ast::Expr::Identifier(Ident::new("DEFAULT"))
+                    }
+                    value => value,
+                };
+                Ok(plan_set_variable("TimeZone".to_string(), vec![value])?.into_logical_plan())
+            }
+            // "SET NAMES ..."
+            //
+            // Alias of "SET client_encoding = ...".
+            ast::Statement::SetNames { charset_name, .. } => Ok(SetVariable {
+                variable: "client_encoding".to_string(),
+                values: charset_name,
+            }
+            .into_logical_plan()),
+            ast::Statement::SetNamesDefault {} => Ok(plan_set_variable(
+                "client_encoding".to_string(),
+                vec![ast::Expr::Identifier(Ident::new("DEFAULT"))],
+            )?
+            .into_logical_plan()),
+            // "USE <database>"
+            //
+            // Sets the native database to use for references that don't
+            // include a database.
+            ast::Statement::Use { db_name } => {
+                let database = normalize_ident(db_name);
+                match self.ctx.get_session_catalog().resolve_database(&database) {
+                    Some(DatabaseEntry {
+                        options: DatabaseOptions::Internal(_),
+                        ..
+                    }) => (),
+                    Some(_) => {
+                        return Err(PlanError::String(format!(
+                        "'{database}' is an external database, only native databases can be used"
+                    )))
+                    }
+                    None => {
+                        return Err(PlanError::String(format!(
+                            "database '{database}' does not exist"
+                        )))
+                    }
+                }
+
+                // The database can't be set directly by users so that it's
+                // always checked to be a native database.
+                Ok(UseDatabase { database }.into_logical_plan())
             }
             // "SHOW ..."
             //
@@ -1515,15 +1942,75 @@ impl<'a> SessionPlanner<'a> {
                     .map_err(|e| PlanError::String(format!("{e}")))?;
                 AlterDatabaseOperation::SetAccessMode { access_mode }
             }
+            parser::AlterDatabaseOperation::SetVariable { variable, values } => {
+                let (variable, value) = plan_session_var_default(variable, values)?;
+                AlterDatabaseOperation::SetVariable { variable, value }
+            }
+            parser::AlterDatabaseOperation::ResetVariable { variable } => {
+                AlterDatabaseOperation::ResetVariable {
+                    variable: normalize_ident(variable),
+                }
+            }
         };
+        check_alter_database_permission(&self.ctx.get_session_vars(), &operation)
+            .map_err(PlanError::String)?;
 
         Ok(AlterDatabase { name, operation }.into_logical_plan())
     }
 
-    fn plan_alter_table_extension(&self, stmt: AlterTableStmtExtension) -> Result<LogicalPlan> {
+    fn plan_alter_role(&self, stmt: AlterRoleStmt) -> Result<LogicalPlan> {
+        validate_ident(&stmt.name)?;
+        let name = normalize_ident(stmt.name);
+        let database = stmt.database.map(normalize_ident);
+
+        let operation = match stmt.operation {
+            parser::AlterRoleOperation::SetVariable { variable, values } => {
+                let (variable, value) = plan_session_var_default(variable, values)?;
+                AlterRoleOperation::SetVariable { variable, value }
+            }
+            parser::AlterRoleOperation::ResetVariable { variable } => {
+                AlterRoleOperation::ResetVariable {
+                    variable: normalize_ident(variable),
+                }
+            }
+            parser::AlterRoleOperation::SetPassword { password } => {
+                if database.is_some() {
+                    return Err(PlanError::InvalidAlterStatement {
+                        msg: "a password can't be set for a single database",
+                    });
+                }
+                AlterRoleOperation::SetPassword {
+                    scram_verifier: password.as_deref().map(plan_scram_verifier).transpose()?,
+                }
+            }
+            parser::AlterRoleOperation::SetReadOnly { read_only } => {
+                if database.is_some() {
+                    return Err(PlanError::InvalidAlterStatement {
+                        msg: "read-only access can't be granted for a single database",
+                    });
+                }
+                AlterRoleOperation::SetReadOnly { read_only }
+            }
+        };
+        check_alter_role_permission(&self.ctx.get_session_vars(), &name, &operation)
+            .map_err(PlanError::String)?;
+
+        Ok(AlterRole {
+            name,
+            database,
+            operation,
+        }
+        .into_logical_plan())
+    }
+
+    async fn plan_alter_table_extension(
+        &self,
+        stmt: AlterTableStmtExtension,
+    ) -> Result<LogicalPlan> {
         validate_object_name(&stmt.name)?;
-        let name = object_name_to_table_ref(stmt.name)?;
-        let name = self.ctx.resolve_table_ref(name)?;
+        let table_ref = object_name_to_table_ref(stmt.name)?;
+        let name = self.ctx.resolve_table_ref(table_ref.clone())?;
+        let database = name.database.into_owned();
         let schema = name.schema.into_owned();
         let name = name.name.into_owned();
 
@@ -1533,9 +2020,37 @@ impl<'a> SessionPlanner<'a> {
                     .map_err(|e| PlanError::String(format!("{e}")))?;
                 AlterTableOperation::SetAccessMode { access_mode }
             }
+            parser::AlterTableOperationExtension::RefreshSchema => {
+                let ent = EntryResolver::from_context(self.ctx)
+                    .resolve_entry_from_reference(table_ref)?
+                    .try_into_table_entry()?;
+                if !ent.meta.external || ent.meta.builtin {
+                    return Err(PlanError::String(format!(
+                        "Cannot refresh the schema of '{}', only external tables have a cached schema",
+                        ent.meta.name
+                    )));
+                }
+
+                let provider = self
+                    .new_external_dispatcher()
+                    .dispatch_external_table(&ent)
+                    .await?;
+                AlterTableOperation::RefreshSchema {
+                    schema: cached_table_schema(&provider.schema()),
+                }
+            }
+            parser::AlterTableOperationExtension::SetTags { tags } => {
+                AlterTableOperation::SetTags {
+                    tags: tags_from_stmt(tags)?,
+                }
+            }
+            parser::AlterTableOperationExtension::UnsetTags { keys } => {
+                AlterTableOperation::UnsetTags { keys }
+            }
         };
 
         Ok(AlterTable {
+            database,
             schema,
             name,
             operation,
@@ -1543,6 +2058,323 @@ impl<'a> SessionPlanner<'a> {
         .into_logical_plan())
     }
 
+    fn plan_alter_schema(&self, stmt: AlterSchemaStmt) -> Result<LogicalPlan> {
+        validate_object_name(&stmt.name)?;
+        let schema_ref = self
+            .ctx
+            .resolve_schema_ref(object_name_to_schema_ref(stmt.name)?);
+
+        let operation = match stmt.operation {
+            parser::AlterSchemaOperation::RenameSchema { new_name } => {
+                AlterSchemaOperation::RenameSchema {
+                    new_name: normalize_ident(new_name),
+                }
+            }
+            parser::AlterSchemaOperation::SetTags { tags } => AlterSchemaOperation::SetTags {
+                tags: tags_from_stmt(tags)?,
+            },
+            parser::AlterSchemaOperation::UnsetTags { keys } => {
+                AlterSchemaOperation::UnsetTags { keys }
+            }
+        };
+
+        Ok(AlterSchema {
+            database: schema_ref.database.into_owned(),
+            name: schema_ref.schema.into_owned(),
+            operation,
+        }
+        .into_logical_plan())
+    }
+
+    /// Plan altering a view.
+    ///
+    /// Views share the table namespace, so this is planned as altering a
+    /// table after checking the object is a view.
+    fn plan_alter_view(&self, stmt: AlterViewStmt) -> Result<LogicalPlan> {
+        validate_object_name(&stmt.name)?;
+        let view_ref = object_name_to_table_ref(stmt.name)?;
+        match EntryResolver::from_context(self.ctx)
+            .resolve_entry_from_reference(view_ref.clone())?
+        {
+            ResolvedEntry::Entry(CatalogEntry::View(_)) => (),
+            _ => return Err(PlanError::String(format!("'{view_ref}' is not a view"))),
+        }
+
+        let name = self.ctx.resolve_table_ref(view_ref)?;
+        let operation = match stmt.operation {
+            parser::AlterViewOperation::RenameView { new_name } => {
+                AlterTableOperation::RenameTable {
+                    new_name: normalize_ident(new_name),
+                }
+            }
+        };
+
+        Ok(AlterTable {
+            database: name.database.into_owned(),
+            schema: name.schema.into_owned(),
+            name: name.name.into_owned(),
+            operation,
+        }
+        .into_logical_plan())
+    }
+
+    /// Plan setting or removing the comment on a schema, table, view or
+    /// column.
+    fn plan_comment_on(&self, stmt: CommentOnStmt) -> Result<LogicalPlan> {
+        validate_object_name(&stmt.name)?;
+        let mut idents = stmt.name.0;
+
+        if stmt.object_type == CommentObjectType::Schema {
+            let schema_ref = self
+                .ctx
+                .resolve_schema_ref(object_name_to_schema_ref(ObjectName(idents))?);
+            return Ok(CommentOn {
+                database: schema_ref.database.into_owned(),
+                schema: schema_ref.schema.into_owned(),
+                name: None,
+                column: None,
+                comment: stmt.comment,
+            }
+            .into_logical_plan());
+        }
+
+        let column = match stmt.object_type {
+            // Guaranteed to have at least two parts by the parser.
+            CommentObjectType::Column => idents.pop().map(normalize_ident),
+            _ => None,
+        };
+        let table_ref = object_name_to_table_ref(ObjectName(idents))?;
+
+        let ent = match EntryResolver::from_context(self.ctx)
+            .resolve_entry_from_reference(table_ref.clone())?
+        {
+            ResolvedEntry::Entry(ent) => ent,
+            ResolvedEntry::NeedsExternalResolution { .. } => {
+                return Err(PlanError::UnsupportedFeature(
+                    "COMMENT ON objects in external databases",
+                ))
+            }
+        };
+        if ent.get_meta().is_temp {
+            return Err(PlanError::UnsupportedFeature("COMMENT ON temporary tables"));
+        }
+        match (stmt.object_type, &ent) {
+            (CommentObjectType::Table, CatalogEntry::Table(_))
+            | (CommentObjectType::View, CatalogEntry::View(_)) => (),
+            (CommentObjectType::Column, CatalogEntry::Table(table)) => {
+                // Columns are only known for native tables and external
+                // tables with a cached schema.
+                let columns = table.get_internal_columns().or_else(|| {
+                    self.ctx
+                        .get_session_catalog()
+                        .table_schema(table.meta.id)
+                        .map(|schema| schema.columns.as_slice())
+                });
+                if let (Some(columns), Some(column)) = (columns, &column) {
+                    if !columns.iter().any(|col| &col.name == column) {
+                        return Err(PlanError::String(format!(
+                            "Column '{column}' does not exist in '{table_ref}'"
+                        )));
+                    }
+                }
+            }
+            (CommentObjectType::Column, ent) => {
+                return Err(PlanError::String(format!(
+                    "Column comments can only be set on tables, '{table_ref}' is a {}",
+                    ent.entry_type(),
+                )))
+            }
+            (object_type, ent) => {
+                return Err(PlanError::String(format!(
+                    "'{table_ref}' is not a {}, it's a {}",
+                    object_type.to_string().to_lowercase(),
+                    ent.entry_type(),
+                )))
+            }
+        }
+
+        let name = self.ctx.resolve_table_ref(table_ref)?;
+        Ok(CommentOn {
+            database: name.database.into_owned(),
+            schema: name.schema.into_owned(),
+            name: Some(name.name.into_owned()),
+            column,
+            comment: stmt.comment,
+        }
+        .into_logical_plan())
+    }
+
+    /// Plan collecting statistics for a table.
+    ///
+    /// Statistics are computed with a single aggregate over the table, see
+    /// `AnalyzeTableExec` for what it should produce.
+    async fn plan_analyze_table(
+        &self,
+        context_provider: &mut PartialContextProvider<'_>,
+        table_name: ObjectName,
+    ) -> Result<LogicalPlan> {
+        validate_object_name(&table_name)?;
+        let table_name = object_name_to_table_ref(table_name)?;
+
+        let resolver = EntryResolver::from_context(self.ctx);
+        let ent = resolver
+            .resolve_entry_from_reference(table_name.clone())?
+            .try_into_table_entry()?;
+        if ent.meta.is_temp || ent.meta.builtin {
+            return Err(PlanError::UnsupportedFeature(
+                "ANALYZE with temporary or builtin tables",
+            ));
+        }
+
+        let catalog = self.ctx.get_session_catalog();
+        let schema = catalog
+            .get_by_oid(ent.meta.parent)
+            .ok_or_else(|| internal!("missing schema for table: {}", ent.meta.name))?
+            .get_meta();
+        let database = catalog
+            .get_by_oid(schema.parent)
+            .ok_or_else(|| internal!("missing database for schema: {}", schema.name))?
+            .get_meta();
+        let tbl_reference = OwnedFullObjectReference {
+            database: database.name.clone().into(),
+            schema: schema.name.clone().into(),
+            name: ent.meta.name.clone().into(),
+        };
+
+        let approx_count_distinct = context_provider
+            .get_aggregate_meta("approx_count_distinct")
+            .await
+            .ok_or_else(|| internal!("missing approx_count_distinct aggregate"))?;
+
+        let source = context_provider
+            .get_table_provider(table_name.clone())
+            .await?;
+        let scan = LogicalPlanBuilder::scan(table_name, source, None)?;
+
+        let mut columns = Vec::new();
+        let mut aggr_exprs = vec![count(lit(1_u8)).alias(analyze_table::NUM_ROWS)];
+        for (idx, field) in scan.schema().fields().iter().enumerate() {
+            let column = Expr::Column(field.qualified_column());
+            columns.push(field.name().clone());
+
+            aggr_exprs
+                .push(count(column.clone()).alias(column_stat_name(idx, analyze_table::COUNT)));
+            if !has_ordered_values(field.data_type()) {
+                continue;
+            }
+            aggr_exprs.push(
+                Expr::AggregateUDF(expr::AggregateUDF::new(
+                    approx_count_distinct.clone(),
+                    vec![column.clone()],
+                    None,
+                    None,
+                ))
+                .alias(column_stat_name(idx, analyze_table::DISTINCT)),
+            );
+            aggr_exprs.push(min(column.clone()).alias(column_stat_name(idx, analyze_table::MIN)));
+            aggr_exprs.push(max(column).alias(column_stat_name(idx, analyze_table::MAX)));
+        }
+        let source = scan.aggregate(Vec::<Expr>::new(), aggr_exprs)?.build()?;
+
+        Ok(AnalyzeTable {
+            tbl_reference,
+            columns,
+            source,
+        }
+        .into_logical_plan())
+    }
+
+    async fn plan_export_catalog(&self, stmt: ExportCatalogStmt) -> Result<LogicalPlan> {
+        self.check_embedded("EXPORT CATALOG")?;
+
+        let mut m = stmt.options;
+        let include_credentials: bool = m.remove_optional("include_credentials")?.unwrap_or(false);
+        let encryption_key: Option<String> = m.remove_optional("encryption_key")?;
+        if include_credentials && encryption_key.is_none() {
+            return Err(PlanError::String(
+                "'encryption_key' is required when including credentials".to_string(),
+            ));
+        }
+
+        let role_passwords = if include_credentials {
+            self.ctx.get_role_passwords().await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(ExportCatalog {
+            path: normalize_ident(stmt.dest),
+            include_credentials,
+            role_passwords,
+            encryption_key,
+        }
+        .into_logical_plan())
+    }
+
+    async fn plan_import_catalog(&self, stmt: ImportCatalogStmt) -> Result<LogicalPlan> {
+        self.check_embedded("IMPORT CATALOG")?;
+        // Dumps may contain role passwords and credentials.
+        self.check_admin("IMPORT CATALOG")?;
+
+        let mut m = stmt.options;
+        let encryption_key: Option<String> = m.remove_optional("encryption_key")?;
+
+        // The dump is read during planning since the file lives on the
+        // client's filesystem (see `check_embedded`), but the catalog may be
+        // mutated remotely.
+        let path = normalize_ident(stmt.source);
+        let dump = tokio::fs::read(&path).await?;
+
+        Ok(ImportCatalog {
+            dump,
+            encryption_key,
+        }
+        .into_logical_plan())
+    }
+
+    /// Check that the session's user is an admin.
+    fn check_admin(&self, stmt: &str) -> Result<()> {
+        if !self.ctx.get_session_vars().is_admin() {
+            return Err(PlanError::String(format!(
+                "Permission denied: {stmt} requires an admin"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that the session is embedded in the client process.
+    ///
+    /// Statements reading or writing arbitrary local files would otherwise
+    /// have access to the server's filesystem.
+    fn check_embedded(&self, stmt: &str) -> Result<()> {
+        if !self.ctx.get_session_vars().is_embedded() {
+            return Err(PlanError::String(format!(
+                "{stmt} is only supported when running GlareDB locally"
+            )));
+        }
+        Ok(())
+    }
+
+    fn plan_restore_catalog(&self, stmt: RestoreCatalogStmt) -> Result<LogicalPlan> {
+        self.check_admin("RESTORE CATALOG")?;
+
+        // Timestamps without a time zone are interpreted as UTC.
+        let nanos = string_to_timestamp_nanos(&stmt.timestamp).map_err(|e| {
+            PlanError::String(format!("Invalid timestamp '{}': {e}", stmt.timestamp))
+        })?;
+        let nanos = u64::try_from(nanos).map_err(|_| {
+            PlanError::String(format!(
+                "Cannot restore catalog to a timestamp before 1970: {}",
+                stmt.timestamp
+            ))
+        })?;
+
+        Ok(RestoreCatalog {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_nanos(nanos),
+        }
+        .into_logical_plan())
+    }
+
     async fn plan_copy_to(&self, stmt: CopyToStmt) -> Result<LogicalPlan> {
         let query = match stmt.source {
             CopyToSource::Table(table) => {
@@ -1592,7 +2424,11 @@ impl<'a> SessionPlanner<'a> {
                 DatasourceUrlType::Gcs => CopyToDestinationOptions::GCS,
                 DatasourceUrlType::S3 => CopyToDestinationOptions::S3_STORAGE,
                 DatasourceUrlType::Azure => CopyToDestinationOptions::AZURE,
-                DatasourceUrlType::Http => return Err(internal!("invalid URL scheme")),
+                DatasourceUrlType::Http => {
+                    return Err(PlanError::InvalidCopyToStatement {
+                        source: "invalid URL scheme".into(),
+                    })
+                }
             };
             (d, Some(u))
         };
@@ -1619,7 +2455,9 @@ impl<'a> SessionPlanner<'a> {
             let bucket = match uri.as_ref() {
                 Some(u) => u
                     .host()
-                    .ok_or(internal!("missing bucket name in URL"))?
+                    .ok_or_else(|| PlanError::InvalidCopyToStatement {
+                        source: "missing bucket name in URL".into(),
+                    })?
                     .to_string(),
                 None => m.remove_required("bucket")?,
             };
@@ -1715,9 +2553,10 @@ impl<'a> SessionPlanner<'a> {
                 })
             }
             other => {
-                return Err(internal!(
-                    "unsupported destination for copying data: {other}"
-                ))
+                return Err(PlanError::Unsupported {
+                    typ: "destination for copying data",
+                    name: other.to_string(),
+                })
             }
         };
 
@@ -1751,14 +2590,84 @@ impl<'a> SessionPlanner<'a> {
                 let row_group_size = m
                     .remove_optional::<usize>("row_group_size")?
                     .unwrap_or(122880);
-                CopyToFormatOptions::Parquet(CopyToFormatOptionsParquet { row_group_size })
+                let bloom_filter_columns = remove_output_columns(
+                    &mut m,
+                    source.schema(),
+                    "bloom_filter_columns",
+                    "bloom filter",
+                )?;
+
+                let compression = m.remove_optional::<String>("compression")?;
+                let compression_level = m.remove_optional::<i32>("compression_level")?;
+                match &compression {
+                    Some(codec) => {
+                        parquet::parse_compression(codec, compression_level).map_err(|e| {
+                            PlanError::InvalidCopyToStatement {
+                                source: Box::new(e),
+                            }
+                        })?;
+                    }
+                    None if compression_level.is_some() => {
+                        return Err(PlanError::InvalidCopyToStatement {
+                            source: "compression_level requires a compression codec".into(),
+                        })
+                    }
+                    None => {}
+                }
+
+                let dictionary = m.remove_optional::<bool>("dictionary")?.unwrap_or(true);
+                let dictionary_enabled_columns = remove_output_columns(
+                    &mut m,
+                    source.schema(),
+                    "dictionary_enabled_columns",
+                    "dictionary",
+                )?;
+                let dictionary_disabled_columns = remove_output_columns(
+                    &mut m,
+                    source.schema(),
+                    "dictionary_disabled_columns",
+                    "dictionary",
+                )?;
+
+                let writer_version = m.remove_optional::<String>("writer_version")?;
+                if let Some(version) = &writer_version {
+                    parquet::parse_writer_version(version).map_err(|e| {
+                        PlanError::InvalidCopyToStatement {
+                            source: Box::new(e),
+                        }
+                    })?;
+                }
+
+                let target_file_size = m.remove_optional::<usize>("target_file_size")?;
+                if target_file_size == Some(0) {
+                    return Err(PlanError::InvalidCopyToStatement {
+                        source: "target_file_size must be greater than zero".into(),
+                    });
+                }
+
+                CopyToFormatOptions::Parquet(CopyToFormatOptionsParquet {
+                    row_group_size,
+                    bloom_filter_columns,
+                    compression,
+                    compression_level,
+                    dictionary,
+                    dictionary_enabled_columns,
+                    dictionary_disabled_columns,
+                    writer_version,
+                    target_file_size,
+                })
             }
             Some(CopyToFormatOptions::JSON) => {
                 let array = m.remove_optional::<bool>("array")?.unwrap_or(false);
                 CopyToFormatOptions::Json(CopyToFormatOptionsJson { array })
             }
             Some(CopyToFormatOptions::BSON) => CopyToFormatOptions::Bson {},
-            Some(other) => return Err(internal!("unsupported output format: {other}")),
+            Some(other) => {
+                return Err(PlanError::Unsupported {
+                    typ: "output format",
+                    name: other.to_string(),
+                })
+            }
         };
 
         validate_copyto_dest_format_support(dest.as_str(), format.as_str()).map_err(|e| {
@@ -1834,6 +2743,38 @@ impl<'a> SessionPlanner<'a> {
     }
 }
 
+/// Returns whether executing the statement may modify the catalog or any
+/// tables.
+///
+/// Copying query results to external locations and exporting the catalog are
+/// considered reads. Neither modifies the database, they only write data the
+/// session can already read to a location outside of it, the same as a client
+/// saving the results of a query. This keeps read-only replicas and roles
+/// usable for exports.
+fn is_write_statement(statement: &StatementWithExtensions) -> bool {
+    match statement {
+        StatementWithExtensions::Statement(ast::Statement::Explain {
+            analyze, statement, ..
+        }) => *analyze && !matches!(statement.as_ref(), ast::Statement::Query(_)),
+        StatementWithExtensions::Statement(stmt) => !matches!(
+            stmt,
+            ast::Statement::Query(_)
+                | ast::Statement::ExplainTable { .. }
+                | ast::Statement::StartTransaction { .. }
+                | ast::Statement::Commit { .. }
+                | ast::Statement::Rollback { .. }
+                | ast::Statement::SetVariable { .. }
+                | ast::Statement::SetTimeZone { .. }
+                | ast::Statement::SetNames { .. }
+                | ast::Statement::SetNamesDefault {}
+                | ast::Statement::Use { .. }
+                | ast::Statement::ShowVariable { .. }
+        ),
+        StatementWithExtensions::CopyTo(_) | StatementWithExtensions::ExportCatalog(_) => false,
+        _ => true,
+    }
+}
+
 /// Creates an accessor from object store external table and validates if the
 /// location returns any objects. If objects are returned, tries to get the file
 /// type and compression of the object.
@@ -1895,6 +2836,75 @@ async fn validate_and_get_file_type_and_compression(
     Ok((file_type, compression))
 }
 
+/// Takes the options for inferring the schema of CSV and JSON files, checking
+/// that the column types are valid.
+fn schema_inference_options(m: &mut StmtOptions) -> Result<SchemaInferenceOptions> {
+    let infer_rows = m.remove_optional::<usize>("infer_rows")?;
+    if infer_rows == Some(0) {
+        return Err(PlanError::String(
+            "infer_rows must be greater than zero".to_string(),
+        ));
+    }
+    let columns = m.remove_optional::<String>("columns")?;
+    if let Some(columns) = &columns {
+        parse_column_types(columns, || None)?;
+    }
+    let all_varchar = m.remove_optional::<bool>("all_varchar")?.unwrap_or(false);
+
+    Ok(SchemaInferenceOptions {
+        infer_rows: infer_rows.map(|rows| rows as u64),
+        columns,
+        all_varchar,
+    })
+}
+
+/// Plan setting a variable to some value.
+///
+/// Setting a variable to `DEFAULT` sets it to the server default.
+fn plan_set_variable(variable: String, values: Vec<ast::Expr>) -> Result<SetVariable> {
+    if let [ast::Expr::Identifier(ident)] = values.as_slice() {
+        if ident.quote_style.is_none() && ident.value.eq_ignore_ascii_case("default") {
+            if let Ok(var) = SessionVars::default().read().get(&variable) {
+                return Ok(SetVariable {
+                    values: var.formatted_value(),
+                    variable,
+                });
+            }
+        }
+    }
+    SetVariable::try_new(variable, values)
+}
+
+/// Get the variable name and value to persist for `ALTER ... SET <variable> =
+/// <values>`.
+///
+/// Errors if the variable doesn't exist, can't be set by users, or if the
+/// value isn't valid for the variable.
+fn plan_session_var_default(variable: Ident, values: Vec<ast::Expr>) -> Result<(String, String)> {
+    let SetVariable {
+        variable, values, ..
+    } = SetVariable::try_new(normalize_ident(variable), values)?;
+    SessionVars::default().set(&variable, &values, VarType::UserDefined)?;
+    Ok((variable, values))
+}
+
+/// Get the verifier to store for a password.
+///
+/// Passwords that are already SCRAM-SHA-256 verifiers are stored as is, which
+/// allows setting a password without sending it to the server.
+fn plan_scram_verifier(password: &str) -> Result<String> {
+    if password.is_empty() {
+        return Err(PlanError::InvalidAlterStatement {
+            msg: "password can't be empty",
+        });
+    }
+    if password.starts_with("SCRAM-SHA-256$") {
+        let verifier: ScramVerifier = password.parse()?;
+        return Ok(verifier.to_string());
+    }
+    Ok(ScramVerifier::new(password)?.to_string())
+}
+
 /// Resolves an ident (unquoted -> lowercase else case sensitive).
 fn normalize_ident(ident: Ident) -> String {
     let normalizer = IdentNormalizer::new(/* normalize = */ true);
@@ -1931,6 +2941,57 @@ fn object_name_to_schema_ref(name: ObjectName) -> Result<OwnedSchemaReference> {
     Ok(r)
 }
 
+/// Collect tags from a `SET TAG` statement, with later values for the same
+/// key replacing earlier ones.
+fn tags_from_stmt(tags: Vec<(String, String)>) -> Result<BTreeMap<String, String>> {
+    if tags.iter().any(|(key, _)| key.is_empty()) {
+        return Err(PlanError::String("Tag keys can't be empty".to_string()));
+    }
+    Ok(tags.into_iter().collect())
+}
+
+/// Create the schema to cache in the catalog for an external table.
+fn cached_table_schema(schema: &Schema) -> CachedTableSchema {
+    let inferred_at = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    CachedTableSchema {
+        columns: InternalColumnDefinition::from_arrow_fields(
+            schema.fields().iter().map(|f| f.as_ref().clone()),
+        ),
+        inferred_at,
+    }
+}
+
+/// Whether min, max, and distinct count statistics should be collected for
+/// values of this type.
+fn has_ordered_values(data_type: &DataType) -> bool {
+    data_type.is_numeric()
+        || matches!(
+            data_type,
+            DataType::Boolean
+                | DataType::Utf8
+                | DataType::LargeUtf8
+                | DataType::Date32
+                | DataType::Date64
+                | DataType::Time32(_)
+                | DataType::Time64(_)
+                | DataType::Timestamp(_, _)
+        )
+}
+
+/// Create the field for a column declared with the given type.
+///
+/// UUID columns are tagged with their extension type so that they're still
+/// treated as UUIDs once stored.
+fn column_field(name: String, sql_type: &ast::DataType, nullable: bool) -> Result<Field> {
+    Ok(match sql_type {
+        ast::DataType::Uuid => uuid_field(name, nullable),
+        other => Field::new(name, convert_data_type(other)?, nullable),
+    })
+}
+
 /// Convert a ast data type to an arrow data type.
 ///
 /// NOTE: This and `convert_simple_data_type` were both taken from datafusion's
@@ -1945,9 +3006,9 @@ fn convert_data_type(sql_type: &ast::DataType) -> Result<DataType> {
                 "field", data_type, true,
             ))))
         }
-        ast::DataType::Array(None) => {
-            Err(internal!("Arrays with unspecified type is not supported",))
-        }
+        ast::DataType::Array(None) => Err(PlanError::UnsupportedFeature(
+            "arrays with unspecified type",
+        )),
         other => convert_simple_data_type(other),
     }
 }
@@ -1981,7 +3042,7 @@ fn convert_simple_data_type(sql_type: &ast::DataType) -> Result<DataType> {
                     // Timestamp With Time Zone
                     // INPUT : [ast::DataType]   TimestampTz + [RuntimeConfig] Time Zone
                     // OUTPUT: [ArrowDataType] Timestamp<TimeUnit, Some(Time Zone)>
-                    return Err(internal!("setting timezone unsupported"))
+                    return Err(PlanError::UnsupportedFeature("timestamps with time zone"))
                 } else {
                     // Timestamp Without Time zone
                     None
@@ -1996,10 +3057,10 @@ fn convert_simple_data_type(sql_type: &ast::DataType) -> Result<DataType> {
                     Ok(DataType::Time64(TimeUnit::Nanosecond))
                 } else {
                     // We dont support TIMETZ and TIME WITH TIME ZONE for now
-                    Err(internal!(
-                        "Unsupported SQL type {:?}",
-                        sql_type
-                    ))
+                    Err(PlanError::Unsupported {
+                        typ: "SQL type",
+                        name: format!("{sql_type:?}"),
+                    })
                 }
             }
             ast::DataType::Numeric(exact_number_info)
@@ -2014,12 +3075,13 @@ fn convert_simple_data_type(sql_type: &ast::DataType) -> Result<DataType> {
                 make_decimal_type(precision, scale)
             }
             ast::DataType::Bytea => Ok(DataType::Binary),
+            ast::DataType::Uuid => Ok(UUID_DATA_TYPE),
+            sql_type if is_geometry_sql_type(sql_type) => Ok(GEOMETRY_DATA_TYPE),
             // Explicitly list all other types so that if sqlparser
             // adds/changes the `ast::DataType` the compiler will tell us on upgrade
             // and avoid bugs like https://github.com/apache/arrow-datafusion/issues/3059
             ast::DataType::Nvarchar(_)
             | ast::DataType::JSON
-            | ast::DataType::Uuid
             | ast::DataType::Binary(_)
             | ast::DataType::Varbinary(_)
             | ast::DataType::Blob(_)
@@ -2044,10 +3106,10 @@ fn convert_simple_data_type(sql_type: &ast::DataType) -> Result<DataType> {
             | ast::DataType::Dec(_)
             | ast::DataType::BigNumeric(_)
             | ast::DataType::BigDecimal(_)
-            | ast::DataType::Clob(_) => Err(internal!(
-                "Unsupported SQL type {:?}",
-                sql_type
-            )),
+            | ast::DataType::Clob(_) => Err(PlanError::Unsupported {
+                typ: "SQL type",
+                name: format!("{sql_type:?}"),
+            }),
         }
 }
 
@@ -2130,6 +3192,41 @@ fn get_ssh_conn_str(m: &mut StmtOptions) -> Result<String> {
     Ok(conn.connection_string())
 }
 
+fn get_proxy_opts(m: &mut StmtOptions) -> Result<TunnelOptionsProxy> {
+    Ok(TunnelOptionsProxy {
+        host: m.remove_required("host")?,
+        port: m.remove_required("port")?,
+        username: m.remove_optional("username")?,
+        password: m.remove_optional("password")?,
+    })
+}
+
+/// Remove a comma separated list of columns from the options, checking that
+/// each column is in the output.
+fn remove_output_columns(
+    m: &mut StmtOptions,
+    output: &DFSchema,
+    key: &str,
+    what: &str,
+) -> Result<Vec<String>> {
+    let columns = match m.remove_optional::<String>(key)? {
+        Some(columns) => columns
+            .split(',')
+            .map(|col| col.trim().to_string())
+            .filter(|col| !col.is_empty())
+            .collect::<Vec<_>>(),
+        None => Vec::new(),
+    };
+    for col in &columns {
+        if output.field_with_unqualified_name(col).is_err() {
+            return Err(PlanError::InvalidCopyToStatement {
+                source: format!("{what} column '{col}' not found in output").into(),
+            });
+        }
+    }
+    Ok(columns)
+}
+
 /// Update storage options with the provided credentials object contents
 fn storage_options_with_credentials(
     storage_options: &mut StorageOptions,
@@ -2174,17 +3271,18 @@ fn make_decimal_type(precision: Option<u64>, scale: Option<u64>) -> Result<DataT
         (Some(p), Some(s)) => (p as u8, s as i8),
         (Some(p), None) => (p as u8, 0),
         (None, Some(_)) => {
-            return Err(internal!("Cannot specify only scale for decimal data type",))
+            return Err(PlanError::InvalidDataType {
+                msg: "cannot specify only scale for decimal data type".to_string(),
+            })
         }
         (None, None) => (DECIMAL128_MAX_PRECISION, DECIMAL_DEFAULT_SCALE),
     };
 
     // Arrow decimal is i128 meaning 38 maximum decimal digits
     if precision == 0 || precision > DECIMAL128_MAX_PRECISION || scale.unsigned_abs() > precision {
-        Err(internal!(
-            "Decimal(precision = {}, scale = {}) should satisfy `0 < precision <= 38`, and `scale <= precision`.",
-            precision, scale
-        ))
+        Err(PlanError::InvalidDataType {
+            msg: format!("Decimal(precision = {precision}, scale = {scale}) should satisfy `0 < precision <= 38`, and `scale <= precision`."),
+        })
     } else {
         Ok(DataType::Decimal128(precision, scale))
     }
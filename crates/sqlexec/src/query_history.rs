@@ -0,0 +1,361 @@
+//! History of executed queries, queryable through `system.query_history`.
+//!
+//! Queries are buffered in memory and periodically appended to the history
+//! table of the database they were executed in. Queries older than the
+//! configured retention are pruned from the table so that it stays bounded.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use catalog::session_catalog::SessionCatalog;
+use chrono::{DateTime, Utc};
+use datafusion::arrow::array::{Int64Builder, StringBuilder, TimestampMicrosecondBuilder};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::logical_expr::{col, lit};
+use datafusion::scalar::ScalarValue;
+use datafusion_ext::session_metrics::{QueryMetrics, QueryObserver};
+use datafusion_ext::vars::SessionVars;
+use datasources::native::access::NativeTableStorage;
+use sqlbuiltins::builtins::SYSTEM_QUERY_HISTORY;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use crate::errors::Result;
+use crate::persisted::PersistedTable;
+
+/// How often buffered queries are appended to the history table.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often queries older than the retention are pruned from each database's
+/// history table.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Max number of queries to buffer per database before dropping the oldest.
+const MAX_BUFFERED_QUERIES: usize = 10_000;
+
+/// Handle for recording queries to the query history table.
+///
+/// Cheaply cloneable, all clones share the same writer. The default history
+/// is disabled and records nothing.
+#[derive(Debug, Clone, Default)]
+pub struct QueryHistory {
+    send: Option<mpsc::UnboundedSender<HistoryMessage>>,
+}
+
+impl QueryHistory {
+    /// Default amount of time to keep queries in the history.
+    pub const DEFAULT_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+    /// Create a new query history keeping queries for `retention`.
+    ///
+    /// This spawns a background task for writing queries and must be called
+    /// from within a tokio runtime.
+    pub fn new(retention: Duration) -> QueryHistory {
+        let (send, recv) = mpsc::unbounded_channel();
+        let worker = HistoryWorker {
+            retention,
+            databases: HashMap::new(),
+        };
+        tokio::spawn(worker.run(recv));
+        QueryHistory { send: Some(send) }
+    }
+
+    /// Wait for all queries received so far to be written.
+    pub async fn flush(&self) {
+        if let Some(send) = &self.send {
+            let (tx, rx) = oneshot::channel();
+            if send.send(HistoryMessage::Flush(tx)).is_err() {
+                error!("query history writer stopped, unable to flush");
+                return;
+            }
+            let _ = rx.await;
+        }
+    }
+
+    /// A query history that records nothing.
+    pub fn disabled() -> QueryHistory {
+        Self::default()
+    }
+
+    /// Create an observer recording queries executed by a session.
+    ///
+    /// Returns `None` if the history is disabled.
+    pub(crate) fn session_observer(
+        &self,
+        vars: &SessionVars,
+        catalog: &SessionCatalog,
+        native_tables: &NativeTableStorage,
+    ) -> Option<Arc<dyn QueryObserver>> {
+        let send = self.send.as_ref()?;
+        let table =
+            match PersistedTable::from_catalog(&SYSTEM_QUERY_HISTORY, catalog, native_tables) {
+                Some(table) => Arc::new(table),
+                None => {
+                    warn!(
+                    "query history table missing from catalog, not recording queries for session"
+                );
+                    return None;
+                }
+            };
+
+        Some(Arc::new(SessionHistory {
+            send: send.clone(),
+            user_name: vars.user_name(),
+            connection_id: vars.connection_id(),
+            database_id: vars.database_id(),
+            table,
+        }))
+    }
+}
+
+/// Records queries for a single session.
+#[derive(Debug)]
+struct SessionHistory {
+    send: mpsc::UnboundedSender<HistoryMessage>,
+    user_name: String,
+    connection_id: Uuid,
+    database_id: Uuid,
+    table: Arc<PersistedTable>,
+}
+
+impl QueryObserver for SessionHistory {
+    fn observe(&self, metric: &QueryMetrics) {
+        let duration = metric.start.elapsed();
+        let entry = HistoryEntry {
+            database_id: self.database_id,
+            table: self.table.clone(),
+            record: HistoryRecord {
+                start_time: Utc::now()
+                    - chrono::Duration::from_std(duration)
+                        .unwrap_or_else(|_| chrono::Duration::zero()),
+                user_name: self.user_name.clone(),
+                connection_id: self.connection_id,
+                query_text: metric.query_text.clone(),
+                result_type: metric.result_type,
+                execution_status: metric.execution_status.as_str(),
+                duration,
+                output_rows: metric.output_rows,
+                bytes_read: metric.bytes_read,
+                bytes_written: metric.bytes_written,
+                error_message: metric.error_message.clone(),
+            },
+        };
+
+        if self.send.send(HistoryMessage::Entry(entry)).is_err() {
+            error!("query history writer stopped, query not recorded");
+        }
+    }
+}
+
+#[derive(Debug)]
+enum HistoryMessage {
+    Entry(HistoryEntry),
+    /// Write all pending queries, notifying the sender once done.
+    Flush(oneshot::Sender<()>),
+}
+
+#[derive(Debug)]
+struct HistoryEntry {
+    database_id: Uuid,
+    table: Arc<PersistedTable>,
+    record: HistoryRecord,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct HistoryRecord {
+    start_time: DateTime<Utc>,
+    user_name: String,
+    connection_id: Uuid,
+    query_text: String,
+    result_type: &'static str,
+    execution_status: &'static str,
+    duration: Duration,
+    output_rows: Option<u64>,
+    bytes_read: Option<u64>,
+    bytes_written: Option<u64>,
+    error_message: Option<String>,
+}
+
+/// History state for a single database.
+struct DatabaseHistory {
+    table: Arc<PersistedTable>,
+    pending: Vec<HistoryRecord>,
+    last_pruned: Option<Instant>,
+}
+
+/// Writes queries to history tables in the background.
+struct HistoryWorker {
+    retention: Duration,
+    databases: HashMap<Uuid, DatabaseHistory>,
+}
+
+impl HistoryWorker {
+    async fn run(mut self, mut recv: mpsc::UnboundedReceiver<HistoryMessage>) {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            tokio::select! {
+                msg = recv.recv() => match msg {
+                    Some(HistoryMessage::Entry(entry)) => self.push(entry),
+                    Some(HistoryMessage::Flush(tx)) => {
+                        self.flush().await;
+                        let _ = tx.send(());
+                    }
+                    None => {
+                        // All handles dropped.
+                        self.flush().await;
+                        return;
+                    }
+                },
+                _ = interval.tick() => self.flush().await,
+            }
+        }
+    }
+
+    fn push(&mut self, entry: HistoryEntry) {
+        let db = self
+            .databases
+            .entry(entry.database_id)
+            .or_insert_with(|| DatabaseHistory {
+                table: entry.table.clone(),
+                pending: Vec::new(),
+                last_pruned: None,
+            });
+        // Use the most recent table since the catalog the entry came from may
+        // be newer.
+        db.table = entry.table;
+        db.pending.push(entry.record);
+
+        if db.pending.len() > MAX_BUFFERED_QUERIES {
+            let num_dropped = db.pending.len() - MAX_BUFFERED_QUERIES;
+            error!(%num_dropped, database_id = %entry.database_id, "dropping oldest queries from query history");
+            db.pending.drain(..num_dropped);
+        }
+    }
+
+    /// Append pending queries and prune old queries for all databases.
+    ///
+    /// Queries are retained on failure so that writing can be retried on the
+    /// next flush.
+    async fn flush(&mut self) {
+        for (database_id, db) in self.databases.iter_mut() {
+            if !db.pending.is_empty() {
+                let batch = records_to_batch(&db.pending);
+                match batch {
+                    Ok(batch) => match db.table.append(batch).await {
+                        Ok(()) => db.pending.clear(),
+                        Err(e) => {
+                            error!(%e, %database_id, num_queries = db.pending.len(), "failed to write query history")
+                        }
+                    },
+                    Err(e) => {
+                        // Same records would fail again, don't hold on to
+                        // them.
+                        error!(%e, %database_id, "failed to build query history batch");
+                        db.pending.clear();
+                    }
+                }
+            }
+
+            let should_prune = match db.last_pruned {
+                Some(last_pruned) => last_pruned.elapsed() >= PRUNE_INTERVAL,
+                None => true,
+            };
+            if should_prune {
+                db.last_pruned = Some(Instant::now());
+                match prune(&db.table, self.retention).await {
+                    Ok(num_deleted) => {
+                        debug!(%num_deleted, %database_id, "pruned query history")
+                    }
+                    Err(e) => error!(%e, %database_id, "failed to prune query history"),
+                }
+            }
+        }
+    }
+}
+
+/// Delete queries that started before the retention period.
+async fn prune(table: &PersistedTable, retention: Duration) -> Result<usize> {
+    let retention = i64::try_from(retention.as_micros()).unwrap_or(i64::MAX);
+    let cutoff = Utc::now().timestamp_micros().saturating_sub(retention);
+    let predicate = col("start_time").lt(lit(ScalarValue::TimestampMicrosecond(
+        Some(cutoff),
+        Some("UTC".into()),
+    )));
+    table.delete_where(predicate).await
+}
+
+fn records_to_batch(records: &[HistoryRecord]) -> Result<RecordBatch> {
+    let mut start_time = TimestampMicrosecondBuilder::new().with_timezone("UTC");
+    let mut user_name = StringBuilder::new();
+    let mut connection_id = StringBuilder::new();
+    let mut query_text = StringBuilder::new();
+    let mut result_type = StringBuilder::new();
+    let mut execution_status = StringBuilder::new();
+    let mut duration_ms = Int64Builder::new();
+    let mut output_rows = Int64Builder::new();
+    let mut bytes_read = Int64Builder::new();
+    let mut bytes_written = Int64Builder::new();
+    let mut error_message = StringBuilder::new();
+
+    for record in records {
+        start_time.append_value(record.start_time.timestamp_micros());
+        user_name.append_value(&record.user_name);
+        connection_id.append_value(record.connection_id.to_string());
+        query_text.append_value(&record.query_text);
+        result_type.append_value(record.result_type);
+        execution_status.append_value(record.execution_status);
+        duration_ms.append_value(record.duration.as_millis() as i64);
+        output_rows.append_option(record.output_rows.map(|v| v as i64));
+        bytes_read.append_option(record.bytes_read.map(|v| v as i64));
+        bytes_written.append_option(record.bytes_written.map(|v| v as i64));
+        error_message.append_option(record.error_message.as_ref());
+    }
+
+    Ok(RecordBatch::try_new(
+        Arc::new(SYSTEM_QUERY_HISTORY.arrow_schema()),
+        vec![
+            Arc::new(start_time.finish()),
+            Arc::new(user_name.finish()),
+            Arc::new(connection_id.finish()),
+            Arc::new(query_text.finish()),
+            Arc::new(result_type.finish()),
+            Arc::new(execution_status.finish()),
+            Arc::new(duration_ms.finish()),
+            Arc::new(output_rows.finish()),
+            Arc::new(bytes_read.finish()),
+            Arc::new(bytes_written.finish()),
+            Arc::new(error_message.finish()),
+        ],
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_matches_table_schema() {
+        let record = HistoryRecord {
+            start_time: Utc::now(),
+            user_name: "glaredb".to_string(),
+            connection_id: Uuid::nil(),
+            query_text: "select 1".to_string(),
+            result_type: "query",
+            execution_status: "success",
+            duration: Duration::from_millis(1500),
+            output_rows: Some(1),
+            bytes_read: None,
+            bytes_written: None,
+            error_message: None,
+        };
+
+        let batch = records_to_batch(&[record.clone(), record]).unwrap();
+        assert_eq!(2, batch.num_rows());
+        assert_eq!(
+            SYSTEM_QUERY_HISTORY.arrow_schema(),
+            batch.schema().as_ref().clone()
+        );
+    }
+}
@@ -3,17 +3,24 @@ use crate::{
     extension_codec::GlareDBExtensionCodec,
 };
 use catalog::session_catalog::{ResolveConfig, SessionCatalog};
-use datafusion::{datasource::TableProvider, physical_plan::ExecutionPlan};
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::ipc::writer::FileWriter as IpcFileWriter;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::{
+    datasource::TableProvider,
+    physical_plan::{ExecutionPlan, Statistics},
+};
 use datafusion_ext::functions::FuncParamValue;
+use datafusion_ext::vars::RemoteCompression;
 use datafusion_proto::{physical_plan::AsExecutionPlan, protobuf::PhysicalPlanNode};
 use protogen::{
     gen::rpcsrv::common,
     gen::rpcsrv::service::{self, execution_service_client::ExecutionServiceClient},
     metastore::types::catalog::CatalogState,
     rpcsrv::types::service::{
-        DispatchAccessRequest, FetchCatalogRequest, FetchCatalogResponse, InitializeSessionRequest,
-        InitializeSessionResponse, PhysicalPlanExecuteRequest, ResolvedTableReference,
-        TableProviderResponse,
+        CancelExecutionRequest, DispatchAccessRequest, FetchCatalogRequest, FetchCatalogResponse,
+        InitializeSessionRequest, InitializeSessionResponse, PhysicalPlanExecuteRequest,
+        ResolvedTableReference, ResumeExecutionRequest, TableProviderResponse,
     },
 };
 use proxyutil::metadata_constants::{DB_NAME_KEY, ORG_KEY, PASSWORD_KEY, USER_KEY};
@@ -23,12 +30,14 @@ use std::{collections::HashMap, fmt, sync::Arc};
 use tonic::{
     metadata::MetadataMap,
     transport::{Certificate, Channel, ClientTlsConfig, Endpoint},
-    IntoRequest, Streaming,
+    IntoRequest, IntoStreamingRequest, Streaming,
 };
 use tracing::debug;
 use url::Url;
 use uuid::Uuid;
 
+use super::compression;
+use super::endpoints::{self, EndpointOptions, EndpointSet};
 use super::table::StubRemoteTableProvider;
 
 const DEFAULT_RPC_PROXY_PORT: u16 = 6443;
@@ -122,6 +131,8 @@ pub enum RemoteClientType {
     Cli,
     Node,
     Python,
+    Adbc,
+    C,
 }
 impl fmt::Display for RemoteClientType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -129,6 +140,8 @@ impl fmt::Display for RemoteClientType {
             RemoteClientType::Cli => write!(f, "cli"),
             RemoteClientType::Node => write!(f, "node"),
             RemoteClientType::Python => write!(f, "python"),
+            RemoteClientType::Adbc => write!(f, "adbc"),
+            RemoteClientType::C => write!(f, "c"),
         }
     }
 }
@@ -142,6 +155,9 @@ pub struct RemoteClient {
 
     /// The auth metadata that gets placed on all requests.
     auth_metadata: Arc<MetadataMap>,
+
+    /// Endpoints sessions can be opened on, if connected to more than one.
+    endpoints: Option<Arc<EndpointSet>>,
 }
 
 impl RemoteClient {
@@ -154,6 +170,26 @@ impl RemoteClient {
         Ok(RemoteClient {
             client,
             auth_metadata: Arc::new(MetadataMap::new()),
+            endpoints: None,
+        })
+    }
+
+    /// Connect to a set of equivalent endpoints without any additional
+    /// authentication metadata.
+    ///
+    /// New sessions are opened on healthy endpoints, failing over to the
+    /// next endpoint if one can't be reached.
+    pub async fn connect_endpoints(dsts: Vec<Url>, opts: EndpointOptions) -> Result<Self> {
+        if dsts.len() == 1 {
+            return Self::connect(dsts.into_iter().next().unwrap()).await;
+        }
+
+        let endpoints = EndpointSet::connect(dsts, opts).await?;
+        let client = endpoints.endpoint(endpoints.candidates()[0]).client();
+        Ok(RemoteClient {
+            client,
+            auth_metadata: Arc::new(MetadataMap::new()),
+            endpoints: Some(endpoints),
         })
     }
 
@@ -253,6 +289,7 @@ impl RemoteClient {
         Ok(RemoteClient {
             client,
             auth_metadata: Arc::new(metadata),
+            endpoints: None,
         })
     }
 
@@ -260,18 +297,26 @@ impl RemoteClient {
         &mut self,
         request: InitializeSessionRequest,
     ) -> Result<(RemoteSessionClient, SessionCatalog)> {
-        let mut request = service::InitializeSessionRequest::from(request).into_request();
-        self.append_auth_metadata(request.metadata_mut());
-
-        let resp = self.client.initialize_session(request).await.map_err(|e| {
-            ExecError::RemoteSession(format!("failed to initialize remote session: {e}"))
-        })?;
-        let resp: InitializeSessionResponse = resp.into_inner().try_into()?;
+        let request = service::InitializeSessionRequest::from(request);
+        let (inner, resp) = match self.endpoints.clone() {
+            Some(endpoints) => {
+                self.initialize_session_failover(&endpoints, request)
+                    .await?
+            }
+            None => {
+                let resp = self.try_initialize_session(request).await.map_err(|e| {
+                    ExecError::RemoteSession(format!("failed to initialize remote session: {e}"))
+                })?;
+                (self.clone(), resp)
+            }
+        };
+        let resp: InitializeSessionResponse = resp.try_into()?;
 
         let remote_sess_client = RemoteSessionClient {
-            inner: self.clone(),
+            inner,
             database_id: resp.database_id,
             user_id: resp.user_id,
+            supported_compression: resp.supported_compression.into(),
         };
 
         Ok((
@@ -286,7 +331,55 @@ impl RemoteClient {
         ))
     }
 
-    fn append_auth_metadata(&self, metadata: &mut MetadataMap) {
+    /// Try to initialize a session on each endpoint in turn, returning a
+    /// client pinned to the endpoint the session was opened on.
+    async fn initialize_session_failover(
+        &mut self,
+        endpoints: &EndpointSet,
+        request: service::InitializeSessionRequest,
+    ) -> Result<(RemoteClient, service::InitializeSessionResponse)> {
+        let mut last_err = None;
+        for idx in endpoints.candidates() {
+            let endpoint = endpoints.endpoint(idx);
+            self.client = endpoint.client();
+            match self.try_initialize_session(request.clone()).await {
+                Ok(resp) => {
+                    endpoints.session_opened(idx);
+                    return Ok((self.clone(), resp));
+                }
+                Err(e) if endpoints::is_unavailable(&e) => {
+                    debug!(url = %endpoint.url(), %e, "failing over to next remote endpoint");
+                    endpoint.mark_unhealthy();
+                    last_err = Some(e);
+                }
+                Err(e) => {
+                    return Err(ExecError::RemoteSession(format!(
+                        "failed to initialize remote session: {e}"
+                    )))
+                }
+            }
+        }
+
+        Err(ExecError::RemoteSession(format!(
+            "failed to initialize remote session on any of the remote endpoints ({}): {}",
+            endpoints.urls().join(", "),
+            last_err.map(|e| e.to_string()).unwrap_or_default(),
+        )))
+    }
+
+    async fn try_initialize_session(
+        &mut self,
+        request: service::InitializeSessionRequest,
+    ) -> Result<service::InitializeSessionResponse, tonic::Status> {
+        let mut request = request.into_request();
+        self.append_request_metadata(request.metadata_mut());
+        Ok(self.client.initialize_session(request).await?.into_inner())
+    }
+
+    /// Append auth metadata and the trace context for the current span to an
+    /// outgoing request.
+    fn append_request_metadata(&self, metadata: &mut MetadataMap) {
+        logutil::otel::inject_trace_context(metadata);
         for kv in self.auth_metadata.iter() {
             match kv {
                 tonic::metadata::KeyAndValueRef::Ascii(key, val) => {
@@ -306,6 +399,8 @@ pub struct RemoteSessionClient {
     inner: RemoteClient,
     database_id: Uuid,
     user_id: Option<Uuid>,
+    /// Compression codecs the remote side can use for result streams.
+    supported_compression: Arc<[common::CompressionCodec]>,
 }
 
 impl RemoteSessionClient {
@@ -323,7 +418,7 @@ impl RemoteSessionClient {
             database_id: self.database_id(),
         })
         .into_request();
-        self.inner.append_auth_metadata(request.metadata_mut());
+        self.inner.append_request_metadata(request.metadata_mut());
 
         let resp: FetchCatalogResponse = self
             .inner
@@ -363,9 +458,10 @@ impl RemoteSessionClient {
             table_ref,
             args,
             opts,
+            provider_id: None,
         })
         .into_request();
-        self.inner.append_auth_metadata(request.metadata_mut());
+        self.inner.append_request_metadata(request.metadata_mut());
 
         let resp: TableProviderResponse = self
             .inner
@@ -383,7 +479,17 @@ impl RemoteSessionClient {
         &mut self,
         physical_plan: Arc<dyn ExecutionPlan>,
         query_text: String,
+        execution_id: Uuid,
+        compression: RemoteCompression,
+        compression_level: i32,
     ) -> Result<Streaming<service::RecordBatchResponse>> {
+        // Fall back to uncompressed results if the remote side doesn't
+        // support the codec.
+        let result_compression = match compression::codec_for(compression) {
+            codec if self.supported_compression.contains(&codec) => codec,
+            _ => common::CompressionCodec::Uncompressed,
+        };
+
         // Encode the physical plan into a protobuf message.
         let physical_plan = {
             let node = PhysicalPlanNode::try_from_physical_plan(
@@ -400,9 +506,12 @@ impl RemoteSessionClient {
             physical_plan,
             user_id: self.user_id,
             query_text,
+            execution_id,
+            result_compression,
+            compression_level,
         })
         .into_request();
-        self.inner.append_auth_metadata(request.metadata_mut());
+        self.inner.append_request_metadata(request.metadata_mut());
 
         let resp = self
             .inner
@@ -416,15 +525,114 @@ impl RemoteSessionClient {
         Ok(resp)
     }
 
+    /// Resume the result stream of an execution started with
+    /// `physical_plan_execute`, starting at the batch with the given sequence
+    /// number.
+    pub async fn resume_execution(
+        &mut self,
+        execution_id: Uuid,
+        sequence: u64,
+    ) -> Result<Streaming<service::RecordBatchResponse>> {
+        let mut request = service::ResumeExecutionRequest::from(ResumeExecutionRequest {
+            database_id: self.database_id(),
+            execution_id,
+            sequence,
+        })
+        .into_request();
+        self.inner.append_request_metadata(request.metadata_mut());
+
+        let resp = self
+            .inner
+            .client
+            .resume_execution(request)
+            .await
+            .map_err(|e| ExecError::RemoteSession(format!("unable to resume execution: {e}")))?
+            .into_inner();
+        Ok(resp)
+    }
+
+    /// Cancel an execution started with `physical_plan_execute`.
+    pub async fn cancel_execution(&mut self, execution_id: Uuid) -> Result<()> {
+        let mut request = service::CancelExecutionRequest::from(CancelExecutionRequest {
+            database_id: self.database_id(),
+            execution_id,
+        })
+        .into_request();
+        self.inner.append_request_metadata(request.metadata_mut());
+
+        self.inner
+            .client
+            .cancel_execution(request)
+            .await
+            .map_err(|e| ExecError::RemoteSession(format!("unable to cancel execution: {e}")))?;
+        Ok(())
+    }
+
     pub async fn broadcast_exchange(
         &mut self,
         stream: impl tonic::IntoStreamingRequest<Message = common::ExecutionResultBatch>,
     ) -> Result<()> {
         let mut req = stream.into_streaming_request();
-        self.inner.append_auth_metadata(req.metadata_mut());
+        self.inner.append_request_metadata(req.metadata_mut());
         let _resp = self.inner.client.broadcast_exchange(req).await?;
         Ok(())
     }
+
+    /// Upload local batches into a temporary table on the remote side.
+    ///
+    /// The returned provider scans the uploaded table remotely, letting
+    /// queries joining local and remote data run entirely on the remote side.
+    pub async fn stage_table(
+        &mut self,
+        schema: SchemaRef,
+        batches: Vec<RecordBatch>,
+        statistics: Statistics,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let database_id = self.database_id().as_bytes().to_vec();
+        let work_id = Uuid::new_v4().as_bytes().to_vec();
+
+        // The remote side reads the schema from the first message, so always
+        // send at least one (possibly empty) batch.
+        let batches = if batches.is_empty() {
+            vec![RecordBatch::new_empty(schema)]
+        } else {
+            batches
+        };
+
+        let messages = batches
+            .iter()
+            .map(|batch| {
+                let mut buf = Vec::new();
+                let mut writer = IpcFileWriter::try_new(&mut buf, &batch.schema())?;
+                writer.write(batch)?;
+                writer.finish()?;
+                drop(writer);
+
+                Ok(common::ExecutionResultBatch {
+                    database_id: database_id.clone(),
+                    arrow_ipc: buf,
+                    work_id: work_id.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut request = futures::stream::iter(messages).into_streaming_request();
+        self.inner.append_request_metadata(request.metadata_mut());
+
+        let resp: TableProviderResponse = self
+            .inner
+            .client
+            .stage_table(request)
+            .await
+            .map_err(|e| ExecError::RemoteSession(format!("unable to stage table: {e}")))?
+            .into_inner()
+            .try_into()?;
+
+        Ok(Arc::new(
+            StubRemoteTableProvider::new(resp.id, Arc::new(resp.schema))
+                .with_statistics(statistics),
+        ) as _)
+    }
 }
 
 #[cfg(test)]
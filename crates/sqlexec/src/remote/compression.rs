@@ -0,0 +1,60 @@
+//! Compression of IPC encoded batches sent over RPC.
+use datafusion_ext::vars::RemoteCompression;
+use protogen::gen::rpcsrv::common::CompressionCodec;
+
+use crate::errors::{ExecError, Result};
+
+/// Codecs this version can compress and decompress, advertised to clients
+/// when initializing a session.
+pub const SUPPORTED_CODECS: [CompressionCodec; 2] = [CompressionCodec::Zstd, CompressionCodec::Lz4];
+
+/// Get the codec to request for a compression setting.
+pub fn codec_for(compression: RemoteCompression) -> CompressionCodec {
+    match compression {
+        RemoteCompression::None => CompressionCodec::Uncompressed,
+        RemoteCompression::Zstd => CompressionCodec::Zstd,
+        RemoteCompression::Lz4 => CompressionCodec::Lz4,
+    }
+}
+
+/// Compress a buffer. `level` is only used for zstd.
+pub fn compress(codec: CompressionCodec, level: i32, buf: Vec<u8>) -> Result<Vec<u8>> {
+    Ok(match codec {
+        CompressionCodec::Uncompressed => buf,
+        CompressionCodec::Zstd => zstd::bulk::compress(&buf, level)
+            .map_err(|e| ExecError::Internal(format!("zstd compress: {e}")))?,
+        CompressionCodec::Lz4 => lz4_flex::compress_prepend_size(&buf),
+    })
+}
+
+/// Decompress a buffer compressed with `compress`.
+pub fn decompress(codec: CompressionCodec, buf: Vec<u8>) -> Result<Vec<u8>> {
+    Ok(match codec {
+        CompressionCodec::Uncompressed => buf,
+        CompressionCodec::Zstd => zstd::stream::decode_all(buf.as_slice())
+            .map_err(|e| ExecError::Internal(format!("zstd decompress: {e}")))?,
+        CompressionCodec::Lz4 => lz4_flex::decompress_size_prepended(&buf)
+            .map_err(|e| ExecError::Internal(format!("lz4 decompress: {e}")))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let buf: Vec<u8> = (0..10_000).map(|i| (i % 7) as u8).collect();
+        for codec in [
+            CompressionCodec::Uncompressed,
+            CompressionCodec::Zstd,
+            CompressionCodec::Lz4,
+        ] {
+            let compressed = compress(codec, 3, buf.clone()).unwrap();
+            if codec != CompressionCodec::Uncompressed {
+                assert!(compressed.len() < buf.len(), "{codec:?}");
+            }
+            assert_eq!(buf, decompress(codec, compressed).unwrap(), "{codec:?}");
+        }
+    }
+}
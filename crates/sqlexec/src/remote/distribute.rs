@@ -0,0 +1,508 @@
+//! Spreading the partitions of remote plans across worker nodes.
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+use dashmap::{DashMap, DashSet};
+use datafusion::physical_plan::repartition::RepartitionExec;
+use datafusion::physical_plan::{ExecutionPlan, Partitioning};
+use datafusion_proto::physical_plan::AsExecutionPlan;
+use datafusion_proto::protobuf::PhysicalPlanNode;
+use protogen::gen::rpcsrv::service::{self, execution_service_client::ExecutionServiceClient};
+use tonic::transport::{Channel, Endpoint};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::context::remote::RemoteSessionContext;
+use crate::errors::{ExecError, Result};
+use crate::extension_codec::GlareDBExtensionCodec;
+use crate::planner::physical_plan::client_recv::ClientExchangeRecvExec;
+use crate::planner::physical_plan::distributed::DistributedExec;
+use crate::planner::physical_plan::remote_scan::{ProviderReference, RemoteScanExec};
+
+/// State of a worker in the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerState {
+    /// Partitions are assigned to the worker.
+    Active,
+    /// No new partitions are assigned, but some are still running.
+    Draining,
+    /// No new partitions are assigned, and none are running. The worker can
+    /// be removed without interrupting queries.
+    Drained,
+}
+
+impl WorkerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkerState::Active => "active",
+            WorkerState::Draining => "draining",
+            WorkerState::Drained => "drained",
+        }
+    }
+}
+
+impl fmt::Display for WorkerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A worker node partitions can be executed on.
+#[derive(Debug)]
+pub struct Worker {
+    address: String,
+    client: ExecutionServiceClient<Channel>,
+    /// Providers cached on the worker, keyed by the id they're cached under
+    /// on the coordinator.
+    providers: DashSet<Uuid>,
+    draining: AtomicBool,
+    running_partitions: AtomicUsize,
+    completed_partitions: AtomicU64,
+}
+
+impl Worker {
+    /// Create a worker for the given address. The connection is established
+    /// on first use.
+    pub(crate) fn connect_lazy(address: String) -> Result<Self> {
+        let channel = Endpoint::from_shared(address.clone())?.connect_lazy();
+        Ok(Worker {
+            address,
+            client: ExecutionServiceClient::new(channel),
+            providers: DashSet::new(),
+            draining: AtomicBool::new(false),
+            running_partitions: AtomicUsize::new(0),
+            completed_partitions: AtomicU64::new(0),
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn client(&self) -> ExecutionServiceClient<Channel> {
+        self.client.clone()
+    }
+
+    pub fn state(&self) -> WorkerState {
+        if !self.draining.load(Ordering::Relaxed) {
+            WorkerState::Active
+        } else if self.running_partitions() > 0 {
+            WorkerState::Draining
+        } else {
+            WorkerState::Drained
+        }
+    }
+
+    /// Number of partitions currently executing on the worker.
+    pub fn running_partitions(&self) -> usize {
+        self.running_partitions.load(Ordering::Relaxed)
+    }
+
+    /// Number of partitions the worker has finished executing.
+    pub fn completed_partitions(&self) -> u64 {
+        self.completed_partitions.load(Ordering::Relaxed)
+    }
+
+    /// Track a partition as running on this worker until the returned guard
+    /// is dropped.
+    pub(crate) fn start_partition(self: &Arc<Self>) -> RunningPartition {
+        self.running_partitions.fetch_add(1, Ordering::Relaxed);
+        RunningPartition {
+            worker: self.clone(),
+        }
+    }
+}
+
+/// A partition running on a worker.
+#[derive(Debug)]
+pub(crate) struct RunningPartition {
+    worker: Arc<Worker>,
+}
+
+impl Drop for RunningPartition {
+    fn drop(&mut self) {
+        self.worker
+            .running_partitions
+            .fetch_sub(1, Ordering::Relaxed);
+        self.worker
+            .completed_partitions
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// The pool of worker nodes a remote node coordinates execution across.
+///
+/// Workers can be added and removed at runtime. Sessions and table providers
+/// are mirrored to every worker when they're created on the coordinator, and
+/// replayed to workers that are registered later. Plans are only distributed
+/// to active workers that have every provider the plan reads from.
+#[derive(Debug, Default)]
+pub struct Workers {
+    workers: RwLock<Vec<Arc<Worker>>>,
+    /// Requests for initializing sessions, keyed by database id.
+    sessions: DashMap<Uuid, service::InitializeSessionRequest>,
+    /// Requests for caching providers, keyed by provider id.
+    providers: DashMap<Uuid, service::DispatchAccessRequest>,
+}
+
+impl Workers {
+    /// Create a pool with workers at the given addresses. Connections are
+    /// established on first use.
+    pub fn connect_lazy(addrs: &[String]) -> Result<Self> {
+        let workers = addrs
+            .iter()
+            .map(|addr| -> Result<_> { Ok(Arc::new(Worker::connect_lazy(addr.clone())?)) })
+            .collect::<Result<_>>()?;
+        Ok(Workers {
+            workers: RwLock::new(workers),
+            ..Default::default()
+        })
+    }
+
+    /// Get all workers in the pool, including draining ones.
+    pub fn workers(&self) -> Vec<Arc<Worker>> {
+        self.workers.read().unwrap().clone()
+    }
+
+    fn active_workers(&self) -> Vec<Arc<Worker>> {
+        self.workers()
+            .into_iter()
+            .filter(|worker| worker.state() == WorkerState::Active)
+            .collect()
+    }
+
+    fn get(&self, address: &str) -> Result<Arc<Worker>> {
+        self.workers()
+            .into_iter()
+            .find(|worker| worker.address == address)
+            .ok_or_else(|| ExecError::UnknownWorker(address.to_string()))
+    }
+
+    /// Add a worker to the pool.
+    ///
+    /// Existing sessions and providers are initialized on the worker before
+    /// it's assigned any partitions. Registering a worker that's already in
+    /// the pool makes it active again if it was draining.
+    pub async fn register(&self, address: String) -> Result<()> {
+        if let Ok(worker) = self.get(&address) {
+            worker.draining.store(false, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        let worker = Arc::new(Worker::connect_lazy(address)?);
+        info!(address = %worker.address, "registering worker");
+
+        let sessions: Vec<_> = self
+            .sessions
+            .iter()
+            .map(|ent| ent.value().clone())
+            .collect();
+        for req in sessions {
+            if let Err(e) = worker.client().initialize_session(req).await {
+                warn!(%e, address = %worker.address, "failed to initialize session on worker");
+            }
+        }
+        let providers: Vec<_> = self
+            .providers
+            .iter()
+            .map(|ent| (*ent.key(), ent.value().clone()))
+            .collect();
+        for (id, req) in providers {
+            match worker.client().dispatch_access(req).await {
+                Ok(_) => {
+                    worker.providers.insert(id);
+                }
+                Err(e) => {
+                    warn!(%e, address = %worker.address, provider_id = %id, "failed to dispatch table access on worker")
+                }
+            }
+        }
+
+        let mut workers = self.workers.write().unwrap();
+        // Another registration for the same address may have finished first.
+        if !workers.iter().any(|w| w.address == worker.address) {
+            workers.push(worker);
+        }
+        Ok(())
+    }
+
+    /// Remove a worker from the pool.
+    ///
+    /// Partitions already running on the worker continue to run.
+    pub fn deregister(&self, address: &str) -> Result<()> {
+        let mut workers = self.workers.write().unwrap();
+        let idx = workers
+            .iter()
+            .position(|worker| worker.address == address)
+            .ok_or_else(|| ExecError::UnknownWorker(address.to_string()))?;
+        info!(%address, "deregistering worker");
+        workers.remove(idx);
+        Ok(())
+    }
+
+    /// Stop assigning partitions to a worker. Partitions already assigned to
+    /// the worker continue to run.
+    pub fn drain(&self, address: &str) -> Result<()> {
+        let worker = self.get(address)?;
+        info!(%address, "draining worker");
+        worker.draining.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Initialize a session on every active worker.
+    pub async fn initialize_session(
+        &self,
+        database_id: Uuid,
+        req: service::InitializeSessionRequest,
+    ) {
+        self.sessions.insert(database_id, req.clone());
+
+        let inits = self.active_workers().into_iter().map(|worker| {
+            let req = req.clone();
+            async move { (worker.client().initialize_session(req).await, worker) }
+        });
+        for (result, worker) in futures::future::join_all(inits).await {
+            if let Err(e) = result {
+                warn!(%e, address = %worker.address, "failed to initialize session on worker");
+            }
+        }
+    }
+
+    /// Cache a provider on every active worker under the same id as the
+    /// coordinator.
+    pub async fn dispatch_access(&self, id: Uuid, mut req: service::DispatchAccessRequest) {
+        req.provider_id = id.into_bytes().to_vec();
+        self.providers.insert(id, req.clone());
+
+        let dispatches = self.active_workers().into_iter().map(|worker| {
+            let req = req.clone();
+            async move { (worker.client().dispatch_access(req).await, worker) }
+        });
+        for (result, worker) in futures::future::join_all(dispatches).await {
+            match result {
+                Ok(_) => {
+                    worker.providers.insert(id);
+                }
+                Err(e) => {
+                    warn!(%e, address = %worker.address, provider_id = %id, "failed to dispatch table access on worker")
+                }
+            }
+        }
+    }
+
+    /// Distribute the partitions of `plan` across active workers.
+    ///
+    /// Starting from the root, the first node on each path with multiple
+    /// partitions is wrapped in a `DistributedExec` if it can be executed on
+    /// workers. Each worker executes the full plan below that node for its
+    /// partitions.
+    pub fn distribute(
+        &self,
+        ctx: &RemoteSessionContext,
+        database_id: Uuid,
+        plan: Arc<dyn ExecutionPlan>,
+    ) -> Arc<dyn ExecutionPlan> {
+        let workers = self.active_workers();
+        if workers.is_empty() {
+            return plan;
+        }
+        distribute_plan(ctx, database_id, &workers, plan)
+    }
+}
+
+fn distribute_plan(
+    ctx: &RemoteSessionContext,
+    database_id: Uuid,
+    workers: &[Arc<Worker>],
+    plan: Arc<dyn ExecutionPlan>,
+) -> Arc<dyn ExecutionPlan> {
+    if plan.output_partitioning().partition_count() > 1 {
+        if let Some(exec) = try_distribute(ctx, database_id, workers, &plan) {
+            return Arc::new(exec);
+        }
+    }
+
+    let children = plan.children();
+    if children.is_empty() {
+        return plan;
+    }
+    let children = children
+        .into_iter()
+        .map(|child| distribute_plan(ctx, database_id, workers, child))
+        .collect();
+
+    match plan.clone().with_new_children(children) {
+        Ok(plan) => plan,
+        Err(e) => {
+            debug!(%e, "failed to replace children with distributed plans");
+            plan
+        }
+    }
+}
+
+fn try_distribute(
+    ctx: &RemoteSessionContext,
+    database_id: Uuid,
+    workers: &[Arc<Worker>],
+    plan: &Arc<dyn ExecutionPlan>,
+) -> Option<DistributedExec> {
+    if !has_deterministic_partitions(plan.as_ref()) {
+        return None;
+    }
+
+    let mut provider_ids = HashSet::new();
+    let shared = with_remote_references(ctx, plan.clone(), &mut provider_ids)?;
+
+    let workers: Vec<_> = workers
+        .iter()
+        .filter(|worker| provider_ids.iter().all(|id| worker.providers.contains(id)))
+        .cloned()
+        .collect();
+    if workers.is_empty() {
+        return None;
+    }
+
+    let encoded = match encode_plan(shared) {
+        Ok(encoded) => encoded,
+        Err(e) => {
+            debug!(%e, "not distributing plan that can't be encoded");
+            return None;
+        }
+    };
+
+    Some(DistributedExec::new(
+        plan.clone(),
+        encoded,
+        database_id,
+        workers,
+    ))
+}
+
+/// Rewrite scans in the plan to reference providers by the id they're cached
+/// under, collecting the ids into `provider_ids`.
+///
+/// Returns `None` if the plan reads from something only available on this
+/// node.
+fn with_remote_references(
+    ctx: &RemoteSessionContext,
+    plan: Arc<dyn ExecutionPlan>,
+    provider_ids: &mut HashSet<Uuid>,
+) -> Option<Arc<dyn ExecutionPlan>> {
+    // Client streams are only sent to this node.
+    if plan.as_any().is::<ClientExchangeRecvExec>() {
+        return None;
+    }
+
+    if let Some(scan) = plan.as_any().downcast_ref::<RemoteScanExec>() {
+        let id = match &scan.provider {
+            ProviderReference::Provider(provider) => ctx.cached_provider_id(provider)?,
+            ProviderReference::RemoteReference(id) => *id,
+        };
+        provider_ids.insert(id);
+        return Some(Arc::new(
+            scan.with_provider(ProviderReference::RemoteReference(id)),
+        ));
+    }
+
+    let children = plan.children();
+    if children.is_empty() {
+        return Some(plan);
+    }
+    let children = children
+        .into_iter()
+        .map(|child| with_remote_references(ctx, child, provider_ids))
+        .collect::<Option<Vec<_>>>()?;
+    plan.with_new_children(children).ok()
+}
+
+/// Check if every partition of the plan contains the same rows regardless of
+/// which node it's executed on.
+///
+/// Round robin repartitioning depends on the order batches arrive in, so
+/// different nodes would place rows in different partitions.
+fn has_deterministic_partitions(plan: &dyn ExecutionPlan) -> bool {
+    if let Some(repartition) = plan.as_any().downcast_ref::<RepartitionExec>() {
+        return matches!(repartition.partitioning(), Partitioning::Hash(_, _));
+    }
+    plan.children()
+        .iter()
+        .all(|child| has_deterministic_partitions(child.as_ref()))
+}
+
+fn encode_plan(plan: Arc<dyn ExecutionPlan>) -> Result<Vec<u8>> {
+    let node =
+        PhysicalPlanNode::try_from_physical_plan(plan, &GlareDBExtensionCodec::new_encoder())?;
+    let mut buf = Vec::new();
+    node.try_encode(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::physical_plan::empty::EmptyExec;
+    use datafusion::physical_plan::expressions::Column;
+
+    use super::*;
+
+    #[test]
+    fn deterministic_partitions() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let input: Arc<dyn ExecutionPlan> = Arc::new(EmptyExec::new(false, schema));
+
+        let hash = RepartitionExec::try_new(
+            input.clone(),
+            Partitioning::Hash(vec![Arc::new(Column::new("a", 0))], 4),
+        )
+        .unwrap();
+        assert!(has_deterministic_partitions(&hash));
+
+        let round_robin =
+            RepartitionExec::try_new(input, Partitioning::RoundRobinBatch(4)).unwrap();
+        assert!(!has_deterministic_partitions(&round_robin));
+
+        // Hash partitioning only depends on the values, not the order they
+        // arrive in.
+        let hash = RepartitionExec::try_new(
+            Arc::new(round_robin),
+            Partitioning::Hash(vec![Arc::new(Column::new("a", 0))], 4),
+        )
+        .unwrap();
+        assert!(has_deterministic_partitions(&hash));
+    }
+
+    #[tokio::test]
+    async fn drain_and_deregister() {
+        let workers = Workers::connect_lazy(&["http://worker-0:6443".to_string()]).unwrap();
+        workers
+            .register("http://worker-1:6443".to_string())
+            .await
+            .unwrap();
+        assert_eq!(2, workers.active_workers().len());
+
+        workers.drain("http://worker-0:6443").unwrap();
+        let worker = workers.get("http://worker-0:6443").unwrap();
+        assert_eq!(WorkerState::Drained, worker.state());
+        assert_eq!(1, workers.active_workers().len());
+
+        // Running partitions keep the worker draining.
+        let running = worker.start_partition();
+        assert_eq!(WorkerState::Draining, worker.state());
+        drop(running);
+        assert_eq!(WorkerState::Drained, worker.state());
+        assert_eq!(1, worker.completed_partitions());
+
+        // Registering again reactivates it.
+        workers
+            .register("http://worker-0:6443".to_string())
+            .await
+            .unwrap();
+        assert_eq!(WorkerState::Active, worker.state());
+
+        workers.deregister("http://worker-1:6443").unwrap();
+        assert_eq!(1, workers.workers().len());
+        assert!(workers.deregister("http://worker-1:6443").is_err());
+    }
+}
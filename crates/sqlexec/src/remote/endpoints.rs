@@ -0,0 +1,258 @@
+//! Client-side balancing and failover across multiple remote endpoints.
+use protogen::gen::rpcsrv::service::execution_service_client::ExecutionServiceClient;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tonic::transport::{Channel, Endpoint};
+use tonic::Code;
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::errors::{ExecError, Result};
+
+/// Default interval between health checks of unhealthy endpoints.
+pub const DEFAULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Default timeout for connecting to an endpoint.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Options for connecting to a set of endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndpointOptions {
+    /// Keep opening sessions on the endpoint the last session was opened on
+    /// for as long as it's healthy, instead of round robin across all healthy
+    /// endpoints.
+    pub sticky: bool,
+    /// Interval between health checks of endpoints marked unhealthy.
+    pub health_check_interval: Duration,
+    /// Timeout for connecting to an endpoint.
+    pub connect_timeout: Duration,
+}
+
+impl Default for EndpointOptions {
+    fn default() -> Self {
+        EndpointOptions {
+            sticky: false,
+            health_check_interval: DEFAULT_HEALTH_CHECK_INTERVAL,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+        }
+    }
+}
+
+/// A single server we can open sessions on.
+#[derive(Debug)]
+pub struct RemoteEndpoint {
+    url: Url,
+    endpoint: Endpoint,
+    /// Lazily connected client, reconnects as needed.
+    client: ExecutionServiceClient<Channel>,
+    healthy: AtomicBool,
+}
+
+impl RemoteEndpoint {
+    fn new(url: Url, opts: &EndpointOptions) -> Result<Self> {
+        let endpoint =
+            Endpoint::from_shared(url.to_string())?.connect_timeout(opts.connect_timeout);
+        let client = ExecutionServiceClient::new(endpoint.connect_lazy());
+        Ok(RemoteEndpoint {
+            url,
+            endpoint,
+            client,
+            healthy: AtomicBool::new(true),
+        })
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn client(&self) -> ExecutionServiceClient<Channel> {
+        self.client.clone()
+    }
+
+    /// Try to connect to the endpoint, updating its health.
+    async fn probe(&self) -> bool {
+        let healthy = match self.endpoint.connect().await {
+            Ok(_) => true,
+            Err(e) => {
+                debug!(url = %self.url, %e, "endpoint health check failed");
+                false
+            }
+        };
+        self.healthy.store(healthy, Ordering::Relaxed);
+        healthy
+    }
+
+    pub(crate) fn mark_unhealthy(&self) {
+        if self.healthy.swap(false, Ordering::Relaxed) {
+            warn!(url = %self.url, "marking remote endpoint unhealthy");
+        }
+    }
+}
+
+/// A set of equivalent endpoints, for example an HA pair of servers.
+///
+/// Sessions are state on the server they were opened on, so failover only
+/// applies to opening new sessions. A session whose endpoint goes away needs
+/// to be reopened by the caller.
+#[derive(Debug)]
+pub struct EndpointSet {
+    endpoints: Vec<RemoteEndpoint>,
+    opts: EndpointOptions,
+    /// Round robin counter for picking the first endpoint to try.
+    next: AtomicUsize,
+    /// Endpoint the last session was opened on, used for sticky sessions.
+    current: AtomicUsize,
+}
+
+impl EndpointSet {
+    /// Create a set of endpoints, connecting to at least one of them.
+    ///
+    /// Endpoints that can't be connected to are marked unhealthy, and are
+    /// periodically checked in the background until the set is dropped.
+    pub async fn connect(urls: Vec<Url>, opts: EndpointOptions) -> Result<Arc<Self>> {
+        if urls.is_empty() {
+            return Err(ExecError::InvalidRemoteExecUrl(
+                "At least one endpoint is required".to_string(),
+            ));
+        }
+
+        let endpoints = urls
+            .into_iter()
+            .map(|url| RemoteEndpoint::new(url, &opts))
+            .collect::<Result<Vec<_>>>()?;
+        let set = Arc::new(EndpointSet {
+            endpoints,
+            opts,
+            next: AtomicUsize::new(0),
+            current: AtomicUsize::new(0),
+        });
+
+        let mut connected = None;
+        for (idx, endpoint) in set.endpoints.iter().enumerate() {
+            if endpoint.probe().await && connected.is_none() {
+                connected = Some(idx);
+            }
+        }
+        let connected = connected.ok_or_else(|| {
+            ExecError::RemoteSession(format!(
+                "failed to connect to any of the remote endpoints: {}",
+                set.urls().join(", ")
+            ))
+        })?;
+        set.current.store(connected, Ordering::Relaxed);
+
+        tokio::spawn(Self::health_check(Arc::downgrade(&set), opts));
+
+        Ok(set)
+    }
+
+    /// Periodically probe unhealthy endpoints until the set is dropped.
+    async fn health_check(set: Weak<Self>, opts: EndpointOptions) {
+        let mut interval = tokio::time::interval(opts.health_check_interval);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let set = match set.upgrade() {
+                Some(set) => set,
+                None => return,
+            };
+            for endpoint in set.endpoints.iter().filter(|e| !e.is_healthy()) {
+                if endpoint.probe().await {
+                    debug!(url = %endpoint.url, "remote endpoint healthy again");
+                }
+            }
+        }
+    }
+
+    pub fn endpoints(&self) -> &[RemoteEndpoint] {
+        &self.endpoints
+    }
+
+    pub fn urls(&self) -> Vec<String> {
+        self.endpoints.iter().map(|e| e.url.to_string()).collect()
+    }
+
+    /// Get the order endpoints should be tried in when opening a session.
+    ///
+    /// Healthy endpoints are always tried first. Unhealthy endpoints are
+    /// still tried afterwards in case they came back since the last health
+    /// check.
+    pub(crate) fn candidates(&self) -> Vec<usize> {
+        let n = self.endpoints.len();
+        let start = if self.opts.sticky {
+            self.current.load(Ordering::Relaxed)
+        } else {
+            self.next.fetch_add(1, Ordering::Relaxed)
+        };
+
+        let (mut healthy, unhealthy): (Vec<_>, Vec<_>) = (0..n)
+            .map(|i| (start + i) % n)
+            .partition(|&idx| self.endpoints[idx].is_healthy());
+        healthy.extend(unhealthy);
+        healthy
+    }
+
+    pub(crate) fn endpoint(&self, idx: usize) -> &RemoteEndpoint {
+        &self.endpoints[idx]
+    }
+
+    /// Record that a session was opened on an endpoint.
+    pub(crate) fn session_opened(&self, idx: usize) {
+        self.endpoints[idx].healthy.store(true, Ordering::Relaxed);
+        self.current.store(idx, Ordering::Relaxed);
+    }
+}
+
+/// Check if an error means the endpoint couldn't be reached, and another
+/// endpoint should be tried.
+pub(crate) fn is_unavailable(status: &tonic::Status) -> bool {
+    matches!(status.code(), Code::Unavailable | Code::DeadlineExceeded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint_set(n: usize, sticky: bool) -> EndpointSet {
+        let opts = EndpointOptions {
+            sticky,
+            ..Default::default()
+        };
+        let endpoints = (0..n)
+            .map(|i| {
+                let url = Url::parse(&format!("http://server-{i}:6443")).unwrap();
+                RemoteEndpoint::new(url, &opts).unwrap()
+            })
+            .collect();
+        EndpointSet {
+            endpoints,
+            opts,
+            next: AtomicUsize::new(0),
+            current: AtomicUsize::new(0),
+        }
+    }
+
+    #[tokio::test]
+    async fn candidate_order() {
+        let set = endpoint_set(3, false);
+        assert_eq!(vec![0, 1, 2], set.candidates());
+        assert_eq!(vec![1, 2, 0], set.candidates());
+
+        // Unhealthy endpoints are tried last.
+        set.endpoint(0).mark_unhealthy();
+        assert_eq!(vec![2, 1, 0], set.candidates());
+
+        let set = endpoint_set(3, true);
+        set.session_opened(1);
+        assert_eq!(vec![1, 2, 0], set.candidates());
+        assert_eq!(vec![1, 2, 0], set.candidates());
+
+        set.endpoint(1).mark_unhealthy();
+        assert_eq!(vec![2, 0, 1], set.candidates());
+    }
+}
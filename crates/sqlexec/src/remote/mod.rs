@@ -1,5 +1,8 @@
 pub mod batch_stream;
 pub mod client;
+pub mod compression;
+pub mod distribute;
+pub mod endpoints;
 pub mod planner;
 pub mod provider_cache;
 pub mod staged_stream;
@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use catalog::dump::{decode_dump, dump_catalog, DumpOptions};
 use catalog::session_catalog::SessionCatalog;
 use datafusion::arrow::datatypes::Schema;
 use datafusion::common::tree_node::Transformed;
@@ -10,8 +11,12 @@ use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
 use datafusion::physical_plan::{ExecutionPlan, PhysicalExpr};
 use datafusion::physical_planner::{DefaultPhysicalPlanner, ExtensionPlanner, PhysicalPlanner};
 use datafusion::prelude::Expr;
+use datafusion_ext::asof::AsofJoinPlanner;
+use datafusion_ext::gapfill::GapFillPlanner;
 use datafusion_ext::metrics::WriteOnlyDataSourceMetricsExecAdapter;
+use datafusion_ext::recursive::RecursiveQueryPlanner;
 use datafusion_ext::runtime::runtime_group::RuntimeGroupExec;
+use datafusion_ext::sample::SamplePlanner;
 use datafusion_ext::transform::TreeNodeExt;
 use protogen::metastore::types::catalog::RuntimePreference;
 use protogen::metastore::types::options::CopyToDestinationOptions;
@@ -22,19 +27,26 @@ use std::sync::Arc;
 
 use crate::planner::extension::ExtensionType;
 use crate::planner::logical_plan::{
-    AlterDatabase, AlterTable, AlterTunnelRotateKeys, CopyTo, CreateCredential, CreateCredentials,
-    CreateExternalDatabase, CreateExternalTable, CreateSchema, CreateTable, CreateTempTable,
-    CreateTunnel, CreateView, Delete, DescribeTable, DropCredentials, DropDatabase, DropSchemas,
-    DropTables, DropTunnel, DropViews, Insert, SetVariable, ShowVariable, Update,
+    AlterDatabase, AlterRole, AlterSchema, AlterTable, AlterTunnelRotateKeys, AnalyzeTable,
+    CommentOn, CopyTo, CreateCredential, CreateCredentials, CreateDatabase, CreateExternalDatabase,
+    CreateExternalTable, CreateSchema, CreateTable, CreateTempTable, CreateTunnel, CreateView,
+    Delete, DescribeTable, DropCredentials, DropDatabase, DropSchemas, DropTables, DropTunnel,
+    DropViews, ExportCatalog, ImportCatalog, Insert, RestoreCatalog, SetVariable, ShowVariable,
+    Update, Upsert, UseDatabase,
 };
 use crate::planner::physical_plan::alter_database::AlterDatabaseExec;
+use crate::planner::physical_plan::alter_role::AlterRoleExec;
+use crate::planner::physical_plan::alter_schema::AlterSchemaExec;
 use crate::planner::physical_plan::alter_table::AlterTableExec;
 use crate::planner::physical_plan::alter_tunnel_rotate_keys::AlterTunnelRotateKeysExec;
+use crate::planner::physical_plan::analyze_table::AnalyzeTableExec;
 use crate::planner::physical_plan::client_recv::ClientExchangeRecvExec;
 use crate::planner::physical_plan::client_send::ClientExchangeSendExec;
+use crate::planner::physical_plan::comment_on::CommentOnExec;
 use crate::planner::physical_plan::copy_to::CopyToExec;
 use crate::planner::physical_plan::create_credential::CreateCredentialExec;
 use crate::planner::physical_plan::create_credentials::CreateCredentialsExec;
+use crate::planner::physical_plan::create_database::CreateDatabaseExec;
 use crate::planner::physical_plan::create_external_database::CreateExternalDatabaseExec;
 use crate::planner::physical_plan::create_external_table::CreateExternalTableExec;
 use crate::planner::physical_plan::create_schema::CreateSchemaExec;
@@ -51,13 +63,18 @@ use crate::planner::physical_plan::drop_tables::DropTablesExec;
 use crate::planner::physical_plan::drop_temp_tables::DropTempTablesExec;
 use crate::planner::physical_plan::drop_tunnel::DropTunnelExec;
 use crate::planner::physical_plan::drop_views::DropViewsExec;
+use crate::planner::physical_plan::export_catalog::ExportCatalogExec;
+use crate::planner::physical_plan::import_catalog::ImportCatalogExec;
 use crate::planner::physical_plan::insert::InsertExec;
 use crate::planner::physical_plan::remote_exec::RemoteExecutionExec;
 use crate::planner::physical_plan::remote_scan::ProviderReference;
+use crate::planner::physical_plan::restore_catalog::RestoreCatalogExec;
 use crate::planner::physical_plan::send_recv::SendRecvJoinExec;
 use crate::planner::physical_plan::set_var::SetVarExec;
 use crate::planner::physical_plan::show_var::ShowVarExec;
 use crate::planner::physical_plan::update::UpdateExec;
+use crate::planner::physical_plan::upsert::UpsertExec;
+use crate::planner::physical_plan::use_database::UseDatabaseExec;
 
 use super::client::RemoteSessionClient;
 
@@ -92,16 +109,59 @@ impl ExtensionPlanner for DDLExtensionPlanner {
                 };
                 RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
             }
+            ExtensionType::AlterRole => {
+                let lp = require_downcast_lp::<AlterRole>(node);
+                let exec = AlterRoleExec {
+                    catalog_version: self.catalog.version(),
+                    name: lp.name.to_string(),
+                    database: lp.database.clone(),
+                    operation: lp.operation.clone(),
+                };
+                RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
+            }
+            ExtensionType::AlterSchema => {
+                let lp = require_downcast_lp::<AlterSchema>(node);
+                let exec = AlterSchemaExec {
+                    catalog_version: self.catalog.version(),
+                    database: lp.database.clone(),
+                    name: lp.name.clone(),
+                    operation: lp.operation.clone(),
+                };
+                RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
+            }
             ExtensionType::AlterTable => {
                 let lp = require_downcast_lp::<AlterTable>(node);
                 let exec = AlterTableExec {
                     catalog_version: self.catalog.version(),
+                    database: lp.database.to_owned(),
                     schema: lp.schema.to_owned(),
                     name: lp.name.to_owned(),
                     operation: lp.operation.clone(),
                 };
                 RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
             }
+            ExtensionType::AnalyzeTable => {
+                let lp = require_downcast_lp::<AnalyzeTable>(node);
+                let exec = AnalyzeTableExec {
+                    catalog_version: self.catalog.version(),
+                    tbl_reference: lp.tbl_reference.clone(),
+                    columns: lp.columns.clone(),
+                    source: physical_inputs.first().unwrap().clone(),
+                };
+                RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
+            }
+            ExtensionType::CommentOn => {
+                let lp = require_downcast_lp::<CommentOn>(node);
+                let exec = CommentOnExec {
+                    catalog_version: self.catalog.version(),
+                    database: lp.database.clone(),
+                    schema: lp.schema.clone(),
+                    name: lp.name.clone(),
+                    column: lp.column.clone(),
+                    comment: lp.comment.clone(),
+                };
+                RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
+            }
             ExtensionType::AlterTunnelRotateKeys => {
                 let lp = require_downcast_lp::<AlterTunnelRotateKeys>(node);
                 let exec = AlterTunnelRotateKeysExec {
@@ -154,6 +214,16 @@ impl ExtensionPlanner for DDLExtensionPlanner {
                     if_not_exists: lp.if_not_exists,
                     tunnel: lp.tunnel.clone(),
                     table_options: lp.table_options.clone(),
+                    schema: lp.schema.clone(),
+                };
+                RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
+            }
+            ExtensionType::CreateDatabase => {
+                let lp = require_downcast_lp::<CreateDatabase>(node);
+                let exec = CreateDatabaseExec {
+                    catalog_version: self.catalog.version(),
+                    database_name: lp.database_name.clone(),
+                    if_not_exists: lp.if_not_exists,
                 };
                 RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
             }
@@ -207,6 +277,7 @@ impl ExtensionPlanner for DDLExtensionPlanner {
                     sql: lp.sql.clone(),
                     columns: lp.columns.clone(),
                     or_replace: lp.or_replace,
+                    dependencies: lp.dependencies.clone(),
                 };
                 RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
             }
@@ -264,6 +335,7 @@ impl ExtensionPlanner for DDLExtensionPlanner {
                             catalog_version: self.catalog.version(),
                             tbl_references: drops,
                             if_exists: plan.if_exists,
+                            cascade: plan.cascade,
                         });
                         RuntimeGroupExec::new(RuntimePreference::Remote, exec)
                     }
@@ -317,6 +389,7 @@ impl ExtensionPlanner for DDLExtensionPlanner {
                     catalog_version: self.catalog.version(),
                     view_references: lp.view_references.clone(),
                     if_exists: lp.if_exists,
+                    cascade: lp.cascade,
                 };
                 RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
             }
@@ -328,6 +401,37 @@ impl ExtensionPlanner for DDLExtensionPlanner {
                 };
                 RuntimeGroupExec::new(RuntimePreference::Local, Arc::new(exec))
             }
+            ExtensionType::ExportCatalog => {
+                let lp = require_downcast_lp::<ExportCatalog>(node);
+                let opts = DumpOptions {
+                    include_credentials: lp.include_credentials,
+                };
+                let exec = ExportCatalogExec {
+                    path: lp.path.clone(),
+                    mutations: dump_catalog(self.catalog.get_state(), &lp.role_passwords, opts),
+                    encryption_key: lp.encryption_key.clone(),
+                };
+                // Dumps are always written to the client's filesystem.
+                RuntimeGroupExec::new(RuntimePreference::Local, Arc::new(exec))
+            }
+            ExtensionType::ImportCatalog => {
+                let lp = require_downcast_lp::<ImportCatalog>(node);
+                let mutations = decode_dump(&lp.dump, lp.encryption_key.as_deref())
+                    .map_err(|e| DataFusionError::Plan(format!("invalid catalog dump: {e}")))?;
+                let exec = ImportCatalogExec {
+                    catalog_version: self.catalog.version(),
+                    mutations,
+                };
+                RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
+            }
+            ExtensionType::RestoreCatalog => {
+                let lp = require_downcast_lp::<RestoreCatalog>(node);
+                let exec = RestoreCatalogExec {
+                    catalog_version: self.catalog.version(),
+                    timestamp: lp.timestamp,
+                };
+                RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
+            }
             ExtensionType::ShowVariable => {
                 let lp = require_downcast_lp::<ShowVariable>(node);
                 let exec = ShowVarExec {
@@ -377,6 +481,19 @@ impl ExtensionPlanner for DDLExtensionPlanner {
                 });
                 RuntimeGroupExec::new(lp.runtime_preference, exec)
             }
+            ExtensionType::Upsert => {
+                let lp = require_downcast_lp::<Upsert>(node);
+                let exec = UpsertExec {
+                    table: lp.table.clone(),
+                    source: Arc::new(WriteOnlyDataSourceMetricsExecAdapter::new(
+                        physical_inputs.first().unwrap().clone(),
+                    )),
+                    conflict_columns: lp.conflict_columns.clone(),
+                    updates: lp.updates.clone(),
+                    where_expr: lp.where_expr.clone(),
+                };
+                RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
+            }
             ExtensionType::Delete => {
                 let lp = require_downcast_lp::<Delete>(node);
                 let exec = DeleteExec {
@@ -385,6 +502,13 @@ impl ExtensionPlanner for DDLExtensionPlanner {
                 };
                 RuntimeGroupExec::new(RuntimePreference::Remote, Arc::new(exec))
             }
+            ExtensionType::UseDatabase => {
+                let lp = require_downcast_lp::<UseDatabase>(node);
+                let exec = UseDatabaseExec {
+                    database: lp.database.clone(),
+                };
+                RuntimeGroupExec::new(RuntimePreference::Local, Arc::new(exec))
+            }
         };
 
         Ok(Some(Arc::new(runtime_group_exec)))
@@ -607,9 +731,13 @@ impl<'a> PhysicalPlanner for RemotePhysicalPlanner<'a> {
         // Create the physical plans. This will call `scan` on the custom table
         // providers meaning we'll have the correct exec refs.
 
-        let physical = DefaultPhysicalPlanner::with_extension_planners(vec![Arc::new(
-            DDLExtensionPlanner::new(self.catalog.clone()),
-        )])
+        let physical = DefaultPhysicalPlanner::with_extension_planners(vec![
+            Arc::new(RecursiveQueryPlanner),
+            Arc::new(SamplePlanner),
+            Arc::new(AsofJoinPlanner),
+            Arc::new(GapFillPlanner),
+            Arc::new(DDLExtensionPlanner::new(self.catalog.clone())),
+        ])
         .create_physical_plan(logical_plan, session_state)
         .await?;
 
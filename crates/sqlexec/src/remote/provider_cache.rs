@@ -2,8 +2,12 @@ use std::sync::Arc;
 
 use dashmap::DashMap;
 use datafusion::datasource::TableProvider;
+use telemetry::metrics::METRICS;
 use uuid::Uuid;
 
+/// Name of the cache used when reporting metrics.
+const CACHE_NAME: &str = "remote_table_provider";
+
 /// Cache for table providers on the remote side.
 // TODO: Need to occasionally clean out.
 #[derive(Default)]
@@ -17,7 +21,26 @@ impl ProviderCache {
     }
 
     pub fn get(&self, id: &Uuid) -> Option<Arc<dyn TableProvider>> {
-        let prov = self.providers.get(id)?;
-        Some(prov.value().clone())
+        match self.providers.get(id) {
+            Some(prov) => {
+                METRICS.cache_hits.with_label(CACHE_NAME).inc();
+                Some(prov.value().clone())
+            }
+            None => {
+                METRICS.cache_misses.with_label(CACHE_NAME).inc();
+                None
+            }
+        }
+    }
+
+    /// Get the id a provider is cached under.
+    pub fn id_of(&self, table: &Arc<dyn TableProvider>) -> Option<Uuid> {
+        // Compare data pointers only, the same provider may have different
+        // vtable pointers across codegen units.
+        let ptr = Arc::as_ptr(table) as *const ();
+        self.providers
+            .iter()
+            .find(|ent| Arc::as_ptr(ent.value()) as *const () == ptr)
+            .map(|ent| *ent.key())
     }
 }
@@ -7,7 +7,7 @@ use datafusion::{
     error::{DataFusionError, Result as DfResult},
     execution::context::SessionState,
     logical_expr::TableType,
-    physical_plan::ExecutionPlan,
+    physical_plan::{ColumnStatistics, ExecutionPlan, Statistics},
     prelude::Expr,
 };
 use uuid::Uuid;
@@ -29,6 +29,8 @@ pub struct StubRemoteTableProvider {
     provider_id: Uuid,
     /// Schema for the table provider.
     schema: Arc<Schema>,
+    /// Statistics for the remote table, e.g. from `ANALYZE TABLE`.
+    statistics: Option<Statistics>,
 }
 
 impl StubRemoteTableProvider {
@@ -36,9 +38,43 @@ impl StubRemoteTableProvider {
         Self {
             provider_id,
             schema,
+            statistics: None,
         }
     }
 
+    /// Returns a copy of this provider with statistics used for planning.
+    pub fn with_statistics(&self, statistics: Statistics) -> Self {
+        Self {
+            provider_id: self.provider_id,
+            schema: self.schema.clone(),
+            statistics: Some(statistics),
+        }
+    }
+
+    /// Statistics for a scan with the given projection and limit.
+    fn scan_statistics(&self, projection: Option<&Vec<usize>>, limit: Option<usize>) -> Statistics {
+        let mut statistics = match &self.statistics {
+            Some(statistics) => statistics.clone(),
+            None => return Statistics::default(),
+        };
+
+        if let (Some(projection), Some(columns)) = (projection, &statistics.column_statistics) {
+            statistics.column_statistics = Some(
+                projection
+                    .iter()
+                    .map(|idx| columns.get(*idx).cloned().unwrap_or_default())
+                    .collect::<Vec<ColumnStatistics>>(),
+            );
+        }
+        if let Some(limit) = limit {
+            statistics.num_rows = statistics.num_rows.map(|rows| rows.min(limit));
+        }
+        // Filters are applied on the remote side, these statistics don't
+        // account for them.
+        statistics.is_exact = false;
+        statistics
+    }
+
     /// Returns the provider ID.
     pub fn id(&self) -> Uuid {
         self.provider_id
@@ -64,6 +100,10 @@ impl TableProvider for StubRemoteTableProvider {
         TableType::View
     }
 
+    fn statistics(&self) -> Option<Statistics> {
+        self.statistics.clone()
+    }
+
     async fn scan(
         &self,
         _state: &SessionState,
@@ -83,6 +123,7 @@ impl TableProvider for StubRemoteTableProvider {
             projection.cloned(),
             filters.to_vec(),
             limit,
+            self.scan_statistics(projection, limit),
         );
 
         Ok(Arc::new(exec))
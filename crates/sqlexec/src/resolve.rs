@@ -1,13 +1,42 @@
 use crate::context::local::LocalSessionContext;
+use crate::errors::ErrorClass;
 use catalog::session_catalog::SessionCatalog;
 use datafusion::sql::TableReference;
-use protogen::metastore::types::catalog::{CatalogEntry, DatabaseEntry, TableEntry};
-use sqlbuiltins::builtins::{CURRENT_SESSION_SCHEMA, DEFAULT_CATALOG};
+use datafusion_ext::suggest::{closest_matches, did_you_mean};
+use protogen::metastore::types::catalog::{CatalogEntry, DatabaseEntry, FunctionType, TableEntry};
+use protogen::metastore::types::options::DatabaseOptions;
+use sqlbuiltins::builtins::CURRENT_SESSION_SCHEMA;
 use std::borrow::Cow;
 
 #[derive(Debug, Clone, thiserror::Error)]
-#[error("failed to resolve: {0}")]
-pub struct ResolveError(String);
+pub enum ResolveError {
+    #[error(
+        "failed to resolve: failed to find table: {reference}{}",
+        did_you_mean(.suggestions).map(|s| format!(", {s}")).unwrap_or_default()
+    )]
+    MissingTable {
+        reference: String,
+        /// Close matches for the reference.
+        suggestions: Vec<String>,
+    },
+
+    #[error("failed to resolve: unable to find database entry for '{0}'")]
+    MissingDatabase(String),
+
+    #[error("failed to resolve: {0}")]
+    InvalidEntry(String),
+}
+
+impl ResolveError {
+    /// Get the class of this error.
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            ResolveError::MissingTable { .. } => ErrorClass::UndefinedTable,
+            ResolveError::MissingDatabase(_) => ErrorClass::UndefinedDatabase,
+            ResolveError::InvalidEntry(_) => ErrorClass::InvalidStatement,
+        }
+    }
+}
 
 type Result<T, E = ResolveError> = std::result::Result<T, E>;
 
@@ -30,11 +59,11 @@ impl<'a> ResolvedEntry<'a> {
     pub fn try_into_table_entry(self) -> Result<TableEntry> {
         match self {
             Self::Entry(CatalogEntry::Table(ent)) => Ok(ent),
-            Self::Entry(ent) => Err(ResolveError(format!(
+            Self::Entry(ent) => Err(ResolveError::InvalidEntry(format!(
                 "{} is not a table entry",
                 ent.get_meta().name
             ))),
-            Self::NeedsExternalResolution { .. } => Err(ResolveError(
+            Self::NeedsExternalResolution { .. } => Err(ResolveError::InvalidEntry(
                 "entry type unknown, external resolution needed".to_string(),
             )),
         }
@@ -49,6 +78,8 @@ impl<'a> ResolvedEntry<'a> {
 pub struct EntryResolver<'a> {
     /// Catalog to lookup entries in.
     pub catalog: &'a SessionCatalog,
+    /// Native database to use when the reference doesn't include one.
+    pub database: String,
     /// Schemas to use when looking up a table.
     pub schema_search_path: Vec<String>,
 }
@@ -57,6 +88,7 @@ impl<'a> EntryResolver<'a> {
     pub fn from_context(ctx: &'a LocalSessionContext) -> Self {
         EntryResolver {
             catalog: ctx.get_session_catalog(),
+            database: ctx.get_session_vars().database(),
             schema_search_path: ctx.get_session_vars().implicit_search_path(),
         }
     }
@@ -83,7 +115,7 @@ impl<'a> EntryResolver<'a> {
                 // Iterate through all schemas in the search path looking for
                 // our table.
                 for schema in self.schema_search_path.iter() {
-                    if let Some(ent) = self.catalog.resolve_entry(DEFAULT_CATALOG, schema, table) {
+                    if let Some(ent) = self.catalog.resolve_entry(&self.database, schema, table) {
                         return Ok(ResolvedEntry::Entry(ent.clone()));
                     }
                     // Continue on, trying the next schema.
@@ -97,7 +129,7 @@ impl<'a> EntryResolver<'a> {
                     }
                 }
 
-                if let Some(ent) = self.catalog.resolve_entry(DEFAULT_CATALOG, schema, table) {
+                if let Some(ent) = self.catalog.resolve_entry(&self.database, schema, table) {
                     return Ok(ResolvedEntry::Entry(ent.clone()));
                 }
             }
@@ -109,13 +141,14 @@ impl<'a> EntryResolver<'a> {
                 if let Some(table) = self.catalog.get_temp_catalog().resolve_temp_table(table) {
                     return Ok(ResolvedEntry::Entry(CatalogEntry::Table(table)));
                 }
-                // If catalog is anything but "default", we know we need to do
+                // If catalog is an external database, we know we need to do
                 // external resolution since we don't store info about
                 // individual tables.
-                if catalog != DEFAULT_CATALOG {
-                    let db_ent = self.catalog.resolve_database(catalog).ok_or_else(|| {
-                        ResolveError(format!("unable to find database entry for '{catalog}'"))
-                    })?;
+                let db_ent = self
+                    .catalog
+                    .resolve_database(catalog)
+                    .ok_or_else(|| ResolveError::MissingDatabase(catalog.to_string()))?;
+                if !matches!(db_ent.options, DatabaseOptions::Internal(_)) {
                     return Ok(ResolvedEntry::NeedsExternalResolution {
                         db_ent,
                         schema: schema.clone(),
@@ -137,6 +170,59 @@ impl<'a> EntryResolver<'a> {
             }
         }
 
-        Err(ResolveError(format!("failed to find table: {reference}")))
+        Err(ResolveError::MissingTable {
+            suggestions: self.table_suggestions(&reference),
+            reference: reference.to_string(),
+        })
+    }
+
+    /// Get tables, views and table functions with names close to the table in
+    /// `reference`, qualified with their schema when not on the search path.
+    fn table_suggestions(&self, reference: &TableReference) -> Vec<String> {
+        let qualified = !matches!(reference, TableReference::Bare { .. });
+
+        let mut candidates = Vec::new();
+        if !qualified {
+            candidates.extend(
+                self.catalog
+                    .get_temp_catalog()
+                    .get_table_entries()
+                    .into_iter()
+                    .map(|ent| ent.meta.name),
+            );
+        }
+
+        for ent in self.catalog.iter_entries() {
+            let schema = match ent.parent_entry {
+                Some(CatalogEntry::Schema(schema)) => schema,
+                _ => continue,
+            };
+            match ent.entry {
+                CatalogEntry::Table(_) | CatalogEntry::View(_) => (),
+                CatalogEntry::Function(func)
+                    if !qualified
+                        && ent.builtin
+                        && func.func_type == FunctionType::TableReturning => {}
+                _ => continue,
+            }
+            // Builtin schemas can be referenced from any native database.
+            let in_database = schema.meta.builtin
+                || self
+                    .catalog
+                    .get_by_oid(schema.meta.parent)
+                    .is_some_and(|db| db.get_meta().name == self.database);
+            if !in_database {
+                continue;
+            }
+
+            let name = &ent.entry.get_meta().name;
+            if !qualified && self.schema_search_path.contains(&schema.meta.name) {
+                candidates.push(name.clone());
+            } else {
+                candidates.push(format!("{}.{}", schema.meta.name, name));
+            }
+        }
+
+        closest_matches(reference.table(), candidates)
     }
 }
@@ -0,0 +1,259 @@
+//! SCRAM-SHA-256 password verifiers.
+//!
+//! Passwords for roles are never stored. Instead a verifier is derived from the
+//! password and stored in the catalog, using the same format as Postgres:
+//!
+//! `SCRAM-SHA-256$<iterations>:<salt>$<StoredKey>:<ServerKey>`
+//!
+//! The verifier is enough to check a client's proof during a SCRAM exchange
+//! (RFC 5802), but can't be used to authenticate as the role.
+//!
+//! Passwords aren't normalized with SASLprep, which matches how Postgres
+//! handles passwords that SASLprep would reject.
+
+use std::fmt;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{digest, hmac, pbkdf2};
+
+use crate::errors::{ExecError, Result};
+
+const SCHEME: &str = "SCRAM-SHA-256";
+
+/// Length of the keys stored in a verifier.
+const KEY_LEN: usize = digest::SHA256_OUTPUT_LEN;
+
+/// Length of generated salts.
+const SALT_LEN: usize = 16;
+
+/// A SCRAM-SHA-256 verifier for a password.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ScramVerifier {
+    pub iterations: u32,
+    pub salt: Vec<u8>,
+    pub stored_key: [u8; KEY_LEN],
+    pub server_key: [u8; KEY_LEN],
+}
+
+impl ScramVerifier {
+    /// Number of iterations used for new verifiers. Same as the Postgres
+    /// default.
+    pub const DEFAULT_ITERATIONS: u32 = 4096;
+
+    /// Create a verifier for a password using a random salt.
+    pub fn new(password: &str) -> Result<ScramVerifier> {
+        let salt = random_bytes::<SALT_LEN>()?;
+        Ok(Self::with_salt(password, &salt, Self::DEFAULT_ITERATIONS))
+    }
+
+    /// Create a verifier for a password using the given salt.
+    pub fn with_salt(password: &str, salt: &[u8], iterations: u32) -> ScramVerifier {
+        let iterations = NonZeroU32::new(iterations).unwrap_or(NonZeroU32::MIN);
+        let mut salted_password = [0; KEY_LEN];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            salt,
+            password.as_bytes(),
+            &mut salted_password,
+        );
+
+        let client_key = hmac_sha256(&salted_password, b"Client Key");
+        let server_key = hmac_sha256(&salted_password, b"Server Key");
+
+        ScramVerifier {
+            iterations: iterations.get(),
+            salt: salt.to_vec(),
+            stored_key: sha256(&client_key),
+            server_key,
+        }
+    }
+
+    /// Create a verifier that no password matches.
+    ///
+    /// Used to go through a full exchange for roles without a password so
+    /// that clients can't tell which roles exist.
+    pub fn mock() -> Result<ScramVerifier> {
+        Ok(ScramVerifier {
+            iterations: Self::DEFAULT_ITERATIONS,
+            salt: random_bytes::<SALT_LEN>()?.to_vec(),
+            stored_key: random_bytes()?,
+            server_key: random_bytes()?,
+        })
+    }
+
+    /// Check the proof sent by a client for the given auth message.
+    pub fn verify_client_proof(&self, auth_message: &[u8], proof: &[u8]) -> bool {
+        if proof.len() != KEY_LEN {
+            return false;
+        }
+        let client_signature = hmac_sha256(&self.stored_key, auth_message);
+        let mut client_key = [0; KEY_LEN];
+        for (i, key) in client_key.iter_mut().enumerate() {
+            *key = proof[i] ^ client_signature[i];
+        }
+
+        // Compare in constant time.
+        sha256(&client_key)
+            .iter()
+            .zip(self.stored_key.iter())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+    }
+
+    /// Compute the signature sent to the client to prove that the server
+    /// knows the verifier.
+    pub fn server_signature(&self, auth_message: &[u8]) -> [u8; KEY_LEN] {
+        hmac_sha256(&self.server_key, auth_message)
+    }
+}
+
+impl fmt::Display for ScramVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{SCHEME}${}:{}${}:{}",
+            self.iterations,
+            BASE64.encode(&self.salt),
+            BASE64.encode(self.stored_key),
+            BASE64.encode(self.server_key),
+        )
+    }
+}
+
+impl fmt::Debug for ScramVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Don't print the keys.
+        f.debug_struct("ScramVerifier")
+            .field("iterations", &self.iterations)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FromStr for ScramVerifier {
+    type Err = ExecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ExecError::InvalidScramVerifier;
+
+        let (scheme, rest) = s.split_once('$').ok_or_else(invalid)?;
+        if scheme != SCHEME {
+            return Err(invalid());
+        }
+        let (params, keys) = rest.split_once('$').ok_or_else(invalid)?;
+        let (iterations, salt) = params.split_once(':').ok_or_else(invalid)?;
+        let (stored_key, server_key) = keys.split_once(':').ok_or_else(invalid)?;
+
+        let iterations: u32 = iterations.parse().map_err(|_| invalid())?;
+        if iterations == 0 {
+            return Err(invalid());
+        }
+        let decode_key = |key: &str| -> Result<[u8; KEY_LEN]> {
+            BASE64
+                .decode(key)
+                .ok()
+                .and_then(|key| key.try_into().ok())
+                .ok_or_else(invalid)
+        };
+
+        Ok(ScramVerifier {
+            iterations,
+            salt: BASE64.decode(salt).map_err(|_| invalid())?,
+            stored_key: decode_key(stored_key)?,
+            server_key: decode_key(server_key)?,
+        })
+    }
+}
+
+/// Generate random bytes using a secure random number generator.
+pub fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    let mut buf = [0; N];
+    SystemRandom::new()
+        .fill(&mut buf)
+        .map_err(|_| ExecError::Internal("failed to generate random bytes".to_string()))?;
+    Ok(buf)
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; KEY_LEN] {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::sign(&key, data)
+        .as_ref()
+        .try_into()
+        .expect("hmac tag should be the length of a sha256 digest")
+}
+
+fn sha256(data: &[u8]) -> [u8; KEY_LEN] {
+    digest::digest(&digest::SHA256, data)
+        .as_ref()
+        .try_into()
+        .expect("digest should be the length of a sha256 digest")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifier_roundtrips() {
+        let verifier = ScramVerifier::new("hunter2").unwrap();
+        let parsed: ScramVerifier = verifier.to_string().parse().unwrap();
+        assert_eq!(verifier, parsed);
+    }
+
+    #[test]
+    fn with_salt_known_value() {
+        let salt = BASE64.decode("1fZf0mOAkkbi8xk9mDYfqA==").unwrap();
+        let verifier = ScramVerifier::with_salt("password", &salt, 4096);
+        assert_eq!(
+            "SCRAM-SHA-256$4096:1fZf0mOAkkbi8xk9mDYfqA==$0qlFKne3F8gfldxaAHGt+iho3BDLZFDB2l9nxDVV5/8=:6OIT5owCUO13U3x8dOwMWO+qX5PeX3BdbCkJMVqiCXA=",
+            verifier.to_string()
+        );
+    }
+
+    #[test]
+    fn verify_client_proof() {
+        // Proof as computed by a client that knows the password.
+        let verifier = ScramVerifier::new("hunter2").unwrap();
+        let auth_message = b"n=,r=abc,r=abcdef,s=c2FsdA==,i=4096,c=biws,r=abcdef";
+
+        let mut salted_password = [0; KEY_LEN];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(verifier.iterations).unwrap(),
+            &verifier.salt,
+            b"hunter2",
+            &mut salted_password,
+        );
+        let client_key = hmac_sha256(&salted_password, b"Client Key");
+        let client_signature = hmac_sha256(&sha256(&client_key), auth_message);
+        let proof: Vec<_> = client_key
+            .iter()
+            .zip(client_signature.iter())
+            .map(|(a, b)| a ^ b)
+            .collect();
+
+        assert!(verifier.verify_client_proof(auth_message, &proof));
+        assert!(!verifier.verify_client_proof(b"other", &proof));
+        assert!(!ScramVerifier::mock()
+            .unwrap()
+            .verify_client_proof(auth_message, &proof));
+    }
+
+    #[test]
+    fn invalid_verifiers() {
+        let invalid = [
+            "",
+            "hunter2",
+            "md5$4096:c2FsdA==$a:b",
+            "SCRAM-SHA-256$0:c2FsdA==$AAAA:AAAA",
+            "SCRAM-SHA-256$4096:c2FsdA==$AAAA:AAAA",
+        ];
+        for s in invalid {
+            s.parse::<ScramVerifier>().unwrap_err();
+        }
+    }
+}
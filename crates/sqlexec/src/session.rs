@@ -5,20 +5,28 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use crate::admission::{AdmittedStream, QueryLimiter};
+use crate::audit::AuditLog;
+use crate::change_feed::ChangeFeed;
 use crate::context::local::{LocalSessionContext, Portal, PreparedStatement};
 use crate::distexec::scheduler::{OutputSink, Scheduler};
 use crate::distexec::stream::create_coalescing_adapter;
 use crate::environment::EnvironmentReader;
 use crate::errors::{ExecError, Result};
+use crate::memory::MemoryTracker;
 use crate::parser::StatementWithExtensions;
+use crate::plan_cache::PlanCache;
 use crate::planner::logical_plan::*;
 use crate::planner::physical_plan::{
     get_count_from_batch, get_operation_from_batch, GENERIC_OPERATION_AND_COUNT_PHYSICAL_SCHEMA,
     GENERIC_OPERATION_PHYSICAL_SCHEMA,
 };
 use crate::planner::session_planner::SessionPlanner;
+use crate::query_history::QueryHistory;
 use crate::remote::client::RemoteClient;
+use crate::remote::distribute::Workers;
 use crate::remote::planner::{DDLExtensionPlanner, RemotePhysicalPlanner};
+use crate::user_functions::{UserTableFunc, UserTableFuncs};
 use catalog::mutator::CatalogMutator;
 use catalog::session_catalog::SessionCatalog;
 use datafusion::arrow::datatypes::Schema;
@@ -31,16 +39,23 @@ use datafusion::physical_plan::{
 };
 use datafusion::physical_planner::{DefaultPhysicalPlanner, PhysicalPlanner};
 use datafusion::scalar::ScalarValue;
-use datafusion_ext::metrics::AggregatedMetrics;
+use datafusion_ext::asof::AsofJoinPlanner;
+use datafusion_ext::gapfill::GapFillPlanner;
+use datafusion_ext::metrics::{AggregatedMetrics, ExecutionProgress};
+use datafusion_ext::recursive::RecursiveQueryPlanner;
+use datafusion_ext::reload::ConfigReloader;
+use datafusion_ext::sample::SamplePlanner;
 use datafusion_ext::session_metrics::{
     BatchStreamWithMetricSender, ExecutionStatus, QueryMetrics, SessionMetricsHandler,
 };
 use datafusion_ext::vars::SessionVars;
+use datafusion_ext::warnings::Warning;
 use datasources::native::access::NativeTableStorage;
 use futures::{Stream, StreamExt};
 use once_cell::sync::Lazy;
 use pgrepr::format::Format;
 use telemetry::Tracker;
+use tracing::{info_span, Instrument};
 use uuid::Uuid;
 
 static EMPTY_EXEC_PLAN: Lazy<Arc<dyn ExecutionPlan>> = Lazy::new(|| {
@@ -91,10 +106,18 @@ pub enum ExecutionResult {
     CreateView,
     /// A table was renamed.
     AlterTable,
+    /// A schema was renamed.
+    AlterSchema,
     /// A database was renamed.
     AlterDatabase,
+    /// A comment was set on an object.
+    CommentOn,
+    /// A role was altered.
+    AlterRole,
     /// A tunnel was altered.
     AlterTunnelRotateKeys,
+    /// Statistics were collected for a table.
+    AnalyzeTable,
     /// A client local variable was set.
     Set,
     /// Tables dropped.
@@ -109,6 +132,12 @@ pub enum ExecutionResult {
     DropTunnel,
     /// Credentials are dropped.
     DropCredentials,
+    /// Catalog exported.
+    ExportCatalog,
+    /// Catalog imported.
+    ImportCatalog,
+    /// Catalog restored.
+    RestoreCatalog,
 }
 // this just makes the `prepare_statement` method a bit more ergonomic.
 pub struct PrepareStatementArg {
@@ -220,8 +249,12 @@ impl ExecutionResult {
             ExecutionResult::CreateSchema => "create_schema",
             ExecutionResult::CreateView => "create_view",
             ExecutionResult::AlterTable => "alter_table",
+            ExecutionResult::AlterSchema => "alter_schema",
             ExecutionResult::AlterDatabase => "alter_database",
+            ExecutionResult::CommentOn => "comment",
+            ExecutionResult::AlterRole => "alter_role",
             ExecutionResult::AlterTunnelRotateKeys => "alter_tunnel_rotate_keys",
+            ExecutionResult::AnalyzeTable => "analyze_table",
             ExecutionResult::Set => "set_local",
             ExecutionResult::DropTables => "drop_tables",
             ExecutionResult::DropViews => "drop_views",
@@ -229,6 +262,9 @@ impl ExecutionResult {
             ExecutionResult::DropDatabase => "drop_database",
             ExecutionResult::DropTunnel => "drop_tunnel",
             ExecutionResult::DropCredentials => "drop_credentials",
+            ExecutionResult::ExportCatalog => "export_catalog",
+            ExecutionResult::ImportCatalog => "import_catalog",
+            ExecutionResult::RestoreCatalog => "restore_catalog",
         }
     }
 
@@ -243,14 +279,20 @@ impl ExecutionResult {
                 | ExecutionResult::CreateSchema
                 | ExecutionResult::CreateView
                 | ExecutionResult::AlterTable
+                | ExecutionResult::AlterSchema
                 | ExecutionResult::AlterDatabase
+                | ExecutionResult::CommentOn
+                | ExecutionResult::AlterRole
                 | ExecutionResult::AlterTunnelRotateKeys
+                | ExecutionResult::AnalyzeTable
                 | ExecutionResult::DropTables
                 | ExecutionResult::DropViews
                 | ExecutionResult::DropSchemas
                 | ExecutionResult::DropDatabase
                 | ExecutionResult::DropTunnel
                 | ExecutionResult::DropCredentials
+                | ExecutionResult::ImportCatalog
+                | ExecutionResult::RestoreCatalog
         )
     }
 
@@ -281,8 +323,12 @@ impl ExecutionResult {
             "create_schema" => ExecutionResult::CreateSchema,
             "create_view" => ExecutionResult::CreateView,
             "alter_table" => ExecutionResult::AlterTable,
+            "alter_schema" => ExecutionResult::AlterSchema,
             "alter_database" => ExecutionResult::AlterDatabase,
+            "comment" => ExecutionResult::CommentOn,
+            "alter_role" => ExecutionResult::AlterRole,
             "alter_tunnel_rotate_keys" => ExecutionResult::AlterTunnelRotateKeys,
+            "analyze_table" => ExecutionResult::AnalyzeTable,
             "set" => ExecutionResult::Set,
             "drop_tables" => ExecutionResult::DropTables,
             "drop_views" => ExecutionResult::DropViews,
@@ -290,6 +336,9 @@ impl ExecutionResult {
             "drop_database" => ExecutionResult::DropDatabase,
             "drop_tunnel" => ExecutionResult::DropTunnel,
             "drop_credentials" => ExecutionResult::DropCredentials,
+            "export_catalog" => ExecutionResult::ExportCatalog,
+            "import_catalog" => ExecutionResult::ImportCatalog,
+            "restore_catalog" => ExecutionResult::RestoreCatalog,
             _ => return None,
         })
     }
@@ -338,8 +387,12 @@ impl fmt::Display for ExecutionResult {
             ExecutionResult::CreateSchema => write!(f, "Schema create"),
             ExecutionResult::CreateView => write!(f, "View created"),
             ExecutionResult::AlterTable => write!(f, "Table altered"),
+            ExecutionResult::AlterSchema => write!(f, "Schema altered"),
             ExecutionResult::AlterDatabase => write!(f, "Database altered"),
+            ExecutionResult::CommentOn => write!(f, "Comment set"),
+            ExecutionResult::AlterRole => write!(f, "Role altered"),
             ExecutionResult::AlterTunnelRotateKeys => write!(f, "Keys rotated"),
+            ExecutionResult::AnalyzeTable => write!(f, "Table analyzed"),
             ExecutionResult::Set => write!(f, "Local variable set"),
             ExecutionResult::DropTables => write!(f, "Table(s) dropped"),
             ExecutionResult::DropViews => write!(f, "View(s) dropped"),
@@ -347,6 +400,9 @@ impl fmt::Display for ExecutionResult {
             ExecutionResult::DropDatabase => write!(f, "Database(s) dropped"),
             ExecutionResult::DropTunnel => write!(f, "Tunnel(s) dropped"),
             ExecutionResult::DropCredentials => write!(f, "Credentials dropped"),
+            ExecutionResult::ExportCatalog => write!(f, "Catalog exported"),
+            ExecutionResult::ImportCatalog => write!(f, "Catalog imported"),
+            ExecutionResult::RestoreCatalog => write!(f, "Catalog restored"),
         }
     }
 }
@@ -382,6 +438,8 @@ impl RecordBatchStream for StreamAndFirstResult {
 /// in the future (e.g. consensus).
 pub struct Session {
     pub(crate) ctx: LocalSessionContext,
+    /// Plan currently being executed, for reporting progress.
+    progress: ExecutionProgress,
 }
 
 impl Session {
@@ -398,13 +456,32 @@ impl Session {
         tracker: Arc<Tracker>,
         spill_path: Option<PathBuf>,
         task_scheduler: Scheduler,
+        query_limiter: QueryLimiter,
+        memory_tracker: MemoryTracker,
+        audit_log: AuditLog,
+        query_history: QueryHistory,
+        change_feed: ChangeFeed,
+        config_reloader: Option<ConfigReloader>,
+        workers: Option<Arc<Workers>>,
+        table_funcs: UserTableFuncs,
+        plan_cache: PlanCache,
     ) -> Result<Session> {
-        let metrics_handler = SessionMetricsHandler::new(
+        let mut metrics_handler = SessionMetricsHandler::new(
             vars.user_id(),
             vars.database_id(),
             vars.connection_id(),
             tracker,
         );
+        if let Some(observer) = audit_log.session_observer(&vars, &catalog, &native_tables) {
+            metrics_handler = metrics_handler.with_observer(observer);
+        }
+        if let Some(observer) = query_history.session_observer(&vars, &catalog, &native_tables) {
+            metrics_handler = metrics_handler.with_observer(observer);
+        }
+        let catalog_mutator = match change_feed.session_observer(&vars, &catalog, &native_tables) {
+            Some(observer) => catalog_mutator.with_observer(observer),
+            None => catalog_mutator,
+        };
 
         let ctx = LocalSessionContext::new(
             vars,
@@ -414,9 +491,18 @@ impl Session {
             metrics_handler,
             spill_path,
             task_scheduler,
+            query_limiter,
+            memory_tracker,
+            config_reloader,
+            workers,
+            table_funcs,
+            plan_cache,
         )?;
 
-        Ok(Session { ctx })
+        Ok(Session {
+            ctx,
+            progress: ExecutionProgress::default(),
+        })
     }
 
     pub async fn attach_remote_session(
@@ -435,6 +521,19 @@ impl Session {
         self.ctx.register_env_reader(env_reader);
     }
 
+    /// Register a table function that can only be called from this session.
+    pub fn register_table_function(&mut self, func: UserTableFunc) -> Result<()> {
+        self.ctx.register_table_function(func)
+    }
+
+    /// Get a handle for observing the progress of queries executed by this
+    /// session.
+    ///
+    /// The handle can be read from while a query is executing.
+    pub fn execution_progress(&self) -> ExecutionProgress {
+        self.progress.clone()
+    }
+
     /// Return the DF session context.
     pub fn df_ctx(&self) -> &datafusion::prelude::SessionContext {
         self.ctx.df_ctx()
@@ -446,26 +545,36 @@ impl Session {
         plan: DfLogicalPlan,
         op: &OperationInfo,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        let state = self.ctx.df_ctx().state();
-        let plan = state.optimize(&plan)?;
-        if let Some(client) = self.ctx.exec_client() {
-            let planner = RemotePhysicalPlanner {
-                database_id: self.ctx.get_database_id(),
-                query_text: op.query_text(),
-                remote_client: client,
-                catalog: self.ctx.get_session_catalog(),
-            };
-            let plan = planner.create_physical_plan(&plan, &state).await?;
-            Ok(plan)
-        } else {
-            // TODO: Possible to not require a catalog clone here?
-            let ddl_planner = DDLExtensionPlanner::new(self.ctx.get_session_catalog().clone());
-            let planner =
-                DefaultPhysicalPlanner::with_extension_planners(vec![Arc::new(ddl_planner)]);
-            let plan = planner.create_physical_plan(&plan, &state).await?;
+        let span = info_span!("create_physical_plan");
+        async {
+            let state = self.ctx.df_state();
+            let plan = state.optimize(&plan)?;
+            if let Some(client) = self.ctx.exec_client() {
+                let planner = RemotePhysicalPlanner {
+                    database_id: self.ctx.get_database_id(),
+                    query_text: op.query_text(),
+                    remote_client: client,
+                    catalog: self.ctx.get_session_catalog(),
+                };
+                let plan = planner.create_physical_plan(&plan, &state).await?;
+                Ok(plan)
+            } else {
+                // TODO: Possible to not require a catalog clone here?
+                let ddl_planner = DDLExtensionPlanner::new(self.ctx.get_session_catalog().clone());
+                let planner = DefaultPhysicalPlanner::with_extension_planners(vec![
+                    Arc::new(RecursiveQueryPlanner),
+                    Arc::new(SamplePlanner),
+                    Arc::new(AsofJoinPlanner),
+                    Arc::new(GapFillPlanner),
+                    Arc::new(ddl_planner),
+                ]);
+                let plan = planner.create_physical_plan(&plan, &state).await?;
 
-            Ok(plan)
+                Ok(plan)
+            }
         }
+        .instrument(span)
+        .await
     }
 
     /// Execute a datafusion physical plan.
@@ -473,7 +582,18 @@ impl Session {
         &self,
         plan: Arc<dyn ExecutionPlan>,
     ) -> Result<SendableRecordBatchStream> {
-        let context = self.ctx.task_context();
+        self.execute_physical_plan_with_op(plan, &OperationInfo::default())
+            .await
+    }
+
+    /// Execute a datafusion physical plan, attributing memory used during
+    /// execution to the query described by `op`.
+    async fn execute_physical_plan_with_op(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        op: &OperationInfo,
+    ) -> Result<SendableRecordBatchStream> {
+        let context = self.ctx.task_context(op.query_text());
         let stream = if self.ctx.get_session_vars().enable_experimental_scheduler() {
             let scheduler = self.ctx.get_task_scheduler();
             let (sink, stream) =
@@ -498,6 +618,12 @@ impl Session {
         self.ctx.get_session_vars().clone()
     }
 
+    /// Take the warnings raised by queries since the last call, e.g. files
+    /// skipped while scanning.
+    pub fn take_new_warnings(&self) -> Vec<Warning> {
+        self.ctx.get_warnings().take_new()
+    }
+
     /// Prepare a parsed statement for future execution.
     pub async fn prepare_statement<T: TryInto<PrepareStatementArg, Error = ExecError>>(
         &mut self,
@@ -510,6 +636,19 @@ impl Session {
         self.ctx.prepare_statement(name, stmt.stmt, params).await
     }
 
+    /// Prepare a statement using a cached plan for the same SQL text, skipping
+    /// parsing and planning.
+    ///
+    /// Returns false if there's no usable cached plan.
+    pub async fn prepare_cached_statement(&mut self, name: String, sql: &str) -> Result<bool> {
+        self.ctx.prepare_cached_statement(name, sql).await
+    }
+
+    /// Cache the plan for a statement prepared from `sql`.
+    pub fn cache_prepared_statement(&self, name: &str, sql: &str) {
+        self.ctx.cache_prepared_statement(name, sql)
+    }
+
     /// Like 'prepare_statement', but for a portal.
     pub async fn prepare_portal(&mut self, portal_id: &str, query: &str) -> Result<()> {
         self.prepare_statement(portal_id.to_string(), query, Vec::new())
@@ -568,6 +707,7 @@ impl Session {
         //
         // We stub out transaction commands since many tools (even BI ones) will
         // try to open a transaction for some queries.
+        self.progress.clear();
         match plan {
             LogicalPlan::Noop => Ok((EMPTY_EXEC_PLAN.clone(), ExecutionResult::EmptyQuery)),
             LogicalPlan::Transaction(_plan) => {
@@ -575,7 +715,10 @@ impl Session {
             }
             LogicalPlan::Datafusion(plan) => {
                 let physical = self.create_physical_plan(plan, op).await?;
-                let stream = self.execute_physical_plan(physical.clone()).await?;
+                self.progress.set_plan(physical.clone());
+                let stream = self
+                    .execute_physical_plan_with_op(physical.clone(), op)
+                    .await?;
 
                 let stream = ExecutionResult::from_stream(stream).await;
 
@@ -642,6 +785,24 @@ impl Session {
             ..Default::default()
         };
 
+        // Wait for a query slot if the engine is at capacity. The slot is held
+        // until the stream for the query is finished.
+        let permit = match &plan {
+            LogicalPlan::Datafusion(_) => {
+                let user = self.ctx.get_session_vars().user_name();
+                match self.ctx.get_query_limiter().acquire(&user).await {
+                    Ok(permit) => Some(permit),
+                    Err(e) => {
+                        metrics.execution_status = ExecutionStatus::Fail;
+                        metrics.error_message = Some(e.to_string());
+                        self.ctx.get_metrics_handler().push_metric(metrics);
+                        return Err(e);
+                    }
+                }
+            }
+            _ => None,
+        };
+
         let stream = match self.execute_logical_plan(plan, &op).await {
             Ok((plan, result)) => match result {
                 ExecutionResult::Error(e) => {
@@ -658,13 +819,18 @@ impl Session {
                         ExecutionResult::Query { stream } => {
                             // Swap out the batch stream with one that will send
                             // metrics at the completions of the stream.
-                            ExecutionResult::Query {
-                                stream: Box::pin(BatchStreamWithMetricSender::new(
+                            let stream: SendableRecordBatchStream =
+                                Box::pin(BatchStreamWithMetricSender::new(
                                     stream,
                                     plan.clone(),
                                     metrics,
                                     self.ctx.get_metrics_handler(),
-                                )),
+                                ));
+                            ExecutionResult::Query {
+                                stream: match permit {
+                                    Some(permit) => Box::pin(AdmittedStream::new(stream, permit)),
+                                    None => stream,
+                                },
                             }
                         }
                         write_result @ ExecutionResult::InsertSuccess { .. }
@@ -744,7 +910,10 @@ impl Session {
             1 => {
                 let stmt = statements.pop_front().unwrap();
                 let planner = SessionPlanner::new(&self.ctx);
-                let plan = planner.plan_ast(stmt).await?;
+                let plan = planner
+                    .plan_ast(stmt)
+                    .instrument(info_span!("plan_statement"))
+                    .await?;
                 Ok(plan)
             }
             _ => Err(ExecError::String(
@@ -769,10 +938,9 @@ impl Session {
     ) -> Result<SendableRecordBatchStream> {
         let plan = self.create_logical_plan(query).await?;
         let plan = plan.try_into_datafusion_plan()?;
-        let plan = self
-            .create_physical_plan(plan, &op_info.unwrap_or_default())
-            .await?;
-        let stream = self.execute_physical_plan(plan).await?;
+        let op_info = op_info.unwrap_or_default();
+        let plan = self.create_physical_plan(plan, &op_info).await?;
+        let stream = self.execute_physical_plan_with_op(plan, &op_info).await?;
 
         Ok(stream)
     }
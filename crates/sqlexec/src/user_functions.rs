@@ -0,0 +1,343 @@
+//! Table functions registered by applications embedding GlareDB.
+//!
+//! User table functions allow data held by the application (in-memory caches,
+//! internal services, etc) to be queried alongside everything else, e.g.
+//! `SELECT * FROM my_func('arg', limit => 10)`.
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::datasource::streaming::StreamingTable;
+use datafusion::datasource::TableProvider;
+use datafusion::error::DataFusionError;
+use datafusion::execution::TaskContext;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::streaming::PartitionStream;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion::scalar::ScalarValue;
+use datafusion_ext::functions::FuncParamValue;
+use futures::future::BoxFuture;
+use futures::{stream, FutureExt, TryStreamExt};
+use sqlbuiltins::functions::FUNCTION_REGISTRY;
+
+use crate::errors::{ExecError, Result};
+
+/// Error returned by a user table function.
+pub type UserTableFuncError = Box<dyn std::error::Error + Send + Sync>;
+
+type UserTableFuncResult = Result<Vec<RecordBatch>, UserTableFuncError>;
+type UserTableFuncImpl =
+    dyn Fn(UserTableFuncArgs) -> BoxFuture<'static, UserTableFuncResult> + Send + Sync;
+
+/// Arguments a user table function was called with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserTableFuncArgs {
+    /// Positional arguments.
+    pub args: Vec<ScalarValue>,
+    /// Named arguments, e.g. `limit => 10`.
+    pub opts: HashMap<String, ScalarValue>,
+}
+
+/// A table function with a fixed output schema, backed by an async closure
+/// producing the function's batches.
+#[derive(Clone)]
+pub struct UserTableFunc {
+    name: String,
+    schema: SchemaRef,
+    func: Arc<UserTableFuncImpl>,
+}
+
+impl UserTableFunc {
+    /// Create a new table function.
+    ///
+    /// `func` is called each time the function is scanned during execution,
+    /// not during planning. Returned batches must have columns matching
+    /// `schema`.
+    ///
+    /// Names are matched as written, and unquoted identifiers in queries are
+    /// lowercased.
+    pub fn new<F, Fut>(name: impl Into<String>, schema: SchemaRef, func: F) -> Self
+    where
+        F: Fn(UserTableFuncArgs) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = UserTableFuncResult> + Send + 'static,
+    {
+        UserTableFunc {
+            name: name.into(),
+            schema,
+            func: Arc::new(move |args| func(args).boxed()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    /// Create a provider for a call to the function with the given arguments.
+    pub(crate) fn create_provider(
+        &self,
+        args: Vec<FuncParamValue>,
+        opts: HashMap<String, FuncParamValue>,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let args = UserTableFuncArgs {
+            args: args
+                .into_iter()
+                .map(|arg| self.arg_to_scalar(arg))
+                .collect::<Result<_>>()?,
+            opts: opts
+                .into_iter()
+                .map(|(name, arg)| Ok((name, self.arg_to_scalar(arg)?)))
+                .collect::<Result<_>>()?,
+        };
+
+        let partition = UserTableFuncPartition {
+            schema: self.schema.clone(),
+            func: self.func.clone(),
+            args,
+        };
+        let table = StreamingTable::try_new(self.schema.clone(), vec![Arc::new(partition)])?;
+        Ok(Arc::new(table))
+    }
+
+    fn arg_to_scalar(&self, arg: FuncParamValue) -> Result<ScalarValue> {
+        match arg {
+            FuncParamValue::Scalar(s) => Ok(s),
+            FuncParamValue::Ident(s) => Ok(ScalarValue::Utf8(Some(s))),
+            FuncParamValue::Array(_) => Err(ExecError::InvalidTableFunctionArgument {
+                func: self.name.clone(),
+                reason: "array arguments are not supported".to_string(),
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for UserTableFunc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserTableFunc")
+            .field("name", &self.name)
+            .field("schema", &self.schema)
+            .finish_non_exhaustive()
+    }
+}
+
+struct UserTableFuncPartition {
+    schema: SchemaRef,
+    func: Arc<UserTableFuncImpl>,
+    args: UserTableFuncArgs,
+}
+
+impl PartitionStream for UserTableFuncPartition {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let schema = self.schema.clone();
+        let batches = (self.func)(self.args.clone()).map(move |result| {
+            let batches = result.map_err(DataFusionError::External)?;
+            // Rebuild batches with the declared schema, checking the columns
+            // match it.
+            batches
+                .into_iter()
+                .map(|batch| {
+                    RecordBatch::try_new(schema.clone(), batch.columns().to_vec())
+                        .map_err(DataFusionError::from)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        });
+        let stream = batches
+            .into_stream()
+            .map_ok(|batches| stream::iter(batches.into_iter().map(Ok)))
+            .try_flatten();
+
+        Box::pin(RecordBatchStreamAdapter::new(self.schema.clone(), stream))
+    }
+}
+
+/// A set of user table functions, shared between everything it's cloned to.
+#[derive(Debug, Clone, Default)]
+pub struct UserTableFuncs {
+    funcs: Arc<RwLock<HashMap<String, Arc<UserTableFunc>>>>,
+}
+
+impl UserTableFuncs {
+    /// Register a function, replacing any existing function with the same
+    /// name.
+    ///
+    /// Errors if the name is already used by a builtin table function.
+    pub fn register(&self, func: UserTableFunc) -> Result<()> {
+        if FUNCTION_REGISTRY.get_table_func(&func.name).is_some() {
+            return Err(ExecError::TableFunctionConflictsWithBuiltin(func.name));
+        }
+        let mut funcs = self.funcs.write().unwrap();
+        funcs.insert(func.name.clone(), Arc::new(func));
+        Ok(())
+    }
+
+    /// Remove a function, returning whether or not it was registered.
+    pub fn deregister(&self, name: &str) -> bool {
+        self.funcs.write().unwrap().remove(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<UserTableFunc>> {
+        self.funcs.read().unwrap().get(name).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::arrow::array::{Int64Array, StringArray};
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion_ext::vars::SessionVars;
+
+    use super::*;
+    use crate::engine::{Engine, SessionStorageConfig};
+    use crate::session::ExecutionResult;
+    use crate::OperationInfo;
+
+    fn users_func() -> UserTableFunc {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        let batch_schema = schema.clone();
+        UserTableFunc::new("users", schema, move |args| {
+            let schema = batch_schema.clone();
+            async move {
+                let count = match args.args.first() {
+                    Some(ScalarValue::Int64(Some(n))) => *n,
+                    _ => 2,
+                };
+                let prefix = match args.opts.get("prefix") {
+                    Some(ScalarValue::Utf8(Some(p))) => p.clone(),
+                    _ => "user".to_string(),
+                };
+                let batch = RecordBatch::try_new(
+                    schema,
+                    vec![
+                        Arc::new(Int64Array::from_iter_values(0..count)),
+                        Arc::new(StringArray::from_iter_values(
+                            (0..count).map(|i| format!("{prefix}{i}")),
+                        )),
+                    ],
+                )?;
+                Ok(vec![batch])
+            }
+        })
+    }
+
+    async fn query(
+        sess: &mut crate::engine::TrackedSession,
+        sql: &str,
+    ) -> Result<Vec<RecordBatch>> {
+        let plan = sess.create_logical_plan(sql).await?;
+        let op = OperationInfo::new().with_query_text(sql.to_string());
+        match sess.execute_logical_plan(plan, &op).await? {
+            (_, ExecutionResult::Query { stream }) => Ok(stream.try_collect::<Vec<_>>().await?),
+            (_, ExecutionResult::Error(e)) => Err(e.into()),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn register_conflicting_with_builtin() {
+        let funcs = UserTableFuncs::default();
+        let func = UserTableFunc::new("generate_series", users_func().schema, |_| async {
+            Ok(Vec::new())
+        });
+        let err = funcs.register(func).unwrap_err();
+        assert!(matches!(
+            err,
+            ExecError::TableFunctionConflictsWithBuiltin(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn query_user_table_func() {
+        let engine = Engine::from_data_dir(None).await.unwrap();
+        engine.register_table_function(users_func()).unwrap();
+        let mut sess = engine
+            .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+            .await
+            .unwrap();
+
+        let batches = query(
+            &mut sess,
+            "SELECT name FROM users(3, prefix => 'u') WHERE id > 0 ORDER BY id",
+        )
+        .await
+        .unwrap();
+        let names: Vec<_> = batches
+            .iter()
+            .flat_map(|b| {
+                let col = b.column(0).as_any().downcast_ref::<StringArray>().unwrap();
+                col.iter()
+                    .map(|v| v.unwrap().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(vec!["u1", "u2"], names);
+
+        // Joins with other sources.
+        let batches = query(
+            &mut sess,
+            "SELECT count(*) FROM users() u JOIN generate_series(0, 10) s ON u.id = s.generate_series",
+        )
+        .await
+        .unwrap();
+        let count = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .value(0);
+        assert_eq!(2, count);
+    }
+
+    #[tokio::test]
+    async fn session_funcs_shadow_engine_funcs() {
+        let engine = Engine::from_data_dir(None).await.unwrap();
+        engine.register_table_function(users_func()).unwrap();
+        let mut sess = engine
+            .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+            .await
+            .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![Field::new("n", DataType::Int64, false)]));
+        let batch_schema = schema.clone();
+        sess.register_table_function(UserTableFunc::new("users", schema, move |_| {
+            let schema = batch_schema.clone();
+            async move {
+                Ok(vec![RecordBatch::try_new(
+                    schema,
+                    vec![Arc::new(Int64Array::from(vec![42]))],
+                )?])
+            }
+        }))
+        .unwrap();
+
+        let batches = query(&mut sess, "SELECT n FROM users()").await.unwrap();
+        assert_eq!(1, batches[0].num_rows());
+
+        // Mismatched batches error during execution.
+        sess.register_table_function(UserTableFunc::new(
+            "bad",
+            Arc::new(Schema::new(vec![Field::new("n", DataType::Utf8, false)])),
+            |_| async {
+                let schema = Arc::new(Schema::new(vec![Field::new("n", DataType::Int64, false)]));
+                Ok(vec![RecordBatch::try_new(
+                    schema,
+                    vec![Arc::new(Int64Array::from(vec![1]))],
+                )?])
+            },
+        ))
+        .unwrap();
+        query(&mut sess, "SELECT * FROM bad()").await.unwrap_err();
+    }
+}
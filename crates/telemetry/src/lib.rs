@@ -1,7 +1,9 @@
 //! Small crate for telemetry code.
+pub mod metrics;
+
 use segment::message::{BatchMessage, Message, Track, User};
 use segment::{Batcher, Client, HttpClient};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinHandle;
 use tracing::{debug, error};
 use uuid::Uuid;
@@ -24,6 +26,13 @@ impl Tracker {
             }
         }
     }
+
+    /// Wait for all events tracked so far to be sent.
+    pub async fn flush(&self) {
+        if let Tracker::Segment(t) = self {
+            t.flush().await
+        }
+    }
 }
 
 impl From<SegmentTracker> for Tracker {
@@ -34,7 +43,7 @@ impl From<SegmentTracker> for Tracker {
 
 #[derive(Debug)]
 pub struct SegmentTracker {
-    tx: mpsc::Sender<BatchMessage>,
+    tx: mpsc::Sender<TrackerMessage>,
     /// Handle for background task responsible for sending segment events.
     _handle: JoinHandle<()>,
 }
@@ -68,10 +77,26 @@ impl SegmentTracker {
             ..Default::default()
         });
 
-        if let Err(e) = self.tx.try_send(msg) {
+        if let Err(e) = self.tx.try_send(TrackerMessage::Event(msg)) {
             error!(%e, "failed to send track message");
         }
     }
+
+    async fn flush(&self) {
+        let (tx, rx) = oneshot::channel();
+        if self.tx.send(TrackerMessage::Flush(tx)).await.is_err() {
+            error!("segment bulk batcher stopped, unable to flush");
+            return;
+        }
+        let _ = rx.await;
+    }
+}
+
+#[derive(Debug)]
+enum TrackerMessage {
+    Event(BatchMessage),
+    /// Send everything received so far, notifying the sender once done.
+    Flush(oneshot::Sender<()>),
 }
 
 /// Send batches to Segment in bulk.
@@ -84,19 +109,21 @@ impl SegmentTracker {
 struct BulkBatcher {
     segment_key: String,
     client: HttpClient,
-    rx: mpsc::Receiver<BatchMessage>,
+    rx: mpsc::Receiver<TrackerMessage>,
 }
 
 impl BulkBatcher {
     async fn run(mut self) {
         loop {
             let mut batch = Batcher::new(None);
+            let mut flushed = Vec::new();
 
             // Get first message.
             match self.rx.recv().await {
-                Some(msg) => {
+                Some(TrackerMessage::Event(msg)) => {
                     batch = self.push(batch, msg).await;
                 }
+                Some(TrackerMessage::Flush(tx)) => flushed.push(tx),
                 None => {
                     debug!("channel closed for segment bulk batcher");
                     return;
@@ -108,11 +135,17 @@ impl BulkBatcher {
             // Errors on empty or disconnected. Disconnected error is fine,
             // means we'll exit on next loop iteration.
             while let Ok(msg) = self.rx.try_recv() {
-                batch = self.push(batch, msg).await;
+                match msg {
+                    TrackerMessage::Event(msg) => batch = self.push(batch, msg).await,
+                    TrackerMessage::Flush(tx) => flushed.push(tx),
+                }
             }
 
             // And flush.
-            self.flush(batch).await
+            self.flush(batch).await;
+            for tx in flushed {
+                let _ = tx.send(());
+            }
         }
     }
 
@@ -0,0 +1,369 @@
+//! Process wide metrics exported in the Prometheus text format.
+//!
+//! Metrics are registered once in `METRICS` and updated in place from
+//! wherever the measured thing happens. Server binaries expose the encoded
+//! metrics on an HTTP endpoint for scraping.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+
+/// Metrics for this process.
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::default);
+
+/// Content type for the Prometheus text exposition format.
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Default histogram buckets, in seconds.
+const DEFAULT_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+/// All metrics tracked by GlareDB.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Queries executed, labeled by execution status.
+    pub queries: Labeled<Counter>,
+    /// Time taken to execute queries.
+    pub query_duration: Histogram,
+    /// Number of sessions currently open.
+    pub active_sessions: Gauge,
+    /// Bytes read from object storage, labeled by the type of source (e.g.
+    /// 's3').
+    pub object_store_bytes_read: Labeled<Counter>,
+    /// Datasource requests retried after transient errors, labeled by the
+    /// type of source.
+    pub datasource_retries: Labeled<Counter>,
+    /// Cache hits, labeled by the cache.
+    pub cache_hits: Labeled<Counter>,
+    /// Cache misses, labeled by the cache.
+    pub cache_misses: Labeled<Counter>,
+    /// Latency of RPCs made to metastore, labeled by RPC method.
+    pub metastore_rpc_duration: Labeled<Histogram>,
+}
+
+impl Metrics {
+    /// Encode all metrics using the Prometheus text format.
+    pub fn encode(&self) -> String {
+        let mut buf = String::new();
+        self.queries.encode(
+            &mut buf,
+            "glaredb_queries_total",
+            "Number of queries executed.",
+            "status",
+        );
+        self.query_duration.encode(
+            &mut buf,
+            "glaredb_query_duration_seconds",
+            "Time taken to execute queries.",
+        );
+        self.active_sessions.encode(
+            &mut buf,
+            "glaredb_active_sessions",
+            "Number of open sessions.",
+        );
+        self.object_store_bytes_read.encode(
+            &mut buf,
+            "glaredb_object_store_bytes_read_total",
+            "Bytes read from object storage.",
+            "source",
+        );
+        self.datasource_retries.encode(
+            &mut buf,
+            "glaredb_datasource_retries_total",
+            "Number of datasource requests retried after transient errors.",
+            "source",
+        );
+        self.cache_hits.encode(
+            &mut buf,
+            "glaredb_cache_hits_total",
+            "Number of cache lookups that found an entry.",
+            "cache",
+        );
+        self.cache_misses.encode(
+            &mut buf,
+            "glaredb_cache_misses_total",
+            "Number of cache lookups that didn't find an entry.",
+            "cache",
+        );
+        self.metastore_rpc_duration.encode(
+            &mut buf,
+            "glaredb_metastore_rpc_duration_seconds",
+            "Latency of RPCs made to metastore.",
+            "method",
+        );
+        buf
+    }
+}
+
+/// A monotonically increasing count.
+#[derive(Debug, Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    pub fn inc_by(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A value that can go up and down.
+#[derive(Debug, Default)]
+pub struct Gauge(AtomicI64);
+
+impl Gauge {
+    pub fn inc(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn dec(&self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> i64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn encode(&self, buf: &mut String, name: &str, help: &str) {
+        write_header(buf, name, help, "gauge");
+        writeln!(buf, "{name} {}", self.get()).unwrap();
+    }
+}
+
+/// Distribution of observed values over a fixed set of buckets.
+#[derive(Debug)]
+pub struct Histogram {
+    /// Upper bounds of each bucket.
+    bounds: &'static [f64],
+    /// Non-cumulative count for each bucket, with a final bucket for values
+    /// greater than all bounds.
+    counts: Vec<AtomicU64>,
+    /// Sum of all observed values, stored as f64 bits.
+    sum: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram {
+            bounds: DEFAULT_BUCKETS,
+            counts: (0..=DEFAULT_BUCKETS.len())
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            sum: AtomicU64::new(0.0_f64.to_bits()),
+        }
+    }
+}
+
+impl Histogram {
+    pub fn observe(&self, value: f64) {
+        let idx = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.counts[idx].fetch_add(1, Ordering::Relaxed);
+        let _ = self
+            .sum
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |sum| {
+                Some((f64::from_bits(sum) + value).to_bits())
+            });
+    }
+
+    pub fn observe_duration(&self, dur: Duration) {
+        self.observe(dur.as_secs_f64());
+    }
+
+    /// Total number of observed values.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().map(|c| c.load(Ordering::Relaxed)).sum()
+    }
+
+    fn encode(&self, buf: &mut String, name: &str, help: &str) {
+        write_header(buf, name, help, "histogram");
+        self.encode_samples(buf, name, "");
+    }
+
+    /// Write the samples for this histogram, prefixing each sample's labels
+    /// with `labels`.
+    fn encode_samples(&self, buf: &mut String, name: &str, labels: &str) {
+        let mut cumulative = 0;
+        for (idx, count) in self.counts.iter().enumerate() {
+            cumulative += count.load(Ordering::Relaxed);
+            let le = match self.bounds.get(idx) {
+                Some(bound) => bound.to_string(),
+                None => "+Inf".to_string(),
+            };
+            writeln!(buf, "{name}_bucket{{{labels}le=\"{le}\"}} {cumulative}").unwrap();
+        }
+        let sum = f64::from_bits(self.sum.load(Ordering::Relaxed));
+        let labels = labels.trim_end_matches(',');
+        if labels.is_empty() {
+            writeln!(buf, "{name}_sum {sum}").unwrap();
+            writeln!(buf, "{name}_count {cumulative}").unwrap();
+        } else {
+            writeln!(buf, "{name}_sum{{{labels}}} {sum}").unwrap();
+            writeln!(buf, "{name}_count{{{labels}}} {cumulative}").unwrap();
+        }
+    }
+}
+
+/// A set of metrics distinguished by the value of a single label.
+#[derive(Debug)]
+pub struct Labeled<T> {
+    metrics: Mutex<BTreeMap<String, Arc<T>>>,
+}
+
+impl<T> Default for Labeled<T> {
+    fn default() -> Self {
+        Labeled {
+            metrics: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl<T: Default> Labeled<T> {
+    /// Get the metric for a label value, creating it if it doesn't exist.
+    pub fn with_label(&self, value: &str) -> Arc<T> {
+        let mut metrics = self.metrics.lock().unwrap();
+        match metrics.get(value) {
+            Some(metric) => metric.clone(),
+            None => {
+                let metric = Arc::new(T::default());
+                metrics.insert(value.to_string(), metric.clone());
+                metric
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Vec<(String, Arc<T>)> {
+        let metrics = self.metrics.lock().unwrap();
+        metrics
+            .iter()
+            .map(|(label, metric)| (label.clone(), metric.clone()))
+            .collect()
+    }
+}
+
+impl Labeled<Counter> {
+    fn encode(&self, buf: &mut String, name: &str, help: &str, label: &str) {
+        write_header(buf, name, help, "counter");
+        for (value, counter) in self.snapshot() {
+            writeln!(
+                buf,
+                "{name}{{{label}=\"{}\"}} {}",
+                escape_label_value(&value),
+                counter.get()
+            )
+            .unwrap();
+        }
+    }
+}
+
+impl Labeled<Histogram> {
+    fn encode(&self, buf: &mut String, name: &str, help: &str, label: &str) {
+        write_header(buf, name, help, "histogram");
+        for (value, histogram) in self.snapshot() {
+            let labels = format!("{label}=\"{}\",", escape_label_value(&value));
+            histogram.encode_samples(buf, name, &labels);
+        }
+    }
+}
+
+fn write_header(buf: &mut String, name: &str, help: &str, typ: &str) {
+    writeln!(buf, "# HELP {name} {help}").unwrap();
+    writeln!(buf, "# TYPE {name} {typ}").unwrap();
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_counters_and_gauges() {
+        let metrics = Metrics::default();
+        metrics.queries.with_label("success").inc_by(3);
+        metrics.queries.with_label("fail").inc();
+        metrics.active_sessions.inc();
+        metrics.active_sessions.inc();
+        metrics.active_sessions.dec();
+        metrics.cache_hits.with_label("weird\"name").inc();
+
+        let out = metrics.encode();
+        assert!(
+            out.contains("# TYPE glaredb_queries_total counter\n"),
+            "{out}"
+        );
+        assert!(
+            out.contains("glaredb_queries_total{status=\"fail\"} 1\n"),
+            "{out}"
+        );
+        assert!(
+            out.contains("glaredb_queries_total{status=\"success\"} 3\n"),
+            "{out}"
+        );
+        assert!(out.contains("glaredb_active_sessions 1\n"), "{out}");
+        assert!(
+            out.contains("glaredb_cache_hits_total{cache=\"weird\\\"name\"} 1\n"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn encode_histograms() {
+        let metrics = Metrics::default();
+        metrics.query_duration.observe(0.002);
+        metrics.query_duration.observe(0.2);
+        metrics.query_duration.observe(100.0);
+        metrics
+            .metastore_rpc_duration
+            .with_label("fetch_catalog")
+            .observe_duration(Duration::from_millis(20));
+
+        assert_eq!(3, metrics.query_duration.count());
+
+        let out = metrics.encode();
+        assert!(
+            out.contains("glaredb_query_duration_seconds_bucket{le=\"0.005\"} 1\n"),
+            "{out}"
+        );
+        assert!(
+            out.contains("glaredb_query_duration_seconds_bucket{le=\"0.25\"} 2\n"),
+            "{out}"
+        );
+        assert!(
+            out.contains("glaredb_query_duration_seconds_bucket{le=\"+Inf\"} 3\n"),
+            "{out}"
+        );
+        assert!(
+            out.contains("glaredb_query_duration_seconds_count 3\n"),
+            "{out}"
+        );
+        assert!(out.contains(
+            "glaredb_metastore_rpc_duration_seconds_bucket{method=\"fetch_catalog\",le=\"0.025\"} 1\n"
+        ), "{out}");
+        assert!(
+            out.contains(
+                "glaredb_metastore_rpc_duration_seconds_count{method=\"fetch_catalog\"} 1\n"
+            ),
+            "{out}"
+        );
+    }
+}
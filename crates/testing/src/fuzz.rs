@@ -0,0 +1,11 @@
+//! Differential query fuzzing.
+//!
+//! Generates random queries against a seeded schema, runs them against both
+//! GlareDB and an oracle (DataFusion or Postgres), and reports crashes and
+//! results that differ, minimizing each failing query first.
+
+mod cli;
+mod fuzzer;
+mod generate;
+
+pub use cli::Cli;
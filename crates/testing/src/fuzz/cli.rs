@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, ValueEnum};
+use datafusion::prelude::SessionContext;
+use glaredb::server::ComputeServer;
+use logutil::{LoggingMode, Verbosity};
+use pgsrv::auth::SingleUserAuthenticator;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::{net::TcpListener, runtime::Builder};
+use tokio_postgres::config::Config as ClientConfig;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::fuzzer::{Failure, Fuzzer, Target};
+use super::generate::{Query, QueryGenerator, Schema};
+use crate::slt::runner::PgTestClient;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Oracle {
+    /// Run queries with DataFusion in process.
+    #[value(name = "datafusion")]
+    DataFusion,
+    /// Run queries against a Postgres server.
+    Postgres,
+}
+
+#[derive(Parser)]
+#[clap(name = "fuzz")]
+#[clap(about = "Compare results of generated queries between GlareDB and an oracle", long_about = None)]
+pub struct Cli {
+    #[clap(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Seed for generating the schema and queries.
+    ///
+    /// A random seed is used and printed if omitted. Runs with the same seed
+    /// generate the same schema and queries.
+    #[clap(long, value_parser)]
+    seed: Option<u64>,
+
+    /// Number of queries to generate.
+    #[clap(short, long, value_parser, default_value_t = 1000)]
+    queries: u64,
+
+    /// Only run the query with this index, as printed for a failure.
+    #[clap(long, value_parser)]
+    only_query: Option<u64>,
+
+    /// Stop after this many failures.
+    #[clap(long, value_parser, default_value_t = 10)]
+    max_failures: usize,
+
+    /// Connection string to use for connecting to GlareDB.
+    ///
+    /// If provided, an embedded server won't be started.
+    #[clap(short, long, value_parser)]
+    connection_string: Option<String>,
+
+    /// Database results are compared against.
+    #[arg(long, value_enum, default_value_t = Oracle::DataFusion)]
+    oracle: Oracle,
+
+    /// Connection string for the Postgres oracle.
+    ///
+    /// The database should use the "C" collation so text is ordered the same
+    /// way as in GlareDB.
+    #[clap(long, value_parser, required_if_eq("oracle", "postgres"))]
+    oracle_connection_string: Option<String>,
+
+    /// Report a query as a crash if GlareDB takes longer than this number of
+    /// seconds to run it.
+    #[clap(long, value_parser, default_value_t = 30)]
+    query_timeout: u64,
+
+    /// Don't minimize failing queries.
+    #[clap(long, value_parser)]
+    no_minimize: bool,
+
+    /// Directory reproducers for failing queries are written to.
+    #[clap(long, value_parser, default_value = "fuzz-failures")]
+    out_dir: PathBuf,
+}
+
+impl Cli {
+    pub fn run() -> Result<()> {
+        let cli = Self::parse();
+
+        let verbosity: Verbosity = cli.verbose.into();
+        let log_mode = match verbosity {
+            Verbosity::Info => LoggingMode::Compact,
+            Verbosity::Debug => LoggingMode::Full,
+            Verbosity::Trace => LoggingMode::Full,
+        };
+        logutil::init(cli.verbose, log_mode, None, None);
+
+        Builder::new_multi_thread()
+            .enable_all()
+            // See the SLT runner, planning deeply nested expressions needs a
+            // bigger stack.
+            .thread_stack_size(4 * 1024 * 1024)
+            .build()?
+            .block_on(cli.fuzz())
+    }
+
+    async fn fuzz(self) -> Result<()> {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        println!("Fuzzing with seed {seed}");
+
+        // Temp directory for metastore
+        let temp_dir = tempfile::tempdir()?;
+
+        let glaredb_config = match &self.connection_string {
+            Some(connection_string) => connection_string.parse()?,
+            None => start_embedded(temp_dir.path()).await?,
+        };
+        let oracle = match self.oracle {
+            Oracle::DataFusion => Target::DataFusion(SessionContext::new()),
+            Oracle::Postgres => {
+                // Required by clap.
+                let config: ClientConfig =
+                    self.oracle_connection_string.as_ref().unwrap().parse()?;
+                Target::Pg(PgTestClient::new(&config).await?)
+            }
+        };
+
+        let mut fuzzer = Fuzzer::connect(
+            glaredb_config,
+            oracle,
+            Duration::from_secs(self.query_timeout),
+        )
+        .await?;
+
+        let schema = Schema::generate(&mut StdRng::seed_from_u64(seed));
+        fuzzer.setup(&schema).await?;
+
+        let indexes = match self.only_query {
+            Some(idx) => idx..idx + 1,
+            None => 0..self.queries,
+        };
+
+        let mut num_queries = 0;
+        let mut failures = 0;
+        for idx in indexes {
+            num_queries += 1;
+            let query = QueryGenerator::new(&schema, query_rng(seed, idx)).generate();
+            let failure = match fuzzer.check(&query).await? {
+                Some(failure) => failure,
+                None => continue,
+            };
+            failures += 1;
+            error!(%query, %failure, "Query {idx} failed with a {}", failure.kind);
+
+            let (query, failure) = if self.no_minimize {
+                (query, failure)
+            } else {
+                info!("Minimizing query {idx}");
+                fuzzer.minimize(query, failure).await?
+            };
+            let path = self.write_reproducer(seed, idx, &schema, &query, &failure)?;
+            println!(
+                "Query {idx}: {}, reproducer written to {}",
+                failure.kind,
+                path.display()
+            );
+
+            if failures >= self.max_failures {
+                break;
+            }
+        }
+
+        println!("Ran {num_queries} queries, {failures} failed");
+        if failures > 0 {
+            Err(anyhow!("Fuzz failures"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Write SQL reproducing a failure, with the results of each side in
+    /// comments.
+    fn write_reproducer(
+        &self,
+        seed: u64,
+        idx: u64,
+        schema: &Schema,
+        query: &Query,
+        failure: &Failure,
+    ) -> Result<PathBuf> {
+        let mut sql = String::new();
+        writeln!(
+            sql,
+            "-- Fuzz {} for seed {seed}, query {idx}.",
+            failure.kind
+        )?;
+        writeln!(
+            sql,
+            "-- Rerun with `just fuzz --seed {seed} --only-query {idx}`."
+        )?;
+        writeln!(sql, "--")?;
+        for line in failure.to_string().lines() {
+            writeln!(sql, "-- {line}")?;
+        }
+        writeln!(sql)?;
+        for stmt in schema.create_statements() {
+            writeln!(sql, "{stmt};")?;
+        }
+        writeln!(sql)?;
+        writeln!(sql, "{query};")?;
+
+        std::fs::create_dir_all(&self.out_dir)?;
+        let path = self.out_dir.join(format!("seed-{seed}-query-{idx}.sql"));
+        std::fs::write(&path, sql)?;
+        Ok(path)
+    }
+}
+
+/// Rng for generating the query with index `idx`, so queries can be
+/// regenerated without generating the queries before them.
+fn query_rng(seed: u64, idx: u64) -> StdRng {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let base: u64 = rng.gen();
+    StdRng::seed_from_u64(base.wrapping_add(idx))
+}
+
+/// Start an embedded GlareDB server, returning the config to connect to it.
+async fn start_embedded(data_dir: &Path) -> Result<ClientConfig> {
+    let listener = TcpListener::bind("0.0.0.0:0").await?;
+    let addr = listener.local_addr()?;
+
+    let server = ComputeServer::builder()
+        .with_authenticator(SingleUserAuthenticator {
+            user: "glaredb".to_string(),
+            password: "glaredb".to_string(),
+        })
+        .with_pg_listener_opt(Some(listener))
+        .with_data_dir(data_dir.to_path_buf())
+        .with_storage_options(HashMap::new())
+        .integration_testing_mode(true)
+        .connect()
+        .await?;
+    tokio::spawn(server.serve());
+
+    let mut config = ClientConfig::new();
+    config
+        .user("glaredb")
+        .password("glaredb")
+        .dbname(&Uuid::new_v4().to_string())
+        .host(&addr.ip().to_string())
+        .port(addr.port());
+    Ok(config)
+}
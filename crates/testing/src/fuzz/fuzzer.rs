@@ -0,0 +1,271 @@
+//! Running queries against GlareDB and an oracle, and comparing the results.
+
+use std::fmt;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use datafusion::arrow::util::display::{ArrayFormatter, FormatOptions};
+use datafusion::prelude::SessionContext;
+use sqllogictest::{AsyncDB, DBOutput};
+use tokio_postgres::Config;
+
+use super::generate::{Query, Schema};
+use crate::slt::runner::PgTestClient;
+
+/// Max number of rows shown for each side of a failure.
+const MAX_DISPLAY_ROWS: usize = 20;
+
+/// Rows returned by a query, with every value formatted as text.
+pub type Rows = Vec<Vec<String>>;
+
+/// A database queries are run against.
+pub enum Target {
+    /// GlareDB or Postgres, over the Postgres protocol.
+    Pg(PgTestClient),
+    /// DataFusion, in process.
+    DataFusion(SessionContext),
+}
+
+impl Target {
+    /// Run `sql`, returning its rows or the error message.
+    ///
+    /// Values are formatted the same way as in SLTs, with `NULL` for nulls
+    /// and `(empty)` for empty strings.
+    pub async fn run(&mut self, sql: &str) -> Result<Rows, String> {
+        match self {
+            Self::Pg(client) => match client.run(sql).await {
+                Ok(DBOutput::Rows { rows, .. }) => Ok(rows),
+                Ok(DBOutput::StatementComplete(_)) => Ok(Vec::new()),
+                Err(e) => Err(e.to_string()),
+            },
+            Self::DataFusion(ctx) => {
+                async fn run_inner(ctx: &SessionContext, sql: &str) -> Result<Rows> {
+                    let batches = ctx.sql(sql).await?.collect().await?;
+                    let options = FormatOptions::default().with_null("NULL");
+
+                    let mut rows = Vec::new();
+                    for batch in batches {
+                        let formatters = batch
+                            .columns()
+                            .iter()
+                            .map(|col| ArrayFormatter::try_new(col.as_ref(), &options))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        for row_idx in 0..batch.num_rows() {
+                            let row = formatters
+                                .iter()
+                                .map(|f| {
+                                    let value = f.value(row_idx).to_string();
+                                    if value.is_empty() {
+                                        "(empty)".to_string()
+                                    } else {
+                                        value.trim().to_owned()
+                                    }
+                                })
+                                .collect();
+                            rows.push(row);
+                        }
+                    }
+                    Ok(rows)
+                }
+                run_inner(ctx, sql).await.map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        match self {
+            Self::Pg(client) => client.is_closed(),
+            Self::DataFusion(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// GlareDB closed the connection or didn't respond in time.
+    Crash,
+    /// Only one of GlareDB and the oracle returned an error.
+    Error,
+    /// Both returned rows, but they're different.
+    Mismatch,
+}
+
+impl fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            FailureKind::Crash => "crash",
+            FailureKind::Error => "error",
+            FailureKind::Mismatch => "mismatch",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A query that failed, with what each side returned.
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub kind: FailureKind,
+    pub glaredb: Result<Rows, String>,
+    pub oracle: Result<Rows, String>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_result(
+            f: &mut fmt::Formatter<'_>,
+            name: &str,
+            res: &Result<Rows, String>,
+        ) -> fmt::Result {
+            match res {
+                Ok(rows) => {
+                    writeln!(f, "{name} ({} rows):", rows.len())?;
+                    for row in rows.iter().take(MAX_DISPLAY_ROWS) {
+                        writeln!(f, "  {}", row.join(" | "))?;
+                    }
+                    if rows.len() > MAX_DISPLAY_ROWS {
+                        writeln!(f, "  ...")?;
+                    }
+                    Ok(())
+                }
+                Err(e) => writeln!(f, "{name} error: {e}"),
+            }
+        }
+        write_result(f, "glaredb", &self.glaredb)?;
+        write_result(f, "oracle", &self.oracle)
+    }
+}
+
+/// Runs queries against GlareDB and an oracle.
+pub struct Fuzzer {
+    glaredb: Target,
+    /// Used to reconnect after a crash.
+    glaredb_config: Config,
+    oracle: Target,
+    query_timeout: Duration,
+}
+
+impl Fuzzer {
+    pub async fn connect(
+        glaredb_config: Config,
+        oracle: Target,
+        query_timeout: Duration,
+    ) -> Result<Self> {
+        let glaredb = Target::Pg(PgTestClient::new(&glaredb_config).await?);
+        Ok(Fuzzer {
+            glaredb,
+            glaredb_config,
+            oracle,
+            query_timeout,
+        })
+    }
+
+    /// Create the schema's tables in both databases.
+    pub async fn setup(&mut self, schema: &Schema) -> Result<()> {
+        for sql in schema.create_statements() {
+            self.glaredb
+                .run(&sql)
+                .await
+                .map_err(|e| anyhow!("Unable to run `{sql}` in glaredb: {e}"))?;
+            self.oracle
+                .run(&sql)
+                .await
+                .map_err(|e| anyhow!("Unable to run `{sql}` in oracle: {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// Run `query` against both databases, returning how it failed, if it
+    /// did.
+    ///
+    /// Queries failing in both databases are assumed to be invalid, and
+    /// aren't failures.
+    pub async fn check(&mut self, query: &Query) -> Result<Option<Failure>> {
+        let sql = query.to_string();
+
+        let glaredb = tokio::time::timeout(self.query_timeout, self.glaredb.run(&sql)).await;
+        let crashed = glaredb.is_err() || self.glaredb.is_closed();
+        let glaredb =
+            glaredb.unwrap_or_else(|_| Err(format!("timed out after {:?}", self.query_timeout)));
+        if crashed {
+            self.glaredb = Target::Pg(PgTestClient::new(&self.glaredb_config).await?);
+        }
+
+        let oracle = self.oracle.run(&sql).await;
+
+        let kind = match (&glaredb, &oracle) {
+            _ if crashed => FailureKind::Crash,
+            (Err(_), Err(_)) => return Ok(None),
+            (Ok(glaredb), Ok(oracle)) if results_match(query, glaredb, oracle) => return Ok(None),
+            (Ok(_), Ok(_)) => FailureKind::Mismatch,
+            _ => FailureKind::Error,
+        };
+
+        Ok(Some(Failure {
+            kind,
+            glaredb,
+            oracle,
+        }))
+    }
+
+    /// Reduce `query` to a simpler query failing the same way.
+    ///
+    /// Repeatedly replaces the query with the first of its reductions that
+    /// still fails with the same kind of failure, until none do.
+    pub async fn minimize(&mut self, query: Query, failure: Failure) -> Result<(Query, Failure)> {
+        let (mut query, mut failure) = (query, failure);
+        'reduce: loop {
+            for candidate in query.reductions() {
+                if let Some(candidate_failure) = self.check(&candidate).await? {
+                    if candidate_failure.kind == failure.kind {
+                        query = candidate;
+                        failure = candidate_failure;
+                        continue 'reduce;
+                    }
+                }
+            }
+            return Ok((query, failure));
+        }
+    }
+}
+
+/// Compare results after normalizing values.
+///
+/// Rows are compared in order only if the query orders them.
+fn results_match(query: &Query, glaredb: &Rows, oracle: &Rows) -> bool {
+    let normalize = |rows: &Rows| {
+        let mut rows: Rows = rows
+            .iter()
+            .map(|row| row.iter().map(|v| normalize_value(v)).collect())
+            .collect();
+        if !query.order_by {
+            rows.sort();
+        }
+        rows
+    };
+    normalize(glaredb) == normalize(oracle)
+}
+
+/// Format a value so databases formatting it differently agree.
+///
+/// Booleans are spelled out, and numbers are rounded to a few decimal places
+/// with trailing zeros removed. Generated text never looks like a number or
+/// boolean, so this doesn't hide differences in text.
+fn normalize_value(value: &str) -> String {
+    match value {
+        "t" | "true" => return "true".to_string(),
+        "f" | "false" => return "false".to_string(),
+        _ => (),
+    }
+    match value.parse::<f64>() {
+        Ok(v) if v.is_finite() => {
+            let v = (v * 1e6).round() / 1e6;
+            // Avoid "-0".
+            if v == 0.0 {
+                "0".to_string()
+            } else {
+                v.to_string()
+            }
+        }
+        _ => value.to_string(),
+    }
+}
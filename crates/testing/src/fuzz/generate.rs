@@ -0,0 +1,752 @@
+//! Random schemas and queries.
+//!
+//! Everything is generated from a seeded rng, so the same seed always
+//! produces the same schema and queries. Values are kept small, and floats
+//! are multiples of 0.25, so results don't depend on overflow behavior or
+//! rounding.
+
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Words used for text values. None of them look like numbers or booleans,
+/// so normalizing results can't change them.
+const WORDS: &[&str] = &[
+    "apple", "banana", "cherry", "delta", "echo", "fig", "grape", "hotel", "", "x y",
+];
+
+/// Max depth of generated expressions.
+const MAX_DEPTH: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Int,
+    Float,
+    Text,
+    Bool,
+}
+
+impl ColumnType {
+    const ALL: [ColumnType; 4] = [
+        ColumnType::Int,
+        ColumnType::Float,
+        ColumnType::Text,
+        ColumnType::Bool,
+    ];
+
+    fn sql_type(&self) -> &'static str {
+        match self {
+            ColumnType::Int => "BIGINT",
+            ColumnType::Float => "DOUBLE PRECISION",
+            ColumnType::Text => "TEXT",
+            ColumnType::Bool => "BOOLEAN",
+        }
+    }
+
+    fn literal(&self, rng: &mut StdRng) -> String {
+        match self {
+            ColumnType::Int => rng.gen_range(-10..=10).to_string(),
+            ColumnType::Float => format!("{:.2}", rng.gen_range(-40..=40) as f64 * 0.25),
+            ColumnType::Text => format!("'{}'", WORDS.choose(rng).unwrap()),
+            ColumnType::Bool => rng.gen_bool(0.5).to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub name: String,
+    pub ty: ColumnType,
+}
+
+#[derive(Debug, Clone)]
+pub struct Table {
+    pub name: String,
+    pub columns: Vec<Column>,
+    /// SQL literals for each row.
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Statements (re)creating the table.
+    pub fn create_statements(&self) -> Vec<String> {
+        let mut stmts = vec![format!("DROP TABLE IF EXISTS {}", self.name)];
+        if self.rows.is_empty() {
+            let columns: Vec<_> = self
+                .columns
+                .iter()
+                .map(|c| format!("{} {}", c.name, c.ty.sql_type()))
+                .collect();
+            stmts.push(format!(
+                "CREATE TABLE {} ({})",
+                self.name,
+                columns.join(", ")
+            ));
+            return stmts;
+        }
+
+        let casts: Vec<_> = self
+            .columns
+            .iter()
+            .map(|c| format!("CAST({0} AS {1}) AS {0}", c.name, c.ty.sql_type()))
+            .collect();
+        let names: Vec<_> = self.columns.iter().map(|c| c.name.as_str()).collect();
+        let rows: Vec<_> = self
+            .rows
+            .iter()
+            .map(|row| format!("({})", row.join(", ")))
+            .collect();
+        stmts.push(format!(
+            "CREATE TABLE {} AS SELECT {} FROM (VALUES {}) AS v({})",
+            self.name,
+            casts.join(", "),
+            rows.join(", "),
+            names.join(", ")
+        ));
+        stmts
+    }
+}
+
+/// Tables queries are generated against.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub tables: Vec<Table>,
+}
+
+impl Schema {
+    pub fn generate(rng: &mut StdRng) -> Schema {
+        let tables = (0..rng.gen_range(2..=3))
+            .map(|i| {
+                // Every table has an int column to join on.
+                let mut types = vec![ColumnType::Int];
+                types.extend(
+                    (0..rng.gen_range(1..=4)).map(|_| *ColumnType::ALL.choose(rng).unwrap()),
+                );
+                types.shuffle(rng);
+
+                let columns: Vec<_> = types
+                    .into_iter()
+                    .enumerate()
+                    .map(|(j, ty)| Column {
+                        name: format!("c{j}"),
+                        ty,
+                    })
+                    .collect();
+
+                // The first row never has nulls, since some engines infer
+                // the types of values from it.
+                let rows = (0..rng.gen_range(0..=20))
+                    .map(|row| {
+                        columns
+                            .iter()
+                            .map(|c| {
+                                if row > 0 && rng.gen_bool(0.15) {
+                                    "NULL".to_string()
+                                } else if c.ty == ColumnType::Int {
+                                    // Narrower range for more join matches.
+                                    rng.gen_range(-5..=5).to_string()
+                                } else {
+                                    c.ty.literal(rng)
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                Table {
+                    name: format!("fuzz_t{i}"),
+                    columns,
+                    rows,
+                }
+            })
+            .collect();
+        Schema { tables }
+    }
+
+    pub fn create_statements(&self) -> Vec<String> {
+        self.tables
+            .iter()
+            .flat_map(|t| t.create_statements())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Column {
+        table: String,
+        name: String,
+    },
+    Literal(String),
+    Binary {
+        op: &'static str,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Not(Box<Expr>),
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+    Function {
+        name: &'static str,
+        args: Vec<Expr>,
+    },
+    Case {
+        when: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Box<Expr>,
+    },
+    Cast {
+        expr: Box<Expr>,
+        ty: ColumnType,
+    },
+    /// An aggregate, `count(*)` if there's no argument.
+    Aggregate {
+        name: &'static str,
+        distinct: bool,
+        arg: Option<Box<Expr>>,
+    },
+}
+
+impl Expr {
+    fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Column { .. } | Expr::Literal(_) => Vec::new(),
+            Expr::Binary { left, right, .. } => vec![left, right],
+            Expr::Not(expr) | Expr::IsNull { expr, .. } | Expr::Cast { expr, .. } => vec![expr],
+            Expr::Function { args, .. } => args.iter().collect(),
+            Expr::Case {
+                when,
+                then,
+                otherwise,
+            } => vec![when, then, otherwise],
+            Expr::Aggregate { arg, .. } => arg.iter().map(|arg| arg.as_ref()).collect(),
+        }
+    }
+
+    /// Simpler versions of this expression, replacing it or one of its
+    /// descendants with one of its children.
+    fn reductions(&self) -> Vec<Expr> {
+        let mut reduced: Vec<Expr> = self.children().into_iter().cloned().collect();
+        match self {
+            Expr::Binary { op, left, right } => {
+                reduced.extend(left.reductions().into_iter().map(|left| Expr::Binary {
+                    op,
+                    left: Box::new(left),
+                    right: right.clone(),
+                }));
+                reduced.extend(right.reductions().into_iter().map(|right| Expr::Binary {
+                    op,
+                    left: left.clone(),
+                    right: Box::new(right),
+                }));
+            }
+            Expr::Not(expr) => reduced.extend(
+                expr.reductions()
+                    .into_iter()
+                    .map(|e| Expr::Not(Box::new(e))),
+            ),
+            Expr::IsNull { expr, negated } => {
+                reduced.extend(expr.reductions().into_iter().map(|e| Expr::IsNull {
+                    expr: Box::new(e),
+                    negated: *negated,
+                }))
+            }
+            Expr::Cast { expr, ty } => {
+                reduced.extend(expr.reductions().into_iter().map(|e| Expr::Cast {
+                    expr: Box::new(e),
+                    ty: *ty,
+                }))
+            }
+            Expr::Aggregate {
+                name,
+                distinct,
+                arg: Some(arg),
+            } => {
+                if *distinct {
+                    reduced.push(Expr::Aggregate {
+                        name,
+                        distinct: false,
+                        arg: Some(arg.clone()),
+                    });
+                }
+                reduced.extend(arg.reductions().into_iter().map(|e| Expr::Aggregate {
+                    name,
+                    distinct: *distinct,
+                    arg: Some(Box::new(e)),
+                }))
+            }
+            _ => (),
+        }
+        // Replacing an aggregate with its argument doesn't make sense in a
+        // grouped query, and the checks would reject it anyway.
+        if matches!(self, Expr::Aggregate { .. }) {
+            reduced.retain(|e| matches!(e, Expr::Aggregate { .. }));
+        }
+        reduced
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Column { table, name } => write!(f, "{table}.{name}"),
+            Expr::Literal(lit) => write!(f, "{lit}"),
+            Expr::Binary { op, left, right } => write!(f, "({left} {op} {right})"),
+            Expr::Not(expr) => write!(f, "(NOT {expr})"),
+            Expr::IsNull { expr, negated } => {
+                let not = if *negated { " NOT" } else { "" };
+                write!(f, "({expr} IS{not} NULL)")
+            }
+            Expr::Function { name, args } => {
+                let args: Vec<_> = args.iter().map(|a| a.to_string()).collect();
+                write!(f, "{name}({})", args.join(", "))
+            }
+            Expr::Case {
+                when,
+                then,
+                otherwise,
+            } => write!(f, "(CASE WHEN {when} THEN {then} ELSE {otherwise} END)"),
+            Expr::Cast { expr, ty } => write!(f, "CAST({expr} AS {})", ty.sql_type()),
+            Expr::Aggregate {
+                name,
+                distinct,
+                arg,
+            } => {
+                let distinct = if *distinct { "DISTINCT " } else { "" };
+                match arg {
+                    Some(arg) => write!(f, "{name}({distinct}{arg})"),
+                    None => write!(f, "{name}(*)"),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinKind {
+    Inner,
+    Left,
+    Right,
+    Full,
+}
+
+impl fmt::Display for JoinKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            JoinKind::Inner => "INNER JOIN",
+            JoinKind::Left => "LEFT JOIN",
+            JoinKind::Right => "RIGHT JOIN",
+            JoinKind::Full => "FULL JOIN",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    pub kind: JoinKind,
+    pub table: String,
+    pub alias: String,
+    pub on: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub table: String,
+    pub alias: String,
+    pub join: Option<Join>,
+    /// Output expressions, aliased as `o0`, `o1`, ...
+    pub projection: Vec<Expr>,
+    pub filter: Option<Expr>,
+    pub group_by: Vec<Expr>,
+    /// Whether rows are ordered by every output column.
+    pub order_by: bool,
+    /// Only generated with `order_by`, otherwise results would be
+    /// nondeterministic.
+    pub limit: Option<u64>,
+}
+
+impl Query {
+    /// Number of output columns.
+    pub fn num_columns(&self) -> usize {
+        self.projection.len()
+    }
+
+    /// Simpler versions of this query, used to minimize failing queries.
+    ///
+    /// Reductions may produce invalid queries, which are expected to fail in
+    /// both engines and be rejected.
+    pub fn reductions(&self) -> Vec<Query> {
+        let mut reduced = Vec::new();
+        let mut with = |f: &dyn Fn(&mut Query)| {
+            let mut query = self.clone();
+            f(&mut query);
+            reduced.push(query);
+        };
+
+        if self.join.is_some() {
+            with(&|q| q.join = None);
+        }
+        if self.filter.is_some() {
+            with(&|q| q.filter = None);
+        }
+        if self.limit.is_some() {
+            with(&|q| q.limit = None);
+        }
+        if self.order_by {
+            with(&|q| {
+                q.order_by = false;
+                q.limit = None;
+            });
+        }
+        if !self.group_by.is_empty() {
+            with(&|q| q.group_by.clear());
+        }
+        for idx in 0..self.group_by.len() {
+            with(&|q| {
+                q.group_by.remove(idx);
+            });
+        }
+        if self.projection.len() > 1 {
+            for idx in 0..self.projection.len() {
+                with(&|q| {
+                    q.projection.remove(idx);
+                });
+            }
+        }
+
+        for (idx, expr) in self.projection.iter().enumerate() {
+            for e in expr.reductions() {
+                let mut query = self.clone();
+                query.projection[idx] = e;
+                reduced.push(query);
+            }
+        }
+        if let Some(filter) = &self.filter {
+            for e in filter.reductions() {
+                let mut query = self.clone();
+                query.filter = Some(e);
+                reduced.push(query);
+            }
+        }
+        if let Some(join) = &self.join {
+            for e in join.on.reductions() {
+                let mut query = self.clone();
+                query.join.as_mut().unwrap().on = e;
+                reduced.push(query);
+            }
+        }
+
+        reduced
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let projection: Vec<_> = self
+            .projection
+            .iter()
+            .enumerate()
+            .map(|(idx, e)| format!("{e} AS o{idx}"))
+            .collect();
+        write!(
+            f,
+            "SELECT {} FROM {} AS {}",
+            projection.join(", "),
+            self.table,
+            self.alias
+        )?;
+        if let Some(join) = &self.join {
+            write!(
+                f,
+                " {} {} AS {} ON {}",
+                join.kind, join.table, join.alias, join.on
+            )?;
+        }
+        if let Some(filter) = &self.filter {
+            write!(f, " WHERE {filter}")?;
+        }
+        if !self.group_by.is_empty() {
+            let group_by: Vec<_> = self.group_by.iter().map(|e| e.to_string()).collect();
+            write!(f, " GROUP BY {}", group_by.join(", "))?;
+        }
+        if self.order_by {
+            let order_by: Vec<_> = (0..self.projection.len())
+                .map(|idx| format!("o{idx} NULLS FIRST"))
+                .collect();
+            write!(f, " ORDER BY {}", order_by.join(", "))?;
+        }
+        if let Some(limit) = self.limit {
+            write!(f, " LIMIT {limit}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Generates queries against a schema.
+pub struct QueryGenerator<'a> {
+    schema: &'a Schema,
+    rng: StdRng,
+    /// Columns in scope, with the alias of their table.
+    scope: Vec<(String, Column)>,
+}
+
+impl<'a> QueryGenerator<'a> {
+    pub fn new(schema: &'a Schema, rng: StdRng) -> Self {
+        QueryGenerator {
+            schema,
+            rng,
+            scope: Vec::new(),
+        }
+    }
+
+    pub fn generate(mut self) -> Query {
+        let table = self.schema.tables.choose(&mut self.rng).unwrap();
+        self.add_scope("a", table);
+
+        let join = if self.rng.gen_bool(0.4) {
+            let other = self.schema.tables.choose(&mut self.rng).unwrap();
+            let kind = *[
+                JoinKind::Inner,
+                JoinKind::Left,
+                JoinKind::Right,
+                JoinKind::Full,
+            ]
+            .choose(&mut self.rng)
+            .unwrap();
+            let left = self.column_of("a", table, ColumnType::Int);
+            let right = self.column_of("b", other, ColumnType::Int);
+            self.add_scope("b", other);
+            Some(Join {
+                kind,
+                table: other.name.clone(),
+                alias: "b".to_string(),
+                on: Expr::Binary {
+                    op: "=",
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+            })
+        } else {
+            None
+        };
+
+        let filter = self
+            .rng
+            .gen_bool(0.5)
+            .then(|| self.expr(ColumnType::Bool, 0));
+
+        let (projection, group_by) = if self.rng.gen_bool(0.3) {
+            let mut group_by = Vec::new();
+            for _ in 0..self.rng.gen_range(0..=2) {
+                let (table, col) = self.scope.choose(&mut self.rng).unwrap().clone();
+                let expr = Expr::Column {
+                    table,
+                    name: col.name,
+                };
+                if !group_by.contains(&expr) {
+                    group_by.push(expr);
+                }
+            }
+            let mut projection = group_by.clone();
+            for _ in 0..self.rng.gen_range(1..=3) {
+                let aggregate = self.aggregate();
+                projection.push(aggregate);
+            }
+            projection.shuffle(&mut self.rng);
+            (projection, group_by)
+        } else {
+            let projection = (0..self.rng.gen_range(1..=4))
+                .map(|_| {
+                    let ty = *ColumnType::ALL.choose(&mut self.rng).unwrap();
+                    self.expr(ty, 0)
+                })
+                .collect();
+            (projection, Vec::new())
+        };
+
+        let order_by = self.rng.gen_bool(0.3);
+        let limit = (order_by && self.rng.gen_bool(0.5)).then(|| self.rng.gen_range(0..=10));
+
+        Query {
+            table: table.name.clone(),
+            alias: "a".to_string(),
+            join,
+            projection,
+            filter,
+            group_by,
+            order_by,
+            limit,
+        }
+    }
+
+    fn add_scope(&mut self, alias: &str, table: &Table) {
+        self.scope
+            .extend(table.columns.iter().map(|c| (alias.to_string(), c.clone())));
+    }
+
+    fn column_of(&mut self, alias: &str, table: &Table, ty: ColumnType) -> Expr {
+        let cols: Vec<_> = table.columns.iter().filter(|c| c.ty == ty).collect();
+        Expr::Column {
+            table: alias.to_string(),
+            name: cols.choose(&mut self.rng).unwrap().name.clone(),
+        }
+    }
+
+    /// A column of type `ty` in scope, if there is one.
+    fn column(&mut self, ty: ColumnType) -> Option<Expr> {
+        let cols: Vec<_> = self.scope.iter().filter(|(_, c)| c.ty == ty).collect();
+        cols.choose(&mut self.rng).map(|(table, c)| Expr::Column {
+            table: table.clone(),
+            name: c.name.clone(),
+        })
+    }
+
+    fn leaf(&mut self, ty: ColumnType) -> Expr {
+        if self.rng.gen_bool(0.7) {
+            if let Some(col) = self.column(ty) {
+                return col;
+            }
+        }
+        if self.rng.gen_bool(0.05) {
+            return Expr::Literal("NULL".to_string());
+        }
+        Expr::Literal(ty.literal(&mut self.rng))
+    }
+
+    fn expr(&mut self, ty: ColumnType, depth: usize) -> Expr {
+        if depth >= MAX_DEPTH || self.rng.gen_bool(0.3) {
+            return self.leaf(ty);
+        }
+        let depth = depth + 1;
+
+        match self.rng.gen_range(0..4) {
+            // Shared by all types.
+            0 => Expr::Case {
+                when: Box::new(self.expr(ColumnType::Bool, depth)),
+                then: Box::new(self.expr(ty, depth)),
+                otherwise: Box::new(self.expr(ty, depth)),
+            },
+            1 => Expr::Function {
+                name: "coalesce",
+                args: vec![self.expr(ty, depth), self.expr(ty, depth)],
+            },
+            _ => match ty {
+                ColumnType::Int => match self.rng.gen_range(0..3) {
+                    0 => Expr::Function {
+                        name: "length",
+                        args: vec![self.expr(ColumnType::Text, depth)],
+                    },
+                    1 => Expr::Function {
+                        name: "abs",
+                        args: vec![self.expr(ty, depth)],
+                    },
+                    _ => self.arithmetic(ty, depth),
+                },
+                ColumnType::Float => match self.rng.gen_range(0..3) {
+                    0 => Expr::Cast {
+                        expr: Box::new(self.expr(ColumnType::Int, depth)),
+                        ty,
+                    },
+                    1 => Expr::Function {
+                        name: "abs",
+                        args: vec![self.expr(ty, depth)],
+                    },
+                    _ => self.arithmetic(ty, depth),
+                },
+                ColumnType::Text => match self.rng.gen_range(0..3) {
+                    0 => Expr::Function {
+                        name: *["upper", "lower"].choose(&mut self.rng).unwrap(),
+                        args: vec![self.expr(ty, depth)],
+                    },
+                    1 => Expr::Cast {
+                        expr: Box::new(self.expr(ColumnType::Int, depth)),
+                        ty,
+                    },
+                    _ => Expr::Binary {
+                        op: "||",
+                        left: Box::new(self.expr(ty, depth)),
+                        right: Box::new(self.expr(ty, depth)),
+                    },
+                },
+                ColumnType::Bool => match self.rng.gen_range(0..4) {
+                    0 => Expr::Not(Box::new(self.expr(ty, depth))),
+                    1 => Expr::IsNull {
+                        expr: Box::new(self.any_expr(depth)),
+                        negated: self.rng.gen_bool(0.5),
+                    },
+                    2 => Expr::Binary {
+                        op: *["AND", "OR"].choose(&mut self.rng).unwrap(),
+                        left: Box::new(self.expr(ty, depth)),
+                        right: Box::new(self.expr(ty, depth)),
+                    },
+                    _ => {
+                        let operand = *ColumnType::ALL.choose(&mut self.rng).unwrap();
+                        Expr::Binary {
+                            op: *["=", "<>", "<", "<=", ">", ">="]
+                                .choose(&mut self.rng)
+                                .unwrap(),
+                            left: Box::new(self.expr(operand, depth)),
+                            right: Box::new(self.expr(operand, depth)),
+                        }
+                    }
+                },
+            },
+        }
+    }
+
+    fn any_expr(&mut self, depth: usize) -> Expr {
+        let ty = *ColumnType::ALL.choose(&mut self.rng).unwrap();
+        self.expr(ty, depth)
+    }
+
+    fn arithmetic(&mut self, ty: ColumnType, depth: usize) -> Expr {
+        Expr::Binary {
+            op: *["+", "-", "*"].choose(&mut self.rng).unwrap(),
+            left: Box::new(self.expr(ty, depth)),
+            right: Box::new(self.expr(ty, depth)),
+        }
+    }
+
+    fn aggregate(&mut self) -> Expr {
+        let aggregate = |name, distinct, arg: Expr| Expr::Aggregate {
+            name,
+            distinct,
+            arg: Some(Box::new(arg)),
+        };
+        let distinct = self.rng.gen_bool(0.2);
+        match self.rng.gen_range(0..5) {
+            0 => Expr::Aggregate {
+                name: "count",
+                distinct: false,
+                arg: None,
+            },
+            1 => {
+                let arg = self.any_expr(1);
+                aggregate("count", distinct, arg)
+            }
+            2 => {
+                let ty = *[ColumnType::Int, ColumnType::Float]
+                    .choose(&mut self.rng)
+                    .unwrap();
+                let arg = self.expr(ty, 1);
+                aggregate(
+                    *["sum", "avg"].choose(&mut self.rng).unwrap(),
+                    distinct,
+                    arg,
+                )
+            }
+            _ => {
+                let ty = *[ColumnType::Int, ColumnType::Float, ColumnType::Text]
+                    .choose(&mut self.rng)
+                    .unwrap();
+                let arg = self.expr(ty, 1);
+                aggregate(*["min", "max"].choose(&mut self.rng).unwrap(), false, arg)
+            }
+        }
+    }
+}
@@ -1 +1,2 @@
+pub mod fuzz;
 pub mod slt;
@@ -1,5 +1,7 @@
 //! Utility to run SQL Logic Tests.
 
 mod cli;
+mod directives;
 pub mod runner;
+mod snapshot;
 mod test;
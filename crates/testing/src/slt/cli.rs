@@ -12,6 +12,7 @@ use anyhow::{anyhow, Result};
 use clap::Parser;
 use glaredb::args::StorageConfigArgs;
 use glaredb::server::ComputeServer;
+use object_store_util::fixtures::{set_fixture_mode, FixtureMode};
 use tokio::{net::TcpListener, runtime::Builder, sync::mpsc, time::Instant};
 use tokio_postgres::config::Config as ClientConfig;
 use uuid::Uuid;
@@ -22,6 +23,21 @@ use crate::slt::test::{
 
 use super::test::ClientProtocol;
 
+/// Variable containing the schema a test runs in.
+const SCHEMA_VAR: &str = "SCHEMA";
+
+/// Options for running each test.
+#[derive(Clone, Copy)]
+struct TestOptions {
+    protocol: ClientProtocol,
+    /// Max time each record in a test may take.
+    record_timeout: Duration,
+    /// Run each test in a new schema.
+    isolate_schemas: bool,
+    /// Rewrite `EXPLAIN` snapshots that don't match.
+    update_snapshots: bool,
+}
+
 #[derive(Parser)]
 #[clap(name = "slt-runner")]
 #[clap(about = "Run sqllogictests against a GlareDB server", long_about = None)]
@@ -70,6 +86,43 @@ pub struct Cli {
     #[clap(long, value_parser, default_value_t = 5 * 60)]
     timeout: u64,
 
+    /// Fail a test if any of its statements or queries take longer than this
+    /// number of seconds.
+    #[clap(long, value_parser, default_value_t = 2 * 60)]
+    record_timeout: u64,
+
+    /// Run each test in its own uniquely named schema, available to tests as
+    /// `${SCHEMA}`.
+    ///
+    /// Tests are created in the schema by setting the `search_path`, which
+    /// allows tests to run concurrently against a shared database, such as one
+    /// provided with `--connection-string`. The schema is dropped if the test
+    /// passes.
+    #[clap(long, value_parser)]
+    isolate_schemas: bool,
+
+    /// Rewrite `EXPLAIN` snapshots that don't match the current plans instead
+    /// of failing.
+    ///
+    /// Review the changes to the `.snap` files before committing them.
+    #[clap(long, value_parser)]
+    update_snapshots: bool,
+
+    /// Record every object read from S3 and GCS into this directory.
+    ///
+    /// Only applies to the embedded server.
+    #[clap(long, value_parser, conflicts_with = "replay_fixtures")]
+    record_fixtures: Option<PathBuf>,
+
+    /// Replay objects recorded with `--record-fixtures` instead of reading
+    /// from S3 and GCS.
+    ///
+    /// Environment variables that aren't set are replaced with placeholders,
+    /// so tests can run without credentials. Only applies to the embedded
+    /// server.
+    #[clap(long, value_parser)]
+    replay_fixtures: Option<PathBuf>,
+
     /// Exclude these tests from the run.
     #[clap(short, long, value_parser)]
     exclude: Vec<String>,
@@ -111,7 +164,16 @@ impl Cli {
             Verbosity::Debug => LoggingMode::Full,
             Verbosity::Trace => LoggingMode::Full,
         };
-        logutil::init(cli.verbose, log_mode, None);
+        logutil::init(cli.verbose, log_mode, None, None);
+
+        let fixture_mode = match (&cli.record_fixtures, &cli.replay_fixtures) {
+            (Some(dir), _) => Some(FixtureMode::Record(dir.clone())),
+            (_, Some(dir)) => Some(FixtureMode::Replay(dir.clone())),
+            (None, None) => None,
+        };
+        if let Some(mode) = fixture_mode {
+            set_fixture_mode(mode).map_err(|_| anyhow!("Fixture mode already set"))?;
+        }
 
         // Abort the program on panic. This will ensure that slt tests will
         // never pass if there's a panic somewhere.
@@ -317,11 +379,11 @@ impl Cli {
             let tx = jobs_tx.clone();
             let hooks = Arc::clone(&hooks);
 
-            let protocol = self.protocol;
+            let opts = self.test_options();
             let data_dir = data_dir.to_path_buf();
 
             tokio::spawn(async move {
-                let res = Self::run_test(protocol, data_dir, &test_name, test, cfg, hooks).await;
+                let res = Self::run_test(opts, data_dir, &test_name, test, cfg, hooks).await;
                 tx.send((test_name.clone(), res)).unwrap();
             });
         }
@@ -376,8 +438,17 @@ impl Cli {
         }
     }
 
+    fn test_options(&self) -> TestOptions {
+        TestOptions {
+            protocol: self.protocol,
+            record_timeout: Duration::from_secs(self.record_timeout),
+            isolate_schemas: self.isolate_schemas,
+            update_snapshots: self.update_snapshots,
+        }
+    }
+
     async fn run_test(
-        mode: ClientProtocol,
+        opts: TestOptions,
         data_dir: PathBuf,
         test_name: &str,
         test: Test,
@@ -385,7 +456,7 @@ impl Cli {
         hooks: Arc<TestHooks>,
     ) -> Result<()> {
         info!("Running test: `{}`", test_name);
-        let client = match mode {
+        let client = match opts.protocol {
             ClientProtocol::Postgres => TestClient::Pg(PgTestClient::new(&client_config).await?),
             ClientProtocol::Rpc => {
                 TestClient::Rpc(RpcTestClient::new(data_dir, &client_config).await?)
@@ -396,6 +467,7 @@ impl Cli {
         };
 
         async fn run_test_inner(
+            opts: TestOptions,
             client: TestClient,
             test_name: &str,
             test: Test,
@@ -406,6 +478,28 @@ impl Cli {
 
             let mut local_vars = HashMap::new();
 
+            // Create the schema before any hooks run so they create objects
+            // in it too.
+            let schema = if opts.isolate_schemas {
+                let schema = format!("slt_{}", Uuid::new_v4().simple());
+                for sql in [
+                    format!("CREATE SCHEMA {schema}"),
+                    format!("SET search_path TO {schema}"),
+                ] {
+                    client
+                        .execute(&sql)
+                        .await
+                        .map_err(|e| anyhow!("Unable to create schema for test: {e}"))?;
+                }
+                Some(schema)
+            } else {
+                None
+            };
+            local_vars.insert(
+                SCHEMA_VAR.to_string(),
+                schema.clone().unwrap_or_else(|| "public".to_string()),
+            );
+
             // Run the actual test
             let hooks = hooks
                 .iter()
@@ -419,8 +513,14 @@ impl Cli {
             }
 
             // Run the actual test
-            test.execute(&client_config, client.clone(), &mut local_vars)
-                .await?;
+            test.execute(
+                &client_config,
+                client.clone(),
+                &mut local_vars,
+                opts.record_timeout,
+                opts.update_snapshots,
+            )
+            .await?;
 
             // Run the post-test hooks
             for (pattern, hook) in hooks {
@@ -429,13 +529,20 @@ impl Cli {
                     .await?;
             }
 
+            if let Some(schema) = schema {
+                client
+                    .execute(&format!("DROP SCHEMA {schema} CASCADE;"))
+                    .await
+                    .map_err(|e| anyhow!("Unable to drop schema for test: {e}"))?;
+            }
+
             let time_taken = Instant::now().duration_since(start);
             tracing::debug!(?time_taken, %test_name, "Done executing");
 
             Ok(())
         }
 
-        let res = run_test_inner(client.clone(), test_name, test, client_config, hooks).await;
+        let res = run_test_inner(opts, client.clone(), test_name, test, client_config, hooks).await;
         // No need to wait for session's close handler since we don't wait for
         // sessions to end in integration testing mode while closing the server.
         let _ = client.close().await;
@@ -0,0 +1,163 @@
+//! Directives changing how query results are compared.
+//!
+//! Directives are comments in a test file, and apply to every query in the
+//! file, including the files it includes:
+//!
+//! ```text
+//! # directive: sort_rows
+//! # directive: round_floats 4
+//! # directive: float_tolerance 1e-9
+//! ```
+//!
+//! - `sort_rows` sorts both the actual and the expected rows before comparing
+//!   them, for sources that return rows in a nondeterministic order.
+//! - `round_floats <DIGITS>` rounds numbers with a fractional part to `DIGITS`
+//!   decimal places in both the actual and the expected rows.
+//! - `float_tolerance <TOLERANCE>` considers numbers equal if they're within
+//!   `TOLERANCE` of each other, scaled by the larger number if it's greater
+//!   than one.
+
+use std::future::Future;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use sqllogictest::default_validator;
+
+const DIRECTIVE_PREFIX: &str = "# directive:";
+
+/// How results are compared, set by directives.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResultOptions {
+    pub sort_rows: bool,
+    pub round_floats: Option<usize>,
+    pub float_tolerance: Option<f64>,
+}
+
+tokio::task_local! {
+    static RESULT_OPTIONS: ResultOptions;
+}
+
+impl ResultOptions {
+    /// Apply the directives in `script`, read from `path`.
+    pub fn parse_directives(&mut self, script: &str, path: &Path) -> Result<()> {
+        for (idx, line) in script.lines().enumerate() {
+            let Some(directive) = line.trim().strip_prefix(DIRECTIVE_PREFIX) else {
+                continue;
+            };
+            let invalid = |msg: &str| {
+                anyhow!(
+                    "Invalid directive at {}:{}: {msg}",
+                    path.to_string_lossy(),
+                    idx + 1
+                )
+            };
+
+            let mut args = directive.split_whitespace();
+            match (args.next(), args.next(), args.next()) {
+                (Some("sort_rows"), None, None) => self.sort_rows = true,
+                (Some("round_floats"), Some(digits), None) => {
+                    self.round_floats = Some(
+                        digits
+                            .parse()
+                            .map_err(|_| invalid("expected a number of digits"))?,
+                    )
+                }
+                (Some("float_tolerance"), Some(tolerance), None) => {
+                    let tolerance: f64 = tolerance
+                        .parse()
+                        .map_err(|_| invalid("expected a tolerance"))?;
+                    if tolerance.is_nan() || tolerance < 0.0 {
+                        return Err(invalid("tolerance must not be negative"));
+                    }
+                    self.float_tolerance = Some(tolerance)
+                }
+                _ => {
+                    return Err(invalid(
+                        "expected `sort_rows`, `round_floats <DIGITS>`, or `float_tolerance <TOLERANCE>`",
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run `fut` with these options used by [`validate`].
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        RESULT_OPTIONS.scope(self, fut).await
+    }
+
+    fn normalize(&self, value: &str) -> String {
+        match (self.round_floats, parse_fractional(value)) {
+            (Some(digits), Some(num)) => format!("{num:.digits$}"),
+            _ => value.to_string(),
+        }
+    }
+
+    fn values_equal(&self, a: &str, b: &str) -> bool {
+        if a == b {
+            return true;
+        }
+        match (self.float_tolerance, a.parse::<f64>(), b.parse::<f64>()) {
+            (Some(tolerance), Ok(a), Ok(b)) => {
+                let scale = a.abs().max(b.abs()).max(1.0);
+                (a - b).abs() <= tolerance * scale
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Parse a number with a fractional part or exponent, leaving integers as
+/// they are.
+fn parse_fractional(value: &str) -> Option<f64> {
+    if !value.contains(['.', 'e', 'E']) {
+        return None;
+    }
+    value.parse().ok().filter(|num: &f64| num.is_finite())
+}
+
+/// Validates query results using the options of the current test's
+/// directives.
+///
+/// Values are compared separately, splitting on whitespace the same as the
+/// default validator does.
+pub fn validate(actual: &[Vec<String>], expected: &[String]) -> bool {
+    let opts = RESULT_OPTIONS
+        .try_with(|opts| opts.clone())
+        .unwrap_or_default();
+    if opts == ResultOptions::default() {
+        return default_validator(actual, expected);
+    }
+
+    let mut actual: Vec<Vec<String>> = actual
+        .iter()
+        .map(|row| {
+            row.iter()
+                .flat_map(|col| col.split_whitespace())
+                .map(|value| opts.normalize(value))
+                .collect()
+        })
+        .collect();
+    let mut expected: Vec<Vec<String>> = expected
+        .iter()
+        .map(|row| {
+            row.split_whitespace()
+                .map(|value| opts.normalize(value))
+                .collect()
+        })
+        .collect();
+
+    if opts.sort_rows {
+        actual.sort();
+        expected.sort();
+    }
+
+    actual.len() == expected.len()
+        && actual.iter().zip(&expected).all(|(actual, expected)| {
+            actual.len() == expected.len()
+                && actual
+                    .iter()
+                    .zip(expected)
+                    .all(|(a, b)| opts.values_equal(a, b))
+        })
+}
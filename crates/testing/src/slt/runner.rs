@@ -5,10 +5,11 @@ use walkdir::WalkDir;
 
 use crate::slt::{
     cli::Cli,
+    snapshot::SNAPSHOT_TEST_SUFFIX,
     test::{Test, TestHooks},
 };
 
-pub use crate::slt::test::{FnTest, Hook, TestClient, TestHook};
+pub use crate::slt::test::{FnTest, Hook, PgTestClient, TestClient, TestHook};
 
 #[derive(Default)]
 pub struct SltRunner {
@@ -38,10 +39,16 @@ impl SltRunner {
         validate_test_file(file_path)?;
 
         let test_name = file_path.strip_prefix(prefix)?.to_string_lossy();
-        // Remove the ".slt" extension to get the test_name.
-        let test_name = test_name.trim_end_matches(".slt");
-
-        self.add_test(test_name.to_string(), Test::File(file_path.to_path_buf()))
+        // Remove the ".slt" or ".explain.sql" extension to get the test_name.
+        let (test_name, test) = match test_name.strip_suffix(SNAPSHOT_TEST_SUFFIX) {
+            Some(name) => (name, Test::Snapshot(file_path.to_path_buf())),
+            None => (
+                test_name.trim_end_matches(".slt"),
+                Test::File(file_path.to_path_buf()),
+            ),
+        };
+
+        self.add_test(test_name.to_string(), test)
     }
 
     pub fn test_files_dir(mut self, dir: impl AsRef<Path>) -> Result<Self> {
@@ -116,9 +123,10 @@ fn validate_test_file(file_path: &Path) -> Result<()> {
 
     match file_path.extension() {
         Some(ext) if ext.to_string_lossy().as_ref() == "slt" => {}
+        _ if file_path.to_string_lossy().ends_with(SNAPSHOT_TEST_SUFFIX) => {}
         _ => {
             return Err(anyhow!(
-                "File `{}` doesn't have `.slt` extension",
+                "File `{}` doesn't have `.slt` or `{SNAPSHOT_TEST_SUFFIX}` extension",
                 file_path.to_string_lossy()
             ))
         }
@@ -0,0 +1,171 @@
+//! Golden file tests for `EXPLAIN` output.
+//!
+//! A snapshot test is a file ending in `.explain.sql` containing statements,
+//! each ending with a `;` at the end of a line. Statements are run in order,
+//! and the output of every `EXPLAIN` statement is compared against the
+//! snapshot next to the file, which replaces the `.sql` extension with
+//! `.snap`:
+//!
+//! ```text
+//! EXPLAIN SELECT * FROM t WHERE a > 1;
+//! ----
+//! logical_plan
+//!   Filter: t.a > Int64(1)
+//!     TableScan: t projection=[a, b]
+//! physical_plan
+//!   ...
+//! ```
+//!
+//! Details depending on the machine running the test, such as the number of
+//! partitions, are replaced with placeholders. Missing snapshots are written
+//! on the first run, and running with `--update-snapshots` rewrites snapshots
+//! that don't match.
+
+use std::fmt::Write as _;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use similar::TextDiff;
+use sqllogictest::{AsyncDB, DBOutput};
+
+use super::test::TestClient;
+
+/// Suffix of snapshot test files.
+pub const SNAPSHOT_TEST_SUFFIX: &str = ".explain.sql";
+
+/// Extension of snapshot files, replacing `.sql`.
+const SNAPSHOT_EXTENSION: &str = "snap";
+
+/// Patterns replaced in plans, with their replacement.
+const REDACTIONS: &[(&str, &str)] = &[
+    (r"RoundRobinBatch\(\d+\)", "RoundRobinBatch(N)"),
+    (r"(Hash\(\[.*\]), \d+\)", "$1, N)"),
+    (r"partitions=\d+", "partitions=N"),
+    (r"partition_sizes=\[[^\]]*\]", "partition_sizes=[...]"),
+    (r"metrics=\[[^\]]*\]", "metrics=[...]"),
+];
+
+/// Run the snapshot test at `path`.
+pub async fn execute(
+    path: &Path,
+    mut client: TestClient,
+    record_timeout: Duration,
+    update_snapshots: bool,
+) -> Result<()> {
+    let script = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Error while opening `{}`: {}", path.to_string_lossy(), e))?;
+    let redactions = REDACTIONS
+        .iter()
+        .map(|(pattern, replacement)| Ok((Regex::new(pattern)?, *replacement)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut actual = String::new();
+    for sql in split_statements(&script) {
+        let output = tokio::time::timeout(record_timeout, client.run(&sql))
+            .await
+            .map_err(|_| {
+                anyhow!(
+                    "test fail: {}: timed out after {record_timeout:?} running:\n{sql}",
+                    path.to_string_lossy()
+                )
+            })?
+            .map_err(|e| {
+                anyhow!(
+                    "test fail: {}: statement failed: {e}\n[SQL] {sql}",
+                    path.to_string_lossy()
+                )
+            })?;
+
+        if !sql
+            .get(..7)
+            .is_some_and(|s| s.eq_ignore_ascii_case("explain"))
+        {
+            continue;
+        }
+
+        writeln!(actual, "{sql}")?;
+        writeln!(actual, "----")?;
+        if let DBOutput::Rows { rows, .. } = output {
+            for row in rows {
+                let mut cols = row.into_iter();
+                if let Some(plan_type) = cols.next() {
+                    writeln!(actual, "{plan_type}")?;
+                }
+                for plan in cols {
+                    let plan = redactions.iter().fold(plan, |plan, (regex, replacement)| {
+                        regex.replace_all(&plan, *replacement).into_owned()
+                    });
+                    for line in plan.lines() {
+                        writeln!(actual, "  {line}")?;
+                    }
+                }
+            }
+        }
+        writeln!(actual)?;
+    }
+
+    let snapshot_path = path.with_extension(SNAPSHOT_EXTENSION);
+    let expected = match std::fs::read_to_string(&snapshot_path) {
+        Ok(expected) => Some(expected),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => {
+            return Err(anyhow!(
+                "Error while opening `{}`: {}",
+                snapshot_path.to_string_lossy(),
+                e
+            ))
+        }
+    };
+
+    match expected {
+        Some(expected) if expected == actual => Ok(()),
+        Some(expected) if !update_snapshots => {
+            let diff = TextDiff::from_lines(&expected, &actual);
+            Err(anyhow!(
+                "test fail: plans don't match `{}`, run with `--update-snapshots` to accept the new plans:\n{}",
+                snapshot_path.to_string_lossy(),
+                diff.unified_diff().header("expected", "actual")
+            ))
+        }
+        expected => {
+            std::fs::write(&snapshot_path, actual)?;
+            if expected.is_some() {
+                tracing::info!("Updated snapshot `{}`", snapshot_path.to_string_lossy());
+            } else {
+                tracing::warn!(
+                    "Wrote new snapshot `{}`, commit it with the test",
+                    snapshot_path.to_string_lossy()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Split a script into statements, dropping comments and the trailing `;`.
+fn split_statements(script: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = Vec::new();
+    for line in script.lines() {
+        let line = line.trim_end();
+        let trimmed = line.trim_start();
+        if current.is_empty() && (trimmed.is_empty() || trimmed.starts_with("--")) {
+            continue;
+        }
+        match line.strip_suffix(';') {
+            Some(last) => {
+                current.push(last);
+                statements.push(current.join("\n"));
+                current.clear();
+            }
+            None => current.push(line),
+        }
+    }
+    if !current.is_empty() {
+        statements.push(current.join("\n"));
+    }
+    statements
+}
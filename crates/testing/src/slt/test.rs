@@ -1,11 +1,15 @@
+use super::directives::{self, ResultOptions};
+use super::snapshot;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use clap::builder::PossibleValue;
 use clap::ValueEnum;
+use datafusion::variable::VarType;
 use datafusion_ext::vars::SessionVars;
 use futures::StreamExt;
 use glob::Pattern;
 use metastore::util::MetastoreClientMode;
+use object_store_util::fixtures::{fixture_mode, FixtureMode};
 use pgrepr::format::Format;
 use pgrepr::scalar::Scalar;
 use pgrepr::types::arrow_to_pg_type;
@@ -79,6 +83,8 @@ const ENV_REGEX: &str = r"\$\{\s*(\w+)\s*\}";
 
 pub enum Test {
     File(PathBuf),
+    /// `EXPLAIN` snapshots, see the `snapshot` module.
+    Snapshot(PathBuf),
     FnTest(Box<dyn FnTest>),
 }
 
@@ -86,32 +92,73 @@ impl Debug for Test {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::File(path) => write!(f, "File({path:?})"),
+            Self::Snapshot(path) => write!(f, "Snapshot({path:?})"),
             Self::FnTest(_) => write!(f, "FnTest"),
         }
     }
 }
 
 impl Test {
+    /// Execute the test.
+    ///
+    /// Each statement and query record in a file must complete within
+    /// `record_timeout`, otherwise the test fails with the record's SQL.
+    /// Query results are compared according to the file's directives, see
+    /// the `directives` module.
+    ///
+    /// Snapshots that don't match are rewritten instead of failing the test
+    /// if `update_snapshots` is set.
     pub async fn execute(
         self,
         config: &Config,
         client: TestClient,
         vars: &mut HashMap<String, String>,
+        record_timeout: Duration,
+        update_snapshots: bool,
     ) -> Result<()> {
         match self {
             Self::File(path) => {
                 let regx = Regex::new(ENV_REGEX).unwrap();
-                let records = parse_file(&regx, &path, vars)?;
+                let mut result_opts = ResultOptions::default();
+                let records = parse_file(&regx, &path, vars, &mut result_opts)?;
 
                 let mut runner = Runner::new(|| {
                     let client = client.clone();
                     async { Ok(client) }
                 });
-
-                runner
-                    .run_multi_async(records)
-                    .await
-                    .map_err(|e| anyhow!("test fail: {}", e))
+                runner.with_validator(directives::validate);
+
+                let run = async move {
+                    for record in records {
+                        let (loc, sql) = match &record {
+                            Record::Halt { .. } => break,
+                            Record::Statement { loc, sql, .. } | Record::Query { loc, sql, .. } => {
+                                (loc.clone(), sql.clone())
+                            }
+                            _ => {
+                                runner
+                                    .run_async(record)
+                                    .await
+                                    .map_err(|e| anyhow!("test fail: {}", e))?;
+                                continue;
+                            }
+                        };
+
+                        tokio::time::timeout(record_timeout, runner.run_async(record))
+                            .await
+                            .map_err(|_| {
+                                anyhow!(
+                                    "test fail: {loc}: timed out after {record_timeout:?} running:\n{sql}"
+                                )
+                            })?
+                            .map_err(|e| anyhow!("test fail: {}", e))?;
+                    }
+                    Ok::<_, anyhow::Error>(())
+                };
+                result_opts.scope(run).await
+            }
+            Self::Snapshot(path) => {
+                snapshot::execute(&path, client, record_timeout, update_snapshots).await
             }
             Self::FnTest(fn_test) => fn_test.run(config, client, vars).await,
         }
@@ -122,6 +169,7 @@ fn parse_file<T: ColumnType>(
     regx: &Regex,
     path: &Path,
     vars: &HashMap<String, String>,
+    result_opts: &mut ResultOptions,
 ) -> Result<Vec<Record<T>>> {
     let script = std::fs::read_to_string(path)
         .map_err(|e| anyhow!("Error while opening `{}`: {}", path.to_string_lossy(), e))?;
@@ -138,6 +186,11 @@ fn parse_file<T: ColumnType>(
         }
         match std::env::var(env_var) {
             Ok(v) => v,
+            // Fixtures are replayed without credentials or the rest of the
+            // environment used when recording them.
+            Err(_) if matches!(fixture_mode(), Some(FixtureMode::Replay(_))) => {
+                format!("replay_{}", env_var.to_lowercase())
+            }
             Err(error) => {
                 let error = anyhow!("Error fetching environment variable `{env_var}`: {error}");
                 let err_msg = error.to_string();
@@ -150,6 +203,8 @@ fn parse_file<T: ColumnType>(
         return Err(err);
     }
 
+    result_opts.parse_directives(&script, path)?;
+
     let mut records = vec![];
 
     let script_name = path.to_str().unwrap();
@@ -190,7 +245,12 @@ fn parse_file<T: ColumnType>(
                 records.push(Record::Injected(Injected::BeginInclude(
                     included_file.clone(),
                 )));
-                records.extend(parse_file(regx, &PathBuf::from(&included_file), vars)?);
+                records.extend(parse_file(
+                    regx,
+                    &PathBuf::from(&included_file),
+                    vars,
+                    result_opts,
+                )?);
                 records.push(Record::Injected(Injected::EndInclude(included_file)));
             }
         }
@@ -254,7 +314,12 @@ impl RpcTestClient {
         let addr = format!("http://0.0.0.0:{port}");
         let remote_client = RemoteClient::connect(addr.parse().unwrap()).await?;
         let mut session = engine
-            .new_local_session_context(SessionVars::default(), SessionStorageConfig::default())
+            .new_local_session_context(
+                SessionVars::default()
+                    .with_is_embedded(true, VarType::System)
+                    .with_is_admin(true, VarType::System),
+                SessionStorageConfig::default(),
+            )
             .await?;
         let test_id = Uuid::new_v4();
         session
@@ -316,6 +381,31 @@ pub enum TestClient {
 }
 
 impl TestClient {
+    /// Run `sql` to completion, ignoring any results.
+    pub async fn execute(&self, sql: &str) -> Result<()> {
+        self.clone().run(sql).await?;
+        Ok(())
+    }
+
+    /// Run `sql`, asserting that it fails with an error matching the regex
+    /// `pattern`.
+    ///
+    /// The same as a `statement error <pattern>` record, for use in
+    /// [`FnTest`]s.
+    pub async fn expect_error(&self, sql: &str, pattern: &str) -> Result<()> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| anyhow!("Invalid expected error pattern `{pattern}`: {e}"))?;
+        match self.clone().run(sql).await {
+            Ok(_) => Err(anyhow!(
+                "expected `{sql}` to fail with an error matching `{pattern}`, but it succeeded"
+            )),
+            Err(e) if regex.is_match(&e.to_string()) => Ok(()),
+            Err(e) => Err(anyhow!(
+                "expected `{sql}` to fail with an error matching `{pattern}`, got: {e}"
+            )),
+        }
+    }
+
     pub async fn close(self) -> Result<()> {
         match self {
             Self::Pg(pg_client) => pg_client.close().await,
@@ -0,0 +1,6 @@
+use anyhow::Result;
+use testing::fuzz::Cli;
+
+fn main() -> Result<()> {
+    Cli::run()
+}
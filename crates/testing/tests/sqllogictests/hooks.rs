@@ -10,6 +10,7 @@ use tokio::{
 };
 use tokio_postgres::{Client, Config};
 use tracing::warn;
+use uuid::Uuid;
 
 /// This [`Hook`] is used to set some local variables that might change for
 /// each test.
@@ -79,7 +80,14 @@ impl SshTunnelHook {
     }
 
     async fn try_create_tunnel(try_num: i32, client: &Client) -> Result<(String, String)> {
-        let tunnel_name = format!("{}_{}", Self::TUNNEL_NAME_PREFIX, try_num);
+        // Tunnels are shared by every schema in a database, keep the name
+        // unique for tests running at the same time.
+        let tunnel_name = format!(
+            "{}_{}_{}",
+            Self::TUNNEL_NAME_PREFIX,
+            Uuid::new_v4().simple(),
+            try_num
+        );
         let port = Self::generate_random_port().await?;
         // Create the tunnel and get public key.
         client
@@ -55,4 +55,8 @@ for TABLE_INFO in "${TABLES_TO_LOAD[@]}"; do
 		"$SCHEMA_FILE" 1>&2
 done
 
+# Empty table for testing inserts, recreated so that it starts out empty.
+$BQ rm -f -t "${BQ_DATASET}.insert_test" 1>&2
+$BQ mk --table "${BQ_DATASET}.insert_test" a:INTEGER,b:STRING,c:TIMESTAMP 1>&2
+
 echo "$BQ_DATASET"
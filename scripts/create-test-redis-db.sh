@@ -0,0 +1,32 @@
+#!/usr/bin/env bash
+
+# Spins up a test redis docker container and loads it with data.
+#
+# The container doesn't require a username or password.
+
+set -e
+CONTAINER_NAME="glaredb_redis_test"
+
+# Remove container if it exists
+if [[ -n "$(docker ps -a -q -f name=$CONTAINER_NAME)" ]]; then
+    docker rm -f $CONTAINER_NAME > /dev/null
+fi
+
+# Start container.
+docker run --name $CONTAINER_NAME -p 6379:6379 --rm -d redis:7 &> /dev/null
+
+# Wait until redis is ready.
+INIT_TIME=$(date +%s)
+until docker exec $CONTAINER_NAME redis-cli ping 2> /dev/null | grep -q PONG; do
+  CURRENT_TIME=$(date +%s)
+  if [[ $((CURRENT_TIME - INIT_TIME)) -gt 60 ]]; then
+    echo "Timed out waiting for Redis to start!" >&2
+    exit 1
+  fi
+  sleep 1
+done
+
+# Load data.
+docker exec -i $CONTAINER_NAME redis-cli < ./testdata/sqllogictests_redis/data/setup-redis.txt > /dev/null
+
+echo "redis://127.0.0.1:6379"
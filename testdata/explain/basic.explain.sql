@@ -0,0 +1,17 @@
+-- Plans for single table queries.
+
+CREATE TEMP TABLE items (id INT, name TEXT, price DOUBLE, category TEXT);
+
+INSERT INTO items VALUES (1, 'apple', 1.5, 'fruit'), (2, 'bread', 3.0, 'bakery'), (3, 'cherry', 6.25, 'fruit');
+
+-- Only the referenced columns should be scanned.
+EXPLAIN SELECT name FROM items;
+
+EXPLAIN SELECT id, name FROM items WHERE price > 2 AND category = 'fruit';
+
+-- The limit should be pushed into the sort.
+EXPLAIN SELECT name FROM items ORDER BY price DESC LIMIT 2;
+
+EXPLAIN SELECT category, count(*), avg(price) FROM items GROUP BY category;
+
+EXPLAIN VERBOSE SELECT id FROM items WHERE id = 1;
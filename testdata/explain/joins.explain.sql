@@ -0,0 +1,26 @@
+-- Plans for joins.
+
+CREATE TEMP TABLE customers (id INT, name TEXT);
+
+CREATE TEMP TABLE orders (id INT, customer_id INT, total DOUBLE);
+
+CREATE TEMP TABLE order_items (order_id INT, item TEXT, quantity INT);
+
+EXPLAIN SELECT c.name, o.total
+FROM customers c
+JOIN orders o ON c.id = o.customer_id;
+
+EXPLAIN SELECT c.name, o.total
+FROM customers c
+LEFT JOIN orders o ON c.id = o.customer_id
+WHERE o.total > 10;
+
+-- Filters on one side of the join should be applied before the join.
+EXPLAIN SELECT c.name, i.item
+FROM customers c
+JOIN orders o ON c.id = o.customer_id
+JOIN order_items i ON o.id = i.order_id
+WHERE c.name = 'alice' AND i.quantity > 1;
+
+EXPLAIN SELECT name FROM customers
+WHERE id IN (SELECT customer_id FROM orders WHERE total > 100);
@@ -194,3 +194,9 @@ LOAD DATA LOCAL INFILE './testdata/sqllogictests_datasources_common/data/gcs-art
             end_station_id = NULLIF(@end_station_id, ''),
             end_station_name = NULLIF(@end_station_name, ''),
             duration_minutes = NULLIF(@duration_minutes, '');
+
+-- insert_test table, written to by the insert tests.
+CREATE TABLE IF NOT EXISTS insert_test (
+    a INT,
+    b TEXT
+);
@@ -20,7 +20,9 @@ CREATE TABLE IF NOT EXISTS datatypes (
     c18 TIMESTAMPTZ,
     c19 NUMERIC,
     c20 NUMERIC(10),
-    c21 NUMERIC(10, 5)
+    c21 NUMERIC(10, 5),
+    c22 INTERVAL,
+    c23 NUMERIC(50, 10)
 );
 
 INSERT INTO datatypes
@@ -45,7 +47,9 @@ VALUES (
     '1999-09-30 16:32:04 IST',
     12345.6789,
     12345.6789,
-    12345.67891234
+    12345.67891234,
+    '1 year 2 mons 3 days 04:05:06.789',
+    1234567890123456789012345678901234567890.0123456789
 );
 
 INSERT INTO datatypes(c1) VALUES (NULL); -- inserts nulls
@@ -85,3 +89,9 @@ CREATE TABLE IF NOT EXISTS bikeshare_trips (
 );
 
 \copy bikeshare_trips FROM './testdata/sqllogictests_datasources_common/data/gcs-artifacts/bikeshare_trips.csv' CSV HEADER;
+
+-- insert_test table, written to by the insert tests.
+CREATE TABLE IF NOT EXISTS insert_test (
+    a INT,
+    b TEXT
+);
@@ -132,3 +132,23 @@ FROM '/repo/testdata/sqllogictests_datasources_common/data/gcs-artifacts/bikesha
   WITH (FORMAT = 'CSV',
         FIRSTROW = 2)
 
+
+IF OBJECT_ID('dbo.cities', 'u') IS NOT NULL
+   DROP TABLE cities;
+GO
+
+-- Non-ASCII text and types read as strings, dates and times.
+CREATE TABLE cities (
+    id         UNIQUEIDENTIFIER,
+    name       NVARCHAR(64),
+    country    NCHAR(2),
+    founded    DATE,
+    sunrise    TIME,
+    population INT
+);
+
+INSERT INTO cities VALUES
+    ('6F9619FF-8B86-D011-B42D-00C04FC964FF', N'Ålesund', N'NO', '1848-01-01', '04:12:00', 67114),
+    ('7F9619FF-8B86-D011-B42D-00C04FC964FF', N'東京', N'JP', '1457-01-01', '04:25:30.5', 13960000),
+    ('8F9619FF-8B86-D011-B42D-00C04FC964FF', N'São Paulo', N'BR', '1554-01-25', '05:30:00', 12330000),
+    ('9F9619FF-8B86-D011-B42D-00C04FC964FF', N'Zürich', N'CH', NULL, NULL, NULL);
@@ -1,3 +1,5 @@
+import datetime
+import gzip
 import os.path
 import random
 
@@ -88,3 +90,55 @@ def test_read_bson(
                 assert len(row) == 5
                 assert row["beatle_name"] in beatles
                 assert beatles.index(row["beatle_name"]) == row["beatle_idx"] - 1
+
+
+def test_read_mongodump(
+    glaredb_connection: psycopg2.extensions.connection,
+    tmp_path_factory: pytest.TempPathFactory,
+):
+    tmp_dir = tmp_path_factory.mktemp(basename="read-bson-mongodump-", numbered=True)
+
+    docs = [
+        {
+            "_id": bson.objectid.ObjectId(),
+            "name": f"user{i}",
+            "created": datetime.datetime(2023, 1, i + 1, tzinfo=datetime.timezone.utc),
+            "tags": ["a", "b"][: i % 3],
+            "profile": {"age": 20 + i},
+        }
+        for i in range(10)
+    ]
+
+    # mongodump writes one file per collection, gzipped with `--gzip`.
+    with open(tmp_dir.joinpath("users.bson"), "wb") as f:
+        for doc in docs:
+            f.write(bson.encode(doc))
+    with gzip.open(tmp_dir.joinpath("users_archive.bson.gz"), "wb") as f:
+        for doc in docs:
+            f.write(bson.encode(doc))
+
+    with glaredb_connection.cursor() as curr:
+        curr.execute(
+            f"create external table dump_users from bson options ( location='{tmp_dir}/users.bson' )"
+        )
+
+    for from_clause in [
+        "dump_users",
+        f"read_bson('{tmp_dir}/users.bson')",
+        f"read_bson('{tmp_dir}/users_archive.bson.gz')",
+    ]:
+        with glaredb_connection.cursor() as curr:
+            curr.execute(
+                f"select name, created, tags, profile['age'] from {from_clause} order by name"
+            )
+            rows = curr.fetchall()
+            assert len(rows) == 10
+            assert rows[0][0] == "user0"
+            assert rows[0][1] == datetime.datetime(2023, 1, 1)
+            assert rows[0][2] == "[]"
+            assert rows[1][2] == '["a"]'
+            assert rows[0][3] == 20
+
+    with glaredb_connection.cursor() as curr:
+        curr.execute(f"select count(*) from read_bson('{tmp_dir}/*.bson*')")
+        assert curr.fetchone()[0] == 20